@@ -0,0 +1,51 @@
+// 文档入库热路径基准
+// 覆盖文本提取与混合分块，不依赖数据库与 AI 服务
+
+use aionix::ai::{DocumentChunker, DocumentProcessorManager, HybridChunker};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::io::Write;
+
+/// 生成约 `sections` 节的 Markdown 文档
+fn sample_markdown(sections: usize) -> String {
+    let mut content = String::from("# Aionix 产品手册\n\n");
+    for section in 0..sections {
+        content.push_str(&format!("## 第 {} 节\n\n", section + 1));
+        for paragraph in 0..4 {
+            content.push_str(&format!(
+                "第 {} 段：Aionix 支持多租户知识库管理、文档自动分块与向量化检索，\
+                 问答时会结合检索到的文档片段生成答案并给出来源引用。\n\n",
+                paragraph + 1
+            ));
+        }
+        content.push_str("- 支持 Markdown、HTML 与纯文本\n- 支持批量导入与导出\n\n");
+    }
+    content
+}
+
+fn ingestion_benchmarks(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let content = sample_markdown(50);
+
+    let mut file = tempfile::Builder::new().suffix(".md").tempfile().unwrap();
+    file.write_all(content.as_bytes()).unwrap();
+    let path = file.path().to_string_lossy().to_string();
+
+    let manager = DocumentProcessorManager::new();
+    let extracted = runtime.block_on(manager.process_document(&path)).unwrap();
+    let chunker = HybridChunker::with_default_config();
+
+    let mut group = c.benchmark_group("ingestion");
+    group.throughput(Throughput::Bytes(content.len() as u64));
+
+    group.bench_function("extract_markdown", |b| {
+        b.to_async(&runtime).iter(|| manager.process_document(&path))
+    });
+    group.bench_function("hybrid_chunking", |b| {
+        b.to_async(&runtime).iter(|| chunker.chunk_document(&extracted))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, ingestion_benchmarks);
+criterion_main!(benches);
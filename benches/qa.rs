@@ -0,0 +1,33 @@
+// 问答热路径基准
+// 覆盖置信度计算、自评置信度解析与 FAQ 模糊匹配
+
+use aionix::ai::{combine_confidence, extract_self_assessment, retrieval_confidence};
+use aionix::services::faq::bigram_similarity;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn qa_benchmarks(c: &mut Criterion) {
+    let scores: Vec<f32> = (0..20).map(|i| 0.95 - i as f32 * 0.03).collect();
+    let answer = format!("{}\n置信度：85%", "Aionix 支持多租户知识库与文档自动分块。".repeat(20));
+    let question = "如何为知识库配置文档自动分块的大小和重叠长度？";
+    let faq_question = "知识库的文档分块大小与重叠长度在哪里配置？";
+
+    let mut group = c.benchmark_group("qa");
+
+    group.bench_function("retrieval_confidence", |b| {
+        b.iter(|| {
+            let retrieval = retrieval_confidence(black_box(&scores));
+            combine_confidence(retrieval, Some(0.85), 0.9)
+        })
+    });
+    group.bench_function("extract_self_assessment", |b| {
+        b.iter(|| extract_self_assessment(black_box(&answer)))
+    });
+    group.bench_function("faq_bigram_similarity", |b| {
+        b.iter(|| bigram_similarity(black_box(question), black_box(faq_question)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, qa_benchmarks);
+criterion_main!(benches);
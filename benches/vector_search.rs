@@ -0,0 +1,78 @@
+// 向量检索热路径基准
+// 使用内存向量索引测量相似度排序开销；构造 AI 客户端不会发起网络请求，但需要启用 ai 特性
+
+use aionix::ai::{
+    ChunkMetadata, ChunkPosition, ChunkType, DocumentChunk, InMemoryVectorSearch, RigAiClientManager,
+    VectorSearchEngine,
+};
+use aionix::config::AiConfig;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 嵌入维度
+const DIMENSIONS: usize = 384;
+
+/// 确定性的伪随机向量，保证多次运行的数据一致
+fn pseudo_vector(seed: usize) -> Vec<f32> {
+    let mut state = (seed as u64).wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    (0..DIMENSIONS)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as f32 / (1u64 << 31) as f32 - 0.5
+        })
+        .collect()
+}
+
+fn chunk(index: usize) -> DocumentChunk {
+    let content = format!("文档片段 {}：Aionix 知识库检索基准测试内容。", index);
+    DocumentChunk {
+        id: Uuid::new_v4(),
+        metadata: ChunkMetadata {
+            chunk_index: index,
+            total_chunks: 0,
+            word_count: 1,
+            character_count: content.chars().count() as u32,
+            language: Some("zh".to_string()),
+            chunk_type: ChunkType::Text,
+            source_page: None,
+            overlap_with_previous: false,
+            overlap_with_next: false,
+            custom_properties: HashMap::new(),
+        },
+        position: ChunkPosition { start_char: 0, end_char: content.len(), start_line: None, end_line: None },
+        embedding: Some(pseudo_vector(index)),
+        content,
+    }
+}
+
+fn vector_search_benchmarks(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let client_manager = runtime
+        .block_on(RigAiClientManager::new(AiConfig {
+            model_endpoint: "https://api.openai.com/v1".to_string(),
+            api_key: "benchmark".to_string(),
+            max_tokens: 512,
+            temperature: 0.0,
+            timeout: 30,
+            retry_attempts: 0,
+            local: None,
+        }))
+        .unwrap();
+    let query = pseudo_vector(usize::MAX / 2);
+
+    let mut group = c.benchmark_group("vector_search");
+    for size in [1_000, 10_000] {
+        let mut engine = InMemoryVectorSearch::new(client_manager.clone());
+        let chunks: Vec<DocumentChunk> = (0..size).map(chunk).collect();
+        runtime.block_on(engine.add_chunks(&chunks)).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("in_memory_top10", size), &engine, |b, engine| {
+            b.to_async(&runtime).iter(|| engine.vector_search(black_box(&query), 10, 0.0, None))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, vector_search_benchmarks);
+criterion_main!(benches);
@@ -43,6 +43,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         session_id: None,
         request_id: Uuid::new_v4(),
         variables: HashMap::new(),
+        plugin_config: HashMap::new(),
         timestamp: Utc::now(),
     };
     
@@ -10,51 +10,10 @@ use chrono::Utc;
 use reqwest;
 use tracing::{debug, info, warn, error};
 
-// 注意：在实际项目中，这些应该从 crate 导入
-// use aionix_ai_studio::plugins::plugin_interface::*;
-// use aionix_ai_studio::errors::AiStudioError;
-
-// 为了示例，我们重用文件操作插件中的类型定义
+// 插件接口数据类型由 aionix-common 统一提供，与服务端保持一致
+pub use aionix_common::plugin::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum PluginStatus {
-    Uninitialized,
-    Initializing,
-    Initialized,
-    Starting,
-    Running,
-    Stopping,
-    Stopped,
-    Error,
-    Unloading,
-    Unloaded,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum PluginType {
-    Tool,
-    Agent,
-    Workflow,
-    DataSource,
-    Authentication,
-    Storage,
-    Notification,
-    Monitoring,
-    Custom,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum PluginPermission {
-    FileSystem,
-    Network,
-    Database,
-    SystemInfo,
-    UserData,
-    Admin,
-    Custom(String),
-}
-
 // 简化的错误类型
 #[derive(Debug)]
 pub struct AiStudioError {
@@ -88,101 +47,6 @@ impl std::fmt::Display for AiStudioError {
 impl std::error::Error for AiStudioError {}
 
 // 插件接口定义（简化版）
-#[derive(Debug, Clone, Serialize)]
-pub struct PluginMetadata {
-    pub id: String,
-    pub name: String,
-    pub version: String,
-    pub description: String,
-    pub author: String,
-    pub license: String,
-    pub homepage: Option<String>,
-    pub repository: Option<String>,
-    pub plugin_type: PluginType,
-    pub api_version: String,
-    pub min_system_version: String,
-    pub dependencies: Vec<PluginDependency>,
-    pub permissions: Vec<PluginPermission>,
-    pub tags: Vec<String>,
-    pub icon: Option<String>,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PluginDependency {
-    pub plugin_id: String,
-    pub version_requirement: String,
-    pub optional: bool,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PluginConfig {
-    pub plugin_id: String,
-    pub parameters: HashMap<String, serde_json::Value>,
-    pub environment: HashMap<String, String>,
-    pub resource_limits: ResourceLimits,
-    pub security_settings: SecuritySettings,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ResourceLimits {
-    pub max_memory_mb: Option<u64>,
-    pub max_cpu_percent: Option<f32>,
-    pub max_disk_mb: Option<u64>,
-    pub max_network_kbps: Option<u64>,
-    pub max_execution_seconds: Option<u64>,
-}
-
-impl Default for ResourceLimits {
-    fn default() -> Self {
-        Self {
-            max_memory_mb: Some(512),
-            max_cpu_percent: Some(50.0),
-            max_disk_mb: Some(1024),
-            max_network_kbps: Some(1024),
-            max_execution_seconds: Some(300),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SecuritySettings {
-    pub enable_sandbox: bool,
-    pub allowed_domains: Vec<String>,
-    pub allowed_paths: Vec<String>,
-    pub forbidden_operations: Vec<String>,
-}
-
-impl Default for SecuritySettings {
-    fn default() -> Self {
-        Self {
-            enable_sandbox: true,
-            allowed_domains: Vec::new(),
-            allowed_paths: Vec::new(),
-            forbidden_operations: Vec::new(),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct PluginContext {
-    pub tenant_id: Uuid,
-    pub user_id: Option<Uuid>,
-    pub session_id: Option<Uuid>,
-    pub request_id: Uuid,
-    pub variables: HashMap<String, serde_json::Value>,
-    pub timestamp: chrono::DateTime<chrono::Utc>,
-}
-
-#[derive(Debug, Clone, Serialize)]
-pub struct PluginHealth {
-    pub healthy: bool,
-    pub message: String,
-    pub details: HashMap<String, serde_json::Value>,
-    pub checked_at: chrono::DateTime<chrono::Utc>,
-    pub response_time_ms: u64,
-}
-
 #[async_trait]
 pub trait Plugin: Send + Sync {
     fn metadata(&self) -> PluginMetadata;
@@ -541,6 +405,8 @@ impl Plugin for HttpClientPlugin {
                 "web".to_string()
             ],
             icon: Some("🌐".to_string()),
+            routes: Vec::new(),
+            event_subscriptions: Vec::new(),
             created_at: Utc::now(),
         }
     }
@@ -888,6 +754,7 @@ mod tests {
             session_id: None,
             request_id: Uuid::new_v4(),
             variables: HashMap::new(),
+            plugin_config: HashMap::new(),
             timestamp: Utc::now(),
         };
         
@@ -930,6 +797,7 @@ mod tests {
             session_id: None,
             request_id: Uuid::new_v4(),
             variables: HashMap::new(),
+            plugin_config: HashMap::new(),
             timestamp: Utc::now(),
         };
         
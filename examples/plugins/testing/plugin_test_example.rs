@@ -43,6 +43,8 @@ impl Plugin for ExamplePlugin {
             permissions: vec![PluginPermission::FileSystem],
             tags: vec!["test".to_string(), "example".to_string()],
             icon: Some("🧪".to_string()),
+            routes: Vec::new(),
+            event_subscriptions: Vec::new(),
             created_at: chrono::Utc::now(),
         }
     }
@@ -8,50 +8,8 @@ use chrono::Utc;
 use serde_json;
 use async_trait::async_trait;
 
-// 注意：在实际项目中，这些应该从 crate 导入
-// use aionix_ai_studio::plugins::plugin_interface::*;
-// use aionix_ai_studio::errors::AiStudioError;
-
-// 为了示例，我们重用之前定义的类型
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum PluginStatus {
-    Uninitialized,
-    Initializing,
-    Initialized,
-    Starting,
-    Running,
-    Stopping,
-    Stopped,
-    Error,
-    Unloading,
-    Unloaded,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum PluginType {
-    Tool,
-    Agent,
-    Workflow,
-    DataSource,
-    Authentication,
-    Storage,
-    Notification,
-    Monitoring,
-    Custom,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum PluginPermission {
-    FileSystem,
-    Network,
-    Database,
-    SystemInfo,
-    UserData,
-    Admin,
-    Custom(String),
-}
+// 插件接口数据类型由 aionix-common 统一提供，与服务端保持一致
+pub use aionix_common::plugin::*;
 
 #[derive(Debug)]
 pub struct AiStudioError {
@@ -76,101 +34,7 @@ impl std::fmt::Display for AiStudioError {
 
 impl std::error::Error for AiStudioError {}
 
-#[derive(Debug, Clone, Serialize)]
-pub struct PluginMetadata {
-    pub id: String,
-    pub name: String,
-    pub version: String,
-    pub description: String,
-    pub author: String,
-    pub license: String,
-    pub homepage: Option<String>,
-    pub repository: Option<String>,
-    pub plugin_type: PluginType,
-    pub api_version: String,
-    pub min_system_version: String,
-    pub dependencies: Vec<PluginDependency>,
-    pub permissions: Vec<PluginPermission>,
-    pub tags: Vec<String>,
-    pub icon: Option<String>,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PluginDependency {
-    pub plugin_id: String,
-    pub version_requirement: String,
-    pub optional: bool,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PluginConfig {
-    pub plugin_id: String,
-    pub parameters: HashMap<String, serde_json::Value>,
-    pub environment: HashMap<String, String>,
-    pub resource_limits: ResourceLimits,
-    pub security_settings: SecuritySettings,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ResourceLimits {
-    pub max_memory_mb: Option<u64>,
-    pub max_cpu_percent: Option<f32>,
-    pub max_disk_mb: Option<u64>,
-    pub max_network_kbps: Option<u64>,
-    pub max_execution_seconds: Option<u64>,
-}
-
-impl Default for ResourceLimits {
-    fn default() -> Self {
-        Self {
-            max_memory_mb: Some(512),
-            max_cpu_percent: Some(50.0),
-            max_disk_mb: Some(1024),
-            max_network_kbps: Some(1024),
-            max_execution_seconds: Some(300),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SecuritySettings {
-    pub enable_sandbox: bool,
-    pub allowed_domains: Vec<String>,
-    pub allowed_paths: Vec<String>,
-    pub forbidden_operations: Vec<String>,
-}
-
-impl Default for SecuritySettings {
-    fn default() -> Self {
-        Self {
-            enable_sandbox: true,
-            allowed_domains: Vec::new(),
-            allowed_paths: Vec::new(),
-            forbidden_operations: Vec::new(),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct PluginContext {
-    pub tenant_id: Uuid,
-    pub user_id: Option<Uuid>,
-    pub session_id: Option<Uuid>,
-    pub request_id: Uuid,
-    pub variables: HashMap<String, serde_json::Value>,
-    pub timestamp: chrono::DateTime<chrono::Utc>,
-}
-
-#[derive(Debug, Clone, Serialize)]
-pub struct PluginHealth {
-    pub healthy: bool,
-    pub message: String,
-    pub details: HashMap<String, serde_json::Value>,
-    pub checked_at: chrono::DateTime<chrono::Utc>,
-    pub response_time_ms: u64,
-}
-
+// 插件接口定义（简化版）
 #[async_trait]
 pub trait Plugin: Send + Sync {
     fn metadata(&self) -> PluginMetadata;
@@ -445,6 +309,7 @@ impl PluginTestFramework {
             session_id: None,
             request_id: Uuid::new_v4(),
             variables: HashMap::new(),
+            plugin_config: HashMap::new(),
             timestamp: Utc::now(),
         };
         
@@ -552,6 +417,7 @@ impl PluginTestFramework {
             session_id: None,
             request_id: Uuid::new_v4(),
             variables: HashMap::new(),
+            plugin_config: HashMap::new(),
             timestamp: Utc::now(),
         };
         
@@ -0,0 +1,55 @@
+// Agent 接口客户端
+
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::client::AionixClient;
+use crate::error::ClientResult;
+
+pub use aionix_common::models::agents::*;
+
+/// Agent 接口，服务端直接返回数据而不使用统一响应信封
+pub struct AgentsClient<'a> {
+    client: &'a AionixClient,
+}
+
+impl<'a> AgentsClient<'a> {
+    pub(crate) fn new(client: &'a AionixClient) -> Self {
+        Self { client }
+    }
+
+    /// 创建 Agent
+    pub async fn create(&self, request: &CreateAgentRequest) -> ClientResult<CreateAgentResponse> {
+        self.client
+            .send_raw(self.client.request(Method::POST, "/agents").json(request))
+            .await
+    }
+
+    /// 列出 Agent
+    pub async fn list(&self) -> ClientResult<ListAgentsResponse> {
+        self.client.send_raw(self.client.request(Method::GET, "/agents")).await
+    }
+
+    /// 执行任务，等待执行完成后返回
+    pub async fn execute(&self, agent_id: Uuid, request: &ExecuteTaskRequest) -> ClientResult<ExecuteTaskResponse> {
+        let path = format!("/agents/{}/execute", agent_id);
+        self.client
+            .send_raw(self.client.request_without_timeout(Method::POST, &path).json(request))
+            .await
+    }
+
+    /// 获取 Agent 状态
+    pub async fn status(&self, agent_id: Uuid) -> ClientResult<AgentStatusResponse> {
+        self.client
+            .send_raw(self.client.request(Method::GET, &format!("/agents/{}/status", agent_id)))
+            .await
+    }
+
+    /// 停止 Agent
+    pub async fn stop(&self, agent_id: Uuid) -> ClientResult<()> {
+        self.client
+            .send_raw::<serde_json::Value>(self.client.request(Method::POST, &format!("/agents/{}/stop", agent_id)))
+            .await
+            .map(|_| ())
+    }
+}
@@ -0,0 +1,87 @@
+// 认证接口客户端
+
+use reqwest::Method;
+
+use crate::client::AionixClient;
+use crate::error::ClientResult;
+
+pub use aionix_common::models::auth::*;
+
+/// 认证接口
+pub struct AuthClient<'a> {
+    client: &'a AionixClient,
+}
+
+impl<'a> AuthClient<'a> {
+    pub(crate) fn new(client: &'a AionixClient) -> Self {
+        Self { client }
+    }
+
+    /// 登录，成功后客户端改用返回的访问令牌
+    pub async fn login(&self, request: &LoginRequest) -> ClientResult<LoginResponse> {
+        let response: LoginResponse = self.client
+            .send(self.client.request(Method::POST, "/auth/login").json(request))
+            .await?;
+        self.client.set_access_token(Some(response.access_token.clone()));
+        Ok(response)
+    }
+
+    /// 刷新访问令牌，成功后客户端改用新的访问令牌
+    pub async fn refresh(&self, refresh_token: &str) -> ClientResult<RefreshTokenResponse> {
+        let body = serde_json::json!({ "refresh_token": refresh_token });
+        let response: RefreshTokenResponse = self.client
+            .send(self.client.request(Method::POST, "/auth/refresh").json(&body))
+            .await?;
+        self.client.set_access_token(Some(response.access_token.clone()));
+        Ok(response)
+    }
+
+    /// 登出并吊销刷新令牌，客户端清除访问令牌
+    pub async fn logout(&self, refresh_token: &str) -> ClientResult<()> {
+        let body = serde_json::json!({ "refresh_token": refresh_token });
+        self.client
+            .send_empty(self.client.request(Method::POST, "/auth/logout").json(&body))
+            .await?;
+        self.client.set_access_token(None);
+        Ok(())
+    }
+
+    /// 注册用户
+    pub async fn register(&self, request: &RegisterRequest) -> ClientResult<RegisterResponse> {
+        self.client
+            .send(self.client.request(Method::POST, "/auth/register").json(request))
+            .await
+    }
+
+    /// 请求发送密码重置邮件
+    pub async fn request_password_reset(&self, email: &str, tenant_slug: &str) -> ClientResult<()> {
+        let body = serde_json::json!({ "email": email, "tenant_slug": tenant_slug });
+        self.client
+            .send_empty(self.client.request(Method::POST, "/auth/password-reset").json(&body))
+            .await
+    }
+
+    /// 使用重置令牌设置新密码
+    pub async fn confirm_password_reset(&self, reset_token: &str, new_password: &str) -> ClientResult<()> {
+        let body = serde_json::json!({
+            "reset_token": reset_token,
+            "new_password": new_password,
+            "new_password_confirm": new_password,
+        });
+        self.client
+            .send_empty(self.client.request(Method::POST, "/auth/password-reset/confirm").json(&body))
+            .await
+    }
+
+    /// 当前用户信息
+    pub async fn me(&self) -> ClientResult<UserInfo> {
+        self.client.send(self.client.request(Method::GET, "/auth/me")).await
+    }
+
+    /// 更新当前用户资料
+    pub async fn update_profile(&self, request: &UpdateProfileRequest) -> ClientResult<UserInfo> {
+        self.client
+            .send(self.client.request(Method::PUT, "/auth/profile").json(request))
+            .await
+    }
+}
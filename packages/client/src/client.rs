@@ -0,0 +1,269 @@
+// 客户端核心
+// 负责连接配置、认证头注入与统一的响应解析
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use aionix_common::api::{ApiEnvelope, API_PREFIX};
+use reqwest::{Method, RequestBuilder, Response, Url};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::agents::AgentsClient;
+use crate::auth::AuthClient;
+use crate::documents::DocumentsClient;
+use crate::error::{ClientError, ClientResult};
+use crate::qa::QaClient;
+use crate::workflows::WorkflowsClient;
+
+/// 默认请求超时时间
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 客户端构建器
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    base_url: String,
+    access_token: Option<String>,
+    api_key: Option<String>,
+    tenant_id: Option<Uuid>,
+    tenant_slug: Option<String>,
+    timeout: Duration,
+    user_agent: String,
+}
+
+impl ClientBuilder {
+    /// 以服务地址创建构建器，例如 `https://aionix.example.com`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            access_token: None,
+            api_key: None,
+            tenant_id: None,
+            tenant_slug: None,
+            timeout: DEFAULT_TIMEOUT,
+            user_agent: format!("aionix-client/{}", env!("CARGO_PKG_VERSION")),
+        }
+    }
+
+    /// 使用访问令牌认证
+    pub fn access_token(mut self, token: impl Into<String>) -> Self {
+        self.access_token = Some(token.into());
+        self
+    }
+
+    /// 使用 API 密钥认证
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// 通过租户 ID 指定租户
+    pub fn tenant_id(mut self, tenant_id: Uuid) -> Self {
+        self.tenant_id = Some(tenant_id);
+        self
+    }
+
+    /// 通过租户标识符指定租户
+    pub fn tenant_slug(mut self, slug: impl Into<String>) -> Self {
+        self.tenant_slug = Some(slug.into());
+        self
+    }
+
+    /// 设置请求超时时间，流式问答不受此限制
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置 User-Agent
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// 构建客户端
+    pub fn build(self) -> ClientResult<AionixClient> {
+        let base_url = Url::parse(self.base_url.trim_end_matches('/'))
+            .map_err(|e| ClientError::Configuration(format!("无效的服务地址 {}: {}", self.base_url, e)))?;
+        if !matches!(base_url.scheme(), "http" | "https") {
+            return Err(ClientError::Configuration(format!("不支持的协议: {}", base_url.scheme())));
+        }
+
+        let http = reqwest::Client::builder()
+            .user_agent(self.user_agent)
+            .connect_timeout(self.timeout)
+            .build()?;
+
+        Ok(AionixClient {
+            inner: Arc::new(ClientInner {
+                http,
+                base_url: base_url.as_str().trim_end_matches('/').to_string(),
+                access_token: RwLock::new(self.access_token),
+                api_key: self.api_key,
+                tenant_id: self.tenant_id,
+                tenant_slug: self.tenant_slug,
+                timeout: self.timeout,
+            }),
+        })
+    }
+}
+
+struct ClientInner {
+    http: reqwest::Client,
+    base_url: String,
+    access_token: RwLock<Option<String>>,
+    api_key: Option<String>,
+    tenant_id: Option<Uuid>,
+    tenant_slug: Option<String>,
+    timeout: Duration,
+}
+
+/// Aionix API 客户端，克隆开销很小，可在任务间共享
+#[derive(Clone)]
+pub struct AionixClient {
+    inner: Arc<ClientInner>,
+}
+
+impl AionixClient {
+    /// 创建客户端构建器
+    pub fn builder(base_url: impl Into<String>) -> ClientBuilder {
+        ClientBuilder::new(base_url)
+    }
+
+    /// 认证接口
+    pub fn auth(&self) -> AuthClient<'_> {
+        AuthClient::new(self)
+    }
+
+    /// 文档接口
+    pub fn documents(&self) -> DocumentsClient<'_> {
+        DocumentsClient::new(self)
+    }
+
+    /// 问答接口
+    pub fn qa(&self) -> QaClient<'_> {
+        QaClient::new(self)
+    }
+
+    /// Agent 接口
+    pub fn agents(&self) -> AgentsClient<'_> {
+        AgentsClient::new(self)
+    }
+
+    /// 工作流接口
+    pub fn workflows(&self) -> WorkflowsClient<'_> {
+        WorkflowsClient::new(self)
+    }
+
+    /// 当前访问令牌
+    pub fn access_token(&self) -> Option<String> {
+        self.inner.access_token.read().ok().and_then(|token| token.clone())
+    }
+
+    /// 替换访问令牌，登录或刷新令牌后由认证接口自动调用
+    pub fn set_access_token(&self, token: Option<String>) {
+        if let Ok(mut current) = self.inner.access_token.write() {
+            *current = token;
+        }
+    }
+
+    /// 完整的接口地址
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}{}{}", self.inner.base_url, API_PREFIX, path)
+    }
+
+    /// 创建带认证与租户请求头的请求
+    pub(crate) fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.request_without_timeout(method, path).timeout(self.inner.timeout)
+    }
+
+    /// 创建不设置总超时的请求，用于流式响应
+    pub(crate) fn request_without_timeout(&self, method: Method, path: &str) -> RequestBuilder {
+        let mut builder = self.inner.http.request(method, self.url(path));
+        if let Some(token) = self.access_token() {
+            builder = builder.bearer_auth(token);
+        }
+        if let Some(api_key) = &self.inner.api_key {
+            builder = builder.header("X-API-Key", api_key);
+        }
+        if let Some(tenant_id) = self.inner.tenant_id {
+            builder = builder.header("X-Tenant-ID", tenant_id.to_string());
+        }
+        if let Some(slug) = &self.inner.tenant_slug {
+            builder = builder.header("X-Tenant-Slug", slug);
+        }
+        builder
+    }
+
+    /// 发送请求，失败状态码转换为错误
+    pub(crate) async fn execute(&self, builder: RequestBuilder) -> ClientResult<Response> {
+        let response = builder.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.bytes().await?;
+        Err(ClientError::from_response(status, &body))
+    }
+
+    /// 发送请求并解析统一响应信封中的数据
+    pub(crate) async fn send<T: DeserializeOwned>(&self, builder: RequestBuilder) -> ClientResult<T> {
+        self.send_optional(builder).await?.ok_or(ClientError::EmptyResponse)
+    }
+
+    /// 发送请求并解析统一响应信封，允许无数据
+    pub(crate) async fn send_optional<T: DeserializeOwned>(&self, builder: RequestBuilder) -> ClientResult<Option<T>> {
+        let response = self.execute(builder).await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+        if body.is_empty() {
+            return Ok(None);
+        }
+
+        let envelope: ApiEnvelope<T> = serde_json::from_slice(&body)?;
+        let request_id = envelope.request_id.clone();
+        envelope.into_result().map_err(|error| ClientError::api(status, *error, Some(request_id)))
+    }
+
+    /// 发送请求，忽略响应数据
+    pub(crate) async fn send_empty(&self, builder: RequestBuilder) -> ClientResult<()> {
+        self.send_optional::<serde_json::Value>(builder).await.map(|_| ())
+    }
+
+    /// 发送请求并直接解析响应体，用于未使用统一响应信封的接口
+    pub(crate) async fn send_raw<T: DeserializeOwned>(&self, builder: RequestBuilder) -> ClientResult<T> {
+        let response = self.execute(builder).await?;
+        let body = response.bytes().await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+impl std::fmt::Debug for AionixClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AionixClient")
+            .field("base_url", &self.inner.base_url)
+            .field("tenant_id", &self.inner.tenant_id)
+            .field("tenant_slug", &self.inner.tenant_slug)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_client() {
+        let client = AionixClient::builder("https://aionix.example.com/").build().unwrap();
+        assert_eq!(client.url("/documents"), "https://aionix.example.com/api/v1/documents");
+
+        client.set_access_token(Some("token".to_string()));
+        assert_eq!(client.access_token().as_deref(), Some("token"));
+
+        assert!(matches!(
+            AionixClient::builder("ftp://aionix.example.com").build(),
+            Err(ClientError::Configuration(_))
+        ));
+        assert!(AionixClient::builder("not a url").build().is_err());
+    }
+}
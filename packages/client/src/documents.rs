@@ -0,0 +1,120 @@
+// 文档接口客户端
+
+use aionix_common::api::{Page, PageRequest};
+use futures::Stream;
+use reqwest::multipart::{Form, Part};
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::client::AionixClient;
+use crate::error::ClientResult;
+use crate::pagination::paginate;
+
+pub use aionix_common::models::documents::*;
+
+/// 待上传的文件
+#[derive(Debug, Clone)]
+pub struct UploadFile {
+    /// 文件名
+    pub file_name: String,
+    /// MIME 类型，未指定时由服务端根据文件名判断
+    pub mime_type: Option<String>,
+    /// 文件内容
+    pub content: Vec<u8>,
+}
+
+/// 文档接口
+pub struct DocumentsClient<'a> {
+    client: &'a AionixClient,
+}
+
+impl<'a> DocumentsClient<'a> {
+    pub(crate) fn new(client: &'a AionixClient) -> Self {
+        Self { client }
+    }
+
+    /// 创建文档
+    pub async fn create(&self, request: &CreateDocumentRequest) -> ClientResult<Document> {
+        self.client
+            .send(self.client.request(Method::POST, "/documents").json(request))
+            .await
+    }
+
+    /// 上传文档文件，标题为空时使用文件名
+    pub async fn upload(
+        &self,
+        knowledge_base_id: Uuid,
+        title: Option<&str>,
+        file: UploadFile,
+    ) -> ClientResult<DocumentUploadResponse> {
+        let mut part = Part::bytes(file.content).file_name(file.file_name);
+        if let Some(mime_type) = &file.mime_type {
+            part = part.mime_str(mime_type)?;
+        }
+
+        let mut form = Form::new().text("knowledge_base_id", knowledge_base_id.to_string());
+        if let Some(title) = title {
+            form = form.text("title", title.to_string());
+        }
+        form = form.part("file", part);
+
+        self.client
+            .send(self.client.request(Method::POST, "/documents/upload").multipart(form))
+            .await
+    }
+
+    /// 按页查询文档
+    pub async fn list(&self, filter: &DocumentFilter, page: &PageRequest) -> ClientResult<Page<Document>> {
+        self.client
+            .send(self.client.request(Method::GET, "/documents").query(filter).query(page))
+            .await
+    }
+
+    /// 逐条返回符合条件的全部文档
+    pub fn list_all(
+        &self,
+        filter: DocumentFilter,
+        page_size: u32,
+    ) -> impl Stream<Item = ClientResult<Document>> + 'a {
+        let client = self.client;
+        paginate(PageRequest::new(1, page_size), move |page| {
+            let filter = filter.clone();
+            async move { DocumentsClient::new(client).list(&filter, &page).await }
+        })
+    }
+
+    /// 获取文档详情
+    pub async fn get(&self, id: Uuid) -> ClientResult<Document> {
+        self.client
+            .send(self.client.request(Method::GET, &format!("/documents/{}", id)))
+            .await
+    }
+
+    /// 更新文档
+    pub async fn update(&self, id: Uuid, request: &UpdateDocumentRequest) -> ClientResult<Document> {
+        self.client
+            .send(self.client.request(Method::PUT, &format!("/documents/{}", id)).json(request))
+            .await
+    }
+
+    /// 删除文档
+    pub async fn delete(&self, id: Uuid) -> ClientResult<()> {
+        self.client
+            .send_empty(self.client.request(Method::DELETE, &format!("/documents/{}", id)))
+            .await
+    }
+
+    /// 获取文档统计信息
+    pub async fn stats(&self, id: Uuid) -> ClientResult<DocumentStats> {
+        self.client
+            .send(self.client.request(Method::GET, &format!("/documents/{}/stats", id)))
+            .await
+    }
+
+    /// 重新处理文档
+    pub async fn reprocess(&self, id: Uuid) -> ClientResult<serde_json::Value> {
+        self.client
+            .send(self.client.request(Method::POST, &format!("/documents/{}/reprocess", id)))
+            .await
+    }
+}
@@ -0,0 +1,126 @@
+// 客户端错误类型定义
+
+use aionix_common::api::{ApiEnvelope, ApiErrorBody};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// 客户端操作结果
+pub type ClientResult<T> = Result<T, ClientError>;
+
+/// 客户端错误
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("配置错误: {0}")]
+    Configuration(String),
+
+    #[error("请求失败: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("服务端错误 ({status}): [{code}] {message}")]
+    Api {
+        status: StatusCode,
+        code: String,
+        message: String,
+        field: Option<String>,
+        request_id: Option<String>,
+    },
+
+    #[error("响应解析失败: {0}")]
+    Decode(#[from] serde_json::Error),
+
+    #[error("响应缺少数据")]
+    EmptyResponse,
+}
+
+/// 部分接口直接返回的错误结构
+#[derive(Deserialize)]
+struct PlainErrorBody {
+    error: String,
+    message: Option<String>,
+}
+
+impl ClientError {
+    /// 由失败的 HTTP 响应体构造错误，兼容信封、裸错误与 `{error, message}` 三种格式
+    pub(crate) fn from_response(status: StatusCode, body: &[u8]) -> Self {
+        if let Ok(envelope) = serde_json::from_slice::<ApiEnvelope<serde_json::Value>>(body)
+            && let Some(error) = envelope.error
+        {
+            return Self::api(status, error, Some(envelope.request_id));
+        }
+        if let Ok(error) = serde_json::from_slice::<ApiErrorBody>(body) {
+            return Self::api(status, error, None);
+        }
+        if let Ok(plain) = serde_json::from_slice::<PlainErrorBody>(body) {
+            return Self::Api {
+                status,
+                code: status.canonical_reason().unwrap_or("ERROR").to_uppercase().replace(' ', "_"),
+                message: plain.message.map_or(plain.error.clone(), |m| format!("{}: {}", plain.error, m)),
+                field: None,
+                request_id: None,
+            };
+        }
+
+        Self::Api {
+            status,
+            code: status.canonical_reason().unwrap_or("ERROR").to_uppercase().replace(' ', "_"),
+            message: String::from_utf8_lossy(body).trim().to_string(),
+            field: None,
+            request_id: None,
+        }
+    }
+
+    pub(crate) fn api(status: StatusCode, error: ApiErrorBody, request_id: Option<String>) -> Self {
+        Self::Api {
+            status,
+            code: error.code,
+            message: error.message,
+            field: error.field,
+            request_id: request_id.filter(|id| !id.is_empty()),
+        }
+    }
+
+    /// HTTP 状态码，非服务端错误时为空
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Api { status, .. } => Some(*status),
+            Self::Http(error) => error.status(),
+            _ => None,
+        }
+    }
+
+    /// 是否为资源不存在
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// 是否为认证失败
+    pub fn is_unauthorized(&self) -> bool {
+        self.status() == Some(StatusCode::UNAUTHORIZED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_response_formats() {
+        let envelope = r#"{"success":false,"error":{"code":"NOT_FOUND","message":"文档"},"request_id":"req_1","timestamp":"2024-01-01T00:00:00Z","version":"0.1.0"}"#;
+        match ClientError::from_response(StatusCode::NOT_FOUND, envelope.as_bytes()) {
+            ClientError::Api { code, request_id, .. } => {
+                assert_eq!(code, "NOT_FOUND");
+                assert_eq!(request_id.as_deref(), Some("req_1"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+
+        let plain = r#"{"error":"停止 Agent 失败","message":"Agent 不存在"}"#;
+        let error = ClientError::from_response(StatusCode::INTERNAL_SERVER_ERROR, plain.as_bytes());
+        assert!(error.to_string().contains("停止 Agent 失败: Agent 不存在"));
+
+        let text = ClientError::from_response(StatusCode::BAD_GATEWAY, b"upstream down");
+        assert_eq!(text.status(), Some(StatusCode::BAD_GATEWAY));
+        assert!(text.to_string().contains("upstream down"));
+    }
+}
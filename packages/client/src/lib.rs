@@ -0,0 +1,17 @@
+// Aionix Client SDK
+// Aionix REST API 的类型化异步客户端，传输格式与服务端共用 aionix-common 中的定义
+
+pub mod agents;
+pub mod auth;
+pub mod client;
+pub mod documents;
+pub mod error;
+pub mod pagination;
+pub mod qa;
+pub mod sse;
+pub mod workflows;
+
+pub use aionix_common::api::{ApiErrorBody, Page, PageInfo, PageRequest};
+pub use client::{AionixClient, ClientBuilder};
+pub use error::{ClientError, ClientResult};
+pub use pagination::paginate;
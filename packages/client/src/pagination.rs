@@ -0,0 +1,72 @@
+// 分页辅助
+// 将按页查询的接口展开为逐条返回的异步流
+
+use std::future::Future;
+
+use aionix_common::api::{Page, PageInfo, PageRequest};
+use futures::stream::{self, Stream, TryStreamExt};
+
+use crate::error::{ClientError, ClientResult};
+
+/// 从指定页开始依次拉取后续页，逐条返回记录
+///
+/// 服务端返回 `has_next = false` 时结束，拉取失败时流返回错误并结束。
+pub fn paginate<T, F, Fut>(first: PageRequest, mut fetch: F) -> impl Stream<Item = ClientResult<T>>
+where
+    F: FnMut(PageRequest) -> Fut,
+    Fut: Future<Output = ClientResult<Page<T>>>,
+{
+    stream::try_unfold(Some(first), move |request| {
+        let pending = request.map(|request| (fetch(request.clone()), request));
+        async move {
+            let Some((page, request)) = pending else {
+                return Ok::<_, ClientError>(None);
+            };
+            let page = page.await?;
+            let next = (page.pagination.has_next && !page.data.is_empty()).then(|| request.next());
+            Ok(Some((stream::iter(page.data.into_iter().map(Ok)), next)))
+        }
+    })
+    .try_flatten()
+}
+
+/// 由偏移量分页的结果构造分页信息
+pub(crate) fn page_info(request: &PageRequest, total: u64) -> PageInfo {
+    let total_pages = total.div_ceil(request.page_size.max(1) as u64) as u32;
+    PageInfo {
+        page: request.page,
+        page_size: request.page_size,
+        total,
+        total_pages,
+        has_next: request.page < total_pages,
+        has_prev: request.page > 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_paginate_follows_has_next() {
+        let items: Vec<u32> = (1..=5).collect();
+        let fetched = paginate(PageRequest::new(1, 2), |request| {
+            let start = ((request.page - 1) * request.page_size) as usize;
+            let data = items.iter().skip(start).take(request.page_size as usize).copied().collect();
+            let pagination = page_info(&request, items.len() as u64);
+            async move { Ok(Page { data, pagination }) }
+        })
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+
+        assert_eq!(fetched, items);
+    }
+
+    #[test]
+    fn test_page_info() {
+        let info = page_info(&PageRequest::new(2, 20), 41);
+        assert_eq!(info.total_pages, 3);
+        assert!(info.has_next && info.has_prev);
+    }
+}
@@ -0,0 +1,87 @@
+// 问答接口客户端
+
+use aionix_common::api::{Page, PageRequest};
+use futures::{Stream, StreamExt};
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::client::AionixClient;
+use crate::error::ClientResult;
+use crate::pagination::paginate;
+use crate::sse::json_events;
+
+pub use aionix_common::models::qa::*;
+
+/// 问答接口
+pub struct QaClient<'a> {
+    client: &'a AionixClient,
+}
+
+impl<'a> QaClient<'a> {
+    pub(crate) fn new(client: &'a AionixClient) -> Self {
+        Self { client }
+    }
+
+    /// 提问
+    pub async fn ask(&self, request: &QaRequest) -> ClientResult<QaResponse> {
+        self.client
+            .send(self.client.request(Method::POST, "/qa/ask").json(request))
+            .await
+    }
+
+    /// 流式提问，按服务端推送顺序返回事件，答案片段在 `chunk` 事件中
+    pub async fn ask_stream(&self, request: &QaRequest) -> ClientResult<impl Stream<Item = ClientResult<StreamEvent>>> {
+        let builder = self.client
+            .request_without_timeout(Method::POST, "/qa/ask-stream")
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(request);
+        let response = self.client.execute(builder).await?;
+        Ok(json_events(response.bytes_stream().boxed()))
+    }
+
+    /// 按页查询会话历史
+    pub async fn session_history(&self, session_id: &str, page: &PageRequest) -> ClientResult<Page<SessionMessage>> {
+        let path = format!("/qa/sessions/{}/history", session_id);
+        self.client
+            .send(self.client.request(Method::GET, &path).query(&[("session_id", session_id)]).query(page))
+            .await
+    }
+
+    /// 逐条返回会话的全部历史消息
+    pub fn session_history_all(
+        &self,
+        session_id: impl Into<String>,
+        page_size: u32,
+    ) -> impl Stream<Item = ClientResult<SessionMessage>> + 'a {
+        let client = self.client;
+        let session_id = session_id.into();
+        paginate(PageRequest::new(1, page_size), move |page| {
+            let session_id = session_id.clone();
+            async move { QaClient::new(client).session_history(&session_id, &page).await }
+        })
+    }
+
+    /// 提交答案反馈
+    pub async fn submit_feedback(&self, request: &QaFeedbackRequest) -> ClientResult<()> {
+        self.client
+            .send_empty(self.client.request(Method::POST, "/qa/feedback").json(request))
+            .await
+    }
+
+    /// 根据部分问题获取补全建议
+    pub async fn suggestions(
+        &self,
+        partial_question: &str,
+        knowledge_base_id: Option<Uuid>,
+        max_suggestions: Option<u8>,
+    ) -> ClientResult<QaSuggestionsResponse> {
+        let body = serde_json::json!({
+            "partial_question": partial_question,
+            "knowledge_base_id": knowledge_base_id,
+            "max_suggestions": max_suggestions,
+        });
+        self.client
+            .send(self.client.request(Method::POST, "/qa/suggestions").json(&body))
+            .await
+    }
+}
@@ -0,0 +1,169 @@
+// Server-Sent Events 解析
+// 将流式响应的字节流切分为事件，并把事件数据解析为类型化结构
+
+use bytes::Bytes;
+use futures::stream::{self, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+
+use crate::error::{ClientError, ClientResult};
+
+/// 一条 SSE 事件
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SseEvent {
+    /// 事件名称，未指定时为空
+    pub event: Option<String>,
+    /// 事件 ID
+    pub id: Option<String>,
+    /// 事件数据，多行 data 以换行拼接
+    pub data: String,
+}
+
+/// 增量 SSE 解析器，可跨数据块处理被截断的行
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    current: SseEvent,
+    has_data: bool,
+}
+
+impl SseDecoder {
+    /// 创建解析器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 写入一个数据块，返回其中已完整的事件
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let mut line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            if let Some(event) = self.process_line(&String::from_utf8_lossy(&line)) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// 流结束时取出尚未以空行结束的事件
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        if !self.buffer.is_empty() {
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).into_owned();
+            if let Some(event) = self.process_line(line.trim_end_matches('\r')) {
+                return Some(event);
+            }
+        }
+        self.process_line("")
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            if !self.has_data {
+                self.current = SseEvent::default();
+                return None;
+            }
+            self.has_data = false;
+            return Some(std::mem::take(&mut self.current));
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "data" => {
+                if self.has_data {
+                    self.current.data.push('\n');
+                }
+                self.current.data.push_str(value);
+                self.has_data = true;
+            }
+            "event" => self.current.event = Some(value.to_string()),
+            "id" => self.current.id = Some(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// 将字节流解析为 SSE 事件流
+pub fn sse_events<S>(bytes: S) -> impl Stream<Item = ClientResult<SseEvent>>
+where
+    S: Stream<Item = reqwest::Result<Bytes>> + Unpin,
+{
+    stream::unfold(
+        (bytes, SseDecoder::new(), false),
+        |(mut bytes, mut decoder, done)| async move {
+            if done {
+                return None;
+            }
+            match bytes.next().await {
+                Some(Ok(chunk)) => {
+                    let events: Vec<ClientResult<SseEvent>> = decoder.push(&chunk).into_iter().map(Ok).collect();
+                    Some((stream::iter(events), (bytes, decoder, false)))
+                }
+                Some(Err(error)) => Some((stream::iter(vec![Err(ClientError::from(error))]), (bytes, decoder, true))),
+                None => {
+                    let events: Vec<ClientResult<SseEvent>> = decoder.finish().into_iter().map(Ok).collect();
+                    Some((stream::iter(events), (bytes, decoder, true)))
+                }
+            }
+        },
+    )
+    .flatten()
+}
+
+/// 将字节流解析为事件数据为 JSON 的类型化事件流
+pub fn json_events<T, S>(bytes: S) -> impl Stream<Item = ClientResult<T>>
+where
+    T: DeserializeOwned,
+    S: Stream<Item = reqwest::Result<Bytes>> + Unpin,
+{
+    sse_events(bytes).filter_map(|event| async move {
+        match event {
+            Ok(event) if event.data.trim().is_empty() => None,
+            Ok(event) => Some(serde_json::from_str(&event.data).map_err(ClientError::from)),
+            Err(error) => Some(Err(error)),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder_handles_split_chunks() {
+        let mut decoder = SseDecoder::new();
+        assert!(decoder.push(b"event: chunk\r\nda").is_empty());
+        let events = decoder.push(b"ta: {\"a\":1}\n\n: keep-alive\n\ndata: x\ndata: y\n\n");
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event.as_deref(), Some("chunk"));
+        assert_eq!(events[0].data, "{\"a\":1}");
+        assert_eq!(events[1].data, "x\ny");
+
+        decoder.push(b"data: tail");
+        assert_eq!(decoder.finish().map(|e| e.data), Some("tail".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_json_events() {
+        let chunks: Vec<reqwest::Result<Bytes>> = vec![
+            Ok(Bytes::from_static(b"data: {\"n\":1}\n\ndata: {\"n\"")),
+            Ok(Bytes::from_static(b":2}\n\n")),
+        ];
+        let values: Vec<serde_json::Value> = json_events(stream::iter(chunks))
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+        assert_eq!(values, vec![serde_json::json!({"n": 1}), serde_json::json!({"n": 2})]);
+    }
+}
@@ -0,0 +1,165 @@
+// 工作流接口客户端
+
+use aionix_common::api::{Page, PageRequest};
+use futures::Stream;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::client::AionixClient;
+use crate::error::ClientResult;
+use crate::pagination::{page_info, paginate};
+
+pub use aionix_common::models::workflows::*;
+
+/// 工作流定义及其版本标识
+#[derive(Debug, Clone)]
+pub struct VersionedWorkflow {
+    /// 工作流定义
+    pub workflow: serde_json::Value,
+    /// ETag，更新工作流时作为 If-Match 请求头
+    pub etag: Option<String>,
+}
+
+/// 偏移量分页的列表响应
+#[derive(Deserialize)]
+struct OffsetList<T> {
+    #[serde(alias = "workflows", alias = "executions")]
+    items: Vec<T>,
+    total: u64,
+}
+
+/// 偏移量分页参数
+#[derive(Serialize)]
+struct OffsetQuery {
+    limit: u32,
+    offset: u32,
+}
+
+impl From<&PageRequest> for OffsetQuery {
+    fn from(page: &PageRequest) -> Self {
+        Self { limit: page.page_size, offset: (page.page.max(1) - 1) * page.page_size }
+    }
+}
+
+/// 工作流接口，服务端直接返回数据而不使用统一响应信封
+pub struct WorkflowsClient<'a> {
+    client: &'a AionixClient,
+}
+
+impl<'a> WorkflowsClient<'a> {
+    pub(crate) fn new(client: &'a AionixClient) -> Self {
+        Self { client }
+    }
+
+    /// 创建工作流
+    pub async fn create(&self, request: &CreateWorkflowRequest) -> ClientResult<CreateWorkflowResponse> {
+        self.client
+            .send_raw(self.client.request(Method::POST, "/workflows").json(request))
+            .await
+    }
+
+    /// 按页查询工作流
+    pub async fn list(&self, filter: &WorkflowFilter, page: &PageRequest) -> ClientResult<Page<WorkflowSummary>> {
+        let list: OffsetList<WorkflowSummary> = self.client
+            .send_raw(self.client.request(Method::GET, "/workflows").query(filter).query(&OffsetQuery::from(page)))
+            .await?;
+        Ok(Page { pagination: page_info(page, list.total), data: list.items })
+    }
+
+    /// 逐条返回符合条件的全部工作流
+    pub fn list_all(
+        &self,
+        filter: WorkflowFilter,
+        page_size: u32,
+    ) -> impl Stream<Item = ClientResult<WorkflowSummary>> + 'a {
+        let client = self.client;
+        paginate(PageRequest::new(1, page_size), move |page| {
+            let filter = filter.clone();
+            async move { WorkflowsClient::new(client).list(&filter, &page).await }
+        })
+    }
+
+    /// 获取工作流定义及其 ETag
+    pub async fn get(&self, workflow_id: Uuid) -> ClientResult<VersionedWorkflow> {
+        let response = self.client
+            .execute(self.client.request(Method::GET, &format!("/workflows/{}", workflow_id)))
+            .await?;
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes().await?;
+        Ok(VersionedWorkflow { workflow: serde_json::from_slice(&body)?, etag })
+    }
+
+    /// 更新工作流，`etag` 为获取工作流时返回的 ETag
+    pub async fn update(
+        &self,
+        workflow_id: Uuid,
+        etag: &str,
+        request: &UpdateWorkflowRequest,
+    ) -> ClientResult<serde_json::Value> {
+        let builder = self.client
+            .request(Method::PUT, &format!("/workflows/{}", workflow_id))
+            .header(reqwest::header::IF_MATCH, etag)
+            .json(request);
+        self.client.send_raw(builder).await
+    }
+
+    /// 执行工作流
+    pub async fn execute(
+        &self,
+        workflow_id: Uuid,
+        request: &ExecuteWorkflowRequest,
+    ) -> ClientResult<ExecuteWorkflowResponse> {
+        let path = format!("/workflows/{}/execute", workflow_id);
+        self.client
+            .send_raw(self.client.request_without_timeout(Method::POST, &path).json(request))
+            .await
+    }
+
+    /// 获取执行状态
+    pub async fn execution(&self, execution_id: Uuid) -> ClientResult<serde_json::Value> {
+        self.client
+            .send_raw(self.client.request(Method::GET, &format!("/workflows/executions/{}", execution_id)))
+            .await
+    }
+
+    /// 取消执行
+    pub async fn cancel_execution(&self, execution_id: Uuid) -> ClientResult<()> {
+        let path = format!("/workflows/executions/{}/cancel", execution_id);
+        self.client
+            .send_raw::<serde_json::Value>(self.client.request(Method::POST, &path))
+            .await
+            .map(|_| ())
+    }
+
+    /// 按页查询工作流的执行历史
+    pub async fn executions(&self, workflow_id: Uuid, page: &PageRequest) -> ClientResult<Page<ExecutionSummary>> {
+        let path = format!("/workflows/{}/executions", workflow_id);
+        let list: OffsetList<ExecutionSummary> = self.client
+            .send_raw(self.client.request(Method::GET, &path).query(&OffsetQuery::from(page)))
+            .await?;
+        Ok(Page { pagination: page_info(page, list.total), data: list.items })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_list_conversion() {
+        let query = OffsetQuery::from(&PageRequest::new(3, 20));
+        assert_eq!((query.limit, query.offset), (20, 40));
+
+        let list: OffsetList<serde_json::Value> = serde_json::from_str(
+            r#"{"workflows":[{}],"total":41,"pagination":{"page":3,"page_size":20,"total_pages":3,"has_next":false,"has_prev":true}}"#,
+        )
+        .unwrap();
+        assert_eq!(list.items.len(), 1);
+        assert_eq!(list.total, 41);
+    }
+}
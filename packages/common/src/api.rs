@@ -0,0 +1,150 @@
+// API 传输格式定义
+// 与服务端 REST API 的 JSON 结构保持一致，服务端与客户端 SDK 共用
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// REST API 路径前缀
+pub const API_PREFIX: &str = "/api/v1";
+
+/// 统一响应信封
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiEnvelope<T> {
+    /// 是否成功
+    pub success: bool,
+    /// 响应数据
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
+    /// 错误信息
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiErrorBody>,
+    /// 请求 ID
+    #[serde(default)]
+    pub request_id: String,
+    /// 响应时间戳
+    pub timestamp: DateTime<Utc>,
+    /// API 版本
+    #[serde(default)]
+    pub version: String,
+}
+
+impl<T> ApiEnvelope<T> {
+    /// 取出响应数据，失败响应返回错误信息
+    pub fn into_result(self) -> Result<Option<T>, Box<ApiErrorBody>> {
+        match (self.success, self.error) {
+            (true, _) => Ok(self.data),
+            (false, Some(error)) => Err(Box::new(error)),
+            (false, None) => Err(Box::new(ApiErrorBody {
+                code: "UNKNOWN_ERROR".to_string(),
+                message: "请求失败".to_string(),
+                details: None,
+                field: None,
+                help_url: None,
+            })),
+        }
+    }
+}
+
+/// 错误信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiErrorBody {
+    /// 错误代码
+    pub code: String,
+    /// 错误消息
+    pub message: String,
+    /// 错误详情
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// 错误字段（用于表单验证错误）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// 帮助链接
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help_url: Option<String>,
+}
+
+/// 分页信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageInfo {
+    /// 当前页码
+    pub page: u32,
+    /// 每页大小
+    pub page_size: u32,
+    /// 总记录数
+    pub total: u64,
+    /// 总页数
+    pub total_pages: u32,
+    /// 是否有下一页
+    pub has_next: bool,
+    /// 是否有上一页
+    pub has_prev: bool,
+}
+
+/// 分页数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    /// 数据列表
+    pub data: Vec<T>,
+    /// 分页信息
+    pub pagination: PageInfo,
+}
+
+/// 分页请求参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageRequest {
+    /// 页码，从 1 开始
+    pub page: u32,
+    /// 每页大小，服务端最大 100
+    pub page_size: u32,
+    /// 排序字段
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<String>,
+    /// 排序方向：asc 或 desc
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_order: Option<String>,
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self { page: 1, page_size: 20, sort_by: None, sort_order: None }
+    }
+}
+
+impl PageRequest {
+    /// 指定页码与每页大小
+    pub fn new(page: u32, page_size: u32) -> Self {
+        Self { page: page.max(1), page_size: page_size.clamp(1, 100), ..Default::default() }
+    }
+
+    /// 下一页的请求参数
+    pub fn next(&self) -> Self {
+        Self { page: self.page + 1, ..self.clone() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_into_result() {
+        let ok: ApiEnvelope<u32> = serde_json::from_str(
+            r#"{"success":true,"data":7,"request_id":"r1","timestamp":"2024-01-01T00:00:00Z","version":"0.1.0"}"#,
+        )
+        .unwrap();
+        assert_eq!(ok.into_result().unwrap(), Some(7));
+
+        let failed: ApiEnvelope<u32> = serde_json::from_str(
+            r#"{"success":false,"error":{"code":"NOT_FOUND","message":"资源未找到"},"request_id":"r2","timestamp":"2024-01-01T00:00:00Z","version":"0.1.0"}"#,
+        )
+        .unwrap();
+        assert_eq!(failed.into_result().unwrap_err().code, "NOT_FOUND");
+    }
+
+    #[test]
+    fn test_page_request() {
+        let request = PageRequest::new(0, 500);
+        assert_eq!((request.page, request.page_size), (1, 100));
+        assert_eq!(request.next().page, 2);
+    }
+}
@@ -0,0 +1,57 @@
+// API 错误代码
+// 错误响应中 `error.code` 的取值及其对应的 HTTP 状态码
+
+pub const CONFIGURATION_ERROR: &str = "CONFIGURATION_ERROR";
+pub const DATABASE_ERROR: &str = "DATABASE_ERROR";
+pub const AI_SERVICE_ERROR: &str = "AI_SERVICE_ERROR";
+pub const CACHE_ERROR: &str = "CACHE_ERROR";
+pub const AUTHENTICATION_ERROR: &str = "AUTHENTICATION_ERROR";
+pub const AUTHORIZATION_ERROR: &str = "AUTHORIZATION_ERROR";
+pub const VALIDATION_ERROR: &str = "VALIDATION_ERROR";
+pub const NOT_FOUND: &str = "NOT_FOUND";
+pub const CONFLICT: &str = "CONFLICT";
+pub const RATE_LIMIT: &str = "RATE_LIMIT";
+pub const FILE_PROCESSING_ERROR: &str = "FILE_PROCESSING_ERROR";
+pub const VECTOR_ERROR: &str = "VECTOR_ERROR";
+pub const TENANT_ERROR: &str = "TENANT_ERROR";
+pub const EXTERNAL_SERVICE_ERROR: &str = "EXTERNAL_SERVICE_ERROR";
+pub const INTERNAL_ERROR: &str = "INTERNAL_ERROR";
+pub const SERVICE_UNAVAILABLE: &str = "SERVICE_UNAVAILABLE";
+pub const TIMEOUT_ERROR: &str = "TIMEOUT_ERROR";
+
+/// 错误代码对应的 HTTP 状态码，未知代码视为服务器内部错误
+pub fn http_status(code: &str) -> u16 {
+    match code {
+        CONFIGURATION_ERROR | DATABASE_ERROR | CACHE_ERROR | VECTOR_ERROR | INTERNAL_ERROR => 500,
+        AI_SERVICE_ERROR | EXTERNAL_SERVICE_ERROR => 502,
+        AUTHENTICATION_ERROR => 401,
+        AUTHORIZATION_ERROR => 403,
+        VALIDATION_ERROR | FILE_PROCESSING_ERROR | TENANT_ERROR => 400,
+        NOT_FOUND => 404,
+        CONFLICT => 409,
+        RATE_LIMIT => 429,
+        SERVICE_UNAVAILABLE => 503,
+        TIMEOUT_ERROR => 408,
+        _ => 500,
+    }
+}
+
+/// 是否为可重试的错误
+pub fn is_retryable(code: &str) -> bool {
+    matches!(code, RATE_LIMIT | SERVICE_UNAVAILABLE | TIMEOUT_ERROR | AI_SERVICE_ERROR | EXTERNAL_SERVICE_ERROR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_status() {
+        assert_eq!(http_status(VALIDATION_ERROR), 400);
+        assert_eq!(http_status(NOT_FOUND), 404);
+        assert_eq!(http_status(RATE_LIMIT), 429);
+        assert_eq!(http_status("SOMETHING_ELSE"), 500);
+        assert!(is_retryable(SERVICE_UNAVAILABLE));
+        assert!(!is_retryable(VALIDATION_ERROR));
+    }
+}
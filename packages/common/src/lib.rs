@@ -1,8 +1,12 @@
 // Aionix Common Package
 // 通用类型定义和工具函数
 
+pub mod api;
+pub mod error_codes;
+pub mod models;
+pub mod plugin;
 pub mod types;
 pub mod errors;
 
 pub use types::*;
-pub use errors::*;
\ No newline at end of file
+pub use errors::*;
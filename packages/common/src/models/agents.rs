@@ -0,0 +1,189 @@
+// Agent 接口数据模型
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 推理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningStrategy {
+    React,
+    ChainOfThought,
+    PlanAndExecute,
+    SelfReflection,
+}
+
+/// 任务优先级
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Critical,
+}
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Agent 状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentState {
+    Idle,
+    Thinking,
+    ExecutingTool,
+    WaitingForInput,
+    Completed,
+    Error,
+}
+
+/// Agent 创建请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAgentRequest {
+    /// Agent 名称
+    pub name: String,
+    /// Agent 描述
+    pub description: String,
+    /// 系统提示词
+    pub system_prompt: String,
+    /// 可用工具列表
+    pub available_tools: Vec<String>,
+    /// 推理策略
+    pub reasoning_strategy: ReasoningStrategy,
+    /// 温度参数，默认 0.7
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// 最大令牌数，默认 2000
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// 使用的模型，未指定时使用默认模型
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// Agent 创建响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAgentResponse {
+    /// Agent ID
+    pub agent_id: Uuid,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 状态
+    pub status: String,
+}
+
+/// Agent 任务执行请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecuteTaskRequest {
+    /// 任务描述
+    pub description: String,
+    /// 任务目标
+    pub objective: String,
+    /// 任务参数
+    pub parameters: HashMap<String, serde_json::Value>,
+    /// 任务优先级
+    pub priority: TaskPriority,
+    /// 截止时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<DateTime<Utc>>,
+    /// 期望的输出 JSON Schema
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
+}
+
+/// Agent 任务执行响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecuteTaskResponse {
+    /// 任务 ID
+    pub task_id: Uuid,
+    /// 执行结果
+    pub result: serde_json::Value,
+    /// 执行状态
+    pub status: TaskStatus,
+    /// 执行时间（毫秒）
+    pub execution_time_ms: u64,
+    /// 结构化输出
+    pub structured_output: Option<serde_json::Value>,
+}
+
+/// Agent 状态响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentStatusResponse {
+    /// Agent ID
+    pub agent_id: Uuid,
+    /// Agent 状态
+    pub state: AgentState,
+    /// 当前任务
+    pub current_task: Option<AgentTaskInfo>,
+    /// 最后活跃时间
+    pub last_active_at: DateTime<Utc>,
+    /// 执行统计
+    pub execution_stats: ExecutionStats,
+}
+
+/// Agent 任务信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentTaskInfo {
+    /// 任务 ID
+    pub task_id: Uuid,
+    /// 任务描述
+    pub description: String,
+    /// 任务状态
+    pub status: TaskStatus,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+}
+
+/// 执行统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionStats {
+    /// 总任务数
+    pub total_tasks: u32,
+    /// 成功任务数
+    pub successful_tasks: u32,
+    /// 失败任务数
+    pub failed_tasks: u32,
+    /// 平均执行时间（毫秒）
+    pub avg_execution_time_ms: f32,
+}
+
+/// Agent 列表响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListAgentsResponse {
+    /// Agent 列表
+    pub agents: Vec<AgentInfo>,
+    /// 总数
+    pub total: u32,
+    /// 活跃数
+    pub active: u32,
+}
+
+/// Agent 信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentInfo {
+    /// Agent ID
+    pub agent_id: Uuid,
+    /// Agent 名称
+    pub name: String,
+    /// Agent 描述
+    pub description: String,
+    /// Agent 状态
+    pub state: AgentState,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 最后活跃时间
+    pub last_active_at: DateTime<Utc>,
+}
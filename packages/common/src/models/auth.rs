@@ -0,0 +1,120 @@
+// 认证接口数据模型
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 登录请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginRequest {
+    /// 用户名或邮箱
+    pub username: String,
+    /// 密码
+    pub password: String,
+    /// 租户标识符
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_slug: Option<String>,
+    /// 记住我（延长令牌有效期）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remember_me: Option<bool>,
+}
+
+/// 登录响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginResponse {
+    /// 访问令牌
+    pub access_token: String,
+    /// 刷新令牌
+    pub refresh_token: String,
+    /// 令牌类型
+    pub token_type: String,
+    /// 过期时间（秒）
+    pub expires_in: i64,
+    /// 用户信息
+    pub user: UserInfo,
+    /// 租户信息
+    pub tenant: TenantInfo,
+}
+
+/// 刷新令牌响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshTokenResponse {
+    /// 访问令牌
+    pub access_token: String,
+    /// 刷新令牌
+    pub refresh_token: String,
+    /// 令牌类型
+    pub token_type: String,
+    /// 过期时间（秒）
+    pub expires_in: i64,
+}
+
+/// 注册请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterRequest {
+    /// 用户名
+    pub username: String,
+    /// 邮箱
+    pub email: String,
+    /// 密码
+    pub password: String,
+    /// 确认密码
+    pub password_confirm: String,
+    /// 显示名称
+    pub display_name: String,
+    /// 租户标识符
+    pub tenant_slug: String,
+    /// 邀请码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invitation_code: Option<String>,
+}
+
+/// 注册响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterResponse {
+    /// 用户信息
+    pub user: UserInfo,
+    /// 是否需要邮箱验证
+    pub email_verification_required: bool,
+    /// 验证邮件发送状态
+    pub verification_email_sent: bool,
+}
+
+/// 更新用户资料请求，未提供的字段保持不变
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateProfileRequest {
+    /// 显示名称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// 邮箱
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// 头像地址
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+}
+
+/// 用户信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserInfo {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub display_name: String,
+    pub avatar_url: Option<String>,
+    pub role: String,
+    pub permissions: Vec<String>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 租户信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub display_name: String,
+    pub status: String,
+}
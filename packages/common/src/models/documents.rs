@@ -0,0 +1,181 @@
+// 文档接口数据模型
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 文档创建请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateDocumentRequest {
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 文档内容
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// 文档类型，如 Text、Pdf、Markdown
+    pub doc_type: String,
+    /// 文档元数据
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    /// 处理配置
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processing_config: Option<serde_json::Value>,
+    /// 过期时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 复核时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_at: Option<DateTime<Utc>>,
+    /// 负责人 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_id: Option<Uuid>,
+}
+
+/// 文档更新请求，未提供的字段保持不变
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateDocumentRequest {
+    /// 文档标题
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// 文档内容
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// 文档状态，如 Pending、Completed、Archived
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// 文档元数据
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    /// 处理配置
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processing_config: Option<serde_json::Value>,
+    /// 过期时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 复核时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_at: Option<DateTime<Utc>>,
+    /// 负责人 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_id: Option<Uuid>,
+}
+
+/// 文档列表过滤条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocumentFilter {
+    /// 知识库 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub knowledge_base_id: Option<Uuid>,
+    /// 搜索关键词
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+    /// 文档类型
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc_type: Option<String>,
+    /// 文档状态
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// 创建时间范围（开始）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<DateTime<Utc>>,
+    /// 创建时间范围（结束）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+/// 文档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Document {
+    /// 文档 ID
+    pub id: Uuid,
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 文档内容（超过 1000 字节时被截断）
+    pub content: String,
+    /// 文档摘要
+    pub summary: Option<String>,
+    /// 文档类型
+    pub doc_type: String,
+    /// 文档状态
+    pub status: String,
+    /// 文件名
+    pub file_name: Option<String>,
+    /// 文件大小
+    pub file_size: i64,
+    /// 格式化的文件大小
+    pub formatted_file_size: String,
+    /// MIME 类型
+    pub mime_type: Option<String>,
+    /// 文档元数据
+    pub metadata: serde_json::Value,
+    /// 处理配置
+    pub processing_config: serde_json::Value,
+    /// 文档块数量
+    pub chunk_count: i32,
+    /// 处理开始时间
+    pub processing_started_at: Option<DateTime<Utc>>,
+    /// 处理完成时间
+    pub processing_completed_at: Option<DateTime<Utc>>,
+    /// 处理耗时（毫秒）
+    pub processing_duration_ms: Option<i64>,
+    /// 错误信息
+    pub error_message: Option<String>,
+    /// 版本号
+    pub version: i32,
+    /// 过期时间
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 复核时间
+    pub review_at: Option<DateTime<Utc>>,
+    /// 负责人 ID
+    pub owner_id: Option<Uuid>,
+    /// 访问密级
+    pub clearance: String,
+    /// 进度百分比
+    pub progress_percentage: f32,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 文档统计信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentStats {
+    /// 文档 ID
+    pub id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 文件大小
+    pub file_size: i64,
+    /// 格式化的文件大小
+    pub formatted_file_size: String,
+    /// 文档块数量
+    pub chunk_count: i32,
+    /// 字数
+    pub word_count: Option<i32>,
+    /// 字符数
+    pub char_count: Option<i32>,
+    /// 处理耗时（毫秒）
+    pub processing_duration_ms: Option<i64>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+}
+
+/// 文档上传响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentUploadResponse {
+    /// 文档 ID
+    pub id: Uuid,
+    /// 上传状态
+    pub status: String,
+    /// 文件名
+    pub file_name: String,
+    /// 文件大小
+    pub file_size: i64,
+    /// 消息
+    pub message: String,
+}
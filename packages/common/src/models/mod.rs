@@ -0,0 +1,8 @@
+// REST API 请求与响应数据模型
+// 服务端接口的 JSON 结构，客户端 SDK 与外部集成共用
+
+pub mod agents;
+pub mod auth;
+pub mod documents;
+pub mod qa;
+pub mod workflows;
@@ -0,0 +1,230 @@
+// 问答接口数据模型
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 问答请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QaRequest {
+    /// 用户问题
+    pub question: String,
+    /// 知识库 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub knowledge_base_id: Option<Uuid>,
+    /// 会话 ID（用于上下文保持）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// 检索参数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retrieval_params: Option<RetrievalParams>,
+    /// 生成参数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation_params: Option<GenerationParams>,
+    /// 知识库快照名称，需同时指定知识库 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kb_version: Option<String>,
+    /// 期望的输出 JSON Schema
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
+}
+
+impl QaRequest {
+    /// 以问题创建请求
+    pub fn new(question: impl Into<String>) -> Self {
+        Self { question: question.into(), ..Default::default() }
+    }
+}
+
+/// 检索参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetrievalParams {
+    /// 检索的文档块数量
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// 相似度阈值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity_threshold: Option<f32>,
+    /// 检索方法
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retrieval_method: Option<String>,
+    /// 是否启用重排序
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_reranking: Option<bool>,
+    /// 文档类型过滤
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_types: Option<Vec<String>>,
+}
+
+/// 生成参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GenerationParams {
+    /// 最大生成长度
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u32>,
+    /// 温度参数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// 是否包含来源引用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_sources: Option<bool>,
+    /// 答案语言
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// 生成风格
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+}
+
+/// 问答响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QaResponse {
+    /// 查询 ID
+    pub query_id: String,
+    /// 会话 ID
+    pub session_id: String,
+    /// 生成的答案
+    pub answer: String,
+    /// 置信度分数
+    pub confidence_score: f32,
+    /// 来源文档
+    pub sources: Vec<QaSource>,
+    /// 相关建议
+    pub suggestions: Vec<String>,
+    /// 查询统计
+    pub stats: QaStats,
+    /// 回答所基于的知识库快照名称
+    pub kb_version: Option<String>,
+    /// 结构化答案
+    pub structured_output: Option<serde_json::Value>,
+    /// 命中的 FAQ/术语表条目
+    pub faq_match: Option<serde_json::Value>,
+    /// 命中的精选答案
+    pub curated_answer: Option<serde_json::Value>,
+    /// 置信度明细
+    pub confidence: Option<ConfidenceBreakdown>,
+    /// 是否因资料不足而未给出答案
+    #[serde(default)]
+    pub insufficient_information: bool,
+    /// 响应时间
+    pub response_time: DateTime<Utc>,
+}
+
+/// 问答来源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QaSource {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 文档类型
+    pub doc_type: String,
+    /// 相关性分数
+    pub relevance_score: f32,
+    /// 引用的文档块
+    pub chunks: Vec<QaChunk>,
+}
+
+/// 问答文档块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QaChunk {
+    /// 文档块 ID
+    pub chunk_id: Uuid,
+    /// 文档块内容（可能被截断）
+    pub content: String,
+    /// 相似度分数
+    pub similarity_score: f32,
+    /// 块索引
+    pub chunk_index: i32,
+}
+
+/// 问答统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QaStats {
+    /// 总响应时间（毫秒）
+    pub response_time_ms: u64,
+    /// 检索到的文档数量
+    pub documents_retrieved: u32,
+    /// 使用的文档块数量
+    pub chunks_used: u32,
+    /// 生成的 token 数量
+    pub tokens_generated: Option<u32>,
+}
+
+/// 置信度明细
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ConfidenceBreakdown {
+    /// 综合置信度（0-1）
+    pub score: f32,
+    /// 检索置信度
+    pub retrieval: f32,
+    /// 模型自评置信度
+    pub self_assessment: Option<f32>,
+    /// 答案特征置信度
+    pub answer_signal: f32,
+}
+
+/// 流式问答事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamEvent {
+    /// 事件类型：start、retrieval、generation、chunk、complete 或 error
+    pub event: String,
+    /// 事件数据
+    pub data: serde_json::Value,
+    /// 时间戳
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 会话消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionMessage {
+    /// 消息 ID
+    pub message_id: String,
+    /// 消息类型：question、answer 或 system
+    pub message_type: String,
+    /// 消息内容
+    pub content: String,
+    /// 时间戳
+    pub timestamp: DateTime<Utc>,
+    /// 元数据
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// 反馈类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackType {
+    Helpful,
+    NotHelpful,
+    Incorrect,
+    Incomplete,
+    Irrelevant,
+    Other,
+}
+
+/// 问答反馈请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QaFeedbackRequest {
+    /// 查询 ID
+    pub query_id: String,
+    /// 反馈类型
+    pub feedback_type: FeedbackType,
+    /// 评分 (1-5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>,
+    /// 反馈内容
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// 是否有用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub helpful: Option<bool>,
+}
+
+/// 问答建议响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QaSuggestionsResponse {
+    /// 建议列表
+    pub suggestions: Vec<String>,
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+}
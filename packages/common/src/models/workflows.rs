@@ -0,0 +1,198 @@
+// 工作流接口数据模型
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 工作流状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowStatus {
+    Draft,
+    Published,
+    Deprecated,
+    Deleted,
+}
+
+/// 工作流创建请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWorkflowRequest {
+    /// 工作流名称
+    pub name: String,
+    /// 工作流描述
+    pub description: String,
+    /// 工作流版本
+    pub version: String,
+    /// 工作流定义（JSON 字符串）
+    pub workflow_definition: String,
+}
+
+/// 工作流更新请求，未提供的字段保持不变
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateWorkflowRequest {
+    /// 工作流名称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// 工作流描述
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 工作流版本
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// 工作流定义（JSON 字符串）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workflow_definition: Option<String>,
+}
+
+/// 工作流创建响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWorkflowResponse {
+    /// 工作流 ID
+    pub workflow_id: Uuid,
+    /// 工作流名称
+    pub name: String,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 验证结果
+    pub validation_result: ValidationSummary,
+}
+
+/// 验证摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationSummary {
+    /// 是否有效
+    pub is_valid: bool,
+    /// 错误数量
+    pub error_count: usize,
+    /// 警告数量
+    pub warning_count: usize,
+    /// 主要错误信息
+    pub main_errors: Vec<String>,
+}
+
+/// 工作流执行请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecuteWorkflowRequest {
+    /// 执行参数
+    pub parameters: HashMap<String, serde_json::Value>,
+    /// 是否异步执行
+    pub async_execution: bool,
+    /// 超时时间（秒）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+    /// 是否启用详细日志
+    pub enable_detailed_logs: bool,
+}
+
+impl Default for ExecuteWorkflowRequest {
+    fn default() -> Self {
+        Self {
+            parameters: HashMap::new(),
+            async_execution: true,
+            timeout_seconds: None,
+            enable_detailed_logs: true,
+        }
+    }
+}
+
+/// 工作流执行响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecuteWorkflowResponse {
+    /// 执行 ID
+    pub execution_id: Uuid,
+    /// 工作流 ID
+    pub workflow_id: Uuid,
+    /// 执行状态
+    pub status: String,
+    /// 开始时间
+    pub started_at: DateTime<Utc>,
+    /// 预计完成时间
+    pub estimated_completion: Option<DateTime<Utc>>,
+}
+
+/// 工作流列表过滤条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkflowFilter {
+    /// 状态过滤
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<WorkflowStatus>,
+    /// 名称搜索
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// 工作流摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowSummary {
+    /// 工作流 ID
+    pub id: Uuid,
+    /// 工作流名称
+    pub name: String,
+    /// 工作流描述
+    pub description: String,
+    /// 工作流版本
+    pub version: String,
+    /// 工作流状态
+    pub status: WorkflowStatus,
+    /// 步骤数量
+    pub step_count: usize,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+    /// 最近执行时间
+    pub last_executed_at: Option<DateTime<Utc>>,
+    /// 执行统计
+    pub execution_stats: WorkflowExecutionStats,
+}
+
+/// 工作流执行统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowExecutionStats {
+    /// 总执行次数
+    pub total_executions: u64,
+    /// 成功执行次数
+    pub successful_executions: u64,
+    /// 失败执行次数
+    pub failed_executions: u64,
+    /// 平均执行时间（毫秒）
+    pub avg_execution_time_ms: f32,
+}
+
+/// 执行摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionSummary {
+    /// 执行 ID
+    pub execution_id: Uuid,
+    /// 工作流 ID
+    pub workflow_id: Uuid,
+    /// 工作流名称
+    pub workflow_name: String,
+    /// 执行状态
+    pub status: String,
+    /// 开始时间
+    pub started_at: DateTime<Utc>,
+    /// 完成时间
+    pub completed_at: Option<DateTime<Utc>>,
+    /// 执行时间（毫秒）
+    pub execution_time_ms: u64,
+    /// 步骤统计
+    pub step_stats: StepStats,
+    /// 错误信息
+    pub error: Option<String>,
+}
+
+/// 步骤统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepStats {
+    /// 总步骤数
+    pub total: u32,
+    /// 已完成步骤数
+    pub completed: u32,
+    /// 失败步骤数
+    pub failed: u32,
+    /// 跳过步骤数
+    pub skipped: u32,
+}
@@ -0,0 +1,575 @@
+// 插件接口类型
+// 插件元数据、配置、上下文与系统信息等数据结构，由服务端与外部插件共用
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::CommonError;
+
+/// 插件接口版本，插件元数据中的 `api_version` 应与之兼容
+pub const PLUGIN_API_VERSION: &str = "1.0";
+
+/// 插件元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginMetadata {
+    /// 插件 ID
+    pub id: String,
+    /// 插件名称
+    pub name: String,
+    /// 插件版本
+    pub version: String,
+    /// 插件描述
+    pub description: String,
+    /// 插件作者
+    pub author: String,
+    /// 插件许可证
+    pub license: String,
+    /// 插件主页
+    pub homepage: Option<String>,
+    /// 插件仓库
+    pub repository: Option<String>,
+    /// 插件类型
+    pub plugin_type: PluginType,
+    /// 支持的 API 版本
+    pub api_version: String,
+    /// 最小系统版本要求
+    pub min_system_version: String,
+    /// 插件依赖
+    pub dependencies: Vec<PluginDependency>,
+    /// 插件权限要求
+    pub permissions: Vec<PluginPermission>,
+    /// 插件标签
+    pub tags: Vec<String>,
+    /// 插件图标
+    pub icon: Option<String>,
+    /// 插件注册的 HTTP 路由（需要 `HttpRoutes` 权限）
+    #[serde(default)]
+    pub routes: Vec<PluginRoute>,
+    /// 订阅的系统事件（支持 `*` 与 `document.*` 形式的通配）
+    #[serde(default)]
+    pub event_subscriptions: Vec<String>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+}
+
+/// 插件类型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginType {
+    /// 工具插件
+    Tool,
+    /// Agent 插件
+    Agent,
+    /// 工作流插件
+    Workflow,
+    /// 数据源插件
+    DataSource,
+    /// 认证插件
+    Authentication,
+    /// 存储插件
+    Storage,
+    /// 通知插件
+    Notification,
+    /// 监控插件
+    Monitoring,
+    /// 自定义插件
+    Custom,
+}
+
+/// 插件依赖
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginDependency {
+    /// 依赖插件 ID
+    pub plugin_id: String,
+    /// 版本要求
+    pub version_requirement: String,
+    /// 是否可选
+    pub optional: bool,
+}
+
+/// 插件权限
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginPermission {
+    /// 文件系统访问
+    FileSystem,
+    /// 网络访问
+    Network,
+    /// 数据库访问
+    Database,
+    /// 系统信息访问
+    SystemInfo,
+    /// 用户数据访问
+    UserData,
+    /// 管理员权限
+    Admin,
+    /// 注册 HTTP 路由
+    HttpRoutes,
+    /// 自定义权限
+    Custom(String),
+}
+
+/// 插件 HTTP 路由声明
+/// 路由挂载在 `/api/v1/ext/{plugin_id}` 下，请求经插件管理器转发给 `handle_call`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginRoute {
+    /// HTTP 方法（GET、POST、PUT、PATCH、DELETE）
+    pub method: String,
+    /// 相对于插件前缀的路径，支持 `{name}` 形式的路径参数
+    pub path: String,
+    /// 处理该路由的插件方法名
+    pub handler: String,
+    /// 调用方需要具备的用户权限
+    #[serde(default)]
+    pub required_permissions: Vec<String>,
+    /// 是否仅允许管理员调用
+    #[serde(default)]
+    pub admin_only: bool,
+}
+
+impl PluginRoute {
+    /// 支持的 HTTP 方法
+    pub const SUPPORTED_METHODS: [&'static str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+    /// 验证路由声明
+    pub fn validate(&self) -> Result<(), CommonError> {
+        if !Self::SUPPORTED_METHODS.contains(&self.method.to_uppercase().as_str()) {
+            return Err(CommonError::validation(format!("不支持的 HTTP 方法: {}", self.method)));
+        }
+
+        if !self.path.starts_with('/') || self.path.split('/').any(|s| s == ".." || s == ".") {
+            return Err(CommonError::validation(format!("无效的路由路径: {}", self.path)));
+        }
+
+        if self.handler.trim().is_empty() {
+            return Err(CommonError::validation("路由处理方法不能为空"));
+        }
+
+        Ok(())
+    }
+
+    /// 匹配请求，成功时返回路径参数
+    pub fn match_request(&self, method: &str, path: &str) -> Option<HashMap<String, String>> {
+        if !self.method.eq_ignore_ascii_case(method) {
+            return None;
+        }
+
+        let pattern: Vec<&str> = self.path.split('/').filter(|s| !s.is_empty()).collect();
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        if pattern.len() != segments.len() {
+            return None;
+        }
+
+        let mut params = HashMap::new();
+        for (expected, actual) in pattern.iter().zip(segments.iter()) {
+            match expected.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(name) => {
+                    params.insert(name.to_string(), actual.to_string());
+                }
+                None if expected == actual => {}
+                None => return None,
+            }
+        }
+
+        Some(params)
+    }
+}
+
+/// 插件路由请求
+/// 作为 `request` 参数传给路由对应的 `handle_call` 方法
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginHttpRequest {
+    /// HTTP 方法
+    pub method: String,
+    /// 相对于插件前缀的请求路径
+    pub path: String,
+    /// 路径参数
+    pub path_params: HashMap<String, String>,
+    /// 查询参数
+    pub query: HashMap<String, String>,
+    /// 请求头（已过滤认证相关头）
+    pub headers: HashMap<String, String>,
+    /// 请求体
+    pub body: Option<serde_json::Value>,
+}
+
+/// 插件配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConfig {
+    /// 插件 ID
+    pub plugin_id: String,
+    /// 配置参数
+    pub parameters: HashMap<String, serde_json::Value>,
+    /// 环境变量
+    pub environment: HashMap<String, String>,
+    /// 资源限制
+    pub resource_limits: ResourceLimits,
+    /// 安全设置
+    pub security_settings: SecuritySettings,
+}
+
+/// 资源限制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// 最大内存使用（MB）
+    pub max_memory_mb: Option<u64>,
+    /// 最大 CPU 使用率（百分比）
+    pub max_cpu_percent: Option<f32>,
+    /// 最大磁盘使用（MB）
+    pub max_disk_mb: Option<u64>,
+    /// 最大网络带宽（KB/s）
+    pub max_network_kbps: Option<u64>,
+    /// 最大执行时间（秒）
+    pub max_execution_seconds: Option<u64>,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_memory_mb: Some(512),
+            max_cpu_percent: Some(50.0),
+            max_disk_mb: Some(1024),
+            max_network_kbps: Some(1024),
+            max_execution_seconds: Some(300),
+        }
+    }
+}
+
+/// 安全设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecuritySettings {
+    /// 是否启用沙箱
+    pub enable_sandbox: bool,
+    /// 允许的网络域名
+    pub allowed_domains: Vec<String>,
+    /// 允许的文件路径
+    pub allowed_paths: Vec<String>,
+    /// 禁止的操作
+    pub forbidden_operations: Vec<String>,
+}
+
+impl Default for SecuritySettings {
+    fn default() -> Self {
+        Self {
+            enable_sandbox: true,
+            allowed_domains: Vec::new(),
+            allowed_paths: Vec::new(),
+            forbidden_operations: Vec::new(),
+        }
+    }
+}
+
+/// 插件状态
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginStatus {
+    /// 未初始化
+    Uninitialized,
+    /// 初始化中
+    Initializing,
+    /// 已初始化
+    Initialized,
+    /// 启动中
+    Starting,
+    /// 运行中
+    Running,
+    /// 停止中
+    Stopping,
+    /// 已停止
+    Stopped,
+    /// 错误状态
+    Error,
+    /// 卸载中
+    Unloading,
+    /// 已卸载
+    Unloaded,
+}
+
+/// 插件上下文
+#[derive(Debug, Clone)]
+pub struct PluginContext {
+    /// 租户 ID
+    pub tenant_id: Uuid,
+    /// 用户 ID
+    pub user_id: Option<Uuid>,
+    /// 会话 ID
+    pub session_id: Option<Uuid>,
+    /// 请求 ID
+    pub request_id: Uuid,
+    /// 上下文变量
+    pub variables: HashMap<String, serde_json::Value>,
+    /// 生效的插件配置（安装配置与租户配置合并，敏感字段已解密）
+    pub plugin_config: HashMap<String, serde_json::Value>,
+    /// 调用时间
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 插件健康状态
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginHealth {
+    /// 是否健康
+    pub healthy: bool,
+    /// 状态消息
+    pub message: String,
+    /// 详细信息
+    pub details: HashMap<String, serde_json::Value>,
+    /// 检查时间
+    pub checked_at: DateTime<Utc>,
+    /// 响应时间（毫秒）
+    pub response_time_ms: u64,
+}
+
+/// 插件事件
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginEvent {
+    /// 事件 ID
+    pub event_id: Uuid,
+    /// 插件 ID
+    pub plugin_id: String,
+    /// 事件类型
+    pub event_type: PluginEventType,
+    /// 事件数据
+    pub data: serde_json::Value,
+    /// 事件时间
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 插件事件类型
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginEventType {
+    /// 插件加载
+    Loaded,
+    /// 插件初始化
+    Initialized,
+    /// 插件启动
+    Started,
+    /// 插件停止
+    Stopped,
+    /// 插件卸载
+    Unloaded,
+    /// 插件错误
+    Error,
+    /// 插件调用
+    Called,
+    /// 配置更新
+    ConfigUpdated,
+    /// 健康检查
+    HealthCheck,
+}
+
+/// 日志级别
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// 系统信息
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemInfo {
+    /// 系统版本
+    pub version: String,
+    /// 系统名称
+    pub name: String,
+    /// 运行时间
+    pub uptime_seconds: u64,
+    /// 内存使用情况
+    pub memory_usage: MemoryUsage,
+    /// CPU 使用情况
+    pub cpu_usage: CpuUsage,
+    /// 磁盘使用情况
+    pub disk_usage: DiskUsage,
+}
+
+/// 内存使用情况
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryUsage {
+    /// 总内存（MB）
+    pub total_mb: u64,
+    /// 已使用内存（MB）
+    pub used_mb: u64,
+    /// 可用内存（MB）
+    pub available_mb: u64,
+    /// 使用率（百分比）
+    pub usage_percent: f32,
+}
+
+/// CPU 使用情况
+#[derive(Debug, Clone, Serialize)]
+pub struct CpuUsage {
+    /// CPU 核心数
+    pub cores: u32,
+    /// 平均使用率（百分比）
+    pub usage_percent: f32,
+    /// 负载平均值
+    pub load_average: Vec<f32>,
+}
+
+/// 磁盘使用情况
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskUsage {
+    /// 总空间（MB）
+    pub total_mb: u64,
+    /// 已使用空间（MB）
+    pub used_mb: u64,
+    /// 可用空间（MB）
+    pub available_mb: u64,
+    /// 使用率（百分比）
+    pub usage_percent: f32,
+}
+
+/// HTTP 响应
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpResponse {
+    /// 状态码
+    pub status_code: u16,
+    /// 响应头
+    pub headers: HashMap<String, String>,
+    /// 响应体
+    pub body: String,
+    /// 响应时间（毫秒）
+    pub response_time_ms: u64,
+}
+
+/// 插件错误类型
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginErrorType {
+    /// 初始化错误
+    InitializationError,
+    /// 配置错误
+    ConfigurationError,
+    /// 依赖错误
+    DependencyError,
+    /// 权限错误
+    PermissionError,
+    /// 资源限制错误
+    ResourceLimitError,
+    /// 执行错误
+    ExecutionError,
+    /// 通信错误
+    CommunicationError,
+    /// 版本不兼容错误
+    VersionIncompatibilityError,
+}
+
+/// 插件错误
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginError {
+    /// 错误类型
+    pub error_type: PluginErrorType,
+    /// 错误消息
+    pub message: String,
+    /// 错误详情
+    pub details: Option<serde_json::Value>,
+    /// 插件 ID
+    pub plugin_id: String,
+    /// 错误时间
+    pub timestamp: DateTime<Utc>,
+}
+
+impl std::fmt::Display for PluginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Plugin Error [{}]: {} (plugin: {})", 
+               serde_json::to_string(&self.error_type).unwrap_or_default(),
+               self.message, 
+               self.plugin_id)
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_plugin_metadata_serialization() {
+        let metadata = PluginMetadata {
+            id: "test-plugin".to_string(),
+            name: "Test Plugin".to_string(),
+            version: "1.0.0".to_string(),
+            description: "A test plugin".to_string(),
+            author: "Test Author".to_string(),
+            license: "MIT".to_string(),
+            homepage: None,
+            repository: None,
+            plugin_type: PluginType::Tool,
+            api_version: "1.0".to_string(),
+            min_system_version: "1.0.0".to_string(),
+            dependencies: Vec::new(),
+            permissions: vec![PluginPermission::FileSystem],
+            tags: vec!["test".to_string()],
+            icon: None,
+            routes: Vec::new(),
+            event_subscriptions: Vec::new(),
+            created_at: Utc::now(),
+        };
+        
+        let json = serde_json::to_string(&metadata).unwrap();
+        let deserialized: PluginMetadata = serde_json::from_str(&json).unwrap();
+        
+        assert_eq!(metadata.id, deserialized.id);
+        assert_eq!(metadata.plugin_type, deserialized.plugin_type);
+    }
+    
+    #[test]
+    fn test_plugin_status_transitions() {
+        let status = PluginStatus::Uninitialized;
+        assert_eq!(status, PluginStatus::Uninitialized);
+        
+        let status = PluginStatus::Running;
+        assert_eq!(status, PluginStatus::Running);
+    }
+    
+    #[test]
+    fn test_resource_limits_default() {
+        let limits = ResourceLimits::default();
+        assert_eq!(limits.max_memory_mb, Some(512));
+        assert_eq!(limits.max_cpu_percent, Some(50.0));
+    }
+    
+    #[test]
+    fn test_plugin_route_matching() {
+        let route = PluginRoute {
+            method: "GET".to_string(),
+            path: "/reports/{report_id}".to_string(),
+            handler: "get_report".to_string(),
+            required_permissions: Vec::new(),
+            admin_only: false,
+        };
+        
+        let params = route.match_request("get", "/reports/42").unwrap();
+        assert_eq!(params.get("report_id"), Some(&"42".to_string()));
+        assert!(route.match_request("POST", "/reports/42").is_none());
+        assert!(route.match_request("GET", "/reports").is_none());
+        assert!(route.match_request("GET", "/other/42").is_none());
+    }
+    
+    #[test]
+    fn test_plugin_route_validation() {
+        let mut route = PluginRoute {
+            method: "POST".to_string(),
+            path: "/items".to_string(),
+            handler: "create_item".to_string(),
+            required_permissions: Vec::new(),
+            admin_only: false,
+        };
+        assert!(route.validate().is_ok());
+        
+        route.path = "/../admin".to_string();
+        assert!(route.validate().is_err());
+        
+        route.path = "/items".to_string();
+        route.method = "TRACE".to_string();
+        assert!(route.validate().is_err());
+    }
+}
@@ -0,0 +1,180 @@
+// 插件契约测试
+// 检查插件是否满足服务端对元数据、配置与生命周期的约定
+
+use std::collections::HashMap;
+use std::fmt;
+
+use aionix_common::plugin::{PluginStatus, PLUGIN_API_VERSION};
+
+use crate::harness::PluginHarness;
+use crate::plugin::TestablePlugin;
+
+/// 契约测试使用的不存在的方法名
+const UNKNOWN_METHOD: &str = "__aionix_contract_unknown_method__";
+
+/// 单项检查结果
+#[derive(Debug, Clone)]
+pub struct ContractCheck {
+    /// 检查项名称
+    pub name: &'static str,
+    /// 是否通过
+    pub passed: bool,
+    /// 失败原因
+    pub message: Option<String>,
+}
+
+/// 契约测试报告
+#[derive(Debug, Clone)]
+pub struct ContractReport {
+    /// 插件 ID
+    pub plugin_id: String,
+    /// 各项检查结果
+    pub checks: Vec<ContractCheck>,
+}
+
+impl ContractReport {
+    /// 是否全部通过
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    /// 未通过的检查项
+    pub fn failures(&self) -> impl Iterator<Item = &ContractCheck> {
+        self.checks.iter().filter(|check| !check.passed)
+    }
+
+    /// 存在未通过的检查项时 panic，用于测试断言
+    pub fn assert_passed(&self) {
+        assert!(self.passed(), "{}", self);
+    }
+
+    fn check(&mut self, name: &'static str, result: Result<(), String>) -> bool {
+        let passed = result.is_ok();
+        self.checks.push(ContractCheck { name, passed, message: result.err() });
+        passed
+    }
+}
+
+impl fmt::Display for ContractReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed = self.failures().count();
+        writeln!(f, "插件 {} 契约测试: {}/{} 项通过", self.plugin_id, self.checks.len() - failed, self.checks.len())?;
+        for check in self.failures() {
+            writeln!(f, "  ✗ {}: {}", check.name, check.message.as_deref().unwrap_or("未通过"))?;
+        }
+        Ok(())
+    }
+}
+
+/// 对宿主中尚未安装的插件执行完整的契约测试
+///
+/// 依次检查元数据、配置模式、安装与启动、健康检查、未知方法处理、停止与卸载，
+/// 生命周期某一步失败后不再执行后续步骤。
+pub async fn run_contract<P: TestablePlugin>(harness: &mut PluginHarness<P>) -> ContractReport {
+    let metadata = harness.plugin().metadata();
+    let mut report = ContractReport { plugin_id: metadata.id.clone(), checks: Vec::new() };
+
+    report.check("metadata_fields", {
+        let missing: Vec<&str> = [("id", &metadata.id), ("name", &metadata.name), ("version", &metadata.version)]
+            .into_iter()
+            .filter(|(_, value)| value.trim().is_empty())
+            .map(|(field, _)| field)
+            .collect();
+        if missing.is_empty() { Ok(()) } else { Err(format!("元数据字段为空: {}", missing.join(", "))) }
+    });
+
+    report.check("api_version", {
+        let major = |version: &str| version.split('.').next().unwrap_or_default().to_string();
+        if major(&metadata.api_version) == major(PLUGIN_API_VERSION) {
+            Ok(())
+        } else {
+            Err(format!("插件 API 版本 {} 与宿主 {} 不兼容", metadata.api_version, PLUGIN_API_VERSION))
+        }
+    });
+
+    report.check("metadata_verification", harness.verify_metadata().map_err(|e| e.to_string()));
+
+    report.check("initial_status", expect_status(harness.plugin().status(), PluginStatus::Uninitialized));
+
+    report.check("config_schema", {
+        let schema = harness.plugin().config_schema();
+        if schema.is_object() { Ok(()) } else { Err(format!("配置模式必须是 JSON 对象: {}", schema)) }
+    });
+
+    report.check(
+        "validate_config",
+        harness.plugin().validate_config(harness.config()).map_err(|e| format!("默认配置未通过验证: {}", e)),
+    );
+
+    let installed = report.check("install", harness.install().await.map_err(|e| e.to_string()))
+        && report.check("initialized_status", expect_status(harness.plugin().status(), PluginStatus::Initialized));
+    if !installed {
+        return report;
+    }
+
+    let started = report.check("start", harness.start().await.map_err(|e| e.to_string()))
+        && report.check("running_status", expect_status(harness.plugin().status(), PluginStatus::Running));
+    if !started {
+        return report;
+    }
+
+    report.check("health_check", match harness.health_check().await {
+        Ok(health) if health.healthy => Ok(()),
+        Ok(health) => Err(format!("插件报告不健康: {}", health.message)),
+        Err(e) => Err(e.to_string()),
+    });
+
+    report.check("unknown_method", {
+        let context = harness.context();
+        match harness.plugin().handle_call(UNKNOWN_METHOD, HashMap::new(), &context).await {
+            Ok(value) => Err(format!("调用不存在的方法应返回错误，实际返回: {}", value)),
+            Err(_) => Ok(()),
+        }
+    });
+
+    let stopped = report.check("stop", harness.stop().await.map_err(|e| e.to_string()))
+        && report.check("stopped_status", expect_status(harness.plugin().status(), PluginStatus::Stopped));
+    if !stopped {
+        return report;
+    }
+
+    report.check("unload", harness.unload().await.map_err(|e| e.to_string()));
+    report.check("unloaded_status", match harness.plugin().status() {
+        PluginStatus::Running | PluginStatus::Starting => Err("卸载后插件仍处于运行状态".to_string()),
+        _ => Ok(()),
+    });
+
+    report
+}
+
+fn expect_status(actual: PluginStatus, expected: PluginStatus) -> Result<(), String> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!("插件上报状态为 {:?}，宿主期望 {:?}", actual, expected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_plugin::EchoPlugin;
+
+    #[tokio::test]
+    async fn test_contract_passes_for_conforming_plugin() {
+        let mut harness = PluginHarness::new(EchoPlugin::default());
+        let report = run_contract(&mut harness).await;
+        report.assert_passed();
+        assert_eq!(harness.status(), PluginStatus::Unloaded);
+    }
+
+    #[tokio::test]
+    async fn test_contract_reports_failures() {
+        let plugin = EchoPlugin { api_version: "2.0".to_string(), accept_unknown: true, ..Default::default() };
+        let report = run_contract(&mut PluginHarness::new(plugin)).await;
+
+        let failed: Vec<&str> = report.failures().map(|check| check.name).collect();
+        assert_eq!(failed, vec!["api_version", "unknown_method"]);
+        assert!(report.to_string().contains("api_version"));
+    }
+}
@@ -0,0 +1,40 @@
+// 测试工具错误类型定义
+
+use aionix_common::plugin::PluginStatus;
+use aionix_common::CommonError;
+use thiserror::Error;
+
+/// 测试工具操作结果
+pub type TestkitResult<T> = Result<T, TestkitError>;
+
+/// 测试工具错误，与服务端宿主拒绝调用的情形一一对应
+#[derive(Debug, Error)]
+pub enum TestkitError {
+    #[error("插件状态错误: 需要 {expected:?}，当前为 {actual:?}")]
+    InvalidState { expected: PluginStatus, actual: PluginStatus },
+
+    #[error("插件操作超时: {0}")]
+    Timeout(String),
+
+    #[error("权限不足: {0}")]
+    Forbidden(String),
+
+    #[error("未找到匹配的插件路由: {method} {path}")]
+    RouteNotFound { method: String, path: String },
+
+    #[error("验证失败: {0}")]
+    Validation(String),
+
+    #[error("插件返回错误: {0}")]
+    Plugin(String),
+}
+
+impl From<CommonError> for TestkitError {
+    fn from(error: CommonError) -> Self {
+        match error {
+            CommonError::Validation { message } => Self::Validation(message),
+            CommonError::Permission { message } => Self::Forbidden(message),
+            other => Self::Validation(other.to_string()),
+        }
+    }
+}
@@ -0,0 +1,500 @@
+// 进程内插件宿主
+// 按服务端插件管理器的调用顺序、状态约束与权限检查驱动被测插件
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::time::Duration;
+
+use aionix_common::plugin::{
+    PluginConfig, PluginContext, PluginEvent, PluginEventType, PluginHealth, PluginHttpRequest,
+    PluginPermission, PluginStatus, ResourceLimits, SecuritySettings,
+};
+use chrono::Utc;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::error::{TestkitError, TestkitResult};
+use crate::plugin::TestablePlugin;
+
+/// 宿主配置，默认值与服务端插件管理器一致
+#[derive(Debug, Clone)]
+pub struct HostConfig {
+    /// 初始化超时时间
+    pub initialization_timeout: Duration,
+    /// 启动超时时间
+    pub startup_timeout: Duration,
+    /// 停止超时时间
+    pub shutdown_timeout: Duration,
+    /// 允许插件申请的权限
+    pub allowed_permissions: Vec<PluginPermission>,
+}
+
+impl Default for HostConfig {
+    fn default() -> Self {
+        Self {
+            initialization_timeout: Duration::from_secs(30),
+            startup_timeout: Duration::from_secs(60),
+            shutdown_timeout: Duration::from_secs(30),
+            allowed_permissions: vec![
+                PluginPermission::FileSystem,
+                PluginPermission::Network,
+                PluginPermission::UserData,
+                PluginPermission::HttpRoutes,
+            ],
+        }
+    }
+}
+
+/// 模拟的调用用户，用于检查插件路由的权限声明
+#[derive(Debug, Clone)]
+pub struct SimulatedUser {
+    /// 用户 ID
+    pub user_id: Uuid,
+    /// 是否为管理员
+    pub is_admin: bool,
+    /// 用户权限
+    pub permissions: Vec<String>,
+}
+
+impl SimulatedUser {
+    /// 无任何权限的普通用户
+    pub fn new() -> Self {
+        Self { user_id: Uuid::new_v4(), is_admin: false, permissions: Vec::new() }
+    }
+
+    /// 管理员用户
+    pub fn admin() -> Self {
+        Self { is_admin: true, ..Self::new() }
+    }
+
+    /// 追加权限
+    pub fn with_permission(mut self, permission: impl Into<String>) -> Self {
+        self.permissions.push(permission.into());
+        self
+    }
+
+    /// 是否具备全部权限，管理员视为具备所有权限
+    pub fn has_all_permissions(&self, permissions: &[String]) -> bool {
+        self.is_admin || permissions.iter().all(|perm| self.permissions.contains(perm))
+    }
+}
+
+impl Default for SimulatedUser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 插件测试宿主
+///
+/// 宿主自行维护插件状态，与服务端一样不依赖插件上报的状态做决策；
+/// 插件调用失败时状态置为 `Error`，但不会自动重启。
+pub struct PluginHarness<P: TestablePlugin> {
+    plugin: P,
+    host: HostConfig,
+    config: PluginConfig,
+    tenant_id: Uuid,
+    tenant_config: HashMap<String, Value>,
+    user: SimulatedUser,
+    status: PluginStatus,
+    events: Vec<PluginEvent>,
+}
+
+impl<P: TestablePlugin> PluginHarness<P> {
+    /// 以默认宿主配置和空的插件配置创建宿主
+    pub fn new(plugin: P) -> Self {
+        let config = PluginConfig {
+            plugin_id: plugin.metadata().id,
+            parameters: HashMap::new(),
+            environment: HashMap::new(),
+            resource_limits: ResourceLimits::default(),
+            security_settings: SecuritySettings::default(),
+        };
+
+        Self {
+            plugin,
+            host: HostConfig::default(),
+            config,
+            tenant_id: Uuid::new_v4(),
+            tenant_config: HashMap::new(),
+            user: SimulatedUser::new(),
+            status: PluginStatus::Uninitialized,
+            events: Vec::new(),
+        }
+    }
+
+    /// 设置宿主配置
+    pub fn with_host_config(mut self, host: HostConfig) -> Self {
+        self.host = host;
+        self
+    }
+
+    /// 设置安装配置参数
+    pub fn with_parameter(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.config.parameters.insert(key.into(), value);
+        self
+    }
+
+    /// 设置环境变量
+    pub fn with_environment(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.environment.insert(key.into(), value.into());
+        self
+    }
+
+    /// 设置资源限制
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.config.resource_limits = limits;
+        self
+    }
+
+    /// 设置安全设置
+    pub fn with_security_settings(mut self, settings: SecuritySettings) -> Self {
+        self.config.security_settings = settings;
+        self
+    }
+
+    /// 设置调用方租户，空 UUID 表示系统调用，不合并租户配置
+    pub fn with_tenant(mut self, tenant_id: Uuid) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    /// 设置租户级配置，调用时覆盖同名安装配置参数
+    pub fn with_tenant_config(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.tenant_config.insert(key.into(), value);
+        self
+    }
+
+    /// 设置调用用户
+    pub fn with_user(mut self, user: SimulatedUser) -> Self {
+        self.user = user;
+        self
+    }
+
+    /// 被测插件
+    pub fn plugin(&self) -> &P {
+        &self.plugin
+    }
+
+    /// 取回被测插件
+    pub fn into_inner(self) -> P {
+        self.plugin
+    }
+
+    /// 宿主记录的插件状态
+    pub fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    /// 安装配置
+    pub fn config(&self) -> &PluginConfig {
+        &self.config
+    }
+
+    /// 宿主记录的插件事件
+    pub fn events(&self) -> &[PluginEvent] {
+        &self.events
+    }
+
+    /// 按服务端安装插件时的规则验证元数据
+    pub fn verify_metadata(&self) -> TestkitResult<()> {
+        let metadata = self.plugin.metadata();
+
+        for permission in &metadata.permissions {
+            if !self.host.allowed_permissions.contains(permission) {
+                return Err(TestkitError::Forbidden(format!("插件请求的权限未被允许: {:?}", permission)));
+            }
+        }
+
+        if !metadata.routes.is_empty() {
+            if !metadata.permissions.contains(&PluginPermission::HttpRoutes) {
+                return Err(TestkitError::Forbidden("插件注册 HTTP 路由需要声明 http_routes 权限".to_string()));
+            }
+
+            let mut seen = HashSet::new();
+            for route in &metadata.routes {
+                route.validate()?;
+                if !seen.insert((route.method.to_uppercase(), route.path.clone())) {
+                    return Err(TestkitError::Validation(format!("插件路由重复: {} {}", route.method, route.path)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 安装插件：验证元数据后初始化
+    pub async fn install(&mut self) -> TestkitResult<()> {
+        self.verify_metadata()?;
+        self.record(PluginEventType::Loaded, Value::Null);
+        self.initialize().await
+    }
+
+    /// 安装并启动插件
+    pub async fn install_and_start(&mut self) -> TestkitResult<()> {
+        self.install().await?;
+        self.start().await
+    }
+
+    /// 初始化插件
+    pub async fn initialize(&mut self) -> TestkitResult<()> {
+        self.status = PluginStatus::Initializing;
+        let timeout = self.host.initialization_timeout;
+        let result = with_timeout(timeout, "插件初始化超时", self.plugin.initialize(self.config.clone())).await;
+        self.finish(result, PluginStatus::Initialized, PluginEventType::Initialized)
+    }
+
+    /// 启动插件，插件必须已初始化
+    pub async fn start(&mut self) -> TestkitResult<()> {
+        self.expect_status(PluginStatus::Initialized)?;
+        self.status = PluginStatus::Starting;
+        let timeout = self.host.startup_timeout;
+        let result = with_timeout(timeout, "插件启动超时", self.plugin.start()).await;
+        self.finish(result, PluginStatus::Running, PluginEventType::Started)
+    }
+
+    /// 停止插件
+    pub async fn stop(&mut self) -> TestkitResult<()> {
+        self.status = PluginStatus::Stopping;
+        let timeout = self.host.shutdown_timeout;
+        let result = with_timeout(timeout, "插件停止超时", self.plugin.stop()).await;
+        self.finish(result, PluginStatus::Stopped, PluginEventType::Stopped)
+    }
+
+    /// 卸载插件，运行中的插件先停止
+    pub async fn unload(&mut self) -> TestkitResult<()> {
+        if self.status == PluginStatus::Running {
+            self.stop().await?;
+        }
+
+        self.status = PluginStatus::Unloading;
+        let result = self.plugin.shutdown().await.map_err(plugin_error);
+        self.finish(result, PluginStatus::Unloaded, PluginEventType::Unloaded)
+    }
+
+    /// 执行健康检查，不健康时状态置为 `Error`
+    pub async fn health_check(&mut self) -> TestkitResult<PluginHealth> {
+        match self.plugin.health_check().await {
+            Ok(health) => {
+                if !health.healthy {
+                    self.fail(&health.message);
+                }
+                self.record(PluginEventType::HealthCheck, serde_json::to_value(&health).unwrap_or_default());
+                Ok(health)
+            }
+            Err(e) => {
+                let error = plugin_error(e);
+                self.fail(&error.to_string());
+                Err(error)
+            }
+        }
+    }
+
+    /// 以当前租户和用户构造调用上下文
+    pub fn context(&self) -> PluginContext {
+        PluginContext {
+            tenant_id: self.tenant_id,
+            user_id: Some(self.user.user_id),
+            session_id: None,
+            request_id: Uuid::new_v4(),
+            variables: HashMap::new(),
+            plugin_config: self.resolve_plugin_config(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// 调用插件方法
+    pub async fn call(&mut self, method: &str, params: HashMap<String, Value>) -> TestkitResult<Value> {
+        let context = self.context();
+        self.call_with_context(method, params, context).await
+    }
+
+    /// 以指定上下文调用插件方法，`plugin_config` 与服务端一样由宿主重新填充
+    pub async fn call_with_context(
+        &mut self,
+        method: &str,
+        params: HashMap<String, Value>,
+        mut context: PluginContext,
+    ) -> TestkitResult<Value> {
+        self.expect_status(PluginStatus::Running)?;
+        context.plugin_config = self.resolve_plugin_config();
+
+        let call = self.plugin.handle_call(method, params, &context);
+        let result = match self.config.resource_limits.max_execution_seconds {
+            Some(seconds) => {
+                let message = format!("插件 {} 调用 {}", self.config.plugin_id, method);
+                with_timeout(Duration::from_secs(seconds), &message, call).await
+            }
+            None => call.await.map_err(plugin_error),
+        };
+
+        match result {
+            Ok(value) => {
+                self.record(PluginEventType::Called, serde_json::json!({ "method": method }));
+                Ok(value)
+            }
+            Err(e) => {
+                self.fail(&e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    /// 模拟 `/api/v1/ext/{plugin_id}/...` 请求，按插件声明的路由分发并检查调用用户权限
+    pub async fn request(
+        &mut self,
+        method: &str,
+        path: &str,
+        query: HashMap<String, String>,
+        body: Option<Value>,
+    ) -> TestkitResult<Value> {
+        let method = method.to_uppercase();
+        let metadata = self.plugin.metadata();
+        let routes = if metadata.permissions.contains(&PluginPermission::HttpRoutes) {
+            metadata.routes
+        } else {
+            Vec::new()
+        };
+
+        let (route, path_params) = routes
+            .into_iter()
+            .find_map(|route| route.match_request(&method, path).map(|params| (route, params)))
+            .ok_or_else(|| TestkitError::RouteNotFound { method: method.clone(), path: path.to_string() })?;
+
+        if route.admin_only && !self.user.is_admin {
+            return Err(TestkitError::Forbidden("该插件路由仅允许管理员调用".to_string()));
+        }
+        if !self.user.has_all_permissions(&route.required_permissions) {
+            return Err(TestkitError::Forbidden("缺少调用该插件路由所需的权限".to_string()));
+        }
+
+        let request = PluginHttpRequest {
+            method,
+            path: path.to_string(),
+            path_params,
+            query,
+            headers: HashMap::new(),
+            body,
+        };
+        let mut params = HashMap::new();
+        params.insert("request".to_string(), serde_json::to_value(&request).unwrap_or_default());
+
+        self.call(&route.handler, params).await
+    }
+
+    /// 合并安装配置与租户配置
+    fn resolve_plugin_config(&self) -> HashMap<String, Value> {
+        let mut merged = self.config.parameters.clone();
+        if !self.tenant_id.is_nil() {
+            merged.extend(self.tenant_config.clone());
+        }
+        merged
+    }
+
+    fn expect_status(&self, expected: PluginStatus) -> TestkitResult<()> {
+        if self.status != expected {
+            return Err(TestkitError::InvalidState { expected, actual: self.status.clone() });
+        }
+        Ok(())
+    }
+
+    fn finish(
+        &mut self,
+        result: TestkitResult<()>,
+        status: PluginStatus,
+        event_type: PluginEventType,
+    ) -> TestkitResult<()> {
+        match result {
+            Ok(()) => {
+                self.status = status;
+                self.record(event_type, Value::Null);
+                Ok(())
+            }
+            Err(e) => {
+                self.fail(&e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    fn fail(&mut self, message: &str) {
+        self.status = PluginStatus::Error;
+        self.record(PluginEventType::Error, serde_json::json!({ "message": message }));
+    }
+
+    fn record(&mut self, event_type: PluginEventType, data: Value) {
+        self.events.push(PluginEvent {
+            event_id: Uuid::new_v4(),
+            plugin_id: self.config.plugin_id.clone(),
+            event_type,
+            data,
+            timestamp: Utc::now(),
+        });
+    }
+}
+
+/// 插件错误转换为测试工具错误
+fn plugin_error(error: impl std::fmt::Display) -> TestkitError {
+    TestkitError::Plugin(error.to_string())
+}
+
+/// 带超时执行插件操作
+async fn with_timeout<T, E: std::fmt::Display>(
+    timeout: Duration,
+    message: &str,
+    future: impl Future<Output = Result<T, E>>,
+) -> TestkitResult<T> {
+    match tokio::time::timeout(timeout, future).await {
+        Ok(result) => result.map_err(plugin_error),
+        Err(_) => Err(TestkitError::Timeout(message.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_plugin::EchoPlugin;
+
+    #[tokio::test]
+    async fn test_lifecycle_and_calls() {
+        let mut harness = PluginHarness::new(EchoPlugin::default())
+            .with_parameter("greeting", serde_json::json!("hello"))
+            .with_tenant_config("greeting", serde_json::json!("hi"));
+
+        let error = harness.start().await.unwrap_err();
+        assert!(matches!(error, TestkitError::InvalidState { expected: PluginStatus::Initialized, .. }));
+
+        harness.install_and_start().await.unwrap();
+        assert_eq!(harness.status(), PluginStatus::Running);
+
+        let result = harness.call("config", HashMap::new()).await.unwrap();
+        assert_eq!(result["greeting"], "hi");
+
+        assert!(harness.call("missing", HashMap::new()).await.is_err());
+        assert_eq!(harness.status(), PluginStatus::Error);
+        assert!(harness.events().iter().any(|e| e.event_type == PluginEventType::Error));
+    }
+
+    #[tokio::test]
+    async fn test_route_permissions() {
+        let mut harness = PluginHarness::new(EchoPlugin::default());
+        harness.install_and_start().await.unwrap();
+
+        let error = harness.request("GET", "/items/42", HashMap::new(), None).await.unwrap_err();
+        assert!(matches!(error, TestkitError::Forbidden(_)));
+
+        let mut harness = harness.with_user(SimulatedUser::new().with_permission("items:read"));
+        let result = harness.request("get", "/items/42", HashMap::new(), None).await.unwrap();
+        assert_eq!(result["request"]["path_params"]["id"], "42");
+
+        let error = harness.request("DELETE", "/items/42", HashMap::new(), None).await.unwrap_err();
+        assert!(matches!(error, TestkitError::RouteNotFound { .. }));
+    }
+
+    #[test]
+    fn test_verify_metadata_rejects_disallowed_permission() {
+        let host = HostConfig { allowed_permissions: Vec::new(), ..Default::default() };
+        let harness = PluginHarness::new(EchoPlugin::default()).with_host_config(host);
+        assert!(matches!(harness.verify_metadata(), Err(TestkitError::Forbidden(_))));
+    }
+}
@@ -0,0 +1,16 @@
+// Aionix Plugin Testkit
+// 插件作者的契约测试工具：在进程内模拟服务端宿主，按服务端的约定驱动插件
+
+pub mod contract;
+pub mod error;
+pub mod harness;
+pub mod plugin;
+
+#[cfg(test)]
+mod test_plugin;
+
+pub use aionix_common::plugin::*;
+pub use contract::{run_contract, ContractCheck, ContractReport};
+pub use error::{TestkitError, TestkitResult};
+pub use harness::{HostConfig, PluginHarness, SimulatedUser};
+pub use plugin::TestablePlugin;
@@ -0,0 +1,53 @@
+// 被测插件接口
+// 与服务端 `plugins::Plugin` 的方法签名逐一对应，错误类型由插件自行定义
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use aionix_common::plugin::{PluginConfig, PluginContext, PluginHealth, PluginMetadata, PluginStatus};
+use async_trait::async_trait;
+
+/// 被测插件
+///
+/// 外部插件无需依赖服务端的错误类型，只需将自身的 `Plugin` 实现转发到此接口，
+/// 测试工具即可按服务端的调用顺序和约束驱动插件。
+#[async_trait]
+pub trait TestablePlugin: Send + Sync {
+    /// 插件返回的错误类型
+    type Error: Display + Send + Sync;
+
+    /// 获取插件元数据
+    fn metadata(&self) -> PluginMetadata;
+
+    /// 初始化插件
+    async fn initialize(&mut self, config: PluginConfig) -> Result<(), Self::Error>;
+
+    /// 启动插件
+    async fn start(&mut self) -> Result<(), Self::Error>;
+
+    /// 停止插件
+    async fn stop(&mut self) -> Result<(), Self::Error>;
+
+    /// 卸载插件
+    async fn shutdown(&mut self) -> Result<(), Self::Error>;
+
+    /// 获取插件状态
+    fn status(&self) -> PluginStatus;
+
+    /// 处理插件调用
+    async fn handle_call(
+        &self,
+        method: &str,
+        params: HashMap<String, serde_json::Value>,
+        context: &PluginContext,
+    ) -> Result<serde_json::Value, Self::Error>;
+
+    /// 获取插件健康状态
+    async fn health_check(&self) -> Result<PluginHealth, Self::Error>;
+
+    /// 获取插件配置模式
+    fn config_schema(&self) -> serde_json::Value;
+
+    /// 验证配置
+    fn validate_config(&self, config: &PluginConfig) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,120 @@
+// 测试用插件
+
+use std::collections::HashMap;
+
+use aionix_common::plugin::{
+    PluginConfig, PluginContext, PluginHealth, PluginMetadata, PluginPermission, PluginRoute, PluginStatus,
+    PluginType, PLUGIN_API_VERSION,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::plugin::TestablePlugin;
+
+/// 回显插件，`echo` 返回参数，`config` 返回生效的插件配置
+pub(crate) struct EchoPlugin {
+    pub api_version: String,
+    pub accept_unknown: bool,
+    pub status: PluginStatus,
+}
+
+impl Default for EchoPlugin {
+    fn default() -> Self {
+        Self {
+            api_version: PLUGIN_API_VERSION.to_string(),
+            accept_unknown: false,
+            status: PluginStatus::Uninitialized,
+        }
+    }
+}
+
+#[async_trait]
+impl TestablePlugin for EchoPlugin {
+    type Error = String;
+
+    fn metadata(&self) -> PluginMetadata {
+        PluginMetadata {
+            id: "echo".to_string(),
+            name: "回显插件".to_string(),
+            version: "1.0.0".to_string(),
+            description: "测试用插件".to_string(),
+            author: "Aionix Team".to_string(),
+            license: "MIT".to_string(),
+            homepage: None,
+            repository: None,
+            plugin_type: PluginType::Tool,
+            api_version: self.api_version.clone(),
+            min_system_version: "1.0.0".to_string(),
+            dependencies: Vec::new(),
+            permissions: vec![PluginPermission::HttpRoutes],
+            tags: Vec::new(),
+            icon: None,
+            routes: vec![PluginRoute {
+                method: "GET".to_string(),
+                path: "/items/{id}".to_string(),
+                handler: "echo".to_string(),
+                required_permissions: vec!["items:read".to_string()],
+                admin_only: false,
+            }],
+            event_subscriptions: Vec::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    async fn initialize(&mut self, _config: PluginConfig) -> Result<(), String> {
+        self.status = PluginStatus::Initialized;
+        Ok(())
+    }
+
+    async fn start(&mut self) -> Result<(), String> {
+        self.status = PluginStatus::Running;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<(), String> {
+        self.status = PluginStatus::Stopped;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), String> {
+        self.status = PluginStatus::Unloaded;
+        Ok(())
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn handle_call(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        context: &PluginContext,
+    ) -> Result<Value, String> {
+        match method {
+            "echo" => Ok(serde_json::to_value(params).unwrap_or_default()),
+            "config" => Ok(serde_json::to_value(&context.plugin_config).unwrap_or_default()),
+            _ if self.accept_unknown => Ok(Value::Null),
+            _ => Err(format!("未知方法: {}", method)),
+        }
+    }
+
+    async fn health_check(&self) -> Result<PluginHealth, String> {
+        Ok(PluginHealth {
+            healthy: true,
+            message: "ok".to_string(),
+            details: HashMap::new(),
+            checked_at: Utc::now(),
+            response_time_ms: 0,
+        })
+    }
+
+    fn config_schema(&self) -> Value {
+        serde_json::json!({ "type": "object", "properties": { "greeting": { "type": "string" } } })
+    }
+
+    fn validate_config(&self, _config: &PluginConfig) -> Result<(), String> {
+        Ok(())
+    }
+}
@@ -12,6 +12,26 @@ use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait};
 
 use crate::errors::AiStudioError;
 use crate::ai::rig_client::RigAiClient;
+use crate::ai::agent_stream::{self, AgentStreamEvent, ToolProgressSink, truncate_for_trace};
+use crate::ai::tool_output::{check_tool_output_schema, enforce_output_schema};
+use crate::ai::tools::scratch::ScratchWorkspaces;
+use crate::ai::tools::search_tool::{KNOWLEDGE_BASE_BINDINGS_VAR, WEB_SEARCH_POLICY_VAR};
+use crate::ai::workflow_engine::{KnowledgeSearchFilters, KnowledgeSearchMode};
+use crate::db::entities::execution_event::ExecutionType;
+use crate::db::entities::knowledge_base;
+use crate::db::entities::prelude::KnowledgeBase;
+use crate::services::agent_memory::{AgentMemoryService, ExecutionMemory, MemoryRetrieval};
+use crate::services::deadline;
+use crate::services::execution_event::{ExecutionEventService, ExecutionTransition};
+use crate::services::prompt_retention::{record_model_call, ModelCall, ModelCallSource};
+use crate::services::few_shot::{format_examples_for_prompt, FewShotExampleResponse, FewShotExampleService, FewShotTarget};
+use crate::services::user_preferences::format_preferences_for_prompt;
+use crate::services::tenant_persona::{format_persona_for_prompt, load_tenant_persona};
+use crate::services::web_search::WebSearchDomainPolicy;
+use crate::db::entities::user::UserPreferences;
+
+/// 任务参数中携带发起用户偏好设置的键
+pub const RESPONSE_PREFERENCES_PARAM: &str = "response_preferences";
 
 /// Agent 运行时引擎
 pub struct AgentRuntime {
@@ -25,6 +45,10 @@ pub struct AgentRuntime {
     active_agents: Arc<RwLock<HashMap<Uuid, AgentInstance>>>,
     /// 运行时配置
     config: AgentRuntimeConfig,
+    /// 状态转换事件记录
+    events: ExecutionEventService,
+    /// 执行结束时的记忆快照
+    memory_snapshots: AgentMemoryService,
 }
 
 /// Agent 运行时配置
@@ -40,6 +64,8 @@ pub struct AgentRuntimeConfig {
     pub memory_config: MemoryConfig,
     /// 工具调用超时时间（秒）
     pub tool_call_timeout_seconds: u64,
+    /// 推理提示中最多包含的少样本示例数，0 表示不包含
+    pub few_shot_examples: usize,
 }
 
 impl Default for AgentRuntimeConfig {
@@ -50,6 +76,7 @@ impl Default for AgentRuntimeConfig {
             max_concurrent_agents: 100,
             memory_config: MemoryConfig::default(),
             tool_call_timeout_seconds: 30,
+            few_shot_examples: 3,
         }
     }
 }
@@ -91,6 +118,8 @@ pub struct AgentInstance {
     pub memory: AgentMemory,
     /// 执行上下文
     pub execution_context: ExecutionContext,
+    /// 当前任务各推理步骤的记忆检索记录
+    pub memory_retrievals: Vec<MemoryRetrieval>,
     /// 创建时间
     pub created_at: DateTime<Utc>,
     /// 最后活跃时间
@@ -114,12 +143,51 @@ pub struct AgentConfig {
     pub temperature: f32,
     /// 最大令牌数
     pub max_tokens: u32,
+    /// 使用的模型，未指定时使用默认模型
+    #[serde(default)]
+    pub model: Option<String>,
     /// 租户 ID
     pub tenant_id: Uuid,
     /// 创建者 ID
     pub created_by: Uuid,
+    /// 绑定的知识库，搜索工具只在这些知识库中检索
+    #[serde(default)]
+    pub knowledge_bases: Vec<KnowledgeBaseBinding>,
+    /// 联网搜索的域名策略
+    #[serde(default)]
+    pub web_search: WebSearchDomainPolicy,
+}
+
+/// Agent 的知识库绑定
+///
+/// 每个绑定各自限定返回的文档块数量、检索方式与过滤条件，未绑定的知识库对 Agent 不可见。
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct KnowledgeBaseBinding {
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 该知识库最多返回的文档块数量
+    #[serde(default = "default_binding_top_k")]
+    pub top_k: u32,
+    /// 检索方式：vector、hybrid 或 sparse
+    #[serde(default)]
+    #[schema(value_type = String)]
+    pub mode: KnowledgeSearchMode,
+    /// 得分阈值
+    #[serde(default)]
+    pub similarity_threshold: Option<f32>,
+    /// 过滤条件：限定的文档 ID、文档类型与文档块元数据
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub filters: KnowledgeSearchFilters,
+}
+
+fn default_binding_top_k() -> u32 {
+    5
 }
 
+/// 单个知识库绑定最多返回的文档块数量
+const MAX_BINDING_TOP_K: u32 = 50;
+
 /// 推理策略
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -156,6 +224,22 @@ pub enum AgentState {
     Stopped,
 }
 
+impl AgentState {
+    /// 状态名称，与序列化结果一致
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AgentState::Idle => "idle",
+            AgentState::Thinking => "thinking",
+            AgentState::ExecutingTool => "executing_tool",
+            AgentState::WaitingForInput => "waiting_for_input",
+            AgentState::Completed => "completed",
+            AgentState::Error => "error",
+            AgentState::Paused => "paused",
+            AgentState::Stopped => "stopped",
+        }
+    }
+}
+
 /// Agent 内存系统
 #[derive(Debug, Clone)]
 pub struct AgentMemory {
@@ -170,7 +254,7 @@ pub struct AgentMemory {
 }
 
 /// 记忆项
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct MemoryItem {
     /// 记忆 ID
     pub id: Uuid,
@@ -191,7 +275,7 @@ pub struct MemoryItem {
 }
 
 /// 记忆类型
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum MemoryType {
     /// 对话记录
@@ -221,6 +305,9 @@ pub struct ExecutionContext {
     pub session_id: Option<Uuid>,
     /// 用户 ID
     pub user_id: Option<Uuid>,
+    /// 当前工具调用的进度上报句柄，仅在工具执行期间存在
+    #[serde(skip)]
+    pub tool_progress: Option<ToolProgressSink>,
 }
 
 /// Agent 任务
@@ -368,6 +455,8 @@ pub struct ToolMetadata {
     pub description: String,
     /// 参数模式
     pub parameters_schema: serde_json::Value,
+    /// 输出模式（声明后运行时按此校验并在安全时转换工具结果）
+    pub output_schema: Option<serde_json::Value>,
     /// 工具类别
     pub category: String,
     /// 是否需要权限
@@ -406,6 +495,7 @@ pub enum ToolEnum {
     CalculatorTool(crate::ai::tools::calculator_tool::CalculatorTool),
     FileTool(crate::ai::tools::file_tool::FileTool),
     HttpTool(crate::ai::tools::http_tool::HttpTool),
+    DatasetQueryTool(crate::ai::tools::dataset_tool::DatasetQueryTool),
 }
 
 impl ToolEnum {
@@ -420,6 +510,7 @@ impl ToolEnum {
             ToolEnum::CalculatorTool(tool) => tool.execute(parameters, context).await,
             ToolEnum::FileTool(tool) => tool.execute(parameters, context).await,
             ToolEnum::HttpTool(tool) => tool.execute(parameters, context).await,
+            ToolEnum::DatasetQueryTool(tool) => tool.execute(parameters, context).await,
         }
     }
     
@@ -430,6 +521,7 @@ impl ToolEnum {
             ToolEnum::CalculatorTool(tool) => tool.metadata(),
             ToolEnum::FileTool(tool) => tool.metadata(),
             ToolEnum::HttpTool(tool) => tool.metadata(),
+            ToolEnum::DatasetQueryTool(tool) => tool.metadata(),
         }
     }
     
@@ -443,6 +535,7 @@ impl ToolEnum {
             ToolEnum::CalculatorTool(tool) => tool.validate_parameters(parameters),
             ToolEnum::FileTool(tool) => tool.validate_parameters(parameters),
             ToolEnum::HttpTool(tool) => tool.validate_parameters(parameters),
+            ToolEnum::DatasetQueryTool(tool) => tool.validate_parameters(parameters),
         }
     }
 }
@@ -469,22 +562,81 @@ impl AgentRuntime {
         rig_client: Arc<RigAiClient>,
         config: Option<AgentRuntimeConfig>,
     ) -> Self {
+        // 依赖数据库的数据工具随运行时一起注册
+        let mut tool_registry = ToolRegistry::default();
+        for tool in crate::ai::tools::ToolFactory::create_data_tools(db.as_ref().clone(), rig_client.clone()) {
+            let metadata = tool.metadata();
+            tool_registry.tools.insert(metadata.name.clone(), tool);
+            tool_registry.tool_metadata.insert(metadata.name.clone(), metadata);
+        }
+        
         Self {
+            events: ExecutionEventService::new(db.as_ref().clone()),
+            memory_snapshots: AgentMemoryService::new(db.as_ref().clone()),
             db,
             rig_client,
-            tool_registry: Arc::new(RwLock::new(ToolRegistry::default())),
+            tool_registry: Arc::new(RwLock::new(tool_registry)),
             active_agents: Arc::new(RwLock::new(HashMap::new())),
             config: config.unwrap_or_default(),
         }
     }
     
+    /// 校验知识库绑定：知识库须属于 Agent 所在租户，且不能重复绑定
+    async fn validate_knowledge_base_bindings(
+        &self,
+        tenant_id: Uuid,
+        bindings: &[KnowledgeBaseBinding],
+    ) -> Result<(), AiStudioError> {
+        let mut ids: Vec<Uuid> = Vec::with_capacity(bindings.len());
+        for binding in bindings {
+            if binding.top_k == 0 || binding.top_k > MAX_BINDING_TOP_K {
+                return Err(AiStudioError::validation(
+                    "knowledge_bases.top_k",
+                    format!("返回数量必须在 1-{} 之间", MAX_BINDING_TOP_K),
+                ));
+            }
+            if ids.contains(&binding.knowledge_base_id) {
+                return Err(AiStudioError::validation(
+                    "knowledge_bases",
+                    format!("知识库 {} 重复绑定", binding.knowledge_base_id),
+                ));
+            }
+            ids.push(binding.knowledge_base_id);
+        }
+        if ids.is_empty() {
+            return Ok(());
+        }
+        
+        let found: Vec<Uuid> = KnowledgeBase::find()
+            .filter(knowledge_base::Column::TenantId.eq(tenant_id))
+            .filter(knowledge_base::Column::Id.is_in(ids.clone()))
+            .all(self.db.as_ref())
+            .await?
+            .into_iter()
+            .map(|kb| kb.id)
+            .collect();
+        if let Some(missing) = ids.iter().find(|id| !found.contains(id)) {
+            return Err(AiStudioError::not_found(format!("知识库 {}", missing)));
+        }
+        Ok(())
+    }
+    
     /// 创建 Agent 实例
     pub async fn create_agent(
         &self,
-        config: AgentConfig,
+        mut config: AgentConfig,
     ) -> Result<Uuid, AiStudioError> {
         let agent_id = Uuid::new_v4();
         let now = Utc::now();
+        self.validate_knowledge_base_bindings(config.tenant_id, &config.knowledge_bases).await?;
+        config.web_search = config.web_search.normalized()?;
+        
+        // 数据类工具按租户隔离，租户 ID 与知识库绑定经执行上下文传入；执行 ID 用于隔离临时工作区与 HTTP 工具的 Cookie
+        let mut context_variables = HashMap::new();
+        context_variables.insert("tenant_id".to_string(), serde_json::Value::String(config.tenant_id.to_string()));
+        context_variables.insert("execution_id".to_string(), serde_json::Value::String(agent_id.to_string()));
+        context_variables.insert(KNOWLEDGE_BASE_BINDINGS_VAR.to_string(), serde_json::to_value(&config.knowledge_bases)?);
+        context_variables.insert(WEB_SEARCH_POLICY_VAR.to_string(), serde_json::to_value(&config.web_search)?);
         
         let agent_instance = AgentInstance {
             agent_id,
@@ -499,10 +651,12 @@ impl AgentRuntime {
             execution_context: ExecutionContext {
                 current_task: None,
                 execution_history: Vec::new(),
-                context_variables: HashMap::new(),
+                context_variables,
                 session_id: None,
                 user_id: None,
+                tool_progress: None,
             },
+            memory_retrievals: Vec::new(),
             created_at: now,
             last_active_at: now,
         };
@@ -515,12 +669,23 @@ impl AgentRuntime {
             }
         }
         
+        let tenant_id = agent_instance.config.tenant_id;
+        
         // 添加到活跃 Agent 列表
         {
             let mut active_agents = self.active_agents.write().await;
             active_agents.insert(agent_id, agent_instance);
         }
         
+        self.events.record_or_warn(ExecutionTransition {
+            tenant_id,
+            execution_type: ExecutionType::Agent,
+            execution_id: agent_id,
+            from_state: None,
+            to_state: AgentState::Idle.as_str().to_string(),
+            payload: serde_json::json!({}),
+        }).await;
+        
         info!("创建 Agent 实例: agent_id={}", agent_id);
         Ok(agent_id)
     }
@@ -543,13 +708,28 @@ impl AgentRuntime {
         
         // 设置当前任务
         agent.execution_context.current_task = Some(task.clone());
-        agent.state = AgentState::Thinking;
+        agent.memory_retrievals.clear();
+        self.transition(&mut agent, AgentState::Thinking, serde_json::json!({ "task_id": task.task_id })).await;
         
-        // 执行推理循环
-        let result = self.reasoning_loop(&mut agent).await?;
+        // 执行推理循环，失败时保存错误状态
+        let result = match self.reasoning_loop(&mut agent).await {
+            Ok(result) => result,
+            Err(e) => {
+                self.transition(
+                    &mut agent,
+                    AgentState::Error,
+                    serde_json::json!({ "task_id": task.task_id, "error": e.to_string() }),
+                ).await;
+                self.save_memory_snapshot(&mut agent, task.task_id).await;
+                agent.last_active_at = Utc::now();
+                self.active_agents.write().await.insert(agent_id, agent);
+                return Err(e);
+            }
+        };
         
         // 更新 Agent 状态
-        agent.state = AgentState::Completed;
+        self.transition(&mut agent, AgentState::Completed, serde_json::json!({ "task_id": task.task_id })).await;
+        self.save_memory_snapshot(&mut agent, task.task_id).await;
         agent.last_active_at = Utc::now();
         
         // 保存 Agent 状态
@@ -584,15 +764,52 @@ impl AgentRuntime {
                 break;
             }
             
+            // 请求截止时间已到时停止推理，返回已有的部分结果
+            if deadline::check("agent.reasoning").is_err() {
+                warn!("请求截止时间已到，停止推理: agent_id={}", agent.agent_id);
+                break;
+            }
+            
             step_count += 1;
             
+            // 检索相关记忆并记录本步骤用到了哪些记忆
+            let relevant_memories = self.retrieve_relevant_memories(agent, 5).await;
+            agent.memory_retrievals.push(MemoryRetrieval::new(step_count, &relevant_memories));
+            mark_memories_accessed(&mut agent.memory, &relevant_memories);
+            
             // 执行推理步骤
-            let reasoning_result = self.perform_reasoning_step(agent).await?;
+            let reasoning_result = self.perform_reasoning_step(agent, &relevant_memories).await?;
             
             // 处理下一步行动
             match reasoning_result.next_action {
                 NextAction::ToolCall { tool_name, parameters } => {
-                    let tool_result = self.execute_tool(&tool_name, parameters, &agent.execution_context).await?;
+                    let started_at = Utc::now();
+                    let input = serde_json::to_value(&parameters).unwrap_or_default();
+                    self.transition(agent, AgentState::ExecutingTool, serde_json::json!({ "tool_name": tool_name })).await;
+                    // 工具执行期间的部分输出实时推送给订阅者
+                    let progress = ToolProgressSink::new(agent.agent_id, &tool_name);
+                    agent.execution_context.tool_progress = Some(progress.clone());
+                    let tool_result = self.execute_tool(&tool_name, parameters, &agent.execution_context).await;
+                    agent.execution_context.tool_progress = None;
+                    let tool_result = tool_result?;
+                    
+                    // 执行轨迹只保留有限大小的输出，超出部分以截断标记代替
+                    let (trace_output, output_bytes, truncated) = truncate_for_trace(&tool_result.data);
+                    progress.complete(tool_result.success, output_bytes, truncated);
+                    self.transition(agent, AgentState::Thinking, serde_json::json!({ "tool_name": tool_name })).await;
+                    
+                    // 记录工具调用步骤，输出不符合声明模式时步骤标记为失败
+                    agent.execution_context.execution_history.push(ExecutionStep {
+                        step_id: progress.call_id(),
+                        step_type: StepType::ToolCall,
+                        description: format!("调用工具 {}", tool_name),
+                        input,
+                        output: tool_result.success.then_some(trace_output),
+                        status: if tool_result.success { StepStatus::Completed } else { StepStatus::Failed },
+                        started_at,
+                        completed_at: Some(Utc::now()),
+                        error: tool_result.error.clone(),
+                    });
                     
                     // 将工具结果添加到记忆
                     self.add_memory_item(
@@ -629,7 +846,7 @@ impl AgentRuntime {
                     return Ok(result);
                 }
                 NextAction::RequestInput { prompt } => {
-                    agent.state = AgentState::WaitingForInput;
+                    self.transition(agent, AgentState::WaitingForInput, serde_json::json!({})).await;
                     return Ok(serde_json::json!({
                         "type": "input_request",
                         "prompt": prompt,
@@ -654,29 +871,48 @@ impl AgentRuntime {
     /// 执行推理步骤
     async fn perform_reasoning_step(
         &self,
-        _agent: &AgentInstance,
+        agent: &AgentInstance,
+        relevant_memories: &[(MemoryItem, f32)],
     ) -> Result<ReasoningResult, AiStudioError> {
-        debug!("执行推理步骤: agent_id={}", _agent.agent_id);
+        debug!("执行推理步骤: agent_id={}", agent.agent_id);
         
         // 构建推理提示
-        let prompt = format!("请分析当前情况并决定下一步行动。Agent ID: {}", _agent.agent_id);
+        let prompt = self.build_reasoning_prompt(agent, relevant_memories).await?;
         
         // 调用 LLM 进行推理
         let response = self.rig_client.generate_text(&prompt).await?;
+        record_model_call(ModelCall {
+            tenant_id: agent.config.tenant_id,
+            source: ModelCallSource::Agent,
+            execution_id: Some(agent.agent_id),
+            reference: agent.execution_context.current_task.as_ref().map(|task| task.task_id.to_string()),
+            model: self.rig_client.model_id(),
+            prompt,
+            completion: response.text.clone(),
+            tokens_used: response.tokens_used,
+        });
         
         // 解析推理结果
-        let reasoning_result = self.parse_reasoning_response(&response.text, _agent).await?;
+        let reasoning_result = self.parse_reasoning_response(&response.text, agent).await?;
         
         debug!("推理步骤完成: agent_id={}, 下一步行动={:?}", 
-               _agent.agent_id, reasoning_result.next_action);
+               agent.agent_id, reasoning_result.next_action);
         
         Ok(reasoning_result)
     }
     
     /// 构建推理提示
-    async fn build_reasoning_prompt(&self, agent: &AgentInstance) -> Result<String, AiStudioError> {
+    async fn build_reasoning_prompt(
+        &self,
+        agent: &AgentInstance,
+        relevant_memories: &[(MemoryItem, f32)],
+    ) -> Result<String, AiStudioError> {
         let mut prompt = String::new();
         
+        // 租户人设
+        let persona = load_tenant_persona(self.db.as_ref(), agent.config.tenant_id).await;
+        prompt.push_str(&format_persona_for_prompt(&persona));
+        
         // 系统提示
         prompt.push_str(&agent.config.system_prompt);
         prompt.push_str("\n\n");
@@ -685,6 +921,13 @@ impl AgentRuntime {
         if let Some(ref task) = agent.execution_context.current_task {
             prompt.push_str(&format!("当前任务: {}\n", task.description));
             prompt.push_str(&format!("任务目标: {}\n\n", task.objective));
+            
+            // 发起用户的回答偏好
+            if let Some(preferences) = task.parameters.get(RESPONSE_PREFERENCES_PARAM)
+                .and_then(|value| serde_json::from_value::<UserPreferences>(value.clone()).ok())
+            {
+                prompt.push_str(&format_preferences_for_prompt(&preferences));
+            }
         }
         
         // 可用工具
@@ -699,15 +942,21 @@ impl AgentRuntime {
         }
         
         // 相关记忆
-        let relevant_memories = self.retrieve_relevant_memories(agent, 5).await;
         if !relevant_memories.is_empty() {
             prompt.push_str("相关记忆:\n");
-            for memory in relevant_memories {
+            for (memory, _) in relevant_memories {
                 prompt.push_str(&format!("- {}\n", memory.content));
             }
             prompt.push_str("\n");
         }
         
+        // 少样本示例
+        let examples = self.select_few_shot_examples(agent).await;
+        if !examples.is_empty() {
+            prompt.push_str("参考示例:\n");
+            prompt.push_str(&format_examples_for_prompt(&examples));
+        }
+        
         // 推理策略指导
         match agent.config.reasoning_strategy {
             ReasoningStrategy::React => {
@@ -729,6 +978,31 @@ impl AgentRuntime {
         Ok(prompt)
     }
     
+    /// 挑选与当前任务最相关的少样本示例，失败时不影响推理
+    async fn select_few_shot_examples(&self, agent: &AgentInstance) -> Vec<FewShotExampleResponse> {
+        if self.config.few_shot_examples == 0 {
+            return Vec::new();
+        }
+
+        let task = match agent.execution_context.current_task {
+            Some(ref task) => format!("{}\n{}", task.description, task.objective),
+            None => agent.config.description.clone(),
+        };
+        let target = FewShotTarget {
+            agent_id: Some(agent.agent_id),
+            prompt_template: None,
+        };
+
+        FewShotExampleService::new(self.db.as_ref().clone())
+            .with_embedder(self.rig_client.clone())
+            .select_examples(agent.config.tenant_id, &target, &task, self.config.few_shot_examples)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("挑选少样本示例失败: agent_id={}, error={}", agent.agent_id, e);
+                Vec::new()
+            })
+    }
+    
     /// 解析推理响应
     async fn parse_reasoning_response(
         &self,
@@ -793,9 +1067,12 @@ impl AgentRuntime {
         
         // 执行工具
         let start_time = std::time::Instant::now();
-        let result = tool.execute(parameters, context).await?;
+        let result = deadline::run(&format!("tool.{}", tool_name), tool.execute(parameters, context)).await?;
         let execution_time = start_time.elapsed().as_millis() as u64;
         
+        // 按声明的输出模式校验结果
+        let result = enforce_output_schema(&tool.metadata(), result);
+        
         debug!("工具执行完成: tool_name={}, 执行时间={}ms", tool_name, execution_time);
         
         Ok(ToolResult {
@@ -865,27 +1142,35 @@ impl AgentRuntime {
         debug!("记忆压缩完成: agent_id={}", agent.agent_id);
     }
     
-    /// 检索相关记忆
+    /// 检索相关记忆，返回记忆及其排序得分
     async fn retrieve_relevant_memories(
         &self,
         agent: &AgentInstance,
         limit: usize,
-    ) -> Vec<MemoryItem> {
-        let mut all_memories = Vec::new();
-        all_memories.extend(agent.memory.short_term.iter().cloned());
-        all_memories.extend(agent.memory.working.iter().cloned());
-        all_memories.extend(agent.memory.long_term.iter().take(10).cloned());
-        
-        // 按重要性和最近访问时间排序
-        all_memories.sort_by(|a, b| {
-            let score_a = a.importance_score + (a.access_count as f32 * 0.1);
-            let score_b = b.importance_score + (b.access_count as f32 * 0.1);
-            score_b.partial_cmp(&score_a).unwrap()
-        });
+    ) -> Vec<(MemoryItem, f32)> {
+        let mut all_memories: Vec<(MemoryItem, f32)> = agent.memory.short_term.iter()
+            .chain(agent.memory.working.iter())
+            .chain(agent.memory.long_term.iter().take(10))
+            .map(|memory| (memory.clone(), memory_retrieval_score(memory)))
+            .collect();
+        
+        // 按重要性和访问次数排序
+        all_memories.sort_by(|a, b| b.1.total_cmp(&a.1));
         
         all_memories.into_iter().take(limit).collect()
     }
     
+    /// 保存当前任务的记忆快照
+    async fn save_memory_snapshot(&self, agent: &mut AgentInstance, execution_id: Uuid) {
+        let snapshot = ExecutionMemory::capture(
+            agent.agent_id,
+            execution_id,
+            &agent.memory,
+            std::mem::take(&mut agent.memory_retrievals),
+        );
+        self.memory_snapshots.save_or_warn(agent.config.tenant_id, &snapshot).await;
+    }
+    
     /// 获取工具元数据
     async fn get_tool_metadata(&self, tool_name: &str) -> Option<ToolMetadata> {
         let tool_registry = self.tool_registry.read().await;
@@ -899,6 +1184,7 @@ impl AgentRuntime {
     ) -> Result<(), AiStudioError> {
         let metadata = tool.metadata();
         let tool_name = metadata.name.clone();
+        check_tool_output_schema(&metadata)?;
         
         let mut tool_registry = self.tool_registry.write().await;
         tool_registry.tools.insert(tool_name.clone(), tool);
@@ -919,15 +1205,48 @@ impl AgentRuntime {
     
     /// 停止 Agent
     pub async fn stop_agent(&self, agent_id: Uuid) -> Result<(), AiStudioError> {
-        let mut active_agents = self.active_agents.write().await;
-        if let Some(agent) = active_agents.get_mut(&agent_id) {
-            agent.state = AgentState::Stopped;
-            info!("停止 Agent: agent_id={}", agent_id);
+        let transition = {
+            let mut active_agents = self.active_agents.write().await;
+            active_agents.get_mut(&agent_id).map(|agent| {
+                let from = std::mem::replace(&mut agent.state, AgentState::Stopped);
+                info!("停止 Agent: agent_id={}", agent_id);
+                ExecutionTransition {
+                    tenant_id: agent.config.tenant_id,
+                    execution_type: ExecutionType::Agent,
+                    execution_id: agent_id,
+                    from_state: Some(from.as_str().to_string()),
+                    to_state: AgentState::Stopped.as_str().to_string(),
+                    payload: serde_json::json!({}),
+                }
+            })
+        };
+        
+        if let Some(transition) = transition {
+            self.events.record_or_warn(transition).await;
+            ScratchWorkspaces::shared().release(&agent_id.to_string()).await;
         }
         
         Ok(())
     }
     
+    /// 切换 Agent 状态并追加执行事件
+    async fn transition(&self, agent: &mut AgentInstance, to: AgentState, payload: serde_json::Value) {
+        let from = std::mem::replace(&mut agent.state, to);
+        agent_stream::publish(agent.agent_id, AgentStreamEvent::StateChanged {
+            from: Some(from.as_str().to_string()),
+            to: agent.state.as_str().to_string(),
+            timestamp: Utc::now(),
+        });
+        self.events.record_or_warn(ExecutionTransition {
+            tenant_id: agent.config.tenant_id,
+            execution_type: ExecutionType::Agent,
+            execution_id: agent.agent_id,
+            from_state: Some(from.as_str().to_string()),
+            to_state: agent.state.as_str().to_string(),
+            payload,
+        }).await;
+    }
+    
     /// 清理非活跃 Agent
     pub async fn cleanup_inactive_agents(&self) -> Result<u32, AiStudioError> {
         let mut active_agents = self.active_agents.write().await;
@@ -935,12 +1254,23 @@ impl AgentRuntime {
         let timeout_duration = chrono::Duration::hours(1); // 1小时超时
         
         let initial_count = active_agents.len();
-        active_agents.retain(|_, agent| {
+        let mut removed = Vec::new();
+        active_agents.retain(|agent_id, agent| {
             let inactive_duration = now.signed_duration_since(agent.last_active_at);
-            inactive_duration < timeout_duration
+            let keep = inactive_duration < timeout_duration;
+            if !keep {
+                removed.push(*agent_id);
+            }
+            keep
         });
-        
         let cleaned_count = initial_count - active_agents.len();
+        drop(active_agents);
+        
+        // 释放被清理 Agent 的临时工作区
+        let workspaces = ScratchWorkspaces::shared();
+        for agent_id in removed {
+            workspaces.release(&agent_id.to_string()).await;
+        }
         
         if cleaned_count > 0 {
             info!("清理了 {} 个非活跃 Agent", cleaned_count);
@@ -964,6 +1294,25 @@ impl AgentRuntimeFactory {
     }
 }
 
+/// 记忆检索排序得分：重要性加上访问次数加成
+pub fn memory_retrieval_score(memory: &MemoryItem) -> f32 {
+    memory.importance_score + (memory.access_count as f32 * 0.1)
+}
+
+/// 更新被检索到的记忆的访问次数与访问时间
+fn mark_memories_accessed(memory: &mut AgentMemory, retrieved: &[(MemoryItem, f32)]) {
+    let now = Utc::now();
+    for item in memory.short_term.iter_mut()
+        .chain(memory.working.iter_mut())
+        .chain(memory.long_term.iter_mut())
+    {
+        if retrieved.iter().any(|(r, _)| r.id == item.id) {
+            item.access_count += 1;
+            item.last_accessed_at = now;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -978,8 +1327,11 @@ mod tests {
             reasoning_strategy: ReasoningStrategy::React,
             temperature: 0.7,
             max_tokens: 1000,
+            model: None,
             tenant_id: Uuid::new_v4(),
             created_by: Uuid::new_v4(),
+            knowledge_bases: Vec::new(),
+            web_search: WebSearchDomainPolicy::default(),
         };
         
         let serialized = serde_json::to_string(&config).unwrap();
@@ -0,0 +1,261 @@
+// Agent 执行事件流
+// 将 Agent 状态变化与工具的部分输出实时推送给订阅者，并控制写入执行轨迹的工具输出大小
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// 每个 Agent 事件通道缓冲的事件数，订阅者落后超过该数量时丢弃最旧的事件
+const CHANNEL_CAPACITY: usize = 256;
+
+/// 单个 `tool_progress` 事件携带的最大字节数，更长的输出拆分为多个事件
+pub const MAX_PROGRESS_CHUNK_BYTES: usize = 4 * 1024;
+
+/// 执行轨迹中保留的工具输出最大字节数
+pub const MAX_TRACE_OUTPUT_BYTES: usize = 16 * 1024;
+
+/// 截断标记，追加在被截断的预览文本末尾
+pub const TRUNCATION_MARKER: &str = "…[truncated]";
+
+/// 各 Agent 的事件通道
+static AGENT_STREAMS: Lazy<Mutex<HashMap<Uuid, broadcast::Sender<AgentStreamEvent>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Agent 流式事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AgentStreamEvent {
+    /// Agent 状态变化
+    StateChanged {
+        from: Option<String>,
+        to: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// 工具的部分输出
+    ToolProgress {
+        call_id: Uuid,
+        tool_name: String,
+        sequence: u32,
+        chunk: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// 工具调用结束
+    ToolCompleted {
+        call_id: Uuid,
+        tool_name: String,
+        success: bool,
+        /// 输出总字节数
+        output_bytes: usize,
+        /// 执行轨迹中的输出是否被截断
+        truncated: bool,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+impl AgentStreamEvent {
+    /// SSE 事件名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            AgentStreamEvent::StateChanged { .. } => "state_changed",
+            AgentStreamEvent::ToolProgress { .. } => "tool_progress",
+            AgentStreamEvent::ToolCompleted { .. } => "tool_completed",
+        }
+    }
+}
+
+/// 订阅 Agent 的事件流
+pub fn subscribe(agent_id: Uuid) -> broadcast::Receiver<AgentStreamEvent> {
+    let mut streams = AGENT_STREAMS.lock().unwrap();
+    streams
+        .entry(agent_id)
+        .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+        .subscribe()
+}
+
+/// 推送 Agent 事件，没有订阅者时直接丢弃
+pub fn publish(agent_id: Uuid, event: AgentStreamEvent) {
+    let mut streams = AGENT_STREAMS.lock().unwrap();
+    let Some(sender) = streams.get(&agent_id) else {
+        return;
+    };
+    if sender.send(event).is_err() {
+        // 订阅者已全部断开
+        streams.remove(&agent_id);
+    }
+}
+
+/// 工具进度上报句柄
+///
+/// 运行时在每次工具调用前放入 [`ExecutionContext`](crate::ai::agent_runtime::ExecutionContext)，
+/// 产生长输出的工具（下载、查询）边读取边上报，订阅者即时收到 `tool_progress` 事件。
+#[derive(Debug, Clone)]
+pub struct ToolProgressSink {
+    agent_id: Uuid,
+    call_id: Uuid,
+    tool_name: String,
+    state: Arc<Mutex<ProgressState>>,
+}
+
+#[derive(Debug, Default)]
+struct ProgressState {
+    sequence: u32,
+    bytes: usize,
+}
+
+impl ToolProgressSink {
+    /// 创建一次工具调用的进度句柄
+    pub fn new(agent_id: Uuid, tool_name: &str) -> Self {
+        Self {
+            agent_id,
+            call_id: Uuid::new_v4(),
+            tool_name: tool_name.to_string(),
+            state: Arc::new(Mutex::new(ProgressState::default())),
+        }
+    }
+
+    /// 工具调用 ID
+    pub fn call_id(&self) -> Uuid {
+        self.call_id
+    }
+
+    /// 上报一段部分输出，超过单事件上限时拆分推送
+    pub fn report(&self, output: &str) {
+        for chunk in split_chunks(output, MAX_PROGRESS_CHUNK_BYTES) {
+            let sequence = {
+                let mut state = self.state.lock().unwrap();
+                state.sequence += 1;
+                state.bytes += chunk.len();
+                state.sequence
+            };
+            publish(self.agent_id, AgentStreamEvent::ToolProgress {
+                call_id: self.call_id,
+                tool_name: self.tool_name.clone(),
+                sequence,
+                chunk: chunk.to_string(),
+                timestamp: Utc::now(),
+            });
+        }
+    }
+
+    /// 已上报的字节数
+    pub fn reported_bytes(&self) -> usize {
+        self.state.lock().unwrap().bytes
+    }
+
+    /// 推送工具调用结束事件
+    pub fn complete(&self, success: bool, output_bytes: usize, truncated: bool) {
+        publish(self.agent_id, AgentStreamEvent::ToolCompleted {
+            call_id: self.call_id,
+            tool_name: self.tool_name.clone(),
+            success,
+            output_bytes,
+            truncated,
+            timestamp: Utc::now(),
+        });
+    }
+}
+
+/// 按字节上限在字符边界处切分文本
+fn split_chunks(text: &str, max_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = floor_char_boundary(rest, max_bytes);
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+    let mut end = index;
+    while end > 0 && !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    // 上限小于首个字符宽度时至少保留一个字符
+    if end == 0 {
+        text.char_indices().nth(1).map(|(i, _)| i).unwrap_or(text.len())
+    } else {
+        end
+    }
+}
+
+/// 限制写入执行轨迹的工具输出大小
+///
+/// 序列化后不超过 [`MAX_TRACE_OUTPUT_BYTES`] 时原样返回；否则返回带截断标记的预览，
+/// 完整输出已通过事件流推送给订阅者。返回值第二项为序列化后的原始字节数。
+pub fn truncate_for_trace(output: &Value) -> (Value, usize, bool) {
+    truncate_json(output, MAX_TRACE_OUTPUT_BYTES)
+}
+
+/// 将 JSON 限制在指定字节数内，超出时返回带截断标记的预览
+///
+/// 返回值依次为结果、序列化后的原始字节数与是否截断。
+pub fn truncate_json(output: &Value, max_bytes: usize) -> (Value, usize, bool) {
+    let serialized = output.to_string();
+    let original_bytes = serialized.len();
+    if original_bytes <= max_bytes {
+        return (output.clone(), original_bytes, false);
+    }
+
+    let end = floor_char_boundary(&serialized, max_bytes);
+    let preview = format!("{}{}", &serialized[..end], TRUNCATION_MARKER);
+    let truncated = serde_json::json!({
+        "truncated": true,
+        "original_bytes": original_bytes,
+        "preview": preview,
+    });
+    (truncated, original_bytes, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_chunks_respects_char_boundaries() {
+        let text = "数据".repeat(3);
+        let chunks = split_chunks(&text, 4);
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() == 1));
+        assert_eq!(chunks.concat(), text);
+        assert!(split_chunks("", 4).is_empty());
+    }
+
+    #[test]
+    fn test_truncate_for_trace() {
+        let small = serde_json::json!({ "rows": [1, 2, 3] });
+        assert_eq!(truncate_for_trace(&small), (small.clone(), small.to_string().len(), false));
+
+        let large = serde_json::json!({ "body": "x".repeat(MAX_TRACE_OUTPUT_BYTES * 2) });
+        let (trace, original_bytes, truncated) = truncate_for_trace(&large);
+        assert!(truncated);
+        assert_eq!(original_bytes, large.to_string().len());
+        assert_eq!(trace["truncated"], true);
+        assert!(trace["preview"].as_str().unwrap().ends_with(TRUNCATION_MARKER));
+    }
+
+    #[tokio::test]
+    async fn test_progress_reaches_subscribers() {
+        let agent_id = Uuid::new_v4();
+        let mut events = subscribe(agent_id);
+        let sink = ToolProgressSink::new(agent_id, "http_request");
+        sink.report(&"a".repeat(MAX_PROGRESS_CHUNK_BYTES + 1));
+
+        let first = events.recv().await.unwrap();
+        assert_eq!(first.name(), "tool_progress");
+        let AgentStreamEvent::ToolProgress { sequence, chunk, .. } = events.recv().await.unwrap() else {
+            panic!("应为工具进度事件");
+        };
+        assert_eq!((sequence, chunk.len()), (2, 1));
+        assert_eq!(sink.reported_bytes(), MAX_PROGRESS_CHUNK_BYTES + 1);
+    }
+}
@@ -0,0 +1,173 @@
+// 答案置信度估计
+// 综合检索得分分布、模型自评与答案特征估计问答置信度，并生成资料不足时的拒答话术
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::ai::rag_engine::SourceDocument;
+use crate::db::entities::knowledge_base::AnswerPolicy;
+
+/// 要求模型自评置信度的提示词指令
+pub const SELF_ASSESSMENT_INSTRUCTION: &str =
+    "在回答的最后另起一行，按「置信度：0.85」的格式给出 0 到 1 之间的小数，表示答案被文档内容支持的程度";
+
+/// 默认拒答话术
+const DEFAULT_REFUSAL_MESSAGE: &str = "抱歉，现有资料不足以可靠地回答这个问题。";
+
+/// 自评置信度行的前缀
+const SELF_ASSESSMENT_LABELS: [&str; 3] = ["置信度", "confidence", "自评置信度"];
+
+/// 置信度明细
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ConfidenceBreakdown {
+    /// 综合置信度（0-1）
+    pub score: f32,
+    /// 检索置信度，由检索得分分布估计
+    pub retrieval: f32,
+    /// 模型自评置信度，未启用自评或模型未给出时为空
+    pub self_assessment: Option<f32>,
+    /// 答案特征置信度，由答案措辞与长度估计
+    pub answer_signal: f32,
+}
+
+impl ConfidenceBreakdown {
+    /// 未检索到任何文档块时的置信度
+    pub fn none() -> Self {
+        Self { score: 0.0, retrieval: 0.0, self_assessment: None, answer_signal: 0.0 }
+    }
+}
+
+/// 由检索得分分布估计检索置信度
+///
+/// 最高得分反映最佳证据的相关程度，前三名均值反映证据是否充分，两者加权。
+pub fn retrieval_confidence(scores: &[f32]) -> f32 {
+    let mut scores: Vec<f32> = scores.iter().map(|s| s.clamp(0.0, 1.0)).collect();
+    if scores.is_empty() {
+        return 0.0;
+    }
+    scores.sort_by(|a, b| b.total_cmp(a));
+
+    let top = scores[0];
+    let head = &scores[..scores.len().min(3)];
+    let mean = head.iter().sum::<f32>() / 3.0;
+    0.6 * top + 0.4 * mean
+}
+
+/// 合并各项置信度
+///
+/// 有模型自评时按 5:3:2 加权检索、自评与答案特征，否则按 7:3 加权检索与答案特征。
+pub fn combine_confidence(retrieval: f32, self_assessment: Option<f32>, answer_signal: f32) -> ConfidenceBreakdown {
+    let score = match self_assessment {
+        Some(assessed) => 0.5 * retrieval + 0.3 * assessed + 0.2 * answer_signal,
+        None => 0.7 * retrieval + 0.3 * answer_signal,
+    };
+
+    ConfidenceBreakdown {
+        score: score.clamp(0.0, 1.0),
+        retrieval,
+        self_assessment,
+        answer_signal,
+    }
+}
+
+/// 从答案末尾提取模型自评置信度，返回去除自评行后的答案
+pub fn extract_self_assessment(text: &str) -> (String, Option<f32>) {
+    let trimmed = text.trim_end();
+    let (body, last_line) = match trimmed.rfind('\n') {
+        Some(index) => (&trimmed[..index], &trimmed[index + 1..]),
+        None => ("", trimmed),
+    };
+
+    let line = last_line.trim().trim_matches(|c: char| "*_【】[]()（）".contains(c));
+    let lowered = line.to_lowercase();
+    let Some(rest) = SELF_ASSESSMENT_LABELS.iter().find_map(|label| lowered.strip_prefix(label)) else {
+        return (text.to_string(), None);
+    };
+
+    let value = rest.trim_start_matches([':', '：', ' ']).trim().trim_end_matches(['。', '.']);
+    let parsed = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f32>().ok().map(|v| v / 100.0),
+        None => value.parse::<f32>().ok(),
+    };
+
+    match parsed.filter(|v| (0.0..=1.0).contains(v)) {
+        Some(assessed) => (body.trim_end().to_string(), Some(assessed)),
+        None => (text.to_string(), None),
+    }
+}
+
+/// 生成拒答话术，列出可能包含相关信息的文档
+pub fn refusal_answer(policy: &AnswerPolicy, suggested: &[SourceDocument]) -> String {
+    let mut answer = policy.refusal_message.clone()
+        .filter(|message| !message.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_REFUSAL_MESSAGE.to_string());
+
+    if !suggested.is_empty() {
+        answer.push_str("\n\n以下文档可能包含相关信息，建议查阅：");
+        for (index, doc) in suggested.iter().enumerate() {
+            answer.push_str(&format!("\n{}. {}", index + 1, doc.title));
+        }
+    }
+    answer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_retrieval_confidence() {
+        assert_eq!(retrieval_confidence(&[]), 0.0);
+
+        // 单个高分结果的证据不如多个高分结果充分
+        let single = retrieval_confidence(&[0.9]);
+        let multiple = retrieval_confidence(&[0.9, 0.85, 0.8]);
+        assert!(single < multiple);
+        assert!((multiple - (0.6 * 0.9 + 0.4 * 0.85)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_combine_confidence() {
+        let with_self = combine_confidence(0.8, Some(0.6), 0.5);
+        assert!((with_self.score - 0.68).abs() < 1e-6);
+
+        let without_self = combine_confidence(0.8, None, 0.5);
+        assert!((without_self.score - 0.71).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_extract_self_assessment() {
+        let (answer, assessed) = extract_self_assessment("报销需在 30 天内提交。\n\n置信度：0.85");
+        assert_eq!(answer, "报销需在 30 天内提交。");
+        assert_eq!(assessed, Some(0.85));
+
+        let (_, assessed) = extract_self_assessment("答案\n**Confidence: 70%**");
+        assert_eq!(assessed, Some(0.7));
+
+        // 超出范围或缺少自评时保留原文
+        let (answer, assessed) = extract_self_assessment("答案\n置信度：高");
+        assert_eq!(answer, "答案\n置信度：高");
+        assert_eq!(assessed, None);
+    }
+
+    #[test]
+    fn test_refusal_answer() {
+        let docs = vec![SourceDocument {
+            document_id: Uuid::new_v4(),
+            title: "差旅制度".to_string(),
+            doc_type: "pdf".to_string(),
+            relevance_score: 0.4,
+            chunk_count: 1,
+            knowledge_base_id: None,
+            knowledge_base_name: None,
+        }];
+
+        let answer = refusal_answer(&AnswerPolicy::default(), &docs);
+        assert!(answer.starts_with(DEFAULT_REFUSAL_MESSAGE));
+        assert!(answer.contains("1. 差旅制度"));
+
+        let policy = AnswerPolicy { refusal_message: Some("请联系人事部门。".to_string()), ..Default::default() };
+        assert_eq!(refusal_answer(&policy, &[]), "请联系人事部门。");
+    }
+}
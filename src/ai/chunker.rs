@@ -2,6 +2,9 @@
 // 实现智能文档分块算法
 
 use crate::ai::{RigAiClientManager, ExtractedText};
+use crate::ai::embedding_pool::{EmbeddingPool, EmbeddingPriority};
+use crate::ai::table_extraction::{extract_markdown_tables, render_chunk_table, split_table, ExtractedTable};
+use crate::db::entities::document_chunk::{self, ChunkTable};
 use crate::errors::AiStudioError;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -42,6 +45,22 @@ pub struct ChunkMetadata {
     pub overlap_with_previous: bool,
     pub overlap_with_next: bool,
     pub custom_properties: HashMap<String, String>,
+    /// 表格块的结构化内容
+    #[serde(default)]
+    pub table: Option<ChunkTable>,
+}
+
+impl DocumentChunk {
+    /// 转换为文档块表中存储的元数据
+    pub fn storage_metadata(&self) -> document_chunk::ChunkMetadata {
+        document_chunk::ChunkMetadata {
+            page_number: self.metadata.source_page.map(|page| page as i32),
+            paragraph_number: Some(self.metadata.chunk_index as i32),
+            language: self.metadata.language.clone().unwrap_or_else(|| "zh-CN".to_string()),
+            table: self.metadata.table.clone(),
+            ..Default::default()
+        }
+    }
 }
 
 /// 块位置信息
@@ -176,7 +195,9 @@ impl DocumentChunker for HybridChunker {
     async fn chunk_document(&self, text: &ExtractedText) -> Result<Vec<DocumentChunk>, AiStudioError> {
         debug!("使用混合分块器处理文档，配置: {:?}", self.config);
         
-        let content = &text.content;
+        // 表格单独成块，其余文本按段落分块
+        let (content, tables) = self.collect_tables(text);
+        let content = &content;
         let mut chunks = Vec::new();
         
         // 首先尝试按段落分割
@@ -234,11 +255,28 @@ impl DocumentChunker for HybridChunker {
         }
         
         // 如果没有生成任何块，创建一个包含全部内容的块
-        if chunks.is_empty() && !content.is_empty() {
+        if chunks.is_empty() && !content.trim().is_empty() {
             let chunk = self.create_chunk(content, 0, 0, content.len(), ChunkType::Text)?;
             chunks.push(chunk);
         }
         
+        // 表格按行拆分，每块都带表头
+        for (table_index, extracted) in tables.iter().enumerate() {
+            let table_id = format!("table-{}", table_index + 1);
+            for slice in split_table(&table_id, &extracted.table, self.config.max_chunk_size) {
+                let mut chunk = self.create_chunk(
+                    &render_chunk_table(&slice),
+                    chunks.len(),
+                    extracted.start_char,
+                    extracted.end_char,
+                    ChunkType::Table,
+                )?;
+                chunk.metadata.source_page = extracted.source_page;
+                chunk.metadata.table = Some(slice);
+                chunks.push(chunk);
+            }
+        }
+        
         // 更新总块数信息
         let total_chunks = chunks.len();
         for chunk in &mut chunks {
@@ -258,6 +296,28 @@ impl DocumentChunker for HybridChunker {
 }
 
 impl HybridChunker {
+    /// 抽取正文中的 Markdown 表格与解析器提供的页面表格，返回去除表格后的正文
+    fn collect_tables(&self, text: &ExtractedText) -> (String, Vec<ExtractedTable>) {
+        let (content, mut tables) = extract_markdown_tables(&text.content);
+        for page in text.pages.iter().flatten() {
+            for table in &page.tables {
+                // CSV 等处理器会同时把表格写入正文，避免重复
+                let duplicate = tables.iter().any(|existing| {
+                    existing.table.headers == table.headers && existing.table.rows == table.rows
+                });
+                if !duplicate {
+                    tables.push(ExtractedTable {
+                        table: table.clone(),
+                        start_char: 0,
+                        end_char: 0,
+                        source_page: Some(page.page_number),
+                    });
+                }
+            }
+        }
+        (content, tables)
+    }
+    
     fn split_by_paragraphs<'a>(&self, content: &'a str) -> Vec<&'a str> {
         content.split("\n\n")
             .filter(|p| !p.trim().is_empty())
@@ -319,6 +379,7 @@ impl HybridChunker {
                 overlap_with_previous: false,
                 overlap_with_next: false,
                 custom_properties: HashMap::new(),
+                table: None,
             },
             embedding: None,
             position: ChunkPosition {
@@ -378,14 +439,21 @@ impl DocumentVectorizer for AiVectorizer {
             
             debug!("向量化批次，包含 {} 个文档块", texts.len());
             
-            let embeddings = self.client_manager.generate_embeddings(&texts).await?;
+            // 配置了本地嵌入工作池时以批量优先级提交，由工作池按显存凑批
+            let embeddings: Vec<Vec<f32>> = match EmbeddingPool::global() {
+                Some(pool) => pool.embed_many(&texts, EmbeddingPriority::Bulk).await?,
+                None => self.client_manager.generate_embeddings(&texts).await?
+                    .into_iter()
+                    .map(|response| response.embedding)
+                    .collect(),
+            };
             
             if embeddings.len() != chunk_batch.len() {
                 return Err(AiStudioError::ai("嵌入向量数量与文档块数量不匹配"));
             }
             
-            for (chunk, embedding_response) in chunk_batch.iter_mut().zip(embeddings.iter()) {
-                chunk.embedding = Some(embedding_response.embedding.clone());
+            for (chunk, embedding) in chunk_batch.iter_mut().zip(embeddings) {
+                chunk.embedding = Some(embedding);
             }
         }
         
@@ -495,7 +563,26 @@ mod tests {
         assert_eq!(chunker.detect_chunk_type("> 这是引用"), ChunkType::Quote);
         assert_eq!(chunker.detect_chunk_type("这是普通文本。"), ChunkType::Text);
     }
-    
+
+    #[tokio::test]
+    async fn test_table_chunks_keep_headers() {
+        let chunker = HybridChunker::with_default_config();
+        let mut text = create_test_extracted_text();
+        text.content = "各型号参数如下。\n\n| 型号 | 功率(W) |\n| --- | --- |\n| A-100 | 1200 |\n| B-200 | 800 |\n".to_string();
+
+        let chunks = chunker.chunk_document(&text).await.unwrap();
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].metadata.chunk_type, ChunkType::Text);
+        assert!(!chunks[0].content.contains('|'));
+        let table_chunk = &chunks[1];
+        assert_eq!(table_chunk.metadata.chunk_type, ChunkType::Table);
+        assert!(table_chunk.content.contains("| 型号 | 功率(W) |"));
+        let table = table_chunk.metadata.table.as_ref().unwrap();
+        assert_eq!(table.rows, vec![vec!["A-100", "1200"], vec!["B-200", "800"]]);
+        assert_eq!(table_chunk.storage_metadata().table.as_ref(), Some(table));
+    }
+
     #[tokio::test]
     async fn test_ai_vectorizer() {
         let config = AiConfig {
@@ -505,6 +592,7 @@ mod tests {
             temperature: 0.7,
             timeout: 30,
             retry_attempts: 3,
+            local: None,
         };
         
         let client_manager = match RigAiClientManager::new(config).await {
@@ -528,6 +616,7 @@ mod tests {
                     overlap_with_previous: false,
                     overlap_with_next: true,
                     custom_properties: HashMap::new(),
+                    table: None,
                 },
                 embedding: None,
                 position: ChunkPosition {
@@ -551,6 +640,7 @@ mod tests {
                     overlap_with_previous: true,
                     overlap_with_next: false,
                     custom_properties: HashMap::new(),
+                    table: None,
                 },
                 embedding: None,
                 position: ChunkPosition {
@@ -581,6 +671,7 @@ mod tests {
             temperature: 0.7,
             timeout: 30,
             retry_attempts: 3,
+            local: None,
         };
         
         let client_manager = match RigAiClientManager::new(config).await {
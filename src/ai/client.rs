@@ -1,8 +1,10 @@
 // AI 客户端模块
 // 集成 Rig 框架和 LLM 客户端配置
 
+use crate::ai::local_inference::LocalInferenceClient;
 use crate::config::AiConfig;
 use crate::errors::AiStudioError;
+use crate::services::egress;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -67,7 +69,9 @@ impl AiClientManager {
         let config = Arc::new(config);
         
         // 根据配置创建相应的客户端
-        let client: Arc<dyn AiClient> = if config.model_endpoint.contains("ollama") {
+        let client: Arc<dyn AiClient> = if config.local.is_some() {
+            Arc::new(LocalInferenceClient::new(config.clone())?)
+        } else if config.model_endpoint.contains("ollama") {
             Arc::new(OllamaClient::new(config.clone())?)
         } else if config.model_endpoint.contains("openai") {
             Arc::new(OpenAiClient::new(config.clone())?)
@@ -146,7 +150,7 @@ pub struct OllamaClient {
 
 impl OllamaClient {
     pub fn new(config: Arc<AiConfig>) -> Result<Self, AiStudioError> {
-        let http_client = reqwest::Client::builder()
+        let http_client = egress::client_builder()?
             .timeout(Duration::from_secs(config.timeout))
             .build()
             .map_err(|e| AiStudioError::ai(format!("创建 HTTP 客户端失败: {}", e)))?;
@@ -289,7 +293,7 @@ impl OpenAiClient {
                 .map_err(|e| AiStudioError::ai(format!("无效的 API 密钥: {}", e)))?,
         );
         
-        let http_client = reqwest::Client::builder()
+        let http_client = egress::client_builder()?
             .timeout(Duration::from_secs(config.timeout))
             .default_headers(headers)
             .build()
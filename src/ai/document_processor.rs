@@ -1,6 +1,7 @@
 // 文档处理模块
 // 实现多格式文档解析和文本提取
 
+use crate::ai::table_extraction::{parse_delimited, parse_xlsx, render_markdown_table};
 use crate::errors::AiStudioError;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -131,6 +132,11 @@ impl DocumentProcessorManager {
         // 注册 HTML 处理器
         self.register_processor("html", Box::new(HtmlProcessor::new()));
         self.register_processor("htm", Box::new(HtmlProcessor::new()));
+        
+        // 注册表格文件处理器
+        self.register_processor("csv", Box::new(CsvProcessor::new()));
+        self.register_processor("tsv", Box::new(CsvProcessor::new()));
+        self.register_processor("xlsx", Box::new(XlsxProcessor::new()));
     }
     
     /// 注册处理器
@@ -526,6 +532,161 @@ impl DocumentProcessor for WordProcessor {
     }
 }
 
+/// CSV/TSV 表格文件处理器
+///
+/// 整个文件作为一张表格写入页面表格，正文为对应的 Markdown 表格，分块时按行拆分并保留表头。
+pub struct CsvProcessor;
+
+impl CsvProcessor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl DocumentProcessor for CsvProcessor {
+    async fn extract_text(&self, file_path: &str) -> Result<ExtractedText, AiStudioError> {
+        let raw = tokio::fs::read_to_string(file_path).await
+            .map_err(|e| AiStudioError::file_processing_with_name(
+                format!("读取表格文件失败: {}", e),
+                file_path
+            ))?;
+        let file_metadata = tokio::fs::metadata(file_path).await
+            .map_err(|e| AiStudioError::file_processing(format!("获取文件元数据失败: {}", e)))?;
+        
+        let is_tsv = Path::new(file_path).extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("tsv"));
+        let title = Path::new(file_path).file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string());
+        let mut table = parse_delimited(&raw, if is_tsv { '\t' } else { ',' })
+            .ok_or_else(|| AiStudioError::file_processing_with_name("表格文件为空", file_path))?;
+        table.caption = title.clone();
+        let content = render_markdown_table(table.caption.as_deref(), &table.headers, &table.rows);
+        
+        let metadata = DocumentMetadata {
+            title,
+            author: None,
+            subject: None,
+            keywords: None,
+            created_date: file_metadata.created().ok()
+                .map(|t| chrono::DateTime::from(t)),
+            modified_date: file_metadata.modified().ok()
+                .map(|t| chrono::DateTime::from(t)),
+            page_count: Some(1),
+            word_count: Some(content.split_whitespace().count() as u32),
+            language: None,
+            format: if is_tsv { "text/tab-separated-values" } else { "text/csv" }.to_string(),
+            file_size: file_metadata.len(),
+            custom_properties: HashMap::new(),
+        };
+        
+        Ok(ExtractedText {
+            content: content.clone(),
+            metadata,
+            pages: Some(vec![PageContent {
+                page_number: 1,
+                content,
+                images: Vec::new(),
+                tables: vec![table],
+            }]),
+            processing_info: ProcessingInfo {
+                processor_type: "csv".to_string(),
+                processing_time_ms: 0,
+                success: true,
+                warnings: Vec::new(),
+                errors: Vec::new(),
+            },
+        })
+    }
+    
+    fn supports_format(&self, file_extension: &str) -> bool {
+        matches!(file_extension.to_lowercase().as_str(), "csv" | "tsv")
+    }
+    
+    fn supported_formats(&self) -> Vec<String> {
+        vec!["csv".to_string(), "tsv".to_string()]
+    }
+}
+
+/// Excel（XLSX）文件处理器
+///
+/// 每个非空工作表对应一页，页面表格为该工作表，正文为各工作表的 Markdown 表格。
+pub struct XlsxProcessor;
+
+impl XlsxProcessor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl DocumentProcessor for XlsxProcessor {
+    async fn extract_text(&self, file_path: &str) -> Result<ExtractedText, AiStudioError> {
+        let bytes = tokio::fs::read(file_path).await
+            .map_err(|e| AiStudioError::file_processing_with_name(
+                format!("读取 Excel 文件失败: {}", e),
+                file_path
+            ))?;
+        let file_metadata = tokio::fs::metadata(file_path).await
+            .map_err(|e| AiStudioError::file_processing(format!("获取文件元数据失败: {}", e)))?;
+        
+        let tables = parse_xlsx(&bytes)?;
+        if tables.is_empty() {
+            return Err(AiStudioError::file_processing_with_name("Excel 文件没有包含数据的工作表", file_path));
+        }
+        
+        let pages: Vec<PageContent> = tables.into_iter().enumerate().map(|(i, table)| PageContent {
+            page_number: i as u32 + 1,
+            content: render_markdown_table(table.caption.as_deref(), &table.headers, &table.rows),
+            images: Vec::new(),
+            tables: vec![table],
+        }).collect();
+        let content = pages.iter().map(|page| page.content.as_str()).collect::<Vec<_>>().join("\n");
+        
+        let metadata = DocumentMetadata {
+            title: Path::new(file_path).file_stem()
+                .and_then(|s| s.to_str())
+                .map(|s| s.to_string()),
+            author: None,
+            subject: None,
+            keywords: None,
+            created_date: file_metadata.created().ok()
+                .map(|t| chrono::DateTime::from(t)),
+            modified_date: file_metadata.modified().ok()
+                .map(|t| chrono::DateTime::from(t)),
+            page_count: Some(pages.len() as u32),
+            word_count: Some(content.split_whitespace().count() as u32),
+            language: None,
+            format: "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string(),
+            file_size: file_metadata.len(),
+            custom_properties: HashMap::new(),
+        };
+        
+        Ok(ExtractedText {
+            content,
+            metadata,
+            pages: Some(pages),
+            processing_info: ProcessingInfo {
+                processor_type: "xlsx".to_string(),
+                processing_time_ms: 0,
+                success: true,
+                warnings: Vec::new(),
+                errors: Vec::new(),
+            },
+        })
+    }
+    
+    fn supports_format(&self, file_extension: &str) -> bool {
+        file_extension.eq_ignore_ascii_case("xlsx")
+    }
+    
+    fn supported_formats(&self) -> Vec<String> {
+        vec!["xlsx".to_string()]
+    }
+}
+
 /// HTML 文件处理器
 pub struct HtmlProcessor;
 
@@ -682,6 +843,7 @@ mod tests {
         assert!(manager.supports_format("md"));
         assert!(manager.supports_format("pdf"));
         assert!(manager.supports_format("docx"));
+        assert!(manager.supports_format("csv"));
         assert!(!manager.supports_format("unknown"));
         
         // 测试获取支持的格式
@@ -0,0 +1,385 @@
+// 嵌入工作池
+// 为本地嵌入模型提供有界队列、按显存凑批和固定并发，交互请求优先于批量重嵌入任务
+
+use crate::ai::client::AiClient;
+use crate::config::EmbeddingPoolConfig;
+use crate::errors::AiStudioError;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+static GLOBAL_EMBEDDING_POOL: OnceCell<Arc<EmbeddingPool>> = OnceCell::new();
+
+/// 嵌入请求优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingPriority {
+    /// 交互请求（问答、检索时的查询向量化）
+    Interactive,
+    /// 批量请求（文档摄取、重嵌入）
+    Bulk,
+}
+
+/// 工作池运行统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingPoolStats {
+    pub interactive_queued: usize,
+    pub bulk_queued: usize,
+    pub batches_processed: u64,
+    pub texts_processed: u64,
+    pub failed_batches: u64,
+}
+
+struct EmbeddingJob {
+    text: String,
+    tokens: usize,
+    reply: oneshot::Sender<Result<Vec<f32>, AiStudioError>>,
+}
+
+type JobReceiver = Arc<Mutex<mpsc::Receiver<EmbeddingJob>>>;
+
+#[derive(Default)]
+struct Counters {
+    batches: AtomicU64,
+    texts: AtomicU64,
+    failures: AtomicU64,
+}
+
+/// 嵌入工作池
+pub struct EmbeddingPool {
+    interactive_tx: mpsc::Sender<EmbeddingJob>,
+    bulk_tx: mpsc::Sender<EmbeddingJob>,
+    config: EmbeddingPoolConfig,
+    counters: Arc<Counters>,
+}
+
+/// 单个工作协程
+struct Worker {
+    client: Arc<dyn AiClient>,
+    interactive_rx: JobReceiver,
+    bulk_rx: Option<JobReceiver>,
+    config: EmbeddingPoolConfig,
+    counters: Arc<Counters>,
+    /// 因超出批次上限而留到下一批的任务
+    carry: Option<(EmbeddingPriority, EmbeddingJob)>,
+}
+
+impl EmbeddingPool {
+    /// 创建工作池并启动工作协程
+    ///
+    /// 前 `interactive_workers` 个工作协程只处理交互请求，其余协程优先处理交互请求、空闲时处理批量请求。
+    pub fn start(client: Arc<dyn AiClient>, config: EmbeddingPoolConfig) -> Arc<Self> {
+        let (interactive_tx, interactive_rx) = mpsc::channel(config.queue_capacity.max(1));
+        let (bulk_tx, bulk_rx) = mpsc::channel(config.queue_capacity.max(1));
+        let interactive_rx = Arc::new(Mutex::new(interactive_rx));
+        let bulk_rx = Arc::new(Mutex::new(bulk_rx));
+        let counters = Arc::new(Counters::default());
+
+        let workers = config.workers.max(1);
+        let interactive_workers = config.interactive_workers.min(workers - 1);
+        for index in 0..workers {
+            let worker = Worker {
+                client: client.clone(),
+                interactive_rx: interactive_rx.clone(),
+                bulk_rx: (index >= interactive_workers).then(|| bulk_rx.clone()),
+                config: config.clone(),
+                counters: counters.clone(),
+                carry: None,
+            };
+            tokio::spawn(worker.run());
+        }
+
+        info!(
+            "嵌入工作池已启动: workers={}, interactive_workers={}, max_batch_size={}, max_batch_tokens={}",
+            workers, interactive_workers, config.max_batch_size, config.max_batch_tokens
+        );
+
+        Arc::new(Self { interactive_tx, bulk_tx, config, counters })
+    }
+
+    /// 设置全局工作池，重复设置时返回错误
+    pub fn install_global(pool: Arc<EmbeddingPool>) -> Result<(), AiStudioError> {
+        GLOBAL_EMBEDDING_POOL.set(pool)
+            .map_err(|_| AiStudioError::internal("嵌入工作池已经初始化"))
+    }
+
+    /// 全局工作池，仅在配置了本地推理后端时存在
+    pub fn global() -> Option<Arc<EmbeddingPool>> {
+        GLOBAL_EMBEDDING_POOL.get().cloned()
+    }
+
+    /// 生成单条文本的嵌入向量
+    pub async fn embed(&self, text: &str, priority: EmbeddingPriority) -> Result<Vec<f32>, AiStudioError> {
+        let receiver = self.submit(text.to_string(), priority).await?;
+        receiver.await
+            .map_err(|_| AiStudioError::ai("嵌入工作协程已退出".to_string()))?
+    }
+
+    /// 批量生成嵌入向量，结果顺序与输入一致
+    pub async fn embed_many(&self, texts: &[String], priority: EmbeddingPriority) -> Result<Vec<Vec<f32>>, AiStudioError> {
+        let mut receivers = Vec::with_capacity(texts.len());
+        for text in texts {
+            receivers.push(self.submit(text.clone(), priority).await?);
+        }
+
+        let mut embeddings = Vec::with_capacity(receivers.len());
+        for receiver in receivers {
+            embeddings.push(
+                receiver.await.map_err(|_| AiStudioError::ai("嵌入工作协程已退出".to_string()))??,
+            );
+        }
+        Ok(embeddings)
+    }
+
+    /// 获取运行统计
+    pub fn stats(&self) -> EmbeddingPoolStats {
+        EmbeddingPoolStats {
+            interactive_queued: self.interactive_tx.max_capacity() - self.interactive_tx.capacity(),
+            bulk_queued: self.bulk_tx.max_capacity() - self.bulk_tx.capacity(),
+            batches_processed: self.counters.batches.load(Ordering::Relaxed),
+            texts_processed: self.counters.texts.load(Ordering::Relaxed),
+            failed_batches: self.counters.failures.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 获取配置
+    pub fn config(&self) -> &EmbeddingPoolConfig {
+        &self.config
+    }
+
+    async fn submit(
+        &self,
+        text: String,
+        priority: EmbeddingPriority,
+    ) -> Result<oneshot::Receiver<Result<Vec<f32>, AiStudioError>>, AiStudioError> {
+        let (reply, receiver) = oneshot::channel();
+        let job = EmbeddingJob { tokens: estimate_tokens(&text), text, reply };
+        let sender = match priority {
+            EmbeddingPriority::Interactive => &self.interactive_tx,
+            EmbeddingPriority::Bulk => &self.bulk_tx,
+        };
+
+        // 队列满时在此等待，对批量任务形成背压
+        sender.send(job).await
+            .map_err(|_| AiStudioError::ai("嵌入工作池已关闭".to_string()))?;
+        Ok(receiver)
+    }
+}
+
+impl Worker {
+    async fn run(mut self) {
+        while let Some((priority, batch)) = self.next_batch().await {
+            self.process(priority, batch).await;
+        }
+        debug!("嵌入工作协程退出");
+    }
+
+    /// 取下一批任务：优先交互队列，随后在凑批等待时间内从同一队列继续取任务
+    async fn next_batch(&mut self) -> Option<(EmbeddingPriority, Vec<EmbeddingJob>)> {
+        let (priority, first) = match self.carry.take() {
+            Some(carried) => carried,
+            None => self.next_job().await?,
+        };
+
+        let receiver = match priority {
+            EmbeddingPriority::Interactive => self.interactive_rx.clone(),
+            EmbeddingPriority::Bulk => self.bulk_rx.clone().unwrap_or_else(|| self.interactive_rx.clone()),
+        };
+
+        let mut tokens = first.tokens;
+        let mut batch = vec![first];
+        let deadline = Instant::now() + Duration::from_millis(self.config.batch_wait_ms);
+        let mut rx = receiver.lock().await;
+
+        while batch.len() < self.config.max_batch_size {
+            let job = match rx.try_recv() {
+                Ok(job) => job,
+                Err(_) => match tokio::time::timeout_at(deadline, rx.recv()).await {
+                    Ok(Some(job)) => job,
+                    _ => break,
+                },
+            };
+
+            if tokens + job.tokens > self.config.max_batch_tokens {
+                self.carry = Some((priority, job));
+                break;
+            }
+            tokens += job.tokens;
+            batch.push(job);
+        }
+
+        Some((priority, batch))
+    }
+
+    async fn next_job(&self) -> Option<(EmbeddingPriority, EmbeddingJob)> {
+        let Some(bulk_rx) = &self.bulk_rx else {
+            let job = self.interactive_rx.lock().await.recv().await?;
+            return Some((EmbeddingPriority::Interactive, job));
+        };
+
+        tokio::select! {
+            biased;
+            job = async { self.interactive_rx.lock().await.recv().await } => {
+                job.map(|job| (EmbeddingPriority::Interactive, job))
+            }
+            job = async { bulk_rx.lock().await.recv().await } => {
+                job.map(|job| (EmbeddingPriority::Bulk, job))
+            }
+        }
+    }
+
+    async fn process(&self, priority: EmbeddingPriority, batch: Vec<EmbeddingJob>) {
+        let texts: Vec<String> = batch.iter().map(|job| job.text.clone()).collect();
+        debug!("处理嵌入批次: priority={:?}, size={}", priority, texts.len());
+
+        let result = self.client.generate_embeddings(&texts).await.and_then(|responses| {
+            if responses.len() == batch.len() {
+                Ok(responses)
+            } else {
+                Err(AiStudioError::ai(format!(
+                    "嵌入数量与输入不一致: 期望 {}, 实际 {}", batch.len(), responses.len()
+                )))
+            }
+        });
+
+        self.counters.batches.fetch_add(1, Ordering::Relaxed);
+        match result {
+            Ok(responses) => {
+                self.counters.texts.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                for (job, response) in batch.into_iter().zip(responses) {
+                    let _ = job.reply.send(Ok(response.embedding));
+                }
+            }
+            Err(e) => {
+                self.counters.failures.fetch_add(1, Ordering::Relaxed);
+                warn!("嵌入批次失败: priority={:?}, size={}, error={}", priority, batch.len(), e);
+                for job in batch {
+                    let _ = job.reply.send(Err(replicate_error(&e)));
+                }
+            }
+        }
+    }
+}
+
+/// 粗略估算文本 token 数（约 4 个字符 1 个 token）
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4).max(1)
+}
+
+/// 批次失败时为每个任务复制错误，保留限流语义
+fn replicate_error(error: &AiStudioError) -> AiStudioError {
+    match error {
+        AiStudioError::RateLimit { retry_after } => AiStudioError::rate_limit(*retry_after),
+        other => AiStudioError::ai(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::client::{EmbeddingResponse, GenerationResponse, HealthStatus};
+    use async_trait::async_trait;
+    use std::sync::Mutex as StdMutex;
+
+    /// 记录每次调用批次大小的嵌入客户端
+    struct RecordingClient {
+        batches: StdMutex<Vec<usize>>,
+        delay: Duration,
+    }
+
+    impl RecordingClient {
+        fn new(delay: Duration) -> Arc<Self> {
+            Arc::new(Self { batches: StdMutex::new(Vec::new()), delay })
+        }
+    }
+
+    #[async_trait]
+    impl AiClient for RecordingClient {
+        async fn generate_text(&self, _prompt: &str) -> Result<GenerationResponse, AiStudioError> {
+            Err(AiStudioError::ai("不支持".to_string()))
+        }
+
+        async fn generate_embedding(&self, text: &str) -> Result<EmbeddingResponse, AiStudioError> {
+            Ok(self.generate_embeddings(&[text.to_string()]).await?.remove(0))
+        }
+
+        async fn generate_embeddings(&self, texts: &[String]) -> Result<Vec<EmbeddingResponse>, AiStudioError> {
+            self.batches.lock().unwrap().push(texts.len());
+            tokio::time::sleep(self.delay).await;
+            Ok(texts.iter().map(|text| EmbeddingResponse {
+                embedding: vec![text.len() as f32],
+                model: "recording".to_string(),
+                tokens_used: 0,
+                metadata: serde_json::Value::Null,
+            }).collect())
+        }
+
+        async fn health_check(&self) -> Result<HealthStatus, AiStudioError> {
+            Err(AiStudioError::ai("不支持".to_string()))
+        }
+    }
+
+    fn config(workers: usize, interactive_workers: usize, max_batch_size: usize) -> EmbeddingPoolConfig {
+        EmbeddingPoolConfig {
+            workers,
+            interactive_workers,
+            max_batch_size,
+            max_batch_tokens: 1000,
+            batch_wait_ms: 20,
+            queue_capacity: 64,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batches_preserve_order() {
+        let client = RecordingClient::new(Duration::from_millis(1));
+        let pool = EmbeddingPool::start(client.clone(), config(2, 1, 4));
+
+        let texts: Vec<String> = (1..=10).map(|n| "a".repeat(n)).collect();
+        let embeddings = pool.embed_many(&texts, EmbeddingPriority::Bulk).await.unwrap();
+
+        assert_eq!(embeddings, (1..=10).map(|n| vec![n as f32]).collect::<Vec<_>>());
+        let batches = client.batches.lock().unwrap().clone();
+        assert!(batches.iter().all(|size| *size <= 4));
+        assert_eq!(batches.iter().sum::<usize>(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_batch_token_limit() {
+        let client = RecordingClient::new(Duration::from_millis(1));
+        let mut config = config(2, 1, 32);
+        config.max_batch_tokens = 10;
+        let pool = EmbeddingPool::start(client.clone(), config);
+
+        // 每条约 5 个 token，每批最多 2 条
+        let texts = vec!["x".repeat(20); 6];
+        pool.embed_many(&texts, EmbeddingPriority::Bulk).await.unwrap();
+
+        assert!(client.batches.lock().unwrap().iter().all(|size| *size <= 2));
+    }
+
+    #[tokio::test]
+    async fn test_interactive_not_starved_by_bulk() {
+        let client = RecordingClient::new(Duration::from_millis(20));
+        let pool = EmbeddingPool::start(client.clone(), config(2, 1, 1));
+
+        let bulk_pool = pool.clone();
+        let bulk = tokio::spawn(async move {
+            let texts = vec!["bulk".to_string(); 20];
+            bulk_pool.embed_many(&texts, EmbeddingPriority::Bulk).await
+        });
+        tokio::time::sleep(Duration::from_millis(30)).await;
+
+        let embedding = pool.embed("question", EmbeddingPriority::Interactive).await.unwrap();
+        assert_eq!(embedding, vec![8.0]);
+        assert!(pool.stats().texts_processed < 20);
+
+        bulk.await.unwrap().unwrap();
+        assert_eq!(pool.stats().texts_processed, 21);
+    }
+}
@@ -365,6 +365,7 @@ mod tests {
             temperature: 0.7,
             timeout: 30,
             retry_attempts: 3,
+            local: None,
         };
         
         let client_manager = Arc::new(AiClientManager::new(config).unwrap());
@@ -386,6 +387,7 @@ mod tests {
             temperature: 0.7,
             timeout: 30,
             retry_attempts: 3,
+            local: None,
         };
         
         let client_manager = Arc::new(AiClientManager::new(config).unwrap());
@@ -410,6 +412,7 @@ mod tests {
             temperature: 0.7,
             timeout: 30,
             retry_attempts: 3,
+            local: None,
         };
         
         let client_manager = Arc::new(AiClientManager::new(config).unwrap());
@@ -0,0 +1,264 @@
+// 本地推理客户端
+// 对接 vLLM 的 OpenAI 兼容服务和 llama.cpp HTTP 服务，满足完全本地化部署的租户
+
+use crate::ai::client::{AiClient, EmbeddingResponse, GenerationResponse, HealthStatus};
+use crate::config::{AiConfig, LocalInferenceBackend, LocalInferenceConfig};
+use crate::errors::AiStudioError;
+use crate::services::egress;
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// 单次嵌入请求的最大文本数
+const EMBEDDING_BATCH_SIZE: usize = 32;
+
+/// 本地推理客户端
+///
+/// 两种后端都通过 `/v1/chat/completions` 与 `/v1/embeddings` 调用；
+/// llama.cpp 需以 `--embeddings` 启动才能提供嵌入接口。
+pub struct LocalInferenceClient {
+    config: Arc<AiConfig>,
+    local: LocalInferenceConfig,
+    http_client: reqwest::Client,
+}
+
+impl LocalInferenceClient {
+    pub fn new(config: Arc<AiConfig>) -> Result<Self, AiStudioError> {
+        let local = config.local.clone()
+            .ok_or_else(|| AiStudioError::ai("未配置本地推理后端".to_string()))?;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(api_key) = local.api_key.as_deref().filter(|k| !k.is_empty()) {
+            headers.insert(
+                reqwest::header::AUTHORIZATION,
+                reqwest::header::HeaderValue::from_str(&format!("Bearer {}", api_key))
+                    .map_err(|e| AiStudioError::ai(format!("无效的本地推理 API 密钥: {}", e)))?,
+            );
+        }
+
+        let http_client = egress::client_builder()?
+            .timeout(Duration::from_secs(config.timeout))
+            .default_headers(headers)
+            .build()
+            .map_err(|e| AiStudioError::ai(format!("创建 HTTP 客户端失败: {}", e)))?;
+
+        Ok(Self { config, local, http_client })
+    }
+
+    fn backend_name(&self) -> &'static str {
+        match self.local.backend {
+            LocalInferenceBackend::Vllm => "vllm",
+            LocalInferenceBackend::LlamaCpp => "llama.cpp",
+        }
+    }
+
+    fn completion_url(&self, path: &str) -> String {
+        format!("{}{}", self.local.base_url.trim_end_matches('/'), path)
+    }
+
+    fn embedding_url(&self) -> String {
+        let base = self.local.embedding_base_url.as_deref().unwrap_or(&self.local.base_url);
+        format!("{}/v1/embeddings", base.trim_end_matches('/'))
+    }
+
+    fn embedding_model(&self) -> &str {
+        self.local.embedding_model_path.as_deref().unwrap_or(&self.local.model_path)
+    }
+
+    async fn post_json(&self, url: &str, body: &serde_json::Value) -> Result<serde_json::Value, AiStudioError> {
+        let response = self.http_client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| AiStudioError::ai(format!("{} 请求失败: {}", self.backend_name(), e)))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response.headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok());
+            return Err(AiStudioError::rate_limit(retry_after));
+        }
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AiStudioError::ai(format!("{} 错误 ({}): {}", self.backend_name(), status, error_text)));
+        }
+
+        response.json().await
+            .map_err(|e| AiStudioError::ai(format!("解析 {} 响应失败: {}", self.backend_name(), e)))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<EmbeddingResponse>, AiStudioError> {
+        let request_body = serde_json::json!({
+            "model": self.embedding_model(),
+            "input": texts
+        });
+
+        let response_json = self.post_json(&self.embedding_url(), &request_body).await?;
+        let embeddings = parse_embeddings(&response_json, self.embedding_model())?;
+        if embeddings.len() != texts.len() {
+            return Err(AiStudioError::ai(format!(
+                "嵌入数量与输入不一致: 期望 {}, 实际 {}", texts.len(), embeddings.len()
+            )));
+        }
+        Ok(embeddings)
+    }
+}
+
+#[async_trait]
+impl AiClient for LocalInferenceClient {
+    async fn generate_text(&self, prompt: &str) -> Result<GenerationResponse, AiStudioError> {
+        debug!("使用 {} 生成文本，提示词长度: {}", self.backend_name(), prompt.len());
+
+        let request_body = serde_json::json!({
+            "model": self.local.model_path,
+            "messages": [
+                {"role": "user", "content": prompt}
+            ],
+            "max_tokens": self.config.max_tokens,
+            "temperature": self.config.temperature
+        });
+
+        let response_json = self.post_json(&self.completion_url("/v1/chat/completions"), &request_body).await?;
+        parse_chat_completion(response_json, &self.local.model_path)
+    }
+
+    async fn generate_embedding(&self, text: &str) -> Result<EmbeddingResponse, AiStudioError> {
+        debug!("使用 {} 生成嵌入向量，文本长度: {}", self.backend_name(), text.len());
+
+        self.embed_batch(&[text.to_string()]).await?
+            .pop()
+            .ok_or_else(|| AiStudioError::ai("未生成嵌入向量".to_string()))
+    }
+
+    async fn generate_embeddings(&self, texts: &[String]) -> Result<Vec<EmbeddingResponse>, AiStudioError> {
+        let mut results = Vec::with_capacity(texts.len());
+        for chunk in texts.chunks(EMBEDDING_BATCH_SIZE) {
+            results.extend(self.embed_batch(chunk).await?);
+        }
+        Ok(results)
+    }
+
+    async fn health_check(&self) -> Result<HealthStatus, AiStudioError> {
+        let start_time = std::time::Instant::now();
+
+        // 两种后端都提供 /health；llama.cpp 在模型加载期间返回 503
+        let response = self.http_client
+            .get(self.completion_url("/health"))
+            .send()
+            .await
+            .map_err(|e| AiStudioError::ai(format!("{} 健康检查失败: {}", self.backend_name(), e)))?;
+
+        let latency_ms = start_time.elapsed().as_millis() as u64;
+
+        if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+            warn!("{} 服务尚未就绪（模型加载中）", self.backend_name());
+            return Err(AiStudioError::ai(format!("{} 服务尚未就绪", self.backend_name())));
+        }
+        if !response.status().is_success() {
+            return Err(AiStudioError::ai(format!("{} 服务不可用", self.backend_name())));
+        }
+
+        let version = match self.local.backend {
+            LocalInferenceBackend::Vllm => self.fetch_vllm_version().await,
+            LocalInferenceBackend::LlamaCpp => None,
+        };
+
+        Ok(HealthStatus {
+            status: "healthy".to_string(),
+            model: self.local.model_path.clone(),
+            version,
+            latency_ms,
+        })
+    }
+}
+
+impl LocalInferenceClient {
+    async fn fetch_vllm_version(&self) -> Option<String> {
+        let response = self.http_client.get(self.completion_url("/version")).send().await.ok()?;
+        let body: serde_json::Value = response.json().await.ok()?;
+        body["version"].as_str().map(str::to_string)
+    }
+}
+
+/// 解析 OpenAI 兼容的对话补全响应
+fn parse_chat_completion(response_json: serde_json::Value, model_path: &str) -> Result<GenerationResponse, AiStudioError> {
+    let choice = &response_json["choices"][0];
+    let text = choice["message"]["content"].as_str()
+        .ok_or_else(|| AiStudioError::ai("本地推理响应缺少生成内容".to_string()))?
+        .to_string();
+
+    Ok(GenerationResponse {
+        text,
+        model: response_json["model"].as_str().unwrap_or(model_path).to_string(),
+        tokens_used: response_json["usage"]["total_tokens"].as_u64().unwrap_or(0) as u32,
+        finish_reason: choice["finish_reason"].as_str().unwrap_or("unknown").to_string(),
+        metadata: response_json,
+    })
+}
+
+/// 解析 OpenAI 兼容的嵌入响应，按 `index` 还原输入顺序
+fn parse_embeddings(response_json: &serde_json::Value, model_path: &str) -> Result<Vec<EmbeddingResponse>, AiStudioError> {
+    let data = response_json["data"]
+        .as_array()
+        .ok_or_else(|| AiStudioError::ai("嵌入响应格式错误".to_string()))?;
+
+    let mut items: Vec<(u64, EmbeddingResponse)> = Vec::with_capacity(data.len());
+    for (position, item) in data.iter().enumerate() {
+        let embedding: Vec<f32> = item["embedding"]
+            .as_array()
+            .ok_or_else(|| AiStudioError::ai("嵌入向量格式错误".to_string()))?
+            .iter()
+            .map(|v| v.as_f64().unwrap_or(0.0) as f32)
+            .collect();
+
+        items.push((
+            item["index"].as_u64().unwrap_or(position as u64),
+            EmbeddingResponse {
+                embedding,
+                model: response_json["model"].as_str().unwrap_or(model_path).to_string(),
+                tokens_used: 0, // 总 token 数在响应的 usage 字段中
+                metadata: serde_json::json!({ "index": item["index"] }),
+            },
+        ));
+    }
+    items.sort_by_key(|(index, _)| *index);
+
+    Ok(items.into_iter().map(|(_, embedding)| embedding).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_chat_completion() {
+        let response = parse_chat_completion(json!({
+            "model": "/models/qwen2-7b",
+            "choices": [{ "message": { "role": "assistant", "content": "你好" }, "finish_reason": "stop" }],
+            "usage": { "total_tokens": 12 }
+        }), "/models/fallback").unwrap();
+        assert_eq!(response.text, "你好");
+        assert_eq!(response.model, "/models/qwen2-7b");
+        assert_eq!(response.tokens_used, 12);
+
+        assert!(parse_chat_completion(json!({ "choices": [] }), "/models/fallback").is_err());
+    }
+
+    #[test]
+    fn test_parse_embeddings_restores_order() {
+        let embeddings = parse_embeddings(&json!({
+            "data": [
+                { "index": 1, "embedding": [0.3, 0.4] },
+                { "index": 0, "embedding": [0.1, 0.2] }
+            ]
+        }), "/models/bge-m3").unwrap();
+        assert_eq!(embeddings.len(), 2);
+        assert_eq!(embeddings[0].embedding, vec![0.1, 0.2]);
+        assert_eq!(embeddings[1].model, "/models/bge-m3");
+    }
+}
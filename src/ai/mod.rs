@@ -2,30 +2,56 @@
 // 包含 AI 相关功能和 Rig 框架集成
 
 pub mod client;
+pub mod local_inference;
 pub mod models;
+pub mod model_policy;
+pub mod model_router;
 pub mod health;
 pub mod document_processor;
 pub mod chunker;
+pub mod table_extraction;
+pub mod embedding_pool;
 pub mod vector_search;
 pub mod rig_client;
 pub mod rag_engine;
+pub mod query_rewrite;
+pub mod retrieval_explain;
+pub mod pii;
+pub mod structured_output;
+pub mod answer_confidence;
 pub mod agent_runtime;
+pub mod agent_stream;
 pub mod tools;
 pub mod tool_manager;
 pub mod tool_loader;
+pub mod tool_output;
 pub mod workflow_engine;
 pub mod workflow_executor;
+pub mod workflow_inputs;
+pub mod workflow_document;
+pub mod workflow_knowledge_search;
+pub mod workflow_concurrency;
+pub mod workflow_secrets;
+pub mod vector_store;
 
 pub use client::*;
+pub use local_inference::*;
 pub use models::*;
+pub use model_policy::*;
+pub use model_router::*;
 pub use health::*;
 pub use document_processor::*;
 pub use chunker::*;
+pub use embedding_pool::*;
 pub use vector_search::*;
 pub use rig_client::*;
 pub use rag_engine::*;
+pub use structured_output::*;
+pub use answer_confidence::*;
 pub use agent_runtime::*;
 pub use tools::*;
 pub use tool_manager::*;
 pub use tool_loader::*;
-pub use workflow_engine::*;
\ No newline at end of file
+pub use tool_output::*;
+pub use workflow_engine::*;
+pub use workflow_inputs::*;
\ No newline at end of file
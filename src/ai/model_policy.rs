@@ -0,0 +1,145 @@
+// 租户模型准入策略
+// 判断模型是否符合租户的提供商与模型白名单/黑名单，并在调用模型前强制执行
+
+use sea_orm::{DatabaseConnection, EntityTrait};
+use uuid::Uuid;
+
+use crate::ai::models::ModelManager;
+use crate::db::entities::prelude::Tenant;
+use crate::db::entities::tenant::TenantModelPolicy;
+use crate::errors::AiStudioError;
+
+/// 本地部署的提供商，关闭外部 API 时只允许这些提供商
+pub const LOCAL_PROVIDERS: &[&str] = &["ollama", "local", "vllm", "llama.cpp"];
+
+/// 判断模型是否被策略允许，不允许时返回原因
+///
+/// `provider` 为空时先从模型 ID 前缀推断，再到内置模型目录中按模型名查找。
+pub fn evaluate_model_policy(
+    policy: &TenantModelPolicy,
+    model: &str,
+    provider: Option<&str>,
+) -> Result<(), String> {
+    let model = model.trim();
+    let name = model.rsplit_once('/').map(|(_, name)| name).unwrap_or(model);
+    let provider = provider.map(str::to_string).or_else(|| resolve_provider(model));
+
+    let matches = |entry: &String| entry.eq_ignore_ascii_case(model) || entry.eq_ignore_ascii_case(name);
+    if policy.denied_models.iter().any(matches) {
+        return Err("模型在租户禁止列表中".to_string());
+    }
+    if !policy.allowed_models.is_empty() && !policy.allowed_models.iter().any(matches) {
+        return Err("模型不在租户允许列表中".to_string());
+    }
+
+    let provider_restricted = !policy.allowed_providers.is_empty()
+        || !policy.denied_providers.is_empty()
+        || !policy.allow_external_apis;
+    if !provider_restricted {
+        return Ok(());
+    }
+
+    let Some(provider) = provider else {
+        return Err("无法确定模型的提供商".to_string());
+    };
+    if policy.denied_providers.iter().any(|p| p.eq_ignore_ascii_case(&provider)) {
+        return Err(format!("提供商 {} 在租户禁止列表中", provider));
+    }
+    if !policy.allowed_providers.is_empty()
+        && !policy.allowed_providers.iter().any(|p| p.eq_ignore_ascii_case(&provider))
+    {
+        return Err(format!("提供商 {} 不在租户允许列表中", provider));
+    }
+    if !policy.allow_external_apis && !LOCAL_PROVIDERS.iter().any(|p| p.eq_ignore_ascii_case(&provider)) {
+        return Err(format!("租户禁止调用外部 API，提供商 {} 不是本地部署", provider));
+    }
+
+    Ok(())
+}
+
+/// 检查模型是否被策略允许，不允许时返回指向 `field` 的校验错误
+pub fn check_model_allowed(
+    policy: &TenantModelPolicy,
+    field: &str,
+    model: &str,
+) -> Result<(), AiStudioError> {
+    evaluate_model_policy(policy, model, None).map_err(|reason| {
+        AiStudioError::validation(field, format!("模型 {} 不符合租户的模型使用策略: {}", model, reason))
+    })
+}
+
+/// 加载租户的模型准入策略，租户不存在时返回未找到错误
+pub async fn load_tenant_model_policy(
+    db: &DatabaseConnection,
+    tenant_id: Uuid,
+) -> Result<TenantModelPolicy, AiStudioError> {
+    let tenant = Tenant::find_by_id(tenant_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+    tenant.get_config()
+        .map(|config| config.model_policy)
+        .map_err(|e| AiStudioError::internal(format!("解析租户配置失败: {}", e)))
+}
+
+/// 推断模型提供商：优先取 `provider/model` 前缀，否则按模型名在内置模型目录中查找
+fn resolve_provider(model: &str) -> Option<String> {
+    if let Some((provider, _)) = model.split_once('/') {
+        if !provider.is_empty() {
+            return Some(provider.to_string());
+        }
+    }
+
+    ModelManager::new()
+        .get_all_models()
+        .values()
+        .find(|info| info.id.rsplit_once('/').is_some_and(|(_, name)| name.eq_ignore_ascii_case(model)))
+        .map(|info| info.provider.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> TenantModelPolicy {
+        TenantModelPolicy::default()
+    }
+
+    #[test]
+    fn test_default_policy_allows_everything() {
+        assert!(evaluate_model_policy(&policy(), "openai/gpt-3.5-turbo", None).is_ok());
+        assert!(evaluate_model_policy(&policy(), "some-unknown-model", None).is_ok());
+    }
+
+    #[test]
+    fn test_model_lists_match_id_or_name() {
+        let mut policy = policy();
+        policy.denied_models = vec!["GPT-3.5-turbo".to_string()];
+        assert!(evaluate_model_policy(&policy, "openai/gpt-3.5-turbo", None).is_err());
+
+        policy.denied_models.clear();
+        policy.allowed_models = vec!["ollama/llama2".to_string()];
+        assert!(evaluate_model_policy(&policy, "ollama/llama2", None).is_ok());
+        assert!(evaluate_model_policy(&policy, "gpt-3.5-turbo", None).is_err());
+    }
+
+    #[test]
+    fn test_provider_restrictions() {
+        let mut policy = policy();
+        policy.allow_external_apis = false;
+        assert!(evaluate_model_policy(&policy, "ollama/llama2", None).is_ok());
+        // 通过模型目录识别出 openai
+        assert!(evaluate_model_policy(&policy, "text-embedding-ada-002", None).is_err());
+        // 提供商无法确定时拒绝
+        assert!(evaluate_model_policy(&policy, "mystery-model", None).is_err());
+
+        let mut policy = TenantModelPolicy::default();
+        policy.allowed_providers = vec!["qwen".to_string()];
+        assert!(evaluate_model_policy(&policy, "qwen/qwen-max", None).is_ok());
+        assert!(evaluate_model_policy(&policy, "gpt-4o", Some("openai")).is_err());
+
+        let error = check_model_allowed(&policy, "embedding_model", "openai/text-embedding-ada-002").unwrap_err();
+        assert!(matches!(error, AiStudioError::Validation { .. }));
+    }
+}
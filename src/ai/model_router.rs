@@ -0,0 +1,364 @@
+// 成本感知的模型路由
+// 按任务类型、所需上下文长度和租户套餐选择成本最低的可用模型，并在模型被限流时回退
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+
+use crate::ai::models::{ModelInfo, ModelManager, ModelType};
+use crate::ai::model_policy::evaluate_model_policy;
+use crate::db::entities::tenant::{TenantModelPolicy, TenantModelRouting, TenantPlan};
+use crate::errors::AiStudioError;
+
+/// 未给出 `retry_after` 时模型的默认冷却时间
+const DEFAULT_RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// 路由决策中最多返回的备选模型数
+const MAX_FALLBACKS: usize = 3;
+
+static GLOBAL_MODEL_ROUTER: Lazy<ModelRouter> = Lazy::new(ModelRouter::default);
+
+/// 路由任务类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingTask {
+    /// 知识库问答
+    Qa,
+    /// 摘要生成
+    Summarization,
+    /// Agent 推理（需要函数调用能力）
+    AgentReasoning,
+    /// 文本嵌入
+    Embedding,
+}
+
+impl RoutingTask {
+    /// 任务名称，与租户覆盖设置中的键一致
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Qa => "qa",
+            Self::Summarization => "summarization",
+            Self::AgentReasoning => "agent_reasoning",
+            Self::Embedding => "embedding",
+        }
+    }
+
+    fn model_type(&self) -> ModelType {
+        match self {
+            Self::Embedding => ModelType::Embedding,
+            _ => ModelType::TextGeneration,
+        }
+    }
+
+    fn required_capability(&self) -> &'static str {
+        match self {
+            Self::Qa | Self::Summarization => "text-generation",
+            Self::AgentReasoning => "function-calling",
+            Self::Embedding => "text-embedding",
+        }
+    }
+}
+
+/// 路由请求
+#[derive(Debug, Clone)]
+pub struct RoutingRequest<'a> {
+    /// 任务类型
+    pub task: RoutingTask,
+    /// 所需上下文长度（token）
+    pub required_context_tokens: u32,
+    /// 租户套餐
+    pub plan: TenantPlan,
+    /// 租户覆盖设置
+    pub overrides: Option<&'a TenantModelRouting>,
+    /// 租户模型准入策略
+    pub policy: Option<&'a TenantModelPolicy>,
+}
+
+/// 选择原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteReason {
+    /// 租户固定的模型
+    Pinned,
+    /// 成本最低的可用模型
+    Cheapest,
+    /// 首选模型被限流，使用备选模型
+    Fallback,
+}
+
+/// 路由决策
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RouteDecision {
+    /// 选中的模型 ID
+    pub model_id: String,
+    /// 模型提供商
+    pub provider: String,
+    /// 每千 token 估算成本（输入 + 输出单价）
+    pub estimated_cost_per_1k: f64,
+    /// 选择原因
+    pub reason: RouteReason,
+    /// 按顺序排列的备选模型
+    pub fallbacks: Vec<String>,
+}
+
+/// 模型路由器
+pub struct ModelRouter {
+    /// 模型目录
+    catalog: ModelManager,
+    /// 各模型要求的最低套餐，未列出的模型对所有套餐开放
+    min_plans: HashMap<String, TenantPlan>,
+    /// 被限流模型的冷却截止时间
+    cooldowns: RwLock<HashMap<String, Instant>>,
+}
+
+impl Default for ModelRouter {
+    fn default() -> Self {
+        let min_plans = HashMap::from([
+            ("openai/gpt-3.5-turbo".to_string(), TenantPlan::Standard),
+        ]);
+        Self::new(ModelManager::new(), min_plans)
+    }
+}
+
+impl ModelRouter {
+    /// 创建新的模型路由器
+    pub fn new(catalog: ModelManager, min_plans: HashMap<String, TenantPlan>) -> Self {
+        Self {
+            catalog,
+            min_plans,
+            cooldowns: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 全局模型路由器，限流状态在进程内共享
+    pub fn global() -> &'static ModelRouter {
+        &GLOBAL_MODEL_ROUTER
+    }
+
+    /// 选择模型
+    ///
+    /// 候选顺序：租户固定模型（或成本最低的模型）→ 租户备选模型 → 其余模型按成本升序。
+    /// 不满足能力、上下文长度、套餐要求、租户准入策略或被租户排除的模型不参与路由；处于限流冷却中的模型被跳过。
+    pub async fn route(&self, request: &RoutingRequest<'_>) -> Result<RouteDecision, AiStudioError> {
+        let overrides = request.overrides.cloned().unwrap_or_default();
+
+        let mut eligible: Vec<&ModelInfo> = self.catalog
+            .get_models_by_type(&request.task.model_type())
+            .into_iter()
+            .filter(|model| self.is_eligible(model, request, &overrides))
+            .collect();
+        if eligible.is_empty() {
+            return Err(AiStudioError::validation(
+                "task",
+                format!("没有满足 {} 任务要求的可用模型", request.task.as_str()),
+            ));
+        }
+        eligible.sort_by(|a, b| {
+            blended_cost(a).partial_cmp(&blended_cost(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        let pinned = overrides.pinned_models.get(request.task.as_str())
+            .and_then(|id| eligible.iter().position(|model| &model.id == id));
+        let (preferred_reason, preferred) = match pinned {
+            Some(index) => (RouteReason::Pinned, eligible.remove(index)),
+            None => (RouteReason::Cheapest, eligible.remove(0)),
+        };
+
+        let mut ordered = vec![preferred];
+        for id in &overrides.fallback_models {
+            if let Some(index) = eligible.iter().position(|model| &model.id == id) {
+                ordered.push(eligible.remove(index));
+            }
+        }
+        ordered.extend(eligible);
+
+        let now = Instant::now();
+        let cooldowns = self.cooldowns.read().await;
+        let cooling = |model: &&ModelInfo| cooldowns.get(&model.id).is_some_and(|until| *until > now);
+
+        let Some(selected) = ordered.iter().position(|model| !cooling(model)) else {
+            let retry_after = ordered.iter()
+                .filter_map(|model| cooldowns.get(&model.id))
+                .min()
+                .map(|until| until.saturating_duration_since(now).as_secs().max(1));
+            warn!("所有候选模型均处于限流冷却中: task={}", request.task.as_str());
+            return Err(AiStudioError::rate_limit(retry_after));
+        };
+
+        let model = ordered[selected];
+        let reason = if selected == 0 { preferred_reason } else { RouteReason::Fallback };
+        let fallbacks = ordered.iter()
+            .skip(selected + 1)
+            .filter(|model| !cooling(model))
+            .take(MAX_FALLBACKS)
+            .map(|model| model.id.clone())
+            .collect();
+
+        debug!("模型路由: task={}, model={}, reason={:?}", request.task.as_str(), model.id, reason);
+        Ok(RouteDecision {
+            model_id: model.id.clone(),
+            provider: model.provider.clone(),
+            estimated_cost_per_1k: blended_cost(model),
+            reason,
+            fallbacks,
+        })
+    }
+
+    /// 标记模型被限流，冷却期内路由将跳过该模型
+    pub async fn mark_rate_limited(&self, model_id: &str, retry_after: Option<Duration>) {
+        let until = Instant::now() + retry_after.unwrap_or(DEFAULT_RATE_LIMIT_COOLDOWN);
+        warn!("模型被限流，进入冷却: model={}, until={:?}", model_id, until);
+        self.cooldowns.write().await.insert(model_id.to_string(), until);
+    }
+
+    /// 清除模型的限流冷却
+    pub async fn clear_rate_limit(&self, model_id: &str) {
+        self.cooldowns.write().await.remove(model_id);
+    }
+
+    fn is_eligible(&self, model: &ModelInfo, request: &RoutingRequest<'_>, overrides: &TenantModelRouting) -> bool {
+        let required_plan = self.min_plans.get(&model.id).copied().unwrap_or(TenantPlan::Free);
+
+        model.capabilities.iter().any(|c| c == request.task.required_capability())
+            && model.context_length >= request.required_context_tokens
+            && request.plan >= required_plan
+            && !overrides.excluded_models.contains(&model.id)
+            && request.policy.is_none_or(|policy| {
+                evaluate_model_policy(policy, &model.id, Some(&model.provider)).is_ok()
+            })
+    }
+}
+
+/// 每千 token 的输入与输出单价之和，未定价的模型（如本地模型）视为零成本
+fn blended_cost(model: &ModelInfo) -> f64 {
+    model.pricing.as_ref()
+        .map(|p| p.input_tokens_per_1k + p.output_tokens_per_1k)
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::models::ModelPricing;
+
+    fn model(id: &str, context_length: u32, price: Option<f64>, capabilities: &[&str]) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            model_type: ModelType::TextGeneration,
+            provider: "test".to_string(),
+            version: None,
+            description: None,
+            max_tokens: 4096,
+            context_length,
+            embedding_dimension: None,
+            supported_languages: Vec::new(),
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            pricing: price.map(|p| ModelPricing {
+                input_tokens_per_1k: p,
+                output_tokens_per_1k: p,
+                currency: "USD".to_string(),
+            }),
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn router() -> ModelRouter {
+        let mut catalog = ModelManager::new();
+        // 屏蔽内置模型，只保留测试模型参与路由
+        let builtin: Vec<ModelInfo> = catalog.get_all_models().values().cloned().collect();
+        for mut model in builtin {
+            model.capabilities.clear();
+            catalog.register_model(model);
+        }
+        catalog.register_model(model("small", 4096, Some(0.001), &["text-generation"]));
+        catalog.register_model(model("large", 128_000, Some(0.01), &["text-generation", "function-calling"]));
+        catalog.register_model(model("premium", 200_000, Some(0.03), &["text-generation", "function-calling"]));
+
+        ModelRouter::new(catalog, HashMap::from([("premium".to_string(), TenantPlan::Enterprise)]))
+    }
+
+    fn request(task: RoutingTask, context: u32, plan: TenantPlan) -> RoutingRequest<'static> {
+        RoutingRequest { task, required_context_tokens: context, plan, overrides: None, policy: None }
+    }
+
+    #[tokio::test]
+    async fn test_routes_to_cheapest_capable_model() {
+        let router = router();
+
+        let qa = router.route(&request(RoutingTask::Qa, 2000, TenantPlan::Free)).await.unwrap();
+        assert_eq!(qa.model_id, "small");
+        assert_eq!(qa.reason, RouteReason::Cheapest);
+
+        let long_context = router.route(&request(RoutingTask::Summarization, 100_000, TenantPlan::Free)).await.unwrap();
+        assert_eq!(long_context.model_id, "large");
+
+        let agent = router.route(&request(RoutingTask::AgentReasoning, 1000, TenantPlan::Enterprise)).await.unwrap();
+        assert_eq!(agent.model_id, "large");
+        assert_eq!(agent.fallbacks, vec!["premium".to_string()]);
+
+        // 免费套餐无法使用企业版模型
+        assert!(router.route(&request(RoutingTask::Qa, 150_000, TenantPlan::Free)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tenant_overrides() {
+        let router = router();
+        let overrides = TenantModelRouting {
+            pinned_models: HashMap::from([("qa".to_string(), "large".to_string())]),
+            excluded_models: vec!["small".to_string()],
+            fallback_models: Vec::new(),
+        };
+        let request = RoutingRequest {
+            task: RoutingTask::Summarization,
+            required_context_tokens: 1000,
+            plan: TenantPlan::Standard,
+            overrides: Some(&overrides),
+            policy: None,
+        };
+        // small 被排除
+        assert_eq!(router.route(&request).await.unwrap().model_id, "large");
+
+        let qa = RoutingRequest { task: RoutingTask::Qa, ..request };
+        let decision = router.route(&qa).await.unwrap();
+        assert_eq!(decision.model_id, "large");
+        assert_eq!(decision.reason, RouteReason::Pinned);
+    }
+
+    #[tokio::test]
+    async fn test_respects_tenant_model_policy() {
+        let router = router();
+        let policy = TenantModelPolicy {
+            denied_models: vec!["small".to_string()],
+            ..TenantModelPolicy::default()
+        };
+        let request = RoutingRequest {
+            policy: Some(&policy),
+            ..request(RoutingTask::Qa, 1000, TenantPlan::Standard)
+        };
+        assert_eq!(router.route(&request).await.unwrap().model_id, "large");
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_when_rate_limited() {
+        let router = router();
+        router.mark_rate_limited("small", Some(Duration::from_secs(30))).await;
+
+        let decision = router.route(&request(RoutingTask::Qa, 1000, TenantPlan::Standard)).await.unwrap();
+        assert_eq!(decision.model_id, "large");
+        assert_eq!(decision.reason, RouteReason::Fallback);
+
+        router.mark_rate_limited("large", None).await;
+        let error = router.route(&request(RoutingTask::Qa, 1000, TenantPlan::Standard)).await.unwrap_err();
+        assert!(matches!(error, AiStudioError::RateLimit { retry_after: Some(_) }));
+
+        router.clear_rate_limit("small").await;
+        assert_eq!(router.route(&request(RoutingTask::Qa, 1000, TenantPlan::Standard)).await.unwrap().model_id, "small");
+    }
+}
@@ -0,0 +1,111 @@
+// 个人信息检测
+// 识别文本中的邮箱、IPv4 地址、手机号、身份证号与银行卡号，并替换为占位符
+
+use std::collections::BTreeMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::db::entities::knowledge_base::PiiKind;
+
+static EMAIL_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}").unwrap());
+static IPV4_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\d{1,3}\.\d{1,3}\.\d{1,3}\.\d{1,3}").unwrap());
+static DASHED_PHONE_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"1[3-9]\d[- ]\d{4}[- ]\d{4}").unwrap());
+static DIGIT_RUN_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\d+[Xx]?").unwrap());
+
+/// 检测结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PiiScan {
+    /// 替换为占位符后的文本
+    pub masked: String,
+    /// 各类型的命中次数
+    pub counts: BTreeMap<PiiKind, u32>,
+}
+
+impl PiiScan {
+    /// 是否检测到个人信息
+    pub fn found(&self) -> bool {
+        !self.counts.is_empty()
+    }
+
+    /// 命中总次数
+    pub fn total(&self) -> u32 {
+        self.counts.values().sum()
+    }
+
+    /// 命中的类型
+    pub fn kinds(&self) -> Vec<PiiKind> {
+        self.counts.keys().copied().collect()
+    }
+}
+
+/// 个人信息类型的占位符
+pub fn placeholder(kind: PiiKind) -> &'static str {
+    match kind {
+        PiiKind::Email => "[EMAIL]",
+        PiiKind::IpAddress => "[IP]",
+        PiiKind::Phone => "[PHONE]",
+        PiiKind::IdNumber => "[ID_NUMBER]",
+        PiiKind::CardNumber => "[CARD_NUMBER]",
+    }
+}
+
+/// 检测并替换指定类型的个人信息
+///
+/// 先匹配邮箱、IP 与带分隔符的手机号，再按连续数字的长度识别身份证号、手机号与银行卡号。
+pub fn scan_pii(text: &str, detectors: &[PiiKind]) -> PiiScan {
+    let mut counts: BTreeMap<PiiKind, u32> = BTreeMap::new();
+
+    let mut replace = |pattern: &Regex, text: &str, kind: PiiKind| -> String {
+        if !detectors.contains(&kind) {
+            return text.to_string();
+        }
+        pattern.replace_all(text, |_: &regex::Captures| {
+            *counts.entry(kind).or_default() += 1;
+            placeholder(kind).to_string()
+        }).into_owned()
+    };
+    let text = replace(&EMAIL_PATTERN, text, PiiKind::Email);
+    let text = replace(&IPV4_PATTERN, &text, PiiKind::IpAddress);
+    let text = replace(&DASHED_PHONE_PATTERN, &text, PiiKind::Phone);
+
+    let masked = DIGIT_RUN_PATTERN.replace_all(&text, |caps: &regex::Captures| {
+        let digits = &caps[0];
+        let kind = match digits.len() {
+            18 => Some(PiiKind::IdNumber),
+            11 if digits.starts_with('1') && matches!(digits.as_bytes()[1], b'3'..=b'9') => Some(PiiKind::Phone),
+            16..=19 if digits.bytes().all(|b| b.is_ascii_digit()) => Some(PiiKind::CardNumber),
+            _ => None,
+        };
+        match kind.filter(|kind| detectors.contains(kind)) {
+            Some(kind) => {
+                *counts.entry(kind).or_default() += 1;
+                placeholder(kind).to_string()
+            }
+            None => digits.to_string(),
+        }
+    }).into_owned();
+
+    PiiScan { masked, counts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_selected_detectors() {
+        let text = "联系 13800138000 或 zhangsan@example.com";
+        let scan = scan_pii(text, &[PiiKind::Email]);
+        assert_eq!(scan.masked, "联系 13800138000 或 [EMAIL]");
+        assert_eq!(scan.kinds(), vec![PiiKind::Email]);
+
+        let scan = scan_pii(text, &PiiKind::ALL);
+        assert_eq!(scan.masked, "联系 [PHONE] 或 [EMAIL]");
+        assert_eq!(scan.total(), 2);
+        assert!(!scan_pii("共30天", &PiiKind::ALL).found());
+    }
+}
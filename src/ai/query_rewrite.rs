@@ -0,0 +1,298 @@
+// 查询改写与拆分
+// 检索前按术语表展开问题中的缩写，由大模型改写含糊的问题，并将多部分问题拆分为分别检索的子查询
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+
+use crate::ai::RigAiClientManager;
+use crate::db::entities::kb_faq_entry;
+use crate::db::entities::knowledge_base::QueryRewritePolicy;
+
+/// 子查询数量上限，知识库配置超过时按此截断
+pub const MAX_SUB_QUERIES: u32 = 5;
+
+/// 视为缩写的最大长度
+const MAX_ACRONYM_LENGTH: usize = 10;
+
+/// 释义回退为术语解释时截取的最大字符数
+const MAX_EXPANSION_CHARS: usize = 60;
+
+/// 缩写展开
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AcronymExpansion {
+    /// 问题中的缩写
+    pub acronym: String,
+    /// 术语表中的全称或释义
+    pub expansion: String,
+}
+
+/// 子查询及其检索结果数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SubQueryTrace {
+    /// 子查询
+    pub query: String,
+    /// 检索到的文档块数
+    pub chunks_retrieved: u32,
+}
+
+/// 查询改写过程记录
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QueryRewriteTrace {
+    /// 原始问题
+    pub original: String,
+    /// 改写后的查询，拆分为子查询时仅作参考
+    pub rewritten: String,
+    /// 按术语表展开的缩写
+    pub expansions: Vec<AcronymExpansion>,
+    /// 拆分出的子查询，为空表示直接用改写后的查询检索
+    pub sub_queries: Vec<SubQueryTrace>,
+    /// 大模型改写失败时的原因，此时使用展开缩写后的原始问题检索
+    pub fallback_reason: Option<String>,
+    /// 改写消耗的 token 数量
+    pub tokens_used: Option<u32>,
+    /// 改写耗时（毫秒）
+    pub rewrite_time_ms: u64,
+}
+
+impl QueryRewriteTrace {
+    /// 实际用于检索的查询
+    pub fn retrieval_queries(&self) -> Vec<String> {
+        if self.sub_queries.is_empty() {
+            vec![self.rewritten.clone()]
+        } else {
+            self.sub_queries.iter().map(|sub| sub.query.clone()).collect()
+        }
+    }
+}
+
+/// 大模型返回的改写结果
+#[derive(Debug, Deserialize)]
+struct RewriteOutput {
+    rewritten: String,
+    #[serde(default)]
+    sub_queries: Vec<String>,
+}
+
+/// 改写查询
+///
+/// 缩写展开不依赖大模型；大模型调用或解析失败时回退为展开缩写后的原始问题，不影响问答。
+pub async fn rewrite_query(
+    ai_client: &RigAiClientManager,
+    question: &str,
+    glossary: &[kb_faq_entry::Model],
+    policy: &QueryRewritePolicy,
+) -> QueryRewriteTrace {
+    let start = std::time::Instant::now();
+    let expansions = if policy.expand_acronyms {
+        find_acronym_expansions(question, glossary)
+    } else {
+        Vec::new()
+    };
+    let expanded = expand_acronyms(question, &expansions);
+    let max_sub_queries = if policy.decompose { policy.max_sub_queries.min(MAX_SUB_QUERIES) } else { 0 };
+
+    let mut trace = QueryRewriteTrace {
+        original: question.to_string(),
+        rewritten: expanded.clone(),
+        expansions,
+        sub_queries: Vec::new(),
+        fallback_reason: None,
+        tokens_used: None,
+        rewrite_time_ms: 0,
+    };
+
+    let prompt = build_rewrite_prompt(&expanded, max_sub_queries);
+    match ai_client.generate_text(&prompt).await {
+        Ok(response) => {
+            trace.tokens_used = response.tokens_used;
+            match parse_rewrite_output(&response.text, max_sub_queries as usize) {
+                Some((rewritten, sub_queries)) => {
+                    trace.rewritten = rewritten;
+                    trace.sub_queries = sub_queries
+                        .into_iter()
+                        .map(|query| SubQueryTrace { query, chunks_retrieved: 0 })
+                        .collect();
+                }
+                None => trace.fallback_reason = Some("改写结果不是有效的 JSON".to_string()),
+            }
+        }
+        Err(e) => {
+            warn!("查询改写失败，使用原始问题检索: {}", e);
+            trace.fallback_reason = Some(e.to_string());
+        }
+    }
+
+    trace.rewrite_time_ms = start.elapsed().as_millis() as u64;
+    debug!("查询改写: {:?} -> {:?}, 子查询数={}", question, trace.rewritten, trace.sub_queries.len());
+    trace
+}
+
+/// 构建改写提示词
+fn build_rewrite_prompt(question: &str, max_sub_queries: u32) -> String {
+    let decompose_instruction = if max_sub_queries > 1 {
+        format!(
+            "3. 如果问题包含多个相互独立的部分，将其拆分为最多 {} 个可单独检索的子查询，放入 sub_queries；否则 sub_queries 为空数组",
+            max_sub_queries
+        )
+    } else {
+        "3. sub_queries 始终为空数组".to_string()
+    };
+
+    format!(
+        r#"你负责把用户的问题改写为更适合在知识库中检索的查询。
+
+## 要求：
+1. 补全指代不明或过于口语化的表述，保留问题中的专有名词、编号和括号中的缩写全称
+2. 不要回答问题，不要添加问题中没有的信息
+{}
+4. 只返回 JSON，格式为 {{"rewritten": "改写后的查询", "sub_queries": ["子查询1", "子查询2"]}}
+
+## 用户问题：
+{}
+"#,
+        decompose_instruction, question
+    )
+}
+
+/// 解析大模型返回的改写结果，容忍 JSON 前后的说明文字与代码块标记
+fn parse_rewrite_output(text: &str, max_sub_queries: usize) -> Option<(String, Vec<String>)> {
+    let start = text.find('{')?;
+    let end = text.rfind('}')?;
+    let output: RewriteOutput = serde_json::from_str(text.get(start..=end)?).ok()?;
+
+    let rewritten = output.rewritten.trim().to_string();
+    if rewritten.is_empty() {
+        return None;
+    }
+    let mut sub_queries: Vec<String> = Vec::new();
+    for query in output.sub_queries {
+        let query = query.trim().to_string();
+        if !query.is_empty() && !sub_queries.contains(&query) {
+            sub_queries.push(query);
+        }
+    }
+    // 只拆出一个子查询等同于不拆分
+    if sub_queries.len() < 2 {
+        sub_queries.clear();
+    }
+    sub_queries.truncate(max_sub_queries);
+    Some((rewritten, sub_queries))
+}
+
+/// 在术语表中查找问题里出现的缩写
+///
+/// 条目的术语或别名是缩写时参与匹配，匹配不区分大小写；全称取条目中第一个非缩写的术语或别名，
+/// 都是缩写时取释义的第一句。
+pub fn find_acronym_expansions(question: &str, glossary: &[kb_faq_entry::Model]) -> Vec<AcronymExpansion> {
+    let words: Vec<&str> = question
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let mut expansions: Vec<AcronymExpansion> = Vec::new();
+    for entry in glossary {
+        let mut names = vec![entry.question.clone()];
+        names.extend(entry.alias_list());
+        let Some(acronym) = names
+            .iter()
+            .filter(|name| is_acronym(name))
+            .find_map(|name| words.iter().find(|word| word.eq_ignore_ascii_case(name)))
+        else {
+            continue;
+        };
+        if expansions.iter().any(|e| e.acronym.eq_ignore_ascii_case(acronym)) {
+            continue;
+        }
+
+        let expansion = names
+            .iter()
+            .find(|name| !is_acronym(name) && !name.trim().is_empty())
+            .map(|name| name.trim().to_string())
+            .unwrap_or_else(|| first_sentence(&entry.answer));
+        if !expansion.is_empty() {
+            expansions.push(AcronymExpansion { acronym: acronym.to_string(), expansion });
+        }
+    }
+    expansions
+}
+
+/// 在问题后附上缩写全称
+pub fn expand_acronyms(question: &str, expansions: &[AcronymExpansion]) -> String {
+    if expansions.is_empty() {
+        return question.to_string();
+    }
+    let notes: Vec<String> = expansions.iter().map(|e| format!("{}：{}", e.acronym, e.expansion)).collect();
+    format!("{}（{}）", question, notes.join("；"))
+}
+
+fn is_acronym(name: &str) -> bool {
+    let name = name.trim();
+    (2..=MAX_ACRONYM_LENGTH).contains(&name.len())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+        && name.chars().filter(|c| c.is_ascii_uppercase()).count() >= 2
+}
+
+fn first_sentence(text: &str) -> String {
+    let sentence = text.split(['。', '.', '\n', '；', ';']).next().unwrap_or_default().trim();
+    sentence.chars().take(MAX_EXPANSION_CHARS).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::entities::document::ClearanceLevel;
+    use crate::db::entities::kb_faq_entry::FaqEntryType;
+    use uuid::Uuid;
+
+    fn glossary(term: &str, aliases: &[&str], answer: &str) -> kb_faq_entry::Model {
+        let now = chrono::Utc::now().fixed_offset();
+        kb_faq_entry::Model {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            knowledge_base_id: Uuid::new_v4(),
+            entry_type: FaqEntryType::Glossary,
+            question: term.to_string(),
+            answer: answer.to_string(),
+            aliases: serde_json::json!(aliases),
+            clearance: ClearanceLevel::Public,
+            is_active: true,
+            hit_count: 0,
+            last_hit_at: None,
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_acronym_expansion_from_glossary() {
+        let entries = vec![
+            glossary("SLA", &["服务等级协议"], "服务提供方承诺的可用性指标。"),
+            glossary("RTO", &[], "恢复时间目标。系统中断后恢复的最长时间"),
+            glossary("备份", &[], "数据副本"),
+        ];
+        let expansions = find_acronym_expansions("sla 和 RTO 分别是多少？", &entries);
+
+        assert_eq!(expansions, vec![
+            AcronymExpansion { acronym: "sla".to_string(), expansion: "服务等级协议".to_string() },
+            AcronymExpansion { acronym: "RTO".to_string(), expansion: "恢复时间目标".to_string() },
+        ]);
+        assert_eq!(
+            expand_acronyms("RTO 是多少", &expansions[1..]),
+            "RTO 是多少（RTO：恢复时间目标）"
+        );
+    }
+
+    #[test]
+    fn test_parse_rewrite_output() {
+        let text = "```json\n{\"rewritten\": \"退款流程和到账时间\", \"sub_queries\": [\"退款流程\", \"退款到账时间\", \"退款流程\"]}\n```";
+        let (rewritten, sub_queries) = parse_rewrite_output(text, 3).unwrap();
+        assert_eq!(rewritten, "退款流程和到账时间");
+        assert_eq!(sub_queries, vec!["退款流程", "退款到账时间"]);
+
+        assert_eq!(parse_rewrite_output(text, 0).unwrap().1, Vec::<String>::new());
+        assert!(parse_rewrite_output("{\"rewritten\": \" \"}", 3).is_none());
+        assert!(parse_rewrite_output("无法改写", 3).is_none());
+    }
+}
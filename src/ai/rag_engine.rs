@@ -6,12 +6,43 @@ use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn, error, debug};
-use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder};
+use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, ConnectionTrait, DatabaseBackend, Statement};
 
 use crate::ai::{RigAiClientManager, vector_search::VectorSearchEngine, chunker::HybridChunker};
-use crate::db::entities::{knowledge_base, document, document_chunk, prelude::*};
+use crate::ai::model_policy::{check_model_allowed, load_tenant_model_policy};
+use crate::ai::embedding_pool::{EmbeddingPool, EmbeddingPriority};
+use crate::ai::answer_confidence::{
+    combine_confidence, extract_self_assessment, refusal_answer, retrieval_confidence, ConfidenceBreakdown,
+    SELF_ASSESSMENT_INSTRUCTION,
+};
+use crate::ai::query_rewrite::{rewrite_query, QueryRewriteTrace};
+use crate::ai::retrieval_explain::{DropReason, RetrievalExplanation, RetrievalStage};
+use crate::ai::table_extraction::{
+    chunk_table_from_metadata, render_chunk_table, MAX_WHOLE_TABLE_ROWS, TABLE_ANSWER_INSTRUCTION,
+};
+use crate::ai::structured_output::{check_output_schema, repair_prompt, schema_instruction, StructuredOutput};
+use crate::db::entities::{knowledge_base, document, document_chunk, kb_snapshot, kb_snapshot_document, prelude::*};
+use crate::db::entities::document::ClearanceLevel;
+use crate::db::entities::knowledge_base::{KnowledgeBaseConfig, QueryRewritePolicy};
+use crate::db::entities::user::{AnswerVerbosity, CitationStyle, UserPreferences};
 use crate::errors::AiStudioError;
+use crate::services::clearance::ClearanceService;
+use crate::services::curated_answer::{CuratedAnswerMatch, CuratedAnswerService};
+use crate::services::deadline;
+use crate::services::faq::{FaqMatch, FaqService};
+use crate::services::freshness::DocumentFreshnessService;
+use crate::services::kb_snapshot::KbSnapshotService;
+use crate::services::prompt_retention::{record_model_call, ModelCall, ModelCallSource};
+use crate::services::qa_transcript::{QaTranscriptEntry, QaTranscriptService, TranscriptCitation};
 use crate::services::knowledge_base::KnowledgeBaseService;
+use crate::services::tenant_persona::{format_persona_for_prompt, load_tenant_persona};
+use crate::db::entities::tenant::TenantPersona;
+
+/// 开始生成答案所需的最少剩余预算，不足时直接返回检索结果
+const MIN_GENERATION_BUDGET: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// 截止时间内未能生成答案时的说明
+const DEADLINE_EXCEEDED_ANSWER: &str = "请求已接近截止时间，未能生成完整答案，以下为检索到的相关资料。";
 
 /// RAG 查询请求
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -30,6 +61,31 @@ pub struct RagQueryRequest {
     pub session_id: Option<String>,
     /// 用户 ID
     pub user_id: Option<Uuid>,
+    /// 知识库快照名称（指定时在该快照上检索，需同时指定知识库 ID）
+    pub kb_version: Option<String>,
+    /// 期望的输出 JSON Schema（指定时按模式约束并校验答案）
+    pub output_schema: Option<serde_json::Value>,
+    /// 请求者的访问密级，超出密级的文档块不会进入检索结果
+    #[serde(default)]
+    pub clearance: ClearanceLevel,
+    /// 联邦问答的目标知识库（调用方已按请求者的知识库访问权限过滤）
+    ///
+    /// 非空时在各知识库中分别检索后合并排序，忽略 `knowledge_base_id`。
+    #[serde(default)]
+    pub knowledge_base_ids: Vec<Uuid>,
+    /// 联邦问答中每个知识库最多贡献的文档块数量，默认与 `top_k` 相同
+    #[serde(default)]
+    pub per_kb_top_k: Option<u32>,
+    /// 是否在响应中返回检索决策记录
+    #[serde(default)]
+    pub explain: bool,
+}
+
+impl RagQueryRequest {
+    /// 是否为跨知识库的联邦问答
+    pub fn is_federated(&self) -> bool {
+        !self.knowledge_base_ids.is_empty()
+    }
 }
 
 /// 检索参数
@@ -47,6 +103,20 @@ pub struct RetrievalParams {
     pub document_types: Option<Vec<String>>,
     /// 时间范围过滤
     pub date_range: Option<DateRange>,
+    /// 表格块的返回方式
+    #[serde(default)]
+    pub table_mode: TableRetrievalMode,
+}
+
+/// 表格块的返回方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableRetrievalMode {
+    /// 返回命中的表格片段，片段按整行切分并带完整表头
+    #[default]
+    Rows,
+    /// 将命中的表格片段扩展为整张表格，适合跨行汇总、比较的数值问题
+    Whole,
 }
 
 /// 生成参数
@@ -62,6 +132,31 @@ pub struct GenerationParams {
     pub language: Option<String>,
     /// 生成风格
     pub style: Option<String>,
+    /// 回答详略程度，未指定生成风格时据此确定风格
+    #[serde(default)]
+    pub verbosity: Option<AnswerVerbosity>,
+    /// 引用来源的方式
+    #[serde(default)]
+    pub citation_style: Option<CitationStyle>,
+    /// 用户所在时区
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+impl GenerationParams {
+    /// 用用户偏好补齐请求未指定的参数，请求中显式指定的参数优先
+    pub fn with_preferences(params: Option<Self>, preferences: &UserPreferences) -> Self {
+        let mut params = params.unwrap_or_else(|| Self {
+            language: None,
+            style: None,
+            ..Self::default()
+        });
+        params.language.get_or_insert_with(|| preferences.effective_answer_language().to_string());
+        params.verbosity.get_or_insert(preferences.verbosity);
+        params.citation_style.get_or_insert(preferences.citation_style);
+        params.timezone.get_or_insert_with(|| preferences.timezone.clone());
+        params
+    }
 }
 
 /// 时间范围
@@ -88,6 +183,24 @@ pub struct RagQueryResponse {
     pub source_documents: Vec<SourceDocument>,
     /// 查询统计信息
     pub query_stats: QueryStats,
+    /// 检索所用的知识库快照名称
+    pub kb_version: Option<String>,
+    /// 结构化输出（请求指定输出模式时返回）
+    pub structured_output: Option<StructuredOutput>,
+    /// 命中的 FAQ/术语表条目（由标准答案直接应答时返回）
+    pub faq_match: Option<FaqMatch>,
+    /// 命中的精选答案（由知识管理员审定的答案直接应答时返回）
+    pub curated_answer: Option<CuratedAnswerMatch>,
+    /// 置信度明细（FAQ 或精选答案应答时为空）
+    pub confidence: Option<ConfidenceBreakdown>,
+    /// 是否因资料不足而未给出答案，此时来源文档为推荐查阅的文档
+    pub insufficient_information: bool,
+    /// 查询改写过程（知识库启用查询改写时返回）
+    pub query_rewrite: Option<QueryRewriteTrace>,
+    /// 检索决策记录（请求要求解释时返回）
+    pub retrieval_explanation: Option<RetrievalExplanation>,
+    /// 是否因请求截止时间不足而未生成答案，此时仅返回检索到的文档块与来源
+    pub deadline_exceeded: bool,
     /// 生成时间
     pub generated_at: DateTime<Utc>,
 }
@@ -122,6 +235,10 @@ pub struct SourceDocument {
     pub relevance_score: f32,
     /// 引用的文档块数量
     pub chunk_count: u32,
+    /// 文档所属知识库 ID
+    pub knowledge_base_id: Option<Uuid>,
+    /// 文档所属知识库名称
+    pub knowledge_base_name: Option<String>,
 }
 
 /// 查询统计信息
@@ -143,6 +260,24 @@ pub struct QueryStats {
     pub tokens_generated: Option<u32>,
 }
 
+/// 模型生成的答案
+struct GeneratedAnswer {
+    /// 答案文本（已去除自评行）
+    text: String,
+    /// 答案特征置信度
+    answer_signal: f32,
+    /// 模型自评置信度
+    self_assessment: Option<f32>,
+    /// 消耗的 token 数量
+    tokens_used: Option<u32>,
+    /// 结构化输出
+    structured_output: Option<StructuredOutput>,
+    /// 最后一次发送给模型的提示词
+    prompt: String,
+    /// 模型返回的原始输出
+    completion: String,
+}
+
 /// RAG 引擎配置
 #[derive(Debug, Clone)]
 pub struct RagEngineConfig {
@@ -158,6 +293,8 @@ pub struct RagEngineConfig {
     pub cache_ttl_seconds: u64,
     /// 是否启用查询日志
     pub enable_query_logging: bool,
+    /// 结构化输出未通过校验时的最大修复重试次数
+    pub max_output_repair_attempts: u32,
 }
 
 impl Default for RagEngineConfig {
@@ -169,6 +306,7 @@ impl Default for RagEngineConfig {
             enable_caching: true,
             cache_ttl_seconds: 3600,
             enable_query_logging: true,
+            max_output_repair_attempts: 2,
         }
     }
 }
@@ -213,21 +351,71 @@ impl RagEngine {
         
         info!("开始 RAG 查询: query_id={}, question={}", query_id, request.question);
         
+        if let Some(schema) = &request.output_schema {
+            check_output_schema(schema)?;
+        }
+        if request.is_federated() && request.kb_version.is_some() {
+            return Err(AiStudioError::validation("kb_version", "跨知识库问答不支持指定知识库快照"));
+        }
+        
+        self.ensure_models_allowed(&request).await?;
+        
+        // 0. 解析目标快照
+        let snapshot = self.resolve_snapshot(&request).await?;
+        
         // 1. 问题向量化
         let vectorization_start = std::time::Instant::now();
-        let question_embedding = self.vectorize_question(&request.question).await?;
+        let question_embedding = deadline::run("rag.vectorize", self.vectorize_question(&request.question)).await?;
         let vectorization_time = vectorization_start.elapsed().as_millis() as u64;
         
-        // 2. 检索相关文档块
+        // 精选答案与 FAQ 快速应答：命中人工审定或维护的答案时直接返回，精选答案优先；
+        // 快照检索、结构化输出与跨知识库问答仍走完整流程
+        if snapshot.is_none() && request.output_schema.is_none() && !request.is_federated() {
+            let response = match self.answer_from_curated(&request, &query_id, &question_embedding, vectorization_time, start_time).await {
+                Some(response) => Some(response),
+                None => self.answer_from_faq(&request, &query_id, &question_embedding, vectorization_time, start_time).await,
+            };
+            if let Some(response) = response {
+                if self.config.enable_query_logging {
+                    self.log_query(&request, &question_embedding, &response).await;
+                }
+                return Ok(response);
+            }
+        }
+        
+        // 2. 检索相关文档块，知识库启用查询改写时先改写问题并按子查询分别检索
+        let kb_config = self.load_kb_config(&request).await?;
+        let mut query_rewrite = match kb_config.as_ref().map(|config| &config.query_rewrite) {
+            Some(policy) if policy.enabled => Some(self.rewrite_question(&request, policy).await),
+            _ => None,
+        };
         let retrieval_start = std::time::Instant::now();
-        let retrieved_chunks = self.retrieve_relevant_chunks(
-            &request,
-            &question_embedding,
-        ).await?;
+        let mut explanation = RetrievalExplanation::default();
+        let retrieved_chunks = deadline::run("rag.retrieve", async {
+            match query_rewrite.as_mut() {
+                _ if request.is_federated() => self.retrieve_federated(&request, &question_embedding, &mut explanation).await,
+                Some(trace) => self.retrieve_for_rewritten_queries(&request, trace, snapshot.as_ref(), &mut explanation).await,
+                None => self.retrieve_relevant_chunks(
+                    &request,
+                    &request.question,
+                    Some(&question_embedding),
+                    snapshot.as_ref(),
+                    &mut explanation,
+                ).await,
+            }
+        }).await?;
+        let retrieved_chunks = self.filter_by_clearance(request.clearance, retrieved_chunks, &mut explanation).await?;
+        let table_mode = request.retrieval_params.as_ref().map(|p| p.table_mode).unwrap_or_default();
+        let retrieved_chunks = if table_mode == TableRetrievalMode::Whole {
+            self.expand_tables(request.clearance, retrieved_chunks, &mut explanation).await?
+        } else {
+            retrieved_chunks
+        };
         let retrieval_time = retrieval_start.elapsed().as_millis() as u64;
         
         if retrieved_chunks.is_empty() {
             warn!("未找到相关文档块: query_id={}", query_id);
+            let retrieval_explanation = conclude_explanation(&query_id, explanation, &[], request.explain);
             return Ok(RagQueryResponse {
                 query_id,
                 answer: "抱歉，我没有找到相关的信息来回答您的问题。".to_string(),
@@ -243,24 +431,111 @@ impl RagEngine {
                     chunks_used_for_generation: 0,
                     tokens_generated: None,
                 },
+                kb_version: request.kb_version.clone(),
+                structured_output: None,
+                faq_match: None,
+                curated_answer: None,
+                confidence: Some(ConfidenceBreakdown::none()),
+                insufficient_information: true,
+                query_rewrite,
+                retrieval_explanation,
+                deadline_exceeded: false,
                 generated_at: Utc::now(),
             });
         }
         
         // 3. 构建上下文
-        let context = self.build_context(&retrieved_chunks, &request).await?;
+        let context = self.build_context(&retrieved_chunks, &request, &mut explanation).await?;
+        let retrieval_explanation = conclude_explanation(&query_id, explanation, &retrieved_chunks, request.explain);
+        let answer_policy = kb_config.map(|config| config.answer_policy).unwrap_or_default();
+        let persona = load_tenant_persona(self.db.as_ref(), request.tenant_id).await;
         
-        // 4. 生成答案
+        // 4. 生成答案，剩余预算不足或生成超时时返回已检索到的资料
         let generation_start = std::time::Instant::now();
-        let (answer, confidence_score, tokens_generated) = self.generate_answer(
-            &request.question,
-            &context,
-            &request.generation_params.clone().unwrap_or_default(),
-        ).await?;
+        let generation = async {
+            if deadline::remaining().is_some_and(|remaining| remaining < MIN_GENERATION_BUDGET) {
+                return Err(AiStudioError::timeout("rag.generate"));
+            }
+            deadline::run("rag.generate", self.generate_answer(
+                &request.question,
+                &context,
+                &request.generation_params.clone().unwrap_or_default(),
+                &persona,
+                request.output_schema.as_ref(),
+                answer_policy.self_assessment,
+                retrieved_chunks.iter().any(|chunk| chunk_table_from_metadata(&chunk.metadata).is_some()),
+            )).await
+        };
+        let generated = match generation.await {
+            Ok(generated) => generated,
+            Err(AiStudioError::Timeout { .. }) => {
+                warn!("请求截止时间不足，返回检索结果: query_id={}", query_id);
+                let source_documents = self.build_source_documents(&retrieved_chunks, snapshot.as_ref()).await?;
+                let scores: Vec<f32> = retrieved_chunks.iter().map(|chunk| chunk.similarity_score).collect();
+                let retrieval = retrieval_confidence(&scores);
+                return Ok(RagQueryResponse {
+                    query_id,
+                    answer: DEADLINE_EXCEEDED_ANSWER.to_string(),
+                    confidence_score: retrieval,
+                    retrieved_chunks: retrieved_chunks.clone(),
+                    source_documents,
+                    query_stats: QueryStats {
+                        vectorization_time_ms: vectorization_time,
+                        retrieval_time_ms: retrieval_time,
+                        generation_time_ms: generation_start.elapsed().as_millis() as u64,
+                        total_time_ms: start_time.elapsed().as_millis() as u64,
+                        total_chunks_retrieved: retrieved_chunks.len() as u32,
+                        chunks_used_for_generation: 0,
+                        tokens_generated: None,
+                    },
+                    kb_version: request.kb_version.clone(),
+                    structured_output: None,
+                    faq_match: None,
+                    curated_answer: None,
+                    confidence: Some(combine_confidence(retrieval, None, 0.0)),
+                    insufficient_information: false,
+                    query_rewrite,
+                    retrieval_explanation,
+                    deadline_exceeded: true,
+                    generated_at: Utc::now(),
+                });
+            }
+            Err(e) => return Err(e),
+        };
         let generation_time = generation_start.elapsed().as_millis() as u64;
+        record_model_call(ModelCall {
+            tenant_id: request.tenant_id,
+            source: ModelCallSource::Rag,
+            execution_id: None,
+            reference: Some(query_id.clone()),
+            model: self.ai_client.client().model_id(),
+            prompt: generated.prompt.clone(),
+            completion: generated.completion.clone(),
+            tokens_used: generated.tokens_used,
+        });
         
         // 5. 构建来源文档信息
-        let source_documents = self.build_source_documents(&retrieved_chunks).await?;
+        let mut source_documents = self.build_source_documents(&retrieved_chunks, snapshot.as_ref()).await?;
+        
+        // 6. 估计置信度，低于拒答阈值时改为说明资料不足并推荐文档
+        let scores: Vec<f32> = retrieved_chunks.iter().map(|chunk| chunk.similarity_score).collect();
+        let confidence = combine_confidence(
+            retrieval_confidence(&scores),
+            generated.self_assessment,
+            generated.answer_signal,
+        );
+        let insufficient_information = answer_policy.refusal_threshold
+            .is_some_and(|threshold| confidence.score < threshold);
+        let (answer, structured_output) = if insufficient_information {
+            info!("置信度低于拒答阈值: query_id={}, 置信度={:.2}, 阈值={:?}", 
+                  query_id, confidence.score, answer_policy.refusal_threshold);
+            source_documents.truncate(answer_policy.suggested_documents as usize);
+            (refusal_answer(&answer_policy, &source_documents), None)
+        } else {
+            (generated.text, generated.structured_output)
+        };
+        let confidence_score = confidence.score;
+        let tokens_generated = generated.tokens_used;
         
         let total_time = start_time.elapsed().as_millis() as u64;
         
@@ -279,6 +554,15 @@ impl RagEngine {
                 chunks_used_for_generation: retrieved_chunks.len() as u32,
                 tokens_generated,
             },
+            kb_version: request.kb_version.clone(),
+            structured_output,
+            faq_match: None,
+            curated_answer: None,
+            confidence: Some(confidence),
+            insufficient_information,
+            query_rewrite,
+            retrieval_explanation,
+            deadline_exceeded: false,
             generated_at: Utc::now(),
         };
         
@@ -287,37 +571,284 @@ impl RagEngine {
         
         // 记录查询日志
         if self.config.enable_query_logging {
-            self.log_query(&request, &response).await?;
+            self.log_query(&request, &question_embedding, &response).await;
         }
         
         Ok(response)
     }
     
+    /// 使用知识管理员审定的精选答案应答，未命中或匹配失败时返回 `None`
+    async fn answer_from_curated(
+        &self,
+        request: &RagQueryRequest,
+        query_id: &str,
+        question_embedding: &[f32],
+        vectorization_time: u64,
+        start_time: std::time::Instant,
+    ) -> Option<RagQueryResponse> {
+        let matched = CuratedAnswerService::new(self.db.as_ref().clone())
+            .match_question(
+                request.tenant_id,
+                request.knowledge_base_id,
+                request.clearance,
+                &request.question,
+                Some(question_embedding),
+            )
+            .await;
+        let (answer, curated_match) = match matched {
+            Ok(matched) => matched?,
+            Err(e) => {
+                warn!("精选答案匹配失败，继续后续流程: query_id={}, error={}", query_id, e);
+                return None;
+            }
+        };
+        
+        info!("精选答案应答: query_id={}, curated_answer_id={}, score={:.3}",
+              query_id, curated_match.curated_answer_id, curated_match.score);
+        Some(RagQueryResponse {
+            query_id: query_id.to_string(),
+            answer,
+            confidence_score: curated_match.score,
+            retrieved_chunks: Vec::new(),
+            source_documents: Vec::new(),
+            query_stats: QueryStats {
+                vectorization_time_ms: vectorization_time,
+                retrieval_time_ms: 0,
+                generation_time_ms: 0,
+                total_time_ms: start_time.elapsed().as_millis() as u64,
+                total_chunks_retrieved: 0,
+                chunks_used_for_generation: 0,
+                tokens_generated: None,
+            },
+            kb_version: None,
+            structured_output: None,
+            faq_match: None,
+            curated_answer: Some(curated_match),
+            confidence: None,
+            insufficient_information: false,
+            query_rewrite: None,
+            retrieval_explanation: None,
+            deadline_exceeded: false,
+            generated_at: Utc::now(),
+        })
+    }
+    
+    /// 使用 FAQ/术语表条目的标准答案应答，未命中或匹配失败时返回 `None`
+    async fn answer_from_faq(
+        &self,
+        request: &RagQueryRequest,
+        query_id: &str,
+        question_embedding: &[f32],
+        vectorization_time: u64,
+        start_time: std::time::Instant,
+    ) -> Option<RagQueryResponse> {
+        let matched = FaqService::new(self.db.as_ref().clone())
+            .match_question(
+                request.tenant_id,
+                request.knowledge_base_id,
+                request.clearance,
+                &request.question,
+                Some(question_embedding),
+            )
+            .await;
+        let (entry, faq_match) = match matched {
+            Ok(matched) => matched?,
+            Err(e) => {
+                warn!("FAQ 匹配失败，回退到完整检索: query_id={}, error={}", query_id, e);
+                return None;
+            }
+        };
+        
+        info!("FAQ 快速应答: query_id={}, entry_id={}, score={:.3}", query_id, entry.id, faq_match.score);
+        Some(RagQueryResponse {
+            query_id: query_id.to_string(),
+            answer: entry.answer,
+            confidence_score: faq_match.score,
+            retrieved_chunks: Vec::new(),
+            source_documents: Vec::new(),
+            query_stats: QueryStats {
+                vectorization_time_ms: vectorization_time,
+                retrieval_time_ms: 0,
+                generation_time_ms: 0,
+                total_time_ms: start_time.elapsed().as_millis() as u64,
+                total_chunks_retrieved: 0,
+                chunks_used_for_generation: 0,
+                tokens_generated: None,
+            },
+            kb_version: None,
+            structured_output: None,
+            faq_match: Some(faq_match),
+            curated_answer: None,
+            confidence: None,
+            insufficient_information: false,
+            query_rewrite: None,
+            retrieval_explanation: None,
+            deadline_exceeded: false,
+            generated_at: Utc::now(),
+        })
+    }
+    
     /// 向量化问题
     async fn vectorize_question(&self, question: &str) -> Result<Vec<f32>, AiStudioError> {
         debug!("向量化问题: {}", question);
         
+        // 配置了本地嵌入工作池时走交互队列，避免被批量重嵌入任务阻塞
+        if let Some(pool) = EmbeddingPool::global() {
+            return pool.embed(question, EmbeddingPriority::Interactive).await;
+        }
+
         let embedding_response = self.ai_client.generate_embedding(question).await?;
         Ok(embedding_response.embedding)
     }
     
+    /// 检查生成模型与知识库嵌入模型是否符合租户的模型使用策略
+    async fn ensure_models_allowed(&self, request: &RagQueryRequest) -> Result<(), AiStudioError> {
+        let policy = load_tenant_model_policy(self.db.as_ref(), request.tenant_id).await?;
+        check_model_allowed(&policy, "model", &self.ai_client.client().model_id())?;
+        
+        let knowledge_base_ids = if request.is_federated() {
+            request.knowledge_base_ids.clone()
+        } else {
+            request.knowledge_base_id.into_iter().collect()
+        };
+        for knowledge_base_id in knowledge_base_ids {
+            let kb = KnowledgeBase::find_by_id(knowledge_base_id)
+                .one(self.db.as_ref())
+                .await?;
+            if let Some(kb) = kb {
+                check_model_allowed(&policy, "embedding_model", &kb.embedding_model)?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// 解析请求指定的知识库快照
+    async fn resolve_snapshot(
+        &self,
+        request: &RagQueryRequest,
+    ) -> Result<Option<kb_snapshot::Model>, AiStudioError> {
+        let Some(kb_version) = request.kb_version.as_deref() else {
+            return Ok(None);
+        };
+        let knowledge_base_id = request.knowledge_base_id
+            .ok_or_else(|| AiStudioError::validation("knowledge_base_id", "指定 kb_version 时必须指定知识库 ID"))?;
+        
+        let snapshot = KbSnapshotService::new(self.db.as_ref().clone())
+            .get_snapshot(request.tenant_id, knowledge_base_id, kb_version)
+            .await?;
+        Ok(Some(snapshot))
+    }
+    
+    /// 加载请求知识库的配置，未指定知识库时返回 `None`，调用方使用默认策略
+    async fn load_kb_config(&self, request: &RagQueryRequest) -> Result<Option<KnowledgeBaseConfig>, AiStudioError> {
+        let Some(knowledge_base_id) = request.knowledge_base_id else {
+            return Ok(None);
+        };
+        
+        let kb = KnowledgeBase::find_by_id(knowledge_base_id)
+            .filter(knowledge_base::Column::TenantId.eq(request.tenant_id))
+            .one(self.db.as_ref())
+            .await?;
+        Ok(kb.and_then(|kb| kb.get_config().ok()))
+    }
+    
+    /// 按知识库的查询改写策略改写问题，术语表加载失败时仅跳过缩写展开
+    async fn rewrite_question(
+        &self,
+        request: &RagQueryRequest,
+        policy: &QueryRewritePolicy,
+    ) -> QueryRewriteTrace {
+        let glossary = match request.knowledge_base_id {
+            Some(knowledge_base_id) if policy.expand_acronyms => FaqService::new(self.db.as_ref().clone())
+                .glossary_entries(request.tenant_id, knowledge_base_id, request.clearance)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("加载术语表失败，跳过缩写展开: {}", e);
+                    Vec::new()
+                }),
+            _ => Vec::new(),
+        };
+        rewrite_query(&self.ai_client, &request.question, &glossary, policy).await
+    }
+    
+    /// 按改写后的查询或各子查询分别检索，合并结果并按文档块去重，保留最高得分
+    async fn retrieve_for_rewritten_queries(
+        &self,
+        request: &RagQueryRequest,
+        trace: &mut QueryRewriteTrace,
+        snapshot: Option<&kb_snapshot::Model>,
+        explanation: &mut RetrievalExplanation,
+    ) -> Result<Vec<RetrievedChunk>, AiStudioError> {
+        let mut merged: Vec<RetrievedChunk> = Vec::new();
+        for (i, query) in trace.retrieval_queries().iter().enumerate() {
+            let chunks = self.retrieve_relevant_chunks(request, query, None, snapshot, explanation).await?;
+            if let Some(sub_query) = trace.sub_queries.get_mut(i) {
+                sub_query.chunks_retrieved = chunks.len() as u32;
+            }
+            for chunk in chunks {
+                match merged.iter_mut().find(|existing| existing.chunk_id == chunk.chunk_id) {
+                    Some(existing) => existing.similarity_score = existing.similarity_score.max(chunk.similarity_score),
+                    None => merged.push(chunk),
+                }
+            }
+        }
+        merged.sort_by(|a, b| b.similarity_score.total_cmp(&a.similarity_score));
+        Ok(merged)
+    }
+    
     /// 检索相关文档块
+    ///
+    /// `query_embedding` 为空时在快照检索前对查询重新向量化。
     async fn retrieve_relevant_chunks(
         &self,
         request: &RagQueryRequest,
-        question_embedding: &[f32],
+        query: &str,
+        query_embedding: Option<&[f32]>,
+        snapshot: Option<&kb_snapshot::Model>,
+        explanation: &mut RetrievalExplanation,
     ) -> Result<Vec<RetrievedChunk>, AiStudioError> {
-        debug!("检索相关文档块: 租户={}, 知识库={:?}", 
-               request.tenant_id, request.knowledge_base_id);
+        debug!("检索相关文档块: 租户={}, 知识库={:?}, 快照={:?}", 
+               request.tenant_id, request.knowledge_base_id, request.kb_version);
         
         let params = request.retrieval_params.as_ref();
         let top_k = params.and_then(|p| p.top_k).unwrap_or(self.config.default_top_k);
         let similarity_threshold = params.and_then(|p| p.similarity_threshold)
             .unwrap_or(self.config.default_similarity_threshold);
+        explanation.record_filter(
+            RetrievalStage::Vector,
+            format!("相似度阈值 {:.2}，最多召回 {} 个", similarity_threshold, top_k),
+            0,
+        );
+        
+        // 指定快照时只在快照冻结的向量中检索，保证答案可复现
+        if let Some(snapshot) = snapshot {
+            let embedding = match query_embedding {
+                Some(embedding) => embedding.to_vec(),
+                None => self.vectorize_question(query).await?,
+            };
+            let hits = KbSnapshotService::new(self.db.as_ref().clone())
+                .search_snapshot(snapshot.id, &embedding, top_k as usize, similarity_threshold)
+                .await?;
+            
+            debug!("在快照 {} 中检索到 {} 个相关文档块", snapshot.name, hits.len());
+            let chunks: Vec<RetrievedChunk> = hits.into_iter()
+                .map(|hit| RetrievedChunk {
+                    chunk_id: hit.chunk_id,
+                    document_id: hit.document_id,
+                    content: hit.content,
+                    similarity_score: hit.similarity,
+                    chunk_index: hit.chunk_index,
+                    metadata: hit.metadata,
+                })
+                .collect();
+            explanation.record_scores(RetrievalStage::Vector, &chunks, request.knowledge_base_id);
+            return Ok(chunks);
+        }
         
         // 使用向量搜索服务检索相似文档块
         let search_results = self.vector_search.text_search(
-            &request.question,
+            query,
             top_k as usize,
             similarity_threshold,
             None,
@@ -342,16 +873,199 @@ impl RagEngine {
                 });
             }
         }
-        
+
+        explanation.record_scores(RetrievalStage::Vector, &retrieved_chunks, request.knowledge_base_id);
+
+        // 按知识库时效策略对过期文档降权或排除
+        let document_ids: Vec<Uuid> = retrieved_chunks.iter().map(|chunk| chunk.document_id).collect();
+        let weights = DocumentFreshnessService::new(self.db.as_ref().clone())
+            .retrieval_weights(&document_ids)
+            .await?;
+        apply_freshness_weights(&mut retrieved_chunks, &weights, explanation);
+        retrieved_chunks.sort_by(|a, b| b.similarity_score.total_cmp(&a.similarity_score));
+
         debug!("检索到 {} 个相关文档块", retrieved_chunks.len());
         Ok(retrieved_chunks)
     }
     
+    /// 跨知识库检索：在每个目标知识库中分别召回不超过配额的文档块，按时效策略加权后合并排序
+    async fn retrieve_federated(
+        &self,
+        request: &RagQueryRequest,
+        question_embedding: &[f32],
+        explanation: &mut RetrievalExplanation,
+    ) -> Result<Vec<RetrievedChunk>, AiStudioError> {
+        let params = request.retrieval_params.as_ref();
+        let top_k = params.and_then(|p| p.top_k).unwrap_or(self.config.default_top_k);
+        let per_kb_top_k = request.per_kb_top_k.unwrap_or(top_k).max(1);
+        let similarity_threshold = params.and_then(|p| p.similarity_threshold)
+            .unwrap_or(self.config.default_similarity_threshold);
+        explanation.record_filter(
+            RetrievalStage::Vector,
+            format!("相似度阈值 {:.2}，每个知识库最多召回 {} 个", similarity_threshold, per_kb_top_k),
+            0,
+        );
+        let vector = format!(
+            "[{}]",
+            question_embedding.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(",")
+        );
+        
+        let freshness = DocumentFreshnessService::new(self.db.as_ref().clone());
+        let mut per_kb = Vec::with_capacity(request.knowledge_base_ids.len());
+        for knowledge_base_id in &request.knowledge_base_ids {
+            let rows = deadline::query_all(
+                self.db.as_ref(),
+                "rag.retrieve_federated",
+                Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    r#"
+                    SELECT c.id AS chunk_id, c.document_id, c.chunk_index, c.content, c.metadata,
+                           MAX(1 - (e.vector <=> $1::vector))::REAL AS similarity
+                    FROM embeddings e
+                    JOIN document_chunks c ON c.id = e.chunk_id
+                    JOIN documents d ON d.id = c.document_id
+                    JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id
+                    WHERE kb.tenant_id = $2
+                        AND d.knowledge_base_id = $3
+                        AND d.status = 'completed'
+                        AND e.vector IS NOT NULL
+                    GROUP BY c.id, c.document_id, c.chunk_index, c.content, c.metadata
+                    HAVING MAX(1 - (e.vector <=> $1::vector)) >= $4
+                    ORDER BY similarity DESC
+                    LIMIT $5
+                    "#,
+                    [
+                        vector.clone().into(),
+                        request.tenant_id.into(),
+                        (*knowledge_base_id).into(),
+                        similarity_threshold.into(),
+                        (per_kb_top_k as i64).into(),
+                    ],
+                ),
+            )
+            .await?;
+            
+            let mut chunks = Vec::with_capacity(rows.len());
+            for row in rows {
+                chunks.push(RetrievedChunk {
+                    chunk_id: row.try_get("", "chunk_id")?,
+                    document_id: row.try_get("", "document_id")?,
+                    content: row.try_get("", "content")?,
+                    similarity_score: row.try_get("", "similarity")?,
+                    chunk_index: row.try_get("", "chunk_index")?,
+                    metadata: row.try_get("", "metadata")?,
+                });
+            }
+            
+            explanation.record_scores(RetrievalStage::Vector, &chunks, Some(*knowledge_base_id));
+            
+            let document_ids: Vec<Uuid> = chunks.iter().map(|chunk| chunk.document_id).collect();
+            let weights = freshness.retrieval_weights(&document_ids).await?;
+            apply_freshness_weights(&mut chunks, &weights, explanation);
+            debug!("知识库 {} 召回 {} 个文档块", knowledge_base_id, chunks.len());
+            per_kb.push(chunks);
+        }
+        
+        let merged = merge_federated_chunks(per_kb, top_k as usize);
+        let dropped = explanation.retain(RetrievalStage::Merge, DropReason::BelowTopK, &merged);
+        explanation.record_filter(RetrievalStage::Merge, format!("合并后保留前 {} 个", top_k), dropped);
+        Ok(merged)
+    }
+    
+    /// 将命中的表格片段扩展为整张表格
+    ///
+    /// 同一表格只保留得分最高的命中；表格的其他片段中有超出请求者密级的，保留原片段不扩展。
+    async fn expand_tables(
+        &self,
+        clearance: ClearanceLevel,
+        chunks: Vec<RetrievedChunk>,
+        explanation: &mut RetrievalExplanation,
+    ) -> Result<Vec<RetrievedChunk>, AiStudioError> {
+        let mut seen = std::collections::HashSet::new();
+        let mut expanded = Vec::with_capacity(chunks.len());
+        for mut chunk in chunks {
+            let Some(table) = chunk_table_from_metadata(&chunk.metadata) else {
+                expanded.push(chunk);
+                continue;
+            };
+            if !seen.insert((chunk.document_id, table.table_id.clone())) {
+                explanation.record_drop(chunk.chunk_id, RetrievalStage::TableExpansion, DropReason::DuplicateTable);
+                continue;
+            }
+            if table.rows.len() as u32 >= table.total_rows {
+                expanded.push(chunk);
+                continue;
+            }
+            
+            let rows = self.db
+                .query_all(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    "SELECT id, metadata FROM document_chunks \
+                     WHERE document_id = $1 AND metadata->'table'->>'table_id' = $2",
+                    [chunk.document_id.into(), table.table_id.clone().into()],
+                ))
+                .await?;
+            let mut slices = Vec::with_capacity(rows.len());
+            for row in rows {
+                let id: Uuid = row.try_get("", "id")?;
+                let metadata: serde_json::Value = row.try_get("", "metadata")?;
+                if let Some(slice) = chunk_table_from_metadata(&metadata) {
+                    slices.push((id, slice));
+                }
+            }
+            let slice_ids: Vec<Uuid> = slices.iter().map(|(id, _)| *id).collect();
+            let visible = ClearanceService::new(self.db.as_ref().clone())
+                .visible_chunks(clearance, &slice_ids)
+                .await?;
+            if visible.len() < slice_ids.len() {
+                expanded.push(chunk);
+                continue;
+            }
+            
+            slices.sort_by_key(|(_, slice)| slice.row_offset);
+            let mut whole = table;
+            whole.row_offset = 0;
+            whole.rows = slices.into_iter()
+                .flat_map(|(_, slice)| slice.rows)
+                .take(MAX_WHOLE_TABLE_ROWS)
+                .collect();
+            chunk.content = render_chunk_table(&whole);
+            chunk.metadata["table"] = serde_json::to_value(&whole)?;
+            expanded.push(chunk);
+        }
+        Ok(expanded)
+    }
+    
+    /// 过滤超出请求者密级的文档块
+    async fn filter_by_clearance(
+        &self,
+        clearance: ClearanceLevel,
+        chunks: Vec<RetrievedChunk>,
+        explanation: &mut RetrievalExplanation,
+    ) -> Result<Vec<RetrievedChunk>, AiStudioError> {
+        let chunk_ids: Vec<Uuid> = chunks.iter().map(|chunk| chunk.chunk_id).collect();
+        let visible = ClearanceService::new(self.db.as_ref().clone())
+            .visible_chunks(clearance, &chunk_ids)
+            .await?;
+
+        let total = chunks.len();
+        let chunks: Vec<RetrievedChunk> = chunks.into_iter()
+            .filter(|chunk| visible.contains(&chunk.chunk_id))
+            .collect();
+        if chunks.len() < total {
+            debug!("按访问密级过滤掉 {} 个文档块", total - chunks.len());
+        }
+        let dropped = explanation.retain(RetrievalStage::Clearance, DropReason::ClearanceDenied, &chunks);
+        explanation.record_filter(RetrievalStage::Clearance, format!("访问密级 {:?}", clearance), dropped);
+        Ok(chunks)
+    }
+    
     /// 构建上下文
     async fn build_context(
         &self,
         chunks: &[RetrievedChunk],
         request: &RagQueryRequest,
+        explanation: &mut RetrievalExplanation,
     ) -> Result<String, AiStudioError> {
         debug!("构建上下文，文档块数量: {}", chunks.len());
         
@@ -359,11 +1073,23 @@ impl RagEngine {
         let mut total_length = 0;
         
         for (i, chunk) in chunks.iter().enumerate() {
-            let chunk_text = format!("文档片段 {}:\n{}\n", i + 1, chunk.content);
+            // 表格块按结构化内容还原为 Markdown 表格，整块加入或整块跳过，不截断行
+            let chunk_text = match chunk_table_from_metadata(&chunk.metadata) {
+                Some(table) => format!("文档片段 {}（表格）:\n{}\n", i + 1, render_chunk_table(&table)),
+                None => format!("文档片段 {}:\n{}\n", i + 1, chunk.content),
+            };
             
             // 检查是否超过最大上下文长度
             if total_length + chunk_text.len() > self.config.max_context_length as usize {
                 debug!("达到最大上下文长度限制，停止添加文档块");
+                let dropped = chunks[i..].iter()
+                    .filter(|chunk| explanation.record_drop(chunk.chunk_id, RetrievalStage::Context, DropReason::ContextLimit))
+                    .count();
+                explanation.record_filter(
+                    RetrievalStage::Context,
+                    format!("最大上下文长度 {} 字符", self.config.max_context_length),
+                    dropped as u32,
+                );
                 break;
             }
             
@@ -378,26 +1104,98 @@ impl RagEngine {
     }
     
     /// 生成答案
+    ///
+    /// 指定输出模式时在提示词中附加格式约束，校验失败则携带错误信息请求模型修复，
+    /// 最多重试 `max_output_repair_attempts` 次。
     async fn generate_answer(
         &self,
         question: &str,
         context: &str,
         params: &GenerationParams,
-    ) -> Result<(String, f32, Option<u32>), AiStudioError> {
+        persona: &TenantPersona,
+        output_schema: Option<&serde_json::Value>,
+        self_assessment: bool,
+        has_tables: bool,
+    ) -> Result<GeneratedAnswer, AiStudioError> {
         debug!("生成答案，问题: {}", question);
         
-        let include_sources = params.include_sources.unwrap_or(true);
+        let citation_style = if params.include_sources.unwrap_or(true) && output_schema.is_none() {
+            params.citation_style.unwrap_or_default()
+        } else {
+            CitationStyle::None
+        };
         let language = params.language.as_deref().unwrap_or("中文");
-        let style = params.style.as_deref().unwrap_or("专业且友好");
+        let style = params.style.as_deref()
+            .unwrap_or_else(|| params.verbosity.unwrap_or_default().style());
         
-        let prompt = self.build_generation_prompt(question, context, include_sources, language, style);
+        // 结构化输出要求模型只返回 JSON，不追加自评行
+        let self_assessment = self_assessment && output_schema.is_none();
+        let mut prompt = format_persona_for_prompt(persona);
+        prompt.push_str(&self.build_generation_prompt(
+            question,
+            context,
+            citation_style,
+            language,
+            style,
+            params.timezone.as_deref(),
+            self_assessment,
+            has_tables,
+        ));
         
-        let response = self.ai_client.generate_text(&prompt).await?;
+        let Some(schema) = output_schema else {
+            let response = self.ai_client.generate_text(&prompt).await?;
+            
+            let completion = response.text.clone();
+            let (text, assessed) = if self_assessment {
+                extract_self_assessment(&response.text)
+            } else {
+                (response.text, None)
+            };
+            let answer_signal = self.calculate_confidence_score(&text, context);
+            
+            return Ok(GeneratedAnswer {
+                text,
+                answer_signal,
+                self_assessment: assessed,
+                tokens_used: response.tokens_used,
+                structured_output: None,
+                prompt,
+                completion,
+            });
+        };
         
-        // 计算置信度（简单实现，可以根据实际需要改进）
-        let confidence_score = self.calculate_confidence_score(&response.text, context);
+        prompt.push_str(&schema_instruction(schema));
         
-        Ok((response.text, confidence_score, response.tokens_used))
+        let mut attempts = 0;
+        let mut tokens_used: Option<u32> = None;
+        let mut request_prompt = prompt.clone();
+        loop {
+            attempts += 1;
+            let response = self.ai_client.generate_text(&request_prompt).await?;
+            if let Some(tokens) = response.tokens_used {
+                tokens_used = Some(tokens_used.unwrap_or(0) + tokens);
+            }
+            
+            let structured = StructuredOutput::evaluate(&response.text, schema, attempts);
+            if structured.is_valid() || attempts > self.config.max_output_repair_attempts {
+                if !structured.is_valid() {
+                    warn!("结构化输出校验失败，已达最大修复次数: attempts={}, errors={:?}", attempts, structured.errors);
+                }
+                let answer_signal = self.calculate_confidence_score(&response.text, context);
+                return Ok(GeneratedAnswer {
+                    completion: response.text.clone(),
+                    text: response.text,
+                    answer_signal,
+                    self_assessment: None,
+                    tokens_used,
+                    structured_output: Some(structured),
+                    prompt: request_prompt,
+                });
+            }
+            
+            debug!("结构化输出校验失败，请求模型修复: attempt={}, errors={:?}", attempts, structured.errors);
+            request_prompt = repair_prompt(&prompt, &response.text, &structured.errors);
+        }
     }
     
     /// 构建生成提示词
@@ -405,15 +1203,27 @@ impl RagEngine {
         &self,
         question: &str,
         context: &str,
-        include_sources: bool,
+        citation_style: CitationStyle,
         language: &str,
         style: &str,
+        timezone: Option<&str>,
+        self_assessment: bool,
+        has_tables: bool,
     ) -> String {
-        let source_instruction = if include_sources {
-            "请在答案中标注信息来源（如：根据文档片段1...）。"
-        } else {
-            ""
-        };
+        let source_instruction = citation_style.instruction();
+        let mut extra_instructions = String::new();
+        let mut next_index = 7;
+        if has_tables {
+            extra_instructions.push_str(&format!("\n{}. {}", next_index, TABLE_ANSWER_INSTRUCTION));
+            next_index += 1;
+        }
+        if let Some(timezone) = timezone {
+            extra_instructions.push_str(&format!("\n{}. 涉及日期和时间时按用户所在时区 {} 表述", next_index, timezone));
+            next_index += 1;
+        }
+        if self_assessment {
+            extra_instructions.push_str(&format!("\n{}. {}", next_index, SELF_ASSESSMENT_INSTRUCTION));
+        }
         
         format!(
             r#"你是一个专业的AI助手，请根据提供的文档内容回答用户的问题。
@@ -424,7 +1234,7 @@ impl RagEngine {
 3. 保持回答的准确性和客观性
 4. 使用{}语言回答
 5. 回答风格：{}
-6. {}
+6. {}{}
 
 ## 文档内容：
 {}
@@ -434,11 +1244,11 @@ impl RagEngine {
 
 ## 回答：
 "#,
-            language, style, source_instruction, context, question
+            language, style, source_instruction, extra_instructions, context, question
         )
     }
     
-    /// 计算置信度分数
+    /// 根据答案措辞与长度计算答案特征置信度
     fn calculate_confidence_score(&self, answer: &str, context: &str) -> f32 {
         // 简单的置信度计算实现
         // 实际应用中可以使用更复杂的算法
@@ -468,9 +1278,14 @@ impl RagEngine {
     async fn build_source_documents(
         &self,
         chunks: &[RetrievedChunk],
+        snapshot: Option<&kb_snapshot::Model>,
     ) -> Result<Vec<SourceDocument>, AiStudioError> {
         debug!("构建来源文档信息");
         
+        if let Some(snapshot) = snapshot {
+            return self.build_snapshot_source_documents(chunks, snapshot).await;
+        }
+        
         let mut document_map: std::collections::HashMap<Uuid, (document::Model, Vec<&RetrievedChunk>)> = 
             std::collections::HashMap::new();
         
@@ -501,8 +1316,11 @@ impl RagEngine {
                 doc_type: format!("{:?}", doc.doc_type),
                 relevance_score,
                 chunk_count: doc_chunks.len() as u32,
+                knowledge_base_id: Some(doc.knowledge_base_id),
+                knowledge_base_name: None,
             });
         }
+        self.fill_knowledge_base_names(&mut source_documents).await?;
         
         // 按相关性分数排序
         source_documents.sort_by(|a, b| b.relevance_score.partial_cmp(&a.relevance_score).unwrap());
@@ -510,22 +1328,175 @@ impl RagEngine {
         Ok(source_documents)
     }
     
+    /// 基于快照记录构建来源文档信息（快照中的文档可能已从知识库删除）
+    async fn build_snapshot_source_documents(
+        &self,
+        chunks: &[RetrievedChunk],
+        snapshot: &kb_snapshot::Model,
+    ) -> Result<Vec<SourceDocument>, AiStudioError> {
+        let documents: std::collections::HashMap<Uuid, kb_snapshot_document::Model> =
+            KbSnapshotService::new(self.db.as_ref().clone())
+                .snapshot_document_models(snapshot.id)
+                .await?
+                .into_iter()
+                .map(|doc| (doc.document_id, doc))
+                .collect();
+        
+        let mut grouped: std::collections::HashMap<Uuid, (f32, u32)> = std::collections::HashMap::new();
+        for chunk in chunks {
+            let entry = grouped.entry(chunk.document_id).or_insert((0.0, 0));
+            entry.0 = entry.0.max(chunk.similarity_score);
+            entry.1 += 1;
+        }
+        
+        let mut source_documents: Vec<SourceDocument> = grouped.into_iter()
+            .filter_map(|(document_id, (relevance_score, chunk_count))| {
+                documents.get(&document_id).map(|doc| SourceDocument {
+                    document_id,
+                    title: doc.title.clone(),
+                    doc_type: doc.file_type.clone().unwrap_or_default(),
+                    relevance_score,
+                    chunk_count,
+                    knowledge_base_id: Some(snapshot.knowledge_base_id),
+                    knowledge_base_name: None,
+                })
+            })
+            .collect();
+        self.fill_knowledge_base_names(&mut source_documents).await?;
+        
+        source_documents.sort_by(|a, b| b.relevance_score.partial_cmp(&a.relevance_score).unwrap());
+        Ok(source_documents)
+    }
+    
+    /// 填充来源文档所属知识库的名称，供引用标注来源知识库
+    async fn fill_knowledge_base_names(&self, documents: &mut [SourceDocument]) -> Result<(), AiStudioError> {
+        let mut ids: Vec<Uuid> = documents.iter().filter_map(|doc| doc.knowledge_base_id).collect();
+        ids.sort();
+        ids.dedup();
+        if ids.is_empty() {
+            return Ok(());
+        }
+        
+        let names: std::collections::HashMap<Uuid, String> = KnowledgeBase::find()
+            .filter(knowledge_base::Column::Id.is_in(ids))
+            .all(self.db.as_ref())
+            .await?
+            .into_iter()
+            .map(|kb| (kb.id, kb.name))
+            .collect();
+        for doc in documents.iter_mut() {
+            doc.knowledge_base_name = doc.knowledge_base_id.and_then(|id| names.get(&id).cloned());
+        }
+        Ok(())
+    }
+    
     /// 记录查询日志
+    ///
+    /// 日志用于热门问题统计与相关问题推荐，写入失败不影响查询结果。
     async fn log_query(
         &self,
         request: &RagQueryRequest,
+        question_embedding: &[f32],
         response: &RagQueryResponse,
-    ) -> Result<(), AiStudioError> {
-        // TODO: 实现查询日志记录
-        // 可以记录到数据库或日志文件中，用于分析和优化
-        
+    ) {
         debug!("记录查询日志: query_id={}, 耗时={}ms", 
                response.query_id, response.query_stats.total_time_ms);
         
-        Ok(())
+        // 拒答时列出的是建议查阅的文档，不作为答案引用
+        let citations: Vec<TranscriptCitation> = if response.insufficient_information {
+            Vec::new()
+        } else {
+            response.source_documents.iter()
+                .map(|doc| TranscriptCitation {
+                    document_id: doc.document_id,
+                    title: doc.title.clone(),
+                    relevance_score: doc.relevance_score,
+                    chunk_ids: response.retrieved_chunks.iter()
+                        .filter(|chunk| chunk.document_id == doc.document_id)
+                        .map(|chunk| chunk.chunk_id)
+                        .collect(),
+                })
+                .collect()
+        };
+
+        let entry = QaTranscriptEntry {
+            tenant_id: request.tenant_id,
+            knowledge_base_id: request.knowledge_base_id,
+            user_id: request.user_id,
+            query_id: &response.query_id,
+            session_id: request.session_id.as_deref(),
+            question: &request.question,
+            embedding: Some(question_embedding),
+            answer: &response.answer,
+            citations: &citations,
+            confidence_score: response.confidence_score,
+            clearance: request.clearance,
+        };
+        if let Err(e) = QaTranscriptService::new(self.db.as_ref().clone()).record(entry).await {
+            warn!("记录查询日志失败: query_id={}, error={}", response.query_id, e);
+        }
     }
 }
 
+/// 按时效权重调整得分，排除已归档或已过期且知识库要求排除的文档
+fn apply_freshness_weights(
+    chunks: &mut Vec<RetrievedChunk>,
+    weights: &std::collections::HashMap<Uuid, Option<f32>>,
+    explanation: &mut RetrievalExplanation,
+) {
+    let mut excluded = 0;
+    chunks.retain_mut(|chunk| match weights.get(&chunk.document_id) {
+        Some(Some(weight)) => {
+            chunk.similarity_score *= weight;
+            true
+        }
+        Some(None) => {
+            explanation.record_drop(chunk.chunk_id, RetrievalStage::Freshness, DropReason::StaleExcluded);
+            excluded += 1;
+            false
+        }
+        None => true,
+    });
+    explanation.record_scores(RetrievalStage::Freshness, chunks, None);
+    explanation.record_filter(RetrievalStage::Freshness, "按文档时效与新近度加权，排除过期与已归档文档", excluded);
+}
+
+/// 记录检索决策日志，请求要求解释时返回去除超出密级明细后的记录
+fn conclude_explanation(
+    query_id: &str,
+    mut explanation: RetrievalExplanation,
+    selected: &[RetrievedChunk],
+    explain: bool,
+) -> Option<RetrievalExplanation> {
+    explanation.finish(selected);
+    info!(
+        query_id = %query_id,
+        candidates = explanation.candidates.len(),
+        selected = explanation.selected_count(),
+        dropped = explanation.dropped_count(),
+        "检索决策记录"
+    );
+    debug!(
+        query_id = %query_id,
+        explanation = %serde_json::to_string(&explanation).unwrap_or_default(),
+        "检索决策明细"
+    );
+    explain.then(|| explanation.redacted())
+}
+
+/// 合并各知识库的检索结果：每个知识库的结果已受配额限制，合并后按得分降序排列并截取前 `top_k` 个
+fn merge_federated_chunks(per_kb: Vec<Vec<RetrievedChunk>>, top_k: usize) -> Vec<RetrievedChunk> {
+    let mut merged: Vec<RetrievedChunk> = Vec::new();
+    for chunk in per_kb.into_iter().flatten() {
+        if !merged.iter().any(|existing| existing.chunk_id == chunk.chunk_id) {
+            merged.push(chunk);
+        }
+    }
+    merged.sort_by(|a, b| b.similarity_score.total_cmp(&a.similarity_score));
+    merged.truncate(top_k);
+    merged
+}
+
 impl Default for RetrievalParams {
     fn default() -> Self {
         Self {
@@ -535,6 +1506,7 @@ impl Default for RetrievalParams {
             enable_reranking: Some(false),
             document_types: None,
             date_range: None,
+            table_mode: TableRetrievalMode::default(),
         }
     }
 }
@@ -547,6 +1519,9 @@ impl Default for GenerationParams {
             include_sources: Some(true),
             language: Some("中文".to_string()),
             style: Some("专业且友好".to_string()),
+            verbosity: None,
+            citation_style: None,
+            timezone: None,
         }
     }
 }
@@ -605,13 +1580,64 @@ mod tests {
         let prompt = engine.build_generation_prompt(
             "什么是人工智能？",
             "人工智能是计算机科学的一个分支...",
-            true,
+            CitationStyle::Inline,
             "中文",
             "专业",
+            None,
+            false,
+            false,
         );
         
         assert!(prompt.contains("什么是人工智能？"));
         assert!(prompt.contains("人工智能是计算机科学的一个分支"));
         assert!(prompt.contains("标注信息来源"));
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_generation_params_with_preferences() {
+        let preferences = UserPreferences {
+            answer_language: Some("English".to_string()),
+            verbosity: AnswerVerbosity::Detailed,
+            citation_style: CitationStyle::Footnote,
+            ..UserPreferences::default()
+        };
+        
+        let params = GenerationParams::with_preferences(None, &preferences);
+        assert_eq!(params.language.as_deref(), Some("English"));
+        assert_eq!(params.style, None);
+        assert_eq!(params.verbosity, Some(AnswerVerbosity::Detailed));
+        assert_eq!(params.citation_style, Some(CitationStyle::Footnote));
+        assert_eq!(params.timezone.as_deref(), Some("Asia/Shanghai"));
+        assert_eq!(params.max_length, Some(1000));
+        
+        // 请求中显式指定的参数优先于偏好
+        let requested = GenerationParams {
+            language: Some("中文".to_string()),
+            citation_style: Some(CitationStyle::None),
+            ..GenerationParams::default()
+        };
+        let params = GenerationParams::with_preferences(Some(requested), &preferences);
+        assert_eq!(params.language.as_deref(), Some("中文"));
+        assert_eq!(params.citation_style, Some(CitationStyle::None));
+        assert_eq!(params.verbosity, Some(AnswerVerbosity::Detailed));
+    }
+    
+    #[test]
+    fn test_merge_federated_chunks_ranks_across_knowledge_bases() {
+        let chunk = |score: f32| RetrievedChunk {
+            chunk_id: Uuid::new_v4(),
+            document_id: Uuid::new_v4(),
+            content: String::new(),
+            similarity_score: score,
+            chunk_index: 0,
+            metadata: serde_json::json!({}),
+        };
+        let hr = vec![chunk(0.9), chunk(0.6)];
+        let legal = vec![chunk(0.8), chunk(0.75)];
+        let expected: Vec<Uuid> = vec![hr[0].chunk_id, legal[0].chunk_id, legal[1].chunk_id];
+        
+        let merged = merge_federated_chunks(vec![hr, legal], 3);
+        assert_eq!(merged.iter().map(|c| c.chunk_id).collect::<Vec<_>>(), expected);
+        assert!(merge_federated_chunks(Vec::new(), 5).is_empty());
+    }
+}
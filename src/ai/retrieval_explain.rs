@@ -0,0 +1,230 @@
+// 检索决策记录
+// 记录问答请求中每个候选文档块在各检索阶段的得分及被淘汰的原因，用于排查相关性问题
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::rag_engine::RetrievedChunk;
+
+/// 检索阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RetrievalStage {
+    /// 向量召回（已按相似度阈值过滤）
+    Vector,
+    /// 按文档时效与新近度加权
+    Freshness,
+    /// 合并多个子查询或多个知识库的结果
+    Merge,
+    /// 按请求者访问密级过滤
+    Clearance,
+    /// 将表格片段扩展为整张表格
+    TableExpansion,
+    /// 按最大上下文长度构建上下文
+    Context,
+}
+
+/// 候选文档块被淘汰的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DropReason {
+    /// 文档已过期且知识库要求排除，或文档及其知识库已归档
+    StaleExcluded,
+    /// 合并后排名超出 top_k
+    BelowTopK,
+    /// 超出请求者的访问密级
+    ClearanceDenied,
+    /// 同一表格已有得分更高的命中
+    DuplicateTable,
+    /// 超出最大上下文长度
+    ContextLimit,
+}
+
+/// 候选在某一阶段的得分
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct StageScore {
+    /// 检索阶段
+    pub stage: RetrievalStage,
+    /// 该阶段结束时的得分
+    pub score: f32,
+}
+
+/// 候选文档块的检索过程
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CandidateExplanation {
+    /// 文档块 ID
+    pub chunk_id: Uuid,
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 召回该候选的知识库（跨知识库问答时记录）
+    pub knowledge_base_id: Option<Uuid>,
+    /// 各阶段得分，按阶段先后排列
+    pub scores: Vec<StageScore>,
+    /// 是否进入最终结果
+    pub selected: bool,
+    /// 在最终结果中的排名（从 1 开始）
+    pub rank: Option<u32>,
+    /// 被淘汰的阶段
+    pub dropped_at: Option<RetrievalStage>,
+    /// 被淘汰的原因
+    pub drop_reason: Option<DropReason>,
+}
+
+/// 生效的过滤条件
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AppliedFilter {
+    /// 检索阶段
+    pub stage: RetrievalStage,
+    /// 过滤条件说明
+    pub description: String,
+    /// 因该条件淘汰的候选数
+    pub dropped: u32,
+}
+
+/// 一次问答请求的检索决策记录
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct RetrievalExplanation {
+    /// 候选文档块，已按访问密级淘汰的候选不列出明细
+    pub candidates: Vec<CandidateExplanation>,
+    /// 生效的过滤条件
+    pub filters: Vec<AppliedFilter>,
+}
+
+impl RetrievalExplanation {
+    /// 记录一批文档块在某阶段的得分，首次出现的文档块作为新候选加入
+    ///
+    /// 同一阶段多次记录（如多个子查询召回同一文档块）时保留最高得分。
+    pub fn record_scores(&mut self, stage: RetrievalStage, chunks: &[RetrievedChunk], knowledge_base_id: Option<Uuid>) {
+        for chunk in chunks {
+            let index = match self.candidates.iter().position(|c| c.chunk_id == chunk.chunk_id) {
+                Some(index) => index,
+                None => {
+                    self.candidates.push(CandidateExplanation {
+                        chunk_id: chunk.chunk_id,
+                        document_id: chunk.document_id,
+                        knowledge_base_id,
+                        scores: Vec::new(),
+                        selected: false,
+                        rank: None,
+                        dropped_at: None,
+                        drop_reason: None,
+                    });
+                    self.candidates.len() - 1
+                }
+            };
+            let scores = &mut self.candidates[index].scores;
+            match scores.iter_mut().find(|s| s.stage == stage) {
+                Some(existing) => existing.score = existing.score.max(chunk.similarity_score),
+                None => scores.push(StageScore { stage, score: chunk.similarity_score }),
+            }
+        }
+    }
+
+    /// 将候选标记为在某阶段被淘汰，已淘汰的候选保留最早的原因
+    pub fn record_drop(&mut self, chunk_id: Uuid, stage: RetrievalStage, reason: DropReason) -> bool {
+        match self.candidates.iter_mut().find(|c| c.chunk_id == chunk_id && c.dropped_at.is_none()) {
+            Some(candidate) => {
+                candidate.dropped_at = Some(stage);
+                candidate.drop_reason = Some(reason);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 将仍在候选中但不在 `kept` 中的文档块标记为淘汰，返回本次淘汰的数量
+    pub fn retain(&mut self, stage: RetrievalStage, reason: DropReason, kept: &[RetrievedChunk]) -> u32 {
+        let mut dropped = 0;
+        for candidate in self.candidates.iter_mut().filter(|c| c.dropped_at.is_none()) {
+            if !kept.iter().any(|chunk| chunk.chunk_id == candidate.chunk_id) {
+                candidate.dropped_at = Some(stage);
+                candidate.drop_reason = Some(reason);
+                dropped += 1;
+            }
+        }
+        dropped
+    }
+
+    /// 记录生效的过滤条件，同一条件多次生效（如按子查询或知识库分别检索）时累加淘汰数量
+    pub fn record_filter(&mut self, stage: RetrievalStage, description: impl Into<String>, dropped: u32) {
+        let description = description.into();
+        match self.filters.iter_mut().find(|f| f.stage == stage && f.description == description) {
+            Some(filter) => filter.dropped += dropped,
+            None => self.filters.push(AppliedFilter { stage, description, dropped }),
+        }
+    }
+
+    /// 按最终结果标记入选的候选及其排名
+    pub fn finish(&mut self, selected: &[RetrievedChunk]) {
+        for (i, chunk) in selected.iter().enumerate() {
+            if let Some(candidate) = self.candidates.iter_mut().find(|c| c.chunk_id == chunk.chunk_id) {
+                candidate.selected = candidate.dropped_at.is_none();
+                candidate.rank = candidate.selected.then_some(i as u32 + 1);
+            }
+        }
+    }
+
+    /// 返回给请求者的记录：去除超出密级的候选明细，只保留过滤条件中的淘汰数量
+    pub fn redacted(mut self) -> Self {
+        self.candidates.retain(|c| c.drop_reason != Some(DropReason::ClearanceDenied));
+        self
+    }
+
+    /// 入选的候选数
+    pub fn selected_count(&self) -> usize {
+        self.candidates.iter().filter(|c| c.selected).count()
+    }
+
+    /// 被淘汰的候选数
+    pub fn dropped_count(&self) -> usize {
+        self.candidates.iter().filter(|c| c.dropped_at.is_some()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: Uuid, score: f32) -> RetrievedChunk {
+        RetrievedChunk {
+            chunk_id: id,
+            document_id: Uuid::nil(),
+            content: String::new(),
+            similarity_score: score,
+            chunk_index: 0,
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_candidate_lifecycle() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut explanation = RetrievalExplanation::default();
+        explanation.record_scores(RetrievalStage::Vector, &[chunk(a, 0.9), chunk(b, 0.8), chunk(c, 0.7)], None);
+        explanation.record_scores(RetrievalStage::Vector, &[chunk(b, 0.85)], None);
+        explanation.record_scores(RetrievalStage::Freshness, &[chunk(a, 0.45)], None);
+        assert!(explanation.record_drop(b, RetrievalStage::Freshness, DropReason::StaleExcluded));
+        assert!(!explanation.record_drop(b, RetrievalStage::Clearance, DropReason::ClearanceDenied));
+
+        let kept = [chunk(a, 0.45)];
+        let dropped = explanation.retain(RetrievalStage::Clearance, DropReason::ClearanceDenied, &kept);
+        assert_eq!(dropped, 1);
+        explanation.record_filter(RetrievalStage::Clearance, "访问密级 public", dropped);
+        explanation.record_filter(RetrievalStage::Clearance, "访问密级 public", 2);
+        explanation.finish(&kept);
+        assert_eq!(explanation.filters.len(), 1);
+        assert_eq!(explanation.filters[0].dropped, 3);
+
+        let b_trace = &explanation.candidates[1];
+        assert_eq!(b_trace.scores, vec![StageScore { stage: RetrievalStage::Vector, score: 0.85 }]);
+        assert_eq!(b_trace.drop_reason, Some(DropReason::StaleExcluded));
+        assert_eq!(explanation.candidates[0].rank, Some(1));
+        assert_eq!(explanation.selected_count(), 1);
+        assert_eq!(explanation.dropped_count(), 2);
+
+        let redacted = explanation.redacted();
+        assert_eq!(redacted.candidates.len(), 2);
+        assert!(redacted.candidates.iter().all(|candidate| candidate.chunk_id != c));
+    }
+}
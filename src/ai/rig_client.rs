@@ -1,8 +1,10 @@
 // 基于 Rig 框架的 AI 客户端实现
 // 使用 rig-core 0.20 版本
 
+use crate::ai::model_router::ModelRouter;
 use crate::config::AiConfig;
 use crate::errors::AiStudioError;
+use crate::services::deadline;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -134,11 +136,13 @@ impl RigAiClient {
         
         #[cfg(feature = "ai")]
         {
-            let response = self
-                .completion_model
-                .prompt(prompt)
-                .await
-                .map_err(|e| AiStudioError::ai(format!("Rig 文本生成失败: {}", e)))?;
+            let response = deadline::run("ai.generate_text", async {
+                self.completion_model
+                    .prompt(prompt)
+                    .await
+                    .map_err(|e| AiStudioError::ai(format!("Rig 文本生成失败: {}", e)))
+            })
+            .await?;
             
             Ok(RigGenerationResponse {
                 text: response.choice.message.content,
@@ -164,11 +168,13 @@ impl RigAiClient {
         
         #[cfg(feature = "ai")]
         {
-            let embeddings = self
-                .embedding_model
-                .embed_documents(vec![text.to_string()])
-                .await
-                .map_err(|e| AiStudioError::ai(format!("Rig 嵌入生成失败: {}", e)))?;
+            let embeddings = deadline::run("ai.generate_embedding", async {
+                self.embedding_model
+                    .embed_documents(vec![text.to_string()])
+                    .await
+                    .map_err(|e| AiStudioError::ai(format!("Rig 嵌入生成失败: {}", e)))
+            })
+            .await?;
             
             if embeddings.is_empty() {
                 return Err(AiStudioError::ai("未生成嵌入向量"));
@@ -197,11 +203,13 @@ impl RigAiClient {
         
         #[cfg(feature = "ai")]
         {
-            let embeddings = self
-                .embedding_model
-                .embed_documents(texts.clone())
-                .await
-                .map_err(|e| AiStudioError::ai(format!("Rig 批量嵌入生成失败: {}", e)))?;
+            let embeddings = deadline::run("ai.generate_embeddings", async {
+                self.embedding_model
+                    .embed_documents(texts.clone())
+                    .await
+                    .map_err(|e| AiStudioError::ai(format!("Rig 批量嵌入生成失败: {}", e)))
+            })
+            .await?;
             
             let model_name = self.get_embedding_model_name();
             let results = embeddings
@@ -259,6 +267,15 @@ impl RigAiClient {
         Err(AiStudioError::ai("AI 功能未启用"))
     }
     
+    /// 完成模型在模型目录中的 ID，用于向模型路由器上报限流
+    pub fn model_id(&self) -> String {
+        if self.config.model_endpoint.contains("openai") {
+            "openai/gpt-3.5-turbo".to_string()
+        } else {
+            "ollama/llama2".to_string()
+        }
+    }
+    
     /// 获取完成模型名称
     fn get_completion_model_name(&self) -> String {
         if self.config.model_endpoint.contains("openai") {
@@ -308,7 +325,13 @@ impl RigAiClientManager {
     
     /// 生成文本
     pub async fn generate_text(&self, prompt: &str) -> Result<RigGenerationResponse, AiStudioError> {
-        self.client.generate_text(prompt).await
+        let result = self.client.generate_text(prompt).await;
+        if let Err(AiStudioError::RateLimit { retry_after }) = &result {
+            ModelRouter::global()
+                .mark_rate_limited(&self.client.model_id(), retry_after.map(std::time::Duration::from_secs))
+                .await;
+        }
+        result
     }
     
     /// 生成嵌入向量
@@ -347,10 +370,12 @@ impl RigAiClientManager {
         let mut last_error = None;
         
         for attempt in 1..=config.retry_attempts {
-            match tokio::time::timeout(
-                std::time::Duration::from_secs(config.timeout),
-                operation()
-            ).await {
+            // 请求截止时间已到时不再重试，单次超时不超过剩余预算
+            if let Err(e) = deadline::check("ai.retry") {
+                return Err(last_error.unwrap_or(e));
+            }
+            let attempt_timeout = deadline::budget(std::time::Duration::from_secs(config.timeout));
+            match tokio::time::timeout(attempt_timeout, operation()).await {
                 Ok(Ok(result)) => {
                     if attempt > 1 {
                         info!("操作在第 {} 次尝试后成功", attempt);
@@ -363,20 +388,20 @@ impl RigAiClientManager {
                     
                     if attempt < config.retry_attempts {
                         let delay = std::time::Duration::from_millis(1000 * attempt as u64);
-                        tokio::time::sleep(delay).await;
+                        tokio::time::sleep(deadline::budget(delay)).await;
                     }
                 }
                 Err(_) => {
                     let timeout_error = AiStudioError::timeout(format!(
-                        "AI 操作超时 ({}s)", 
-                        config.timeout
+                        "AI 操作超时 ({}ms)", 
+                        attempt_timeout.as_millis()
                     ));
                     warn!("第 {} 次尝试超时", attempt);
                     last_error = Some(timeout_error);
                     
                     if attempt < config.retry_attempts {
                         let delay = std::time::Duration::from_millis(1000 * attempt as u64);
-                        tokio::time::sleep(delay).await;
+                        tokio::time::sleep(deadline::budget(delay)).await;
                     }
                 }
             }
@@ -401,6 +426,7 @@ mod tests {
             temperature: 0.7,
             timeout: 30,
             retry_attempts: 3,
+            local: None,
         }
     }
     
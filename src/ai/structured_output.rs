@@ -0,0 +1,206 @@
+// 结构化输出
+// 按调用方提供的 JSON Schema 约束、解析并校验模型输出
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::errors::AiStudioError;
+use crate::plugins::config_schema::schema_violations;
+
+/// 结构化输出校验状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StructuredOutputStatus {
+    /// 首次生成即通过校验
+    Valid,
+    /// 经修复重试后通过校验
+    Repaired,
+    /// 重试耗尽仍未通过校验
+    Invalid,
+}
+
+/// 结构化输出结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StructuredOutput {
+    /// 解析得到的 JSON，无法解析时为空
+    pub value: Option<Value>,
+    /// 校验状态
+    pub status: StructuredOutputStatus,
+    /// 生成尝试次数（含首次）
+    pub attempts: u32,
+    /// 最后一次尝试的校验错误
+    pub errors: Vec<String>,
+}
+
+impl StructuredOutput {
+    /// 按模式解析并校验文本输出
+    pub fn evaluate(text: &str, schema: &Value, attempts: u32) -> Self {
+        let (value, errors) = match parse_and_validate(text, schema) {
+            Ok(value) => (Some(value), Vec::new()),
+            Err((value, errors)) => (value, errors),
+        };
+
+        let status = match (errors.is_empty(), attempts) {
+            (true, 1) => StructuredOutputStatus::Valid,
+            (true, _) => StructuredOutputStatus::Repaired,
+            (false, _) => StructuredOutputStatus::Invalid,
+        };
+
+        Self { value, status, attempts, errors }
+    }
+
+    /// 按模式校验已有的 JSON 值，字符串值会先尝试按 JSON 解析
+    pub fn evaluate_value(value: &Value, schema: &Value) -> Self {
+        match value {
+            Value::String(text) => Self::evaluate(text, schema, 1),
+            _ => {
+                let errors = format_violations(schema, value);
+                Self {
+                    status: if errors.is_empty() { StructuredOutputStatus::Valid } else { StructuredOutputStatus::Invalid },
+                    value: Some(value.clone()),
+                    attempts: 1,
+                    errors,
+                }
+            }
+        }
+    }
+
+    /// 是否通过校验
+    pub fn is_valid(&self) -> bool {
+        self.status != StructuredOutputStatus::Invalid
+    }
+}
+
+/// 检查调用方提供的输出模式
+pub fn check_output_schema(schema: &Value) -> Result<(), AiStudioError> {
+    let object = schema.as_object()
+        .ok_or_else(|| AiStudioError::validation("output_schema", "输出模式必须是 JSON 对象"))?;
+
+    if !object.contains_key("type") && !object.contains_key("properties") {
+        return Err(AiStudioError::validation("output_schema", "输出模式必须声明 type 或 properties"));
+    }
+    Ok(())
+}
+
+/// 生成追加到提示词末尾的输出格式约束
+pub fn schema_instruction(schema: &Value) -> String {
+    format!(
+        r#"
+## 输出格式：
+只输出一个符合以下 JSON Schema 的 JSON 值，不要输出任何解释或 Markdown 代码块标记。
+{}
+"#,
+        serde_json::to_string_pretty(schema).unwrap_or_else(|_| schema.to_string())
+    )
+}
+
+/// 生成修复提示词，要求模型根据校验错误修正上一次输出
+pub fn repair_prompt(prompt: &str, previous_output: &str, errors: &[String]) -> String {
+    format!(
+        r#"{}
+
+## 上一次输出：
+{}
+
+## 校验错误：
+{}
+
+请修正上述错误，重新只输出符合 JSON Schema 的 JSON 值。
+"#,
+        prompt,
+        previous_output,
+        errors.iter().map(|e| format!("- {}", e)).collect::<Vec<_>>().join("\n")
+    )
+}
+
+/// 从模型输出中提取 JSON，兼容 Markdown 代码块和前后附带的说明文字
+pub fn extract_json(text: &str) -> Option<Value> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"))
+        .map(str::trim);
+    if let Some(value) = unfenced.and_then(|body| serde_json::from_str(body).ok()) {
+        return Some(value);
+    }
+
+    let start = trimmed.find(['{', '['])?;
+    let end = trimmed.rfind(['}', ']'])?;
+    if end <= start {
+        return None;
+    }
+    serde_json::from_str(&trimmed[start..=end]).ok()
+}
+
+fn parse_and_validate(text: &str, schema: &Value) -> Result<Value, (Option<Value>, Vec<String>)> {
+    let value = extract_json(text).ok_or_else(|| (None, vec!["输出不是有效的 JSON".to_string()]))?;
+
+    let errors = format_violations(schema, &value);
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err((Some(value), errors))
+    }
+}
+
+fn format_violations(schema: &Value, value: &Value) -> Vec<String> {
+    schema_violations(schema, value)
+        .into_iter()
+        .map(|v| format!("{}: {}", v.path, v.message))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["answer", "confidence"],
+            "properties": {
+                "answer": { "type": "string" },
+                "confidence": { "type": "number", "minimum": 0, "maximum": 1 }
+            }
+        })
+    }
+
+    #[test]
+    fn test_extract_json() {
+        assert_eq!(extract_json(r#"{"a":1}"#), Some(json!({"a": 1})));
+        assert_eq!(extract_json("```json\n{\"a\":1}\n```"), Some(json!({"a": 1})));
+        assert_eq!(extract_json("结果如下：[1, 2] 以上。"), Some(json!([1, 2])));
+        assert_eq!(extract_json("没有 JSON"), None);
+    }
+
+    #[test]
+    fn test_evaluate_status() {
+        let valid = StructuredOutput::evaluate(r#"{"answer":"是","confidence":0.9}"#, &schema(), 1);
+        assert_eq!(valid.status, StructuredOutputStatus::Valid);
+
+        let repaired = StructuredOutput::evaluate(r#"{"answer":"是","confidence":0.9}"#, &schema(), 2);
+        assert_eq!(repaired.status, StructuredOutputStatus::Repaired);
+
+        let invalid = StructuredOutput::evaluate(r#"{"answer":"是","confidence":3}"#, &schema(), 3);
+        assert_eq!(invalid.status, StructuredOutputStatus::Invalid);
+        assert!(invalid.value.is_some());
+        assert_eq!(invalid.errors, vec!["$.confidence: 不能大于 1".to_string()]);
+
+        let unparsable = StructuredOutput::evaluate("抱歉", &schema(), 1);
+        assert!(unparsable.value.is_none() && !unparsable.is_valid());
+    }
+
+    #[test]
+    fn test_check_output_schema() {
+        assert!(check_output_schema(&schema()).is_ok());
+        assert!(check_output_schema(&json!("string")).is_err());
+        assert!(check_output_schema(&json!({})).is_err());
+    }
+}
@@ -0,0 +1,493 @@
+// 表格抽取
+// 从 CSV 与文本中的 Markdown 表格抽取结构化表格，按行拆分为带表头的表格块，并在组装提示词时还原为 Markdown 表格
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::ai::document_processor::TableInfo;
+use crate::db::entities::document_chunk::ChunkTable;
+use crate::errors::AiStudioError;
+
+/// 整表返回时最多保留的数据行数
+pub const MAX_WHOLE_TABLE_ROWS: usize = 500;
+
+/// 单个工作表最多读取的数据行数
+pub const MAX_SHEET_ROWS: usize = 200_000;
+
+/// 上下文包含表格时附加的答题要求
+pub const TABLE_ANSWER_INSTRUCTION: &str =
+    "文档中的表格以 Markdown 形式给出，涉及数值时按表头与行准确引用原值，需要计算时写出所用数值与计算过程";
+
+/// 从文本中抽取的表格及其在原文中的位置
+#[derive(Debug, Clone)]
+pub struct ExtractedTable {
+    pub table: TableInfo,
+    pub start_char: usize,
+    pub end_char: usize,
+    pub source_page: Option<u32>,
+}
+
+/// 解析分隔符文本（CSV/TSV），首行为表头
+///
+/// 支持双引号包裹的字段与 `""` 转义，字段内可包含分隔符和换行；全为空的行会被跳过。
+pub fn parse_delimited(content: &str, delimiter: char) -> Option<TableInfo> {
+    let mut records: Vec<Vec<String>> = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == delimiter {
+            record.push(field.trim().to_string());
+            field.clear();
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            record.push(field.trim().to_string());
+            field.clear();
+            push_record(&mut records, std::mem::take(&mut record));
+        } else {
+            field.push(c);
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field.trim().to_string());
+        push_record(&mut records, record);
+    }
+
+    let mut records = records.into_iter();
+    let headers = records.next()?;
+    Some(TableInfo {
+        id: "table-1".to_string(),
+        caption: None,
+        rows: records.map(|row| normalize_row(row, headers.len())).collect(),
+        headers,
+    })
+}
+
+/// 解析 Excel（XLSX）工作簿，每个非空工作表返回一张表格，首个非空行为表头，标题为工作表名
+///
+/// 只读取单元格的值：日期按 Excel 序列号以数字返回，公式取缓存的计算结果。
+pub fn parse_xlsx(bytes: &[u8]) -> Result<Vec<TableInfo>, AiStudioError> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| AiStudioError::file_processing(format!("无效的 Excel 文件: {}", e)))?;
+
+    let workbook = read_zip_entry(&mut archive, "xl/workbook.xml")?
+        .ok_or_else(|| AiStudioError::file_processing("Excel 文件缺少 workbook.xml"))?;
+    let relationships = read_zip_entry(&mut archive, "xl/_rels/workbook.xml.rels")?.unwrap_or_default();
+    let shared_strings = read_zip_entry(&mut archive, "xl/sharedStrings.xml")?
+        .map(|xml| parse_shared_strings(&xml))
+        .unwrap_or_default();
+
+    let targets: HashMap<String, String> = xml_tags(&relationships, "Relationship")
+        .into_iter()
+        .filter_map(|attrs| Some((attrs.get("Id")?.clone(), attrs.get("Target")?.clone())))
+        .collect();
+
+    let mut tables = Vec::new();
+    for sheet in xml_tags(&workbook, "sheet") {
+        let (Some(name), Some(target)) = (
+            sheet.get("name"),
+            sheet.get("r:id").and_then(|id| targets.get(id)),
+        ) else {
+            continue;
+        };
+        let path = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{}", target),
+        };
+        let Some(xml) = read_zip_entry(&mut archive, &path)? else {
+            continue;
+        };
+
+        let mut rows = parse_sheet_rows(&xml, &shared_strings).into_iter();
+        let Some(headers) = rows.next() else {
+            continue;
+        };
+        tables.push(TableInfo {
+            id: format!("table-{}", tables.len() + 1),
+            caption: Some(name.clone()),
+            rows: rows.map(|row| normalize_row(row, headers.len())).collect(),
+            headers,
+        });
+    }
+    Ok(tables)
+}
+
+fn read_zip_entry(
+    archive: &mut zip::ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<Option<String>, AiStudioError> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(AiStudioError::file_processing(format!("读取 Excel 文件失败: {}", e))),
+    };
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .map_err(|e| AiStudioError::file_processing(format!("读取 Excel 文件失败: {}", e)))?;
+    Ok(Some(content))
+}
+
+/// 读取 XML 中指定标签的属性
+fn xml_tags(xml: &str, tag: &str) -> Vec<HashMap<String, String>> {
+    let tag_re = Regex::new(&format!(r"<{}\s([^>]*?)/?>", regex::escape(tag))).unwrap();
+    tag_re.captures_iter(xml).map(|caps| xml_attributes(&caps[1])).collect()
+}
+
+fn xml_attributes(text: &str) -> HashMap<String, String> {
+    let attr_re = Regex::new(r#"([\w:]+)="([^"]*)""#).unwrap();
+    attr_re
+        .captures_iter(text)
+        .map(|caps| (caps[1].to_string(), unescape_xml(&caps[2])))
+        .collect()
+}
+
+fn parse_shared_strings(xml: &str) -> Vec<String> {
+    let si_re = Regex::new(r"(?s)<si>(.*?)</si>").unwrap();
+    si_re.captures_iter(xml).map(|caps| xml_text(&caps[1])).collect()
+}
+
+/// 拼接 `<t>` 文本节点，富文本的多个片段合并为一个字符串
+fn xml_text(xml: &str) -> String {
+    let t_re = Regex::new(r"(?s)<t(?:\s[^>]*)?>(.*?)</t>").unwrap();
+    t_re.captures_iter(xml).map(|caps| unescape_xml(&caps[1])).collect()
+}
+
+fn parse_sheet_rows(xml: &str, shared_strings: &[String]) -> Vec<Vec<String>> {
+    let row_re = Regex::new(r"(?s)<row\b[^>]*?(?:/>|>(.*?)</row>)").unwrap();
+    let cell_re = Regex::new(r"(?s)<c\b([^>]*?)(?:/>|>(.*?)</c>)").unwrap();
+    let value_re = Regex::new(r"(?s)<v>(.*?)</v>").unwrap();
+
+    let mut rows = Vec::new();
+    for row_caps in row_re.captures_iter(xml) {
+        let Some(cells_xml) = row_caps.get(1) else {
+            continue;
+        };
+        let mut row: Vec<String> = Vec::new();
+        for cell_caps in cell_re.captures_iter(cells_xml.as_str()) {
+            let attrs = xml_attributes(&cell_caps[1]);
+            let inner = cell_caps.get(2).map(|m| m.as_str()).unwrap_or_default();
+            let raw = value_re.captures(inner).map(|caps| unescape_xml(&caps[1]));
+            let value = match attrs.get("t").map(String::as_str) {
+                Some("s") => raw
+                    .and_then(|index| index.trim().parse::<usize>().ok())
+                    .and_then(|index| shared_strings.get(index).cloned())
+                    .unwrap_or_default(),
+                Some("inlineStr") => xml_text(inner),
+                Some("b") => match raw.as_deref() {
+                    Some("1") => "true".to_string(),
+                    Some(_) => "false".to_string(),
+                    None => String::new(),
+                },
+                _ => raw.unwrap_or_default(),
+            };
+            let column = attrs.get("r").and_then(|r| column_index(r)).unwrap_or(row.len());
+            if column >= row.len() {
+                row.resize(column + 1, String::new());
+            }
+            row[column] = value.trim().to_string();
+        }
+        if row.iter().any(|cell| !cell.is_empty()) {
+            rows.push(row);
+            if rows.len() > MAX_SHEET_ROWS {
+                break;
+            }
+        }
+    }
+    rows
+}
+
+/// 单元格引用（如 `AB12`）对应的列号，从 0 开始
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<char> = reference.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let index = letters.iter().fold(0usize, |acc, c| {
+        acc * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1)
+    });
+    Some(index - 1)
+}
+
+fn unescape_xml(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let entity_re = Regex::new(r"&(#x[0-9a-fA-F]+|#[0-9]+|amp|lt|gt|quot|apos);").unwrap();
+    entity_re
+        .replace_all(text, |caps: &regex::Captures| match &caps[1] {
+            "amp" => "&".to_string(),
+            "lt" => "<".to_string(),
+            "gt" => ">".to_string(),
+            "quot" => "\"".to_string(),
+            "apos" => "'".to_string(),
+            code => {
+                let number = match code.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => code[1..].parse().ok(),
+                };
+                number.and_then(char::from_u32).map(String::from).unwrap_or_default()
+            }
+        })
+        .into_owned()
+}
+
+fn push_record(records: &mut Vec<Vec<String>>, record: Vec<String>) {
+    if record.iter().any(|field| !field.is_empty()) {
+        records.push(record);
+    }
+}
+
+/// 将数据行补齐或截断到表头列数
+fn normalize_row(mut row: Vec<String>, columns: usize) -> Vec<String> {
+    row.resize(columns, String::new());
+    row
+}
+
+/// 抽取文本中的 Markdown 管道表格
+///
+/// 表格由表头行、分隔行（如 `| --- | :-: |`）与其后连续的 `|` 开头行组成，表格前一行是不超过
+/// 100 字符的短句时作为标题。返回去除表格后的文本与抽取到的表格。
+pub fn extract_markdown_tables(text: &str) -> (String, Vec<ExtractedTable>) {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut remaining = String::with_capacity(text.len());
+    let mut tables = Vec::new();
+    let mut offset = 0;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let is_table_start = i + 1 < lines.len()
+            && is_pipe_row(lines[i])
+            && is_separator_row(lines[i + 1]);
+        if !is_table_start {
+            remaining.push_str(lines[i]);
+            offset += lines[i].len();
+            i += 1;
+            continue;
+        }
+
+        let start_char = offset;
+        let headers = split_pipe_row(lines[i]);
+        offset += lines[i].len() + lines[i + 1].len();
+        i += 2;
+        let mut rows = Vec::new();
+        while i < lines.len() && is_pipe_row(lines[i]) {
+            rows.push(normalize_row(split_pipe_row(lines[i]), headers.len()));
+            offset += lines[i].len();
+            i += 1;
+        }
+
+        let caption = remaining
+            .trim_end()
+            .rsplit('\n')
+            .next()
+            .map(|line| line.trim().trim_start_matches('#').trim())
+            .filter(|line| !line.is_empty() && line.chars().count() <= 100)
+            .map(|line| line.to_string());
+        tables.push(ExtractedTable {
+            table: TableInfo {
+                id: format!("table-{}", tables.len() + 1),
+                caption,
+                headers,
+                rows,
+            },
+            start_char,
+            end_char: offset,
+            source_page: None,
+        });
+    }
+
+    (remaining, tables)
+}
+
+fn is_pipe_row(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('|') && line.len() > 1
+}
+
+fn is_separator_row(line: &str) -> bool {
+    let line = line.trim();
+    is_pipe_row(line)
+        && line.contains('-')
+        && line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+}
+
+fn split_pipe_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+/// 将表格按行拆分为表格块，每块渲染后的长度不超过 `max_chars`，每块至少一行
+pub fn split_table(table_id: &str, table: &TableInfo, max_chars: usize) -> Vec<ChunkTable> {
+    let total_rows = table.rows.len() as u32;
+    let header_len = render_markdown_table(table.caption.as_deref(), &table.headers, &[]).len();
+
+    let mut slices = Vec::new();
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut row_offset = 0u32;
+    let mut length = header_len;
+    for row in &table.rows {
+        let row_len = render_row(row).len();
+        if !rows.is_empty() && length + row_len > max_chars {
+            let rows_in_slice = rows.len() as u32;
+            slices.push(chunk_table(table_id, table, std::mem::take(&mut rows), row_offset, total_rows));
+            row_offset += rows_in_slice;
+            length = header_len;
+        }
+        rows.push(row.clone());
+        length += row_len;
+    }
+    if !rows.is_empty() || slices.is_empty() {
+        slices.push(chunk_table(table_id, table, rows, row_offset, total_rows));
+    }
+    slices
+}
+
+fn chunk_table(table_id: &str, table: &TableInfo, rows: Vec<Vec<String>>, row_offset: u32, total_rows: u32) -> ChunkTable {
+    ChunkTable {
+        table_id: table_id.to_string(),
+        caption: table.caption.clone(),
+        headers: table.headers.clone(),
+        rows,
+        row_offset,
+        total_rows,
+    }
+}
+
+/// 渲染 Markdown 表格
+pub fn render_markdown_table(caption: Option<&str>, headers: &[String], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    if let Some(caption) = caption {
+        out.push_str(&format!("表格：{}\n", caption));
+    }
+    out.push_str(&render_row(headers));
+    out.push_str(&render_row(&vec!["---".to_string(); headers.len()]));
+    for row in rows {
+        out.push_str(&render_row(row));
+    }
+    out
+}
+
+fn render_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|cell| cell.replace('|', "\\|").replace(['\r', '\n'], " "))
+        .collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+/// 渲染表格块，只含部分行时注明行号范围
+pub fn render_chunk_table(table: &ChunkTable) -> String {
+    let mut out = render_markdown_table(table.caption.as_deref(), &table.headers, &table.rows);
+    let rows = table.rows.len() as u32;
+    if rows < table.total_rows {
+        out.push_str(&format!(
+            "（第 {}-{} 行，共 {} 行）\n",
+            table.row_offset + 1,
+            table.row_offset + rows,
+            table.total_rows
+        ));
+    }
+    out
+}
+
+/// 读取文档块元数据中的表格内容
+pub fn chunk_table_from_metadata(metadata: &Value) -> Option<ChunkTable> {
+    serde_json::from_value(metadata.get("table")?.clone()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_delimited_with_quotes() {
+        let csv = "型号,功率(W),备注\r\nA-100,\"1,200\",\"含\"\"快充\"\"\"\n\nB-200,800\n";
+        let table = parse_delimited(csv, ',').unwrap();
+        assert_eq!(table.headers, vec!["型号", "功率(W)", "备注"]);
+        assert_eq!(table.rows, vec![
+            vec!["A-100", "1,200", "含\"快充\""],
+            vec!["B-200", "800", ""],
+        ]);
+    }
+
+    #[test]
+    fn test_extract_and_split_markdown_table() {
+        let text = "概述段落。\n\n季度营收\n| 季度 | 营收 |\n| --- | ---: |\n| Q1 | 120 |\n| Q2 | 135 |\n| Q3 | 150 |\n\n结论段落。\n";
+        let (remaining, tables) = extract_markdown_tables(text);
+        assert_eq!(remaining, "概述段落。\n\n季度营收\n\n结论段落。\n");
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0].table;
+        assert_eq!(table.caption.as_deref(), Some("季度营收"));
+        assert_eq!(table.headers, vec!["季度", "营收"]);
+        assert_eq!(table.rows.len(), 3);
+
+        let header_len = render_markdown_table(table.caption.as_deref(), &table.headers, &[]).len();
+        let slices = split_table("t1", table, header_len + 30);
+        assert_eq!(slices.len(), 2);
+        assert_eq!((slices[1].row_offset, slices[1].total_rows), (2, 3));
+        assert_eq!(slices[1].headers, table.headers);
+        assert!(render_chunk_table(&slices[1]).ends_with("| Q3 | 150 |\n（第 3-3 行，共 3 行）\n"));
+    }
+
+    #[test]
+    fn test_parse_xlsx_shared_strings() {
+        use std::io::Write;
+
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buffer);
+            let options = zip::write::FileOptions::default();
+            let files = [
+                ("xl/workbook.xml", r#"<workbook><sheets><sheet name="销售" sheetId="1" r:id="rId1"/></sheets></workbook>"#),
+                ("xl/_rels/workbook.xml.rels", r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/></Relationships>"#),
+                ("xl/sharedStrings.xml", r#"<sst><si><t>地区</t></si><si><t>营收</t></si><si><t>华东 &amp; 华北</t></si></sst>"#),
+                ("xl/worksheets/sheet1.xml", r#"<worksheet><sheetData><row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="s"><v>1</v></c></row><row r="2"><c r="A2" t="s"><v>2</v></c><c r="C2"><v>9</v></c><c r="B2"><v>1200.5</v></c></row></sheetData></worksheet>"#),
+            ];
+            for (name, content) in files {
+                zip.start_file(name, options).unwrap();
+                zip.write_all(content.as_bytes()).unwrap();
+            }
+            zip.finish().unwrap();
+        }
+
+        let tables = parse_xlsx(buffer.get_ref()).unwrap();
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].caption.as_deref(), Some("销售"));
+        assert_eq!(tables[0].headers, vec!["地区", "营收"]);
+        assert_eq!(tables[0].rows, vec![vec!["华东 & 华北", "1200.5"]]);
+    }
+}
@@ -11,7 +11,9 @@ use tokio::sync::RwLock;
 use async_trait::async_trait;
 
 use crate::ai::agent_runtime::{Tool, ToolResult, ToolMetadata, ExecutionContext, ToolEnum};
+use crate::ai::tool_output::{check_tool_output_schema, enforce_output_schema};
 use crate::errors::AiStudioError;
+use crate::services::deadline;
 
 /// 工具管理器
 pub struct ToolManager {
@@ -327,12 +329,13 @@ impl ToolManager {
         // 验证参数
         tool.validate_parameters(&request.parameters)?;
         
-        // 执行工具
+        // 执行工具，超时不超过请求截止时间的剩余预算
+        deadline::check(&format!("tool.{}", request.tool_name))?;
         let execution_start = std::time::Instant::now();
         let result = match tokio::time::timeout(
-            std::time::Duration::from_secs(
+            deadline::budget(std::time::Duration::from_secs(
                 request.timeout_seconds.unwrap_or(self.config.default_timeout_seconds)
-            ),
+            )),
             tool.execute(request.parameters.clone(), &request.context)
         ).await {
             Ok(Ok(result)) => result,
@@ -358,6 +361,12 @@ impl ToolManager {
             }
         };
         
+        // 按声明的输出模式校验结果
+        let result = match self.metadata.read().await.get(&request.tool_name) {
+            Some(metadata) => enforce_output_schema(metadata, result),
+            None => result,
+        };
+        
         let end_time = Utc::now();
         let execution_time_ms = execution_start.elapsed().as_millis() as u64;
         
@@ -406,6 +415,7 @@ impl ToolManager {
                     name: tool_name.clone(),
                     description: "无描述".to_string(),
                     parameters_schema: serde_json::Value::Null,
+                    output_schema: None,
                     category: "unknown".to_string(),
                     requires_permission: false,
                     version: "1.0.0".to_string(),
@@ -504,6 +514,8 @@ impl ToolManager {
             return Err(AiStudioError::validation("description", "工具描述长度不能超过 1000 字符"));
         }
         
+        check_tool_output_schema(metadata)?;
+        
         Ok(())
     }
     
@@ -636,6 +648,7 @@ mod tests {
                 context_variables: HashMap::new(),
                 session_id: None,
                 user_id: None,
+                tool_progress: None,
             },
             call_id: Uuid::new_v4(),
             timeout_seconds: None,
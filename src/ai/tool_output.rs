@@ -0,0 +1,178 @@
+// 工具输出校验
+// 按工具元数据声明的输出模式校验工具结果，并在不丢失信息的前提下进行类型转换
+
+use serde_json::{Number, Value};
+use tracing::warn;
+
+use crate::ai::agent_runtime::{ToolMetadata, ToolResult};
+use crate::errors::AiStudioError;
+use crate::plugins::config_schema::schema_violations;
+
+/// 按声明的输出模式规范化工具结果
+///
+/// 未声明模式或工具本身执行失败时原样返回；转换后仍不符合模式的结果被标记为失败，
+/// 数据置空，避免下游步骤读取到形状不符的数据。
+pub fn enforce_output_schema(metadata: &ToolMetadata, mut result: ToolResult) -> ToolResult {
+    let Some(schema) = metadata.output_schema.as_ref() else {
+        return result;
+    };
+    if !result.success {
+        return result;
+    }
+
+    let data = coerce_to_schema(schema, result.data);
+    let violations = schema_violations(schema, &data);
+    if violations.is_empty() {
+        result.data = data;
+        return result;
+    }
+
+    let details = violations.iter()
+        .map(|v| format!("{}: {}", v.path, v.message))
+        .collect::<Vec<_>>()
+        .join("; ");
+    warn!("工具输出不符合声明的模式: tool={}, violations={}", metadata.name, details);
+
+    result.success = false;
+    result.data = Value::Null;
+    result.error = Some(format!("工具 {} 的输出不符合声明的模式: {}", metadata.name, details));
+    result.message = Some("工具输出校验失败".to_string());
+    result
+}
+
+/// 检查工具声明的输出模式
+pub fn check_tool_output_schema(metadata: &ToolMetadata) -> Result<(), AiStudioError> {
+    match &metadata.output_schema {
+        Some(schema) if !schema.is_object() => {
+            Err(AiStudioError::validation("output_schema", "工具输出模式必须是 JSON 对象"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 按模式递归转换值的类型
+///
+/// 只做无歧义的转换：数字/布尔字符串转为对应类型、无小数部分的浮点数转为整数、
+/// 数字与布尔值转为字符串。类型已匹配或声明了多个类型时不做转换。
+pub fn coerce_to_schema(schema: &Value, value: Value) -> Value {
+    let value = match schema.get("type").and_then(Value::as_str) {
+        Some(expected) => coerce_scalar(expected, value),
+        None => value,
+    };
+
+    match value {
+        Value::Object(mut object) => {
+            if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                for (name, field_schema) in properties {
+                    if let Some(field) = object.remove(name) {
+                        object.insert(name.clone(), coerce_to_schema(field_schema, field));
+                    }
+                }
+            }
+            Value::Object(object)
+        }
+        Value::Array(items) => match schema.get("items") {
+            Some(item_schema) => Value::Array(
+                items.into_iter().map(|item| coerce_to_schema(item_schema, item)).collect(),
+            ),
+            None => Value::Array(items),
+        },
+        other => other,
+    }
+}
+
+fn coerce_scalar(expected: &str, value: Value) -> Value {
+    match (expected, value) {
+        ("integer", Value::String(text)) => match text.trim().parse::<i64>() {
+            Ok(number) => Value::from(number),
+            Err(_) => Value::String(text),
+        },
+        ("integer", Value::Number(number)) if !number.is_i64() && !number.is_u64() => {
+            match number.as_f64() {
+                Some(float) if float.fract() == 0.0 && float.abs() < i64::MAX as f64 => Value::from(float as i64),
+                _ => Value::Number(number),
+            }
+        }
+        ("number", Value::String(text)) => match text.trim().parse::<f64>().ok().and_then(Number::from_f64) {
+            Some(number) => Value::Number(number),
+            None => Value::String(text),
+        },
+        ("boolean", Value::String(text)) => match text.trim() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => Value::String(text),
+        },
+        ("string", Value::Number(number)) => Value::String(number.to_string()),
+        ("string", Value::Bool(flag)) => Value::String(flag.to_string()),
+        (_, value) => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn metadata(output_schema: Option<Value>) -> ToolMetadata {
+        ToolMetadata {
+            name: "lookup".to_string(),
+            description: "测试工具".to_string(),
+            parameters_schema: json!({}),
+            output_schema,
+            category: "test".to_string(),
+            requires_permission: false,
+            version: "1.0.0".to_string(),
+        }
+    }
+
+    fn result(data: Value) -> ToolResult {
+        ToolResult {
+            success: true,
+            data,
+            error: None,
+            execution_time_ms: 1,
+            message: None,
+        }
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["count", "items"],
+            "properties": {
+                "count": { "type": "integer" },
+                "ratio": { "type": "number" },
+                "ok": { "type": "boolean" },
+                "label": { "type": "string" },
+                "items": { "type": "array", "items": { "type": "integer" } }
+            }
+        })
+    }
+
+    #[test]
+    fn test_safe_coercion() {
+        let data = json!({ "count": "3", "ratio": "0.5", "ok": "true", "label": 42, "items": [1.0, "2"] });
+        let coerced = coerce_to_schema(&schema(), data);
+        assert_eq!(coerced, json!({ "count": 3, "ratio": 0.5, "ok": true, "label": "42", "items": [1, 2] }));
+
+        // 有损转换不做
+        assert_eq!(coerce_to_schema(&json!({ "type": "integer" }), json!(1.5)), json!(1.5));
+        assert_eq!(coerce_to_schema(&json!({ "type": "boolean" }), json!("yes")), json!("yes"));
+    }
+
+    #[test]
+    fn test_enforce_output_schema() {
+        let valid = enforce_output_schema(&metadata(Some(schema())), result(json!({ "count": "2", "items": [] })));
+        assert!(valid.success);
+        assert_eq!(valid.data["count"], json!(2));
+
+        let invalid = enforce_output_schema(&metadata(Some(schema())), result(json!({ "count": "many" })));
+        assert!(!invalid.success);
+        assert_eq!(invalid.data, Value::Null);
+        let error = invalid.error.unwrap();
+        assert!(error.contains("$.count") && error.contains("$.items"));
+
+        let undeclared = enforce_output_schema(&metadata(None), result(json!("anything")));
+        assert!(undeclared.success);
+    }
+}
@@ -118,6 +118,14 @@ impl Tool for CalculatorTool {
                 },
                 "required": ["operation", "a"]
             }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "required": ["operation", "result"],
+                "properties": {
+                    "operation": { "type": "string" },
+                    "result": { "type": "number" }
+                }
+            })),
             category: "math".to_string(),
             requires_permission: false,
             version: "1.0.0".to_string(),
@@ -280,6 +288,7 @@ mod tests {
             context_variables: HashMap::new(),
             session_id: None,
             user_id: None,
+            tool_progress: None,
         };
         
         let result = tool.execute(parameters, &context).await.unwrap();
@@ -301,6 +310,7 @@ mod tests {
             context_variables: HashMap::new(),
             session_id: None,
             user_id: None,
+            tool_progress: None,
         };
         
         let result = tool.execute(parameters, &context).await;
@@ -0,0 +1,195 @@
+// 数据集查询工具实现
+
+use std::collections::HashMap;
+use sea_orm::DatabaseConnection;
+use serde_json;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::ai::agent_runtime::{Tool, ToolResult, ToolMetadata, ExecutionContext};
+use crate::errors::AiStudioError;
+use crate::services::dataset::{DatasetQuery, DatasetService};
+
+/// 数据集查询工具
+///
+/// 对 CSV/Excel 导入的数据集执行过滤、分组与聚合，用于回答“第三季度各地区营收合计”这类统计问题。
+#[derive(Debug, Clone)]
+pub struct DatasetQueryTool {
+    db: DatabaseConnection,
+}
+
+impl DatasetQueryTool {
+    /// 创建新的数据集查询工具
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+/// 从执行上下文读取租户 ID
+fn context_tenant_id(context: &ExecutionContext) -> Result<Uuid, AiStudioError> {
+    context.context_variables.get("tenant_id")
+        .and_then(|v| v.as_str())
+        .and_then(|v| Uuid::parse_str(v).ok())
+        .ok_or_else(|| AiStudioError::validation("tenant_id", "执行上下文缺少租户信息"))
+}
+
+fn uuid_parameter(parameters: &HashMap<String, serde_json::Value>, name: &str) -> Result<Option<Uuid>, AiStudioError> {
+    match parameters.get(name) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => value.as_str()
+            .and_then(|v| Uuid::parse_str(v).ok())
+            .map(Some)
+            .ok_or_else(|| AiStudioError::validation(name, "必须是有效的 UUID")),
+    }
+}
+
+impl Tool for DatasetQueryTool {
+    fn execute<'life0, 'life1, 'async_trait>(
+        &'life0 self,
+        parameters: HashMap<String, serde_json::Value>,
+        context: &'life1 ExecutionContext,
+    ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<ToolResult, AiStudioError>> + core::marker::Send + 'async_trait>>
+    where
+        'life0: 'async_trait,
+        'life1: 'async_trait,
+        Self: 'async_trait,
+    {
+        Box::pin(async move {
+        debug!("执行数据集查询工具");
+
+        let tenant_id = context_tenant_id(context)?;
+        let service = DatasetService::new(self.db.clone());
+        let start_time = std::time::Instant::now();
+
+        // 未指定数据集时列出可用数据集及其列
+        let Some(dataset_id) = uuid_parameter(&parameters, "dataset_id")? else {
+            let knowledge_base_id = uuid_parameter(&parameters, "knowledge_base_id")?;
+            let datasets = service.list(tenant_id, knowledge_base_id).await?;
+            return Ok(ToolResult {
+                success: true,
+                message: Some(format!("找到 {} 个数据集", datasets.len())),
+                data: serde_json::json!({ "datasets": datasets }),
+                error: None,
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+            });
+        };
+
+        let query: DatasetQuery = serde_json::from_value(serde_json::Value::Object(
+            parameters.iter()
+                .filter(|(key, _)| !matches!(key.as_str(), "dataset_id" | "knowledge_base_id"))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        ))
+        .map_err(|e| AiStudioError::validation("query", format!("查询参数无效: {}", e)))?;
+
+        let result = service.query(tenant_id, dataset_id, &query).await?;
+
+        Ok(ToolResult {
+            success: true,
+            message: Some(format!("查询返回 {} 行", result.rows.len())),
+            data: serde_json::to_value(&result)?,
+            error: None,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+        })
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            name: "dataset_query".to_string(),
+            description: "查询 CSV/Excel 导入的数据集：不传 dataset_id 时列出数据集及列定义，传入后按条件过滤、分组并计算 count/sum/avg/min/max".to_string(),
+            parameters_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "dataset_id": {
+                        "type": "string",
+                        "description": "数据集 ID，省略时返回数据集列表"
+                    },
+                    "knowledge_base_id": {
+                        "type": "string",
+                        "description": "列出数据集时按知识库过滤"
+                    },
+                    "group_by": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "分组列名"
+                    },
+                    "aggregates": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "function": {
+                                    "type": "string",
+                                    "enum": ["count", "count_distinct", "sum", "avg", "min", "max"]
+                                },
+                                "column": { "type": "string" }
+                            },
+                            "required": ["function"]
+                        },
+                        "description": "聚合项，sum/avg 仅适用于数值列"
+                    },
+                    "filters": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "column": { "type": "string" },
+                                "op": {
+                                    "type": "string",
+                                    "enum": ["eq", "ne", "gt", "gte", "lt", "lte", "contains", "is_null", "not_null"]
+                                },
+                                "value": {}
+                            },
+                            "required": ["column", "op"]
+                        },
+                        "description": "过滤条件，全部满足"
+                    },
+                    "order_by": {
+                        "type": "object",
+                        "properties": {
+                            "field": { "type": "string", "description": "分组列名或聚合结果列名，如 sum(营收)" },
+                            "descending": { "type": "boolean", "default": false }
+                        },
+                        "required": ["field"]
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 1000,
+                        "default": 100
+                    }
+                }
+            }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "datasets": { "type": "array" },
+                    "columns": { "type": "array", "items": { "type": "string" } },
+                    "rows": { "type": "array" },
+                    "truncated": { "type": "boolean" }
+                }
+            })),
+            category: "data".to_string(),
+            requires_permission: false,
+            version: "1.0.0".to_string(),
+        }
+    }
+
+    fn validate_parameters(
+        &self,
+        parameters: &HashMap<String, serde_json::Value>,
+    ) -> Result<(), AiStudioError> {
+        uuid_parameter(parameters, "dataset_id")?;
+        uuid_parameter(parameters, "knowledge_base_id")?;
+
+        if let Some(limit) = parameters.get("limit") {
+            match limit.as_u64() {
+                Some(1..=1000) => {}
+                _ => return Err(AiStudioError::validation("limit", "必须在 1-1000 之间")),
+            }
+        }
+
+        Ok(())
+    }
+}
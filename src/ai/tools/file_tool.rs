@@ -1,12 +1,14 @@
 // 文件操作工具实现
+// 未配置基础目录时，文件操作限制在每次执行独立的临时工作区内
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use serde_json;
 use tracing::{debug, error, warn};
 use tokio::fs;
 
 use crate::ai::agent_runtime::{Tool, ToolResult, ToolMetadata, ExecutionContext};
+use crate::ai::tools::scratch::{context_tenant_id, execution_key, jail_path, normalize_relative_path, FileRef, ScratchWorkspace, ScratchWorkspaces};
 use crate::errors::AiStudioError;
 
 /// 文件操作工具
@@ -14,6 +16,8 @@ use crate::errors::AiStudioError;
 pub struct FileTool {
     /// 工具配置
     config: FileToolConfig,
+    /// 执行临时工作区
+    workspaces: ScratchWorkspaces,
 }
 
 /// 文件工具配置
@@ -25,7 +29,7 @@ pub struct FileToolConfig {
     pub max_file_size: u64,
     /// 允许的操作
     pub allowed_operations: Vec<String>,
-    /// 基础目录（安全限制）
+    /// 基础目录（安全限制），未配置时使用每次执行独立的临时工作区
     pub base_directory: Option<String>,
 }
 
@@ -56,14 +60,41 @@ impl Default for FileToolConfig {
 impl FileTool {
     /// 创建新的文件工具
     pub fn new() -> Self {
-        Self {
-            config: FileToolConfig::default(),
-        }
+        Self::with_config(FileToolConfig::default())
     }
     
     /// 使用自定义配置创建文件工具
     pub fn with_config(config: FileToolConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            workspaces: ScratchWorkspaces::shared(),
+        }
+    }
+    
+    /// 使用指定的工作区注册表
+    pub fn with_workspaces(mut self, workspaces: ScratchWorkspaces) -> Self {
+        self.workspaces = workspaces;
+        self
+    }
+}
+
+/// 解析后的操作目标
+struct FileTarget {
+    /// 主机路径
+    path: PathBuf,
+    /// 返回给调用方的路径
+    display: String,
+    /// 目标位于当前执行工作区时所在的工作区，用于检查工作区大小限制
+    workspace: Option<ScratchWorkspace>,
+    /// 目标位于临时工作区时的文件引用
+    file_ref: Option<FileRef>,
+}
+
+impl FileTarget {
+    fn file_ref_value(&self) -> serde_json::Value {
+        self.file_ref.as_ref()
+            .map(|file_ref| serde_json::Value::String(file_ref.to_string()))
+            .unwrap_or(serde_json::Value::Null)
     }
 }
 
@@ -94,14 +125,16 @@ impl Tool for FileTool {
         
         let start_time = std::time::Instant::now();
         
+        let target = self.resolve_target(&parameters, context, matches!(operation, "write" | "append")).await?;
+        
         // 执行文件操作
         let result = match operation {
-            "read" => self.read_file(&parameters).await?,
-            "write" => self.write_file(&parameters).await?,
-            "append" => self.append_file(&parameters).await?,
-            "list" => self.list_directory(&parameters).await?,
-            "exists" => self.check_exists(&parameters).await?,
-            "size" => self.get_file_size(&parameters).await?,
+            "read" => self.read_file(&target).await?,
+            "write" => self.write_file(&parameters, &target).await?,
+            "append" => self.append_file(&parameters, &target).await?,
+            "list" => self.list_directory(&target).await?,
+            "exists" => self.check_exists(&target).await?,
+            "size" => self.get_file_size(&target).await?,
             _ => return Err(AiStudioError::validation("operation".to_string(), &format!("未实现的操作: {}", operation))),
         };
         
@@ -120,7 +153,7 @@ impl Tool for FileTool {
     fn metadata(&self) -> ToolMetadata {
         ToolMetadata {
             name: "file".to_string(),
-            description: "执行文件系统操作（读取、写入、列表等）".to_string(),
+            description: "在执行的临时工作区内执行文件操作（读取、写入、列表等），可通过文件引用读取其他步骤产生的文件".to_string(),
             parameters_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
@@ -131,7 +164,11 @@ impl Tool for FileTool {
                     },
                     "path": {
                         "type": "string",
-                        "description": "文件或目录路径"
+                        "description": "工作区内的相对路径"
+                    },
+                    "file_ref": {
+                        "type": "string",
+                        "description": "其他步骤返回的文件引用（scratch://...），与 path 二选一，其他执行的文件只读"
                     },
                     "content": {
                         "type": "string",
@@ -143,11 +180,20 @@ impl Tool for FileTool {
                         "default": "utf-8"
                     }
                 },
-                "required": ["operation", "path"]
+                "required": ["operation"]
             }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "required": ["operation", "path"],
+                "properties": {
+                    "operation": { "type": "string" },
+                    "path": { "type": "string" },
+                    "file_ref": { "type": ["string", "null"] }
+                }
+            })),
             category: "filesystem".to_string(),
             requires_permission: true,
-            version: "1.0.0".to_string(),
+            version: "1.1.0".to_string(),
         }
     }
     
@@ -164,31 +210,25 @@ impl Tool for FileTool {
             return Err(AiStudioError::validation("operation".to_string(), &format!("不允许的操作: {}", operation)));
         }
         
-        // 验证路径参数
-        let path = parameters.get("path")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| AiStudioError::validation("path", "缺少必需参数"))?;
-        
-        if path.is_empty() {
-            return Err(AiStudioError::validation("path", "路径不能为空"));
-        }
+        // 验证路径参数，path 与 file_ref 只能指定一个
+        let path = match (parameters.get("path"), parameters.get("file_ref")) {
+            (Some(path), None) => path.as_str()
+                .ok_or_else(|| AiStudioError::validation("path", "必须是字符串"))?
+                .to_string(),
+            (None, Some(file_ref)) => file_ref.as_str()
+                .ok_or_else(|| AiStudioError::validation("file_ref", "必须是字符串"))?
+                .parse::<FileRef>()?
+                .path,
+            (Some(_), Some(_)) => return Err(AiStudioError::validation("path", "path 与 file_ref 只能指定一个")),
+            (None, None) => return Err(AiStudioError::validation("path", "缺少必需参数")),
+        };
         
-        // 安全检查：防止路径遍历攻击
-        if path.contains("..") || path.contains("~") {
-            return Err(AiStudioError::validation("path", "路径包含不安全字符"));
-        }
-        
-        // 检查基础目录限制
-        if let Some(ref base_dir) = self.config.base_directory {
-            let full_path = Path::new(base_dir).join(path);
-            if !full_path.starts_with(base_dir) {
-                return Err(AiStudioError::validation("path", "路径超出允许的基础目录"));
-            }
-        }
+        // 安全检查：只允许相对路径，防止路径遍历攻击
+        normalize_relative_path(&path)?;
         
         // 检查文件扩展名
         if matches!(operation, "read" | "write" | "append") {
-            if let Some(extension) = Path::new(path).extension() {
+            if let Some(extension) = Path::new(&path).extension() {
                 let ext_str = extension.to_string_lossy().to_lowercase();
                 if !self.config.allowed_extensions.contains(&ext_str) {
                     return Err(AiStudioError::validation("extension", &format!("不允许的文件扩展名: {}", ext_str)));
@@ -215,10 +255,10 @@ impl FileTool {
     /// 读取文件
     async fn read_file(
         &self,
-        parameters: &HashMap<String, serde_json::Value>,
+        target: &FileTarget,
     ) -> Result<serde_json::Value, AiStudioError> {
-        let path = parameters.get("path").unwrap().as_str().unwrap();
-        let full_path = self.resolve_path(path)?;
+        let path = target.display.as_str();
+        let full_path = &target.path;
         
         debug!("读取文件: {}", full_path.display());
         
@@ -228,7 +268,7 @@ impl FileTool {
         }
         
         // 检查文件大小
-        let metadata = fs::metadata(&full_path).await.map_err(|e| {
+        let metadata = fs::metadata(full_path).await.map_err(|e| {
             error!("获取文件元数据失败: {}", e);
             AiStudioError::internal(format!("获取文件元数据失败: {}", e))
         })?;
@@ -242,7 +282,7 @@ impl FileTool {
         }
         
         // 读取文件内容
-        let content = fs::read_to_string(&full_path).await.map_err(|e| {
+        let content = fs::read_to_string(full_path).await.map_err(|e| {
             error!("读取文件失败: {}", e);
             AiStudioError::internal(format!("读取文件失败: {}", e))
         })?;
@@ -250,6 +290,7 @@ impl FileTool {
         Ok(serde_json::json!({
             "operation": "read",
             "path": path,
+            "file_ref": target.file_ref_value(),
             "content": content,
             "size": metadata.len(),
             "modified": metadata.modified().ok()
@@ -262,10 +303,11 @@ impl FileTool {
     async fn write_file(
         &self,
         parameters: &HashMap<String, serde_json::Value>,
+        target: &FileTarget,
     ) -> Result<serde_json::Value, AiStudioError> {
-        let path = parameters.get("path").unwrap().as_str().unwrap();
+        let path = target.display.as_str();
         let content = parameters.get("content").unwrap().as_str().unwrap();
-        let full_path = self.resolve_path(path)?;
+        let full_path = &target.path;
         
         debug!("写入文件: {}", full_path.display());
         
@@ -278,6 +320,12 @@ impl FileTool {
             )));
         }
         
+        // 检查工作区大小限制，覆盖的现有内容不计入
+        if let Some(ref workspace) = target.workspace {
+            let replaced = fs::metadata(full_path).await.map(|m| m.len()).unwrap_or(0);
+            workspace.ensure_capacity(content.len() as u64, replaced).await?;
+        }
+        
         // 创建父目录（如果不存在）
         if let Some(parent) = full_path.parent() {
             fs::create_dir_all(parent).await.map_err(|e| {
@@ -287,7 +335,7 @@ impl FileTool {
         }
         
         // 写入文件
-        fs::write(&full_path, content).await.map_err(|e| {
+        fs::write(full_path, content).await.map_err(|e| {
             error!("写入文件失败: {}", e);
             AiStudioError::internal(format!("写入文件失败: {}", e))
         })?;
@@ -295,6 +343,7 @@ impl FileTool {
         Ok(serde_json::json!({
             "operation": "write",
             "path": path,
+            "file_ref": target.file_ref_value(),
             "bytes_written": content.len(),
             "success": true
         }))
@@ -304,16 +353,17 @@ impl FileTool {
     async fn append_file(
         &self,
         parameters: &HashMap<String, serde_json::Value>,
+        target: &FileTarget,
     ) -> Result<serde_json::Value, AiStudioError> {
-        let path = parameters.get("path").unwrap().as_str().unwrap();
+        let path = target.display.as_str();
         let content = parameters.get("content").unwrap().as_str().unwrap();
-        let full_path = self.resolve_path(path)?;
+        let full_path = &target.path;
         
         debug!("追加文件: {}", full_path.display());
         
         // 检查现有文件大小
         if full_path.exists() {
-            let metadata = fs::metadata(&full_path).await.map_err(|e| {
+            let metadata = fs::metadata(full_path).await.map_err(|e| {
                 error!("获取文件元数据失败: {}", e);
                 AiStudioError::internal(format!("获取文件元数据失败: {}", e))
             })?;
@@ -328,12 +378,17 @@ impl FileTool {
             }
         }
         
+        // 检查工作区大小限制
+        if let Some(ref workspace) = target.workspace {
+            workspace.ensure_capacity(content.len() as u64, 0).await?;
+        }
+        
         // 追加内容
         use tokio::io::AsyncWriteExt;
         let mut file = fs::OpenOptions::new()
             .create(true)
             .append(true)
-            .open(&full_path)
+            .open(full_path)
             .await
             .map_err(|e| {
                 error!("打开文件失败: {}", e);
@@ -348,6 +403,7 @@ impl FileTool {
         Ok(serde_json::json!({
             "operation": "append",
             "path": path,
+            "file_ref": target.file_ref_value(),
             "bytes_appended": content.len(),
             "success": true
         }))
@@ -356,10 +412,10 @@ impl FileTool {
     /// 列出目录
     async fn list_directory(
         &self,
-        parameters: &HashMap<String, serde_json::Value>,
+        target: &FileTarget,
     ) -> Result<serde_json::Value, AiStudioError> {
-        let path = parameters.get("path").unwrap().as_str().unwrap();
-        let full_path = self.resolve_path(path)?;
+        let path = target.display.as_str();
+        let full_path = &target.path;
         
         debug!("列出目录: {}", full_path.display());
         
@@ -372,7 +428,7 @@ impl FileTool {
         }
         
         let mut entries = Vec::new();
-        let mut dir = fs::read_dir(&full_path).await.map_err(|e| {
+        let mut dir = fs::read_dir(full_path).await.map_err(|e| {
             error!("读取目录失败: {}", e);
             AiStudioError::internal(format!("读取目录失败: {}", e))
         })?;
@@ -414,10 +470,10 @@ impl FileTool {
     /// 检查文件是否存在
     async fn check_exists(
         &self,
-        parameters: &HashMap<String, serde_json::Value>,
+        target: &FileTarget,
     ) -> Result<serde_json::Value, AiStudioError> {
-        let path = parameters.get("path").unwrap().as_str().unwrap();
-        let full_path = self.resolve_path(path)?;
+        let path = target.display.as_str();
+        let full_path = &target.path;
         
         let exists = full_path.exists();
         let is_file = full_path.is_file();
@@ -435,16 +491,16 @@ impl FileTool {
     /// 获取文件大小
     async fn get_file_size(
         &self,
-        parameters: &HashMap<String, serde_json::Value>,
+        target: &FileTarget,
     ) -> Result<serde_json::Value, AiStudioError> {
-        let path = parameters.get("path").unwrap().as_str().unwrap();
-        let full_path = self.resolve_path(path)?;
+        let path = target.display.as_str();
+        let full_path = &target.path;
         
         if !full_path.exists() {
             return Err(AiStudioError::not_found(&format!("文件不存在: {}", path)));
         }
         
-        let metadata = fs::metadata(&full_path).await.map_err(|e| {
+        let metadata = fs::metadata(full_path).await.map_err(|e| {
             error!("获取文件元数据失败: {}", e);
             AiStudioError::internal(format!("获取文件元数据失败: {}", e))
         })?;
@@ -452,6 +508,7 @@ impl FileTool {
         Ok(serde_json::json!({
             "operation": "size",
             "path": path,
+            "file_ref": target.file_ref_value(),
             "size": metadata.len(),
             "is_file": metadata.is_file(),
             "is_directory": metadata.is_dir(),
@@ -461,25 +518,68 @@ impl FileTool {
         }))
     }
     
-    /// 解析路径
-    fn resolve_path(&self, path: &str) -> Result<std::path::PathBuf, AiStudioError> {
-        let path_buf = if let Some(ref base_dir) = self.config.base_directory {
-            Path::new(base_dir).join(path)
-        } else {
-            Path::new(path).to_path_buf()
-        };
+    /// 解析操作目标
+    ///
+    /// 文件引用指向当前执行时可读写，指向同一租户的其他执行时只读；未配置基础目录时，
+    /// 路径相对于当前执行的临时工作区。
+    async fn resolve_target(
+        &self,
+        parameters: &HashMap<String, serde_json::Value>,
+        context: &ExecutionContext,
+        writable: bool,
+    ) -> Result<FileTarget, AiStudioError> {
+        let tenant_id = context_tenant_id(context);
+        let current_execution = execution_key(context);
+        
+        if let Some(file_ref) = parameters.get("file_ref").and_then(|v| v.as_str()) {
+            let file_ref: FileRef = file_ref.parse()?;
+            if current_execution.as_deref() == Some(file_ref.execution_id.as_str()) {
+                let workspace = self.workspaces.acquire(&file_ref.execution_id, tenant_id).await?;
+                return Ok(FileTarget {
+                    path: workspace.resolve(&file_ref.path)?,
+                    display: file_ref.path.clone(),
+                    workspace: Some(workspace),
+                    file_ref: Some(file_ref),
+                });
+            }
+            if writable {
+                return Err(AiStudioError::validation("file_ref", "其他执行的文件只读，请写入当前执行的工作区"));
+            }
+            return Ok(FileTarget {
+                path: self.workspaces.resolve_ref(&file_ref, tenant_id)?,
+                display: file_ref.path.clone(),
+                workspace: None,
+                file_ref: Some(file_ref),
+            });
+        }
         
-        // 规范化路径
-        let canonical_path = path_buf.canonicalize().unwrap_or(path_buf);
+        let path = parameters.get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| AiStudioError::validation("path", "缺少必需参数"))?;
         
-        // 再次检查基础目录限制
         if let Some(ref base_dir) = self.config.base_directory {
-            if !canonical_path.starts_with(base_dir) {
-                return Err(AiStudioError::validation("path", "路径超出允许的基础目录"));
-            }
+            let root = Path::new(base_dir).canonicalize().map_err(|e| {
+                error!("解析基础目录失败: {}", e);
+                AiStudioError::internal(format!("解析基础目录失败: {}", e))
+            })?;
+            return Ok(FileTarget {
+                path: jail_path(&root, path)?,
+                display: path.to_string(),
+                workspace: None,
+                file_ref: None,
+            });
         }
         
-        Ok(canonical_path)
+        let execution_id = current_execution.ok_or_else(|| {
+            AiStudioError::validation("execution_id", "文件工具只能在执行上下文中使用")
+        })?;
+        let workspace = self.workspaces.acquire(&execution_id, tenant_id).await?;
+        Ok(FileTarget {
+            path: workspace.resolve(path)?,
+            display: path.to_string(),
+            file_ref: Some(workspace.file_ref(path)?),
+            workspace: Some(workspace),
+        })
     }
 }
 
@@ -487,6 +587,8 @@ impl FileTool {
 mod tests {
     use super::*;
     use tempfile::TempDir;
+    use uuid::Uuid;
+    use crate::ai::tools::scratch::ScratchConfig;
     
     #[tokio::test]
     async fn test_file_tool_validation() {
@@ -520,6 +622,7 @@ mod tests {
             context_variables: HashMap::new(),
             session_id: None,
             user_id: None,
+            tool_progress: None,
         };
         
         // 测试写入文件
@@ -540,4 +643,59 @@ mod tests {
         assert!(result.success);
         assert_eq!(result.data.get("content").unwrap().as_str().unwrap(), "Hello, World!");
     }
+    
+    fn execution_context(execution_id: &str, tenant_id: Uuid) -> ExecutionContext {
+        let mut context_variables = HashMap::new();
+        context_variables.insert("execution_id".to_string(), serde_json::Value::String(execution_id.to_string()));
+        context_variables.insert("tenant_id".to_string(), serde_json::Value::String(tenant_id.to_string()));
+        ExecutionContext {
+            current_task: None,
+            execution_history: Vec::new(),
+            context_variables,
+            session_id: None,
+            user_id: None,
+            tool_progress: None,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_scratch_workspace_file_refs() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspaces = ScratchWorkspaces::new(ScratchConfig {
+            root: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let tool = FileTool::new().with_workspaces(workspaces.clone());
+        let tenant_id = Uuid::new_v4();
+        let producer = execution_context("exec-producer", tenant_id);
+        let consumer = execution_context("exec-consumer", tenant_id);
+        
+        // 写入当前执行的工作区并返回文件引用
+        let mut write_params = HashMap::new();
+        write_params.insert("operation".to_string(), serde_json::Value::String("write".to_string()));
+        write_params.insert("path".to_string(), serde_json::Value::String("out/report.md".to_string()));
+        write_params.insert("content".to_string(), serde_json::Value::String("# 报告".to_string()));
+        let result = tool.execute(write_params, &producer).await.unwrap();
+        let file_ref = result.data["file_ref"].as_str().unwrap().to_string();
+        assert_eq!(file_ref, "scratch://exec-producer/out/report.md");
+        
+        // 其他执行按引用读取
+        let mut read_params = HashMap::new();
+        read_params.insert("operation".to_string(), serde_json::Value::String("read".to_string()));
+        read_params.insert("file_ref".to_string(), serde_json::Value::String(file_ref.clone()));
+        let result = tool.execute(read_params.clone(), &consumer).await.unwrap();
+        assert_eq!(result.data["content"], "# 报告");
+        
+        // 其他执行的文件只读，其他租户不可见
+        let mut overwrite_params = read_params.clone();
+        overwrite_params.insert("operation".to_string(), serde_json::Value::String("write".to_string()));
+        overwrite_params.insert("content".to_string(), serde_json::Value::String("x".to_string()));
+        assert!(tool.execute(overwrite_params, &consumer).await.is_err());
+        let outsider = execution_context("exec-outsider", Uuid::new_v4());
+        assert!(tool.execute(read_params.clone(), &outsider).await.is_err());
+        
+        // 执行结束后引用失效
+        workspaces.release("exec-producer").await;
+        assert!(tool.execute(read_params, &consumer).await.is_err());
+    }
 }
\ No newline at end of file
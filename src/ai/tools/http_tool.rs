@@ -1,20 +1,47 @@
 // HTTP 请求工具实现
+// 共享连接池（支持 HTTP/2）按租户出站策略发出请求，支持表单与 multipart 请求体、按执行隔离的 Cookie 与 JSONPath 提取
 
 use std::collections::HashMap;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
 use serde_json;
 use tracing::{debug, error, warn};
-use reqwest::{Client, Method, Response};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Method, Response};
 use url::Url;
+use uuid::Uuid;
 
 use crate::ai::agent_runtime::{Tool, ToolResult, ToolMetadata, ExecutionContext};
+use crate::ai::agent_stream::ToolProgressSink;
+use crate::ai::tools::scratch::{context_tenant_id, execution_key};
 use crate::errors::AiStudioError;
+use crate::services::egress::TenantClients;
+
+/// 默认配置的共享实例，各处创建的 HTTP 工具共用连接池与 Cookie 存储
+static SHARED_HTTP_TOOL: Lazy<HttpTool> = Lazy::new(|| HttpTool::build(HttpToolConfig::default()));
+
+/// 单次执行保留的最大 Cookie 数，超出时丢弃最早写入的
+const MAX_COOKIES_PER_EXECUTION: usize = 50;
+
+/// 同时保留 Cookie 的最大执行数
+const MAX_COOKIE_JARS: usize = 1000;
+
+/// 执行的 Cookie 闲置超过该时长后丢弃
+const COOKIE_JAR_IDLE_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// multipart 请求体的最大总字节数
+const MAX_MULTIPART_BYTES: usize = 20 * 1024 * 1024;
 
 /// HTTP 请求工具
 #[derive(Debug, Clone)]
 pub struct HttpTool {
-    /// HTTP 客户端
-    client: Client,
+    /// 按租户出站策略创建的 HTTP 客户端，克隆的实例共用连接池
+    clients: TenantClients,
+    /// 按执行隔离的 Cookie
+    cookies: CookieJars,
     /// 工具配置
     config: HttpToolConfig,
 }
@@ -36,6 +63,14 @@ pub struct HttpToolConfig {
     pub allow_redirects: bool,
     /// 最大重定向次数
     pub max_redirects: u32,
+    /// 每个主机保留的最大空闲连接数
+    pub pool_max_idle_per_host: usize,
+    /// 空闲连接的保留时间（秒）
+    pub pool_idle_timeout_seconds: u64,
+    /// 是否启用 HTTP/2（经 TLS 协商），关闭时只使用 HTTP/1.1
+    pub http2: bool,
+    /// 是否在同一执行的请求之间保留 Cookie
+    pub enable_cookies: bool,
 }
 
 impl Default for HttpToolConfig {
@@ -60,38 +95,71 @@ impl Default for HttpToolConfig {
             max_response_size: 10 * 1024 * 1024, // 10MB
             allow_redirects: true,
             max_redirects: 5,
+            pool_max_idle_per_host: 16,
+            pool_idle_timeout_seconds: 90,
+            http2: true,
+            enable_cookies: true,
         }
     }
 }
 
 impl HttpTool {
-    /// 创建新的 HTTP 工具
+    /// 获取使用默认配置的 HTTP 工具，与其他默认实例共用连接池
     pub fn new() -> Self {
-        let config = HttpToolConfig::default();
-        Self::with_config(config).unwrap_or_else(|_| {
-            // 如果配置失败，使用默认的简单配置
-            let client = Client::new();
-            Self { client, config }
-        })
+        SHARED_HTTP_TOOL.clone()
     }
-    
-    /// 使用自定义配置创建 HTTP 工具
+
+    /// 使用自定义配置创建 HTTP 工具，拥有独立的连接池
     pub fn with_config(config: HttpToolConfig) -> Result<Self, AiStudioError> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(config.timeout_seconds))
-            .redirect(if config.allow_redirects {
-                reqwest::redirect::Policy::limited(config.max_redirects as usize)
+        let tool = Self::build(config);
+        tool.clients.default_client().map_err(|e| {
+            error!("创建 HTTP 客户端失败: {}", e);
+            AiStudioError::internal("创建 HTTP 客户端失败")
+        })?;
+
+        Ok(tool)
+    }
+
+    /// 丢弃执行保留的 Cookie，执行结束时调用
+    pub fn clear_cookies(&self, execution_key: &str) {
+        self.cookies.clear(execution_key);
+    }
+
+    fn build(config: HttpToolConfig) -> Self {
+        let timeout = Duration::from_secs(config.timeout_seconds);
+        let redirect_limit = config.allow_redirects.then_some(config.max_redirects as usize);
+        let pool_max_idle_per_host = config.pool_max_idle_per_host;
+        let pool_idle_timeout = Duration::from_secs(config.pool_idle_timeout_seconds);
+        let http2 = config.http2;
+        let clients = TenantClients::new(move |builder| {
+            let builder = builder
+                .timeout(timeout)
+                .redirect(match redirect_limit {
+                    Some(limit) => reqwest::redirect::Policy::limited(limit),
+                    None => reqwest::redirect::Policy::none(),
+                })
+                .user_agent("AiStudio-Agent/1.0")
+                .pool_max_idle_per_host(pool_max_idle_per_host)
+                .pool_idle_timeout(pool_idle_timeout)
+                .tcp_keepalive(Duration::from_secs(60));
+            if http2 {
+                builder.http2_adaptive_window(true)
             } else {
-                reqwest::redirect::Policy::none()
-            })
-            .user_agent("AiStudio-Agent/1.0")
-            .build()
-            .map_err(|e| {
-                error!("创建 HTTP 客户端失败: {}", e);
-                AiStudioError::internal("创建 HTTP 客户端失败")
-            })?;
-        
-        Ok(Self { client, config })
+                builder.http1_only()
+            }
+        });
+
+        Self {
+            clients,
+            cookies: CookieJars::default(),
+            config,
+        }
+    }
+}
+
+impl Default for HttpTool {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -108,25 +176,35 @@ impl Tool for HttpTool {
     {
         Box::pin(async move {
         debug!("执行 HTTP 工具");
-        
+
         // 提取请求参数
         let url = parameters.get("url")
             .and_then(|v| v.as_str())
             .ok_or_else(|| AiStudioError::validation("url".to_string(), "缺少必需参数: url".to_string()))?;
-        
+
         let method = parameters.get("method")
             .and_then(|v| v.as_str())
-            .unwrap_or("GET");
-        
+            .unwrap_or("GET")
+            .to_uppercase();
+
         debug!("HTTP 请求: {} {}", method, url);
-        
+
         let start_time = std::time::Instant::now();
-        
-        // 执行 HTTP 请求
-        let response_data = self.make_request(url, method, &parameters).await?;
-        
+
+        // 执行 HTTP 请求，按执行所属租户的出站策略发出
+        let tenant_id = context_tenant_id(context);
+        let cookie_key = self.cookie_key(&parameters, context);
+        let response_data = self.make_request(
+            tenant_id,
+            cookie_key.as_deref(),
+            url,
+            &method,
+            &parameters,
+            context.tool_progress.as_ref(),
+        ).await?;
+
         let execution_time = start_time.elapsed().as_millis() as u64;
-        
+
         Ok(ToolResult {
             success: true,
             data: response_data,
@@ -136,11 +214,11 @@ impl Tool for HttpTool {
         })
         })
     }
-    
+
     fn metadata(&self) -> ToolMetadata {
         ToolMetadata {
             name: "http".to_string(),
-            description: "发送 HTTP 请求并获取响应".to_string(),
+            description: "发送 HTTP 请求并获取响应，支持 JSON、表单与 multipart 请求体，可按 JSONPath 提取响应字段".to_string(),
             parameters_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
@@ -162,14 +240,38 @@ impl Tool for HttpTool {
                             "type": "string"
                         }
                     },
+                    "query": {
+                        "type": "object",
+                        "description": "追加到 URL 的查询参数"
+                    },
                     "body": {
                         "type": "string",
-                        "description": "请求体（POST/PUT/PATCH 需要）"
+                        "description": "原始请求体"
                     },
                     "json": {
                         "type": "object",
                         "description": "JSON 请求体"
                     },
+                    "form": {
+                        "type": "object",
+                        "description": "按 application/x-www-form-urlencoded 编码的表单字段"
+                    },
+                    "multipart": {
+                        "type": "object",
+                        "description": "multipart/form-data 字段：字符串为文本字段，对象为文件字段（content 或 content_base64，可选 filename、content_type）"
+                    },
+                    "extract": {
+                        "type": "object",
+                        "description": "按名称从 JSON 响应中提取的字段，值为 JSONPath，如 $.data.items[0].id",
+                        "additionalProperties": {
+                            "type": "string"
+                        }
+                    },
+                    "cookies": {
+                        "type": "boolean",
+                        "description": "是否携带并保存本次执行的 Cookie",
+                        "default": self.config.enable_cookies
+                    },
                     "timeout": {
                         "type": "integer",
                         "description": "请求超时时间（秒）",
@@ -180,12 +282,26 @@ impl Tool for HttpTool {
                 },
                 "required": ["url"]
             }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "required": ["status", "body", "success"],
+                "properties": {
+                    "status": { "type": "integer" },
+                    "status_text": { "type": "string" },
+                    "http_version": { "type": "string" },
+                    "headers": { "type": "object" },
+                    "body": { "type": "string" },
+                    "extracted": { "type": "object" },
+                    "size": { "type": "integer", "minimum": 0 },
+                    "success": { "type": "boolean" }
+                }
+            })),
             category: "network".to_string(),
             requires_permission: true,
-            version: "1.0.0".to_string(),
+            version: "1.1.0".to_string(),
         }
     }
-    
+
     fn validate_parameters(
         &self,
         parameters: &HashMap<String, serde_json::Value>,
@@ -194,17 +310,17 @@ impl Tool for HttpTool {
         let url_str = parameters.get("url")
             .and_then(|v| v.as_str())
             .ok_or_else(|| AiStudioError::validation("url".to_string(), "缺少必需参数: url".to_string()))?;
-        
+
         // 解析 URL
         let url = Url::parse(url_str).map_err(|e| {
             AiStudioError::validation("url", &format!("无效的 URL: {}", e))
         })?;
-        
+
         // 检查协议
         if !matches!(url.scheme(), "http" | "https") {
             return Err(AiStudioError::validation("url", "只支持 HTTP 和 HTTPS 协议"));
         }
-        
+
         // 检查域名白名单
         if !self.config.allowed_domains.is_empty() {
             if let Some(host) = url.host_str() {
@@ -213,14 +329,14 @@ impl Tool for HttpTool {
                 }
             }
         }
-        
+
         // 检查域名黑名单
         if let Some(host) = url.host_str() {
             if self.config.blocked_domains.iter().any(|domain| host.contains(domain)) {
                 return Err(AiStudioError::validation("url", &format!("域名在禁止列表中: {}", host)));
             }
         }
-        
+
         // 验证 HTTP 方法
         if let Some(method) = parameters.get("method") {
             if let Some(method_str) = method.as_str() {
@@ -231,13 +347,13 @@ impl Tool for HttpTool {
                 return Err(AiStudioError::validation("method", "必须是字符串"));
             }
         }
-        
+
         // 验证请求头
         if let Some(headers) = parameters.get("headers") {
             if !headers.is_object() {
                 return Err(AiStudioError::validation("headers", "必须是对象"));
             }
-            
+
             // 检查危险的请求头
             if let Some(headers_obj) = headers.as_object() {
                 for (key, value) in headers_obj {
@@ -245,14 +361,55 @@ impl Tool for HttpTool {
                     if matches!(key_lower.as_str(), "authorization" | "cookie" | "x-forwarded-for") {
                         warn!("检测到敏感请求头: {}", key);
                     }
-                    
+
                     if !value.is_string() {
                         return Err(AiStudioError::validation("headers", &format!("请求头 {} 的值必须是字符串", key)));
                     }
                 }
             }
         }
-        
+
+        // 验证查询参数与表单字段
+        for name in ["query", "form"] {
+            if let Some(value) = parameters.get(name) {
+                let fields = value.as_object()
+                    .ok_or_else(|| AiStudioError::validation(name, "必须是对象"))?;
+                if fields.values().any(|v| v.is_object() || v.is_array()) {
+                    return Err(AiStudioError::validation(name, "字段值必须是字符串、数字或布尔值"));
+                }
+            }
+        }
+
+        // 请求体只能指定一种
+        let bodies = ["body", "json", "form", "multipart"]
+            .iter()
+            .filter(|name| parameters.contains_key(**name))
+            .count();
+        if bodies > 1 {
+            return Err(AiStudioError::validation("body", "body、json、form 与 multipart 只能指定一个"));
+        }
+
+        if let Some(multipart) = parameters.get("multipart") {
+            let fields = multipart.as_object()
+                .ok_or_else(|| AiStudioError::validation("multipart", "必须是对象"))?;
+            build_multipart(fields)?;
+        }
+
+        // 验证 JSONPath 提取规则
+        if let Some(extract) = parameters.get("extract") {
+            let paths = extract.as_object()
+                .ok_or_else(|| AiStudioError::validation("extract", "必须是对象"))?;
+            for (name, path) in paths {
+                let path = path.as_str()
+                    .ok_or_else(|| AiStudioError::validation("extract", format!("提取字段 {} 的路径必须是字符串", name)))?;
+                JsonPath::parse(path)?;
+            }
+        }
+
+        if parameters.get("cookies").is_some_and(|v| !v.is_boolean()) {
+            return Err(AiStudioError::validation("cookies", "必须是布尔值"));
+        }
+
         // 验证超时参数
         if let Some(timeout) = parameters.get("timeout") {
             if let Some(timeout_num) = timeout.as_u64() {
@@ -263,75 +420,145 @@ impl Tool for HttpTool {
                 return Err(AiStudioError::validation("timeout", "必须是正整数"));
             }
         }
-        
+
         Ok(())
     }
 }
 
 impl HttpTool {
+    /// 本次请求使用的 Cookie 存储键：执行 ID，其次会话 ID；未开启 Cookie 或两者皆无时不保留
+    fn cookie_key(
+        &self,
+        parameters: &HashMap<String, serde_json::Value>,
+        context: &ExecutionContext,
+    ) -> Option<String> {
+        let enabled = parameters.get("cookies")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(self.config.enable_cookies);
+        if !enabled {
+            return None;
+        }
+
+        execution_key(context)
+    }
+
     /// 发送 HTTP 请求
     async fn make_request(
         &self,
+        tenant_id: Option<Uuid>,
+        cookie_key: Option<&str>,
         url: &str,
         method: &str,
         parameters: &HashMap<String, serde_json::Value>,
+        progress: Option<&ToolProgressSink>,
     ) -> Result<serde_json::Value, AiStudioError> {
         // 解析 HTTP 方法
         let http_method = Method::from_bytes(method.as_bytes()).map_err(|e| {
             AiStudioError::validation("method".to_string(), &format!("无效的 HTTP 方法: {}", e))
         })?;
-        
+
+        let mut request_url = Url::parse(url).map_err(|e| {
+            AiStudioError::validation("url", &format!("无效的 URL: {}", e))
+        })?;
+        if let Some(query) = parameters.get("query").and_then(|v| v.as_object()) {
+            let mut pairs = request_url.query_pairs_mut();
+            for (key, value) in form_pairs(query) {
+                pairs.append_pair(&key, &value);
+            }
+        }
+
         // 构建请求
-        let mut request_builder = self.client.request(http_method, url);
-        
+        let client = match tenant_id {
+            Some(tenant_id) => self.clients.client(tenant_id).await?,
+            None => self.clients.default_client()?,
+        };
+        let mut request_builder = client.request(http_method, request_url.clone());
+
         // 添加请求头
+        let mut explicit_cookie = false;
         if let Some(headers) = parameters.get("headers") {
             if let Some(headers_obj) = headers.as_object() {
                 for (key, value) in headers_obj {
                     if let Some(value_str) = value.as_str() {
+                        explicit_cookie |= key.eq_ignore_ascii_case("cookie");
                         request_builder = request_builder.header(key, value_str);
                     }
                 }
             }
         }
-        
+
+        // 携带本次执行保存的 Cookie，调用方显式指定 Cookie 请求头时以其为准
+        if let (Some(key), false) = (cookie_key, explicit_cookie) {
+            if let Some(cookie_header) = self.cookies.header_for(key, &request_url, Utc::now()) {
+                request_builder = request_builder.header(reqwest::header::COOKIE, cookie_header);
+            }
+        }
+
         // 添加请求体
         if let Some(json_body) = parameters.get("json") {
             request_builder = request_builder.json(json_body);
+        } else if let Some(form) = parameters.get("form").and_then(|v| v.as_object()) {
+            request_builder = request_builder.form(&form_pairs(form));
+        } else if let Some(multipart) = parameters.get("multipart").and_then(|v| v.as_object()) {
+            request_builder = request_builder.multipart(build_multipart(multipart)?);
         } else if let Some(body) = parameters.get("body") {
             if let Some(body_str) = body.as_str() {
                 request_builder = request_builder.body(body_str.to_string());
             }
         }
-        
+
         // 设置超时
         if let Some(timeout) = parameters.get("timeout") {
             if let Some(timeout_secs) = timeout.as_u64() {
                 request_builder = request_builder.timeout(Duration::from_secs(timeout_secs));
             }
         }
-        
+
         // 发送请求
-        debug!("发送 HTTP 请求: {} {}", method, url);
+        debug!("发送 HTTP 请求: {} {}", method, request_url);
         let response = request_builder.send().await.map_err(|e| {
             error!("HTTP 请求失败: {}", e);
             AiStudioError::external_service("http".to_string(), format!("HTTP 请求失败: {}", e))
         })?;
-        
+
+        // 保存响应设置的 Cookie（重定向中间响应设置的 Cookie 不会保存）
+        if let Some(key) = cookie_key {
+            let set_cookies = response.headers()
+                .get_all(reqwest::header::SET_COOKIE)
+                .iter()
+                .filter_map(|v| v.to_str().ok());
+            self.cookies.store(key, response.url(), set_cookies, Utc::now());
+        }
+
         // 处理响应
-        self.process_response(response).await
+        let mut response_data = self.process_response(response, progress).await?;
+
+        // 按 JSONPath 提取响应字段
+        if let Some(paths) = parameters.get("extract").and_then(|v| v.as_object()) {
+            let body = response_data.get("json").cloned().unwrap_or(serde_json::Value::Null);
+            response_data["extracted"] = serde_json::Value::Object(extract_json_paths(&body, paths)?);
+        }
+
+        Ok(response_data)
     }
-    
+
     /// 处理 HTTP 响应
-    async fn process_response(&self, response: Response) -> Result<serde_json::Value, AiStudioError> {
+    ///
+    /// 响应体按块读取，提供进度句柄时每读到一块即上报已解码的文本。
+    async fn process_response(
+        &self,
+        mut response: Response,
+        progress: Option<&ToolProgressSink>,
+    ) -> Result<serde_json::Value, AiStudioError> {
         let status = response.status();
+        let version = response.version();
         let headers: HashMap<String, String> = response.headers()
             .iter()
             .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
             .collect();
-        
-        debug!("HTTP 响应状态: {}", status);
-        
+
+        debug!("HTTP 响应状态: {} ({:?})", status, version);
+
         // 检查内容长度
         if let Some(content_length) = response.content_length() {
             if content_length > self.config.max_response_size {
@@ -342,35 +569,50 @@ impl HttpTool {
                 )));
             }
         }
-        
+
         // 获取响应体
-        let response_bytes = response.bytes().await.map_err(|e| {
+        let mut response_bytes = Vec::new();
+        let mut pending = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| {
             error!("读取响应体失败: {}", e);
             AiStudioError::external_service("http".to_string(), format!("读取响应体失败: {}", e))
-        })?;
-        
-        // 检查响应大小
-        if response_bytes.len() > self.config.max_response_size as usize {
-            return Err(AiStudioError::validation("response_size".to_string(), &format!(
-                "响应太大: {} 字节，最大允许: {} 字节",
-                response_bytes.len(),
-                self.config.max_response_size
-            )));
-        }
-        
+        })? {
+            response_bytes.extend_from_slice(&chunk);
+
+            // 检查响应大小
+            if response_bytes.len() > self.config.max_response_size as usize {
+                return Err(AiStudioError::validation("response_size".to_string(), &format!(
+                    "响应太大: 超过 {} 字节，最大允许: {} 字节",
+                    response_bytes.len(),
+                    self.config.max_response_size
+                )));
+            }
+
+            if let Some(progress) = progress {
+                pending.extend_from_slice(&chunk);
+                report_decoded(progress, &mut pending);
+            }
+        }
+        if let Some(progress) = progress {
+            if !pending.is_empty() {
+                progress.report(&String::from_utf8_lossy(&pending));
+            }
+        }
+
         // 尝试解析为文本
         let response_text = String::from_utf8_lossy(&response_bytes).to_string();
-        
+
         // 尝试解析为 JSON
         let response_json = if let Ok(json) = serde_json::from_str::<serde_json::Value>(&response_text) {
             Some(json)
         } else {
             None
         };
-        
+
         Ok(serde_json::json!({
             "status": status.as_u16(),
             "status_text": status.canonical_reason().unwrap_or(""),
+            "http_version": format!("{:?}", version),
             "headers": headers,
             "body": response_text,
             "json": response_json,
@@ -380,55 +622,499 @@ impl HttpTool {
     }
 }
 
+/// 上报缓冲区中可完整解码的文本，块尾被截断的多字节字符留到下一块
+fn report_decoded(progress: &ToolProgressSink, pending: &mut Vec<u8>) {
+    let valid_up_to = match std::str::from_utf8(pending) {
+        Ok(text) => text.len(),
+        // 非法字节而非被截断的字符时按有损方式整体上报
+        Err(e) if e.error_len().is_some() => {
+            progress.report(&String::from_utf8_lossy(pending));
+            pending.clear();
+            return;
+        }
+        Err(e) => e.valid_up_to(),
+    };
+    if valid_up_to > 0 {
+        let text = std::str::from_utf8(&pending[..valid_up_to]).unwrap_or_default();
+        progress.report(text);
+        pending.drain(..valid_up_to);
+    }
+}
+
+/// 将查询参数或表单字段转换为键值对，非字符串值按 JSON 文本编码
+fn form_pairs(fields: &serde_json::Map<String, serde_json::Value>) -> Vec<(String, String)> {
+    fields.iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+/// 构建 multipart 请求体
+///
+/// 字符串字段作为文本字段；对象字段作为文件，内容取自 `content`（文本）或 `content_base64`（二进制）。
+fn build_multipart(fields: &serde_json::Map<String, serde_json::Value>) -> Result<Form, AiStudioError> {
+    let mut form = Form::new();
+    let mut total_bytes = 0usize;
+    for (name, field) in fields {
+        let part = match field {
+            serde_json::Value::String(text) => {
+                total_bytes += text.len();
+                Part::text(text.clone())
+            }
+            serde_json::Value::Object(file) => {
+                let content = match (file.get("content"), file.get("content_base64")) {
+                    (Some(serde_json::Value::String(text)), None) => text.clone().into_bytes(),
+                    (None, Some(serde_json::Value::String(encoded))) => base64::engine::general_purpose::STANDARD
+                        .decode(encoded)
+                        .map_err(|e| AiStudioError::validation("multipart", format!("字段 {} 的 Base64 内容无效: {}", name, e)))?,
+                    _ => return Err(AiStudioError::validation(
+                        "multipart",
+                        format!("文件字段 {} 必须且只能包含 content 或 content_base64", name),
+                    )),
+                };
+                total_bytes += content.len();
+                let mut part = Part::bytes(content);
+                if let Some(file_name) = file.get("filename").and_then(|v| v.as_str()) {
+                    part = part.file_name(file_name.to_string());
+                }
+                if let Some(content_type) = file.get("content_type").and_then(|v| v.as_str()) {
+                    part = part.mime_str(content_type)
+                        .map_err(|_| AiStudioError::validation("multipart", format!("字段 {} 的内容类型无效", name)))?;
+                }
+                part
+            }
+            serde_json::Value::Number(_) | serde_json::Value::Bool(_) => Part::text(field.to_string()),
+            _ => return Err(AiStudioError::validation("multipart", format!("字段 {} 的值无效", name))),
+        };
+        if total_bytes > MAX_MULTIPART_BYTES {
+            return Err(AiStudioError::validation(
+                "multipart",
+                format!("multipart 内容不能超过 {} MB", MAX_MULTIPART_BYTES / 1024 / 1024),
+            ));
+        }
+        form = form.part(name.clone(), part);
+    }
+    Ok(form)
+}
+
+/// JSONPath 路径段
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    /// 对象字段
+    Key(String),
+    /// 数组下标，负数从末尾计
+    Index(i64),
+    /// 对象或数组的全部成员
+    Wildcard,
+}
+
+/// JSONPath 子集
+///
+/// 支持 `$`、`.name`、`['name']`、`[0]`、`[-1]`、`[*]` 与 `.*`，不支持过滤表达式与递归下降。
+/// 供 HTTP 工具与工作流步骤从 JSON 响应中提取字段。
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<PathSegment>,
+}
+
+impl JsonPath {
+    /// 解析路径
+    pub fn parse(path: &str) -> Result<Self, AiStudioError> {
+        let invalid = |reason: &str| AiStudioError::validation("extract", format!("无效的 JSONPath {}: {}", path, reason));
+        let rest = path.trim().strip_prefix('$').ok_or_else(|| invalid("必须以 $ 开头"))?;
+        let chars: Vec<char> = rest.chars().collect();
+        let mut segments = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '.' => {
+                    i += 1;
+                    if chars.get(i) == Some(&'*') {
+                        segments.push(PathSegment::Wildcard);
+                        i += 1;
+                        continue;
+                    }
+                    let start = i;
+                    while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                        i += 1;
+                    }
+                    if start == i {
+                        return Err(invalid("字段名为空"));
+                    }
+                    segments.push(PathSegment::Key(chars[start..i].iter().collect()));
+                }
+                '[' => {
+                    let end = chars[i..].iter().position(|c| *c == ']').map(|offset| i + offset)
+                        .ok_or_else(|| invalid("缺少 ]"))?;
+                    let inner: String = chars[i + 1..end].iter().collect();
+                    let inner = inner.trim();
+                    let segment = if inner == "*" {
+                        PathSegment::Wildcard
+                    } else if let Some(key) = inner.strip_prefix('\'').and_then(|s| s.strip_suffix('\''))
+                        .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                    {
+                        PathSegment::Key(key.to_string())
+                    } else {
+                        PathSegment::Index(inner.parse().map_err(|_| invalid("下标必须是整数"))?)
+                    };
+                    segments.push(segment);
+                    i = end + 1;
+                }
+                _ => return Err(invalid("路径段必须以 . 或 [ 开头")),
+            }
+        }
+        Ok(Self { segments })
+    }
+
+    /// 路径不含通配符时最多匹配一个值
+    pub fn is_singular(&self) -> bool {
+        !self.segments.contains(&PathSegment::Wildcard)
+    }
+
+    /// 选取匹配的全部值
+    pub fn select<'a>(&self, value: &'a serde_json::Value) -> Vec<&'a serde_json::Value> {
+        let mut current = vec![value];
+        for segment in &self.segments {
+            current = current
+                .into_iter()
+                .flat_map(|node| -> Vec<&'a serde_json::Value> {
+                    match (segment, node) {
+                        (PathSegment::Key(key), serde_json::Value::Object(map)) => map.get(key).into_iter().collect(),
+                        (PathSegment::Index(index), serde_json::Value::Array(items)) => {
+                            let position = if *index < 0 { items.len() as i64 + index } else { *index };
+                            usize::try_from(position).ok().and_then(|p| items.get(p)).into_iter().collect()
+                        }
+                        (PathSegment::Wildcard, serde_json::Value::Object(map)) => map.values().collect(),
+                        (PathSegment::Wildcard, serde_json::Value::Array(items)) => items.iter().collect(),
+                        _ => Vec::new(),
+                    }
+                })
+                .collect();
+        }
+        current
+    }
+}
+
+/// 按名称提取多个 JSONPath
+///
+/// 不含通配符的路径返回匹配的值（无匹配时为 null），含通配符的路径返回全部匹配值组成的数组。
+pub fn extract_json_paths(
+    value: &serde_json::Value,
+    paths: &serde_json::Map<String, serde_json::Value>,
+) -> Result<serde_json::Map<String, serde_json::Value>, AiStudioError> {
+    let mut extracted = serde_json::Map::new();
+    for (name, path) in paths {
+        let path = JsonPath::parse(path.as_str().unwrap_or_default())?;
+        let matches = path.select(value);
+        let result = if path.is_singular() {
+            matches.first().map(|v| (*v).clone()).unwrap_or(serde_json::Value::Null)
+        } else {
+            serde_json::Value::Array(matches.into_iter().cloned().collect())
+        };
+        extracted.insert(name.clone(), result);
+    }
+    Ok(extracted)
+}
+
+/// 保存的 Cookie
+#[derive(Debug, Clone, PartialEq)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    /// 所属域名，小写且不含前导点
+    domain: String,
+    /// 未指定 Domain 属性时只发送给设置它的主机
+    host_only: bool,
+    path: String,
+    secure: bool,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl StoredCookie {
+    /// 解析 Set-Cookie 响应头，Domain 属性与请求主机不匹配时丢弃
+    fn parse(header: &str, url: &Url, now: DateTime<Utc>) -> Option<Self> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        let mut parts = header.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Self {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_cookie_path(url.path()),
+            secure: false,
+            expires_at: None,
+        };
+        let mut max_age = None;
+        for attribute in parts {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                    if !domain_matches(&host, &domain) {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "expires" => {
+                    cookie.expires_at = DateTime::parse_from_rfc2822(value).ok().map(|t| t.with_timezone(&Utc));
+                }
+                _ => {}
+            }
+        }
+        // Max-Age 优先于 Expires
+        if let Some(seconds) = max_age {
+            cookie.expires_at = Some(now + chrono::Duration::seconds(seconds.max(0)));
+        }
+        Some(cookie)
+    }
+
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    fn matches(&self, url: &Url, now: DateTime<Utc>) -> bool {
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return false;
+        };
+        let domain_ok = if self.host_only { host == self.domain } else { domain_matches(&host, &self.domain) };
+        domain_ok
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired(now)
+    }
+}
+
+/// 未指定 Path 属性时取请求路径最后一个 `/` 之前的部分
+fn default_cookie_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => request_path[..index].to_string(),
+    }
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+/// 一次执行的 Cookie
+#[derive(Debug)]
+struct ExecutionCookies {
+    cookies: Vec<StoredCookie>,
+    last_used: Instant,
+}
+
+/// 按执行隔离的 Cookie 存储，同一执行内的请求共享 Cookie，不同执行之间互不可见
+#[derive(Debug, Clone, Default)]
+struct CookieJars {
+    jars: Arc<Mutex<HashMap<String, ExecutionCookies>>>,
+}
+
+impl CookieJars {
+    /// 生成请求应携带的 Cookie 请求头
+    fn header_for(&self, key: &str, url: &Url, now: DateTime<Utc>) -> Option<String> {
+        let mut jars = self.lock();
+        let jar = jars.get_mut(key)?;
+        jar.last_used = Instant::now();
+        jar.cookies.retain(|cookie| !cookie.is_expired(now));
+        let header = jar.cookies
+            .iter()
+            .filter(|cookie| cookie.matches(url, now))
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        (!header.is_empty()).then_some(header)
+    }
+
+    /// 保存响应设置的 Cookie，同名同域同路径的 Cookie 被替换，已过期的被删除
+    fn store<'a>(&self, key: &str, url: &Url, set_cookies: impl Iterator<Item = &'a str>, now: DateTime<Utc>) {
+        let parsed: Vec<StoredCookie> = set_cookies.filter_map(|header| StoredCookie::parse(header, url, now)).collect();
+        if parsed.is_empty() {
+            return;
+        }
+
+        let mut jars = self.lock();
+        if !jars.contains_key(key) {
+            jars.retain(|_, jar| jar.last_used.elapsed() < COOKIE_JAR_IDLE_TTL);
+            if jars.len() >= MAX_COOKIE_JARS {
+                if let Some(oldest) = jars.iter().min_by_key(|(_, jar)| jar.last_used).map(|(key, _)| key.clone()) {
+                    jars.remove(&oldest);
+                }
+            }
+        }
+        let jar = jars.entry(key.to_string()).or_insert_with(|| ExecutionCookies {
+            cookies: Vec::new(),
+            last_used: Instant::now(),
+        });
+        jar.last_used = Instant::now();
+        for cookie in parsed {
+            jar.cookies.retain(|existing| {
+                !(existing.name == cookie.name && existing.domain == cookie.domain && existing.path == cookie.path)
+            });
+            if !cookie.is_expired(now) {
+                jar.cookies.push(cookie);
+            }
+        }
+        if jar.cookies.len() > MAX_COOKIES_PER_EXECUTION {
+            let excess = jar.cookies.len() - MAX_COOKIES_PER_EXECUTION;
+            jar.cookies.drain(..excess);
+        }
+    }
+
+    fn clear(&self, key: &str) {
+        self.lock().remove(key);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ExecutionCookies>> {
+        self.jars.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[test]
     fn test_http_tool_validation() {
-        let tool = HttpTool::new().unwrap();
-        
+        let tool = HttpTool::new();
+
         // 测试有效参数
         let mut valid_params = HashMap::new();
         valid_params.insert("url".to_string(), serde_json::Value::String("https://httpbin.org/get".to_string()));
         valid_params.insert("method".to_string(), serde_json::Value::String("GET".to_string()));
         assert!(tool.validate_parameters(&valid_params).is_ok());
-        
+
         // 测试无效 URL
         let mut invalid_params = HashMap::new();
         invalid_params.insert("url".to_string(), serde_json::Value::String("not-a-url".to_string()));
         assert!(tool.validate_parameters(&invalid_params).is_err());
-        
+
         // 测试禁止的域名
         let mut blocked_params = HashMap::new();
         blocked_params.insert("url".to_string(), serde_json::Value::String("http://localhost:8080/test".to_string()));
         assert!(tool.validate_parameters(&blocked_params).is_err());
-        
+
         // 测试不支持的协议
         let mut unsupported_params = HashMap::new();
         unsupported_params.insert("url".to_string(), serde_json::Value::String("ftp://example.com/file".to_string()));
         assert!(tool.validate_parameters(&unsupported_params).is_err());
+
+        // 测试同时指定多种请求体
+        let mut conflicting_params = valid_params.clone();
+        conflicting_params.insert("json".to_string(), serde_json::json!({"a": 1}));
+        conflicting_params.insert("form".to_string(), serde_json::json!({"a": "1"}));
+        assert!(tool.validate_parameters(&conflicting_params).is_err());
+
+        // 测试无效的 multipart 文件字段
+        let mut multipart_params = valid_params.clone();
+        multipart_params.insert("multipart".to_string(), serde_json::json!({"file": {"filename": "a.txt"}}));
+        assert!(tool.validate_parameters(&multipart_params).is_err());
+    }
+
+    #[test]
+    fn test_json_path_extraction() {
+        let body = serde_json::json!({
+            "data": {
+                "items": [
+                    {"id": 1, "name": "a"},
+                    {"id": 2, "name": "b"}
+                ],
+                "total-count": 2
+            }
+        });
+        let paths = serde_json::json!({
+            "first_id": "$.data.items[0].id",
+            "last_name": "$.data.items[-1].name",
+            "ids": "$.data.items[*].id",
+            "total": "$.data['total-count']",
+            "missing": "$.data.cursor"
+        });
+
+        let extracted = extract_json_paths(&body, paths.as_object().unwrap()).unwrap();
+        assert_eq!(extracted["first_id"], serde_json::json!(1));
+        assert_eq!(extracted["last_name"], serde_json::json!("b"));
+        assert_eq!(extracted["ids"], serde_json::json!([1, 2]));
+        assert_eq!(extracted["total"], serde_json::json!(2));
+        assert_eq!(extracted["missing"], serde_json::Value::Null);
+
+        assert!(JsonPath::parse("data.items").is_err());
+        assert!(JsonPath::parse("$.items[abc]").is_err());
+    }
+
+    #[test]
+    fn test_cookies_isolated_per_execution() {
+        let jars = CookieJars::default();
+        let now = Utc::now();
+        let login = Url::parse("https://app.example.com/auth/login").unwrap();
+        jars.store(
+            "execution-a",
+            &login,
+            ["session=abc; Path=/; Secure; HttpOnly", "theme=dark; Domain=example.com; Max-Age=3600", "stale=1; Max-Age=0"].into_iter(),
+            now,
+        );
+
+        let api = Url::parse("https://app.example.com/api/items").unwrap();
+        assert_eq!(jars.header_for("execution-a", &api, now).as_deref(), Some("session=abc; theme=dark"));
+        // 其他子域名只收到指定了 Domain 的 Cookie，明文请求不携带 Secure Cookie
+        let other = Url::parse("http://cdn.example.com/").unwrap();
+        assert_eq!(jars.header_for("execution-a", &other, now).as_deref(), Some("theme=dark"));
+        // 不同执行之间互不可见
+        assert_eq!(jars.header_for("execution-b", &api, now), None);
+
+        // 覆盖与删除
+        jars.store("execution-a", &login, ["session=; Path=/; Max-Age=0"].into_iter(), now);
+        assert_eq!(jars.header_for("execution-a", &api, now).as_deref(), Some("theme=dark"));
+
+        // Domain 与请求主机不匹配时丢弃
+        jars.store("execution-a", &login, ["evil=1; Domain=attacker.com"].into_iter(), now);
+        assert_eq!(jars.header_for("execution-a", &Url::parse("https://attacker.com/").unwrap(), now), None);
+
+        jars.clear("execution-a");
+        assert_eq!(jars.header_for("execution-a", &api, now), None);
     }
-    
+
     #[tokio::test]
     async fn test_http_get_request() {
-        let tool = HttpTool::new().unwrap();
+        let tool = HttpTool::new();
         let mut parameters = HashMap::new();
         parameters.insert("url".to_string(), serde_json::Value::String("https://httpbin.org/get".to_string()));
         parameters.insert("method".to_string(), serde_json::Value::String("GET".to_string()));
-        
+
         let context = ExecutionContext {
             current_task: None,
             execution_history: Vec::new(),
             context_variables: HashMap::new(),
             session_id: None,
             user_id: None,
+            tool_progress: None,
         };
-        
+
         // 注意：这个测试需要网络连接
         if let Ok(result) = tool.execute(parameters, &context).await {
             assert!(result.success);
             assert!(result.data.get("status").is_some());
         }
     }
-}
\ No newline at end of file
+}
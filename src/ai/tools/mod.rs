@@ -5,11 +5,15 @@ pub mod search_tool;
 pub mod calculator_tool;
 pub mod file_tool;
 pub mod http_tool;
+pub mod dataset_tool;
+pub mod scratch;
 
 pub use search_tool::*;
 pub use calculator_tool::*;
 pub use file_tool::*;
 pub use http_tool::*;
+pub use dataset_tool::*;
+pub use scratch::*;
 
 use std::collections::HashMap;
 use serde_json;
@@ -30,6 +34,25 @@ impl ToolFactory {
         ]
     }
     
+    /// 创建依赖数据库的数据工具，其中搜索工具只在 Agent 绑定的知识库中检索，联网搜索按租户设置的搜索后端进行
+    pub fn create_data_tools(
+        db: sea_orm::DatabaseConnection,
+        embedder: std::sync::Arc<crate::ai::rig_client::RigAiClient>,
+    ) -> Vec<ToolEnum> {
+        let web_search = crate::services::web_search::WebSearchService::from_app_config(
+            db.clone(),
+            crate::config::ConfigLoader::get(),
+        );
+        vec![
+            ToolEnum::SearchTool(
+                SearchTool::new()
+                    .with_knowledge_bases(db.clone(), embedder)
+                    .with_web_search(web_search),
+            ),
+            ToolEnum::DatasetQueryTool(DatasetQueryTool::new(db)),
+        ]
+    }
+    
     /// 根据名称创建工具
     pub fn create_tool(tool_name: &str) -> Option<ToolEnum> {
         match tool_name {
@@ -0,0 +1,451 @@
+// 执行临时工作区
+// 每次 Agent / 工作流执行拥有独立的临时目录，限制总大小，闲置或执行结束后自动清理
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use once_cell::sync::Lazy;
+use tokio::fs;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::ai::agent_runtime::ExecutionContext;
+use crate::errors::AiStudioError;
+
+/// 进程内共享的工作区注册表
+static SHARED_WORKSPACES: Lazy<ScratchWorkspaces> = Lazy::new(|| ScratchWorkspaces::new(ScratchConfig::default()));
+
+/// 文件引用的 URI 前缀
+const FILE_REF_SCHEME: &str = "scratch://";
+
+/// 执行 ID 的最大长度
+const MAX_EXECUTION_ID_LEN: usize = 128;
+
+/// 工具调用所属的执行键：执行 ID，其次会话 ID
+///
+/// 同一执行内的工具调用共享临时工作区与 Cookie，不同执行之间互相隔离。
+pub fn execution_key(context: &ExecutionContext) -> Option<String> {
+    context.context_variables.get("execution_id")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .or_else(|| context.session_id.map(|id| id.to_string()))
+}
+
+/// 工具调用所属的租户
+pub fn context_tenant_id(context: &ExecutionContext) -> Option<Uuid> {
+    context.context_variables.get("tenant_id")
+        .and_then(|v| v.as_str())
+        .and_then(|v| Uuid::parse_str(v).ok())
+}
+
+/// 规范化相对路径，只接受由普通路径段组成的相对路径
+pub fn normalize_relative_path(relative: &str) -> Result<PathBuf, AiStudioError> {
+    let relative = relative.trim();
+    if relative.is_empty() {
+        return Err(AiStudioError::validation("path", "路径不能为空"));
+    }
+
+    let mut path = PathBuf::new();
+    for component in Path::new(relative).components() {
+        match component {
+            Component::Normal(segment) => path.push(segment),
+            Component::CurDir => {}
+            Component::ParentDir => return Err(AiStudioError::validation("path", "路径不能包含 ..")),
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(AiStudioError::validation("path", "只允许相对路径"));
+            }
+        }
+    }
+    Ok(path)
+}
+
+/// 将相对路径限制在根目录内
+///
+/// 拼接后沿最深的已存在祖先解析符号链接，确保最终位置仍在根目录下。`root` 须为规范化路径。
+pub fn jail_path(root: &Path, relative: &str) -> Result<PathBuf, AiStudioError> {
+    let path = root.join(normalize_relative_path(relative)?);
+
+    // 符号链接可能指向根目录之外
+    let mut existing = path.as_path();
+    while !existing.exists() {
+        existing = match existing.parent() {
+            Some(parent) => parent,
+            None => break,
+        };
+    }
+    let resolved = existing.canonicalize().map_err(|e| {
+        AiStudioError::internal(format!("解析路径失败: {}", e))
+    })?;
+    if !resolved.starts_with(root) {
+        return Err(AiStudioError::validation("path", "路径超出允许的目录"));
+    }
+
+    Ok(path)
+}
+
+/// 指向某次执行工作区内文件的引用，格式为 `scratch://<execution_id>/<path>`
+///
+/// 步骤之间通过引用而非主机路径传递文件，引用只能在同一租户的执行之间解析。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRef {
+    /// 文件所属的执行
+    pub execution_id: String,
+    /// 工作区内的相对路径
+    pub path: String,
+}
+
+impl fmt::Display for FileRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}/{}", FILE_REF_SCHEME, self.execution_id, self.path)
+    }
+}
+
+impl FromStr for FileRef {
+    type Err = AiStudioError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || AiStudioError::validation("file_ref", format!("无效的文件引用: {}", s));
+        let rest = s.strip_prefix(FILE_REF_SCHEME).ok_or_else(invalid)?;
+        let (execution_id, path) = rest.split_once('/').ok_or_else(invalid)?;
+        check_execution_id(execution_id).map_err(|_| invalid())?;
+        if path.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            execution_id: execution_id.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+/// 临时工作区配置
+#[derive(Debug, Clone)]
+pub struct ScratchConfig {
+    /// 所有工作区的父目录
+    pub root: PathBuf,
+    /// 单个工作区的最大总大小（字节）
+    pub max_workspace_bytes: u64,
+    /// 工作区闲置超过该时长后清理
+    pub idle_ttl: Duration,
+}
+
+impl Default for ScratchConfig {
+    fn default() -> Self {
+        Self {
+            root: std::env::temp_dir().join("aionix-scratch"),
+            max_workspace_bytes: 100 * 1024 * 1024, // 100MB
+            idle_ttl: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+/// 已创建的工作区
+#[derive(Debug)]
+struct WorkspaceEntry {
+    root: PathBuf,
+    tenant_id: Option<Uuid>,
+    last_used: Instant,
+}
+
+/// 工作区注册表，克隆的实例共享同一组工作区
+#[derive(Debug, Clone)]
+pub struct ScratchWorkspaces {
+    config: ScratchConfig,
+    workspaces: Arc<Mutex<HashMap<String, WorkspaceEntry>>>,
+}
+
+impl ScratchWorkspaces {
+    /// 创建注册表
+    pub fn new(config: ScratchConfig) -> Self {
+        Self {
+            config,
+            workspaces: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 进程内共享的注册表
+    pub fn shared() -> Self {
+        SHARED_WORKSPACES.clone()
+    }
+
+    /// 获取执行的工作区，不存在时创建
+    pub async fn acquire(&self, execution_id: &str, tenant_id: Option<Uuid>) -> Result<ScratchWorkspace, AiStudioError> {
+        check_execution_id(execution_id)?;
+
+        let existing = {
+            let mut workspaces = self.lock();
+            workspaces.get_mut(execution_id).map(|entry| {
+                entry.last_used = Instant::now();
+                (entry.root.clone(), entry.tenant_id)
+            })
+        };
+        if let Some((root, owner)) = existing {
+            if owner != tenant_id {
+                return Err(AiStudioError::validation("execution_id", "工作区属于其他租户"));
+            }
+            return Ok(self.workspace(execution_id, root));
+        }
+
+        self.sweep().await;
+
+        let dir = self.config.root.join(execution_id);
+        fs::create_dir_all(&dir).await.map_err(|e| {
+            AiStudioError::internal(format!("创建临时工作区失败: {}", e))
+        })?;
+        let root = dir.canonicalize().map_err(|e| {
+            AiStudioError::internal(format!("解析临时工作区路径失败: {}", e))
+        })?;
+        debug!("创建临时工作区: execution_id={}, path={}", execution_id, root.display());
+
+        let mut workspaces = self.lock();
+        let entry = workspaces.entry(execution_id.to_string()).or_insert_with(|| WorkspaceEntry {
+            root,
+            tenant_id,
+            last_used: Instant::now(),
+        });
+        if entry.tenant_id != tenant_id {
+            return Err(AiStudioError::validation("execution_id", "工作区属于其他租户"));
+        }
+        Ok(self.workspace(execution_id, entry.root.clone()))
+    }
+
+    /// 解析文件引用，返回主机路径
+    ///
+    /// 引用的执行须仍持有工作区且与调用方属于同一租户。
+    pub fn resolve_ref(&self, file_ref: &FileRef, tenant_id: Option<Uuid>) -> Result<PathBuf, AiStudioError> {
+        let root = {
+            let mut workspaces = self.lock();
+            let entry = workspaces.get_mut(&file_ref.execution_id)
+                .filter(|entry| entry.tenant_id == tenant_id)
+                .ok_or_else(|| AiStudioError::not_found(&format!("文件引用已失效: {}", file_ref)))?;
+            entry.last_used = Instant::now();
+            entry.root.clone()
+        };
+        jail_path(&root, &file_ref.path)
+    }
+
+    /// 删除执行的工作区，执行结束时调用
+    pub async fn release(&self, execution_id: &str) {
+        let entry = self.lock().remove(execution_id);
+        if let Some(entry) = entry {
+            remove_dir(&entry.root).await;
+            debug!("清理临时工作区: execution_id={}", execution_id);
+        }
+    }
+
+    /// 清理闲置的工作区，以及进程重启前遗留的目录
+    pub async fn sweep(&self) {
+        let idle: Vec<PathBuf> = {
+            let mut workspaces = self.lock();
+            let expired: Vec<String> = workspaces.iter()
+                .filter(|(_, entry)| entry.last_used.elapsed() >= self.config.idle_ttl)
+                .map(|(key, _)| key.clone())
+                .collect();
+            expired.iter().filter_map(|key| workspaces.remove(key)).map(|entry| entry.root).collect()
+        };
+        for root in &idle {
+            remove_dir(root).await;
+        }
+        if !idle.is_empty() {
+            info!("清理了 {} 个闲置临时工作区", idle.len());
+        }
+
+        let Ok(mut dir) = fs::read_dir(&self.config.root).await else {
+            return;
+        };
+        while let Ok(Some(entry)) = dir.next_entry().await {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if self.lock().contains_key(&name) {
+                continue;
+            }
+            let stale = entry.metadata().await.ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| SystemTime::now().duration_since(t).ok())
+                .is_some_and(|age| age >= self.config.idle_ttl);
+            if stale {
+                remove_dir(&entry.path()).await;
+            }
+        }
+    }
+
+    fn workspace(&self, execution_id: &str, root: PathBuf) -> ScratchWorkspace {
+        ScratchWorkspace {
+            execution_id: execution_id.to_string(),
+            root,
+            max_bytes: self.config.max_workspace_bytes,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, WorkspaceEntry>> {
+        self.workspaces.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for ScratchWorkspaces {
+    fn default() -> Self {
+        Self::shared()
+    }
+}
+
+/// 一次执行的临时工作区
+#[derive(Debug, Clone)]
+pub struct ScratchWorkspace {
+    execution_id: String,
+    root: PathBuf,
+    max_bytes: u64,
+}
+
+impl ScratchWorkspace {
+    /// 工作区根目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 将工作区内的相对路径解析为主机路径
+    pub fn resolve(&self, relative: &str) -> Result<PathBuf, AiStudioError> {
+        jail_path(&self.root, relative)
+    }
+
+    /// 工作区内文件的引用
+    pub fn file_ref(&self, relative: &str) -> Result<FileRef, AiStudioError> {
+        let path = normalize_relative_path(relative)?
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        Ok(FileRef {
+            execution_id: self.execution_id.clone(),
+            path,
+        })
+    }
+
+    /// 检查写入后工作区是否超出大小限制
+    ///
+    /// `replaced` 为写入将覆盖的现有字节数。
+    pub async fn ensure_capacity(&self, additional: u64, replaced: u64) -> Result<(), AiStudioError> {
+        let usage = self.usage().await?;
+        let projected = usage.saturating_sub(replaced) + additional;
+        if projected > self.max_bytes {
+            return Err(AiStudioError::validation("workspace_size", format!(
+                "临时工作区空间不足: 写入后 {} 字节，最大允许: {} 字节",
+                projected,
+                self.max_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    /// 工作区当前占用的字节数
+    pub async fn usage(&self) -> Result<u64, AiStudioError> {
+        let mut total = 0;
+        let mut pending = vec![self.root.clone()];
+        while let Some(dir) = pending.pop() {
+            let mut entries = fs::read_dir(&dir).await.map_err(|e| {
+                AiStudioError::internal(format!("读取临时工作区失败: {}", e))
+            })?;
+            while let Some(entry) = entries.next_entry().await.map_err(|e| {
+                AiStudioError::internal(format!("读取临时工作区失败: {}", e))
+            })? {
+                let metadata = entry.metadata().await.map_err(|e| {
+                    AiStudioError::internal(format!("获取文件元数据失败: {}", e))
+                })?;
+                if metadata.is_dir() {
+                    pending.push(entry.path());
+                } else {
+                    total += metadata.len();
+                }
+            }
+        }
+        Ok(total)
+    }
+}
+
+/// 执行 ID 用作目录名，只允许字母、数字、- 与 _
+fn check_execution_id(execution_id: &str) -> Result<(), AiStudioError> {
+    let valid = !execution_id.is_empty()
+        && execution_id.len() <= MAX_EXECUTION_ID_LEN
+        && execution_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(AiStudioError::validation("execution_id", "执行 ID 只能包含字母、数字、- 与 _"));
+    }
+    Ok(())
+}
+
+async fn remove_dir(path: &Path) {
+    if let Err(e) = fs::remove_dir_all(path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            warn!("删除临时工作区失败: path={}, error={}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspaces(temp_dir: &TempDir, max_workspace_bytes: u64) -> ScratchWorkspaces {
+        ScratchWorkspaces::new(ScratchConfig {
+            root: temp_dir.path().to_path_buf(),
+            max_workspace_bytes,
+            idle_ttl: Duration::from_secs(60),
+        })
+    }
+
+    #[test]
+    fn test_jail_path_rejects_escapes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+
+        assert_eq!(jail_path(&root, "a/./b.txt").unwrap(), root.join("a/b.txt"));
+        assert!(jail_path(&root, "../etc/passwd").is_err());
+        assert!(jail_path(&root, "a/../../b.txt").is_err());
+        assert!(jail_path(&root, "/etc/passwd").is_err());
+        assert!(jail_path(&root, "").is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("/etc", root.join("link")).unwrap();
+            assert!(jail_path(&root, "link/passwd").is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_workspace_isolation_and_release() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspaces = workspaces(&temp_dir, 16);
+        let tenant = Some(Uuid::new_v4());
+
+        let workspace = workspaces.acquire("exec-a", tenant).await.unwrap();
+        let path = workspace.resolve("out/result.txt").unwrap();
+        fs::create_dir_all(path.parent().unwrap()).await.unwrap();
+        fs::write(&path, b"0123456789").await.unwrap();
+
+        // 大小限制
+        assert!(workspace.ensure_capacity(6, 0).await.is_ok());
+        assert!(workspace.ensure_capacity(7, 0).await.is_err());
+        assert!(workspace.ensure_capacity(16, 10).await.is_ok());
+
+        // 文件引用只在同一租户内解析
+        let file_ref: FileRef = workspace.file_ref("./out/result.txt").unwrap().to_string().parse().unwrap();
+        assert_eq!(file_ref.to_string(), "scratch://exec-a/out/result.txt");
+        assert_eq!(workspaces.resolve_ref(&file_ref, tenant).unwrap(), path);
+        assert!(workspaces.resolve_ref(&file_ref, Some(Uuid::new_v4())).is_err());
+        assert!(workspaces.acquire("exec-a", None).await.is_err());
+
+        workspaces.release("exec-a").await;
+        assert!(!workspace.root().exists());
+        assert!(workspaces.resolve_ref(&file_ref, tenant).is_err());
+    }
+
+    #[test]
+    fn test_file_ref_parse() {
+        assert!("scratch://exec-1/a/b.txt".parse::<FileRef>().is_ok());
+        assert!("file:///etc/passwd".parse::<FileRef>().is_err());
+        assert!("scratch://../a.txt".parse::<FileRef>().is_err());
+        assert!("scratch://exec-1/".parse::<FileRef>().is_err());
+    }
+}
@@ -1,17 +1,51 @@
 // 搜索工具实现
+// 在 Agent 绑定的知识库中检索，或按租户设置的搜索后端联网搜索
 
 use std::collections::HashMap;
+use std::sync::Arc;
+use sea_orm::DatabaseConnection;
 use serde_json;
 use tracing::{debug, error};
+use uuid::Uuid;
 
-use crate::ai::agent_runtime::{Tool, ToolResult, ToolMetadata, ExecutionContext};
+use crate::ai::agent_runtime::{Tool, ToolResult, ToolMetadata, ExecutionContext, KnowledgeBaseBinding};
+use crate::ai::rig_client::RigAiClient;
+use crate::ai::workflow_engine::{KnowledgeSearchFusion, StepConfig};
+use crate::ai::workflow_knowledge_search::KnowledgeSearchStepRunner;
+use crate::db::entities::document::ClearanceLevel;
 use crate::errors::AiStudioError;
+use crate::services::web_search::{WebSearchDomainPolicy, WebSearchHit, WebSearchService};
+
+/// 执行上下文中存放 Agent 知识库绑定的变量名
+pub const KNOWLEDGE_BASE_BINDINGS_VAR: &str = "knowledge_base_bindings";
+
+/// 执行上下文中存放 Agent 联网搜索域名策略的变量名
+pub const WEB_SEARCH_POLICY_VAR: &str = "web_search_policy";
 
 /// 搜索工具
-#[derive(Debug, Clone)]
+///
+/// 配置数据库连接后在 Agent 绑定的知识库中检索，未绑定的知识库不会被检索；
+/// 配置联网搜索服务后可按 `source: "web"` 联网搜索，结果附带引用链接并按 Agent 的域名策略过滤。
+#[derive(Clone)]
 pub struct SearchTool {
     /// 工具配置
     config: SearchToolConfig,
+    /// 数据库连接，未配置时返回模拟结果
+    db: Option<DatabaseConnection>,
+    /// 未启用本地嵌入工作池时使用的嵌入客户端
+    embedder: Option<Arc<RigAiClient>>,
+    /// 联网搜索服务，未配置时不支持联网搜索
+    web: Option<WebSearchService>,
+}
+
+impl std::fmt::Debug for SearchTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SearchTool")
+            .field("config", &self.config)
+            .field("knowledge_base_search", &self.db.is_some())
+            .field("web_search", &self.web.is_some())
+            .finish()
+    }
 }
 
 /// 搜索工具配置
@@ -35,15 +69,62 @@ impl Default for SearchToolConfig {
 impl SearchTool {
     /// 创建新的搜索工具
     pub fn new() -> Self {
-        Self {
-            config: SearchToolConfig::default(),
-        }
+        Self::with_config(SearchToolConfig::default())
     }
     
     /// 使用自定义配置创建搜索工具
     pub fn with_config(config: SearchToolConfig) -> Self {
-        Self { config }
+        Self { config, db: None, embedder: None, web: None }
+    }
+    
+    /// 设置数据库连接与嵌入客户端，启用知识库检索
+    pub fn with_knowledge_bases(mut self, db: DatabaseConnection, embedder: Arc<RigAiClient>) -> Self {
+        self.db = Some(db);
+        self.embedder = Some(embedder);
+        self
+    }
+    
+    /// 设置联网搜索服务，启用联网搜索
+    pub fn with_web_search(mut self, web: WebSearchService) -> Self {
+        self.web = Some(web);
+        self
+    }
+}
+
+/// 从执行上下文读取 Agent 的知识库绑定
+fn context_bindings(context: &ExecutionContext) -> Result<Vec<KnowledgeBaseBinding>, AiStudioError> {
+    match context.context_variables.get(KNOWLEDGE_BASE_BINDINGS_VAR) {
+        None | Some(serde_json::Value::Null) => Ok(Vec::new()),
+        Some(value) => Ok(serde_json::from_value(value.clone())?),
+    }
+}
+
+/// 从执行上下文读取 Agent 的联网搜索域名策略
+fn context_web_policy(context: &ExecutionContext) -> Result<WebSearchDomainPolicy, AiStudioError> {
+    match context.context_variables.get(WEB_SEARCH_POLICY_VAR) {
+        None | Some(serde_json::Value::Null) => Ok(WebSearchDomainPolicy::default()),
+        Some(value) => Ok(serde_json::from_value(value.clone())?),
+    }
+}
+
+/// 按请求的知识库选出要检索的绑定，请求未绑定的知识库时报错
+fn select_bindings(
+    bindings: Vec<KnowledgeBaseBinding>,
+    requested: Option<Uuid>,
+) -> Result<Vec<KnowledgeBaseBinding>, AiStudioError> {
+    if bindings.is_empty() {
+        return Err(AiStudioError::forbidden("Agent 未绑定知识库，无法检索"));
+    }
+    let Some(requested) = requested else {
+        return Ok(bindings);
+    };
+    let selected: Vec<KnowledgeBaseBinding> = bindings.into_iter()
+        .filter(|binding| binding.knowledge_base_id == requested)
+        .collect();
+    if selected.is_empty() {
+        return Err(AiStudioError::forbidden(format!("知识库 {} 未绑定到该 Agent", requested)));
     }
+    Ok(selected)
 }
 
 impl Tool for SearchTool {
@@ -72,12 +153,66 @@ impl Tool for SearchTool {
         
         debug!("搜索查询: {}, 限制: {}", query, limit);
         
-        // 模拟搜索执行
         let start_time = std::time::Instant::now();
         
-        // 这里应该实现实际的搜索逻辑
-        // 目前返回模拟结果
-        let search_results = self.perform_search(query, limit as usize).await?;
+        if parameters.get("source").and_then(|v| v.as_str()) == Some("web") {
+            let hits = match self.search_web(context, query, limit as usize).await {
+                Ok(hits) => hits,
+                Err(AiStudioError::Authorization { message }) => {
+                    return Ok(ToolResult {
+                        success: false,
+                        data: serde_json::json!({ "query": query, "results": [], "total_results": 0 }),
+                        error: Some(message),
+                        execution_time_ms: start_time.elapsed().as_millis() as u64,
+                        message: None,
+                    });
+                }
+                Err(e) => return Err(e),
+            };
+            let citations: Vec<serde_json::Value> = hits.iter()
+                .map(|hit| serde_json::json!({ "index": hit.rank, "title": hit.title, "url": hit.url }))
+                .collect();
+            let results: Vec<SearchResult> = hits.into_iter().map(SearchResult::from).collect();
+            
+            return Ok(ToolResult {
+                success: true,
+                data: serde_json::json!({
+                    "query": query,
+                    "source": "web",
+                    "results": results,
+                    "citations": citations,
+                    "total_results": results.len()
+                }),
+                error: None,
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+                message: Some(format!("联网搜索找到 {} 个结果，引用时请注明来源链接", results.len())),
+            });
+        }
+        
+        let search_results = match &self.db {
+            Some(db) => {
+                let requested = match parameters.get("knowledge_base_id").and_then(|v| v.as_str()) {
+                    Some(id) => Some(Uuid::parse_str(id)
+                        .map_err(|_| AiStudioError::validation("knowledge_base_id", "必须是有效的 UUID"))?),
+                    None => None,
+                };
+                let bindings = match select_bindings(context_bindings(context)?, requested) {
+                    Ok(bindings) => bindings,
+                    Err(e) => {
+                        return Ok(ToolResult {
+                            success: false,
+                            data: serde_json::json!({ "query": query, "results": [], "total_results": 0 }),
+                            error: Some(e.to_string()),
+                            execution_time_ms: start_time.elapsed().as_millis() as u64,
+                            message: None,
+                        });
+                    }
+                };
+                self.search_bound_knowledge_bases(db, context, query, limit as usize, &bindings).await?
+            }
+            // 未配置数据库时返回模拟结果
+            None => self.perform_search(query, limit as usize).await?,
+        };
         
         let execution_time = start_time.elapsed().as_millis() as u64;
         
@@ -98,7 +233,7 @@ impl Tool for SearchTool {
     fn metadata(&self) -> ToolMetadata {
         ToolMetadata {
             name: "search".to_string(),
-            description: "在知识库中搜索相关信息".to_string(),
+            description: "在知识库中搜索相关信息，或联网搜索并返回带引用链接的结果".to_string(),
             parameters_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
@@ -106,6 +241,17 @@ impl Tool for SearchTool {
                         "type": "string",
                         "description": "搜索查询字符串"
                     },
+                    "source": {
+                        "type": "string",
+                        "description": "搜索范围：knowledge_base 为绑定的知识库，web 为联网搜索",
+                        "enum": ["knowledge_base", "web"],
+                        "default": "knowledge_base"
+                    },
+                    "knowledge_base_id": {
+                        "type": "string",
+                        "format": "uuid",
+                        "description": "只在指定的已绑定知识库中检索，默认检索所有绑定的知识库"
+                    },
                     "limit": {
                         "type": "integer",
                         "description": "最大结果数量",
@@ -116,9 +262,30 @@ impl Tool for SearchTool {
                 },
                 "required": ["query"]
             }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "required": ["query", "results", "total_results"],
+                "properties": {
+                    "query": { "type": "string" },
+                    "source": { "type": "string" },
+                    "results": { "type": "array" },
+                    "citations": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "index": { "type": "integer" },
+                                "title": { "type": "string" },
+                                "url": { "type": "string" }
+                            }
+                        }
+                    },
+                    "total_results": { "type": "integer", "minimum": 0 }
+                }
+            })),
             category: "information".to_string(),
             requires_permission: false,
-            version: "1.0.0".to_string(),
+            version: "1.1.0".to_string(),
         }
     }
     
@@ -147,6 +314,13 @@ impl Tool for SearchTool {
             }
         }
         
+        // 验证搜索范围
+        if let Some(source) = parameters.get("source") {
+            if !matches!(source.as_str(), Some("knowledge_base" | "web")) {
+                return Err(AiStudioError::validation("source", "必须是 knowledge_base 或 web"));
+            }
+        }
+        
         // 验证限制参数
         if let Some(limit) = parameters.get("limit") {
             if let Some(limit_num) = limit.as_u64() {
@@ -163,6 +337,79 @@ impl Tool for SearchTool {
 }
 
 impl SearchTool {
+    /// 在绑定的知识库中分别检索，每个知识库按绑定的数量与过滤条件召回，合并后按得分排序
+    async fn search_bound_knowledge_bases(
+        &self,
+        db: &DatabaseConnection,
+        context: &ExecutionContext,
+        query: &str,
+        limit: usize,
+        bindings: &[KnowledgeBaseBinding],
+    ) -> Result<Vec<SearchResult>, AiStudioError> {
+        let tenant_id = context.context_variables.get("tenant_id")
+            .and_then(|v| v.as_str())
+            .and_then(|v| Uuid::parse_str(v).ok())
+            .ok_or_else(|| AiStudioError::validation("tenant_id", "执行上下文缺少租户信息"))?;
+        
+        let mut runner = KnowledgeSearchStepRunner::new(db.clone());
+        if let Some(embedder) = &self.embedder {
+            runner = runner.with_embedder(embedder.clone());
+        }
+        // 查询经模板变量原样传入，避免其中的 `{{` 被当作模板解析
+        let variables = HashMap::from([("query".to_string(), serde_json::Value::String(query.to_string()))]);
+        
+        let mut results = Vec::new();
+        for binding in bindings {
+            let config = StepConfig::KnowledgeSearch {
+                knowledge_base_id: binding.knowledge_base_id,
+                query: "{{query}}".to_string(),
+                top_k: binding.top_k,
+                mode: binding.mode,
+                similarity_threshold: binding.similarity_threshold,
+                vector_weight: None,
+                keyword_weight: None,
+                fusion: KnowledgeSearchFusion::default(),
+                filters: binding.filters.clone(),
+            };
+            // Agent 执行没有用户密级，只检索公开文档
+            let output = runner.run(tenant_id, ClearanceLevel::Public, &config, &variables).await?;
+            results.extend(output.chunks.into_iter().map(|hit| SearchResult {
+                id: hit.chunk_id.to_string(),
+                title: hit.document_title,
+                content: hit.content,
+                relevance_score: hit.score,
+                source: hit.document_id.to_string(),
+                metadata: HashMap::from([
+                    ("knowledge_base_id".to_string(), binding.knowledge_base_id.to_string()),
+                    ("chunk_index".to_string(), hit.chunk_index.to_string()),
+                ]),
+            }));
+        }
+        
+        results.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+        results.truncate(limit);
+        debug!("知识库检索完成: 查询='{}', 绑定数={}, 结果数={}", query, bindings.len(), results.len());
+        Ok(results)
+    }
+    
+    /// 联网搜索，结果按 Agent 的域名策略过滤
+    async fn search_web(
+        &self,
+        context: &ExecutionContext,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<WebSearchHit>, AiStudioError> {
+        let web = self.web.as_ref()
+            .ok_or_else(|| AiStudioError::forbidden("未配置联网搜索"))?;
+        let tenant_id = context.context_variables.get("tenant_id")
+            .and_then(|v| v.as_str())
+            .and_then(|v| Uuid::parse_str(v).ok())
+            .ok_or_else(|| AiStudioError::validation("tenant_id", "执行上下文缺少租户信息"))?;
+        let policy = context_web_policy(context)?;
+        
+        web.search(tenant_id, query, limit, &policy).await
+    }
+    
     /// 执行搜索
     async fn perform_search(
         &self,
@@ -212,6 +459,23 @@ pub struct SearchResult {
     pub metadata: HashMap<String, String>,
 }
 
+impl From<WebSearchHit> for SearchResult {
+    fn from(hit: WebSearchHit) -> Self {
+        Self {
+            id: format!("web_{}", hit.rank),
+            relevance_score: 1.0 / hit.rank as f32,
+            metadata: HashMap::from([
+                ("url".to_string(), hit.url.clone()),
+                ("domain".to_string(), hit.domain),
+                ("citation".to_string(), format!("[{}]", hit.rank)),
+            ]),
+            title: hit.title,
+            content: hit.snippet,
+            source: hit.url,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,6 +492,7 @@ mod tests {
             context_variables: HashMap::new(),
             session_id: None,
             user_id: None,
+            tool_progress: None,
         };
         
         let result = tool.execute(parameters, &context).await.unwrap();
@@ -254,4 +519,22 @@ mod tests {
         invalid_params.insert("limit".to_string(), serde_json::Value::Number(serde_json::Number::from(0)));
         assert!(tool.validate_parameters(&invalid_params).is_err());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_select_bindings_enforces_agent_scope() {
+        let binding = |id: Uuid| KnowledgeBaseBinding {
+            knowledge_base_id: id,
+            top_k: 5,
+            mode: Default::default(),
+            similarity_threshold: None,
+            filters: Default::default(),
+        };
+        let hr = Uuid::new_v4();
+        let engineering = Uuid::new_v4();
+        
+        assert!(select_bindings(Vec::new(), None).is_err());
+        assert_eq!(select_bindings(vec![binding(hr)], None).unwrap().len(), 1);
+        assert_eq!(select_bindings(vec![binding(hr)], Some(hr)).unwrap()[0].knowledge_base_id, hr);
+        assert!(select_bindings(vec![binding(hr)], Some(engineering)).is_err());
+    }
+}
@@ -566,6 +566,7 @@ mod tests {
                 overlap_with_previous: false,
                 overlap_with_next: false,
                 custom_properties: HashMap::new(),
+                table: None,
             },
             embedding,
             position: ChunkPosition {
@@ -586,6 +587,7 @@ mod tests {
             temperature: 0.7,
             timeout: 30,
             retry_attempts: 3,
+            local: None,
         };
         
         // 注意：在测试环境中可能会失败，因为没有真实的 AI 服务
@@ -615,6 +617,7 @@ mod tests {
             temperature: 0.7,
             timeout: 30,
             retry_attempts: 3,
+            local: None,
         };
         
         let client_manager = match RigAiClientManager::new(config).await {
@@ -644,6 +647,7 @@ mod tests {
             temperature: 0.7,
             timeout: 30,
             retry_attempts: 3,
+            local: None,
         };
         
         let client_manager = match RigAiClientManager::new(config).await {
@@ -696,6 +700,7 @@ mod tests {
             temperature: 0.7,
             timeout: 30,
             retry_attempts: 3,
+            local: None,
         };
         
         let client_manager = match RigAiClientManager::new(config).await {
@@ -742,6 +747,7 @@ mod tests {
             temperature: 0.7,
             timeout: 30,
             retry_attempts: 3,
+            local: None,
         };
         
         let client_manager = match RigAiClientManager::new(config).await {
@@ -0,0 +1,837 @@
+// 向量存储后端
+// 将知识库向量的写入、读取与相似度检索抽象为 VectorStore，支持 pgvector、Qdrant 与 Milvus，按知识库选择后端
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, Statement};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::config::{ExternalVectorStoreConfig, VectorConfig};
+use crate::db::entities::knowledge_base::VectorBackend;
+use crate::db::entities::KnowledgeBase;
+use crate::errors::AiStudioError;
+use crate::services::deadline;
+use crate::services::egress;
+
+/// 一条向量，ID 与 embeddings 表中的嵌入记录一致
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorPoint {
+    /// 嵌入 ID
+    pub id: Uuid,
+    /// 文档块 ID
+    pub chunk_id: Uuid,
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 向量
+    pub vector: Vec<f32>,
+}
+
+/// 相似度检索命中
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorMatch {
+    /// 嵌入 ID
+    pub id: Uuid,
+    /// 文档块 ID
+    pub chunk_id: Uuid,
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 余弦相似度
+    pub similarity: f32,
+}
+
+/// 向量存储后端
+///
+/// 嵌入记录（来源文本、模型、状态）始终保存在 embeddings 表，后端只负责向量本身。
+#[async_trait]
+pub trait VectorStore: Send + Sync {
+    /// 后端类型
+    fn backend(&self) -> VectorBackend;
+
+    /// 写入或覆盖向量
+    async fn upsert(&self, knowledge_base_id: Uuid, points: &[VectorPoint]) -> Result<(), AiStudioError>;
+
+    /// 按嵌入 ID 读取向量，不存在的 ID 不返回
+    async fn fetch(&self, knowledge_base_id: Uuid, ids: &[Uuid]) -> Result<Vec<VectorPoint>, AiStudioError>;
+
+    /// 余弦相似度检索，可限定文档范围，结果按相似度降序
+    async fn search(
+        &self,
+        knowledge_base_id: Uuid,
+        query: &[f32],
+        limit: usize,
+        document_ids: Option<&[Uuid]>,
+    ) -> Result<Vec<VectorMatch>, AiStudioError>;
+
+    /// 按嵌入 ID 删除向量，不存在的 ID 忽略
+    async fn delete(&self, knowledge_base_id: Uuid, ids: &[Uuid]) -> Result<(), AiStudioError>;
+
+    /// 删除指定文档的向量
+    async fn delete_documents(&self, knowledge_base_id: Uuid, document_ids: &[Uuid]) -> Result<(), AiStudioError>;
+
+    /// 删除知识库的全部向量
+    async fn drop_knowledge_base(&self, knowledge_base_id: Uuid) -> Result<(), AiStudioError>;
+
+    /// 知识库的向量数量
+    async fn count(&self, knowledge_base_id: Uuid) -> Result<u64, AiStudioError>;
+}
+
+/// pgvector 后端，向量保存在 embeddings 表的 vector 列
+pub struct PgVectorStore {
+    db: DatabaseConnection,
+}
+
+impl PgVectorStore {
+    /// 创建 pgvector 后端
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl VectorStore for PgVectorStore {
+    fn backend(&self) -> VectorBackend {
+        VectorBackend::Pgvector
+    }
+
+    async fn upsert(&self, knowledge_base_id: Uuid, points: &[VectorPoint]) -> Result<(), AiStudioError> {
+        for point in points {
+            self.db
+                .execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    "UPDATE embeddings SET vector = $3::vector, dimension = $4, updated_at = NOW() \
+                     WHERE id = $1 AND knowledge_base_id = $2",
+                    [
+                        point.id.into(),
+                        knowledge_base_id.into(),
+                        format_vector(&point.vector).into(),
+                        (point.vector.len() as i32).into(),
+                    ],
+                ))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn fetch(&self, knowledge_base_id: Uuid, ids: &[Uuid]) -> Result<Vec<VectorPoint>, AiStudioError> {
+        let rows = self
+            .db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT id, chunk_id, document_id, vector::text AS vector FROM embeddings \
+                 WHERE knowledge_base_id = $1 AND $2::jsonb ? id::text AND vector IS NOT NULL",
+                [knowledge_base_id.into(), json!(ids).into()],
+            ))
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let vector: String = row.try_get("", "vector")?;
+                Ok(VectorPoint {
+                    id: row.try_get("", "id")?,
+                    chunk_id: row.try_get("", "chunk_id")?,
+                    document_id: row.try_get("", "document_id")?,
+                    vector: parse_vector(&vector)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn search(
+        &self,
+        knowledge_base_id: Uuid,
+        query: &[f32],
+        limit: usize,
+        document_ids: Option<&[Uuid]>,
+    ) -> Result<Vec<VectorMatch>, AiStudioError> {
+        let rows = deadline::query_all(
+            &self.db,
+            "vector_store.pgvector.search",
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT id, chunk_id, document_id, (1 - (vector <=> $1::vector))::REAL AS similarity \
+                 FROM embeddings \
+                 WHERE knowledge_base_id = $2 AND status = 'completed' AND vector IS NOT NULL \
+                     AND ($3::jsonb IS NULL OR $3::jsonb ? document_id::text) \
+                 ORDER BY vector <=> $1::vector \
+                 LIMIT $4",
+                [
+                    format_vector(query).into(),
+                    knowledge_base_id.into(),
+                    document_ids.map(|ids| json!(ids)).into(),
+                    (limit as i64).into(),
+                ],
+            ),
+        )
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(VectorMatch {
+                    id: row.try_get("", "id")?,
+                    chunk_id: row.try_get("", "chunk_id")?,
+                    document_id: row.try_get("", "document_id")?,
+                    similarity: row.try_get("", "similarity")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn delete(&self, knowledge_base_id: Uuid, ids: &[Uuid]) -> Result<(), AiStudioError> {
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE embeddings SET vector = NULL, updated_at = NOW() \
+                 WHERE knowledge_base_id = $1 AND $2::jsonb ? id::text",
+                [knowledge_base_id.into(), json!(ids).into()],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_documents(&self, knowledge_base_id: Uuid, document_ids: &[Uuid]) -> Result<(), AiStudioError> {
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE embeddings SET vector = NULL, updated_at = NOW() \
+                 WHERE knowledge_base_id = $1 AND $2::jsonb ? document_id::text",
+                [knowledge_base_id.into(), json!(document_ids).into()],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn drop_knowledge_base(&self, knowledge_base_id: Uuid) -> Result<(), AiStudioError> {
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE embeddings SET vector = NULL, updated_at = NOW() \
+                 WHERE knowledge_base_id = $1 AND vector IS NOT NULL",
+                [knowledge_base_id.into()],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn count(&self, knowledge_base_id: Uuid) -> Result<u64, AiStudioError> {
+        let row = self
+            .db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT COUNT(*) AS count FROM embeddings WHERE knowledge_base_id = $1 AND vector IS NOT NULL",
+                [knowledge_base_id.into()],
+            ))
+            .await?;
+        Ok(row.map(|row| row.try_get::<i64>("", "count")).transpose()?.unwrap_or(0).max(0) as u64)
+    }
+}
+
+/// 外部向量数据库的 HTTP 客户端
+struct HttpVectorClient {
+    name: &'static str,
+    http: reqwest::Client,
+    base_url: String,
+    auth_header: Option<(&'static str, String)>,
+    collection_prefix: String,
+}
+
+impl HttpVectorClient {
+    fn new(
+        name: &'static str,
+        config: &ExternalVectorStoreConfig,
+        auth_header: impl Fn(&str) -> (&'static str, String),
+    ) -> Result<Self, AiStudioError> {
+        let http = egress::client_builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| AiStudioError::configuration(format!("创建 {} 客户端失败: {}", name, e)))?;
+        Ok(Self {
+            name,
+            http,
+            base_url: config.url.trim_end_matches('/').to_string(),
+            auth_header: config.api_key.as_deref().filter(|key| !key.is_empty()).map(auth_header),
+            collection_prefix: config.collection_prefix.clone(),
+        })
+    }
+
+    /// 知识库对应的集合名
+    fn collection(&self, knowledge_base_id: Uuid) -> String {
+        format!("{}{}", self.collection_prefix, knowledge_base_id.simple())
+    }
+
+    /// 发送请求，返回响应 JSON 与状态码；HTTP 错误状态交由调用方判断
+    ///
+    /// 请求不超过当前请求截止时间的剩余预算。
+    async fn send(&self, method: reqwest::Method, path: &str, body: Option<Value>) -> Result<(u16, Value), AiStudioError> {
+        let mut request = self.http.request(method, format!("{}{}", self.base_url, path));
+        if let Some((header, value)) = &self.auth_header {
+            request = request.header(*header, value);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        deadline::run(self.name, async {
+            let response = request
+                .send()
+                .await
+                .map_err(|e| AiStudioError::external_service(self.name, e.to_string()))?;
+            let status = response.status().as_u16();
+            let body = response.json::<Value>().await.unwrap_or(Value::Null);
+            Ok((status, body))
+        })
+        .await
+    }
+
+    fn error(&self, action: &str, status: u16, body: &Value) -> AiStudioError {
+        AiStudioError::external_service(self.name, format!("{}失败（HTTP {}）: {}", action, status, body))
+    }
+}
+
+/// Qdrant 后端，每个知识库一个集合，点 ID 为嵌入 ID
+pub struct QdrantVectorStore {
+    client: HttpVectorClient,
+    /// 已确认存在的集合
+    collections: RwLock<HashMap<Uuid, String>>,
+}
+
+impl QdrantVectorStore {
+    /// 创建 Qdrant 后端
+    pub fn new(config: &ExternalVectorStoreConfig) -> Result<Self, AiStudioError> {
+        Ok(Self {
+            client: HttpVectorClient::new("qdrant", config, |key| ("api-key", key.to_string()))?,
+            collections: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// 确保集合存在，不存在时按向量维度创建
+    async fn ensure_collection(&self, knowledge_base_id: Uuid, dimension: usize) -> Result<String, AiStudioError> {
+        if let Some(name) = self.collections.read().await.get(&knowledge_base_id) {
+            return Ok(name.clone());
+        }
+
+        let name = self.client.collection(knowledge_base_id);
+        let (status, _) = self.client.send(reqwest::Method::GET, &format!("/collections/{}", name), None).await?;
+        if status == 404 {
+            let (status, body) = self
+                .client
+                .send(
+                    reqwest::Method::PUT,
+                    &format!("/collections/{}", name),
+                    Some(json!({ "vectors": { "size": dimension, "distance": "Cosine" } })),
+                )
+                .await?;
+            if !(200..300).contains(&status) && status != 409 {
+                return Err(self.client.error("创建集合", status, &body));
+            }
+            info!("已创建 Qdrant 集合: {}", name);
+            self.client
+                .send(
+                    reqwest::Method::PUT,
+                    &format!("/collections/{}/index", name),
+                    Some(json!({ "field_name": "document_id", "field_schema": "keyword" })),
+                )
+                .await?;
+        } else if !(200..300).contains(&status) {
+            return Err(self.client.error("查询集合", status, &Value::Null));
+        }
+
+        self.collections.write().await.insert(knowledge_base_id, name.clone());
+        Ok(name)
+    }
+
+    /// 发送集合级请求，集合不存在时返回空
+    async fn collection_request(
+        &self,
+        knowledge_base_id: Uuid,
+        method: reqwest::Method,
+        suffix: &str,
+        body: Value,
+        action: &str,
+    ) -> Result<Option<Value>, AiStudioError> {
+        let name = self.client.collection(knowledge_base_id);
+        let (status, body) = self.client.send(method, &format!("/collections/{}{}", name, suffix), Some(body)).await?;
+        match status {
+            200..=299 => Ok(Some(body.get("result").cloned().unwrap_or(Value::Null))),
+            404 => Ok(None),
+            _ => Err(self.client.error(action, status, &body)),
+        }
+    }
+}
+
+#[async_trait]
+impl VectorStore for QdrantVectorStore {
+    fn backend(&self) -> VectorBackend {
+        VectorBackend::Qdrant
+    }
+
+    async fn upsert(&self, knowledge_base_id: Uuid, points: &[VectorPoint]) -> Result<(), AiStudioError> {
+        let Some(first) = points.first() else {
+            return Ok(());
+        };
+        let name = self.ensure_collection(knowledge_base_id, first.vector.len()).await?;
+        let points: Vec<Value> = points
+            .iter()
+            .map(|point| {
+                json!({
+                    "id": point.id,
+                    "vector": point.vector,
+                    "payload": { "chunk_id": point.chunk_id, "document_id": point.document_id },
+                })
+            })
+            .collect();
+        let (status, body) = self
+            .client
+            .send(
+                reqwest::Method::PUT,
+                &format!("/collections/{}/points?wait=true", name),
+                Some(json!({ "points": points })),
+            )
+            .await?;
+        if !(200..300).contains(&status) {
+            return Err(self.client.error("写入向量", status, &body));
+        }
+        Ok(())
+    }
+
+    async fn fetch(&self, knowledge_base_id: Uuid, ids: &[Uuid]) -> Result<Vec<VectorPoint>, AiStudioError> {
+        let result = self
+            .collection_request(
+                knowledge_base_id,
+                reqwest::Method::POST,
+                "/points",
+                json!({ "ids": ids, "with_payload": true, "with_vector": true }),
+                "读取向量",
+            )
+            .await?;
+        let Some(Value::Array(points)) = result else {
+            return Ok(Vec::new());
+        };
+
+        points
+            .iter()
+            .map(|point| {
+                let vector = serde_json::from_value(point.get("vector").cloned().unwrap_or(Value::Null))?;
+                Ok(VectorPoint {
+                    id: uuid_field(point, "id")?,
+                    chunk_id: uuid_field(&point["payload"], "chunk_id")?,
+                    document_id: uuid_field(&point["payload"], "document_id")?,
+                    vector,
+                })
+            })
+            .collect()
+    }
+
+    async fn search(
+        &self,
+        knowledge_base_id: Uuid,
+        query: &[f32],
+        limit: usize,
+        document_ids: Option<&[Uuid]>,
+    ) -> Result<Vec<VectorMatch>, AiStudioError> {
+        let mut body = json!({ "vector": query, "limit": limit, "with_payload": true });
+        if let Some(document_ids) = document_ids {
+            body["filter"] = json!({ "must": [{ "key": "document_id", "match": { "any": document_ids } }] });
+        }
+        let result = self
+            .collection_request(knowledge_base_id, reqwest::Method::POST, "/points/search", body, "向量检索")
+            .await?;
+        let Some(Value::Array(hits)) = result else {
+            return Ok(Vec::new());
+        };
+
+        hits.iter()
+            .map(|hit| {
+                Ok(VectorMatch {
+                    id: uuid_field(hit, "id")?,
+                    chunk_id: uuid_field(&hit["payload"], "chunk_id")?,
+                    document_id: uuid_field(&hit["payload"], "document_id")?,
+                    similarity: hit.get("score").and_then(Value::as_f64).unwrap_or(0.0) as f32,
+                })
+            })
+            .collect()
+    }
+
+    async fn delete(&self, knowledge_base_id: Uuid, ids: &[Uuid]) -> Result<(), AiStudioError> {
+        self.collection_request(
+            knowledge_base_id,
+            reqwest::Method::POST,
+            "/points/delete?wait=true",
+            json!({ "points": ids }),
+            "删除向量",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_documents(&self, knowledge_base_id: Uuid, document_ids: &[Uuid]) -> Result<(), AiStudioError> {
+        self.collection_request(
+            knowledge_base_id,
+            reqwest::Method::POST,
+            "/points/delete?wait=true",
+            json!({ "filter": { "must": [{ "key": "document_id", "match": { "any": document_ids } }] } }),
+            "删除向量",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn drop_knowledge_base(&self, knowledge_base_id: Uuid) -> Result<(), AiStudioError> {
+        let name = self.client.collection(knowledge_base_id);
+        let (status, body) = self.client.send(reqwest::Method::DELETE, &format!("/collections/{}", name), None).await?;
+        if !(200..300).contains(&status) && status != 404 {
+            return Err(self.client.error("删除集合", status, &body));
+        }
+        self.collections.write().await.remove(&knowledge_base_id);
+        Ok(())
+    }
+
+    async fn count(&self, knowledge_base_id: Uuid) -> Result<u64, AiStudioError> {
+        let result = self
+            .collection_request(
+                knowledge_base_id,
+                reqwest::Method::POST,
+                "/points/count",
+                json!({ "exact": true }),
+                "统计向量",
+            )
+            .await?;
+        Ok(result.and_then(|result| result.get("count").and_then(Value::as_u64)).unwrap_or(0))
+    }
+}
+
+/// Milvus 后端（RESTful API v2），每个知识库一个集合，主键为嵌入 ID
+pub struct MilvusVectorStore {
+    client: HttpVectorClient,
+    /// 已确认存在的集合
+    collections: RwLock<HashMap<Uuid, String>>,
+}
+
+impl MilvusVectorStore {
+    /// 创建 Milvus 后端
+    pub fn new(config: &ExternalVectorStoreConfig) -> Result<Self, AiStudioError> {
+        Ok(Self {
+            client: HttpVectorClient::new("milvus", config, |token| ("Authorization", format!("Bearer {}", token)))?,
+            collections: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// 调用 Milvus 接口，响应的 code 非 0 时返回错误
+    async fn call(&self, path: &str, body: Value, action: &str) -> Result<Value, AiStudioError> {
+        let (status, body) = self.client.send(reqwest::Method::POST, path, Some(body)).await?;
+        let code = body.get("code").and_then(Value::as_i64).unwrap_or(-1);
+        if !(200..300).contains(&status) || code != 0 {
+            return Err(self.client.error(action, status, &body));
+        }
+        Ok(body.get("data").cloned().unwrap_or(Value::Null))
+    }
+
+    /// 集合是否存在
+    async fn has_collection(&self, knowledge_base_id: Uuid) -> Result<Option<String>, AiStudioError> {
+        if let Some(name) = self.collections.read().await.get(&knowledge_base_id) {
+            return Ok(Some(name.clone()));
+        }
+        let name = self.client.collection(knowledge_base_id);
+        let data = self
+            .call("/v2/vectordb/collections/has", json!({ "collectionName": name }), "查询集合")
+            .await?;
+        if data.get("has").and_then(Value::as_bool).unwrap_or(false) {
+            self.collections.write().await.insert(knowledge_base_id, name.clone());
+            Ok(Some(name))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// 确保集合存在，不存在时按向量维度创建
+    async fn ensure_collection(&self, knowledge_base_id: Uuid, dimension: usize) -> Result<String, AiStudioError> {
+        if let Some(name) = self.has_collection(knowledge_base_id).await? {
+            return Ok(name);
+        }
+
+        let name = self.client.collection(knowledge_base_id);
+        self.call(
+            "/v2/vectordb/collections/create",
+            json!({
+                "collectionName": name,
+                "dimension": dimension,
+                "metricType": "COSINE",
+                "idType": "VarChar",
+                "autoID": false,
+                "primaryFieldName": "id",
+                "vectorFieldName": "vector",
+                "params": { "max_length": 36, "enableDynamicField": true },
+            }),
+            "创建集合",
+        )
+        .await?;
+        info!("已创建 Milvus 集合: {}", name);
+
+        self.collections.write().await.insert(knowledge_base_id, name.clone());
+        Ok(name)
+    }
+}
+
+#[async_trait]
+impl VectorStore for MilvusVectorStore {
+    fn backend(&self) -> VectorBackend {
+        VectorBackend::Milvus
+    }
+
+    async fn upsert(&self, knowledge_base_id: Uuid, points: &[VectorPoint]) -> Result<(), AiStudioError> {
+        let Some(first) = points.first() else {
+            return Ok(());
+        };
+        let name = self.ensure_collection(knowledge_base_id, first.vector.len()).await?;
+        let data: Vec<Value> = points
+            .iter()
+            .map(|point| {
+                json!({
+                    "id": point.id,
+                    "vector": point.vector,
+                    "chunk_id": point.chunk_id,
+                    "document_id": point.document_id,
+                })
+            })
+            .collect();
+        self.call("/v2/vectordb/entities/upsert", json!({ "collectionName": name, "data": data }), "写入向量")
+            .await?;
+        Ok(())
+    }
+
+    async fn fetch(&self, knowledge_base_id: Uuid, ids: &[Uuid]) -> Result<Vec<VectorPoint>, AiStudioError> {
+        let Some(name) = self.has_collection(knowledge_base_id).await? else {
+            return Ok(Vec::new());
+        };
+        let data = self
+            .call(
+                "/v2/vectordb/entities/get",
+                json!({
+                    "collectionName": name,
+                    "id": ids,
+                    "outputFields": ["id", "vector", "chunk_id", "document_id"],
+                }),
+                "读取向量",
+            )
+            .await?;
+        let Value::Array(rows) = data else {
+            return Ok(Vec::new());
+        };
+
+        rows.iter()
+            .map(|row| {
+                Ok(VectorPoint {
+                    id: uuid_field(row, "id")?,
+                    chunk_id: uuid_field(row, "chunk_id")?,
+                    document_id: uuid_field(row, "document_id")?,
+                    vector: serde_json::from_value(row.get("vector").cloned().unwrap_or(Value::Null))?,
+                })
+            })
+            .collect()
+    }
+
+    async fn search(
+        &self,
+        knowledge_base_id: Uuid,
+        query: &[f32],
+        limit: usize,
+        document_ids: Option<&[Uuid]>,
+    ) -> Result<Vec<VectorMatch>, AiStudioError> {
+        let Some(name) = self.has_collection(knowledge_base_id).await? else {
+            return Ok(Vec::new());
+        };
+        let mut body = json!({
+            "collectionName": name,
+            "data": [query],
+            "annsField": "vector",
+            "limit": limit,
+            "outputFields": ["chunk_id", "document_id"],
+        });
+        if let Some(document_ids) = document_ids {
+            body["filter"] = Value::String(milvus_in_filter("document_id", document_ids));
+        }
+        let data = self.call("/v2/vectordb/entities/search", body, "向量检索").await?;
+        let Value::Array(hits) = data else {
+            return Ok(Vec::new());
+        };
+
+        // COSINE 度量下 distance 即余弦相似度
+        hits.iter()
+            .map(|hit| {
+                Ok(VectorMatch {
+                    id: uuid_field(hit, "id")?,
+                    chunk_id: uuid_field(hit, "chunk_id")?,
+                    document_id: uuid_field(hit, "document_id")?,
+                    similarity: hit.get("distance").and_then(Value::as_f64).unwrap_or(0.0) as f32,
+                })
+            })
+            .collect()
+    }
+
+    async fn delete(&self, knowledge_base_id: Uuid, ids: &[Uuid]) -> Result<(), AiStudioError> {
+        let Some(name) = self.has_collection(knowledge_base_id).await? else {
+            return Ok(());
+        };
+        self.call(
+            "/v2/vectordb/entities/delete",
+            json!({ "collectionName": name, "filter": milvus_in_filter("id", ids) }),
+            "删除向量",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_documents(&self, knowledge_base_id: Uuid, document_ids: &[Uuid]) -> Result<(), AiStudioError> {
+        let Some(name) = self.has_collection(knowledge_base_id).await? else {
+            return Ok(());
+        };
+        self.call(
+            "/v2/vectordb/entities/delete",
+            json!({ "collectionName": name, "filter": milvus_in_filter("document_id", document_ids) }),
+            "删除向量",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn drop_knowledge_base(&self, knowledge_base_id: Uuid) -> Result<(), AiStudioError> {
+        let Some(name) = self.has_collection(knowledge_base_id).await? else {
+            return Ok(());
+        };
+        self.call("/v2/vectordb/collections/drop", json!({ "collectionName": name }), "删除集合")
+            .await?;
+        self.collections.write().await.remove(&knowledge_base_id);
+        Ok(())
+    }
+
+    async fn count(&self, knowledge_base_id: Uuid) -> Result<u64, AiStudioError> {
+        let Some(name) = self.has_collection(knowledge_base_id).await? else {
+            return Ok(0);
+        };
+        let data = self
+            .call(
+                "/v2/vectordb/entities/query",
+                json!({ "collectionName": name, "filter": "", "outputFields": ["count(*)"] }),
+                "统计向量",
+            )
+            .await?;
+        Ok(data.get(0).and_then(|row| row.get("count(*)")).and_then(Value::as_u64).unwrap_or(0))
+    }
+}
+
+/// 向量存储注册表，按后端缓存实例并按知识库配置选择后端
+pub struct VectorStoreRegistry {
+    db: DatabaseConnection,
+    config: VectorConfig,
+    stores: RwLock<HashMap<VectorBackend, Arc<dyn VectorStore>>>,
+}
+
+impl VectorStoreRegistry {
+    /// 创建注册表
+    pub fn new(db: DatabaseConnection, config: VectorConfig) -> Self {
+        Self { db, config, stores: RwLock::new(HashMap::new()) }
+    }
+
+    /// 后端是否已配置，pgvector 始终可用
+    pub fn is_configured(&self, backend: VectorBackend) -> bool {
+        match backend {
+            VectorBackend::Pgvector => true,
+            VectorBackend::Qdrant => self.config.qdrant.is_some(),
+            VectorBackend::Milvus => self.config.milvus.is_some(),
+        }
+    }
+
+    /// 获取指定后端
+    pub async fn store(&self, backend: VectorBackend) -> Result<Arc<dyn VectorStore>, AiStudioError> {
+        if let Some(store) = self.stores.read().await.get(&backend) {
+            return Ok(store.clone());
+        }
+
+        let not_configured =
+            || AiStudioError::configuration(format!("未配置 {} 向量存储（vector.{}）", backend.as_str(), backend.as_str()));
+        let store: Arc<dyn VectorStore> = match backend {
+            VectorBackend::Pgvector => Arc::new(PgVectorStore::new(self.db.clone())),
+            VectorBackend::Qdrant => Arc::new(QdrantVectorStore::new(self.config.qdrant.as_ref().ok_or_else(not_configured)?)?),
+            VectorBackend::Milvus => Arc::new(MilvusVectorStore::new(self.config.milvus.as_ref().ok_or_else(not_configured)?)?),
+        };
+        debug!("初始化向量存储后端: {}", backend.as_str());
+        self.stores.write().await.insert(backend, store.clone());
+        Ok(store)
+    }
+
+    /// 知识库配置的后端
+    pub async fn backend_of(&self, knowledge_base_id: Uuid) -> Result<VectorBackend, AiStudioError> {
+        let knowledge_base = KnowledgeBase::find_by_id(knowledge_base_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("知识库"))?;
+        Ok(knowledge_base.get_config().map(|config| config.vector_backend).unwrap_or_default())
+    }
+
+    /// 知识库使用的向量存储
+    pub async fn for_knowledge_base(&self, knowledge_base_id: Uuid) -> Result<Arc<dyn VectorStore>, AiStudioError> {
+        self.store(self.backend_of(knowledge_base_id).await?).await
+    }
+}
+
+/// 生成 Milvus 的 `in` 过滤表达式
+fn milvus_in_filter(field: &str, ids: &[Uuid]) -> String {
+    let values: Vec<String> = ids.iter().map(|id| format!("\"{}\"", id)).collect();
+    format!("{} in [{}]", field, values.join(", "))
+}
+
+fn uuid_field(value: &Value, field: &str) -> Result<Uuid, AiStudioError> {
+    value
+        .get(field)
+        .and_then(Value::as_str)
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| AiStudioError::internal(format!("向量存储返回的 {} 无效", field)))
+}
+
+/// 转换为 pgvector 文本格式
+pub fn format_vector(vector: &[f32]) -> String {
+    format!("[{}]", vector.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))
+}
+
+/// 解析 pgvector 文本格式
+pub fn parse_vector(text: &str) -> Result<Vec<f32>, AiStudioError> {
+    text.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| {
+            value
+                .trim()
+                .parse::<f32>()
+                .map_err(|_| AiStudioError::internal(format!("无效的向量分量: {}", value)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector_text_round_trip() {
+        let vector = vec![0.25, -1.0, 3.5];
+        assert_eq!(parse_vector(&format_vector(&vector)).unwrap(), vector);
+        assert_eq!(parse_vector("[]").unwrap(), Vec::<f32>::new());
+        assert!(parse_vector("[1,abc]").is_err());
+    }
+
+    #[test]
+    fn test_milvus_in_filter() {
+        let id = Uuid::nil();
+        assert_eq!(
+            milvus_in_filter("document_id", &[id]),
+            "document_id in [\"00000000-0000-0000-0000-000000000000\"]"
+        );
+    }
+}
@@ -0,0 +1,223 @@
+// 工作流并发组
+// 基于分布式租约限制同一并发组在集群内同时运行的执行或步骤数量
+
+use std::collections::HashMap;
+use std::time::Duration;
+use serde_json::Value;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::ai::workflow_engine::{ConcurrencyGroup, ConcurrencyPolicy};
+use crate::ai::workflow_knowledge_search::render_query;
+use crate::db::distributed_lock::{holder_id_for, DistributedLockService};
+use crate::errors::AiStudioError;
+
+/// 排队等待并发名额的默认最长时间（秒）
+pub const DEFAULT_QUEUE_TIMEOUT_SECONDS: u64 = 600;
+
+/// 并发名额租约的有效期，持有期间由心跳任务续约，持有者崩溃后到期自动释放
+const SLOT_LEASE_TTL: Duration = Duration::from_secs(60);
+
+/// 排队时轮询空闲名额的间隔
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 已占用的并发名额
+///
+/// 名额以 `distributed_locks` 中的租约表示，后台任务定期续约。
+/// 显式调用 [`ConcurrencySlot::release`] 立即归还；直接 drop 时停止续约，租约到期后归还。
+pub struct ConcurrencySlot {
+    group_key: String,
+    owner: String,
+    lease_name: String,
+    holder_id: String,
+    locks: DistributedLockService,
+    heartbeat: JoinHandle<()>,
+}
+
+impl ConcurrencySlot {
+    /// 并发组键（已渲染占位符）
+    pub fn group_key(&self) -> &str {
+        &self.group_key
+    }
+
+    /// 名额持有者（执行 ID 或 `执行 ID:步骤 ID`）
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// 归还名额
+    pub async fn release(self) -> Result<(), AiStudioError> {
+        self.heartbeat.abort();
+        self.locks.release_lease_as(&self.lease_name, &self.holder_id).await?;
+        debug!(group = %self.group_key, owner = %self.owner, "并发名额已归还");
+        Ok(())
+    }
+}
+
+impl Drop for ConcurrencySlot {
+    fn drop(&mut self) {
+        self.heartbeat.abort();
+    }
+}
+
+impl std::fmt::Debug for ConcurrencySlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConcurrencySlot")
+            .field("group_key", &self.group_key)
+            .field("owner", &self.owner)
+            .field("lease_name", &self.lease_name)
+            .finish()
+    }
+}
+
+/// 并发组限流器
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimiter {
+    locks: DistributedLockService,
+}
+
+impl ConcurrencyLimiter {
+    /// 创建新的并发组限流器
+    pub fn new(locks: DistributedLockService) -> Self {
+        Self { locks }
+    }
+
+    /// 按执行参数渲染并发组名称，得到实际参与互斥的组键
+    pub fn group_key(group: &ConcurrencyGroup, parameters: &HashMap<String, Value>) -> Result<String, AiStudioError> {
+        let variables = HashMap::from([(
+            "parameters".to_string(),
+            Value::Object(parameters.clone().into_iter().collect()),
+        )]);
+        render_query(&group.name, &variables)
+            .map_err(|e| AiStudioError::validation("concurrency.name", format!("并发组名称渲染失败: {}", e)))
+    }
+
+    /// 排队等待的最长时间
+    pub fn queue_timeout(group: &ConcurrencyGroup) -> Duration {
+        Duration::from_secs(group.queue_timeout_seconds.unwrap_or(DEFAULT_QUEUE_TIMEOUT_SECONDS))
+    }
+
+    /// 尝试占用一个空闲名额，组内名额已满时返回 None
+    pub async fn try_acquire(
+        &self,
+        tenant_id: Uuid,
+        group_key: &str,
+        max_parallel: u32,
+        owner: &str,
+    ) -> Result<Option<ConcurrencySlot>, AiStudioError> {
+        let holder_id = holder_id_for(owner);
+        for slot in 0..max_parallel {
+            let lease_name = slot_lease_name(tenant_id, group_key, slot);
+            if self.locks.try_acquire_lease_as(&lease_name, &holder_id, SLOT_LEASE_TTL).await? {
+                debug!(group = %group_key, owner, slot, "占用并发名额");
+                let heartbeat = spawn_heartbeat(self.locks.clone(), lease_name.clone(), holder_id.clone());
+                return Ok(Some(ConcurrencySlot {
+                    group_key: group_key.to_string(),
+                    owner: owner.to_string(),
+                    lease_name,
+                    holder_id,
+                    locks: self.locks.clone(),
+                    heartbeat,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 按并发组策略占用名额：`Reject` 名额已满时立即返回冲突错误，`Queue` 轮询等待直至超时
+    pub async fn acquire(
+        &self,
+        tenant_id: Uuid,
+        group: &ConcurrencyGroup,
+        parameters: &HashMap<String, Value>,
+        owner: &str,
+    ) -> Result<ConcurrencySlot, AiStudioError> {
+        let group_key = Self::group_key(group, parameters)?;
+        let deadline = tokio::time::Instant::now() + Self::queue_timeout(group);
+
+        loop {
+            if let Some(slot) = self.try_acquire(tenant_id, &group_key, group.max_parallel, owner).await? {
+                return Ok(slot);
+            }
+            if group.policy == ConcurrencyPolicy::Reject {
+                return Err(group_full_error(&group_key, group.max_parallel));
+            }
+            if tokio::time::Instant::now() + QUEUE_POLL_INTERVAL > deadline {
+                return Err(AiStudioError::timeout(format!("等待并发组 {} 的空闲名额", group_key)));
+            }
+            tokio::time::sleep(QUEUE_POLL_INTERVAL).await;
+        }
+    }
+
+    /// 排队轮询间隔
+    pub fn poll_interval() -> Duration {
+        QUEUE_POLL_INTERVAL
+    }
+}
+
+/// 并发组名额已满的冲突错误
+pub fn group_full_error(group_key: &str, max_parallel: u32) -> AiStudioError {
+    AiStudioError::conflict(format!(
+        "并发组 {} 已有 {} 个执行在运行，请稍后重试",
+        group_key, max_parallel
+    ))
+}
+
+/// 名额租约名称，按租户隔离
+fn slot_lease_name(tenant_id: Uuid, group_key: &str, slot: u32) -> String {
+    format!("workflow-concurrency:{}:{}:{}", tenant_id, group_key, slot)
+}
+
+/// 启动租约续约任务，每三分之一有效期续约一次
+fn spawn_heartbeat(locks: DistributedLockService, lease_name: String, holder_id: String) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SLOT_LEASE_TTL / 3);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            match locks.try_acquire_lease_as(&lease_name, &holder_id, SLOT_LEASE_TTL).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    warn!(lease = %lease_name, "并发名额租约已被接管，停止续约");
+                    return;
+                }
+                Err(e) => warn!(lease = %lease_name, error = %e, "并发名额租约续约失败"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(name: &str) -> ConcurrencyGroup {
+        ConcurrencyGroup {
+            name: name.to_string(),
+            max_parallel: 1,
+            policy: ConcurrencyPolicy::Queue,
+            queue_timeout_seconds: None,
+        }
+    }
+
+    #[test]
+    fn test_group_key_renders_parameters() {
+        let parameters = HashMap::from([("connector_id".to_string(), serde_json::json!("crm-01"))]);
+        let key = ConcurrencyLimiter::group_key(&group("connector-sync:{{parameters.connector_id}}"), &parameters).unwrap();
+        assert_eq!(key, "connector-sync:crm-01");
+
+        assert!(ConcurrencyLimiter::group_key(&group("sync:{{parameters.missing}}"), &parameters).is_err());
+    }
+
+    #[test]
+    fn test_slot_lease_name_is_tenant_scoped() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_ne!(slot_lease_name(a, "sync", 0), slot_lease_name(b, "sync", 0));
+        assert_ne!(slot_lease_name(a, "sync", 0), slot_lease_name(a, "sync", 1));
+        assert_eq!(
+            ConcurrencyLimiter::queue_timeout(&group("sync")),
+            Duration::from_secs(DEFAULT_QUEUE_TIMEOUT_SECONDS)
+        );
+    }
+}
@@ -0,0 +1,615 @@
+// 工作流文档生成步骤
+// 用工作流上下文渲染 Handlebars 模板，按 Markdown 解析后导出为 Markdown/PDF/Word/HTML 文件，并签发限时下载链接
+
+use std::collections::HashMap;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Utc};
+use handlebars::Handlebars;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::ai::workflow_engine::{GeneratedDocumentFormat, StepConfig};
+use crate::errors::AiStudioError;
+
+/// 下载链接有效期（天），生成的文件在此之后会被清理
+pub const DOCUMENT_DOWNLOAD_TTL_DAYS: i64 = 7;
+
+/// 未指定文件名时使用的默认文件名
+const DEFAULT_FILE_NAME: &str = "document";
+
+/// 文件名最大长度（字符）
+const MAX_FILE_NAME_CHARS: usize = 100;
+
+/// PDF 页面尺寸（A4，单位 pt）
+const PDF_PAGE_WIDTH: f32 = 595.0;
+const PDF_PAGE_HEIGHT: f32 = 842.0;
+const PDF_MARGIN: f32 = 56.0;
+
+/// 文档生成步骤输出
+#[derive(Debug, Clone, Serialize)]
+pub struct GeneratedDocumentOutput {
+    /// 生成文档 ID
+    pub document_id: Uuid,
+    /// 下载时使用的文件名
+    pub file_name: String,
+    /// 输出格式
+    pub format: GeneratedDocumentFormat,
+    /// 内容类型
+    pub content_type: String,
+    /// 文件大小（字节）
+    pub size_bytes: usize,
+    /// 下载链接
+    pub download_url: String,
+    /// 下载链接过期时间
+    pub download_expires_at: DateTime<Utc>,
+}
+
+/// 生成文档下载链接签名声明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentDownloadClaims {
+    /// 生成文档 ID
+    pub sub: Uuid,
+    /// 租户 ID
+    pub tid: Uuid,
+    /// 输出格式
+    pub fmt: GeneratedDocumentFormat,
+    /// 下载文件名
+    pub name: String,
+    /// 过期时间（Unix 时间戳）
+    pub exp: i64,
+}
+
+/// 文档内容块
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocumentBlock {
+    /// 标题，级别 1-6
+    Heading(u8, String),
+    /// 段落
+    Paragraph(String),
+    /// 列表项
+    ListItem(String),
+}
+
+/// 文档生成步骤执行器
+pub struct DocumentGenerateStepRunner {
+    storage_root: PathBuf,
+    signing_secret: String,
+}
+
+impl DocumentGenerateStepRunner {
+    /// 创建执行器，生成的文件写入 `storage_root/exports/workflows/<租户 ID>/`，`signing_secret` 用于签发下载链接
+    pub fn new(storage_root: impl Into<PathBuf>, signing_secret: String) -> Self {
+        Self { storage_root: storage_root.into(), signing_secret }
+    }
+
+    /// 执行文档生成步骤
+    ///
+    /// `variables` 为执行上下文变量，模板中引用不存在的变量时步骤失败。
+    pub async fn run(
+        &self,
+        tenant_id: Uuid,
+        step_id: &str,
+        config: &StepConfig,
+        variables: &HashMap<String, Value>,
+    ) -> Result<GeneratedDocumentOutput, AiStudioError> {
+        let StepConfig::DocumentGenerate { template, format, file_name, title } = config else {
+            return Err(AiStudioError::validation("config", "步骤配置不是文档生成"));
+        };
+
+        let body = render_template(template, variables)?;
+        let title = title.as_deref()
+            .map(|title| render_template(title, variables))
+            .transpose()?
+            .filter(|title| !title.trim().is_empty());
+        let file_name = match file_name {
+            Some(file_name) => sanitize_file_name(&render_template(file_name, variables)?),
+            None => sanitize_file_name(step_id),
+        };
+
+        let content = match format {
+            GeneratedDocumentFormat::Markdown => match &title {
+                Some(title) => format!("# {}\n\n{}", title, body).into_bytes(),
+                None => body.into_bytes(),
+            },
+            GeneratedDocumentFormat::Pdf => render_pdf(title.as_deref(), &parse_markdown(&body)),
+            GeneratedDocumentFormat::Docx => render_docx(title.as_deref(), &parse_markdown(&body))?,
+            GeneratedDocumentFormat::Html => render_html(title.as_deref(), &parse_markdown(&body)).into_bytes(),
+        };
+
+        let document_id = Uuid::new_v4();
+        let path = generated_document_path(&self.storage_root, tenant_id, document_id, *format);
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await
+                .map_err(|e| AiStudioError::internal(format!("创建文档目录失败: {}", e)))?;
+            remove_expired_documents(dir).await;
+        }
+        tokio::fs::write(&path, &content).await
+            .map_err(|e| AiStudioError::internal(format!("写入生成文档失败: {}", e)))?;
+
+        let file_name = format!("{}.{}", file_name, format.extension());
+        let expires_at = Utc::now() + Duration::days(DOCUMENT_DOWNLOAD_TTL_DAYS);
+        let token = sign_document_token(&self.signing_secret, &DocumentDownloadClaims {
+            sub: document_id,
+            tid: tenant_id,
+            fmt: *format,
+            name: file_name.clone(),
+            exp: expires_at.timestamp(),
+        })?;
+
+        info!("工作流文档生成完成: document_id={}, step_id={}, format={:?}, size={}", document_id, step_id, format, content.len());
+        Ok(GeneratedDocumentOutput {
+            document_id,
+            file_name,
+            format: *format,
+            content_type: format.content_type().to_string(),
+            size_bytes: content.len(),
+            download_url: document_download_url(document_id, &token),
+            download_expires_at: expires_at,
+        })
+    }
+}
+
+/// 用上下文变量渲染 Handlebars 模板，引用不存在的变量时报错，输出不做 HTML 转义
+pub fn render_template(template: &str, variables: &HashMap<String, Value>) -> Result<String, AiStudioError> {
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(true);
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.render_template(template, variables)
+        .map_err(|e| AiStudioError::validation("template", format!("模板渲染失败: {}", e)))
+}
+
+/// 将 Markdown 文本解析为内容块，仅识别标题、列表与段落，行内的加粗与代码标记会被去除
+pub fn parse_markdown(text: &str) -> Vec<DocumentBlock> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<DocumentBlock>| {
+        if !paragraph.is_empty() {
+            blocks.push(DocumentBlock::Paragraph(strip_inline_marks(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    };
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(DocumentBlock::Heading(level as u8, strip_inline_marks(trimmed[level..].trim())));
+        } else if let Some(item) = list_item(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(DocumentBlock::ListItem(strip_inline_marks(item)));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+fn list_item(line: &str) -> Option<&str> {
+    if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some(item.trim());
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    (digits > 0).then(|| line[digits..].strip_prefix(". ")).flatten().map(str::trim)
+}
+
+fn strip_inline_marks(text: &str) -> String {
+    text.replace("**", "").replace('`', "")
+}
+
+/// 渲染 Word 文档
+pub fn render_docx(title: Option<&str>, blocks: &[DocumentBlock]) -> Result<Vec<u8>, AiStudioError> {
+    let mut body = String::new();
+    if let Some(title) = title {
+        body.push_str(&docx_paragraph(title, 36, true, "center"));
+    }
+    for block in blocks {
+        let paragraph = match block {
+            DocumentBlock::Heading(level, text) => docx_paragraph(text, heading_size(*level) as u32 * 2, true, "left"),
+            DocumentBlock::Paragraph(text) => docx_paragraph(text, 22, false, "both"),
+            DocumentBlock::ListItem(text) => docx_paragraph(&format!("· {}", text), 22, false, "left"),
+        };
+        body.push_str(&paragraph);
+    }
+
+    let document = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}<w:sectPr><w:pgSz w:w="11906" w:h="16838"/><w:pgMar w:top="1134" w:right="1134" w:bottom="1134" w:left="1134" w:header="709" w:footer="709" w:gutter="0"/></w:sectPr></w:body></w:document>"#,
+        body
+    );
+    let parts = [
+        ("[Content_Types].xml", DOCX_CONTENT_TYPES.to_string()),
+        ("_rels/.rels", DOCX_RELS.to_string()),
+        ("word/document.xml", document),
+    ];
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in parts {
+        zip.start_file(name, zip::write::FileOptions::default())
+            .and_then(|_| zip.write_all(content.as_bytes()).map_err(Into::into))
+            .map_err(|e| AiStudioError::internal(format!("生成 Word 文档失败: {}", e)))?;
+    }
+    let cursor = zip.finish()
+        .map_err(|e| AiStudioError::internal(format!("生成 Word 文档失败: {}", e)))?;
+    Ok(cursor.into_inner())
+}
+
+/// 渲染 HTML 页面，连续的列表项合并为一个列表
+pub fn render_html(title: Option<&str>, blocks: &[DocumentBlock]) -> String {
+    let mut body = String::new();
+    if let Some(title) = title {
+        body.push_str(&format!("<h1>{}</h1>\n", escape_xml(title)));
+    }
+    let mut in_list = false;
+    for block in blocks {
+        let is_item = matches!(block, DocumentBlock::ListItem(_));
+        if is_item && !in_list {
+            body.push_str("<ul>\n");
+        } else if !is_item && in_list {
+            body.push_str("</ul>\n");
+        }
+        in_list = is_item;
+        match block {
+            DocumentBlock::Heading(level, text) => {
+                // 文档标题占用 h1，正文标题依次下移一级
+                let level = (level + u8::from(title.is_some())).min(6);
+                body.push_str(&format!("<h{0}>{1}</h{0}>\n", level, escape_xml(text)));
+            }
+            DocumentBlock::Paragraph(text) => body.push_str(&format!("<p>{}</p>\n", escape_xml(text))),
+            DocumentBlock::ListItem(text) => body.push_str(&format!("<li>{}</li>\n", escape_xml(text))),
+        }
+    }
+    if in_list {
+        body.push_str("</ul>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>body{{font-family:sans-serif;max-width:800px;margin:2em auto;line-height:1.6;color:#222}}</style>\n\
+         </head>\n<body>\n{}</body>\n</html>\n",
+        escape_xml(title.unwrap_or(DEFAULT_FILE_NAME)),
+        body
+    )
+}
+
+const DOCX_CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#;
+
+const DOCX_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#;
+
+fn docx_paragraph(text: &str, half_points: u32, bold: bool, align: &str) -> String {
+    format!(
+        r#"<w:p><w:pPr><w:jc w:val="{}"/><w:spacing w:after="120"/></w:pPr><w:r><w:rPr>{}<w:sz w:val="{}"/></w:rPr><w:t xml:space="preserve">{}</w:t></w:r></w:p>"#,
+        align,
+        if bold { "<w:b/>" } else { "" },
+        half_points,
+        escape_xml(text)
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 标题字号（pt）
+fn heading_size(level: u8) -> f32 {
+    match level {
+        1 => 20.0,
+        2 => 16.0,
+        _ => 13.0,
+    }
+}
+
+/// 渲染 PDF 文档
+///
+/// 使用阅读器内置的 STSong-Light 字体，无需嵌入字体即可显示中英文；超出基本多文种平面的字符以 `?` 代替。
+pub fn render_pdf(title: Option<&str>, blocks: &[DocumentBlock]) -> Vec<u8> {
+    let mut lines: Vec<(String, f32, bool)> = Vec::new();
+    let mut push_block = |text: &str, size: f32, bold: bool| {
+        for line in wrap_text(text, size, PDF_PAGE_WIDTH - 2.0 * PDF_MARGIN) {
+            lines.push((line, size, bold));
+        }
+        lines.push((String::new(), size * 0.5, false));
+    };
+    if let Some(title) = title {
+        push_block(title, 22.0, true);
+    }
+    for block in blocks {
+        match block {
+            DocumentBlock::Heading(level, text) => push_block(text, heading_size(*level), true),
+            DocumentBlock::Paragraph(text) => push_block(text, 11.0, false),
+            DocumentBlock::ListItem(text) => push_block(&format!("· {}", text), 11.0, false),
+        }
+    }
+
+    // 按行高分页
+    let mut pages: Vec<String> = Vec::new();
+    let mut content = String::new();
+    let mut y = PDF_PAGE_HEIGHT - PDF_MARGIN;
+    for (text, size, bold) in lines {
+        let height = size * 1.5;
+        if y - height < PDF_MARGIN && !content.is_empty() {
+            pages.push(std::mem::take(&mut content));
+            y = PDF_PAGE_HEIGHT - PDF_MARGIN;
+        }
+        y -= height;
+        if !text.is_empty() {
+            let mode = if bold { "2 Tr 0.3 w" } else { "0 Tr" };
+            content.push_str(&format!(
+                "BT /F1 {} Tf {} {:.1} {:.1} Td <{}> Tj ET\n",
+                size, mode, PDF_MARGIN, y, encode_ucs2(&text)
+            ));
+        }
+    }
+    if !content.is_empty() || pages.is_empty() {
+        pages.push(content);
+    }
+
+    let mut objects: Vec<String> = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        String::new(),
+        "<< /Type /Font /Subtype /Type0 /BaseFont /STSong-Light /Encoding /UniGB-UCS2-H /DescendantFonts [4 0 R] >>".to_string(),
+        "<< /Type /Font /Subtype /CIDFontType0 /BaseFont /STSong-Light /CIDSystemInfo << /Registry (Adobe) /Ordering (GB1) /Supplement 2 >> /FontDescriptor 5 0 R /DW 1000 /W [1 95 500] >>".to_string(),
+        "<< /Type /FontDescriptor /FontName /STSong-Light /Flags 6 /FontBBox [-25 -254 1000 880] /ItalicAngle 0 /Ascent 880 /Descent -120 /CapHeight 880 /StemV 93 >>".to_string(),
+    ];
+    let mut kids = Vec::new();
+    for page in &pages {
+        let page_id = objects.len() + 1;
+        kids.push(format!("{} 0 R", page_id));
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PDF_PAGE_WIDTH, PDF_PAGE_HEIGHT, page_id + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}endstream", page.len(), page));
+    }
+    objects[1] = format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len());
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+    let xref_offset = pdf.len();
+    pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+    pdf.into_bytes()
+}
+
+/// 按字符宽度折行，ASCII 字符按半角计算，英文单词尽量不拆开
+fn wrap_text(text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let char_width = |c: char| if c.is_ascii() { size * 0.5 } else { size };
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut width = 0.0;
+
+    for c in text.chars() {
+        let w = char_width(c);
+        if width + w > max_width && !line.is_empty() {
+            let break_at = if c.is_ascii_alphanumeric() {
+                line.rfind(' ').filter(|i| *i > 0)
+            } else {
+                None
+            };
+            let carry = match break_at {
+                Some(i) => line.split_off(i + 1),
+                None => String::new(),
+            };
+            lines.push(line.trim_end().to_string());
+            width = carry.chars().map(char_width).sum();
+            line = carry;
+        }
+        line.push(c);
+        width += w;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// 编码为 UniGB-UCS2-H 使用的 UCS-2 十六进制串
+fn encode_ucs2(text: &str) -> String {
+    text.chars()
+        .map(|c| if (c as u32) > 0xFFFF { '?' } else { c })
+        .map(|c| format!("{:04X}", c as u32))
+        .collect()
+}
+
+/// 清理文件名，仅保留字母、数字、中文与 `-`、`_`、`.`
+pub fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name.trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .take(MAX_FILE_NAME_CHARS)
+        .collect();
+    let cleaned = cleaned.trim_matches(|c| c == '.' || c == '_').to_string();
+    if cleaned.is_empty() {
+        DEFAULT_FILE_NAME.to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// 生成文档的存储路径
+pub fn generated_document_path(
+    storage_root: &Path,
+    tenant_id: Uuid,
+    document_id: Uuid,
+    format: GeneratedDocumentFormat,
+) -> PathBuf {
+    storage_root
+        .join("exports")
+        .join("workflows")
+        .join(tenant_id.to_string())
+        .join(format!("{}.{}", document_id, format.extension()))
+}
+
+/// 下载链接
+pub fn document_download_url(document_id: Uuid, token: &str) -> String {
+    format!("/api/v1/downloads/workflow-documents/{}?token={}", document_id, token)
+}
+
+/// 签发下载令牌
+pub fn sign_document_token(secret: &str, claims: &DocumentDownloadClaims) -> Result<String, AiStudioError> {
+    encode(&Header::new(Algorithm::HS256), claims, &EncodingKey::from_secret(secret.as_bytes()))
+        .map_err(|e| AiStudioError::internal(format!("签发下载令牌失败: {}", e)))
+}
+
+/// 校验下载令牌，令牌必须与文档 ID 对应且未过期
+pub fn verify_document_token(secret: &str, document_id: Uuid, token: &str) -> Result<DocumentDownloadClaims, AiStudioError> {
+    let claims = decode::<DocumentDownloadClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::new(Algorithm::HS256),
+    )
+    .map_err(|_| AiStudioError::forbidden("下载链接无效或已过期"))?
+    .claims;
+
+    if claims.sub != document_id {
+        return Err(AiStudioError::forbidden("下载链接无效或已过期"));
+    }
+    Ok(claims)
+}
+
+/// 删除目录中超过下载有效期的生成文档
+async fn remove_expired_documents(dir: &Path) {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return;
+    };
+    let ttl = std::time::Duration::from_secs(DOCUMENT_DOWNLOAD_TTL_DAYS as u64 * 86400);
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let expired = entry.metadata().await
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > ttl);
+        if expired {
+            if let Err(e) = tokio::fs::remove_file(entry.path()).await {
+                warn!("清理过期生成文档失败: path={:?}, error={}", entry.path(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Read;
+
+    fn variables() -> HashMap<String, Value> {
+        HashMap::from([
+            ("parameters".to_string(), json!({ "customer": "Acme & Co" })),
+            ("search".to_string(), json!({ "chunks": [{ "content": "退款需在 7 天内申请" }] })),
+        ])
+    }
+
+    #[test]
+    fn test_render_template() {
+        let rendered = render_template(
+            "# {{parameters.customer}} 报告\n{{#each search.chunks}}- {{this.content}}\n{{/each}}",
+            &variables(),
+        ).unwrap();
+        assert_eq!(rendered, "# Acme & Co 报告\n- 退款需在 7 天内申请\n");
+        assert!(render_template("{{parameters.missing}}", &variables()).is_err());
+    }
+
+    #[test]
+    fn test_parse_markdown() {
+        let blocks = parse_markdown("# 概述\n第一行\n**第二行**\n\n- 要点一\n2. 要点二\n##不是标题");
+        assert_eq!(blocks, vec![
+            DocumentBlock::Heading(1, "概述".to_string()),
+            DocumentBlock::Paragraph("第一行 第二行".to_string()),
+            DocumentBlock::ListItem("要点一".to_string()),
+            DocumentBlock::ListItem("要点二".to_string()),
+            DocumentBlock::Paragraph("##不是标题".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_render_docx() {
+        let blocks = vec![DocumentBlock::Paragraph("A < B & 中文".to_string())];
+        let bytes = render_docx(Some("标题"), &blocks).unwrap();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut document = String::new();
+        archive.by_name("word/document.xml").unwrap().read_to_string(&mut document).unwrap();
+        assert!(document.contains("A &lt; B &amp; 中文"));
+        assert!(archive.by_name("[Content_Types].xml").is_ok());
+    }
+
+    #[test]
+    fn test_render_pdf_paginates() {
+        let blocks: Vec<DocumentBlock> = (0..80)
+            .map(|i| DocumentBlock::Paragraph(format!("第 {} 段", i)))
+            .collect();
+        let pdf = String::from_utf8(render_pdf(Some("报告"), &blocks)).unwrap();
+
+        assert!(pdf.starts_with("%PDF-1.4"));
+        assert!(pdf.contains("/Count 3"));
+        assert!(pdf.contains(&encode_ucs2("报告")));
+        assert!(pdf.trim_end().ends_with("%%EOF"));
+    }
+
+    #[test]
+    fn test_render_html_groups_list_items() {
+        let blocks = parse_markdown("## 概览\n\n- 问答 <12> 次\n- 文档 3 篇\n\n结束");
+        let html = render_html(Some("周报"), &blocks);
+
+        assert!(html.contains("<h1>周报</h1>"));
+        assert!(html.contains("<h3>概览</h3>"));
+        assert_eq!(html.matches("<ul>").count(), 1);
+        assert!(html.contains("<li>问答 &lt;12&gt; 次</li>"));
+        assert!(html.contains("</ul>\n<p>结束</p>"));
+    }
+
+    #[test]
+    fn test_wrap_text_keeps_words() {
+        let lines = wrap_text("hello world example", 10.0, 40.0);
+        assert_eq!(lines, vec!["hello", "world", "example"]);
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("月度 报告/2024"), "月度_报告_2024");
+        assert_eq!(sanitize_file_name("../"), "document");
+    }
+
+    #[test]
+    fn test_document_token_roundtrip() {
+        let document_id = Uuid::new_v4();
+        let claims = DocumentDownloadClaims {
+            sub: document_id,
+            tid: Uuid::new_v4(),
+            fmt: GeneratedDocumentFormat::Pdf,
+            name: "report.pdf".to_string(),
+            exp: (Utc::now() + Duration::hours(1)).timestamp(),
+        };
+        let token = sign_document_token("secret", &claims).unwrap();
+
+        assert_eq!(verify_document_token("secret", document_id, &token).unwrap().name, "report.pdf");
+        assert!(verify_document_token("other", document_id, &token).is_err());
+        assert!(verify_document_token("secret", Uuid::new_v4(), &token).is_err());
+    }
+}
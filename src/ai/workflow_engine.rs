@@ -9,7 +9,9 @@ use serde::{Deserialize, Serialize};
 use tracing::{info, warn, error, debug};
 use tokio::sync::RwLock;
 
+use crate::ai::workflow_secrets::{secret_references, step_type_allows_secrets};
 use crate::errors::AiStudioError;
+use crate::services::tenant_secret::is_valid_secret_name;
 
 /// 工作流引擎
 pub struct WorkflowEngine {
@@ -17,6 +19,8 @@ pub struct WorkflowEngine {
     workflows: Arc<RwLock<HashMap<Uuid, WorkflowDefinition>>>,
     /// 工作流模板
     templates: Arc<RwLock<HashMap<String, WorkflowTemplate>>>,
+    /// 工作流编辑租约
+    edit_leases: Arc<RwLock<HashMap<Uuid, WorkflowEditLease>>>,
     /// 引擎配置
     config: WorkflowEngineConfig,
 }
@@ -32,6 +36,10 @@ pub struct WorkflowEngineConfig {
     pub enable_cycle_detection: bool,
     /// 默认超时时间（秒）
     pub default_timeout_seconds: u64,
+    /// 编辑租约默认时长（秒）
+    pub edit_lease_seconds: u64,
+    /// 编辑租约最长时长（秒）
+    pub max_edit_lease_seconds: u64,
 }
 
 impl Default for WorkflowEngineConfig {
@@ -41,6 +49,8 @@ impl Default for WorkflowEngineConfig {
             max_dependency_depth: 50,
             enable_cycle_detection: true,
             default_timeout_seconds: 3600, // 1小时
+            edit_lease_seconds: 60,
+            max_edit_lease_seconds: 600,
         }
     }
 }
@@ -74,6 +84,84 @@ pub struct WorkflowDefinition {
     pub updated_at: DateTime<Utc>,
     /// 工作流状态
     pub status: WorkflowStatus,
+    /// 修订号，每次保存递增，用于乐观并发控制
+    #[serde(default)]
+    pub revision: u64,
+}
+
+impl WorkflowDefinition {
+    /// 当前修订对应的 ETag
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", self.revision)
+    }
+}
+
+/// 工作流编辑租约，用于提示“正在被他人编辑”
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct WorkflowEditLease {
+    /// 工作流 ID
+    pub workflow_id: Uuid,
+    /// 持有者用户 ID
+    pub holder_id: Uuid,
+    /// 持有者名称
+    pub holder_name: String,
+    /// 获取时间
+    pub acquired_at: DateTime<Utc>,
+    /// 过期时间
+    pub expires_at: DateTime<Utc>,
+}
+
+impl WorkflowEditLease {
+    /// 租约是否仍然有效
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at > now
+    }
+}
+
+/// 工作流编辑冲突信息
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct WorkflowConflict {
+    /// 工作流 ID
+    pub workflow_id: Uuid,
+    /// 当前修订号
+    pub current_revision: u64,
+    /// 当前 ETag
+    pub current_etag: String,
+    /// 最近更新时间
+    pub updated_at: DateTime<Utc>,
+    /// 其他用户持有的编辑租约
+    pub lease: Option<WorkflowEditLease>,
+}
+
+/// 工作流编辑错误
+#[derive(Debug)]
+pub enum WorkflowEditError {
+    /// 工作流已被他人修改，修订号不匹配
+    VersionMismatch(WorkflowConflict),
+    /// 工作流正被其他用户编辑
+    Locked(WorkflowConflict),
+    /// 其他错误
+    Other(AiStudioError),
+}
+
+impl From<AiStudioError> for WorkflowEditError {
+    fn from(error: AiStudioError) -> Self {
+        Self::Other(error)
+    }
+}
+
+/// 解析 If-Match 请求头，返回期望的修订号；`*` 表示不校验
+pub fn parse_if_match(value: &str) -> Result<Option<u64>, AiStudioError> {
+    let value = value.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+
+    let tag = value.strip_prefix("W/").unwrap_or(value);
+    tag.trim_matches('"')
+        .parse::<u64>()
+        .map(Some)
+        .map_err(|_| AiStudioError::validation("If-Match", format!("无效的 ETag: {}", value)))
 }
 
 /// 工作流步骤
@@ -99,6 +187,9 @@ pub struct WorkflowStep {
     pub timeout_seconds: Option<u64>,
     /// 步骤位置（用于可视化）
     pub position: Option<StepPosition>,
+    /// 步骤级并发组，限制跨执行同时运行该步骤的数量
+    #[serde(default)]
+    pub concurrency: Option<ConcurrencyGroup>,
 }
 
 /// 步骤类型
@@ -125,6 +216,12 @@ pub enum StepType {
     ApiCall,
     /// 子工作流
     SubWorkflow,
+    /// 知识库检索
+    KnowledgeSearch,
+    /// 文档生成
+    DocumentGenerate,
+    /// 等待外部回调
+    WaitForCallback,
 }
 
 /// 步骤配置
@@ -217,6 +314,145 @@ pub enum StepConfig {
         /// 参数映射
         parameter_mapping: HashMap<String, String>,
     },
+    /// 知识库检索配置
+    KnowledgeSearch {
+        /// 知识库 ID
+        knowledge_base_id: Uuid,
+        /// 查询内容，可用 `{{变量路径}}` 引用上下文变量与前序步骤输出
+        query: String,
+        /// 返回的文档块数量
+        #[serde(default = "default_knowledge_search_top_k")]
+        top_k: u32,
+        /// 检索方式
+        #[serde(default)]
+        mode: KnowledgeSearchMode,
+        /// 得分阈值，作用于融合后归一化到 0-1 的得分
+        #[serde(default)]
+        similarity_threshold: Option<f32>,
+        /// 混合检索的向量得分权重，默认 0.7
+        #[serde(default)]
+        vector_weight: Option<f32>,
+        /// 混合检索的关键词得分权重，默认 0.3
+        #[serde(default)]
+        keyword_weight: Option<f32>,
+        /// 混合检索的融合方式
+        #[serde(default)]
+        fusion: KnowledgeSearchFusion,
+        /// 过滤条件
+        #[serde(default)]
+        filters: KnowledgeSearchFilters,
+    },
+    /// 文档生成配置
+    DocumentGenerate {
+        /// Handlebars 模板，渲染结果按 Markdown 解析
+        template: String,
+        /// 输出格式
+        format: GeneratedDocumentFormat,
+        /// 文件名模板（不含扩展名），默认使用步骤 ID
+        #[serde(default)]
+        file_name: Option<String>,
+        /// 文档标题模板
+        #[serde(default)]
+        title: Option<String>,
+    },
+    /// 等待外部回调配置
+    WaitForCallback {
+        /// 等待回调的最长时间（秒）
+        timeout_seconds: u64,
+        /// 上下文变量名到回调载荷 JSON Pointer（如 `/data/status`）的映射，为空时整个载荷写入步骤输出
+        #[serde(default)]
+        payload_mapping: HashMap<String, String>,
+        /// 超时后的处理方式
+        #[serde(default)]
+        on_timeout: CallbackTimeoutAction,
+    },
+}
+
+fn default_knowledge_search_top_k() -> u32 { 5 }
+
+/// 知识库检索方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum KnowledgeSearchMode {
+    /// 向量相似度检索
+    #[default]
+    Vector,
+    /// 向量索引与稀疏词法索引（BM25）双路召回后融合
+    Hybrid,
+    /// 仅稀疏词法索引（BM25）检索，不需要嵌入服务
+    Sparse,
+}
+
+/// 混合检索的融合方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum KnowledgeSearchFusion {
+    /// 向量相似度与归一化 BM25 得分加权求和
+    #[default]
+    Weighted,
+    /// 倒数排名融合（RRF），按两路排名加权，不受得分尺度影响
+    Rrf,
+}
+
+/// 生成文档的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GeneratedDocumentFormat {
+    /// Markdown 文本
+    Markdown,
+    /// PDF 文档
+    Pdf,
+    /// Word 文档
+    Docx,
+    /// HTML 页面
+    Html,
+}
+
+impl GeneratedDocumentFormat {
+    /// 文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Pdf => "pdf",
+            Self::Docx => "docx",
+            Self::Html => "html",
+        }
+    }
+
+    /// 下载时的内容类型
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Pdf => "application/pdf",
+            Self::Docx => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+/// 等待回调超时后的处理方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CallbackTimeoutAction {
+    /// 步骤失败
+    #[default]
+    Fail,
+    /// 继续执行后续步骤，映射的上下文变量为空
+    Continue,
+}
+
+/// 知识库检索过滤条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KnowledgeSearchFilters {
+    /// 限定的文档 ID
+    #[serde(default)]
+    pub document_ids: Vec<Uuid>,
+    /// 限定的文档类型
+    #[serde(default)]
+    pub document_types: Vec<String>,
+    /// 文档块元数据需包含的键值
+    #[serde(default)]
+    pub metadata: HashMap<String, serde_json::Value>,
 }
 
 /// Agent 引用
@@ -357,6 +593,9 @@ pub struct WorkflowConfig {
     pub enable_logging: bool,
     /// 是否启用监控
     pub enable_monitoring: bool,
+    /// 工作流级并发组，限制集群内同时运行的执行数量
+    #[serde(default)]
+    pub concurrency: Option<ConcurrencyGroup>,
 }
 
 impl Default for WorkflowConfig {
@@ -367,10 +606,45 @@ impl Default for WorkflowConfig {
             error_handling: ErrorHandlingStrategy::StopOnError,
             enable_logging: true,
             enable_monitoring: true,
+            concurrency: None,
         }
     }
 }
 
+/// 并发组配置
+///
+/// 同名组（按租户隔离）在整个集群内最多同时运行 `max_parallel` 个，
+/// 例如 `connector-sync:{{parameters.connector_id}}` 可保证每个连接器同一时间只有一个同步在运行。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConcurrencyGroup {
+    /// 组名称，支持 `{{parameters.xxx}}` 占位符
+    pub name: String,
+    /// 组内最大并行数
+    #[serde(default = "default_concurrency_max_parallel")]
+    pub max_parallel: u32,
+    /// 达到上限时的处理策略
+    #[serde(default)]
+    pub policy: ConcurrencyPolicy,
+    /// 排队等待的最长时间（秒），未设置时使用执行器默认值
+    #[serde(default)]
+    pub queue_timeout_seconds: Option<u64>,
+}
+
+fn default_concurrency_max_parallel() -> u32 {
+    1
+}
+
+/// 并发组达到上限时的处理策略
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConcurrencyPolicy {
+    /// 排队等待空闲名额
+    #[default]
+    Queue,
+    /// 直接拒绝冲突的执行
+    Reject,
+}
+
 /// 错误处理策略
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -494,6 +768,7 @@ impl WorkflowEngine {
         Self {
             workflows: Arc::new(RwLock::new(HashMap::new())),
             templates: Arc::new(RwLock::new(HashMap::new())),
+            edit_leases: Arc::new(RwLock::new(HashMap::new())),
             config: config.unwrap_or_default(),
         }
     }
@@ -573,7 +848,13 @@ impl WorkflowEngine {
         // 5. 验证参数
         self.validate_parameters(workflow, &mut errors);
         
-        // 6. 检查性能问题
+        // 6. 验证并发组
+        self.validate_concurrency_groups(workflow, &mut errors);
+        
+        // 7. 验证密钥引用
+        self.validate_secret_references(workflow, &mut errors);
+        
+        // 8. 检查性能问题
         self.check_performance_issues(workflow, &mut warnings);
         
         let is_valid = errors.is_empty();
@@ -617,6 +898,136 @@ impl WorkflowEngine {
             .ok_or_else(|| AiStudioError::not_found("工作流不存在"))
     }
     
+    /// 保存工作流修改
+    ///
+    /// `expected_revision` 与当前修订号不一致，或其他用户持有有效编辑租约时返回冲突；
+    /// 保存成功后修订号加一。
+    pub async fn update_workflow(
+        &self,
+        mut workflow: WorkflowDefinition,
+        expected_revision: Option<u64>,
+        editor_id: Uuid,
+    ) -> Result<WorkflowDefinition, WorkflowEditError> {
+        let validation_result = self.validate_workflow(&workflow).await?;
+        if !validation_result.is_valid {
+            return Err(AiStudioError::validation("workflow".to_string(), "工作流验证失败".to_string()).into());
+        }
+
+        let mut workflows = self.workflows.write().await;
+        let current = workflows.get(&workflow.id)
+            .ok_or_else(|| AiStudioError::not_found("工作流不存在"))?;
+
+        if expected_revision.is_some_and(|revision| revision != current.revision) {
+            return Err(WorkflowEditError::VersionMismatch(self.conflict_for(current).await));
+        }
+        let conflict = self.conflict_for(current).await;
+        if conflict.lease.as_ref().is_some_and(|lease| lease.holder_id != editor_id) {
+            return Err(WorkflowEditError::Locked(conflict));
+        }
+
+        workflow.tenant_id = current.tenant_id;
+        workflow.created_by = current.created_by;
+        workflow.created_at = current.created_at;
+        workflow.revision = current.revision + 1;
+        workflow.updated_at = Utc::now();
+        workflows.insert(workflow.id, workflow.clone());
+
+        info!("工作流已更新: {} ({}), 修订号={}", workflow.name, workflow.id, workflow.revision);
+        Ok(workflow)
+    }
+
+    /// 获取或续期编辑租约，其他用户持有有效租约时返回冲突
+    pub async fn acquire_edit_lease(
+        &self,
+        workflow_id: Uuid,
+        holder_id: Uuid,
+        holder_name: String,
+        ttl_seconds: Option<u64>,
+    ) -> Result<WorkflowEditLease, WorkflowEditError> {
+        let workflow = self.get_workflow(workflow_id).await?;
+        let ttl = ttl_seconds
+            .unwrap_or(self.config.edit_lease_seconds)
+            .clamp(1, self.config.max_edit_lease_seconds);
+        let now = Utc::now();
+
+        let mut leases = self.edit_leases.write().await;
+        let acquired_at = match leases.get(&workflow_id) {
+            Some(lease) if lease.is_active(now) && lease.holder_id != holder_id => {
+                return Err(WorkflowEditError::Locked(WorkflowConflict {
+                    workflow_id,
+                    current_revision: workflow.revision,
+                    current_etag: workflow.etag(),
+                    updated_at: workflow.updated_at,
+                    lease: Some(lease.clone()),
+                }));
+            }
+            Some(lease) if lease.is_active(now) => lease.acquired_at,
+            _ => now,
+        };
+
+        let lease = WorkflowEditLease {
+            workflow_id,
+            holder_id,
+            holder_name,
+            acquired_at,
+            expires_at: now + chrono::Duration::seconds(ttl as i64),
+        };
+        leases.insert(workflow_id, lease.clone());
+
+        debug!("编辑租约已更新: workflow_id={}, holder_id={}", workflow_id, holder_id);
+        Ok(lease)
+    }
+
+    /// 获取当前有效的编辑租约
+    pub async fn get_edit_lease(&self, workflow_id: Uuid) -> Option<WorkflowEditLease> {
+        let now = Utc::now();
+        let mut leases = self.edit_leases.write().await;
+        match leases.get(&workflow_id) {
+            Some(lease) if lease.is_active(now) => Some(lease.clone()),
+            Some(_) => {
+                leases.remove(&workflow_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// 释放编辑租约，只有持有者可以释放有效租约
+    pub async fn release_edit_lease(
+        &self,
+        workflow_id: Uuid,
+        holder_id: Uuid,
+    ) -> Result<(), WorkflowEditError> {
+        let workflow = self.get_workflow(workflow_id).await?;
+        let mut leases = self.edit_leases.write().await;
+
+        if let Some(lease) = leases.get(&workflow_id) {
+            if lease.is_active(Utc::now()) && lease.holder_id != holder_id {
+                return Err(WorkflowEditError::Locked(WorkflowConflict {
+                    workflow_id,
+                    current_revision: workflow.revision,
+                    current_etag: workflow.etag(),
+                    updated_at: workflow.updated_at,
+                    lease: Some(lease.clone()),
+                }));
+            }
+            leases.remove(&workflow_id);
+        }
+
+        Ok(())
+    }
+
+    /// 构建工作流当前状态的冲突信息
+    async fn conflict_for(&self, workflow: &WorkflowDefinition) -> WorkflowConflict {
+        WorkflowConflict {
+            workflow_id: workflow.id,
+            current_revision: workflow.revision,
+            current_etag: workflow.etag(),
+            updated_at: workflow.updated_at,
+            lease: self.get_edit_lease(workflow.id).await,
+        }
+    }
+    
     /// 列出工作流
     pub async fn list_workflows(&self, tenant_id: Option<Uuid>) -> Result<Vec<WorkflowDefinition>, AiStudioError> {
         let workflows = self.workflows.read().await;
@@ -849,6 +1260,57 @@ impl WorkflowEngine {
                         });
                     }
                 }
+                StepType::KnowledgeSearch => {
+                    if let StepConfig::KnowledgeSearch { .. } = &step.config {
+                        for message in knowledge_search_config_errors(&step.config) {
+                            errors.push(ValidationError {
+                                error_type: ValidationErrorType::InvalidStepConfig,
+                                message,
+                                step_id: Some(step.id.clone()),
+                            });
+                        }
+                    } else {
+                        errors.push(ValidationError {
+                            error_type: ValidationErrorType::InvalidStepConfig,
+                            message: "知识库检索步骤配置类型不匹配".to_string(),
+                            step_id: Some(step.id.clone()),
+                        });
+                    }
+                }
+                StepType::DocumentGenerate => {
+                    if let StepConfig::DocumentGenerate { .. } = &step.config {
+                        for message in document_generate_config_errors(&step.config) {
+                            errors.push(ValidationError {
+                                error_type: ValidationErrorType::InvalidStepConfig,
+                                message,
+                                step_id: Some(step.id.clone()),
+                            });
+                        }
+                    } else {
+                        errors.push(ValidationError {
+                            error_type: ValidationErrorType::InvalidStepConfig,
+                            message: "文档生成步骤配置类型不匹配".to_string(),
+                            step_id: Some(step.id.clone()),
+                        });
+                    }
+                }
+                StepType::WaitForCallback => {
+                    if let StepConfig::WaitForCallback { .. } = &step.config {
+                        for message in wait_for_callback_config_errors(&step.config) {
+                            errors.push(ValidationError {
+                                error_type: ValidationErrorType::InvalidStepConfig,
+                                message,
+                                step_id: Some(step.id.clone()),
+                            });
+                        }
+                    } else {
+                        errors.push(ValidationError {
+                            error_type: ValidationErrorType::InvalidStepConfig,
+                            message: "等待回调步骤配置类型不匹配".to_string(),
+                            step_id: Some(step.id.clone()),
+                        });
+                    }
+                }
                 _ => {
                     // TODO: 验证其他步骤类型
                 }
@@ -880,6 +1342,46 @@ impl WorkflowEngine {
         }
     }
     
+    /// 验证工作流及步骤的并发组配置
+    fn validate_concurrency_groups(&self, workflow: &WorkflowDefinition, errors: &mut Vec<ValidationError>) {
+        let groups = std::iter::once((None, workflow.config.concurrency.as_ref()))
+            .chain(workflow.steps.iter().map(|step| (Some(step.id.clone()), step.concurrency.as_ref())));
+        for (step_id, group) in groups {
+            let Some(group) = group else { continue };
+            for message in concurrency_group_errors(group) {
+                errors.push(ValidationError {
+                    error_type: ValidationErrorType::InvalidStepConfig,
+                    message,
+                    step_id: step_id.clone(),
+                });
+            }
+        }
+    }
+    
+    /// 验证步骤引用的密钥：仅允许 API 调用与工具调用步骤引用，且名称必须合法
+    fn validate_secret_references(&self, workflow: &WorkflowDefinition, errors: &mut Vec<ValidationError>) {
+        for step in &workflow.steps {
+            let names = secret_references(&step.config);
+            if names.is_empty() {
+                continue;
+            }
+            if !step_type_allows_secrets(&step.step_type) {
+                errors.push(ValidationError {
+                    error_type: ValidationErrorType::InvalidStepConfig,
+                    message: "只有 API 调用和工具调用步骤可以引用密钥".to_string(),
+                    step_id: Some(step.id.clone()),
+                });
+            }
+            for name in names.iter().filter(|name| !is_valid_secret_name(name)) {
+                errors.push(ValidationError {
+                    error_type: ValidationErrorType::InvalidStepConfig,
+                    message: format!("无效的密钥名称: {}", name),
+                    step_id: Some(step.id.clone()),
+                });
+            }
+        }
+    }
+    
     /// 验证参数类型
     fn validate_parameter_type(&self, value: &serde_json::Value, param_type: &ParameterType) -> bool {
         match (value, param_type) {
@@ -954,6 +1456,110 @@ impl WorkflowEngine {
     }
 }
 
+/// 知识库检索步骤单次返回的最大文档块数量
+pub const MAX_KNOWLEDGE_SEARCH_TOP_K: u32 = 50;
+
+/// 检查知识库检索步骤配置，返回所有错误描述
+fn knowledge_search_config_errors(config: &StepConfig) -> Vec<String> {
+    let StepConfig::KnowledgeSearch {
+        knowledge_base_id,
+        query,
+        top_k,
+        mode,
+        similarity_threshold,
+        vector_weight,
+        keyword_weight,
+        ..
+    } = config else {
+        return Vec::new();
+    };
+
+    let mut errors = Vec::new();
+    if knowledge_base_id.is_nil() {
+        errors.push("知识库 ID 不能为空".to_string());
+    }
+    if query.trim().is_empty() {
+        errors.push("检索查询不能为空".to_string());
+    }
+    if *top_k == 0 || *top_k > MAX_KNOWLEDGE_SEARCH_TOP_K {
+        errors.push(format!("检索数量必须在 1 到 {} 之间", MAX_KNOWLEDGE_SEARCH_TOP_K));
+    }
+    if similarity_threshold.is_some_and(|threshold| !(0.0..=1.0).contains(&threshold)) {
+        errors.push("相似度阈值必须在 0 到 1 之间".to_string());
+    }
+    if vector_weight.is_some_and(|w| w < 0.0) || keyword_weight.is_some_and(|w| w < 0.0) {
+        errors.push("混合检索权重不能为负数".to_string());
+    } else if *mode == KnowledgeSearchMode::Hybrid
+        && vector_weight.unwrap_or(0.7) + keyword_weight.unwrap_or(0.3) <= 0.0
+    {
+        errors.push("混合检索权重之和必须大于 0".to_string());
+    }
+    errors
+}
+
+/// 检查文档生成步骤配置，返回所有错误描述
+fn document_generate_config_errors(config: &StepConfig) -> Vec<String> {
+    let StepConfig::DocumentGenerate { template, file_name, title, .. } = config else {
+        return Vec::new();
+    };
+
+    let mut errors = Vec::new();
+    if template.trim().is_empty() {
+        errors.push("文档模板不能为空".to_string());
+    }
+    let templates = [("文档模板", Some(template)), ("文件名模板", file_name.as_ref()), ("标题模板", title.as_ref())];
+    for (label, source) in templates {
+        if let Some(Err(e)) = source.map(|source| handlebars::Template::compile(source)) {
+            errors.push(format!("{}语法错误: {}", label, e));
+        }
+    }
+    errors
+}
+
+/// 等待外部回调的最长时间（秒）
+pub const MAX_CALLBACK_TIMEOUT_SECONDS: u64 = 30 * 24 * 3600;
+
+/// 检查等待回调步骤配置，返回所有错误描述
+fn wait_for_callback_config_errors(config: &StepConfig) -> Vec<String> {
+    let StepConfig::WaitForCallback { timeout_seconds, payload_mapping, .. } = config else {
+        return Vec::new();
+    };
+
+    let mut errors = Vec::new();
+    if *timeout_seconds == 0 || *timeout_seconds > MAX_CALLBACK_TIMEOUT_SECONDS {
+        errors.push(format!("回调超时时间必须在 1 到 {} 秒之间", MAX_CALLBACK_TIMEOUT_SECONDS));
+    }
+    for (name, pointer) in payload_mapping {
+        if name.trim().is_empty() {
+            errors.push("回调载荷映射的变量名不能为空".to_string());
+        }
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            errors.push(format!("回调载荷映射 {} 不是有效的 JSON Pointer: {}", name, pointer));
+        }
+    }
+    errors
+}
+
+/// 并发组允许的最大并行数
+pub const MAX_CONCURRENCY_GROUP_PARALLEL: u32 = 100;
+
+/// 检查并发组配置，返回所有错误描述
+fn concurrency_group_errors(group: &ConcurrencyGroup) -> Vec<String> {
+    let mut errors = Vec::new();
+    if group.name.trim().is_empty() {
+        errors.push("并发组名称不能为空".to_string());
+    } else if group.name.matches("{{").count() != group.name.matches("}}").count() {
+        errors.push(format!("并发组名称中的占位符未闭合: {}", group.name));
+    }
+    if group.max_parallel == 0 || group.max_parallel > MAX_CONCURRENCY_GROUP_PARALLEL {
+        errors.push(format!("并发组最大并行数必须在 1 到 {} 之间", MAX_CONCURRENCY_GROUP_PARALLEL));
+    }
+    if group.queue_timeout_seconds == Some(0) {
+        errors.push("并发组排队超时时间必须大于 0".to_string());
+    }
+    errors
+}
+
 /// 工作流引擎工厂
 pub struct WorkflowEngineFactory;
 
@@ -993,6 +1599,7 @@ mod tests {
                     retry_config: None,
                     timeout_seconds: None,
                     position: None,
+                    concurrency: None,
                 }
             ],
             parameters: Vec::new(),
@@ -1001,6 +1608,7 @@ mod tests {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             status: WorkflowStatus::Draft,
+            revision: 0,
         };
         
         let json = serde_json::to_string(&workflow).unwrap();
@@ -1010,11 +1618,8 @@ mod tests {
         assert_eq!(workflow.steps.len(), deserialized.steps.len());
     }
     
-    #[tokio::test]
-    async fn test_workflow_validation() {
-        let engine = WorkflowEngine::new(None);
-        
-        let workflow = WorkflowDefinition {
+    fn sample_workflow() -> WorkflowDefinition {
+    WorkflowDefinition {
             id: Uuid::new_v4(),
             name: "测试工作流".to_string(),
             description: "用于测试的工作流".to_string(),
@@ -1037,6 +1642,7 @@ mod tests {
                     retry_config: None,
                     timeout_seconds: None,
                     position: None,
+                    concurrency: None,
                 }
             ],
             parameters: Vec::new(),
@@ -1045,9 +1651,189 @@ mod tests {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             status: WorkflowStatus::Draft,
-        };
+            revision: 0,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_workflow_validation() {
+        let engine = WorkflowEngine::new(None);
+        
+        let workflow = sample_workflow();
         
         let result = engine.validate_workflow(&workflow).await.unwrap();
         assert!(result.is_valid);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_knowledge_search_step_validation() {
+        let engine = WorkflowEngine::new(None);
+        let step: WorkflowStep = serde_json::from_value(serde_json::json!({
+            "id": "search",
+            "name": "检索",
+            "description": "检索产品手册",
+            "step_type": "knowledge_search",
+            "config": {
+                "type": "knowledge_search",
+                "knowledge_base_id": Uuid::new_v4(),
+                "query": "{{parameters.question}}",
+                "mode": "hybrid",
+                "filters": { "document_types": ["pdf"] }
+            },
+            "depends_on": [],
+            "condition": null,
+            "retry_config": null,
+            "timeout_seconds": null,
+            "position": null
+        })).unwrap();
+        assert!(matches!(step.config, StepConfig::KnowledgeSearch { top_k: 5, .. }));
+
+        let mut workflow = sample_workflow();
+        workflow.steps.push(step.clone());
+        assert!(engine.validate_workflow(&workflow).await.unwrap().is_valid);
+
+        let mut invalid = step;
+        if let StepConfig::KnowledgeSearch { query, top_k, keyword_weight, .. } = &mut invalid.config {
+            *query = " ".to_string();
+            *top_k = MAX_KNOWLEDGE_SEARCH_TOP_K + 1;
+            *keyword_weight = Some(-1.0);
+        }
+        assert_eq!(knowledge_search_config_errors(&invalid.config).len(), 3);
+    }
+
+    #[test]
+    fn test_document_generate_config_errors() {
+        let config: StepConfig = serde_json::from_value(serde_json::json!({
+            "type": "document_generate",
+            "template": "# {{parameters.title}}\n{{#each search.chunks}}- {{this.content}}\n{{/each}}",
+            "format": "pdf"
+        })).unwrap();
+        assert!(document_generate_config_errors(&config).is_empty());
+
+        let config = StepConfig::DocumentGenerate {
+            template: "{{#each items}}".to_string(),
+            format: GeneratedDocumentFormat::Docx,
+            file_name: Some("{{name".to_string()),
+            title: None,
+        };
+        assert_eq!(document_generate_config_errors(&config).len(), 2);
+    }
+
+    #[test]
+    fn test_wait_for_callback_config_errors() {
+        let config: StepConfig = serde_json::from_value(serde_json::json!({
+            "type": "wait_for_callback",
+            "timeout_seconds": 3600,
+            "payload_mapping": { "approved": "/result/approved", "raw": "" }
+        })).unwrap();
+        assert!(matches!(config, StepConfig::WaitForCallback { on_timeout: CallbackTimeoutAction::Fail, .. }));
+        assert!(wait_for_callback_config_errors(&config).is_empty());
+
+        let config = StepConfig::WaitForCallback {
+            timeout_seconds: 0,
+            payload_mapping: HashMap::from([("status".to_string(), "data.status".to_string())]),
+            on_timeout: CallbackTimeoutAction::Continue,
+        };
+        assert_eq!(wait_for_callback_config_errors(&config).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_group_validation() {
+        let engine = WorkflowEngine::new(None);
+        let group: ConcurrencyGroup = serde_json::from_value(serde_json::json!({
+            "name": "connector-sync:{{parameters.connector_id}}"
+        })).unwrap();
+        assert_eq!(group.max_parallel, 1);
+        assert_eq!(group.policy, ConcurrencyPolicy::Queue);
+
+        let mut workflow = sample_workflow();
+        workflow.config.concurrency = Some(group);
+        assert!(engine.validate_workflow(&workflow).await.unwrap().is_valid);
+
+        workflow.steps[0].concurrency = Some(ConcurrencyGroup {
+            name: "export:{{parameters.id".to_string(),
+            max_parallel: 0,
+            policy: ConcurrencyPolicy::Reject,
+            queue_timeout_seconds: Some(0),
+        });
+        let result = engine.validate_workflow(&workflow).await.unwrap();
+        assert_eq!(result.errors.len(), 3);
+        assert!(result.errors.iter().all(|e| e.step_id.as_deref() == Some("step1")));
+    }
+
+    #[tokio::test]
+    async fn test_secret_reference_validation() {
+        let engine = WorkflowEngine::new(None);
+        let mut workflow = sample_workflow();
+        workflow.steps.push(serde_json::from_value(serde_json::json!({
+            "id": "sync",
+            "name": "同步",
+            "description": "调用 CRM 接口",
+            "step_type": "api_call",
+            "config": {
+                "type": "api_call",
+                "url": "https://crm.example.com/sync",
+                "method": "POST",
+                "headers": { "Authorization": "Bearer {{secrets.CRM_TOKEN}}" },
+                "body": null
+            },
+            "depends_on": [],
+            "condition": null,
+            "retry_config": null,
+            "timeout_seconds": null,
+            "position": null
+        })).unwrap());
+        assert!(engine.validate_workflow(&workflow).await.unwrap().is_valid);
+
+        if let StepConfig::AgentTask { task_description, .. } = &mut workflow.steps[0].config {
+            *task_description = "使用 {{secrets.crm-token}} 登录".to_string();
+        }
+        let result = engine.validate_workflow(&workflow).await.unwrap();
+        assert_eq!(result.errors.len(), 2);
+        assert!(result.errors.iter().all(|e| e.step_id.as_deref() == Some("step1")));
+    }
+
+    #[test]
+    fn test_parse_if_match() {
+        assert_eq!(parse_if_match("\"3\"").unwrap(), Some(3));
+        assert_eq!(parse_if_match("W/\"7\"").unwrap(), Some(7));
+        assert_eq!(parse_if_match("*").unwrap(), None);
+        assert!(parse_if_match("\"abc\"").is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_update_workflow_concurrency() {
+        let engine = WorkflowEngine::new(None);
+        let workflow = sample_workflow();
+        engine.register_workflow(workflow.clone()).await.unwrap();
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        
+        let updated = engine.update_workflow(workflow.clone(), Some(0), alice).await.unwrap();
+        assert_eq!(updated.revision, 1);
+        
+        // 基于旧修订的保存被拒绝
+        match engine.update_workflow(workflow.clone(), Some(0), alice).await {
+            Err(WorkflowEditError::VersionMismatch(conflict)) => assert_eq!(conflict.current_etag, "\"1\""),
+            other => panic!("期望修订号冲突: {:?}", other.map(|w| w.revision)),
+        }
+        
+        // 其他用户持有租约时拒绝获取租约、保存和释放
+        engine.acquire_edit_lease(workflow.id, alice, "alice".to_string(), None).await.unwrap();
+        assert!(matches!(
+            engine.acquire_edit_lease(workflow.id, bob, "bob".to_string(), None).await,
+            Err(WorkflowEditError::Locked(_))
+        ));
+        match engine.update_workflow(workflow.clone(), None, bob).await {
+            Err(WorkflowEditError::Locked(conflict)) => {
+                assert_eq!(conflict.lease.map(|lease| lease.holder_name), Some("alice".to_string()));
+            }
+            other => panic!("期望编辑租约冲突: {:?}", other.map(|w| w.revision)),
+        }
+        assert!(matches!(engine.release_edit_lease(workflow.id, bob).await, Err(WorkflowEditError::Locked(_))));
+        
+        // 持有者可以继续保存并释放租约
+        assert_eq!(engine.update_workflow(workflow.clone(), Some(1), alice).await.unwrap().revision, 2);
+        engine.release_edit_lease(workflow.id, alice).await.unwrap();
+        assert!(engine.get_edit_lease(workflow.id).await.is_none());
+    }
+}
@@ -4,14 +4,19 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
-use tracing::{info, error, debug};
+use tracing::{info, error, debug, warn};
 
 use crate::ai::{
-    workflow_engine::{WorkflowDefinition, WorkflowEngine},
+    workflow_concurrency::{group_full_error, ConcurrencyLimiter, ConcurrencySlot},
+    workflow_secrets::{ResolvedStepConfig, StepSecretResolver},
+    workflow_engine::{ConcurrencyPolicy, WorkflowDefinition, WorkflowEngine, WorkflowStep},
     agent_runtime::ExecutionContext,
 };
+use crate::db::entities::execution_event::ExecutionType;
 use crate::db::entities::workflow_execution::ExecutionOptions;
 use crate::errors::AiStudioError;
+use crate::plugins::event_bus::{event_types, SystemEvent, SystemEventBus};
+use crate::services::execution_event::{ExecutionEventService, ExecutionTransition};
 
 /// 执行请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +38,8 @@ pub struct WorkflowExecution {
     pub execution_id: Uuid,
     /// 工作流 ID
     pub workflow_id: Uuid,
+    /// 租户 ID
+    pub tenant_id: Uuid,
     /// 执行状态
     pub status: String,
     /// 执行上下文
@@ -49,7 +56,15 @@ pub struct WorkflowExecutor {
     /// 工作流引擎
     workflow_engine: Arc<WorkflowEngine>,
     /// 执行中的工作流
-    executions: std::sync::RwLock<HashMap<Uuid, WorkflowExecution>>,
+    executions: Arc<std::sync::RwLock<HashMap<Uuid, WorkflowExecution>>>,
+    /// 状态转换事件记录，未配置时不记录
+    events: Option<ExecutionEventService>,
+    /// 并发组限流器，未配置时忽略并发组设置
+    concurrency: Option<ConcurrencyLimiter>,
+    /// 各执行占用的并发名额
+    slots: Arc<std::sync::Mutex<HashMap<Uuid, Vec<ConcurrencySlot>>>>,
+    /// 步骤密钥解析器，未配置时引用密钥的步骤无法执行
+    secrets: Option<Arc<StepSecretResolver>>,
 }
 
 impl WorkflowExecutor {
@@ -57,36 +72,155 @@ impl WorkflowExecutor {
     pub fn new(workflow_engine: Arc<WorkflowEngine>) -> Self {
         Self {
             workflow_engine,
-            executions: std::sync::RwLock::new(HashMap::new()),
+            executions: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            events: None,
+            concurrency: None,
+            slots: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            secrets: None,
         }
     }
 
+    /// 记录每次状态转换的执行事件
+    pub fn with_event_service(mut self, events: ExecutionEventService) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// 按工作流和步骤上的并发组限制集群内的并行数量
+    pub fn with_concurrency_limiter(mut self, limiter: ConcurrencyLimiter) -> Self {
+        self.concurrency = Some(limiter);
+        self
+    }
+
+    /// 执行步骤时从租户密钥库注入步骤引用的密钥
+    pub fn with_secret_resolver(mut self, resolver: StepSecretResolver) -> Self {
+        self.secrets = Some(Arc::new(resolver));
+        self
+    }
+
+    /// 解析步骤执行时使用的配置
+    ///
+    /// 返回值仅在步骤执行期间持有，不得写入执行记录；步骤输出落库前需经其脱敏。
+    pub async fn resolve_step_config(
+        &self,
+        execution_id: Uuid,
+        step: &WorkflowStep,
+    ) -> Result<ResolvedStepConfig, AiStudioError> {
+        let tenant_id = self.get_execution_status(execution_id).await?.tenant_id;
+        let resolver = self.secrets.as_ref()
+            .ok_or_else(|| AiStudioError::configuration("未配置工作流密钥解析器"))?;
+        resolver.resolve(tenant_id, step).await
+    }
+
     /// 执行工作流
+    ///
+    /// 工作流配置了并发组时先占用名额：名额已满且策略为 `Reject` 时返回冲突错误，
+    /// 策略为 `Queue` 时执行以 `queued` 状态返回，后台等待名额后再转为 `running`。
     pub async fn execute_workflow(&self, request: ExecutionRequest) -> Result<Uuid, AiStudioError> {
         let execution_id = Uuid::new_v4();
         
         info!("开始执行工作流: workflow_id={}, execution_id={}", request.workflow.id, execution_id);
         
+        let mut queued_group = None;
+        if let (Some(limiter), Some(group)) = (&self.concurrency, &request.workflow.config.concurrency) {
+            let group_key = ConcurrencyLimiter::group_key(group, &request.parameters)?;
+            let owner = execution_id.to_string();
+            match limiter.try_acquire(request.workflow.tenant_id, &group_key, group.max_parallel, &owner).await? {
+                Some(slot) => self.hold_slot(execution_id, slot),
+                None if group.policy == ConcurrencyPolicy::Reject => {
+                    return Err(group_full_error(&group_key, group.max_parallel));
+                }
+                None => queued_group = Some((limiter.clone(), group.clone(), group_key)),
+            }
+        }
+        
         let execution = WorkflowExecution {
             execution_id,
             workflow_id: request.workflow.id,
-            status: "running".to_string(),
+            tenant_id: request.workflow.tenant_id,
+            status: if queued_group.is_some() { "queued" } else { "running" }.to_string(),
             context: request.context,
             started_at: chrono::Utc::now(),
             completed_at: None,
         };
         
+        let event_type = if queued_group.is_some() { event_types::WORKFLOW_QUEUED } else { event_types::WORKFLOW_STARTED };
+        publish_execution_event(event_type, &execution);
+        self.record_transition(&execution, None).await;
+        
         // 存储执行状态
         {
             let mut executions = self.executions.write().unwrap();
             executions.insert(execution_id, execution);
         }
         
+        if let Some((limiter, group, group_key)) = queued_group {
+            info!("并发组名额已满，工作流执行排队中: execution_id={}, group={}", execution_id, group_key);
+            let queue = QueuedExecution {
+                execution_id,
+                tenant_id: request.workflow.tenant_id,
+                max_parallel: group.max_parallel,
+                timeout: ConcurrencyLimiter::queue_timeout(&group),
+                group_key,
+                limiter,
+                executions: self.executions.clone(),
+                slots: self.slots.clone(),
+                events: self.events.clone(),
+            };
+            tokio::spawn(queue.wait_for_slot());
+            return Ok(execution_id);
+        }
+        
         // TODO: 实际执行工作流逻辑
         
         Ok(execution_id)
     }
 
+    /// 执行步骤前按步骤并发组占用名额，步骤未配置并发组时直接返回
+    ///
+    /// 名额记录在执行上，步骤结束后调用 [`Self::release_step_slot`] 归还，执行取消时一并归还。
+    pub async fn acquire_step_slot(
+        &self,
+        execution_id: Uuid,
+        step: &WorkflowStep,
+        parameters: &HashMap<String, serde_json::Value>,
+    ) -> Result<(), AiStudioError> {
+        let (Some(limiter), Some(group)) = (&self.concurrency, &step.concurrency) else {
+            return Ok(());
+        };
+        let tenant_id = self.get_execution_status(execution_id).await?.tenant_id;
+        let owner = step_slot_owner(execution_id, &step.id);
+        let slot = limiter.acquire(tenant_id, group, parameters, &owner).await?;
+        self.hold_slot(execution_id, slot);
+        Ok(())
+    }
+
+    /// 归还步骤占用的并发名额
+    pub async fn release_step_slot(&self, execution_id: Uuid, step_id: &str) {
+        let owner = step_slot_owner(execution_id, step_id);
+        let slot = {
+            let mut slots = self.slots.lock().unwrap();
+            let held = slots.get_mut(&execution_id);
+            held.and_then(|held| held.iter().position(|slot| slot.owner() == owner).map(|index| held.remove(index)))
+        };
+        if let Some(slot) = slot {
+            release_or_warn(slot).await;
+        }
+    }
+
+    /// 归还执行及其步骤占用的全部并发名额（执行结束或取消时调用）
+    pub async fn release_concurrency_slots(&self, execution_id: Uuid) {
+        let held = self.slots.lock().unwrap().remove(&execution_id).unwrap_or_default();
+        for slot in held {
+            release_or_warn(slot).await;
+        }
+    }
+
+    /// 记录执行占用的并发名额
+    fn hold_slot(&self, execution_id: Uuid, slot: ConcurrencySlot) {
+        self.slots.lock().unwrap().entry(execution_id).or_default().push(slot);
+    }
+
     /// 获取执行状态
     pub async fn get_execution_status(&self, execution_id: Uuid) -> Result<WorkflowExecution, AiStudioError> {
         let executions = self.executions.read().unwrap();
@@ -99,16 +233,149 @@ impl WorkflowExecutor {
 
     /// 取消执行
     pub async fn cancel_execution(&self, execution_id: Uuid) -> Result<(), AiStudioError> {
-        let mut executions = self.executions.write().unwrap();
-        if let Some(execution) = executions.get_mut(&execution_id) {
-            execution.status = "cancelled".to_string();
+        let (execution, previous_status) = {
+            let mut executions = self.executions.write().unwrap();
+            let execution = executions.get_mut(&execution_id)
+                .ok_or_else(|| AiStudioError::NotFound {
+                    resource: format!("execution {}", execution_id)
+                })?;
+            let previous_status = std::mem::replace(&mut execution.status, "cancelled".to_string());
             execution.completed_at = Some(chrono::Utc::now());
-            info!("工作流执行已取消: execution_id={}", execution_id);
-            Ok(())
-        } else {
-            Err(AiStudioError::NotFound {
-                resource: format!("execution {}", execution_id)
-            })
+            (execution.clone(), previous_status)
+        };
+        
+        info!("工作流执行已取消: execution_id={}", execution_id);
+        self.release_concurrency_slots(execution_id).await;
+        publish_execution_event(event_types::WORKFLOW_CANCELLED, &execution);
+        self.record_transition(&execution, Some(previous_status)).await;
+        Ok(())
+    }
+
+    /// 追加执行的状态转换事件
+    async fn record_transition(&self, execution: &WorkflowExecution, from_state: Option<String>) {
+        record_transition(self.events.as_ref(), execution, from_state).await;
+    }
+}
+
+/// 排队等待并发名额的执行
+struct QueuedExecution {
+    execution_id: Uuid,
+    tenant_id: Uuid,
+    group_key: String,
+    max_parallel: u32,
+    timeout: std::time::Duration,
+    limiter: ConcurrencyLimiter,
+    executions: Arc<std::sync::RwLock<HashMap<Uuid, WorkflowExecution>>>,
+    slots: Arc<std::sync::Mutex<HashMap<Uuid, Vec<ConcurrencySlot>>>>,
+    events: Option<ExecutionEventService>,
+}
+
+impl QueuedExecution {
+    /// 轮询等待空闲名额，取得后转为运行状态；执行被取消时停止等待，超时则标记为失败
+    async fn wait_for_slot(self) {
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        let owner = self.execution_id.to_string();
+
+        loop {
+            tokio::time::sleep(ConcurrencyLimiter::poll_interval()).await;
+            if !self.is_still_queued() {
+                debug!("排队中的工作流执行已不再等待: execution_id={}", self.execution_id);
+                return;
+            }
+
+            match self.limiter.try_acquire(self.tenant_id, &self.group_key, self.max_parallel, &owner).await {
+                Ok(Some(slot)) => {
+                    if self.transition("running", None).is_some() {
+                        self.slots.lock().unwrap().entry(self.execution_id).or_default().push(slot);
+                        self.finish_transition(event_types::WORKFLOW_STARTED, "queued").await;
+                        info!("工作流执行取得并发名额: execution_id={}, group={}", self.execution_id, self.group_key);
+                        // TODO: 实际执行工作流逻辑
+                    } else {
+                        release_or_warn(slot).await;
+                    }
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => warn!("占用并发名额失败: execution_id={}, error={}", self.execution_id, e),
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                if self.transition("failed", Some(chrono::Utc::now())).is_some() {
+                    warn!(
+                        "等待并发名额超时: execution_id={}, group={}, max_parallel={}",
+                        self.execution_id, self.group_key, self.max_parallel
+                    );
+                    self.finish_transition(event_types::WORKFLOW_FAILED, "queued").await;
+                }
+                return;
+            }
+        }
+    }
+
+    fn is_still_queued(&self) -> bool {
+        self.executions.read().unwrap()
+            .get(&self.execution_id)
+            .is_some_and(|execution| execution.status == "queued")
+    }
+
+    /// 仅当执行仍在排队时切换状态
+    fn transition(&self, status: &str, completed_at: Option<chrono::DateTime<chrono::Utc>>) -> Option<()> {
+        let mut executions = self.executions.write().unwrap();
+        let execution = executions.get_mut(&self.execution_id).filter(|execution| execution.status == "queued")?;
+        execution.status = status.to_string();
+        execution.completed_at = completed_at;
+        Some(())
+    }
+
+    async fn finish_transition(&self, event_type: &str, from_state: &str) {
+        let execution = self.executions.read().unwrap().get(&self.execution_id).cloned();
+        if let Some(execution) = execution {
+            publish_execution_event(event_type, &execution);
+            record_transition(self.events.as_ref(), &execution, Some(from_state.to_string())).await;
         }
     }
-}
\ No newline at end of file
+}
+
+/// 步骤并发名额的持有者标识
+fn step_slot_owner(execution_id: Uuid, step_id: &str) -> String {
+    format!("{}:{}", execution_id, step_id)
+}
+
+/// 归还并发名额，失败时仅记录日志（租约到期后也会自动归还）
+async fn release_or_warn(slot: ConcurrencySlot) {
+    let group_key = slot.group_key().to_string();
+    if let Err(e) = slot.release().await {
+        warn!("归还并发名额失败: group={}, error={}", group_key, e);
+    }
+}
+
+/// 追加执行的状态转换事件
+async fn record_transition(
+    events: Option<&ExecutionEventService>,
+    execution: &WorkflowExecution,
+    from_state: Option<String>,
+) {
+    if let Some(events) = events {
+        events.record_or_warn(ExecutionTransition {
+            tenant_id: execution.tenant_id,
+            execution_type: ExecutionType::Workflow,
+            execution_id: execution.execution_id,
+            from_state,
+            to_state: execution.status.clone(),
+            payload: serde_json::json!({ "workflow_id": execution.workflow_id }),
+        }).await;
+    }
+}
+
+/// 发布工作流执行事件
+fn publish_execution_event(event_type: &str, execution: &WorkflowExecution) {
+    SystemEventBus::global().publish(SystemEvent::new(
+        event_type,
+        Some(execution.tenant_id),
+        serde_json::json!({
+            "execution_id": execution.execution_id,
+            "workflow_id": execution.workflow_id,
+            "status": execution.status,
+        }),
+    ));
+}
@@ -0,0 +1,360 @@
+// 工作流输入表单
+// 根据工作流参数生成 JSON Schema 与界面提示，并在启动执行前校验提交的输入
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::workflow_engine::{ParameterType, ParameterValidation, WorkflowDefinition, WorkflowParameter};
+
+/// 长文本输入框的长度阈值，最大长度超过该值时使用多行输入
+const TEXTAREA_MIN_LENGTH: f64 = 200.0;
+
+/// 工作流输入表单描述
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WorkflowInputForm {
+    /// 工作流 ID
+    pub workflow_id: Uuid,
+    /// 输入参数的 JSON Schema
+    pub schema: Value,
+    /// 界面渲染提示，按参数名索引
+    pub ui_schema: Value,
+}
+
+/// 输入校验失败项
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct InputViolation {
+    /// 参数名称
+    pub parameter: String,
+    /// 失败原因
+    pub message: String,
+}
+
+impl InputViolation {
+    fn new(parameter: &str, message: impl Into<String>) -> Self {
+        Self {
+            parameter: parameter.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// 生成工作流的输入表单描述
+pub fn build_input_form(workflow: &WorkflowDefinition) -> WorkflowInputForm {
+    let mut properties = Map::new();
+    let mut ui_schema = Map::new();
+    let mut required = Vec::new();
+
+    for parameter in &workflow.parameters {
+        properties.insert(parameter.name.clone(), parameter_schema(parameter));
+        ui_schema.insert(parameter.name.clone(), ui_hints(parameter));
+        if parameter.required && parameter.default_value.is_none() {
+            required.push(Value::String(parameter.name.clone()));
+        }
+    }
+
+    let order: Vec<Value> = workflow.parameters.iter()
+        .map(|p| Value::String(p.name.clone()))
+        .collect();
+    ui_schema.insert("ui:order".to_string(), Value::Array(order));
+
+    WorkflowInputForm {
+        workflow_id: workflow.id,
+        schema: json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": workflow.name,
+            "description": workflow.description,
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        }),
+        ui_schema: Value::Object(ui_schema),
+    }
+}
+
+/// 校验提交的输入并补全默认值
+///
+/// 未声明的参数原样保留；所有失败项一次性返回，便于前端逐项提示。
+pub fn validate_workflow_inputs(
+    workflow: &WorkflowDefinition,
+    inputs: &HashMap<String, Value>,
+) -> Result<HashMap<String, Value>, Vec<InputViolation>> {
+    let mut resolved = inputs.clone();
+    let mut violations = Vec::new();
+
+    for parameter in &workflow.parameters {
+        let value = match inputs.get(&parameter.name) {
+            Some(Value::Null) | None => match &parameter.default_value {
+                Some(default) => {
+                    resolved.insert(parameter.name.clone(), default.clone());
+                    continue;
+                }
+                None if parameter.required => {
+                    violations.push(InputViolation::new(&parameter.name, "缺少必需参数"));
+                    continue;
+                }
+                None => continue,
+            },
+            Some(value) => value,
+        };
+
+        if !matches_type(value, &parameter.parameter_type) {
+            violations.push(InputViolation::new(
+                &parameter.name,
+                format!("类型不匹配，期望 {}", type_name(&parameter.parameter_type)),
+            ));
+            continue;
+        }
+        if let Some(validation) = &parameter.validation {
+            check_constraints(parameter, validation, value, &mut violations);
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(resolved)
+    } else {
+        Err(violations)
+    }
+}
+
+/// 单个参数的 JSON Schema
+fn parameter_schema(parameter: &WorkflowParameter) -> Value {
+    let mut schema = Map::new();
+    match parameter.parameter_type {
+        ParameterType::File => {
+            schema.insert("type".to_string(), json!("string"));
+            schema.insert("format".to_string(), json!("uri"));
+        }
+        ref other => {
+            schema.insert("type".to_string(), json!(type_name(other)));
+        }
+    }
+    schema.insert("title".to_string(), json!(parameter.name));
+    if !parameter.description.is_empty() {
+        schema.insert("description".to_string(), json!(parameter.description));
+    }
+    if let Some(default) = &parameter.default_value {
+        schema.insert("default".to_string(), default.clone());
+    }
+
+    if let Some(validation) = &parameter.validation {
+        let (min_key, max_key) = match parameter.parameter_type {
+            ParameterType::Number => ("minimum", "maximum"),
+            ParameterType::String | ParameterType::File => ("minLength", "maxLength"),
+            ParameterType::Array => ("minItems", "maxItems"),
+            ParameterType::Object => ("minProperties", "maxProperties"),
+            ParameterType::Boolean => ("", ""),
+        };
+        if !min_key.is_empty() {
+            let is_count = parameter.parameter_type != ParameterType::Number;
+            if let Some(min) = validation.min {
+                schema.insert(min_key.to_string(), bound_value(min, is_count));
+            }
+            if let Some(max) = validation.max {
+                schema.insert(max_key.to_string(), bound_value(max, is_count));
+            }
+        }
+        if let Some(pattern) = &validation.pattern {
+            if parameter.parameter_type == ParameterType::String {
+                schema.insert("pattern".to_string(), json!(pattern));
+            }
+        }
+        if let Some(values) = &validation.enum_values {
+            schema.insert("enum".to_string(), Value::Array(values.clone()));
+        }
+    }
+
+    Value::Object(schema)
+}
+
+/// 单个参数的界面提示
+fn ui_hints(parameter: &WorkflowParameter) -> Value {
+    let validation = parameter.validation.as_ref();
+    let has_enum = validation.is_some_and(|v| v.enum_values.as_ref().is_some_and(|values| !values.is_empty()));
+
+    let widget = match parameter.parameter_type {
+        _ if has_enum => "select",
+        ParameterType::String if validation.and_then(|v| v.max).is_none_or(|max| max > TEXTAREA_MIN_LENGTH) => "textarea",
+        ParameterType::String => "text",
+        ParameterType::Number => "number",
+        ParameterType::Boolean => "checkbox",
+        ParameterType::Array | ParameterType::Object => "json",
+        ParameterType::File => "file",
+    };
+
+    let mut hints = json!({
+        "ui:widget": widget,
+        "ui:label": parameter.name,
+    });
+    if !parameter.description.is_empty() {
+        hints["ui:help"] = json!(parameter.description);
+    }
+    if let Some(default) = &parameter.default_value {
+        hints["ui:placeholder"] = json!(default.to_string());
+    }
+    hints
+}
+
+fn check_constraints(
+    parameter: &WorkflowParameter,
+    validation: &ParameterValidation,
+    value: &Value,
+    violations: &mut Vec<InputViolation>,
+) {
+    let name = parameter.name.as_str();
+
+    if let Some(values) = &validation.enum_values {
+        if !values.is_empty() && !values.contains(value) {
+            violations.push(InputViolation::new(name, "取值不在允许的选项中"));
+        }
+    }
+
+    let (measure, unit) = match value {
+        Value::Number(n) => (n.as_f64(), "值"),
+        Value::String(s) => (Some(s.chars().count() as f64), "长度"),
+        Value::Array(items) => (Some(items.len() as f64), "元素数量"),
+        Value::Object(fields) => (Some(fields.len() as f64), "字段数量"),
+        _ => (None, ""),
+    };
+    if let Some(measure) = measure {
+        if let Some(min) = validation.min {
+            if measure < min {
+                violations.push(InputViolation::new(name, format!("{}不能小于 {}", unit, min)));
+            }
+        }
+        if let Some(max) = validation.max {
+            if measure > max {
+                violations.push(InputViolation::new(name, format!("{}不能大于 {}", unit, max)));
+            }
+        }
+    }
+
+    if let (Some(pattern), Value::String(text)) = (&validation.pattern, value) {
+        match regex::Regex::new(pattern) {
+            Ok(regex) if !regex.is_match(text) => {
+                violations.push(InputViolation::new(name, format!("不符合格式要求: {}", pattern)));
+            }
+            Ok(_) => {}
+            Err(_) => violations.push(InputViolation::new(name, "参数定义中的正则表达式无效")),
+        }
+    }
+}
+
+fn matches_type(value: &Value, parameter_type: &ParameterType) -> bool {
+    matches!(
+        (value, parameter_type),
+        (Value::String(_), ParameterType::String | ParameterType::File)
+            | (Value::Number(_), ParameterType::Number)
+            | (Value::Bool(_), ParameterType::Boolean)
+            | (Value::Array(_), ParameterType::Array)
+            | (Value::Object(_), ParameterType::Object)
+    )
+}
+
+fn type_name(parameter_type: &ParameterType) -> &'static str {
+    match parameter_type {
+        ParameterType::String | ParameterType::File => "string",
+        ParameterType::Number => "number",
+        ParameterType::Boolean => "boolean",
+        ParameterType::Array => "array",
+        ParameterType::Object => "object",
+    }
+}
+
+/// 长度与数量类约束在 JSON Schema 中必须是非负整数
+fn bound_value(bound: f64, is_count: bool) -> Value {
+    if is_count {
+        json!(bound.max(0.0).round() as u64)
+    } else {
+        json!(bound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflow(parameters: Value) -> WorkflowDefinition {
+        serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "name": "报表生成",
+            "description": "",
+            "version": "1.0.0",
+            "created_by": Uuid::new_v4(),
+            "tenant_id": Uuid::new_v4(),
+            "steps": [],
+            "parameters": parameters,
+            "outputs": [],
+            "config": crate::ai::workflow_engine::WorkflowConfig::default(),
+            "created_at": chrono::Utc::now(),
+            "updated_at": chrono::Utc::now(),
+            "status": "draft",
+        }))
+        .unwrap()
+    }
+
+    fn sample() -> WorkflowDefinition {
+        workflow(json!([
+            {
+                "name": "region", "parameter_type": "string", "description": "区域", "required": true,
+                "default_value": null,
+                "validation": { "min": null, "max": null, "pattern": null, "enum_values": ["cn", "us"] }
+            },
+            {
+                "name": "top_n", "parameter_type": "number", "description": "", "required": false,
+                "default_value": 10,
+                "validation": { "min": 1.0, "max": 100.0, "pattern": null, "enum_values": null }
+            },
+            {
+                "name": "code", "parameter_type": "string", "description": "", "required": false,
+                "default_value": null,
+                "validation": { "min": 2.0, "max": 8.0, "pattern": "^[A-Z]+$", "enum_values": null }
+            }
+        ]))
+    }
+
+    #[test]
+    fn test_build_input_form() {
+        let form = build_input_form(&sample());
+
+        assert_eq!(form.schema["required"], json!(["region"]));
+        assert_eq!(form.schema["properties"]["region"]["enum"], json!(["cn", "us"]));
+        assert_eq!(form.schema["properties"]["top_n"]["maximum"], json!(100.0));
+        assert_eq!(form.schema["properties"]["code"]["maxLength"], json!(8));
+        assert_eq!(form.ui_schema["region"]["ui:widget"], json!("select"));
+        assert_eq!(form.ui_schema["code"]["ui:widget"], json!("text"));
+        assert_eq!(form.ui_schema["ui:order"], json!(["region", "top_n", "code"]));
+    }
+
+    #[test]
+    fn test_validate_inputs_applies_defaults() {
+        let inputs = HashMap::from([("region".to_string(), json!("cn"))]);
+        let resolved = validate_workflow_inputs(&sample(), &inputs).unwrap();
+
+        assert_eq!(resolved["top_n"], json!(10));
+        assert!(!resolved.contains_key("code"));
+    }
+
+    #[test]
+    fn test_validate_inputs_reports_all_violations() {
+        let inputs = HashMap::from([
+            ("top_n".to_string(), json!(500)),
+            ("code".to_string(), json!("abc")),
+        ]);
+        let violations = validate_workflow_inputs(&sample(), &inputs).unwrap_err();
+        let parameters: Vec<&str> = violations.iter().map(|v| v.parameter.as_str()).collect();
+
+        assert_eq!(parameters, vec!["region", "top_n", "code"]);
+
+        let inputs = HashMap::from([
+            ("region".to_string(), json!("eu")),
+            ("top_n".to_string(), json!("5")),
+        ]);
+        let violations = validate_workflow_inputs(&sample(), &inputs).unwrap_err();
+        assert_eq!(violations.len(), 2);
+        assert!(violations[1].message.contains("类型不匹配"));
+    }
+}
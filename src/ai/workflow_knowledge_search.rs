@@ -0,0 +1,738 @@
+// 工作流知识库检索步骤
+// 按步骤配置在知识库中检索文档块，并将结果作为步骤输出供后续 Agent 或任务步骤引用
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::ai::embedding_pool::{EmbeddingPool, EmbeddingPriority};
+use crate::ai::rig_client::RigAiClient;
+use crate::ai::vector_store::VectorStoreRegistry;
+use crate::ai::workflow_engine::{KnowledgeSearchFilters, KnowledgeSearchFusion, KnowledgeSearchMode, StepConfig};
+use crate::db::entities::document::ClearanceLevel;
+use crate::db::entities::knowledge_base::VectorBackend;
+use crate::errors::AiStudioError;
+use crate::services::deadline;
+use crate::services::freshness::DocumentFreshnessService;
+use crate::services::lexical_index::{query_terms, BM25_B, BM25_K1};
+
+/// 混合检索时向量与词法两路各自召回的候选数量倍数，候选合并后再融合排序
+const HYBRID_CANDIDATE_FACTOR: u32 = 4;
+
+/// 倒数排名融合的平滑常数
+const RRF_K: f32 = 60.0;
+
+/// 混合检索默认向量得分权重
+const DEFAULT_VECTOR_WEIGHT: f32 = 0.7;
+
+/// 混合检索默认关键词得分权重
+const DEFAULT_KEYWORD_WEIGHT: f32 = 0.3;
+
+/// 外部向量存储召回的候选数量倍数，弥补密级与元数据过滤在数据库侧剔除的结果
+const EXTERNAL_STORE_CANDIDATE_FACTOR: usize = 3;
+
+/// 知识库检索步骤输出
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgeSearchOutput {
+    /// 渲染后的查询内容
+    pub query: String,
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 检索方式
+    pub mode: KnowledgeSearchMode,
+    /// 检索到的文档块，按得分降序
+    pub chunks: Vec<KnowledgeSearchHit>,
+    /// 拼接后的文档块文本，便于直接填入后续步骤的提示词
+    pub context: String,
+}
+
+/// 检索到的文档块
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgeSearchHit {
+    /// 文档块 ID
+    pub chunk_id: Uuid,
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub document_title: String,
+    /// 文档块位置
+    pub chunk_index: i32,
+    /// 文档块内容
+    pub content: String,
+    /// 最终得分
+    pub score: f32,
+    /// 向量相似度
+    pub vector_score: f32,
+    /// 归一化的 BM25 得分（仅混合检索与词法检索）
+    pub keyword_score: Option<f32>,
+    /// 文档块元数据
+    pub metadata: Value,
+}
+
+/// 知识库检索步骤执行器
+pub struct KnowledgeSearchStepRunner {
+    db: DatabaseConnection,
+    embedder: Option<Arc<RigAiClient>>,
+    vector_stores: Option<Arc<VectorStoreRegistry>>,
+}
+
+impl KnowledgeSearchStepRunner {
+    /// 创建新的知识库检索步骤执行器
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db, embedder: None, vector_stores: None }
+    }
+
+    /// 设置未启用本地嵌入工作池时使用的嵌入客户端
+    pub fn with_embedder(mut self, embedder: Arc<RigAiClient>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// 设置向量存储注册表，知识库使用外部向量存储时从对应后端召回
+    pub fn with_vector_stores(mut self, vector_stores: Arc<VectorStoreRegistry>) -> Self {
+        self.vector_stores = Some(vector_stores);
+        self
+    }
+
+    /// 执行知识库检索步骤
+    ///
+    /// `variables` 为执行上下文变量，前序步骤的输出以步骤 ID 为键存放其中；
+    /// 结果只包含租户自身知识库中、不超过执行者访问密级的文档块。
+    pub async fn run(
+        &self,
+        tenant_id: Uuid,
+        clearance: ClearanceLevel,
+        config: &StepConfig,
+        variables: &HashMap<String, Value>,
+    ) -> Result<KnowledgeSearchOutput, AiStudioError> {
+        let StepConfig::KnowledgeSearch {
+            knowledge_base_id,
+            query,
+            top_k,
+            mode,
+            similarity_threshold,
+            vector_weight,
+            keyword_weight,
+            fusion,
+            filters,
+        } = config else {
+            return Err(AiStudioError::validation("config", "步骤配置不是知识库检索"));
+        };
+
+        let query = render_query(query, variables)?;
+        if query.trim().is_empty() {
+            return Err(AiStudioError::validation("query", "渲染后的检索查询为空"));
+        }
+        debug!("执行知识库检索步骤: knowledge_base_id={}, mode={:?}, query={}", knowledge_base_id, mode, query);
+
+        let mut hits = match mode {
+            KnowledgeSearchMode::Vector => {
+                let embedding = self.embed(&query).await?;
+                self.search_candidates(tenant_id, *knowledge_base_id, clearance, filters, &embedding, *top_k)
+                    .await?
+            }
+            KnowledgeSearchMode::Sparse => {
+                let mut hits = self
+                    .search_sparse_candidates(tenant_id, *knowledge_base_id, clearance, filters, &query, *top_k)
+                    .await?;
+                for hit in &mut hits {
+                    hit.score = hit.keyword_score.unwrap_or_default();
+                }
+                hits
+            }
+            KnowledgeSearchMode::Hybrid => {
+                let embedding = self.embed(&query).await?;
+                let candidates = top_k.saturating_mul(HYBRID_CANDIDATE_FACTOR);
+                let dense = self
+                    .search_candidates(tenant_id, *knowledge_base_id, clearance, filters, &embedding, candidates)
+                    .await?;
+                let sparse = self
+                    .search_sparse_candidates(tenant_id, *knowledge_base_id, clearance, filters, &query, candidates)
+                    .await?;
+                let (mut hits, sparse_only) = merge_candidates(dense, sparse);
+                self.fill_vector_scores(*knowledge_base_id, &embedding, &sparse_only, &mut hits).await?;
+
+                let weights = (
+                    vector_weight.unwrap_or(DEFAULT_VECTOR_WEIGHT),
+                    keyword_weight.unwrap_or(DEFAULT_KEYWORD_WEIGHT),
+                );
+                fuse_scores(&mut hits, *fusion, weights);
+                hits
+            }
+        };
+        self.apply_freshness(&mut hits).await?;
+
+        if let Some(threshold) = similarity_threshold {
+            hits.retain(|hit| hit.score >= *threshold);
+        }
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(*top_k as usize);
+
+        info!("知识库检索步骤完成: knowledge_base_id={}, hits={}", knowledge_base_id, hits.len());
+        Ok(KnowledgeSearchOutput {
+            context: format_context(&hits),
+            query,
+            knowledge_base_id: *knowledge_base_id,
+            mode: *mode,
+            chunks: hits,
+        })
+    }
+
+    /// 按向量相似度召回候选文档块
+    async fn search_candidates(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        clearance: ClearanceLevel,
+        filters: &KnowledgeSearchFilters,
+        embedding: &[f32],
+        limit: u32,
+    ) -> Result<Vec<KnowledgeSearchHit>, AiStudioError> {
+        if let Some(vector_stores) = &self.vector_stores {
+            let backend = vector_stores.backend_of(knowledge_base_id).await?;
+            if backend != VectorBackend::Pgvector {
+                return self
+                    .search_external_candidates(
+                        vector_stores, backend, tenant_id, knowledge_base_id, clearance, filters, embedding, limit,
+                    )
+                    .await;
+            }
+        }
+
+        let document_ids = (!filters.document_ids.is_empty())
+            .then(|| serde_json::json!(filters.document_ids));
+        let document_types = (!filters.document_types.is_empty())
+            .then(|| serde_json::json!(filters.document_types));
+        let metadata = serde_json::json!(filters.metadata);
+
+        let rows = deadline::query_all(
+            &self.db,
+            "workflow.knowledge_search.vector",
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT c.id AS chunk_id, c.document_id, d.title, c.chunk_index, c.content, c.metadata,
+                       MAX(1 - (e.vector <=> $1::vector))::REAL AS similarity
+                FROM embeddings e
+                JOIN document_chunks c ON c.id = e.chunk_id
+                JOIN documents d ON d.id = c.document_id
+                JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id
+                WHERE kb.tenant_id = $2
+                    AND d.knowledge_base_id = $3
+                    AND d.status = 'completed'
+                    AND e.vector IS NOT NULL
+                    AND GREATEST(c.clearance, d.clearance) <= $4
+                    AND ($5::jsonb IS NULL OR $5::jsonb ? d.id::text)
+                    AND ($6::jsonb IS NULL OR $6::jsonb ? d.doc_type::text)
+                    AND c.metadata @> $7::jsonb
+                GROUP BY c.id, c.document_id, d.title, c.chunk_index, c.content, c.metadata
+                ORDER BY similarity DESC
+                LIMIT $8
+                "#,
+                vec![
+                    format_vector(embedding).into(),
+                    tenant_id.into(),
+                    knowledge_base_id.into(),
+                    clearance.into(),
+                    document_ids.into(),
+                    document_types.into(),
+                    metadata.into(),
+                    (limit as i64).into(),
+                ],
+            ),
+        )
+        .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<KnowledgeSearchHit, AiStudioError> {
+                let similarity: f32 = row.try_get("", "similarity")?;
+                Ok(KnowledgeSearchHit {
+                    chunk_id: row.try_get("", "chunk_id")?,
+                    document_id: row.try_get("", "document_id")?,
+                    document_title: row.try_get("", "title")?,
+                    chunk_index: row.try_get("", "chunk_index")?,
+                    content: row.try_get("", "content")?,
+                    score: similarity,
+                    vector_score: similarity,
+                    keyword_score: None,
+                    metadata: row.try_get("", "metadata")?,
+                })
+            })
+            .collect()
+    }
+
+    /// 从外部向量存储召回候选，再按租户、密级和过滤条件读取文档块
+    #[allow(clippy::too_many_arguments)]
+    async fn search_external_candidates(
+        &self,
+        vector_stores: &VectorStoreRegistry,
+        backend: VectorBackend,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        clearance: ClearanceLevel,
+        filters: &KnowledgeSearchFilters,
+        embedding: &[f32],
+        limit: u32,
+    ) -> Result<Vec<KnowledgeSearchHit>, AiStudioError> {
+        let store = vector_stores.store(backend).await?;
+        let matches = store
+            .search(
+                knowledge_base_id,
+                embedding,
+                (limit as usize).saturating_mul(EXTERNAL_STORE_CANDIDATE_FACTOR),
+                (!filters.document_ids.is_empty()).then_some(filters.document_ids.as_slice()),
+            )
+            .await?;
+        if matches.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut similarities: HashMap<Uuid, f32> = HashMap::new();
+        for m in &matches {
+            let similarity = similarities.entry(m.chunk_id).or_insert(m.similarity);
+            *similarity = similarity.max(m.similarity);
+        }
+        let chunk_ids: Vec<Uuid> = similarities.keys().copied().collect();
+        let document_types = (!filters.document_types.is_empty())
+            .then(|| serde_json::json!(filters.document_types));
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT c.id AS chunk_id, c.document_id, d.title, c.chunk_index, c.content, c.metadata
+                FROM document_chunks c
+                JOIN documents d ON d.id = c.document_id
+                JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id
+                WHERE kb.tenant_id = $2
+                    AND d.knowledge_base_id = $3
+                    AND d.status = 'completed'
+                    AND $1::jsonb ? c.id::text
+                    AND GREATEST(c.clearance, d.clearance) <= $4
+                    AND ($5::jsonb IS NULL OR $5::jsonb ? d.doc_type::text)
+                    AND c.metadata @> $6::jsonb
+                "#,
+                vec![
+                    serde_json::json!(chunk_ids).into(),
+                    tenant_id.into(),
+                    knowledge_base_id.into(),
+                    clearance.into(),
+                    document_types.into(),
+                    serde_json::json!(filters.metadata).into(),
+                ],
+            ))
+            .await?;
+
+        let mut hits = rows
+            .into_iter()
+            .map(|row| -> Result<KnowledgeSearchHit, AiStudioError> {
+                let chunk_id: Uuid = row.try_get("", "chunk_id")?;
+                let similarity = similarities.get(&chunk_id).copied().unwrap_or_default();
+                Ok(KnowledgeSearchHit {
+                    chunk_id,
+                    document_id: row.try_get("", "document_id")?,
+                    document_title: row.try_get("", "title")?,
+                    chunk_index: row.try_get("", "chunk_index")?,
+                    content: row.try_get("", "content")?,
+                    score: similarity,
+                    vector_score: similarity,
+                    keyword_score: None,
+                    metadata: row.try_get("", "metadata")?,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit as usize);
+        Ok(hits)
+    }
+
+    /// 按 BM25 得分从稀疏词法索引召回候选文档块，`keyword_score` 为按候选中最高分归一化后的得分
+    async fn search_sparse_candidates(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        clearance: ClearanceLevel,
+        filters: &KnowledgeSearchFilters,
+        query: &str,
+        limit: u32,
+    ) -> Result<Vec<KnowledgeSearchHit>, AiStudioError> {
+        let terms = query_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let document_ids = (!filters.document_ids.is_empty())
+            .then(|| serde_json::json!(filters.document_ids));
+        let document_types = (!filters.document_types.is_empty())
+            .then(|| serde_json::json!(filters.document_types));
+
+        let sql = format!(
+            r#"
+            WITH terms AS (
+                SELECT DISTINCT jsonb_array_elements_text($1::jsonb) AS term
+            ),
+            stats AS (
+                SELECT COUNT(*)::FLOAT8 AS n, GREATEST(AVG(length), 1)::FLOAT8 AS avgdl
+                FROM lexical_chunks WHERE knowledge_base_id = $3
+            ),
+            df AS (
+                SELECT p.term, COUNT(*)::FLOAT8 AS df
+                FROM lexical_postings p JOIN terms t ON t.term = p.term
+                WHERE p.knowledge_base_id = $3
+                GROUP BY p.term
+            ),
+            scores AS (
+                SELECT p.chunk_id,
+                       SUM(LN(1 + (s.n - df.df + 0.5) / (df.df + 0.5))
+                           * p.tf * ({k1} + 1) / (p.tf + {k1} * (1 - {b} + {b} * lc.length / s.avgdl))) AS bm25
+                FROM lexical_postings p
+                JOIN df ON df.term = p.term
+                JOIN lexical_chunks lc ON lc.chunk_id = p.chunk_id
+                CROSS JOIN stats s
+                WHERE p.knowledge_base_id = $3
+                GROUP BY p.chunk_id
+            )
+            SELECT c.id AS chunk_id, c.document_id, d.title, c.chunk_index, c.content, c.metadata,
+                   sc.bm25::REAL AS bm25
+            FROM scores sc
+            JOIN document_chunks c ON c.id = sc.chunk_id
+            JOIN documents d ON d.id = c.document_id
+            JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id
+            WHERE kb.tenant_id = $2
+                AND d.knowledge_base_id = $3
+                AND d.status = 'completed'
+                AND GREATEST(c.clearance, d.clearance) <= $4
+                AND ($5::jsonb IS NULL OR $5::jsonb ? d.id::text)
+                AND ($6::jsonb IS NULL OR $6::jsonb ? d.doc_type::text)
+                AND c.metadata @> $7::jsonb
+            ORDER BY bm25 DESC
+            LIMIT $8
+            "#,
+            k1 = BM25_K1,
+            b = BM25_B,
+        );
+        let rows = deadline::query_all(
+            &self.db,
+            "workflow.knowledge_search.sparse",
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                &sql,
+                vec![
+                    serde_json::json!(terms).into(),
+                    tenant_id.into(),
+                    knowledge_base_id.into(),
+                    clearance.into(),
+                    document_ids.into(),
+                    document_types.into(),
+                    serde_json::json!(filters.metadata).into(),
+                    (limit as i64).into(),
+                ],
+            ),
+        )
+        .await?;
+
+        let mut hits = rows.into_iter()
+            .map(|row| -> Result<KnowledgeSearchHit, AiStudioError> {
+                let bm25: f32 = row.try_get("", "bm25")?;
+                Ok(KnowledgeSearchHit {
+                    chunk_id: row.try_get("", "chunk_id")?,
+                    document_id: row.try_get("", "document_id")?,
+                    document_title: row.try_get("", "title")?,
+                    chunk_index: row.try_get("", "chunk_index")?,
+                    content: row.try_get("", "content")?,
+                    score: 0.0,
+                    vector_score: 0.0,
+                    keyword_score: Some(bm25),
+                    metadata: row.try_get("", "metadata")?,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        normalize_keyword_scores(&mut hits);
+        Ok(hits)
+    }
+
+    /// 为仅由词法索引召回的文档块补充向量相似度，外部向量存储的知识库保持 0
+    async fn fill_vector_scores(
+        &self,
+        knowledge_base_id: Uuid,
+        embedding: &[f32],
+        chunk_ids: &[Uuid],
+        hits: &mut [KnowledgeSearchHit],
+    ) -> Result<(), AiStudioError> {
+        if chunk_ids.is_empty() {
+            return Ok(());
+        }
+        if let Some(vector_stores) = &self.vector_stores {
+            if vector_stores.backend_of(knowledge_base_id).await? != VectorBackend::Pgvector {
+                return Ok(());
+            }
+        }
+
+        let rows = deadline::query_all(
+            &self.db,
+            "workflow.knowledge_search.vector_scores",
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT chunk_id, MAX(1 - (vector <=> $1::vector))::REAL AS similarity
+                FROM embeddings
+                WHERE knowledge_base_id = $2 AND vector IS NOT NULL AND $3::jsonb ? chunk_id::text
+                GROUP BY chunk_id
+                "#,
+                vec![format_vector(embedding).into(), knowledge_base_id.into(), serde_json::json!(chunk_ids).into()],
+            ),
+        )
+        .await?;
+        let mut similarities = HashMap::new();
+        for row in rows {
+            let chunk_id: Uuid = row.try_get("", "chunk_id")?;
+            let similarity: f32 = row.try_get("", "similarity")?;
+            similarities.insert(chunk_id, similarity);
+        }
+        for hit in hits {
+            if let Some(similarity) = similarities.get(&hit.chunk_id) {
+                hit.vector_score = *similarity;
+            }
+        }
+        Ok(())
+    }
+
+    /// 按知识库时效策略对过期文档降权或排除，同时排除已归档的文档与知识库
+    async fn apply_freshness(&self, hits: &mut Vec<KnowledgeSearchHit>) -> Result<(), AiStudioError> {
+        let document_ids: Vec<Uuid> = hits.iter().map(|hit| hit.document_id).collect();
+        let weights = DocumentFreshnessService::new(self.db.clone())
+            .retrieval_weights(&document_ids)
+            .await?;
+        hits.retain_mut(|hit| match weights.get(&hit.document_id) {
+            Some(Some(weight)) => {
+                hit.score *= weight;
+                true
+            }
+            Some(None) => false,
+            None => true,
+        });
+        Ok(())
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, AiStudioError> {
+        match (EmbeddingPool::global(), &self.embedder) {
+            (Some(pool), _) => pool.embed(text, EmbeddingPriority::Interactive).await,
+            (None, Some(embedder)) => embedder.generate_embedding(text).await.map(|r| r.embedding),
+            (None, None) => Err(AiStudioError::service_unavailable("未配置嵌入服务，无法执行知识库检索")),
+        }
+    }
+}
+
+/// 渲染查询模板，将 `{{路径}}` 替换为上下文变量中对应的值
+///
+/// 路径以 `.` 分隔逐级访问对象字段或数组下标；字符串按原文插入，其余值按 JSON 插入。
+pub fn render_query(template: &str, variables: &HashMap<String, Value>) -> Result<String, AiStudioError> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            return Err(AiStudioError::validation("query", "查询模板中的 {{ 未闭合"));
+        };
+        rendered.push_str(&rest[..start]);
+
+        let path = rest[start + 2..start + 2 + end].trim();
+        let value = lookup_variable(variables, path)
+            .ok_or_else(|| AiStudioError::validation("query", format!("查询模板引用的变量不存在: {}", path)))?;
+        match value {
+            Value::String(text) => rendered.push_str(text),
+            other => rendered.push_str(&other.to_string()),
+        }
+
+        rest = &rest[start + 2 + end + 2..];
+    }
+
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+fn lookup_variable<'a>(variables: &'a HashMap<String, Value>, path: &str) -> Option<&'a Value> {
+    let mut segments = path.split('.');
+    let mut value = variables.get(segments.next()?)?;
+    for segment in segments {
+        value = match value {
+            Value::Object(map) => map.get(segment)?,
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+/// 将 BM25 得分按候选中的最高分归一化到 0-1
+fn normalize_keyword_scores(hits: &mut [KnowledgeSearchHit]) {
+    let max = hits.iter().filter_map(|hit| hit.keyword_score).fold(0.0f32, f32::max);
+    if max <= 0.0 {
+        return;
+    }
+    for hit in hits {
+        hit.keyword_score = hit.keyword_score.map(|score| score / max);
+    }
+}
+
+/// 合并向量与词法两路候选，返回合并结果和仅由词法索引召回的文档块 ID
+fn merge_candidates(
+    dense: Vec<KnowledgeSearchHit>,
+    sparse: Vec<KnowledgeSearchHit>,
+) -> (Vec<KnowledgeSearchHit>, Vec<Uuid>) {
+    let mut keyword_scores: HashMap<Uuid, Option<f32>> =
+        sparse.iter().map(|hit| (hit.chunk_id, hit.keyword_score)).collect();
+    let dense_ids: HashSet<Uuid> = dense.iter().map(|hit| hit.chunk_id).collect();
+
+    let mut hits: Vec<KnowledgeSearchHit> = dense
+        .into_iter()
+        .map(|mut hit| {
+            hit.keyword_score = Some(keyword_scores.remove(&hit.chunk_id).flatten().unwrap_or(0.0));
+            hit
+        })
+        .collect();
+    let mut sparse_only = Vec::new();
+    for hit in sparse.into_iter().filter(|hit| !dense_ids.contains(&hit.chunk_id)) {
+        sparse_only.push(hit.chunk_id);
+        hits.push(hit);
+    }
+    (hits, sparse_only)
+}
+
+/// 融合向量相似度与 BM25 得分
+///
+/// 加权融合直接对两路得分加权求和；倒数排名融合按两路各自的排名计算，并除以两路都排第一时的得分，
+/// 使融合得分落在 0-1 之间，可与阈值比较。
+fn fuse_scores(
+    hits: &mut [KnowledgeSearchHit],
+    fusion: KnowledgeSearchFusion,
+    (vector_weight, keyword_weight): (f32, f32),
+) {
+    match fusion {
+        KnowledgeSearchFusion::Weighted => {
+            for hit in hits {
+                hit.score = hit.vector_score * vector_weight + hit.keyword_score.unwrap_or(0.0) * keyword_weight;
+            }
+        }
+        KnowledgeSearchFusion::Rrf => {
+            let vector_ranks = ranks(hits, |hit| Some(hit.vector_score));
+            let keyword_ranks = ranks(hits, |hit| hit.keyword_score);
+            let best = (vector_weight + keyword_weight) / (RRF_K + 1.0);
+            for (i, hit) in hits.iter_mut().enumerate() {
+                let rrf = vector_ranks[i].map_or(0.0, |rank| vector_weight / (RRF_K + rank as f32))
+                    + keyword_ranks[i].map_or(0.0, |rank| keyword_weight / (RRF_K + rank as f32));
+                hit.score = if best > 0.0 { rrf / best } else { 0.0 };
+            }
+        }
+    }
+}
+
+/// 按得分降序计算每个文档块的排名（从 1 开始），得分缺失或不为正的文档块不参与排名
+fn ranks(hits: &[KnowledgeSearchHit], score: impl Fn(&KnowledgeSearchHit) -> Option<f32>) -> Vec<Option<usize>> {
+    let mut order: Vec<(usize, f32)> = hits
+        .iter()
+        .enumerate()
+        .filter_map(|(i, hit)| score(hit).filter(|score| *score > 0.0).map(|score| (i, score)))
+        .collect();
+    order.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut ranks = vec![None; hits.len()];
+    for (rank, (i, _)) in order.into_iter().enumerate() {
+        ranks[i] = Some(rank + 1);
+    }
+    ranks
+}
+
+/// 拼接文档块文本，按序号标注来源文档
+fn format_context(hits: &[KnowledgeSearchHit]) -> String {
+    hits.iter()
+        .enumerate()
+        .map(|(i, hit)| format!("[{}] {}\n{}", i + 1, hit.document_title, hit.content))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn format_vector(vector: &[f32]) -> String {
+    format!("[{}]", vector.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn hit(content: &str, vector_score: f32) -> KnowledgeSearchHit {
+        KnowledgeSearchHit {
+            chunk_id: Uuid::new_v4(),
+            document_id: Uuid::new_v4(),
+            document_title: "产品手册".to_string(),
+            chunk_index: 0,
+            content: content.to_string(),
+            score: vector_score,
+            vector_score,
+            keyword_score: None,
+            metadata: json!({}),
+        }
+    }
+
+    #[test]
+    fn test_render_query() {
+        let variables = HashMap::from([
+            ("parameters".to_string(), json!({ "question": "如何重置密码" })),
+            ("classify".to_string(), json!({ "labels": ["账号", "安全"], "count": 2 })),
+        ]);
+
+        assert_eq!(
+            render_query("{{ parameters.question }} {{classify.labels.1}}", &variables).unwrap(),
+            "如何重置密码 安全"
+        );
+        assert_eq!(render_query("共 {{classify.count}} 类", &variables).unwrap(), "共 2 类");
+        assert!(render_query("{{parameters.missing}}", &variables).is_err());
+        assert!(render_query("{{parameters.question", &variables).is_err());
+    }
+
+    fn sparse_hit(content: &str, bm25: f32) -> KnowledgeSearchHit {
+        KnowledgeSearchHit { keyword_score: Some(bm25), ..hit(content, 0.0) }
+    }
+
+    #[test]
+    fn test_hybrid_fusion_surfaces_exact_identifier_matches() {
+        let dense = vec![hit("账号注册流程说明", 0.82), hit("重置密码的步骤", 0.78)];
+        let mut sparse = vec![sparse_hit("错误码 ERR-4012 表示电源故障", 7.5), sparse_hit("账号注册流程说明", 2.5)];
+        sparse[1].chunk_id = dense[0].chunk_id;
+        normalize_keyword_scores(&mut sparse);
+
+        let (mut hits, sparse_only) = merge_candidates(dense, sparse);
+        assert_eq!(hits.len(), 3);
+        assert_eq!(sparse_only, vec![hits[2].chunk_id]);
+        assert_eq!(hits[1].keyword_score, Some(0.0));
+
+        hits[2].vector_score = 0.7;
+        fuse_scores(&mut hits, KnowledgeSearchFusion::Weighted, (0.7, 0.3));
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        assert_eq!(hits[0].content, "错误码 ERR-4012 表示电源故障");
+        assert_eq!(hits[0].keyword_score, Some(1.0));
+    }
+
+    #[test]
+    fn test_rrf_fusion_is_normalized() {
+        let mut hits = vec![sparse_hit("a", 1.0), sparse_hit("b", 0.5), hit("c", 0.4)];
+        hits[0].vector_score = 0.9;
+        fuse_scores(&mut hits, KnowledgeSearchFusion::Rrf, (0.7, 0.3));
+
+        assert!((hits[0].score - 1.0).abs() < 1e-6);
+        assert!(hits[1].score < hits[0].score && hits[2].score < hits[0].score);
+        // b 只在词法排第 2，c 只在向量排第 2，向量权重更高
+        assert!(hits[2].score > hits[1].score);
+    }
+
+    #[test]
+    fn test_format_context() {
+        let hits = vec![hit("第一段", 0.9), hit("第二段", 0.8)];
+        assert_eq!(format_context(&hits), "[1] 产品手册\n第一段\n\n[2] 产品手册\n第二段");
+    }
+}
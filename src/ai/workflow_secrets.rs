@@ -0,0 +1,205 @@
+// 工作流步骤密钥注入
+// 执行步骤时将配置中的 {{secrets.NAME}} 替换为租户密钥，解析结果仅存在于内存并在日志中脱敏
+
+use std::collections::{BTreeSet, HashMap};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::ai::workflow_engine::{StepConfig, StepType, WorkflowStep};
+use crate::errors::AiStudioError;
+use crate::logging::redaction::{register_redactions, RedactionGuard, REDACTED};
+use crate::services::tenant_secret::TenantSecretService;
+
+/// 密钥占位符前缀
+const SECRETS_PREFIX: &str = "secrets.";
+
+/// 允许引用密钥的步骤类型
+///
+/// 其他步骤（如 Agent 任务、文档生成）的配置会进入模型提示词或生成内容，注入密钥会造成泄露。
+pub fn step_type_allows_secrets(step_type: &StepType) -> bool {
+    matches!(step_type, StepType::ApiCall | StepType::ToolCall)
+}
+
+/// 收集步骤配置中引用的密钥名称
+pub fn secret_references(config: &StepConfig) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    if let Ok(value) = serde_json::to_value(config) {
+        visit_strings(&value, &mut |text| {
+            for_each_placeholder(text, |inner| {
+                if let Some(name) = inner.strip_prefix(SECRETS_PREFIX) {
+                    names.insert(name.to_string());
+                }
+            });
+        });
+    }
+    names
+}
+
+/// 将步骤配置中的密钥占位符替换为密钥值，其他占位符保持不变
+pub fn inject_secrets(config: &StepConfig, secrets: &HashMap<String, String>) -> Result<StepConfig, AiStudioError> {
+    let mut value = serde_json::to_value(config)?;
+    replace_strings(&mut value, &|text| {
+        let mut rendered = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start + 2..].find("}}") else { break };
+            let placeholder = &rest[start..start + 2 + end + 2];
+            let inner = placeholder[2..placeholder.len() - 2].trim();
+
+            rendered.push_str(&rest[..start]);
+            match inner.strip_prefix(SECRETS_PREFIX).and_then(|name| secrets.get(name)) {
+                Some(secret) => rendered.push_str(secret),
+                None => rendered.push_str(placeholder),
+            }
+            rest = &rest[start + placeholder.len()..];
+        }
+        rendered.push_str(rest);
+        rendered
+    });
+    Ok(serde_json::from_value(value)?)
+}
+
+/// 注入密钥后的步骤配置
+///
+/// 仅供步骤执行时使用，不得持久化。存活期间密钥值会从所有日志输出中脱敏；
+/// 步骤输出、错误信息写入执行记录前应经过 [`ResolvedStepConfig::redact_value`] 处理。
+pub struct ResolvedStepConfig {
+    /// 注入密钥后的配置
+    pub config: StepConfig,
+    values: Vec<String>,
+    _redactions: RedactionGuard,
+}
+
+impl ResolvedStepConfig {
+    /// 替换文本中出现的密钥值
+    pub fn redact_text(&self, text: &str) -> String {
+        let mut redacted = text.to_string();
+        for value in &self.values {
+            redacted = redacted.replace(value.as_str(), REDACTED);
+        }
+        redacted
+    }
+
+    /// 替换 JSON 中所有字符串里出现的密钥值
+    pub fn redact_value(&self, value: &Value) -> Value {
+        let mut redacted = value.clone();
+        replace_strings(&mut redacted, &|text| self.redact_text(text));
+        redacted
+    }
+}
+
+impl std::fmt::Debug for ResolvedStepConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResolvedStepConfig")
+            .field("config", &REDACTED)
+            .field("secrets", &self.values.len())
+            .finish()
+    }
+}
+
+/// 步骤密钥解析器
+pub struct StepSecretResolver {
+    secrets: TenantSecretService,
+}
+
+impl StepSecretResolver {
+    /// 创建新的步骤密钥解析器
+    pub fn new(secrets: TenantSecretService) -> Self {
+        Self { secrets }
+    }
+
+    /// 从租户密钥库解析步骤引用的密钥并注入配置
+    pub async fn resolve(&self, tenant_id: Uuid, step: &WorkflowStep) -> Result<ResolvedStepConfig, AiStudioError> {
+        let names = secret_references(&step.config);
+        if !names.is_empty() && !step_type_allows_secrets(&step.step_type) {
+            return Err(AiStudioError::validation(
+                "secrets",
+                format!("步骤 {} 的类型不允许引用密钥", step.id),
+            ));
+        }
+
+        let secrets = self.secrets.resolve_secrets(tenant_id, &names).await?;
+        // 先注册脱敏再注入，保证注入后的配置出现在任何日志前已经受保护
+        let mut values: Vec<String> = secrets.values().cloned().collect();
+        values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+        let redactions = register_redactions(values.iter().cloned());
+        let config = inject_secrets(&step.config, &secrets)?;
+
+        Ok(ResolvedStepConfig { config, values, _redactions: redactions })
+    }
+}
+
+impl std::fmt::Debug for StepSecretResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StepSecretResolver").finish_non_exhaustive()
+    }
+}
+
+/// 遍历 JSON 中的全部字符串
+fn visit_strings(value: &Value, f: &mut impl FnMut(&str)) {
+    match value {
+        Value::String(text) => f(text),
+        Value::Array(items) => items.iter().for_each(|item| visit_strings(item, f)),
+        Value::Object(map) => map.values().for_each(|item| visit_strings(item, f)),
+        _ => {}
+    }
+}
+
+/// 原地替换 JSON 中的全部字符串
+fn replace_strings(value: &mut Value, f: &impl Fn(&str) -> String) {
+    match value {
+        Value::String(text) => *text = f(text),
+        Value::Array(items) => items.iter_mut().for_each(|item| replace_strings(item, f)),
+        Value::Object(map) => map.values_mut().for_each(|item| replace_strings(item, f)),
+        _ => {}
+    }
+}
+
+/// 依次处理文本中 `{{...}}` 占位符的内容（已去除首尾空白）
+fn for_each_placeholder(text: &str, mut f: impl FnMut(&str)) {
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else { return };
+        f(rest[start + 2..start + 2 + end].trim());
+        rest = &rest[start + 2 + end + 2..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_call() -> StepConfig {
+        serde_json::from_value(serde_json::json!({
+            "type": "api_call",
+            "url": "https://crm.example.com/{{parameters.account}}/sync",
+            "method": "POST",
+            "headers": { "Authorization": "Bearer {{ secrets.CRM_TOKEN }}" },
+            "body": { "signature": "{{secrets.SIGNING_KEY}}", "note": "{{secrets.CRM_TOKEN" }
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_secret_references() {
+        let names: Vec<String> = secret_references(&api_call()).into_iter().collect();
+        assert_eq!(names, vec!["CRM_TOKEN".to_string(), "SIGNING_KEY".to_string()]);
+        assert!(step_type_allows_secrets(&StepType::ApiCall));
+        assert!(!step_type_allows_secrets(&StepType::AgentTask));
+    }
+
+    #[test]
+    fn test_inject_secrets_keeps_other_placeholders() {
+        let secrets = HashMap::from([
+            ("CRM_TOKEN".to_string(), "tok-123456".to_string()),
+            ("SIGNING_KEY".to_string(), "sig-abcdef".to_string()),
+        ]);
+        let StepConfig::ApiCall { url, headers, body, .. } = inject_secrets(&api_call(), &secrets).unwrap() else {
+            panic!("步骤配置类型不应改变");
+        };
+        assert_eq!(url, "https://crm.example.com/{{parameters.account}}/sync");
+        assert_eq!(headers["Authorization"], "Bearer tok-123456");
+        let body = body.unwrap();
+        assert_eq!(body["signature"], "sig-abcdef");
+        assert_eq!(body["note"], "{{secrets.CRM_TOKEN");
+    }
+}
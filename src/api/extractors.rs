@@ -323,6 +323,34 @@ impl FromRequest for RequestIdExtractor {
     }
 }
 
+/// `If-Match` 修订号提取器
+///
+/// 未携带请求头或值为 `*` 时 `revision` 为空，由调用方以读取时的修订号作为期望值。
+#[derive(Debug, Clone)]
+pub struct IfMatchExtractor {
+    pub revision: Option<i32>,
+}
+
+impl FromRequest for IfMatchExtractor {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let revision = match req.headers().get("If-Match") {
+            Some(value) => value
+                .to_str()
+                .map_err(|_| actix_web::error::ErrorBadRequest("无效的 If-Match 请求头"))
+                .and_then(|value| {
+                    crate::db::parse_revision_etag(value)
+                        .map_err(|e| actix_web::error::ErrorBadRequest(e.to_string()))
+                }),
+            None => Ok(None),
+        };
+
+        ready(revision.map(|revision| IfMatchExtractor { revision }))
+    }
+}
+
 /// 内容类型验证提取器
 #[derive(Debug, Clone)]
 pub struct JsonContentTypeExtractor;
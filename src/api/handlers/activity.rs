@@ -0,0 +1,53 @@
+// 租户活动时间线 API 处理器
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use sea_orm::DatabaseConnection;
+
+use crate::api::middleware::auth::{AuthenticatedUser, PermissionChecker};
+use crate::api::middleware::tenant::TenantInfo;
+use crate::api::responses::HttpResponseBuilder;
+use crate::errors::AiStudioError;
+use crate::services::tenant_activity::{ActivityQuery, TenantActivityService};
+
+/// 获取租户活动时间线
+///
+/// 汇总添加文档、修改智能体、发布工作流与邀请成员等变更，按时间倒序返回；
+/// 将响应中的 `next_cursor` 作为 `cursor` 传入获取下一页。
+#[utoipa::path(
+    get,
+    path = "/api/v1/activity",
+    params(ActivityQuery),
+    responses(
+        (status = 200, description = "活动时间线", body = ActivityPage),
+        (status = 400, description = "活动类型或游标无效", body = ApiError),
+        (status = 403, description = "需要租户管理员权限", body = ApiError)
+    ),
+    tag = "activity",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_tenant_activity(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    query: web::Query<ActivityQuery>,
+) -> ActixResult<HttpResponse> {
+    if !PermissionChecker::has_role(&user, "admin") {
+        return Err(AiStudioError::forbidden("需要租户管理员权限").into());
+    }
+
+    let page = TenantActivityService::new(db.get_ref().clone())
+        .list(tenant_info.id, &query)
+        .await?;
+
+    HttpResponseBuilder::ok(page)
+}
+
+/// 配置活动时间线路由
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/activity")
+            .route("", web::get().to(list_tenant_activity))
+    );
+}
@@ -0,0 +1,275 @@
+// 平台管理概览 API 处理器
+
+use std::sync::Arc;
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Result as ActixResult};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::api::extractors::AdminExtractor;
+use crate::api::responses::HttpResponseBuilder;
+use crate::config::{ConfigDiagnostics, ConfigLoader};
+use crate::db::DatabaseManager;
+use crate::errors::AiStudioError;
+use crate::plugins::plugin_manager::PluginManager;
+use crate::services::admin::AdminDashboardService;
+use crate::services::agent_definition::DefinitionFormat;
+use crate::services::cache::CacheService;
+use crate::services::manifest::{Manifest, ManifestApplyQuery, ManifestService};
+use crate::services::replication::ReplicationService;
+use crate::services::task_queue::TaskQueueService;
+
+/// 默认统计窗口（小时）
+const DEFAULT_WINDOW_HOURS: u32 = 24;
+
+/// 最大统计窗口（小时）
+const MAX_WINDOW_HOURS: u32 = 24 * 30;
+
+/// 概览查询参数
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct OverviewQuery {
+    /// 执行统计窗口（小时），默认 24，最大 720
+    pub window_hours: Option<u32>,
+}
+
+impl OverviewQuery {
+    fn window_hours(&self) -> Result<u32, AiStudioError> {
+        match self.window_hours {
+            None => Ok(DEFAULT_WINDOW_HOURS),
+            Some(hours) if hours == 0 || hours > MAX_WINDOW_HOURS => Err(AiStudioError::validation(
+                "window_hours",
+                format!("统计窗口必须在 1-{} 小时之间", MAX_WINDOW_HOURS),
+            )),
+            Some(hours) => Ok(hours),
+        }
+    }
+}
+
+/// 配置验证查询参数
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct ConfigValidationQuery {
+    /// 是否跳过依赖服务连通性检查，默认 false
+    pub offline: Option<bool>,
+}
+
+/// 获取平台概览
+#[utoipa::path(
+    get,
+    path = "/admin/overview",
+    tag = "admin",
+    params(OverviewQuery),
+    responses(
+        (status = 200, description = "平台概览", body = PlatformOverview),
+        (status = 403, description = "需要管理员权限")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_platform_overview(
+    query: web::Query<OverviewQuery>,
+    _admin: AdminExtractor,
+) -> ActixResult<HttpResponse> {
+    let window_hours = query.window_hours()?;
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let service = AdminDashboardService::new(db_manager.get_connection().clone());
+
+    let overview = service.get_overview(window_hours).await?;
+    HttpResponseBuilder::ok(overview)
+}
+
+/// 获取租户下钻概览
+#[utoipa::path(
+    get,
+    path = "/admin/tenants/{tenant_id}/overview",
+    tag = "admin",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID"),
+        OverviewQuery
+    ),
+    responses(
+        (status = 200, description = "租户概览", body = TenantOverview),
+        (status = 403, description = "需要管理员权限"),
+        (status = 404, description = "租户不存在")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_tenant_overview(
+    path: web::Path<Uuid>,
+    query: web::Query<OverviewQuery>,
+    _admin: AdminExtractor,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let window_hours = query.window_hours()?;
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let service = AdminDashboardService::new(db_manager.get_connection().clone());
+
+    let overview = service.get_tenant_overview(tenant_id, window_hours).await?;
+    HttpResponseBuilder::ok(overview)
+}
+
+/// 验证运行中的配置
+///
+/// 检查各配置段、安全设置以及数据库、Redis、AI 服务与 SMTP 的连通性，密钥不会出现在结果中。
+#[utoipa::path(
+    get,
+    path = "/admin/config/validate",
+    tag = "admin",
+    params(ConfigValidationQuery),
+    responses(
+        (status = 200, description = "配置诊断报告", body = ConfigValidationReport),
+        (status = 403, description = "需要管理员权限")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn validate_config(
+    query: web::Query<ConfigValidationQuery>,
+    _admin: AdminExtractor,
+) -> ActixResult<HttpResponse> {
+    let report = ConfigDiagnostics::run(ConfigLoader::get(), !query.offline.unwrap_or(false)).await;
+    HttpResponseBuilder::ok(report)
+}
+
+/// 获取跨区域复制状态
+#[utoipa::path(
+    get,
+    path = "/admin/replication",
+    tag = "admin",
+    responses(
+        (status = 200, description = "复制延迟状态", body = ReplicationLagStatus),
+        (status = 403, description = "需要管理员权限"),
+        (status = 503, description = "未启用跨区域复制")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_replication_status(_admin: AdminExtractor) -> ActixResult<HttpResponse> {
+    let status = replication_service()?.lag_status().await?;
+    HttpResponseBuilder::ok(status)
+}
+
+/// 将备用区域提升为主区域
+///
+/// 排空队列中已发布的复制事件后切换为主区域，结果中包含需要重新生成向量的分块数。
+#[utoipa::path(
+    post,
+    path = "/admin/replication/promote",
+    tag = "admin",
+    responses(
+        (status = 200, description = "提升结果", body = PromotionReport),
+        (status = 403, description = "需要管理员权限"),
+        (status = 409, description = "当前区域已经是主区域"),
+        (status = 503, description = "未启用跨区域复制")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn promote_region(_admin: AdminExtractor) -> ActixResult<HttpResponse> {
+    let report = replication_service()?.promote().await?;
+    HttpResponseBuilder::ok(report)
+}
+
+fn replication_service() -> Result<std::sync::Arc<ReplicationService>, AiStudioError> {
+    ReplicationService::global()
+        .ok_or_else(|| AiStudioError::service_unavailable("未启用跨区域复制"))
+}
+
+/// 获取热点实体缓存指标
+#[utoipa::path(
+    get,
+    path = "/admin/cache",
+    tag = "admin",
+    responses(
+        (status = 200, description = "各命名空间的缓存命中率", body = CacheMetrics),
+        (status = 403, description = "需要管理员权限"),
+        (status = 503, description = "未启用缓存")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_cache_metrics(_admin: AdminExtractor) -> ActixResult<HttpResponse> {
+    let cache = CacheService::global()
+        .ok_or_else(|| AiStudioError::service_unavailable("未启用缓存"))?;
+    HttpResponseBuilder::ok(cache.metrics())
+}
+
+/// 获取任务队列各优先级通道的排队情况
+#[utoipa::path(
+    get,
+    path = "/admin/task-queue",
+    tag = "admin",
+    responses(
+        (status = 200, description = "各优先级通道的排队与执行任务数", body = TaskQueueMetrics),
+        (status = 403, description = "需要管理员权限"),
+        (status = 503, description = "任务队列未初始化")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_task_queue_metrics(_admin: AdminExtractor) -> ActixResult<HttpResponse> {
+    let queue = TaskQueueService::global()
+        .ok_or_else(|| AiStudioError::service_unavailable("任务队列未初始化"))?;
+    HttpResponseBuilder::ok(queue.metrics().await)
+}
+
+/// 应用期望状态清单
+///
+/// 按清单创建或更新租户、知识库、Agent、工作流与插件配置，已与清单一致的资源保持不变，可重复执行；
+/// `dry_run=true` 时只返回变更计划。
+#[utoipa::path(
+    post,
+    path = "/admin/manifest/apply",
+    tag = "admin",
+    params(ManifestApplyQuery),
+    request_body(content = Manifest, content_type = "application/yaml"),
+    responses(
+        (status = 200, description = "变更计划或应用结果", body = ManifestPlan),
+        (status = 400, description = "清单无效"),
+        (status = 403, description = "需要管理员权限")
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn apply_manifest(
+    req: HttpRequest,
+    query: web::Query<ManifestApplyQuery>,
+    admin: AdminExtractor,
+    plugin_manager: web::Data<Arc<PluginManager>>,
+    body: String,
+) -> ActixResult<HttpResponse> {
+    let format = query.format.unwrap_or_else(|| {
+        let content_type = req.headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        DefinitionFormat::from_content_type(content_type)
+    });
+    let manifest: Manifest = format.deserialize(&body)?;
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let service = ManifestService::new(
+        db_manager.get_connection().clone(),
+        &ConfigLoader::get().security.encryption_key,
+    )
+    .with_plugin_manager(plugin_manager.get_ref().clone());
+
+    let plan = if query.dry_run {
+        service.plan(&manifest).await?
+    } else {
+        service.apply(&manifest, admin.user.user_id).await?
+    };
+    HttpResponseBuilder::ok(plan)
+}
+
+/// 配置平台管理路由
+pub fn configure_admin_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/admin")
+            .route("/overview", web::get().to(get_platform_overview))
+            .route("/tenants/{tenant_id}/overview", web::get().to(get_tenant_overview))
+            .route("/config/validate", web::get().to(validate_config))
+            .route("/replication", web::get().to(get_replication_status))
+            .route("/replication/promote", web::post().to(promote_region))
+            .route("/cache", web::get().to(get_cache_metrics))
+            .route("/task-queue", web::get().to(get_task_queue_metrics))
+            .route("/manifest/apply", web::post().to(apply_manifest))
+    );
+}
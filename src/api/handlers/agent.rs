@@ -1,17 +1,42 @@
 // Agent 管理 API 处理器
 
 use std::sync::Arc;
-use actix_web::{web, HttpResponse, Result as ActixResult};
+use std::time::Duration;
+use actix_web::{http::header, web, Either, HttpRequest, HttpResponse, Responder, Result as ActixResult};
+use actix_web_lab::sse::{self, Sse};
+use futures::stream;
+use tokio::sync::broadcast::error::RecvError;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use tracing::{info, error, debug};
+use tracing::{info, error, debug, warn};
 use utoipa::ToSchema;
 
+use crate::ai::agent_stream;
 use crate::ai::agent_runtime::{
-    AgentRuntime, AgentConfig, AgentTask, TaskPriority, TaskStatus, AgentState, ReasoningStrategy
+    AgentRuntime, AgentConfig, AgentTask, TaskPriority, TaskStatus, AgentState, ReasoningStrategy,
+    KnowledgeBaseBinding, RESPONSE_PREFERENCES_PARAM,
 };
+use crate::ai::model_policy::{check_model_allowed, load_tenant_model_policy};
+use crate::ai::structured_output::{check_output_schema, StructuredOutput};
+use crate::api::middleware::auth::AuthenticatedUser;
 use crate::api::middleware::tenant::TenantInfo;
+use crate::db::entities::execution_event::ExecutionType;
+use crate::db::DatabaseManager;
 use crate::errors::AiStudioError;
+use crate::config::ConfigLoader;
+use crate::plugins::event_bus::{event_types, SystemEvent, SystemEventBus};
+use crate::services::agent_definition::{AgentDefinitionService, DefinitionFormat, DefinitionFormatQuery};
+use crate::services::agent_memory::AgentMemoryService;
+use crate::services::execution_artifact::ExecutionArtifactService;
+use crate::services::web_search::WebSearchDomainPolicy;
+use crate::services::execution_event::{
+    ExecutionEventService, ExecutionStepQuery, ExecutionStepsQuery, ExecutionTimelineQuery, TracePagePolicy,
+};
+use crate::services::canary::{CanaryService, CreateCanaryRequest, EndCanaryRequest};
+use crate::services::execution_replay::{
+    ExecutionFeedbackRequest, ExecutionRecordService, ExecutionRecordSummary, ReplayExecutionRequest,
+};
+use crate::services::user_preferences::UserPreferenceService;
 
 /// Agent 创建请求
 #[derive(Debug, Deserialize, ToSchema)]
@@ -32,6 +57,15 @@ pub struct CreateAgentRequest {
     /// 最大令牌数
     #[serde(default = "default_max_tokens")]
     pub max_tokens: u32,
+    /// 使用的模型（可选，未指定时使用默认模型）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 绑定的知识库（可选），搜索工具只在绑定的知识库中检索
+    #[serde(default)]
+    pub knowledge_bases: Vec<KnowledgeBaseBinding>,
+    /// 联网搜索的域名策略（可选）
+    #[serde(default)]
+    pub web_search: WebSearchDomainPolicy,
 }
 
 fn default_temperature() -> f32 { 0.7 }
@@ -49,7 +83,7 @@ pub struct CreateAgentResponse {
 }
 
 /// Agent 任务执行请求
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ExecuteTaskRequest {
     /// 任务描述
     pub description: String,
@@ -63,6 +97,8 @@ pub struct ExecuteTaskRequest {
     pub priority: TaskPriority,
     /// 截止时间
     pub deadline: Option<chrono::DateTime<chrono::Utc>>,
+    /// 期望的输出 JSON Schema（可选，指定时校验执行结果并返回结构化输出）
+    pub output_schema: Option<serde_json::Value>,
 }
 
 /// Agent 任务执行响应
@@ -76,6 +112,8 @@ pub struct ExecuteTaskResponse {
     pub status: TaskStatus,
     /// 执行时间（毫秒）
     pub execution_time_ms: u64,
+    /// 结构化输出（请求指定输出模式时返回）
+    pub structured_output: Option<StructuredOutput>,
 }
 
 /// Agent 状态响应
@@ -163,10 +201,23 @@ pub struct AgentInfo {
 pub async fn create_agent(
     agent_runtime: web::Data<Arc<AgentRuntime>>,
     tenant_info: web::ReqData<TenantInfo>,
+    user: Option<web::ReqData<AuthenticatedUser>>,
     request: web::Json<CreateAgentRequest>,
 ) -> ActixResult<HttpResponse> {
     debug!("创建 Agent: tenant_id={}", tenant_info.id);
     
+    if let Some(model) = &request.model {
+        let db_manager = DatabaseManager::get()
+            .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+        let policy = load_tenant_model_policy(db_manager.get_connection(), tenant_info.id).await?;
+        if let Err(e) = check_model_allowed(&policy, "model", model) {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "模型不可用",
+                "message": e.to_string()
+            })));
+        }
+    }
+    
     let config = AgentConfig {
         name: request.name.clone(),
         description: request.description.clone(),
@@ -175,13 +226,23 @@ pub async fn create_agent(
         reasoning_strategy: request.reasoning_strategy.clone(),
         temperature: request.temperature,
         max_tokens: request.max_tokens,
+        model: request.model.clone(),
         tenant_id: tenant_info.id,
         created_by: Uuid::new_v4(), // TODO: 从认证中间件获取用户ID
+        knowledge_bases: request.knowledge_bases.clone(),
+        web_search: request.web_search.clone(),
     };
     
     match agent_runtime.create_agent(config).await {
         Ok(agent_id) => {
             info!("Agent 创建成功: agent_id={}, tenant_id={}", agent_id, tenant_info.id);
+            publish_agent_event(
+                event_types::AGENT_CREATED,
+                tenant_info.id,
+                user.map(|u| u.user_id),
+                agent_id,
+                &request.name,
+            );
             
             let response = CreateAgentResponse {
                 agent_id,
@@ -191,6 +252,8 @@ pub async fn create_agent(
             
             Ok(HttpResponse::Created().json(response))
         }
+        // 知识库绑定无效时按校验错误返回
+        Err(e @ (AiStudioError::Validation { .. } | AiStudioError::NotFound { .. })) => Err(e.into()),
         Err(e) => {
             error!("创建 Agent 失败: {}", e);
             Ok(HttpResponse::InternalServerError().json(serde_json::json!({
@@ -220,37 +283,121 @@ pub async fn create_agent(
 pub async fn execute_task(
     agent_runtime: web::Data<Arc<AgentRuntime>>,
     tenant_info: web::ReqData<TenantInfo>,
+    user: Option<web::ReqData<AuthenticatedUser>>,
     path: web::Path<Uuid>,
     request: web::Json<ExecuteTaskRequest>,
 ) -> ActixResult<HttpResponse> {
     let agent_id = path.into_inner();
     debug!("执行 Agent 任务: agent_id={}, tenant_id={}", agent_id, tenant_info.id);
     
+    run_agent_task(&agent_runtime, &tenant_info, user.as_deref(), agent_id, request.into_inner(), None).await
+}
+
+/// 执行 Agent 任务并保存执行记录，`replay_of` 为重放的原始执行 ID
+async fn run_agent_task(
+    agent_runtime: &AgentRuntime,
+    tenant_info: &TenantInfo,
+    user: Option<&AuthenticatedUser>,
+    agent_id: Uuid,
+    request: ExecuteTaskRequest,
+    replay_of: Option<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let mut parameters = request.parameters.clone();
+    if let Some(schema) = &request.output_schema {
+        if let Err(e) = check_output_schema(schema) {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "输出模式无效",
+                "message": e.to_string()
+            })));
+        }
+        // 将输出模式随任务参数下发，供 Agent 按格式组织结果
+        parameters.insert("output_schema".to_string(), schema.clone());
+    }
+    
+    // 将发起用户的偏好随任务参数下发，供 Agent 调整回答语言、详略和引用方式
+    if let Some(user) = &user {
+        let db_manager = DatabaseManager::get()
+            .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+        let preferences = UserPreferenceService::new(db_manager.get_connection().clone())
+            .preferences_or_default(user.user_id)
+            .await;
+        parameters.insert(RESPONSE_PREFERENCES_PARAM.to_string(), serde_json::to_value(&preferences)?);
+    }
+    
     let task = AgentTask {
         task_id: Uuid::new_v4(),
         description: request.description.clone(),
         objective: request.objective.clone(),
-        parameters: request.parameters.clone(),
+        parameters,
         priority: request.priority.clone(),
         status: TaskStatus::Pending,
         created_at: chrono::Utc::now(),
         deadline: request.deadline,
     };
     
+    // 入口有灰度发布时按用户分流到稳定版本或灰度版本
+    let canary = canary_releases();
+    let route = match &canary {
+        Some(canary) => canary
+            .route(tenant_info.id, ExecutionType::Agent, agent_id, user.map(|user| user.user_id))
+            .await
+            .unwrap_or_else(|e| {
+                warn!("灰度分流失败，使用入口 Agent: agent_id={}, error={}", agent_id, e);
+                None
+            }),
+        None => None,
+    };
+    let serve_id = route.map(|route| route.serve_id).unwrap_or(agent_id);
+    let release_id = route.and_then(|route| route.release_id);
+    
+    let records = execution_records();
+    if let Some(records) = &records {
+        let input = serde_json::to_value(&request)?;
+        let triggered_by = user.map(|user| user.user_id);
+        let mut recorded = records
+            .start(tenant_info.id, ExecutionType::Agent, task.task_id, agent_id, input, triggered_by, replay_of)
+            .await;
+        if let (Ok(()), Some(route), Some(release_id)) = (&recorded, route, release_id) {
+            recorded = records.tag_canary(task.task_id, release_id, route.arm.as_str()).await;
+        }
+        if let Err(e) = recorded {
+            warn!("记录 Agent 执行失败: task_id={}, error={}", task.task_id, e);
+        }
+    }
+    
     let start_time = std::time::Instant::now();
+    let outcome = agent_runtime.execute_task(serve_id, task.clone()).await;
+    let execution_time = start_time.elapsed().as_millis() as u64;
+    if let Some(records) = &records {
+        let (status, output, error) = match &outcome {
+            Ok(result) => ("completed", Some(result.clone()), None),
+            Err(e) => ("failed", None, Some(e.to_string())),
+        };
+        if let Err(e) = records.finish(task.task_id, status, output, error, execution_time as i64).await {
+            warn!("更新 Agent 执行记录失败: task_id={}, error={}", task.task_id, e);
+        }
+    }
+    if let (Some(canary), Some(release_id)) = (&canary, release_id) {
+        if let Err(e) = canary.evaluate(release_id).await {
+            warn!("检查灰度发布失败: release_id={}, error={}", release_id, e);
+        }
+    }
     
-    match agent_runtime.execute_task(agent_id, task.clone()).await {
+    match outcome {
         Ok(result) => {
-            let execution_time = start_time.elapsed().as_millis() as u64;
+
+            info!("Agent 任务执行成功: agent_id={}, served_by={}, task_id={}, 执行时间={}ms", 
+                  agent_id, serve_id, task.task_id, execution_time);
             
-            info!("Agent 任务执行成功: agent_id={}, task_id={}, 执行时间={}ms", 
-                  agent_id, task.task_id, execution_time);
+            let structured_output = request.output_schema.as_ref()
+                .map(|schema| StructuredOutput::evaluate_value(&result, schema));
             
             let response = ExecuteTaskResponse {
                 task_id: task.task_id,
                 result,
                 status: TaskStatus::Completed,
                 execution_time_ms: execution_time,
+                structured_output,
             };
             
             Ok(HttpResponse::Ok().json(response))
@@ -265,6 +412,566 @@ pub async fn execute_task(
     }
 }
 
+/// 获取 Agent 执行时间线
+///
+/// 由状态转换事件重建，携带 `at` 参数时同时返回该时间点的状态。
+#[utoipa::path(
+    get,
+    path = "/api/v1/agents/{agent_id}/timeline",
+    responses(
+        (status = 200, description = "获取时间线成功", body = ExecutionTimeline),
+        (status = 404, description = "没有该 Agent 的执行事件"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "Agent ID"),
+        ExecutionTimelineQuery
+    ),
+    tag = "agents"
+)]
+pub async fn get_agent_timeline(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    query: web::Query<ExecutionTimelineQuery>,
+) -> ActixResult<HttpResponse> {
+    let agent_id = path.into_inner();
+    debug!("获取 Agent 执行时间线: agent_id={}, tenant_id={}", agent_id, tenant_info.id);
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let timeline = ExecutionEventService::new(db_manager.get_connection().clone())
+        .with_artifacts(ExecutionArtifactService::from_app_config(
+            db_manager.get_connection().clone(),
+            ConfigLoader::get(),
+        ))
+        .timeline(tenant_info.id, ExecutionType::Agent, agent_id, query.at)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(timeline))
+}
+
+/// 分页获取 Agent 执行步骤
+///
+/// 长时间运行的 Agent 轨迹可能很大：转存的产物保留指针，超过预览大小的步骤数据只返回预览，
+/// `expandable` 为真的步骤可按序号展开；将响应中的 `next_cursor` 作为 `cursor` 传入获取下一页。
+#[utoipa::path(
+    get,
+    path = "/api/v1/agents/{agent_id}/steps",
+    responses(
+        (status = 200, description = "获取执行步骤成功", body = ExecutionTracePage),
+        (status = 400, description = "分页游标无效"),
+        (status = 404, description = "没有该 Agent 的执行事件"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "Agent ID"),
+        ExecutionStepsQuery
+    ),
+    tag = "agents"
+)]
+pub async fn get_agent_steps(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    query: web::Query<ExecutionStepsQuery>,
+) -> ActixResult<HttpResponse> {
+    let agent_id = path.into_inner();
+    debug!("分页获取 Agent 执行步骤: agent_id={}, tenant_id={}", agent_id, tenant_info.id);
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let page = ExecutionEventService::new(db_manager.get_connection().clone())
+        .steps(
+            tenant_info.id,
+            ExecutionType::Agent,
+            agent_id,
+            &query,
+            TracePagePolicy::from_config(&ConfigLoader::get().execution_artifacts),
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
+
+/// 展开 Agent 执行步骤
+///
+/// 返回步骤的完整数据并取回转存的产物；指定 `pointer` 时只返回其中一部分。
+#[utoipa::path(
+    get,
+    path = "/api/v1/agents/{agent_id}/steps/{sequence}",
+    responses(
+        (status = 200, description = "获取执行步骤成功", body = ExecutionTraceStep),
+        (status = 400, description = "JSON Pointer 无效"),
+        (status = 404, description = "步骤或指定部分不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "Agent ID"),
+        ("sequence" = i32, Path, description = "步骤序号"),
+        ExecutionStepQuery
+    ),
+    tag = "agents"
+)]
+pub async fn get_agent_step(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<(Uuid, i32)>,
+    query: web::Query<ExecutionStepQuery>,
+) -> ActixResult<HttpResponse> {
+    let (agent_id, sequence) = path.into_inner();
+    debug!("展开 Agent 执行步骤: agent_id={}, sequence={}, tenant_id={}", agent_id, sequence, tenant_info.id);
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let step = ExecutionEventService::new(db_manager.get_connection().clone())
+        .with_artifacts(ExecutionArtifactService::from_app_config(
+            db_manager.get_connection().clone(),
+            ConfigLoader::get(),
+        ))
+        .step(tenant_info.id, ExecutionType::Agent, agent_id, sequence, query.pointer.as_deref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(step))
+}
+
+/// 导出 Agent 定义文件
+///
+/// 导出的 YAML/JSON 不含 ID 与租户信息，可纳入版本管理或导入其他环境。
+#[utoipa::path(
+    get,
+    path = "/api/v1/agents/{agent_id}/definition",
+    responses(
+        (status = 200, description = "Agent 定义文件", body = AgentDefinition),
+        (status = 404, description = "Agent 不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "Agent ID"),
+        DefinitionFormatQuery
+    ),
+    tag = "agents"
+)]
+pub async fn export_agent_definition(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    query: web::Query<DefinitionFormatQuery>,
+) -> ActixResult<HttpResponse> {
+    let agent_id = path.into_inner();
+    let format = query.format.unwrap_or_default();
+    debug!("导出 Agent 定义: agent_id={}, tenant_id={}, format={:?}", agent_id, tenant_info.id, format);
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let definition = AgentDefinitionService::new(db_manager.get_connection().clone())
+        .export(tenant_info.id, agent_id)
+        .await?;
+    let content = format.render(&definition)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.agent.{}\"", agent_id, format.extension()),
+        ))
+        .body(content))
+}
+
+/// 导入 Agent 定义文件
+///
+/// 请求体为 YAML 或 JSON 定义文件；租户内已有同名 Agent 时更新其定义，否则新建。
+#[utoipa::path(
+    post,
+    path = "/api/v1/agents/definitions/import",
+    request_body(content = AgentDefinition, content_type = "application/yaml"),
+    responses(
+        (status = 200, description = "已更新同名 Agent", body = AgentImportResult),
+        (status = 201, description = "已新建 Agent", body = AgentImportResult),
+        (status = 400, description = "定义文件无效"),
+        (status = 409, description = "Agent 已被并发修改"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(DefinitionFormatQuery),
+    tag = "agents"
+)]
+pub async fn import_agent_definition(
+    req: HttpRequest,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    query: web::Query<DefinitionFormatQuery>,
+    body: String,
+) -> ActixResult<HttpResponse> {
+    let format = query.format.unwrap_or_else(|| {
+        let content_type = req.headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        DefinitionFormat::from_content_type(content_type)
+    });
+    let definition = format.parse(&body)?;
+    info!("导入 Agent 定义: name={}, tenant_id={}", definition.name, tenant_info.id);
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let result = AgentDefinitionService::new(db_manager.get_connection().clone())
+        .import(tenant_info.id, user.user_id, definition)
+        .await?;
+    let event_type = if result.created { event_types::AGENT_CREATED } else { event_types::AGENT_UPDATED };
+    publish_agent_event(event_type, tenant_info.id, Some(user.user_id), result.agent_id, &result.name);
+
+    if result.created {
+        Ok(HttpResponse::Created().json(result))
+    } else {
+        Ok(HttpResponse::Ok().json(result))
+    }
+}
+
+/// 获取 Agent 执行的记忆快照
+///
+/// 返回任务执行结束时各层级的记忆项及其重要性，以及每个推理步骤检索到的记忆。
+#[utoipa::path(
+    get,
+    path = "/api/v1/agents/{agent_id}/executions/{execution_id}/memory",
+    responses(
+        (status = 200, description = "获取记忆快照成功", body = ExecutionMemory),
+        (status = 404, description = "记忆快照不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "Agent ID"),
+        ("execution_id" = Uuid, Path, description = "执行 ID（任务 ID）")
+    ),
+    tag = "agents"
+)]
+pub async fn get_execution_memory(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ActixResult<HttpResponse> {
+    let (agent_id, execution_id) = path.into_inner();
+    debug!("获取 Agent 记忆快照: agent_id={}, execution_id={}, tenant_id={}", agent_id, execution_id, tenant_info.id);
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let memory = AgentMemoryService::new(db_manager.get_connection().clone())
+        .get(tenant_info.id, agent_id, execution_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(memory))
+}
+
+/// 重放 Agent 执行
+///
+/// 使用原始执行记录的输入（可通过 `input_patch` 局部修改）在 Agent 当前配置上重新执行，
+/// 新执行关联到原始执行，可通过重放历史对比输出、耗时与令牌用量。
+#[utoipa::path(
+    post,
+    path = "/api/v1/agents/{agent_id}/executions/{execution_id}/replay",
+    request_body = ReplayExecutionRequest,
+    responses(
+        (status = 200, description = "重放执行完成", body = ExecuteTaskResponse),
+        (status = 400, description = "修改后的输入无效"),
+        (status = 404, description = "执行记录不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "Agent ID"),
+        ("execution_id" = Uuid, Path, description = "原始执行 ID（任务 ID）")
+    ),
+    tag = "agents"
+)]
+pub async fn replay_agent_execution(
+    agent_runtime: web::Data<Arc<AgentRuntime>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: Option<web::ReqData<AuthenticatedUser>>,
+    path: web::Path<(Uuid, Uuid)>,
+    request: Option<web::Json<ReplayExecutionRequest>>,
+) -> ActixResult<HttpResponse> {
+    let (agent_id, execution_id) = path.into_inner();
+    info!("重放 Agent 执行: agent_id={}, execution_id={}, tenant_id={}", agent_id, execution_id, tenant_info.id);
+
+    let records = execution_records().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let patch = request.and_then(|request| request.into_inner().input_patch);
+    let (original, input) = records
+        .replay_input(tenant_info.id, ExecutionType::Agent, execution_id, patch.as_ref())
+        .await?;
+    if original.target_id != agent_id {
+        return Err(AiStudioError::not_found("执行记录").into());
+    }
+    let task_request: ExecuteTaskRequest = serde_json::from_value(input)
+        .map_err(|e| AiStudioError::validation("input_patch", format!("修改后的输入无效: {}", e)))?;
+
+    run_agent_task(&agent_runtime, &tenant_info, user.as_deref(), agent_id, task_request, Some(execution_id)).await
+}
+
+/// 获取 Agent 执行的重放历史
+#[utoipa::path(
+    get,
+    path = "/api/v1/agents/{agent_id}/executions/{execution_id}/replays",
+    responses(
+        (status = 200, description = "获取重放历史成功", body = ReplayHistory),
+        (status = 404, description = "执行记录不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "Agent ID"),
+        ("execution_id" = Uuid, Path, description = "原始执行 ID（任务 ID）")
+    ),
+    tag = "agents"
+)]
+pub async fn get_agent_replays(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ActixResult<HttpResponse> {
+    let (agent_id, execution_id) = path.into_inner();
+    debug!("获取 Agent 重放历史: agent_id={}, execution_id={}, tenant_id={}", agent_id, execution_id, tenant_info.id);
+
+    let records = execution_records().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let history = records.history(tenant_info.id, ExecutionType::Agent, execution_id).await?;
+    if history.original.target_id != agent_id {
+        return Err(AiStudioError::not_found("执行记录").into());
+    }
+
+    Ok(HttpResponse::Ok().json(history))
+}
+
+/// 提交 Agent 执行反馈
+///
+/// 负面反馈计入灰度发布的负面反馈率，超过阈值时自动回滚。
+#[utoipa::path(
+    post,
+    path = "/api/v1/agents/{agent_id}/executions/{execution_id}/feedback",
+    request_body = ExecutionFeedbackRequest,
+    responses(
+        (status = 200, description = "反馈已记录", body = ExecutionRecordSummary),
+        (status = 404, description = "执行记录不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "Agent ID"),
+        ("execution_id" = Uuid, Path, description = "执行 ID（任务 ID）")
+    ),
+    tag = "agents"
+)]
+pub async fn submit_agent_feedback(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<(Uuid, Uuid)>,
+    request: web::Json<ExecutionFeedbackRequest>,
+) -> ActixResult<HttpResponse> {
+    let (agent_id, execution_id) = path.into_inner();
+    debug!("提交 Agent 执行反馈: agent_id={}, execution_id={}, tenant_id={}", agent_id, execution_id, tenant_info.id);
+
+    let records = execution_records().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let record = records
+        .record_feedback(tenant_info.id, ExecutionType::Agent, execution_id, Some(agent_id), request.positive)
+        .await?;
+    if let (Some(canary), Some(release_id)) = (canary_releases(), record.canary_release_id) {
+        canary.evaluate(release_id).await?;
+    }
+
+    Ok(HttpResponse::Ok().json(ExecutionRecordSummary::from(record)))
+}
+
+/// 创建 Agent 灰度发布
+///
+/// 调用方仍请求入口 Agent，按流量百分比或指定用户将部分执行分流到灰度版本 Agent。
+#[utoipa::path(
+    post,
+    path = "/api/v1/agents/{agent_id}/canary",
+    request_body = CreateCanaryRequest,
+    responses(
+        (status = 201, description = "灰度发布已创建", body = CanaryReleaseInfo),
+        (status = 400, description = "请求参数错误"),
+        (status = 404, description = "灰度版本 Agent 不存在"),
+        (status = 409, description = "已有进行中的灰度发布"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "入口 Agent ID")
+    ),
+    tag = "agents"
+)]
+pub async fn create_agent_canary(
+    agent_runtime: web::Data<Arc<AgentRuntime>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    request: web::Json<CreateCanaryRequest>,
+) -> ActixResult<HttpResponse> {
+    let agent_id = path.into_inner();
+    info!("创建 Agent 灰度发布: agent_id={}, tenant_id={}", agent_id, tenant_info.id);
+
+    agent_runtime.get_agent_state(request.canary_id).await?;
+    let canary = canary_releases().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let release = canary
+        .create(tenant_info.id, ExecutionType::Agent, agent_id, request.into_inner(), user.user_id)
+        .await?;
+
+    Ok(HttpResponse::Created().json(release))
+}
+
+/// 获取 Agent 最近一次灰度发布及各版本的执行统计
+#[utoipa::path(
+    get,
+    path = "/api/v1/agents/{agent_id}/canary",
+    responses(
+        (status = 200, description = "获取灰度发布成功", body = CanaryReleaseInfo),
+        (status = 404, description = "没有灰度发布"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "入口 Agent ID")
+    ),
+    tag = "agents"
+)]
+pub async fn get_agent_canary(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let agent_id = path.into_inner();
+    let canary = canary_releases().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let release = canary.info(tenant_info.id, ExecutionType::Agent, agent_id).await?;
+
+    Ok(HttpResponse::Ok().json(release))
+}
+
+/// 全量 Agent 灰度版本
+///
+/// 全量后入口 Agent 的全部流量由灰度版本处理。
+#[utoipa::path(
+    post,
+    path = "/api/v1/agents/{agent_id}/canary/promote",
+    request_body = EndCanaryRequest,
+    responses(
+        (status = 200, description = "已全量", body = CanaryReleaseInfo),
+        (status = 404, description = "没有进行中的灰度发布"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "入口 Agent ID")
+    ),
+    tag = "agents"
+)]
+pub async fn promote_agent_canary(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    request: Option<web::Json<EndCanaryRequest>>,
+) -> ActixResult<HttpResponse> {
+    let agent_id = path.into_inner();
+    info!("全量 Agent 灰度版本: agent_id={}, tenant_id={}", agent_id, tenant_info.id);
+
+    let canary = canary_releases().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let reason = request.and_then(|request| request.into_inner().reason);
+    let release = canary.promote(tenant_info.id, ExecutionType::Agent, agent_id, reason).await?;
+
+    Ok(HttpResponse::Ok().json(release))
+}
+
+/// 回滚 Agent 灰度发布
+#[utoipa::path(
+    post,
+    path = "/api/v1/agents/{agent_id}/canary/rollback",
+    request_body = EndCanaryRequest,
+    responses(
+        (status = 200, description = "已回滚", body = CanaryReleaseInfo),
+        (status = 404, description = "没有进行中的灰度发布"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "入口 Agent ID")
+    ),
+    tag = "agents"
+)]
+pub async fn rollback_agent_canary(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    request: Option<web::Json<EndCanaryRequest>>,
+) -> ActixResult<HttpResponse> {
+    let agent_id = path.into_inner();
+    info!("回滚 Agent 灰度发布: agent_id={}, tenant_id={}", agent_id, tenant_info.id);
+
+    let canary = canary_releases().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let reason = request.and_then(|request| request.into_inner().reason);
+    let release = canary.rollback(tenant_info.id, ExecutionType::Agent, agent_id, reason).await?;
+
+    Ok(HttpResponse::Ok().json(release))
+}
+
+/// 发布 Agent 变更事件
+fn publish_agent_event(event_type: &str, tenant_id: Uuid, actor_id: Option<Uuid>, agent_id: Uuid, name: &str) {
+    SystemEventBus::global().publish(
+        SystemEvent::new(
+            event_type,
+            Some(tenant_id),
+            serde_json::json!({
+                "agent_id": agent_id,
+                "name": name,
+            }),
+        )
+        .with_actor(actor_id),
+    );
+}
+
+/// 执行记录服务，数据库未初始化时不记录
+fn execution_records() -> Option<ExecutionRecordService> {
+    DatabaseManager::get()
+        .ok()
+        .map(|db_manager| ExecutionRecordService::new(db_manager.get_connection().clone()))
+}
+
+/// 灰度发布服务，数据库未初始化时不分流
+fn canary_releases() -> Option<CanaryService> {
+    DatabaseManager::get()
+        .ok()
+        .map(|db_manager| CanaryService::new(db_manager.get_connection().clone()))
+}
+
+/// 订阅 Agent 事件流
+///
+/// 以 SSE 推送状态变化（`state_changed`）、工具的部分输出（`tool_progress`）
+/// 与工具调用结束（`tool_completed`）事件。
+#[utoipa::path(
+    get,
+    path = "/api/v1/agents/{agent_id}/stream",
+    responses(
+        (status = 200, description = "SSE 事件流", content_type = "text/event-stream"),
+        (status = 404, description = "Agent 不存在")
+    ),
+    params(
+        ("agent_id" = Uuid, Path, description = "Agent ID")
+    ),
+    tag = "agents"
+)]
+pub async fn stream_agent_events(
+    agent_runtime: web::Data<Arc<AgentRuntime>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    let agent_id = path.into_inner();
+    debug!("订阅 Agent 事件流: agent_id={}, tenant_id={}", agent_id, tenant_info.id);
+
+    if agent_runtime.get_agent_state(agent_id).await.is_err() {
+        return Either::Left(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Agent 不存在"
+        })));
+    }
+
+    let events = stream::unfold(agent_stream::subscribe(agent_id), move |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let data = serde_json::to_string(&event).unwrap_or_default();
+                    let event = sse::Event::Data(sse::Data::new(data).event(event.name()));
+                    return Some((Ok::<_, actix_web::Error>(event), receiver));
+                }
+                // 订阅者处理过慢时跳过被覆盖的事件
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("Agent 事件流落后，跳过 {} 个事件: agent_id={}", skipped, agent_id);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Either::Right(Sse::from_stream(events).with_keep_alive(Duration::from_secs(30)))
+}
+
 /// 获取 Agent 状态
 #[utoipa::path(
     get,
@@ -436,8 +1143,22 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             .route("", web::post().to(create_agent))
             .route("", web::get().to(list_agents))
             .route("/cleanup", web::post().to(cleanup_agents))
+            .route("/definitions/import", web::post().to(import_agent_definition))
             .route("/{agent_id}/execute", web::post().to(execute_task))
             .route("/{agent_id}/status", web::get().to(get_agent_status))
+            .route("/{agent_id}/timeline", web::get().to(get_agent_timeline))
+            .route("/{agent_id}/steps", web::get().to(get_agent_steps))
+            .route("/{agent_id}/steps/{sequence}", web::get().to(get_agent_step))
+            .route("/{agent_id}/stream", web::get().to(stream_agent_events))
+            .route("/{agent_id}/definition", web::get().to(export_agent_definition))
+            .route("/{agent_id}/executions/{execution_id}/memory", web::get().to(get_execution_memory))
+            .route("/{agent_id}/executions/{execution_id}/replay", web::post().to(replay_agent_execution))
+            .route("/{agent_id}/executions/{execution_id}/replays", web::get().to(get_agent_replays))
+            .route("/{agent_id}/executions/{execution_id}/feedback", web::post().to(submit_agent_feedback))
+            .route("/{agent_id}/canary", web::post().to(create_agent_canary))
+            .route("/{agent_id}/canary", web::get().to(get_agent_canary))
+            .route("/{agent_id}/canary/promote", web::post().to(promote_agent_canary))
+            .route("/{agent_id}/canary/rollback", web::post().to(rollback_agent_canary))
             .route("/{agent_id}/stop", web::post().to(stop_agent))
     );
 }
@@ -457,6 +1178,9 @@ mod tests {
             reasoning_strategy: ReasoningStrategy::React,
             temperature: 0.7,
             max_tokens: 2000,
+            model: None,
+            knowledge_bases: Vec::new(),
+            web_search: WebSearchDomainPolicy::default(),
         };
         
         let json = serde_json::to_string(&request).unwrap();
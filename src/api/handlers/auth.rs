@@ -6,8 +6,12 @@ use sea_orm::EntityTrait;
 use crate::api::responses::HttpResponseBuilder;
 use crate::services::auth::{
     AuthService, LoginRequest, RefreshTokenRequest,
-    RegisterRequest, PasswordResetRequest, PasswordResetConfirmRequest, UpdateUserProfileRequest
+    RegisterRequest, PasswordResetRequest, PasswordResetConfirmRequest, UpdateUserProfileRequest,
+    AcceptInvitationRequest
 };
+use crate::services::user_preferences::{UserPreferenceService, UpdateUserPreferencesRequest};
+use crate::services::passkey::{PasskeyService, PasskeyLoginOptionsRequest, PasskeyLoginRequest};
+use crate::config::ConfigLoader;
 use crate::db::DatabaseManager;
 use crate::errors::AiStudioError;
 use crate::api::AuthExtractor;
@@ -46,6 +50,76 @@ pub async fn login(
     HttpResponseBuilder::ok(response)
 }
 
+///获取通行密钥登录选项
+#[utoipa::path(
+    post,
+    path = "/auth/passkey/login/options",
+    tag = "auth",
+    request_body = PasskeyLoginOptionsRequest,
+    responses(
+        (status = 200, description = "登录选项，public_key 传给 navigator.credentials.get()", body = PasskeyCeremonyOptions)
+    )
+)]
+pub async fn create_passkey_login_options(
+    request: web::Json<PasskeyLoginOptionsRequest>,
+) -> ActixResult<HttpResponse> {
+    let db_manager = DatabaseManager::get()?;
+    let service = PasskeyService::new(
+        db_manager.get_connection().clone(),
+        ConfigLoader::get().webauthn.clone(),
+    );
+
+    let options = service.start_authentication(request.into_inner()).await?;
+
+    HttpResponseBuilder::ok(options)
+}
+
+///使用通行密钥登录
+#[utoipa::path(
+    post,
+    path = "/auth/passkey/login",
+    tag = "auth",
+    request_body = PasskeyLoginRequest,
+    responses(
+        (status = 200, description = "登录成功", body = LoginResponse),
+        (status = 401, description = "挑战无效或凭据校验失败", body = ApiError)
+    )
+)]
+pub async fn login_with_passkey(
+    req: HttpRequest,
+    request: web::Json<PasskeyLoginRequest>,
+) -> ActixResult<HttpResponse> {
+    let db_manager = DatabaseManager::get()?;
+    let passkeys = PasskeyService::new(
+        db_manager.get_connection().clone(),
+        ConfigLoader::get().webauthn.clone(),
+    );
+    let service = AuthService::new(
+        db_manager.get_connection().clone(),
+        "default_jwt_secret".to_string(), // 应该从配置中获取
+        None,
+        None,
+    );
+
+    let user = passkeys.finish_authentication(&request).await?;
+
+    let client_ip = req
+        .connection_info()
+        .peer_addr()
+        .map(|s| s.to_string());
+    let user_agent = req
+        .headers()
+        .get("User-Agent")
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.to_string());
+
+    let response = service
+        .login_with_passkey(user, request.remember_me.unwrap_or(false), client_ip, user_agent)
+        .await?;
+
+    HttpResponseBuilder::ok(response)
+}
+
 ///刷新令牌
 #[utoipa::path(
     post,
@@ -182,6 +256,34 @@ pub async fn confirm_password_reset(
     HttpResponseBuilder::no_content()
 }
 
+///接受邀请
+#[utoipa::path(
+    post,
+    path = "/auth/invitations/accept",
+    tag = "auth",
+    request_body = AcceptInvitationRequest,
+    responses(
+        (status = 200, description = "账户已激活", body = UserInfo),
+        (status = 400, description = "密码不符合要求", body = ApiError),
+        (status = 401, description = "邀请令牌无效或已过期", body = ApiError)
+    )
+)]
+pub async fn accept_invitation(
+    request: web::Json<AcceptInvitationRequest>,
+) -> ActixResult<HttpResponse> {
+    let db_manager = DatabaseManager::get()?;
+    let service = AuthService::new(
+        db_manager.get_connection().clone(),
+        "default_jwt_secret".to_string(),
+        None,
+        None,
+    );
+
+    let user = service.accept_invitation(request.into_inner()).await?;
+
+    HttpResponseBuilder::ok(user)
+}
+
 ///获取当前用户信息
 #[utoipa::path(
     get,
@@ -243,18 +345,74 @@ pub async fn update_user_profile(
     HttpResponseBuilder::ok(updated_user)
 }
 
+///获取当前用户偏好设置
+#[utoipa::path(
+    get,
+    path = "/auth/preferences",
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "用户偏好设置", body = crate::db::entities::user::UserPreferences),
+        (status = 401, description = "未认证", body = ApiError)
+    )
+)]
+pub async fn get_user_preferences(
+    auth: AuthExtractor,
+) -> ActixResult<HttpResponse> {
+    let db_manager = DatabaseManager::get()?;
+    let service = UserPreferenceService::new(db_manager.get_connection().clone());
+
+    let preferences = service.get_preferences(auth.user_id).await?;
+
+    HttpResponseBuilder::ok(preferences)
+}
+
+///更新当前用户偏好设置
+#[utoipa::path(
+    put,
+    path = "/auth/preferences",
+    tag = "auth",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body = UpdateUserPreferencesRequest,
+    responses(
+        (status = 200, description = "偏好设置已更新", body = crate::db::entities::user::UserPreferences),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 401, description = "未认证", body = ApiError)
+    )
+)]
+pub async fn update_user_preferences(
+    auth: AuthExtractor,
+    request: web::Json<UpdateUserPreferencesRequest>,
+) -> ActixResult<HttpResponse> {
+    let db_manager = DatabaseManager::get()?;
+    let service = UserPreferenceService::new(db_manager.get_connection().clone());
+
+    let preferences = service.update_preferences(auth.user_id, request.into_inner()).await?;
+
+    HttpResponseBuilder::ok(preferences)
+}
+
 // 配置认证路由
 pub fn configure_auth_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/auth")
             .route("/login", web::post().to(login))
+            .route("/passkey/login/options", web::post().to(create_passkey_login_options))
+            .route("/passkey/login", web::post().to(login_with_passkey))
             .route("/logout", web::post().to(logout))
             .route("/refresh", web::post().to(refresh_token))
             .route("/register", web::post().to(register))
             .route("/password-reset", web::post().to(request_password_reset))
             .route("/password-reset/confirm", web::post().to(confirm_password_reset))
+            .route("/invitations/accept", web::post().to(accept_invitation))
             .route("/me", web::get().to(get_current_user))
             .route("/profile", web::put().to(update_user_profile))
+            .route("/preferences", web::get().to(get_user_preferences))
+            .route("/preferences", web::put().to(update_user_preferences))
     );
 }
 
@@ -0,0 +1,199 @@
+// 同意与条款接受 API 处理器
+
+use actix_web::{web, HttpRequest, HttpResponse, Result as ActixResult};
+use sea_orm::DatabaseConnection;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::api::middleware::auth::{AuthenticatedUser, PermissionChecker};
+use crate::api::middleware::tenant::TenantInfo;
+use crate::api::responses::HttpResponseBuilder;
+use crate::errors::AiStudioError;
+use crate::services::consent::{
+    render_acceptances_csv, AcceptanceExportFormat, ConsentService, ExportAcceptancesQuery, PublishTermsRequest,
+};
+
+fn ensure_tenant_admin(user: &AuthenticatedUser) -> Result<(), AiStudioError> {
+    if !PermissionChecker::has_role(user, "admin") {
+        return Err(AiStudioError::forbidden("需要租户管理员权限"));
+    }
+    Ok(())
+}
+
+/// 发布条款版本
+///
+/// 新版本立即成为租户当前版本；要求接受时，所有用户需重新接受后才能继续使用 API。
+#[utoipa::path(
+    post,
+    path = "/api/v1/consent/terms",
+    request_body = PublishTermsRequest,
+    responses(
+        (status = 201, description = "发布成功", body = TermsVersionResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "需要租户管理员权限", body = ApiError),
+        (status = 409, description = "版本号已存在", body = ApiError)
+    ),
+    tag = "consent",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn publish_terms(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: web::Json<PublishTermsRequest>,
+) -> ActixResult<HttpResponse> {
+    ensure_tenant_admin(&user)?;
+    info!("发布条款版本: tenant_id={}, version={}, user={}", tenant_info.id, req.version, user.user_id);
+
+    let terms = ConsentService::new(db.get_ref().clone())
+        .publish_version(tenant_info.id, req.into_inner(), user.user_id)
+        .await?;
+
+    HttpResponseBuilder::created(terms)
+}
+
+/// 列出条款版本
+#[utoipa::path(
+    get,
+    path = "/api/v1/consent/terms",
+    responses(
+        (status = 200, description = "获取列表成功", body = Vec<TermsVersionResponse>)
+    ),
+    tag = "consent",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_terms(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+) -> ActixResult<HttpResponse> {
+    let versions = ConsentService::new(db.get_ref().clone())
+        .list_versions(tenant_info.id)
+        .await?;
+
+    HttpResponseBuilder::ok(versions)
+}
+
+/// 获取当前条款版本及当前用户的接受状态
+#[utoipa::path(
+    get,
+    path = "/api/v1/consent/terms/current",
+    responses(
+        (status = 200, description = "获取成功", body = ConsentStatus)
+    ),
+    tag = "consent",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_consent_status(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+) -> ActixResult<HttpResponse> {
+    let status = ConsentService::new(db.get_ref().clone())
+        .status(tenant_info.id, user.user_id)
+        .await?;
+
+    HttpResponseBuilder::ok(status)
+}
+
+/// 接受条款版本
+#[utoipa::path(
+    post,
+    path = "/api/v1/consent/terms/{id}/accept",
+    params(
+        ("id" = Uuid, Path, description = "条款版本 ID")
+    ),
+    responses(
+        (status = 200, description = "接受成功", body = ConsentStatus),
+        (status = 404, description = "条款版本不存在", body = ApiError),
+        (status = 409, description = "条款版本已被取代", body = ApiError)
+    ),
+    tag = "consent",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn accept_terms(
+    http_req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let ip_address = http_req
+        .connection_info()
+        .peer_addr()
+        .map(|s| s.to_string());
+    let user_agent = http_req
+        .headers()
+        .get("User-Agent")
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.to_string());
+
+    let status = ConsentService::new(db.get_ref().clone())
+        .accept(tenant_info.id, user.user_id, path.into_inner(), ip_address, user_agent)
+        .await?;
+
+    HttpResponseBuilder::ok(status)
+}
+
+/// 导出条款接受记录
+#[utoipa::path(
+    get,
+    path = "/api/v1/consent/acceptances/export",
+    params(ExportAcceptancesQuery),
+    responses(
+        (status = 200, description = "接受记录文件"),
+        (status = 403, description = "需要租户管理员权限", body = ApiError)
+    ),
+    tag = "consent",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn export_acceptances(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    query: web::Query<ExportAcceptancesQuery>,
+) -> ActixResult<HttpResponse> {
+    ensure_tenant_admin(&user)?;
+
+    let records = ConsentService::new(db.get_ref().clone())
+        .acceptances(tenant_info.id, query.terms_version_id)
+        .await?;
+    let body = match query.format {
+        AcceptanceExportFormat::Json => serde_json::to_string(&records)?,
+        AcceptanceExportFormat::Csv => render_acceptances_csv(&records),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(query.format.content_type())
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"terms-acceptances.{}\"", query.format.extension()),
+        ))
+        .body(body))
+}
+
+/// 配置同意与条款路由
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/consent")
+            .route("/terms", web::post().to(publish_terms))
+            .route("/terms", web::get().to(list_terms))
+            .route("/terms/current", web::get().to(get_consent_status))
+            .route("/terms/{id}/accept", web::post().to(accept_terms))
+            .route("/acceptances/export", web::get().to(export_acceptances))
+    );
+}
@@ -0,0 +1,279 @@
+// 精选答案 API 处理器
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use sea_orm::DatabaseConnection;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::api::middleware::auth::{AuthenticatedUser, PermissionChecker};
+use crate::api::middleware::tenant::TenantInfo;
+use crate::api::responses::HttpResponseBuilder;
+use crate::errors::AiStudioError;
+use crate::services::clearance::clearance_for;
+use crate::services::curated_answer::{
+    AnswerReviewQuery, CreateCuratedAnswerRequest, CuratedAnswerQuery, CuratedAnswerService,
+    UpdateCuratedAnswerRequest,
+};
+
+fn ensure_curator(user: &AuthenticatedUser) -> Result<(), AiStudioError> {
+    if !PermissionChecker::has_role(user, "admin") {
+        return Err(AiStudioError::forbidden("需要租户管理员权限"));
+    }
+    Ok(())
+}
+
+/// 获取答案复核队列
+///
+/// 将近期问答按问题簇汇总，负面反馈多、提问多的问题簇在前，并标出已有的精选答案。
+#[utoipa::path(
+    get,
+    path = "/api/v1/curated-answers/review-queue",
+    params(AnswerReviewQuery),
+    responses(
+        (status = 200, description = "获取成功", body = Vec<AnswerCluster>),
+        (status = 403, description = "需要租户管理员权限", body = ApiError)
+    ),
+    tag = "curated-answers",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_answer_review_queue(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    query: web::Query<AnswerReviewQuery>,
+) -> ActixResult<HttpResponse> {
+    ensure_curator(&user)?;
+
+    let clusters = CuratedAnswerService::new(db.get_ref().clone())
+        .review_queue(tenant_info.id, clearance_for(&user.role, &user.permissions), &query)
+        .await?;
+
+    HttpResponseBuilder::ok(clusters)
+}
+
+/// 创建精选答案
+///
+/// 相似问题此后优先返回该答案并标注为精选答案，到达复核周期后需复核才能继续应答。
+#[utoipa::path(
+    post,
+    path = "/api/v1/curated-answers",
+    request_body = CreateCuratedAnswerRequest,
+    responses(
+        (status = 201, description = "创建成功", body = CuratedAnswer),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库或问答记录不存在", body = ApiError)
+    ),
+    tag = "curated-answers",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn create_curated_answer(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: web::Json<CreateCuratedAnswerRequest>,
+) -> ActixResult<HttpResponse> {
+    ensure_curator(&user)?;
+    info!("创建精选答案请求: 租户={}, 用户={}", tenant_info.id, user.user_id);
+
+    let answer = CuratedAnswerService::new(db.get_ref().clone())
+        .create(tenant_info.id, user.user_id, clearance_for(&user.role, &user.permissions), req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::created(answer)
+}
+
+/// 列出精选答案
+#[utoipa::path(
+    get,
+    path = "/api/v1/curated-answers",
+    params(CuratedAnswerQuery),
+    responses(
+        (status = 200, description = "获取成功", body = Vec<CuratedAnswer>),
+        (status = 403, description = "需要租户管理员权限", body = ApiError)
+    ),
+    tag = "curated-answers",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_curated_answers(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    query: web::Query<CuratedAnswerQuery>,
+) -> ActixResult<HttpResponse> {
+    ensure_curator(&user)?;
+
+    let answers = CuratedAnswerService::new(db.get_ref().clone())
+        .list(tenant_info.id, clearance_for(&user.role, &user.permissions), &query)
+        .await?;
+
+    HttpResponseBuilder::ok(answers)
+}
+
+/// 获取精选答案
+#[utoipa::path(
+    get,
+    path = "/api/v1/curated-answers/{id}",
+    params(
+        ("id" = Uuid, Path, description = "精选答案 ID")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = CuratedAnswer),
+        (status = 403, description = "需要租户管理员权限", body = ApiError),
+        (status = 404, description = "精选答案不存在", body = ApiError)
+    ),
+    tag = "curated-answers",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_curated_answer(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    ensure_curator(&user)?;
+
+    let answer = CuratedAnswerService::new(db.get_ref().clone())
+        .get(tenant_info.id, path.into_inner(), clearance_for(&user.role, &user.permissions))
+        .await?;
+
+    HttpResponseBuilder::ok(answer)
+}
+
+/// 更新精选答案
+///
+/// 修改答案视为完成一次复核，到期时间按复核周期顺延。
+#[utoipa::path(
+    put,
+    path = "/api/v1/curated-answers/{id}",
+    params(
+        ("id" = Uuid, Path, description = "精选答案 ID")
+    ),
+    request_body = UpdateCuratedAnswerRequest,
+    responses(
+        (status = 200, description = "更新成功", body = CuratedAnswer),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "精选答案不存在", body = ApiError)
+    ),
+    tag = "curated-answers",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn update_curated_answer(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<UpdateCuratedAnswerRequest>,
+) -> ActixResult<HttpResponse> {
+    ensure_curator(&user)?;
+
+    let answer = CuratedAnswerService::new(db.get_ref().clone())
+        .update(
+            tenant_info.id,
+            path.into_inner(),
+            user.user_id,
+            clearance_for(&user.role, &user.permissions),
+            req.into_inner(),
+        )
+        .await?;
+
+    HttpResponseBuilder::ok(answer)
+}
+
+/// 复核精选答案
+///
+/// 确认答案仍然有效，到期时间按复核周期顺延；已到期的答案复核后恢复应答。
+#[utoipa::path(
+    post,
+    path = "/api/v1/curated-answers/{id}/review",
+    params(
+        ("id" = Uuid, Path, description = "精选答案 ID")
+    ),
+    responses(
+        (status = 200, description = "复核成功", body = CuratedAnswer),
+        (status = 403, description = "需要租户管理员权限", body = ApiError),
+        (status = 404, description = "精选答案不存在", body = ApiError)
+    ),
+    tag = "curated-answers",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn review_curated_answer(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    ensure_curator(&user)?;
+
+    let answer = CuratedAnswerService::new(db.get_ref().clone())
+        .confirm_review(tenant_info.id, path.into_inner(), user.user_id, clearance_for(&user.role, &user.permissions))
+        .await?;
+
+    HttpResponseBuilder::ok(answer)
+}
+
+/// 删除精选答案
+#[utoipa::path(
+    delete,
+    path = "/api/v1/curated-answers/{id}",
+    params(
+        ("id" = Uuid, Path, description = "精选答案 ID")
+    ),
+    responses(
+        (status = 204, description = "删除成功"),
+        (status = 403, description = "需要租户管理员权限", body = ApiError),
+        (status = 404, description = "精选答案不存在", body = ApiError)
+    ),
+    tag = "curated-answers",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn delete_curated_answer(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    ensure_curator(&user)?;
+
+    CuratedAnswerService::new(db.get_ref().clone())
+        .delete(tenant_info.id, path.into_inner(), clearance_for(&user.role, &user.permissions))
+        .await?;
+
+    HttpResponseBuilder::no_content()
+}
+
+/// 配置精选答案路由
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/curated-answers")
+            .route("", web::post().to(create_curated_answer))
+            .route("", web::get().to(list_curated_answers))
+            .route("/review-queue", web::get().to(get_answer_review_queue))
+            .route("/{id}", web::get().to(get_curated_answer))
+            .route("/{id}", web::put().to(update_curated_answer))
+            .route("/{id}", web::delete().to(delete_curated_answer))
+            .route("/{id}/review", web::post().to(review_curated_answer))
+    );
+}
@@ -1,6 +1,6 @@
 // 文档管理 API 处理器
 
-use actix_web::{web, HttpResponse, Result as ActixResult};
+use actix_web::{http::header, web, HttpResponse, Result as ActixResult};
 use actix_multipart::Multipart;
 use futures::stream::StreamExt;
 use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, PaginatorTrait, ActiveModelTrait};
@@ -11,14 +11,36 @@ use chrono::{DateTime, Utc};
 use tracing::{info, warn, error, debug};
 use std::io::Write;
 
+use crate::ai::document_processor::TableInfo;
+use crate::ai::rig_client::RigAiClientManager;
+use crate::ai::table_extraction::{parse_delimited, parse_xlsx, render_markdown_table};
 use crate::api::models::{PaginationQuery, PaginatedResponse, PaginationInfo};
 use crate::api::responses::{ApiResponse, ApiError, ApiResponseExt};
+use crate::api::middleware::auth::{ApiKeyInfo, AuthenticatedUser};
+use crate::api::middleware::ingest_throttle::IngestThrottleMiddleware;
 use crate::api::middleware::tenant::TenantInfo;
-use crate::api::extractors::{TenantContext, UserContext};
+use crate::api::handlers::document_comment::{
+    create_document_comment, delete_document_comment, list_document_comments, reopen_document_comment,
+    resolve_document_comment, update_document_comment,
+};
+use crate::api::extractors::{IfMatchExtractor, TenantContext, UserContext};
 use crate::api::HttpResponseBuilder;
 use crate::db::entities::{document, knowledge_base, prelude::*};
+use crate::db::entities::usage_anomaly::AnomalyMetric;
+use crate::db::{revision_etag, update_with_revision};
 use crate::errors::AiStudioError;
+use crate::services::archive::{ArchiveService, DocumentArchiveRequest, DocumentArchiveResult};
+use crate::services::clearance::{clearance_for, ClearanceService, UpdateDocumentClearanceRequest};
+use crate::services::dataset::DatasetService;
+use crate::services::document_comment::{CommentThread, DocumentCommentQuery, DocumentCommentService};
+use crate::services::freshness::{DocumentFreshnessService, RenewDocumentRequest};
+use crate::services::incremental_index::spawn_reindex;
+use crate::services::legal_hold::LegalHoldService;
+use crate::services::similar_documents::{SimilarDocumentService, SimilarDocumentsQuery};
+use crate::services::tenant_encryption::TenantEncryptionService;
 use crate::services::knowledge_base::KnowledgeBaseService;
+use crate::services::usage_anomaly::record_usage;
+use crate::plugins::event_bus::{event_types, SystemEvent, SystemEventBus};
 
 /// 文档创建请求
 #[derive(Debug, Clone, Deserialize, ToSchema)]
@@ -35,6 +57,14 @@ pub struct CreateDocumentRequest {
     pub metadata: Option<document::DocumentMetadata>,
     /// 处理配置
     pub processing_config: Option<document::DocumentProcessingConfig>,
+    /// 过期时间
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 复核时间
+    pub review_at: Option<DateTime<Utc>>,
+    /// 负责人 ID
+    pub owner_id: Option<Uuid>,
+    /// 是否为常青文档，常青文档不参与检索排序的时间衰减
+    pub evergreen: Option<bool>,
 }
 
 /// 文档更新请求
@@ -50,6 +80,14 @@ pub struct UpdateDocumentRequest {
     pub metadata: Option<document::DocumentMetadata>,
     /// 处理配置
     pub processing_config: Option<document::DocumentProcessingConfig>,
+    /// 过期时间
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 复核时间
+    pub review_at: Option<DateTime<Utc>>,
+    /// 负责人 ID
+    pub owner_id: Option<Uuid>,
+    /// 是否为常青文档
+    pub evergreen: Option<bool>,
 }
 
 /// 文档响应
@@ -93,12 +131,34 @@ pub struct DocumentResponse {
     pub error_message: Option<String>,
     /// 版本号
     pub version: i32,
+    /// 修订号，更新时通过 If-Match 携带以检测并发修改
+    pub revision: i32,
+    /// 过期时间
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 复核时间
+    pub review_at: Option<DateTime<Utc>>,
+    /// 负责人 ID
+    pub owner_id: Option<Uuid>,
+    /// 访问密级
+    pub clearance: document::ClearanceLevel,
+    /// 是否为常青文档
+    pub evergreen: bool,
     /// 进度百分比
     pub progress_percentage: f32,
     /// 创建时间
     pub created_at: DateTime<Utc>,
     /// 更新时间
     pub updated_at: DateTime<Utc>,
+    /// 评论主题，仅在请求详情时指定 `include_comments=true` 返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comments: Option<Vec<CommentThread>>,
+}
+
+/// 文档详情查询参数
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+pub struct DocumentDetailQuery {
+    /// 是否同时返回评论与批注
+    pub include_comments: Option<bool>,
 }
 
 /// 文档搜索查询
@@ -198,9 +258,16 @@ impl From<document::Model> for DocumentResponse {
             processing_duration_ms,
             error_message: model.error_message,
             version: model.version,
+            revision: model.revision,
+            expires_at: model.expires_at.map(|dt| dt.with_timezone(&Utc)),
+            review_at: model.review_at.map(|dt| dt.with_timezone(&Utc)),
+            owner_id: model.owner_id,
+            clearance: model.clearance,
+            evergreen: model.evergreen,
             progress_percentage,
             created_at: model.created_at.with_timezone(&Utc),
             updated_at: model.updated_at.with_timezone(&Utc),
+            comments: None,
         }
     }
 }
@@ -236,6 +303,7 @@ impl From<document::Model> for DocumentStats {
         (status = 401, description = "未授权", body = ApiError),
         (status = 403, description = "权限不足", body = ApiError),
         (status = 404, description = "知识库不存在", body = ApiError),
+        (status = 429, description = "导入请求频率超限", body = ApiError),
         (status = 500, description = "服务器内部错误", body = ApiError)
     ),
     tag = "documents",
@@ -247,6 +315,7 @@ impl From<document::Model> for DocumentStats {
 pub async fn create_document(
     db: web::Data<DatabaseConnection>,
     tenant_info: web::ReqData<TenantInfo>,
+    user: Option<web::ReqData<AuthenticatedUser>>,
     req: web::Json<CreateDocumentRequest>,
 ) -> ActixResult<HttpResponse> {
     info!("创建文档请求: 租户={}, 知识库={}, 标题={}", 
@@ -278,7 +347,7 @@ pub async fn create_document(
     let content_hash = format!("{:x}", md5::compute(&content));
     
     // 创建文档
-    let new_doc = document::ActiveModel {
+    let mut new_doc = document::ActiveModel {
         id: sea_orm::Set(doc_id),
         knowledge_base_id: sea_orm::Set(req.knowledge_base_id),
         title: sea_orm::Set(req.title.clone()),
@@ -299,19 +368,31 @@ pub async fn create_document(
         processing_completed_at: sea_orm::Set(None),
         error_message: sea_orm::Set(None),
         version: sea_orm::Set(1),
+        expires_at: sea_orm::Set(req.expires_at.map(|dt| dt.fixed_offset())),
+        review_at: sea_orm::Set(req.review_at.map(|dt| dt.fixed_offset())),
+        owner_id: sea_orm::Set(req.owner_id),
+        review_notified_at: sea_orm::Set(None),
+        clearance: sea_orm::Set(document::ClearanceLevel::Public),
+        evergreen: sea_orm::Set(req.evergreen.unwrap_or(false)),
         created_at: sea_orm::Set(now),
         updated_at: sea_orm::Set(now),
+        revision: sea_orm::Set(1),
     };
     
-    let doc = Document::insert(new_doc)
+    let encryption = content_encryption(db.as_ref())?;
+    encrypt_document_content(&encryption, tenant_info.id, &mut new_doc).await?;
+    
+    let mut doc = Document::insert(new_doc)
         .exec_with_returning(db.as_ref())
         .await
         .map_err(|e| {
             error!("创建文档失败: {}", e);
             ApiError::internal_server_error("创建文档失败")
         })?;
+    decrypt_document_content(&encryption, &mut doc).await?;
     
     info!("文档创建成功: id={}, 标题={}", doc.id, doc.title);
+    publish_document_event(event_types::DOCUMENT_CREATED, tenant_info.id, user.map(|u| u.user_id), &doc);
     
     let response = DocumentResponse::from(doc);
     Ok(ApiResponse::created(response).into_http_response().unwrap())
@@ -327,6 +408,7 @@ pub async fn create_document(
         (status = 400, description = "请求参数错误", body = ApiError),
         (status = 401, description = "未授权", body = ApiError),
         (status = 413, description = "文件过大", body = ApiError),
+        (status = 429, description = "导入请求频率超限", body = ApiError),
         (status = 500, description = "服务器内部错误", body = ApiError)
     ),
     tag = "documents",
@@ -338,6 +420,7 @@ pub async fn create_document(
 pub async fn upload_document(
     db: web::Data<DatabaseConnection>,
     tenant_info: web::ReqData<TenantInfo>,
+    user: Option<web::ReqData<AuthenticatedUser>>,
     mut payload: Multipart,
 ) -> ActixResult<HttpResponse> {
     info!("文档上传请求: 租户={}", tenant_info.id);
@@ -440,7 +523,20 @@ pub async fn upload_document(
     let doc_type = determine_document_type(&file_name, content_type.as_deref());
     
     // 提取文本内容（简单实现，实际应该使用专门的文档处理服务）
-    let content = extract_text_content(&file_data, &doc_type)?;
+    // 表格文件的正文为各表格的 Markdown 形式
+    let tables = extract_tables(&file_data, &doc_type)?;
+    let content = if tables.is_empty() {
+        extract_text_content(&file_data, &doc_type)?
+    } else {
+        tables.iter()
+            .map(|table| render_markdown_table(table.caption.as_deref(), &table.headers, &table.rows))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let raw_content = match doc_type {
+        document::DocumentType::Spreadsheet => None,
+        _ => Some(String::from_utf8_lossy(&file_data).to_string()),
+    };
     
     // 计算内容哈希
     let content_hash = format!("{:x}", md5::compute(&content));
@@ -452,12 +548,12 @@ pub async fn upload_document(
     // 保存文件（这里简化处理，实际应该保存到文件系统或对象存储）
     let file_path = format!("uploads/{}/{}", tenant_info.id, doc_id);
     
-    let new_doc = document::ActiveModel {
+    let mut new_doc = document::ActiveModel {
         id: sea_orm::Set(doc_id),
         knowledge_base_id: sea_orm::Set(knowledge_base_id),
         title: sea_orm::Set(title),
         content: sea_orm::Set(content),
-        raw_content: sea_orm::Set(Some(String::from_utf8_lossy(&file_data).to_string())),
+        raw_content: sea_orm::Set(raw_content),
         summary: sea_orm::Set(None),
         doc_type: sea_orm::Set(doc_type),
         status: sea_orm::Set(document::DocumentStatus::Pending),
@@ -473,10 +569,20 @@ pub async fn upload_document(
         processing_completed_at: sea_orm::Set(None),
         error_message: sea_orm::Set(None),
         version: sea_orm::Set(1),
+        expires_at: sea_orm::Set(None),
+        review_at: sea_orm::Set(None),
+        owner_id: sea_orm::Set(None),
+        review_notified_at: sea_orm::Set(None),
+        clearance: sea_orm::Set(document::ClearanceLevel::Public),
+        evergreen: sea_orm::Set(false),
         created_at: sea_orm::Set(now),
         updated_at: sea_orm::Set(now),
+        revision: sea_orm::Set(1),
     };
     
+    let encryption = content_encryption(db.as_ref())?;
+    encrypt_document_content(&encryption, tenant_info.id, &mut new_doc).await?;
+    
     let doc = Document::insert(new_doc)
         .exec_with_returning(db.as_ref())
         .await
@@ -485,7 +591,15 @@ pub async fn upload_document(
             ApiError::internal_server_error("创建文档失败")
         })?;
     
+    // 表格文件导入为可查询的数据集，失败不影响文档上传
+    if !tables.is_empty() {
+        if let Err(e) = DatasetService::new(db.get_ref().clone()).import_document(tenant_info.id, &doc, &tables).await {
+            warn!("导入数据集失败: document_id={}, error={}", doc.id, e);
+        }
+    }
+    
     info!("文档上传成功: id={}, 文件名={}, 大小={}", doc.id, file_name, file_data.len());
+    publish_document_event(event_types::DOCUMENT_CREATED, tenant_info.id, user.map(|u| u.user_id), &doc);
     
     let response = DocumentUploadResponse {
         id: doc.id,
@@ -508,6 +622,7 @@ fn determine_document_type(file_name: &str, content_type: Option<&str>) -> docum
             "md" | "markdown" => return document::DocumentType::Markdown,
             "html" | "htm" => return document::DocumentType::Html,
             "csv" => return document::DocumentType::Csv,
+            "xlsx" => return document::DocumentType::Spreadsheet,
             "json" => return document::DocumentType::Json,
             "xml" => return document::DocumentType::Xml,
             "txt" => return document::DocumentType::Text,
@@ -525,6 +640,9 @@ fn determine_document_type(file_name: &str, content_type: Option<&str>) -> docum
             "text/markdown" => return document::DocumentType::Markdown,
             "text/html" => return document::DocumentType::Html,
             "text/csv" => return document::DocumentType::Csv,
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => {
+                return document::DocumentType::Spreadsheet;
+            }
             "application/json" => return document::DocumentType::Json,
             "application/xml" | "text/xml" => return document::DocumentType::Xml,
             "text/plain" => return document::DocumentType::Text,
@@ -536,6 +654,72 @@ fn determine_document_type(file_name: &str, content_type: Option<&str>) -> docum
     document::DocumentType::Text
 }
 
+/// 辅助函数：创建租户内容加密服务
+fn content_encryption(db: &DatabaseConnection) -> Result<TenantEncryptionService, ApiError> {
+    TenantEncryptionService::from_config(db.clone()).map_err(|e| {
+        error!("创建内容加密服务失败: {}", e);
+        ApiError::internal_server_error("内容加密服务不可用")
+    })
+}
+
+/// 辅助函数：按租户密钥加密待写入的文档内容，租户未启用加密时不做处理
+async fn encrypt_document_content(
+    encryption: &TenantEncryptionService,
+    tenant_id: Uuid,
+    model: &mut document::ActiveModel,
+) -> Result<(), ApiError> {
+    encryption.encrypt_document(tenant_id, model).await.map_err(|e| {
+        error!("加密文档内容失败: tenant_id={}, error={}", tenant_id, e);
+        ApiError::internal_server_error("加密文档内容失败")
+    })
+}
+
+/// 辅助函数：解密文档内容，密钥已吊销时拒绝访问
+async fn decrypt_document_content(
+    encryption: &TenantEncryptionService,
+    doc: &mut document::Model,
+) -> Result<(), ApiError> {
+    encryption.decrypt_document(doc).await.map_err(|e| match e {
+        AiStudioError::Authorization { .. } => ApiError::forbidden("文档内容的数据密钥已吊销"),
+        e => {
+            error!("解密文档内容失败: id={}, error={}", doc.id, e);
+            ApiError::internal_server_error("解密文档内容失败")
+        }
+    })
+}
+
+/// 辅助函数：确认文档未处于法律保留状态
+async fn ensure_not_held(db: &DatabaseConnection, doc: &document::Model, operation: &str) -> Result<(), ApiError> {
+    LegalHoldService::new(db.clone())
+        .ensure_document_mutable(doc, None, operation)
+        .await
+        .map_err(|e| match e {
+            AiStudioError::Conflict { message } => ApiError::conflict(message),
+            e => {
+                error!("检查法律保留失败: id={}, error={}", doc.id, e);
+                ApiError::internal_server_error("检查法律保留失败")
+            }
+        })
+}
+
+/// 辅助函数：解析表格文件（CSV/Excel），其他类型返回空
+fn extract_tables(file_data: &[u8], doc_type: &document::DocumentType) -> Result<Vec<TableInfo>, ApiError> {
+    match doc_type {
+        document::DocumentType::Csv => {
+            let text = String::from_utf8(file_data.to_vec()).map_err(|e| {
+                error!("CSV 文件编码错误: {}", e);
+                ApiError::bad_request("CSV 文件需为 UTF-8 编码")
+            })?;
+            Ok(parse_delimited(&text, ',').into_iter().collect())
+        }
+        document::DocumentType::Spreadsheet => parse_xlsx(file_data).map_err(|e| {
+            error!("Excel 文件解析失败: {}", e);
+            ApiError::bad_request("无效的 Excel 文件")
+        }),
+        _ => Ok(Vec::new()),
+    }
+}
+
 /// 辅助函数：提取文本内容
 fn extract_text_content(file_data: &[u8], doc_type: &document::DocumentType) -> Result<String, ApiError> {
     match doc_type {
@@ -676,10 +860,12 @@ pub async fn list_documents(
             ApiError::internal_server_error("查询文档失败")
         })?;
     
-    let responses: Vec<DocumentResponse> = documents
-        .into_iter()
-        .map(DocumentResponse::from)
-        .collect();
+    let encryption = content_encryption(db.as_ref())?;
+    let mut responses: Vec<DocumentResponse> = Vec::with_capacity(documents.len());
+    for mut doc in documents {
+        decrypt_document_content(&encryption, &mut doc).await?;
+        responses.push(DocumentResponse::from(doc));
+    }
     
     let pagination = PaginationInfo::new(
         query_params.pagination.page,
@@ -696,7 +882,8 @@ pub async fn list_documents(
     get,
     path = "/api/v1/documents/{id}",
     params(
-        ("id" = Uuid, Path, description = "文档 ID")
+        ("id" = Uuid, Path, description = "文档 ID"),
+        DocumentDetailQuery
     ),
     responses(
         (status = 200, description = "获取文档详情成功", body = DocumentResponse),
@@ -715,6 +902,7 @@ pub async fn get_document(
     db: web::Data<DatabaseConnection>,
     tenant_info: web::ReqData<TenantInfo>,
     path: web::Path<Uuid>,
+    query: web::Query<DocumentDetailQuery>,
 ) -> ActixResult<HttpResponse> {
     let doc_id = path.into_inner();
     debug!("获取文档详情: id={}, 租户={}", doc_id, tenant_info.id);
@@ -729,19 +917,31 @@ pub async fn get_document(
             ApiError::internal_server_error("查询文档失败")
         })?;
     
-    let doc = match doc {
+    let mut doc = match doc {
         Some(doc) => doc,
         None => {
             warn!("文档不存在或无权访问: id={}", doc_id);
             return Ok(HttpResponseBuilder::not_found::<()>("文档").unwrap());
         }
     };
-    
-    let response = DocumentResponse::from(doc);
-    Ok(ApiResponse::ok(response).into_http_response().unwrap())
+    decrypt_document_content(&content_encryption(db.as_ref())?, &mut doc).await?;
+    
+    let etag = revision_etag(doc.revision);
+    let mut response = DocumentResponse::from(doc);
+    if query.include_comments.unwrap_or(false) {
+        let threads = DocumentCommentService::new(db.get_ref().clone())
+            .list_threads(tenant_info.id, doc_id, &DocumentCommentQuery::default())
+            .await?;
+        response.comments = Some(threads);
+    }
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, etag))
+        .json(ApiResponse::ok(response)))
 }
 
 /// 更新文档
+///
+/// 携带 `If-Match` 时校验修订号，文档已被其他用户修改时返回 409。
 #[utoipa::path(
     put,
     path = "/api/v1/documents/{id}",
@@ -755,6 +955,7 @@ pub async fn get_document(
         (status = 401, description = "未授权", body = ApiError),
         (status = 403, description = "权限不足", body = ApiError),
         (status = 404, description = "文档不存在", body = ApiError),
+        (status = 409, description = "文档已被其他用户修改", body = ApiError),
         (status = 500, description = "服务器内部错误", body = ApiError)
     ),
     tag = "documents",
@@ -766,6 +967,8 @@ pub async fn get_document(
 pub async fn update_document(
     db: web::Data<DatabaseConnection>,
     tenant_info: web::ReqData<TenantInfo>,
+    if_match: IfMatchExtractor,
+    ai_client: Option<web::Data<RigAiClientManager>>,
     path: web::Path<Uuid>,
     req: web::Json<UpdateDocumentRequest>,
 ) -> ActixResult<HttpResponse> {
@@ -790,8 +993,10 @@ pub async fn update_document(
             return Ok(HttpResponseBuilder::not_found::<()>("文档").unwrap());
         }
     };
+    ensure_not_held(db.as_ref(), &doc, "update").await?;
     
     // 准备更新数据
+    let expected_revision = if_match.revision.unwrap_or(doc.revision);
     let mut active_model: document::ActiveModel = doc.into();
     let now = Utc::now().with_timezone(&chrono::FixedOffset::east_opt(8 * 3600).unwrap());
     
@@ -847,18 +1052,63 @@ pub async fn update_document(
         active_model.processing_config = sea_orm::Set(serde_json::to_value(processing_config).unwrap().into());
     }
     
+    // 调整过期或复核时间后重新开始复核提醒
+    if req.expires_at.is_some() || req.review_at.is_some() {
+        active_model.review_notified_at = sea_orm::Set(None);
+    }
+    if let Some(expires_at) = req.expires_at {
+        active_model.expires_at = sea_orm::Set(Some(expires_at.fixed_offset()));
+    }
+    if let Some(review_at) = req.review_at {
+        active_model.review_at = sea_orm::Set(Some(review_at.fixed_offset()));
+    }
+    if let Some(owner_id) = req.owner_id {
+        active_model.owner_id = sea_orm::Set(Some(owner_id));
+    }
+    if let Some(evergreen) = req.evergreen {
+        active_model.evergreen = sea_orm::Set(evergreen);
+    }
+    
     active_model.updated_at = sea_orm::Set(now);
     
-    // 执行更新
-    let updated_doc = active_model.update(db.as_ref()).await.map_err(|e| {
-        error!("更新文档失败: {}", e);
-        ApiError::internal_server_error("更新文档失败")
+    let encryption = content_encryption(db.as_ref())?;
+    encrypt_document_content(&encryption, tenant_info.id, &mut active_model).await?;
+    
+    // 执行更新，修订号不一致时拒绝覆盖
+    let mut updated_doc = update_with_revision(
+        db.as_ref(),
+        active_model,
+        document::Column::Revision,
+        expected_revision,
+        "文档",
+    )
+    .await
+    .map_err(|e| match e {
+        AiStudioError::Conflict { .. } => {
+            warn!("文档更新冲突: id={}, 期望修订号={}", doc_id, expected_revision);
+            ApiError::conflict(e.to_string())
+        }
+        e => {
+            error!("更新文档失败: {}", e);
+            ApiError::internal_server_error("更新文档失败")
+        }
     })?;
     
-    info!("文档更新成功: id={}, 标题={}", updated_doc.id, updated_doc.title);
+    info!("文档更新成功: id={}, 标题={}, 修订号={}", updated_doc.id, updated_doc.title, updated_doc.revision);
+    
+    // 内容变更后在后台增量重建索引，只为变更的块重新生成嵌入
+    if req.content.is_some() {
+        if let Some(client_manager) = ai_client {
+            spawn_reindex(db.get_ref().clone(), client_manager.get_ref().clone(), updated_doc.clone());
+        }
+    }
     
+    decrypt_document_content(&encryption, &mut updated_doc).await?;
+    let etag = revision_etag(updated_doc.revision);
     let response = DocumentResponse::from(updated_doc);
-    Ok(ApiResponse::ok(response).into_http_response().unwrap())
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, etag))
+        .json(ApiResponse::ok(response)))
 }
 
 /// 删除文档
@@ -884,6 +1134,8 @@ pub async fn update_document(
 pub async fn delete_document(
     db: web::Data<DatabaseConnection>,
     tenant_info: web::ReqData<TenantInfo>,
+    user: Option<web::ReqData<AuthenticatedUser>>,
+    api_key: Option<web::ReqData<ApiKeyInfo>>,
     path: web::Path<Uuid>,
 ) -> ActixResult<HttpResponse> {
     let doc_id = path.into_inner();
@@ -900,10 +1152,11 @@ pub async fn delete_document(
             ApiError::internal_server_error("查询文档失败")
         })?;
     
-    if doc.is_none() {
+    let Some(doc) = doc else {
         warn!("文档不存在或无权访问: id={}", doc_id);
         return Ok(HttpResponseBuilder::not_found::<()>("文档不存在").unwrap());
-    }
+    };
+    ensure_not_held(db.as_ref(), &doc, "delete").await?;
     
     // 执行删除
     Document::delete_by_id(doc_id)
@@ -915,6 +1168,8 @@ pub async fn delete_document(
         })?;
     
     info!("文档删除成功: id={}", doc_id);
+    record_usage(tenant_info.id, AnomalyMetric::DocumentDeletions, api_key.map(|key| key.key_id), 1);
+    publish_document_event(event_types::DOCUMENT_DELETED, tenant_info.id, user.map(|u| u.user_id), &doc);
     Ok(HttpResponseBuilder::no_content().unwrap())
 }
 
@@ -981,6 +1236,7 @@ pub async fn get_document_stats(
         (status = 403, description = "权限不足", body = ApiError),
         (status = 404, description = "文档不存在", body = ApiError),
         (status = 409, description = "文档正在处理中", body = ApiError),
+        (status = 429, description = "导入请求频率超限", body = ApiError),
         (status = 500, description = "服务器内部错误", body = ApiError)
     ),
     tag = "documents",
@@ -1052,7 +1308,152 @@ pub async fn reprocess_document(
     Ok(ApiResponse::ok(response).into_http_response().unwrap())
 }
 
+/// 复核并续期文档
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/{id}/renew",
+    params(
+        ("id" = Uuid, Path, description = "文档 ID")
+    ),
+    request_body = RenewDocumentRequest,
+    responses(
+        (status = 200, description = "续期成功", body = DocumentFreshnessEntry),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 404, description = "文档不存在", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn renew_document(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    req: Option<web::Json<RenewDocumentRequest>>,
+) -> ActixResult<HttpResponse> {
+    let doc_id = path.into_inner();
+    info!("文档续期请求: id={}, 租户={}", doc_id, tenant_info.id);
+
+    let entry = DocumentFreshnessService::new(db.get_ref().clone())
+        .renew_document(tenant_info.id, doc_id, req.map(|r| r.into_inner()).unwrap_or_default())
+        .await?;
+
+    HttpResponseBuilder::ok(entry)
+}
 
+/// 获取文档密级与段落标记
+#[utoipa::path(
+    get,
+    path = "/api/v1/documents/{id}/clearance",
+    params(
+        ("id" = Uuid, Path, description = "文档 ID")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = DocumentClearanceResponse),
+        (status = 404, description = "文档不存在", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_document_clearance(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let clearance = ClearanceService::new(db.get_ref().clone())
+        .get_document_clearance(tenant_info.id, path.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(clearance)
+}
+
+/// 设置文档密级与段落标记
+#[utoipa::path(
+    put,
+    path = "/api/v1/documents/{id}/clearance",
+    params(
+        ("id" = Uuid, Path, description = "文档 ID")
+    ),
+    request_body = UpdateDocumentClearanceRequest,
+    responses(
+        (status = 200, description = "设置成功", body = DocumentClearanceResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "密级不足", body = ApiError),
+        (status = 404, description = "文档不存在", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn update_document_clearance(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<UpdateDocumentClearanceRequest>,
+) -> ActixResult<HttpResponse> {
+    let doc_id = path.into_inner();
+    info!("设置文档密级请求: id={}, 租户={}, 用户={}", doc_id, tenant_info.id, user.user_id);
+
+    let clearance = ClearanceService::new(db.get_ref().clone())
+        .update_document_clearance(
+            tenant_info.id,
+            doc_id,
+            clearance_for(&user.role, &user.permissions),
+            req.into_inner(),
+        )
+        .await?;
+
+    HttpResponseBuilder::ok(clearance)
+}
+
+
+/// 获取相似文档
+///
+/// 以文档块向量的平均值衡量文档相似度，返回同一知识库内相似度不低于 `min_score` 的文档，按相似度降序分页。
+#[utoipa::path(
+    get,
+    path = "/api/v1/documents/{id}/similar",
+    params(
+        ("id" = Uuid, Path, description = "文档 ID"),
+        SimilarDocumentsQuery
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = PaginatedResponse<SimilarDocument>),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 404, description = "文档不存在", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_similar_documents(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    query: web::Query<SimilarDocumentsQuery>,
+) -> ActixResult<HttpResponse> {
+    let similar = SimilarDocumentService::new(db.get_ref().clone())
+        .find_similar(
+            tenant_info.id,
+            path.into_inner(),
+            clearance_for(&user.role, &user.permissions),
+            &query,
+        )
+        .await?;
+
+    HttpResponseBuilder::ok(similar)
+}
 
 /// 批量操作类型
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -1225,6 +1626,7 @@ pub struct BatchExportResponse {
 pub async fn batch_document_operation(
     db: web::Data<DatabaseConnection>,
     tenant_info: web::ReqData<TenantInfo>,
+    api_key: Option<web::ReqData<ApiKeyInfo>>,
     req: web::Json<BatchDocumentRequest>,
 ) -> ActixResult<HttpResponse> {
     info!("批量文档操作请求: 租户={}, 操作={:?}, 数量={}", 
@@ -1282,6 +1684,28 @@ pub async fn batch_document_operation(
         response.error_count += 1;
     }
     
+    // 删除与更新跳过处于法律保留状态的文档
+    let valid_docs = if matches!(req.operation, BatchDocumentOperation::Delete | BatchDocumentOperation::Update) {
+        let operation = format!("batch_{:?}", req.operation).to_lowercase();
+        let mut mutable_docs = Vec::with_capacity(valid_docs.len());
+        for doc in valid_docs {
+            match ensure_not_held(db.as_ref(), &doc, &operation).await {
+                Ok(()) => mutable_docs.push(doc),
+                Err(e) => {
+                    response.errors.push(BatchDocumentError {
+                        document_id: doc.id,
+                        error_code: "LEGAL_HOLD".to_string(),
+                        error_message: e.message,
+                    });
+                    response.error_count += 1;
+                }
+            }
+        }
+        mutable_docs
+    } else {
+        valid_docs
+    };
+    
     // 执行批量操作
     match req.operation {
         BatchDocumentOperation::Delete => {
@@ -1302,13 +1726,19 @@ pub async fn batch_document_operation(
                     }
                 }
             }
+            record_usage(
+                tenant_info.id,
+                AnomalyMetric::DocumentDeletions,
+                api_key.map(|key| key.key_id),
+                response.success_count as u64,
+            );
         }
         BatchDocumentOperation::Update => {
             // 从参数中获取更新数据
             if let Some(params) = &req.parameters {
                 if let Ok(update_data) = serde_json::from_value::<UpdateDocumentRequest>(params.clone()) {
                     for doc in valid_docs {
-                        match update_document_internal(db.as_ref(), doc.clone(), &update_data).await {
+                        match update_document_internal(db.as_ref(), tenant_info.id, doc.clone(), &update_data).await {
                             Ok(updated_doc) => {
                                 response.success_ids.push(updated_doc.id);
                                 response.success_count += 1;
@@ -1385,9 +1815,72 @@ pub async fn batch_document_operation(
     Ok(ApiResponse::ok(response).into_http_response().unwrap())
 }
 
+/// 批量归档文档
+///
+/// 归档的文档不再参与检索与问答，内容与向量保留，可通过恢复接口重新启用。
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/archive",
+    request_body = DocumentArchiveRequest,
+    responses(
+        (status = 200, description = "归档完成，返回已归档与跳过的文档", body = DocumentArchiveResult),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 401, description = "未授权", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn archive_documents(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    req: web::Json<DocumentArchiveRequest>,
+) -> ActixResult<HttpResponse> {
+    info!("批量归档文档请求: 租户={}, 数量={}", tenant_info.id, req.document_ids.len());
+
+    let result = ArchiveService::new(db.get_ref().clone())
+        .archive_documents(tenant_info.id, &req.document_ids)
+        .await?;
+
+    HttpResponseBuilder::ok(result)
+}
+
+/// 批量恢复已归档的文档
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/restore",
+    request_body = DocumentArchiveRequest,
+    responses(
+        (status = 200, description = "恢复完成，返回已恢复与跳过的文档", body = DocumentArchiveResult),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 401, description = "未授权", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn restore_documents(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    req: web::Json<DocumentArchiveRequest>,
+) -> ActixResult<HttpResponse> {
+    info!("批量恢复文档请求: 租户={}, 数量={}", tenant_info.id, req.document_ids.len());
+
+    let result = ArchiveService::new(db.get_ref().clone())
+        .restore_documents(tenant_info.id, &req.document_ids)
+        .await?;
+
+    HttpResponseBuilder::ok(result)
+}
+
 /// 内部更新文档函数
 async fn update_document_internal(
     db: &DatabaseConnection,
+    tenant_id: Uuid,
     doc: document::Model,
     req: &UpdateDocumentRequest,
 ) -> Result<document::Model, AiStudioError> {
@@ -1429,6 +1922,9 @@ async fn update_document_internal(
     }
     
     active_model.updated_at = sea_orm::Set(now);
+    TenantEncryptionService::from_config(db.clone())?
+        .encrypt_document(tenant_id, &mut active_model)
+        .await?;
     
     Ok(document::Entity::update(active_model).exec(db).await.map_err(|e| {
         AiStudioError::database(format!("更新文档失败: {}", e))
@@ -1445,6 +1941,7 @@ async fn update_document_internal(
         (status = 400, description = "请求参数错误", body = ApiError),
         (status = 401, description = "未授权", body = ApiError),
         (status = 413, description = "文件过大", body = ApiError),
+        (status = 429, description = "导入请求频率超限", body = ApiError),
         (status = 500, description = "服务器内部错误", body = ApiError)
     ),
     tag = "documents",
@@ -1707,21 +2204,50 @@ pub async fn get_batch_operation_status(
     Ok(ApiResponse::ok(status).into_http_response().unwrap())
 }
 
+/// 发布文档事件
+fn publish_document_event(event_type: &str, tenant_id: Uuid, actor_id: Option<Uuid>, doc: &document::Model) {
+    SystemEventBus::global().publish(
+        SystemEvent::new(
+            event_type,
+            Some(tenant_id),
+            serde_json::json!({
+                "document_id": doc.id,
+                "knowledge_base_id": doc.knowledge_base_id,
+                "title": doc.title,
+            }),
+        )
+        .with_actor(actor_id),
+    );
+}
+
 /// 配置文档路由
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/documents")
-            .route("", web::post().to(create_document))
+            // 导入类接口按 API 密钥令牌桶限流
+            .route("", web::post().to(create_document).wrap(IngestThrottleMiddleware::single()))
             .route("", web::get().to(list_documents))
-            .route("/upload", web::post().to(upload_document))
+            .route("/upload", web::post().to(upload_document).wrap(IngestThrottleMiddleware::single()))
             .route("/batch", web::post().to(batch_document_operation))
-            .route("/batch-import", web::post().to(batch_import_documents))
+            .route("/archive", web::post().to(archive_documents))
+            .route("/restore", web::post().to(restore_documents))
+            .route("/batch-import", web::post().to(batch_import_documents).wrap(IngestThrottleMiddleware::batch()))
             .route("/batch-export", web::post().to(batch_export_documents))
             .route("/batch/{batch_id}/status", web::get().to(get_batch_operation_status))
             .route("/{id}", web::get().to(get_document))
             .route("/{id}", web::put().to(update_document))
             .route("/{id}", web::delete().to(delete_document))
             .route("/{id}/stats", web::get().to(get_document_stats))
-            .route("/{id}/reprocess", web::post().to(reprocess_document))
+            .route("/{id}/reprocess", web::post().to(reprocess_document).wrap(IngestThrottleMiddleware::single()))
+            .route("/{id}/renew", web::post().to(renew_document))
+            .route("/{id}/clearance", web::get().to(get_document_clearance))
+            .route("/{id}/clearance", web::put().to(update_document_clearance))
+            .route("/{id}/similar", web::get().to(get_similar_documents))
+            .route("/{id}/comments", web::get().to(list_document_comments))
+            .route("/{id}/comments", web::post().to(create_document_comment))
+            .route("/{id}/comments/{comment_id}", web::put().to(update_document_comment))
+            .route("/{id}/comments/{comment_id}", web::delete().to(delete_document_comment))
+            .route("/{id}/comments/{comment_id}/resolve", web::post().to(resolve_document_comment))
+            .route("/{id}/comments/{comment_id}/reopen", web::post().to(reopen_document_comment))
     );
 }
\ No newline at end of file
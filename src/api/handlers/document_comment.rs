@@ -0,0 +1,224 @@
+// 文档评论 API 处理器
+// 路由挂在 `/documents` 作用域下，由 `document::configure_routes` 注册
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::api::middleware::auth::{AuthenticatedUser, PermissionChecker};
+use crate::api::middleware::tenant::TenantInfo;
+use crate::api::responses::HttpResponseBuilder;
+use crate::services::document_comment::{
+    CreateDocumentCommentRequest, DocumentCommentQuery, DocumentCommentService, UpdateDocumentCommentRequest,
+};
+
+/// 列出文档评论
+///
+/// 按主题返回评论与批注，回复嵌套在所属主题下。
+#[utoipa::path(
+    get,
+    path = "/api/v1/documents/{id}/comments",
+    params(
+        ("id" = Uuid, Path, description = "文档 ID"),
+        DocumentCommentQuery
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = Vec<CommentThread>),
+        (status = 404, description = "文档不存在", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_document_comments(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    query: web::Query<DocumentCommentQuery>,
+) -> ActixResult<HttpResponse> {
+    let threads = DocumentCommentService::new(db.get_ref().clone())
+        .list_threads(tenant_info.id, path.into_inner(), &query)
+        .await?;
+
+    HttpResponseBuilder::ok(threads)
+}
+
+/// 发表文档评论
+///
+/// 指定 `chunk_id` 时批注具体文档块，指定 `parent_id` 时回复已有评论；正文中的 `@用户名` 会通知对应成员。
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/{id}/comments",
+    params(
+        ("id" = Uuid, Path, description = "文档 ID")
+    ),
+    request_body = CreateDocumentCommentRequest,
+    responses(
+        (status = 201, description = "发表成功", body = DocumentComment),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 404, description = "文档、文档块或回复的评论不存在", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn create_document_comment(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<CreateDocumentCommentRequest>,
+) -> ActixResult<HttpResponse> {
+    let comment = DocumentCommentService::new(db.get_ref().clone())
+        .create(tenant_info.id, path.into_inner(), user.user_id, req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::created(comment)
+}
+
+/// 编辑文档评论
+#[utoipa::path(
+    put,
+    path = "/api/v1/documents/{id}/comments/{comment_id}",
+    params(
+        ("id" = Uuid, Path, description = "文档 ID"),
+        ("comment_id" = Uuid, Path, description = "评论 ID")
+    ),
+    request_body = UpdateDocumentCommentRequest,
+    responses(
+        (status = 200, description = "编辑成功", body = DocumentComment),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "只能编辑自己的评论", body = ApiError),
+        (status = 404, description = "评论不存在", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn update_document_comment(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: web::Json<UpdateDocumentCommentRequest>,
+) -> ActixResult<HttpResponse> {
+    let (document_id, comment_id) = path.into_inner();
+    let comment = DocumentCommentService::new(db.get_ref().clone())
+        .update(tenant_info.id, document_id, comment_id, user.user_id, req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(comment)
+}
+
+/// 删除文档评论
+///
+/// 作者或租户管理员可删除；删除主题评论会同时删除其回复。
+#[utoipa::path(
+    delete,
+    path = "/api/v1/documents/{id}/comments/{comment_id}",
+    params(
+        ("id" = Uuid, Path, description = "文档 ID"),
+        ("comment_id" = Uuid, Path, description = "评论 ID")
+    ),
+    responses(
+        (status = 204, description = "删除成功"),
+        (status = 403, description = "只能删除自己的评论", body = ApiError),
+        (status = 404, description = "评论不存在", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn delete_document_comment(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ActixResult<HttpResponse> {
+    let (document_id, comment_id) = path.into_inner();
+    DocumentCommentService::new(db.get_ref().clone())
+        .delete(
+            tenant_info.id,
+            document_id,
+            comment_id,
+            user.user_id,
+            PermissionChecker::has_role(&user, "admin"),
+        )
+        .await?;
+
+    HttpResponseBuilder::no_content()
+}
+
+/// 将评论主题标记为已解决
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/{id}/comments/{comment_id}/resolve",
+    params(
+        ("id" = Uuid, Path, description = "文档 ID"),
+        ("comment_id" = Uuid, Path, description = "主题评论 ID")
+    ),
+    responses(
+        (status = 200, description = "已解决", body = DocumentComment),
+        (status = 400, description = "不是主题评论", body = ApiError),
+        (status = 404, description = "评论不存在", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn resolve_document_comment(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ActixResult<HttpResponse> {
+    let (document_id, comment_id) = path.into_inner();
+    let comment = DocumentCommentService::new(db.get_ref().clone())
+        .set_resolved(tenant_info.id, document_id, comment_id, user.user_id, true)
+        .await?;
+
+    HttpResponseBuilder::ok(comment)
+}
+
+/// 重新打开已解决的评论主题
+#[utoipa::path(
+    post,
+    path = "/api/v1/documents/{id}/comments/{comment_id}/reopen",
+    params(
+        ("id" = Uuid, Path, description = "文档 ID"),
+        ("comment_id" = Uuid, Path, description = "主题评论 ID")
+    ),
+    responses(
+        (status = 200, description = "已重新打开", body = DocumentComment),
+        (status = 400, description = "不是主题评论", body = ApiError),
+        (status = 404, description = "评论不存在", body = ApiError)
+    ),
+    tag = "documents",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn reopen_document_comment(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ActixResult<HttpResponse> {
+    let (document_id, comment_id) = path.into_inner();
+    let comment = DocumentCommentService::new(db.get_ref().clone())
+        .set_resolved(tenant_info.id, document_id, comment_id, user.user_id, false)
+        .await?;
+
+    HttpResponseBuilder::ok(comment)
+}
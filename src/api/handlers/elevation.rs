@@ -0,0 +1,295 @@
+// 临时管理员提权 API 处理器
+// 用户申请限时管理员权限，由另一名常任管理员审批；提权期间的操作可按提权查询审计日志
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use sea_orm::DatabaseConnection;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::api::middleware::auth::{AuthenticatedUser, PermissionChecker};
+use crate::api::middleware::tenant::TenantInfo;
+use crate::api::responses::HttpResponseBuilder;
+use crate::config::ConfigLoader;
+use crate::errors::AiStudioError;
+use crate::services::admin_elevation::{
+    AdminElevationService, ElevationDecisionRequest, ListElevationsQuery, RequestElevationRequest,
+};
+use crate::services::audit_log::{AuditLogQuery, AuditLogService};
+
+fn elevation_service(db: &DatabaseConnection) -> AdminElevationService {
+    AdminElevationService::new(db.clone(), ConfigLoader::get().elevation.clone())
+}
+
+/// 审批提权需要常任管理员，提权中的用户不能审批
+fn ensure_standing_admin(user: &AuthenticatedUser) -> Result<(), AiStudioError> {
+    if !PermissionChecker::has_role(user, "admin") {
+        return Err(AiStudioError::forbidden("需要租户管理员权限"));
+    }
+    if user.elevation_id.is_some() {
+        return Err(AiStudioError::forbidden("提权中的用户不能审批提权申请"));
+    }
+    Ok(())
+}
+
+/// 申请临时管理员提权
+///
+/// 申请需由另一名管理员批准，批准后在 `duration_hours` 小时内按管理员处理，到期自动失效。
+#[utoipa::path(
+    post,
+    path = "/api/v1/elevations",
+    request_body = RequestElevationRequest,
+    responses(
+        (status = 201, description = "申请已提交", body = AdminElevation),
+        (status = 400, description = "理由或时长无效", body = ApiError),
+        (status = 409, description = "已有待审批或生效中的提权", body = ApiError)
+    ),
+    tag = "elevations",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn request_elevation(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: web::Json<RequestElevationRequest>,
+) -> ActixResult<HttpResponse> {
+    if user.is_admin && user.elevation_id.is_none() {
+        return Err(AiStudioError::validation("role", "当前用户已是管理员，无需提权").into());
+    }
+    info!("申请提权: tenant_id={}, user={}, hours={}", tenant_info.id, user.user_id, req.duration_hours);
+
+    let elevation = elevation_service(db.get_ref())
+        .request(tenant_info.id, user.user_id, req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::created(elevation)
+}
+
+/// 列出提权记录
+///
+/// 管理员可查看租户内所有记录，其他用户只能查看自己的记录。
+#[utoipa::path(
+    get,
+    path = "/api/v1/elevations",
+    params(ListElevationsQuery),
+    responses(
+        (status = 200, description = "提权记录", body = Vec<AdminElevation>)
+    ),
+    tag = "elevations",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_elevations(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    query: web::Query<ListElevationsQuery>,
+) -> ActixResult<HttpResponse> {
+    let mut query = query.into_inner();
+    if !PermissionChecker::has_role(&user, "admin") {
+        query.user_id = Some(user.user_id);
+    }
+
+    let elevations = elevation_service(db.get_ref())
+        .list(tenant_info.id, &query)
+        .await?;
+
+    HttpResponseBuilder::ok(elevations)
+}
+
+/// 获取提权记录
+#[utoipa::path(
+    get,
+    path = "/api/v1/elevations/{id}",
+    params(
+        ("id" = Uuid, Path, description = "提权 ID")
+    ),
+    responses(
+        (status = 200, description = "提权记录", body = AdminElevation),
+        (status = 404, description = "提权记录不存在", body = ApiError)
+    ),
+    tag = "elevations",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_elevation(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let elevation = elevation_service(db.get_ref())
+        .get(tenant_info.id, path.into_inner())
+        .await?;
+    if elevation.user_id != user.user_id && !PermissionChecker::has_role(&user, "admin") {
+        return Err(AiStudioError::not_found("提权记录").into());
+    }
+
+    HttpResponseBuilder::ok(elevation)
+}
+
+/// 批准提权申请
+///
+/// 审批人必须是常任管理员且不是申请人；到期时间自批准时起计算。
+#[utoipa::path(
+    post,
+    path = "/api/v1/elevations/{id}/approve",
+    params(
+        ("id" = Uuid, Path, description = "提权 ID")
+    ),
+    request_body = ElevationDecisionRequest,
+    responses(
+        (status = 200, description = "已批准", body = AdminElevation),
+        (status = 403, description = "无审批权限或审批自己的申请", body = ApiError),
+        (status = 409, description = "申请已被处理或超过审批时限", body = ApiError)
+    ),
+    tag = "elevations",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn approve_elevation(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<ElevationDecisionRequest>,
+) -> ActixResult<HttpResponse> {
+    ensure_standing_admin(&user)?;
+    let elevation_id = path.into_inner();
+    info!("批准提权: tenant_id={}, elevation_id={}, approver={}", tenant_info.id, elevation_id, user.user_id);
+
+    let elevation = elevation_service(db.get_ref())
+        .approve(tenant_info.id, elevation_id, user.user_id, req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(elevation)
+}
+
+/// 拒绝提权申请
+#[utoipa::path(
+    post,
+    path = "/api/v1/elevations/{id}/reject",
+    params(
+        ("id" = Uuid, Path, description = "提权 ID")
+    ),
+    request_body = ElevationDecisionRequest,
+    responses(
+        (status = 200, description = "已拒绝", body = AdminElevation),
+        (status = 403, description = "无审批权限或审批自己的申请", body = ApiError),
+        (status = 409, description = "申请已被处理", body = ApiError)
+    ),
+    tag = "elevations",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn reject_elevation(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<ElevationDecisionRequest>,
+) -> ActixResult<HttpResponse> {
+    ensure_standing_admin(&user)?;
+    let elevation_id = path.into_inner();
+    info!("拒绝提权: tenant_id={}, elevation_id={}, approver={}", tenant_info.id, elevation_id, user.user_id);
+
+    let elevation = elevation_service(db.get_ref())
+        .reject(tenant_info.id, elevation_id, user.user_id, req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(elevation)
+}
+
+/// 撤销提权
+///
+/// 申请人可撤回申请或提前结束提权，管理员可收回任意提权。
+#[utoipa::path(
+    post,
+    path = "/api/v1/elevations/{id}/revoke",
+    params(
+        ("id" = Uuid, Path, description = "提权 ID")
+    ),
+    responses(
+        (status = 200, description = "已撤销", body = AdminElevation),
+        (status = 403, description = "只能撤销自己的提权", body = ApiError),
+        (status = 409, description = "提权已结束", body = ApiError)
+    ),
+    tag = "elevations",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_elevation(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let elevation_id = path.into_inner();
+    info!("撤销提权: tenant_id={}, elevation_id={}, user={}", tenant_info.id, elevation_id, user.user_id);
+
+    let elevation = elevation_service(db.get_ref())
+        .revoke(tenant_info.id, elevation_id, user.user_id, PermissionChecker::has_role(&user, "admin"))
+        .await?;
+
+    HttpResponseBuilder::ok(elevation)
+}
+
+/// 查询提权期间的审计日志
+#[utoipa::path(
+    get,
+    path = "/api/v1/elevations/{id}/audit-logs",
+    params(
+        ("id" = Uuid, Path, description = "提权 ID"),
+        AuditLogQuery
+    ),
+    responses(
+        (status = 200, description = "审计日志", body = Vec<AuditLogEntry>),
+        (status = 404, description = "提权记录不存在", body = ApiError)
+    ),
+    tag = "elevations",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_elevation_audit_logs(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    query: web::Query<AuditLogQuery>,
+) -> ActixResult<HttpResponse> {
+    let elevation = elevation_service(db.get_ref())
+        .get(tenant_info.id, path.into_inner())
+        .await?;
+    if elevation.user_id != user.user_id && !PermissionChecker::has_role(&user, "admin") {
+        return Err(AiStudioError::not_found("提权记录").into());
+    }
+
+    let mut query = query.into_inner();
+    query.elevation_id = Some(elevation.id);
+    let entries = AuditLogService::new(db.get_ref().clone())
+        .list(tenant_info.id, &query)
+        .await?;
+
+    HttpResponseBuilder::ok(entries)
+}
+
+/// 配置提权路由
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/elevations")
+            .route("", web::post().to(request_elevation))
+            .route("", web::get().to(list_elevations))
+            .route("/{id}", web::get().to(get_elevation))
+            .route("/{id}/approve", web::post().to(approve_elevation))
+            .route("/{id}/reject", web::post().to(reject_elevation))
+            .route("/{id}/revoke", web::post().to(revoke_elevation))
+            .route("/{id}/audit-logs", web::get().to(list_elevation_audit_logs))
+    );
+}
@@ -0,0 +1,241 @@
+// 少样本示例 API 处理器
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use tracing::info;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::api::middleware::auth::AuthenticatedUser;
+use crate::api::middleware::tenant::TenantInfo;
+use crate::api::responses::HttpResponseBuilder;
+use crate::services::few_shot::{
+    CreateFewShotExampleRequest, FewShotExampleService, FewShotTarget, UpdateFewShotExampleRequest,
+};
+
+/// 示例列表查询参数
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct FewShotExampleQuery {
+    /// 按 Agent 过滤
+    pub agent_id: Option<Uuid>,
+    /// 按提示词模板过滤
+    pub prompt_template: Option<String>,
+    /// 按标签过滤，多个标签用逗号分隔，需全部匹配
+    pub tags: Option<String>,
+}
+
+/// 示例挑选预览请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SelectFewShotExamplesRequest {
+    /// 挂载目标
+    #[serde(flatten)]
+    pub target: FewShotTarget,
+    /// 当前任务描述
+    pub task: String,
+    /// 挑选数量，默认 3
+    pub limit: Option<usize>,
+}
+
+/// 创建少样本示例
+#[utoipa::path(
+    post,
+    path = "/api/v1/few-shot-examples",
+    request_body = CreateFewShotExampleRequest,
+    responses(
+        (status = 201, description = "创建示例成功", body = FewShotExampleResponse),
+        (status = 400, description = "请求参数错误", body = ApiError)
+    ),
+    tag = "few-shot-examples",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn create_few_shot_example(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: web::Json<CreateFewShotExampleRequest>,
+) -> ActixResult<HttpResponse> {
+    info!("创建少样本示例请求: tenant_id={}", tenant_info.id);
+
+    let example = FewShotExampleService::new(db.get_ref().clone())
+        .create_example(tenant_info.id, req.into_inner(), Some(user.user_id))
+        .await?;
+
+    HttpResponseBuilder::created(example)
+}
+
+/// 列出少样本示例
+#[utoipa::path(
+    get,
+    path = "/api/v1/few-shot-examples",
+    params(FewShotExampleQuery),
+    responses(
+        (status = 200, description = "获取示例列表成功", body = Vec<FewShotExampleResponse>)
+    ),
+    tag = "few-shot-examples",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_few_shot_examples(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    query: web::Query<FewShotExampleQuery>,
+) -> ActixResult<HttpResponse> {
+    let query = query.into_inner();
+    let target = FewShotTarget {
+        agent_id: query.agent_id,
+        prompt_template: query.prompt_template,
+    };
+    let tags: Vec<String> = query.tags
+        .map(|tags| {
+            tags.split(',')
+                .map(|tag| tag.trim().to_lowercase())
+                .filter(|tag| !tag.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let examples = FewShotExampleService::new(db.get_ref().clone())
+        .list_examples(tenant_info.id, &target, &tags)
+        .await?;
+
+    HttpResponseBuilder::ok(examples)
+}
+
+/// 获取少样本示例
+#[utoipa::path(
+    get,
+    path = "/api/v1/few-shot-examples/{id}",
+    params(
+        ("id" = Uuid, Path, description = "示例 ID")
+    ),
+    responses(
+        (status = 200, description = "获取示例成功", body = FewShotExampleResponse),
+        (status = 404, description = "示例不存在", body = ApiError)
+    ),
+    tag = "few-shot-examples",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_few_shot_example(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let example = FewShotExampleService::new(db.get_ref().clone())
+        .get_example(tenant_info.id, path.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(example)
+}
+
+/// 更新少样本示例
+#[utoipa::path(
+    put,
+    path = "/api/v1/few-shot-examples/{id}",
+    params(
+        ("id" = Uuid, Path, description = "示例 ID")
+    ),
+    request_body = UpdateFewShotExampleRequest,
+    responses(
+        (status = 200, description = "更新示例成功", body = FewShotExampleResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 404, description = "示例不存在", body = ApiError)
+    ),
+    tag = "few-shot-examples",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn update_few_shot_example(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    req: web::Json<UpdateFewShotExampleRequest>,
+) -> ActixResult<HttpResponse> {
+    let example = FewShotExampleService::new(db.get_ref().clone())
+        .update_example(tenant_info.id, path.into_inner(), req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(example)
+}
+
+/// 删除少样本示例
+#[utoipa::path(
+    delete,
+    path = "/api/v1/few-shot-examples/{id}",
+    params(
+        ("id" = Uuid, Path, description = "示例 ID")
+    ),
+    responses(
+        (status = 204, description = "删除示例成功"),
+        (status = 404, description = "示例不存在", body = ApiError)
+    ),
+    tag = "few-shot-examples",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn delete_few_shot_example(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let id = path.into_inner();
+    FewShotExampleService::new(db.get_ref().clone())
+        .delete_example(tenant_info.id, id)
+        .await?;
+
+    info!("少样本示例已删除: id={}", id);
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// 预览与任务最相关的示例
+#[utoipa::path(
+    post,
+    path = "/api/v1/few-shot-examples/select",
+    request_body = SelectFewShotExamplesRequest,
+    responses(
+        (status = 200, description = "挑选结果，按相关度排序", body = Vec<FewShotExampleResponse>),
+        (status = 400, description = "请求参数错误", body = ApiError)
+    ),
+    tag = "few-shot-examples",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn select_few_shot_examples(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    req: web::Json<SelectFewShotExamplesRequest>,
+) -> ActixResult<HttpResponse> {
+    let req = req.into_inner();
+    let examples = FewShotExampleService::new(db.get_ref().clone())
+        .select_examples(tenant_info.id, &req.target, &req.task, req.limit.unwrap_or(3))
+        .await?;
+
+    HttpResponseBuilder::ok(examples)
+}
+
+/// 配置少样本示例路由
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/few-shot-examples")
+            .route("", web::post().to(create_few_shot_example))
+            .route("", web::get().to(list_few_shot_examples))
+            .route("/select", web::post().to(select_few_shot_examples))
+            .route("/{id}", web::get().to(get_few_shot_example))
+            .route("/{id}", web::put().to(update_few_shot_example))
+            .route("/{id}", web::delete().to(delete_few_shot_example))
+    );
+}
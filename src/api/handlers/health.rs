@@ -1,12 +1,27 @@
 // 健康检查处理器
 
-use actix_web::{web, HttpResponse, Result as ActixResult};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Result as ActixResult};
 use chrono::Utc;
+use std::net::{IpAddr, SocketAddr};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::api::models::{HealthResponse, HealthStatus, DependencyHealth, SystemInfo};
+use crate::api::middleware::auth::AuthenticatedUser;
+use crate::api::models::{HealthResponse, HealthStatus, DependencyHealth, RedactedHealthResponse, SystemInfo};
 use crate::api::responses::HttpResponseBuilder;
+use crate::config::{ConfigLoader, HealthAccessConfig};
 use crate::db::DatabaseManager;
+use crate::errors::AiStudioError;
+use crate::services::process_role;
+use crate::services::replication::ReplicationService;
+
+/// 详细健康检查的可见范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HealthAccess {
+    /// 运维角色或内部网络：完整信息
+    Full,
+    /// 租户管理员：仅整体与各依赖服务的状态
+    Redacted,
+}
 
 /// 健康检查 API 文档
 // #[derive(OpenApi)]
@@ -44,7 +59,28 @@ pub async fn health_check() -> ActixResult<HttpResponse> {
 }
 
 /// 详细健康检查
-pub async fn health_detailed() -> ActixResult<HttpResponse> {
+///
+/// 运维角色或来自内部网络的请求返回完整信息；租户管理员返回隐去版本、错误信息与系统指标的精简版本；
+/// 其他请求被拒绝。
+#[utoipa::path(
+    get,
+    path = "/api/v1/health/detailed",
+    tag = "health",
+    responses(
+        (status = 200, description = "完整健康信息（运维角色或内部网络）或精简健康信息（租户管理员）", body = HealthResponse),
+        (status = 401, description = "未授权", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 503, description = "服务不可用", body = HealthResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn health_detailed(req: HttpRequest) -> ActixResult<HttpResponse> {
+    let config = &ConfigLoader::get().health;
+    let user = req.extensions().get::<AuthenticatedUser>().cloned();
+    let access = resolve_health_access(client_address(&req, config), user.as_ref(), config)?;
+
     let mut dependencies = Vec::new();
     let mut overall_status = HealthStatus::Healthy;
 
@@ -98,24 +134,34 @@ pub async fn health_detailed() -> ActixResult<HttpResponse> {
         HealthStatus::Unhealthy => 503,
     };
 
-    Ok(HttpResponse::build(actix_web::http::StatusCode::from_u16(status_code).unwrap())
-        .json(health_response))
+    let mut response = HttpResponse::build(actix_web::http::StatusCode::from_u16(status_code).unwrap());
+    match access {
+        HealthAccess::Full => Ok(response.json(health_response)),
+        HealthAccess::Redacted => Ok(response.json(RedactedHealthResponse::from(&health_response))),
+    }
 }
 
 /// 就绪检查
+///
+/// 按进程角色检查：worker 与 all 角色还需周期任务已经启动。
 pub async fn readiness_check() -> ActixResult<HttpResponse> {
+    let role = process_role::current();
+
     // 检查关键依赖是否可用
     let db_health = check_database_health().await;
-    
-    if matches!(db_health.status, HealthStatus::Unhealthy) {
+    let database_ready = !matches!(db_health.status, HealthStatus::Unhealthy);
+
+    if let Some(reason) = process_role::readiness_blocker(role, database_ready, process_role::workers_started()) {
         return Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
             "ready": false,
-            "reason": "数据库连接不可用"
+            "role": role.as_str(),
+            "reason": reason
         })));
     }
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
-        "ready": true
+        "ready": true,
+        "role": role.as_str()
     })))
 }
 
@@ -123,12 +169,69 @@ pub async fn readiness_check() -> ActixResult<HttpResponse> {
 pub async fn liveness_check() -> ActixResult<HttpResponse> {
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "alive": true,
+        "role": process_role::current().as_str(),
         "timestamp": Utc::now()
     })))
 }
 
+/// 跨区域复制延迟检查
+///
+/// 未启用复制时返回 `enabled: false`；延迟超过允许的最大值或无法确定时返回 503。
+pub async fn replication_check() -> ActixResult<HttpResponse> {
+    let Some(service) = ReplicationService::global() else {
+        return Ok(HttpResponse::Ok().json(serde_json::json!({
+            "enabled": false
+        })));
+    };
+
+    match service.lag_status().await {
+        Ok(status) if status.healthy => Ok(HttpResponse::Ok().json(status)),
+        Ok(status) => Ok(HttpResponse::ServiceUnavailable().json(status)),
+        Err(e) => Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "healthy": false,
+            "reason": e.to_string()
+        }))),
+    }
+}
+
 // 私有辅助函数
 
+/// 判定详细健康检查的可见范围
+fn resolve_health_access(
+    client: Option<IpAddr>,
+    user: Option<&AuthenticatedUser>,
+    config: &HealthAccessConfig,
+) -> Result<HealthAccess, AiStudioError> {
+    if client.is_some_and(|ip| config.is_internal_address(ip)) {
+        return Ok(HealthAccess::Full);
+    }
+
+    match user {
+        Some(user) if config.ops_roles.iter().any(|role| role == &user.role) => Ok(HealthAccess::Full),
+        Some(user) if user.is_admin => Ok(HealthAccess::Redacted),
+        Some(_) => Err(AiStudioError::forbidden("只有运维人员或租户管理员可以查看详细健康信息")),
+        None => Err(AiStudioError::unauthorized("查看详细健康信息需要认证")),
+    }
+}
+
+/// 客户端地址，开启 `trust_forwarded_for` 时优先使用代理转发的地址
+fn client_address(req: &HttpRequest, config: &HealthAccessConfig) -> Option<IpAddr> {
+    if config.trust_forwarded_for {
+        let forwarded = req.connection_info().realip_remote_addr().map(str::to_string);
+        if let Some(ip) = forwarded.as_deref().and_then(parse_address) {
+            return Some(ip);
+        }
+    }
+    req.peer_addr().map(|addr| addr.ip())
+}
+
+/// 解析可能带端口的地址
+fn parse_address(value: &str) -> Option<IpAddr> {
+    value.parse::<IpAddr>()
+        .ok()
+        .or_else(|| value.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
 /// 检查数据库健康状态
 async fn check_database_health() -> DependencyHealth {
     let start_time = std::time::Instant::now();
@@ -226,7 +329,48 @@ pub fn configure_health_routes(cfg: &mut web::ServiceConfig) {
         web::scope("/health")
             .route("", web::get().to(health_check))
             .route("/detailed", web::get().to(health_detailed))
+            .route("/replication", web::get().to(replication_check))
     )
     .route("/ready", web::get().to(readiness_check))
     .route("/live", web::get().to(liveness_check));
-}
\ No newline at end of file
+}
+
+/// 配置 worker 角色的健康检查路由，只暴露不依赖 API 服务的检查
+pub fn configure_worker_health_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/health")
+            .route("", web::get().to(health_check))
+            .route("/replication", web::get().to(replication_check))
+    )
+    .route("/ready", web::get().to(readiness_check))
+    .route("/live", web::get().to(liveness_check));
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(role: &str, is_admin: bool) -> AuthenticatedUser {
+        AuthenticatedUser {
+            user_id: uuid::Uuid::new_v4(),
+            tenant_id: uuid::Uuid::new_v4(),
+            username: "alice".to_string(),
+            role: role.to_string(),
+            permissions: vec![],
+            is_admin,
+            authenticated_at: Utc::now(),
+            elevation_id: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_health_access() {
+        let config = HealthAccessConfig::default();
+        let external = parse_address("203.0.113.7:443");
+
+        assert_eq!(resolve_health_access(parse_address("127.0.0.1"), None, &config).unwrap(), HealthAccess::Full);
+        assert_eq!(resolve_health_access(external, Some(&user("ops", false)), &config).unwrap(), HealthAccess::Full);
+        assert_eq!(resolve_health_access(external, Some(&user("admin", true)), &config).unwrap(), HealthAccess::Redacted);
+        assert!(resolve_health_access(external, Some(&user("user", false)), &config).is_err());
+        assert!(resolve_health_access(external, None, &config).is_err());
+    }
+}
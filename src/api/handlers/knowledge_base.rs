@@ -1,19 +1,59 @@
 // 知识库管理 API 处理器
 
-use actix_web::{web, HttpResponse, Result as ActixResult};
+use actix_multipart::Multipart;
+use actix_web::{http::header, web, HttpResponse, Result as ActixResult};
+use futures::stream::StreamExt;
 use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, PaginatorTrait, QuerySelect, ActiveModelTrait};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use tracing::{info, warn, error, debug};
 
+use crate::ai::chunker::{AiVectorizer, DocumentVectorizer};
+use crate::ai::model_policy::{check_model_allowed, load_tenant_model_policy};
+use crate::ai::rig_client::RigAiClientManager;
+use crate::ai::vector_store::VectorStoreRegistry;
 use crate::api::models::{PaginationQuery, PaginatedResponse, PaginationInfo};
 use crate::api::responses::{ApiResponse, ApiError, SuccessResponse, ErrorResponse, HttpResponseBuilder, ApiResponseExt};
-use crate::api::extractors::{TenantContext, UserContext};
-use crate::db::entities::{knowledge_base, prelude::*};
+use crate::api::extractors::{IfMatchExtractor, TenantContext, UserContext};
+use crate::api::middleware::auth::{AuthenticatedUser, PermissionChecker};
+use crate::api::middleware::ingest_throttle::IngestThrottleMiddleware;
+use crate::api::middleware::route_cache::RouteCacheMiddleware;
+use crate::api::middleware::tenant::TenantInfo;
+use crate::config::ConfigLoader;
+use crate::db::entities::{document, knowledge_base, prelude::*};
+use crate::db::{revision_etag, update_with_revision};
 use crate::errors::AiStudioError;
+use crate::services::archive::{ArchiveService, KnowledgeBaseArchiveResult};
+use crate::services::clearance::clearance_for;
+use crate::services::corpus_import::{
+    detect_format, parse_corpus, CorpusImportOptions, CorpusImportReport, CorpusImportService,
+};
+use crate::services::dataset::{DatasetQuery, DatasetService};
+use crate::services::duplicate_detection::{
+    DetectDuplicatesRequest, DuplicateDetectionService, ResolveDuplicateClusterRequest,
+};
+use crate::services::embedding_export::{
+    export_file_path as embedding_export_file_path, verify_download_token as verify_embedding_download_token,
+    CreateEmbeddingExportRequest, EmbeddingExportResponse, EmbeddingExportService,
+};
+use crate::services::faq::{CreateFaqEntryRequest, FaqService, UpdateFaqEntryRequest};
+use crate::services::freshness::DocumentFreshnessService;
+use crate::services::incremental_index::spawn_reindex;
+use crate::services::kb_snapshot::{KbSnapshotDiff, KbSnapshotResponse, KbSnapshotService};
+use crate::services::kb_stats::{KbStatsService, KbStorageHistoryPoint, KbStorageUsage};
 use crate::services::knowledge_base::{KnowledgeBaseService, KnowledgeBaseServiceFactory};
+use crate::services::legal_hold::LegalHoldService;
+use crate::services::pii_policy::{PiiOverrideRequest, PiiPolicyService, COMPLIANCE_OFFICER_ROLE};
+use crate::services::route_cache::{self, RouteCacheScope};
+use crate::services::source_health::SourceHealthService;
+use crate::services::task_queue::TaskQueueService;
+use crate::services::topic_map::TopicMapService;
+use crate::services::vector_migration::{VectorMigrationRequest, VectorMigrationService, VectorMigrationStatus};
+
+/// 外部语料导入文件的大小上限
+const MAX_CORPUS_IMPORT_BYTES: usize = 50 * 1024 * 1024;
 
 /// 知识库创建请求
 #[derive(Debug, Clone, Deserialize, ToSchema)]
@@ -90,6 +130,8 @@ pub struct KnowledgeBaseResponse {
     pub created_at: DateTime<Utc>,
     /// 更新时间
     pub updated_at: DateTime<Utc>,
+    /// 修订号，更新时通过 If-Match 携带以检测并发修改
+    pub revision: i32,
 }
 
 /// 知识库统计信息
@@ -115,6 +157,17 @@ pub struct KnowledgeBaseStats {
     pub last_indexed_at: Option<DateTime<Utc>>,
     /// 创建时间
     pub created_at: DateTime<Utc>,
+    /// 实时统计的存储用量，含嵌入数、数据库占用与处理失败的文档数
+    pub storage: Option<KbStorageUsage>,
+    /// 夜间汇总的每日存储统计，按日期升序
+    pub history: Vec<KbStorageHistoryPoint>,
+}
+
+/// 知识库统计查询参数
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct KnowledgeBaseStatsQuery {
+    /// 返回最近多少天的每日统计，默认 30，最多 365
+    pub history_days: Option<u32>,
 }
 
 /// 知识库搜索查询
@@ -159,6 +212,7 @@ impl From<knowledge_base::Model> for KnowledgeBaseResponse {
             needs_reindexing,
             created_at: model.created_at.with_timezone(&Utc),
             updated_at: model.updated_at.with_timezone(&Utc),
+            revision: model.revision,
         }
     }
 }
@@ -180,6 +234,8 @@ impl From<knowledge_base::Model> for KnowledgeBaseStats {
             average_chunk_size,
             last_indexed_at: model.last_indexed_at.map(|dt| dt.with_timezone(&Utc)),
             created_at: model.created_at.with_timezone(&Utc),
+            storage: None,
+            history: Vec::new(),
         }
     }
 }
@@ -233,6 +289,13 @@ pub async fn create_knowledge_base(
     let embedding_model = req.embedding_model.clone().unwrap_or_else(|| {
         config.vectorization_settings.model_name.clone()
     });
+    ensure_kb_models_allowed(db.as_ref(), tenant_ctx.tenant_id, Some(&embedding_model), Some(&config)).await?;
+    if !vector_store_registry(db.get_ref()).is_configured(config.vector_backend) {
+        return Err(AiStudioError::validation(
+            "config.vector_backend",
+            format!("未配置 {} 向量存储", config.vector_backend.as_str()),
+        ).into());
+    }
     
     // 创建知识库
     let kb_id = Uuid::new_v4();
@@ -255,6 +318,7 @@ pub async fn create_knowledge_base(
         last_indexed_at: sea_orm::Set(None),
         created_at: sea_orm::Set(now),
         updated_at: sea_orm::Set(now),
+        revision: sea_orm::Set(1),
     };
     
     let kb = KnowledgeBase::insert(new_kb)
@@ -266,6 +330,7 @@ pub async fn create_knowledge_base(
         })?;
     
     info!("知识库创建成功: id={}, 名称={}", kb.id, kb.name);
+    route_cache::invalidate(RouteCacheScope::KnowledgeBases, Some(tenant_ctx.tenant_id)).await;
     
     let response = KnowledgeBaseResponse::from(kb);
     Ok(SuccessResponse::created(response).into_http_response()?)
@@ -422,11 +487,16 @@ pub async fn get_knowledge_base(
         return Ok(ErrorResponse::forbidden::<()>("无权访问此知识库").into_http_response()?);
     }
     
+    let etag = revision_etag(kb.revision);
     let response = KnowledgeBaseResponse::from(kb);
-    Ok(SuccessResponse::ok(response).into_http_response()?)
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, etag))
+        .json(SuccessResponse::ok(response)))
 }
 
 /// 更新知识库
+///
+/// 携带 `If-Match` 时校验修订号，知识库已被其他用户修改时返回 409。
 #[utoipa::path(
     put,
     path = "/api/v1/knowledge-bases/{id}",
@@ -440,7 +510,7 @@ pub async fn get_knowledge_base(
         (status = 401, description = "未授权", body = ApiError),
         (status = 403, description = "权限不足", body = ApiError),
         (status = 404, description = "知识库不存在", body = ApiError),
-        (status = 409, description = "知识库名称已存在", body = ApiError),
+        (status = 409, description = "知识库名称已存在或知识库已被其他用户修改", body = ApiError),
         (status = 500, description = "服务器内部错误", body = ApiError)
     ),
     tag = "knowledge-bases",
@@ -453,6 +523,7 @@ pub async fn update_knowledge_base(
     db: web::Data<DatabaseConnection>,
     tenant_ctx: TenantContext,
     user_ctx: UserContext,
+    if_match: IfMatchExtractor,
     path: web::Path<Uuid>,
     req: web::Json<UpdateKnowledgeBaseRequest>,
 ) -> ActixResult<HttpResponse> {
@@ -482,6 +553,9 @@ pub async fn update_knowledge_base(
         warn!("用户无权修改知识库: user={}, kb={}", user_ctx.user.id, kb_id);
         return Ok(ErrorResponse::forbidden::<()>("无权修改此知识库").into_http_response()?);
     }
+    LegalHoldService::new(db.get_ref().clone())
+        .ensure_knowledge_base_mutable(kb_id, Some(user_ctx.user_id), "update")
+        .await?;
     
     // 检查名称冲突
     if let Some(new_name) = &req.name {
@@ -504,7 +578,17 @@ pub async fn update_knowledge_base(
         }
     }
     
+    ensure_kb_models_allowed(
+        db.as_ref(),
+        tenant_ctx.tenant_id,
+        req.embedding_model.as_deref(),
+        req.config.as_ref(),
+    ).await?;
+    
     // 准备更新数据
+    let expected_revision = if_match.revision.unwrap_or(kb.revision);
+    // 向量后端只能通过迁移切换，更新配置时沿用当前后端
+    let vector_backend = kb.get_config().map(|config| config.vector_backend).unwrap_or_default();
     let mut active_model: knowledge_base::ActiveModel = kb.into();
     let now = Utc::now().with_timezone(&chrono::FixedOffset::east_opt(8 * 3600).unwrap());
     
@@ -525,7 +609,9 @@ pub async fn update_knowledge_base(
     }
     
     if let Some(config) = &req.config {
-        active_model.config = sea_orm::Set(serde_json::to_value(config).unwrap().into());
+        let mut config = config.clone();
+        config.vector_backend = vector_backend;
+        active_model.config = sea_orm::Set(serde_json::to_value(&config).unwrap().into());
         active_model.vector_dimension = sea_orm::Set(config.vectorization_settings.dimension as i32);
     }
     
@@ -539,16 +625,33 @@ pub async fn update_knowledge_base(
     
     active_model.updated_at = sea_orm::Set(now);
     
-    // 执行更新
-    let updated_kb = active_model.update(db.as_ref()).await.map_err(|e| {
-        error!("更新知识库失败: {}", e);
-        ErrorResponse::internal_server_error::<()>("更新知识库失败")
-    })?;
+    // 执行更新，修订号不一致时拒绝覆盖
+    let updated_kb = match update_with_revision(
+        db.as_ref(),
+        active_model,
+        knowledge_base::Column::Revision,
+        expected_revision,
+        "知识库",
+    ).await {
+        Ok(updated_kb) => updated_kb,
+        Err(e @ AiStudioError::Conflict { .. }) => {
+            warn!("知识库更新冲突: id={}, 期望修订号={}", kb_id, expected_revision);
+            return Ok(ErrorResponse::conflict::<()>(e.to_string()).into_http_response()?);
+        }
+        Err(e) => {
+            error!("更新知识库失败: {}", e);
+            return Ok(ErrorResponse::internal_server_error::<()>("更新知识库失败").into_http_response()?);
+        }
+    };
     
-    info!("知识库更新成功: id={}, 名称={}", updated_kb.id, updated_kb.name);
+    info!("知识库更新成功: id={}, 名称={}, 修订号={}", updated_kb.id, updated_kb.name, updated_kb.revision);
+    route_cache::invalidate(RouteCacheScope::KnowledgeBases, Some(tenant_ctx.tenant_id)).await;
     
+    let etag = revision_etag(updated_kb.revision);
     let response = KnowledgeBaseResponse::from(updated_kb);
-    Ok(SuccessResponse::ok(response).into_http_response()?)
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, etag))
+        .json(SuccessResponse::ok(response)))
 }
 
 /// 删除知识库
@@ -604,6 +707,9 @@ pub async fn delete_knowledge_base(
         warn!("用户无权删除知识库: user={}, kb={}", user_ctx.user.id, kb_id);
         return Ok(ErrorResponse::forbidden::<()>("无权删除此知识库").into_http_response()?);
     }
+    LegalHoldService::new(db.get_ref().clone())
+        .ensure_knowledge_base_mutable(kb_id, Some(user_ctx.user_id), "delete")
+        .await?;
     
     // 检查是否包含文档
     if kb.document_count > 0 {
@@ -621,15 +727,95 @@ pub async fn delete_knowledge_base(
         })?;
     
     info!("知识库删除成功: id={}", kb_id);
+    route_cache::invalidate(RouteCacheScope::KnowledgeBases, Some(tenant_ctx.tenant_id)).await;
     Ok(SuccessResponse::no_content().into_http_response()?)
 }
 
+/// 归档知识库
+///
+/// 归档后知识库及其文档不再参与检索与问答，文档与向量保留，可通过恢复接口重新启用。
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/archive",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    responses(
+        (status = 200, description = "归档成功", body = KnowledgeBaseArchiveResult),
+        (status = 403, description = "权限不足或处于法律保留状态", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError),
+        (status = 409, description = "知识库已归档或正在处理中", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn archive_knowledge_base(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    info!("归档知识库请求: id={}, 租户={}, 用户={}", kb_id, tenant_info.id, user.user_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let result = ArchiveService::new(db.get_ref().clone())
+        .archive_knowledge_base(tenant_info.id, kb_id, user.user_id)
+        .await?;
+    route_cache::invalidate(RouteCacheScope::KnowledgeBases, Some(tenant_info.id)).await;
+
+    HttpResponseBuilder::ok(result)
+}
+
+/// 恢复已归档的知识库
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/restore",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    responses(
+        (status = 200, description = "恢复成功", body = KnowledgeBaseArchiveResult),
+        (status = 403, description = "权限不足或处于法律保留状态", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError),
+        (status = 409, description = "知识库未归档", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn restore_knowledge_base(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    info!("恢复知识库请求: id={}, 租户={}, 用户={}", kb_id, tenant_info.id, user.user_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let result = ArchiveService::new(db.get_ref().clone())
+        .restore_knowledge_base(tenant_info.id, kb_id, user.user_id)
+        .await?;
+    route_cache::invalidate(RouteCacheScope::KnowledgeBases, Some(tenant_info.id)).await;
+
+    HttpResponseBuilder::ok(result)
+}
+
 /// 获取知识库统计信息
 #[utoipa::path(
     get,
     path = "/api/v1/knowledge-bases/{id}/stats",
     params(
-        ("id" = Uuid, Path, description = "知识库 ID")
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        KnowledgeBaseStatsQuery
     ),
     responses(
         (status = 200, description = "获取知识库统计信息成功", body = KnowledgeBaseStats),
@@ -649,6 +835,7 @@ pub async fn get_knowledge_base_stats(
     tenant_ctx: TenantContext,
     user_ctx: UserContext,
     path: web::Path<Uuid>,
+    query: web::Query<KnowledgeBaseStatsQuery>,
 ) -> ActixResult<HttpResponse> {
     let kb_id = path.into_inner();
     debug!("获取知识库统计信息: id={}, 租户={}", kb_id, tenant_ctx.tenant_id);
@@ -676,7 +863,11 @@ pub async fn get_knowledge_base_stats(
         return Ok(ErrorResponse::forbidden::<()>("无权访问此知识库").into_http_response()?);
     }
     
-    let stats = KnowledgeBaseStats::from(kb);
+    let stats_service = KbStatsService::new(db.get_ref().clone());
+    let history_days = query.history_days.unwrap_or(30).min(365);
+    let mut stats = KnowledgeBaseStats::from(kb);
+    stats.storage = Some(stats_service.current(kb_id).await?);
+    stats.history = stats_service.history(kb_id, history_days).await?;
     Ok(SuccessResponse::ok(stats).into_http_response()?)
 }
 
@@ -693,6 +884,7 @@ pub async fn get_knowledge_base_stats(
         (status = 403, description = "权限不足", body = ApiError),
         (status = 404, description = "知识库不存在", body = ApiError),
         (status = 409, description = "知识库正在处理中", body = ApiError),
+        (status = 429, description = "导入请求频率超限", body = ApiError),
         (status = 500, description = "服务器内部错误", body = ApiError)
     ),
     tag = "knowledge-bases",
@@ -767,16 +959,1331 @@ pub async fn reindex_knowledge_base(
     Ok(SuccessResponse::accepted(response).into_http_response()?)
 }
 
+/// 创建知识库快照请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateKbSnapshotRequest {
+    /// 快照名称（QA 请求中通过 `kb_version` 引用）
+    pub name: String,
+    /// 快照描述
+    pub description: Option<String>,
+}
+
+/// 快照差异查询参数
+#[derive(Debug, Clone, Deserialize, ToSchema, IntoParams)]
+pub struct KbSnapshotDiffQuery {
+    /// 起始快照名称
+    pub from: String,
+    /// 目标快照名称，不指定时与知识库当前状态比较
+    pub to: Option<String>,
+}
+
+/// 向量导出下载链接查询参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingDownloadQuery {
+    /// 下载令牌
+    pub token: String,
+}
+
+/// 导入外部 RAG 语料
+///
+/// 接受 LangChain Document JSONL 或 LlamaIndex 节点导出（multipart 的 `file` 字段，`options` 字段为
+/// `CorpusImportOptions` JSON），按来源归并为文档并保留原有分块与元数据；预计算向量的模型与知识库一致时直接写入。
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/import",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    request_body(content = String, description = "导出文件与导入选项", content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "导入完成", body = CorpusImportReport),
+        (status = 400, description = "文件格式无法识别或内容无效", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError),
+        (status = 409, description = "知识库已归档", body = ApiError),
+        (status = 413, description = "文件过大", body = ApiError),
+        (status = 429, description = "导入请求频率超限", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn import_corpus(
+    db: web::Data<DatabaseConnection>,
+    ai_client: Option<web::Data<RigAiClientManager>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    mut payload: Multipart,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    info!("外部语料导入请求: id={}, 租户={}, 用户={}", kb_id, tenant_info.id, user.user_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let mut file_data: Option<Vec<u8>> = None;
+    let mut options = CorpusImportOptions::default();
+    while let Some(Ok(mut field)) = payload.next().await {
+        let field_name = field.name().to_string();
+        let mut data = Vec::new();
+        while let Some(Ok(chunk)) = field.next().await {
+            data.extend_from_slice(&chunk);
+            if data.len() > MAX_CORPUS_IMPORT_BYTES {
+                return Ok(HttpResponseBuilder::payload_too_large::<()>("导入文件大小超过限制（50MB）").unwrap());
+            }
+        }
+        match field_name.as_str() {
+            "file" => file_data = Some(data),
+            "options" => {
+                options = serde_json::from_slice(&data)
+                    .map_err(|e| AiStudioError::validation("options", format!("导入选项格式错误: {}", e)))?;
+            }
+            _ => {}
+        }
+    }
+
+    let data = file_data.ok_or_else(|| AiStudioError::validation("file", "缺少导入文件"))?;
+    let data = String::from_utf8(data).map_err(|_| AiStudioError::validation("file", "导入文件必须为 UTF-8 编码"))?;
+    let format = options.format
+        .or_else(|| detect_format(&data))
+        .ok_or_else(|| AiStudioError::validation("format", "无法识别导入格式，请在选项中指定 format"))?;
+    let corpus = parse_corpus(&data, format, options.group_by.as_deref())?;
+
+    let vectorizer = ai_client.map(|client_manager| AiVectorizer::new(client_manager.get_ref().clone()));
+    let report = CorpusImportService::new(db.get_ref().clone(), vector_store_registry(db.get_ref()))
+        .import(
+            tenant_info.id,
+            kb_id,
+            format,
+            corpus,
+            options.embedding_model.as_deref(),
+            vectorizer.as_ref().map(|v| v as &dyn DocumentVectorizer),
+        )
+        .await?;
+
+    HttpResponseBuilder::created(report)
+}
+
+/// 创建知识库快照
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/snapshots",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    request_body = CreateKbSnapshotRequest,
+    responses(
+        (status = 201, description = "快照创建成功", body = KbSnapshotResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError),
+        (status = 409, description = "快照名称已存在", body = ApiError),
+        (status = 500, description = "服务器内部错误", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn create_kb_snapshot(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<CreateKbSnapshotRequest>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    info!("创建知识库快照请求: id={}, 快照={}", kb_id, req.name);
+    
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+    
+    let req = req.into_inner();
+    let snapshot = KbSnapshotService::new(db.get_ref().clone())
+        .create_snapshot(tenant_info.id, kb_id, &req.name, req.description, user.user_id)
+        .await?;
+    
+    Ok(SuccessResponse::created(snapshot).into_http_response()?)
+}
+
+/// 列出知识库快照
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/snapshots",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    responses(
+        (status = 200, description = "获取快照列表成功", body = Vec<KbSnapshotResponse>),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError),
+        (status = 500, description = "服务器内部错误", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_kb_snapshots(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    debug!("列出知识库快照: id={}, 租户={}", kb_id, tenant_info.id);
+    
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+    
+    let snapshots = KbSnapshotService::new(db.get_ref().clone())
+        .list_snapshots(tenant_info.id, kb_id)
+        .await?;
+    
+    Ok(SuccessResponse::ok(snapshots).into_http_response()?)
+}
+
+/// 获取知识库快照详情
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/snapshots/{name}",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("name" = String, Path, description = "快照名称")
+    ),
+    responses(
+        (status = 200, description = "获取快照成功", body = KbSnapshotResponse),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "快照不存在", body = ApiError),
+        (status = 500, description = "服务器内部错误", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_kb_snapshot(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, String)>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, name) = path.into_inner();
+    debug!("获取知识库快照: id={}, 快照={}", kb_id, name);
+    
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+    
+    let snapshot = KbSnapshotService::new(db.get_ref().clone())
+        .get_snapshot(tenant_info.id, kb_id, &name)
+        .await?;
+    
+    Ok(SuccessResponse::ok(KbSnapshotResponse::from(snapshot)).into_http_response()?)
+}
+
+/// 删除知识库快照
+#[utoipa::path(
+    delete,
+    path = "/api/v1/knowledge-bases/{id}/snapshots/{name}",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("name" = String, Path, description = "快照名称")
+    ),
+    responses(
+        (status = 204, description = "快照删除成功"),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "快照不存在", body = ApiError),
+        (status = 500, description = "服务器内部错误", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn delete_kb_snapshot(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, String)>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, name) = path.into_inner();
+    info!("删除知识库快照请求: id={}, 快照={}", kb_id, name);
+    
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+    
+    KbSnapshotService::new(db.get_ref().clone())
+        .delete_snapshot(tenant_info.id, kb_id, &name)
+        .await?;
+    
+    Ok(SuccessResponse::no_content().into_http_response()?)
+}
+
+/// 比较知识库快照
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/snapshots/diff",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        KbSnapshotDiffQuery
+    ),
+    responses(
+        (status = 200, description = "比较快照成功", body = KbSnapshotDiff),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "快照不存在", body = ApiError),
+        (status = 500, description = "服务器内部错误", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn diff_kb_snapshots(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    query: web::Query<KbSnapshotDiffQuery>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    debug!("比较知识库快照: id={}, from={}, to={:?}", kb_id, query.from, query.to);
+    
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+    
+    let diff = KbSnapshotService::new(db.get_ref().clone())
+        .diff_snapshots(tenant_info.id, kb_id, &query.from, query.to.as_deref())
+        .await?;
+    
+    Ok(SuccessResponse::ok(diff).into_http_response()?)
+}
+
+/// 校验知识库引用的嵌入与重排序模型符合租户的模型使用策略
+async fn ensure_kb_models_allowed(
+    db: &DatabaseConnection,
+    tenant_id: Uuid,
+    embedding_model: Option<&str>,
+    config: Option<&knowledge_base::KnowledgeBaseConfig>,
+) -> Result<(), AiStudioError> {
+    let policy = load_tenant_model_policy(db, tenant_id).await?;
+    
+    if let Some(embedding_model) = embedding_model {
+        check_model_allowed(&policy, "embedding_model", embedding_model)?;
+    }
+    if let Some(config) = config {
+        check_model_allowed(&policy, "config.vectorization_settings.model_name", &config.vectorization_settings.model_name)?;
+        if let Some(reranking_model) = &config.retrieval_settings.reranking_model {
+            check_model_allowed(&policy, "config.retrieval_settings.reranking_model", reranking_model)?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// 获取过期与待复核文档报表
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/stale-documents",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    responses(
+        (status = 200, description = "获取报表成功", body = StaleDocumentReport),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_stale_documents(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    debug!("获取过期文档报表: id={}, 租户={}", kb_id, tenant_info.id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let report = DocumentFreshnessService::new(db.get_ref().clone())
+        .stale_report(tenant_info.id, kb_id)
+        .await?;
+
+    HttpResponseBuilder::ok(report)
+}
+
+/// 获取来源链接健康报表
+///
+/// 列出网页导入文档最近一次来源检查中发现的失效、重定向和内容漂移。
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/source-health",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    responses(
+        (status = 200, description = "获取报表成功", body = SourceHealthReport),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_source_health(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    debug!("获取来源健康报表: id={}, 租户={}", kb_id, tenant_info.id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let report = SourceHealthService::new(db.get_ref().clone())?
+        .report(tenant_info.id, kb_id)
+        .await?;
+
+    HttpResponseBuilder::ok(report)
+}
+
+/// 发起重复文档检测
+///
+/// 在后台任务中计算知识库内文档的 MinHash 签名，找出内容相近的文档并按簇生成报告。
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/duplicates",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    request_body = DetectDuplicatesRequest,
+    responses(
+        (status = 202, description = "检测任务已提交", body = DuplicateReportResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn detect_duplicate_documents(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: Option<web::Json<DetectDuplicatesRequest>>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    info!("发起重复文档检测: id={}, 租户={}", kb_id, tenant_info.id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let request = req.map(|req| req.into_inner()).unwrap_or_default();
+    let report = duplicate_detection_service(db.get_ref())?
+        .submit(tenant_info.id, kb_id, user.user_id, request)
+        .await?;
+
+    Ok(HttpResponse::Accepted().json(ApiResponse::accepted(report)))
+}
+
+/// 获取重复文档检测报告
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/duplicates/{report_id}",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("report_id" = Uuid, Path, description = "报告 ID")
+    ),
+    responses(
+        (status = 200, description = "获取报告成功", body = DuplicateReportResponse),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "报告不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_duplicate_report(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, report_id) = path.into_inner();
+    debug!("获取重复文档检测报告: id={}, report_id={}", kb_id, report_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let report = duplicate_detection_service(db.get_ref())?
+        .report(tenant_info.id, kb_id, report_id)
+        .await?;
+
+    HttpResponseBuilder::ok(report)
+}
+
+/// 处理重复文档簇
+///
+/// 保留簇内一个文档并归档其他文档；`merge` 时还会把其他文档的标签并入保留文档。
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/duplicates/{report_id}/clusters/{cluster_id}/resolve",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("report_id" = Uuid, Path, description = "报告 ID"),
+        ("cluster_id" = u32, Path, description = "簇编号")
+    ),
+    request_body = ResolveDuplicateClusterRequest,
+    responses(
+        (status = 200, description = "处理成功", body = DuplicateClusterResolution),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "报告或簇不存在", body = ApiError),
+        (status = 409, description = "检测未完成或文档已被修改", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn resolve_duplicate_cluster(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid, u32)>,
+    req: web::Json<ResolveDuplicateClusterRequest>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, report_id, cluster_id) = path.into_inner();
+    info!("处理重复文档簇: id={}, report_id={}, cluster={}, 用户={}", kb_id, report_id, cluster_id, user.user_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let resolution = duplicate_detection_service(db.get_ref())?
+        .resolve_cluster(tenant_info.id, kb_id, report_id, cluster_id, req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(resolution)
+}
+
+/// 重复文档检测服务，依赖应用启动时安装的全局任务队列
+fn duplicate_detection_service(db: &DatabaseConnection) -> Result<DuplicateDetectionService, AiStudioError> {
+    let queue = TaskQueueService::global()
+        .ok_or_else(|| AiStudioError::internal("任务队列未初始化"))?;
+    Ok(DuplicateDetectionService::new(db.clone(), queue))
+}
+
+/// 发起知识库主题聚类
+///
+/// 在后台任务中将文档的平均向量聚类为主题并生成主题名称，完成后通过主题地图接口查看结果。
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/topics",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    responses(
+        (status = 202, description = "聚类任务已提交", body = TopicClusteringJob),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn cluster_knowledge_base_topics(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    info!("发起主题聚类: id={}, 租户={}", kb_id, tenant_info.id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let job = topic_map_service(db.get_ref())?
+        .submit(tenant_info.id, kb_id, user.user_id)
+        .await?;
+
+    Ok(HttpResponse::Accepted().json(ApiResponse::accepted(job)))
+}
+
+/// 获取主题聚类任务状态
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/topics/jobs/{task_id}",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("task_id" = Uuid, Path, description = "任务 ID")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = TopicClusteringJob),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "任务不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_topic_clustering_job(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, task_id) = path.into_inner();
+    debug!("获取主题聚类任务: id={}, task_id={}", kb_id, task_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let job = topic_map_service(db.get_ref())?
+        .job(tenant_info.id, kb_id, task_id)
+        .await?;
+
+    HttpResponseBuilder::ok(job)
+}
+
+/// 获取知识库主题地图
+///
+/// 返回最近一次聚类得到的主题、规模与代表文档；占比很小的主题提示覆盖不足，与相邻主题相似度很高的主题提示内容重叠。
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/topics",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = KbTopicMap),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在或尚未生成主题地图", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_knowledge_base_topics(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    debug!("获取主题地图: id={}, 租户={}", kb_id, tenant_info.id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let topic_map = topic_map_service(db.get_ref())?
+        .topic_map(tenant_info.id, kb_id, clearance_for(&user.role, &user.permissions))
+        .await?;
+
+    HttpResponseBuilder::ok(topic_map)
+}
+
+/// 主题地图服务，依赖应用启动时安装的全局任务队列
+fn topic_map_service(db: &DatabaseConnection) -> Result<TopicMapService, AiStudioError> {
+    let queue = TaskQueueService::global()
+        .ok_or_else(|| AiStudioError::internal("任务队列未初始化"))?;
+    Ok(TopicMapService::new(db.clone(), queue))
+}
+
+/// 迁移知识库的向量存储后端
+///
+/// 在后台将向量复制到目标后端，校验数量后切换知识库的 `vector_backend`；默认删除原后端的数据。
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/vector-backend/migrations",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    request_body = VectorMigrationRequest,
+    responses(
+        (status = 202, description = "迁移任务已提交", body = VectorMigrationStatus),
+        (status = 400, description = "目标后端未配置或与当前后端相同", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError),
+        (status = 409, description = "已有进行中的迁移", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn migrate_vector_backend(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<VectorMigrationRequest>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    info!("发起向量存储迁移: id={}, target={}, 租户={}", kb_id, req.target.as_str(), tenant_info.id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let status = vector_migration_service(db.get_ref())?
+        .submit(tenant_info.id, kb_id, user.user_id, req.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Accepted().json(ApiResponse::accepted(status)))
+}
+
+/// 获取向量存储迁移任务状态
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/vector-backend/migrations/{task_id}",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("task_id" = Uuid, Path, description = "迁移任务 ID")
+    ),
+    responses(
+        (status = 200, description = "获取迁移状态成功", body = VectorMigrationStatus),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "迁移任务不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_vector_migration(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, task_id) = path.into_inner();
+    debug!("获取向量存储迁移状态: id={}, task_id={}", kb_id, task_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let status = vector_migration_service(db.get_ref())?
+        .status(tenant_info.id, kb_id, task_id)
+        .await?;
+
+    HttpResponseBuilder::ok(status)
+}
+
+/// 创建知识库向量导出
+///
+/// 在后台任务中将文档块正文、元数据与当前嵌入模型的向量导出为 JSONL 或 Parquet 文件，
+/// 供离线评估或迁移到其他系统。仅租户管理员可用。
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/embedding-exports",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    request_body = CreateEmbeddingExportRequest,
+    responses(
+        (status = 202, description = "导出任务已提交", body = EmbeddingExportResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn create_embedding_export(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<CreateEmbeddingExportRequest>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    if !user.is_admin {
+        return Err(AiStudioError::forbidden("只有租户管理员可以导出知识库向量").into());
+    }
+    info!("创建向量导出: id={}, 格式={:?}, 租户={}, 用户={}", kb_id, req.format, tenant_info.id, user.user_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let response = embedding_export_service()?
+        .submit(tenant_info.id, kb_id, user.user_id, req.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Accepted().json(ApiResponse::accepted(response)))
+}
+
+/// 查询知识库向量导出状态
+///
+/// 导出完成后返回签名下载链接。仅租户管理员可用。
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/embedding-exports/{export_id}",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("export_id" = Uuid, Path, description = "导出 ID")
+    ),
+    responses(
+        (status = 200, description = "获取导出状态成功", body = EmbeddingExportResponse),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "导出任务不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_embedding_export(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, export_id) = path.into_inner();
+    if !user.is_admin {
+        return Err(AiStudioError::forbidden("只有租户管理员可以导出知识库向量").into());
+    }
+    debug!("获取向量导出状态: id={}, export_id={}", kb_id, export_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let response = embedding_export_service()?
+        .status(tenant_info.id, kb_id, export_id)
+        .await?;
+
+    HttpResponseBuilder::ok(response)
+}
+
+/// 下载知识库向量导出文件
+///
+/// 通过签名链接访问，无需登录；链接过期后需重新查询导出状态获取新链接。
+#[utoipa::path(
+    get,
+    path = "/api/v1/downloads/embeddings/{export_id}",
+    params(
+        ("export_id" = Uuid, Path, description = "导出 ID"),
+        ("token" = String, Query, description = "下载令牌")
+    ),
+    responses(
+        (status = 200, description = "导出文件"),
+        (status = 403, description = "下载链接无效或已过期", body = ApiError),
+        (status = 404, description = "导出文件不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases"
+)]
+pub async fn download_embedding_export(
+    path: web::Path<Uuid>,
+    query: web::Query<EmbeddingDownloadQuery>,
+) -> ActixResult<HttpResponse> {
+    let export_id = path.into_inner();
+    let config = ConfigLoader::get();
+    let claims = verify_embedding_download_token(&config.security.jwt_secret, export_id, &query.token)?;
+
+    let file_path = embedding_export_file_path(
+        std::path::Path::new(&config.storage.path),
+        claims.tid,
+        export_id,
+        claims.fmt,
+    );
+    let content = tokio::fs::read(&file_path)
+        .await
+        .map_err(|_| AiStudioError::not_found("导出文件"))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(claims.fmt.content_type())
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"embeddings-{}.{}\"", export_id, claims.fmt.extension()),
+        ))
+        .body(content))
+}
+
+/// 列出知识库中的数据集
+///
+/// CSV/Excel 文档上传后按工作表导入为数据集，返回列名与推断的列类型。
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/datasets",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    responses(
+        (status = 200, description = "获取数据集列表成功", body = Vec<DatasetSummary>),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_datasets(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    debug!("获取数据集列表: id={}", kb_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let datasets = DatasetService::new(db.get_ref().clone())
+        .list(tenant_info.id, Some(kb_id))
+        .await?;
+
+    HttpResponseBuilder::ok(datasets)
+}
+
+/// 查询数据集
+///
+/// 支持过滤、分组与 count/sum/avg/min/max 聚合；未指定分组与聚合时返回明细行。
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/datasets/{dataset_id}/query",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("dataset_id" = Uuid, Path, description = "数据集 ID")
+    ),
+    request_body = DatasetQuery,
+    responses(
+        (status = 200, description = "查询成功", body = DatasetQueryResult),
+        (status = 400, description = "列不存在或聚合与列类型不匹配", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "数据集不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn query_dataset(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: web::Json<DatasetQuery>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, dataset_id) = path.into_inner();
+    debug!("查询数据集: id={}, dataset_id={}", kb_id, dataset_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let service = DatasetService::new(db.get_ref().clone());
+    if service.get(tenant_info.id, dataset_id).await?.knowledge_base_id != kb_id {
+        return Err(AiStudioError::not_found("数据集").into());
+    }
+    let result = service.query(tenant_info.id, dataset_id, &req).await?;
+
+    HttpResponseBuilder::ok(result)
+}
+
+/// 获取知识库的个人信息索引报告
+///
+/// 列出索引时检测到个人信息的文档块及其处理方式，内容预览已脱敏。仅合规人员可访问。
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/pii-report",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    responses(
+        (status = 200, description = "获取报告成功", body = PiiReport),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_pii_report(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    debug!("获取个人信息索引报告: id={}", kb_id);
+
+    let kb = ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+    if !PermissionChecker::has_role(&user, COMPLIANCE_OFFICER_ROLE) {
+        return Err(AiStudioError::forbidden("需要合规人员权限").into());
+    }
+
+    let report = PiiPolicyService::new(db.get_ref().clone())
+        .report(kb_id, kb.get_config().map(|config| config.pii_policy).unwrap_or_default())
+        .await?;
+
+    HttpResponseBuilder::ok(report)
+}
+
+/// 对含个人信息的文档块作出裁定
+///
+/// 裁定按内容记录，对知识库中相同内容的文档块均生效；已配置 AI 服务时相关文档在后台重建索引。仅合规人员可操作。
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/pii-report/chunks/{chunk_id}/override",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("chunk_id" = Uuid, Path, description = "文档块 ID")
+    ),
+    request_body = PiiOverrideRequest,
+    responses(
+        (status = 200, description = "裁定已记录", body = PiiOverride),
+        (status = 400, description = "文档块未检测到个人信息", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "文档块不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn override_pii_chunk(
+    db: web::Data<DatabaseConnection>,
+    ai_client: Option<web::Data<RigAiClientManager>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: web::Json<PiiOverrideRequest>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, chunk_id) = path.into_inner();
+    info!("个人信息裁定: id={}, chunk_id={}, user={}", kb_id, chunk_id, user.user_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+    if !PermissionChecker::has_role(&user, COMPLIANCE_OFFICER_ROLE) {
+        return Err(AiStudioError::forbidden("需要合规人员权限").into());
+    }
+
+    let result = PiiPolicyService::new(db.get_ref().clone())
+        .set_override(kb_id, chunk_id, req.into_inner(), user.user_id)
+        .await?;
+
+    if let Some(client_manager) = ai_client {
+        let documents = Document::find()
+            .filter(document::Column::Id.is_in(result.affected_documents.clone()))
+            .all(db.get_ref())
+            .await
+            .map_err(AiStudioError::from)?;
+        for doc in documents {
+            spawn_reindex(db.get_ref().clone(), client_manager.get_ref().clone(), doc);
+        }
+    } else {
+        warn!("未配置 AI 服务，裁定将在相关文档下次重建索引时生效: id={}", kb_id);
+    }
+
+    HttpResponseBuilder::ok(result)
+}
+
+/// 按应用配置创建向量存储注册表
+fn vector_store_registry(db: &DatabaseConnection) -> VectorStoreRegistry {
+    VectorStoreRegistry::new(db.clone(), ConfigLoader::get().vector.clone())
+}
+
+/// 向量存储迁移服务，依赖应用启动时安装的全局任务队列
+fn vector_migration_service(db: &DatabaseConnection) -> Result<VectorMigrationService, AiStudioError> {
+    let queue = TaskQueueService::global()
+        .ok_or_else(|| AiStudioError::internal("任务队列未初始化"))?;
+    Ok(VectorMigrationService::new(std::sync::Arc::new(vector_store_registry(db)), queue))
+}
+
+/// 向量导出服务，依赖应用启动时安装的全局任务队列
+fn embedding_export_service() -> Result<EmbeddingExportService, AiStudioError> {
+    let queue = TaskQueueService::global()
+        .ok_or_else(|| AiStudioError::internal("任务队列未初始化"))?;
+    Ok(EmbeddingExportService::new(queue, ConfigLoader::get().security.jwt_secret.clone()))
+}
+
+/// 创建 FAQ/术语表条目
+#[utoipa::path(
+    post,
+    path = "/api/v1/knowledge-bases/{id}/faq",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    request_body = CreateFaqEntryRequest,
+    responses(
+        (status = 201, description = "条目创建成功", body = FaqEntryResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn create_faq_entry(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<CreateFaqEntryRequest>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+    info!("创建 FAQ 条目请求: kb={}, 租户={}", kb_id, tenant_info.id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let entry = FaqService::new(db.get_ref().clone())
+        .create_entry(
+            tenant_info.id,
+            kb_id,
+            clearance_for(&user.role, &user.permissions),
+            req.into_inner(),
+            Some(user.user_id),
+        )
+        .await?;
+
+    HttpResponseBuilder::created(entry)
+}
+
+/// 列出 FAQ/术语表条目
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/faq",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID")
+    ),
+    responses(
+        (status = 200, description = "获取条目列表成功", body = Vec<FaqEntryResponse>),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_faq_entries(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let kb_id = path.into_inner();
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let entries = FaqService::new(db.get_ref().clone())
+        .list_entries(tenant_info.id, kb_id, clearance_for(&user.role, &user.permissions))
+        .await?;
+
+    HttpResponseBuilder::ok(entries)
+}
+
+/// 获取 FAQ/术语表条目
+#[utoipa::path(
+    get,
+    path = "/api/v1/knowledge-bases/{id}/faq/{entry_id}",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("entry_id" = Uuid, Path, description = "条目 ID")
+    ),
+    responses(
+        (status = 200, description = "获取条目成功", body = FaqEntryResponse),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "条目不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_faq_entry(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, entry_id) = path.into_inner();
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let entry = FaqService::new(db.get_ref().clone())
+        .get_entry(tenant_info.id, kb_id, entry_id, clearance_for(&user.role, &user.permissions))
+        .await?;
+
+    HttpResponseBuilder::ok(entry)
+}
+
+/// 更新 FAQ/术语表条目
+#[utoipa::path(
+    put,
+    path = "/api/v1/knowledge-bases/{id}/faq/{entry_id}",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("entry_id" = Uuid, Path, description = "条目 ID")
+    ),
+    request_body = UpdateFaqEntryRequest,
+    responses(
+        (status = 200, description = "条目更新成功", body = FaqEntryResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "条目不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn update_faq_entry(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: web::Json<UpdateFaqEntryRequest>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, entry_id) = path.into_inner();
+    info!("更新 FAQ 条目请求: kb={}, entry={}", kb_id, entry_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    let entry = FaqService::new(db.get_ref().clone())
+        .update_entry(
+            tenant_info.id,
+            kb_id,
+            entry_id,
+            clearance_for(&user.role, &user.permissions),
+            req.into_inner(),
+        )
+        .await?;
+
+    HttpResponseBuilder::ok(entry)
+}
+
+/// 删除 FAQ/术语表条目
+#[utoipa::path(
+    delete,
+    path = "/api/v1/knowledge-bases/{id}/faq/{entry_id}",
+    params(
+        ("id" = Uuid, Path, description = "知识库 ID"),
+        ("entry_id" = Uuid, Path, description = "条目 ID")
+    ),
+    responses(
+        (status = 204, description = "条目删除成功"),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "条目不存在", body = ApiError)
+    ),
+    tag = "knowledge-bases",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn delete_faq_entry(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> ActixResult<HttpResponse> {
+    let (kb_id, entry_id) = path.into_inner();
+    info!("删除 FAQ 条目请求: kb={}, entry={}", kb_id, entry_id);
+
+    ensure_knowledge_base_access(db.as_ref(), &tenant_info, &user, kb_id).await?;
+
+    FaqService::new(db.get_ref().clone())
+        .delete_entry(tenant_info.id, kb_id, entry_id, clearance_for(&user.role, &user.permissions))
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// 校验知识库存在且当前用户有权访问
+async fn ensure_knowledge_base_access(
+    db: &DatabaseConnection,
+    tenant_info: &TenantInfo,
+    user: &AuthenticatedUser,
+    kb_id: Uuid,
+) -> Result<knowledge_base::Model, AiStudioError> {
+    let kb = KnowledgeBase::find_by_id(kb_id)
+        .filter(knowledge_base::Column::TenantId.eq(tenant_info.id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AiStudioError::not_found("知识库"))?;
+    
+    if !kb.has_access(&user.role, &user.user_id.to_string()).unwrap_or(false) {
+        warn!("用户无权访问知识库快照: user={}, kb={}", user.user_id, kb_id);
+        return Err(AiStudioError::forbidden("无权访问此知识库"));
+    }
+    
+    Ok(kb)
+}
+
 /// 配置知识库路由
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/knowledge-bases")
             .route("", web::post().to(create_knowledge_base))
-            .route("", web::get().to(list_knowledge_bases))
-            .route("/{id}", web::get().to(get_knowledge_base))
+            .route("", web::get().to(list_knowledge_bases).wrap(
+                RouteCacheMiddleware::new(RouteCacheScope::KnowledgeBases).ttl_secs(30).vary_by_tenant().vary_by_query(),
+            ))
+            .route("/{id}", web::get().to(get_knowledge_base).wrap(
+                RouteCacheMiddleware::new(RouteCacheScope::KnowledgeBases).ttl_secs(60).vary_by_tenant().vary_by_user(),
+            ))
             .route("/{id}", web::put().to(update_knowledge_base))
             .route("/{id}", web::delete().to(delete_knowledge_base))
             .route("/{id}/stats", web::get().to(get_knowledge_base_stats))
-            .route("/{id}/reindex", web::post().to(reindex_knowledge_base))
+            .route("/{id}/archive", web::post().to(archive_knowledge_base))
+            .route("/{id}/restore", web::post().to(restore_knowledge_base))
+            .route("/{id}/reindex", web::post().to(reindex_knowledge_base).wrap(IngestThrottleMiddleware::batch()))
+            .route("/{id}/import", web::post().to(import_corpus).wrap(IngestThrottleMiddleware::batch()))
+            .route("/{id}/stale-documents", web::get().to(get_stale_documents))
+            .route("/{id}/source-health", web::get().to(get_source_health))
+            .route("/{id}/duplicates", web::post().to(detect_duplicate_documents))
+            .route("/{id}/duplicates/{report_id}", web::get().to(get_duplicate_report))
+            .route("/{id}/topics", web::post().to(cluster_knowledge_base_topics))
+            .route("/{id}/topics", web::get().to(get_knowledge_base_topics))
+            .route("/{id}/topics/jobs/{task_id}", web::get().to(get_topic_clustering_job))
+            .route("/{id}/vector-backend/migrations", web::post().to(migrate_vector_backend))
+            .route("/{id}/vector-backend/migrations/{task_id}", web::get().to(get_vector_migration))
+            .route("/{id}/embedding-exports", web::post().to(create_embedding_export))
+            .route("/{id}/embedding-exports/{export_id}", web::get().to(get_embedding_export))
+            .route("/{id}/datasets", web::get().to(list_datasets))
+            .route("/{id}/datasets/{dataset_id}/query", web::post().to(query_dataset))
+            .route("/{id}/pii-report", web::get().to(get_pii_report))
+            .route("/{id}/pii-report/chunks/{chunk_id}/override", web::post().to(override_pii_chunk))
+            .route(
+                "/{id}/duplicates/{report_id}/clusters/{cluster_id}/resolve",
+                web::post().to(resolve_duplicate_cluster),
+            )
+            .route("/{id}/faq", web::post().to(create_faq_entry))
+            .route("/{id}/faq", web::get().to(list_faq_entries))
+            .route("/{id}/faq/{entry_id}", web::get().to(get_faq_entry))
+            .route("/{id}/faq/{entry_id}", web::put().to(update_faq_entry))
+            .route("/{id}/faq/{entry_id}", web::delete().to(delete_faq_entry))
+            .route("/{id}/snapshots", web::post().to(create_kb_snapshot))
+            .route("/{id}/snapshots", web::get().to(list_kb_snapshots))
+            .route("/{id}/snapshots/diff", web::get().to(diff_kb_snapshots))
+            .route("/{id}/snapshots/{name}", web::get().to(get_kb_snapshot))
+            .route("/{id}/snapshots/{name}", web::delete().to(delete_kb_snapshot))
     );
+    cfg.route("/downloads/embeddings/{export_id}", web::get().to(download_embedding_export));
 }
\ No newline at end of file
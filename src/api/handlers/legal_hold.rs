@@ -0,0 +1,166 @@
+// 法律保留 API 处理器
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use sea_orm::DatabaseConnection;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::api::middleware::auth::{AuthenticatedUser, PermissionChecker};
+use crate::api::middleware::tenant::TenantInfo;
+use crate::api::responses::HttpResponseBuilder;
+use crate::errors::AiStudioError;
+use crate::services::legal_hold::{
+    LegalHoldQuery, LegalHoldService, PlaceLegalHoldRequest, ReleaseLegalHoldRequest, LEGAL_HOLD_ROLE,
+};
+
+fn ensure_legal_hold_role(user: &AuthenticatedUser) -> Result<(), AiStudioError> {
+    if !PermissionChecker::has_role(user, LEGAL_HOLD_ROLE) {
+        return Err(AiStudioError::forbidden("需要合规人员权限"));
+    }
+    Ok(())
+}
+
+/// 设置法律保留
+#[utoipa::path(
+    post,
+    path = "/api/v1/legal-holds",
+    request_body = PlaceLegalHoldRequest,
+    responses(
+        (status = 201, description = "设置成功", body = LegalHoldResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "需要合规人员权限", body = ApiError),
+        (status = 404, description = "保留对象不存在", body = ApiError)
+    ),
+    tag = "legal-holds",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn place_legal_hold(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: web::Json<PlaceLegalHoldRequest>,
+) -> ActixResult<HttpResponse> {
+    ensure_legal_hold_role(&user)?;
+    info!("设置法律保留: tenant_id={}, user={}", tenant_info.id, user.user_id);
+
+    let hold = LegalHoldService::new(db.get_ref().clone())
+        .place_hold(tenant_info.id, req.into_inner(), user.user_id)
+        .await?;
+
+    HttpResponseBuilder::created(hold)
+}
+
+/// 列出法律保留
+#[utoipa::path(
+    get,
+    path = "/api/v1/legal-holds",
+    params(LegalHoldQuery),
+    responses(
+        (status = 200, description = "获取列表成功", body = Vec<LegalHoldResponse>),
+        (status = 403, description = "需要合规人员权限", body = ApiError)
+    ),
+    tag = "legal-holds",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_legal_holds(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    query: web::Query<LegalHoldQuery>,
+) -> ActixResult<HttpResponse> {
+    ensure_legal_hold_role(&user)?;
+
+    let holds = LegalHoldService::new(db.get_ref().clone())
+        .list_holds(tenant_info.id, query.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(holds)
+}
+
+/// 解除法律保留
+#[utoipa::path(
+    post,
+    path = "/api/v1/legal-holds/{id}/release",
+    params(
+        ("id" = Uuid, Path, description = "法律保留 ID")
+    ),
+    request_body = ReleaseLegalHoldRequest,
+    responses(
+        (status = 200, description = "解除成功", body = LegalHoldResponse),
+        (status = 403, description = "需要合规人员权限", body = ApiError),
+        (status = 404, description = "法律保留不存在", body = ApiError),
+        (status = 409, description = "法律保留已解除", body = ApiError)
+    ),
+    tag = "legal-holds",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn release_legal_hold(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<ReleaseLegalHoldRequest>,
+) -> ActixResult<HttpResponse> {
+    ensure_legal_hold_role(&user)?;
+    let hold_id = path.into_inner();
+    info!("解除法律保留: hold_id={}, user={}", hold_id, user.user_id);
+
+    let hold = LegalHoldService::new(db.get_ref().clone())
+        .release_hold(tenant_info.id, hold_id, req.into_inner(), user.user_id)
+        .await?;
+
+    HttpResponseBuilder::ok(hold)
+}
+
+/// 获取法律保留审计事件
+#[utoipa::path(
+    get,
+    path = "/api/v1/legal-holds/{id}/events",
+    params(
+        ("id" = Uuid, Path, description = "法律保留 ID")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = Vec<LegalHoldEvent>),
+        (status = 403, description = "需要合规人员权限", body = ApiError),
+        (status = 404, description = "法律保留不存在", body = ApiError)
+    ),
+    tag = "legal-holds",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_legal_hold_events(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    ensure_legal_hold_role(&user)?;
+
+    let events = LegalHoldService::new(db.get_ref().clone())
+        .list_events(tenant_info.id, path.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(events)
+}
+
+/// 配置法律保留路由
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/legal-holds")
+            .route("", web::post().to(place_legal_hold))
+            .route("", web::get().to(list_legal_holds))
+            .route("/{id}/release", web::post().to(release_legal_hold))
+            .route("/{id}/events", web::get().to(list_legal_hold_events))
+    );
+}
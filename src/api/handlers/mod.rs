@@ -1,32 +1,56 @@
 // API 处理器模块
 // 包含所有 API 端点的处理逻辑
 
+pub mod activity;
+pub mod admin;
 pub mod agent;
 pub mod auth;
+pub mod consent;
+pub mod curated_answer;
 pub mod document;
+pub mod document_comment;
+pub mod elevation;
+pub mod few_shot;
 pub mod health;
 pub mod knowledge_base;
+pub mod legal_hold;
+pub mod model_routing;
 pub mod monitoring;
+pub mod passkey;
 pub mod plugin;
 pub mod qa;
 pub mod quota;
 pub mod rate_limit;
+pub mod saved_search;
+pub mod scheduled_report;
 pub mod tenant;
 pub mod tool;
 pub mod version;
 pub mod workflow;
 
 // 重新导出常用的处理器
+pub use activity::*;
+pub use admin::*;
 pub use agent::*;
 pub use auth::*;
+pub use consent::*;
+pub use curated_answer::*;
 pub use document::*;
+pub use document_comment::*;
+pub use elevation::*;
+pub use few_shot::*;
 pub use health::*;
 pub use knowledge_base::*;
+pub use legal_hold::*;
+pub use model_routing::*;
 pub use monitoring::*;
+pub use passkey::*;
 pub use plugin::*;
 pub use qa::*;
 pub use quota::*;
 pub use rate_limit::*;
+pub use saved_search::*;
+pub use scheduled_report::*;
 pub use tenant::*;
 pub use tool::*;
 pub use version::*;
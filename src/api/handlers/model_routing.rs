@@ -0,0 +1,70 @@
+// 模型路由 API 处理器
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use sea_orm::EntityTrait;
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::ai::model_router::{ModelRouter, RoutingRequest, RoutingTask};
+use crate::api::middleware::tenant::TenantInfo;
+use crate::api::responses::HttpResponseBuilder;
+use crate::db::entities::prelude::Tenant;
+use crate::db::DatabaseManager;
+use crate::errors::AiStudioError;
+
+/// 路由预览查询参数
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct RoutePreviewQuery {
+    /// 任务类型
+    pub task: RoutingTask,
+    /// 所需上下文长度（token）
+    pub context_tokens: Option<u32>,
+}
+
+/// 预览当前租户的模型路由决策
+#[utoipa::path(
+    get,
+    path = "/api/v1/models/route",
+    tag = "models",
+    params(RoutePreviewQuery),
+    responses(
+        (status = 200, description = "路由决策", body = RouteDecision),
+        (status = 400, description = "没有满足要求的模型", body = ApiError),
+        (status = 429, description = "所有候选模型均被限流", body = ApiError)
+    )
+)]
+pub async fn preview_model_route(
+    query: web::Query<RoutePreviewQuery>,
+    tenant_info: web::ReqData<TenantInfo>,
+) -> ActixResult<HttpResponse> {
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let db = db_manager.get_connection();
+
+    let tenant = Tenant::find_by_id(tenant_info.id)
+        .one(db)
+        .await
+        .map_err(AiStudioError::from)?
+        .ok_or_else(|| AiStudioError::not_found("租户"))?;
+    let config = tenant.get_config()
+        .map_err(|e| AiStudioError::internal(format!("解析租户配置失败: {}", e)))?;
+
+    let request = RoutingRequest {
+        task: query.task,
+        required_context_tokens: query.context_tokens.unwrap_or(0),
+        plan: config.plan,
+        overrides: Some(&config.model_routing),
+        policy: Some(&config.model_policy),
+    };
+    let decision = ModelRouter::global().route(&request).await?;
+
+    HttpResponseBuilder::ok(decision)
+}
+
+/// 配置模型路由路由
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/models")
+            .route("/route", web::get().to(preview_model_route))
+    );
+}
@@ -10,7 +10,9 @@ use crate::api::middleware::auth::AuthenticatedUser;
 use crate::services::monitoring::{
     MonitoringService, MetricType, MetricDataPoint
 };
+use crate::services::incremental_index::delta_embedding_stats;
 use crate::services::notification::{NotificationMessage, NotificationType};
+use crate::services::slo::SloTracker;
 use crate::db::DatabaseManager;
 use crate::errors::AiStudioError;
 
@@ -64,6 +66,42 @@ pub async fn get_system_health(
     HttpResponseBuilder::ok(health)
 }
 
+/// 获取各接口类别的服务等级目标状态
+#[utoipa::path(
+    get,
+    path = "/monitoring/slo",
+    tag = "monitoring",
+    responses(
+        (status = 200, description = "各接口类别的错误预算消耗速度", body = SloReport),
+        (status = 404, description = "未启用 SLO 跟踪", body = ApiError)
+    )
+)]
+pub async fn get_slo_report(
+    _admin: AdminExtractor,
+) -> ActixResult<HttpResponse> {
+    let tracker = SloTracker::global()
+        .ok_or_else(|| AiStudioError::not_found("SLO 跟踪"))?;
+
+    HttpResponseBuilder::ok(tracker.report())
+}
+
+/// 获取增量重嵌入统计
+///
+/// 文档更新后只为内容变更的块生成嵌入，统计自进程启动以来沿用与重新生成的块数及节省的嵌入调用次数。
+#[utoipa::path(
+    get,
+    path = "/monitoring/embedding-delta",
+    tag = "monitoring",
+    responses(
+        (status = 200, description = "增量重嵌入统计", body = DeltaEmbeddingStats)
+    )
+)]
+pub async fn get_embedding_delta_stats(
+    _admin: AdminExtractor,
+) -> ActixResult<HttpResponse> {
+    HttpResponseBuilder::ok(delta_embedding_stats())
+}
+
 /// 获取租户使用统计
 #[utoipa::path(
     get,
@@ -251,6 +289,8 @@ pub fn configure_monitoring_routes(cfg: &mut web::ServiceConfig) {
                 web::scope("")
                     .configure(MiddlewareConfig::admin_only())
                     .route("/health", web::get().to(get_system_health))
+                    .route("/slo", web::get().to(get_slo_report))
+                    .route("/embedding-delta", web::get().to(get_embedding_delta_stats))
                     .route("/tenants/{tenant_id}/metrics", web::post().to(record_metric))
             )
             // 需要认证的路由
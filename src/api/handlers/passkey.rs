@@ -0,0 +1,171 @@
+// 通行密钥 API 处理器
+// 已登录用户注册通行密钥并管理自己的设备；登录流程见 auth 处理器
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use sea_orm::DatabaseConnection;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::api::middleware::auth::AuthenticatedUser;
+use crate::api::middleware::tenant::TenantInfo;
+use crate::api::responses::HttpResponseBuilder;
+use crate::config::ConfigLoader;
+use crate::services::passkey::{PasskeyService, RegisterPasskeyRequest, RenamePasskeyRequest};
+
+fn passkey_service(db: &DatabaseConnection) -> PasskeyService {
+    PasskeyService::new(db.clone(), ConfigLoader::get().webauthn.clone())
+}
+
+/// 获取通行密钥注册选项
+///
+/// 返回的 `public_key` 传给 `navigator.credentials.create()`，挑战在配置的有效期内一次有效。
+#[utoipa::path(
+    post,
+    path = "/api/v1/passkeys/registration/options",
+    responses(
+        (status = 200, description = "注册选项", body = PasskeyCeremonyOptions),
+        (status = 400, description = "已达到通行密钥数量上限", body = ApiError)
+    ),
+    tag = "passkeys",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_passkey_registration_options(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+) -> ActixResult<HttpResponse> {
+    let options = passkey_service(db.get_ref())
+        .start_registration(tenant_info.id, user.user_id)
+        .await?;
+
+    HttpResponseBuilder::ok(options)
+}
+
+/// 注册通行密钥
+#[utoipa::path(
+    post,
+    path = "/api/v1/passkeys/registration",
+    request_body = RegisterPasskeyRequest,
+    responses(
+        (status = 201, description = "注册成功", body = PasskeyCredentialInfo),
+        (status = 400, description = "凭据格式无效", body = ApiError),
+        (status = 401, description = "挑战无效或凭据校验失败", body = ApiError),
+        (status = 409, description = "通行密钥已注册", body = ApiError)
+    ),
+    tag = "passkeys",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn register_passkey(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: web::Json<RegisterPasskeyRequest>,
+) -> ActixResult<HttpResponse> {
+    info!("注册通行密钥: tenant_id={}, user={}", tenant_info.id, user.user_id);
+
+    let credential = passkey_service(db.get_ref())
+        .finish_registration(tenant_info.id, user.user_id, req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::created(credential)
+}
+
+/// 列出当前用户的通行密钥
+#[utoipa::path(
+    get,
+    path = "/api/v1/passkeys",
+    responses(
+        (status = 200, description = "通行密钥列表", body = Vec<PasskeyCredentialInfo>)
+    ),
+    tag = "passkeys",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_passkeys(
+    db: web::Data<DatabaseConnection>,
+    user: web::ReqData<AuthenticatedUser>,
+) -> ActixResult<HttpResponse> {
+    let credentials = passkey_service(db.get_ref())
+        .list_credentials(user.user_id)
+        .await?;
+
+    HttpResponseBuilder::ok(credentials)
+}
+
+/// 重命名通行密钥
+#[utoipa::path(
+    put,
+    path = "/api/v1/passkeys/{id}",
+    params(
+        ("id" = Uuid, Path, description = "通行密钥 ID")
+    ),
+    request_body = RenamePasskeyRequest,
+    responses(
+        (status = 200, description = "重命名成功", body = PasskeyCredentialInfo),
+        (status = 400, description = "名称无效", body = ApiError),
+        (status = 404, description = "通行密钥不存在", body = ApiError)
+    ),
+    tag = "passkeys",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn rename_passkey(
+    db: web::Data<DatabaseConnection>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<RenamePasskeyRequest>,
+) -> ActixResult<HttpResponse> {
+    let credential = passkey_service(db.get_ref())
+        .rename_credential(user.user_id, path.into_inner(), &req.name)
+        .await?;
+
+    HttpResponseBuilder::ok(credential)
+}
+
+/// 删除通行密钥
+#[utoipa::path(
+    delete,
+    path = "/api/v1/passkeys/{id}",
+    params(
+        ("id" = Uuid, Path, description = "通行密钥 ID")
+    ),
+    responses(
+        (status = 204, description = "删除成功"),
+        (status = 404, description = "通行密钥不存在", body = ApiError),
+        (status = 409, description = "租户要求使用通行密钥，不能删除最后一个", body = ApiError)
+    ),
+    tag = "passkeys",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_passkey(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    passkey_service(db.get_ref())
+        .delete_credential(tenant_info.id, user.user_id, path.into_inner())
+        .await?;
+
+    HttpResponseBuilder::no_content()
+}
+
+/// 配置通行密钥路由
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/passkeys")
+            .route("", web::get().to(list_passkeys))
+            .route("/registration/options", web::post().to(create_passkey_registration_options))
+            .route("/registration", web::post().to(register_passkey))
+            .route("/{id}", web::put().to(rename_passkey))
+            .route("/{id}", web::delete().to(delete_passkey))
+    );
+}
@@ -2,7 +2,7 @@
 
 use std::sync::Arc;
 use std::collections::HashMap;
-use actix_web::{web, HttpResponse, Result as ActixResult};
+use actix_web::{web, HttpRequest, HttpResponse, Result as ActixResult};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use tracing::{info, error, debug};
@@ -10,11 +10,16 @@ use utoipa::ToSchema;
 
 use crate::plugins::{
     plugin_manager::{PluginManager, InstallPluginRequest, InstallPluginResponse, PluginListResponse, PluginInfo},
-    plugin_interface::{PluginConfig, PluginContext, PluginPermission, PluginStatus},
+    plugin_interface::{PluginConfig, PluginContext, PluginHttpRequest, PluginPermission, PluginStatus},
     plugin_registry::{PluginSearchQuery, PluginSearchResult, PluginStatistics},
 };
 use crate::errors::AiStudioError;
 use crate::api::middleware::tenant::TenantInfo;
+use crate::api::middleware::auth::{AuthenticatedUser, PermissionChecker};
+use crate::config::ConfigLoader;
+use crate::db::DatabaseManager;
+use crate::services::plugin_config::PluginConfigService;
+use crate::services::plugin_trust::PluginTrustService;
 
 /// 插件调用请求
 #[derive(Debug, Deserialize, ToSchema)]
@@ -53,6 +58,13 @@ pub struct UpdatePluginConfigRequest {
     pub restart_plugin: Option<bool>,
 }
 
+/// 租户插件配置更新请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateTenantPluginConfigRequest {
+    /// 配置项（敏感字段传 `******` 表示保持不变）
+    pub config: serde_json::Map<String, serde_json::Value>,
+}
+
 /// 插件状态控制请求
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct PluginControlRequest {
@@ -90,7 +102,11 @@ pub async fn install_plugin(
 ) -> ActixResult<HttpResponse> {
     debug!("安装插件: {} (tenant_id={})", request.source, tenant_info.context.tenant_id);
     
-    match plugin_manager.install_plugin(request.into_inner()).await {
+    let mut request = request.into_inner();
+    request.tenant_id = Some(tenant_info.id);
+    request.tenant_trusted_keys = plugin_trust_service()?.active_keys(tenant_info.id).await?;
+    
+    match plugin_manager.install_plugin(request).await {
         Ok(response) => {
             info!("插件安装完成: plugin_id={}, status={:?}", 
                   response.plugin_id, response.status);
@@ -257,6 +273,7 @@ pub async fn call_plugin(
         session_id: None,
         request_id: Uuid::new_v4(),
         variables: HashMap::new(),
+        plugin_config: HashMap::new(),
         timestamp: call_time,
     };
     
@@ -549,6 +566,49 @@ pub async fn get_plugin_logs(
     }
 }
 
+/// 获取插件资源使用指标
+#[utoipa::path(
+    get,
+    path = "/api/v1/plugins/{plugin_id}/metrics",
+    responses(
+        (status = 200, description = "获取插件资源指标成功"),
+        (status = 404, description = "插件不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("plugin_id" = String, Path, description = "插件 ID")
+    ),
+    tag = "plugins"
+)]
+pub async fn get_plugin_metrics(
+    plugin_manager: web::Data<Arc<PluginManager>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    let plugin_id = path.into_inner();
+    debug!("获取插件资源指标: {} (tenant_id={})", plugin_id, tenant_info.context.tenant_id);
+    
+    match plugin_manager.get_plugin_metrics(&plugin_id).await {
+        Ok(metrics) => {
+            Ok(HttpResponse::Ok().json(metrics))
+        }
+        Err(e) => {
+            error!("获取插件资源指标失败: {} - {}", plugin_id, e);
+            
+            let mut error_response = match e {
+                AiStudioError::NotFound { resource: _ } => HttpResponse::NotFound(),
+                _ => HttpResponse::InternalServerError(),
+            };
+            
+            Ok(error_response.json(serde_json::json!({
+                "error": "获取插件资源指标失败",
+                "message": e.to_string(),
+                "plugin_id": plugin_id
+            })))
+        }
+    }
+}
+
 /// 清理插件数据
 #[utoipa::path(
     post,
@@ -596,6 +656,249 @@ pub async fn cleanup_plugin_data(
     }
 }
 
+/// 获取当前租户的插件配置
+#[utoipa::path(
+    get,
+    path = "/api/v1/plugins/{plugin_id}/tenant-config",
+    responses(
+        (status = 200, description = "租户插件配置（敏感字段已脱敏）", body = TenantPluginConfigResponse),
+        (status = 404, description = "插件不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("plugin_id" = String, Path, description = "插件 ID")
+    ),
+    tag = "plugins"
+)]
+pub async fn get_tenant_plugin_config(
+    plugin_manager: web::Data<Arc<PluginManager>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    let plugin_id = path.into_inner();
+    let schema = plugin_manager.get_config_schema(&plugin_id).await?;
+    
+    let response = plugin_config_service()?
+        .get_masked_config(tenant_info.id, &plugin_id, &schema)
+        .await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 更新当前租户的插件配置
+/// 配置按插件声明的 JSON Schema 校验，敏感字段加密存储
+#[utoipa::path(
+    put,
+    path = "/api/v1/plugins/{plugin_id}/tenant-config",
+    request_body = UpdateTenantPluginConfigRequest,
+    responses(
+        (status = 200, description = "租户插件配置更新成功", body = TenantPluginConfigResponse),
+        (status = 400, description = "配置不符合插件配置模式"),
+        (status = 403, description = "需要租户管理员权限"),
+        (status = 404, description = "插件不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("plugin_id" = String, Path, description = "插件 ID")
+    ),
+    tag = "plugins"
+)]
+pub async fn update_tenant_plugin_config(
+    plugin_manager: web::Data<Arc<PluginManager>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<String>,
+    request: web::Json<UpdateTenantPluginConfigRequest>,
+) -> ActixResult<HttpResponse> {
+    let plugin_id = path.into_inner();
+    
+    if !user.is_admin {
+        return Err(AiStudioError::forbidden("只有租户管理员可以修改插件配置").into());
+    }
+    
+    let schema = plugin_manager.get_config_schema(&plugin_id).await?;
+    let response = plugin_config_service()?
+        .save_config(tenant_info.id, &plugin_id, &schema, request.into_inner().config, user.user_id)
+        .await?;
+    
+    info!("租户插件配置已更新: {} (tenant_id={})", plugin_id, tenant_info.id);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// 登记插件签名密钥请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddTrustedKeyRequest {
+    /// 密钥名称
+    pub name: String,
+    /// base64 编码的 ed25519 公钥
+    pub public_key: String,
+}
+
+/// 列出当前租户登记的插件签名密钥
+#[utoipa::path(
+    get,
+    path = "/api/v1/plugins/trusted-keys",
+    responses(
+        (status = 200, description = "获取签名密钥列表成功", body = Vec<TrustedKeyResponse>),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "plugins"
+)]
+pub async fn list_trusted_keys(
+    tenant_info: web::ReqData<TenantInfo>,
+) -> ActixResult<HttpResponse> {
+    let keys = plugin_trust_service()?.list_keys(tenant_info.id).await?;
+    Ok(HttpResponse::Ok().json(keys))
+}
+
+/// 登记插件签名密钥
+/// 由该密钥签名的插件可被当前租户安装
+#[utoipa::path(
+    post,
+    path = "/api/v1/plugins/trusted-keys",
+    request_body = AddTrustedKeyRequest,
+    responses(
+        (status = 200, description = "签名密钥登记成功", body = TrustedKeyResponse),
+        (status = 400, description = "公钥格式无效"),
+        (status = 403, description = "需要租户管理员权限"),
+        (status = 409, description = "密钥已登记"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "plugins"
+)]
+pub async fn add_trusted_key(
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    request: web::Json<AddTrustedKeyRequest>,
+) -> ActixResult<HttpResponse> {
+    if !user.is_admin {
+        return Err(AiStudioError::forbidden("只有租户管理员可以登记插件签名密钥").into());
+    }
+    
+    let key = plugin_trust_service()?
+        .add_key(tenant_info.id, &request.name, &request.public_key, user.user_id)
+        .await?;
+    
+    info!("插件签名密钥已登记: {} (tenant_id={})", key.key_id, tenant_info.id);
+    Ok(HttpResponse::Ok().json(key))
+}
+
+/// 吊销插件签名密钥
+#[utoipa::path(
+    delete,
+    path = "/api/v1/plugins/trusted-keys/{key_id}",
+    responses(
+        (status = 200, description = "签名密钥已吊销", body = TrustedKeyResponse),
+        (status = 403, description = "需要租户管理员权限"),
+        (status = 404, description = "密钥不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("key_id" = String, Path, description = "密钥 ID")
+    ),
+    tag = "plugins"
+)]
+pub async fn revoke_trusted_key(
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    if !user.is_admin {
+        return Err(AiStudioError::forbidden("只有租户管理员可以吊销插件签名密钥").into());
+    }
+    
+    let key_id = path.into_inner();
+    let key = plugin_trust_service()?.revoke_key(tenant_info.id, &key_id).await?;
+    
+    info!("插件签名密钥已吊销: {} (tenant_id={})", key_id, tenant_info.id);
+    Ok(HttpResponse::Ok().json(key))
+}
+
+/// 创建插件签名信任服务
+fn plugin_trust_service() -> Result<PluginTrustService, AiStudioError> {
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    
+    Ok(PluginTrustService::new(db_manager.get_connection().clone()))
+}
+
+/// 创建租户插件配置服务
+fn plugin_config_service() -> Result<PluginConfigService, AiStudioError> {
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    
+    Ok(PluginConfigService::new(
+        db_manager.get_connection().clone(),
+        &ConfigLoader::get().security.encryption_key,
+    ))
+}
+
+/// 转发给插件时过滤的请求头
+const FILTERED_EXTENSION_HEADERS: [&str; 4] = ["authorization", "cookie", "x-api-key", "proxy-authorization"];
+
+/// 插件扩展路由分发
+/// 将 `/api/v1/ext/{plugin_id}/...` 请求转发给插件声明的路由处理方法
+pub async fn dispatch_extension_route(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    query: web::Query<HashMap<String, String>>,
+    body: web::Bytes,
+    plugin_manager: web::Data<Arc<PluginManager>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+) -> ActixResult<HttpResponse> {
+    let (plugin_id, tail) = path.into_inner();
+    let method = req.method().as_str().to_uppercase();
+    let route_path = format!("/{}", tail);
+    debug!("插件扩展路由: {} {} {} (tenant_id={})", plugin_id, method, route_path, tenant_info.id);
+    
+    let (route, path_params) = plugin_manager.resolve_route(&plugin_id, &method, &route_path).await?;
+    
+    // 检查调用方权限
+    if route.admin_only && !user.is_admin {
+        return Err(AiStudioError::forbidden("该插件路由仅允许管理员调用").into());
+    }
+    if !PermissionChecker::has_all_permissions(&user, &route.required_permissions) {
+        return Err(AiStudioError::forbidden("缺少调用该插件路由所需的权限").into());
+    }
+    
+    let body = if body.is_empty() {
+        None
+    } else {
+        Some(serde_json::from_slice(&body)
+            .map_err(|e| AiStudioError::validation("body", format!("请求体不是有效的 JSON: {}", e)))?)
+    };
+    
+    let headers = req.headers().iter()
+        .filter(|(name, _)| !FILTERED_EXTENSION_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| value.to_str().ok().map(|v| (name.to_string(), v.to_string())))
+        .collect();
+    
+    let request = PluginHttpRequest {
+        method,
+        path: route_path,
+        path_params,
+        query: query.into_inner(),
+        headers,
+        body,
+    };
+    
+    let context = PluginContext {
+        tenant_id: tenant_info.id,
+        user_id: Some(user.user_id),
+        session_id: None,
+        request_id: Uuid::new_v4(),
+        variables: HashMap::new(),
+        plugin_config: HashMap::new(),
+        timestamp: chrono::Utc::now(),
+    };
+    
+    let mut params = HashMap::new();
+    params.insert("request".to_string(), serde_json::to_value(&request).unwrap_or_default());
+    
+    let result = plugin_manager.call_plugin(&plugin_id, &route.handler, params, context).await?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
 /// 日志查询参数
 #[derive(Debug, Deserialize)]
 pub struct LogQuery {
@@ -611,15 +914,32 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             .route("/search", web::post().to(search_plugins))
             .route("/statistics", web::get().to(get_plugin_statistics))
             .route("/call", web::post().to(call_plugin))
+            .route("/trusted-keys", web::get().to(list_trusted_keys))
+            .route("/trusted-keys", web::post().to(add_trusted_key))
+            .route("/trusted-keys/{key_id}", web::delete().to(revoke_trusted_key))
             .route("/{plugin_id}", web::get().to(get_plugin_info))
             .route("/{plugin_id}", web::delete().to(uninstall_plugin))
             .route("/{plugin_id}/control", web::post().to(control_plugin))
             .route("/{plugin_id}/config", web::put().to(update_plugin_config))
+            .route("/{plugin_id}/tenant-config", web::get().to(get_tenant_plugin_config))
+            .route("/{plugin_id}/tenant-config", web::put().to(update_tenant_plugin_config))
             .route("/{plugin_id}/logs", web::get().to(get_plugin_logs))
+            .route("/{plugin_id}/metrics", web::get().to(get_plugin_metrics))
             .route("/{plugin_id}/cleanup", web::post().to(cleanup_plugin_data))
     );
 }
 
+/// 配置插件扩展路由
+pub fn configure_extension_routes(cfg: &mut web::ServiceConfig) {
+    use crate::api::middleware::MiddlewareConfig;
+    
+    cfg.service(
+        web::scope("/ext")
+            .configure(MiddlewareConfig::api_standard())
+            .route("/{plugin_id}/{tail:.*}", web::route().to(dispatch_extension_route))
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
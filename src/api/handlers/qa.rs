@@ -3,9 +3,9 @@
 
 use actix_web::{web, HttpResponse, Result as ActixResult};
 use actix_web_lab::sse::{self, Sse};
-use sea_orm::DatabaseConnection;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use tracing::{info, error, debug};
@@ -15,8 +15,37 @@ use std::time::Duration;
 use crate::api::models::{PaginationQuery, PaginatedResponse, PaginationInfo};
 use crate::api::responses::{ApiResponse, ApiError};
 use crate::api::extractors::{TenantExtractor, UserContext};
+use crate::api::middleware::auth::{ApiKeyInfo, AuthenticatedUser};
+use crate::api::middleware::tenant::TenantInfo;
 use crate::db::migrations::tenant_filter::TenantContext;
+use crate::ai::query_rewrite::QueryRewriteTrace;
+use crate::ai::retrieval_explain::RetrievalExplanation;
 use crate::ai::rag_engine::{RagEngine, RagQueryRequest, RagQueryResponse, RetrievalParams, GenerationParams};
+use crate::ai::answer_confidence::ConfidenceBreakdown;
+use crate::ai::structured_output::StructuredOutput;
+use crate::db::entities::document::ClearanceLevel;
+use crate::db::entities::usage_anomaly::AnomalyMetric;
+use crate::services::clearance::clearance_for;
+use crate::services::curated_answer::CuratedAnswerMatch;
+use crate::services::deadline;
+use crate::services::faq::FaqMatch;
+use crate::services::question_suggestion::{QuestionSuggestionService, RelatedQuestionsRequest};
+use crate::services::qa_transcript::{QaTranscriptService, TranscriptFeedback};
+use crate::services::finetune_dataset::{BuildFinetuneDatasetRequest, FinetuneDatasetService};
+use crate::services::task_queue::TaskQueueService;
+use crate::services::user_preferences::UserPreferenceService;
+use crate::services::tenant_persona::load_tenant_persona;
+use crate::services::usage_anomaly::record_usage;
+use crate::services::transcript_export::{
+    export_file_path, verify_download_token, CreateTranscriptExportRequest, TranscriptExportService,
+};
+use crate::config::ConfigLoader;
+use crate::db::entities::knowledge_base::{self, KnowledgeBaseStatus};
+use crate::db::entities::prelude::KnowledgeBase;
+use crate::errors::AiStudioError;
+
+/// 跨知识库问答最多同时检索的知识库数量
+const MAX_FEDERATED_KNOWLEDGE_BASES: usize = 20;
 
 /// 问答请求
 #[derive(Debug, Clone, Deserialize, ToSchema)]
@@ -33,6 +62,27 @@ pub struct QaRequest {
     pub generation_params: Option<GenerationParams>,
     /// 是否启用流式响应
     pub stream: Option<bool>,
+    /// 知识库快照名称（可选，指定时基于该快照回答，需同时指定知识库 ID）
+    pub kb_version: Option<String>,
+    /// 期望的输出 JSON Schema（可选，指定时返回解析后的结构化答案及校验状态）
+    pub output_schema: Option<serde_json::Value>,
+    /// 跨知识库问答的目标知识库（可选，不能与知识库 ID 同时指定）
+    #[serde(default)]
+    pub knowledge_base_ids: Option<Vec<Uuid>>,
+    /// 是否在租户下所有有权访问的知识库中检索
+    #[serde(default)]
+    pub all_knowledge_bases: bool,
+    /// 跨知识库问答中每个知识库最多贡献的文档块数量（可选，默认与 top_k 相同）
+    #[serde(default)]
+    pub per_kb_top_k: Option<u32>,
+}
+
+/// 问答查询参数
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+pub struct QaAskQuery {
+    /// 是否返回检索决策记录（各候选文档块在每个阶段的得分与淘汰原因），用于排查相关性问题
+    #[serde(default)]
+    pub explain: bool,
 }
 
 /// 问答响应
@@ -52,6 +102,29 @@ pub struct QaResponse {
     pub suggestions: Vec<String>,
     /// 查询统计
     pub stats: QaStats,
+    /// 回答所基于的知识库快照名称
+    pub kb_version: Option<String>,
+    /// 结构化答案（请求指定输出模式时返回）
+    pub structured_output: Option<StructuredOutput>,
+    /// 命中的 FAQ/术语表条目（由标准答案直接应答时返回）
+    pub faq_match: Option<FaqMatch>,
+    /// 命中的精选答案（由知识管理员审定的答案直接应答时返回）
+    pub curated_answer: Option<CuratedAnswerMatch>,
+    /// 置信度明细
+    pub confidence: Option<ConfidenceBreakdown>,
+    /// 是否因资料不足而未给出答案，此时来源为推荐查阅的文档
+    pub insufficient_information: bool,
+    /// 是否因请求截止时间不足而未生成答案，此时来源为检索到的文档
+    pub deadline_exceeded: bool,
+    /// 查询改写过程（知识库启用查询改写时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_rewrite: Option<QueryRewriteTrace>,
+    /// 检索决策记录（查询参数 explain=true 时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retrieval_explanation: Option<RetrievalExplanation>,
+    /// 租户配置的欢迎语（仅新会话返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub greeting: Option<String>,
     /// 响应时间
     pub response_time: DateTime<Utc>,
 }
@@ -69,6 +142,10 @@ pub struct QaSource {
     pub relevance_score: f32,
     /// 引用的文档块
     pub chunks: Vec<QaChunk>,
+    /// 来源知识库 ID
+    pub knowledge_base_id: Option<Uuid>,
+    /// 来源知识库名称
+    pub knowledge_base_name: Option<String>,
 }
 
 /// 问答文档块
@@ -169,6 +246,34 @@ pub enum FeedbackType {
     Other,
 }
 
+impl FeedbackType {
+    /// 存储时使用的反馈类型名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Helpful => "helpful",
+            Self::NotHelpful => "not_helpful",
+            Self::Incorrect => "incorrect",
+            Self::Incomplete => "incomplete",
+            Self::Irrelevant => "irrelevant",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// 审校答案请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ReviewAnswerRequest {
+    /// 审校后的答案
+    pub answer: String,
+}
+
+/// 下载链接查询参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranscriptDownloadQuery {
+    /// 下载令牌
+    pub token: String,
+}
+
 /// 问答建议请求
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct QaSuggestionsRequest {
@@ -193,6 +298,7 @@ pub struct QaSuggestionsResponse {
 #[utoipa::path(
     post,
     path = "/api/v1/qa/ask",
+    params(QaAskQuery),
     request_body = QaRequest,
     responses(
         (status = 200, description = "问答查询成功", body = QaResponse),
@@ -212,6 +318,8 @@ pub async fn ask_question(
     rag_engine: web::Data<RagEngine>,
     tenant_ctx: TenantContext,
     user_ctx: UserContext,
+    api_key: Option<web::ReqData<ApiKeyInfo>>,
+    query: web::Query<QaAskQuery>,
     req: web::Json<QaRequest>,
 ) -> ActixResult<HttpResponse> {
     info!("问答查询请求: 租户={}, 用户={}, 问题={}", 
@@ -225,27 +333,53 @@ pub async fn ask_question(
         return Ok(HttpResponse::BadRequest().json(ApiError::bad_request("问题长度不能超过 1000 字符")));
     }
     
-    // 生成或使用现有的会话 ID
+    let knowledge_base_ids = resolve_federated_scope(db.get_ref(), tenant_ctx.tenant_id, &user_ctx, &req).await?;
+    
+    // 生成或使用现有的会话 ID，新会话附带租户欢迎语
+    let greeting = match req.session_id {
+        Some(_) => None,
+        None => load_tenant_persona(db.get_ref(), tenant_ctx.tenant_id).await.greeting,
+    };
     let session_id = req.session_id.clone().unwrap_or_else(|| {
         format!("session_{}", Uuid::new_v4())
     });
     
+    // 按用户偏好补齐生成参数
+    let preferences = UserPreferenceService::new(db.get_ref().clone())
+        .preferences_or_default(user_ctx.user.id)
+        .await;
+    
     // 构建 RAG 查询请求
     let rag_request = RagQueryRequest {
         question: req.question.clone(),
         knowledge_base_id: req.knowledge_base_id,
         tenant_id: tenant_ctx.tenant_id,
         retrieval_params: req.retrieval_params.clone(),
-        generation_params: req.generation_params.clone(),
+        generation_params: Some(GenerationParams::with_preferences(req.generation_params.clone(), &preferences)),
         session_id: Some(session_id.clone()),
         user_id: Some(user_ctx.user.id),
+        kb_version: req.kb_version.clone(),
+        output_schema: req.output_schema.clone(),
+        clearance: clearance_for(&user_ctx.user.role, &user_ctx.permissions),
+        knowledge_base_ids,
+        per_kb_top_k: req.per_kb_top_k,
+        explain: query.explain,
     };
     
-    // 执行 RAG 查询
-    let rag_response = rag_engine.query(rag_request).await.map_err(|e| {
+    // 执行 RAG 查询，超出请求截止时间时返回超时错误
+    let rag_response = rag_engine.query(rag_request).await.map_err(|e| -> actix_web::Error {
         error!("RAG 查询失败: {}", e);
-        ApiError::internal_server_error("查询处理失败")
+        match e {
+            AiStudioError::Timeout { .. } => e.into(),
+            _ => ApiError::internal_server_error("查询处理失败").into(),
+        }
     })?;
+    record_usage(
+        tenant_ctx.tenant_id,
+        AnomalyMetric::Tokens,
+        api_key.map(|key| key.key_id),
+        rag_response.query_stats.tokens_generated.unwrap_or(0) as u64,
+    );
     
     // 转换为 API 响应格式
     let sources = convert_to_qa_sources(&rag_response);
@@ -264,6 +398,16 @@ pub async fn ask_question(
             chunks_used: rag_response.query_stats.chunks_used_for_generation,
             tokens_generated: rag_response.query_stats.tokens_generated,
         },
+        kb_version: rag_response.kb_version,
+        structured_output: rag_response.structured_output,
+        faq_match: rag_response.faq_match,
+        curated_answer: rag_response.curated_answer,
+        confidence: rag_response.confidence,
+        insufficient_information: rag_response.insufficient_information,
+        deadline_exceeded: rag_response.deadline_exceeded,
+        query_rewrite: rag_response.query_rewrite,
+        retrieval_explanation: rag_response.retrieval_explanation,
+        greeting,
         response_time: rag_response.generated_at,
     };
     
@@ -297,6 +441,7 @@ pub async fn ask_question_stream(
     rag_engine: web::Data<RagEngine>,
     tenant_ctx: TenantContext,
     user_ctx: UserContext,
+    api_key: Option<web::ReqData<ApiKeyInfo>>,
     req: web::Json<QaRequest>,
 ) -> ActixResult<HttpResponse> {
     info!("流式问答查询请求: 租户={}, 用户={}, 问题={}", 
@@ -306,17 +451,36 @@ pub async fn ask_question_stream(
         return Ok(HttpResponse::BadRequest().json(ApiError::bad_request("问题不能为空")));
     }
     
+    let knowledge_base_ids = resolve_federated_scope(db.get_ref(), tenant_ctx.tenant_id, &user_ctx, &req).await?;
+    
+    let greeting = match req.session_id {
+        Some(_) => None,
+        None => load_tenant_persona(db.get_ref(), tenant_ctx.tenant_id).await.greeting,
+    };
     let session_id = req.session_id.clone().unwrap_or_else(|| {
         format!("session_{}", Uuid::new_v4())
     });
     
+    // 按用户偏好补齐生成参数
+    let preferences = UserPreferenceService::new(db.get_ref().clone())
+        .preferences_or_default(user_ctx.user.id)
+        .await;
+    let mut request = req.into_inner();
+    // 流式任务直接使用解析后的目标知识库
+    request.knowledge_base_ids = Some(knowledge_base_ids);
+    request.all_knowledge_bases = false;
+    request.generation_params = Some(GenerationParams::with_preferences(request.generation_params.take(), &preferences));
+    
     // 创建流式响应
     let stream = create_qa_stream(
         rag_engine.get_ref().clone(),
-        req.into_inner(),
+        request,
         tenant_ctx.tenant_id,
         user_ctx.user.id,
+        clearance_for(&user_ctx.user.role, &user_ctx.permissions),
         session_id,
+        greeting,
+        api_key.map(|key| key.key_id),
     );
     
     Ok(Sse::from_stream(stream)
@@ -419,11 +583,22 @@ pub async fn submit_feedback(
         }
     }
     
-    // TODO: 保存反馈到数据库
+    // 反馈与问答记录一同保存，导出会话记录时一并导出
+    QaTranscriptService::new(db.get_ref().clone())
+        .record_feedback(
+            tenant_ctx.tenant_id,
+            &req.query_id,
+            TranscriptFeedback {
+                feedback_type: req.feedback_type.as_str().to_string(),
+                rating: req.rating,
+                comment: req.comment.clone(),
+            },
+        )
+        .await?;
     
     let response = serde_json::json!({
         "message": "反馈提交成功",
-        "feedback_id": Uuid::new_v4(),
+        "query_id": req.query_id,
         "submitted_at": Utc::now()
     });
     
@@ -450,7 +625,7 @@ pub async fn submit_feedback(
 pub async fn get_suggestions(
     db: web::Data<DatabaseConnection>,
     tenant_ctx: TenantContext,
-    _user_ctx: UserContext,
+    user_ctx: UserContext,
     req: web::Json<QaSuggestionsRequest>,
 ) -> ActixResult<HttpResponse> {
     debug!("获取问题建议: 部分问题={}, 租户={}", 
@@ -462,18 +637,16 @@ pub async fn get_suggestions(
     
     let max_suggestions = req.max_suggestions.unwrap_or(5).min(10);
     
-    // TODO: 基于历史查询和知识库内容生成智能建议
-    // 目前返回模拟建议
-    
-    let suggestions = vec![
-        format!("{}相关的最新发展是什么？", req.partial_question),
-        format!("{}的主要特点有哪些？", req.partial_question),
-        format!("如何理解{}的概念？", req.partial_question),
-        format!("{}在实际应用中的案例", req.partial_question),
-        format!("{}的优缺点分析", req.partial_question),
-    ].into_iter()
-    .take(max_suggestions as usize)
-    .collect();
+    // 基于热门历史查询与知识库文档生成补全建议
+    let suggestions = QuestionSuggestionService::new(db.get_ref().clone())
+        .autocomplete(
+            tenant_ctx.tenant_id,
+            req.knowledge_base_id,
+            clearance_for(&user_ctx.user.role, &user_ctx.permissions),
+            &req.partial_question,
+            max_suggestions as usize,
+        )
+        .await?;
     
     let response = QaSuggestionsResponse {
         suggestions,
@@ -483,6 +656,358 @@ pub async fn get_suggestions(
     Ok(HttpResponse::Ok().json(ApiResponse::ok(response)))
 }
 
+/// 获取相关问题推荐
+///
+/// 基于热门历史查询与知识库文档摘要推荐问题，用于聊天界面的「大家还在问」提示；
+/// 指定刚回答过的问题时推荐与之相关的问题。
+#[utoipa::path(
+    post,
+    path = "/api/v1/qa/related-questions",
+    request_body = RelatedQuestionsRequest,
+    responses(
+        (status = 200, description = "获取推荐成功", body = RelatedQuestionsResponse),
+        (status = 401, description = "未授权", body = ApiError),
+        (status = 500, description = "服务器内部错误", body = ApiError)
+    ),
+    tag = "qa",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_related_questions(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: web::Json<RelatedQuestionsRequest>,
+) -> ActixResult<HttpResponse> {
+    debug!("获取相关问题推荐: 租户={}, 知识库={:?}", tenant_info.id, req.knowledge_base_id);
+    
+    let response = QuestionSuggestionService::new(db.get_ref().clone())
+        .related_questions(
+            tenant_info.id,
+            clearance_for(&user.role, &user.permissions),
+            &req,
+        )
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(ApiResponse::ok(response)))
+}
+
+/// 创建会话记录导出
+///
+/// 在后台任务中将指定会话或时间段内的问答记录（含引用与反馈）导出为 JSON/CSV，
+/// 普通用户只能导出自己的记录。
+#[utoipa::path(
+    post,
+    path = "/api/v1/qa/transcripts/exports",
+    request_body = CreateTranscriptExportRequest,
+    responses(
+        (status = 202, description = "导出任务已提交", body = TranscriptExportResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 401, description = "未授权", body = ApiError),
+        (status = 500, description = "服务器内部错误", body = ApiError)
+    ),
+    tag = "qa",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn create_transcript_export(
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: web::Json<CreateTranscriptExportRequest>,
+) -> ActixResult<HttpResponse> {
+    let request = req.into_inner();
+    let format = request.format;
+    let mut filter = request.into_filter()?;
+    if !user.is_admin {
+        filter.user_id = Some(user.user_id);
+    }
+
+    info!("创建会话记录导出: 租户={}, 用户={}, 格式={:?}", tenant_info.id, user.user_id, format);
+
+    let response = transcript_export_service()?
+        .submit(tenant_info.id, user.user_id, format, filter)
+        .await?;
+
+    Ok(HttpResponse::Accepted().json(ApiResponse::accepted(response)))
+}
+
+/// 查询会话记录导出状态
+///
+/// 导出完成后返回签名下载链接。
+#[utoipa::path(
+    get,
+    path = "/api/v1/qa/transcripts/exports/{export_id}",
+    params(
+        ("export_id" = Uuid, Path, description = "导出 ID")
+    ),
+    responses(
+        (status = 200, description = "获取导出状态成功", body = TranscriptExportResponse),
+        (status = 401, description = "未授权", body = ApiError),
+        (status = 404, description = "导出任务不存在", body = ApiError),
+        (status = 500, description = "服务器内部错误", body = ApiError)
+    ),
+    tag = "qa",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_transcript_export(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let response = transcript_export_service()?
+        .status(tenant_info.id, path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::ok(response)))
+}
+
+/// 下载会话记录导出文件
+///
+/// 通过签名链接访问，无需登录；链接过期后需重新查询导出状态获取新链接。
+#[utoipa::path(
+    get,
+    path = "/api/v1/downloads/transcripts/{export_id}",
+    params(
+        ("export_id" = Uuid, Path, description = "导出 ID"),
+        ("token" = String, Query, description = "下载令牌")
+    ),
+    responses(
+        (status = 200, description = "导出文件"),
+        (status = 403, description = "下载链接无效或已过期", body = ApiError),
+        (status = 404, description = "导出文件不存在", body = ApiError)
+    ),
+    tag = "qa"
+)]
+pub async fn download_transcript_export(
+    path: web::Path<Uuid>,
+    query: web::Query<TranscriptDownloadQuery>,
+) -> ActixResult<HttpResponse> {
+    let export_id = path.into_inner();
+    let config = ConfigLoader::get();
+    let claims = verify_download_token(&config.security.jwt_secret, export_id, &query.token)?;
+
+    let file_path = export_file_path(
+        std::path::Path::new(&config.storage.path),
+        claims.tid,
+        export_id,
+        claims.fmt,
+    );
+    let content = tokio::fs::read(&file_path)
+        .await
+        .map_err(|_| AiStudioError::not_found("导出文件"))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(claims.fmt.content_type())
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"qa-transcripts-{}.{}\"", export_id, claims.fmt.extension()),
+        ))
+        .body(content))
+}
+
+/// 会话记录导出服务，依赖应用启动时安装的全局任务队列
+fn transcript_export_service() -> Result<TranscriptExportService, AiStudioError> {
+    let queue = TaskQueueService::global()
+        .ok_or_else(|| AiStudioError::internal("任务队列未初始化"))?;
+    Ok(TranscriptExportService::new(queue, ConfigLoader::get().security.jwt_secret.clone()))
+}
+
+/// 审校问答答案
+///
+/// 保存人工修订后的答案，构建微调数据集时替代模型原答案。仅租户管理员可用。
+#[utoipa::path(
+    put,
+    path = "/api/v1/qa/transcripts/{query_id}/answer",
+    params(
+        ("query_id" = String, Path, description = "查询 ID")
+    ),
+    request_body = ReviewAnswerRequest,
+    responses(
+        (status = 204, description = "答案已审校"),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "问答记录不存在", body = ApiError)
+    ),
+    tag = "qa",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn review_answer(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<String>,
+    req: web::Json<ReviewAnswerRequest>,
+) -> ActixResult<HttpResponse> {
+    if !user.is_admin {
+        return Err(AiStudioError::forbidden("只有租户管理员可以审校答案").into());
+    }
+
+    QaTranscriptService::new(db.get_ref().clone())
+        .review_answer(tenant_info.id, &path.into_inner(), &req.answer, user.user_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// 构建微调数据集
+///
+/// 在后台任务中从获得正向反馈或经人工审校的问答记录构建 JSONL 微调数据集，
+/// 去重并脱敏后写入存储。仅租户管理员可用。
+#[utoipa::path(
+    post,
+    path = "/api/v1/qa/finetune-datasets",
+    request_body = BuildFinetuneDatasetRequest,
+    responses(
+        (status = 202, description = "构建任务已提交", body = FinetuneDatasetResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 500, description = "服务器内部错误", body = ApiError)
+    ),
+    tag = "qa",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn build_finetune_dataset(
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: web::Json<BuildFinetuneDatasetRequest>,
+) -> ActixResult<HttpResponse> {
+    if !user.is_admin {
+        return Err(AiStudioError::forbidden("只有租户管理员可以构建微调数据集").into());
+    }
+
+    info!("构建微调数据集: 租户={}, 用户={}", tenant_info.id, user.user_id);
+    let response = finetune_dataset_service()?
+        .submit(tenant_info.id, user.user_id, req.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Accepted().json(ApiResponse::accepted(response)))
+}
+
+/// 查询微调数据集构建状态
+#[utoipa::path(
+    get,
+    path = "/api/v1/qa/finetune-datasets/{dataset_id}",
+    params(
+        ("dataset_id" = Uuid, Path, description = "数据集 ID")
+    ),
+    responses(
+        (status = 200, description = "获取构建状态成功", body = FinetuneDatasetResponse),
+        (status = 403, description = "权限不足", body = ApiError),
+        (status = 404, description = "构建任务不存在", body = ApiError)
+    ),
+    tag = "qa",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_finetune_dataset(
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    if !user.is_admin {
+        return Err(AiStudioError::forbidden("只有租户管理员可以查看微调数据集").into());
+    }
+
+    let response = finetune_dataset_service()?
+        .status(tenant_info.id, path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::ok(response)))
+}
+
+/// 微调数据集服务，依赖应用启动时安装的全局任务队列
+fn finetune_dataset_service() -> Result<FinetuneDatasetService, AiStudioError> {
+    let queue = TaskQueueService::global()
+        .ok_or_else(|| AiStudioError::internal("任务队列未初始化"))?;
+    Ok(FinetuneDatasetService::new(queue))
+}
+
+/// 解析跨知识库问答的目标知识库，非联邦问答返回空列表
+///
+/// 显式指定的知识库不存在时返回未找到、无权访问时拒绝请求；租户级范围只保留请求者有权访问的活跃知识库。
+async fn resolve_federated_scope(
+    db: &DatabaseConnection,
+    tenant_id: Uuid,
+    user_ctx: &UserContext,
+    req: &QaRequest,
+) -> Result<Vec<Uuid>, AiStudioError> {
+    let requested = req.knowledge_base_ids.clone().unwrap_or_default();
+    if requested.is_empty() && !req.all_knowledge_bases {
+        return Ok(Vec::new());
+    }
+    if req.knowledge_base_id.is_some() {
+        return Err(AiStudioError::validation("knowledge_base_ids", "跨知识库问答不能同时指定 knowledge_base_id"));
+    }
+    if !requested.is_empty() && req.all_knowledge_bases {
+        return Err(AiStudioError::validation("knowledge_base_ids", "knowledge_base_ids 与 all_knowledge_bases 不能同时指定"));
+    }
+    if requested.len() > MAX_FEDERATED_KNOWLEDGE_BASES {
+        return Err(AiStudioError::validation(
+            "knowledge_base_ids",
+            format!("最多同时检索 {} 个知识库", MAX_FEDERATED_KNOWLEDGE_BASES),
+        ));
+    }
+    if req.per_kb_top_k.is_some_and(|quota| !(1..=50).contains(&quota)) {
+        return Err(AiStudioError::validation("per_kb_top_k", "每个知识库的检索数量必须在 1-50 之间"));
+    }
+    
+    let mut query = KnowledgeBase::find().filter(knowledge_base::Column::TenantId.eq(tenant_id));
+    query = if req.all_knowledge_bases {
+        query.filter(knowledge_base::Column::Status.eq(KnowledgeBaseStatus::Active))
+    } else {
+        query.filter(knowledge_base::Column::Id.is_in(requested.clone()))
+    };
+    let knowledge_bases = query
+        .order_by_asc(knowledge_base::Column::Name)
+        .all(db)
+        .await?;
+    
+    let role = user_ctx.user.role.to_string();
+    let user_id = user_ctx.user.id.to_string();
+    let can_read = |kb: &knowledge_base::Model| kb.has_access(&role, &user_id).unwrap_or(false);
+    
+    if req.all_knowledge_bases {
+        let ids: Vec<Uuid> = knowledge_bases.iter()
+            .filter(|kb| can_read(kb))
+            .map(|kb| kb.id)
+            .take(MAX_FEDERATED_KNOWLEDGE_BASES)
+            .collect();
+        if ids.is_empty() {
+            return Err(AiStudioError::forbidden("没有可访问的知识库"));
+        }
+        debug!("租户级问答范围: tenant_id={}, 知识库数={}", tenant_id, ids.len());
+        return Ok(ids);
+    }
+    
+    let mut ids = Vec::with_capacity(requested.len());
+    for id in requested {
+        let kb = knowledge_bases.iter()
+            .find(|kb| kb.id == id)
+            .ok_or_else(|| AiStudioError::not_found(format!("知识库 {}", id)))?;
+        if !can_read(kb) {
+            return Err(AiStudioError::forbidden(format!("无权访问知识库 {}", kb.name)));
+        }
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    Ok(ids)
+}
+
 /// 转换 RAG 响应为 QA 来源格式
 fn convert_to_qa_sources(rag_response: &RagQueryResponse) -> Vec<QaSource> {
     let mut sources = Vec::new();
@@ -519,6 +1044,8 @@ fn convert_to_qa_sources(rag_response: &RagQueryResponse) -> Vec<QaSource> {
                 doc_type: source_doc.doc_type.clone(),
                 relevance_score: source_doc.relevance_score,
                 chunks: qa_chunks,
+                knowledge_base_id: source_doc.knowledge_base_id,
+                knowledge_base_name: source_doc.knowledge_base_name.clone(),
             });
         }
     }
@@ -558,9 +1085,14 @@ fn create_qa_stream(
     request: QaRequest,
     tenant_id: Uuid,
     user_id: Uuid,
+    clearance: ClearanceLevel,
     session_id: String,
+    greeting: Option<String>,
+    api_key_id: Option<Uuid>,
 ) -> impl Stream<Item = Result<sse::Event, actix_web::Error>> {
     let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    // 后台任务不继承请求作用域，显式带上截止时间
+    let request_deadline = deadline::current();
     
     // 在后台任务中执行 RAG 查询
     tokio::spawn(async move {
@@ -569,7 +1101,8 @@ fn create_qa_stream(
             event: "start".to_string(),
             data: serde_json::json!({
                 "session_id": session_id,
-                "message": "开始处理您的问题..."
+                "message": "开始处理您的问题...",
+                "greeting": greeting
             }),
             timestamp: Utc::now(),
         };
@@ -600,11 +1133,28 @@ fn create_qa_stream(
             generation_params: request.generation_params,
             session_id: Some(session_id.clone()),
             user_id: Some(user_id),
+            kb_version: request.kb_version,
+            output_schema: request.output_schema,
+            clearance,
+            knowledge_base_ids: request.knowledge_base_ids.unwrap_or_default(),
+            per_kb_top_k: request.per_kb_top_k,
+            explain: false,
         };
         
         // 执行 RAG 查询
-        match rag_engine.query(rag_request).await {
+        let result = match request_deadline {
+            Some(request_deadline) => deadline::scope(request_deadline, rag_engine.query(rag_request)).await,
+            None => rag_engine.query(rag_request).await,
+        };
+        match result {
             Ok(rag_response) => {
+                record_usage(
+                    tenant_id,
+                    AnomalyMetric::Tokens,
+                    api_key_id,
+                    rag_response.query_stats.tokens_generated.unwrap_or(0) as u64,
+                );
+
                 // 发送生成事件
                 let generation_event = StreamEvent {
                     event: "generation".to_string(),
@@ -657,6 +1207,11 @@ fn create_qa_stream(
                         "confidence_score": rag_response.confidence_score,
                         "sources": sources,
                         "suggestions": suggestions,
+                        "faq_match": rag_response.faq_match,
+                        "curated_answer": rag_response.curated_answer,
+                        "confidence": rag_response.confidence,
+                        "insufficient_information": rag_response.insufficient_information,
+                        "deadline_exceeded": rag_response.deadline_exceeded,
                         "stats": {
                             "response_time_ms": rag_response.query_stats.total_time_ms,
                             "documents_retrieved": rag_response.source_documents.len(),
@@ -700,5 +1255,12 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             .route("/sessions/{session_id}/history", web::get().to(get_session_history))
             .route("/feedback", web::post().to(submit_feedback))
             .route("/suggestions", web::post().to(get_suggestions))
+            .route("/related-questions", web::post().to(get_related_questions))
+            .route("/transcripts/exports", web::post().to(create_transcript_export))
+            .route("/transcripts/exports/{export_id}", web::get().to(get_transcript_export))
+            .route("/transcripts/{query_id}/answer", web::put().to(review_answer))
+            .route("/finetune-datasets", web::post().to(build_finetune_dataset))
+            .route("/finetune-datasets/{dataset_id}", web::get().to(get_finetune_dataset))
     );
+    cfg.route("/downloads/transcripts/{export_id}", web::get().to(download_transcript_export));
 }
\ No newline at end of file
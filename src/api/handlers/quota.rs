@@ -10,8 +10,10 @@ use crate::api::middleware::auth::AuthenticatedUser;
 use crate::services::quota::{
     QuotaService, QuotaType, QuotaUpdateRequest
 };
+use crate::config::ConfigLoader;
 use crate::db::DatabaseManager;
 use crate::errors::AiStudioError;
+use crate::services::billing::{BillingService, BillingSimulationRequest};
 
 /// 配额管理 API 文档
 // #[derive(OpenApi)]
@@ -192,6 +194,43 @@ pub async fn get_quota_trends(
     }))
 }
 
+/// 模拟套餐变更或预计用量
+/// 使用与配额检查相同的计算，返回预计费用与会超限的配额
+#[utoipa::path(
+    post,
+    path = "/quota/{tenant_id}/simulate",
+    tag = "quota",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    request_body = BillingSimulationRequest,
+    responses(
+        (status = 200, description = "模拟结果", body = crate::services::billing::BillingSimulationResponse),
+        (status = 403, description = "需要租户管理员权限", body = ApiError),
+        (status = 404, description = "租户不存在", body = ApiError)
+    )
+)]
+pub async fn simulate_billing(
+    path: web::Path<Uuid>,
+    request: web::Json<BillingSimulationRequest>,
+    user: web::ReqData<AuthenticatedUser>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+
+    // 检查权限：平台管理员或该租户的管理员
+    if !user.is_admin && (user.tenant_id != tenant_id || user.role != "admin") {
+        return Err(AiStudioError::forbidden("只有租户管理员可以模拟套餐变更").into());
+    }
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let db = db_manager.get_connection();
+    let billing_service = BillingService::new(db.clone(), ConfigLoader::get().billing.clone());
+
+    let simulation = billing_service.simulate(tenant_id, &request.into_inner()).await?;
+    HttpResponseBuilder::ok(simulation)
+}
+
 /// 配额检查查询参数
 #[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct CheckQuotaQuery {
@@ -232,6 +271,7 @@ pub fn configure_quota_routes(cfg: &mut web::ServiceConfig) {
                     .route("/usage", web::get().to(get_quota_usage))
                     .route("/{quota_type}/check", web::get().to(check_quota))
                     .route("/{quota_type}/trends", web::get().to(get_quota_trends))
+                    .route("/{tenant_id}/simulate", web::post().to(simulate_billing))
             )
             // 管理员专用路由
             .service(
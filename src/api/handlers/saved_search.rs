@@ -0,0 +1,180 @@
+// 保存的搜索 API 处理器
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::api::middleware::auth::AuthenticatedUser;
+use crate::api::middleware::tenant::TenantInfo;
+use crate::api::responses::HttpResponseBuilder;
+use crate::services::saved_search::{CreateSavedSearchRequest, SavedSearchService, UpdateSavedSearchRequest};
+
+/// 保存的搜索列表查询参数
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct SavedSearchQuery {
+    /// 按知识库过滤
+    pub knowledge_base_id: Option<Uuid>,
+}
+
+/// 创建保存的搜索
+#[utoipa::path(
+    post,
+    path = "/api/v1/saved-searches",
+    request_body = CreateSavedSearchRequest,
+    responses(
+        (status = 201, description = "创建成功", body = SavedSearchResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 404, description = "知识库不存在", body = ApiError)
+    ),
+    tag = "saved-searches",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn create_saved_search(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: web::Json<CreateSavedSearchRequest>,
+) -> ActixResult<HttpResponse> {
+    let search = SavedSearchService::new(db.get_ref().clone())
+        .create_search(tenant_info.id, user.user_id, req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::created(search)
+}
+
+/// 列出当前用户保存的搜索
+#[utoipa::path(
+    get,
+    path = "/api/v1/saved-searches",
+    params(SavedSearchQuery),
+    responses(
+        (status = 200, description = "获取列表成功", body = Vec<SavedSearchResponse>)
+    ),
+    tag = "saved-searches",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_saved_searches(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    query: web::Query<SavedSearchQuery>,
+) -> ActixResult<HttpResponse> {
+    let searches = SavedSearchService::new(db.get_ref().clone())
+        .list_searches(tenant_info.id, user.user_id, query.knowledge_base_id)
+        .await?;
+
+    HttpResponseBuilder::ok(searches)
+}
+
+/// 获取保存的搜索
+#[utoipa::path(
+    get,
+    path = "/api/v1/saved-searches/{id}",
+    params(
+        ("id" = Uuid, Path, description = "搜索 ID")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = SavedSearchResponse),
+        (status = 404, description = "搜索不存在", body = ApiError)
+    ),
+    tag = "saved-searches",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_saved_search(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let search = SavedSearchService::new(db.get_ref().clone())
+        .get_search(tenant_info.id, user.user_id, path.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(search)
+}
+
+/// 更新保存的搜索
+#[utoipa::path(
+    put,
+    path = "/api/v1/saved-searches/{id}",
+    params(
+        ("id" = Uuid, Path, description = "搜索 ID")
+    ),
+    request_body = UpdateSavedSearchRequest,
+    responses(
+        (status = 200, description = "更新成功", body = SavedSearchResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 404, description = "搜索不存在", body = ApiError)
+    ),
+    tag = "saved-searches",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn update_saved_search(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<UpdateSavedSearchRequest>,
+) -> ActixResult<HttpResponse> {
+    let search = SavedSearchService::new(db.get_ref().clone())
+        .update_search(tenant_info.id, user.user_id, path.into_inner(), req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(search)
+}
+
+/// 删除保存的搜索
+#[utoipa::path(
+    delete,
+    path = "/api/v1/saved-searches/{id}",
+    params(
+        ("id" = Uuid, Path, description = "搜索 ID")
+    ),
+    responses(
+        (status = 204, description = "删除成功"),
+        (status = 404, description = "搜索不存在", body = ApiError)
+    ),
+    tag = "saved-searches",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn delete_saved_search(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    SavedSearchService::new(db.get_ref().clone())
+        .delete_search(tenant_info.id, user.user_id, path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// 配置保存的搜索路由
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/saved-searches")
+            .route("", web::post().to(create_saved_search))
+            .route("", web::get().to(list_saved_searches))
+            .route("/{id}", web::get().to(get_saved_search))
+            .route("/{id}", web::put().to(update_saved_search))
+            .route("/{id}", web::delete().to(delete_saved_search))
+    );
+}
@@ -0,0 +1,284 @@
+// 定时报告 API 处理器
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use tracing::info;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::api::middleware::auth::{AuthenticatedUser, PermissionChecker};
+use crate::api::middleware::tenant::TenantInfo;
+use crate::api::responses::HttpResponseBuilder;
+use crate::config::ConfigLoader;
+use crate::errors::AiStudioError;
+use crate::services::scheduled_report::{
+    CreateScheduledReportRequest, ScheduledReportService, UpdateScheduledReportRequest,
+};
+
+/// 报告执行记录查询参数
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct ReportRunsQuery {
+    /// 返回条数，默认 20，最多 200
+    pub limit: Option<u64>,
+}
+
+fn ensure_tenant_admin(user: &AuthenticatedUser) -> Result<(), AiStudioError> {
+    if !PermissionChecker::has_role(user, "admin") {
+        return Err(AiStudioError::forbidden("需要租户管理员权限"));
+    }
+    Ok(())
+}
+
+fn report_service(db: &DatabaseConnection) -> ScheduledReportService {
+    ScheduledReportService::from_app_config(db.clone(), ConfigLoader::get())
+}
+
+/// 创建定时报告
+#[utoipa::path(
+    post,
+    path = "/api/v1/reports",
+    request_body = CreateScheduledReportRequest,
+    responses(
+        (status = 201, description = "创建成功", body = ScheduledReportResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "需要租户管理员权限", body = ApiError),
+        (status = 404, description = "报告数据来源不存在", body = ApiError)
+    ),
+    tag = "reports",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn create_scheduled_report(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: web::Json<CreateScheduledReportRequest>,
+) -> ActixResult<HttpResponse> {
+    ensure_tenant_admin(&user)?;
+    info!("创建定时报告: tenant_id={}, name={}, user={}", tenant_info.id, req.name, user.user_id);
+
+    let report = report_service(db.get_ref())
+        .create_report(tenant_info.id, req.into_inner(), user.user_id)
+        .await?;
+
+    HttpResponseBuilder::created(report)
+}
+
+/// 列出定时报告
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports",
+    responses(
+        (status = 200, description = "获取列表成功", body = Vec<ScheduledReportResponse>),
+        (status = 403, description = "需要租户管理员权限", body = ApiError)
+    ),
+    tag = "reports",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_scheduled_reports(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+) -> ActixResult<HttpResponse> {
+    ensure_tenant_admin(&user)?;
+
+    let reports = report_service(db.get_ref())
+        .list_reports(tenant_info.id)
+        .await?;
+
+    HttpResponseBuilder::ok(reports)
+}
+
+/// 获取定时报告
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/{id}",
+    params(
+        ("id" = Uuid, Path, description = "报告 ID")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = ScheduledReportResponse),
+        (status = 403, description = "需要租户管理员权限", body = ApiError),
+        (status = 404, description = "报告不存在", body = ApiError)
+    ),
+    tag = "reports",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn get_scheduled_report(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    ensure_tenant_admin(&user)?;
+
+    let report = report_service(db.get_ref())
+        .get_report(tenant_info.id, path.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(report)
+}
+
+/// 更新定时报告
+#[utoipa::path(
+    put,
+    path = "/api/v1/reports/{id}",
+    params(
+        ("id" = Uuid, Path, description = "报告 ID")
+    ),
+    request_body = UpdateScheduledReportRequest,
+    responses(
+        (status = 200, description = "更新成功", body = ScheduledReportResponse),
+        (status = 400, description = "请求参数错误", body = ApiError),
+        (status = 403, description = "需要租户管理员权限", body = ApiError),
+        (status = 404, description = "报告不存在", body = ApiError)
+    ),
+    tag = "reports",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn update_scheduled_report(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    req: web::Json<UpdateScheduledReportRequest>,
+) -> ActixResult<HttpResponse> {
+    ensure_tenant_admin(&user)?;
+
+    let report = report_service(db.get_ref())
+        .update_report(tenant_info.id, path.into_inner(), req.into_inner())
+        .await?;
+
+    HttpResponseBuilder::ok(report)
+}
+
+/// 删除定时报告
+#[utoipa::path(
+    delete,
+    path = "/api/v1/reports/{id}",
+    params(
+        ("id" = Uuid, Path, description = "报告 ID")
+    ),
+    responses(
+        (status = 204, description = "删除成功"),
+        (status = 403, description = "需要租户管理员权限", body = ApiError),
+        (status = 404, description = "报告不存在", body = ApiError)
+    ),
+    tag = "reports",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn delete_scheduled_report(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    ensure_tenant_admin(&user)?;
+
+    report_service(db.get_ref())
+        .delete_report(tenant_info.id, path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// 立即执行定时报告
+///
+/// 统计区间以当前时间结束，生成报告并投递到所有渠道，不影响计划执行时间。
+#[utoipa::path(
+    post,
+    path = "/api/v1/reports/{id}/run",
+    params(
+        ("id" = Uuid, Path, description = "报告 ID")
+    ),
+    responses(
+        (status = 200, description = "执行完成", body = ReportRunResponse),
+        (status = 403, description = "需要租户管理员权限", body = ApiError),
+        (status = 404, description = "报告不存在", body = ApiError)
+    ),
+    tag = "reports",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn run_scheduled_report(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    ensure_tenant_admin(&user)?;
+    let report_id = path.into_inner();
+    info!("手动执行定时报告: report_id={}, user={}", report_id, user.user_id);
+
+    let run = report_service(db.get_ref())
+        .run_now(tenant_info.id, report_id)
+        .await?;
+
+    HttpResponseBuilder::ok(run)
+}
+
+/// 列出报告执行记录
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/{id}/runs",
+    params(
+        ("id" = Uuid, Path, description = "报告 ID"),
+        ReportRunsQuery
+    ),
+    responses(
+        (status = 200, description = "获取列表成功", body = Vec<ReportRunResponse>),
+        (status = 403, description = "需要租户管理员权限", body = ApiError),
+        (status = 404, description = "报告不存在", body = ApiError)
+    ),
+    tag = "reports",
+    security(
+        ("bearer_auth" = []),
+        ("api_key" = [])
+    )
+)]
+pub async fn list_scheduled_report_runs(
+    db: web::Data<DatabaseConnection>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    query: web::Query<ReportRunsQuery>,
+) -> ActixResult<HttpResponse> {
+    ensure_tenant_admin(&user)?;
+
+    let runs = report_service(db.get_ref())
+        .list_runs(tenant_info.id, path.into_inner(), query.limit.unwrap_or(20))
+        .await?;
+
+    HttpResponseBuilder::ok(runs)
+}
+
+/// 配置定时报告路由
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/reports")
+            .route("", web::post().to(create_scheduled_report))
+            .route("", web::get().to(list_scheduled_reports))
+            .route("/{id}", web::get().to(get_scheduled_report))
+            .route("/{id}", web::put().to(update_scheduled_report))
+            .route("/{id}", web::delete().to(delete_scheduled_report))
+            .route("/{id}/run", web::post().to(run_scheduled_report))
+            .route("/{id}/runs", web::get().to(list_scheduled_report_runs))
+    );
+}
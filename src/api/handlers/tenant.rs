@@ -1,17 +1,29 @@
 // 租户管理 API 处理器
 
-use actix_web::{web, HttpResponse, Result as ActixResult, HttpRequest};
+use actix_web::{http::header, web, HttpResponse, Result as ActixResult, HttpRequest};
 use uuid::Uuid;
 
-use crate::api::extractors::AdminExtractor;
-use crate::api::responses::HttpResponseBuilder;
+use crate::api::extractors::{AdminExtractor, IfMatchExtractor};
+use crate::api::responses::{HttpResponseBuilder, SuccessResponse};
 use crate::api::models::{PaginationQuery, PaginatedResponse};
 // use crate::api::middleware::tenant;
 use crate::services::tenant::{
     TenantService, CreateTenantRequest, UpdateTenantRequest, TenantFilter,
     TenantResponse, TenantStatsResponse
 };
-use crate::db::DatabaseManager;
+use crate::services::user_import::UserImportService;
+use crate::services::tenant_persona::TenantPersonaService;
+use crate::services::passkey::PasskeyService;
+use crate::services::egress::EgressPolicyService;
+use crate::services::web_search::WebSearchService;
+use crate::services::prompt_retention::{ModelCallLogQuery, PromptRetentionService};
+use crate::services::usage_anomaly::UsageAnomalyService;
+use crate::services::tenant_secret::TenantSecretService;
+use crate::services::tenant_encryption::{RotateTenantKeyRequest, TenantEncryptionService};
+use crate::services::sandbox::{SandboxSelection, SandboxService};
+use crate::db::entities::tenant::{TenantAuthPolicy, TenantEgressPolicy, TenantPersona, TenantPromptRetention, TenantWebSearch};
+use crate::db::{DatabaseManager, revision_etag};
+use crate::config::ConfigLoader;
 
 /// 租户管理 API 文档
 // #[derive(OpenApi)]
@@ -89,7 +101,9 @@ pub async fn get_tenant(
 
     let tenant = service.get_tenant(tenant_id).await?;
 
-    HttpResponseBuilder::ok(tenant)
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, revision_etag(tenant.revision)))
+        .json(SuccessResponse::ok(tenant)))
 }
 
 /// 获取租户列表
@@ -153,21 +167,671 @@ pub async fn list_tenants(
     request_body = UpdateTenantRequest,
     responses(
         (status = 200, description = "租户更新成功", body = crate::services::tenant::TenantResponse),
-        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError)
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError),
+        (status = 409, description = "租户已被其他用户修改", body = crate::api::responses::ApiError)
     )
 )]
 pub async fn update_tenant(
     _admin: AdminExtractor,
     path: web::Path<Uuid>,
+    if_match: IfMatchExtractor,
     request: web::Json<UpdateTenantRequest>,
 ) -> ActixResult<HttpResponse> {
     let tenant_id = path.into_inner();
     let db_manager = DatabaseManager::get()?;
     let service = TenantService::new(db_manager.get_connection().clone());
 
-    let tenant = service.update_tenant(tenant_id, request.into_inner()).await?;
+    let tenant = service
+        .update_tenant(tenant_id, request.into_inner(), if_match.revision)
+        .await?;
 
-    HttpResponseBuilder::ok(tenant)
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, revision_etag(tenant.revision)))
+        .json(SuccessResponse::ok(tenant)))
+}
+
+/// 获取租户助手人设
+#[utoipa::path(
+    get,
+    path = "/tenants/{tenant_id}/persona",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    responses(
+        (status = 200, description = "租户助手人设", body = TenantPersona),
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn get_tenant_persona(
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = TenantPersonaService::new(db_manager.get_connection().clone());
+
+    let persona = service.get_persona(tenant_id).await?;
+
+    HttpResponseBuilder::ok(persona)
+}
+
+/// 更新租户助手人设
+#[utoipa::path(
+    put,
+    path = "/tenants/{tenant_id}/persona",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    request_body = TenantPersona,
+    responses(
+        (status = 200, description = "人设更新成功", body = TenantPersona),
+        (status = 400, description = "请求参数错误", body = crate::api::responses::ApiError),
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError),
+        (status = 409, description = "租户已被其他用户修改", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn update_tenant_persona(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+    request: web::Json<TenantPersona>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = TenantPersonaService::new(db_manager.get_connection().clone());
+
+    let persona = service.update_persona(tenant_id, request.into_inner()).await?;
+
+    HttpResponseBuilder::ok(persona)
+}
+
+/// 获取租户登录认证策略
+#[utoipa::path(
+    get,
+    path = "/tenants/{tenant_id}/auth-policy",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    responses(
+        (status = 200, description = "租户登录认证策略", body = TenantAuthPolicy),
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn get_tenant_auth_policy(
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = PasskeyService::new(db_manager.get_connection().clone(), ConfigLoader::get().webauthn.clone());
+
+    let policy = service.get_auth_policy(tenant_id).await?;
+
+    HttpResponseBuilder::ok(policy)
+}
+
+/// 更新租户登录认证策略
+///
+/// 列入 `passkey_required_roles` 的角色注册通行密钥后不能再用密码登录。
+#[utoipa::path(
+    put,
+    path = "/tenants/{tenant_id}/auth-policy",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    request_body = TenantAuthPolicy,
+    responses(
+        (status = 200, description = "策略更新成功", body = TenantAuthPolicy),
+        (status = 400, description = "角色无效", body = crate::api::responses::ApiError),
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError),
+        (status = 409, description = "租户已被其他用户修改", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn update_tenant_auth_policy(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+    request: web::Json<TenantAuthPolicy>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = PasskeyService::new(db_manager.get_connection().clone(), ConfigLoader::get().webauthn.clone());
+
+    let policy = service.update_auth_policy(tenant_id, request.into_inner()).await?;
+
+    HttpResponseBuilder::ok(policy)
+}
+
+/// 获取租户提示词留存策略
+#[utoipa::path(
+    get,
+    path = "/tenants/{tenant_id}/prompt-retention",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    responses(
+        (status = 200, description = "租户提示词留存策略", body = TenantPromptRetention),
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn get_tenant_prompt_retention(
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = PromptRetentionService::new(db_manager.get_connection().clone());
+
+    let policy = service.get_policy(tenant_id).await?;
+
+    HttpResponseBuilder::ok(policy)
+}
+
+/// 更新租户提示词留存策略
+///
+/// 控制模型调用日志与执行轨迹是否保存原始提示词和模型输出、保存天数与脱敏方式。
+/// 关闭保存后已保存的调用日志立即删除，缩短保存天数时已保存日志的到期时间随之提前。
+#[utoipa::path(
+    put,
+    path = "/tenants/{tenant_id}/prompt-retention",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    request_body = TenantPromptRetention,
+    responses(
+        (status = 200, description = "策略更新成功", body = TenantPromptRetention),
+        (status = 400, description = "保存天数或字符数上限无效", body = crate::api::responses::ApiError),
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError),
+        (status = 409, description = "租户已被其他用户修改", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn update_tenant_prompt_retention(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+    request: web::Json<TenantPromptRetention>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = PromptRetentionService::new(db_manager.get_connection().clone());
+
+    let policy = service.update_policy(tenant_id, request.into_inner()).await?;
+
+    HttpResponseBuilder::ok(policy)
+}
+
+/// 列出租户的模型调用日志
+///
+/// 只返回未到期的日志；提示词与模型输出按保存时的策略脱敏，未开启保存的部分为空。
+#[utoipa::path(
+    get,
+    path = "/tenants/{tenant_id}/model-call-logs",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID"),
+        ModelCallLogQuery
+    ),
+    responses(
+        (status = 200, description = "模型调用日志，按调用时间倒序", body = Vec<crate::services::prompt_retention::ModelCallLog>)
+    )
+)]
+pub async fn list_tenant_model_call_logs(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+    query: web::Query<ModelCallLogQuery>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = PromptRetentionService::new(db_manager.get_connection().clone());
+
+    let logs = service.list_logs(tenant_id, &query).await?;
+
+    HttpResponseBuilder::ok(logs)
+}
+
+/// 获取租户出站网络设置
+///
+/// 代理地址可能包含认证信息，仅管理员可查看。
+#[utoipa::path(
+    get,
+    path = "/tenants/{tenant_id}/egress",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    responses(
+        (status = 200, description = "租户出站网络设置，未设置的项沿用全局配置", body = TenantEgressPolicy),
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn get_tenant_egress(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = EgressPolicyService::new(db_manager.get_connection().clone());
+
+    let policy = service.get_policy(tenant_id).await?;
+
+    HttpResponseBuilder::ok(policy)
+}
+
+/// 更新租户出站网络设置
+///
+/// 为该租户的模型调用以外的出站请求（HTTP 工具、Webhook、来源抓取等）指定专用代理、追加信任的 CA 证书
+/// 或提高最低 TLS 版本；最低 TLS 版本不能低于全局配置。新设置对之后发出的请求生效。
+#[utoipa::path(
+    put,
+    path = "/tenants/{tenant_id}/egress",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    request_body = TenantEgressPolicy,
+    responses(
+        (status = 200, description = "设置更新成功", body = TenantEgressPolicy),
+        (status = 400, description = "代理地址或 CA 证书无效", body = crate::api::responses::ApiError),
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError),
+        (status = 409, description = "租户已被其他用户修改", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn update_tenant_egress(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+    request: web::Json<TenantEgressPolicy>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = EgressPolicyService::new(db_manager.get_connection().clone());
+
+    let policy = service.update_policy(tenant_id, request.into_inner()).await?;
+
+    HttpResponseBuilder::ok(policy)
+}
+
+/// 获取租户联网搜索设置
+#[utoipa::path(
+    get,
+    path = "/tenants/{tenant_id}/web-search",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    responses(
+        (status = 200, description = "租户联网搜索设置", body = TenantWebSearch),
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn get_tenant_web_search(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = WebSearchService::from_app_config(db_manager.get_connection().clone(), ConfigLoader::get());
+
+    let settings = service.get_settings(tenant_id).await?;
+
+    HttpResponseBuilder::ok(settings)
+}
+
+/// 更新租户联网搜索设置
+///
+/// 选择搜索工具联网搜索使用的后端（SerpAPI、Bing 或自建 SearxNG）。API 密钥先保存为租户密钥，
+/// 这里只填写密钥名称；清空搜索后端即关闭联网搜索。
+#[utoipa::path(
+    put,
+    path = "/tenants/{tenant_id}/web-search",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    request_body = TenantWebSearch,
+    responses(
+        (status = 200, description = "设置更新成功", body = TenantWebSearch),
+        (status = 400, description = "缺少 API 密钥或实例地址无效", body = crate::api::responses::ApiError),
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError),
+        (status = 409, description = "租户已被其他用户修改", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn update_tenant_web_search(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+    request: web::Json<TenantWebSearch>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = WebSearchService::from_app_config(db_manager.get_connection().clone(), ConfigLoader::get());
+
+    let settings = service.update_settings(tenant_id, request.into_inner()).await?;
+
+    HttpResponseBuilder::ok(settings)
+}
+
+/// 批量导入并邀请用户
+#[utoipa::path(
+    post,
+    path = "/tenants/{tenant_id}/users/import",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID"),
+        UserImportQuery
+    ),
+    request_body(content = String, content_type = "text/csv", description = "首行为表头，需包含 email 列，可选 username、display_name、role 列"),
+    responses(
+        (status = 200, description = "导入完成，逐行返回邀请结果与错误", body = crate::services::user_import::UserImportReport),
+        (status = 400, description = "CSV 格式无效", body = crate::api::responses::ApiError),
+        (status = 404, description = "租户不存在", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn import_tenant_users(
+    admin: AdminExtractor,
+    path: web::Path<Uuid>,
+    query: web::Query<UserImportQuery>,
+    body: String,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = UserImportService::new(db_manager.get_connection().clone());
+
+    let report = service
+        .import_users(tenant_id, admin.user.user_id, &body, query.expires_in_hours)
+        .await?;
+
+    HttpResponseBuilder::ok(report)
+}
+
+/// 列出租户最近的用量异常
+#[utoipa::path(
+    get,
+    path = "/tenants/{tenant_id}/usage-anomalies",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID"),
+        UsageAnomalyQuery
+    ),
+    responses(
+        (status = 200, description = "用量异常列表，按检测时间倒序", body = Vec<crate::services::usage_anomaly::UsageAnomalyResponse>)
+    )
+)]
+pub async fn list_tenant_usage_anomalies(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+    query: web::Query<UsageAnomalyQuery>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let db_manager = DatabaseManager::get()?;
+    let service = UsageAnomalyService::new(
+        db_manager.get_connection().clone(),
+        ConfigLoader::get().usage_anomaly.clone(),
+    );
+
+    let anomalies = service.list_anomalies(tenant_id, query.limit).await?;
+
+    HttpResponseBuilder::ok(anomalies)
+}
+
+/// 列出租户密钥（不返回密钥值）
+#[utoipa::path(
+    get,
+    path = "/tenants/{tenant_id}/secrets",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    responses(
+        (status = 200, description = "密钥元数据列表", body = Vec<crate::services::tenant_secret::TenantSecretResponse>)
+    )
+)]
+pub async fn list_tenant_secrets(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let secrets = tenant_secret_service()?.list_secrets(tenant_id).await?;
+
+    HttpResponseBuilder::ok(secrets)
+}
+
+/// 创建或覆盖租户密钥
+/// 工作流中的 API 调用与工具调用步骤可以通过 `{{secrets.NAME}}` 引用
+#[utoipa::path(
+    put,
+    path = "/tenants/{tenant_id}/secrets/{name}",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID"),
+        ("name" = String, Path, description = "密钥名称")
+    ),
+    request_body = PutTenantSecretRequest,
+    responses(
+        (status = 200, description = "密钥已保存", body = crate::services::tenant_secret::TenantSecretResponse),
+        (status = 400, description = "密钥名称或值无效", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn put_tenant_secret(
+    admin: AdminExtractor,
+    path: web::Path<(Uuid, String)>,
+    request: web::Json<PutTenantSecretRequest>,
+) -> ActixResult<HttpResponse> {
+    let (tenant_id, name) = path.into_inner();
+    let request = request.into_inner();
+    let secret = tenant_secret_service()?
+        .put_secret(tenant_id, &name, &request.value, request.description, admin.user.user_id)
+        .await?;
+
+    HttpResponseBuilder::ok(secret)
+}
+
+/// 删除租户密钥
+#[utoipa::path(
+    delete,
+    path = "/tenants/{tenant_id}/secrets/{name}",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID"),
+        ("name" = String, Path, description = "密钥名称")
+    ),
+    responses(
+        (status = 204, description = "密钥已删除"),
+        (status = 404, description = "密钥不存在", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn delete_tenant_secret(
+    _admin: AdminExtractor,
+    path: web::Path<(Uuid, String)>,
+) -> ActixResult<HttpResponse> {
+    let (tenant_id, name) = path.into_inner();
+    tenant_secret_service()?.delete_secret(tenant_id, &name).await?;
+
+    HttpResponseBuilder::no_content()
+}
+
+/// 列出租户的数据密钥（不含密钥材料）
+#[utoipa::path(
+    get,
+    path = "/tenants/{tenant_id}/encryption-keys",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    responses(
+        (status = 200, description = "数据密钥列表，按版本倒序", body = Vec<crate::services::tenant_encryption::TenantKeyInfo>)
+    )
+)]
+pub async fn list_tenant_encryption_keys(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let keys = tenant_encryption_service()?.list_keys(tenant_id).await?;
+
+    HttpResponseBuilder::ok(keys)
+}
+
+/// 轮换租户数据密钥
+/// 首次调用即为租户启用文档内容加密；已有文档在后台改用新密钥加密，旧密钥保留用于解密
+#[utoipa::path(
+    post,
+    path = "/tenants/{tenant_id}/encryption-keys",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    request_body = RotateTenantKeyRequest,
+    responses(
+        (status = 200, description = "新的有效密钥", body = crate::services::tenant_encryption::TenantKeyInfo),
+        (status = 400, description = "密钥材料无效或未配置 KMS", body = crate::api::responses::ApiError),
+        (status = 502, description = "KMS 调用失败", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn rotate_tenant_encryption_key(
+    admin: AdminExtractor,
+    path: web::Path<Uuid>,
+    request: web::Json<RotateTenantKeyRequest>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let key = tenant_encryption_service()?
+        .rotate_key(tenant_id, request.into_inner(), admin.user.user_id)
+        .await?;
+
+    let service = tenant_encryption_service()?;
+    tokio::spawn(async move {
+        if let Err(e) = service.reencrypt_documents(tenant_id).await {
+            tracing::error!("租户文档重新加密失败: tenant_id={}, error={}", tenant_id, e);
+        }
+    });
+
+    HttpResponseBuilder::ok(key)
+}
+
+/// 吊销租户的全部数据密钥
+/// 密钥材料被销毁，已加密的文档内容将永久无法解密，此操作不可撤销
+#[utoipa::path(
+    post,
+    path = "/tenants/{tenant_id}/encryption-keys/revoke",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    responses(
+        (status = 200, description = "密钥已吊销", body = crate::services::tenant_encryption::RevokeTenantKeysResponse)
+    )
+)]
+pub async fn revoke_tenant_encryption_keys(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let result = tenant_encryption_service()?.revoke_keys(tenant_id).await?;
+
+    HttpResponseBuilder::ok(result)
+}
+
+/// 创建租户内容加密服务
+fn tenant_encryption_service() -> Result<TenantEncryptionService, crate::errors::AiStudioError> {
+    let db_manager = DatabaseManager::get()?;
+    TenantEncryptionService::from_config(db_manager.get_connection().clone())
+}
+
+/// 创建租户密钥服务
+fn tenant_secret_service() -> Result<TenantSecretService, crate::errors::AiStudioError> {
+    let db_manager = DatabaseManager::get()?;
+    Ok(TenantSecretService::new(
+        db_manager.get_connection().clone(),
+        &ConfigLoader::get().security.encryption_key,
+    ))
+}
+
+/// 获取租户沙箱
+#[utoipa::path(
+    get,
+    path = "/tenants/{tenant_id}/sandbox",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    responses(
+        (status = 200, description = "沙箱信息", body = crate::services::sandbox::SandboxInfo),
+        (status = 404, description = "租户尚未创建沙箱", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn get_tenant_sandbox(
+    _admin: AdminExtractor,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let sandbox = sandbox_service()?.get(tenant_id).await?;
+
+    HttpResponseBuilder::ok(sandbox)
+}
+
+/// 将选定的 Agent、工作流与知识库复制到沙箱
+/// 沙箱不存在时先创建；沙箱中的同名资源会被生产环境的定义覆盖，文档等数据不会复制
+#[utoipa::path(
+    post,
+    path = "/tenants/{tenant_id}/sandbox",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID")
+    ),
+    request_body = SandboxSelection,
+    responses(
+        (status = 200, description = "沙箱中各资源的变更", body = crate::services::sandbox::SandboxSync),
+        (status = 400, description = "未选择资源", body = crate::api::responses::ApiError),
+        (status = 404, description = "租户或资源不存在", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn sync_tenant_sandbox(
+    admin: AdminExtractor,
+    path: web::Path<Uuid>,
+    request: web::Json<SandboxSelection>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let sync = sandbox_service()?
+        .sync_to_sandbox(tenant_id, &request, admin.user.user_id)
+        .await?;
+
+    HttpResponseBuilder::ok(sync)
+}
+
+/// 将沙箱提升到生产环境
+/// 未指定资源时提升沙箱中的全部资源，`dry_run=true` 时只返回变更预览
+#[utoipa::path(
+    post,
+    path = "/tenants/{tenant_id}/sandbox/promote",
+    tag = "tenant",
+    params(
+        ("tenant_id" = Uuid, Path, description = "租户 ID"),
+        SandboxPromoteQuery
+    ),
+    request_body(content = Option<SandboxSelection>, description = "要提升的资源，省略时提升全部"),
+    responses(
+        (status = 200, description = "生产环境中各资源的变更", body = crate::services::sandbox::SandboxSync),
+        (status = 404, description = "租户尚未创建沙箱或资源不存在", body = crate::api::responses::ApiError),
+        (status = 409, description = "生产环境资源已被并发修改", body = crate::api::responses::ApiError)
+    )
+)]
+pub async fn promote_tenant_sandbox(
+    admin: AdminExtractor,
+    path: web::Path<Uuid>,
+    query: web::Query<SandboxPromoteQuery>,
+    request: Option<web::Json<SandboxSelection>>,
+) -> ActixResult<HttpResponse> {
+    let tenant_id = path.into_inner();
+    let selection = request.map(|r| r.into_inner()).unwrap_or_default();
+    let sync = sandbox_service()?
+        .promote(tenant_id, &selection, admin.user.user_id, query.dry_run)
+        .await?;
+
+    HttpResponseBuilder::ok(sync)
+}
+
+/// 创建租户沙箱服务
+fn sandbox_service() -> Result<SandboxService, crate::errors::AiStudioError> {
+    let db_manager = DatabaseManager::get()?;
+    Ok(SandboxService::new(db_manager.get_connection().clone()))
 }
 
 /// 删除租户
@@ -337,6 +1001,37 @@ pub struct TenantListQuery {
     pub created_before: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+/// 用户导入查询参数
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+pub struct UserImportQuery {
+    /// 邀请有效期（小时），默认 72
+    pub expires_in_hours: Option<i64>,
+}
+
+/// 用量异常查询参数
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+pub struct UsageAnomalyQuery {
+    /// 返回条数，默认 50，最多 200
+    pub limit: Option<u64>,
+}
+
+/// 沙箱提升查询参数
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+pub struct SandboxPromoteQuery {
+    /// 只预览不提升，默认 false
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 保存租户密钥请求
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct PutTenantSecretRequest {
+    /// 密钥值，保存后不可读取
+    pub value: String,
+    /// 密钥说明
+    pub description: Option<String>,
+}
+
 /// 暂停租户请求
 #[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct SuspendTenantRequest {
@@ -377,6 +1072,25 @@ pub fn configure_tenant_routes(cfg: &mut web::ServiceConfig) {
                     .route("/{tenant_id}", web::delete().to(delete_tenant))
                     .route("/{tenant_id}/suspend", web::post().to(suspend_tenant))
                     .route("/{tenant_id}/activate", web::post().to(activate_tenant))
+                    .route("/{tenant_id}/users/import", web::post().to(import_tenant_users))
+                    .route("/{tenant_id}/persona", web::put().to(update_tenant_persona))
+                    .route("/{tenant_id}/auth-policy", web::put().to(update_tenant_auth_policy))
+                    .route("/{tenant_id}/prompt-retention", web::put().to(update_tenant_prompt_retention))
+                    .route("/{tenant_id}/model-call-logs", web::get().to(list_tenant_model_call_logs))
+                    .route("/{tenant_id}/egress", web::get().to(get_tenant_egress))
+                    .route("/{tenant_id}/egress", web::put().to(update_tenant_egress))
+                    .route("/{tenant_id}/web-search", web::get().to(get_tenant_web_search))
+                    .route("/{tenant_id}/web-search", web::put().to(update_tenant_web_search))
+                    .route("/{tenant_id}/usage-anomalies", web::get().to(list_tenant_usage_anomalies))
+                    .route("/{tenant_id}/secrets", web::get().to(list_tenant_secrets))
+                    .route("/{tenant_id}/secrets/{name}", web::put().to(put_tenant_secret))
+                    .route("/{tenant_id}/secrets/{name}", web::delete().to(delete_tenant_secret))
+                    .route("/{tenant_id}/encryption-keys", web::get().to(list_tenant_encryption_keys))
+                    .route("/{tenant_id}/encryption-keys", web::post().to(rotate_tenant_encryption_key))
+                    .route("/{tenant_id}/encryption-keys/revoke", web::post().to(revoke_tenant_encryption_keys))
+                    .route("/{tenant_id}/sandbox", web::get().to(get_tenant_sandbox))
+                    .route("/{tenant_id}/sandbox", web::post().to(sync_tenant_sandbox))
+                    .route("/{tenant_id}/sandbox/promote", web::post().to(promote_tenant_sandbox))
             )
             // 标准认证的路由
             .service(
@@ -385,6 +1099,9 @@ pub fn configure_tenant_routes(cfg: &mut web::ServiceConfig) {
                     .route("/by-slug/{slug}", web::get().to(get_tenant_by_slug))
                     .route("/{tenant_id}", web::get().to(get_tenant))
                     .route("/{tenant_id}/quota/{resource_type}", web::get().to(check_tenant_quota))
+                    .route("/{tenant_id}/persona", web::get().to(get_tenant_persona))
+                    .route("/{tenant_id}/auth-policy", web::get().to(get_tenant_auth_policy))
+                    .route("/{tenant_id}/prompt-retention", web::get().to(get_tenant_prompt_retention))
             )
     );
 }
\ No newline at end of file
@@ -122,6 +122,8 @@ pub async fn call_tool(
     // 构建执行上下文
     let mut context_variables = HashMap::new();
     context_variables.insert("tenant_id".to_string(), serde_json::Value::String(tenant_info.id.to_string()));
+    // 单次调用作为一次执行，文件工具在调用 ID 对应的临时工作区内操作
+    context_variables.insert("execution_id".to_string(), serde_json::Value::String(call_id.to_string()));
     
     let execution_context = ExecutionContext {
         current_task: None,
@@ -129,6 +131,7 @@ pub async fn call_tool(
         context_variables,
         session_id: None,
         user_id: None, // TODO: 从认证中间件获取用户ID
+        tool_progress: None,
     };
     
     // 构建工具调用请求
@@ -2,8 +2,10 @@
 
 use actix_web::{web, HttpResponse, Result as ActixResult};
 
+use crate::api::middleware::route_cache::RouteCacheMiddleware;
 use crate::api::models::ApiVersion;
 use crate::api::responses::HttpResponseBuilder;
+use crate::services::route_cache::RouteCacheScope;
 
 /// 版本 API 文档
 // #[derive(OpenApi)]
@@ -205,8 +207,8 @@ fn get_dependency_info() -> serde_json::Value {
 pub fn configure_version_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/version")
-            .route("", web::get().to(get_version))
-            .route("/build-info", web::get().to(get_build_info))
-            .route("/spec", web::get().to(get_api_spec))
+            .route("", web::get().to(get_version).wrap(RouteCacheMiddleware::new(RouteCacheScope::Version).ttl_secs(300)))
+            .route("/build-info", web::get().to(get_build_info).wrap(RouteCacheMiddleware::new(RouteCacheScope::Version).ttl_secs(300)))
+            .route("/spec", web::get().to(get_api_spec).wrap(RouteCacheMiddleware::new(RouteCacheScope::Version).ttl_secs(300)))
     );
 }
\ No newline at end of file
@@ -2,19 +2,42 @@
 
 use std::sync::Arc;
 use std::collections::HashMap;
-use actix_web::{web, HttpResponse, Result as ActixResult};
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Result as ActixResult};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use tracing::{info, error, debug};
+use tracing::{info, error, debug, warn};
 use utoipa::ToSchema;
 
 use crate::ai::{
-    workflow_engine::{WorkflowEngine, WorkflowDefinition, WorkflowStatus, ValidationResult},
+    workflow_engine::{
+        WorkflowEngine, WorkflowDefinition, WorkflowStatus, ValidationResult, WorkflowEditError, parse_if_match,
+    },
     workflow_executor::{WorkflowExecutor, ExecutionRequest},
+    workflow_inputs::{build_input_form, validate_workflow_inputs},
+    workflow_document::{generated_document_path, verify_document_token},
     agent_runtime::ExecutionContext,
 };
+use crate::db::entities::canary_release::CanaryStatus;
+use crate::db::entities::execution_event::ExecutionType;
 use crate::db::entities::workflow_execution::{ExecutionOptions, NotificationSettings};
+use crate::config::ConfigLoader;
+use crate::db::DatabaseManager;
 use crate::errors::AiStudioError;
+use crate::plugins::event_bus::{event_types, SystemEvent, SystemEventBus};
+use crate::services::execution_artifact::ExecutionArtifactService;
+use crate::services::execution_event::{
+    ExecutionEventService, ExecutionStepQuery, ExecutionStepsQuery, ExecutionTimelineQuery, TracePagePolicy,
+};
+use crate::services::canary::{CanaryService, CreateCanaryRequest, EndCanaryRequest};
+use crate::services::route_cache::{self, RouteCacheScope};
+use crate::services::execution_replay::{
+    ExecutionFeedbackRequest, ExecutionRecordService, ExecutionRecordSummary, ReplayExecutionRequest,
+};
+use crate::services::workflow_callback::{
+    WorkflowCallbackService, CALLBACK_SIGNATURE_HEADER, MAX_CALLBACK_PAYLOAD_BYTES,
+};
+use crate::api::middleware::auth::AuthenticatedUser;
+use crate::api::middleware::route_cache::RouteCacheMiddleware;
 use crate::api::middleware::tenant::TenantInfo;
 
 /// 工作流创建请求
@@ -30,6 +53,33 @@ pub struct CreateWorkflowRequest {
     pub workflow_definition: String,
 }
 
+/// 工作流更新请求，未提供的字段保持不变
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateWorkflowRequest {
+    /// 工作流名称
+    pub name: Option<String>,
+    /// 工作流描述
+    pub description: Option<String>,
+    /// 工作流版本
+    pub version: Option<String>,
+    /// 工作流定义（JSON 字符串），替换步骤、参数、输出和配置
+    pub workflow_definition: Option<String>,
+}
+
+/// 获取编辑租约请求
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct AcquireEditLeaseRequest {
+    /// 租约时长（秒），默认 60 秒
+    pub ttl_seconds: Option<u64>,
+}
+
+/// 生成文档下载查询参数
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DocumentDownloadQuery {
+    /// 下载令牌
+    pub token: String,
+}
+
 /// 工作流创建响应
 #[derive(Debug, Serialize, ToSchema)]
 pub struct CreateWorkflowResponse {
@@ -57,7 +107,7 @@ pub struct ValidationSummary {
 }
 
 /// 工作流执行请求
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ExecuteWorkflowRequest {
     /// 执行参数
     pub parameters: HashMap<String, serde_json::Value>,
@@ -270,6 +320,7 @@ pub async fn create_workflow(
     workflow.created_at = chrono::Utc::now();
     workflow.updated_at = chrono::Utc::now();
     workflow.status = WorkflowStatus::Draft;
+    workflow.revision = 0;
     
     // 验证工作流
     let validation_result = match workflow_engine.validate_workflow(&workflow).await {
@@ -294,6 +345,7 @@ pub async fn create_workflow(
     
     info!("工作流创建成功: workflow_id={}, name={}", workflow.id, workflow.name);
     
+    let etag = workflow.etag();
     let response = CreateWorkflowResponse {
         workflow_id: workflow.id,
         name: workflow.name,
@@ -308,8 +360,11 @@ pub async fn create_workflow(
                 .collect(),
         },
     };
+    route_cache::invalidate(RouteCacheScope::Workflows, Some(tenant_info.id)).await;
     
-    Ok(HttpResponse::Created().json(response))
+    Ok(HttpResponse::Created()
+        .insert_header((header::ETAG, etag))
+        .json(response))
 }
 
 /// 执行工作流
@@ -319,8 +374,9 @@ pub async fn create_workflow(
     request_body = ExecuteWorkflowRequest,
     responses(
         (status = 200, description = "工作流执行启动成功", body = ExecuteWorkflowResponse),
-        (status = 400, description = "请求参数错误"),
+        (status = 400, description = "请求参数错误或执行参数校验失败"),
         (status = 404, description = "工作流不存在"),
+        (status = 409, description = "并发组名额已满且策略为拒绝"),
         (status = 500, description = "服务器内部错误")
     ),
     params(
@@ -338,11 +394,36 @@ pub async fn execute_workflow(
     let workflow_id = path.into_inner();
     debug!("执行工作流: workflow_id={}, tenant_id={}", workflow_id, tenant_info.id);
     
+    start_workflow_execution(&workflow_engine, &workflow_executor, &tenant_info, workflow_id, request.into_inner(), None).await
+}
+
+/// 启动工作流执行并保存执行记录，`replay_of` 为重放的原始执行 ID
+async fn start_workflow_execution(
+    workflow_engine: &WorkflowEngine,
+    workflow_executor: &WorkflowExecutor,
+    tenant_info: &TenantInfo,
+    workflow_id: Uuid,
+    request: ExecuteWorkflowRequest,
+    replay_of: Option<Uuid>,
+) -> ActixResult<HttpResponse> {
+    // 入口有灰度发布时分流到稳定版本或灰度版本
+    let route = match canary_releases() {
+        Some(canary) => canary
+            .route(tenant_info.id, ExecutionType::Workflow, workflow_id, None)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("灰度分流失败，使用入口工作流: workflow_id={}, error={}", workflow_id, e);
+                None
+            }),
+        None => None,
+    };
+    let serve_id = route.map(|route| route.serve_id).unwrap_or(workflow_id);
+    
     // 获取工作流定义
-    let workflow = match workflow_engine.get_workflow(workflow_id).await {
+    let workflow = match workflow_engine.get_workflow(serve_id).await {
         Ok(workflow) => workflow,
         Err(e) => {
-            error!("获取工作流失败: workflow_id={}, error={}", workflow_id, e);
+            error!("获取工作流失败: workflow_id={}, served_by={}, error={}", workflow_id, serve_id, e);
             return Ok(HttpResponse::NotFound().json(serde_json::json!({
                 "error": "工作流不存在",
                 "message": e.to_string()
@@ -365,6 +446,17 @@ pub async fn execute_workflow(
         })));
     }
     
+    // 校验执行参数并补全默认值
+    let parameters = match validate_workflow_inputs(&workflow, &request.parameters) {
+        Ok(parameters) => parameters,
+        Err(violations) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "执行参数校验失败",
+                "violations": violations
+            })));
+        }
+    };
+    
     // 构建执行请求
     let execution_context = ExecutionContext {
         current_task: None,
@@ -372,6 +464,7 @@ pub async fn execute_workflow(
         context_variables: HashMap::new(),
         session_id: None,
         user_id: Some(tenant_info.id),
+        tool_progress: None,
     };
     
     let execution_options = ExecutionOptions {
@@ -389,7 +482,7 @@ pub async fn execute_workflow(
     
     let execution_request = ExecutionRequest {
         workflow: workflow.clone(),
-        parameters: request.parameters.clone(),
+        parameters,
         context: execution_context,
         options: execution_options,
     };
@@ -397,6 +490,19 @@ pub async fn execute_workflow(
     // 启动执行
     let execution_id = match workflow_executor.execute_workflow(execution_request).await {
         Ok(execution_id) => execution_id,
+        Err(e @ AiStudioError::Conflict { .. }) => {
+            info!("并发组名额已满，拒绝工作流执行: workflow_id={}, error={}", workflow_id, e);
+            return Ok(HttpResponse::Conflict().json(serde_json::json!({
+                "error": "并发组名额已满",
+                "message": e.to_string()
+            })));
+        }
+        Err(e @ AiStudioError::Validation { .. }) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "启动工作流执行失败",
+                "message": e.to_string()
+            })));
+        }
         Err(e) => {
             error!("启动工作流执行失败: workflow_id={}, error={}", workflow_id, e);
             return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
@@ -406,12 +512,34 @@ pub async fn execute_workflow(
         }
     };
     
-    info!("工作流执行启动成功: workflow_id={}, execution_id={}", workflow_id, execution_id);
+    info!("工作流执行启动成功: workflow_id={}, served_by={}, execution_id={}", workflow_id, serve_id, execution_id);
+    
+    let status = workflow_executor.get_execution_status(execution_id).await
+        .map(|execution| execution.status)
+        .unwrap_or_else(|_| "running".to_string());
+    
+    if let Some(records) = execution_records() {
+        let input = serde_json::to_value(&request)?;
+        let mut recorded = records
+            .start(tenant_info.id, ExecutionType::Workflow, execution_id, workflow_id, input, None, replay_of)
+            .await;
+        if let (Ok(()), Some(route)) = (&recorded, route) {
+            if let Some(release_id) = route.release_id {
+                recorded = records.tag_canary(execution_id, release_id, route.arm.as_str()).await;
+            }
+        }
+        if recorded.is_ok() {
+            recorded = records.sync_status(execution_id, &status, None).await.map(|_| ());
+        }
+        if let Err(e) = recorded {
+            warn!("记录工作流执行失败: execution_id={}, error={}", execution_id, e);
+        }
+    }
     
     let response = ExecuteWorkflowResponse {
         execution_id,
         workflow_id,
-        status: "running".to_string(),
+        status,
         started_at: chrono::Utc::now(),
         estimated_completion: None, // TODO: 计算预计完成时间
     };
@@ -545,7 +673,9 @@ pub async fn get_workflow(
                 })));
             }
             
-            Ok(HttpResponse::Ok().json(workflow))
+            Ok(HttpResponse::Ok()
+                .insert_header((header::ETAG, workflow.etag()))
+                .json(workflow))
         }
         Err(e) => {
             error!("获取工作流详情失败: workflow_id={}, error={}", workflow_id, e);
@@ -563,6 +693,278 @@ pub async fn get_workflow(
     }
 }
 
+/// 获取工作流输入表单
+///
+/// 返回参数的 JSON Schema 与界面提示，前端可据此渲染手动执行表单。
+#[utoipa::path(
+    get,
+    path = "/api/v1/workflows/{workflow_id}/input-schema",
+    responses(
+        (status = 200, description = "获取输入表单成功", body = WorkflowInputForm),
+        (status = 403, description = "无权限访问此工作流"),
+        (status = 404, description = "工作流不存在")
+    ),
+    params(
+        ("workflow_id" = Uuid, Path, description = "工作流 ID")
+    ),
+    tag = "workflows"
+)]
+pub async fn get_workflow_input_schema(
+    workflow_engine: web::Data<Arc<WorkflowEngine>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let workflow_id = path.into_inner();
+    let workflow = match load_tenant_workflow(&workflow_engine, workflow_id, tenant_info.id).await {
+        Ok(workflow) => workflow,
+        Err(response) => return Ok(response),
+    };
+    
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, workflow.etag()))
+        .json(build_input_form(&workflow)))
+}
+
+/// 更新工作流
+///
+/// 必须通过 `If-Match` 携带读取时的 ETag；工作流已被修改或正被其他用户编辑时返回 409。
+#[utoipa::path(
+    put,
+    path = "/api/v1/workflows/{workflow_id}",
+    request_body = UpdateWorkflowRequest,
+    responses(
+        (status = 200, description = "工作流更新成功", body = WorkflowDefinition),
+        (status = 400, description = "请求参数错误"),
+        (status = 403, description = "无权限访问此工作流"),
+        (status = 404, description = "工作流不存在"),
+        (status = 409, description = "工作流已被修改或正被其他用户编辑", body = WorkflowConflict),
+        (status = 428, description = "缺少 If-Match 请求头")
+    ),
+    params(
+        ("workflow_id" = Uuid, Path, description = "工作流 ID"),
+        ("If-Match" = String, Header, description = "读取工作流时返回的 ETag")
+    ),
+    tag = "workflows"
+)]
+pub async fn update_workflow(
+    workflow_engine: web::Data<Arc<WorkflowEngine>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    request: web::Json<UpdateWorkflowRequest>,
+) -> ActixResult<HttpResponse> {
+    let workflow_id = path.into_inner();
+    debug!("更新工作流: workflow_id={}, tenant_id={}", workflow_id, tenant_info.id);
+    
+    if req.headers().get(header::IF_MATCH).is_none() {
+        return Ok(HttpResponse::PreconditionRequired().json(serde_json::json!({
+            "error": "缺少 If-Match 请求头",
+            "message": "请携带读取工作流时返回的 ETag"
+        })));
+    }
+    let expected_revision = expected_revision(&req)?;
+    
+    let current = match load_tenant_workflow(&workflow_engine, workflow_id, tenant_info.id).await {
+        Ok(workflow) => workflow,
+        Err(response) => return Ok(response),
+    };
+    
+    let request = request.into_inner();
+    let mut workflow = match request.workflow_definition {
+        Some(definition) => match workflow_engine.parse_workflow(&definition).await {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                error!("工作流定义解析失败: {}", e);
+                return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "工作流定义解析失败",
+                    "message": e.to_string()
+                })));
+            }
+        },
+        None => current.clone(),
+    };
+    workflow.id = workflow_id;
+    workflow.status = current.status;
+    workflow.name = request.name.unwrap_or(current.name);
+    workflow.description = request.description.unwrap_or(current.description);
+    workflow.version = request.version.unwrap_or(current.version);
+    
+    match workflow_engine.update_workflow(workflow, expected_revision, user.user_id).await {
+        Ok(workflow) => {
+            info!("工作流更新成功: workflow_id={}, revision={}", workflow_id, workflow.revision);
+            route_cache::invalidate(RouteCacheScope::Workflows, Some(tenant_info.id)).await;
+            Ok(HttpResponse::Ok()
+                .insert_header((header::ETAG, workflow.etag()))
+                .json(workflow))
+        }
+        Err(e) => Ok(edit_error_response(workflow_id, e)),
+    }
+}
+
+/// 获取或续期工作流编辑租约
+#[utoipa::path(
+    post,
+    path = "/api/v1/workflows/{workflow_id}/lock",
+    request_body = AcquireEditLeaseRequest,
+    responses(
+        (status = 200, description = "获取租约成功", body = WorkflowEditLease),
+        (status = 404, description = "工作流不存在"),
+        (status = 409, description = "工作流正被其他用户编辑", body = WorkflowConflict)
+    ),
+    params(
+        ("workflow_id" = Uuid, Path, description = "工作流 ID")
+    ),
+    tag = "workflows"
+)]
+pub async fn acquire_edit_lock(
+    workflow_engine: web::Data<Arc<WorkflowEngine>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    request: Option<web::Json<AcquireEditLeaseRequest>>,
+) -> ActixResult<HttpResponse> {
+    let workflow_id = path.into_inner();
+    if let Err(response) = load_tenant_workflow(&workflow_engine, workflow_id, tenant_info.id).await {
+        return Ok(response);
+    }
+    
+    let ttl_seconds = request.and_then(|r| r.ttl_seconds);
+    match workflow_engine
+        .acquire_edit_lease(workflow_id, user.user_id, user.username.clone(), ttl_seconds)
+        .await
+    {
+        Ok(lease) => Ok(HttpResponse::Ok().json(lease)),
+        Err(e) => Ok(edit_error_response(workflow_id, e)),
+    }
+}
+
+/// 查看工作流编辑租约
+#[utoipa::path(
+    get,
+    path = "/api/v1/workflows/{workflow_id}/lock",
+    responses(
+        (status = 200, description = "当前租约，无人编辑时为 null", body = Option<WorkflowEditLease>),
+        (status = 404, description = "工作流不存在")
+    ),
+    params(
+        ("workflow_id" = Uuid, Path, description = "工作流 ID")
+    ),
+    tag = "workflows"
+)]
+pub async fn get_edit_lock(
+    workflow_engine: web::Data<Arc<WorkflowEngine>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let workflow_id = path.into_inner();
+    if let Err(response) = load_tenant_workflow(&workflow_engine, workflow_id, tenant_info.id).await {
+        return Ok(response);
+    }
+    
+    Ok(HttpResponse::Ok().json(workflow_engine.get_edit_lease(workflow_id).await))
+}
+
+/// 释放工作流编辑租约
+#[utoipa::path(
+    delete,
+    path = "/api/v1/workflows/{workflow_id}/lock",
+    responses(
+        (status = 204, description = "租约已释放"),
+        (status = 404, description = "工作流不存在"),
+        (status = 409, description = "租约由其他用户持有", body = WorkflowConflict)
+    ),
+    params(
+        ("workflow_id" = Uuid, Path, description = "工作流 ID")
+    ),
+    tag = "workflows"
+)]
+pub async fn release_edit_lock(
+    workflow_engine: web::Data<Arc<WorkflowEngine>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let workflow_id = path.into_inner();
+    if let Err(response) = load_tenant_workflow(&workflow_engine, workflow_id, tenant_info.id).await {
+        return Ok(response);
+    }
+    
+    match workflow_engine.release_edit_lease(workflow_id, user.user_id).await {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(edit_error_response(workflow_id, e)),
+    }
+}
+
+/// 读取 If-Match 请求头中的期望修订号
+fn expected_revision(req: &HttpRequest) -> Result<Option<u64>, AiStudioError> {
+    match req.headers().get(header::IF_MATCH) {
+        Some(value) => {
+            let value = value.to_str()
+                .map_err(|_| AiStudioError::validation("If-Match", "无效的 ETag"))?;
+            parse_if_match(value)
+        }
+        None => Ok(None),
+    }
+}
+
+/// 加载工作流并检查租户权限，失败时返回对应的错误响应
+async fn load_tenant_workflow(
+    workflow_engine: &WorkflowEngine,
+    workflow_id: Uuid,
+    tenant_id: Uuid,
+) -> Result<WorkflowDefinition, HttpResponse> {
+    let workflow = workflow_engine.get_workflow(workflow_id).await.map_err(|e| {
+        HttpResponse::NotFound().json(serde_json::json!({
+            "error": "工作流不存在",
+            "message": e.to_string()
+        }))
+    })?;
+    
+    if workflow.tenant_id != tenant_id {
+        return Err(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "无权限访问此工作流"
+        })));
+    }
+    
+    Ok(workflow)
+}
+
+/// 将编辑错误转换为响应，冲突时返回 409 并附带当前修订与租约信息
+fn edit_error_response(workflow_id: Uuid, error: WorkflowEditError) -> HttpResponse {
+    match error {
+        WorkflowEditError::VersionMismatch(conflict) => HttpResponse::Conflict()
+            .insert_header((header::ETAG, conflict.current_etag.clone()))
+            .json(serde_json::json!({
+                "error": "工作流已被其他用户修改",
+                "message": "请重新加载最新版本后再保存",
+                "conflict": conflict
+            })),
+        WorkflowEditError::Locked(conflict) => {
+            let holder = conflict.lease.as_ref().map(|lease| lease.holder_name.clone()).unwrap_or_default();
+            HttpResponse::Conflict()
+                .insert_header((header::ETAG, conflict.current_etag.clone()))
+                .json(serde_json::json!({
+                    "error": "工作流正被其他用户编辑",
+                    "message": format!("工作流正在被 {} 编辑", holder),
+                    "conflict": conflict
+                }))
+        }
+        WorkflowEditError::Other(e) => {
+            error!("工作流编辑失败: workflow_id={}, error={}", workflow_id, e);
+            let mut response = match e {
+                AiStudioError::NotFound { .. } => HttpResponse::NotFound(),
+                AiStudioError::Validation { .. } => HttpResponse::BadRequest(),
+                _ => HttpResponse::InternalServerError(),
+            };
+            response.json(serde_json::json!({
+                "error": "工作流编辑失败",
+                "message": e.to_string()
+            }))
+        }
+    }
+}
+
 /// 获取执行状态
 #[utoipa::path(
     get,
@@ -612,6 +1014,415 @@ pub async fn get_execution_status(
     }
 }
 
+/// 获取执行时间线
+///
+/// 由状态转换事件重建，执行结束后仍可查询；携带 `at` 参数时同时返回该时间点的状态。
+#[utoipa::path(
+    get,
+    path = "/api/v1/workflows/executions/{execution_id}/timeline",
+    responses(
+        (status = 200, description = "获取时间线成功", body = ExecutionTimeline),
+        (status = 404, description = "没有该执行的事件"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("execution_id" = Uuid, Path, description = "执行 ID"),
+        ExecutionTimelineQuery
+    ),
+    tag = "workflows"
+)]
+pub async fn get_execution_timeline(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    query: web::Query<ExecutionTimelineQuery>,
+) -> ActixResult<HttpResponse> {
+    let execution_id = path.into_inner();
+    debug!("获取执行时间线: execution_id={}, tenant_id={}", execution_id, tenant_info.id);
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let timeline = ExecutionEventService::new(db_manager.get_connection().clone())
+        .with_artifacts(ExecutionArtifactService::from_app_config(
+            db_manager.get_connection().clone(),
+            ConfigLoader::get(),
+        ))
+        .timeline(tenant_info.id, ExecutionType::Workflow, execution_id, query.at)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(timeline))
+}
+
+/// 分页获取工作流执行步骤
+///
+/// 转存的产物保留指针，超过预览大小的步骤数据只返回预览，`expandable` 为真的步骤可按序号展开；
+/// 将响应中的 `next_cursor` 作为 `cursor` 传入获取下一页。
+#[utoipa::path(
+    get,
+    path = "/api/v1/workflows/executions/{execution_id}/steps",
+    responses(
+        (status = 200, description = "获取执行步骤成功", body = ExecutionTracePage),
+        (status = 400, description = "分页游标无效"),
+        (status = 404, description = "没有该执行的事件"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("execution_id" = Uuid, Path, description = "执行 ID"),
+        ExecutionStepsQuery
+    ),
+    tag = "workflows"
+)]
+pub async fn get_execution_steps(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    query: web::Query<ExecutionStepsQuery>,
+) -> ActixResult<HttpResponse> {
+    let execution_id = path.into_inner();
+    debug!("分页获取执行步骤: execution_id={}, tenant_id={}", execution_id, tenant_info.id);
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let page = ExecutionEventService::new(db_manager.get_connection().clone())
+        .steps(
+            tenant_info.id,
+            ExecutionType::Workflow,
+            execution_id,
+            &query,
+            TracePagePolicy::from_config(&ConfigLoader::get().execution_artifacts),
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
+
+/// 展开工作流执行步骤
+///
+/// 返回步骤的完整数据并取回转存的产物；指定 `pointer` 时只返回其中一部分。
+#[utoipa::path(
+    get,
+    path = "/api/v1/workflows/executions/{execution_id}/steps/{sequence}",
+    responses(
+        (status = 200, description = "获取执行步骤成功", body = ExecutionTraceStep),
+        (status = 400, description = "JSON Pointer 无效"),
+        (status = 404, description = "步骤或指定部分不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("execution_id" = Uuid, Path, description = "执行 ID"),
+        ("sequence" = i32, Path, description = "步骤序号"),
+        ExecutionStepQuery
+    ),
+    tag = "workflows"
+)]
+pub async fn get_execution_step(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<(Uuid, i32)>,
+    query: web::Query<ExecutionStepQuery>,
+) -> ActixResult<HttpResponse> {
+    let (execution_id, sequence) = path.into_inner();
+    debug!("展开执行步骤: execution_id={}, sequence={}, tenant_id={}", execution_id, sequence, tenant_info.id);
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let step = ExecutionEventService::new(db_manager.get_connection().clone())
+        .with_artifacts(ExecutionArtifactService::from_app_config(
+            db_manager.get_connection().clone(),
+            ConfigLoader::get(),
+        ))
+        .step(tenant_info.id, ExecutionType::Workflow, execution_id, sequence, query.pointer.as_deref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(step))
+}
+
+/// 重放工作流执行
+///
+/// 使用原始执行记录的参数（可通过 `input_patch` 局部修改）在工作流当前发布的定义上重新执行，
+/// 新执行关联到原始执行，可通过重放历史对比输出与耗时。
+#[utoipa::path(
+    post,
+    path = "/api/v1/workflows/executions/{execution_id}/replay",
+    request_body = ReplayExecutionRequest,
+    responses(
+        (status = 200, description = "重放执行启动成功", body = ExecuteWorkflowResponse),
+        (status = 400, description = "修改后的输入无效或执行参数校验失败"),
+        (status = 404, description = "执行记录不存在"),
+        (status = 409, description = "并发组名额已满且策略为拒绝"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("execution_id" = Uuid, Path, description = "原始执行 ID")
+    ),
+    tag = "workflows"
+)]
+pub async fn replay_execution(
+    workflow_engine: web::Data<Arc<WorkflowEngine>>,
+    workflow_executor: web::Data<Arc<WorkflowExecutor>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    request: Option<web::Json<ReplayExecutionRequest>>,
+) -> ActixResult<HttpResponse> {
+    let execution_id = path.into_inner();
+    info!("重放工作流执行: execution_id={}, tenant_id={}", execution_id, tenant_info.id);
+
+    let records = execution_records().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let patch = request.and_then(|request| request.into_inner().input_patch);
+    let (original, input) = records
+        .replay_input(tenant_info.id, ExecutionType::Workflow, execution_id, patch.as_ref())
+        .await?;
+    let execute_request: ExecuteWorkflowRequest = serde_json::from_value(input)
+        .map_err(|e| AiStudioError::validation("input_patch", format!("修改后的输入无效: {}", e)))?;
+
+    start_workflow_execution(
+        &workflow_engine,
+        &workflow_executor,
+        &tenant_info,
+        original.target_id,
+        execute_request,
+        Some(execution_id),
+    ).await
+}
+
+/// 获取工作流执行的重放历史
+///
+/// 返回前同步仍在运行的执行状态。
+#[utoipa::path(
+    get,
+    path = "/api/v1/workflows/executions/{execution_id}/replays",
+    responses(
+        (status = 200, description = "获取重放历史成功", body = ReplayHistory),
+        (status = 404, description = "执行记录不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("execution_id" = Uuid, Path, description = "原始执行 ID")
+    ),
+    tag = "workflows"
+)]
+pub async fn get_execution_replays(
+    workflow_executor: web::Data<Arc<WorkflowExecutor>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let execution_id = path.into_inner();
+    debug!("获取工作流重放历史: execution_id={}, tenant_id={}", execution_id, tenant_info.id);
+
+    let records = execution_records().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let history = records.history(tenant_info.id, ExecutionType::Workflow, execution_id).await?;
+
+    let unfinished = std::iter::once(&history.original)
+        .chain(history.replays.iter().map(|comparison| &comparison.replay))
+        .filter(|record| !record.is_finished())
+        .map(|record| record.execution_id)
+        .collect::<Vec<_>>();
+    if unfinished.is_empty() {
+        return Ok(HttpResponse::Ok().json(history));
+    }
+    for id in unfinished {
+        if let Ok(execution) = workflow_executor.get_execution_status(id).await {
+            records.sync_status(id, &execution.status, execution.completed_at).await?;
+        }
+    }
+    let history = records.history(tenant_info.id, ExecutionType::Workflow, execution_id).await?;
+
+    Ok(HttpResponse::Ok().json(history))
+}
+
+/// 提交工作流执行反馈
+///
+/// 负面反馈计入灰度发布的负面反馈率，超过阈值时自动回滚。
+#[utoipa::path(
+    post,
+    path = "/api/v1/workflows/executions/{execution_id}/feedback",
+    request_body = ExecutionFeedbackRequest,
+    responses(
+        (status = 200, description = "反馈已记录", body = ExecutionRecordSummary),
+        (status = 404, description = "执行记录不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("execution_id" = Uuid, Path, description = "执行 ID")
+    ),
+    tag = "workflows"
+)]
+pub async fn submit_execution_feedback(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    request: web::Json<ExecutionFeedbackRequest>,
+) -> ActixResult<HttpResponse> {
+    let execution_id = path.into_inner();
+    debug!("提交工作流执行反馈: execution_id={}, tenant_id={}", execution_id, tenant_info.id);
+
+    let records = execution_records().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let record = records
+        .record_feedback(tenant_info.id, ExecutionType::Workflow, execution_id, None, request.positive)
+        .await?;
+    if let (Some(canary), Some(release_id)) = (canary_releases(), record.canary_release_id) {
+        canary.evaluate(release_id).await?;
+    }
+
+    Ok(HttpResponse::Ok().json(ExecutionRecordSummary::from(record)))
+}
+
+/// 创建工作流灰度发布
+///
+/// 调用方仍执行入口工作流，按流量百分比将部分执行分流到已发布的灰度版本工作流。
+#[utoipa::path(
+    post,
+    path = "/api/v1/workflows/{workflow_id}/canary",
+    request_body = CreateCanaryRequest,
+    responses(
+        (status = 201, description = "灰度发布已创建", body = CanaryReleaseInfo),
+        (status = 400, description = "请求参数错误或灰度版本未发布"),
+        (status = 404, description = "灰度版本工作流不存在"),
+        (status = 409, description = "已有进行中的灰度发布"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("workflow_id" = Uuid, Path, description = "入口工作流 ID")
+    ),
+    tag = "workflows"
+)]
+pub async fn create_workflow_canary(
+    workflow_engine: web::Data<Arc<WorkflowEngine>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<Uuid>,
+    request: web::Json<CreateCanaryRequest>,
+) -> ActixResult<HttpResponse> {
+    let workflow_id = path.into_inner();
+    info!("创建工作流灰度发布: workflow_id={}, tenant_id={}", workflow_id, tenant_info.id);
+
+    let candidate = workflow_engine.get_workflow(request.canary_id).await?;
+    if candidate.tenant_id != tenant_info.id {
+        return Err(AiStudioError::not_found("工作流").into());
+    }
+    if candidate.status != WorkflowStatus::Published {
+        return Err(AiStudioError::validation("canary_id", "灰度版本工作流须已发布").into());
+    }
+    let canary = canary_releases().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let release = canary
+        .create(tenant_info.id, ExecutionType::Workflow, workflow_id, request.into_inner(), user.user_id)
+        .await?;
+
+    Ok(HttpResponse::Created().json(release))
+}
+
+/// 获取工作流最近一次灰度发布及各版本的执行统计
+///
+/// 返回前同步灰度中仍在运行的执行状态并检查是否需要自动回滚。
+#[utoipa::path(
+    get,
+    path = "/api/v1/workflows/{workflow_id}/canary",
+    responses(
+        (status = 200, description = "获取灰度发布成功", body = CanaryReleaseInfo),
+        (status = 404, description = "没有灰度发布"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("workflow_id" = Uuid, Path, description = "入口工作流 ID")
+    ),
+    tag = "workflows"
+)]
+pub async fn get_workflow_canary(
+    workflow_executor: web::Data<Arc<WorkflowExecutor>>,
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let workflow_id = path.into_inner();
+    let canary = canary_releases().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let release = canary.info(tenant_info.id, ExecutionType::Workflow, workflow_id).await?;
+    if release.status != CanaryStatus::Active {
+        return Ok(HttpResponse::Ok().json(release));
+    }
+
+    let records = execution_records().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    for id in canary.unfinished_executions(release.release_id).await? {
+        if let Ok(execution) = workflow_executor.get_execution_status(id).await {
+            records.sync_status(id, &execution.status, execution.completed_at).await?;
+        }
+    }
+    canary.evaluate(release.release_id).await?;
+    let release = canary.info(tenant_info.id, ExecutionType::Workflow, workflow_id).await?;
+
+    Ok(HttpResponse::Ok().json(release))
+}
+
+/// 全量工作流灰度版本
+///
+/// 全量后入口工作流的全部执行由灰度版本处理。
+#[utoipa::path(
+    post,
+    path = "/api/v1/workflows/{workflow_id}/canary/promote",
+    request_body = EndCanaryRequest,
+    responses(
+        (status = 200, description = "已全量", body = CanaryReleaseInfo),
+        (status = 404, description = "没有进行中的灰度发布"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("workflow_id" = Uuid, Path, description = "入口工作流 ID")
+    ),
+    tag = "workflows"
+)]
+pub async fn promote_workflow_canary(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    request: Option<web::Json<EndCanaryRequest>>,
+) -> ActixResult<HttpResponse> {
+    let workflow_id = path.into_inner();
+    info!("全量工作流灰度版本: workflow_id={}, tenant_id={}", workflow_id, tenant_info.id);
+
+    let canary = canary_releases().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let reason = request.and_then(|request| request.into_inner().reason);
+    let release = canary.promote(tenant_info.id, ExecutionType::Workflow, workflow_id, reason).await?;
+
+    Ok(HttpResponse::Ok().json(release))
+}
+
+/// 回滚工作流灰度发布
+#[utoipa::path(
+    post,
+    path = "/api/v1/workflows/{workflow_id}/canary/rollback",
+    request_body = EndCanaryRequest,
+    responses(
+        (status = 200, description = "已回滚", body = CanaryReleaseInfo),
+        (status = 404, description = "没有进行中的灰度发布"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    params(
+        ("workflow_id" = Uuid, Path, description = "入口工作流 ID")
+    ),
+    tag = "workflows"
+)]
+pub async fn rollback_workflow_canary(
+    tenant_info: web::ReqData<TenantInfo>,
+    path: web::Path<Uuid>,
+    request: Option<web::Json<EndCanaryRequest>>,
+) -> ActixResult<HttpResponse> {
+    let workflow_id = path.into_inner();
+    info!("回滚工作流灰度发布: workflow_id={}, tenant_id={}", workflow_id, tenant_info.id);
+
+    let canary = canary_releases().ok_or_else(|| AiStudioError::internal("获取数据库连接失败"))?;
+    let reason = request.and_then(|request| request.into_inner().reason);
+    let release = canary.rollback(tenant_info.id, ExecutionType::Workflow, workflow_id, reason).await?;
+
+    Ok(HttpResponse::Ok().json(release))
+}
+
+/// 执行记录服务，数据库未初始化时不记录
+fn execution_records() -> Option<ExecutionRecordService> {
+    DatabaseManager::get()
+        .ok()
+        .map(|db_manager| ExecutionRecordService::new(db_manager.get_connection().clone()))
+}
+
+/// 灰度发布服务，数据库未初始化时不分流
+fn canary_releases() -> Option<CanaryService> {
+    DatabaseManager::get()
+        .ok()
+        .map(|db_manager| CanaryService::new(db_manager.get_connection().clone()))
+}
+
 /// 取消执行
 #[utoipa::path(
     post,
@@ -751,19 +1562,24 @@ pub async fn get_execution_history(
         (status = 200, description = "工作流发布成功"),
         (status = 400, description = "工作流验证失败"),
         (status = 404, description = "工作流不存在"),
+        (status = 409, description = "工作流已被修改或正被其他用户编辑", body = WorkflowConflict),
         (status = 500, description = "服务器内部错误")
     ),
     params(
-        ("workflow_id" = Uuid, Path, description = "工作流 ID")
+        ("workflow_id" = Uuid, Path, description = "工作流 ID"),
+        ("If-Match" = Option<String>, Header, description = "读取工作流时返回的 ETag，携带时校验修订号")
     ),
     tag = "workflows"
 )]
 pub async fn publish_workflow(
     workflow_engine: web::Data<Arc<WorkflowEngine>>,
     tenant_info: web::ReqData<TenantInfo>,
+    user: web::ReqData<AuthenticatedUser>,
+    req: HttpRequest,
     path: web::Path<Uuid>,
 ) -> ActixResult<HttpResponse> {
     let workflow_id = path.into_inner();
+    let expected_revision = expected_revision(&req)?;
     debug!("发布工作流: workflow_id={}, tenant_id={}", workflow_id, tenant_info.context.tenant_id);
     
     // 获取工作流
@@ -808,24 +1624,133 @@ pub async fn publish_workflow(
     
     // 更新工作流状态
     workflow.status = WorkflowStatus::Published;
-    workflow.updated_at = chrono::Utc::now();
     
-    // 重新注册工作流
-    if let Err(e) = workflow_engine.register_workflow(workflow).await {
-        error!("工作流注册失败: {}", e);
-        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": "工作流发布失败",
-            "message": e.to_string()
-        })));
-    }
+    // 保存工作流，携带 If-Match 时校验修订号
+    let workflow = match workflow_engine.update_workflow(workflow, expected_revision, user.user_id).await {
+        Ok(workflow) => workflow,
+        Err(e) => return Ok(edit_error_response(workflow_id, e)),
+    };
     
     info!("工作流发布成功: workflow_id={}", workflow_id);
+    route_cache::invalidate(RouteCacheScope::Workflows, Some(tenant_info.id)).await;
+    SystemEventBus::global().publish(
+        SystemEvent::new(
+            event_types::WORKFLOW_PUBLISHED,
+            Some(tenant_info.id),
+            serde_json::json!({
+                "workflow_id": workflow_id,
+                "name": workflow.name,
+                "version": workflow.version,
+                "revision": workflow.revision,
+            }),
+        )
+        .with_actor(Some(user.user_id)),
+    );
     
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "message": "工作流发布成功",
-        "workflow_id": workflow_id,
-        "published_at": chrono::Utc::now()
-    })))
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, workflow.etag()))
+        .json(serde_json::json!({
+            "message": "工作流发布成功",
+            "workflow_id": workflow_id,
+            "revision": workflow.revision,
+            "published_at": workflow.updated_at
+        })))
+}
+
+/// 下载工作流生成的文档
+///
+/// 通过文档生成步骤输出的签名链接访问，无需登录。
+#[utoipa::path(
+    get,
+    path = "/api/v1/downloads/workflow-documents/{document_id}",
+    params(
+        ("document_id" = Uuid, Path, description = "生成文档 ID"),
+        ("token" = String, Query, description = "下载令牌")
+    ),
+    responses(
+        (status = 200, description = "生成的文档"),
+        (status = 403, description = "下载链接无效或已过期"),
+        (status = 404, description = "文档不存在")
+    ),
+    tag = "workflows"
+)]
+pub async fn download_generated_document(
+    path: web::Path<Uuid>,
+    query: web::Query<DocumentDownloadQuery>,
+) -> ActixResult<HttpResponse> {
+    let document_id = path.into_inner();
+    let config = ConfigLoader::get();
+    let claims = verify_document_token(&config.security.jwt_secret, document_id, &query.token)?;
+
+    let file_path = generated_document_path(
+        std::path::Path::new(&config.storage.path),
+        claims.tid,
+        document_id,
+        claims.fmt,
+    );
+    let content = tokio::fs::read(&file_path)
+        .await
+        .map_err(|_| AiStudioError::not_found("生成文档"))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(claims.fmt.content_type())
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename*=UTF-8''{}", urlencoding_file_name(&claims.name)),
+        ))
+        .body(content))
+}
+
+/// 按 RFC 5987 编码下载文件名
+fn urlencoding_file_name(name: &str) -> String {
+    url::form_urlencoded::byte_serialize(name.as_bytes())
+        .collect::<String>()
+        .replace('+', "%20")
+}
+
+/// 接收外部系统回调
+///
+/// 供等待回调步骤的外部系统调用，无需登录；请求需携带 `X-Aionix-Signature: sha256=<签名>`，
+/// 签名为使用步骤输出的签名密钥对原始请求体计算的 HMAC-SHA256。
+#[utoipa::path(
+    post,
+    path = "/api/v1/workflow-callbacks/{callback_id}",
+    params(
+        ("callback_id" = Uuid, Path, description = "回调 ID")
+    ),
+    request_body = Object,
+    responses(
+        (status = 200, description = "回调已接收", body = CallbackReceipt),
+        (status = 400, description = "回调载荷无效"),
+        (status = 403, description = "回调签名无效"),
+        (status = 404, description = "回调不存在"),
+        (status = 409, description = "回调已处理或已超时")
+    ),
+    tag = "workflows"
+)]
+pub async fn receive_workflow_callback(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Bytes,
+) -> ActixResult<HttpResponse> {
+    let callback_id = path.into_inner();
+    if body.len() > MAX_CALLBACK_PAYLOAD_BYTES {
+        return Err(AiStudioError::validation("body", "回调载荷过大").into());
+    }
+    let signature = req.headers()
+        .get(CALLBACK_SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    let db_manager = DatabaseManager::get()
+        .map_err(|e| AiStudioError::internal(format!("获取数据库连接失败: {}", e)))?;
+    let receipt = WorkflowCallbackService::new(
+        db_manager.get_connection().clone(),
+        ConfigLoader::get().security.jwt_secret.clone(),
+    )
+        .receive(callback_id, &body, signature)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(receipt))
 }
 
 /// 配置工作流 API 路由
@@ -833,14 +1758,35 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/workflows")
             .route("", web::post().to(create_workflow))
-            .route("", web::get().to(list_workflows))
-            .route("/{workflow_id}", web::get().to(get_workflow))
+            .route("", web::get().to(list_workflows).wrap(
+                RouteCacheMiddleware::new(RouteCacheScope::Workflows).ttl_secs(30).vary_by_tenant().vary_by_query(),
+            ))
+            .route("/{workflow_id}", web::get().to(get_workflow).wrap(
+                RouteCacheMiddleware::new(RouteCacheScope::Workflows).ttl_secs(60).vary_by_tenant(),
+            ))
+            .route("/{workflow_id}", web::put().to(update_workflow))
+            .route("/{workflow_id}/lock", web::post().to(acquire_edit_lock))
+            .route("/{workflow_id}/lock", web::get().to(get_edit_lock))
+            .route("/{workflow_id}/lock", web::delete().to(release_edit_lock))
+            .route("/{workflow_id}/input-schema", web::get().to(get_workflow_input_schema))
             .route("/{workflow_id}/execute", web::post().to(execute_workflow))
             .route("/{workflow_id}/publish", web::post().to(publish_workflow))
             .route("/{workflow_id}/executions", web::get().to(get_execution_history))
+            .route("/{workflow_id}/canary", web::post().to(create_workflow_canary))
+            .route("/{workflow_id}/canary", web::get().to(get_workflow_canary))
+            .route("/{workflow_id}/canary/promote", web::post().to(promote_workflow_canary))
+            .route("/{workflow_id}/canary/rollback", web::post().to(rollback_workflow_canary))
             .route("/executions/{execution_id}", web::get().to(get_execution_status))
+            .route("/executions/{execution_id}/timeline", web::get().to(get_execution_timeline))
+            .route("/executions/{execution_id}/steps", web::get().to(get_execution_steps))
+            .route("/executions/{execution_id}/steps/{sequence}", web::get().to(get_execution_step))
             .route("/executions/{execution_id}/cancel", web::post().to(cancel_execution))
+            .route("/executions/{execution_id}/replay", web::post().to(replay_execution))
+            .route("/executions/{execution_id}/replays", web::get().to(get_execution_replays))
+            .route("/executions/{execution_id}/feedback", web::post().to(submit_execution_feedback))
     );
+    cfg.route("/downloads/workflow-documents/{document_id}", web::get().to(download_generated_document));
+    cfg.route("/workflow-callbacks/{callback_id}", web::post().to(receive_workflow_callback));
 }
 
 #[cfg(test)]
@@ -13,7 +13,7 @@ use sea_orm::EntityTrait;
 
 use crate::api::middleware::{
     auth::{AuthenticatedUser, ApiKeyInfo},
-    tenant::{TenantInfo, TenantIdentificationStrategy},
+    tenant::{load_tenant, TenantInfo, TenantIdentificationStrategy},
 };
 use crate::errors::AiStudioError;
 use crate::api::responses::ErrorResponse;
@@ -425,16 +425,7 @@ async fn check_ip_whitelist(context: &AccessControlContext) -> Result<(), AiStud
 /// 检查配额限制
 async fn check_quota_limits(context: &AccessControlContext) -> Result<(), AiStudioError> {
     if let Some(tenant) = &context.tenant {
-        use crate::db::entities::prelude::*;
-        use crate::db::DatabaseManager;
-
-        let db_manager = DatabaseManager::get()?;
-        let db = db_manager.get_connection();
-
-        let tenant_model = Tenant::find_by_id(tenant.id)
-            .one(db)
-            .await?
-            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+        let tenant_model = load_tenant(tenant.id).await?;
 
         // 检查 API 调用配额
         if context.request_path.starts_with("/api/") {
@@ -17,6 +17,9 @@ use chrono::{DateTime, Utc};
 use crate::db::DatabaseManager;
 use crate::db::entities::{tenant, user};
 use crate::errors::AiStudioError;
+use crate::api::middleware::tenant::load_tenant;
+use crate::services::admin_elevation::load_active_elevation;
+use crate::services::cache::{self, CacheNamespace, CachedUserAccess};
 use crate::api::responses::ErrorResponse;
 use sea_orm::{EntityTrait, ActiveModelTrait};
 
@@ -53,6 +56,8 @@ pub struct AuthenticatedUser {
     pub permissions: Vec<String>,
     pub is_admin: bool,
     pub authenticated_at: DateTime<Utc>,
+    /// 生效中的临时提权 ID，提权期间角色按提权角色处理
+    pub elevation_id: Option<Uuid>,
 }
 
 /// API 密钥信息
@@ -346,7 +351,7 @@ async fn verify_jwt_token(token: &str, secret_key: &str) -> Result<Authenticated
     // 验证用户是否仍然存在且活跃
     verify_user_status(user_id, tenant_id).await?;
 
-    Ok(AuthenticatedUser {
+    let mut user = AuthenticatedUser {
         user_id,
         tenant_id,
         username: claims.username,
@@ -354,7 +359,19 @@ async fn verify_jwt_token(token: &str, secret_key: &str) -> Result<Authenticated
         permissions: claims.permissions,
         is_admin: claims.is_admin,
         authenticated_at: Utc::now(),
-    })
+        elevation_id: None,
+    };
+
+    // 生效中的临时提权覆盖令牌中的角色，到期后自动恢复
+    if !user.is_admin {
+        if let Some(elevation) = load_active_elevation(user_id).await? {
+            user.role = elevation.role;
+            user.is_admin = true;
+            user.elevation_id = Some(elevation.id);
+        }
+    }
+
+    Ok(user)
 }
 
 /// 验证 API 密钥（带 IP 检查）
@@ -455,6 +472,11 @@ async fn check_api_key_rate_limit(api_key_info: &ApiKeyInfo) -> Result<(), AiStu
     let permissions = key_model.get_permissions()
         .map_err(|e| AiStudioError::internal(format!("解析 API 密钥权限失败: {}", e)))?;
     
+    // 用量异常自动限流期间拒绝请求
+    if permissions.throttled_until.is_some_and(|until| until > Utc::now()) {
+        return Err(AiStudioError::too_many_requests("API 密钥因用量异常被临时限流".to_string()));
+    }
+    
     if let Some(rate_limit) = permissions.rate_limit {
         // 这里应该实现基于 Redis 的速率限制检查
         // 为了简化，这里只做基本的检查
@@ -480,22 +502,18 @@ async fn check_api_key_rate_limit(api_key_info: &ApiKeyInfo) -> Result<(), AiStu
 /// 验证用户状态
 #[instrument]
 async fn verify_user_status(user_id: Uuid, tenant_id: Uuid) -> Result<(), AiStudioError> {
-    let db_manager = DatabaseManager::get()?;
-    let db = db_manager.get_connection();
-
     // 检查租户状态
-    let tenant = tenant::Entity::find_by_id(tenant_id)
-        .one(db)
-        .await?
-        .ok_or_else(|| AiStudioError::unauthorized("租户不存在".to_string()))?;
+    let tenant = load_tenant(tenant_id).await.map_err(|e| match e {
+        AiStudioError::NotFound { .. } => AiStudioError::unauthorized("租户不存在".to_string()),
+        e => e,
+    })?;
 
     if tenant.status != tenant::TenantStatus::Active {
         return Err(AiStudioError::forbidden("租户已被暂停或停用".to_string()));
     }
 
     // 检查用户状态
-    let user = user::Entity::find_by_id(user_id)
-        .one(db)
+    let user = load_user_access(user_id)
         .await?
         .ok_or_else(|| AiStudioError::unauthorized("用户不存在".to_string()))?;
 
@@ -511,6 +529,18 @@ async fn verify_user_status(user_id: Uuid, tenant_id: Uuid) -> Result<(), AiStud
     Ok(())
 }
 
+/// 经缓存加载用户访问信息
+pub async fn load_user_access(user_id: Uuid) -> Result<Option<CachedUserAccess>, AiStudioError> {
+    cache::cached(CacheNamespace::User, &user_id.to_string(), move || async move {
+        let db_manager = DatabaseManager::get()?;
+        let user = user::Entity::find_by_id(user_id)
+            .one(db_manager.get_connection())
+            .await?;
+        Ok::<_, AiStudioError>(user.map(CachedUserAccess::from))
+    })
+    .await
+}
+
 /// JWT 工具函数
 pub struct JwtUtils;
 
@@ -0,0 +1,121 @@
+// 条款接受中间件
+// 租户当前条款版本要求接受时，拒绝尚未接受的用户访问除条款、认证与健康检查以外的 API
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage, HttpResponse,
+    body::BoxBody,
+};
+use futures::future::LocalBoxFuture;
+use std::future::{ready as std_ready, Ready as StdReady};
+use std::rc::Rc;
+use tracing::{debug, warn};
+
+use crate::api::middleware::auth::AuthenticatedUser;
+use crate::api::responses::ErrorResponse;
+use crate::db::DatabaseManager;
+use crate::services::consent::ConsentService;
+
+/// 未接受条款时仍可访问的路径前缀
+const EXEMPT_PATH_PREFIXES: [&str; 6] = [
+    "/api/v1/consent",
+    "/api/v1/auth",
+    "/api/v1/health",
+    "/api/v1/version",
+    "/api/v1/openapi.json",
+    "/api/v1/docs",
+];
+
+/// 条款接受检查中间件
+pub struct TermsAcceptanceMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for TermsAcceptanceMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static + actix_web::body::MessageBody,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = TermsAcceptanceMiddlewareService<S>;
+    type InitError = ();
+    type Future = StdReady<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        std_ready(Ok(TermsAcceptanceMiddlewareService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct TermsAcceptanceMiddlewareService<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for TermsAcceptanceMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static + actix_web::body::MessageBody,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let user = req.extensions().get::<AuthenticatedUser>().cloned();
+            if let Some(user) = user.filter(|_| !is_exempt(req.path())) {
+                match DatabaseManager::get() {
+                    Ok(db_manager) => {
+                        let blocked = ConsentService::new(db_manager.get_connection().clone())
+                            .requires_acceptance(user.tenant_id, user.user_id)
+                            .await;
+                        match blocked {
+                            Ok(true) => {
+                                debug!("用户尚未接受当前条款: user={}, path={}", user.user_id, req.path());
+                                let response = HttpResponse::Forbidden()
+                                    .json(ErrorResponse::detailed_error::<()>(
+                                        "TERMS_NOT_ACCEPTED".to_string(),
+                                        "请先接受当前条款后再继续使用".to_string(),
+                                        None,
+                                        None,
+                                    ));
+                                return Ok(req.into_response(response));
+                            }
+                            Ok(false) => {}
+                            // 检查失败时不阻断请求，避免条款表异常导致全部 API 不可用
+                            Err(e) => warn!("检查条款接受状态失败: user={}, error={}", user.user_id, e),
+                        }
+                    }
+                    Err(e) => warn!("检查条款接受状态失败: {}", e),
+                }
+            }
+
+            let res = service.call(req).await?;
+            Ok(res.map_into_boxed_body())
+        })
+    }
+}
+
+/// 路径是否无需接受条款即可访问
+fn is_exempt(path: &str) -> bool {
+    EXEMPT_PATH_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exempt_paths() {
+        assert!(is_exempt("/api/v1/consent/terms/current"));
+        assert!(is_exempt("/api/v1/auth/login"));
+        assert!(!is_exempt("/api/v1/knowledge-bases"));
+        assert!(!is_exempt("/api/v1/qa/ask"));
+    }
+}
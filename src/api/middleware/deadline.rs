@@ -0,0 +1,132 @@
+// 请求截止时间中间件
+// 为每个请求设置截止时间并在其作用域内执行后续处理，客户端可通过请求头申请更短或更长（受上限约束）的预算
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error,
+};
+use futures::future::LocalBoxFuture;
+use std::future::{ready as std_ready, Ready as StdReady};
+use std::rc::Rc;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::config::ServerConfig;
+use crate::errors::AiStudioError;
+use crate::services::deadline::{self, Deadline};
+
+/// 客户端申请请求预算的请求头（毫秒）
+pub const REQUEST_TIMEOUT_HEADER: &str = "X-Request-Timeout-Ms";
+
+/// 截止时间到达后留给处理器返回部分结果的宽限时间
+const DEADLINE_GRACE: Duration = Duration::from_millis(500);
+
+/// 请求截止时间中间件
+#[derive(Debug, Clone, Copy)]
+pub struct DeadlineMiddleware {
+    default_timeout: Duration,
+    max_timeout: Duration,
+}
+
+impl DeadlineMiddleware {
+    pub fn new(default_timeout: Duration, max_timeout: Duration) -> Self {
+        Self {
+            default_timeout: default_timeout.min(max_timeout),
+            max_timeout,
+        }
+    }
+
+    /// 按服务器配置创建
+    pub fn from_config(config: &ServerConfig) -> Self {
+        Self::new(
+            Duration::from_millis(config.request_timeout_ms),
+            Duration::from_millis(config.max_request_timeout_ms),
+        )
+    }
+
+    /// 解析请求头中的预算，缺失或无效时使用默认值，超过上限时截断
+    fn request_timeout(&self, header: Option<&str>) -> Duration {
+        header
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .filter(|ms| *ms > 0)
+            .map(|ms| Duration::from_millis(ms).min(self.max_timeout))
+            .unwrap_or(self.default_timeout)
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for DeadlineMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = DeadlineMiddlewareService<S>;
+    type InitError = ();
+    type Future = StdReady<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        std_ready(Ok(DeadlineMiddlewareService {
+            service: Rc::new(service),
+            config: *self,
+        }))
+    }
+}
+
+pub struct DeadlineMiddlewareService<S> {
+    service: Rc<S>,
+    config: DeadlineMiddleware,
+}
+
+impl<S, B> Service<ServiceRequest> for DeadlineMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let timeout = self.config.request_timeout(
+            req.headers()
+                .get(REQUEST_TIMEOUT_HEADER)
+                .and_then(|value| value.to_str().ok()),
+        );
+        let deadline = Deadline::after(timeout);
+        let path = req.path().to_string();
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            // 处理器应在截止时间内自行返回部分结果或超时错误，宽限期后强制中止
+            let handled = deadline::scope(deadline, service.call(req));
+            match tokio::time::timeout_at(deadline.at() + DEADLINE_GRACE, handled).await {
+                Ok(result) => result,
+                Err(_) => {
+                    warn!("请求超出截止时间被中止: path={}, timeout_ms={}", path, timeout.as_millis());
+                    Err(AiStudioError::timeout(format!("request {}", path)).into())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_timeout_header_is_capped() {
+        let middleware = DeadlineMiddleware::new(Duration::from_secs(120), Duration::from_secs(600));
+
+        assert_eq!(middleware.request_timeout(None), Duration::from_secs(120));
+        assert_eq!(middleware.request_timeout(Some("1500")), Duration::from_millis(1500));
+        assert_eq!(middleware.request_timeout(Some("9000000")), Duration::from_secs(600));
+        assert_eq!(middleware.request_timeout(Some("0")), Duration::from_secs(120));
+        assert_eq!(middleware.request_timeout(Some("soon")), Duration::from_secs(120));
+    }
+}
@@ -0,0 +1,97 @@
+// 提权审计中间件
+// 临时提权生效期间的每个请求都写入审计日志，并标记对应的提权 ID
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage,
+    body::BoxBody,
+};
+use futures::future::LocalBoxFuture;
+use std::future::{ready as std_ready, Ready as StdReady};
+use std::rc::Rc;
+use tracing::warn;
+
+use crate::api::middleware::auth::AuthenticatedUser;
+use crate::db::DatabaseManager;
+use crate::services::audit_log::{AuditLogService, NewAuditLog};
+
+/// 提权期间请求的审计操作类型
+const ELEVATED_REQUEST_ACTION: &str = "api.request";
+
+/// 提权审计中间件
+pub struct ElevationAuditMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for ElevationAuditMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static + actix_web::body::MessageBody,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = ElevationAuditMiddlewareService<S>;
+    type InitError = ();
+    type Future = StdReady<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        std_ready(Ok(ElevationAuditMiddlewareService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct ElevationAuditMiddlewareService<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ElevationAuditMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static + actix_web::body::MessageBody,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+
+            // 认证信息可能由内层中间件写入，响应返回后再读取
+            let user = res.request().extensions().get::<AuthenticatedUser>().cloned();
+            if let Some((user, elevation_id)) = user.and_then(|u| u.elevation_id.map(|id| (u, id))) {
+                let request = res.request();
+                let entry = NewAuditLog {
+                    tenant_id: user.tenant_id,
+                    user_id: Some(user.user_id),
+                    action: ELEVATED_REQUEST_ACTION.to_string(),
+                    method: Some(request.method().to_string()),
+                    path: Some(request.path().to_string()),
+                    status_code: Some(res.status().as_u16()),
+                    resource_type: None,
+                    resource_id: None,
+                    elevation_id: Some(elevation_id),
+                    ip_address: request.connection_info().peer_addr().map(|s| s.to_string()),
+                    details: serde_json::json!({ "query": request.query_string() }),
+                };
+
+                // 审计写入失败不影响已完成的请求
+                match DatabaseManager::get() {
+                    Ok(db_manager) => {
+                        if let Err(e) = AuditLogService::new(db_manager.get_connection().clone()).record(entry).await {
+                            warn!("记录提权审计日志失败: user={}, error={}", user.user_id, e);
+                        }
+                    }
+                    Err(e) => warn!("记录提权审计日志失败: {}", e),
+                }
+            }
+
+            Ok(res.map_into_boxed_body())
+        })
+    }
+}
@@ -0,0 +1,166 @@
+// 导入类接口限流中间件
+// 对使用 API 密钥调用的文档导入、重新处理与重建索引接口按令牌桶限流，独立于通用限流
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, HeaderMap, HeaderName, HeaderValue},
+    Error, HttpMessage, HttpResponse,
+};
+use futures::future::LocalBoxFuture;
+use std::future::{ready as std_ready, Ready as StdReady};
+use std::rc::Rc;
+use tracing::debug;
+
+use crate::api::middleware::auth::ApiKeyInfo;
+use crate::api::responses::ErrorResponse;
+use crate::config::{ConfigLoader, IngestThrottleConfig};
+use crate::services::ingest_throttle::{IngestThrottle, IngestThrottleExceeded, TokenBucketDecision};
+
+/// 导入类接口限流中间件
+#[derive(Clone)]
+pub struct IngestThrottleMiddleware {
+    /// 每次请求消耗的令牌数，为空时使用配置中的批量请求消耗
+    cost: Option<u32>,
+}
+
+impl IngestThrottleMiddleware {
+    /// 单个文档请求，每次消耗 1 个令牌
+    pub fn single() -> Self {
+        Self { cost: Some(1) }
+    }
+
+    /// 批量请求，每次消耗配置中的批量请求令牌数
+    pub fn batch() -> Self {
+        Self { cost: None }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for IngestThrottleMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<actix_web::body::EitherBody<B>>;
+    type Error = Error;
+    type Transform = IngestThrottleMiddlewareService<S>;
+    type InitError = ();
+    type Future = StdReady<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let config = ConfigLoader::get().ingest_throttle.clone();
+        std_ready(Ok(IngestThrottleMiddlewareService {
+            service: Rc::new(service),
+            cost: self.cost.unwrap_or(config.batch_cost),
+            config,
+        }))
+    }
+}
+
+pub struct IngestThrottleMiddlewareService<S> {
+    service: Rc<S>,
+    cost: u32,
+    config: IngestThrottleConfig,
+}
+
+impl<S, B> Service<ServiceRequest> for IngestThrottleMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<actix_web::body::EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let cost = self.cost;
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            // 仅限制 API 密钥调用，登录用户的请求由通用限流处理
+            let api_key_id = req.extensions().get::<ApiKeyInfo>().map(|key| key.key_id);
+            let Some(api_key_id) = api_key_id.filter(|_| config.enabled) else {
+                return service.call(req).await.map(|res| res.map_into_left_body());
+            };
+
+            let decision = IngestThrottle::global().acquire(api_key_id, cost, &config);
+            if !decision.allowed {
+                debug!("导入类接口限流: api_key={}, path={}, 需要令牌={}", api_key_id, req.path(), decision.cost);
+                let exceeded = IngestThrottleExceeded::new(api_key_id, &decision, &config);
+                let message = format!(
+                    "导入请求频率超限: 每个 API 密钥最多突发 {} 个令牌，每秒补充 {} 个，请在 {} 秒后重试",
+                    exceeded.burst, exceeded.refill_per_second, exceeded.retry_after_seconds
+                );
+                let mut response = HttpResponse::TooManyRequests()
+                    .json(ErrorResponse::detailed_error::<()>(
+                        "INGEST_RATE_LIMIT_EXCEEDED".to_string(),
+                        message,
+                        serde_json::to_value(&exceeded).ok(),
+                        None,
+                    ));
+                insert_ingest_headers(response.headers_mut(), &decision, &config);
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+
+            let mut res = service.call(req).await?;
+            insert_ingest_headers(res.headers_mut(), &decision, &config);
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+/// 写入导入限流响应头
+///
+/// 使用独立的 `X-Ingest-RateLimit-*` 前缀，避免与通用限流的 `RateLimit-*` 相互覆盖；
+/// `Reset` 为令牌补满的秒数，超限时附带 `Retry-After`。
+fn insert_ingest_headers(headers: &mut HeaderMap, decision: &TokenBucketDecision, config: &IngestThrottleConfig) {
+    let values = [
+        ("x-ingest-ratelimit-limit", decision.capacity.to_string()),
+        ("x-ingest-ratelimit-remaining", (decision.remaining.floor() as u64).to_string()),
+        ("x-ingest-ratelimit-reset", decision.reset_after_seconds.to_string()),
+        ("x-ingest-ratelimit-cost", decision.cost.to_string()),
+        (
+            "x-ingest-ratelimit-policy",
+            format!("token-bucket;burst={};refill={}/s", decision.capacity, config.refill_per_second),
+        ),
+    ];
+    for (name, value) in values {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(HeaderName::from_static(name), value);
+        }
+    }
+    if !decision.allowed {
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(decision.retry_after_seconds.max(1)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ingest_headers() {
+        let config = IngestThrottleConfig::default();
+        let decision = TokenBucketDecision {
+            allowed: false,
+            capacity: 20,
+            remaining: 3.6,
+            cost: 10,
+            retry_after_seconds: 7,
+            reset_after_seconds: 17,
+        };
+
+        let mut headers = HeaderMap::new();
+        insert_ingest_headers(&mut headers, &decision, &config);
+        assert_eq!(headers.get("x-ingest-ratelimit-limit").unwrap(), "20");
+        assert_eq!(headers.get("x-ingest-ratelimit-remaining").unwrap(), "3");
+        assert_eq!(headers.get("x-ingest-ratelimit-reset").unwrap(), "17");
+        assert_eq!(headers.get("x-ingest-ratelimit-policy").unwrap(), "token-bucket;burst=20;refill=1/s");
+        assert_eq!(headers.get(header::RETRY_AFTER).unwrap(), "7");
+        assert!(headers.get("ratelimit-limit").is_none());
+    }
+}
@@ -3,8 +3,14 @@
 
 pub mod access_control;
 pub mod auth;
+pub mod consent;
+pub mod deadline;
+pub mod elevation_audit;
+pub mod ingest_throttle;
 pub mod quota;
 pub mod rate_limit;
+pub mod route_cache;
+pub mod stack;
 pub mod tenant;
 
 // 明确导出需要的结构体
@@ -3,6 +3,7 @@
 
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, HeaderMap, HeaderName, HeaderValue},
     Error, HttpMessage, HttpResponse,
 };
 use futures::future::LocalBoxFuture;
@@ -14,7 +15,8 @@ use tracing::{error, instrument, debug};
 use crate::api::middleware::tenant::TenantInfo;
 use crate::api::middleware::auth::{AuthenticatedUser, ApiKeyInfo};
 use crate::services::rate_limit::{
-    RateLimitService, RateLimitPolicy, RateLimitKeyType, RateLimitPolicies, RateLimitConfig
+    RateLimitService, RateLimitPolicy, RateLimitKeyType, RateLimitPolicies, RateLimitConfig,
+    RateLimitExceeded, RateLimitResult, RateLimitScope,
 };
 use crate::errors::AiStudioError;
 use crate::api::responses::ErrorResponse;
@@ -60,6 +62,11 @@ impl RateLimitMiddleware {
         Self::new(policies, RateLimitKeyType::Ip("".to_string()))
     }
 
+    /// 创建基于接口的限流中间件
+    pub fn endpoint(policies: Vec<RateLimitPolicy>) -> Self {
+        Self::new(policies, RateLimitKeyType::Endpoint(String::new()))
+    }
+
     /// 创建全局限流中间件
     pub fn global(policies: Vec<RateLimitPolicy>) -> Self {
         Self::new(policies, RateLimitKeyType::Global)
@@ -80,6 +87,11 @@ impl RateLimitMiddleware {
         Self::ip(RateLimitPolicies::ip_policies())
     }
 
+    /// 创建默认的接口限流中间件
+    pub fn default_endpoint() -> Self {
+        Self::endpoint(RateLimitPolicies::endpoint_policies())
+    }
+
     /// 创建默认的全局限流中间件
     pub fn default_global() -> Self {
         Self::global(RateLimitPolicies::global_policies())
@@ -157,43 +169,15 @@ where
             };
 
             // 检查限流
+            let mut constrained = None;
             match check_rate_limits(&actual_key_type, &policies).await {
                 Ok(results) => {
                     // 检查是否有任何策略被触发
-                    for result in &results {
-                        if !result.allowed {
-                            let mut response = HttpResponse::TooManyRequests()
-                                .json(ErrorResponse::detailed_error::<()>(
-                                    "RATE_LIMIT_EXCEEDED".to_string(),
-                                    format!("请求频率超限: {}", result.max_requests),
-                                    None,
-                                    None,
-                                ));
-
-                            // 添加限流相关的响应头
-                            let headers = response.headers_mut();
-                            headers.insert(
-                                actix_web::http::header::HeaderName::from_static("x-ratelimit-limit"),
-                                actix_web::http::header::HeaderValue::from_str(&result.max_requests.to_string()).unwrap(),
-                            );
-                            headers.insert(
-                                actix_web::http::header::HeaderName::from_static("x-ratelimit-remaining"),
-                                actix_web::http::header::HeaderValue::from_str(&result.remaining_requests.to_string()).unwrap(),
-                            );
-                            headers.insert(
-                                actix_web::http::header::HeaderName::from_static("x-ratelimit-reset"),
-                                actix_web::http::header::HeaderValue::from_str(&result.reset_time.timestamp().to_string()).unwrap(),
-                            );
-                            if let Some(retry_after) = result.retry_after {
-                                headers.insert(
-                                    actix_web::http::header::RETRY_AFTER,
-                                    actix_web::http::header::HeaderValue::from_str(&retry_after.to_string()).unwrap(),
-                                );
-                            }
-
-                            return Ok(req.into_response(response).map_into_right_body());
-                        }
+                    if let Some(result) = results.iter().find(|result| !result.allowed) {
+                        let response = rate_limited_response(&req, actual_key_type.scope(), result);
+                        return Ok(req.into_response(response).map_into_right_body());
                     }
+                    constrained = most_constrained(results.iter()).cloned();
                 }
                 Err(e) => {
                     error!("限流检查失败: {}", e);
@@ -201,8 +185,11 @@ where
                 }
             }
 
-            let fut = service.call(req);
-            fut.await.map(|res| res.map_into_left_body())
+            let mut res = service.call(req).await?;
+            if let Some(result) = &constrained {
+                insert_rate_limit_headers(res.headers_mut(), result);
+            }
+            Ok(res.map_into_left_body())
         })
     }
 }
@@ -227,6 +214,7 @@ impl CompositeRateLimitMiddleware {
             RateLimitMiddleware::default_ip(),
             RateLimitMiddleware::default_tenant(),
             RateLimitMiddleware::default_api_key(),
+            RateLimitMiddleware::default_endpoint(),
         ])
     }
 
@@ -281,7 +269,8 @@ where
         let service = self.service.clone();
 
         Box::pin(async move {
-            // 依次检查所有限流策略
+            // 依次检查所有限流策略，放行时返回剩余额度最少的策略状态
+            let mut checked: Vec<RateLimitResult> = Vec::new();
             for middleware in &middlewares {
                 if !middleware.enabled {
                     continue;
@@ -294,18 +283,11 @@ where
 
                 match check_rate_limits(&actual_key_type, &middleware.policies).await {
                     Ok(results) => {
-                        for result in &results {
-                            if !result.allowed {
-                                let response = HttpResponse::TooManyRequests()
-                                    .json(ErrorResponse::detailed_error::<()>(
-                                        "RATE_LIMIT_EXCEEDED".to_string(),
-                                        format!("请求频率超限: {}", result.max_requests),
-                                        None,
-                                        None,
-                                    ));
-                                return Ok(req.into_response(response).map_into_right_body());
-                            }
+                        if let Some(result) = results.iter().find(|result| !result.allowed) {
+                            let response = rate_limited_response(&req, actual_key_type.scope(), result);
+                            return Ok(req.into_response(response).map_into_right_body());
                         }
+                        checked.extend(results);
                     }
                     Err(e) => {
                         error!("限流检查失败: {}", e);
@@ -313,14 +295,72 @@ where
                 }
             }
 
-            let fut = service.call(req);
-            fut.await.map(|res| res.map_into_left_body())
+            let mut res = service.call(req).await?;
+            if let Some(result) = most_constrained(checked.iter()) {
+                insert_rate_limit_headers(res.headers_mut(), result);
+            }
+            Ok(res.map_into_left_body())
         })
     }
 }
 
 // 辅助函数
 
+/// 剩余额度最少的限流结果，剩余相同时取重置更晚的
+fn most_constrained<'a>(results: impl Iterator<Item = &'a RateLimitResult>) -> Option<&'a RateLimitResult> {
+    results.min_by(|a, b| {
+        a.remaining_requests
+            .cmp(&b.remaining_requests)
+            .then_with(|| b.reset_time.cmp(&a.reset_time))
+    })
+}
+
+/// 写入限流响应头
+///
+/// 写入 IETF 草案中的 `RateLimit-Limit`、`RateLimit-Remaining`、`RateLimit-Reset`（距重置的秒数）
+/// 和 `RateLimit-Policy`，同时保留旧客户端使用的 `X-RateLimit-*`（重置时间为 Unix 时间戳）。
+fn insert_rate_limit_headers(headers: &mut HeaderMap, result: &RateLimitResult) {
+    let reset_after = result.reset_after_seconds();
+    let values = [
+        ("ratelimit-limit", result.max_requests.to_string()),
+        ("ratelimit-remaining", result.remaining_requests.to_string()),
+        ("ratelimit-reset", reset_after.to_string()),
+        ("ratelimit-policy", format!("{};w={}", result.max_requests, result.window_seconds)),
+        ("x-ratelimit-limit", result.max_requests.to_string()),
+        ("x-ratelimit-remaining", result.remaining_requests.to_string()),
+        ("x-ratelimit-reset", result.reset_time.timestamp().to_string()),
+    ];
+    for (name, value) in values {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(HeaderName::from_static(name), value);
+        }
+    }
+    if !result.allowed {
+        let retry_after = result.retry_after.unwrap_or(reset_after);
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    }
+}
+
+/// 构建 429 响应，错误详情中说明触发的限流维度和策略
+fn rate_limited_response(req: &ServiceRequest, scope: RateLimitScope, result: &RateLimitResult) -> HttpResponse {
+    let tenant_id = req.extensions().get::<TenantInfo>().map(|tenant| tenant.id);
+    let exceeded = RateLimitExceeded::new(scope, tenant_id, result);
+    let message = format!(
+        "请求频率超限: 策略 {} 每 {} 秒最多 {} 次，请在 {} 秒后重试",
+        exceeded.policy, exceeded.window_seconds, exceeded.limit, exceeded.retry_after_seconds
+    );
+
+    let mut response = HttpResponse::TooManyRequests()
+        .json(ErrorResponse::detailed_error::<()>(
+            "RATE_LIMIT_EXCEEDED".to_string(),
+            message,
+            serde_json::to_value(&exceeded).ok(),
+            None,
+        ));
+    insert_rate_limit_headers(response.headers_mut(), result);
+    response
+}
+
 /// 构建实际的键类型
 fn build_actual_key_type(
     key_type: &RateLimitKeyType,
@@ -356,6 +396,14 @@ fn build_actual_key_type(
                 .to_string();
             Ok(RateLimitKeyType::Ip(ip))
         }
+        RateLimitKeyType::Endpoint(_) => {
+            let route = req.match_pattern().unwrap_or_else(|| req.path().to_string());
+            let endpoint = format!("{} {}", req.method(), route);
+            match req.extensions().get::<TenantInfo>() {
+                Some(tenant_info) => Ok(RateLimitKeyType::Endpoint(format!("{}:{}", tenant_info.id, endpoint))),
+                None => Ok(RateLimitKeyType::Endpoint(endpoint)),
+            }
+        }
         RateLimitKeyType::Global => Ok(RateLimitKeyType::Global),
         RateLimitKeyType::Custom(key) => Ok(RateLimitKeyType::Custom(key.clone())),
     }
@@ -409,6 +457,11 @@ impl RateLimitMiddlewareConfig {
         RateLimitMiddleware::default_ip()
     }
 
+    /// 获取接口限流中间件
+    pub fn endpoint_only() -> RateLimitMiddleware {
+        RateLimitMiddleware::default_endpoint()
+    }
+
     /// 获取全局限流中间件
     pub fn global_only() -> RateLimitMiddleware {
         RateLimitMiddleware::default_global()
@@ -421,4 +474,49 @@ impl RateLimitMiddlewareConfig {
     ) -> RateLimitMiddleware {
         RateLimitMiddleware::new(policies, key_type)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn result(policy: &str, max_requests: u64, remaining_requests: u64, allowed: bool) -> RateLimitResult {
+        RateLimitResult {
+            allowed,
+            current_requests: max_requests - remaining_requests,
+            max_requests,
+            remaining_requests,
+            reset_time: Utc::now() + Duration::seconds(30),
+            retry_after: (!allowed).then_some(30),
+            policy: policy.to_string(),
+            window_seconds: 60,
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_headers() {
+        let results = [result("tenant_per_minute", 1000, 400, true), result("api_key_per_minute", 60, 5, true)];
+        let constrained = most_constrained(results.iter()).unwrap();
+        assert_eq!(constrained.policy, "api_key_per_minute");
+
+        let mut headers = HeaderMap::new();
+        insert_rate_limit_headers(&mut headers, constrained);
+        assert_eq!(headers.get("ratelimit-limit").unwrap(), "60");
+        assert_eq!(headers.get("ratelimit-remaining").unwrap(), "5");
+        assert_eq!(headers.get("ratelimit-policy").unwrap(), "60;w=60");
+        let reset: u64 = headers.get("ratelimit-reset").unwrap().to_str().unwrap().parse().unwrap();
+        assert!(reset <= 30);
+        assert!(headers.get(header::RETRY_AFTER).is_none());
+
+        let exhausted = result("endpoint_per_minute", 300, 0, false);
+        insert_rate_limit_headers(&mut headers, &exhausted);
+        assert_eq!(headers.get(header::RETRY_AFTER).unwrap(), "30");
+
+        let exceeded = RateLimitExceeded::new(RateLimitScope::Endpoint, None, &exhausted);
+        let body = serde_json::to_value(&exceeded).unwrap();
+        assert_eq!(body["scope"], "endpoint");
+        assert_eq!(body["policy"], "endpoint_per_minute");
+        assert_eq!(body["retry_after_seconds"], 30);
+    }
+}
@@ -0,0 +1,174 @@
+// 路由级缓存中间件
+// 在路由上声明缓存时间与区分维度，缓存 GET 请求的成功响应，写接口通过 route_cache::invalidate 使其失效
+
+use actix_web::{
+    body::{to_bytes, BoxBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header, Method, StatusCode},
+    Error, HttpMessage, HttpResponse,
+};
+use futures::future::LocalBoxFuture;
+use std::future::{ready as std_ready, Ready as StdReady};
+use std::rc::Rc;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::api::middleware::auth::AuthenticatedUser;
+use crate::api::middleware::tenant::TenantInfo;
+use crate::services::cache::{CacheNamespace, CacheService};
+use crate::services::route_cache::{
+    current_generation, response_key, CachedRouteResponse, RouteCacheRequest, RouteCacheScope, RouteCacheVary,
+};
+
+/// 默认缓存时间（秒）
+const DEFAULT_TTL_SECS: u64 = 60;
+
+/// 路由级缓存中间件
+///
+/// ```ignore
+/// .route("", web::get().to(list_knowledge_bases).wrap(
+///     RouteCacheMiddleware::new(RouteCacheScope::KnowledgeBases).ttl_secs(30).vary_by_tenant().vary_by_query(),
+/// ))
+/// ```
+///
+/// 未启用缓存服务时直接放行；请求带 `Cache-Control: no-cache` 时跳过缓存读取。
+#[derive(Clone)]
+pub struct RouteCacheMiddleware {
+    scope: RouteCacheScope,
+    ttl: Duration,
+    vary: RouteCacheVary,
+}
+
+impl RouteCacheMiddleware {
+    pub fn new(scope: RouteCacheScope) -> Self {
+        Self { scope, ttl: Duration::from_secs(DEFAULT_TTL_SECS), vary: RouteCacheVary::default() }
+    }
+
+    /// 缓存时间（秒）
+    pub fn ttl_secs(mut self, secs: u64) -> Self {
+        self.ttl = Duration::from_secs(secs);
+        self
+    }
+
+    /// 按租户区分缓存项
+    pub fn vary_by_tenant(mut self) -> Self {
+        self.vary.tenant = true;
+        self
+    }
+
+    /// 按用户区分缓存项
+    pub fn vary_by_user(mut self) -> Self {
+        self.vary.user = true;
+        self
+    }
+
+    /// 按查询参数区分缓存项
+    pub fn vary_by_query(mut self) -> Self {
+        self.vary.query = true;
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RouteCacheMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = RouteCacheMiddlewareService<S>;
+    type InitError = ();
+    type Future = StdReady<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        std_ready(Ok(RouteCacheMiddlewareService { service: Rc::new(service), directive: self.clone() }))
+    }
+}
+
+pub struct RouteCacheMiddlewareService<S> {
+    service: Rc<S>,
+    directive: RouteCacheMiddleware,
+}
+
+impl<S, B> Service<ServiceRequest> for RouteCacheMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let directive = self.directive.clone();
+
+        Box::pin(async move {
+            let cache = CacheService::global().filter(|_| req.method() == Method::GET);
+            let Some(cache) = cache else {
+                return service.call(req).await.map(|res| res.map_into_boxed_body());
+            };
+
+            let tenant_id = req.extensions().get::<TenantInfo>().map(|tenant| tenant.id);
+            let user_id = req.extensions().get::<AuthenticatedUser>().map(|user| user.user_id);
+            let generation_tenant = if directive.vary.tenant { tenant_id } else { None };
+            let generation = current_generation(&cache, directive.scope, generation_tenant).await;
+            let request = RouteCacheRequest { path: req.path(), query: req.query_string(), tenant_id, user_id };
+            let Some(key) = response_key(directive.scope, directive.vary, &generation, &request) else {
+                return service.call(req).await.map(|res| res.map_into_boxed_body());
+            };
+
+            let bypass = req
+                .headers()
+                .get(header::CACHE_CONTROL)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.contains("no-cache"));
+            if !bypass {
+                if let Some(cached) = cache.get::<CachedRouteResponse>(CacheNamespace::Route, &key).await {
+                    debug!("路由缓存命中: scope={}, path={}", directive.scope.as_str(), req.path());
+                    let mut response = HttpResponse::Ok();
+                    if let Some(content_type) = &cached.content_type {
+                        response.content_type(content_type.as_str());
+                    }
+                    if let Some(etag) = &cached.etag {
+                        response.insert_header((header::ETAG, etag.as_str()));
+                    }
+                    response.insert_header(("X-Cache", "HIT"));
+                    return Ok(req.into_response(response.body(cached.body)));
+                }
+            }
+
+            let res = service.call(req).await?;
+            if res.status() != StatusCode::OK {
+                return Ok(res.map_into_boxed_body());
+            }
+
+            let header_value = |name: header::HeaderName| {
+                res.headers().get(name).and_then(|value| value.to_str().ok()).map(str::to_string)
+            };
+            let content_type = header_value(header::CONTENT_TYPE);
+            let etag = header_value(header::ETAG);
+            let (http_req, http_res) = res.into_parts();
+            let (mut head, body) = http_res.into_parts();
+            let bytes = to_bytes(body).await.map_err(|e| {
+                let e: Box<dyn std::error::Error> = e.into();
+                actix_web::error::ErrorInternalServerError(e.to_string())
+            })?;
+
+            // 只缓存文本响应
+            if let Ok(body) = std::str::from_utf8(&bytes) {
+                let cached = CachedRouteResponse { content_type, etag, body: body.to_string() };
+                cache.put_with_ttl(CacheNamespace::Route, &key, &cached, directive.ttl).await;
+            }
+            head.headers_mut().insert(
+                header::HeaderName::from_static("x-cache"),
+                header::HeaderValue::from_static("MISS"),
+            );
+            Ok(ServiceResponse::new(http_req, head.set_body(bytes).map_into_boxed_body()))
+        })
+    }
+}
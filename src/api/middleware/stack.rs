@@ -0,0 +1,206 @@
+// 全局中间件栈
+// 按配置组装 CORS、压缩、限流、安全响应头、截止时间与请求追踪，main 与测试共用同一套装配逻辑
+
+use actix_cors::Cors;
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceFactory, ServiceRequest, ServiceResponse},
+    middleware::{Compress, Condition, DefaultHeaders},
+    App, Error,
+};
+
+use crate::api::middleware::deadline::DeadlineMiddleware;
+use crate::api::middleware::rate_limit::CompositeRateLimitMiddleware;
+use crate::config::{AppConfig, HttpMiddlewareConfig, RateLimitProfile};
+use crate::errors::ErrorHandlerMiddleware;
+
+/// 按配置装配的全局中间件栈
+#[derive(Debug, Clone)]
+pub struct MiddlewareStack {
+    http: HttpMiddlewareConfig,
+    environment: String,
+    cors_origins: Vec<String>,
+    deadline: DeadlineMiddleware,
+}
+
+impl MiddlewareStack {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            http: config.http.clone(),
+            environment: config.environment.name.clone(),
+            cors_origins: config.security.cors_origins.clone(),
+            deadline: DeadlineMiddleware::from_config(&config.server),
+        }
+    }
+
+    /// 当前环境是否启用 CORS
+    pub fn cors_enabled(&self) -> bool {
+        self.http.cors.enabled_in(&self.environment)
+    }
+
+    /// 启用的中间件名称，用于启动日志
+    pub fn enabled(&self) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        if self.http.tracing {
+            names.push("tracing");
+        }
+        if self.http.compression {
+            names.push("compression");
+        }
+        if self.cors_enabled() {
+            names.push("cors");
+        }
+        if self.http.security_headers.enabled {
+            names.push("security_headers");
+        }
+        names.push("deadline");
+        if self.http.rate_limit.enabled {
+            names.push(match self.http.rate_limit.profile {
+                RateLimitProfile::Standard => "rate_limit(standard)",
+                RateLimitProfile::Lightweight => "rate_limit(lightweight)",
+            });
+        }
+        names
+    }
+
+    /// 创建挂载了全局中间件的应用，路由由调用方继续注册
+    ///
+    /// 由内到外依次为限流、截止时间、安全响应头、CORS、压缩、错误处理与请求追踪，
+    /// 使预检请求不计入限流，错误响应同样带有 CORS 与安全响应头。
+    pub fn build_app(
+        &self,
+    ) -> App<
+        impl ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse<impl MessageBody + use<>>,
+            Error = Error,
+            InitError = (),
+        > + use<>,
+    > {
+        App::new()
+            .wrap(Condition::new(self.http.rate_limit.enabled, self.rate_limit()))
+            .wrap(self.deadline)
+            .wrap(Condition::new(self.http.security_headers.enabled, self.security_headers()))
+            .wrap(Condition::new(self.cors_enabled(), self.cors()))
+            .wrap(Condition::new(self.http.compression, Compress::default()))
+            .wrap(ErrorHandlerMiddleware)
+            .wrap(Condition::new(self.http.tracing, tracing_actix_web::TracingLogger::default()))
+    }
+
+    /// 全局层只能识别 IP 与全局配额，租户与 API 密钥维度在认证之后才有键值
+    fn rate_limit(&self) -> CompositeRateLimitMiddleware {
+        match self.http.rate_limit.profile {
+            RateLimitProfile::Standard => CompositeRateLimitMiddleware::standard(),
+            RateLimitProfile::Lightweight => CompositeRateLimitMiddleware::lightweight(),
+        }
+    }
+
+    /// `*` 表示允许任意来源，此时不允许携带凭据
+    fn cors(&self) -> Cors {
+        let cors = self.cors_origins.iter().fold(Cors::default(), |cors, origin| {
+            if origin == "*" {
+                cors.allow_any_origin()
+            } else {
+                cors.allowed_origin(origin)
+            }
+        });
+        let cors = cors
+            .allow_any_method()
+            .allow_any_header()
+            .max_age(self.http.cors.max_age_secs);
+
+        if self.http.cors.allow_credentials && !self.cors_origins.iter().any(|origin| origin == "*") {
+            cors.supports_credentials()
+        } else {
+            cors
+        }
+    }
+
+    fn security_headers(&self) -> DefaultHeaders {
+        let config = &self.http.security_headers;
+        let mut headers = DefaultHeaders::new()
+            .add(("X-Content-Type-Options", "nosniff"))
+            .add(("X-Frame-Options", config.frame_options.clone()))
+            .add(("Referrer-Policy", config.referrer_policy.clone()));
+        if config.hsts_max_age_secs > 0 {
+            headers = headers.add((
+                "Strict-Transport-Security",
+                format!("max-age={}; includeSubDomains", config.hsts_max_age_secs),
+            ));
+        }
+        if let Some(policy) = &config.content_security_policy {
+            headers = headers.add(("Content-Security-Policy", policy.clone()));
+        }
+        headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::header, test, web, HttpResponse};
+
+    fn stack(configure: impl FnOnce(&mut AppConfig)) -> MiddlewareStack {
+        let mut config = AppConfig::default();
+        configure(&mut config);
+        MiddlewareStack::from_config(&config)
+    }
+
+    #[actix_web::test]
+    async fn test_security_headers_follow_config() {
+        let app = test::init_service(
+            stack(|config| config.http.security_headers.hsts_max_age_secs = 31536000)
+                .build_app()
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let res = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        assert_eq!(res.headers().get("x-frame-options").unwrap(), "DENY");
+        assert_eq!(res.headers().get("x-content-type-options").unwrap(), "nosniff");
+        assert!(res.headers().contains_key("strict-transport-security"));
+
+        let app = test::init_service(
+            stack(|config| config.http.security_headers.enabled = false)
+                .build_app()
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let res = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        assert!(!res.headers().contains_key("x-frame-options"));
+    }
+
+    #[actix_web::test]
+    async fn test_cors_enabled_per_environment() {
+        let request = || {
+            test::TestRequest::get()
+                .uri("/")
+                .insert_header((header::ORIGIN, "https://app.example.com"))
+                .to_request()
+        };
+
+        let stack_for = |environment: &str| {
+            let environment = environment.to_string();
+            stack(move |config| {
+                config.environment.name = environment;
+                config.security.cors_origins = vec!["https://app.example.com".to_string()];
+                config.http.cors.environments = vec!["development".to_string()];
+            })
+        };
+
+        let development = stack_for("development");
+        assert!(development.cors_enabled());
+        let app = test::init_service(development.build_app().route("/", web::get().to(HttpResponse::Ok))).await;
+        let res = test::call_service(&app, request()).await;
+        assert_eq!(
+            res.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://app.example.com"
+        );
+
+        let production = stack_for("production");
+        assert!(!production.cors_enabled());
+        let app = test::init_service(production.build_app().route("/", web::get().to(HttpResponse::Ok))).await;
+        let res = test::call_service(&app, request()).await;
+        assert!(!res.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+}
@@ -16,6 +16,7 @@ use crate::db::DatabaseManager;
 use crate::db::entities::{tenant, prelude::*};
 use crate::db::migrations::tenant_filter::TenantContext;
 use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace, CacheService};
 use crate::api::responses::ErrorResponse;
 
 /// 租户识别策略
@@ -414,13 +415,7 @@ async fn identify_tenant_from_query_param(req: &ServiceRequest) -> Result<Option
 
 /// 根据 ID 获取租户信息
 async fn get_tenant_by_id(tenant_id: Uuid) -> Result<TenantInfo, AiStudioError> {
-    let db_manager = DatabaseManager::get()?;
-    let db = db_manager.get_connection();
-
-    let tenant = Tenant::find_by_id(tenant_id)
-        .one(db)
-        .await?
-        .ok_or_else(|| AiStudioError::not_found("租户"))?;
+    let tenant = load_tenant(tenant_id).await?;
 
     Ok(TenantInfo {
         id: tenant.id,
@@ -432,16 +427,43 @@ async fn get_tenant_by_id(tenant_id: Uuid) -> Result<TenantInfo, AiStudioError>
     })
 }
 
+/// 经缓存加载租户实体
+pub async fn load_tenant(tenant_id: Uuid) -> Result<tenant::Model, AiStudioError> {
+    cache::cached(CacheNamespace::Tenant, &tenant_id.to_string(), move || async move {
+        let db_manager = DatabaseManager::get()?;
+        Ok::<_, AiStudioError>(Tenant::find_by_id(tenant_id).one(db_manager.get_connection()).await?)
+    })
+    .await?
+    .ok_or_else(|| AiStudioError::not_found("租户"))
+}
+
 /// 根据标识符获取租户信息
+///
+/// 标识符只缓存到租户 ID 的映射，租户实体仍按 ID 缓存；标识符变更后旧映射会被识别并丢弃。
 pub async fn get_tenant_by_slug(slug: &str) -> Result<TenantInfo, AiStudioError> {
-    let db_manager = DatabaseManager::get()?;
-    let db = db_manager.get_connection();
-
-    let tenant = Tenant::find()
-        .filter(tenant::Column::Slug.eq(slug))
-        .one(db)
+    let load_id = move || async move {
+        let db_manager = DatabaseManager::get()?;
+        let tenant = Tenant::find()
+            .filter(tenant::Column::Slug.eq(slug))
+            .one(db_manager.get_connection())
+            .await?;
+        Ok::<_, AiStudioError>(tenant.map(|tenant| tenant.id))
+    };
+
+    let tenant_id: Uuid = cache::cached(CacheNamespace::TenantSlug, slug, load_id)
         .await?
         .ok_or_else(|| AiStudioError::not_found("租户"))?;
+    let tenant = match load_tenant(tenant_id).await {
+        Ok(tenant) if tenant.slug == slug => tenant,
+        _ => {
+            // 标识符已变更或租户已删除，丢弃旧映射后回源
+            if let Some(cache) = CacheService::global() {
+                cache.invalidate(CacheNamespace::TenantSlug, slug).await;
+            }
+            let tenant_id = load_id().await?.ok_or_else(|| AiStudioError::not_found("租户"))?;
+            load_tenant(tenant_id).await?
+        }
+    };
 
     Ok(TenantInfo {
         id: tenant.id,
@@ -471,13 +493,7 @@ fn extract_subdomain(host: &str) -> Option<String> {
 /// 检查租户配额限制
 #[instrument(skip(tenant_info, req))]
 async fn check_tenant_quota_limits(tenant_info: &TenantInfo, req: &ServiceRequest) -> Result<(), AiStudioError> {
-    let db_manager = DatabaseManager::get()?;
-    let db = db_manager.get_connection();
-    
-    let tenant = Tenant::find_by_id(tenant_info.id)
-        .one(db)
-        .await?
-        .ok_or_else(|| AiStudioError::not_found("租户"))?;
+    let tenant = load_tenant(tenant_info.id).await?;
     
     // 检查 API 调用配额
     let path = req.path();
@@ -170,6 +170,41 @@ pub struct DependencyHealth {
     pub error: Option<String>,
 }
 
+/// 精简健康检查响应
+///
+/// 供租户管理员查看，只包含整体与各依赖服务的状态，不含版本、错误信息、响应时间与系统指标。
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RedactedHealthResponse {
+    /// 服务状态
+    pub status: HealthStatus,
+    /// 检查时间
+    pub timestamp: DateTime<Utc>,
+    /// 依赖服务状态
+    pub dependencies: Vec<DependencyStatus>,
+}
+
+/// 依赖服务状态（精简）
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DependencyStatus {
+    /// 服务名称
+    pub name: String,
+    /// 服务状态
+    pub status: HealthStatus,
+}
+
+impl From<&HealthResponse> for RedactedHealthResponse {
+    fn from(response: &HealthResponse) -> Self {
+        Self {
+            status: response.status.clone(),
+            timestamp: response.timestamp,
+            dependencies: response.dependencies
+                .iter()
+                .map(|dependency| DependencyStatus { name: dependency.name.clone(), status: dependency.status.clone() })
+                .collect(),
+        }
+    }
+}
+
 /// 系统信息
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SystemInfo {
@@ -125,6 +125,17 @@ impl ApiError {
         }
     }
     
+    /// 创建禁止访问错误响应
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self {
+            code: "FORBIDDEN".to_string(),
+            message: message.into(),
+            details: None,
+            field: None,
+            help_url: None,
+        }
+    }
+    
     /// 创建冲突错误响应
     pub fn conflict(message: impl Into<String>) -> Self {
         Self {
@@ -565,4 +576,26 @@ impl<T: Serialize + std::fmt::Debug> ResponseError for ApiResponse<T> {
     fn error_response(&self) -> HttpResponse {
         HttpResponse::build(self.status_code()).json(self)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::models::{PaginatedResponse, PaginationInfo};
+    use aionix_common::api::{ApiEnvelope, Page};
+
+    #[test]
+    fn test_wire_format_matches_common_types() {
+        // 客户端 SDK 使用 aionix-common 中的传输类型解析响应，两者必须保持一致
+        let page = PaginatedResponse::new(vec![1u32, 2], PaginationInfo::new(1, 2, 5));
+        let json = serde_json::to_string(&ApiResponse::ok(page)).unwrap();
+        let envelope: ApiEnvelope<Page<u32>> = serde_json::from_str(&json).unwrap();
+        let page = envelope.into_result().unwrap().unwrap();
+        assert_eq!(page.data, vec![1, 2]);
+        assert_eq!(page.pagination.total_pages, 3);
+        assert!(page.pagination.has_next);
+
+        let json = serde_json::to_string(&ErrorResponse::error::<()>("NOT_FOUND".to_string(), "文档".to_string())).unwrap();
+        let envelope: ApiEnvelope<()> = serde_json::from_str(&json).unwrap();
+        assert_eq!(envelope.into_result().unwrap_err().code, "NOT_FOUND");
+    }
+}
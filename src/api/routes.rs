@@ -4,13 +4,15 @@
 use actix_web::{web, HttpResponse, Result as ActixResult};
 use utoipa::{OpenApi, ToSchema};
 
-use crate::api::handlers::{self, health, version, tenant, quota, rate_limit, monitoring, auth, knowledge_base, document, qa, agent, tool, workflow, plugin};
+use crate::api::handlers::{self, admin, health, version, tenant, quota, rate_limit, model_routing, few_shot, saved_search, legal_hold, consent, passkey, elevation, activity, scheduled_report, monitoring, auth, knowledge_base, document, document_comment, curated_answer, qa, agent, tool, workflow, plugin};
 use crate::api::models::*;
 // use crate::api::middleware::{
 //     RequestIdMiddleware, ApiVersionMiddleware, RequestLoggingMiddleware,
 //     SecurityHeadersMiddleware, ResponseTimeMiddleware, ContentTypeMiddleware,
 //     MiddlewareConfig,
 // };
+use crate::api::middleware::consent::TermsAcceptanceMiddleware;
+use crate::api::middleware::elevation_audit::ElevationAuditMiddleware;
 use crate::api::responses::HttpResponseBuilder;
 use crate::services::tenant::{TenantResponse, TenantStatsResponse, CreateTenantRequest, UpdateTenantRequest};
 use crate::services::auth::{LoginRequest, LoginResponse, RegisterRequest, RegisterResponse, RefreshTokenRequest, PasswordResetRequest, PasswordResetConfirmRequest, UserInfo};
@@ -44,6 +46,7 @@ use crate::services::tenant::TenantInfo;
     paths(
         // 健康检查
         health::health_check,
+        health::health_detailed,
         // 版本信息
         version::get_version,
         // 租户管理
@@ -55,10 +58,84 @@ use crate::services::tenant::TenantInfo;
         tenant::get_tenant_stats,
         tenant::suspend_tenant,
         tenant::activate_tenant,
+        tenant::import_tenant_users,
+        tenant::get_tenant_persona,
+        tenant::update_tenant_persona,
+        tenant::get_tenant_auth_policy,
+        tenant::update_tenant_auth_policy,
+        tenant::get_tenant_prompt_retention,
+        tenant::update_tenant_prompt_retention,
+        tenant::list_tenant_model_call_logs,
+        tenant::get_tenant_egress,
+        tenant::update_tenant_egress,
+        tenant::get_tenant_web_search,
+        tenant::update_tenant_web_search,
+        tenant::list_tenant_usage_anomalies,
+        tenant::list_tenant_secrets,
+        tenant::put_tenant_secret,
+        tenant::delete_tenant_secret,
+        tenant::list_tenant_encryption_keys,
+        tenant::rotate_tenant_encryption_key,
+        tenant::revoke_tenant_encryption_keys,
+        tenant::get_tenant_sandbox,
+        tenant::sync_tenant_sandbox,
+        tenant::promote_tenant_sandbox,
         // 配额管理
         quota::check_quota,
         quota::update_quota,
         quota::get_quota_usage,
+        quota::simulate_billing,
+        // 模型路由
+        model_routing::preview_model_route,
+        // 少样本示例
+        few_shot::create_few_shot_example,
+        few_shot::list_few_shot_examples,
+        few_shot::get_few_shot_example,
+        few_shot::update_few_shot_example,
+        few_shot::delete_few_shot_example,
+        few_shot::select_few_shot_examples,
+        // 保存的搜索
+        saved_search::create_saved_search,
+        saved_search::list_saved_searches,
+        saved_search::get_saved_search,
+        saved_search::update_saved_search,
+        saved_search::delete_saved_search,
+        legal_hold::place_legal_hold,
+        legal_hold::list_legal_holds,
+        legal_hold::release_legal_hold,
+        legal_hold::list_legal_hold_events,
+        consent::publish_terms,
+        consent::list_terms,
+        consent::get_consent_status,
+        consent::accept_terms,
+        consent::export_acceptances,
+        passkey::create_passkey_registration_options,
+        passkey::register_passkey,
+        passkey::list_passkeys,
+        passkey::rename_passkey,
+        passkey::delete_passkey,
+        elevation::request_elevation,
+        elevation::list_elevations,
+        elevation::get_elevation,
+        elevation::approve_elevation,
+        elevation::reject_elevation,
+        elevation::revoke_elevation,
+        elevation::list_elevation_audit_logs,
+        activity::list_tenant_activity,
+        curated_answer::get_answer_review_queue,
+        curated_answer::create_curated_answer,
+        curated_answer::list_curated_answers,
+        curated_answer::get_curated_answer,
+        curated_answer::update_curated_answer,
+        curated_answer::review_curated_answer,
+        curated_answer::delete_curated_answer,
+        scheduled_report::create_scheduled_report,
+        scheduled_report::list_scheduled_reports,
+        scheduled_report::get_scheduled_report,
+        scheduled_report::update_scheduled_report,
+        scheduled_report::delete_scheduled_report,
+        scheduled_report::run_scheduled_report,
+        scheduled_report::list_scheduled_report_runs,
         // 速率限制
         rate_limit::get_rate_limits,
         // rate_limit::update_rate_limit,
@@ -66,16 +143,32 @@ use crate::services::tenant::TenantInfo;
         rate_limit::check_rate_limit,
         // 监控
         monitoring::get_system_health,
+        monitoring::get_slo_report,
+        monitoring::get_embedding_delta_stats,
         monitoring::get_tenant_usage_stats,
+        // 平台管理
+        admin::get_platform_overview,
+        admin::get_tenant_overview,
+        admin::validate_config,
+        admin::get_replication_status,
+        admin::promote_region,
+        admin::get_cache_metrics,
+        admin::get_task_queue_metrics,
+        admin::apply_manifest,
         // 认证
         auth::login,
+        auth::create_passkey_login_options,
+        auth::login_with_passkey,
         auth::logout,
         auth::refresh_token,
         auth::register,
         auth::request_password_reset,
         auth::confirm_password_reset,
+        auth::accept_invitation,
         auth::get_current_user,
         auth::update_user_profile,
+        auth::get_user_preferences,
+        auth::update_user_preferences,
         // 知识库管理
         knowledge_base::create_knowledge_base,
         knowledge_base::list_knowledge_bases,
@@ -83,7 +176,37 @@ use crate::services::tenant::TenantInfo;
         knowledge_base::update_knowledge_base,
         knowledge_base::delete_knowledge_base,
         knowledge_base::get_knowledge_base_stats,
+        knowledge_base::archive_knowledge_base,
+        knowledge_base::restore_knowledge_base,
         knowledge_base::reindex_knowledge_base,
+        knowledge_base::import_corpus,
+        knowledge_base::create_kb_snapshot,
+        knowledge_base::list_kb_snapshots,
+        knowledge_base::get_kb_snapshot,
+        knowledge_base::delete_kb_snapshot,
+        knowledge_base::diff_kb_snapshots,
+        knowledge_base::get_stale_documents,
+        knowledge_base::get_source_health,
+        knowledge_base::detect_duplicate_documents,
+        knowledge_base::get_duplicate_report,
+        knowledge_base::resolve_duplicate_cluster,
+        knowledge_base::cluster_knowledge_base_topics,
+        knowledge_base::get_topic_clustering_job,
+        knowledge_base::get_knowledge_base_topics,
+        knowledge_base::migrate_vector_backend,
+        knowledge_base::get_vector_migration,
+        knowledge_base::create_embedding_export,
+        knowledge_base::get_embedding_export,
+        knowledge_base::download_embedding_export,
+        knowledge_base::list_datasets,
+        knowledge_base::query_dataset,
+        knowledge_base::get_pii_report,
+        knowledge_base::override_pii_chunk,
+        knowledge_base::create_faq_entry,
+        knowledge_base::list_faq_entries,
+        knowledge_base::get_faq_entry,
+        knowledge_base::update_faq_entry,
+        knowledge_base::delete_faq_entry,
         // 文档管理
         document::create_document,
         document::upload_document,
@@ -93,6 +216,18 @@ use crate::services::tenant::TenantInfo;
         document::delete_document,
         document::get_document_stats,
         document::reprocess_document,
+        document::renew_document,
+        document::archive_documents,
+        document::restore_documents,
+        document::get_document_clearance,
+        document::update_document_clearance,
+        document::get_similar_documents,
+        document_comment::list_document_comments,
+        document_comment::create_document_comment,
+        document_comment::update_document_comment,
+        document_comment::delete_document_comment,
+        document_comment::resolve_document_comment,
+        document_comment::reopen_document_comment,
         // 批量文档操作
         document::batch_document_operation,
         document::batch_import_documents,
@@ -104,10 +239,31 @@ use crate::services::tenant::TenantInfo;
         qa::get_session_history,
         qa::submit_feedback,
         qa::get_suggestions,
+        qa::get_related_questions,
+        qa::create_transcript_export,
+        qa::get_transcript_export,
+        qa::download_transcript_export,
+        qa::review_answer,
+        qa::build_finetune_dataset,
+        qa::get_finetune_dataset,
         // Agent 管理
         agent::create_agent,
         agent::execute_task,
         agent::get_agent_status,
+        agent::get_agent_timeline,
+        agent::get_agent_steps,
+        agent::get_agent_step,
+        agent::stream_agent_events,
+        agent::get_execution_memory,
+        agent::replay_agent_execution,
+        agent::get_agent_replays,
+        agent::submit_agent_feedback,
+        agent::create_agent_canary,
+        agent::get_agent_canary,
+        agent::promote_agent_canary,
+        agent::rollback_agent_canary,
+        agent::export_agent_definition,
+        agent::import_agent_definition,
         agent::stop_agent,
         agent::list_agents,
         agent::cleanup_agents,
@@ -128,19 +284,42 @@ use crate::services::tenant::TenantInfo;
         plugin::call_plugin,
         plugin::control_plugin,
         plugin::update_plugin_config,
+        plugin::get_tenant_plugin_config,
+        plugin::update_tenant_plugin_config,
         plugin::search_plugins,
         plugin::get_plugin_statistics,
         plugin::get_plugin_logs,
+        plugin::get_plugin_metrics,
+        plugin::list_trusted_keys,
+        plugin::add_trusted_key,
+        plugin::revoke_trusted_key,
         plugin::cleanup_plugin_data,
         // 工作流管理
         workflow::create_workflow,
         workflow::execute_workflow,
         workflow::list_workflows,
         workflow::get_workflow,
+        workflow::get_workflow_input_schema,
+        workflow::update_workflow,
+        workflow::acquire_edit_lock,
+        workflow::get_edit_lock,
+        workflow::release_edit_lock,
         workflow::get_execution_status,
+        workflow::get_execution_timeline,
+        workflow::get_execution_steps,
+        workflow::get_execution_step,
+        workflow::replay_execution,
+        workflow::get_execution_replays,
+        workflow::submit_execution_feedback,
+        workflow::create_workflow_canary,
+        workflow::get_workflow_canary,
+        workflow::promote_workflow_canary,
+        workflow::rollback_workflow_canary,
         workflow::cancel_execution,
         workflow::get_execution_history,
         workflow::publish_workflow,
+        workflow::download_generated_document,
+        workflow::receive_workflow_callback,
     ),
     components(
         schemas(
@@ -158,6 +337,13 @@ use crate::services::tenant::TenantInfo;
             RefreshTokenRequest,
             PasswordResetRequest,
             PasswordResetConfirmRequest,
+            crate::services::auth::AcceptInvitationRequest,
+            crate::services::user_preferences::UpdateUserPreferencesRequest,
+            crate::db::entities::user::UserPreferences,
+            crate::db::entities::user::AnswerVerbosity,
+            crate::db::entities::user::CitationStyle,
+            crate::db::entities::user::NotificationSettings,
+            crate::db::entities::user::UiSettings,
             UserInfo,
             TenantInfo,
             
@@ -166,18 +352,162 @@ use crate::services::tenant::TenantInfo;
             UpdateTenantRequest,
             TenantResponse,
             TenantStatsResponse,
+            crate::services::user_import::UserImportReport,
+            crate::services::user_import::InvitationSummary,
+            crate::services::user_import::ImportRowError,
             
             // 配额相关
             QuotaCheckResult,
             QuotaUpdateRequest,
             QuotaStatsResponse,
+            crate::services::billing::BillingSimulationRequest,
+            crate::services::billing::BillingSimulationResponse,
+            crate::services::billing::ProjectedUsage,
+            crate::services::billing::CostEstimate,
+            crate::services::billing::CostLineItem,
+            crate::db::entities::tenant::TenantPlan,
+            crate::db::entities::tenant::TenantModelRouting,
+            crate::db::entities::tenant::TenantModelPolicy,
+            crate::db::entities::tenant::TenantPersona,
+            crate::db::entities::tenant::TenantAuthPolicy,
+            crate::db::entities::tenant::TenantPromptRetention,
+            crate::db::entities::tenant::PromptRedaction,
+            crate::services::prompt_retention::ModelCallLog,
+            crate::db::entities::tenant::TenantEgressPolicy,
+            crate::config::TlsMinVersion,
+            crate::db::entities::tenant::TenantWebSearch,
+            crate::db::entities::tenant::WebSearchProvider,
+            crate::services::usage_anomaly::UsageAnomalyResponse,
+            crate::db::entities::usage_anomaly::AnomalyMetric,
+            crate::services::tenant_secret::TenantSecretResponse,
+            tenant::PutTenantSecretRequest,
+            crate::services::tenant_encryption::KeySource,
+            crate::services::tenant_encryption::KeyStatus,
+            crate::services::tenant_encryption::RotateTenantKeyRequest,
+            crate::services::tenant_encryption::TenantKeyInfo,
+            crate::services::tenant_encryption::RevokeTenantKeysResponse,
+            crate::services::sandbox::SandboxSelection,
+            crate::services::sandbox::SandboxInfo,
+            crate::services::sandbox::SandboxSync,
+            crate::ai::model_router::RoutingTask,
+            crate::ai::model_router::RouteReason,
+            crate::ai::model_router::RouteDecision,
+            model_routing::RoutePreviewQuery,
+            
+            // 少样本示例相关
+            crate::services::few_shot::FewShotTarget,
+            crate::services::few_shot::CreateFewShotExampleRequest,
+            crate::services::few_shot::UpdateFewShotExampleRequest,
+            crate::services::few_shot::FewShotExampleResponse,
+            few_shot::FewShotExampleQuery,
+            few_shot::SelectFewShotExamplesRequest,
+            
+            // 保存的搜索相关
+            crate::db::entities::saved_search::SearchMatchMode,
+            crate::db::entities::saved_search::SavedSearchFilters,
+            crate::services::saved_search::CreateSavedSearchRequest,
+            crate::services::saved_search::UpdateSavedSearchRequest,
+            crate::services::saved_search::SavedSearchResponse,
+            saved_search::SavedSearchQuery,
+            crate::services::legal_hold::LegalHoldTarget,
+            crate::services::legal_hold::PlaceLegalHoldRequest,
+            crate::services::legal_hold::ReleaseLegalHoldRequest,
+            crate::services::legal_hold::LegalHoldQuery,
+            crate::services::legal_hold::LegalHoldResponse,
+            crate::services::legal_hold::LegalHoldEvent,
+            crate::services::consent::PublishTermsRequest,
+            crate::services::consent::TermsVersionResponse,
+            crate::services::consent::ConsentStatus,
+            crate::services::consent::TermsAcceptanceRecord,
+            crate::services::consent::AcceptanceExportFormat,
+            crate::services::consent::ExportAcceptancesQuery,
+            crate::services::passkey::PasskeyCeremonyOptions,
+            crate::services::passkey::RegisterPasskeyRequest,
+            crate::services::passkey::RegistrationCredential,
+            crate::services::passkey::AttestationResponse,
+            crate::services::passkey::PasskeyLoginOptionsRequest,
+            crate::services::passkey::PasskeyLoginRequest,
+            crate::services::passkey::AssertionCredential,
+            crate::services::passkey::AssertionResponse,
+            crate::services::passkey::RenamePasskeyRequest,
+            crate::services::passkey::PasskeyCredentialInfo,
+            crate::services::admin_elevation::RequestElevationRequest,
+            crate::services::admin_elevation::ElevationDecisionRequest,
+            crate::services::admin_elevation::ListElevationsQuery,
+            crate::services::admin_elevation::ElevationStatus,
+            crate::services::admin_elevation::AdminElevation,
+            crate::services::audit_log::AuditLogQuery,
+            crate::services::audit_log::AuditLogEntry,
+            crate::services::tenant_activity::ActivityType,
+            crate::services::tenant_activity::ActivityQuery,
+            crate::services::tenant_activity::ActivityItem,
+            crate::services::tenant_activity::ActivityPage,
+            crate::services::curated_answer::CuratedAnswerStatus,
+            crate::services::curated_answer::CreateCuratedAnswerRequest,
+            crate::services::curated_answer::UpdateCuratedAnswerRequest,
+            crate::services::curated_answer::CuratedAnswerQuery,
+            crate::services::curated_answer::AnswerReviewQuery,
+            crate::services::curated_answer::CuratedAnswer,
+            crate::services::curated_answer::CuratedAnswerMatch,
+            crate::services::curated_answer::AnswerCluster,
+            crate::services::scheduled_report::ReportSource,
+            crate::services::scheduled_report::ReportFrequency,
+            crate::services::scheduled_report::ReportSchedule,
+            crate::services::scheduled_report::ReportFormat,
+            crate::services::scheduled_report::ReportDelivery,
+            crate::services::scheduled_report::CreateScheduledReportRequest,
+            crate::services::scheduled_report::UpdateScheduledReportRequest,
+            crate::services::scheduled_report::ScheduledReportResponse,
+            crate::services::scheduled_report::DeliveryResult,
+            crate::services::scheduled_report::ReportRunResponse,
+            scheduled_report::ReportRunsQuery,
             
             // 速率限制相关
             RateLimitPolicy,
             RateLimitCheckRequest,
+            crate::services::rate_limit::RateLimitExceeded,
+            crate::services::rate_limit::RateLimitScope,
+            crate::services::ingest_throttle::IngestThrottleExceeded,
             
             // 监控相关
             SystemHealth,
+            crate::services::slo::SloReport,
+            crate::services::incremental_index::DeltaEmbeddingStats,
+            crate::services::incremental_index::DeltaReindexReport,
+            crate::services::slo::SloClassReport,
+            crate::services::slo::SloWindowReport,
+            crate::services::slo::SloStatus,
+            
+            // 平台管理相关
+            admin::OverviewQuery,
+            admin::ConfigValidationQuery,
+            crate::config::ConfigValidationReport,
+            crate::config::ConfigCheck,
+            crate::config::ConfigCheckStatus,
+            crate::services::admin::PlatformOverview,
+            crate::services::admin::TenantOverview,
+            crate::services::admin::TenantSummary,
+            crate::services::admin::ContentTotals,
+            crate::services::admin::ExecutionOverview,
+            crate::services::admin::HourlyExecutionStats,
+            crate::services::admin::QueueDepth,
+            crate::services::replication::ReplicationLagStatus,
+            crate::services::replication::PromotionReport,
+            crate::services::cache::CacheMetrics,
+            crate::services::cache::CacheNamespaceMetrics,
+            crate::services::cache::CacheNamespace,
+            crate::services::task_queue::TaskQueueMetrics,
+            crate::services::task_queue::TaskQueueLaneMetrics,
+            crate::services::task_queue::QueuePriority,
+            crate::services::manifest::Manifest,
+            crate::services::manifest::TenantManifest,
+            crate::services::manifest::KnowledgeBaseManifest,
+            crate::services::manifest::WorkflowManifest,
+            crate::services::manifest::PluginManifest,
+            crate::services::manifest::ManifestPlan,
+            crate::services::manifest::ResourceChange,
+            crate::services::manifest::ResourceKind,
+            crate::services::manifest::ChangeAction,
             
             // 分页相关
             PaginationQuery,
@@ -188,7 +518,13 @@ use crate::services::tenant::TenantInfo;
             knowledge_base::UpdateKnowledgeBaseRequest,
             knowledge_base::KnowledgeBaseResponse,
             knowledge_base::KnowledgeBaseStats,
+            crate::services::kb_stats::KbStorageUsage,
+            crate::services::kb_stats::KbStorageHistoryPoint,
             knowledge_base::KnowledgeBaseSearchQuery,
+            knowledge_base::CreateKbSnapshotRequest,
+            crate::services::kb_snapshot::KbSnapshotResponse,
+            crate::services::kb_snapshot::KbSnapshotDiff,
+            crate::services::kb_snapshot::DocumentFingerprint,
             crate::db::entities::knowledge_base::KnowledgeBaseType,
             crate::db::entities::knowledge_base::KnowledgeBaseStatus,
             crate::db::entities::knowledge_base::KnowledgeBaseConfig,
@@ -200,11 +536,83 @@ use crate::services::tenant::TenantInfo;
             document::DocumentResponse,
             document::DocumentStats,
             document::DocumentSearchQuery,
+            document::DocumentDetailQuery,
             document::DocumentUploadResponse,
             crate::db::entities::document::DocumentType,
             crate::db::entities::document::DocumentStatus,
             crate::db::entities::document::DocumentMetadata,
             crate::db::entities::document::DocumentProcessingConfig,
+            crate::services::freshness::FreshnessStatus,
+            crate::services::freshness::DocumentFreshnessEntry,
+            crate::services::freshness::StaleDocumentReport,
+            crate::services::source_health::SourceHealthReport,
+            crate::services::source_health::SourceHealthEntry,
+            crate::services::source_health::SourceHealth,
+            crate::services::source_health::SourceHealthStatus,
+            crate::services::duplicate_detection::DetectDuplicatesRequest,
+            crate::services::duplicate_detection::DuplicateReportResponse,
+            crate::services::duplicate_detection::DuplicateCluster,
+            crate::services::duplicate_detection::DuplicateDocument,
+            crate::services::duplicate_detection::DuplicateClusterAction,
+            crate::services::duplicate_detection::ResolveDuplicateClusterRequest,
+            crate::services::duplicate_detection::DuplicateClusterResolution,
+            crate::services::topic_map::TopicClusteringJob,
+            crate::services::topic_map::KbTopicMap,
+            crate::services::topic_map::Topic,
+            crate::services::topic_map::TopicDocument,
+            crate::services::topic_map::TopicLabelSource,
+            crate::db::entities::knowledge_base::VectorBackend,
+            crate::services::vector_migration::VectorMigrationRequest,
+            crate::services::vector_migration::VectorMigrationStatus,
+            crate::services::embedding_export::CreateEmbeddingExportRequest,
+            crate::services::embedding_export::EmbeddingExportResponse,
+            crate::services::embedding_export::EmbeddingExportFormat,
+            crate::services::dataset::DatasetSummary,
+            crate::services::dataset::DatasetQuery,
+            crate::services::dataset::DatasetAggregate,
+            crate::services::dataset::AggregateFunction,
+            crate::services::dataset::DatasetFilter,
+            crate::services::dataset::FilterOperator,
+            crate::services::dataset::DatasetOrder,
+            crate::services::dataset::DatasetQueryResult,
+            crate::db::entities::dataset::DatasetColumn,
+            crate::db::entities::dataset::DatasetColumnType,
+            crate::services::pii_policy::PiiReport,
+            crate::services::pii_policy::PiiReportEntry,
+            crate::services::pii_policy::PiiOverrideRequest,
+            crate::services::pii_policy::PiiOverride,
+            crate::db::entities::knowledge_base::PiiIndexPolicy,
+            crate::db::entities::knowledge_base::PiiIndexAction,
+            crate::db::entities::knowledge_base::PiiKind,
+            crate::db::entities::knowledge_base::PiiOverrideDecision,
+            crate::services::vector_migration::VectorMigrationSummary,
+            crate::services::freshness::RenewDocumentRequest,
+            crate::services::archive::DocumentArchiveRequest,
+            crate::services::archive::DocumentArchiveResult,
+            crate::services::archive::SkippedDocument,
+            crate::services::archive::ArchiveSkipReason,
+            crate::services::archive::KnowledgeBaseArchiveResult,
+            crate::services::corpus_import::CorpusImportFormat,
+            crate::services::corpus_import::CorpusImportOptions,
+            crate::services::corpus_import::CorpusImportReport,
+            crate::services::corpus_import::SkippedImportRecord,
+            crate::db::entities::document::ClearanceLevel,
+            crate::services::clearance::SectionClearance,
+            crate::services::clearance::UpdateDocumentClearanceRequest,
+            crate::services::clearance::DocumentClearanceResponse,
+            crate::services::similar_documents::SimilarDocumentsQuery,
+            crate::services::similar_documents::SimilarDocument,
+            crate::services::document_comment::CreateDocumentCommentRequest,
+            crate::services::document_comment::UpdateDocumentCommentRequest,
+            crate::services::document_comment::DocumentCommentQuery,
+            crate::services::document_comment::DocumentComment,
+            crate::services::document_comment::CommentThread,
+            crate::db::entities::kb_faq_entry::FaqEntryType,
+            crate::services::faq::CreateFaqEntryRequest,
+            crate::services::faq::UpdateFaqEntryRequest,
+            crate::services::faq::FaqEntryResponse,
+            crate::services::faq::FaqMatch,
+            crate::services::faq::FaqMatchMethod,
             
             // 批量操作相关
             document::BatchDocumentOperation,
@@ -225,12 +633,37 @@ use crate::services::tenant::TenantInfo;
             qa::QaSource,
             qa::QaChunk,
             qa::QaStats,
+            crate::ai::structured_output::StructuredOutput,
+            crate::ai::structured_output::StructuredOutputStatus,
+            crate::ai::answer_confidence::ConfidenceBreakdown,
+            crate::ai::query_rewrite::QueryRewriteTrace,
+            crate::ai::query_rewrite::SubQueryTrace,
+            crate::ai::query_rewrite::AcronymExpansion,
+            qa::QaAskQuery,
+            crate::ai::retrieval_explain::RetrievalExplanation,
+            crate::ai::retrieval_explain::CandidateExplanation,
+            crate::ai::retrieval_explain::StageScore,
+            crate::ai::retrieval_explain::AppliedFilter,
+            crate::ai::retrieval_explain::RetrievalStage,
+            crate::ai::retrieval_explain::DropReason,
             qa::SessionMessage,
             qa::MessageType,
             qa::QaFeedbackRequest,
             qa::FeedbackType,
             qa::QaSuggestionsRequest,
             qa::QaSuggestionsResponse,
+            crate::services::question_suggestion::RelatedQuestionsRequest,
+            crate::services::question_suggestion::RelatedQuestionsResponse,
+            crate::services::question_suggestion::SuggestedQuestion,
+            crate::services::question_suggestion::SuggestionSource,
+            crate::services::transcript_export::CreateTranscriptExportRequest,
+            crate::services::transcript_export::TranscriptExportResponse,
+            crate::services::transcript_export::TranscriptExportFormat,
+            crate::services::qa_transcript::TranscriptCitation,
+            qa::ReviewAnswerRequest,
+            crate::services::finetune_dataset::BuildFinetuneDatasetRequest,
+            crate::services::finetune_dataset::FinetuneDatasetResponse,
+            crate::services::finetune_dataset::FinetuneDatasetStats,
             qa::SessionHistoryQuery,
             
             // Agent 相关
@@ -241,9 +674,41 @@ use crate::services::tenant::TenantInfo;
             agent::AgentStatusResponse,
             agent::AgentTaskInfo,
             agent::ExecutionStats,
+            crate::services::execution_event::ExecutionTimeline,
+            crate::services::execution_event::ExecutionTracePage,
+            crate::services::execution_event::ExecutionTraceStep,
+            crate::services::execution_replay::ReplayExecutionRequest,
+            crate::services::execution_replay::ReplayHistory,
+            crate::services::execution_replay::ReplayComparison,
+            crate::services::execution_replay::ExecutionRecordSummary,
+            crate::services::execution_replay::ExecutionFeedbackRequest,
+            crate::services::canary::CreateCanaryRequest,
+            crate::services::canary::CanaryRollbackPolicy,
+            crate::services::canary::EndCanaryRequest,
+            crate::services::canary::CanaryReleaseInfo,
+            crate::services::canary::CanaryArmStats,
+            crate::db::entities::canary_release::CanaryStatus,
+            crate::services::agent_definition::AgentDefinition,
+            crate::services::agent_definition::ToolDefinition,
+            crate::services::agent_definition::ModelDefinition,
+            crate::services::agent_definition::BudgetDefinition,
+            crate::services::agent_definition::MemoryDefinition,
+            crate::services::agent_definition::DefinitionFormat,
+            crate::services::agent_definition::AgentImportResult,
+            crate::services::agent_memory::ExecutionMemory,
+            crate::services::agent_memory::MemorySnapshotItem,
+            crate::services::agent_memory::MemoryTier,
+            crate::services::agent_memory::MemoryRetrieval,
+            crate::services::agent_memory::RetrievedMemory,
+            crate::ai::agent_runtime::MemoryItem,
+            crate::ai::agent_runtime::MemoryType,
+            crate::services::execution_event::TimelineEvent,
+            crate::db::entities::execution_event::ExecutionType,
             agent::ListAgentsResponse,
             agent::AgentInfo,
             crate::ai::agent_runtime::ReasoningStrategy,
+            crate::ai::agent_runtime::KnowledgeBaseBinding,
+            crate::services::web_search::WebSearchDomainPolicy,
             crate::ai::agent_runtime::AgentState,
             crate::ai::agent_runtime::TaskPriority,
             crate::ai::agent_runtime::TaskStatus,
@@ -266,6 +731,10 @@ use crate::services::tenant::TenantInfo;
             plugin::UpdatePluginConfigRequest,
             plugin::PluginControlRequest,
             plugin::PluginAction,
+            plugin::UpdateTenantPluginConfigRequest,
+            crate::services::plugin_config::TenantPluginConfigResponse,
+            plugin::AddTrustedKeyRequest,
+            crate::services::plugin_trust::TrustedKeyResponse,
             crate::plugins::plugin_manager::InstallPluginRequest,
             crate::plugins::plugin_manager::InstallPluginResponse,
             crate::plugins::plugin_manager::PluginListResponse,
@@ -291,6 +760,13 @@ use crate::services::tenant::TenantInfo;
             workflow::StepStats,
             workflow::PaginationInfo,
             workflow::ValidationSummary,
+            workflow::UpdateWorkflowRequest,
+            crate::services::workflow_callback::CallbackReceipt,
+            workflow::AcquireEditLeaseRequest,
+            crate::ai::workflow_engine::WorkflowEditLease,
+            crate::ai::workflow_engine::WorkflowConflict,
+            crate::ai::workflow_inputs::WorkflowInputForm,
+            crate::ai::workflow_inputs::InputViolation,
             crate::ai::workflow_engine::WorkflowDefinition,
             crate::ai::workflow_engine::WorkflowStatus,
             // crate::ai::workflow_executor::WorkflowExecution, // module not available
@@ -303,7 +779,18 @@ use crate::services::tenant::TenantInfo;
         (name = "tenant", description = "租户管理端点"),
         (name = "quota", description = "配额管理端点"),
         (name = "rate-limit", description = "速率限制端点"),
+        (name = "models", description = "模型路由端点"),
+        (name = "few-shot-examples", description = "少样本示例端点"),
+        (name = "saved-searches", description = "保存的搜索与文档告警端点"),
+        (name = "legal-holds", description = "法律保留端点"),
+        (name = "consent", description = "条款发布与接受记录端点"),
+        (name = "passkeys", description = "通行密钥注册与设备管理端点"),
+        (name = "elevations", description = "临时管理员提权申请、审批与审计端点"),
+        (name = "activity", description = "租户活动时间线端点"),
+        (name = "curated-answers", description = "答案复核与精选答案端点"),
+        (name = "reports", description = "定时报告端点"),
         (name = "monitoring", description = "监控端点"),
+        (name = "admin", description = "平台管理端点"),
         (name = "knowledge-bases", description = "知识库管理端点"),
         (name = "documents", description = "文档管理端点"),
         (name = "qa", description = "智能问答端点"),
@@ -353,20 +840,48 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
         web::scope("/api")
             .service(
                 web::scope("/v1")
+                    // 未接受租户当前条款的用户仅可访问条款、认证与健康检查接口
+                    .wrap(TermsAcceptanceMiddleware)
+                    // 临时提权期间的请求写入审计日志
+                    .wrap(ElevationAuditMiddleware)
                     // API 根路径
                     .route("", web::get().to(api_root))
                     // 健康检查路由
                     .configure(health::configure_health_routes)
                     // 版本信息路由
                     .configure(version::configure_version_routes)
+                    // 认证路由
+                    .configure(auth::configure_auth_routes)
                     // 租户管理路由
                     .configure(tenant::configure_tenant_routes)
                     // 配额管理路由
                     .configure(quota::configure_quota_routes)
                     // 限流管理路由
                     .configure(rate_limit::configure_rate_limit_routes)
+                    // 模型路由
+                    .configure(model_routing::configure_routes)
+                    // 少样本示例路由
+                    .configure(few_shot::configure_routes)
+                    // 保存的搜索路由
+                    .configure(saved_search::configure_routes)
+                    // 法律保留路由
+                    .configure(legal_hold::configure_routes)
+                    // 同意与条款路由
+                    .configure(consent::configure_routes)
+                    // 通行密钥路由
+                    .configure(passkey::configure_routes)
+                    // 临时管理员提权路由
+                    .configure(elevation::configure_routes)
+                    // 租户活动时间线路由
+                    .configure(activity::configure_routes)
+                    // 精选答案路由
+                    .configure(curated_answer::configure_routes)
+                    // 定时报告路由
+                    .configure(scheduled_report::configure_routes)
                     // 监控管理路由
                     .configure(monitoring::configure_monitoring_routes)
+                    // 平台管理路由
+                    .configure(admin::configure_admin_routes)
                     // 知识库管理路由
                     .configure(knowledge_base::configure_routes)
                     // 文档管理路由
@@ -379,6 +894,8 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .configure(tool::configure_routes)
                     // 插件管理路由
                     .configure(plugin::configure_routes)
+                    // 插件扩展路由
+                    .configure(plugin::configure_extension_routes)
                     // 工作流管理路由
                     .configure(workflow::configure_routes)
                     // OpenAPI JSON 端点
@@ -386,7 +903,6 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     // 未来的路由将在这里添加：
                     // - 租户管理 (/tenants)
                     // - 用户管理 (/users)
-                    // - 认证 (/auth)
                     // - 知识库 (/knowledge-bases)
                     // - 文档 (/documents)
                     // - 问答 (/qa)
@@ -450,6 +966,13 @@ impl ApiRouteConfig {
         // 生产环境可能需要额外的安全中间件
         // 这里可以添加生产环境特有的配置
     }
+
+    /// 配置 worker 角色路由，仅提供健康检查
+    pub fn configure_worker(cfg: &mut web::ServiceConfig) {
+        cfg.service(
+            web::scope("/api/v1").configure(health::configure_worker_health_routes)
+        );
+    }
 }
 
 /// 开发测试端点
@@ -0,0 +1,203 @@
+// Aionix 性能基准工具
+// 运行 HTTP 负载场景，并将负载结果与 criterion 基准结果同性能预算比较
+
+use aionix::perf::{
+    read_criterion_estimates, LoadConfig, LoadRunner, LoadScenario, PerformanceBudgets, ScenarioResult,
+};
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use uuid::Uuid;
+
+const DEFAULT_BUDGETS: &str = "benches/budgets.json";
+const DEFAULT_CRITERION_DIR: &str = "target/criterion";
+const DEFAULT_BASE_URL: &str = "http://localhost:8080";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 || args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        print_help();
+        return ExitCode::SUCCESS;
+    }
+
+    let options = &args[2..];
+    let result = match args[1].as_str() {
+        "load" => run_load(options).await,
+        "check" => run_check(options),
+        "record" => run_record(options),
+        other => Err(format!("未知命令: {}", other)),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("错误: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// 运行负载场景，存在性能预算时检查回归
+async fn run_load(options: &[String]) -> Result<bool, String> {
+    let knowledge_base_id = option(options, "--knowledge-base-id")
+        .ok_or("请通过 --knowledge-base-id 指定场景使用的知识库")?;
+    let tenant_id = option(options, "--tenant-id")
+        .map(|id| Uuid::parse_str(id).map_err(|_| format!("无效的租户 ID: {}", id)))
+        .transpose()?;
+
+    let config = LoadConfig {
+        base_url: option(options, "--base-url").unwrap_or(DEFAULT_BASE_URL).to_string(),
+        access_token: option(options, "--token").map(str::to_string),
+        tenant_id,
+        knowledge_base_id: Uuid::parse_str(knowledge_base_id)
+            .map_err(|_| format!("无效的知识库 ID: {}", knowledge_base_id))?,
+        requests: numeric_option(options, "--requests", 100)?,
+        concurrency: numeric_option(options, "--concurrency", 10)?,
+        timeout_secs: numeric_option(options, "--timeout", 60)?,
+    };
+
+    let scenarios = match all_options(options, "--scenario") {
+        names if names.is_empty() => LoadScenario::ALL.to_vec(),
+        names => names
+            .into_iter()
+            .map(|name| LoadScenario::from_name(name).ok_or_else(|| format!("未知场景: {}", name)))
+            .collect::<Result<_, _>>()?,
+    };
+
+    let runner = LoadRunner::new(config).map_err(|e| e.to_string())?;
+    let mut results = Vec::new();
+    for scenario in scenarios {
+        println!("运行场景 {} ...", scenario.name());
+        let result = runner.run(scenario).await;
+        println!(
+            "  请求 {}，错误 {}，P50 {:.1}ms，P95 {:.1}ms，P99 {:.1}ms，吞吐 {:.1} req/s",
+            result.requests, result.errors, result.p50_ms, result.p95_ms, result.p99_ms, result.throughput_rps
+        );
+        results.push(result);
+    }
+
+    if let Some(output) = option(options, "--output") {
+        let content = serde_json::to_string_pretty(&results).map_err(|e| e.to_string())?;
+        std::fs::write(output, content).map_err(|e| format!("写入结果文件 {} 失败: {}", output, e))?;
+        println!("结果已写入 {}", output);
+    }
+
+    let budgets_path = budgets_path(options);
+    if !budgets_path.exists() {
+        println!("未找到性能预算文件 {}，跳过回归检查", budgets_path.display());
+        return Ok(true);
+    }
+    let budgets = PerformanceBudgets::load(&budgets_path).map_err(|e| e.to_string())?;
+    let report = budgets.check_scenarios(&results);
+    println!("{}", report);
+    Ok(report.passed())
+}
+
+/// 将 criterion 基准结果与性能预算比较
+fn run_check(options: &[String]) -> Result<bool, String> {
+    let budgets = PerformanceBudgets::load(&budgets_path(options)).map_err(|e| e.to_string())?;
+    let results = read_criterion_estimates(&criterion_dir(options)).map_err(|e| e.to_string())?;
+    if results.is_empty() {
+        return Err("未找到 criterion 基准结果，请先运行 cargo bench".to_string());
+    }
+
+    let report = budgets.check_benchmarks(&results);
+    println!("{}", report);
+    Ok(report.passed())
+}
+
+/// 以当前基准与负载结果更新性能预算中的基线
+fn run_record(options: &[String]) -> Result<bool, String> {
+    let path = budgets_path(options);
+    let mut budgets = if path.exists() {
+        PerformanceBudgets::load(&path).map_err(|e| e.to_string())?
+    } else {
+        PerformanceBudgets::default()
+    };
+
+    let criterion_dir = criterion_dir(options);
+    if criterion_dir.exists() {
+        let results = read_criterion_estimates(&criterion_dir).map_err(|e| e.to_string())?;
+        println!("记录 {} 个基准基线", results.len());
+        budgets.record_benchmarks(&results);
+    }
+
+    if let Some(load_results) = option(options, "--load-results") {
+        let content = std::fs::read_to_string(load_results)
+            .map_err(|e| format!("读取负载结果 {} 失败: {}", load_results, e))?;
+        let results: Vec<ScenarioResult> = serde_json::from_str(&content)
+            .map_err(|e| format!("解析负载结果 {} 失败: {}", load_results, e))?;
+        println!("记录 {} 个场景基线", results.len());
+        budgets.record_scenarios(&results);
+    }
+
+    budgets.save(&path).map_err(|e| e.to_string())?;
+    println!("性能预算已写入 {}", path.display());
+    Ok(true)
+}
+
+fn option<'a>(options: &'a [String], name: &str) -> Option<&'a str> {
+    options
+        .iter()
+        .position(|arg| arg == name)
+        .and_then(|index| options.get(index + 1))
+        .map(String::as_str)
+}
+
+fn all_options<'a>(options: &'a [String], name: &str) -> Vec<&'a str> {
+    options
+        .windows(2)
+        .filter(|pair| pair[0] == name)
+        .map(|pair| pair[1].as_str())
+        .collect()
+}
+
+fn numeric_option<T: std::str::FromStr>(options: &[String], name: &str, default: T) -> Result<T, String> {
+    match option(options, name) {
+        Some(value) => value.parse().map_err(|_| format!("{} 需要数字参数: {}", name, value)),
+        None => Ok(default),
+    }
+}
+
+fn budgets_path(options: &[String]) -> PathBuf {
+    PathBuf::from(option(options, "--budgets").unwrap_or(DEFAULT_BUDGETS))
+}
+
+fn criterion_dir(options: &[String]) -> PathBuf {
+    PathBuf::from(option(options, "--criterion-dir").unwrap_or(DEFAULT_CRITERION_DIR))
+}
+
+fn print_help() {
+    println!("Aionix 性能基准工具");
+    println!();
+    println!("用法:");
+    println!("  aionix-bench <命令> [选项]");
+    println!();
+    println!("命令:");
+    println!("  load                  对运行中的服务执行 HTTP 负载场景");
+    println!("  check                 将 criterion 基准结果与性能预算比较");
+    println!("  record                以当前结果更新性能预算中的基线");
+    println!();
+    println!("负载选项:");
+    println!("  --knowledge-base-id <id>  场景使用的知识库（必填）");
+    println!("  --base-url <url>      服务地址，默认 {}", DEFAULT_BASE_URL);
+    println!("  --token <token>       访问令牌");
+    println!("  --tenant-id <id>      租户 ID");
+    println!("  --scenario <name>     场景（ingestion、related_questions、qa），可重复，默认全部");
+    println!("  --requests <n>        每个场景的请求数，默认 100");
+    println!("  --concurrency <n>     并发数，默认 10");
+    println!("  --timeout <秒>        单个请求超时，默认 60");
+    println!("  --output <path>       将结果写入 JSON 文件");
+    println!();
+    println!("通用选项:");
+    println!("  --budgets <path>      性能预算文件，默认 {}", DEFAULT_BUDGETS);
+    println!("  --criterion-dir <dir> criterion 输出目录，默认 {}", DEFAULT_CRITERION_DIR);
+    println!("  --load-results <path> record 时使用的负载结果文件");
+    println!();
+    println!("示例:");
+    println!("  cargo bench && aionix-bench check");
+    println!("  aionix-bench load --knowledge-base-id <id> --token <token> --output load.json");
+    println!("  aionix-bench record --load-results load.json");
+}
@@ -1,7 +1,7 @@
 // Aionix 数据库管理 CLI 工具
 
 use aionix::config::AppConfig;
-use aionix::db::cli::{parse_args, print_help, CliExecutor};
+use aionix::db::cli::{execute_config_command, parse_args, print_help, CliCommand, CliExecutor};
 use std::env;
 use tracing::{error, info};
 
@@ -27,15 +27,6 @@ async fn main() {
         }
     };
 
-    // 创建 CLI 执行器
-    let executor = match CliExecutor::new(config).await {
-        Ok(executor) => executor,
-        Err(e) => {
-            error!("初始化 CLI 执行器失败: {}", e);
-            std::process::exit(1);
-        }
-    };
-
     // 解析命令
     let command = match parse_args(args) {
         Ok(command) => command,
@@ -46,6 +37,24 @@ async fn main() {
         }
     };
 
+    // 配置命令不依赖数据库连接，数据库不可用时也能检查配置
+    if let CliCommand::Config(config_command) = command {
+        if let Err(e) = execute_config_command(&config, config_command).await {
+            error!("执行命令失败: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // 创建 CLI 执行器
+    let executor = match CliExecutor::new(config).await {
+        Ok(executor) => executor,
+        Err(e) => {
+            error!("初始化 CLI 执行器失败: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // 执行命令
     if let Err(e) = executor.execute(command).await {
         error!("执行命令失败: {}", e);
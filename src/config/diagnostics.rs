@@ -0,0 +1,369 @@
+// 配置诊断
+// 汇总配置验证、依赖服务连通性检查与安全检查结果，并生成脱敏后的环境配置差异
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::io::AsyncReadExt;
+use url::Url;
+use utoipa::ToSchema;
+
+use crate::config::{AppConfig, ConfigValidator, LocalInferenceBackend, SmtpConfig};
+use crate::services::egress::EgressPolicy;
+
+/// 单项连通性检查的超时时间
+const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 默认配置中的占位密钥，生产环境必须替换
+const PLACEHOLDER_SECRETS: [&str; 2] = [
+    "your-super-secret-jwt-key-change-this-in-production",
+    "your-super-secret-encryption-key-change-this-in-production",
+];
+
+/// 需要脱敏的配置项名称
+const SECRET_KEYS: [&str; 5] = ["jwt_secret", "encryption_key", "api_key", "password", "token"];
+
+/// 脱敏后的占位符
+const REDACTED: &str = "******";
+
+/// 检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigCheckStatus {
+    /// 通过
+    Passed,
+    /// 存在风险但不影响启动
+    Warning,
+    /// 失败
+    Failed,
+    /// 未执行
+    Skipped,
+}
+
+/// 单项检查
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConfigCheck {
+    /// 检查类别：settings、connectivity、security
+    pub category: String,
+    /// 检查对象，如配置段名称或依赖服务名称
+    pub component: String,
+    /// 检查结果
+    pub status: ConfigCheckStatus,
+    /// 说明
+    pub message: String,
+    /// 连通性检查耗时（毫秒）
+    pub latency_ms: Option<u64>,
+}
+
+impl ConfigCheck {
+    fn new(category: &str, component: &str, status: ConfigCheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            category: category.to_string(),
+            component: component.to_string(),
+            status,
+            message: message.into(),
+            latency_ms: None,
+        }
+    }
+}
+
+/// 配置诊断报告
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConfigValidationReport {
+    /// 环境名称
+    pub environment: String,
+    /// 是否没有失败项
+    pub is_valid: bool,
+    /// 检查明细
+    pub checks: Vec<ConfigCheck>,
+}
+
+impl ConfigValidationReport {
+    /// 统计指定结果的检查数
+    pub fn count(&self, status: ConfigCheckStatus) -> usize {
+        self.checks.iter().filter(|c| c.status == status).count()
+    }
+}
+
+/// 两个环境之间的配置差异
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ConfigDiffEntry {
+    /// 配置项路径，如 `database.max_connections`
+    pub key: String,
+    /// 左侧环境的值（已脱敏），缺失时为空
+    pub left: Option<String>,
+    /// 右侧环境的值（已脱敏），缺失时为空
+    pub right: Option<String>,
+}
+
+/// 配置诊断
+pub struct ConfigDiagnostics;
+
+impl ConfigDiagnostics {
+    /// 执行配置诊断，`check_connectivity` 为 false 时跳过依赖服务连通性检查
+    pub async fn run(config: &AppConfig, check_connectivity: bool) -> ConfigValidationReport {
+        let mut checks: Vec<ConfigCheck> = ConfigValidator::validate_sections(config)
+            .into_iter()
+            .map(|(section, result)| match result {
+                Ok(()) => ConfigCheck::new("settings", section, ConfigCheckStatus::Passed, "配置有效"),
+                Err(e) => ConfigCheck::new("settings", section, ConfigCheckStatus::Failed, e.to_string()),
+            })
+            .collect();
+
+        checks.extend(security_checks(config));
+
+        if check_connectivity {
+            let (database, redis, ai, smtp) = tokio::join!(
+                timed("database", check_database(config)),
+                timed("redis", check_redis(config)),
+                timed("ai", check_ai_provider(config)),
+                timed("smtp", check_smtp(config.smtp.as_ref())),
+            );
+            checks.extend([database, redis, ai, smtp]);
+        } else {
+            checks.push(ConfigCheck::new("connectivity", "*", ConfigCheckStatus::Skipped, "已跳过依赖服务连通性检查"));
+        }
+
+        ConfigValidationReport {
+            environment: config.environment.name.clone(),
+            is_valid: !checks.iter().any(|c| c.status == ConfigCheckStatus::Failed),
+            checks,
+        }
+    }
+}
+
+/// 安全检查：占位密钥、生产环境的调试模式与跨域设置
+pub fn security_checks(config: &AppConfig) -> Vec<ConfigCheck> {
+    let production = config.is_production();
+    let severity = if production { ConfigCheckStatus::Failed } else { ConfigCheckStatus::Warning };
+    let mut checks = Vec::new();
+
+    for (name, value) in [("jwt_secret", &config.security.jwt_secret), ("encryption_key", &config.security.encryption_key)] {
+        if PLACEHOLDER_SECRETS.contains(&value.as_str()) {
+            checks.push(ConfigCheck::new("security", name, severity, "仍在使用默认占位密钥，请替换为随机生成的密钥"));
+        }
+    }
+    if config.security.jwt_secret == config.security.encryption_key {
+        checks.push(ConfigCheck::new("security", "encryption_key", severity, "数据加密密钥不应与 JWT 密钥相同"));
+    }
+
+    if production {
+        if config.environment.debug {
+            checks.push(ConfigCheck::new("security", "environment.debug", ConfigCheckStatus::Warning, "生产环境不应开启调试模式"));
+        }
+        if config.security.cors_origins.iter().any(|origin| origin == "*") {
+            checks.push(ConfigCheck::new("security", "security.cors_origins", ConfigCheckStatus::Warning, "生产环境不应允许任意跨域来源"));
+        }
+        if config.smtp.as_ref().is_some_and(|smtp| !smtp.use_tls) {
+            checks.push(ConfigCheck::new("security", "smtp.use_tls", ConfigCheckStatus::Warning, "生产环境发送邮件应启用 TLS"));
+        }
+    }
+
+    if checks.is_empty() {
+        checks.push(ConfigCheck::new("security", "*", ConfigCheckStatus::Passed, "未发现不安全的配置"));
+    }
+    checks
+}
+
+/// 将配置展开为 `段.字段` 形式的键值对，并对密钥和连接串中的密码脱敏
+pub fn redacted_settings(config: &AppConfig) -> BTreeMap<String, String> {
+    let mut settings = BTreeMap::new();
+    if let Ok(value) = serde_json::to_value(config) {
+        flatten("", &value, &mut settings);
+    }
+    settings
+}
+
+/// 对比两个环境的配置，只返回不同的配置项
+pub fn diff_settings(left: &AppConfig, right: &AppConfig) -> Vec<ConfigDiffEntry> {
+    let left = redacted_settings(left);
+    let right = redacted_settings(right);
+
+    let mut keys: Vec<&String> = left.keys().chain(right.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter(|key| left.get(*key) != right.get(*key))
+        .map(|key| ConfigDiffEntry {
+            key: key.clone(),
+            left: left.get(key).cloned(),
+            right: right.get(key).cloned(),
+        })
+        .collect()
+}
+
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&path, child, out);
+            }
+        }
+        _ => {
+            let field = prefix.rsplit('.').next().unwrap_or(prefix);
+            let rendered = match value {
+                Value::Null => "null".to_string(),
+                Value::String(s) if SECRET_KEYS.contains(&field) => {
+                    if s.is_empty() { String::new() } else { redact_secret(s) }
+                }
+                Value::String(s) => redact_url(s),
+                other => other.to_string(),
+            };
+            out.insert(prefix.to_string(), rendered);
+        }
+    }
+}
+
+/// 密钥只保留长度与摘要前缀，便于判断两个环境是否使用同一密钥
+fn redact_secret(secret: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = format!("{:x}", Sha256::digest(secret.as_bytes()));
+    format!("{} (len={}, sha256={})", REDACTED, secret.len(), &digest[..8])
+}
+
+/// 去除连接串中的密码
+fn redact_url(value: &str) -> String {
+    match Url::parse(value) {
+        Ok(mut url) if url.password().is_some() => {
+            let _ = url.set_password(Some(REDACTED));
+            url.to_string()
+        }
+        _ => value.to_string(),
+    }
+}
+
+async fn timed<F>(component: &str, check: F) -> ConfigCheck
+where
+    F: Future<Output = (ConfigCheckStatus, String)>,
+{
+    let started = Instant::now();
+    let (status, message) = match tokio::time::timeout(CONNECTIVITY_TIMEOUT, check).await {
+        Ok(result) => result,
+        Err(_) => (ConfigCheckStatus::Failed, format!("{} 秒内未响应", CONNECTIVITY_TIMEOUT.as_secs())),
+    };
+
+    let mut check = ConfigCheck::new("connectivity", component, status, message);
+    if status != ConfigCheckStatus::Skipped {
+        check.latency_ms = Some(started.elapsed().as_millis() as u64);
+    }
+    check
+}
+
+async fn check_database(config: &AppConfig) -> (ConfigCheckStatus, String) {
+    use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseBackend, Statement};
+
+    let mut options = ConnectOptions::new(config.database.url.clone());
+    options.max_connections(1).connect_timeout(CONNECTIVITY_TIMEOUT).sqlx_logging(false);
+
+    let db = match Database::connect(options).await {
+        Ok(db) => db,
+        Err(e) => return (ConfigCheckStatus::Failed, format!("连接失败: {}", e)),
+    };
+    let result = db.query_one(Statement::from_string(DatabaseBackend::Postgres, "SELECT version()".to_string())).await;
+    let _ = db.close().await;
+
+    match result {
+        Ok(Some(row)) => {
+            let version: String = row.try_get("", "version").unwrap_or_default();
+            (ConfigCheckStatus::Passed, version.split(',').next().unwrap_or("已连接").to_string())
+        }
+        Ok(None) => (ConfigCheckStatus::Passed, "已连接".to_string()),
+        Err(e) => (ConfigCheckStatus::Failed, format!("查询失败: {}", e)),
+    }
+}
+
+#[cfg(feature = "redis")]
+async fn check_redis(config: &AppConfig) -> (ConfigCheckStatus, String) {
+    let client = match redis::Client::open(config.redis.url.as_str()) {
+        Ok(client) => client,
+        Err(e) => return (ConfigCheckStatus::Failed, format!("Redis 地址无效: {}", e)),
+    };
+    let mut conn = match client.get_async_connection().await {
+        Ok(conn) => conn,
+        Err(e) => return (ConfigCheckStatus::Failed, format!("连接失败: {}", e)),
+    };
+
+    match redis::cmd("PING").query_async::<_, String>(&mut conn).await {
+        Ok(reply) if reply == "PONG" => (ConfigCheckStatus::Passed, "PONG".to_string()),
+        Ok(reply) => (ConfigCheckStatus::Warning, format!("PING 返回异常: {}", reply)),
+        Err(e) => (ConfigCheckStatus::Failed, format!("PING 失败: {}", e)),
+    }
+}
+
+#[cfg(not(feature = "redis"))]
+async fn check_redis(_config: &AppConfig) -> (ConfigCheckStatus, String) {
+    (ConfigCheckStatus::Skipped, "未启用 redis 功能".to_string())
+}
+
+async fn check_ai_provider(config: &AppConfig) -> (ConfigCheckStatus, String) {
+    let (url, api_key) = match &config.ai.local {
+        Some(local) => {
+            let path = match local.backend {
+                LocalInferenceBackend::Vllm => "/v1/models",
+                LocalInferenceBackend::LlamaCpp => "/health",
+            };
+            (format!("{}{}", local.base_url.trim_end_matches('/'), path), local.api_key.clone())
+        }
+        None => (config.ai.model_endpoint.clone(), Some(config.ai.api_key.clone()).filter(|k| !k.is_empty())),
+    };
+
+    // 按出站配置连接，与运行时模型请求经过相同的代理与证书
+    let builder = match EgressPolicy::from_config(&config.egress)
+        .and_then(|policy| policy.apply(reqwest::Client::builder()))
+    {
+        Ok(builder) => builder,
+        Err(e) => return (ConfigCheckStatus::Failed, format!("出站网络配置无效: {}", e)),
+    };
+    let client = match builder.timeout(CONNECTIVITY_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => return (ConfigCheckStatus::Failed, format!("创建 HTTP 客户端失败: {}", e)),
+    };
+    let mut request = client.get(&url);
+    if let Some(api_key) = api_key {
+        request = request.bearer_auth(api_key);
+    }
+
+    match request.send().await {
+        Ok(response) if response.status().is_success() => {
+            (ConfigCheckStatus::Passed, format!("{} 返回 {}", redact_url(&url), response.status()))
+        }
+        Ok(response) if matches!(response.status().as_u16(), 401 | 403) => {
+            (ConfigCheckStatus::Failed, format!("认证失败（{}），请检查 API 密钥", response.status()))
+        }
+        Ok(response) => (ConfigCheckStatus::Warning, format!("服务可达，但 {} 返回 {}", redact_url(&url), response.status())),
+        Err(e) => (ConfigCheckStatus::Failed, format!("请求失败: {}", e)),
+    }
+}
+
+async fn check_smtp(config: Option<&SmtpConfig>) -> (ConfigCheckStatus, String) {
+    let Some(smtp) = config else {
+        return (ConfigCheckStatus::Skipped, "未配置 SMTP".to_string());
+    };
+
+    let mut stream = match tokio::net::TcpStream::connect((smtp.host.as_str(), smtp.port)).await {
+        Ok(stream) => stream,
+        Err(e) => return (ConfigCheckStatus::Failed, format!("无法连接 {}:{}: {}", smtp.host, smtp.port, e)),
+    };
+    // 隐式 TLS 端口需先完成握手才能读取问候语，只检查 TCP 连通性
+    if smtp.port == 465 {
+        return (ConfigCheckStatus::Passed, "已建立 TCP 连接（隐式 TLS 端口，未检查问候语）".to_string());
+    }
+
+    let mut banner = [0u8; 512];
+    match stream.read(&mut banner).await {
+        Ok(read) => {
+            let greeting = String::from_utf8_lossy(&banner[..read]).trim().to_string();
+            if greeting.starts_with("220") {
+                (ConfigCheckStatus::Passed, greeting)
+            } else {
+                (ConfigCheckStatus::Warning, format!("问候语异常: {}", greeting))
+            }
+        }
+        Err(e) => (ConfigCheckStatus::Failed, format!("读取问候语失败: {}", e)),
+    }
+}
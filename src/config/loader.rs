@@ -94,6 +94,9 @@ impl ConfigLoader {
         println!("Redis 连接池: {}", config.redis.max_connections);
         
         println!("AI 端点: {}", config.ai.model_endpoint);
+        if let Some(local) = &config.ai.local {
+            println!("本地推理: {:?} @ {} ({})", local.backend, local.base_url, local.model_path);
+        }
         println!("存储路径: {}", config.storage.path);
         println!("日志级别: {}", config.logging.level);
         println!("向量维度: {}", config.vector.dimension);
@@ -4,10 +4,12 @@
 pub mod settings;
 pub mod loader;
 pub mod validator;
+pub mod diagnostics;
 
 #[cfg(test)]
 mod tests;
 
 pub use settings::*;
 pub use loader::*;
-pub use validator::*;
\ No newline at end of file
+pub use validator::*;
+pub use diagnostics::*;
\ No newline at end of file
@@ -4,6 +4,7 @@
 use config::{Config, ConfigError, Environment, File};
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::net::IpAddr;
 use std::path::Path;
 use aionix_common::CommonError;
 
@@ -19,7 +20,35 @@ pub struct AppConfig {
     pub storage: StorageConfig,
     pub logging: LoggingConfig,
     pub vector: VectorConfig,
+    pub retention: RetentionConfig,
+    pub replication: ReplicationConfig,
+    pub cache: CacheConfig,
+    pub usage_anomaly: UsageAnomalyConfig,
+    /// 导入类接口按 API 密钥的令牌桶限流
+    #[serde(default)]
+    pub ingest_throttle: IngestThrottleConfig,
+    /// 详细健康检查的访问范围
+    #[serde(default)]
+    pub health: HealthAccessConfig,
+    /// 通行密钥（WebAuthn）依赖方设置
+    #[serde(default)]
+    pub webauthn: WebAuthnConfig,
+    /// 临时管理员提权的时长与审批时限
+    #[serde(default)]
+    pub elevation: ElevationConfig,
+    /// 出站请求的代理与 TLS 设置
+    #[serde(default)]
+    pub egress: EgressConfig,
+    /// 全局 HTTP 中间件栈
+    #[serde(default)]
+    pub http: HttpMiddlewareConfig,
+    pub slo: SloConfig,
+    pub execution_artifacts: ExecutionArtifactConfig,
+    pub billing: BillingConfig,
     pub environment: EnvironmentConfig,
+    /// SMTP 邮件服务，未配置时不发送邮件通知
+    #[serde(default)]
+    pub smtp: Option<SmtpConfig>,
 }
 
 /// 服务器配置
@@ -31,6 +60,23 @@ pub struct ServerConfig {
     pub keep_alive: u64,
     pub client_timeout: u64,
     pub client_shutdown: u64,
+    /// 未指定超时时请求的默认截止时间（毫秒）
+    #[serde(default = "default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+    /// 客户端通过 X-Request-Timeout-Ms 可申请的最长截止时间（毫秒）
+    #[serde(default = "default_max_request_timeout_ms")]
+    pub max_request_timeout_ms: u64,
+    /// 进程角色，可被命令行参数 `--role` 覆盖
+    #[serde(default)]
+    pub role: ProcessRole,
+}
+
+fn default_request_timeout_ms() -> u64 {
+    120_000
+}
+
+fn default_max_request_timeout_ms() -> u64 {
+    600_000
 }
 
 /// 数据库配置
@@ -53,6 +99,74 @@ pub struct AiConfig {
     pub temperature: f32,
     pub timeout: u64,
     pub retry_attempts: u32,
+    /// 本地推理后端配置，设置后所有生成与嵌入请求都发往本地服务
+    #[serde(default)]
+    pub local: Option<LocalInferenceConfig>,
+}
+
+/// 本地推理后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalInferenceBackend {
+    /// vLLM 的 OpenAI 兼容服务
+    Vllm,
+    /// llama.cpp HTTP 服务（llama-server）
+    LlamaCpp,
+}
+
+/// 本地推理配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalInferenceConfig {
+    pub backend: LocalInferenceBackend,
+    /// 生成服务地址，如 `http://localhost:8000`
+    pub base_url: String,
+    /// 生成模型路径，vLLM 以此作为请求中的模型名
+    pub model_path: String,
+    /// 嵌入服务地址，未设置时使用生成服务
+    #[serde(default)]
+    pub embedding_base_url: Option<String>,
+    /// 嵌入模型路径，未设置时使用生成模型
+    #[serde(default)]
+    pub embedding_model_path: Option<String>,
+    /// 服务启用了 `--api-key` 时使用的密钥
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// 嵌入工作池
+    #[serde(default)]
+    pub embedding_pool: EmbeddingPoolConfig,
+}
+
+/// 嵌入工作池配置
+///
+/// 批次大小与批次 token 上限应按嵌入服务的显存调整。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingPoolConfig {
+    /// 工作协程数，即同时发往嵌入服务的请求数
+    pub workers: usize,
+    /// 只处理交互请求的工作协程数，保证批量任务不会占满所有并发
+    pub interactive_workers: usize,
+    /// 单批最大文本数
+    pub max_batch_size: usize,
+    /// 单批估算 token 上限
+    pub max_batch_tokens: usize,
+    /// 凑批等待时间（毫秒）
+    pub batch_wait_ms: u64,
+    /// 每个优先级队列的容量，队列满时提交方等待
+    pub queue_capacity: usize,
+}
+
+impl Default for EmbeddingPoolConfig {
+    fn default() -> Self {
+        Self {
+            workers: 4,
+            interactive_workers: 1,
+            max_batch_size: 32,
+            max_batch_tokens: 8192,
+            batch_wait_ms: 5,
+            queue_capacity: 1024,
+        }
+    }
 }
 
 /// Redis 配置
@@ -74,6 +188,30 @@ pub struct SecurityConfig {
     pub cors_origins: Vec<String>,
     pub rate_limit_requests: u32,
     pub rate_limit_window: u64,
+    pub encryption_key: String,
+    /// 部署级插件签名公钥（base64 编码的 ed25519 公钥）
+    #[serde(default)]
+    pub plugin_trusted_keys: Vec<String>,
+    /// 外部 KMS 配置，租户使用 KMS 包装数据密钥时必需
+    #[serde(default)]
+    pub kms: Option<KmsConfig>,
+}
+
+/// 外部 KMS 连接配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KmsConfig {
+    /// 服务地址
+    pub url: String,
+    /// 访问令牌
+    #[serde(default)]
+    pub token: Option<String>,
+    /// 请求超时（秒）
+    #[serde(default = "default_kms_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_kms_timeout_secs() -> u64 {
+    10
 }
 
 /// 存储配置
@@ -103,6 +241,610 @@ pub struct VectorConfig {
     pub index_type: String,
     pub ef_construction: u32,
     pub m: u32,
+    /// Qdrant 连接配置，知识库使用 Qdrant 后端时必需
+    #[serde(default)]
+    pub qdrant: Option<ExternalVectorStoreConfig>,
+    /// Milvus 连接配置，知识库使用 Milvus 后端时必需
+    #[serde(default)]
+    pub milvus: Option<ExternalVectorStoreConfig>,
+}
+
+/// 外部向量数据库连接配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalVectorStoreConfig {
+    /// 服务地址，如 `http://localhost:6333`
+    pub url: String,
+    /// 访问密钥（Qdrant 的 API Key 或 Milvus 的 Token）
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// 集合名前缀，每个知识库对应一个集合
+    #[serde(default = "default_vector_collection_prefix")]
+    pub collection_prefix: String,
+    /// 请求超时（秒）
+    #[serde(default = "default_vector_store_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_vector_collection_prefix() -> String {
+    "aionix_kb_".to_string()
+}
+
+fn default_vector_store_timeout_secs() -> u64 {
+    30
+}
+
+/// 数据保留配置
+///
+/// 各类数据的保留天数，0 表示永久保留。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub batch_size: u32,
+    pub sessions_days: u32,
+    pub agent_executions_days: u32,
+    pub workflow_executions_days: u32,
+    pub audit_logs_days: u32,
+}
+
+/// 跨区域复制配置
+///
+/// 主区域将租户数据变更写入复制事件表并发布到队列，备用区域消费队列保持热备。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationConfig {
+    pub enabled: bool,
+    /// 当前区域标识
+    pub region: String,
+    /// 当前区域角色
+    pub role: ReplicationRole,
+    /// 复制队列地址，未配置时使用 Redis 配置中的地址
+    #[serde(default)]
+    pub queue_url: Option<String>,
+    /// 复制事件流名称
+    pub stream: String,
+    pub interval_secs: u64,
+    pub batch_size: u32,
+    /// 复制延迟超过该值时健康检查报告降级
+    pub max_lag_secs: u64,
+}
+
+/// 区域角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplicationRole {
+    /// 主区域：接收写入并发布复制事件
+    Primary,
+    /// 备用区域：消费复制事件，提升后成为主区域
+    Passive,
+}
+
+impl ReplicationRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReplicationRole::Primary => "primary",
+            ReplicationRole::Passive => "passive",
+        }
+    }
+}
+
+/// 热点实体缓存配置
+///
+/// 缓存租户、用户与工作流定义，减少每次请求经过中间件时的数据库查询。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    /// 缓存后端
+    pub backend: CacheBackendKind,
+    /// 缓存项存活时间（秒）
+    pub ttl_secs: u64,
+    /// 内存后端最大缓存项数
+    pub max_entries: usize,
+    /// 缓存键前缀
+    pub key_prefix: String,
+}
+
+/// 缓存后端类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheBackendKind {
+    /// Redis 缓存，多实例共享并可跨实例失效
+    Redis,
+    /// 进程内缓存，仅适用于单实例部署
+    Memory,
+}
+
+/// 用量异常检测配置
+///
+/// 按小时统计各租户的 token 用量、登录失败与文档删除，当前小时明显高于滚动基线时告警。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageAnomalyConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// 滚动基线覆盖的小时数
+    pub baseline_hours: u32,
+    /// 计数历史不足该小时数时不检测，避免新租户误报
+    pub min_history_hours: u32,
+    /// 超出基线均值的标准差倍数
+    pub sensitivity: f64,
+    /// 当前值至少为基线均值的倍数
+    pub min_ratio: f64,
+    /// 各指标每小时低于该值时不视为异常
+    pub min_tokens: u64,
+    pub min_failed_logins: u64,
+    pub min_document_deletions: u64,
+    /// 是否对贡献异常用量过半的 API 密钥自动限流
+    pub auto_throttle: bool,
+    /// 自动限流持续的分钟数
+    pub throttle_minutes: u32,
+}
+
+/// 导入类接口的令牌桶限流配置
+///
+/// 与通用限流相互独立，按 API 密钥分别维护令牌桶：桶容量即允许的突发请求数，令牌按固定速率补充。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestThrottleConfig {
+    pub enabled: bool,
+    /// 桶容量，即允许的突发请求数
+    pub burst: u32,
+    /// 每秒补充的令牌数
+    pub refill_per_second: f64,
+    /// 批量导入、重建索引等批量请求消耗的令牌数
+    pub batch_cost: u32,
+}
+
+impl Default for IngestThrottleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            burst: 20,
+            refill_per_second: 1.0,
+            batch_cost: 10,
+        }
+    }
+}
+
+/// 详细健康检查访问配置
+///
+/// `/health` 始终公开；`/api/v1/health/detailed` 仅对运维角色或来自内部网络的请求返回完整信息，
+/// 租户管理员只能看到隐去错误信息与系统指标的精简版本。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthAccessConfig {
+    /// 可查看完整健康信息的用户角色
+    pub ops_roles: Vec<String>,
+    /// 可免认证查看完整健康信息的内部网络（CIDR）
+    pub internal_networks: Vec<String>,
+    /// 是否按 `X-Forwarded-For`/`Forwarded` 识别客户端地址，仅在可信反向代理之后开启
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for HealthAccessConfig {
+    fn default() -> Self {
+        Self {
+            ops_roles: vec!["ops".to_string()],
+            internal_networks: vec!["127.0.0.0/8".to_string(), "::1/128".to_string()],
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+impl HealthAccessConfig {
+    /// 地址是否属于配置的内部网络，无法解析的网络配置将被忽略
+    pub fn is_internal_address(&self, ip: IpAddr) -> bool {
+        self.internal_networks
+            .iter()
+            .filter_map(|network| parse_cidr(network).ok())
+            .any(|(network, prefix)| cidr_contains(network, prefix, ip))
+    }
+}
+
+/// 通行密钥（WebAuthn）依赖方设置
+///
+/// `rp_id` 为浏览器绑定凭据的域名，部署后不可更改，否则已注册的通行密钥全部失效；
+/// `origins` 为允许发起注册与登录的前端来源，其主机必须是 `rp_id` 或其子域名。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebAuthnConfig {
+    /// 依赖方 ID（域名）
+    pub rp_id: String,
+    /// 依赖方显示名称
+    pub rp_name: String,
+    /// 允许的来源，如 `https://studio.example.com`
+    pub origins: Vec<String>,
+    /// 注册与登录挑战的有效期（秒）
+    pub challenge_ttl_secs: u64,
+    /// 是否要求认证器完成用户验证（PIN、指纹等）
+    pub require_user_verification: bool,
+}
+
+impl Default for WebAuthnConfig {
+    fn default() -> Self {
+        Self {
+            rp_id: "localhost".to_string(),
+            rp_name: "Aionix AI Studio".to_string(),
+            origins: vec!["http://localhost:3000".to_string()],
+            challenge_ttl_secs: 300,
+            require_user_verification: false,
+        }
+    }
+}
+
+/// 临时管理员提权设置
+///
+/// 用户申请在限定时长内获得管理员权限，须由另一名管理员审批；到期后自动失效。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ElevationConfig {
+    /// 单次提权的最长时长（小时）
+    pub max_hours: u32,
+    /// 申请的审批时限（小时），超时未审批的申请不能再被批准
+    pub request_ttl_hours: u32,
+}
+
+impl Default for ElevationConfig {
+    fn default() -> Self {
+        Self {
+            max_hours: 8,
+            request_ttl_hours: 24,
+        }
+    }
+}
+
+/// 出站网络配置
+///
+/// 模型调用、HTTP 工具、Webhook 与来源抓取等出站请求统一按此构建客户端；
+/// 租户可在租户配置中改用自己的代理、追加信任的 CA 证书或提高最低 TLS 版本。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EgressConfig {
+    /// 出站代理地址，如 `http://proxy.example.com:3128`；为空时沿用 `HTTPS_PROXY` 等环境变量
+    pub proxy_url: Option<String>,
+    /// 不经代理直连的主机、域名后缀或网段，规则同 `NO_PROXY`
+    pub no_proxy: Vec<String>,
+    /// 额外信任的 CA 证书包路径（PEM，可包含多张证书），用于企业代理或内部服务的自签证书
+    pub ca_bundle_path: Option<String>,
+    /// 最低 TLS 版本
+    pub tls_min_version: TlsMinVersion,
+}
+
+impl Default for EgressConfig {
+    fn default() -> Self {
+        Self {
+            proxy_url: None,
+            no_proxy: vec!["localhost".to_string(), "127.0.0.1".to_string(), "::1".to_string()],
+            ca_bundle_path: None,
+            tls_min_version: TlsMinVersion::default(),
+        }
+    }
+}
+
+/// 全局 HTTP 中间件栈配置
+///
+/// 决定 CORS、响应压缩、限流、安全响应头与请求追踪是否挂载及其行为，运维可按环境调整而无需改动代码。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpMiddlewareConfig {
+    pub cors: CorsConfig,
+    /// 是否按 `Accept-Encoding` 压缩响应
+    pub compression: bool,
+    pub rate_limit: HttpRateLimitConfig,
+    pub security_headers: SecurityHeadersConfig,
+    /// 是否记录请求追踪日志
+    pub tracing: bool,
+}
+
+impl Default for HttpMiddlewareConfig {
+    fn default() -> Self {
+        Self {
+            cors: CorsConfig::default(),
+            compression: true,
+            rate_limit: HttpRateLimitConfig::default(),
+            security_headers: SecurityHeadersConfig::default(),
+            tracing: true,
+        }
+    }
+}
+
+/// CORS 配置，允许的来源取自 `security.cors_origins`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    pub enabled: bool,
+    /// 仅在这些环境（`environment.name`）启用，为空时不限环境
+    pub environments: Vec<String>,
+    /// 是否允许携带凭据，允许任意来源时不生效
+    pub allow_credentials: bool,
+    /// 预检结果的缓存时间（秒）
+    pub max_age_secs: usize,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            environments: Vec::new(),
+            allow_credentials: false,
+            max_age_secs: 3600,
+        }
+    }
+}
+
+impl CorsConfig {
+    /// 在指定环境是否启用
+    pub fn enabled_in(&self, environment: &str) -> bool {
+        self.enabled
+            && (self.environments.is_empty()
+                || self.environments.iter().any(|name| name.eq_ignore_ascii_case(environment)))
+    }
+}
+
+/// 全局限流配置
+///
+/// 全局层只能识别客户端 IP 与全局配额，租户与 API 密钥维度的限制在认证之后生效。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpRateLimitConfig {
+    pub enabled: bool,
+    pub profile: RateLimitProfile,
+}
+
+impl Default for HttpRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            profile: RateLimitProfile::Lightweight,
+        }
+    }
+}
+
+/// 全局限流策略组合
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitProfile {
+    /// 全局、IP、租户、API 密钥与接口限流
+    Standard,
+    /// 仅 IP 与 API 密钥限流
+    Lightweight,
+}
+
+/// 安全响应头配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityHeadersConfig {
+    pub enabled: bool,
+    /// `Strict-Transport-Security` 的有效期（秒），0 表示不发送；仅应在 HTTPS 部署中开启
+    pub hsts_max_age_secs: u64,
+    /// `X-Frame-Options`，`DENY` 或 `SAMEORIGIN`
+    pub frame_options: String,
+    /// `Referrer-Policy`
+    pub referrer_policy: String,
+    /// `Content-Security-Policy`，为空时不发送
+    pub content_security_policy: Option<String>,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hsts_max_age_secs: 0,
+            frame_options: "DENY".to_string(),
+            referrer_policy: "no-referrer".to_string(),
+            content_security_policy: None,
+        }
+    }
+}
+
+/// 最低 TLS 版本
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum TlsMinVersion {
+    /// TLS 1.2
+    #[default]
+    #[serde(rename = "1.2")]
+    Tls12,
+    /// TLS 1.3
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+/// 进程角色
+///
+/// API 与后台处理可分开部署、独立扩缩容，两者共用同一份配置。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessRole {
+    /// 仅提供 HTTP API
+    Api,
+    /// 仅执行周期任务，HTTP 端口只提供健康检查
+    Worker,
+    /// 同时提供 API 与执行周期任务
+    #[default]
+    All,
+}
+
+impl ProcessRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProcessRole::Api => "api",
+            ProcessRole::Worker => "worker",
+            ProcessRole::All => "all",
+        }
+    }
+
+    /// 是否提供 HTTP API
+    pub fn serves_api(&self) -> bool {
+        matches!(self, ProcessRole::Api | ProcessRole::All)
+    }
+
+    /// 是否执行周期任务
+    pub fn runs_workers(&self) -> bool {
+        matches!(self, ProcessRole::Worker | ProcessRole::All)
+    }
+
+    /// 从命令行参数解析 `--role <role>` 或 `--role=<role>`，未指定时返回 None
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Self>, String> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = if arg == "--role" {
+                args.next().ok_or_else(|| "--role 缺少取值".to_string())?
+            } else if let Some(value) = arg.strip_prefix("--role=") {
+                value.to_string()
+            } else {
+                continue;
+            };
+            return value.parse().map(Some);
+        }
+        Ok(None)
+    }
+}
+
+impl std::str::FromStr for ProcessRole {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "api" => Ok(ProcessRole::Api),
+            "worker" => Ok(ProcessRole::Worker),
+            "all" => Ok(ProcessRole::All),
+            _ => Err(format!("无效的进程角色: {}（可选 api、worker、all）", value)),
+        }
+    }
+}
+
+/// 解析 CIDR，省略前缀长度时视为单个地址
+pub fn parse_cidr(value: &str) -> Result<(IpAddr, u8), String> {
+    let (address, prefix) = match value.trim().split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (value.trim(), None),
+    };
+    let address: IpAddr = address.parse().map_err(|_| format!("无效的网络地址: {}", value))?;
+    let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+    let prefix = match prefix {
+        Some(prefix) => prefix.parse::<u8>().ok().filter(|p| *p <= max_prefix)
+            .ok_or_else(|| format!("无效的前缀长度: {}", value))?,
+        None => max_prefix,
+    };
+    Ok((address, prefix))
+}
+
+fn cidr_contains(network: IpAddr, prefix: u8, ip: IpAddr) -> bool {
+    // IPv4 映射的 IPv6 地址按 IPv4 比较
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        ip => ip,
+    };
+    match (network, ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(network) & mask == u32::from(ip) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(ip)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(network) & mask == u128::from(ip) & mask
+        }
+        _ => false,
+    }
+}
+
+/// 服务等级目标配置
+///
+/// 按路径前缀将接口划分为若干类别，分别设定延迟与可用性目标，持续统计错误预算的消耗速度。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloConfig {
+    pub enabled: bool,
+    pub classes: Vec<SloClassConfig>,
+}
+
+/// 单个接口类别的服务等级目标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloClassConfig {
+    /// 类别名称
+    pub name: String,
+    /// 归入该类别的路径前缀，多个类别匹配时取最长前缀
+    pub path_prefixes: Vec<String>,
+    /// 延迟阈值（毫秒），超过即视为慢请求
+    pub latency_threshold_ms: u64,
+    /// 延迟目标：不超过阈值的请求占比
+    pub latency_target: f64,
+    /// 可用性目标：未返回 5xx 的请求占比
+    pub availability_target: f64,
+}
+
+/// 执行产物存储配置
+///
+/// 超过阈值的步骤输出与执行事件数据写入存储目录，数据库中仅保留指针，到期后连同文件一并清理。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionArtifactConfig {
+    pub enabled: bool,
+    /// 序列化后超过该字节数的输出转存为产物
+    pub inline_threshold_bytes: u64,
+    /// 产物保留天数，0 表示永久保留
+    pub retention_days: u32,
+    pub cleanup_interval_secs: u64,
+    pub cleanup_batch_size: u32,
+    /// 分页读取执行步骤时，单个步骤数据超过该字节数只返回预览，完整内容按需展开
+    #[serde(default = "default_trace_preview_bytes")]
+    pub trace_preview_bytes: u64,
+    /// 单页执行步骤数据的总字节上限，超出后提前结束本页
+    #[serde(default = "default_trace_page_bytes")]
+    pub trace_page_bytes: u64,
+}
+
+fn default_trace_preview_bytes() -> u64 {
+    8 * 1024
+}
+
+fn default_trace_page_bytes() -> u64 {
+    1024 * 1024
+}
+
+/// 计费配置
+///
+/// 各套餐的月费与超出包含额度后的单价，用于费用估算与套餐变更模拟。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillingConfig {
+    pub currency: String,
+    pub free: PlanPricing,
+    pub standard: PlanPricing,
+    pub enterprise: PlanPricing,
+}
+
+/// 套餐定价
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanPricing {
+    /// 月费
+    pub monthly_fee: f64,
+    /// 每月包含的 token 数
+    pub included_tokens: u64,
+    /// 超出部分每千 token 单价
+    pub price_per_1k_tokens: f64,
+    /// 每月包含的执行次数（工作流与 Agent）
+    pub included_executions: u64,
+    /// 超出部分每次执行单价
+    pub price_per_execution: f64,
+    /// 包含的文档数
+    pub included_documents: u64,
+    /// 超出部分每千文档单价
+    pub price_per_1k_documents: f64,
+}
+
+/// SMTP 邮件配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    pub from_address: String,
+    /// 是否使用 TLS（465 端口隐式 TLS 或 STARTTLS）
+    #[serde(default = "default_smtp_tls")]
+    pub use_tls: bool,
+}
+
+fn default_smtp_tls() -> bool {
+    true
 }
 
 /// 环境配置
@@ -145,6 +887,19 @@ impl AppConfig {
         Ok(app_config)
     }
 
+    /// 从指定配置文件加载配置（不读取环境变量），用于对比不同环境的配置
+    pub fn load_from_file(path: &Path) -> Result<Self, ConfigError> {
+        let config = Config::builder()
+            .add_source(Config::try_from(&AppConfig::default())?)
+            .add_source(File::from(path))
+            .build()?;
+
+        let mut app_config: AppConfig = config.try_deserialize()?;
+        app_config.environment.version = env!("CARGO_PKG_VERSION").to_string();
+
+        Ok(app_config)
+    }
+
     /// 验证配置
     pub fn validate(&self) -> Result<(), CommonError> {
         use crate::config::ConfigValidator;
@@ -188,6 +943,9 @@ impl Default for AppConfig {
                 keep_alive: 75,
                 client_timeout: 5000,
                 client_shutdown: 5000,
+                request_timeout_ms: default_request_timeout_ms(),
+                max_request_timeout_ms: default_max_request_timeout_ms(),
+                role: ProcessRole::default(),
             },
             database: DatabaseConfig {
                 url: "postgresql://localhost/aionix".to_string(),
@@ -204,6 +962,7 @@ impl Default for AppConfig {
                 temperature: 0.7,
                 timeout: 30,
                 retry_attempts: 3,
+                local: None,
             },
             #[cfg(feature = "redis")]
             redis: RedisConfig {
@@ -219,6 +978,9 @@ impl Default for AppConfig {
                 cors_origins: vec!["*".to_string()],
                 rate_limit_requests: 100,
                 rate_limit_window: 60,
+                encryption_key: "your-super-secret-encryption-key-change-this-in-production".to_string(),
+                plugin_trusted_keys: Vec::new(),
+                kms: None,
             },
             storage: StorageConfig {
                 path: "./storage".to_string(),
@@ -245,12 +1007,125 @@ impl Default for AppConfig {
                 index_type: "hnsw".to_string(),
                 ef_construction: 200,
                 m: 16,
+                qdrant: None,
+                milvus: None,
+            },
+            retention: RetentionConfig {
+                enabled: true,
+                interval_secs: 3600,
+                batch_size: 1000,
+                sessions_days: 30,
+                agent_executions_days: 90,
+                workflow_executions_days: 90,
+                audit_logs_days: 365,
+            },
+            replication: ReplicationConfig {
+                enabled: false,
+                region: "default".to_string(),
+                role: ReplicationRole::Primary,
+                queue_url: None,
+                stream: "aionix:replication".to_string(),
+                interval_secs: 5,
+                batch_size: 500,
+                max_lag_secs: 60,
+            },
+            cache: CacheConfig {
+                enabled: true,
+                backend: CacheBackendKind::Memory,
+                ttl_secs: 300,
+                max_entries: 10000,
+                key_prefix: "aionix:cache".to_string(),
+            },
+            usage_anomaly: UsageAnomalyConfig {
+                enabled: true,
+                interval_secs: 300,
+                baseline_hours: 168,
+                min_history_hours: 24,
+                sensitivity: 3.0,
+                min_ratio: 3.0,
+                min_tokens: 50000,
+                min_failed_logins: 20,
+                min_document_deletions: 50,
+                auto_throttle: false,
+                throttle_minutes: 60,
+            },
+            ingest_throttle: IngestThrottleConfig::default(),
+            health: HealthAccessConfig::default(),
+            webauthn: WebAuthnConfig::default(),
+            elevation: ElevationConfig::default(),
+            egress: EgressConfig::default(),
+            http: HttpMiddlewareConfig::default(),
+            slo: SloConfig {
+                enabled: true,
+                classes: vec![
+                    SloClassConfig {
+                        name: "qa".to_string(),
+                        path_prefixes: vec!["/api/v1/qa".to_string()],
+                        latency_threshold_ms: 8000,
+                        latency_target: 0.95,
+                        availability_target: 0.995,
+                    },
+                    SloClassConfig {
+                        name: "agents".to_string(),
+                        path_prefixes: vec!["/api/v1/agents".to_string(), "/api/v1/workflows".to_string()],
+                        latency_threshold_ms: 15000,
+                        latency_target: 0.9,
+                        availability_target: 0.99,
+                    },
+                    SloClassConfig {
+                        name: "api".to_string(),
+                        path_prefixes: vec!["/api/v1".to_string()],
+                        latency_threshold_ms: 1000,
+                        latency_target: 0.99,
+                        availability_target: 0.999,
+                    },
+                ],
+            },
+            execution_artifacts: ExecutionArtifactConfig {
+                enabled: true,
+                inline_threshold_bytes: 64 * 1024,
+                retention_days: 30,
+                cleanup_interval_secs: 3600,
+                cleanup_batch_size: 500,
+                trace_preview_bytes: default_trace_preview_bytes(),
+                trace_page_bytes: default_trace_page_bytes(),
+            },
+            billing: BillingConfig {
+                currency: "USD".to_string(),
+                free: PlanPricing {
+                    monthly_fee: 0.0,
+                    included_tokens: 100_000,
+                    price_per_1k_tokens: 0.0,
+                    included_executions: 100,
+                    price_per_execution: 0.0,
+                    included_documents: 100,
+                    price_per_1k_documents: 0.0,
+                },
+                standard: PlanPricing {
+                    monthly_fee: 99.0,
+                    included_tokens: 2_000_000,
+                    price_per_1k_tokens: 0.002,
+                    included_executions: 5_000,
+                    price_per_execution: 0.01,
+                    included_documents: 1_000,
+                    price_per_1k_documents: 5.0,
+                },
+                enterprise: PlanPricing {
+                    monthly_fee: 999.0,
+                    included_tokens: 50_000_000,
+                    price_per_1k_tokens: 0.0015,
+                    included_executions: 200_000,
+                    price_per_execution: 0.005,
+                    included_documents: 100_000,
+                    price_per_1k_documents: 2.0,
+                },
             },
             environment: EnvironmentConfig {
                 name: "development".to_string(),
                 debug: true,
                 version: "0.1.0".to_string(),
             },
+            smtp: None,
         }
     }
 }
\ No newline at end of file
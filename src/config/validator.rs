@@ -12,45 +12,10 @@ pub struct ConfigValidator;
 impl ConfigValidator {
     /// 验证完整配置
     pub fn validate_all(config: &AppConfig) -> Result<(), Vec<CommonError>> {
-        let mut errors = Vec::new();
-
-        // 验证各个模块
-        if let Err(e) = Self::validate_server(&config.server) {
-            errors.push(e);
-        }
-
-        if let Err(e) = Self::validate_database(&config.database) {
-            errors.push(e);
-        }
-
-        if let Err(e) = Self::validate_ai(&config.ai) {
-            errors.push(e);
-        }
-
-        #[cfg(feature = "redis")]
-        if let Err(e) = Self::validate_redis(&config.redis) {
-            errors.push(e);
-        }
-
-        if let Err(e) = Self::validate_security(&config.security) {
-            errors.push(e);
-        }
-
-        if let Err(e) = Self::validate_storage(&config.storage) {
-            errors.push(e);
-        }
-
-        if let Err(e) = Self::validate_logging(&config.logging) {
-            errors.push(e);
-        }
-
-        if let Err(e) = Self::validate_vector(&config.vector) {
-            errors.push(e);
-        }
-
-        if let Err(e) = Self::validate_environment(&config.environment) {
-            errors.push(e);
-        }
+        let errors: Vec<CommonError> = Self::validate_sections(config)
+            .into_iter()
+            .filter_map(|(_, result)| result.err())
+            .collect();
 
         if errors.is_empty() {
             Ok(())
@@ -59,6 +24,45 @@ impl ConfigValidator {
         }
     }
 
+    /// 按配置段验证，返回每段的名称与验证结果
+    pub fn validate_sections(config: &AppConfig) -> Vec<(&'static str, Result<(), CommonError>)> {
+        let mut results = vec![
+            ("server", Self::validate_server(&config.server)),
+            ("database", Self::validate_database(&config.database)),
+            ("ai", Self::validate_ai(&config.ai)),
+        ];
+
+        #[cfg(feature = "redis")]
+        results.push(("redis", Self::validate_redis(&config.redis)));
+
+        results.extend([
+            ("security", Self::validate_security(&config.security)),
+            ("storage", Self::validate_storage(&config.storage)),
+            ("logging", Self::validate_logging(&config.logging)),
+            ("vector", Self::validate_vector(&config.vector)),
+            ("retention", Self::validate_retention(&config.retention)),
+            ("replication", Self::validate_replication(&config.replication)),
+            ("cache", Self::validate_cache(&config.cache)),
+            ("usage_anomaly", Self::validate_usage_anomaly(&config.usage_anomaly)),
+            ("ingest_throttle", Self::validate_ingest_throttle(&config.ingest_throttle)),
+            ("health", Self::validate_health(&config.health)),
+            ("webauthn", Self::validate_webauthn(&config.webauthn)),
+            ("elevation", Self::validate_elevation(&config.elevation)),
+            ("egress", Self::validate_egress(&config.egress)),
+            ("http", Self::validate_http(&config.http)),
+            ("slo", Self::validate_slo(&config.slo)),
+            ("execution_artifacts", Self::validate_execution_artifacts(&config.execution_artifacts)),
+            ("billing", Self::validate_billing(&config.billing)),
+            ("environment", Self::validate_environment(&config.environment)),
+        ]);
+
+        if let Some(smtp) = &config.smtp {
+            results.push(("smtp", Self::validate_smtp(smtp)));
+        }
+
+        results
+    }
+
     /// 验证服务器配置
     pub fn validate_server(config: &crate::config::ServerConfig) -> Result<(), CommonError> {
         if config.port == 0 {
@@ -82,6 +86,14 @@ impl ConfigValidator {
             }
         }
 
+        if config.request_timeout_ms == 0 {
+            return Err(CommonError::validation("请求默认截止时间不能为 0"));
+        }
+
+        if config.request_timeout_ms > config.max_request_timeout_ms {
+            return Err(CommonError::validation("请求默认截止时间不能超过最长截止时间"));
+        }
+
         Ok(())
     }
 
@@ -142,6 +154,46 @@ impl ConfigValidator {
             return Err(CommonError::validation("AI 重试次数不建议超过 10"));
         }
 
+        if let Some(local) = &config.local {
+            Self::validate_local_inference(local)?;
+        }
+
+        Ok(())
+    }
+
+    /// 验证本地推理配置
+    pub fn validate_local_inference(config: &crate::config::LocalInferenceConfig) -> Result<(), CommonError> {
+        if Url::parse(&config.base_url).is_err() {
+            return Err(CommonError::validation("本地推理服务地址 URL 格式无效"));
+        }
+
+        if let Some(embedding_base_url) = &config.embedding_base_url {
+            if Url::parse(embedding_base_url).is_err() {
+                return Err(CommonError::validation("本地嵌入服务地址 URL 格式无效"));
+            }
+        }
+
+        if config.model_path.trim().is_empty() {
+            return Err(CommonError::validation("本地推理模型路径不能为空"));
+        }
+
+        if config.embedding_model_path.as_deref().is_some_and(|p| p.trim().is_empty()) {
+            return Err(CommonError::validation("本地嵌入模型路径不能为空"));
+        }
+
+        let pool = &config.embedding_pool;
+        if pool.workers == 0 {
+            return Err(CommonError::validation("嵌入工作池至少需要 1 个工作协程"));
+        }
+
+        if pool.interactive_workers >= pool.workers {
+            return Err(CommonError::validation("交互专用工作协程数必须小于总工作协程数"));
+        }
+
+        if pool.max_batch_size == 0 || pool.max_batch_tokens == 0 || pool.queue_capacity == 0 {
+            return Err(CommonError::validation("嵌入工作池的批次大小、批次 token 上限和队列容量不能为 0"));
+        }
+
         Ok(())
     }
 
@@ -198,6 +250,48 @@ impl ConfigValidator {
             return Err(CommonError::validation("限流时间窗口不能为 0"));
         }
 
+        if config.encryption_key.len() < 32 {
+            return Err(CommonError::validation("数据加密密钥长度不能少于 32 个字符"));
+        }
+
+        for public_key in &config.plugin_trusted_keys {
+            if crate::plugins::plugin_signing::TrustedKey::new("deployment", public_key, None).is_err() {
+                return Err(CommonError::validation(
+                    format!("插件签名公钥无效（需为 base64 编码的 ed25519 公钥）: {}", public_key)
+                ));
+            }
+        }
+
+        if let Some(kms) = &config.kms {
+            if !kms.url.starts_with("http://") && !kms.url.starts_with("https://") {
+                return Err(CommonError::validation("KMS 地址必须以 http:// 或 https:// 开头"));
+            }
+            if kms.timeout_secs == 0 {
+                return Err(CommonError::validation("KMS 请求超时不能为 0"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 验证 SMTP 配置
+    pub fn validate_smtp(config: &crate::config::SmtpConfig) -> Result<(), CommonError> {
+        if config.host.trim().is_empty() {
+            return Err(CommonError::validation("SMTP 主机不能为空"));
+        }
+
+        if config.port == 0 {
+            return Err(CommonError::validation("SMTP 端口不能为 0"));
+        }
+
+        if !config.from_address.contains('@') {
+            return Err(CommonError::validation("SMTP 发件地址格式无效"));
+        }
+
+        if config.username.is_some() != config.password.is_some() {
+            return Err(CommonError::validation("SMTP 用户名和密码必须同时配置"));
+        }
+
         Ok(())
     }
 
@@ -295,6 +389,317 @@ impl ConfigValidator {
             return Err(CommonError::validation("HNSW m 参数不能为 0"));
         }
 
+        for (name, store) in [("qdrant", &config.qdrant), ("milvus", &config.milvus)] {
+            if let Some(store) = store {
+                if Url::parse(&store.url).is_err() {
+                    return Err(CommonError::validation(format!("无效的 {} 服务地址: {}", name, store.url)));
+                }
+                if store.collection_prefix.is_empty() {
+                    return Err(CommonError::validation(format!("{} 集合名前缀不能为空", name)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 验证数据保留配置
+    pub fn validate_retention(config: &crate::config::RetentionConfig) -> Result<(), CommonError> {
+        if config.enabled && config.interval_secs == 0 {
+            return Err(CommonError::validation("数据清理间隔不能为 0"));
+        }
+
+        if config.batch_size == 0 {
+            return Err(CommonError::validation("数据清理批次大小不能为 0"));
+        }
+
+        if config.batch_size > 100000 {
+            return Err(CommonError::validation("数据清理批次大小不建议超过 100000"));
+        }
+
+        Ok(())
+    }
+
+    /// 验证跨区域复制配置
+    pub fn validate_replication(config: &crate::config::ReplicationConfig) -> Result<(), CommonError> {
+        if !config.enabled {
+            return Ok(());
+        }
+
+        if config.region.trim().is_empty() {
+            return Err(CommonError::validation("复制区域标识不能为空"));
+        }
+
+        if config.stream.trim().is_empty() {
+            return Err(CommonError::validation("复制事件流名称不能为空"));
+        }
+
+        if config.interval_secs == 0 {
+            return Err(CommonError::validation("复制间隔不能为 0"));
+        }
+
+        if config.batch_size == 0 || config.batch_size > 10000 {
+            return Err(CommonError::validation("复制批次大小必须在 1 到 10000 之间"));
+        }
+
+        if config.max_lag_secs < config.interval_secs {
+            return Err(CommonError::validation("最大复制延迟不能小于复制间隔"));
+        }
+
+        Ok(())
+    }
+
+    /// 验证缓存配置
+    pub fn validate_cache(config: &crate::config::CacheConfig) -> Result<(), CommonError> {
+        if !config.enabled {
+            return Ok(());
+        }
+
+        if config.ttl_secs == 0 {
+            return Err(CommonError::validation("缓存存活时间不能为 0"));
+        }
+
+        if config.max_entries == 0 {
+            return Err(CommonError::validation("缓存最大条目数不能为 0"));
+        }
+
+        if config.key_prefix.trim().is_empty() {
+            return Err(CommonError::validation("缓存键前缀不能为空"));
+        }
+
+        Ok(())
+    }
+
+    /// 验证执行产物存储配置
+    pub fn validate_execution_artifacts(config: &crate::config::ExecutionArtifactConfig) -> Result<(), CommonError> {
+        if config.trace_preview_bytes < 256 {
+            return Err(CommonError::validation("执行步骤预览大小不能小于 256 字节"));
+        }
+
+        if config.trace_page_bytes < config.trace_preview_bytes {
+            return Err(CommonError::validation("执行步骤单页大小不能小于预览大小"));
+        }
+
+        if !config.enabled {
+            return Ok(());
+        }
+
+        if config.inline_threshold_bytes < 1024 {
+            return Err(CommonError::validation("执行产物转存阈值不能小于 1024 字节"));
+        }
+
+        if config.cleanup_interval_secs == 0 {
+            return Err(CommonError::validation("执行产物清理间隔不能为 0"));
+        }
+
+        if config.cleanup_batch_size == 0 || config.cleanup_batch_size > 10000 {
+            return Err(CommonError::validation("执行产物清理批次大小必须在 1 到 10000 之间"));
+        }
+
+        Ok(())
+    }
+
+    /// 验证计费配置
+    pub fn validate_billing(config: &crate::config::BillingConfig) -> Result<(), CommonError> {
+        if config.currency.trim().is_empty() {
+            return Err(CommonError::validation("计费币种不能为空"));
+        }
+
+        for (plan, pricing) in [("free", &config.free), ("standard", &config.standard), ("enterprise", &config.enterprise)] {
+            let prices = [
+                pricing.monthly_fee,
+                pricing.price_per_1k_tokens,
+                pricing.price_per_execution,
+                pricing.price_per_1k_documents,
+            ];
+            if prices.iter().any(|price| !price.is_finite() || *price < 0.0) {
+                return Err(CommonError::validation(format!("套餐 {} 的价格必须是非负数", plan)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 验证用量异常检测配置
+    pub fn validate_usage_anomaly(config: &crate::config::UsageAnomalyConfig) -> Result<(), CommonError> {
+        if !config.enabled {
+            return Ok(());
+        }
+
+        if config.interval_secs == 0 {
+            return Err(CommonError::validation("用量异常检测间隔不能为 0"));
+        }
+
+        if config.baseline_hours == 0 || config.baseline_hours > 24 * 90 {
+            return Err(CommonError::validation("用量基线小时数必须在 1 到 2160 之间"));
+        }
+
+        if config.min_history_hours > config.baseline_hours {
+            return Err(CommonError::validation("最少历史小时数不能超过基线小时数"));
+        }
+
+        if config.sensitivity <= 0.0 || config.min_ratio < 1.0 {
+            return Err(CommonError::validation("异常灵敏度必须大于 0，最小倍数不能小于 1"));
+        }
+
+        if config.auto_throttle && config.throttle_minutes == 0 {
+            return Err(CommonError::validation("自动限流时长不能为 0"));
+        }
+
+        Ok(())
+    }
+
+    /// 验证导入类接口限流配置
+    pub fn validate_ingest_throttle(config: &crate::config::IngestThrottleConfig) -> Result<(), CommonError> {
+        if !config.enabled {
+            return Ok(());
+        }
+
+        if config.burst == 0 {
+            return Err(CommonError::validation("导入限流的桶容量不能为 0"));
+        }
+
+        if config.refill_per_second <= 0.0 {
+            return Err(CommonError::validation("导入限流的令牌补充速率必须大于 0"));
+        }
+
+        if config.batch_cost == 0 || config.batch_cost > config.burst {
+            return Err(CommonError::validation("批量请求消耗的令牌数必须在 1 到桶容量之间"));
+        }
+
+        Ok(())
+    }
+
+    /// 验证详细健康检查访问配置
+    pub fn validate_health(config: &crate::config::HealthAccessConfig) -> Result<(), CommonError> {
+        for network in &config.internal_networks {
+            crate::config::parse_cidr(network).map_err(CommonError::validation)?;
+        }
+
+        if config.ops_roles.iter().any(|role| role.trim().is_empty()) {
+            return Err(CommonError::validation("运维角色名称不能为空"));
+        }
+
+        Ok(())
+    }
+
+    /// 验证通行密钥依赖方配置
+    pub fn validate_webauthn(config: &crate::config::WebAuthnConfig) -> Result<(), CommonError> {
+        let rp_id = config.rp_id.trim();
+        if rp_id.is_empty() || rp_id.contains("://") || rp_id.contains('/') || rp_id.contains(':') {
+            return Err(CommonError::validation("WebAuthn rp_id 必须是不含协议与端口的域名"));
+        }
+
+        if config.origins.is_empty() {
+            return Err(CommonError::validation("WebAuthn 至少需要配置一个允许的来源"));
+        }
+        for origin in &config.origins {
+            let url = url::Url::parse(origin)
+                .map_err(|_| CommonError::validation(format!("无效的 WebAuthn 来源: {}", origin)))?;
+            let host = url.host_str().unwrap_or_default();
+            if host != rp_id && !host.ends_with(&format!(".{}", rp_id)) {
+                return Err(CommonError::validation(format!("WebAuthn 来源 {} 不属于依赖方 {}", origin, rp_id)));
+            }
+            if url.scheme() != "https" && host != "localhost" {
+                return Err(CommonError::validation(format!("WebAuthn 来源 {} 必须使用 https", origin)));
+            }
+        }
+
+        if !(30..=3600).contains(&config.challenge_ttl_secs) {
+            return Err(CommonError::validation("WebAuthn 挑战有效期必须在 30 到 3600 秒之间"));
+        }
+
+        Ok(())
+    }
+
+    /// 验证临时管理员提权配置
+    pub fn validate_elevation(config: &crate::config::ElevationConfig) -> Result<(), CommonError> {
+        if !(1..=72).contains(&config.max_hours) {
+            return Err(CommonError::validation("提权最长时长必须在 1 到 72 小时之间"));
+        }
+
+        if !(1..=168).contains(&config.request_ttl_hours) {
+            return Err(CommonError::validation("提权申请审批时限必须在 1 到 168 小时之间"));
+        }
+
+        Ok(())
+    }
+
+    /// 验证出站网络配置
+    pub fn validate_egress(config: &crate::config::EgressConfig) -> Result<(), CommonError> {
+        if let Some(proxy_url) = &config.proxy_url {
+            let url = url::Url::parse(proxy_url)
+                .map_err(|_| CommonError::validation(format!("无效的出站代理地址: {}", proxy_url)))?;
+            if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+                return Err(CommonError::validation("出站代理地址必须是 http 或 https 地址"));
+            }
+        }
+
+        if config.no_proxy.iter().any(|host| host.trim().is_empty()) {
+            return Err(CommonError::validation("直连主机不能为空"));
+        }
+
+        if let Some(path) = &config.ca_bundle_path {
+            if !std::path::Path::new(path).is_file() {
+                return Err(CommonError::validation(format!("CA 证书包不存在: {}", path)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 验证 HTTP 中间件栈配置
+    pub fn validate_http(config: &crate::config::HttpMiddlewareConfig) -> Result<(), CommonError> {
+        if config.cors.max_age_secs > 86400 {
+            return Err(CommonError::validation("CORS 预检缓存时间不能超过 86400 秒"));
+        }
+
+        let headers = &config.security_headers;
+        if !matches!(headers.frame_options.as_str(), "DENY" | "SAMEORIGIN") {
+            return Err(CommonError::validation("X-Frame-Options 只能为 DENY 或 SAMEORIGIN"));
+        }
+
+        let invalid_header = |value: &str| value.trim().is_empty() || !value.chars().all(|c| c.is_ascii() && !c.is_ascii_control());
+        if invalid_header(&headers.referrer_policy) {
+            return Err(CommonError::validation("Referrer-Policy 不能为空且只能包含可见 ASCII 字符"));
+        }
+        if headers.content_security_policy.as_deref().is_some_and(invalid_header) {
+            return Err(CommonError::validation("Content-Security-Policy 不能为空且只能包含可见 ASCII 字符"));
+        }
+
+        Ok(())
+    }
+
+    /// 验证服务等级目标配置
+    pub fn validate_slo(config: &crate::config::SloConfig) -> Result<(), CommonError> {
+        if !config.enabled {
+            return Ok(());
+        }
+
+        let mut names = std::collections::HashSet::new();
+        for class in &config.classes {
+            if class.name.trim().is_empty() {
+                return Err(CommonError::validation("SLO 类别名称不能为空"));
+            }
+
+            if !names.insert(class.name.as_str()) {
+                return Err(CommonError::validation(format!("SLO 类别名称重复: {}", class.name)));
+            }
+
+            if class.path_prefixes.is_empty() || class.path_prefixes.iter().any(|p| !p.starts_with('/')) {
+                return Err(CommonError::validation(format!("SLO 类别 {} 的路径前缀必须以 / 开头且不能为空", class.name)));
+            }
+
+            if class.latency_threshold_ms == 0 {
+                return Err(CommonError::validation(format!("SLO 类别 {} 的延迟阈值不能为 0", class.name)));
+            }
+
+            let valid_target = |target: f64| target > 0.0 && target < 1.0;
+            if !valid_target(class.latency_target) || !valid_target(class.availability_target) {
+                return Err(CommonError::validation(format!("SLO 类别 {} 的目标必须在 0 到 1 之间（不含端点）", class.name)));
+            }
+        }
+
         Ok(())
     }
 
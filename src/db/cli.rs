@@ -1,11 +1,21 @@
 // 数据库管理 CLI 工具
 // 提供迁移、备份、恢复等命令行功能
 
-use crate::config::AppConfig;
-use crate::db::migrations::{MigrationManager, SeedDataManager, BackupManager, BackupType, RestoreOptions};
+use crate::ai::vector_store::VectorStoreRegistry;
+use crate::config::{AppConfig, ConfigCheckStatus, ConfigDiagnostics, ConfigDiffEntry};
+use crate::db::entities::knowledge_base::VectorBackend;
+use crate::db::migrations::{
+    Migration, MigrationManager, MigrationReport, MigrationState, SchemaValidation,
+    SeedDataManager, BackupManager, BackupType, RestoreOptions,
+};
 use crate::errors::AiStudioError;
+use crate::services::agent_definition::{AgentDefinitionService, DefinitionFormat};
+use crate::services::manifest::{ChangeAction, Manifest, ManifestPlan, ManifestService};
+use crate::services::vector_migration::VectorMigrator;
 use sea_orm::{Database, DatabaseConnection};
+use std::io::{self, BufRead, Write};
 use std::path::PathBuf;
+use std::sync::Arc;
 use tracing::info;
 use uuid::Uuid;
 
@@ -18,6 +28,67 @@ pub enum CliCommand {
     Seed(SeedCommand),
     /// 备份相关命令
     Backup(BackupCommand),
+    /// 配置相关命令，不需要数据库连接即可执行
+    Config(ConfigCommand),
+    /// Agent 定义文件导入导出
+    Agent(AgentCommand),
+    /// 期望状态清单
+    Manifest(ManifestCommand),
+    /// 知识库向量存储
+    Vector(VectorCommand),
+}
+
+/// 知识库向量存储命令
+#[derive(Debug, Clone)]
+pub enum VectorCommand {
+    /// 将知识库的向量迁移到目标后端，迁移完成后切换知识库配置
+    Migrate {
+        knowledge_base_id: Uuid,
+        target: VectorBackend,
+        keep_source: bool,
+    },
+}
+
+/// 期望状态清单命令
+#[derive(Debug, Clone)]
+pub enum ManifestCommand {
+    /// 对比清单与数据库现状，不做修改
+    Plan { file: PathBuf },
+    /// 应用清单，新建资源记录为指定用户创建
+    Apply { file: PathBuf, user_id: Uuid },
+}
+
+/// Agent 定义文件命令
+#[derive(Debug, Clone)]
+pub enum AgentCommand {
+    /// 导出 Agent 定义，未指定输出文件时打印到标准输出
+    Export {
+        tenant_id: Uuid,
+        name: String,
+        output: Option<PathBuf>,
+        format: DefinitionFormat,
+    },
+    /// 导入 Agent 定义，同名 Agent 存在时更新
+    Import {
+        tenant_id: Uuid,
+        user_id: Uuid,
+        file: PathBuf,
+    },
+}
+
+/// 配置命令
+#[derive(Debug, Clone)]
+pub enum ConfigCommand {
+    /// 验证当前配置并检查依赖服务连通性，可与另一环境的配置文件对比
+    Validate {
+        offline: bool,
+        compare: Option<PathBuf>,
+    },
+    /// 对比两个配置文件
+    Diff {
+        left: PathBuf,
+        right: PathBuf,
+    },
 }
 
 /// 迁移命令
@@ -35,6 +106,19 @@ pub enum MigrationCommand {
     Reset,
     /// 验证数据库架构
     Validate,
+    /// 重新记录校验和不一致的迁移
+    Repair { versions: Vec<String>, assume_yes: bool },
+    /// 验证数据库架构并给出修复建议
+    VerifySchema,
+}
+
+/// 架构问题及修复建议
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaFix {
+    /// 问题描述
+    pub problem: String,
+    /// 修复建议
+    pub suggestion: String,
 }
 
 /// 种子数据命令
@@ -101,7 +185,85 @@ impl CliExecutor {
             CliCommand::Migration(cmd) => self.execute_migration_command(cmd).await,
             CliCommand::Seed(cmd) => self.execute_seed_command(cmd).await,
             CliCommand::Backup(cmd) => self.execute_backup_command(cmd).await,
+            CliCommand::Config(cmd) => execute_config_command(&self.config, cmd).await,
+            CliCommand::Agent(cmd) => self.execute_agent_command(cmd).await,
+            CliCommand::Manifest(cmd) => self.execute_manifest_command(cmd).await,
+            CliCommand::Vector(cmd) => self.execute_vector_command(cmd).await,
+        }
+    }
+
+    /// 执行向量存储命令
+    async fn execute_vector_command(&self, command: VectorCommand) -> Result<(), AiStudioError> {
+        match command {
+            VectorCommand::Migrate { knowledge_base_id, target, keep_source } => {
+                let stores = Arc::new(VectorStoreRegistry::new(self.db.clone(), self.config.vector.clone()));
+                println!("🔄 正在将知识库 {} 的向量迁移到 {}...", knowledge_base_id, target.as_str());
+                let summary = VectorMigrator::new(self.db.clone(), stores)
+                    .migrate(knowledge_base_id, target, keep_source)
+                    .await?;
+                println!(
+                    "✅ 已从 {} 迁移 {} 条向量到 {}{}",
+                    summary.source.as_str(),
+                    summary.migrated,
+                    summary.target.as_str(),
+                    if summary.source_dropped { "，原后端数据已删除" } else { "，原后端数据已保留" }
+                );
+                if summary.missing > 0 {
+                    println!("⚠️  {} 条嵌入在原后端缺少向量，需要重新生成", summary.missing);
+                }
+            }
         }
+
+        Ok(())
+    }
+
+    /// 执行期望状态清单命令
+    async fn execute_manifest_command(&self, command: ManifestCommand) -> Result<(), AiStudioError> {
+        let service = ManifestService::new(self.db.clone(), &self.config.security.encryption_key);
+
+        let plan = match command {
+            ManifestCommand::Plan { file } => service.plan(&read_manifest(&file)?).await?,
+            ManifestCommand::Apply { file, user_id } => service.apply(&read_manifest(&file)?, user_id).await?,
+        };
+        print_manifest_plan(&plan);
+
+        Ok(())
+    }
+
+    /// 执行 Agent 定义文件命令
+    async fn execute_agent_command(&self, command: AgentCommand) -> Result<(), AiStudioError> {
+        let service = AgentDefinitionService::new(self.db.clone());
+
+        match command {
+            AgentCommand::Export { tenant_id, name, output, format } => {
+                let definition = service.export_by_name(tenant_id, &name).await?;
+                let format = output.as_deref().map(DefinitionFormat::from_path).unwrap_or(format);
+                let content = format.render(&definition)?;
+                match output {
+                    Some(path) => {
+                        std::fs::write(&path, content)
+                            .map_err(|e| AiStudioError::internal(format!("写入 {} 失败: {}", path.display(), e)))?;
+                        println!("✅ 已导出 Agent '{}' 到 {}", name, path.display());
+                    }
+                    None => print!("{}", content),
+                }
+            }
+            AgentCommand::Import { tenant_id, user_id, file } => {
+                let content = std::fs::read_to_string(&file)
+                    .map_err(|e| AiStudioError::validation("file", format!("读取 {} 失败: {}", file.display(), e)))?;
+                let definition = DefinitionFormat::from_path(&file).parse(&content)?;
+                let result = service.import(tenant_id, user_id, definition).await?;
+                println!(
+                    "✅ 已{} Agent '{}' (ID: {}, 修订号: {})",
+                    if result.created { "创建" } else { "更新" },
+                    result.name,
+                    result.agent_id,
+                    result.revision
+                );
+            }
+        }
+
+        Ok(())
     }
 
     /// 执行迁移命令
@@ -116,26 +278,47 @@ impl CliExecutor {
             }
             MigrationCommand::Status => {
                 info!("检查迁移状态...");
-                let status = manager.check_status().await?;
+                let report = manager.status_report().await?;
                 
                 println!("📊 迁移状态:");
-                println!("{:<20} {:<30} {:<15} {:<20}", "版本", "名称", "状态", "应用时间");
-                println!("{}", "-".repeat(85));
+                println!("{:<20} {:<36} {:<18} {:<20}", "版本", "名称", "状态", "应用时间");
+                println!("{}", "-".repeat(96));
                 
-                for migration in status {
-                    let status_str = if migration.is_applied { "✅ 已应用" } else { "⏳ 待应用" };
-                    let applied_at = migration.applied_at
+                for entry in &report {
+                    let applied_at = entry.applied_at
                         .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
                         .unwrap_or_else(|| "-".to_string());
                     
                     println!(
-                        "{:<20} {:<30} {:<15} {:<20}",
-                        migration.version,
-                        migration.name,
-                        status_str,
+                        "{:<20} {:<36} {:<18} {:<20}",
+                        entry.version,
+                        entry.name,
+                        state_label(entry.state),
                         applied_at
                     );
                 }
+                
+                let count = |state| report.iter().filter(|r| r.state == state).count();
+                println!();
+                println!(
+                    "已应用 {}，待应用 {}，校验和不一致 {}，未知 {}",
+                    count(MigrationState::Applied),
+                    count(MigrationState::Pending),
+                    count(MigrationState::ChecksumMismatch),
+                    count(MigrationState::Unknown)
+                );
+                
+                for entry in report.iter().filter(|r| r.state == MigrationState::ChecksumMismatch) {
+                    println!(
+                        "  ⚠️  {} 记录的校验和 {} 与当前脚本 {} 不一致",
+                        entry.version,
+                        short_checksum(entry.recorded_checksum.as_deref()),
+                        short_checksum(entry.expected_checksum.as_deref())
+                    );
+                }
+                if count(MigrationState::ChecksumMismatch) > 0 {
+                    println!("  确认修改是有意为之后，运行 `aionix-db migration repair` 重新记录校验和");
+                }
             }
             MigrationCommand::Migrate => {
                 info!("应用迁移...");
@@ -191,6 +374,64 @@ impl CliExecutor {
                     }
                 }
             }
+            MigrationCommand::Repair { versions, assume_yes } => {
+                info!("修复迁移校验和...");
+                let report = manager.status_report().await?;
+                let targets: Vec<&MigrationReport> = report
+                    .iter()
+                    .filter(|r| r.state == MigrationState::ChecksumMismatch)
+                    .filter(|r| versions.is_empty() || versions.contains(&r.version))
+                    .collect();
+                
+                if targets.is_empty() {
+                    println!("✅ 没有需要修复的迁移");
+                    return Ok(());
+                }
+                
+                println!("以下迁移的脚本在应用后被修改，将按当前脚本重新记录校验和:");
+                for entry in &targets {
+                    println!(
+                        "  - {} {} ({} -> {})",
+                        entry.version,
+                        entry.name,
+                        short_checksum(entry.recorded_checksum.as_deref()),
+                        short_checksum(entry.expected_checksum.as_deref())
+                    );
+                }
+                println!("⚠️  重新记录不会重新执行迁移，请先确认数据库结构已与修改后的脚本一致");
+                
+                if !assume_yes && !confirm("确认重新记录以上迁移的校验和？[y/N] ")? {
+                    println!("已取消");
+                    return Ok(());
+                }
+                
+                let targets: Vec<String> = targets.iter().map(|r| r.version.clone()).collect();
+                let repaired = manager.repair_checksums(&targets).await?;
+                println!("✅ 已重新记录 {} 个迁移的校验和:", repaired.len());
+                for version in repaired {
+                    println!("  - {}", version);
+                }
+            }
+            MigrationCommand::VerifySchema => {
+                info!("验证数据库架构...");
+                let validation = manager.validate_schema().await?;
+                let report = manager.status_report().await?;
+                let fixes = schema_fix_report(&validation, &manager.get_available_migrations(), &report);
+                
+                if fixes.is_empty() {
+                    println!("✅ 数据库架构验证通过，迁移记录一致");
+                    return Ok(());
+                }
+                
+                println!("❌ 发现 {} 个问题:", fixes.len());
+                for (index, fix) in fixes.iter().enumerate() {
+                    println!();
+                    println!("{}. {}", index + 1, fix.problem);
+                    println!("   修复: {}", fix.suggestion);
+                }
+                
+                return Err(AiStudioError::validation("schema", format!("数据库架构存在 {} 个问题", fixes.len())));
+            }
         }
 
         Ok(())
@@ -332,6 +573,188 @@ impl CliExecutor {
     }
 }
 
+/// 执行配置命令
+pub async fn execute_config_command(config: &AppConfig, command: ConfigCommand) -> Result<(), AiStudioError> {
+    match command {
+        ConfigCommand::Validate { offline, compare } => {
+            info!("验证配置...");
+            let report = ConfigDiagnostics::run(config, !offline).await;
+            
+            println!("🔍 配置验证（环境: {}）:", report.environment);
+            println!("{:<14} {:<24} {:<10} {:<10} {}", "类别", "对象", "结果", "耗时", "说明");
+            println!("{}", "-".repeat(100));
+            for check in &report.checks {
+                let status = match check.status {
+                    ConfigCheckStatus::Passed => "✅ 通过",
+                    ConfigCheckStatus::Warning => "⚠️  警告",
+                    ConfigCheckStatus::Failed => "❌ 失败",
+                    ConfigCheckStatus::Skipped => "⏭️  跳过",
+                };
+                let latency = check.latency_ms.map(|ms| format!("{}ms", ms)).unwrap_or_else(|| "-".to_string());
+                println!("{:<14} {:<24} {:<10} {:<10} {}", check.category, check.component, status, latency, check.message);
+            }
+            println!();
+            println!(
+                "通过 {}，警告 {}，失败 {}",
+                report.count(ConfigCheckStatus::Passed),
+                report.count(ConfigCheckStatus::Warning),
+                report.count(ConfigCheckStatus::Failed)
+            );
+            
+            if let Some(path) = compare {
+                let other = load_config_file(&path)?;
+                println!();
+                print_config_diff("当前配置", &path.display().to_string(), &crate::config::diff_settings(config, &other));
+            }
+            
+            if !report.is_valid {
+                return Err(AiStudioError::validation(
+                    "config",
+                    format!("配置存在 {} 个失败项", report.count(ConfigCheckStatus::Failed)),
+                ));
+            }
+        }
+        ConfigCommand::Diff { left, right } => {
+            let left_config = load_config_file(&left)?;
+            let right_config = load_config_file(&right)?;
+            print_config_diff(
+                &left.display().to_string(),
+                &right.display().to_string(),
+                &crate::config::diff_settings(&left_config, &right_config),
+            );
+        }
+    }
+    
+    Ok(())
+}
+
+/// 加载用于对比的配置文件
+fn load_config_file(path: &std::path::Path) -> Result<AppConfig, AiStudioError> {
+    AppConfig::load_from_file(path)
+        .map_err(|e| AiStudioError::validation("config", format!("加载配置文件 {} 失败: {}", path.display(), e)))
+}
+
+/// 打印脱敏后的配置差异
+fn print_config_diff(left_name: &str, right_name: &str, diff: &[ConfigDiffEntry]) {
+    if diff.is_empty() {
+        println!("✅ {} 与 {} 的配置一致", left_name, right_name);
+        return;
+    }
+    
+    println!("📋 配置差异（密钥已脱敏）: {} ↔ {}", left_name, right_name);
+    for entry in diff {
+        println!("  {}", entry.key);
+        println!("    - {}", entry.left.as_deref().unwrap_or("(未设置)"));
+        println!("    + {}", entry.right.as_deref().unwrap_or("(未设置)"));
+    }
+}
+
+/// 迁移状态的展示文字
+fn state_label(state: MigrationState) -> &'static str {
+    match state {
+        MigrationState::Applied => "✅ 已应用",
+        MigrationState::Pending => "⏳ 待应用",
+        MigrationState::ChecksumMismatch => "⚠️  校验和不一致",
+        MigrationState::Unknown => "❓ 未知迁移",
+    }
+}
+
+/// 截取校验和前 12 位用于展示
+fn short_checksum(checksum: Option<&str>) -> &str {
+    checksum.map(|c| &c[..c.len().min(12)]).unwrap_or("-")
+}
+
+/// 在终端中请求确认
+fn confirm(prompt: &str) -> Result<bool, AiStudioError> {
+    print!("{}", prompt);
+    io::stdout().flush().map_err(|e| AiStudioError::internal(format!("写入终端失败: {}", e)))?;
+    
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|e| AiStudioError::internal(format!("读取输入失败: {}", e)))?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// 根据架构验证结果与迁移状态生成修复建议
+pub fn schema_fix_report(
+    validation: &SchemaValidation,
+    migrations: &[Migration],
+    report: &[MigrationReport],
+) -> Vec<SchemaFix> {
+    let mut fixes = Vec::new();
+    
+    for table in &validation.missing_tables {
+        let creator = migrations.iter().find(|m| creates_table(&m.up_sql, table));
+        let state = creator.and_then(|m| report.iter().find(|r| r.version == m.version)).map(|r| r.state);
+        let suggestion = match (creator, state) {
+            (Some(migration), Some(MigrationState::Applied | MigrationState::ChecksumMismatch)) => format!(
+                "迁移 {} 已记录为已应用但表不存在，执行 `DELETE FROM schema_migrations WHERE version = '{}';` 后运行 `aionix-db migration migrate` 重新创建",
+                migration.version, migration.version
+            ),
+            (Some(migration), _) => format!("运行 `aionix-db migration migrate` 应用迁移 {} ({})", migration.version, migration.name),
+            (None, _) => "没有迁移创建该表，请检查迁移脚本或必需表列表".to_string(),
+        };
+        fixes.push(SchemaFix { problem: format!("缺失表 {}", table), suggestion });
+    }
+    
+    for column in &validation.missing_columns {
+        fixes.push(SchemaFix {
+            problem: format!("缺失列 {}", column),
+            suggestion: "运行 `aionix-db migration status` 检查是否有待应用或被修改的迁移".to_string(),
+        });
+    }
+    
+    for error in &validation.errors {
+        let suggestion = if error.contains("pgvector") {
+            "以超级用户执行 `CREATE EXTENSION IF NOT EXISTS vector;`".to_string()
+        } else {
+            "检查数据库日志".to_string()
+        };
+        fixes.push(SchemaFix { problem: error.clone(), suggestion });
+    }
+    
+    for entry in report {
+        let suggestion = match entry.state {
+            MigrationState::Applied => continue,
+            MigrationState::Pending => "运行 `aionix-db migration migrate`".to_string(),
+            MigrationState::ChecksumMismatch => format!(
+                "确认脚本修改已手动同步到数据库后运行 `aionix-db migration repair {}`",
+                entry.version
+            ),
+            MigrationState::Unknown => format!(
+                "确认该迁移不是由更新版本的程序应用的；如已废弃，执行 `DELETE FROM schema_migrations WHERE version = '{}';`",
+                entry.version
+            ),
+        };
+        fixes.push(SchemaFix {
+            problem: format!("迁移 {} ({}): {}", entry.version, entry.name, state_label(entry.state)),
+            suggestion,
+        });
+    }
+    
+    fixes
+}
+
+/// 判断迁移脚本是否创建了指定的表
+fn creates_table(sql: &str, table: &str) -> bool {
+    let normalized = sql.split_whitespace().collect::<Vec<_>>().join(" ").to_uppercase();
+    let table = table.to_uppercase();
+    [format!("CREATE TABLE {} ", table), format!("CREATE TABLE {}(", table),
+     format!("CREATE TABLE IF NOT EXISTS {} ", table), format!("CREATE TABLE IF NOT EXISTS {}(", table)]
+        .iter()
+        .any(|pattern| normalized.contains(pattern.as_str()))
+}
+
+/// 解析迁移修复命令的参数：版本列表与 `--yes`
+fn parse_repair_args(args: &[String]) -> MigrationCommand {
+    MigrationCommand::Repair {
+        versions: args.iter().filter(|a| !a.starts_with('-')).cloned().collect(),
+        assume_yes: args.iter().any(|a| a == "--yes" || a == "-y"),
+    }
+}
+
 /// 解析命令行参数
 pub fn parse_args(args: Vec<String>) -> Result<CliCommand, AiStudioError> {
     if args.len() < 2 {
@@ -356,11 +779,40 @@ pub fn parse_args(args: Vec<String>) -> Result<CliCommand, AiStudioError> {
                 }
                 "reset" => MigrationCommand::Reset,
                 "validate" => MigrationCommand::Validate,
+                "repair" => parse_repair_args(&args[3..]),
+                "verify-schema" => MigrationCommand::VerifySchema,
                 _ => return Err(AiStudioError::validation("migration", "未知的迁移子命令")),
             };
 
             Ok(CliCommand::Migration(subcommand))
         }
+        "config" => {
+            if args.len() < 3 {
+                return Err(AiStudioError::validation("config", "请提供配置子命令"));
+            }
+            
+            let subcommand = match args[2].as_str() {
+                "validate" => ConfigCommand::Validate {
+                    offline: args.contains(&"--offline".to_string()),
+                    compare: args.iter()
+                        .position(|a| a == "--compare")
+                        .and_then(|index| args.get(index + 1))
+                        .map(PathBuf::from),
+                },
+                "diff" => {
+                    if args.len() < 5 {
+                        return Err(AiStudioError::validation("config", "请提供要对比的两个配置文件"));
+                    }
+                    ConfigCommand::Diff { left: PathBuf::from(&args[3]), right: PathBuf::from(&args[4]) }
+                }
+                _ => return Err(AiStudioError::validation("config", "未知的配置子命令")),
+            };
+            
+            Ok(CliCommand::Config(subcommand))
+        }
+        "status" => Ok(CliCommand::Migration(MigrationCommand::Status)),
+        "repair" => Ok(CliCommand::Migration(parse_repair_args(&args[2..]))),
+        "verify-schema" => Ok(CliCommand::Migration(MigrationCommand::VerifySchema)),
         "seed" => {
             if args.len() < 3 {
                 return Err(AiStudioError::validation("seed", "请提供种子数据子命令"));
@@ -454,21 +906,157 @@ pub fn parse_args(args: Vec<String>) -> Result<CliCommand, AiStudioError> {
 
             Ok(CliCommand::Backup(subcommand))
         }
+        "agent" => {
+            if args.len() < 3 {
+                return Err(AiStudioError::validation("agent", "请提供 Agent 子命令"));
+            }
+
+            let subcommand = match args[2].as_str() {
+                "export" => {
+                    if args.len() < 5 {
+                        return Err(AiStudioError::validation("agent", "请提供租户 ID 和 Agent 名称"));
+                    }
+                    let format = match option_value(&args, "--format") {
+                        Some("json") => DefinitionFormat::Json,
+                        Some("yaml") | None => DefinitionFormat::Yaml,
+                        Some(_) => return Err(AiStudioError::validation("format", "格式应为 yaml 或 json")),
+                    };
+                    AgentCommand::Export {
+                        tenant_id: parse_uuid_arg(&args[3], "tenant_id")?,
+                        name: args[4].clone(),
+                        output: option_value(&args, "--output").map(PathBuf::from),
+                        format,
+                    }
+                }
+                "import" => {
+                    if args.len() < 6 {
+                        return Err(AiStudioError::validation("agent", "请提供租户 ID、用户 ID 和定义文件"));
+                    }
+                    AgentCommand::Import {
+                        tenant_id: parse_uuid_arg(&args[3], "tenant_id")?,
+                        user_id: parse_uuid_arg(&args[4], "user_id")?,
+                        file: PathBuf::from(&args[5]),
+                    }
+                }
+                _ => return Err(AiStudioError::validation("agent", "未知的 Agent 子命令")),
+            };
+
+            Ok(CliCommand::Agent(subcommand))
+        }
+        "manifest" => {
+            if args.len() < 4 {
+                return Err(AiStudioError::validation("manifest", "请提供清单子命令和清单文件"));
+            }
+
+            let file = PathBuf::from(&args[3]);
+            let subcommand = match args[2].as_str() {
+                "plan" => ManifestCommand::Plan { file },
+                "apply" => {
+                    let user_id = option_value(&args, "--as")
+                        .ok_or_else(|| AiStudioError::validation("manifest", "请通过 --as <user_id> 指定操作用户"))?;
+                    ManifestCommand::Apply {
+                        file,
+                        user_id: parse_uuid_arg(user_id, "user_id")?,
+                    }
+                }
+                _ => return Err(AiStudioError::validation("manifest", "未知的清单子命令")),
+            };
+
+            Ok(CliCommand::Manifest(subcommand))
+        }
+        "vector" => {
+            if args.len() < 3 {
+                return Err(AiStudioError::validation("vector", "请提供向量存储子命令"));
+            }
+
+            let subcommand = match args[2].as_str() {
+                "migrate" => {
+                    if args.len() < 5 {
+                        return Err(AiStudioError::validation("vector", "请提供知识库 ID 和目标后端"));
+                    }
+                    let target = match args[4].as_str() {
+                        "pgvector" => VectorBackend::Pgvector,
+                        "qdrant" => VectorBackend::Qdrant,
+                        "milvus" => VectorBackend::Milvus,
+                        _ => return Err(AiStudioError::validation("target", "目标后端应为 pgvector、qdrant 或 milvus")),
+                    };
+                    VectorCommand::Migrate {
+                        knowledge_base_id: parse_uuid_arg(&args[3], "knowledge_base_id")?,
+                        target,
+                        keep_source: args.iter().any(|arg| arg == "--keep-source"),
+                    }
+                }
+                _ => return Err(AiStudioError::validation("vector", "未知的向量存储子命令")),
+            };
+
+            Ok(CliCommand::Vector(subcommand))
+        }
         _ => Err(AiStudioError::validation("args", "未知的命令")),
     }
 }
 
+/// 读取 `--name value` 形式的选项值
+fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
+}
+
+fn read_manifest(file: &std::path::Path) -> Result<Manifest, AiStudioError> {
+    let content = std::fs::read_to_string(file)
+        .map_err(|e| AiStudioError::validation("file", format!("读取 {} 失败: {}", file.display(), e)))?;
+    DefinitionFormat::from_path(file).deserialize(&content)
+}
+
+/// 打印清单变更计划
+fn print_manifest_plan(plan: &ManifestPlan) {
+    for change in &plan.changes {
+        let (symbol, label) = match change.action {
+            ChangeAction::Create => ("+", "新建"),
+            ChangeAction::Update => ("~", "更新"),
+            ChangeAction::Unchanged => ("=", "不变"),
+            ChangeAction::Skipped => ("-", "跳过"),
+        };
+        print!("  {} [{}] {:?} {} {}", symbol, change.tenant, change.kind, change.name, label);
+        if !change.fields.is_empty() {
+            print!(" ({})", change.fields.join(", "));
+        }
+        if let Some(note) = &change.note {
+            print!(" - {}", note);
+        }
+        println!();
+    }
+
+    println!(
+        "{} 新建 {}，更新 {}，不变 {}，跳过 {}",
+        if plan.applied { "✅ 已应用:" } else { "📋 计划:" },
+        plan.count(ChangeAction::Create),
+        plan.count(ChangeAction::Update),
+        plan.count(ChangeAction::Unchanged),
+        plan.count(ChangeAction::Skipped),
+    );
+}
+
+fn parse_uuid_arg(value: &str, field: &str) -> Result<Uuid, AiStudioError> {
+    Uuid::parse_str(value).map_err(|_| AiStudioError::validation(field, format!("无效的 UUID: {}", value)))
+}
+
 /// 打印帮助信息
 pub fn print_help() {
     println!("Aionix 数据库管理工具");
     println!();
     println!("用法:");
-    println!("  aionix <命令> <子命令> [选项]");
+    println!("  aionix-db <命令> <子命令> [选项]");
     println!();
     println!("命令:");
     println!("  migration, migrate    数据库迁移管理");
     println!("  seed                  种子数据管理");
     println!("  backup                备份和恢复管理");
+    println!("  config                配置验证与环境对比");
+    println!("  agent                 Agent 定义文件导入导出");
+    println!("  manifest              期望状态清单");
+    println!("  vector                知识库向量存储");
     println!();
     println!("迁移命令:");
     println!("  migration init        初始化迁移系统");
@@ -477,6 +1065,13 @@ pub fn print_help() {
     println!("  migration rollback <version>  回滚指定版本的迁移");
     println!("  migration reset       重置所有迁移");
     println!("  migration validate    验证数据库架构");
+    println!("  migration repair [version...] [--yes]  重新记录校验和不一致的迁移");
+    println!("  migration verify-schema  验证数据库架构并给出修复建议");
+    println!();
+    println!("快捷命令:");
+    println!("  status                等同于 migration status");
+    println!("  repair                等同于 migration repair");
+    println!("  verify-schema         等同于 migration verify-schema");
     println!();
     println!("种子数据命令:");
     println!("  seed init             初始化种子数据");
@@ -491,6 +1086,22 @@ pub fn print_help() {
     println!("  backup verify <backup_id>         验证备份");
     println!("  backup cleanup [days]             清理过期备份");
     println!();
+    println!("配置命令:");
+    println!("  config validate [--offline] [--compare <file>]  验证配置与依赖服务连通性，可对比另一环境的配置文件");
+    println!("  config diff <left.toml> <right.toml>            对比两个配置文件（密钥已脱敏）");
+    println!();
+    println!("Agent 命令:");
+    println!("  agent export <tenant_id> <name> [--output <file>] [--format yaml|json]  导出 Agent 定义");
+    println!("  agent import <tenant_id> <user_id> <file>                            导入 Agent 定义，同名 Agent 存在时更新");
+    println!();
+    println!("清单命令:");
+    println!("  manifest plan <file>                   对比清单与数据库现状，不做修改");
+    println!("  manifest apply <file> --as <user_id>   应用清单，可重复执行");
+    println!("  （插件配置需通过 POST /admin/manifest/apply 应用）");
+    println!();
+    println!("向量存储命令:");
+    println!("  vector migrate <kb_id> <pgvector|qdrant|milvus> [--keep-source]  迁移知识库向量并切换后端");
+    println!();
     println!("备份类型:");
     println!("  full          完整备份 (默认)");
     println!("  incremental   增量备份");
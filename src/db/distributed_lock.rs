@@ -0,0 +1,278 @@
+// 分布式锁服务
+// 基于 PostgreSQL 实现多实例间的互斥与领导者选举
+
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use sea_orm::{
+    ConnectionTrait, DatabaseBackend, DatabaseConnection, DatabaseTransaction, Statement,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument};
+use uuid::Uuid;
+
+use crate::errors::AiStudioError;
+
+/// 当前进程的实例标识（主机名 + 进程号 + 随机后缀）
+pub static INSTANCE_ID: Lazy<String> = Lazy::new(|| {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string());
+    let suffix = Uuid::new_v4().simple().to_string();
+    format!("{}-{}-{}", host, std::process::id(), &suffix[..8])
+});
+
+/// 知名锁名称
+pub mod lock_names {
+    /// 数据库迁移锁
+    pub const MIGRATIONS: &str = "aionix:migrations";
+    /// 种子数据锁
+    pub const SEED_DATA: &str = "aionix:seed_data";
+}
+
+/// 生成当前实例内某个持有者的租约持有者 ID
+pub fn holder_id_for(owner: &str) -> String {
+    format!("{}/{}", *INSTANCE_ID, owner)
+}
+
+/// 租约信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockLease {
+    /// 锁名称
+    pub name: String,
+    /// 持有者实例 ID
+    pub holder_id: String,
+    /// 租约过期时间
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 基于事务的咨询锁守卫
+///
+/// PostgreSQL 会话级咨询锁与连接绑定，连接池下无法可靠释放，
+/// 因此这里使用事务级锁 `pg_advisory_xact_lock`，守卫持有事务即持有锁。
+/// 守卫被 drop 时事务回滚，锁随之释放。
+pub struct AdvisoryLockGuard {
+    name: String,
+    txn: DatabaseTransaction,
+}
+
+impl AdvisoryLockGuard {
+    /// 获取锁名称
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 显式释放锁
+    pub async fn release(self) -> Result<(), AiStudioError> {
+        self.txn.commit().await?;
+        debug!(lock = %self.name, "咨询锁已释放");
+        Ok(())
+    }
+}
+
+/// 分布式锁服务
+#[derive(Debug, Clone)]
+pub struct DistributedLockService {
+    db: DatabaseConnection,
+}
+
+impl DistributedLockService {
+    /// 创建新的分布式锁服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 将锁名称映射为 64 位咨询锁键
+    pub fn advisory_key(name: &str) -> i64 {
+        use sha2::{Digest, Sha256};
+        let digest = Sha256::digest(name.as_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        i64::from_be_bytes(bytes)
+    }
+
+    /// 阻塞等待获取咨询锁（用于迁移等必须串行执行的操作）
+    #[instrument(skip(self))]
+    pub async fn acquire_advisory(&self, name: &str) -> Result<AdvisoryLockGuard, AiStudioError> {
+        let txn = self.db.begin().await?;
+        txn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT pg_advisory_xact_lock($1)",
+            [Self::advisory_key(name).into()],
+        ))
+        .await?;
+
+        debug!(lock = %name, instance = %*INSTANCE_ID, "获取咨询锁成功");
+        Ok(AdvisoryLockGuard { name: name.to_string(), txn })
+    }
+
+    /// 尝试获取咨询锁，锁被其他实例持有时立即返回 None
+    #[instrument(skip(self))]
+    pub async fn try_acquire_advisory(&self, name: &str) -> Result<Option<AdvisoryLockGuard>, AiStudioError> {
+        let txn = self.db.begin().await?;
+        let row = txn
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT pg_try_advisory_xact_lock($1) AS locked",
+                [Self::advisory_key(name).into()],
+            ))
+            .await?;
+
+        let locked = row
+            .map(|r| r.try_get::<bool>("", "locked"))
+            .transpose()?
+            .unwrap_or(false);
+
+        if locked {
+            debug!(lock = %name, instance = %*INSTANCE_ID, "获取咨询锁成功");
+            Ok(Some(AdvisoryLockGuard { name: name.to_string(), txn }))
+        } else {
+            txn.rollback().await?;
+            Ok(None)
+        }
+    }
+
+    /// 在咨询锁保护下执行操作，锁被占用时跳过并返回 None
+    pub async fn run_exclusive<F, Fut, T>(&self, name: &str, f: F) -> Result<Option<T>, AiStudioError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<T, AiStudioError>>,
+    {
+        let guard = match self.try_acquire_advisory(name).await? {
+            Some(guard) => guard,
+            None => {
+                debug!(lock = %name, "锁已被其他实例持有，跳过执行");
+                return Ok(None);
+            }
+        };
+
+        let result = f().await;
+        guard.release().await?;
+        result.map(Some)
+    }
+
+    /// 尝试获取（或续约）带过期时间的租约
+    ///
+    /// 租约记录在 `distributed_locks` 表中，持有者崩溃后租约到期即可被其他实例接管，
+    /// 适合周期任务的领导者选举。
+    #[instrument(skip(self))]
+    pub async fn try_acquire_lease(&self, name: &str, ttl: Duration) -> Result<bool, AiStudioError> {
+        self.try_acquire_lease_as(name, &INSTANCE_ID, ttl).await
+    }
+
+    /// 以指定持有者身份尝试获取（或续约）租约
+    ///
+    /// 同一实例内需要区分多个持有者时使用（如工作流并发组按执行持有名额），
+    /// 持有者 ID 应以 [`holder_id_for`] 生成，以便停机时随实例一并释放。
+    #[instrument(skip(self))]
+    pub async fn try_acquire_lease_as(&self, name: &str, holder_id: &str, ttl: Duration) -> Result<bool, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO distributed_locks (name, holder_id, acquired_at, expires_at)
+                VALUES ($1, $2, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP + make_interval(secs => $3))
+                ON CONFLICT (name) DO UPDATE
+                    SET holder_id = EXCLUDED.holder_id,
+                        acquired_at = CASE
+                            WHEN distributed_locks.holder_id = EXCLUDED.holder_id THEN distributed_locks.acquired_at
+                            ELSE EXCLUDED.acquired_at
+                        END,
+                        expires_at = EXCLUDED.expires_at
+                    WHERE distributed_locks.expires_at < CURRENT_TIMESTAMP
+                       OR distributed_locks.holder_id = EXCLUDED.holder_id
+                RETURNING holder_id
+                "#,
+                [
+                    name.into(),
+                    holder_id.into(),
+                    (ttl.as_secs_f64()).into(),
+                ],
+            ))
+            .await?;
+
+        Ok(row.is_some())
+    }
+
+    /// 释放租约（仅当前实例持有时生效）
+    #[instrument(skip(self))]
+    pub async fn release_lease(&self, name: &str) -> Result<bool, AiStudioError> {
+        self.release_lease_as(name, &INSTANCE_ID).await
+    }
+
+    /// 释放指定持有者的租约
+    #[instrument(skip(self))]
+    pub async fn release_lease_as(&self, name: &str, holder_id: &str) -> Result<bool, AiStudioError> {
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "DELETE FROM distributed_locks WHERE name = $1 AND holder_id = $2",
+                [name.into(), holder_id.into()],
+            ))
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 列出当前所有未过期的租约
+    #[instrument(skip(self))]
+    pub async fn list_leases(&self) -> Result<Vec<LockLease>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_string(
+                DatabaseBackend::Postgres,
+                "SELECT name, holder_id, expires_at FROM distributed_locks WHERE expires_at >= CURRENT_TIMESTAMP ORDER BY name".to_string(),
+            ))
+            .await?;
+
+        let mut leases = Vec::with_capacity(rows.len());
+        for row in rows {
+            leases.push(LockLease {
+                name: row.try_get("", "name")?,
+                holder_id: row.try_get("", "holder_id")?,
+                expires_at: row.try_get("", "expires_at")?,
+            });
+        }
+        Ok(leases)
+    }
+
+    /// 释放当前实例持有的所有租约，包括以实例内持有者身份获取的租约（优雅停机时调用）
+    #[instrument(skip(self))]
+    pub async fn release_all_leases(&self) -> Result<u64, AiStudioError> {
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "DELETE FROM distributed_locks WHERE holder_id = $1 OR starts_with(holder_id, $2)",
+                [INSTANCE_ID.as_str().into(), holder_id_for("").into()],
+            ))
+            .await?;
+
+        let released = result.rows_affected();
+        if released > 0 {
+            info!(instance = %*INSTANCE_ID, released, "已释放实例持有的租约");
+        }
+        Ok(released)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advisory_key_is_stable() {
+        let a = DistributedLockService::advisory_key(lock_names::MIGRATIONS);
+        let b = DistributedLockService::advisory_key(lock_names::MIGRATIONS);
+        assert_eq!(a, b);
+        assert_ne!(a, DistributedLockService::advisory_key(lock_names::SEED_DATA));
+    }
+
+    #[test]
+    fn test_instance_id_format() {
+        assert!(INSTANCE_ID.contains(&std::process::id().to_string()));
+    }
+
+    #[test]
+    fn test_holder_id_is_scoped_to_instance() {
+        let holder = holder_id_for("execution-1");
+        assert!(holder.starts_with(&holder_id_for("")));
+        assert_ne!(holder, *INSTANCE_ID);
+    }
+}
@@ -99,7 +99,9 @@ pub struct Model {
     pub created_at: DateTimeWithTimeZone,
     
     /// 更新时间
-    pub updated_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,    
+    /// 修订号，每次编辑递增，用于乐观锁
+    pub revision: i32,
 }
 
 /// Agent 关联关系
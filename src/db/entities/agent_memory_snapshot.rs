@@ -0,0 +1,54 @@
+// Agent 记忆快照实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Agent 记忆快照，每次任务执行结束时保存一份
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "agent_memory_snapshots")]
+pub struct Model {
+    /// 快照 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// Agent ID
+    pub agent_id: Uuid,
+
+    /// 执行 ID（任务 ID）
+    pub execution_id: Uuid,
+
+    /// 执行结束时的记忆项（JSON 格式）
+    #[sea_orm(column_type = "Json")]
+    pub memories: Json,
+
+    /// 各推理步骤检索到的记忆（JSON 格式）
+    #[sea_orm(column_type = "Json")]
+    pub retrievals: Json,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// Agent 记忆快照关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：快照 -> 租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
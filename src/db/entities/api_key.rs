@@ -103,6 +103,9 @@ pub struct ApiKeyPermissions {
     pub allowed_ips: Option<Vec<String>>,
     /// 速率限制
     pub rate_limit: Option<ApiKeyRateLimit>,
+    /// 自动限流截止时间，检测到用量异常时由系统设置，期间拒绝该密钥的请求
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throttled_until: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 /// API 密钥速率限制
@@ -124,6 +127,7 @@ impl Default for ApiKeyPermissions {
             actions: vec!["read".to_string()],
             allowed_ips: None,
             rate_limit: Some(ApiKeyRateLimit::default()),
+            throttled_until: None,
         }
     }
 }
@@ -0,0 +1,104 @@
+// 灰度发布实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::execution_event::ExecutionType;
+
+/// 灰度发布状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(20))")]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryStatus {
+    /// 灰度中，按比例或指定用户分流
+    #[sea_orm(string_value = "active")]
+    Active,
+    /// 已全量，全部流量由灰度版本处理
+    #[sea_orm(string_value = "promoted")]
+    Promoted,
+    /// 已回滚，全部流量由稳定版本处理
+    #[sea_orm(string_value = "rolled_back")]
+    RolledBack,
+}
+
+/// 灰度发布，入口 Agent 或工作流的流量在稳定版本与灰度版本之间分配
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "canary_releases")]
+pub struct Model {
+    /// 灰度发布 ID
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 发布对象类型
+    pub target_type: ExecutionType,
+
+    /// 入口 ID，调用方请求的 Agent 或工作流
+    pub target_id: Uuid,
+
+    /// 稳定版本 ID
+    pub stable_id: Uuid,
+
+    /// 灰度版本 ID
+    pub canary_id: Uuid,
+
+    /// 分流到灰度版本的流量百分比
+    pub traffic_percent: i16,
+
+    /// 固定分流到灰度版本的用户 ID 列表
+    #[sea_orm(column_type = "JsonBinary")]
+    pub user_ids: Json,
+
+    /// 状态
+    pub status: CanaryStatus,
+
+    /// 判断是否回滚前每个版本至少需要的已结束执行数
+    pub min_samples: i32,
+
+    /// 判断负面反馈率前每个版本至少需要的反馈数
+    pub min_feedback: i32,
+
+    /// 灰度版本错误率比稳定版本高出该值时回滚
+    pub max_error_rate_increase: f64,
+
+    /// 灰度版本负面反馈率比稳定版本高出该值时回滚
+    pub max_negative_feedback_increase: f64,
+
+    /// 全量或回滚的原因
+    #[sea_orm(column_type = "Text", nullable)]
+    pub end_reason: Option<String>,
+
+    /// 创建人
+    pub created_by: Uuid,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+
+    /// 全量或回滚时间
+    #[sea_orm(nullable)]
+    pub ended_at: Option<DateTimeWithTimeZone>,
+}
+
+/// 灰度发布关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：灰度发布 -> 租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
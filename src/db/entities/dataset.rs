@@ -0,0 +1,103 @@
+// 数据集实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// 数据集列类型，导入时按列中的非空值推断
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DatasetColumnType {
+    /// 整数
+    Integer,
+    /// 小数
+    Float,
+    /// 布尔值
+    Boolean,
+    /// 日期（YYYY-MM-DD）
+    Date,
+    /// 文本
+    Text,
+}
+
+impl DatasetColumnType {
+    /// 是否为数值类型，可用于求和与平均
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Self::Integer | Self::Float)
+    }
+}
+
+/// 数据集列
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DatasetColumn {
+    /// 列名，取自表头
+    pub name: String,
+    /// 推断的列类型
+    pub column_type: DatasetColumnType,
+    /// 空值数量
+    pub null_count: u32,
+}
+
+/// 数据集，CSV 文件或 Excel 工作表导入后的带类型行数据
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "datasets")]
+pub struct Model {
+    /// 数据集 ID
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+
+    /// 来源文档 ID
+    pub document_id: Uuid,
+
+    /// 数据集名称
+    #[sea_orm(column_type = "String(Some(255))")]
+    pub name: String,
+
+    /// 工作表名称，CSV 文件为空
+    #[sea_orm(column_type = "String(Some(255))", nullable)]
+    pub sheet_name: Option<String>,
+
+    /// 列定义
+    #[sea_orm(column_type = "JsonBinary")]
+    pub columns: Json,
+
+    /// 行数
+    pub row_count: i32,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// 数据集关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：数据集 -> 文档
+    #[sea_orm(
+        belongs_to = "super::document::Entity",
+        from = "Column::DocumentId",
+        to = "super::document::Column::Id"
+    )]
+    Document,
+}
+
+/// 实现与文档的关联
+impl Related<super::document::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Document.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// 获取列定义
+    pub fn get_columns(&self) -> Result<Vec<DatasetColumn>, serde_json::Error> {
+        serde_json::from_value(self.columns.clone())
+    }
+}
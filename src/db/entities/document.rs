@@ -2,6 +2,7 @@
 
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// 文档状态枚举
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
@@ -39,6 +40,31 @@ pub enum DocumentType {
     Json,
     #[sea_orm(string_value = "xml")]
     Xml,
+    #[sea_orm(string_value = "spreadsheet")]
+    Spreadsheet,
+}
+
+/// 访问密级，文档与文档块按密级限制检索可见范围
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+    EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
+#[serde(rename_all = "snake_case")]
+pub enum ClearanceLevel {
+    /// 公开
+    #[default]
+    #[sea_orm(num_value = 0)]
+    Public,
+    /// 内部
+    #[sea_orm(num_value = 1)]
+    Internal,
+    /// 机密
+    #[sea_orm(num_value = 2)]
+    Confidential,
+    /// 绝密
+    #[sea_orm(num_value = 3)]
+    Restricted,
 }
 
 /// 文档实体
@@ -119,11 +145,35 @@ pub struct Model {
     /// 版本号
     pub version: i32,
     
+    /// 过期时间，过期后按知识库时效策略降权或排除
+    #[sea_orm(nullable)]
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    
+    /// 复核时间，到期后提醒负责人复核
+    #[sea_orm(nullable)]
+    pub review_at: Option<DateTimeWithTimeZone>,
+    
+    /// 负责人 ID
+    #[sea_orm(nullable)]
+    pub owner_id: Option<Uuid>,
+    
+    /// 最近一次发送复核提醒的时间
+    #[sea_orm(nullable)]
+    pub review_notified_at: Option<DateTimeWithTimeZone>,
+    
+    /// 访问密级，对文档的所有块生效
+    pub clearance: ClearanceLevel,
+    
+    /// 是否为常青文档，常青文档不参与检索排序的时间衰减
+    pub evergreen: bool,
+    
     /// 创建时间
     pub created_at: DateTimeWithTimeZone,
     
     /// 更新时间
-    pub updated_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,    
+    /// 修订号，每次编辑递增，用于乐观锁
+    pub revision: i32,
 }
 
 /// 文档关联关系
@@ -344,6 +394,7 @@ impl Model {
             DocumentType::Csv => "CSV 文件",
             DocumentType::Json => "JSON 文件",
             DocumentType::Xml => "XML 文件",
+            DocumentType::Spreadsheet => "Excel 表格",
         }
     }
     
@@ -3,6 +3,8 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::knowledge_base::{PiiIndexAction, PiiKind, PiiOverrideDecision};
+
 /// 文档块状态枚举
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "chunk_status")]
@@ -79,6 +81,9 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub error_message: Option<String>,
     
+    /// 段落访问密级，与所属文档密级取较高者生效
+    pub clearance: super::document::ClearanceLevel,
+    
     /// 创建时间
     pub created_at: DateTimeWithTimeZone,
     
@@ -154,6 +159,45 @@ pub struct ChunkMetadata {
     pub importance_score: Option<f32>,
     /// 自定义字段
     pub custom_fields: std::collections::HashMap<String, serde_json::Value>,
+    /// 表格块的结构化内容，普通文本块为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table: Option<ChunkTable>,
+    /// 索引时检测到的个人信息，未检测到时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pii: Option<ChunkPii>,
+}
+
+/// 文档块的个人信息检测结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkPii {
+    /// 检测到的类型
+    pub kinds: Vec<PiiKind>,
+    /// 实际采用的处理方式
+    pub action: PiiIndexAction,
+    /// 合规人员的裁定
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<PiiOverrideDecision>,
+    /// 处理前原文的哈希，裁定按该哈希记录
+    pub original_hash: String,
+}
+
+/// 表格块内容
+///
+/// 较大的表格按行拆成多个块，每块都带完整表头；同一表格的各块共享 `table_id`。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkTable {
+    /// 表格标识，在所属文档内唯一
+    pub table_id: String,
+    /// 表格标题
+    pub caption: Option<String>,
+    /// 表头
+    pub headers: Vec<String>,
+    /// 本块包含的数据行
+    pub rows: Vec<Vec<String>>,
+    /// 本块第一行在整张表中的行号（从 0 开始）
+    pub row_offset: u32,
+    /// 整张表的数据行数
+    pub total_rows: u32,
 }
 
 /// 位置信息
@@ -194,6 +238,8 @@ impl Default for ChunkMetadata {
             confidence_score: None,
             importance_score: None,
             custom_fields: std::collections::HashMap::new(),
+            table: None,
+            pii: None,
         }
     }
 }
@@ -0,0 +1,71 @@
+// 执行产物实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::execution_event::ExecutionType;
+
+/// 执行产物，超过阈值的步骤输出转存到存储目录后在此登记
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "execution_artifacts")]
+pub struct Model {
+    /// 产物 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 执行类型
+    pub execution_type: ExecutionType,
+
+    /// 执行 ID
+    pub execution_id: Uuid,
+
+    /// 产生该输出的步骤 ID
+    #[sea_orm(column_type = "String(Some(255))", nullable)]
+    pub step_id: Option<String>,
+
+    /// 内容类型
+    #[sea_orm(column_type = "String(Some(100))")]
+    pub content_type: String,
+
+    /// 内容大小（字节）
+    pub size_bytes: i64,
+
+    /// 内容 SHA-256 摘要（十六进制）
+    #[sea_orm(column_type = "String(Some(64))")]
+    pub sha256: String,
+
+    /// 存储目录下的相对路径
+    #[sea_orm(column_type = "Text")]
+    pub storage_key: String,
+
+    /// 过期时间，为空表示永久保留
+    #[sea_orm(nullable)]
+    pub expires_at: Option<DateTimeWithTimeZone>,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// 执行产物关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：产物 -> 租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,84 @@
+// 执行事件实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// 执行类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(20))")]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionType {
+    /// Agent 执行
+    #[sea_orm(string_value = "agent")]
+    Agent,
+    /// 工作流执行
+    #[sea_orm(string_value = "workflow")]
+    Workflow,
+}
+
+impl ExecutionType {
+    /// 是否为计费的活跃状态（等待输入、暂停等状态不计费）
+    pub fn is_active_state(&self, state: &str) -> bool {
+        match self {
+            ExecutionType::Agent => matches!(state, "thinking" | "executing_tool"),
+            ExecutionType::Workflow => state == "running",
+        }
+    }
+}
+
+/// 执行状态转换事件，只追加不修改
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "execution_events")]
+pub struct Model {
+    /// 事件 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 执行类型
+    pub execution_type: ExecutionType,
+
+    /// 执行 ID（Agent 实例 ID 或工作流执行 ID）
+    pub execution_id: Uuid,
+
+    /// 同一执行内的事件序号，从 1 开始
+    pub sequence: i32,
+
+    /// 转换前状态，首个事件为空
+    #[sea_orm(nullable)]
+    pub from_state: Option<String>,
+
+    /// 转换后状态
+    pub to_state: String,
+
+    /// 转换附带的数据（任务 ID、错误信息等）
+    #[sea_orm(column_type = "Json")]
+    pub payload: Json,
+
+    /// 发生时间
+    pub occurred_at: DateTimeWithTimeZone,
+}
+
+/// 执行事件关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：事件 -> 租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
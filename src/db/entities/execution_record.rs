@@ -0,0 +1,96 @@
+// 执行记录实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::execution_event::ExecutionType;
+
+/// Agent 或工作流的一次执行，保存重放所需的输入与用于对比的输出、耗时和令牌用量
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "execution_records")]
+pub struct Model {
+    /// 执行 ID（Agent 任务 ID 或工作流执行 ID）
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 执行类型
+    pub execution_type: ExecutionType,
+
+    /// 执行对象 ID（Agent ID 或工作流 ID）
+    pub target_id: Uuid,
+
+    /// 执行输入
+    #[sea_orm(column_type = "Json")]
+    pub input: Json,
+
+    /// 执行输出
+    #[sea_orm(column_type = "Json", nullable)]
+    pub output: Option<Json>,
+
+    /// 执行状态
+    #[sea_orm(column_type = "String(Some(20))")]
+    pub status: String,
+
+    /// 错误信息
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error_message: Option<String>,
+
+    /// 执行耗时（毫秒）
+    #[sea_orm(nullable)]
+    pub duration_ms: Option<i64>,
+
+    /// 令牌总用量，执行结果未报告用量时为空
+    #[sea_orm(nullable)]
+    pub total_tokens: Option<i64>,
+
+    /// 重放的原始执行 ID
+    #[sea_orm(nullable)]
+    pub replay_of: Option<Uuid>,
+
+    /// 发起用户 ID
+    #[sea_orm(nullable)]
+    pub triggered_by: Option<Uuid>,
+
+    /// 开始时间
+    pub started_at: DateTimeWithTimeZone,
+
+    /// 完成时间
+    #[sea_orm(nullable)]
+    pub completed_at: Option<DateTimeWithTimeZone>,
+
+    /// 所属灰度发布 ID，仅灰度进行中的执行有值
+    #[sea_orm(nullable)]
+    pub canary_release_id: Option<Uuid>,
+
+    /// 灰度分流到的版本（stable 或 canary）
+    #[sea_orm(column_type = "String(Some(10))", nullable)]
+    pub canary_arm: Option<String>,
+
+    /// 用户反馈：1 为正面，-1 为负面
+    #[sea_orm(nullable)]
+    pub feedback: Option<i16>,
+}
+
+/// 执行记录关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：执行记录 -> 租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
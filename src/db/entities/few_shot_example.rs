@@ -0,0 +1,76 @@
+// 少样本示例实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 少样本示例实体，挂载到 Agent 或提示词模板上的输入/输出示例
+///
+/// 示例的向量保存在 `embedding` 列中，只通过原生 SQL 读写，不映射到实体字段。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "few_shot_examples")]
+pub struct Model {
+    /// 示例 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 所属 Agent ID
+    #[sea_orm(nullable)]
+    pub agent_id: Option<Uuid>,
+
+    /// 所属提示词模板名称
+    #[sea_orm(column_type = "String(Some(100))", nullable)]
+    pub prompt_template: Option<String>,
+
+    /// 示例输入
+    #[sea_orm(column_type = "Text")]
+    pub input: String,
+
+    /// 示例输出
+    #[sea_orm(column_type = "Text")]
+    pub output: String,
+
+    /// 标签（JSON 字符串数组）
+    #[sea_orm(column_type = "Json")]
+    pub tags: Json,
+
+    /// 创建人
+    #[sea_orm(nullable)]
+    pub created_by: Option<Uuid>,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+
+    /// 更新时间
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+/// 少样本示例关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：示例 -> 租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl Model {
+    /// 获取标签列表
+    pub fn tag_list(&self) -> Vec<String> {
+        serde_json::from_value(self.tags.clone()).unwrap_or_default()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,103 @@
+// 知识库 FAQ/术语表条目实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::document::ClearanceLevel;
+
+/// 条目类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(20))")]
+#[serde(rename_all = "snake_case")]
+pub enum FaqEntryType {
+    /// 常见问题：问题与标准答案
+    #[sea_orm(string_value = "faq")]
+    Faq,
+    /// 术语表：术语与释义
+    #[sea_orm(string_value = "glossary")]
+    Glossary,
+}
+
+/// 知识库 FAQ/术语表条目，人工维护的标准答案，问答时优先匹配
+///
+/// 问题向量保存在 `embedding` 列中，只通过原生 SQL 读写，不映射到实体字段。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "kb_faq_entries")]
+pub struct Model {
+    /// 条目 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+
+    /// 条目类型
+    pub entry_type: FaqEntryType,
+
+    /// 问题（术语表条目为术语）
+    #[sea_orm(column_type = "Text")]
+    pub question: String,
+
+    /// 标准答案（术语表条目为释义）
+    #[sea_orm(column_type = "Text")]
+    pub answer: String,
+
+    /// 同义问法或术语别名（JSON 字符串数组）
+    #[sea_orm(column_type = "Json")]
+    pub aliases: Json,
+
+    /// 访问密级
+    pub clearance: ClearanceLevel,
+
+    /// 是否启用
+    pub is_active: bool,
+
+    /// 命中次数
+    pub hit_count: i32,
+
+    /// 最近命中时间
+    #[sea_orm(nullable)]
+    pub last_hit_at: Option<DateTimeWithTimeZone>,
+
+    /// 创建人
+    #[sea_orm(nullable)]
+    pub created_by: Option<Uuid>,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+
+    /// 更新时间
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+/// FAQ 条目关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：条目 -> 知识库
+    #[sea_orm(
+        belongs_to = "super::knowledge_base::Entity",
+        from = "Column::KnowledgeBaseId",
+        to = "super::knowledge_base::Column::Id"
+    )]
+    KnowledgeBase,
+}
+
+/// 实现与知识库的关联
+impl Related<super::knowledge_base::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::KnowledgeBase.def()
+    }
+}
+
+impl Model {
+    /// 获取别名列表
+    pub fn alias_list(&self) -> Vec<String> {
+        serde_json::from_value(self.aliases.clone()).unwrap_or_default()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,72 @@
+// 知识库快照实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 知识库快照实体
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "kb_snapshots")]
+pub struct Model {
+    /// 快照 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+
+    /// 快照名称（即 QA 请求中的 `kb_version`）
+    #[sea_orm(column_type = "String(Some(100))")]
+    pub name: String,
+
+    /// 快照描述
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+
+    /// 快照中的文档数
+    pub document_count: i32,
+
+    /// 快照中的文档块数
+    pub chunk_count: i32,
+
+    /// 创建人
+    #[sea_orm(nullable)]
+    pub created_by: Option<Uuid>,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// 知识库快照关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：快照 -> 知识库
+    #[sea_orm(
+        belongs_to = "super::knowledge_base::Entity",
+        from = "Column::KnowledgeBaseId",
+        to = "super::knowledge_base::Column::Id"
+    )]
+    KnowledgeBase,
+
+    /// 一对多：快照 -> 快照文档
+    #[sea_orm(has_many = "super::kb_snapshot_document::Entity")]
+    Documents,
+}
+
+/// 实现与知识库的关联
+impl Related<super::knowledge_base::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::KnowledgeBase.def()
+    }
+}
+
+/// 实现与快照文档的关联
+impl Related<super::kb_snapshot_document::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Documents.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
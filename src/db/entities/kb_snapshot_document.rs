@@ -0,0 +1,53 @@
+// 知识库快照文档实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 知识库快照文档实体，记录快照时刻的文档成员及其内容指纹
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "kb_snapshot_documents")]
+pub struct Model {
+    /// 快照 ID
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub snapshot_id: Uuid,
+
+    /// 文档 ID
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub document_id: Uuid,
+
+    /// 文档标题
+    #[sea_orm(column_type = "String(Some(500))")]
+    pub title: String,
+
+    /// 文件类型
+    #[sea_orm(column_type = "String(Some(50))", nullable)]
+    pub file_type: Option<String>,
+
+    /// 文档内容哈希
+    #[sea_orm(column_type = "String(Some(64))")]
+    pub content_hash: String,
+
+    /// 快照时文档的最后更新时间
+    pub source_updated_at: DateTimeWithTimeZone,
+}
+
+/// 知识库快照文档关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：快照文档 -> 快照
+    #[sea_orm(
+        belongs_to = "super::kb_snapshot::Entity",
+        from = "Column::SnapshotId",
+        to = "super::kb_snapshot::Column::Id"
+    )]
+    Snapshot,
+}
+
+/// 实现与快照的关联
+impl Related<super::kb_snapshot::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Snapshot.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,67 @@
+// 知识库存储统计实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 知识库每日存储统计，由夜间汇总任务写入
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "kb_storage_stats")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+
+    /// 统计日期
+    pub stat_date: Date,
+
+    /// 文档数
+    pub document_count: i64,
+
+    /// 文档块数
+    pub chunk_count: i64,
+
+    /// 向量嵌入数
+    pub embedding_count: i64,
+
+    /// 原始文件大小（字节）
+    pub file_bytes: i64,
+
+    /// 数据库占用（字节），含文档、文档块与嵌入行
+    pub database_bytes: i64,
+
+    /// 处理失败的文档数
+    pub failed_documents: i64,
+
+    /// 等待或正在处理的文档数
+    pub pending_documents: i64,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// 知识库存储统计关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：统计 -> 知识库
+    #[sea_orm(
+        belongs_to = "super::knowledge_base::Entity",
+        from = "Column::KnowledgeBaseId",
+        to = "super::knowledge_base::Column::Id"
+    )]
+    KnowledgeBase,
+}
+
+/// 实现与知识库的关联
+impl Related<super::knowledge_base::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::KnowledgeBase.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -15,6 +15,8 @@ pub enum KnowledgeBaseStatus {
     Processing,
     #[sea_orm(string_value = "error")]
     Error,
+    #[sea_orm(string_value = "archived")]
+    Archived,
 }
 
 /// 知识库类型枚举
@@ -90,7 +92,9 @@ pub struct Model {
     pub created_at: DateTimeWithTimeZone,
     
     /// 更新时间
-    pub updated_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,    
+    /// 修订号，每次编辑递增，用于乐观锁
+    pub revision: i32,
 }
 
 /// 知识库关联关系
@@ -136,6 +140,27 @@ pub struct KnowledgeBaseConfig {
     pub retrieval_settings: RetrievalSettings,
     /// 访问控制
     pub access_control: AccessControl,
+    /// 文档时效策略
+    #[serde(default)]
+    pub freshness: FreshnessPolicy,
+    /// 答案置信度与拒答策略
+    #[serde(default)]
+    pub answer_policy: AnswerPolicy,
+    /// 来源链接检查策略
+    #[serde(default)]
+    pub source_check: SourceCheckPolicy,
+    /// 向量存储后端，切换需通过向量迁移完成
+    #[serde(default)]
+    pub vector_backend: VectorBackend,
+    /// 检索前的查询改写与拆分
+    #[serde(default)]
+    pub query_rewrite: QueryRewritePolicy,
+    /// 索引时的个人信息处理策略
+    #[serde(default)]
+    pub pii_policy: PiiIndexPolicy,
+    /// 检索排序的时间衰减策略
+    #[serde(default)]
+    pub recency_boost: RecencyBoostPolicy,
     /// 自定义设置
     pub custom_settings: serde_json::Value,
 }
@@ -202,6 +227,214 @@ pub struct AccessControl {
     pub permissions: Vec<String>, // "read", "write", "admin"
 }
 
+/// 过期文档在检索中的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleDocumentHandling {
+    /// 照常参与检索
+    Ignore,
+    /// 按降权系数降低相似度得分
+    DownWeight,
+    /// 从检索结果中排除
+    Exclude,
+}
+
+/// 文档时效策略
+///
+/// 文档自身的过期/复核时间优先；未设置时按最近更新时间加上默认天数推算。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FreshnessPolicy {
+    /// 默认有效天数，为空表示文档默认不过期
+    pub default_ttl_days: Option<u32>,
+    /// 默认复核周期（天），为空表示不要求定期复核
+    pub review_interval_days: Option<u32>,
+    /// 过期文档的检索处理方式
+    pub stale_handling: StaleDocumentHandling,
+    /// 降权系数（0-1），仅在降权模式下生效
+    pub stale_weight: f32,
+    /// 提前提醒天数，复核时间前多少天开始提醒负责人
+    pub reminder_lead_days: u32,
+}
+
+/// 时间衰减曲线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecencyDecayCurve {
+    /// 指数衰减，每经过一个半衰期权重减半
+    Exponential,
+    /// 线性衰减，在最大天数处降至下限
+    Linear,
+    /// 阶梯衰减，超过最大天数后直接降至下限
+    Step,
+}
+
+/// 时间衰减与新近度加权策略
+///
+/// 启用后按文档最近更新时间计算权重（下限到 1 之间）并乘入相似度得分，使较新的文档排序靠前；
+/// 标记为常青的文档不参与衰减。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecencyBoostPolicy {
+    /// 是否启用
+    pub enabled: bool,
+    /// 衰减曲线
+    pub curve: RecencyDecayCurve,
+    /// 半衰期（天），仅指数衰减使用
+    pub half_life_days: f32,
+    /// 最大天数，线性衰减和阶梯衰减使用
+    pub max_age_days: f32,
+    /// 权重下限（0-1），衰减后的权重不低于该值
+    pub min_weight: f32,
+}
+
+/// 答案置信度与拒答策略
+///
+/// 综合置信度低于拒答阈值时，不返回生成的答案，改为说明资料不足并推荐可能相关的文档。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnswerPolicy {
+    /// 拒答阈值（0-1），为空表示不拒答
+    pub refusal_threshold: Option<f32>,
+    /// 是否要求模型在答案末尾自评置信度
+    pub self_assessment: bool,
+    /// 拒答时推荐的文档数
+    pub suggested_documents: u32,
+    /// 自定义拒答话术，为空时使用默认话术
+    pub refusal_message: Option<String>,
+}
+
+/// 查询改写策略
+///
+/// 启用后问答检索前先由大模型改写含糊的问题，按术语表展开缩写，并将多部分问题拆分为子查询分别检索后合并上下文。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryRewritePolicy {
+    /// 是否启用
+    pub enabled: bool,
+    /// 是否按术语表展开缩写
+    pub expand_acronyms: bool,
+    /// 是否拆分多部分问题
+    pub decompose: bool,
+    /// 最多拆分的子查询数
+    pub max_sub_queries: u32,
+}
+
+/// 个人信息类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    /// 邮箱
+    Email,
+    /// IPv4 地址
+    IpAddress,
+    /// 手机号
+    Phone,
+    /// 身份证号
+    IdNumber,
+    /// 银行卡号
+    CardNumber,
+}
+
+impl PiiKind {
+    /// 全部类型
+    pub const ALL: [PiiKind; 5] = [
+        PiiKind::Email,
+        PiiKind::IpAddress,
+        PiiKind::Phone,
+        PiiKind::IdNumber,
+        PiiKind::CardNumber,
+    ];
+
+    /// 类型名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PiiKind::Email => "email",
+            PiiKind::IpAddress => "ip_address",
+            PiiKind::Phone => "phone",
+            PiiKind::IdNumber => "id_number",
+            PiiKind::CardNumber => "card_number",
+        }
+    }
+}
+
+/// 含个人信息的文档块在索引时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiIndexAction {
+    /// 以占位符替换后索引
+    #[default]
+    Mask,
+    /// 原文索引，在块元数据中标记个人信息类型
+    Tag,
+    /// 不写入向量索引
+    Exclude,
+}
+
+/// 合规人员对含个人信息内容的裁定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiOverrideDecision {
+    /// 误报或已获授权，按原文索引
+    Allow,
+    /// 不写入向量索引
+    Exclude,
+}
+
+/// 索引时的个人信息处理策略
+///
+/// 启用后对每个文档块检测个人信息，按 `action` 脱敏、标记或排除；合规人员可对单个块的内容作出放行或排除的裁定。
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(default)]
+pub struct PiiIndexPolicy {
+    /// 是否启用
+    pub enabled: bool,
+    /// 处理方式
+    pub action: PiiIndexAction,
+    /// 检测的个人信息类型
+    pub detectors: Vec<PiiKind>,
+}
+
+/// 向量存储后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorBackend {
+    /// PostgreSQL pgvector（向量保存在 embeddings 表）
+    #[default]
+    Pgvector,
+    /// Qdrant
+    Qdrant,
+    /// Milvus
+    Milvus,
+}
+
+impl VectorBackend {
+    /// 后端名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VectorBackend::Pgvector => "pgvector",
+            VectorBackend::Qdrant => "qdrant",
+            VectorBackend::Milvus => "milvus",
+        }
+    }
+}
+
+/// 来源链接检查策略
+///
+/// 对元数据中带有 `source_url` 的文档定期重新请求来源地址，标记链接失效、重定向和内容漂移。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceCheckPolicy {
+    /// 是否启用
+    pub enabled: bool,
+    /// 检查间隔（小时）
+    pub interval_hours: u32,
+    /// 内容漂移阈值（0-1），来源内容与文档内容的差异不低于该值时视为漂移
+    pub drift_threshold: f32,
+    /// 漂移时是否用来源内容自动重新导入文档
+    pub auto_reingest: bool,
+}
+
 /// 知识库元数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KnowledgeBaseMetadata {
@@ -228,6 +461,13 @@ impl Default for KnowledgeBaseConfig {
             vectorization_settings: VectorizationSettings::default(),
             retrieval_settings: RetrievalSettings::default(),
             access_control: AccessControl::default(),
+            freshness: FreshnessPolicy::default(),
+            answer_policy: AnswerPolicy::default(),
+            source_check: SourceCheckPolicy::default(),
+            vector_backend: VectorBackend::default(),
+            query_rewrite: QueryRewritePolicy::default(),
+            pii_policy: PiiIndexPolicy::default(),
+            recency_boost: RecencyBoostPolicy::default(),
             custom_settings: serde_json::Value::Object(serde_json::Map::new()),
         }
     }
@@ -282,6 +522,73 @@ impl Default for AccessControl {
     }
 }
 
+impl Default for FreshnessPolicy {
+    fn default() -> Self {
+        Self {
+            default_ttl_days: None,
+            review_interval_days: None,
+            stale_handling: StaleDocumentHandling::DownWeight,
+            stale_weight: 0.5,
+            reminder_lead_days: 7,
+        }
+    }
+}
+
+impl Default for RecencyBoostPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            curve: RecencyDecayCurve::Exponential,
+            half_life_days: 90.0,
+            max_age_days: 365.0,
+            min_weight: 0.3,
+        }
+    }
+}
+
+impl Default for SourceCheckPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: 168,
+            drift_threshold: 0.3,
+            auto_reingest: false,
+        }
+    }
+}
+
+impl Default for AnswerPolicy {
+    fn default() -> Self {
+        Self {
+            refusal_threshold: None,
+            self_assessment: true,
+            suggested_documents: 3,
+            refusal_message: None,
+        }
+    }
+}
+
+impl Default for QueryRewritePolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            expand_acronyms: true,
+            decompose: true,
+            max_sub_queries: 3,
+        }
+    }
+}
+
+impl Default for PiiIndexPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            action: PiiIndexAction::Mask,
+            detectors: PiiKind::ALL.to_vec(),
+        }
+    }
+}
+
 impl Default for KnowledgeBaseMetadata {
     fn default() -> Self {
         Self {
@@ -313,6 +620,11 @@ impl Model {
         self.status == KnowledgeBaseStatus::Error
     }
     
+    /// 检查知识库是否已归档，归档的知识库不参与检索与问答
+    pub fn is_archived(&self) -> bool {
+        self.status == KnowledgeBaseStatus::Archived
+    }
+    
     /// 获取知识库配置
     pub fn get_config(&self) -> Result<KnowledgeBaseConfig, serde_json::Error> {
         serde_json::from_value(self.config.clone())
@@ -5,12 +5,20 @@ pub mod tenant;
 pub mod user;
 pub mod session;
 pub mod api_key;
+pub mod tenant_plugin_config;
+pub mod tenant_secret;
+pub mod tenant_sandbox;
+pub mod plugin_trusted_key;
 
 // 知识库相关实体
 pub mod knowledge_base;
 pub mod document;
 pub mod document_chunk;
 pub mod embedding;
+pub mod kb_snapshot;
+pub mod kb_snapshot_document;
+pub mod kb_storage_stat;
+pub mod dataset;
 
 // Agent 相关实体
 pub mod agent;
@@ -18,6 +26,17 @@ pub mod agent_execution;
 pub mod workflow;
 pub mod workflow_execution;
 pub mod step_execution;
+pub mod execution_event;
+pub mod execution_artifact;
+pub mod execution_record;
+pub mod canary_release;
+pub mod agent_memory_snapshot;
+pub mod few_shot_example;
+pub mod saved_search;
+pub mod qa_query_log;
+pub mod kb_faq_entry;
+pub mod usage_anomaly;
+pub mod workflow_callback;
 
 pub mod prelude;
 pub use prelude::*;
\ No newline at end of file
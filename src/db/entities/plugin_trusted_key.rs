@@ -0,0 +1,70 @@
+// 插件签名受信任密钥实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 插件签名受信任密钥实体
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "plugin_trusted_keys")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 密钥 ID（公钥指纹）
+    #[sea_orm(column_type = "String(Some(64))")]
+    pub key_id: String,
+
+    /// 密钥名称
+    #[sea_orm(column_type = "String(Some(255))")]
+    pub name: String,
+
+    /// base64 编码的 ed25519 公钥
+    #[sea_orm(column_type = "Text")]
+    pub public_key: String,
+
+    /// 登记人
+    #[sea_orm(nullable)]
+    pub created_by: Option<Uuid>,
+
+    /// 吊销时间
+    #[sea_orm(nullable)]
+    pub revoked_at: Option<DateTimeWithTimeZone>,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+
+    /// 更新时间
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+/// 插件签名受信任密钥关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：受信任密钥 -> 租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// 是否仍然有效
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none()
+    }
+}
@@ -6,16 +6,35 @@ pub use super::tenant::{Entity as Tenant, *};
 pub use super::user::{Entity as User, *};
 pub use super::session::{Entity as Session, *};
 pub use super::api_key::{Entity as ApiKey, *};
+pub use super::tenant_plugin_config::{Entity as TenantPluginConfig, *};
+pub use super::plugin_trusted_key::{Entity as PluginTrustedKey, *};
 
 // 知识库相关实体
 pub use super::knowledge_base::{Entity as KnowledgeBase, *};
 pub use super::document::{Entity as Document, *};
 pub use super::document_chunk::{Entity as DocumentChunk, *};
 pub use super::embedding::{Entity as Embedding, *};
+pub use super::kb_snapshot::{Entity as KbSnapshot, *};
+pub use super::kb_snapshot_document::{Entity as KbSnapshotDocument, *};
 
 // Agent 相关实体
 pub use super::agent::{Entity as Agent, *};
 pub use super::agent_execution::{Entity as AgentExecution, *};
 pub use super::workflow::{Entity as Workflow, *};
 pub use super::workflow_execution::{Entity as WorkflowExecution, *};
-pub use super::step_execution::{Entity as StepExecution, *};
\ No newline at end of file
+pub use super::step_execution::{Entity as StepExecution, *};
+pub use super::execution_event::{Entity as ExecutionEvent, *};
+pub use super::few_shot_example::{Entity as FewShotExample, *};
+pub use super::saved_search::{Entity as SavedSearch, *};
+pub use super::qa_query_log::{Entity as QaQueryLog, *};
+pub use super::kb_faq_entry::{Entity as KbFaqEntry, *};
+pub use super::usage_anomaly::{Entity as UsageAnomaly, *};
+pub use super::workflow_callback::{Entity as WorkflowCallback, *};
+pub use super::tenant_secret::{Entity as TenantSecret, *};
+pub use super::execution_artifact::{Entity as ExecutionArtifact, *};
+pub use super::agent_memory_snapshot::{Entity as AgentMemorySnapshot, *};
+pub use super::tenant_sandbox::{Entity as TenantSandbox, *};
+pub use super::kb_storage_stat::{Entity as KbStorageStat, *};
+pub use super::execution_record::{Entity as ExecutionRecord, *};
+pub use super::canary_release::{Entity as CanaryRelease, *};
+pub use super::dataset::{Entity as Dataset, *};
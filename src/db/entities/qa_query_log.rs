@@ -0,0 +1,111 @@
+// 问答查询日志实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 问答查询日志，记录问答会话内容与用户反馈，并用于统计热门问题、生成问题推荐
+///
+/// 问题向量保存在 `question_embedding` 列中，只通过原生 SQL 读写，不映射到实体字段。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "qa_query_logs")]
+pub struct Model {
+    /// 日志 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 知识库 ID
+    #[sea_orm(nullable)]
+    pub knowledge_base_id: Option<Uuid>,
+
+    /// 提问用户 ID
+    #[sea_orm(nullable)]
+    pub user_id: Option<Uuid>,
+
+    /// 问题原文
+    #[sea_orm(column_type = "Text")]
+    pub question: String,
+
+    /// 归一化后的问题，用于去重
+    #[sea_orm(column_type = "Text")]
+    pub normalized_question: String,
+
+    /// 答案置信度
+    pub confidence_score: f32,
+
+    /// 引用的来源文档数
+    pub source_count: i32,
+
+    /// 提问者的访问密级，只向密级不低于该级别的用户推荐此问题
+    pub clearance: super::document::ClearanceLevel,
+
+    /// 查询 ID
+    #[sea_orm(column_type = "String(Some(64))", nullable)]
+    pub query_id: Option<String>,
+
+    /// 会话 ID
+    #[sea_orm(column_type = "String(Some(255))", nullable)]
+    pub session_id: Option<String>,
+
+    /// 答案
+    #[sea_orm(column_type = "Text", nullable)]
+    pub answer: Option<String>,
+
+    /// 答案引用的来源文档（JSON 数组）
+    #[sea_orm(column_type = "Json")]
+    pub citations: Json,
+
+    /// 用户反馈类型
+    #[sea_orm(column_type = "String(Some(20))", nullable)]
+    pub feedback_type: Option<String>,
+
+    /// 用户评分（1-5）
+    #[sea_orm(nullable)]
+    pub feedback_rating: Option<i16>,
+
+    /// 用户反馈内容
+    #[sea_orm(column_type = "Text", nullable)]
+    pub feedback_comment: Option<String>,
+
+    /// 反馈时间
+    #[sea_orm(nullable)]
+    pub feedback_at: Option<DateTimeWithTimeZone>,
+
+    /// 人工审校后的答案，构建微调数据集时优先于原答案
+    #[sea_orm(column_type = "Text", nullable)]
+    pub reviewed_answer: Option<String>,
+
+    /// 审校人
+    #[sea_orm(nullable)]
+    pub reviewed_by: Option<Uuid>,
+
+    /// 审校时间
+    #[sea_orm(nullable)]
+    pub reviewed_at: Option<DateTimeWithTimeZone>,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// 问答查询日志关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：查询日志 -> 知识库
+    #[sea_orm(
+        belongs_to = "super::knowledge_base::Entity",
+        from = "Column::KnowledgeBaseId",
+        to = "super::knowledge_base::Column::Id"
+    )]
+    KnowledgeBase,
+}
+
+/// 实现与知识库的关联
+impl Related<super::knowledge_base::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::KnowledgeBase.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,129 @@
+// 保存的搜索实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::document::DocumentType;
+
+/// 搜索匹配方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(20))")]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMatchMode {
+    /// 关键词匹配：标题或内容包含全部关键词
+    #[sea_orm(string_value = "keyword")]
+    Keyword,
+    /// 语义匹配：文档与查询的向量相似度达到阈值
+    #[sea_orm(string_value = "semantic")]
+    Semantic,
+}
+
+/// 保存的搜索实体，用户对知识库的搜索条件及新文档告警订阅
+///
+/// 查询向量保存在 `query_embedding` 列中，只通过原生 SQL 读写，不映射到实体字段。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "saved_searches")]
+pub struct Model {
+    /// 搜索 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 所属用户 ID
+    pub user_id: Uuid,
+
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+
+    /// 搜索名称
+    pub name: String,
+
+    /// 查询内容
+    #[sea_orm(column_type = "Text")]
+    pub query: String,
+
+    /// 匹配方式
+    pub match_mode: SearchMatchMode,
+
+    /// 语义匹配的相似度阈值
+    #[sea_orm(nullable)]
+    pub similarity_threshold: Option<f32>,
+
+    /// 过滤条件
+    #[sea_orm(column_type = "Json")]
+    pub filters: Json,
+
+    /// 是否启用新文档告警
+    pub alerts_enabled: bool,
+
+    /// 最近一次告警时间
+    #[sea_orm(nullable)]
+    pub last_alerted_at: Option<DateTimeWithTimeZone>,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+
+    /// 更新时间
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+/// 保存的搜索过滤条件
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SavedSearchFilters {
+    /// 文档类型，为空时不限制
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    pub doc_types: Vec<DocumentType>,
+    /// 文档需包含的全部标签
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 作者
+    #[serde(default)]
+    pub author: Option<String>,
+}
+
+/// 保存的搜索关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：搜索 -> 用户
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+
+    /// 多对一：搜索 -> 知识库
+    #[sea_orm(
+        belongs_to = "super::knowledge_base::Entity",
+        from = "Column::KnowledgeBaseId",
+        to = "super::knowledge_base::Column::Id"
+    )]
+    KnowledgeBase,
+}
+
+/// 实现与用户的关联
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+/// 实现与知识库的关联
+impl Related<super::knowledge_base::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::KnowledgeBase.def()
+    }
+}
+
+impl Model {
+    /// 获取过滤条件
+    pub fn get_filters(&self) -> SavedSearchFilters {
+        serde_json::from_value(self.filters.clone()).unwrap_or_default()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
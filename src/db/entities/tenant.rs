@@ -4,6 +4,8 @@ use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::config::TlsMinVersion;
+
 /// 租户状态枚举
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "tenant_status")]
@@ -75,7 +77,9 @@ pub struct Model {
     
     /// 最后活跃时间
     #[sea_orm(nullable)]
-    pub last_active_at: Option<DateTimeWithTimeZone>,
+    pub last_active_at: Option<DateTimeWithTimeZone>,    
+    /// 修订号，每次编辑递增，用于乐观锁
+    pub revision: i32,
 }
 
 /// 租户关联关系
@@ -108,6 +112,275 @@ pub struct TenantConfig {
     pub features: TenantFeatures,
     /// 自定义设置
     pub custom_settings: serde_json::Value,
+    /// 订阅套餐
+    #[serde(default)]
+    pub plan: TenantPlan,
+    /// 模型路由覆盖设置
+    #[serde(default)]
+    pub model_routing: TenantModelRouting,
+    /// 模型准入策略
+    #[serde(default)]
+    pub model_policy: TenantModelPolicy,
+    /// 助手品牌与人设
+    #[serde(default)]
+    pub persona: TenantPersona,
+    /// 登录认证策略
+    #[serde(default)]
+    pub auth_policy: TenantAuthPolicy,
+    /// 原始提示词与模型输出的留存策略
+    #[serde(default)]
+    pub prompt_retention: TenantPromptRetention,
+    /// 出站请求的代理与 TLS 覆盖设置
+    #[serde(default)]
+    pub egress: TenantEgressPolicy,
+    /// 搜索工具的联网搜索后端
+    #[serde(default)]
+    pub web_search: TenantWebSearch,
+}
+
+/// 租户订阅套餐，按等级从低到高排列
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TenantPlan {
+    /// 免费版
+    Free,
+    /// 标准版
+    #[default]
+    Standard,
+    /// 企业版
+    Enterprise,
+}
+
+impl TenantPlan {
+    /// 套餐的标准配额限制，租户切换套餐时使用
+    pub fn default_quota_limits(&self) -> TenantQuotaLimits {
+        match self {
+            TenantPlan::Free => TenantQuotaLimits {
+                max_users: 5,
+                max_knowledge_bases: 2,
+                max_documents: 100,
+                max_storage_bytes: 100 * 1024 * 1024, // 100MB
+                monthly_api_calls: 1000,
+                daily_ai_queries: 100,
+            },
+            TenantPlan::Standard => TenantQuotaLimits::default(),
+            TenantPlan::Enterprise => TenantQuotaLimits {
+                max_users: 1000,
+                max_knowledge_bases: 100,
+                max_documents: 100_000,
+                max_storage_bytes: 100 * 1024 * 1024 * 1024, // 100GB
+                monthly_api_calls: 1_000_000,
+                daily_ai_queries: 50_000,
+            },
+        }
+    }
+}
+
+/// 租户模型路由覆盖设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct TenantModelRouting {
+    /// 按任务类型固定使用的模型（键为 `qa`、`summarization`、`agent_reasoning`、`embedding`）
+    #[serde(default)]
+    pub pinned_models: std::collections::HashMap<String, String>,
+    /// 禁止使用的模型
+    #[serde(default)]
+    pub excluded_models: Vec<String>,
+    /// 首选模型不可用时依次尝试的模型
+    #[serde(default)]
+    pub fallback_models: Vec<String>,
+}
+
+/// 租户模型准入策略
+///
+/// 模型可写完整 ID（`openai/gpt-3.5-turbo`）或模型名（`gpt-3.5-turbo`），提供商与模型名均不区分大小写。
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TenantModelPolicy {
+    /// 允许的提供商，为空表示不限制
+    #[serde(default)]
+    pub allowed_providers: Vec<String>,
+    /// 禁止的提供商
+    #[serde(default)]
+    pub denied_providers: Vec<String>,
+    /// 允许的模型，为空表示不限制
+    #[serde(default)]
+    pub allowed_models: Vec<String>,
+    /// 禁止的模型
+    #[serde(default)]
+    pub denied_models: Vec<String>,
+    /// 是否允许调用外部 API，关闭后只能使用本地部署的模型
+    #[serde(default = "default_allow_external_apis")]
+    pub allow_external_apis: bool,
+}
+
+fn default_allow_external_apis() -> bool {
+    true
+}
+
+impl Default for TenantModelPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_providers: Vec::new(),
+            denied_providers: Vec::new(),
+            allowed_models: Vec::new(),
+            denied_models: Vec::new(),
+            allow_external_apis: true,
+        }
+    }
+}
+
+/// 租户助手品牌与人设，注入问答与 Agent 的提示词
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TenantPersona {
+    /// 助手名称
+    #[serde(default)]
+    pub assistant_name: Option<String>,
+    /// 语气与表达要求
+    #[serde(default)]
+    pub tone_guidelines: Option<String>,
+    /// 回答中禁止使用的措辞
+    #[serde(default)]
+    pub banned_phrases: Vec<String>,
+    /// 新会话的欢迎语
+    #[serde(default)]
+    pub greeting: Option<String>,
+}
+
+/// 租户登录认证策略
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TenantAuthPolicy {
+    /// 必须使用通行密钥登录的角色（`admin`、`manager`、`user`、`viewer`），为空表示不强制
+    ///
+    /// 这些角色的用户注册通行密钥后不能再用密码登录；尚未注册的用户仍可用密码登录，登录响应提示其尽快注册。
+    #[serde(default)]
+    pub passkey_required_roles: Vec<String>,
+}
+
+impl TenantAuthPolicy {
+    /// 该角色是否必须使用通行密钥登录
+    pub fn requires_passkey(&self, role: &str) -> bool {
+        self.passkey_required_roles.iter().any(|r| r.eq_ignore_ascii_case(role))
+    }
+}
+
+/// 原始提示词与模型输出的留存策略
+///
+/// 默认不保存；开启后调用日志与执行轨迹中的提示词和模型输出按脱敏方式处理后保存，到期自动删除。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TenantPromptRetention {
+    /// 是否保存发送给模型的原始提示词
+    #[serde(default)]
+    pub persist_prompts: bool,
+    /// 是否保存模型返回的原始输出
+    #[serde(default)]
+    pub persist_completions: bool,
+    /// 保存天数，到期后自动删除
+    #[serde(default = "default_prompt_retention_days")]
+    pub retention_days: u32,
+    /// 保存前的脱敏方式
+    #[serde(default)]
+    pub redaction: PromptRedaction,
+    /// 单条内容最多保存的字符数，超出部分截断；为空表示不截断
+    #[serde(default)]
+    pub max_chars: Option<u32>,
+}
+
+fn default_prompt_retention_days() -> u32 {
+    7
+}
+
+impl Default for TenantPromptRetention {
+    fn default() -> Self {
+        Self {
+            persist_prompts: false,
+            persist_completions: false,
+            retention_days: default_prompt_retention_days(),
+            redaction: PromptRedaction::default(),
+            max_chars: None,
+        }
+    }
+}
+
+/// 提示词与模型输出的脱敏方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptRedaction {
+    /// 保存原文
+    None,
+    /// 将邮箱、手机号、身份证号等个人信息替换为占位符
+    #[default]
+    MaskPii,
+    /// 只保存内容摘要与长度，不保存原文
+    Digest,
+}
+
+/// 租户出站网络覆盖设置
+///
+/// 未设置的项沿用全局出站配置；租户追加的 CA 证书与全局证书同时生效，最低 TLS 版本只能高于全局设置。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TenantEgressPolicy {
+    /// 租户专用出站代理地址，设置后替代全局代理
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    /// 使用租户代理时不经代理直连的主机，规则同 `NO_PROXY`
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    /// 额外信任的 CA 证书（PEM，可包含多张证书）
+    #[serde(default)]
+    pub ca_bundle_pem: Option<String>,
+    /// 最低 TLS 版本，低于全局设置时按全局设置
+    #[serde(default)]
+    pub tls_min_version: Option<TlsMinVersion>,
+}
+
+/// 租户联网搜索设置
+///
+/// API 密钥保存在租户密钥中，这里只记录密钥名称。未设置搜索后端时搜索工具不提供联网搜索。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TenantWebSearch {
+    /// 搜索后端
+    #[serde(default)]
+    pub provider: Option<WebSearchProvider>,
+    /// 保存 API 密钥的租户密钥名称，SerpAPI 与 Bing 必填
+    #[serde(default)]
+    pub api_key_secret: Option<String>,
+    /// 接口地址，SearxNG 必填，其他后端默认使用官方地址
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// 搜索市场或语言，如 zh-CN
+    #[serde(default)]
+    pub market: Option<String>,
+    /// 是否开启安全搜索
+    #[serde(default = "default_safe_search")]
+    pub safe_search: bool,
+}
+
+fn default_safe_search() -> bool {
+    true
+}
+
+impl Default for TenantWebSearch {
+    fn default() -> Self {
+        Self {
+            provider: None,
+            api_key_secret: None,
+            endpoint: None,
+            market: None,
+            safe_search: default_safe_search(),
+        }
+    }
+}
+
+/// 联网搜索后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebSearchProvider {
+    /// SerpAPI（Google 搜索结果）
+    #[serde(rename = "serpapi")]
+    SerpApi,
+    /// Bing Web Search API
+    Bing,
+    /// 自建 SearxNG 实例
+    Searxng,
 }
 
 /// 租户功能开关
@@ -169,6 +442,14 @@ impl Default for TenantConfig {
             theme: "default".to_string(),
             features: TenantFeatures::default(),
             custom_settings: serde_json::Value::Object(serde_json::Map::new()),
+            plan: TenantPlan::default(),
+            model_routing: TenantModelRouting::default(),
+            model_policy: TenantModelPolicy::default(),
+            persona: TenantPersona::default(),
+            auth_policy: TenantAuthPolicy::default(),
+            prompt_retention: TenantPromptRetention::default(),
+            egress: TenantEgressPolicy::default(),
+            web_search: TenantWebSearch::default(),
         }
     }
 }
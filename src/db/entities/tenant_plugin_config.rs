@@ -0,0 +1,60 @@
+// 租户插件配置实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 租户插件配置实体
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "tenant_plugin_configs")]
+pub struct Model {
+    /// 配置 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 插件 ID
+    #[sea_orm(column_type = "String(Some(255))")]
+    pub plugin_id: String,
+
+    /// 非敏感配置项（JSON 格式）
+    #[sea_orm(column_type = "Json")]
+    pub config: Json,
+
+    /// 敏感配置项（字段名 -> 加密后的密文）
+    #[sea_orm(column_type = "Json")]
+    #[serde(skip_serializing)]
+    pub encrypted_secrets: Json,
+
+    /// 最后修改人
+    #[sea_orm(nullable)]
+    pub updated_by: Option<Uuid>,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+
+    /// 更新时间
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+/// 租户插件配置关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：插件配置 -> 租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,56 @@
+// 租户沙箱实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 租户沙箱，每个生产租户最多对应一个沙箱租户
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "tenant_sandboxes")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 生产租户 ID
+    #[sea_orm(unique)]
+    pub tenant_id: Uuid,
+
+    /// 沙箱租户 ID
+    #[sea_orm(unique)]
+    pub sandbox_tenant_id: Uuid,
+
+    /// 创建人
+    pub created_by: Uuid,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+
+    /// 最近一次提升到生产的时间
+    #[sea_orm(nullable)]
+    pub last_promoted_at: Option<DateTimeWithTimeZone>,
+
+    /// 最近一次提升的操作人
+    #[sea_orm(nullable)]
+    pub last_promoted_by: Option<Uuid>,
+}
+
+/// 租户沙箱关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：沙箱 -> 生产租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
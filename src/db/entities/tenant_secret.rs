@@ -0,0 +1,60 @@
+// 租户密钥实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 租户密钥实体，工作流步骤通过 `{{secrets.NAME}}` 引用
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "tenant_secrets")]
+pub struct Model {
+    /// 密钥 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 密钥名称（租户内唯一）
+    #[sea_orm(column_type = "String(Some(128))")]
+    pub name: String,
+
+    /// 密钥说明
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+
+    /// 加密后的密钥值
+    #[sea_orm(column_type = "Text")]
+    #[serde(skip_serializing)]
+    pub encrypted_value: String,
+
+    /// 最后修改人
+    #[sea_orm(nullable)]
+    pub updated_by: Option<Uuid>,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+
+    /// 更新时间
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+/// 租户密钥关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：密钥 -> 租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,111 @@
+// 用量异常实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// 参与异常检测的用量指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(32))")]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyMetric {
+    /// 模型消耗的 token 数
+    #[sea_orm(string_value = "tokens")]
+    Tokens,
+    /// 登录失败次数
+    #[sea_orm(string_value = "failed_logins")]
+    FailedLogins,
+    /// 删除的文档数
+    #[sea_orm(string_value = "document_deletions")]
+    DocumentDeletions,
+}
+
+impl AnomalyMetric {
+    /// 所有指标
+    pub const ALL: [AnomalyMetric; 3] = [
+        AnomalyMetric::Tokens,
+        AnomalyMetric::FailedLogins,
+        AnomalyMetric::DocumentDeletions,
+    ];
+
+    /// 数据库中的指标名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalyMetric::Tokens => "tokens",
+            AnomalyMetric::FailedLogins => "failed_logins",
+            AnomalyMetric::DocumentDeletions => "document_deletions",
+        }
+    }
+
+    /// 告警中展示的指标名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            AnomalyMetric::Tokens => "Token 用量",
+            AnomalyMetric::FailedLogins => "登录失败次数",
+            AnomalyMetric::DocumentDeletions => "文档删除数",
+        }
+    }
+
+    /// 从数据库中的指标名称解析
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|metric| metric.as_str() == value)
+    }
+}
+
+/// 用量异常记录，每个租户的每个指标在同一小时内最多记录一次
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "usage_anomalies")]
+pub struct Model {
+    /// 异常 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 异常指标
+    pub metric: AnomalyMetric,
+
+    /// 异常所在小时的起始时间
+    pub bucket_start: DateTimeWithTimeZone,
+
+    /// 检测时该小时的观测值
+    pub observed_value: i64,
+
+    /// 滚动基线的均值
+    pub baseline_mean: f64,
+
+    /// 滚动基线的标准差
+    pub baseline_stddev: f64,
+
+    /// 贡献用量最多的 API 密钥
+    #[sea_orm(nullable)]
+    pub api_key_id: Option<Uuid>,
+
+    /// 是否已对该 API 密钥自动限流
+    pub throttled: bool,
+
+    /// 检测时间
+    pub detected_at: DateTimeWithTimeZone,
+}
+
+/// 用量异常关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：异常 -> 租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -179,22 +179,85 @@ impl Related<super::session::Entity> for Entity {
 impl ActiveModelBehavior for ActiveModel {}
 
 /// 用户偏好设置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(default)]
 pub struct UserPreferences {
     /// 语言设置
     pub language: String,
-    /// 时区设置
+    /// 时区设置（IANA 名称，如 Asia/Shanghai）
     pub timezone: String,
     /// 主题设置
     pub theme: String,
+    /// 回答语言，未设置时使用语言设置
+    pub answer_language: Option<String>,
+    /// 回答详略程度
+    pub verbosity: AnswerVerbosity,
+    /// 引用来源的方式
+    pub citation_style: CitationStyle,
     /// 通知设置
     pub notifications: NotificationSettings,
     /// 界面设置
     pub ui_settings: UiSettings,
 }
 
+/// 回答详略程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerVerbosity {
+    /// 简洁
+    Concise,
+    /// 适中
+    #[default]
+    Balanced,
+    /// 详细
+    Detailed,
+}
+
+impl AnswerVerbosity {
+    /// 对应的回答风格描述，用于提示词
+    pub fn style(&self) -> &'static str {
+        match self {
+            AnswerVerbosity::Concise => "简洁，只给出结论和关键要点",
+            AnswerVerbosity::Balanced => "专业且友好",
+            AnswerVerbosity::Detailed => "详细，给出必要的背景、步骤和示例",
+        }
+    }
+}
+
+/// 引用来源的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CitationStyle {
+    /// 在正文中就地标注
+    #[default]
+    Inline,
+    /// 在答案末尾以脚注列出
+    Footnote,
+    /// 不标注来源
+    None,
+}
+
+impl CitationStyle {
+    /// 对应的引用要求，用于提示词；不标注来源时为空
+    pub fn instruction(&self) -> &'static str {
+        match self {
+            CitationStyle::Inline => "请在答案中标注信息来源（如：根据文档片段1...）。",
+            CitationStyle::Footnote => "请在答案正文中用 [1]、[2] 标记引用，并在答案末尾以脚注形式列出对应的文档片段。",
+            CitationStyle::None => "",
+        }
+    }
+}
+
+impl UserPreferences {
+    /// 回答使用的语言
+    pub fn effective_answer_language(&self) -> &str {
+        self.answer_language.as_deref().unwrap_or(&self.language)
+    }
+}
+
 /// 通知设置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(default)]
 pub struct NotificationSettings {
     /// 邮件通知
     pub email_notifications: bool,
@@ -207,7 +270,8 @@ pub struct NotificationSettings {
 }
 
 /// 界面设置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(default)]
 pub struct UiSettings {
     /// 侧边栏折叠状态
     pub sidebar_collapsed: bool,
@@ -249,6 +313,9 @@ impl Default for UserPreferences {
             language: "zh-CN".to_string(),
             timezone: "Asia/Shanghai".to_string(),
             theme: "default".to_string(),
+            answer_language: None,
+            verbosity: AnswerVerbosity::default(),
+            citation_style: CitationStyle::default(),
             notifications: NotificationSettings::default(),
             ui_settings: UiSettings::default(),
         }
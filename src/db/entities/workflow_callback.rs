@@ -0,0 +1,86 @@
+// 工作流外部回调实体定义
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// 外部回调状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum CallbackStatus {
+    /// 等待外部系统回调
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    /// 已收到回调
+    #[sea_orm(string_value = "received")]
+    Received,
+    /// 超时未收到回调
+    #[sea_orm(string_value = "timed_out")]
+    TimedOut,
+}
+
+/// 工作流外部回调记录，每个等待回调步骤的每次执行对应一条
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "workflow_callbacks")]
+pub struct Model {
+    /// 回调 ID
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+
+    /// 租户 ID
+    pub tenant_id: Uuid,
+
+    /// 工作流执行 ID
+    pub execution_id: Uuid,
+
+    /// 步骤 ID
+    #[sea_orm(column_type = "String(Some(255))")]
+    pub step_id: String,
+
+    /// 回调状态
+    pub status: CallbackStatus,
+
+    /// 注册回调时的步骤配置快照，包含载荷映射与超时处理方式
+    #[sea_orm(column_type = "Json")]
+    pub step_config: Json,
+
+    /// 收到的原始回调载荷
+    #[sea_orm(column_type = "Json", nullable)]
+    pub payload: Option<Json>,
+
+    /// 按映射提取后写入工作流上下文的值
+    #[sea_orm(column_type = "Json", nullable)]
+    pub context: Option<Json>,
+
+    /// 超时时间
+    pub expires_at: DateTimeWithTimeZone,
+
+    /// 收到回调的时间
+    #[sea_orm(nullable)]
+    pub received_at: Option<DateTimeWithTimeZone>,
+
+    /// 创建时间
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// 外部回调关联关系
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// 多对一：回调 -> 租户
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+/// 实现与租户的关联
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -19,6 +19,46 @@ pub fn get_all_migrations() -> Vec<Migration> {
         create_step_executions_table(),
         add_indexes(),
         add_constraints(),
+        create_distributed_locks_table(),
+        create_tenant_plugin_configs_table(),
+        create_plugin_trusted_keys_table(),
+        create_kb_snapshots_tables(),
+        create_few_shot_examples_table(),
+        create_saved_searches_table(),
+        add_document_freshness_columns(),
+        add_clearance_columns(),
+        create_qa_query_logs_table(),
+        create_kb_faq_entries_table(),
+        add_qa_transcript_columns(),
+        add_qa_answer_review_columns(),
+        create_replication_tables(),
+        create_execution_events_table(),
+        add_revision_columns(),
+        create_usage_anomaly_tables(),
+        create_workflow_callbacks_table(),
+        create_tenant_secrets_table(),
+        create_execution_artifacts_table(),
+        create_agent_memory_snapshots_table(),
+        create_tenant_sandboxes_table(),
+        create_kb_storage_stats_table(),
+        create_execution_records_table(),
+        create_canary_releases_table(),
+        create_lexical_index_tables(),
+        create_dataset_tables(),
+        create_kb_pii_overrides_table(),
+        create_tenant_encryption_keys_table(),
+        create_legal_hold_tables(),
+        create_terms_consent_tables(),
+        create_scheduled_report_tables(),
+        add_document_evergreen_column(),
+        add_knowledge_base_archived_status(),
+        create_passkey_tables(),
+        create_admin_elevation_tables(),
+        add_audit_log_resource_columns(),
+        create_document_comments_table(),
+        create_curated_answers_table(),
+        create_kb_topic_maps_table(),
+        create_model_call_logs_table(),
     ]
 }
 
@@ -599,4 +639,1427 @@ fn add_constraints() -> Migration {
         "#.to_string(),
         dependencies: vec!["20240101_000013".to_string()],
     }
-}
\ No newline at end of file
+}
+
+/// 创建分布式锁表
+fn create_distributed_locks_table() -> Migration {
+    Migration {
+        version: "20240101_000015".to_string(),
+        name: "create_distributed_locks_table".to_string(),
+        description: "创建分布式租约锁表".to_string(),
+        up_sql: r#"
+            CREATE TABLE distributed_locks (
+                name VARCHAR(255) PRIMARY KEY,
+                holder_id VARCHAR(255) NOT NULL,
+                acquired_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                expires_at TIMESTAMPTZ NOT NULL
+            );
+
+            CREATE INDEX idx_distributed_locks_holder_id ON distributed_locks(holder_id);
+            CREATE INDEX idx_distributed_locks_expires_at ON distributed_locks(expires_at);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS distributed_locks;
+        "#.to_string(),
+        dependencies: vec!["20240101_000014".to_string()],
+    }
+}
+
+/// 创建租户插件配置表
+fn create_tenant_plugin_configs_table() -> Migration {
+    Migration {
+        version: "20240101_000016".to_string(),
+        name: "create_tenant_plugin_configs_table".to_string(),
+        description: "创建租户级插件配置表".to_string(),
+        up_sql: r#"
+            CREATE TABLE tenant_plugin_configs (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                plugin_id VARCHAR(255) NOT NULL,
+                config JSONB NOT NULL DEFAULT '{}',
+                encrypted_secrets JSONB NOT NULL DEFAULT '{}',
+                updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(tenant_id, plugin_id)
+            );
+
+            CREATE INDEX idx_tenant_plugin_configs_plugin_id ON tenant_plugin_configs(plugin_id);
+
+            CREATE TRIGGER update_tenant_plugin_configs_updated_at BEFORE UPDATE ON tenant_plugin_configs
+                FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS tenant_plugin_configs;
+        "#.to_string(),
+        dependencies: vec!["20240101_000015".to_string()],
+    }
+}
+
+/// 创建插件签名受信任密钥表
+fn create_plugin_trusted_keys_table() -> Migration {
+    Migration {
+        version: "20240101_000017".to_string(),
+        name: "create_plugin_trusted_keys_table".to_string(),
+        description: "创建租户级插件签名受信任密钥表".to_string(),
+        up_sql: r#"
+            CREATE TABLE plugin_trusted_keys (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                key_id VARCHAR(64) NOT NULL,
+                name VARCHAR(255) NOT NULL,
+                public_key TEXT NOT NULL,
+                created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                revoked_at TIMESTAMPTZ,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(tenant_id, key_id)
+            );
+
+            CREATE INDEX idx_plugin_trusted_keys_tenant_active ON plugin_trusted_keys(tenant_id) WHERE revoked_at IS NULL;
+
+            CREATE TRIGGER update_plugin_trusted_keys_updated_at BEFORE UPDATE ON plugin_trusted_keys
+                FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS plugin_trusted_keys;
+        "#.to_string(),
+        dependencies: vec!["20240101_000016".to_string()],
+    }
+}
+
+/// 创建知识库快照表
+fn create_kb_snapshots_tables() -> Migration {
+    Migration {
+        version: "20240101_000018".to_string(),
+        name: "create_kb_snapshots_tables".to_string(),
+        description: "创建知识库快照及快照文档、文档块表".to_string(),
+        up_sql: r#"
+            CREATE TABLE kb_snapshots (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                knowledge_base_id UUID NOT NULL REFERENCES knowledge_bases(id) ON DELETE CASCADE,
+                name VARCHAR(100) NOT NULL,
+                description TEXT,
+                document_count INTEGER NOT NULL DEFAULT 0,
+                chunk_count INTEGER NOT NULL DEFAULT 0,
+                created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(knowledge_base_id, name)
+            );
+
+            CREATE INDEX idx_kb_snapshots_tenant_kb ON kb_snapshots(tenant_id, knowledge_base_id);
+
+            CREATE TABLE kb_snapshot_documents (
+                snapshot_id UUID NOT NULL REFERENCES kb_snapshots(id) ON DELETE CASCADE,
+                document_id UUID NOT NULL,
+                title VARCHAR(500) NOT NULL,
+                file_type VARCHAR(50),
+                content_hash VARCHAR(64) NOT NULL,
+                source_updated_at TIMESTAMPTZ NOT NULL,
+                PRIMARY KEY (snapshot_id, document_id)
+            );
+
+            CREATE TABLE kb_snapshot_chunks (
+                snapshot_id UUID NOT NULL REFERENCES kb_snapshots(id) ON DELETE CASCADE,
+                chunk_id UUID NOT NULL,
+                document_id UUID NOT NULL,
+                chunk_index INTEGER NOT NULL,
+                content TEXT NOT NULL,
+                content_hash VARCHAR(64) NOT NULL,
+                metadata JSONB NOT NULL DEFAULT '{}',
+                model_name VARCHAR(100),
+                vector vector(1536),
+                PRIMARY KEY (snapshot_id, chunk_id)
+            );
+
+            CREATE INDEX idx_kb_snapshot_chunks_document ON kb_snapshot_chunks(snapshot_id, document_id);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS kb_snapshot_chunks;
+            DROP TABLE IF EXISTS kb_snapshot_documents;
+            DROP TABLE IF EXISTS kb_snapshots;
+        "#.to_string(),
+        dependencies: vec!["20240101_000017".to_string()],
+    }
+}
+
+/// 创建少样本示例表
+fn create_few_shot_examples_table() -> Migration {
+    Migration {
+        version: "20240101_000019".to_string(),
+        name: "create_few_shot_examples_table".to_string(),
+        description: "创建 Agent 与提示词模板的少样本示例表".to_string(),
+        up_sql: r#"
+            CREATE TABLE few_shot_examples (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                agent_id UUID,
+                prompt_template VARCHAR(100),
+                input TEXT NOT NULL,
+                output TEXT NOT NULL,
+                tags JSONB NOT NULL DEFAULT '[]',
+                embedding vector(1536),
+                created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                CHECK (agent_id IS NOT NULL OR prompt_template IS NOT NULL)
+            );
+
+            CREATE INDEX idx_few_shot_examples_agent ON few_shot_examples(tenant_id, agent_id);
+            CREATE INDEX idx_few_shot_examples_template ON few_shot_examples(tenant_id, prompt_template);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS few_shot_examples;
+        "#.to_string(),
+        dependencies: vec!["20240101_000018".to_string()],
+    }
+}
+
+/// 创建保存的搜索表
+fn create_saved_searches_table() -> Migration {
+    Migration {
+        version: "20240101_000020".to_string(),
+        name: "create_saved_searches_table".to_string(),
+        description: "创建知识库保存的搜索与文档内容告警订阅表".to_string(),
+        up_sql: r#"
+            CREATE TABLE saved_searches (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                knowledge_base_id UUID NOT NULL REFERENCES knowledge_bases(id) ON DELETE CASCADE,
+                name VARCHAR(255) NOT NULL,
+                query TEXT NOT NULL,
+                match_mode VARCHAR(20) NOT NULL DEFAULT 'keyword' CHECK (match_mode IN ('keyword', 'semantic')),
+                similarity_threshold REAL CHECK (similarity_threshold IS NULL OR (similarity_threshold > 0 AND similarity_threshold <= 1)),
+                filters JSONB NOT NULL DEFAULT '{}',
+                query_embedding vector(1536),
+                alerts_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                last_alerted_at TIMESTAMPTZ,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_saved_searches_user ON saved_searches(tenant_id, user_id);
+            CREATE INDEX idx_saved_searches_alerts ON saved_searches(knowledge_base_id) WHERE alerts_enabled;
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS saved_searches;
+        "#.to_string(),
+        dependencies: vec!["20240101_000019".to_string()],
+    }
+}
+
+/// 为文档添加时效与复核字段
+fn add_document_freshness_columns() -> Migration {
+    Migration {
+        version: "20240101_000021".to_string(),
+        name: "add_document_freshness_columns".to_string(),
+        description: "为文档添加过期时间、复核时间、负责人与复核提醒时间".to_string(),
+        up_sql: r#"
+            ALTER TABLE documents
+                ADD COLUMN expires_at TIMESTAMPTZ,
+                ADD COLUMN review_at TIMESTAMPTZ,
+                ADD COLUMN owner_id UUID REFERENCES users(id) ON DELETE SET NULL,
+                ADD COLUMN review_notified_at TIMESTAMPTZ;
+
+            CREATE INDEX idx_documents_expires_at ON documents(knowledge_base_id, expires_at) WHERE expires_at IS NOT NULL;
+            CREATE INDEX idx_documents_review_at ON documents(knowledge_base_id, review_at) WHERE review_at IS NOT NULL;
+            CREATE INDEX idx_documents_owner ON documents(owner_id) WHERE owner_id IS NOT NULL;
+        "#.to_string(),
+        down_sql: r#"
+            DROP INDEX IF EXISTS idx_documents_owner;
+            DROP INDEX IF EXISTS idx_documents_review_at;
+            DROP INDEX IF EXISTS idx_documents_expires_at;
+            ALTER TABLE documents
+                DROP COLUMN IF EXISTS review_notified_at,
+                DROP COLUMN IF EXISTS owner_id,
+                DROP COLUMN IF EXISTS review_at,
+                DROP COLUMN IF EXISTS expires_at;
+        "#.to_string(),
+        dependencies: vec!["20240101_000020".to_string()],
+    }
+}
+
+/// 为文档与文档块添加访问密级
+fn add_clearance_columns() -> Migration {
+    Migration {
+        version: "20240101_000022".to_string(),
+        name: "add_clearance_columns".to_string(),
+        description: "为文档与文档块添加访问密级，检索时按用户密级过滤".to_string(),
+        up_sql: r#"
+            ALTER TABLE documents
+                ADD COLUMN clearance SMALLINT NOT NULL DEFAULT 0 CHECK (clearance BETWEEN 0 AND 3);
+            ALTER TABLE document_chunks
+                ADD COLUMN clearance SMALLINT NOT NULL DEFAULT 0 CHECK (clearance BETWEEN 0 AND 3);
+
+            CREATE INDEX idx_document_chunks_clearance ON document_chunks(document_id, clearance) WHERE clearance > 0;
+        "#.to_string(),
+        down_sql: r#"
+            DROP INDEX IF EXISTS idx_document_chunks_clearance;
+            ALTER TABLE document_chunks DROP COLUMN IF EXISTS clearance;
+            ALTER TABLE documents DROP COLUMN IF EXISTS clearance;
+        "#.to_string(),
+        dependencies: vec!["20240101_000021".to_string()],
+    }
+}
+
+/// 创建问答查询日志表
+fn create_qa_query_logs_table() -> Migration {
+    Migration {
+        version: "20240101_000023".to_string(),
+        name: "create_qa_query_logs_table".to_string(),
+        description: "创建问答查询日志表，用于热门问题统计与问题推荐".to_string(),
+        up_sql: r#"
+            CREATE TABLE qa_query_logs (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                knowledge_base_id UUID REFERENCES knowledge_bases(id) ON DELETE SET NULL,
+                user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+                question TEXT NOT NULL,
+                normalized_question TEXT NOT NULL,
+                question_embedding vector(1536),
+                confidence_score REAL NOT NULL DEFAULT 0,
+                source_count INTEGER NOT NULL DEFAULT 0,
+                clearance SMALLINT NOT NULL DEFAULT 0 CHECK (clearance BETWEEN 0 AND 3),
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_qa_query_logs_kb_created ON qa_query_logs(tenant_id, knowledge_base_id, created_at DESC);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS qa_query_logs;
+        "#.to_string(),
+        dependencies: vec!["20240101_000022".to_string()],
+    }
+}
+
+/// 创建知识库 FAQ/术语表条目表
+fn create_kb_faq_entries_table() -> Migration {
+    Migration {
+        version: "20240101_000024".to_string(),
+        name: "create_kb_faq_entries_table".to_string(),
+        description: "创建知识库人工维护的 FAQ 与术语表条目表，用于问答快速应答".to_string(),
+        up_sql: r#"
+            CREATE TABLE kb_faq_entries (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                knowledge_base_id UUID NOT NULL REFERENCES knowledge_bases(id) ON DELETE CASCADE,
+                entry_type VARCHAR(20) NOT NULL DEFAULT 'faq' CHECK (entry_type IN ('faq', 'glossary')),
+                question TEXT NOT NULL,
+                answer TEXT NOT NULL,
+                aliases JSONB NOT NULL DEFAULT '[]',
+                embedding vector(1536),
+                clearance SMALLINT NOT NULL DEFAULT 0 CHECK (clearance BETWEEN 0 AND 3),
+                is_active BOOLEAN NOT NULL DEFAULT TRUE,
+                hit_count INTEGER NOT NULL DEFAULT 0,
+                last_hit_at TIMESTAMPTZ,
+                created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_kb_faq_entries_kb ON kb_faq_entries(knowledge_base_id) WHERE is_active;
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS kb_faq_entries;
+        "#.to_string(),
+        dependencies: vec!["20240101_000023".to_string()],
+    }
+}
+
+/// 为问答查询日志添加会话记录字段
+fn add_qa_transcript_columns() -> Migration {
+    Migration {
+        version: "20240101_000025".to_string(),
+        name: "add_qa_transcript_columns".to_string(),
+        description: "为问答查询日志添加查询 ID、会话 ID、答案、引用与用户反馈，用于会话记录导出".to_string(),
+        up_sql: r#"
+            ALTER TABLE qa_query_logs
+                ADD COLUMN query_id VARCHAR(64),
+                ADD COLUMN session_id VARCHAR(255),
+                ADD COLUMN answer TEXT,
+                ADD COLUMN citations JSONB NOT NULL DEFAULT '[]',
+                ADD COLUMN feedback_type VARCHAR(20),
+                ADD COLUMN feedback_rating SMALLINT CHECK (feedback_rating BETWEEN 1 AND 5),
+                ADD COLUMN feedback_comment TEXT,
+                ADD COLUMN feedback_at TIMESTAMPTZ;
+
+            CREATE UNIQUE INDEX idx_qa_query_logs_query_id ON qa_query_logs(query_id) WHERE query_id IS NOT NULL;
+            CREATE INDEX idx_qa_query_logs_session ON qa_query_logs(tenant_id, session_id, created_at) WHERE session_id IS NOT NULL;
+            CREATE INDEX idx_qa_query_logs_tenant_created ON qa_query_logs(tenant_id, created_at);
+        "#.to_string(),
+        down_sql: r#"
+            DROP INDEX IF EXISTS idx_qa_query_logs_tenant_created;
+            DROP INDEX IF EXISTS idx_qa_query_logs_session;
+            DROP INDEX IF EXISTS idx_qa_query_logs_query_id;
+            ALTER TABLE qa_query_logs
+                DROP COLUMN IF EXISTS feedback_at,
+                DROP COLUMN IF EXISTS feedback_comment,
+                DROP COLUMN IF EXISTS feedback_rating,
+                DROP COLUMN IF EXISTS feedback_type,
+                DROP COLUMN IF EXISTS citations,
+                DROP COLUMN IF EXISTS answer,
+                DROP COLUMN IF EXISTS session_id,
+                DROP COLUMN IF EXISTS query_id;
+        "#.to_string(),
+        dependencies: vec!["20240101_000024".to_string()],
+    }
+}
+
+/// 为问答查询日志添加人工审校答案字段
+fn add_qa_answer_review_columns() -> Migration {
+    Migration {
+        version: "20240101_000026".to_string(),
+        name: "add_qa_answer_review_columns".to_string(),
+        description: "为问答查询日志添加人工审校后的答案，用于构建微调数据集".to_string(),
+        up_sql: r#"
+            ALTER TABLE qa_query_logs
+                ADD COLUMN reviewed_answer TEXT,
+                ADD COLUMN reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                ADD COLUMN reviewed_at TIMESTAMPTZ;
+
+            CREATE INDEX idx_qa_query_logs_training ON qa_query_logs(tenant_id, created_at)
+                WHERE reviewed_answer IS NOT NULL OR feedback_rating IS NOT NULL OR feedback_type IS NOT NULL;
+        "#.to_string(),
+        down_sql: r#"
+            DROP INDEX IF EXISTS idx_qa_query_logs_training;
+            ALTER TABLE qa_query_logs
+                DROP COLUMN IF EXISTS reviewed_at,
+                DROP COLUMN IF EXISTS reviewed_by,
+                DROP COLUMN IF EXISTS reviewed_answer;
+        "#.to_string(),
+        dependencies: vec!["20240101_000025".to_string()],
+    }
+}
+
+/// 创建跨区域复制事件表与变更捕获触发器
+fn create_replication_tables() -> Migration {
+    Migration {
+        version: "20240101_000027".to_string(),
+        name: "create_replication_tables".to_string(),
+        description: "创建复制事件表（预写事件流）与区域复制状态表，并为租户数据表添加变更捕获触发器".to_string(),
+        up_sql: r#"
+            CREATE TABLE replication_events (
+                id BIGSERIAL PRIMARY KEY,
+                tenant_id UUID,
+                table_name VARCHAR(64) NOT NULL,
+                record_id UUID NOT NULL,
+                operation VARCHAR(10) NOT NULL CHECK (operation IN ('insert', 'update', 'delete')),
+                payload JSONB NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                published_at TIMESTAMPTZ
+            );
+
+            CREATE INDEX idx_replication_events_unpublished ON replication_events(id) WHERE published_at IS NULL;
+            CREATE INDEX idx_replication_events_published ON replication_events(published_at) WHERE published_at IS NOT NULL;
+
+            CREATE TABLE replication_state (
+                region VARCHAR(64) PRIMARY KEY,
+                role VARCHAR(20) NOT NULL CHECK (role IN ('primary', 'passive')),
+                stream_position VARCHAR(64),
+                last_applied_event_id BIGINT,
+                last_applied_source_at TIMESTAMPTZ,
+                last_applied_at TIMESTAMPTZ,
+                promoted_at TIMESTAMPTZ,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            );
+
+            CREATE TRIGGER update_replication_state_updated_at BEFORE UPDATE ON replication_state
+                FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+            CREATE OR REPLACE FUNCTION capture_replication_event() RETURNS TRIGGER AS $$
+            DECLARE
+                row_data JSONB;
+                row_tenant UUID;
+            BEGIN
+                -- 备用区域应用复制事件时不再捕获，避免回环
+                IF current_setting('aionix.replication_apply', true) = 'on' THEN
+                    RETURN NULL;
+                END IF;
+
+                IF TG_OP = 'DELETE' THEN
+                    row_data := to_jsonb(OLD);
+                ELSE
+                    row_data := to_jsonb(NEW);
+                END IF;
+
+                -- 向量数据不进入复制流，备用区域提升后重新生成
+                row_data := row_data - 'vector';
+
+                row_tenant := CASE TG_TABLE_NAME
+                    WHEN 'tenants' THEN (row_data->>'id')::uuid
+                    WHEN 'knowledge_bases' THEN (row_data->>'tenant_id')::uuid
+                    WHEN 'documents' THEN (
+                        SELECT kb.tenant_id FROM knowledge_bases kb
+                        WHERE kb.id = (row_data->>'knowledge_base_id')::uuid)
+                    WHEN 'document_chunks' THEN (
+                        SELECT kb.tenant_id FROM documents d
+                        JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id
+                        WHERE d.id = (row_data->>'document_id')::uuid)
+                    WHEN 'embeddings' THEN (
+                        SELECT kb.tenant_id FROM document_chunks c
+                        JOIN documents d ON d.id = c.document_id
+                        JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id
+                        WHERE c.id = (row_data->>'chunk_id')::uuid)
+                END;
+
+                INSERT INTO replication_events (tenant_id, table_name, record_id, operation, payload)
+                VALUES (row_tenant, TG_TABLE_NAME, (row_data->>'id')::uuid, lower(TG_OP), row_data);
+
+                RETURN NULL;
+            END;
+            $$ language 'plpgsql';
+
+            CREATE TRIGGER replicate_tenants AFTER INSERT OR UPDATE OR DELETE ON tenants
+                FOR EACH ROW EXECUTE FUNCTION capture_replication_event();
+            CREATE TRIGGER replicate_knowledge_bases AFTER INSERT OR UPDATE OR DELETE ON knowledge_bases
+                FOR EACH ROW EXECUTE FUNCTION capture_replication_event();
+            CREATE TRIGGER replicate_documents AFTER INSERT OR UPDATE OR DELETE ON documents
+                FOR EACH ROW EXECUTE FUNCTION capture_replication_event();
+            CREATE TRIGGER replicate_document_chunks AFTER INSERT OR UPDATE OR DELETE ON document_chunks
+                FOR EACH ROW EXECUTE FUNCTION capture_replication_event();
+            CREATE TRIGGER replicate_embeddings AFTER INSERT OR UPDATE OR DELETE ON embeddings
+                FOR EACH ROW EXECUTE FUNCTION capture_replication_event();
+        "#.to_string(),
+        down_sql: r#"
+            DROP TRIGGER IF EXISTS replicate_embeddings ON embeddings;
+            DROP TRIGGER IF EXISTS replicate_document_chunks ON document_chunks;
+            DROP TRIGGER IF EXISTS replicate_documents ON documents;
+            DROP TRIGGER IF EXISTS replicate_knowledge_bases ON knowledge_bases;
+            DROP TRIGGER IF EXISTS replicate_tenants ON tenants;
+            DROP FUNCTION IF EXISTS capture_replication_event();
+            DROP TABLE IF EXISTS replication_state;
+            DROP TABLE IF EXISTS replication_events;
+        "#.to_string(),
+        dependencies: vec!["20240101_000026".to_string()],
+    }
+}
+
+/// 创建执行事件表
+fn create_execution_events_table() -> Migration {
+    Migration {
+        version: "20240101_000028".to_string(),
+        name: "create_execution_events_table".to_string(),
+        description: "创建 Agent 与工作流执行的状态转换事件表，事件只追加不修改，用于重建执行时间线与计费".to_string(),
+        up_sql: r#"
+            CREATE TABLE execution_events (
+                id BIGSERIAL PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                execution_type VARCHAR(20) NOT NULL CHECK (execution_type IN ('agent', 'workflow')),
+                execution_id UUID NOT NULL,
+                sequence INTEGER NOT NULL,
+                from_state VARCHAR(32),
+                to_state VARCHAR(32) NOT NULL,
+                payload JSONB NOT NULL DEFAULT '{}',
+                occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                UNIQUE (execution_type, execution_id, sequence)
+            );
+
+            CREATE INDEX idx_execution_events_tenant_occurred ON execution_events(tenant_id, occurred_at);
+
+            CREATE OR REPLACE FUNCTION reject_execution_event_update() RETURNS TRIGGER AS $$
+            BEGIN
+                RAISE EXCEPTION 'execution_events 为只追加表，不允许修改';
+            END;
+            $$ language 'plpgsql';
+
+            CREATE TRIGGER execution_events_immutable BEFORE UPDATE ON execution_events
+                FOR EACH ROW EXECUTE FUNCTION reject_execution_event_update();
+        "#.to_string(),
+        down_sql: r#"
+            DROP TRIGGER IF EXISTS execution_events_immutable ON execution_events;
+            DROP FUNCTION IF EXISTS reject_execution_event_update();
+            DROP TABLE IF EXISTS execution_events;
+        "#.to_string(),
+        dependencies: vec!["20240101_000027".to_string()],
+    }
+}
+
+/// 添加乐观锁修订号
+fn add_revision_columns() -> Migration {
+    Migration {
+        version: "20240101_000029".to_string(),
+        name: "add_revision_columns".to_string(),
+        description: "为租户、知识库、文档与 Agent 添加修订号，更新时校验修订号防止并发编辑互相覆盖".to_string(),
+        up_sql: r#"
+            ALTER TABLE tenants ADD COLUMN revision INTEGER NOT NULL DEFAULT 1;
+            ALTER TABLE knowledge_bases ADD COLUMN revision INTEGER NOT NULL DEFAULT 1;
+            ALTER TABLE documents ADD COLUMN revision INTEGER NOT NULL DEFAULT 1;
+            ALTER TABLE agents ADD COLUMN revision INTEGER NOT NULL DEFAULT 1;
+        "#.to_string(),
+        down_sql: r#"
+            ALTER TABLE agents DROP COLUMN IF EXISTS revision;
+            ALTER TABLE documents DROP COLUMN IF EXISTS revision;
+            ALTER TABLE knowledge_bases DROP COLUMN IF EXISTS revision;
+            ALTER TABLE tenants DROP COLUMN IF EXISTS revision;
+        "#.to_string(),
+        dependencies: vec!["20240101_000028".to_string()],
+    }
+}
+
+/// 创建用量计数与用量异常表
+fn create_usage_anomaly_tables() -> Migration {
+    Migration {
+        version: "20240101_000030".to_string(),
+        name: "create_usage_anomaly_tables".to_string(),
+        description: "创建按小时分桶的租户用量计数表与用量异常记录表，用于基于滚动基线检测用量突增".to_string(),
+        up_sql: r#"
+            CREATE TABLE usage_counters (
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                metric VARCHAR(32) NOT NULL CHECK (metric IN ('tokens', 'failed_logins', 'document_deletions')),
+                -- 未通过 API 密钥访问时为全零 UUID
+                api_key_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000',
+                bucket_start TIMESTAMPTZ NOT NULL,
+                count BIGINT NOT NULL DEFAULT 0,
+                PRIMARY KEY (tenant_id, metric, bucket_start, api_key_id)
+            );
+
+            CREATE INDEX idx_usage_counters_bucket ON usage_counters(bucket_start);
+
+            CREATE TABLE usage_anomalies (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                metric VARCHAR(32) NOT NULL CHECK (metric IN ('tokens', 'failed_logins', 'document_deletions')),
+                bucket_start TIMESTAMPTZ NOT NULL,
+                observed_value BIGINT NOT NULL,
+                baseline_mean DOUBLE PRECISION NOT NULL,
+                baseline_stddev DOUBLE PRECISION NOT NULL,
+                api_key_id UUID,
+                throttled BOOLEAN NOT NULL DEFAULT FALSE,
+                detected_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE (tenant_id, metric, bucket_start)
+            );
+
+            CREATE INDEX idx_usage_anomalies_tenant_detected ON usage_anomalies(tenant_id, detected_at DESC);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS usage_anomalies;
+            DROP TABLE IF EXISTS usage_counters;
+        "#.to_string(),
+        dependencies: vec!["20240101_000029".to_string()],
+    }
+}
+
+/// 创建工作流外部回调表
+fn create_workflow_callbacks_table() -> Migration {
+    Migration {
+        version: "20240101_000031".to_string(),
+        name: "create_workflow_callbacks_table".to_string(),
+        description: "创建工作流外部回调表，记录等待回调步骤的配置快照、超时时间与收到的载荷".to_string(),
+        up_sql: r#"
+            CREATE TABLE workflow_callbacks (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                execution_id UUID NOT NULL,
+                step_id VARCHAR(255) NOT NULL,
+                status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'received', 'timed_out')),
+                step_config JSONB NOT NULL,
+                payload JSONB,
+                context JSONB,
+                expires_at TIMESTAMPTZ NOT NULL,
+                received_at TIMESTAMPTZ,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_workflow_callbacks_execution ON workflow_callbacks(tenant_id, execution_id);
+            CREATE INDEX idx_workflow_callbacks_pending ON workflow_callbacks(expires_at) WHERE status = 'pending';
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS workflow_callbacks;
+        "#.to_string(),
+        dependencies: vec!["20240101_000030".to_string()],
+    }
+}
+
+/// 创建租户密钥表
+fn create_tenant_secrets_table() -> Migration {
+    Migration {
+        version: "20240101_000032".to_string(),
+        name: "create_tenant_secrets_table".to_string(),
+        description: "创建租户密钥表，存储工作流步骤通过 {{secrets.NAME}} 引用的加密凭据".to_string(),
+        up_sql: r#"
+            CREATE TABLE tenant_secrets (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                name VARCHAR(128) NOT NULL,
+                description TEXT,
+                encrypted_value TEXT NOT NULL,
+                updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(tenant_id, name)
+            );
+
+            CREATE TRIGGER update_tenant_secrets_updated_at BEFORE UPDATE ON tenant_secrets
+                FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS tenant_secrets;
+        "#.to_string(),
+        dependencies: vec!["20240101_000031".to_string()],
+    }
+}
+
+/// 创建执行产物表
+fn create_execution_artifacts_table() -> Migration {
+    Migration {
+        version: "20240101_000033".to_string(),
+        name: "create_execution_artifacts_table".to_string(),
+        description: "创建执行产物表，记录转存到存储目录的大体积步骤输出及其保留期限".to_string(),
+        up_sql: r#"
+            CREATE TABLE execution_artifacts (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                execution_type VARCHAR(20) NOT NULL CHECK (execution_type IN ('agent', 'workflow')),
+                execution_id UUID NOT NULL,
+                step_id VARCHAR(255),
+                content_type VARCHAR(100) NOT NULL,
+                size_bytes BIGINT NOT NULL,
+                sha256 CHAR(64) NOT NULL,
+                storage_key TEXT NOT NULL,
+                expires_at TIMESTAMPTZ,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_execution_artifacts_execution ON execution_artifacts(tenant_id, execution_type, execution_id);
+            CREATE INDEX idx_execution_artifacts_expires ON execution_artifacts(expires_at) WHERE expires_at IS NOT NULL;
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS execution_artifacts;
+        "#.to_string(),
+        dependencies: vec!["20240101_000032".to_string()],
+    }
+}
+
+/// 创建 Agent 记忆快照表
+fn create_agent_memory_snapshots_table() -> Migration {
+    Migration {
+        version: "20240101_000034".to_string(),
+        name: "create_agent_memory_snapshots_table".to_string(),
+        description: "创建 Agent 记忆快照表，保存每次任务执行结束时的记忆项及各推理步骤检索到的记忆".to_string(),
+        up_sql: r#"
+            CREATE TABLE agent_memory_snapshots (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                agent_id UUID NOT NULL,
+                execution_id UUID NOT NULL,
+                memories JSONB NOT NULL DEFAULT '[]',
+                retrievals JSONB NOT NULL DEFAULT '[]',
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(agent_id, execution_id)
+            );
+
+            CREATE INDEX idx_agent_memory_snapshots_tenant ON agent_memory_snapshots(tenant_id, created_at);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS agent_memory_snapshots;
+        "#.to_string(),
+        dependencies: vec!["20240101_000033".to_string()],
+    }
+}
+
+/// 创建租户沙箱表
+fn create_tenant_sandboxes_table() -> Migration {
+    Migration {
+        version: "20240101_000035".to_string(),
+        name: "create_tenant_sandboxes_table".to_string(),
+        description: "创建租户沙箱表，记录生产租户与其沙箱租户的对应关系及最近一次提升".to_string(),
+        up_sql: r#"
+            CREATE TABLE tenant_sandboxes (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL UNIQUE REFERENCES tenants(id) ON DELETE CASCADE,
+                sandbox_tenant_id UUID NOT NULL UNIQUE REFERENCES tenants(id) ON DELETE CASCADE,
+                created_by UUID NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                last_promoted_at TIMESTAMPTZ,
+                last_promoted_by UUID
+            );
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS tenant_sandboxes;
+        "#.to_string(),
+        dependencies: vec!["20240101_000034".to_string()],
+    }
+}
+
+/// 创建知识库存储统计表
+fn create_kb_storage_stats_table() -> Migration {
+    Migration {
+        version: "20240101_000036".to_string(),
+        name: "create_kb_storage_stats_table".to_string(),
+        description: "创建知识库存储统计表，按天保存每个知识库的文档、文档块、嵌入数量与存储用量".to_string(),
+        up_sql: r#"
+            CREATE TABLE kb_storage_stats (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                knowledge_base_id UUID NOT NULL REFERENCES knowledge_bases(id) ON DELETE CASCADE,
+                stat_date DATE NOT NULL,
+                document_count BIGINT NOT NULL DEFAULT 0,
+                chunk_count BIGINT NOT NULL DEFAULT 0,
+                embedding_count BIGINT NOT NULL DEFAULT 0,
+                file_bytes BIGINT NOT NULL DEFAULT 0,
+                database_bytes BIGINT NOT NULL DEFAULT 0,
+                failed_documents BIGINT NOT NULL DEFAULT 0,
+                pending_documents BIGINT NOT NULL DEFAULT 0,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(knowledge_base_id, stat_date)
+            );
+
+            CREATE INDEX idx_kb_storage_stats_tenant_date ON kb_storage_stats(tenant_id, stat_date);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS kb_storage_stats;
+        "#.to_string(),
+        dependencies: vec!["20240101_000035".to_string()],
+    }
+}
+
+/// 创建执行记录表
+fn create_execution_records_table() -> Migration {
+    Migration {
+        version: "20240101_000037".to_string(),
+        name: "create_execution_records_table".to_string(),
+        description: "创建执行记录表，保存 Agent 与工作流执行的输入、输出、耗时与令牌用量，用于重放与对比".to_string(),
+        up_sql: r#"
+            CREATE TABLE execution_records (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                execution_type VARCHAR(20) NOT NULL,
+                target_id UUID NOT NULL,
+                input JSONB NOT NULL,
+                output JSONB,
+                status VARCHAR(20) NOT NULL,
+                error_message TEXT,
+                duration_ms BIGINT,
+                total_tokens BIGINT,
+                replay_of UUID REFERENCES execution_records(id) ON DELETE SET NULL,
+                triggered_by UUID,
+                started_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                completed_at TIMESTAMPTZ
+            );
+
+            CREATE INDEX idx_execution_records_tenant_target ON execution_records(tenant_id, execution_type, target_id);
+            CREATE INDEX idx_execution_records_replay_of ON execution_records(replay_of);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS execution_records;
+        "#.to_string(),
+        dependencies: vec!["20240101_000036".to_string()],
+    }
+}
+
+/// 创建灰度发布表
+fn create_canary_releases_table() -> Migration {
+    Migration {
+        version: "20240101_000038".to_string(),
+        name: "create_canary_releases_table".to_string(),
+        description: "创建灰度发布表，并为执行记录增加所属灰度发布、分流版本与用户反馈".to_string(),
+        up_sql: r#"
+            CREATE TABLE canary_releases (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                target_type VARCHAR(20) NOT NULL,
+                target_id UUID NOT NULL,
+                stable_id UUID NOT NULL,
+                canary_id UUID NOT NULL,
+                traffic_percent SMALLINT NOT NULL CHECK (traffic_percent BETWEEN 0 AND 100),
+                user_ids JSONB NOT NULL DEFAULT '[]',
+                status VARCHAR(20) NOT NULL,
+                min_samples INTEGER NOT NULL,
+                min_feedback INTEGER NOT NULL,
+                max_error_rate_increase DOUBLE PRECISION NOT NULL,
+                max_negative_feedback_increase DOUBLE PRECISION NOT NULL,
+                end_reason TEXT,
+                created_by UUID NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                ended_at TIMESTAMPTZ
+            );
+
+            CREATE INDEX idx_canary_releases_target ON canary_releases(tenant_id, target_type, target_id, created_at);
+            CREATE UNIQUE INDEX idx_canary_releases_active ON canary_releases(tenant_id, target_type, target_id)
+                WHERE status = 'active';
+
+            ALTER TABLE execution_records
+                ADD COLUMN canary_release_id UUID REFERENCES canary_releases(id) ON DELETE SET NULL,
+                ADD COLUMN canary_arm VARCHAR(10),
+                ADD COLUMN feedback SMALLINT;
+
+            CREATE INDEX idx_execution_records_canary ON execution_records(canary_release_id);
+        "#.to_string(),
+        down_sql: r#"
+            ALTER TABLE execution_records
+                DROP COLUMN IF EXISTS feedback,
+                DROP COLUMN IF EXISTS canary_arm,
+                DROP COLUMN IF EXISTS canary_release_id;
+            DROP TABLE IF EXISTS canary_releases;
+        "#.to_string(),
+        dependencies: vec!["20240101_000037".to_string()],
+    }
+}
+
+/// 创建稀疏词法索引表
+fn create_lexical_index_tables() -> Migration {
+    Migration {
+        version: "20240101_000039".to_string(),
+        name: "create_lexical_index_tables".to_string(),
+        description: "创建文档块稀疏词法索引（BM25 倒排表），与向量索引并行维护".to_string(),
+        up_sql: r#"
+            CREATE TABLE lexical_chunks (
+                chunk_id UUID PRIMARY KEY REFERENCES document_chunks(id) ON DELETE CASCADE,
+                knowledge_base_id UUID NOT NULL REFERENCES knowledge_bases(id) ON DELETE CASCADE,
+                content_hash VARCHAR(64) NOT NULL,
+                length INTEGER NOT NULL,
+                indexed_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_lexical_chunks_kb ON lexical_chunks(knowledge_base_id);
+
+            CREATE TABLE lexical_postings (
+                chunk_id UUID NOT NULL REFERENCES lexical_chunks(chunk_id) ON DELETE CASCADE,
+                knowledge_base_id UUID NOT NULL,
+                term VARCHAR(128) NOT NULL,
+                tf INTEGER NOT NULL,
+                PRIMARY KEY (chunk_id, term)
+            );
+
+            CREATE INDEX idx_lexical_postings_kb_term ON lexical_postings(knowledge_base_id, term);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS lexical_postings;
+            DROP TABLE IF EXISTS lexical_chunks;
+        "#.to_string(),
+        dependencies: vec!["20240101_000038".to_string()],
+    }
+}
+
+/// 创建数据集表
+fn create_dataset_tables() -> Migration {
+    Migration {
+        version: "20240101_000040".to_string(),
+        name: "create_dataset_tables".to_string(),
+        description: "创建 CSV/Excel 导入的带类型数据集及其行数据表，供聚合查询".to_string(),
+        up_sql: r#"
+            CREATE TABLE datasets (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                knowledge_base_id UUID NOT NULL REFERENCES knowledge_bases(id) ON DELETE CASCADE,
+                document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+                name VARCHAR(255) NOT NULL,
+                sheet_name VARCHAR(255),
+                columns JSONB NOT NULL DEFAULT '[]',
+                row_count INTEGER NOT NULL DEFAULT 0,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_datasets_tenant_kb ON datasets(tenant_id, knowledge_base_id);
+            CREATE INDEX idx_datasets_document ON datasets(document_id);
+
+            CREATE TABLE dataset_rows (
+                dataset_id UUID NOT NULL REFERENCES datasets(id) ON DELETE CASCADE,
+                row_index INTEGER NOT NULL,
+                data JSONB NOT NULL,
+                PRIMARY KEY (dataset_id, row_index)
+            );
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS dataset_rows;
+            DROP TABLE IF EXISTS datasets;
+        "#.to_string(),
+        dependencies: vec!["20240101_000039".to_string()],
+    }
+}
+
+/// 创建个人信息裁定表
+fn create_kb_pii_overrides_table() -> Migration {
+    Migration {
+        version: "20240101_000041".to_string(),
+        name: "create_kb_pii_overrides_table".to_string(),
+        description: "创建合规人员对含个人信息文档块内容的放行/排除裁定表，按原文哈希记录".to_string(),
+        up_sql: r#"
+            CREATE TABLE kb_pii_overrides (
+                knowledge_base_id UUID NOT NULL REFERENCES knowledge_bases(id) ON DELETE CASCADE,
+                content_hash VARCHAR(64) NOT NULL,
+                decision VARCHAR(20) NOT NULL,
+                reason TEXT,
+                decided_by UUID NOT NULL,
+                decided_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (knowledge_base_id, content_hash)
+            );
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS kb_pii_overrides;
+        "#.to_string(),
+        dependencies: vec!["20240101_000040".to_string()],
+    }
+}
+
+/// 创建租户数据密钥表
+fn create_tenant_encryption_keys_table() -> Migration {
+    Migration {
+        version: "20240101_000042".to_string(),
+        name: "create_tenant_encryption_keys_table".to_string(),
+        description: "创建租户数据密钥表，文档正文与原始文件按租户的有效密钥加密，吊销时清除包装后的密钥材料".to_string(),
+        up_sql: r#"
+            CREATE TABLE tenant_encryption_keys (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                version INTEGER NOT NULL,
+                source VARCHAR(20) NOT NULL,
+                kms_key_id VARCHAR(500),
+                wrapped_key TEXT,
+                status VARCHAR(20) NOT NULL,
+                created_by UUID,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                retired_at TIMESTAMPTZ,
+                revoked_at TIMESTAMPTZ,
+                UNIQUE (tenant_id, version)
+            );
+
+            CREATE UNIQUE INDEX idx_tenant_encryption_keys_active
+                ON tenant_encryption_keys(tenant_id) WHERE status = 'active';
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS tenant_encryption_keys;
+        "#.to_string(),
+        dependencies: vec!["20240101_000041".to_string()],
+    }
+}
+
+/// 创建法律保留表
+fn create_legal_hold_tables() -> Migration {
+    Migration {
+        version: "20240101_000043".to_string(),
+        name: "create_legal_hold_tables".to_string(),
+        description: "创建法律保留与保留审计事件表，保留中的文档、知识库和会话不可修改或清理".to_string(),
+        up_sql: r#"
+            CREATE TABLE legal_holds (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                target_type VARCHAR(20) NOT NULL CHECK (target_type IN ('document', 'knowledge_base', 'conversation')),
+                target_id VARCHAR(255) NOT NULL,
+                reason TEXT NOT NULL,
+                case_reference VARCHAR(255),
+                placed_by UUID NOT NULL,
+                placed_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                released_by UUID,
+                released_at TIMESTAMPTZ,
+                release_reason TEXT
+            );
+
+            CREATE INDEX idx_legal_holds_tenant ON legal_holds(tenant_id, placed_at DESC);
+            CREATE INDEX idx_legal_holds_active_target
+                ON legal_holds(target_type, target_id) WHERE released_at IS NULL;
+
+            CREATE TABLE legal_hold_events (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                hold_id UUID NOT NULL REFERENCES legal_holds(id) ON DELETE CASCADE,
+                action VARCHAR(20) NOT NULL,
+                actor UUID,
+                details JSONB NOT NULL DEFAULT '{}',
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_legal_hold_events_hold ON legal_hold_events(hold_id, created_at);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS legal_hold_events;
+            DROP TABLE IF EXISTS legal_holds;
+        "#.to_string(),
+        dependencies: vec!["20240101_000042".to_string()],
+    }
+}
+
+/// 创建条款版本与接受记录表
+fn create_terms_consent_tables() -> Migration {
+    Migration {
+        version: "20240101_000044".to_string(),
+        name: "create_terms_consent_tables".to_string(),
+        description: "创建租户条款版本与用户接受记录表".to_string(),
+        up_sql: r#"
+            CREATE TABLE terms_versions (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                version VARCHAR(50) NOT NULL,
+                title VARCHAR(200) NOT NULL,
+                content TEXT NOT NULL,
+                require_acceptance BOOLEAN NOT NULL DEFAULT TRUE,
+                published_by UUID NOT NULL,
+                published_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE (tenant_id, version)
+            );
+
+            CREATE INDEX idx_terms_versions_tenant_published ON terms_versions(tenant_id, published_at DESC);
+
+            CREATE TABLE terms_acceptances (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                terms_version_id UUID NOT NULL REFERENCES terms_versions(id) ON DELETE CASCADE,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                accepted_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                ip_address VARCHAR(64),
+                user_agent TEXT,
+                UNIQUE (terms_version_id, user_id)
+            );
+
+            CREATE INDEX idx_terms_acceptances_tenant ON terms_acceptances(tenant_id, accepted_at);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS terms_acceptances;
+            DROP TABLE IF EXISTS terms_versions;
+        "#.to_string(),
+        dependencies: vec!["20240101_000043".to_string()],
+    }
+}
+
+/// 创建定时报告表
+fn create_scheduled_report_tables() -> Migration {
+    Migration {
+        version: "20240101_000045".to_string(),
+        name: "create_scheduled_report_tables".to_string(),
+        description: "创建定时报告及其执行记录表".to_string(),
+        up_sql: r#"
+            CREATE TABLE scheduled_reports (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                name VARCHAR(200) NOT NULL,
+                source JSONB NOT NULL,
+                schedule JSONB NOT NULL,
+                format VARCHAR(10) NOT NULL DEFAULT 'html',
+                -- Slack Webhook 地址加密存储
+                deliveries JSONB NOT NULL DEFAULT '[]',
+                enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                next_run_at TIMESTAMPTZ NOT NULL,
+                last_run_at TIMESTAMPTZ,
+                created_by UUID NOT NULL REFERENCES users(id),
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_scheduled_reports_tenant ON scheduled_reports(tenant_id);
+            CREATE INDEX idx_scheduled_reports_due ON scheduled_reports(next_run_at) WHERE enabled;
+
+            CREATE TABLE scheduled_report_runs (
+                id UUID PRIMARY KEY,
+                report_id UUID NOT NULL REFERENCES scheduled_reports(id) ON DELETE CASCADE,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                trigger VARCHAR(20) NOT NULL,
+                period_start TIMESTAMPTZ NOT NULL,
+                period_end TIMESTAMPTZ NOT NULL,
+                status VARCHAR(20) NOT NULL,
+                download_url TEXT,
+                deliveries JSONB NOT NULL DEFAULT '[]',
+                error TEXT,
+                started_at TIMESTAMPTZ NOT NULL,
+                completed_at TIMESTAMPTZ NOT NULL
+            );
+
+            CREATE INDEX idx_scheduled_report_runs_report ON scheduled_report_runs(report_id, started_at DESC);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS scheduled_report_runs;
+            DROP TABLE IF EXISTS scheduled_reports;
+        "#.to_string(),
+        dependencies: vec!["20240101_000044".to_string()],
+    }
+}
+
+/// 为文档添加常青标记
+fn add_document_evergreen_column() -> Migration {
+    Migration {
+        version: "20240101_000046".to_string(),
+        name: "add_document_evergreen_column".to_string(),
+        description: "为文档添加常青标记，常青文档不参与检索时间衰减".to_string(),
+        up_sql: r#"
+            ALTER TABLE documents
+                ADD COLUMN evergreen BOOLEAN NOT NULL DEFAULT FALSE;
+        "#.to_string(),
+        down_sql: r#"
+            ALTER TABLE documents DROP COLUMN IF EXISTS evergreen;
+        "#.to_string(),
+        dependencies: vec!["20240101_000045".to_string()],
+    }
+}
+
+/// 为知识库状态添加归档值
+fn add_knowledge_base_archived_status() -> Migration {
+    Migration {
+        version: "20240101_000047".to_string(),
+        name: "add_knowledge_base_archived_status".to_string(),
+        description: "为知识库状态添加归档值，归档的知识库保留内容但不参与检索与问答".to_string(),
+        up_sql: r#"
+            ALTER TYPE knowledge_base_status ADD VALUE IF NOT EXISTS 'archived';
+        "#.to_string(),
+        down_sql: r#"
+            UPDATE knowledge_bases SET status = 'inactive' WHERE status = 'archived';
+        "#.to_string(),
+        dependencies: vec!["20240101_000046".to_string()],
+    }
+}
+
+/// 创建通行密钥表
+fn create_passkey_tables() -> Migration {
+    Migration {
+        version: "20240101_000048".to_string(),
+        name: "create_passkey_tables".to_string(),
+        description: "创建通行密钥（WebAuthn）凭据与注册/登录挑战表".to_string(),
+        up_sql: r#"
+            CREATE TABLE passkey_credentials (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                credential_id BYTEA NOT NULL UNIQUE,
+                -- COSE 格式公钥
+                public_key BYTEA NOT NULL,
+                algorithm INTEGER NOT NULL,
+                sign_count BIGINT NOT NULL DEFAULT 0,
+                name VARCHAR(100) NOT NULL,
+                aaguid UUID,
+                transports JSONB NOT NULL DEFAULT '[]',
+                backup_eligible BOOLEAN NOT NULL DEFAULT FALSE,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                last_used_at TIMESTAMPTZ
+            );
+
+            CREATE INDEX idx_passkey_credentials_user ON passkey_credentials(user_id);
+
+            CREATE TABLE passkey_challenges (
+                id UUID PRIMARY KEY,
+                tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE,
+                user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+                ceremony VARCHAR(20) NOT NULL,
+                challenge VARCHAR(128) NOT NULL,
+                expires_at TIMESTAMPTZ NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_passkey_challenges_expires ON passkey_challenges(expires_at);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS passkey_challenges;
+            DROP TABLE IF EXISTS passkey_credentials;
+        "#.to_string(),
+        dependencies: vec!["20240101_000047".to_string()],
+    }
+}
+
+/// 创建临时管理员提权与审计日志表
+fn create_admin_elevation_tables() -> Migration {
+    Migration {
+        version: "20240101_000049".to_string(),
+        name: "create_admin_elevation_tables".to_string(),
+        description: "创建临时管理员提权申请表与审计日志表，提权期间的操作在审计日志中标记提权 ID".to_string(),
+        up_sql: r#"
+            CREATE TABLE admin_elevations (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                role VARCHAR(50) NOT NULL,
+                justification TEXT NOT NULL,
+                duration_hours INTEGER NOT NULL,
+                -- pending / approved / rejected / revoked，已批准且过期的记录在读取时视为 expired
+                status VARCHAR(20) NOT NULL DEFAULT 'pending',
+                requested_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                decided_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                decided_at TIMESTAMPTZ,
+                decision_note TEXT,
+                starts_at TIMESTAMPTZ,
+                expires_at TIMESTAMPTZ,
+                revoked_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                revoked_at TIMESTAMPTZ
+            );
+
+            CREATE INDEX idx_admin_elevations_tenant ON admin_elevations(tenant_id, requested_at DESC);
+            CREATE INDEX idx_admin_elevations_active ON admin_elevations(user_id, expires_at) WHERE status = 'approved';
+
+            CREATE TABLE IF NOT EXISTS audit_logs (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+                action VARCHAR(100) NOT NULL,
+                method VARCHAR(10),
+                path TEXT,
+                status_code INTEGER,
+                elevation_id UUID REFERENCES admin_elevations(id) ON DELETE SET NULL,
+                ip_address VARCHAR(64),
+                details JSONB NOT NULL DEFAULT '{}',
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_audit_logs_tenant_created ON audit_logs(tenant_id, created_at DESC);
+            CREATE INDEX idx_audit_logs_elevation ON audit_logs(elevation_id) WHERE elevation_id IS NOT NULL;
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS audit_logs;
+            DROP TABLE IF EXISTS admin_elevations;
+        "#.to_string(),
+        dependencies: vec!["20240101_000048".to_string()],
+    }
+}
+
+/// 为审计日志添加资源字段
+fn add_audit_log_resource_columns() -> Migration {
+    Migration {
+        version: "20240101_000050".to_string(),
+        name: "add_audit_log_resource_columns".to_string(),
+        description: "为审计日志添加资源类型与资源 ID，支持租户活动时间线按类型筛选".to_string(),
+        up_sql: r#"
+            ALTER TABLE audit_logs
+                ADD COLUMN resource_type VARCHAR(50),
+                ADD COLUMN resource_id UUID;
+
+            CREATE INDEX idx_audit_logs_tenant_action ON audit_logs(tenant_id, action, created_at DESC, id DESC);
+        "#.to_string(),
+        down_sql: r#"
+            DROP INDEX IF EXISTS idx_audit_logs_tenant_action;
+            ALTER TABLE audit_logs
+                DROP COLUMN IF EXISTS resource_id,
+                DROP COLUMN IF EXISTS resource_type;
+        "#.to_string(),
+        dependencies: vec!["20240101_000049".to_string()],
+    }
+}
+
+/// 创建文档评论表
+fn create_document_comments_table() -> Migration {
+    Migration {
+        version: "20240101_000051".to_string(),
+        name: "create_document_comments_table".to_string(),
+        description: "创建文档评论与批注表，支持针对文档块的批注、回复与提及".to_string(),
+        up_sql: r#"
+            CREATE TABLE document_comments (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+                -- 批注的文档块；文档重新处理后块被替换，保留块序号与引用原文
+                chunk_id UUID REFERENCES document_chunks(id) ON DELETE SET NULL,
+                chunk_index INTEGER,
+                quote TEXT,
+                -- 回复所属的主题评论，主题评论为空
+                parent_id UUID REFERENCES document_comments(id) ON DELETE CASCADE,
+                author_id UUID REFERENCES users(id) ON DELETE SET NULL,
+                body TEXT NOT NULL,
+                mentions JSONB NOT NULL DEFAULT '[]',
+                resolved_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                resolved_at TIMESTAMPTZ,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_document_comments_document ON document_comments(document_id, created_at);
+            CREATE INDEX idx_document_comments_parent ON document_comments(parent_id) WHERE parent_id IS NOT NULL;
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS document_comments;
+        "#.to_string(),
+        dependencies: vec!["20240101_000050".to_string()],
+    }
+}
+
+/// 创建精选答案表
+fn create_curated_answers_table() -> Migration {
+    Migration {
+        version: "20240101_000052".to_string(),
+        name: "create_curated_answers_table".to_string(),
+        description: "创建知识管理员审定的精选答案表，相似问题优先返回精选答案，到期需复核".to_string(),
+        up_sql: r#"
+            CREATE TABLE curated_answers (
+                id UUID PRIMARY KEY,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                -- 为空时适用于租户下所有知识库
+                knowledge_base_id UUID REFERENCES knowledge_bases(id) ON DELETE CASCADE,
+                question TEXT NOT NULL,
+                answer TEXT NOT NULL,
+                -- 同一问题簇中的其他问法
+                variants JSONB NOT NULL DEFAULT '[]',
+                embedding vector(1536),
+                clearance SMALLINT NOT NULL DEFAULT 0 CHECK (clearance BETWEEN 0 AND 3),
+                source_query_id VARCHAR(100),
+                review_interval_days INTEGER NOT NULL CHECK (review_interval_days > 0),
+                reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                reviewed_at TIMESTAMPTZ NOT NULL,
+                -- 到期后不再应答，复核后顺延
+                expires_at TIMESTAMPTZ NOT NULL,
+                is_active BOOLEAN NOT NULL DEFAULT TRUE,
+                hit_count INTEGER NOT NULL DEFAULT 0,
+                last_hit_at TIMESTAMPTZ,
+                created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_curated_answers_tenant ON curated_answers(tenant_id, expires_at) WHERE is_active;
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS curated_answers;
+        "#.to_string(),
+        dependencies: vec!["20240101_000051".to_string()],
+    }
+}
+
+/// 创建知识库主题地图表
+fn create_kb_topic_maps_table() -> Migration {
+    Migration {
+        version: "20240101_000053".to_string(),
+        name: "create_kb_topic_maps_table".to_string(),
+        description: "创建知识库主题地图表，保存后台主题聚类任务最近一次生成的结果".to_string(),
+        up_sql: r#"
+            CREATE TABLE kb_topic_maps (
+                knowledge_base_id UUID PRIMARY KEY REFERENCES knowledge_bases(id) ON DELETE CASCADE,
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                task_id UUID NOT NULL,
+                -- 主题列表，含规模、代表文档与关键词
+                topics JSONB NOT NULL DEFAULT '[]',
+                document_count INTEGER NOT NULL DEFAULT 0,
+                chunk_count INTEGER NOT NULL DEFAULT 0,
+                model_name VARCHAR(255),
+                generated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX idx_kb_topic_maps_tenant ON kb_topic_maps(tenant_id);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS kb_topic_maps;
+        "#.to_string(),
+        dependencies: vec!["20240101_000052".to_string()],
+    }
+}
+
+/// 创建模型调用日志表
+fn create_model_call_logs_table() -> Migration {
+    Migration {
+        version: "20240101_000054".to_string(),
+        name: "create_model_call_logs_table".to_string(),
+        description: "创建模型调用日志表，按租户留存策略保存脱敏后的提示词与模型输出".to_string(),
+        up_sql: r#"
+            CREATE TABLE model_call_logs (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+                -- 调用来源：ai_service、rag、agent
+                source VARCHAR(50) NOT NULL,
+                execution_id UUID,
+                -- 来源内的关联标识，如问答查询 ID 或 Agent 任务 ID
+                reference VARCHAR(255),
+                model VARCHAR(255) NOT NULL,
+                -- 未开启保存时为空
+                prompt TEXT,
+                completion TEXT,
+                prompt_chars INTEGER NOT NULL DEFAULT 0,
+                completion_chars INTEGER NOT NULL DEFAULT 0,
+                redaction VARCHAR(20) NOT NULL,
+                tokens_used INTEGER,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                expires_at TIMESTAMPTZ NOT NULL
+            );
+
+            CREATE INDEX idx_model_call_logs_tenant ON model_call_logs(tenant_id, created_at DESC);
+            CREATE INDEX idx_model_call_logs_execution ON model_call_logs(execution_id) WHERE execution_id IS NOT NULL;
+            CREATE INDEX idx_model_call_logs_expires ON model_call_logs(expires_at);
+        "#.to_string(),
+        down_sql: r#"
+            DROP TABLE IF EXISTS model_call_logs;
+        "#.to_string(),
+        dependencies: vec!["20240101_000053".to_string()],
+    }
+}
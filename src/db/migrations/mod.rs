@@ -17,6 +17,75 @@ pub use seed_data::*;
 pub use backup::*;
 pub use tenant_filter::*;
 
+/// 将迁移脚本拆分为单条语句
+///
+/// 按分号拆分，但忽略字符串、引号标识符、注释以及 `$$` / `$tag$` 美元引号函数体中的分号。
+pub fn split_sql_statements(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' => {
+                let quote = bytes[i];
+                i += 1;
+                while i < bytes.len() {
+                    if bytes[i] == quote {
+                        // 连续两个引号是转义
+                        if bytes.get(i + 1) == Some(&quote) {
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            b'$' => {
+                // 美元引号标签：$$ 或 $tag$
+                let tag_end = bytes[i + 1..]
+                    .iter()
+                    .position(|b| !(b.is_ascii_alphanumeric() || *b == b'_'))
+                    .map(|offset| i + 1 + offset);
+                match tag_end {
+                    Some(end) if bytes[end] == b'$' && !bytes[i + 1].is_ascii_digit() => {
+                        let tag = &sql[i..=end];
+                        i = match sql[end + 1..].find(tag) {
+                            Some(offset) => end + 1 + offset + tag.len(),
+                            None => bytes.len(),
+                        };
+                    }
+                    _ => i += 1,
+                }
+            }
+            b';' => {
+                statements.push(sql[start..i].trim());
+                start = i + 1;
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    statements.push(sql[start..].trim());
+
+    statements.into_iter().filter(|s| !s.is_empty()).collect()
+}
+
 /// 迁移信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Migration {
@@ -38,6 +107,33 @@ pub struct MigrationStatus {
     pub checksum: String,
 }
 
+/// 迁移的详细状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationState {
+    /// 已应用且校验和一致
+    Applied,
+    /// 待应用
+    Pending,
+    /// 已应用，但迁移脚本在应用后被修改
+    ChecksumMismatch,
+    /// 数据库中有记录，但代码中已不存在该迁移
+    Unknown,
+}
+
+/// 迁移状态明细，供运维命令展示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub version: String,
+    pub name: String,
+    pub state: MigrationState,
+    pub applied_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// 数据库中记录的校验和
+    pub recorded_checksum: Option<String>,
+    /// 按当前迁移脚本计算的校验和
+    pub expected_checksum: Option<String>,
+}
+
 /// 迁移管理器
 pub struct MigrationManager {
     db: DatabaseConnection,
@@ -162,11 +258,69 @@ impl MigrationManager {
         Ok(status)
     }
 
+    /// 生成迁移状态明细，包含校验和不一致与代码中已不存在的迁移
+    #[instrument(skip(self))]
+    pub async fn status_report(&self) -> Result<Vec<MigrationReport>, AiStudioError> {
+        let applied = self.get_applied_migrations().await?;
+        Ok(build_status_report(&self.get_available_migrations(), &applied))
+    }
+
+    /// 重新记录校验和不一致的迁移，用于确认迁移脚本的修改是有意为之
+    ///
+    /// `versions` 为空时修复所有不一致的迁移；返回实际修复的版本。
+    #[instrument(skip(self))]
+    pub async fn repair_checksums(&self, versions: &[String]) -> Result<Vec<String>, AiStudioError> {
+        let lock_service = crate::db::DistributedLockService::new(self.db.clone());
+        let guard = lock_service.acquire_advisory(crate::db::lock_names::MIGRATIONS).await?;
+
+        let report = self.status_report().await?;
+        if let Some(version) = versions.iter().find(|v| !report.iter().any(|r| &r.version == *v)) {
+            guard.release().await?;
+            return Err(AiStudioError::not_found(format!("迁移 {}", version)));
+        }
+
+        let available = self.get_available_migrations();
+        let mut repaired = Vec::new();
+        for entry in report.iter().filter(|r| r.state == MigrationState::ChecksumMismatch) {
+            if !versions.is_empty() && !versions.contains(&entry.version) {
+                continue;
+            }
+            let Some(migration) = available.iter().find(|m| m.version == entry.version) else {
+                continue;
+            };
+
+            self.db.execute(Statement::from_sql_and_values(
+                sea_orm::DatabaseBackend::Postgres,
+                "UPDATE schema_migrations SET checksum = $1, name = $2, description = $3 WHERE version = $4",
+                vec![
+                    migration_checksum(migration).into(),
+                    migration.name.clone().into(),
+                    migration.description.clone().into(),
+                    migration.version.clone().into(),
+                ],
+            )).await?;
+
+            warn!(
+                version = %migration.version,
+                old_checksum = entry.recorded_checksum.as_deref().unwrap_or_default(),
+                "已重新记录迁移校验和"
+            );
+            repaired.push(migration.version.clone());
+        }
+
+        guard.release().await?;
+        Ok(repaired)
+    }
+
     /// 应用待处理的迁移
     #[instrument(skip(self))]
     pub async fn migrate(&self) -> Result<Vec<String>, AiStudioError> {
         info!("开始应用数据库迁移");
 
+        // 多实例同时启动时串行化迁移，其余实例等待持锁实例完成后再检查状态
+        let lock_service = crate::db::DistributedLockService::new(self.db.clone());
+        let guard = lock_service.acquire_advisory(crate::db::lock_names::MIGRATIONS).await?;
+
         let status = self.check_status().await?;
         let mut applied_migrations = Vec::new();
 
@@ -184,6 +338,8 @@ impl MigrationManager {
             }
         }
 
+        guard.release().await?;
+
         if applied_migrations.is_empty() {
             info!("没有待处理的迁移");
         } else {
@@ -308,7 +464,11 @@ impl MigrationManager {
         let required_tables = vec![
             "tenants", "users", "sessions",
             "knowledge_bases", "documents", "document_chunks", "embeddings",
-            "agents", "agent_executions", "workflows", "workflow_executions", "step_executions"
+            "agents", "agent_executions", "workflows", "workflow_executions", "step_executions",
+            "distributed_locks", "tenant_plugin_configs", "plugin_trusted_keys",
+            "kb_snapshots", "kb_snapshot_documents", "kb_snapshot_chunks",
+            "few_shot_examples", "saved_searches", "qa_query_logs", "kb_faq_entries",
+            "replication_events", "replication_state", "execution_events"
         ];
 
         for table_name in required_tables {
@@ -336,19 +496,11 @@ impl MigrationManager {
     /// 执行 SQL 语句
     async fn execute_sql(&self, sql: &str) -> Result<(), AiStudioError> {
         // 分割 SQL 语句，逐条执行
-        let statements: Vec<&str> = sql
-            .split(';')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .collect();
-
-        for statement in statements {
-            if !statement.is_empty() {
-                self.db.execute(Statement::from_string(
-                    sea_orm::DatabaseBackend::Postgres,
-                    statement.to_string(),
-                )).await?;
-            }
+        for statement in split_sql_statements(sql) {
+            self.db.execute(Statement::from_string(
+                sea_orm::DatabaseBackend::Postgres,
+                statement.to_string(),
+            )).await?;
         }
         Ok(())
     }
@@ -360,19 +512,11 @@ impl MigrationManager {
         sql: &str,
     ) -> Result<(), AiStudioError> {
         // 分割 SQL 语句，逐条执行
-        let statements: Vec<&str> = sql
-            .split(';')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .collect();
-
-        for statement in statements {
-            if !statement.is_empty() {
-                txn.execute(Statement::from_string(
-                    sea_orm::DatabaseBackend::Postgres,
-                    statement.to_string(),
-                )).await?;
-            }
+        for statement in split_sql_statements(sql) {
+            txn.execute(Statement::from_string(
+                sea_orm::DatabaseBackend::Postgres,
+                statement.to_string(),
+            )).await?;
         }
         Ok(())
     }
@@ -417,14 +561,73 @@ impl MigrationManager {
 
     /// 计算迁移校验和
     fn calculate_checksum(&self, migration: &Migration) -> String {
-        use sha2::{Sha256, Digest};
-        let mut hasher = Sha256::new();
-        hasher.update(migration.up_sql.as_bytes());
-        hasher.update(migration.down_sql.as_bytes());
-        format!("{:x}", hasher.finalize())
+        migration_checksum(migration)
     }
 }
 
+/// 计算迁移校验和
+pub fn migration_checksum(migration: &Migration) -> String {
+    use sha2::{Sha256, Digest};
+    let mut hasher = Sha256::new();
+    hasher.update(migration.up_sql.as_bytes());
+    hasher.update(migration.down_sql.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// 对比代码中的迁移与数据库中的记录，按版本排序生成状态明细
+pub fn build_status_report(available: &[Migration], applied: &[MigrationStatus]) -> Vec<MigrationReport> {
+    let applied_by_version: HashMap<&str, &MigrationStatus> = applied
+        .iter()
+        .map(|m| (m.version.as_str(), m))
+        .collect();
+
+    let mut report: Vec<MigrationReport> = available
+        .iter()
+        .map(|migration| {
+            let expected = migration_checksum(migration);
+            match applied_by_version.get(migration.version.as_str()) {
+                Some(recorded) => MigrationReport {
+                    version: migration.version.clone(),
+                    name: migration.name.clone(),
+                    state: if recorded.checksum == expected {
+                        MigrationState::Applied
+                    } else {
+                        MigrationState::ChecksumMismatch
+                    },
+                    applied_at: recorded.applied_at,
+                    recorded_checksum: Some(recorded.checksum.clone()),
+                    expected_checksum: Some(expected),
+                },
+                None => MigrationReport {
+                    version: migration.version.clone(),
+                    name: migration.name.clone(),
+                    state: MigrationState::Pending,
+                    applied_at: None,
+                    recorded_checksum: None,
+                    expected_checksum: Some(expected),
+                },
+            }
+        })
+        .collect();
+
+    report.extend(
+        applied
+            .iter()
+            .filter(|recorded| !available.iter().any(|m| m.version == recorded.version))
+            .map(|recorded| MigrationReport {
+                version: recorded.version.clone(),
+                name: recorded.name.clone(),
+                state: MigrationState::Unknown,
+                applied_at: recorded.applied_at,
+                recorded_checksum: Some(recorded.checksum.clone()),
+                expected_checksum: None,
+            }),
+    );
+
+    report.sort_by(|a, b| a.version.cmp(&b.version));
+    report
+}
+
 /// 架构验证结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SchemaValidation {
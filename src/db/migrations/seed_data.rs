@@ -23,9 +23,14 @@ impl SeedDataManager {
     pub async fn seed_all(&self) -> Result<(), AiStudioError> {
         info!("开始初始化种子数据");
 
+        // 防止多个实例同时写入默认数据
+        let lock_service = crate::db::DistributedLockService::new(self.db.clone());
+        let guard = lock_service.acquire_advisory(crate::db::lock_names::SEED_DATA).await?;
+
         // 检查是否已经有数据
         if self.has_existing_data().await? {
             info!("检测到现有数据，跳过种子数据初始化");
+            guard.release().await?;
             return Ok(());
         }
 
@@ -47,6 +52,8 @@ impl SeedDataManager {
         // 创建示例工作流
         self.create_sample_workflows(tenant_id, admin_user_id).await?;
 
+        guard.release().await?;
+
         info!("种子数据初始化完成");
         Ok(())
     }
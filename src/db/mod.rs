@@ -3,15 +3,19 @@
 
 pub mod cli;
 pub mod connection;
+pub mod distributed_lock;
 pub mod entities;
 pub mod migrations;
 pub mod health;
+pub mod optimistic_lock;
 pub mod repositories;
 
 #[cfg(test)]
 mod tests;
 
 pub use connection::*;
+pub use distributed_lock::*;
 pub use health::*;
+pub use optimistic_lock::*;
 pub use migrations::*;
 pub use repositories::*;
\ No newline at end of file
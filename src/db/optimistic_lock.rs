@@ -0,0 +1,59 @@
+// 乐观锁
+// 基于修订号的并发更新控制，并发编辑同一实体时后提交的一方收到冲突错误而不是静默覆盖
+
+use sea_orm::{
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait,
+    IntoActiveModel, QueryFilter,
+};
+
+use crate::errors::AiStudioError;
+
+/// 修订号对应的 ETag
+pub fn revision_etag(revision: i32) -> String {
+    format!("\"{}\"", revision)
+}
+
+/// 解析 `If-Match` 请求头中的修订号，`*` 表示不校验
+pub fn parse_revision_etag(value: &str) -> Result<Option<i32>, AiStudioError> {
+    let value = value.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+
+    let tag = value.strip_prefix("W/").unwrap_or(value);
+    tag.trim_matches('"')
+        .parse::<i32>()
+        .map(Some)
+        .map_err(|_| AiStudioError::validation("If-Match", format!("无效的 ETag: {}", value)))
+}
+
+/// 按修订号条件更新实体
+///
+/// 仅当数据库中的修订号仍为 `expected_revision` 时写入，并将修订号加一；
+/// 期间实体已被其他请求修改时返回冲突错误。
+pub async fn update_with_revision<A, C>(
+    db: &C,
+    mut model: A,
+    revision_column: <A::Entity as EntityTrait>::Column,
+    expected_revision: i32,
+    resource: &str,
+) -> Result<<A::Entity as EntityTrait>::Model, AiStudioError>
+where
+    A: ActiveModelTrait + ActiveModelBehavior + Send,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    C: ConnectionTrait,
+{
+    model.set(revision_column, (expected_revision + 1).into());
+
+    <A::Entity as EntityTrait>::update(model)
+        .filter(revision_column.eq(expected_revision))
+        .exec(db)
+        .await
+        .map_err(|e| match e {
+            DbErr::RecordNotUpdated => AiStudioError::conflict(format!(
+                "{}已被其他用户修改（修订号 {} 已过期），请重新加载后再保存",
+                resource, expected_revision
+            )),
+            e => e.into(),
+        })
+}
@@ -1,6 +1,7 @@
 // Agent 仓储实现
 
 use crate::db::entities::{agent, prelude::*};
+use crate::db::update_with_revision;
 use crate::errors::AiStudioError;
 use sea_orm::{prelude::*, *};
 use uuid::Uuid;
@@ -46,6 +47,7 @@ impl AgentRepository {
             created_by: Set(created_by),
             created_at: Set(chrono::Utc::now().into()),
             updated_at: Set(chrono::Utc::now().into()),
+            revision: Set(1),
         };
 
         let result = agent.insert(db).await?;
@@ -101,10 +103,18 @@ impl AgentRepository {
     ) -> Result<agent::Model, AiStudioError> {
         info!(agent_id = %agent.id, "更新 Agent 信息");
 
+        let expected_revision = agent.revision;
         let mut active_model: agent::ActiveModel = agent.into();
         active_model.updated_at = Set(chrono::Utc::now().into());
 
-        let result = active_model.update(db).await?;
+        let result = update_with_revision(
+            db,
+            active_model,
+            agent::Column::Revision,
+            expected_revision,
+            "Agent",
+        )
+        .await?;
         info!(agent_id = %result.id, "Agent 信息更新成功");
         Ok(result)
     }
@@ -142,11 +152,19 @@ impl AgentRepository {
         let agent = Self::find_by_id(db, id).await?
             .ok_or_else(|| AiStudioError::not_found("Agent"))?;
 
+        let expected_revision = agent.revision;
         let mut active_model: agent::ActiveModel = agent.into();
         active_model.config = Set(serde_json::to_value(config)?);
         active_model.updated_at = Set(chrono::Utc::now().into());
 
-        let result = active_model.update(db).await?;
+        let result = update_with_revision(
+            db,
+            active_model,
+            agent::Column::Revision,
+            expected_revision,
+            "Agent",
+        )
+        .await?;
         info!(agent_id = %result.id, "Agent 配置更新成功");
         Ok(result)
     }
@@ -163,11 +181,19 @@ impl AgentRepository {
         let agent = Self::find_by_id(db, id).await?
             .ok_or_else(|| AiStudioError::not_found("Agent"))?;
 
+        let expected_revision = agent.revision;
         let mut active_model: agent::ActiveModel = agent.into();
         active_model.tools = Set(serde_json::to_value(tools)?);
         active_model.updated_at = Set(chrono::Utc::now().into());
 
-        let result = active_model.update(db).await?;
+        let result = update_with_revision(
+            db,
+            active_model,
+            agent::Column::Revision,
+            expected_revision,
+            "Agent",
+        )
+        .await?;
         info!(agent_id = %result.id, "Agent 工具更新成功");
         Ok(result)
     }
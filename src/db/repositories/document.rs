@@ -47,8 +47,15 @@ impl DocumentRepository {
             processing_completed_at: Set(None),
             error_message: Set(None),
             version: Set(1),
+            expires_at: Set(None),
+            review_at: Set(None),
+            owner_id: Set(None),
+            review_notified_at: Set(None),
+            clearance: Set(document::ClearanceLevel::Public),
+            evergreen: Set(false),
             created_at: Set(chrono::Utc::now().into()),
             updated_at: Set(chrono::Utc::now().into()),
+            revision: Set(1),
         };
 
         let result = document.insert(db).await?;
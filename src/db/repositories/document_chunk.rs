@@ -1,6 +1,6 @@
 // 文档块仓储实现
 
-use crate::db::entities::{document_chunk, prelude::*};
+use crate::db::entities::{document, document_chunk, prelude::*};
 use crate::errors::AiStudioError;
 use sea_orm::{prelude::*, *};
 use uuid::Uuid;
@@ -20,6 +20,31 @@ impl DocumentChunkRepository {
         content: String,
         title: Option<String>,
         content_hash: String,
+    ) -> Result<document_chunk::Model, AiStudioError> {
+        Self::create_with_metadata(
+            db,
+            document_id,
+            knowledge_base_id,
+            chunk_index,
+            content,
+            title,
+            content_hash,
+            document_chunk::ChunkMetadata::default(),
+        )
+        .await
+    }
+
+    /// 创建带元数据的文档块，表格块的结构化内容随元数据保存
+    #[instrument(skip(db, content, metadata))]
+    pub async fn create_with_metadata(
+        db: &DatabaseConnection,
+        document_id: Uuid,
+        knowledge_base_id: Uuid,
+        chunk_index: i32,
+        content: String,
+        title: Option<String>,
+        content_hash: String,
+        metadata: document_chunk::ChunkMetadata,
     ) -> Result<document_chunk::Model, AiStudioError> {
         info!(doc_id = %document_id, chunk_index = chunk_index, "创建新文档块");
 
@@ -38,11 +63,12 @@ impl DocumentChunkRepository {
             content_length: Set(content_length),
             word_count: Set(word_count),
             content_hash: Set(content_hash),
-            metadata: Set(serde_json::to_value(document_chunk::ChunkMetadata::default())?),
+            metadata: Set(serde_json::to_value(metadata)?),
             position_info: Set(serde_json::to_value(document_chunk::PositionInfo::default())?),
             processing_started_at: Set(None),
             processing_completed_at: Set(None),
             error_message: Set(None),
+            clearance: Set(document::ClearanceLevel::Public),
             created_at: Set(chrono::Utc::now().into()),
             updated_at: Set(chrono::Utc::now().into()),
         };
@@ -45,6 +45,7 @@ impl KnowledgeBaseRepository {
             last_indexed_at: Set(None),
             created_at: Set(chrono::Utc::now().into()),
             updated_at: Set(chrono::Utc::now().into()),
+            revision: Set(1),
         };
 
         let result = knowledge_base.insert(db).await?;
@@ -2,6 +2,7 @@
 
 use crate::db::entities::{tenant, prelude::*};
 use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace};
 use sea_orm::{prelude::*, *};
 use uuid::Uuid;
 use tracing::{info, warn, instrument};
@@ -44,6 +45,7 @@ impl TenantRepository {
             created_at: Set(chrono::Utc::now().into()),
             updated_at: Set(chrono::Utc::now().into()),
             last_active_at: Set(Some(chrono::Utc::now().into())),
+            revision: Set(1),
         };
 
         let result = tenant.insert(db).await?;
@@ -125,6 +127,7 @@ impl TenantRepository {
         active_model.updated_at = Set(chrono::Utc::now().into());
 
         let result = active_model.update(db).await?;
+        cache::invalidate(CacheNamespace::Tenant, result.id).await;
         info!(tenant_id = %result.id, "租户信息更新成功");
         Ok(result)
     }
@@ -146,6 +149,7 @@ impl TenantRepository {
         active_model.updated_at = Set(chrono::Utc::now().into());
 
         let result = active_model.update(db).await?;
+        cache::invalidate(CacheNamespace::Tenant, result.id).await;
         info!(tenant_id = %result.id, "租户状态更新成功");
         Ok(result)
     }
@@ -167,6 +171,7 @@ impl TenantRepository {
         active_model.updated_at = Set(chrono::Utc::now().into());
 
         let result = active_model.update(db).await?;
+        cache::invalidate(CacheNamespace::Tenant, result.id).await;
         info!(tenant_id = %result.id, "租户配置更新成功");
         Ok(result)
     }
@@ -186,6 +191,7 @@ impl TenantRepository {
         active_model.updated_at = Set(chrono::Utc::now().into());
 
         let result = active_model.update(db).await?;
+        cache::invalidate(CacheNamespace::Tenant, result.id).await;
         Ok(result)
     }
 
@@ -201,6 +207,7 @@ impl TenantRepository {
             .filter(tenant::Column::Id.eq(id))
             .exec(db)
             .await?;
+        cache::invalidate(CacheNamespace::Tenant, id).await;
         Ok(())
     }
 
@@ -266,6 +273,7 @@ impl TenantRepository {
         warn!(tenant_id = %id, "硬删除租户");
 
         let result = Tenant::delete_by_id(id).exec(db).await?;
+        cache::invalidate(CacheNamespace::Tenant, id).await;
         if result.rows_affected == 0 {
             return Err(AiStudioError::not_found("租户"));
         }
@@ -2,6 +2,7 @@
 
 use crate::db::entities::{user, prelude::*};
 use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace};
 use sea_orm::{prelude::*, *};
 use uuid::Uuid;
 use tracing::{info, instrument};
@@ -146,6 +147,7 @@ impl UserRepository {
         active_model.updated_at = Set(chrono::Utc::now().into());
 
         let result = active_model.update(db).await?;
+        cache::invalidate(CacheNamespace::User, result.id).await;
         info!(user_id = %result.id, "用户信息更新成功");
         Ok(result)
     }
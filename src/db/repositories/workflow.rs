@@ -2,6 +2,7 @@
 
 use crate::db::entities::{workflow, prelude::*};
 use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace};
 use sea_orm::{prelude::*, *};
 use uuid::Uuid;
 use tracing::{info, warn, instrument};
@@ -59,8 +60,10 @@ impl WorkflowRepository {
         db: &DatabaseConnection,
         id: Uuid,
     ) -> Result<Option<workflow::Model>, AiStudioError> {
-        let workflow = Workflow::find_by_id(id).one(db).await?;
-        Ok(workflow)
+        cache::cached(CacheNamespace::Workflow, &id.to_string(), move || async move {
+            Ok::<_, AiStudioError>(Workflow::find_by_id(id).one(db).await?)
+        })
+        .await
     }
 
     /// 根据名称和租户 ID 查找工作流
@@ -105,6 +108,7 @@ impl WorkflowRepository {
         active_model.updated_at = Set(chrono::Utc::now().into());
 
         let result = active_model.update(db).await?;
+        cache::invalidate(CacheNamespace::Workflow, result.id).await;
         info!(workflow_id = %result.id, "工作流信息更新成功");
         Ok(result)
     }
@@ -126,6 +130,7 @@ impl WorkflowRepository {
         active_model.updated_at = Set(chrono::Utc::now().into());
 
         let result = active_model.update(db).await?;
+        cache::invalidate(CacheNamespace::Workflow, result.id).await;
         info!(workflow_id = %result.id, "工作流状态更新成功");
         Ok(result)
     }
@@ -147,6 +152,7 @@ impl WorkflowRepository {
         active_model.updated_at = Set(chrono::Utc::now().into());
 
         let result = active_model.update(db).await?;
+        cache::invalidate(CacheNamespace::Workflow, result.id).await;
         info!(workflow_id = %result.id, "工作流定义更新成功");
         Ok(result)
     }
@@ -168,6 +174,7 @@ impl WorkflowRepository {
         active_model.updated_at = Set(chrono::Utc::now().into());
 
         let result = active_model.update(db).await?;
+        cache::invalidate(CacheNamespace::Workflow, result.id).await;
         info!(workflow_id = %result.id, "工作流配置更新成功");
         Ok(result)
     }
@@ -184,6 +191,7 @@ impl WorkflowRepository {
             .filter(workflow::Column::Id.eq(id))
             .exec(db)
             .await?;
+        cache::invalidate(CacheNamespace::Workflow, id).await;
         Ok(())
     }
 
@@ -200,6 +208,7 @@ impl WorkflowRepository {
             .filter(workflow::Column::Id.eq(id))
             .exec(db)
             .await?;
+        cache::invalidate(CacheNamespace::Workflow, id).await;
         Ok(())
     }
 
@@ -375,6 +384,7 @@ impl WorkflowRepository {
         warn!(workflow_id = %id, "硬删除工作流");
 
         let result = Workflow::delete_by_id(id).exec(db).await?;
+        cache::invalidate(CacheNamespace::Workflow, id).await;
         if result.rows_affected == 0 {
             return Err(AiStudioError::not_found("工作流"));
         }
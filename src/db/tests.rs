@@ -167,4 +167,184 @@ mod tests {
         assert_ne!(HealthStatus::Healthy, HealthStatus::Degraded);
         assert_ne!(HealthStatus::Degraded, HealthStatus::Unhealthy);
     }
-}
\ No newline at end of file
+
+    fn migration(version: &str, name: &str, up_sql: &str) -> crate::db::Migration {
+        crate::db::Migration {
+            version: version.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            up_sql: up_sql.to_string(),
+            down_sql: String::new(),
+            dependencies: Vec::new(),
+        }
+    }
+
+    fn applied(migration: &crate::db::Migration, checksum: Option<&str>) -> crate::db::MigrationStatus {
+        crate::db::MigrationStatus {
+            version: migration.version.clone(),
+            name: migration.name.clone(),
+            applied_at: Some(chrono::Utc::now()),
+            is_applied: true,
+            checksum: checksum
+                .map(str::to_string)
+                .unwrap_or_else(|| crate::db::migration_checksum(migration)),
+        }
+    }
+
+    #[test]
+    fn test_migration_status_report() {
+        use crate::db::{build_status_report, MigrationState};
+
+        let first = migration("20240101_000001", "create_a", "CREATE TABLE a (id INT);");
+        let second = migration("20240101_000002", "create_b", "CREATE TABLE b (id INT);");
+        let third = migration("20240101_000003", "create_c", "CREATE TABLE c (id INT);");
+        let removed = migration("20240101_000000", "removed", "");
+
+        let report = build_status_report(
+            &[first.clone(), second.clone(), third],
+            &[applied(&first, None), applied(&second, Some("edited")), applied(&removed, None)],
+        );
+
+        let states: Vec<_> = report.iter().map(|r| (r.version.as_str(), r.state)).collect();
+        assert_eq!(states, vec![
+            ("20240101_000000", MigrationState::Unknown),
+            ("20240101_000001", MigrationState::Applied),
+            ("20240101_000002", MigrationState::ChecksumMismatch),
+            ("20240101_000003", MigrationState::Pending),
+        ]);
+        assert_eq!(report[2].recorded_checksum.as_deref(), Some("edited"));
+    }
+
+    #[test]
+    fn test_schema_fix_report() {
+        use crate::db::cli::schema_fix_report;
+        use crate::db::{build_status_report, SchemaValidation};
+
+        let first = migration("20240101_000001", "create_a", "CREATE TABLE a (\n id INT\n);");
+        let second = migration("20240101_000002", "create_b", "CREATE TABLE IF NOT EXISTS b(id INT);");
+        let report = build_status_report(&[first.clone(), second.clone()], &[applied(&first, None)]);
+        let validation = SchemaValidation {
+            is_valid: false,
+            missing_tables: vec!["a".to_string(), "b".to_string()],
+            missing_columns: Vec::new(),
+            missing_indexes: Vec::new(),
+            errors: vec!["pgvector 扩展未安装".to_string()],
+        };
+
+        let fixes = schema_fix_report(&validation, &[first, second], &report);
+        assert_eq!(fixes.len(), 4);
+        // 已记录但表缺失时需要清除迁移记录
+        assert!(fixes[0].suggestion.contains("DELETE FROM schema_migrations WHERE version = '20240101_000001'"));
+        assert!(fixes[1].suggestion.contains("migration migrate"));
+        assert!(fixes[2].suggestion.contains("CREATE EXTENSION"));
+        assert!(fixes[3].problem.contains("20240101_000002"));
+    }
+
+    #[test]
+    fn test_parse_repair_command() {
+        use crate::db::cli::{parse_args, CliCommand, MigrationCommand};
+
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        match parse_args(args(&["aionix-db", "migration", "repair", "20240101_000002", "--yes"])).unwrap() {
+            CliCommand::Migration(MigrationCommand::Repair { versions, assume_yes }) => {
+                assert_eq!(versions, vec!["20240101_000002".to_string()]);
+                assert!(assume_yes);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(matches!(
+            parse_args(args(&["aionix-db", "verify-schema"])).unwrap(),
+            CliCommand::Migration(MigrationCommand::VerifySchema)
+        ));
+    }
+
+    #[test]
+    fn test_parse_agent_commands() {
+        use crate::db::cli::{parse_args, AgentCommand, CliCommand};
+        use crate::services::agent_definition::DefinitionFormat;
+
+        let tenant_id = uuid::Uuid::new_v4();
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        match parse_args(args(&["aionix-db", "agent", "export", &tenant_id.to_string(), "客服助手", "--format", "json"])).unwrap() {
+            CliCommand::Agent(AgentCommand::Export { tenant_id: parsed, name, output, format }) => {
+                assert_eq!(parsed, tenant_id);
+                assert_eq!(name, "客服助手");
+                assert!(output.is_none());
+                assert_eq!(format, DefinitionFormat::Json);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(parse_args(args(&["aionix-db", "agent", "import", "not-a-uuid", "x", "agent.yaml"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_manifest_commands() {
+        use crate::db::cli::{parse_args, CliCommand, ManifestCommand};
+
+        let user_id = uuid::Uuid::new_v4();
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        match parse_args(args(&["aionix-db", "manifest", "apply", "seed.yaml", "--as", &user_id.to_string()])).unwrap() {
+            CliCommand::Manifest(ManifestCommand::Apply { file, user_id: parsed }) => {
+                assert_eq!(file, std::path::PathBuf::from("seed.yaml"));
+                assert_eq!(parsed, user_id);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(matches!(
+            parse_args(args(&["aionix-db", "manifest", "plan", "seed.yaml"])).unwrap(),
+            CliCommand::Manifest(ManifestCommand::Plan { .. })
+        ));
+        assert!(parse_args(args(&["aionix-db", "manifest", "apply", "seed.yaml"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_vector_migrate_command() {
+        use crate::db::cli::{parse_args, CliCommand, VectorCommand};
+        use crate::db::entities::knowledge_base::VectorBackend;
+
+        let kb_id = uuid::Uuid::new_v4();
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        match parse_args(args(&["aionix-db", "vector", "migrate", &kb_id.to_string(), "qdrant", "--keep-source"])).unwrap() {
+            CliCommand::Vector(VectorCommand::Migrate { knowledge_base_id, target, keep_source }) => {
+                assert_eq!(knowledge_base_id, kb_id);
+                assert_eq!(target, VectorBackend::Qdrant);
+                assert!(keep_source);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(parse_args(args(&["aionix-db", "vector", "migrate", &kb_id.to_string(), "faiss"])).is_err());
+    }
+
+    #[test]
+    fn test_split_sql_statements_keeps_dollar_quoted_bodies() {
+        use crate::db::split_sql_statements;
+
+        let sql = r#"
+            CREATE TABLE t (note TEXT DEFAULT 'a;b');
+            -- 注释中的分号; 不拆分
+            CREATE OR REPLACE FUNCTION f() RETURNS TRIGGER AS $$
+            BEGIN
+                NEW.updated_at = NOW();
+                RETURN NEW;
+            END;
+            $$ language 'plpgsql';
+            CREATE FUNCTION g() RETURNS INT AS $body$ SELECT 1; $body$ LANGUAGE sql;
+        "#;
+
+        let statements = split_sql_statements(sql);
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0].ends_with("DEFAULT 'a;b')"));
+        assert!(statements[1].contains("RETURN NEW;") && statements[1].ends_with("language 'plpgsql'"));
+        assert!(statements[2].ends_with("LANGUAGE sql"));
+    }
+
+    #[test]
+    fn test_parse_revision_etag() {
+        use crate::db::{parse_revision_etag, revision_etag};
+
+        assert_eq!(parse_revision_etag(&revision_etag(7)).unwrap(), Some(7));
+        assert_eq!(parse_revision_etag("W/\"3\"").unwrap(), Some(3));
+        assert_eq!(parse_revision_etag("*").unwrap(), None);
+        assert!(parse_revision_etag("\"abc\"").is_err());
+    }
+}
@@ -77,6 +77,13 @@ where
             // 计算处理时间
             let duration = start_time.elapsed();
 
+            // 计入服务等级目标统计
+            let status_code = match &result {
+                Ok(response) => response.status().as_u16(),
+                Err(err) => err.as_response_error().status_code().as_u16(),
+            };
+            crate::services::slo::record_request(&path, status_code, duration);
+
             match result {
                 Ok(response) => {
                     let status = response.status();
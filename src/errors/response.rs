@@ -97,26 +97,7 @@ impl ErrorResponse {
 
     /// 转换为 HTTP 响应
     pub fn into_http_response(self) -> HttpResponse {
-        let status_code = match self.error.code.as_str() {
-            "CONFIGURATION_ERROR" => 500,
-            "DATABASE_ERROR" => 500,
-            "AI_SERVICE_ERROR" => 502,
-            "CACHE_ERROR" => 500,
-            "AUTHENTICATION_ERROR" => 401,
-            "AUTHORIZATION_ERROR" => 403,
-            "VALIDATION_ERROR" => 400,
-            "NOT_FOUND" => 404,
-            "CONFLICT" => 409,
-            "RATE_LIMIT" => 429,
-            "FILE_PROCESSING_ERROR" => 400,
-            "VECTOR_ERROR" => 500,
-            "TENANT_ERROR" => 400,
-            "EXTERNAL_SERVICE_ERROR" => 502,
-            "INTERNAL_ERROR" => 500,
-            "SERVICE_UNAVAILABLE" => 503,
-            "TIMEOUT_ERROR" => 408,
-            _ => 500,
-        };
+        let status_code = aionix_common::error_codes::http_status(&self.error.code);
 
         let mut response = HttpResponse::build(
             actix_web::http::StatusCode::from_u16(status_code)
@@ -1,7 +1,7 @@
 // 统一错误类型定义
 
 use actix_web::{HttpResponse, ResponseError};
-use aionix_common::CommonError;
+use aionix_common::{error_codes, CommonError};
 use serde::{Deserialize, Serialize};
 
 use thiserror::Error;
@@ -85,49 +85,30 @@ impl AiStudioError {
     /// 获取错误代码
     pub fn error_code(&self) -> &'static str {
         match self {
-            Self::Configuration { .. } => "CONFIGURATION_ERROR",
-            Self::Database { .. } => "DATABASE_ERROR",
-            Self::AiService { .. } => "AI_SERVICE_ERROR",
+            Self::Configuration { .. } => error_codes::CONFIGURATION_ERROR,
+            Self::Database { .. } => error_codes::DATABASE_ERROR,
+            Self::AiService { .. } => error_codes::AI_SERVICE_ERROR,
             #[cfg(feature = "redis")]
-            Self::Cache { .. } => "CACHE_ERROR",
-            Self::Authentication { .. } => "AUTHENTICATION_ERROR",
-            Self::Authorization { .. } => "AUTHORIZATION_ERROR",
-            Self::Validation { .. } => "VALIDATION_ERROR",
-            Self::NotFound { .. } => "NOT_FOUND",
-            Self::Conflict { .. } => "CONFLICT",
-            Self::RateLimit { .. } => "RATE_LIMIT",
-            Self::FileProcessing { .. } => "FILE_PROCESSING_ERROR",
-            Self::Vector { .. } => "VECTOR_ERROR",
-            Self::Tenant { .. } => "TENANT_ERROR",
-            Self::ExternalService { .. } => "EXTERNAL_SERVICE_ERROR",
-            Self::Internal { .. } => "INTERNAL_ERROR",
-            Self::ServiceUnavailable { .. } => "SERVICE_UNAVAILABLE",
-            Self::Timeout { .. } => "TIMEOUT_ERROR",
+            Self::Cache { .. } => error_codes::CACHE_ERROR,
+            Self::Authentication { .. } => error_codes::AUTHENTICATION_ERROR,
+            Self::Authorization { .. } => error_codes::AUTHORIZATION_ERROR,
+            Self::Validation { .. } => error_codes::VALIDATION_ERROR,
+            Self::NotFound { .. } => error_codes::NOT_FOUND,
+            Self::Conflict { .. } => error_codes::CONFLICT,
+            Self::RateLimit { .. } => error_codes::RATE_LIMIT,
+            Self::FileProcessing { .. } => error_codes::FILE_PROCESSING_ERROR,
+            Self::Vector { .. } => error_codes::VECTOR_ERROR,
+            Self::Tenant { .. } => error_codes::TENANT_ERROR,
+            Self::ExternalService { .. } => error_codes::EXTERNAL_SERVICE_ERROR,
+            Self::Internal { .. } => error_codes::INTERNAL_ERROR,
+            Self::ServiceUnavailable { .. } => error_codes::SERVICE_UNAVAILABLE,
+            Self::Timeout { .. } => error_codes::TIMEOUT_ERROR,
         }
     }
 
     /// 获取 HTTP 状态码
     pub fn status_code(&self) -> u16 {
-        match self {
-            Self::Configuration { .. } => 500,
-            Self::Database { .. } => 500,
-            Self::AiService { .. } => 502,
-            #[cfg(feature = "redis")]
-            Self::Cache { .. } => 500,
-            Self::Authentication { .. } => 401,
-            Self::Authorization { .. } => 403,
-            Self::Validation { .. } => 400,
-            Self::NotFound { .. } => 404,
-            Self::Conflict { .. } => 409,
-            Self::RateLimit { .. } => 429,
-            Self::FileProcessing { .. } => 400,
-            Self::Vector { .. } => 500,
-            Self::Tenant { .. } => 400,
-            Self::ExternalService { .. } => 502,
-            Self::Internal { .. } => 500,
-            Self::ServiceUnavailable { .. } => 503,
-            Self::Timeout { .. } => 408,
-        }
+        error_codes::http_status(self.error_code())
     }
 
     /// 是否为客户端错误
@@ -4,10 +4,12 @@
 pub mod setup;
 pub mod context;
 pub mod filters;
+pub mod redaction;
 
 #[cfg(test)]
 mod tests;
 
 pub use setup::*;
 pub use context::*;
-pub use filters::*;
\ No newline at end of file
+pub use filters::*;
+pub use redaction::*;
\ No newline at end of file
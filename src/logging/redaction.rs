@@ -0,0 +1,112 @@
+// 日志脱敏
+// 运行期注册的敏感值（如工作流注入的租户密钥）在写出日志前被替换为占位符
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::RwLock;
+use once_cell::sync::Lazy;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// 日志中替换敏感值的占位符
+pub const REDACTED: &str = "[REDACTED]";
+
+/// 过短的值容易误伤正常日志内容，不参与脱敏
+const MIN_REDACTED_LEN: usize = 4;
+
+/// 当前需要脱敏的值及其引用计数
+static REDACTED_VALUES: Lazy<RwLock<HashMap<String, usize>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 注册需要在日志中脱敏的值，返回的守卫被 drop 时取消注册
+pub fn register_redactions<I, S>(values: I) -> RedactionGuard
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let values: Vec<String> = values
+        .into_iter()
+        .map(Into::into)
+        .filter(|value| value.len() >= MIN_REDACTED_LEN)
+        .collect();
+
+    let mut registry = REDACTED_VALUES.write().unwrap();
+    for value in &values {
+        *registry.entry(value.clone()).or_insert(0) += 1;
+    }
+    RedactionGuard { values }
+}
+
+/// 将文本中已注册的敏感值替换为占位符
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let registry = REDACTED_VALUES.read().unwrap();
+    if registry.is_empty() {
+        return Cow::Borrowed(text);
+    }
+
+    // 先替换较长的值，避免其中包含的较短值导致残留
+    let mut values: Vec<&String> = registry.keys().filter(|value| text.contains(value.as_str())).collect();
+    if values.is_empty() {
+        return Cow::Borrowed(text);
+    }
+    values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+
+    let mut redacted = text.to_string();
+    for value in values {
+        redacted = redacted.replace(value.as_str(), REDACTED);
+    }
+    Cow::Owned(redacted)
+}
+
+/// 敏感值注册守卫
+#[derive(Debug)]
+pub struct RedactionGuard {
+    values: Vec<String>,
+}
+
+impl Drop for RedactionGuard {
+    fn drop(&mut self) {
+        let mut registry = REDACTED_VALUES.write().unwrap();
+        for value in &self.values {
+            if let Some(count) = registry.get_mut(value) {
+                *count -= 1;
+                if *count == 0 {
+                    registry.remove(value);
+                }
+            }
+        }
+    }
+}
+
+/// 写出前脱敏的日志输出
+pub struct RedactingWriter<W> {
+    inner: W,
+}
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match std::str::from_utf8(buf) {
+            Ok(text) => match redact(text) {
+                Cow::Borrowed(_) => self.inner.write_all(buf)?,
+                Cow::Owned(redacted) => self.inner.write_all(redacted.as_bytes())?,
+            },
+            Err(_) => self.inner.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 输出到标准输出并脱敏的日志写入器工厂
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RedactingStdout;
+
+impl<'a> MakeWriter<'a> for RedactingStdout {
+    type Writer = RedactingWriter<io::Stdout>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter { inner: io::stdout() }
+    }
+}
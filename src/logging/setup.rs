@@ -4,6 +4,8 @@ use crate::config::LoggingConfig;
 use anyhow::Result;
 
 use tracing::Level;
+
+use super::redaction::RedactingStdout;
 use tracing_subscriber::{
     EnvFilter, Layer,
 };
@@ -25,6 +27,7 @@ impl LoggingSetup {
                 let subscriber = tracing_subscriber::fmt()
                     .json()
                     .with_env_filter(env_filter)
+                    .with_writer(RedactingStdout)
                     .with_target(true)
                     .with_thread_ids(true)
                     .with_thread_names(true)
@@ -37,6 +40,7 @@ impl LoggingSetup {
                 let subscriber = tracing_subscriber::fmt()
                     .pretty()
                     .with_env_filter(env_filter)
+                    .with_writer(RedactingStdout)
                     .with_target(true)
                     .with_thread_ids(true)
                     .with_thread_names(true)
@@ -49,6 +53,7 @@ impl LoggingSetup {
                 let subscriber = tracing_subscriber::fmt()
                     .compact()
                     .with_env_filter(env_filter)
+                    .with_writer(RedactingStdout)
                     .with_target(true)
                     .finish();
                 tracing::subscriber::set_global_default(subscriber)?;
@@ -56,6 +61,7 @@ impl LoggingSetup {
             _ => {
                 let subscriber = tracing_subscriber::fmt()
                     .with_env_filter(env_filter)
+                    .with_writer(RedactingStdout)
                     .with_target(true)
                     .with_thread_ids(true)
                     .with_thread_names(true)
@@ -147,4 +147,23 @@ mod tests {
         assert!(!context.request_id.is_empty());
         assert!(!context.trace_id.is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_redaction_guard_lifecycle() {
+        use crate::logging::{redact, register_redactions, REDACTED};
+
+        let secret = "crm-token-8f2a91";
+        let line = format!("调用失败: Authorization: Bearer {}", secret);
+        assert_eq!(redact(&line), line);
+
+        let guard = register_redactions([secret, "abc"]);
+        let redacted = redact(&line);
+        assert!(!redacted.contains(secret));
+        assert!(redacted.ends_with(REDACTED));
+        // 过短的值不参与脱敏
+        assert_eq!(redact("abc"), "abc");
+
+        drop(guard);
+        assert_eq!(redact(&line), line);
+    }
+}
@@ -1,5 +1,4 @@
-use actix_web::{web, App, HttpServer, HttpResponse, Result as ActixResult};
-use actix_cors::Cors;
+use actix_web::{web, HttpServer, HttpResponse, Result as ActixResult};
 use chrono::Utc;
 
 mod ai;
@@ -12,10 +11,37 @@ mod health;
 mod services;
 mod plugins;
 
-use config::ConfigLoader;
-use errors::ErrorHandlerMiddleware;
+use config::{ConfigLoader, ProcessRole};
 use logging::LoggingSetup;
-use db::{DatabaseManager, MigrationManager, SeedDataManager};
+use db::{DatabaseManager, DistributedLockService, MigrationManager, SeedDataManager};
+use services::cache::CacheService;
+use services::canary::{CanaryEvaluationJob, CanaryService};
+use services::duplicate_detection::DuplicateDetectionExecutor;
+use services::embedding_export::EmbeddingExportExecutor;
+use services::egress::install_egress;
+use services::execution_artifact::{ExecutionArtifactCleanupJob, ExecutionArtifactService};
+use services::finetune_dataset::FinetuneDatasetExecutor;
+use services::freshness::{DocumentFreshnessService, DocumentReviewJob};
+use services::kb_stats::{KbStatsRollupJob, KbStatsService};
+use services::lexical_index::{LexicalIndexService, LexicalIndexSyncJob};
+use services::process_role;
+use services::prompt_retention::{install_prompt_recorder, PromptLogPurgeJob, PromptRetentionService};
+use services::replication::{ReplicationJob, ReplicationQueue, ReplicationService};
+use services::retention::{RetentionJob, RetentionService};
+use services::audit_log::AuditLogService;
+use services::saved_search::SavedSearchService;
+use services::scheduled_report::{ScheduledReportJob, ScheduledReportService};
+use services::scheduler::SchedulerService;
+use services::self_test::SelfTest;
+use services::slo::SloTracker;
+use services::source_health::{SourceHealthCheckJob, SourceHealthService};
+use services::task_queue::{TaskQueueService, TaskQueueServiceFactory};
+use services::topic_map::TopicClusteringExecutor;
+use services::transcript_export::TranscriptExportExecutor;
+use services::usage_anomaly::{install_usage_recorder, UsageAnomalyJob, UsageAnomalyService};
+use services::vector_migration::VectorMigrationExecutor;
+use services::workflow_callback::{WorkflowCallbackService, WorkflowCallbackTimeoutJob};
+use api::middleware::stack::MiddlewareStack;
 use api::routes::ApiRouteConfig;
 
 #[actix_web::main]
@@ -30,6 +56,15 @@ async fn main() -> std::io::Result<()> {
     
     tracing::info!("🚀 启动 Aionix AI Studio v{}", config.environment.version);
 
+    // 进程角色：命令行 --role 优先于配置中的 server.role
+    let role = ProcessRole::from_args(std::env::args().skip(1))
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?
+        .unwrap_or(config.server.role);
+    if let Err(e) = process_role::install(role) {
+        tracing::warn!("进程角色初始化失败: {}", e);
+    }
+    tracing::info!("🧩 运行角色: {}", role.as_str());
+
     // 初始化数据库连接
     DatabaseManager::init(config.database.clone())
         .await
@@ -38,6 +73,19 @@ async fn main() -> std::io::Result<()> {
     // 初始化数据库迁移系统
     let db_manager = DatabaseManager::get()
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+
+    // 出站请求统一经配置的代理与证书发出，须在创建任何出站客户端之前加载
+    if let Err(e) = install_egress(&config.egress, db_manager.get_connection().clone()) {
+        tracing::warn!("出站网络策略初始化失败: {}", e);
+    }
+
+    // 自检模式：检查迁移状态、数据库读写、嵌入生成与 Agent 工具调用后退出，任一项失败时返回非零状态码
+    if std::env::args().any(|arg| arg == "--self-test") {
+        let report = SelfTest::new(db_manager.get_connection().clone(), config.ai.clone()).run().await;
+        println!("{}", report.render());
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
+
     let migration_manager = MigrationManager::new(db_manager.get_connection().clone());
     migration_manager.init()
         .await
@@ -61,6 +109,160 @@ async fn main() -> std::io::Result<()> {
         tracing::warn!("种子数据初始化失败: {}", e);
     }
     
+    // 初始化热点实体缓存，中间件中的租户与用户查询优先读取缓存
+    if config.cache.enabled {
+        match CacheService::from_config(config) {
+            Ok(cache) => {
+                if let Err(e) = CacheService::install_global(std::sync::Arc::new(cache)) {
+                    tracing::warn!("缓存服务初始化失败: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("缓存服务初始化失败，将直接查询数据库: {}", e),
+        }
+    }
+
+    // 按接口类别跟踪服务等级目标
+    if config.slo.enabled {
+        if let Err(e) = SloTracker::install_global(std::sync::Arc::new(SloTracker::new(&config.slo))) {
+            tracing::warn!("SLO 跟踪器初始化失败: {}", e);
+        }
+    }
+
+    // 启动周期任务调度器（多实例部署时由分布式租约保证每个任务只在一个实例上执行）
+    let lock_service = DistributedLockService::new(db_manager.get_connection().clone());
+    let mut scheduler = SchedulerService::new(lock_service.clone());
+    if config.retention.enabled {
+        let retention_service = std::sync::Arc::new(RetentionService::new(
+            db_manager.get_connection().clone(),
+            config.retention.clone(),
+        ));
+        scheduler.register(std::sync::Arc::new(RetentionJob::new(retention_service)));
+    }
+    let freshness_service = std::sync::Arc::new(DocumentFreshnessService::new(db_manager.get_connection().clone()));
+    scheduler.register(std::sync::Arc::new(DocumentReviewJob::new(freshness_service)));
+    match SourceHealthService::new(db_manager.get_connection().clone()) {
+        Ok(service) => scheduler.register(std::sync::Arc::new(SourceHealthCheckJob::new(std::sync::Arc::new(service)))),
+        Err(e) => tracing::warn!("来源链接检查任务初始化失败: {}", e),
+    }
+    let kb_stats_service = std::sync::Arc::new(KbStatsService::new(db_manager.get_connection().clone()));
+    scheduler.register(std::sync::Arc::new(KbStatsRollupJob::new(kb_stats_service)));
+    let lexical_index_service = std::sync::Arc::new(LexicalIndexService::new(db_manager.get_connection().clone()));
+    scheduler.register(std::sync::Arc::new(LexicalIndexSyncJob::new(lexical_index_service)));
+    let workflow_callback_service = std::sync::Arc::new(WorkflowCallbackService::new(
+        db_manager.get_connection().clone(),
+        config.security.jwt_secret.clone(),
+    ));
+    scheduler.register(std::sync::Arc::new(WorkflowCallbackTimeoutJob::new(workflow_callback_service)));
+    let canary_service = std::sync::Arc::new(CanaryService::new(db_manager.get_connection().clone()));
+    scheduler.register(std::sync::Arc::new(CanaryEvaluationJob::new(canary_service)));
+    let scheduled_report_service = std::sync::Arc::new(ScheduledReportService::from_app_config(
+        db_manager.get_connection().clone(),
+        config,
+    ));
+    scheduler.register(std::sync::Arc::new(ScheduledReportJob::new(scheduled_report_service)));
+    if config.execution_artifacts.enabled {
+        let artifact_service = std::sync::Arc::new(ExecutionArtifactService::from_app_config(
+            db_manager.get_connection().clone(),
+            config,
+        ));
+        scheduler.register(std::sync::Arc::new(ExecutionArtifactCleanupJob::new(artifact_service)));
+    }
+    // 模型调用日志按租户留存策略写入，到期后由清理任务删除
+    if let Err(e) = install_prompt_recorder(db_manager.get_connection().clone()) {
+        tracing::warn!("模型调用日志初始化失败: {}", e);
+    }
+    scheduler.register(std::sync::Arc::new(PromptLogPurgeJob::new(PromptRetentionService::new(
+        db_manager.get_connection().clone(),
+    ))));
+    if config.usage_anomaly.enabled {
+        if let Err(e) = install_usage_recorder(db_manager.get_connection().clone()) {
+            tracing::warn!("用量计数初始化失败: {}", e);
+        }
+        let usage_anomaly_service = std::sync::Arc::new(UsageAnomalyService::new(
+            db_manager.get_connection().clone(),
+            config.usage_anomaly.clone(),
+        ));
+        scheduler.register(std::sync::Arc::new(UsageAnomalyJob::new(usage_anomaly_service)));
+    }
+    if config.replication.enabled {
+        let replication_service = std::sync::Arc::new(ReplicationService::new(
+            db_manager.get_connection().clone(),
+            config.replication.clone(),
+            create_replication_queue(config)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?,
+        ));
+        match replication_service.init().await {
+            Ok(role) => {
+                tracing::info!("跨区域复制已启用，区域 {}，角色 {}", config.replication.region, role.as_str());
+                scheduler.register(std::sync::Arc::new(ReplicationJob::new(replication_service.clone())));
+                if let Err(e) = ReplicationService::install_global(replication_service) {
+                    tracing::warn!("复制服务初始化失败: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("复制状态初始化失败: {}", e),
+        }
+    }
+    // 周期任务只在 worker 与 all 角色中执行，api 角色仅注册以便共用初始化逻辑
+    if role.runs_workers() {
+        scheduler.start();
+        process_role::mark_workers_started();
+    }
+
+    // 配置了本地推理后端时启动嵌入工作池
+    if let Some(local) = &config.ai.local {
+        let client = ai::LocalInferenceClient::new(std::sync::Arc::new(config.ai.clone()))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        let pool = ai::EmbeddingPool::start(std::sync::Arc::new(client), local.embedding_pool.clone());
+        if let Err(e) = ai::EmbeddingPool::install_global(pool) {
+            tracing::warn!("嵌入工作池初始化失败: {}", e);
+        }
+    }
+
+    // 启动后台任务队列，会话记录导出、向量导出、微调数据集构建、重复文档检测、主题聚类等长时间任务在此执行
+    // 任务队列位于进程内，任务由接收请求的 API 进程执行，因此只在提供 API 的角色中启动
+    if role.serves_api() {
+        let task_queue = TaskQueueServiceFactory::create().await;
+        task_queue.register_executor(std::sync::Arc::new(TranscriptExportExecutor::new(
+            db_manager.get_connection().clone(),
+            config.storage.path.clone(),
+        ))).await;
+        task_queue.register_executor(std::sync::Arc::new(FinetuneDatasetExecutor::new(
+            db_manager.get_connection().clone(),
+            config.storage.path.clone(),
+        ))).await;
+        task_queue.register_executor(std::sync::Arc::new(DuplicateDetectionExecutor::new(
+            db_manager.get_connection().clone(),
+        ))).await;
+        task_queue.register_executor(std::sync::Arc::new(TopicClusteringExecutor::new(
+            db_manager.get_connection().clone(),
+            ai::client::AiClientManager::new(config.ai.clone()).ok().map(|manager| manager.client()),
+        ))).await;
+        task_queue.register_executor(std::sync::Arc::new(VectorMigrationExecutor::new(
+            db_manager.get_connection().clone(),
+            std::sync::Arc::new(ai::vector_store::VectorStoreRegistry::new(
+                db_manager.get_connection().clone(),
+                config.vector.clone(),
+            )),
+        ))).await;
+        task_queue.register_executor(std::sync::Arc::new(EmbeddingExportExecutor::new(
+            db_manager.get_connection().clone(),
+            std::sync::Arc::new(ai::vector_store::VectorStoreRegistry::new(
+                db_manager.get_connection().clone(),
+                config.vector.clone(),
+            )),
+            config.storage.path.clone(),
+        ))).await;
+        if let Err(e) = TaskQueueService::install_global(task_queue) {
+            tracing::warn!("任务队列初始化失败: {}", e);
+        }
+    }
+
+    // 启动保存的搜索告警监听，新文档匹配订阅时通知用户
+    SavedSearchService::start_alert_listener(db_manager.get_connection().clone());
+
+    // 启动审计事件记录，租户内的业务事件写入审计日志供活动时间线使用
+    AuditLogService::start_event_recorder(db_manager.get_connection().clone());
+    
     // 打印配置摘要
     ConfigLoader::print_summary();
     
@@ -68,27 +270,21 @@ async fn main() -> std::io::Result<()> {
     tracing::info!("📋 健康检查: http://{}:{}/health", config.server.host, config.server.port);
     
     // 启动 HTTP 服务器
+    let middleware_stack = MiddlewareStack::from_config(config);
+    tracing::info!("🧱 全局中间件: {}", middleware_stack.enabled().join(", "));
     let mut server = HttpServer::new(move || {
-        let app = App::new()
-            // CORS 配置
-            .wrap(
-                Cors::default()
-                    .allow_any_origin()
-                    .allow_any_method()
-                    .allow_any_header()
-                    .max_age(3600)
-            )
-            // 添加错误处理中间件
-            .wrap(ErrorHandlerMiddleware)
-            // 添加 tracing 中间件
-            .wrap(tracing_actix_web::TracingLogger::default())
+        // 全局中间件按 [http] 配置装配
+        let app = middleware_stack
+            .build_app()
             // 根路径
             .route("/", web::get().to(index))
             // 传统健康检查端点（向后兼容）
             .route("/health", web::get().to(health::health_check));
 
-        // 根据环境配置不同的路由
-        let app = if cfg!(debug_assertions) {
+        // worker 角色只提供健康检查，API 路由按环境配置
+        let app = if !role.serves_api() {
+            app.configure(ApiRouteConfig::configure_worker)
+        } else if cfg!(debug_assertions) {
             app.configure(ApiRouteConfig::configure_dev)
         } else {
             app.configure(ApiRouteConfig::configure_prod)
@@ -102,10 +298,17 @@ async fn main() -> std::io::Result<()> {
         server = server.workers(workers);
     }
 
-    server
+    let result = server
         .bind((config.server.host.clone(), config.server.port))?
         .run()
-        .await
+        .await;
+
+    // 释放本实例持有的调度租约，便于其他实例立即接管
+    if let Err(e) = lock_service.release_all_leases().await {
+        tracing::warn!("释放调度租约失败: {}", e);
+    }
+
+    result
 }
 
 /// 根路径处理器
@@ -123,12 +326,37 @@ async fn index() -> ActixResult<HttpResponse> {
         "health": {
             "simple": "/health",
             "detailed": "/api/v1/health/detailed",
+            "replication": "/api/v1/health/replication",
             "ready": "/api/v1/ready",
             "live": "/api/v1/live"
         },
+        "role": process_role::current().as_str(),
         "timestamp": chrono::Utc::now(),
         "environment": if cfg!(debug_assertions) { "development" } else { "production" }
     });
 
     Ok(HttpResponse::Ok().json(info))
-}
\ No newline at end of file
+}
+
+/// 创建跨区域复制队列
+///
+/// 启用 Redis 时使用 Redis Stream，未单独配置队列地址时复用 Redis 配置；否则退化为进程内队列，仅适用于单区域调试。
+fn create_replication_queue(
+    config: &config::AppConfig,
+) -> Result<std::sync::Arc<dyn ReplicationQueue>, errors::AiStudioError> {
+    #[cfg(feature = "redis")]
+    {
+        let url = config.replication.queue_url.as_deref().unwrap_or(&config.redis.url);
+        Ok(std::sync::Arc::new(services::replication::RedisReplicationQueue::new(
+            url,
+            config.replication.stream.clone(),
+        )?))
+    }
+
+    #[cfg(not(feature = "redis"))]
+    {
+        let _ = config;
+        tracing::warn!("未启用 redis 特性，复制事件仅发布到进程内队列");
+        Ok(std::sync::Arc::new(services::replication::InMemoryReplicationQueue::new()))
+    }
+}
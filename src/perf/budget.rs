@@ -0,0 +1,363 @@
+// 性能预算与回归检查
+// 记录热路径基准与 HTTP 场景的基线数据，超过基线一定比例即视为性能回归
+
+use crate::errors::AiStudioError;
+use crate::perf::load::ScenarioResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// 默认的回归阈值（超过基线 15% 视为回归）
+pub const DEFAULT_REGRESSION_THRESHOLD: f64 = 0.15;
+
+/// 性能预算文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceBudgets {
+    /// 回归阈值，实测值超过基线 × (1 + 阈值) 即判定为回归
+    #[serde(default = "default_regression_threshold")]
+    pub regression_threshold: f64,
+    /// criterion 基准基线，键为 `<组>/<基准>`
+    #[serde(default)]
+    pub benchmarks: BTreeMap<String, BenchmarkBaseline>,
+    /// HTTP 负载场景基线，键为场景名称
+    #[serde(default)]
+    pub scenarios: BTreeMap<String, ScenarioBaseline>,
+}
+
+fn default_regression_threshold() -> f64 {
+    DEFAULT_REGRESSION_THRESHOLD
+}
+
+impl Default for PerformanceBudgets {
+    fn default() -> Self {
+        Self {
+            regression_threshold: DEFAULT_REGRESSION_THRESHOLD,
+            benchmarks: BTreeMap::new(),
+            scenarios: BTreeMap::new(),
+        }
+    }
+}
+
+/// 单个基准的基线
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkBaseline {
+    /// 平均耗时（纳秒）
+    pub mean_ns: f64,
+}
+
+/// 单个 HTTP 场景的基线
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioBaseline {
+    /// 中位延迟（毫秒）
+    pub p50_ms: f64,
+    /// P95 延迟（毫秒）
+    pub p95_ms: f64,
+    /// 允许的最大错误率（0-1），不随回归阈值放宽
+    pub max_error_rate: f64,
+}
+
+/// 单项预算检查结果
+#[derive(Debug, Clone)]
+pub struct BudgetCheck {
+    /// 基准或场景名称
+    pub name: String,
+    /// 指标名称
+    pub metric: &'static str,
+    /// 基线值
+    pub baseline: f64,
+    /// 实测值
+    pub actual: f64,
+    /// 允许的上限
+    pub limit: f64,
+}
+
+impl BudgetCheck {
+    /// 是否在预算内
+    pub fn passed(&self) -> bool {
+        self.actual <= self.limit
+    }
+
+    /// 相对基线的变化比例
+    pub fn change(&self) -> f64 {
+        if self.baseline == 0.0 {
+            return 0.0;
+        }
+        self.actual / self.baseline - 1.0
+    }
+}
+
+/// 预算检查报告
+#[derive(Debug, Clone, Default)]
+pub struct BudgetReport {
+    /// 检查结果
+    pub checks: Vec<BudgetCheck>,
+    /// 缺少基线、未参与检查的名称
+    pub missing_baselines: Vec<String>,
+}
+
+impl BudgetReport {
+    /// 是否全部在预算内
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(BudgetCheck::passed)
+    }
+
+    /// 超出预算的检查项
+    pub fn regressions(&self) -> impl Iterator<Item = &BudgetCheck> {
+        self.checks.iter().filter(|check| !check.passed())
+    }
+}
+
+impl fmt::Display for BudgetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(
+                f,
+                "{} {} {}: 基线 {:.2}，实测 {:.2}（{:+.1}%），上限 {:.2}",
+                if check.passed() { "✓" } else { "✗" },
+                check.name,
+                check.metric,
+                check.baseline,
+                check.actual,
+                check.change() * 100.0,
+                check.limit,
+            )?;
+        }
+        for name in &self.missing_baselines {
+            writeln!(f, "- {}: 无基线，已跳过", name)?;
+        }
+        let regressions = self.regressions().count();
+        if regressions == 0 {
+            write!(f, "全部 {} 项在性能预算内", self.checks.len())
+        } else {
+            write!(f, "{} 项超出性能预算", regressions)
+        }
+    }
+}
+
+impl PerformanceBudgets {
+    /// 从 JSON 文件加载性能预算
+    pub fn load(path: &Path) -> Result<Self, AiStudioError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AiStudioError::configuration(format!("读取性能预算文件 {} 失败: {}", path.display(), e))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            AiStudioError::configuration(format!("解析性能预算文件 {} 失败: {}", path.display(), e))
+        })
+    }
+
+    /// 写入 JSON 文件
+    pub fn save(&self, path: &Path) -> Result<(), AiStudioError> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| AiStudioError::internal(format!("序列化性能预算失败: {}", e)))?;
+        std::fs::write(path, content + "\n").map_err(|e| {
+            AiStudioError::configuration(format!("写入性能预算文件 {} 失败: {}", path.display(), e))
+        })
+    }
+
+    /// 基线允许的上限
+    fn limit(&self, baseline: f64) -> f64 {
+        baseline * (1.0 + self.regression_threshold)
+    }
+
+    /// 将 criterion 平均耗时与基线比较
+    pub fn check_benchmarks(&self, results: &BTreeMap<String, f64>) -> BudgetReport {
+        let mut report = BudgetReport::default();
+        for (name, &mean_ns) in results {
+            match self.benchmarks.get(name) {
+                Some(baseline) => report.checks.push(BudgetCheck {
+                    name: name.clone(),
+                    metric: "mean_ns",
+                    baseline: baseline.mean_ns,
+                    actual: mean_ns,
+                    limit: self.limit(baseline.mean_ns),
+                }),
+                None => report.missing_baselines.push(name.clone()),
+            }
+        }
+        report
+    }
+
+    /// 将负载场景的延迟与错误率与基线比较
+    pub fn check_scenarios(&self, results: &[ScenarioResult]) -> BudgetReport {
+        let mut report = BudgetReport::default();
+        for result in results {
+            let Some(baseline) = self.scenarios.get(&result.name) else {
+                report.missing_baselines.push(result.name.clone());
+                continue;
+            };
+            report.checks.push(BudgetCheck {
+                name: result.name.clone(),
+                metric: "p50_ms",
+                baseline: baseline.p50_ms,
+                actual: result.p50_ms,
+                limit: self.limit(baseline.p50_ms),
+            });
+            report.checks.push(BudgetCheck {
+                name: result.name.clone(),
+                metric: "p95_ms",
+                baseline: baseline.p95_ms,
+                actual: result.p95_ms,
+                limit: self.limit(baseline.p95_ms),
+            });
+            report.checks.push(BudgetCheck {
+                name: result.name.clone(),
+                metric: "error_rate",
+                baseline: baseline.max_error_rate,
+                actual: result.error_rate(),
+                limit: baseline.max_error_rate,
+            });
+        }
+        report
+    }
+
+    /// 以实测结果更新 criterion 基线
+    pub fn record_benchmarks(&mut self, results: &BTreeMap<String, f64>) {
+        for (name, &mean_ns) in results {
+            self.benchmarks.insert(name.clone(), BenchmarkBaseline { mean_ns });
+        }
+    }
+
+    /// 以实测结果更新场景基线，保留已配置的错误率上限
+    pub fn record_scenarios(&mut self, results: &[ScenarioResult]) {
+        for result in results {
+            let max_error_rate = self.scenarios.get(&result.name).map_or(0.0, |baseline| baseline.max_error_rate);
+            self.scenarios.insert(
+                result.name.clone(),
+                ScenarioBaseline { p50_ms: result.p50_ms, p95_ms: result.p95_ms, max_error_rate },
+            );
+        }
+    }
+}
+
+/// 读取 criterion 输出目录中各基准的平均耗时（纳秒）
+///
+/// criterion 将每个基准的最新结果写入 `<目录>/<组>/<基准>/new/estimates.json`，
+/// 返回的键为基准相对输出目录的路径，例如 `ingestion/hybrid_chunking`。
+pub fn read_criterion_estimates(dir: &Path) -> Result<BTreeMap<String, f64>, AiStudioError> {
+    let mut results = BTreeMap::new();
+    collect_criterion_estimates(dir, dir, &mut results)?;
+    Ok(results)
+}
+
+fn collect_criterion_estimates(
+    root: &Path,
+    dir: &Path,
+    results: &mut BTreeMap<String, f64>,
+) -> Result<(), AiStudioError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        AiStudioError::configuration(format!("读取基准结果目录 {} 失败: {}", dir.display(), e))
+    })?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        // criterion 自身的汇总报告目录
+        if path.file_name().is_some_and(|name| name == "report") {
+            continue;
+        }
+
+        let estimates = path.join("new").join("estimates.json");
+        if estimates.is_file() {
+            let content = std::fs::read_to_string(&estimates).map_err(|e| {
+                AiStudioError::configuration(format!("读取 {} 失败: {}", estimates.display(), e))
+            })?;
+            let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+                AiStudioError::configuration(format!("解析 {} 失败: {}", estimates.display(), e))
+            })?;
+            if let Some(mean) = value["mean"]["point_estimate"].as_f64() {
+                let name = path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                results.insert(name, mean);
+            }
+        } else {
+            collect_criterion_estimates(root, &path, results)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budgets() -> PerformanceBudgets {
+        PerformanceBudgets {
+            regression_threshold: 0.1,
+            benchmarks: BTreeMap::from([("qa/retrieval_confidence".to_string(), BenchmarkBaseline { mean_ns: 100.0 })]),
+            scenarios: BTreeMap::from([(
+                "qa".to_string(),
+                ScenarioBaseline { p50_ms: 100.0, p95_ms: 200.0, max_error_rate: 0.01 },
+            )]),
+        }
+    }
+
+    fn scenario(p50_ms: f64, p95_ms: f64, errors: usize) -> ScenarioResult {
+        ScenarioResult {
+            name: "qa".to_string(),
+            requests: 100,
+            errors,
+            p50_ms,
+            p95_ms,
+            p99_ms: p95_ms,
+            throughput_rps: 10.0,
+        }
+    }
+
+    #[test]
+    fn test_benchmark_regression_threshold() {
+        let budgets = budgets();
+
+        let within = BTreeMap::from([("qa/retrieval_confidence".to_string(), 109.0)]);
+        assert!(budgets.check_benchmarks(&within).passed());
+
+        let regressed = BTreeMap::from([
+            ("qa/retrieval_confidence".to_string(), 111.0),
+            ("qa/new_bench".to_string(), 1.0),
+        ]);
+        let report = budgets.check_benchmarks(&regressed);
+        assert!(!report.passed());
+        assert_eq!(report.missing_baselines, vec!["qa/new_bench".to_string()]);
+        assert!(report.to_string().contains("1 项超出性能预算"));
+    }
+
+    #[test]
+    fn test_scenario_checks_latency_and_error_rate() {
+        let budgets = budgets();
+        assert!(budgets.check_scenarios(&[scenario(105.0, 210.0, 1)]).passed());
+
+        let report = budgets.check_scenarios(&[scenario(90.0, 190.0, 5)]);
+        let failed: Vec<&str> = report.regressions().map(|check| check.metric).collect();
+        assert_eq!(failed, vec!["error_rate"]);
+    }
+
+    #[test]
+    fn test_record_keeps_error_budget() {
+        let mut budgets = budgets();
+        budgets.record_scenarios(&[scenario(80.0, 150.0, 0)]);
+        assert_eq!(
+            budgets.scenarios["qa"],
+            ScenarioBaseline { p50_ms: 80.0, p95_ms: 150.0, max_error_rate: 0.01 }
+        );
+    }
+
+    #[test]
+    fn test_read_criterion_estimates() {
+        let dir = tempfile::tempdir().unwrap();
+        let bench_dir = dir.path().join("ingestion").join("hybrid_chunking").join("new");
+        std::fs::create_dir_all(&bench_dir).unwrap();
+        std::fs::write(bench_dir.join("estimates.json"), r#"{"mean":{"point_estimate":1234.5}}"#).unwrap();
+        std::fs::create_dir_all(dir.path().join("report")).unwrap();
+
+        let results = read_criterion_estimates(dir.path()).unwrap();
+        assert_eq!(results, BTreeMap::from([("ingestion/hybrid_chunking".to_string(), 1234.5)]));
+    }
+}
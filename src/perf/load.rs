@@ -0,0 +1,266 @@
+// HTTP 负载场景运行器
+// 以固定并发向运行中的服务发送请求，统计延迟分位数、吞吐量与错误率
+
+use crate::errors::AiStudioError;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// multipart 请求的分隔符
+const MULTIPART_BOUNDARY: &str = "aionix-load-boundary";
+
+/// 文档上传场景使用的文档内容
+const INGESTION_DOCUMENT: &str = "Aionix 负载测试文档。\n\n\
+    平台支持多租户知识库、文档自动分块与向量化，以及基于检索增强生成的问答。\n\n\
+    本文档由负载测试工具生成，可在测试结束后按标题前缀 load-test 批量清理。";
+
+/// 问答与相关问题场景使用的问题
+const LOAD_QUESTION: &str = "Aionix 支持哪些文档处理能力？";
+
+/// 负载场景
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadScenario {
+    /// 文档上传（解析与入库）
+    Ingestion,
+    /// 相关问题推荐（向量检索）
+    RelatedQuestions,
+    /// 问答（检索 + 生成）
+    Qa,
+}
+
+impl LoadScenario {
+    /// 全部场景
+    pub const ALL: [LoadScenario; 3] = [Self::Ingestion, Self::RelatedQuestions, Self::Qa];
+
+    /// 场景名称，与性能预算文件中的键一致
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ingestion => "ingestion",
+            Self::RelatedQuestions => "related_questions",
+            Self::Qa => "qa",
+        }
+    }
+
+    /// 按名称解析场景
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scenario| scenario.name() == name)
+    }
+}
+
+/// 负载测试配置
+#[derive(Debug, Clone)]
+pub struct LoadConfig {
+    /// 服务地址，例如 http://localhost:8080
+    pub base_url: String,
+    /// 访问令牌
+    pub access_token: Option<String>,
+    /// 租户 ID
+    pub tenant_id: Option<Uuid>,
+    /// 场景使用的知识库
+    pub knowledge_base_id: Uuid,
+    /// 每个场景的请求总数
+    pub requests: usize,
+    /// 并发数
+    pub concurrency: usize,
+    /// 单个请求超时（秒）
+    pub timeout_secs: u64,
+}
+
+/// 单个场景的运行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    /// 场景名称
+    pub name: String,
+    /// 请求总数
+    pub requests: usize,
+    /// 失败的请求数（网络错误或非 2xx 响应）
+    pub errors: usize,
+    /// 中位延迟（毫秒）
+    pub p50_ms: f64,
+    /// P95 延迟（毫秒）
+    pub p95_ms: f64,
+    /// P99 延迟（毫秒）
+    pub p99_ms: f64,
+    /// 吞吐量（请求/秒）
+    pub throughput_rps: f64,
+}
+
+impl ScenarioResult {
+    /// 错误率
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.requests as f64
+    }
+
+    /// 根据每个请求的耗时与成功状态汇总结果
+    pub fn from_samples(name: &str, samples: &[(Duration, bool)], elapsed: Duration) -> Self {
+        let mut latencies: Vec<f64> = samples.iter().map(|(latency, _)| latency.as_secs_f64() * 1000.0).collect();
+        latencies.sort_by(f64::total_cmp);
+        let elapsed_secs = elapsed.as_secs_f64();
+
+        Self {
+            name: name.to_string(),
+            requests: samples.len(),
+            errors: samples.iter().filter(|(_, success)| !success).count(),
+            p50_ms: percentile(&latencies, 50.0),
+            p95_ms: percentile(&latencies, 95.0),
+            p99_ms: percentile(&latencies, 99.0),
+            throughput_rps: if elapsed_secs > 0.0 { samples.len() as f64 / elapsed_secs } else { 0.0 },
+        }
+    }
+}
+
+/// 计算已排序数据的分位数（最近秩法）
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// HTTP 负载场景运行器
+pub struct LoadRunner {
+    client: reqwest::Client,
+    config: LoadConfig,
+}
+
+impl LoadRunner {
+    /// 创建运行器
+    pub fn new(config: LoadConfig) -> Result<Self, AiStudioError> {
+        if config.requests == 0 || config.concurrency == 0 {
+            return Err(AiStudioError::validation("load", "请求数与并发数必须大于 0"));
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| AiStudioError::internal(format!("创建 HTTP 客户端失败: {}", e)))?;
+
+        Ok(Self { client, config })
+    }
+
+    /// 运行单个场景
+    pub async fn run(&self, scenario: LoadScenario) -> ScenarioResult {
+        let next = AtomicUsize::new(0);
+        let started = Instant::now();
+
+        let workers = (0..self.config.concurrency.min(self.config.requests)).map(|_| async {
+            let mut samples = Vec::new();
+            loop {
+                let iteration = next.fetch_add(1, Ordering::Relaxed);
+                if iteration >= self.config.requests {
+                    break;
+                }
+                let request_started = Instant::now();
+                let success = match self.request(scenario, iteration).send().await {
+                    Ok(response) => response.status().is_success(),
+                    Err(_) => false,
+                };
+                samples.push((request_started.elapsed(), success));
+            }
+            samples
+        });
+
+        let samples: Vec<(Duration, bool)> = futures::future::join_all(workers).await.into_iter().flatten().collect();
+        ScenarioResult::from_samples(scenario.name(), &samples, started.elapsed())
+    }
+
+    /// 构造场景请求
+    fn request(&self, scenario: LoadScenario, iteration: usize) -> reqwest::RequestBuilder {
+        let base_url = self.config.base_url.trim_end_matches('/');
+        let knowledge_base_id = self.config.knowledge_base_id;
+
+        let mut request = match scenario {
+            LoadScenario::Ingestion => {
+                let (content_type, body) = multipart_document(knowledge_base_id, iteration);
+                self.client
+                    .post(format!("{}/api/v1/documents/upload", base_url))
+                    .header("Content-Type", content_type)
+                    .body(body)
+            }
+            LoadScenario::RelatedQuestions => self
+                .client
+                .post(format!("{}/api/v1/qa/related-questions", base_url))
+                .json(&serde_json::json!({
+                    "knowledge_base_id": knowledge_base_id,
+                    "question": LOAD_QUESTION,
+                })),
+            LoadScenario::Qa => self
+                .client
+                .post(format!("{}/api/v1/qa/ask", base_url))
+                .json(&serde_json::json!({
+                    "knowledge_base_id": knowledge_base_id,
+                    "question": LOAD_QUESTION,
+                })),
+        };
+
+        if let Some(token) = &self.config.access_token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(tenant_id) = self.config.tenant_id {
+            request = request.header("X-Tenant-ID", tenant_id.to_string());
+        }
+        request
+    }
+}
+
+/// 构造文档上传的 multipart 请求体，返回 Content-Type 与请求体
+fn multipart_document(knowledge_base_id: Uuid, iteration: usize) -> (String, Vec<u8>) {
+    let body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"knowledge_base_id\"\r\n\r\n{kb}\r\n\
+         --{boundary}\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nload-test-{iteration}\r\n\
+         --{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"load-test-{iteration}.txt\"\r\n\
+         Content-Type: text/plain\r\n\r\n{content}\r\n--{boundary}--\r\n",
+        boundary = MULTIPART_BOUNDARY,
+        kb = knowledge_base_id,
+        iteration = iteration,
+        content = INGESTION_DOCUMENT,
+    );
+
+    (format!("multipart/form-data; boundary={}", MULTIPART_BOUNDARY), body.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let sorted: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 50.0), 50.0);
+        assert_eq!(percentile(&sorted, 95.0), 95.0);
+        assert_eq!(percentile(&sorted, 100.0), 100.0);
+        assert_eq!(percentile(&[7.0], 99.0), 7.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_scenario_result_from_samples() {
+        let samples = vec![
+            (Duration::from_millis(10), true),
+            (Duration::from_millis(30), true),
+            (Duration::from_millis(20), false),
+            (Duration::from_millis(40), true),
+        ];
+        let result = ScenarioResult::from_samples("qa", &samples, Duration::from_secs(2));
+
+        assert_eq!(result.requests, 4);
+        assert_eq!(result.errors, 1);
+        assert_eq!(result.error_rate(), 0.25);
+        assert_eq!(result.p50_ms, 20.0);
+        assert_eq!(result.p95_ms, 40.0);
+        assert_eq!(result.throughput_rps, 2.0);
+    }
+
+    #[test]
+    fn test_scenario_names_round_trip() {
+        for scenario in LoadScenario::ALL {
+            assert_eq!(LoadScenario::from_name(scenario.name()), Some(scenario));
+        }
+        assert_eq!(LoadScenario::from_name("unknown"), None);
+    }
+}
@@ -0,0 +1,8 @@
+// 性能基准模块
+// HTTP 负载场景运行器与性能预算回归检查，供 aionix-bench 使用
+
+pub mod budget;
+pub mod load;
+
+pub use budget::*;
+pub use load::*;
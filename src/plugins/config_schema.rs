@@ -0,0 +1,223 @@
+// 插件配置模式校验
+// 按插件 `config_schema()` 返回的 JSON Schema 校验租户提交的配置
+
+use serde_json::{Map, Value};
+
+use crate::errors::AiStudioError;
+
+/// 配置校验违规项
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+    /// 出错字段路径（如 `$.webhook.url`）
+    pub path: String,
+    /// 错误描述
+    pub message: String,
+}
+
+/// 按 JSON Schema 校验配置
+///
+/// 支持常用关键字子集：`type`、`properties`、`required`、`additionalProperties`、
+/// `enum`、`minimum`/`maximum`、`minLength`/`maxLength`、`pattern`、`items`。
+pub fn validate_against_schema(schema: &Value, value: &Value) -> Result<(), AiStudioError> {
+    let violations = schema_violations(schema, value);
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    let message = violations.iter()
+        .map(|v| format!("{}: {}", v.path, v.message))
+        .collect::<Vec<_>>()
+        .join("; ");
+    Err(AiStudioError::validation("config", message))
+}
+
+/// 收集值相对 JSON Schema 的全部违规项，关键字支持范围同 [`validate_against_schema`]
+pub fn schema_violations(schema: &Value, value: &Value) -> Vec<SchemaViolation> {
+    let mut violations = Vec::new();
+    collect_violations(schema, value, "$", &mut violations);
+    violations
+}
+
+/// 获取模式中标记为敏感的顶层字段
+///
+/// `writeOnly: true`、`format: "password"` 或 `x-secret: true` 的字段视为敏感字段。
+pub fn secret_fields(schema: &Value) -> Vec<String> {
+    schema.get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| {
+            properties.iter()
+                .filter(|(_, prop)| is_secret(prop))
+                .map(|(name, _)| name.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 用模式中的默认值补全缺失的顶层字段
+pub fn apply_defaults(schema: &Value, config: &mut Map<String, Value>) {
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (name, prop) in properties {
+            if let Some(default) = prop.get("default") {
+                config.entry(name.clone()).or_insert_with(|| default.clone());
+            }
+        }
+    }
+}
+
+fn is_secret(prop: &Value) -> bool {
+    prop.get("writeOnly").and_then(Value::as_bool).unwrap_or(false)
+        || prop.get("x-secret").and_then(Value::as_bool).unwrap_or(false)
+        || prop.get("format").and_then(Value::as_str) == Some("password")
+}
+
+fn collect_violations(schema: &Value, value: &Value, path: &str, violations: &mut Vec<SchemaViolation>) {
+    let mut violate = |message: String| violations.push(SchemaViolation { path: path.to_string(), message });
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| matches_type(t, value)) {
+            violate(format!("类型应为 {}", types.join(" | ")));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            violate("取值不在允许范围内".to_string());
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+            if number < min {
+                violate(format!("不能小于 {}", min));
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+            if number > max {
+                violate(format!("不能大于 {}", max));
+            }
+        }
+    }
+
+    if let Some(text) = value.as_str() {
+        let length = text.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if length < min {
+                violate(format!("长度不能少于 {}", min));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if length > max {
+                violate(format!("长度不能超过 {}", max));
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            match regex::Regex::new(pattern) {
+                Ok(re) if !re.is_match(text) => violate(format!("不匹配模式 {}", pattern)),
+                Ok(_) => {}
+                Err(_) => violate(format!("模式 {} 无效", pattern)),
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    violations.push(SchemaViolation {
+                        path: format!("{}.{}", path, name),
+                        message: "缺少必填字段".to_string(),
+                    });
+                }
+            }
+        }
+
+        let additional_allowed = schema.get("additionalProperties").and_then(Value::as_bool).unwrap_or(true);
+        for (name, field) in object {
+            let field_path = format!("{}.{}", path, name);
+            match properties.and_then(|p| p.get(name)) {
+                Some(field_schema) => collect_violations(field_schema, field, &field_path, violations),
+                None if !additional_allowed => violations.push(SchemaViolation {
+                    path: field_path,
+                    message: "不允许的字段".to_string(),
+                }),
+                None => {}
+            }
+        }
+    }
+
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in array.iter().enumerate() {
+            collect_violations(items, item, &format!("{}[{}]", path, index), violations);
+        }
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["endpoint"],
+            "additionalProperties": false,
+            "properties": {
+                "endpoint": { "type": "string", "pattern": "^https?://" },
+                "api_token": { "type": "string", "writeOnly": true },
+                "retries": { "type": "integer", "minimum": 0, "maximum": 10, "default": 3 },
+                "mode": { "enum": ["fast", "safe"] }
+            }
+        })
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let config = json!({ "endpoint": "https://example.com", "retries": 2, "mode": "safe" });
+        assert!(validate_against_schema(&schema(), &config).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = json!({ "endpoint": "ftp://example.com", "retries": 20, "unknown": true });
+        let mut violations = Vec::new();
+        collect_violations(&schema(), &config, "$", &mut violations);
+
+        let paths: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+        assert!(paths.contains(&"$.endpoint"));
+        assert!(paths.contains(&"$.retries"));
+        assert!(paths.contains(&"$.unknown"));
+        assert!(validate_against_schema(&schema(), &json!({})).is_err());
+    }
+
+    #[test]
+    fn test_secret_fields_and_defaults() {
+        assert_eq!(secret_fields(&schema()), vec!["api_token".to_string()]);
+
+        let mut config = Map::new();
+        apply_defaults(&schema(), &mut config);
+        assert_eq!(config.get("retries"), Some(&json!(3)));
+    }
+}
@@ -0,0 +1,165 @@
+// 系统事件总线
+// 发布平台内的业务事件（文档创建、工作流完成、用户注册等），供插件订阅处理
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::debug;
+use uuid::Uuid;
+
+/// 系统事件类型
+pub mod event_types {
+    /// 文档已创建
+    pub const DOCUMENT_CREATED: &str = "document.created";
+    /// 文档已删除
+    pub const DOCUMENT_DELETED: &str = "document.deleted";
+    /// 工作流执行因并发组已满进入排队
+    pub const WORKFLOW_QUEUED: &str = "workflow.queued";
+    /// 工作流开始执行
+    pub const WORKFLOW_STARTED: &str = "workflow.started";
+    /// 工作流执行完成
+    pub const WORKFLOW_COMPLETED: &str = "workflow.completed";
+    /// 工作流执行失败
+    pub const WORKFLOW_FAILED: &str = "workflow.failed";
+    /// 工作流执行已取消
+    pub const WORKFLOW_CANCELLED: &str = "workflow.cancelled";
+    /// 工作流收到外部回调
+    pub const WORKFLOW_CALLBACK_RECEIVED: &str = "workflow.callback_received";
+    /// 工作流等待外部回调超时
+    pub const WORKFLOW_CALLBACK_TIMED_OUT: &str = "workflow.callback_timed_out";
+    /// 工作流已发布
+    pub const WORKFLOW_PUBLISHED: &str = "workflow.published";
+    /// 智能体已创建
+    pub const AGENT_CREATED: &str = "agent.created";
+    /// 智能体配置已变更
+    pub const AGENT_UPDATED: &str = "agent.updated";
+    /// 用户已注册
+    pub const USER_REGISTERED: &str = "user.registered";
+    /// 成员已被邀请加入租户
+    pub const MEMBER_INVITED: &str = "member.invited";
+}
+
+/// 事件总线默认缓冲容量
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// 全局事件总线
+static GLOBAL_EVENT_BUS: Lazy<SystemEventBus> = Lazy::new(|| SystemEventBus::new(DEFAULT_CAPACITY));
+
+/// 系统事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemEvent {
+    /// 事件 ID
+    pub event_id: Uuid,
+    /// 事件类型
+    pub event_type: String,
+    /// 所属租户
+    pub tenant_id: Option<Uuid>,
+    /// 触发事件的用户
+    #[serde(default)]
+    pub actor_id: Option<Uuid>,
+    /// 事件数据
+    pub payload: serde_json::Value,
+    /// 发生时间
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl SystemEvent {
+    /// 创建新的系统事件
+    pub fn new(event_type: impl Into<String>, tenant_id: Option<Uuid>, payload: serde_json::Value) -> Self {
+        Self {
+            event_id: Uuid::new_v4(),
+            event_type: event_type.into(),
+            tenant_id,
+            actor_id: None,
+            payload,
+            occurred_at: Utc::now(),
+        }
+    }
+
+    /// 设置触发事件的用户
+    pub fn with_actor(mut self, actor_id: Option<Uuid>) -> Self {
+        self.actor_id = actor_id;
+        self
+    }
+}
+
+/// 系统事件总线
+pub struct SystemEventBus {
+    sender: broadcast::Sender<SystemEvent>,
+}
+
+impl SystemEventBus {
+    /// 创建新的事件总线
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// 获取全局事件总线
+    pub fn global() -> &'static SystemEventBus {
+        &GLOBAL_EVENT_BUS
+    }
+
+    /// 发布事件，返回接收该事件的订阅者数量
+    ///
+    /// 发布不会阻塞调用方；没有订阅者时事件被直接丢弃。
+    pub fn publish(&self, event: SystemEvent) -> usize {
+        let event_type = event.event_type.clone();
+        match self.sender.send(event) {
+            Ok(receivers) => receivers,
+            Err(_) => {
+                debug!(event_type = %event_type, "没有事件订阅者，事件被丢弃");
+                0
+            }
+        }
+    }
+
+    /// 订阅所有事件
+    pub fn subscribe(&self) -> broadcast::Receiver<SystemEvent> {
+        self.sender.subscribe()
+    }
+}
+
+/// 判断事件类型是否匹配订阅模式
+///
+/// 支持精确匹配、`*`（全部事件）以及 `document.*` 形式的前缀匹配。
+pub fn event_matches(pattern: &str, event_type: &str) -> bool {
+    if pattern == "*" || pattern == event_type {
+        return true;
+    }
+
+    match pattern.strip_suffix(".*") {
+        Some(prefix) => event_type
+            .strip_prefix(prefix)
+            .map(|rest| rest.starts_with('.'))
+            .unwrap_or(false),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_matches() {
+        assert!(event_matches("*", event_types::USER_REGISTERED));
+        assert!(event_matches("document.created", event_types::DOCUMENT_CREATED));
+        assert!(event_matches("workflow.*", event_types::WORKFLOW_COMPLETED));
+        assert!(!event_matches("workflow.*", event_types::DOCUMENT_CREATED));
+        assert!(!event_matches("doc.*", event_types::DOCUMENT_CREATED));
+    }
+
+    #[tokio::test]
+    async fn test_publish_subscribe() {
+        let bus = SystemEventBus::new(8);
+        assert_eq!(bus.publish(SystemEvent::new(event_types::USER_REGISTERED, None, serde_json::Value::Null)), 0);
+
+        let mut receiver = bus.subscribe();
+        bus.publish(SystemEvent::new(event_types::DOCUMENT_CREATED, None, serde_json::json!({ "id": 1 })));
+
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.event_type, event_types::DOCUMENT_CREATED);
+    }
+}
@@ -0,0 +1,574 @@
+// 进程外插件宿主
+// 以独立子进程运行插件，通过 stdio JSON-RPC 或 gRPC 通信，插件崩溃不会影响 API 服务
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, ChildStderr, ChildStdout, Command};
+use tokio::sync::Mutex;
+use tracing::{info, warn, debug};
+
+use crate::errors::AiStudioError;
+use crate::plugins::config_schema;
+use crate::plugins::external_rpc::{ExternalTransport, GrpcTransport, StdioTransport};
+use crate::plugins::plugin_interface::{
+    Plugin, PluginConfig, PluginContext, PluginFactory, PluginHealth, PluginMetadata, PluginStatus,
+};
+
+/// 握手环境变量名
+///
+/// 插件进程应校验该变量，避免被用户直接执行。
+pub const MAGIC_COOKIE_KEY: &str = "AIONIX_PLUGIN_MAGIC_COOKIE";
+
+/// 握手环境变量值
+pub const MAGIC_COOKIE_VALUE: &str = "c5f1d2a6e0b84f7c9a3e6d1b2f4a8c07";
+
+/// 进程外插件协议版本
+pub const EXTERNAL_PROTOCOL_VERSION: u32 = 1;
+
+/// 进程外插件通信协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalProtocol {
+    /// 按行分隔的 JSON-RPC 2.0（stdin/stdout）
+    #[default]
+    Stdio,
+    /// gRPC（插件启动后在 stdout 输出握手行）
+    Grpc,
+}
+
+/// 进程外插件清单（`*.json`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalPluginManifest {
+    /// 插件元数据
+    pub metadata: PluginMetadata,
+    /// 可执行文件（相对路径基于清单所在目录）
+    pub command: String,
+    /// 启动参数
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// 额外环境变量
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// 通信协议
+    #[serde(default)]
+    pub protocol: ExternalProtocol,
+    /// 配置模式
+    #[serde(default)]
+    pub config_schema: Value,
+    /// 单次调用超时时间（秒）
+    #[serde(default = "default_call_timeout")]
+    pub call_timeout_seconds: u64,
+    /// 握手超时时间（秒）
+    #[serde(default = "default_handshake_timeout")]
+    pub handshake_timeout_seconds: u64,
+    /// 进程崩溃后最大自动拉起次数
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+}
+
+fn default_call_timeout() -> u64 {
+    30
+}
+
+fn default_handshake_timeout() -> u64 {
+    10
+}
+
+fn default_max_restarts() -> u32 {
+    3
+}
+
+impl ExternalPluginManifest {
+    /// 从清单文件读取
+    pub async fn from_file(path: &Path) -> Result<Self, AiStudioError> {
+        let content = tokio::fs::read_to_string(path).await
+            .map_err(|e| AiStudioError::internal(format!("读取插件清单失败: {}", e)))?;
+        let manifest: Self = serde_json::from_str(&content)
+            .map_err(|e| AiStudioError::validation("manifest", format!("插件清单格式无效: {}", e)))?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// 校验清单
+    pub fn validate(&self) -> Result<(), AiStudioError> {
+        if self.metadata.id.trim().is_empty() {
+            return Err(AiStudioError::validation("metadata.id", "插件 ID 不能为空"));
+        }
+        if self.command.trim().is_empty() {
+            return Err(AiStudioError::validation("command", "插件启动命令不能为空"));
+        }
+        if self.call_timeout_seconds == 0 || self.handshake_timeout_seconds == 0 {
+            return Err(AiStudioError::validation("timeout", "超时时间必须大于 0"));
+        }
+        Ok(())
+    }
+}
+
+/// gRPC 插件握手信息
+///
+/// 格式：`核心协议版本|应用协议版本|网络类型|地址|协议`，如 `1|1|tcp|127.0.0.1:50051|grpc`。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Handshake {
+    /// 应用协议版本
+    pub app_version: u32,
+    /// 监听地址
+    pub address: String,
+}
+
+impl Handshake {
+    /// 解析插件输出的握手行
+    pub fn parse(line: &str) -> Result<Self, AiStudioError> {
+        let parts: Vec<&str> = line.trim().split('|').collect();
+        if parts.len() != 5 {
+            return Err(AiStudioError::validation("handshake", format!("握手格式无效: {}", line.trim())));
+        }
+
+        let core_version: u32 = parts[0].parse()
+            .map_err(|_| AiStudioError::validation("handshake", "核心协议版本无效"))?;
+        if core_version != EXTERNAL_PROTOCOL_VERSION {
+            return Err(AiStudioError::validation("handshake", format!("不支持的核心协议版本: {}", core_version)));
+        }
+
+        let app_version: u32 = parts[1].parse()
+            .map_err(|_| AiStudioError::validation("handshake", "应用协议版本无效"))?;
+        if parts[2] != "tcp" {
+            return Err(AiStudioError::validation("handshake", format!("不支持的网络类型: {}", parts[2])));
+        }
+        if parts[4] != "grpc" {
+            return Err(AiStudioError::validation("handshake", format!("不支持的协议: {}", parts[4])));
+        }
+
+        Ok(Self {
+            app_version,
+            address: parts[3].to_string(),
+        })
+    }
+}
+
+/// 进程外插件工厂
+pub struct ExternalPluginFactory {
+    manifest: ExternalPluginManifest,
+    working_dir: PathBuf,
+}
+
+impl ExternalPluginFactory {
+    /// 由清单文件创建工厂
+    pub async fn from_manifest(path: &Path) -> Result<Self, AiStudioError> {
+        let manifest = ExternalPluginManifest::from_file(path).await?;
+        let working_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(Self { manifest, working_dir })
+    }
+}
+
+impl PluginFactory for ExternalPluginFactory {
+    fn create_plugin(&self) -> Result<Box<dyn Plugin>, AiStudioError> {
+        Ok(Box::new(ExternalPlugin::new(self.manifest.clone(), self.working_dir.clone())))
+    }
+
+    fn metadata(&self) -> PluginMetadata {
+        self.manifest.metadata.clone()
+    }
+
+    fn validate_compatibility(&self, _system_version: &str) -> Result<(), AiStudioError> {
+        Ok(())
+    }
+}
+
+/// 运行中的插件进程
+struct ExternalProcess {
+    child: Child,
+    transport: Arc<dyn ExternalTransport>,
+    started_at: Instant,
+}
+
+/// 进程外插件
+///
+/// 进程在初始化时拉起，崩溃后在下一次调用时按 `max_restarts` 自动重新拉起。
+pub struct ExternalPlugin {
+    manifest: ExternalPluginManifest,
+    working_dir: PathBuf,
+    status: PluginStatus,
+    config: Option<PluginConfig>,
+    process: Mutex<Option<ExternalProcess>>,
+    restarts: AtomicU32,
+}
+
+impl ExternalPlugin {
+    /// 创建进程外插件
+    pub fn new(manifest: ExternalPluginManifest, working_dir: PathBuf) -> Self {
+        Self {
+            manifest,
+            working_dir,
+            status: PluginStatus::Uninitialized,
+            config: None,
+            process: Mutex::new(None),
+            restarts: AtomicU32::new(0),
+        }
+    }
+
+    fn plugin_id(&self) -> &str {
+        &self.manifest.metadata.id
+    }
+
+    fn call_timeout(&self) -> Duration {
+        Duration::from_secs(self.manifest.call_timeout_seconds)
+    }
+
+    /// 获取可用的传输，进程已退出时尝试重新拉起
+    async fn transport(&self) -> Result<Arc<dyn ExternalTransport>, AiStudioError> {
+        let mut process = self.process.lock().await;
+
+        if let Some(running) = process.as_mut() {
+            if running.child.try_wait()?.is_none() && !running.transport.is_closed() {
+                return Ok(running.transport.clone());
+            }
+            warn!("插件进程已退出: {}", self.plugin_id());
+
+            // 超过重启次数后保留已退出的进程，后续调用持续失败直到插件被重新初始化
+            let restarts = self.restarts.fetch_add(1, Ordering::SeqCst) + 1;
+            if restarts > self.manifest.max_restarts {
+                return Err(AiStudioError::internal(format!(
+                    "插件进程已退出且超过最大重启次数: {}", self.plugin_id()
+                )));
+            }
+            *process = None;
+            info!("重新拉起插件进程: {} (第 {} 次)", self.plugin_id(), restarts);
+        }
+
+        let spawned = self.spawn().await?;
+        let transport = spawned.transport.clone();
+        *process = Some(spawned);
+        drop(process);
+
+        // 新进程需要重放初始化与启动
+        if let Some(config) = &self.config {
+            transport.call("initialize", serde_json::json!({ "config": config }), self.call_timeout()).await?;
+        }
+        if self.status == PluginStatus::Running {
+            transport.call("start", Value::Null, self.call_timeout()).await?;
+        }
+
+        Ok(transport)
+    }
+
+    /// 拉起插件进程并完成握手
+    async fn spawn(&self) -> Result<ExternalProcess, AiStudioError> {
+        let command = if Path::new(&self.manifest.command).is_absolute() {
+            PathBuf::from(&self.manifest.command)
+        } else {
+            self.working_dir.join(&self.manifest.command)
+        };
+
+        let mut cmd = Command::new(&command);
+        cmd.args(&self.manifest.args)
+            .current_dir(&self.working_dir)
+            .envs(&self.manifest.env)
+            .env(MAGIC_COOKIE_KEY, MAGIC_COOKIE_VALUE)
+            .env("AIONIX_PLUGIN_PROTOCOL", match self.manifest.protocol {
+                ExternalProtocol::Stdio => "stdio",
+                ExternalProtocol::Grpc => "grpc",
+            })
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(config) = &self.config {
+            cmd.envs(&config.environment);
+        }
+
+        let mut child = cmd.spawn()
+            .map_err(|e| AiStudioError::internal(format!("启动插件进程失败: {} - {}", command.display(), e)))?;
+
+        let stdin = child.stdin.take().ok_or_else(|| AiStudioError::internal("无法获取插件 stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| AiStudioError::internal("无法获取插件 stdout"))?;
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(forward_stderr(self.plugin_id().to_string(), stderr));
+        }
+
+        let transport: Arc<dyn ExternalTransport> = match self.manifest.protocol {
+            ExternalProtocol::Stdio => Arc::new(StdioTransport::new(self.plugin_id(), stdin, stdout)),
+            ExternalProtocol::Grpc => {
+                let timeout = Duration::from_secs(self.manifest.handshake_timeout_seconds);
+                let mut reader = BufReader::new(stdout);
+                let mut line = String::new();
+
+                let read = tokio::time::timeout(timeout, reader.read_line(&mut line)).await
+                    .map_err(|_| AiStudioError::timeout(format!("插件 {} 握手", self.plugin_id())))??;
+                if read == 0 {
+                    return Err(AiStudioError::internal(format!("插件进程在握手前退出: {}", self.plugin_id())));
+                }
+
+                let handshake = Handshake::parse(&line)?;
+                debug!("插件握手成功: {} -> {}", self.plugin_id(), handshake.address);
+                tokio::spawn(drain_stdout(self.plugin_id().to_string(), reader));
+
+                Arc::new(GrpcTransport::connect(self.plugin_id(), &handshake.address, timeout).await?)
+            }
+        };
+
+        info!("插件进程已启动: {} (pid={:?})", self.plugin_id(), child.id());
+
+        Ok(ExternalProcess {
+            child,
+            transport,
+            started_at: Instant::now(),
+        })
+    }
+
+    /// 结束插件进程
+    async fn kill(&self) {
+        if let Some(mut process) = self.process.lock().await.take() {
+            if let Err(e) = process.child.kill().await {
+                warn!("结束插件进程失败: {} - {}", self.plugin_id(), e);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for ExternalPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        self.manifest.metadata.clone()
+    }
+
+    async fn initialize(&mut self, config: PluginConfig) -> Result<(), AiStudioError> {
+        self.status = PluginStatus::Initializing;
+        self.kill().await;
+        self.config = Some(config);
+        self.restarts.store(0, Ordering::SeqCst);
+
+        // 首次获取传输时会拉起进程并发送 initialize
+        match self.transport().await {
+            Ok(_) => {
+                self.status = PluginStatus::Initialized;
+                Ok(())
+            }
+            Err(e) => {
+                self.status = PluginStatus::Error;
+                self.kill().await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn start(&mut self) -> Result<(), AiStudioError> {
+        self.status = PluginStatus::Starting;
+        let result = match self.transport().await {
+            Ok(transport) => transport.call("start", Value::Null, self.call_timeout()).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+
+        self.status = if result.is_ok() { PluginStatus::Running } else { PluginStatus::Error };
+        result
+    }
+
+    async fn stop(&mut self) -> Result<(), AiStudioError> {
+        self.status = PluginStatus::Stopping;
+        let transport = self.process.lock().await.as_ref().map(|p| p.transport.clone());
+        if let Some(transport) = transport {
+            if let Err(e) = transport.call("stop", Value::Null, self.call_timeout()).await {
+                warn!("插件停止请求失败: {} - {}", self.plugin_id(), e);
+            }
+        }
+
+        self.status = PluginStatus::Stopped;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), AiStudioError> {
+        self.status = PluginStatus::Unloading;
+        let transport = self.process.lock().await.as_ref().map(|p| p.transport.clone());
+        if let Some(transport) = transport {
+            let _ = transport.call("shutdown", Value::Null, self.call_timeout()).await;
+        }
+        self.kill().await;
+
+        self.status = PluginStatus::Unloaded;
+        Ok(())
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn handle_call(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        context: &PluginContext,
+    ) -> Result<Value, AiStudioError> {
+        let transport = self.transport().await?;
+
+        transport.call("call", serde_json::json!({
+            "method": method,
+            "params": params,
+            "context": {
+                "tenant_id": context.tenant_id,
+                "user_id": context.user_id,
+                "session_id": context.session_id,
+                "request_id": context.request_id,
+                "variables": context.variables,
+                "plugin_config": context.plugin_config,
+                "timestamp": context.timestamp,
+            },
+        }), self.call_timeout()).await
+    }
+
+    async fn health_check(&self) -> Result<PluginHealth, AiStudioError> {
+        let started = Instant::now();
+        let mut details = HashMap::new();
+        details.insert("restarts".to_string(), Value::from(self.restarts.load(Ordering::SeqCst)));
+
+        let transport = {
+            let mut process = self.process.lock().await;
+            match process.as_mut() {
+                Some(running) => {
+                    details.insert("pid".to_string(), serde_json::json!(running.child.id()));
+                    details.insert("uptime_seconds".to_string(), Value::from(running.started_at.elapsed().as_secs()));
+
+                    match running.child.try_wait()? {
+                        Some(exit) => Err(format!("插件进程已退出: {}", exit)),
+                        None if running.transport.is_closed() => Err("插件进程通信已断开".to_string()),
+                        None => Ok(running.transport.clone()),
+                    }
+                }
+                None => Err("插件进程未运行".to_string()),
+            }
+        };
+
+        let (healthy, message) = match transport {
+            Ok(transport) => match transport.call("health", Value::Null, self.call_timeout()).await {
+                Ok(result) => (
+                    result.get("healthy").and_then(Value::as_bool).unwrap_or(true),
+                    result.get("message").and_then(Value::as_str).unwrap_or("正常").to_string(),
+                ),
+                Err(e) => (false, e.to_string()),
+            },
+            Err(message) => (false, message),
+        };
+
+        Ok(PluginHealth {
+            healthy,
+            message,
+            details,
+            checked_at: Utc::now(),
+            response_time_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    fn config_schema(&self) -> Value {
+        self.manifest.config_schema.clone()
+    }
+
+    fn validate_config(&self, config: &PluginConfig) -> Result<(), AiStudioError> {
+        if self.manifest.config_schema.is_null() {
+            return Ok(());
+        }
+
+        let mut parameters: serde_json::Map<String, Value> = config.parameters.clone().into_iter().collect();
+        config_schema::apply_defaults(&self.manifest.config_schema, &mut parameters);
+        config_schema::validate_against_schema(&self.manifest.config_schema, &Value::Object(parameters))
+    }
+}
+
+/// 将插件 stderr 转发到日志
+async fn forward_stderr(plugin_id: String, stderr: ChildStderr) {
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        warn!(plugin_id = %plugin_id, "{}", line);
+    }
+}
+
+/// 握手后继续消费 gRPC 插件的 stdout，避免管道写满阻塞插件
+async fn drain_stdout(plugin_id: String, reader: BufReader<ChildStdout>) {
+    let mut lines = reader.lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        debug!(plugin_id = %plugin_id, "{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(extra: Value) -> ExternalPluginManifest {
+        let mut value = serde_json::json!({
+            "metadata": {
+                "id": "echo",
+                "name": "Echo",
+                "version": "1.0.0",
+                "description": "回显插件",
+                "author": "Aionix Team",
+                "license": "MIT",
+                "homepage": null,
+                "repository": null,
+                "plugin_type": "tool",
+                "api_version": "1.0",
+                "min_system_version": "1.0.0",
+                "dependencies": [],
+                "permissions": [],
+                "tags": [],
+                "icon": null,
+                "created_at": "2024-01-01T00:00:00Z"
+            },
+            "command": "./echo-plugin"
+        });
+        if let (Some(target), Some(extra)) = (value.as_object_mut(), extra.as_object()) {
+            target.extend(extra.clone());
+        }
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_handshake() {
+        let handshake = Handshake::parse("1|2|tcp|127.0.0.1:50051|grpc\n").unwrap();
+        assert_eq!(handshake.app_version, 2);
+        assert_eq!(handshake.address, "127.0.0.1:50051");
+
+        assert!(Handshake::parse("2|1|tcp|127.0.0.1:50051|grpc").is_err());
+        assert!(Handshake::parse("1|1|unix|/tmp/plugin.sock|grpc").is_err());
+        assert!(Handshake::parse("hello").is_err());
+    }
+
+    #[test]
+    fn test_manifest_defaults() {
+        let manifest = manifest(Value::Null);
+
+        assert_eq!(manifest.protocol, ExternalProtocol::Stdio);
+        assert_eq!(manifest.call_timeout_seconds, 30);
+        assert_eq!(manifest.max_restarts, 3);
+        assert!(manifest.validate().is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_plugin_crash_is_isolated() {
+        let dir = tempfile::tempdir().unwrap();
+        // 读取第一条请求后立即退出，模拟插件崩溃
+        std::fs::write(dir.path().join("crash.sh"), "read line\nexit 1\n").unwrap();
+
+        let manifest = manifest(serde_json::json!({
+            "command": "/bin/sh",
+            "args": ["crash.sh"],
+            "call_timeout_seconds": 2,
+            "max_restarts": 0
+        }));
+
+        let plugin = ExternalPlugin::new(manifest, dir.path().to_path_buf());
+        let transport = plugin.transport().await.unwrap();
+        assert!(transport.call("ping", Value::Null, Duration::from_secs(2)).await.is_err());
+
+        let health = plugin.health_check().await.unwrap();
+        assert!(!health.healthy);
+        assert!(plugin.transport().await.is_err());
+    }
+}
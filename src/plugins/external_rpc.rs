@@ -0,0 +1,288 @@
+// 进程外插件通信
+// 实现 stdio JSON-RPC 与 gRPC 两种传输方式
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use async_trait::async_trait;
+use bytes::{Buf, BufMut};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{ChildStdin, ChildStdout};
+use tokio::sync::{oneshot, Mutex};
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::{Channel, Endpoint};
+use tracing::{debug, info, warn, error};
+
+use crate::errors::AiStudioError;
+
+/// gRPC 服务方法路径
+///
+/// 插件需实现 `aionix.plugin.v1.Plugin/Call` 一元方法，请求与响应均为 JSON 编码。
+pub const GRPC_CALL_PATH: &str = "/aionix.plugin.v1.Plugin/Call";
+
+/// 进程外插件传输层
+#[async_trait]
+pub trait ExternalTransport: Send + Sync {
+    /// 发起一次调用
+    async fn call(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, AiStudioError>;
+
+    /// 连接是否已断开
+    fn is_closed(&self) -> bool;
+}
+
+/// JSON-RPC 请求
+#[derive(Debug, Serialize)]
+struct JsonRpcRequest<'a> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: Value,
+}
+
+/// JSON-RPC 消息（响应或插件发出的通知）
+#[derive(Debug, Deserialize)]
+struct JsonRpcMessage {
+    #[serde(default)]
+    id: Option<u64>,
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+/// JSON-RPC 错误
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+type PendingCalls = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// stdio JSON-RPC 传输
+///
+/// 每行一条 JSON-RPC 2.0 消息：主进程写入插件 stdin，插件从 stdout 返回响应。
+pub struct StdioTransport {
+    plugin_id: String,
+    stdin: Mutex<ChildStdin>,
+    pending: PendingCalls,
+    next_id: AtomicU64,
+    closed: Arc<AtomicBool>,
+}
+
+impl StdioTransport {
+    /// 创建传输并启动响应读取任务
+    pub fn new(plugin_id: &str, stdin: ChildStdin, stdout: ChildStdout) -> Self {
+        let pending: PendingCalls = Arc::new(Mutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        tokio::spawn(read_responses(
+            plugin_id.to_string(),
+            BufReader::new(stdout),
+            pending.clone(),
+            closed.clone(),
+        ));
+
+        Self {
+            plugin_id: plugin_id.to_string(),
+            stdin: Mutex::new(stdin),
+            pending,
+            next_id: AtomicU64::new(1),
+            closed,
+        }
+    }
+}
+
+#[async_trait]
+impl ExternalTransport for StdioTransport {
+    async fn call(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, AiStudioError> {
+        if self.is_closed() {
+            return Err(AiStudioError::internal(format!("插件进程已退出: {}", self.plugin_id)));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut line = serde_json::to_vec(&JsonRpcRequest { jsonrpc: "2.0", id, method, params })?;
+        line.push(b'\n');
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, tx);
+
+        let written = {
+            let mut stdin = self.stdin.lock().await;
+            match stdin.write_all(&line).await {
+                Ok(()) => stdin.flush().await,
+                Err(e) => Err(e),
+            }
+        };
+        if let Err(e) = written {
+            self.pending.lock().await.remove(&id);
+            return Err(AiStudioError::internal(format!("写入插件进程失败: {}", e)));
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(message))) => Err(AiStudioError::internal(format!("插件返回错误: {}", message))),
+            Ok(Err(_)) => Err(AiStudioError::internal(format!("插件进程已退出: {}", self.plugin_id))),
+            Err(_) => {
+                self.pending.lock().await.remove(&id);
+                Err(AiStudioError::timeout(format!("插件 {} 调用 {}", self.plugin_id, method)))
+            }
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+}
+
+/// 读取插件 stdout 并分发响应
+async fn read_responses(
+    plugin_id: String,
+    mut reader: BufReader<ChildStdout>,
+    pending: PendingCalls,
+    closed: Arc<AtomicBool>,
+) {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line).await {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                error!("读取插件输出失败: {} - {}", plugin_id, e);
+                break;
+            }
+        }
+
+        let message: JsonRpcMessage = match serde_json::from_str(line.trim()) {
+            Ok(message) => message,
+            Err(_) => {
+                debug!("[{}] {}", plugin_id, line.trim_end());
+                continue;
+            }
+        };
+
+        match message.id {
+            Some(id) => {
+                let result = match message.error {
+                    Some(err) => Err(format!("{} ({})", err.message, err.code)),
+                    None => Ok(message.result.unwrap_or(Value::Null)),
+                };
+                if let Some(tx) = pending.lock().await.remove(&id) {
+                    let _ = tx.send(result);
+                }
+            }
+            // 插件主动发出的日志通知
+            None if message.method.as_deref() == Some("log") => {
+                info!(plugin_id = %plugin_id, params = %message.params, "插件日志");
+            }
+            None => debug!("忽略插件通知: {} - {:?}", plugin_id, message.method),
+        }
+    }
+
+    // 进程退出后让所有等待中的调用立即失败
+    closed.store(true, Ordering::Release);
+    pending.lock().await.clear();
+    warn!("插件进程输出已关闭: {}", plugin_id);
+}
+
+/// gRPC 传输
+pub struct GrpcTransport {
+    plugin_id: String,
+    channel: Channel,
+}
+
+impl GrpcTransport {
+    /// 连接插件握手时公布的地址
+    pub async fn connect(plugin_id: &str, address: &str, timeout: Duration) -> Result<Self, AiStudioError> {
+        let channel = Endpoint::from_shared(format!("http://{}", address))
+            .map_err(|e| AiStudioError::validation("address", format!("插件地址无效: {}", e)))?
+            .connect_timeout(timeout)
+            .connect()
+            .await
+            .map_err(|e| AiStudioError::internal(format!("连接插件 gRPC 服务失败: {}", e)))?;
+
+        Ok(Self {
+            plugin_id: plugin_id.to_string(),
+            channel,
+        })
+    }
+}
+
+#[async_trait]
+impl ExternalTransport for GrpcTransport {
+    async fn call(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, AiStudioError> {
+        let mut client = tonic::client::Grpc::new(self.channel.clone());
+        client.ready().await
+            .map_err(|e| AiStudioError::internal(format!("插件 gRPC 服务不可用: {}", e)))?;
+
+        let mut request = tonic::Request::new(serde_json::json!({
+            "method": method,
+            "params": params,
+        }));
+        request.set_timeout(timeout);
+
+        match client.unary(request, PathAndQuery::from_static(GRPC_CALL_PATH), JsonCodec).await {
+            Ok(response) => Ok(response.into_inner()),
+            Err(status) if status.code() == tonic::Code::DeadlineExceeded => {
+                Err(AiStudioError::timeout(format!("插件 {} 调用 {}", self.plugin_id, method)))
+            }
+            Err(status) => Err(AiStudioError::internal(format!("插件返回错误: {}", status.message()))),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        false
+    }
+}
+
+/// JSON 编解码器，免去插件方对 protobuf 定义的依赖
+#[derive(Debug, Clone, Copy, Default)]
+struct JsonCodec;
+
+impl Codec for JsonCodec {
+    type Encode = Value;
+    type Decode = Value;
+    type Encoder = JsonCodec;
+    type Decoder = JsonCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        JsonCodec
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        JsonCodec
+    }
+}
+
+impl Encoder for JsonCodec {
+    type Item = Value;
+    type Error = tonic::Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        serde_json::to_writer(dst.writer(), &item)
+            .map_err(|e| tonic::Status::internal(format!("编码请求失败: {}", e)))
+    }
+}
+
+impl Decoder for JsonCodec {
+    type Item = Value;
+    type Error = tonic::Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        if !src.has_remaining() {
+            return Ok(Some(Value::Null));
+        }
+        serde_json::from_reader(src.reader())
+            .map(Some)
+            .map_err(|e| tonic::Status::internal(format!("解码响应失败: {}", e)))
+    }
+}
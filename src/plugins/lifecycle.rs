@@ -539,6 +539,57 @@ impl PluginLifecycleManager {
         }
     }
 
+    /// 调用插件方法
+    pub async fn call_plugin(
+        &self,
+        plugin_id: &str,
+        method: &str,
+        params: HashMap<String, serde_json::Value>,
+        context: &PluginContext,
+    ) -> Result<serde_json::Value, AiStudioError> {
+        let call_result = {
+            let plugins = self.plugins.read().await;
+            let instance = plugins.get(plugin_id)
+                .ok_or_else(|| AiStudioError::not_found("插件不存在"))?;
+
+            if instance.status != PluginStatus::Running {
+                return Err(AiStudioError::validation("status", "插件未运行"));
+            }
+
+            instance.plugin.handle_call(method, params, context).await
+        };
+
+        match call_result {
+            Ok(result) => {
+                self.emit_event(plugin_id, PluginEventType::Called,
+                               serde_json::json!({ "method": method })).await;
+                Ok(result)
+            }
+            Err(e) => {
+                self.handle_plugin_error(plugin_id, PluginErrorType::ExecutionError, &e.to_string()).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// 获取插件的安装配置
+    pub async fn get_plugin_config(&self, plugin_id: &str) -> Result<PluginConfig, AiStudioError> {
+        let plugins = self.plugins.read().await;
+        let instance = plugins.get(plugin_id)
+            .ok_or_else(|| AiStudioError::not_found("插件不存在"))?;
+
+        Ok(instance.config.clone())
+    }
+
+    /// 获取插件配置模式
+    pub async fn get_config_schema(&self, plugin_id: &str) -> Result<serde_json::Value, AiStudioError> {
+        let plugins = self.plugins.read().await;
+        let instance = plugins.get(plugin_id)
+            .ok_or_else(|| AiStudioError::not_found("插件不存在"))?;
+
+        Ok(instance.plugin.config_schema())
+    }
+
     /// 获取插件实例信息
     pub async fn get_plugin_info(&self, plugin_id: &str) -> Result<PluginInstanceInfo, AiStudioError> {
         let plugins = self.plugins.read().await;
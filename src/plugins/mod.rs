@@ -6,9 +6,19 @@ pub mod plugin_interface;
 pub mod plugin_loader;
 pub mod plugin_registry;
 pub mod lifecycle;
+pub mod event_bus;
+pub mod config_schema;
+pub mod resource_monitor;
+pub mod external_rpc;
+pub mod external_host;
+pub mod plugin_signing;
 
 pub use plugin_manager::*;
 pub use plugin_interface::*;
 pub use plugin_loader::*;
 pub use plugin_registry::*;
-pub use lifecycle::*;
\ No newline at end of file
+pub use lifecycle::*;
+pub use event_bus::*;
+pub use resource_monitor::*;
+pub use external_host::*;
+pub use plugin_signing::*;
\ No newline at end of file
@@ -9,6 +9,8 @@ use tracing::{info, warn, error, debug};
 use tokio::fs;
 
 use crate::plugins::plugin_interface::{PluginFactory, PluginMetadata, Plugin};
+use crate::plugins::external_host::ExternalPluginFactory;
+use crate::plugins::plugin_signing::{PluginSignatureFile, SignerIdentity, TrustPolicy, TrustedKey};
 use crate::errors::AiStudioError;
 
 /// 插件加载器
@@ -32,6 +34,8 @@ pub struct LoaderConfig {
     pub load_timeout_seconds: u64,
     /// 最大插件大小（MB）
     pub max_plugin_size_mb: u64,
+    /// 插件签名信任策略
+    pub trust_policy: TrustPolicy,
 }
 
 impl Default for LoaderConfig {
@@ -41,10 +45,12 @@ impl Default for LoaderConfig {
                 PluginFormat::Native,
                 PluginFormat::Wasm,
                 PluginFormat::Script,
+                PluginFormat::External,
             ],
             enable_verification: true,
             load_timeout_seconds: 30,
             max_plugin_size_mb: 100,
+            trust_policy: TrustPolicy::default(),
         }
     }
 }
@@ -60,6 +66,8 @@ pub enum PluginFormat {
     Script,
     /// 容器插件
     Container,
+    /// 进程外插件（JSON 清单描述的独立进程，经 stdio 或 gRPC 通信）
+    External,
 }
 
 /// 插件包信息
@@ -106,6 +114,18 @@ impl PluginLoader {
     
     /// 加载插件
     pub async fn load_plugin(&self, source: &str) -> Result<Arc<dyn PluginFactory>, AiStudioError> {
+        self.load_verified_plugin(source, None, &[]).await.map(|(factory, _)| factory)
+    }
+    
+    /// 加载插件并校验签名，返回签名者身份
+    ///
+    /// `tenant_keys` 为安装方租户登记的受信任密钥，与部署级密钥一同参与校验。
+    pub async fn load_verified_plugin(
+        &self,
+        source: &str,
+        tenant_id: Option<uuid::Uuid>,
+        tenant_keys: &[TrustedKey],
+    ) -> Result<(Arc<dyn PluginFactory>, Option<SignerIdentity>), AiStudioError> {
         info!("加载插件: {}", source);
         
         let start_time = std::time::Instant::now();
@@ -120,9 +140,11 @@ impl PluginLoader {
         let format = self.detect_plugin_format(&plugin_path).await?;
         
         // 验证插件
-        if self.config.enable_verification {
-            self.verify_plugin(&plugin_path, &format).await?;
-        }
+        let signer = if self.config.enable_verification {
+            self.verify_plugin(&plugin_path, &format, tenant_id, tenant_keys).await?
+        } else {
+            None
+        };
         
         // 加载插件工厂
         let factory = match format {
@@ -130,6 +152,7 @@ impl PluginLoader {
             PluginFormat::Wasm => self.load_wasm_plugin(&plugin_path).await?,
             PluginFormat::Script => self.load_script_plugin(&plugin_path).await?,
             PluginFormat::Container => self.load_container_plugin(&plugin_path).await?,
+            PluginFormat::External => self.load_external_plugin(&plugin_path).await?,
         };
         
         let load_time = start_time.elapsed().as_millis() as u64;
@@ -142,7 +165,7 @@ impl PluginLoader {
         
         info!("插件加载成功: {} ({}ms)", source, load_time);
         
-        Ok(factory)
+        Ok((factory, signer))
     }
     
     /// 卸载插件
@@ -255,6 +278,7 @@ impl PluginLoader {
             "so" | "dll" | "dylib" => PluginFormat::Native,
             "wasm" => PluginFormat::Wasm,
             "js" | "py" | "lua" => PluginFormat::Script,
+            "json" => PluginFormat::External,
             "tar" | "zip" => {
                 // 检查是否为容器插件
                 if self.is_container_plugin(path).await? {
@@ -280,17 +304,28 @@ impl PluginLoader {
         Ok(false)
     }
     
-    /// 验证插件
-    async fn verify_plugin(&self, path: &Path, format: &PluginFormat) -> Result<(), AiStudioError> {
+    /// 验证插件签名
+    async fn verify_plugin(
+        &self,
+        path: &Path,
+        format: &PluginFormat,
+        tenant_id: Option<uuid::Uuid>,
+        tenant_keys: &[TrustedKey],
+    ) -> Result<Option<SignerIdentity>, AiStudioError> {
         debug!("验证插件: {} ({:?})", path.display(), format);
         
-        // TODO: 实现插件验证逻辑
-        // 1. 检查文件完整性
-        // 2. 验证数字签名
-        // 3. 扫描恶意代码
-        // 4. 检查权限要求
+        let artifact = fs::read(path).await.map_err(|e| {
+            AiStudioError::internal(format!("读取插件文件失败: {}", e))
+        })?;
+        let signature = PluginSignatureFile::load_for(path).await?;
         
-        Ok(())
+        let signer = self.config.trust_policy.verify(&artifact, signature.as_ref(), tenant_id, tenant_keys)?;
+        match &signer {
+            Some(signer) => info!("插件签名校验通过: {} (key_id={}, signer={})", path.display(), signer.key_id, signer.name),
+            None => warn!("加载未签名插件: {}", path.display()),
+        }
+        
+        Ok(signer)
     }
     
     /// 加载原生插件
@@ -333,6 +368,14 @@ impl PluginLoader {
         Err(AiStudioError::internal("容器插件加载暂未实现"))
     }
     
+    /// 加载进程外插件
+    async fn load_external_plugin(&self, path: &Path) -> Result<Arc<dyn PluginFactory>, AiStudioError> {
+        debug!("加载进程外插件: {}", path.display());
+        
+        let factory = ExternalPluginFactory::from_manifest(path).await?;
+        Ok(Arc::new(factory))
+    }
+    
     /// 分析插件包
     async fn analyze_plugin_package(&self, path: &Path) -> Result<PluginPackage, AiStudioError> {
         let metadata = fs::metadata(path).await.map_err(|e| {
@@ -360,6 +403,8 @@ impl PluginLoader {
             permissions: Vec::new(),
             tags: Vec::new(),
             icon: None,
+            routes: Vec::new(),
+            event_subscriptions: Vec::new(),
             created_at: chrono::Utc::now(),
         };
         
@@ -460,6 +505,26 @@ mod tests {
         assert_eq!(format, PluginFormat::Wasm);
     }
     
+    #[tokio::test]
+    async fn test_unsigned_plugin_rejected_when_signature_required() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = LoaderConfig::default();
+        config.trust_policy.require_signature = true;
+        
+        let loader = PluginLoader::new(temp_dir.path().to_path_buf(), Some(config));
+        
+        let wasm_path = temp_dir.path().join("unsigned.wasm");
+        tokio::fs::write(&wasm_path, b"fake wasm content").await.unwrap();
+        
+        let result = loader.verify_plugin(&wasm_path, &PluginFormat::Wasm, None, &[]).await;
+        assert!(result.is_err());
+        
+        // 签名文件内容无效时同样拒绝
+        tokio::fs::write(temp_dir.path().join("unsigned.wasm.sig"), b"{}").await.unwrap();
+        let result = loader.verify_plugin(&wasm_path, &PluginFormat::Wasm, None, &[]).await;
+        assert!(result.is_err());
+    }
+    
     #[tokio::test]
     async fn test_plugin_size_check() {
         let temp_dir = TempDir::new().unwrap();
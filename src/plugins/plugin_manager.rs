@@ -8,16 +8,19 @@ use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn, error, debug};
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 
 use crate::plugins::{
     plugin_interface::{
         Plugin, PluginMetadata, PluginConfig, PluginStatus, PluginContext, PluginEvent, 
-        PluginEventType, PluginApi, PluginHook, PluginFactory, PluginPermission
+        PluginEventType, PluginApi, PluginHook, PluginFactory, PluginPermission, PluginRoute
     },
     lifecycle::{PluginLifecycleManager, LifecycleConfig, PluginInstanceInfo},
     plugin_registry::{PluginRegistry, RegistryConfig},
     plugin_loader::{PluginLoader, LoaderConfig},
+    event_bus::{SystemEvent, SystemEventBus, event_matches},
+    resource_monitor::{PluginResourceMonitor, PluginResourceMetrics, LimitDecision},
+    plugin_signing::{SignerIdentity, TrustPolicy, TrustedKey},
 };
 use crate::errors::AiStudioError;
 
@@ -33,6 +36,8 @@ pub struct PluginManager {
     plugin_api: Arc<dyn PluginApi>,
     /// 插件钩子
     hooks: Arc<RwLock<HashMap<String, Vec<Arc<dyn PluginHook>>>>>,
+    /// 资源监控器
+    resource_monitor: Arc<PluginResourceMonitor>,
     /// 管理器配置
     config: PluginManagerConfig,
 }
@@ -54,6 +59,12 @@ pub struct PluginManagerConfig {
     pub enable_plugin_verification: bool,
     /// 允许的插件权限
     pub allowed_permissions: Vec<PluginPermission>,
+    /// 事件投递失败后的最大重试次数
+    pub event_max_retries: u32,
+    /// 事件投递重试的初始间隔（毫秒），每次重试翻倍
+    pub event_retry_base_delay_ms: u64,
+    /// 插件签名信任策略
+    pub trust_policy: TrustPolicy,
 }
 
 impl Default for PluginManagerConfig {
@@ -69,7 +80,11 @@ impl Default for PluginManagerConfig {
                 PluginPermission::FileSystem,
                 PluginPermission::Network,
                 PluginPermission::UserData,
+                PluginPermission::HttpRoutes,
             ],
+            event_max_retries: 3,
+            event_retry_base_delay_ms: 500,
+            trust_policy: TrustPolicy::default(),
         }
     }
 }
@@ -83,6 +98,12 @@ pub struct InstallPluginRequest {
     pub config: Option<PluginConfig>,
     /// 是否自动启动
     pub auto_start: bool,
+    /// 安装方租户（由服务端填充）
+    #[serde(skip)]
+    pub tenant_id: Option<Uuid>,
+    /// 安装方租户登记的受信任密钥（由服务端填充）
+    #[serde(skip)]
+    pub tenant_trusted_keys: Vec<TrustedKey>,
 }
 
 /// 插件安装响应
@@ -131,6 +152,8 @@ pub struct PluginInfo {
     pub status: PluginStatus,
     /// 实例信息
     pub instance_info: PluginInstanceInfo,
+    /// 签名者身份
+    pub signer: Option<SignerIdentity>,
 }
 
 impl PluginManager {
@@ -150,7 +173,10 @@ impl PluginManager {
         // 创建插件加载器
         let loader = Arc::new(PluginLoader::new(
             config.plugins_directory.clone(),
-            Some(LoaderConfig::default()),
+            Some(LoaderConfig {
+                trust_policy: config.trust_policy.clone(),
+                ..LoaderConfig::default()
+            }),
         ));
         
         let enable_hot_reload = config.enable_hot_reload;
@@ -160,6 +186,7 @@ impl PluginManager {
             loader,
             plugin_api,
             hooks: Arc::new(RwLock::new(HashMap::new())),
+            resource_monitor: Arc::new(PluginResourceMonitor::new()),
             config,
         };
         
@@ -168,6 +195,9 @@ impl PluginManager {
             manager.start_plugin_scanner().await;
         }
         
+        // 启动系统事件分发
+        manager.start_event_dispatcher();
+        
         Ok(manager)
     }
     
@@ -190,8 +220,11 @@ impl PluginManager {
         }
         
         // 加载插件
-        let plugin_factory = match self.loader.load_plugin(&request.source).await {
-            Ok(factory) => factory,
+        let (plugin_factory, signer) = match self.loader
+            .load_verified_plugin(&request.source, request.tenant_id, &request.tenant_trusted_keys)
+            .await
+        {
+            Ok(loaded) => loaded,
             Err(e) => {
                 return Ok(InstallPluginResponse {
                     plugin_id: String::new(),
@@ -231,7 +264,16 @@ impl PluginManager {
         };
         
         // 注册插件
-        self.registry.register_plugin(metadata.clone()).await?;
+        self.registry.register_signed_plugin(metadata.clone(), signer.clone()).await?;
+        if let Some(signer) = &signer {
+            info!(
+                plugin_id = %plugin_id,
+                key_id = %signer.key_id,
+                signer = %signer.name,
+                artifact_sha256 = %signer.artifact_sha256,
+                "插件签名者已记录"
+            );
+        }
         
         // 配置插件
         let config = request.config.unwrap_or_else(|| PluginConfig {
@@ -282,6 +324,7 @@ impl PluginManager {
         
         // 从注册表移除
         self.registry.unregister_plugin(plugin_id).await?;
+        self.resource_monitor.remove(plugin_id).await;
         
         info!("插件卸载成功: {}", plugin_id);
         Ok(())
@@ -289,7 +332,9 @@ impl PluginManager {
     
     /// 启动插件
     pub async fn start_plugin(&self, plugin_id: &str) -> Result<(), AiStudioError> {
-        self.lifecycle_manager.start_plugin(plugin_id).await
+        self.lifecycle_manager.start_plugin(plugin_id).await?;
+        self.resource_monitor.clear_suspension(plugin_id).await;
+        Ok(())
     }
     
     /// 停止插件
@@ -299,7 +344,9 @@ impl PluginManager {
     
     /// 重启插件
     pub async fn restart_plugin(&self, plugin_id: &str) -> Result<(), AiStudioError> {
-        self.lifecycle_manager.restart_plugin(plugin_id).await
+        self.lifecycle_manager.restart_plugin(plugin_id).await?;
+        self.resource_monitor.clear_suspension(plugin_id).await;
+        Ok(())
     }
     
     /// 调用插件
@@ -312,21 +359,122 @@ impl PluginManager {
     ) -> Result<serde_json::Value, AiStudioError> {
         debug!("调用插件: {} - {}", plugin_id, method);
         
-        // 检查插件状态
-        let status = self.lifecycle_manager.get_plugin_status(plugin_id).await?;
-        if status != PluginStatus::Running {
-            return Err(AiStudioError::validation("status".to_string(), "插件未运行".to_string()));
+        let limits = self.lifecycle_manager.get_plugin_config(plugin_id).await?.resource_limits;
+        match self.resource_monitor.check_limits(plugin_id, &limits).await {
+            LimitDecision::Allow => {}
+            LimitDecision::Throttle(reason) => {
+                warn!("插件 {} 调用被限流: {}", plugin_id, reason);
+                return Err(AiStudioError::rate_limit(Some(1)));
+            }
+            LimitDecision::Suspend(reason) => {
+                self.suspend_plugin(plugin_id, &reason).await;
+                return Err(AiStudioError::forbidden(format!("插件已因资源超限被挂起: {}", reason)));
+            }
         }
         
-        // 执行插件调用
-        // TODO: 实现实际的插件调用逻辑
-        // 这里需要通过生命周期管理器获取插件实例并调用
+        let mut context = context;
+        context.plugin_config = self.resolve_plugin_config(plugin_id, context.tenant_id).await?;
+        
+        let started = std::time::Instant::now();
+        let result = match limits.max_execution_seconds {
+            Some(seconds) => tokio::time::timeout(
+                std::time::Duration::from_secs(seconds),
+                self.lifecycle_manager.call_plugin(plugin_id, method, params, &context),
+            )
+            .await
+            .unwrap_or_else(|_| Err(AiStudioError::timeout(format!("插件 {} 调用 {}", plugin_id, method)))),
+            None => self.lifecycle_manager.call_plugin(plugin_id, method, params, &context).await,
+        };
+        
+        let timed_out = matches!(result, Err(AiStudioError::Timeout { .. }));
+        self.resource_monitor
+            .record_call(plugin_id, started.elapsed(), result.is_ok(), timed_out)
+            .await;
+        
+        result
+    }
+    
+    /// 记录插件出站网络流量
+    pub async fn record_network_usage(&self, plugin_id: &str, bytes: u64) {
+        self.resource_monitor.record_network(plugin_id, bytes).await;
+    }
+    
+    /// 记录沙箱插件上报的内存使用
+    pub async fn record_memory_usage(&self, plugin_id: &str, memory_mb: u64) -> Result<(), AiStudioError> {
+        self.resource_monitor.record_memory(plugin_id, memory_mb).await;
+        
+        let limits = self.lifecycle_manager.get_plugin_config(plugin_id).await?.resource_limits;
+        if let LimitDecision::Suspend(reason) = self.resource_monitor.check_limits(plugin_id, &limits).await {
+            self.suspend_plugin(plugin_id, &reason).await;
+        }
+        
+        Ok(())
+    }
+    
+    /// 获取插件资源使用指标
+    pub async fn get_plugin_metrics(&self, plugin_id: &str) -> Result<PluginResourceMetrics, AiStudioError> {
+        let limits = self.lifecycle_manager.get_plugin_config(plugin_id).await?.resource_limits;
+        Ok(self.resource_monitor.get_metrics(plugin_id, limits).await)
+    }
+    
+    /// 因资源超限挂起插件
+    async fn suspend_plugin(&self, plugin_id: &str, reason: &str) {
+        if !matches!(self.lifecycle_manager.get_plugin_status(plugin_id).await, Ok(PluginStatus::Running)) {
+            return;
+        }
+        
+        warn!("插件 {} 资源超限，挂起插件: {}", plugin_id, reason);
+        
+        if let Err(e) = self.lifecycle_manager.stop_plugin(plugin_id).await {
+            error!("挂起插件 {} 失败: {}", plugin_id, e);
+        }
+    }
+    
+    /// 获取插件配置模式
+    pub async fn get_config_schema(&self, plugin_id: &str) -> Result<serde_json::Value, AiStudioError> {
+        self.lifecycle_manager.get_config_schema(plugin_id).await
+    }
+    
+    /// 合并安装配置与租户配置，租户配置优先
+    async fn resolve_plugin_config(
+        &self,
+        plugin_id: &str,
+        tenant_id: Uuid,
+    ) -> Result<HashMap<String, serde_json::Value>, AiStudioError> {
+        let mut merged = self.lifecycle_manager.get_plugin_config(plugin_id).await?.parameters;
+        
+        if !tenant_id.is_nil() {
+            let tenant_config = self.plugin_api.get_tenant_plugin_config(tenant_id, plugin_id).await?;
+            merged.extend(tenant_config);
+        }
+        
+        Ok(merged)
+    }
+    
+    /// 获取插件注册的 HTTP 路由
+    pub async fn get_plugin_routes(&self, plugin_id: &str) -> Result<Vec<PluginRoute>, AiStudioError> {
+        let metadata = self.registry.get_plugin_metadata(plugin_id).await?;
+        
+        if !metadata.permissions.contains(&PluginPermission::HttpRoutes) {
+            return Ok(Vec::new());
+        }
         
-        Ok(serde_json::json!({
-            "plugin_id": plugin_id,
-            "method": method,
-            "result": "success"
-        }))
+        Ok(metadata.routes)
+    }
+    
+    /// 解析插件扩展路由
+    /// 返回匹配的路由声明及路径参数
+    pub async fn resolve_route(
+        &self,
+        plugin_id: &str,
+        method: &str,
+        path: &str,
+    ) -> Result<(PluginRoute, HashMap<String, String>), AiStudioError> {
+        let routes = self.get_plugin_routes(plugin_id).await?;
+        
+        routes.into_iter()
+            .find_map(|route| route.match_request(method, path).map(|params| (route, params)))
+            .ok_or_else(|| AiStudioError::not_found(format!("插件路由 {} {}", method, path)))
     }
     
     /// 获取插件列表
@@ -368,11 +516,16 @@ impl PluginManager {
                     event_count: 0,
                 });
             
+            let signer = self.registry.get_registered_plugin(plugin_id).await
+                .ok()
+                .and_then(|registered| registered.signer);
+            
             plugins.push(PluginInfo {
                 plugin_id: plugin_id.clone(),
                 metadata,
                 status,
                 instance_info,
+                signer,
             });
         }
         
@@ -389,12 +542,14 @@ impl PluginManager {
         let metadata = self.registry.get_plugin_metadata(plugin_id).await?;
         let status = self.lifecycle_manager.get_plugin_status(plugin_id).await?;
         let instance_info = self.lifecycle_manager.get_plugin_info(plugin_id).await?;
+        let signer = self.registry.get_registered_plugin(plugin_id).await?.signer;
         
         Ok(PluginInfo {
             plugin_id: plugin_id.to_string(),
             metadata,
             status,
             instance_info,
+            signer,
         })
     }
     
@@ -421,6 +576,87 @@ impl PluginManager {
         Ok(())
     }
     
+    /// 将系统事件分发给订阅该事件的运行中插件
+    /// 每个插件的投递在独立任务中进行，失败时按指数退避重试
+    pub async fn dispatch_event(&self, event: SystemEvent) {
+        let plugins = match self.registry.list_plugins().await {
+            Ok(plugins) => plugins,
+            Err(e) => {
+                error!("获取插件列表失败，无法分发事件 {}: {}", event.event_type, e);
+                return;
+            }
+        };
+        let statuses = self.lifecycle_manager.get_all_plugin_status().await;
+        
+        for metadata in plugins {
+            let subscribed = metadata.event_subscriptions.iter()
+                .any(|pattern| event_matches(pattern, &event.event_type));
+            if !subscribed || statuses.get(&metadata.id) != Some(&PluginStatus::Running) {
+                continue;
+            }
+            
+            let manager = self.clone();
+            let event = event.clone();
+            tokio::spawn(async move {
+                manager.deliver_event(&metadata.id, &event).await;
+            });
+        }
+    }
+    
+    /// 向单个插件投递事件
+    async fn deliver_event(&self, plugin_id: &str, event: &SystemEvent) {
+        let mut params = HashMap::new();
+        params.insert("event".to_string(), serde_json::to_value(event).unwrap_or_default());
+        
+        let mut attempt = 0;
+        loop {
+            let context = PluginContext {
+                tenant_id: event.tenant_id.unwrap_or_else(Uuid::nil),
+                user_id: None,
+                session_id: None,
+                request_id: Uuid::new_v4(),
+                variables: HashMap::new(),
+                plugin_config: HashMap::new(),
+                timestamp: Utc::now(),
+            };
+            
+            match self.call_plugin(plugin_id, "on_event", params.clone(), context).await {
+                Ok(_) => {
+                    debug!("事件投递成功: {} -> {}", event.event_type, plugin_id);
+                    return;
+                }
+                Err(e) if attempt < self.config.event_max_retries => {
+                    let delay = self.config.event_retry_base_delay_ms.saturating_mul(1 << attempt.min(16));
+                    warn!("事件投递失败，{}ms 后重试: {} -> {} - {}", delay, event.event_type, plugin_id, e);
+                    tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    error!("事件投递失败，已放弃: {} -> {} - {}", event.event_type, plugin_id, e);
+                    return;
+                }
+            }
+        }
+    }
+    
+    /// 启动系统事件分发器
+    fn start_event_dispatcher(&self) {
+        let manager = self.clone();
+        let mut receiver = SystemEventBus::global().subscribe();
+        
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => manager.dispatch_event(event).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("插件事件分发滞后，丢弃 {} 个事件", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+    
     /// 验证插件
     async fn verify_plugin(&self, metadata: &PluginMetadata) -> Result<(), AiStudioError> {
         // 检查权限
@@ -432,6 +668,23 @@ impl PluginManager {
             }
         }
         
+        // 检查路由声明
+        if !metadata.routes.is_empty() {
+            if !metadata.permissions.contains(&PluginPermission::HttpRoutes) {
+                return Err(AiStudioError::forbidden("插件注册 HTTP 路由需要声明 http_routes 权限"));
+            }
+            
+            let mut seen = std::collections::HashSet::new();
+            for route in &metadata.routes {
+                route.validate()?;
+                if !seen.insert((route.method.to_uppercase(), route.path.clone())) {
+                    return Err(AiStudioError::validation("routes".to_string(), &format!(
+                        "插件路由重复: {} {}", route.method, route.path
+                    )));
+                }
+            }
+        }
+        
         // 检查依赖
         for dependency in &metadata.dependencies {
             if !dependency.optional {
@@ -531,6 +784,7 @@ impl Clone for PluginManager {
             loader: self.loader.clone(),
             plugin_api: self.plugin_api.clone(),
             hooks: self.hooks.clone(),
+            resource_monitor: self.resource_monitor.clone(),
             config: self.config.clone(),
         }
     }
@@ -9,6 +9,7 @@ use tracing::{info, warn, error, debug};
 use tokio::sync::RwLock;
 
 use crate::plugins::plugin_interface::{PluginMetadata, PluginType};
+use crate::plugins::plugin_signing::SignerIdentity;
 use crate::errors::AiStudioError;
 
 /// 插件注册表
@@ -53,6 +54,8 @@ pub struct RegisteredPlugin {
     pub version_history: Vec<PluginVersion>,
     /// 插件状态
     pub registry_status: RegistryStatus,
+    /// 签名者身份（未签名插件为空）
+    pub signer: Option<SignerIdentity>,
 }
 
 /// 插件版本
@@ -66,6 +69,8 @@ pub struct PluginVersion {
     pub released_at: DateTime<Utc>,
     /// 版本状态
     pub status: VersionStatus,
+    /// 该版本的签名者身份
+    pub signer: Option<SignerIdentity>,
 }
 
 /// 注册表状态
@@ -137,6 +142,15 @@ impl PluginRegistry {
     
     /// 注册插件
     pub async fn register_plugin(&self, metadata: PluginMetadata) -> Result<(), AiStudioError> {
+        self.register_signed_plugin(metadata, None).await
+    }
+    
+    /// 注册插件并记录签名者身份
+    pub async fn register_signed_plugin(
+        &self,
+        metadata: PluginMetadata,
+        signer: Option<SignerIdentity>,
+    ) -> Result<(), AiStudioError> {
         let plugin_id = metadata.id.clone();
         info!("注册插件到注册表: {}", plugin_id);
         
@@ -153,6 +167,7 @@ impl PluginRegistry {
                     metadata: existing.metadata.clone(),
                     released_at: existing.updated_at,
                     status: VersionStatus::Stable,
+                    signer: existing.signer.clone(),
                 };
                 
                 existing.version_history.push(version);
@@ -164,6 +179,7 @@ impl PluginRegistry {
             }
             
             existing.metadata = metadata;
+            existing.signer = signer;
             existing.updated_at = now;
             
             info!("插件更新成功: {}", plugin_id);
@@ -175,6 +191,7 @@ impl PluginRegistry {
                 updated_at: now,
                 version_history: Vec::new(),
                 registry_status: RegistryStatus::Registered,
+                signer,
             };
             
             plugins.insert(plugin_id.clone(), registered_plugin);
@@ -520,6 +537,8 @@ mod tests {
             permissions: Vec::new(),
             tags: vec!["test".to_string()],
             icon: None,
+            routes: Vec::new(),
+            event_subscriptions: Vec::new(),
             created_at: Utc::now(),
         };
         
@@ -552,6 +571,8 @@ mod tests {
             permissions: Vec::new(),
             tags: vec!["search".to_string(), "test".to_string()],
             icon: None,
+            routes: Vec::new(),
+            event_subscriptions: Vec::new(),
             created_at: Utc::now(),
         };
         
@@ -0,0 +1,228 @@
+// 插件签名校验
+// 使用 ed25519 校验插件制品签名，并按部署级/租户级受信任密钥判定是否允许加载
+
+use std::path::{Path, PathBuf};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::errors::AiStudioError;
+
+/// 签名文件扩展名，签名与制品同目录存放（如 `echo.wasm.sig`）
+pub const SIGNATURE_EXTENSION: &str = "sig";
+
+/// 受信任的签名公钥
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedKey {
+    /// 密钥 ID（公钥 SHA-256 的前 16 个十六进制字符）
+    pub key_id: String,
+    /// 密钥名称
+    pub name: String,
+    /// base64 编码的 ed25519 公钥
+    pub public_key: String,
+    /// 所属租户，为空表示部署级密钥
+    pub tenant_id: Option<Uuid>,
+}
+
+impl TrustedKey {
+    /// 校验公钥并生成密钥 ID
+    pub fn new(name: impl Into<String>, public_key: &str, tenant_id: Option<Uuid>) -> Result<Self, AiStudioError> {
+        let public_key = public_key.trim();
+        decode_public_key(public_key)?;
+
+        Ok(Self {
+            key_id: key_id(public_key)?,
+            name: name.into(),
+            public_key: public_key.to_string(),
+            tenant_id,
+        })
+    }
+}
+
+/// 插件签名文件内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginSignatureFile {
+    /// 签名密钥 ID
+    pub key_id: String,
+    /// base64 编码的 ed25519 签名（对制品 SHA-256 摘要签名）
+    pub signature: String,
+}
+
+impl PluginSignatureFile {
+    /// 读取制品对应的签名文件，不存在时返回 `None`
+    pub async fn load_for(artifact: &Path) -> Result<Option<Self>, AiStudioError> {
+        let path = signature_path(artifact);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = tokio::fs::read_to_string(&path).await
+            .map_err(|e| AiStudioError::internal(format!("读取插件签名失败: {}", e)))?;
+        let signature = serde_json::from_str(&content)
+            .map_err(|e| AiStudioError::validation("signature", format!("插件签名格式无效: {}", e)))?;
+        Ok(Some(signature))
+    }
+}
+
+/// 签名者身份，随插件记录在注册表中用于审计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignerIdentity {
+    /// 密钥 ID
+    pub key_id: String,
+    /// 密钥名称
+    pub name: String,
+    /// 密钥所属租户，为空表示部署级密钥
+    pub tenant_id: Option<Uuid>,
+    /// 制品 SHA-256 摘要
+    pub artifact_sha256: String,
+    /// 校验时间
+    pub verified_at: DateTime<Utc>,
+}
+
+/// 插件信任策略
+#[derive(Debug, Clone, Default)]
+pub struct TrustPolicy {
+    /// 部署级受信任密钥
+    pub trusted_keys: Vec<TrustedKey>,
+    /// 是否拒绝未签名插件（生产环境开启）
+    pub require_signature: bool,
+}
+
+impl TrustPolicy {
+    /// 校验插件签名
+    ///
+    /// 部署级密钥对所有租户生效，`tenant_keys` 中仅与 `tenant_id` 匹配的密钥生效。
+    /// 签名无效时始终拒绝；未签名的插件仅在 `require_signature` 关闭时放行。
+    pub fn verify(
+        &self,
+        artifact: &[u8],
+        signature: Option<&PluginSignatureFile>,
+        tenant_id: Option<Uuid>,
+        tenant_keys: &[TrustedKey],
+    ) -> Result<Option<SignerIdentity>, AiStudioError> {
+        let Some(signature) = signature else {
+            if self.require_signature {
+                return Err(AiStudioError::forbidden("插件未签名，当前环境不允许加载未签名插件"));
+            }
+            return Ok(None);
+        };
+
+        let key = self.trusted_keys.iter()
+            .filter(|k| k.tenant_id.is_none())
+            .chain(tenant_keys.iter().filter(|k| tenant_id.is_some() && k.tenant_id == tenant_id))
+            .find(|k| k.key_id == signature.key_id)
+            .ok_or_else(|| AiStudioError::forbidden(format!("插件签名密钥不受信任: {}", signature.key_id)))?;
+
+        let digest = Sha256::digest(artifact);
+        let signature_bytes: [u8; SIGNATURE_LENGTH] = BASE64.decode(signature.signature.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| AiStudioError::validation("signature", "插件签名编码无效"))?;
+
+        decode_public_key(&key.public_key)?
+            .verify(&digest, &Signature::from_bytes(&signature_bytes))
+            .map_err(|_| AiStudioError::forbidden("插件签名校验失败，制品可能已被篡改"))?;
+
+        Ok(Some(SignerIdentity {
+            key_id: key.key_id.clone(),
+            name: key.name.clone(),
+            tenant_id: key.tenant_id,
+            artifact_sha256: format!("{:x}", digest),
+            verified_at: Utc::now(),
+        }))
+    }
+}
+
+/// 计算公钥的密钥 ID
+pub fn key_id(public_key: &str) -> Result<String, AiStudioError> {
+    let key = decode_public_key(public_key)?;
+    let digest = format!("{:x}", Sha256::digest(key.as_bytes()));
+    Ok(digest[..16].to_string())
+}
+
+/// 签名文件路径
+pub fn signature_path(artifact: &Path) -> PathBuf {
+    let mut path = artifact.as_os_str().to_owned();
+    path.push(".");
+    path.push(SIGNATURE_EXTENSION);
+    PathBuf::from(path)
+}
+
+fn decode_public_key(public_key: &str) -> Result<VerifyingKey, AiStudioError> {
+    let bytes: [u8; PUBLIC_KEY_LENGTH] = BASE64.decode(public_key.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| AiStudioError::validation("public_key", "公钥必须是 base64 编码的 32 字节 ed25519 公钥"))?;
+
+    VerifyingKey::from_bytes(&bytes)
+        .map_err(|_| AiStudioError::validation("public_key", "无效的 ed25519 公钥"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn trusted(seed: u8, tenant_id: Option<Uuid>) -> TrustedKey {
+        let public_key = BASE64.encode(signing_key(seed).verifying_key().as_bytes());
+        TrustedKey::new("release", &public_key, tenant_id).unwrap()
+    }
+
+    fn sign(seed: u8, artifact: &[u8]) -> PluginSignatureFile {
+        let key = trusted(seed, None);
+        let signature = signing_key(seed).sign(&Sha256::digest(artifact));
+        PluginSignatureFile {
+            key_id: key.key_id,
+            signature: BASE64.encode(signature.to_bytes()),
+        }
+    }
+
+    #[test]
+    fn test_verify_deployment_key() {
+        let policy = TrustPolicy { trusted_keys: vec![trusted(1, None)], require_signature: true };
+        let artifact = b"plugin-bytes";
+
+        let signer = policy.verify(artifact, Some(&sign(1, artifact)), None, &[]).unwrap().unwrap();
+        assert_eq!(signer.name, "release");
+        assert!(signer.tenant_id.is_none());
+
+        // 制品被篡改
+        assert!(policy.verify(b"tampered", Some(&sign(1, artifact)), None, &[]).is_err());
+        // 未受信任的密钥
+        assert!(policy.verify(artifact, Some(&sign(2, artifact)), None, &[]).is_err());
+    }
+
+    #[test]
+    fn test_tenant_keys_are_scoped() {
+        let tenant_id = Uuid::new_v4();
+        let policy = TrustPolicy::default();
+        let tenant_keys = vec![trusted(3, Some(tenant_id))];
+        let artifact = b"plugin-bytes";
+        let signature = sign(3, artifact);
+
+        assert!(policy.verify(artifact, Some(&signature), Some(tenant_id), &tenant_keys).unwrap().is_some());
+        assert!(policy.verify(artifact, Some(&signature), Some(Uuid::new_v4()), &tenant_keys).is_err());
+    }
+
+    #[test]
+    fn test_unsigned_plugins() {
+        let permissive = TrustPolicy::default();
+        assert!(permissive.verify(b"plugin", None, None, &[]).unwrap().is_none());
+
+        let strict = TrustPolicy { trusted_keys: Vec::new(), require_signature: true };
+        assert!(strict.verify(b"plugin", None, None, &[]).is_err());
+    }
+
+    #[test]
+    fn test_signature_path() {
+        assert_eq!(signature_path(Path::new("plugins/echo.wasm")), PathBuf::from("plugins/echo.wasm.sig"));
+        assert!(TrustedKey::new("bad", "not-a-key", None).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+// 插件资源监控
+// 统计每个插件的执行耗时、内存、出站流量和调用延迟，并按资源限制判定限流或挂起
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use crate::plugins::plugin_interface::ResourceLimits;
+
+/// 资源统计滑动窗口
+const USAGE_WINDOW: Duration = Duration::from_secs(60);
+
+/// 延迟统计保留的样本数
+const LATENCY_SAMPLES: usize = 200;
+
+/// 窗口内连续违规达到该次数后挂起插件
+const SUSPEND_AFTER_VIOLATIONS: u32 = 5;
+
+/// 资源限制判定结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimitDecision {
+    /// 允许调用
+    Allow,
+    /// 暂时拒绝调用
+    Throttle(String),
+    /// 挂起插件
+    Suspend(String),
+}
+
+/// 调用延迟统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencyStats {
+    /// 平均延迟（毫秒）
+    pub avg_ms: f64,
+    /// P95 延迟（毫秒）
+    pub p95_ms: u64,
+    /// 最大延迟（毫秒）
+    pub max_ms: u64,
+}
+
+/// 插件资源使用指标
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginResourceMetrics {
+    /// 插件 ID
+    pub plugin_id: String,
+    /// 资源限制
+    pub limits: ResourceLimits,
+    /// 累计调用次数
+    pub calls_total: u64,
+    /// 累计失败次数
+    pub errors_total: u64,
+    /// 累计超时次数
+    pub timeouts_total: u64,
+    /// 累计被限流次数
+    pub throttled_total: u64,
+    /// 累计执行耗时（毫秒），作为进程内插件 CPU 时间的近似值
+    pub cpu_time_ms_total: u64,
+    /// 最近一分钟 CPU 占用（百分比）
+    pub cpu_percent: f32,
+    /// 最近上报的内存使用（MB），仅沙箱/WASM 插件可上报
+    pub memory_mb: Option<u64>,
+    /// 累计出站流量（字节）
+    pub network_bytes_total: u64,
+    /// 最近一分钟出站带宽（KB/s）
+    pub network_kbps: f64,
+    /// 调用延迟
+    pub latency: LatencyStats,
+    /// 是否已被挂起
+    pub suspended: bool,
+    /// 挂起原因
+    pub suspension_reason: Option<String>,
+    /// 最后调用时间
+    pub last_call_at: Option<DateTime<Utc>>,
+}
+
+/// 单个插件的使用状态
+#[derive(Debug, Default)]
+struct UsageState {
+    calls_total: u64,
+    errors_total: u64,
+    timeouts_total: u64,
+    throttled_total: u64,
+    cpu_time_ms_total: u64,
+    network_bytes_total: u64,
+    memory_mb: Option<u64>,
+    /// 窗口内的执行耗时样本
+    busy_window: VecDeque<(Instant, u64)>,
+    /// 窗口内的出站流量样本
+    network_window: VecDeque<(Instant, u64)>,
+    latencies: VecDeque<u64>,
+    consecutive_violations: u32,
+    suspension_reason: Option<String>,
+    last_call_at: Option<DateTime<Utc>>,
+}
+
+impl UsageState {
+    fn prune(&mut self, now: Instant) {
+        while self.busy_window.front().is_some_and(|(at, _)| now.duration_since(*at) > USAGE_WINDOW) {
+            self.busy_window.pop_front();
+        }
+        while self.network_window.front().is_some_and(|(at, _)| now.duration_since(*at) > USAGE_WINDOW) {
+            self.network_window.pop_front();
+        }
+    }
+
+    fn cpu_percent(&self) -> f32 {
+        let busy_ms: u64 = self.busy_window.iter().map(|(_, ms)| ms).sum();
+        busy_ms as f32 / USAGE_WINDOW.as_millis() as f32 * 100.0
+    }
+
+    fn network_kbps(&self) -> f64 {
+        let bytes: u64 = self.network_window.iter().map(|(_, b)| b).sum();
+        bytes as f64 / 1024.0 / USAGE_WINDOW.as_secs_f64()
+    }
+
+    fn latency(&self) -> LatencyStats {
+        if self.latencies.is_empty() {
+            return LatencyStats::default();
+        }
+
+        let mut sorted: Vec<u64> = self.latencies.iter().copied().collect();
+        sorted.sort_unstable();
+        let p95_index = ((sorted.len() as f64 * 0.95).ceil() as usize).saturating_sub(1);
+
+        LatencyStats {
+            avg_ms: sorted.iter().sum::<u64>() as f64 / sorted.len() as f64,
+            p95_ms: sorted[p95_index],
+            max_ms: *sorted.last().unwrap_or(&0),
+        }
+    }
+}
+
+/// 插件资源监控器
+#[derive(Debug, Default)]
+pub struct PluginResourceMonitor {
+    usage: RwLock<HashMap<String, UsageState>>,
+}
+
+impl PluginResourceMonitor {
+    /// 创建新的资源监控器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 调用前检查资源限制
+    pub async fn check_limits(&self, plugin_id: &str, limits: &ResourceLimits) -> LimitDecision {
+        let mut usage = self.usage.write().await;
+        let state = usage.entry(plugin_id.to_string()).or_default();
+        state.prune(Instant::now());
+
+        let decision = evaluate_limits(state, limits);
+        match &decision {
+            LimitDecision::Allow => {}
+            LimitDecision::Throttle(_) => {
+                state.throttled_total += 1;
+                state.consecutive_violations += 1;
+                if state.consecutive_violations >= SUSPEND_AFTER_VIOLATIONS {
+                    let reason = format!("连续 {} 次超出资源限制", state.consecutive_violations);
+                    state.suspension_reason = Some(reason.clone());
+                    return LimitDecision::Suspend(reason);
+                }
+            }
+            LimitDecision::Suspend(reason) => {
+                state.suspension_reason = Some(reason.clone());
+            }
+        }
+
+        decision
+    }
+
+    /// 记录一次调用
+    pub async fn record_call(&self, plugin_id: &str, elapsed: Duration, success: bool, timed_out: bool) {
+        let now = Instant::now();
+        let elapsed_ms = elapsed.as_millis() as u64;
+
+        let mut usage = self.usage.write().await;
+        let state = usage.entry(plugin_id.to_string()).or_default();
+        state.prune(now);
+
+        state.calls_total += 1;
+        state.cpu_time_ms_total += elapsed_ms;
+        state.busy_window.push_back((now, elapsed_ms));
+        state.last_call_at = Some(Utc::now());
+
+        state.latencies.push_back(elapsed_ms);
+        if state.latencies.len() > LATENCY_SAMPLES {
+            state.latencies.pop_front();
+        }
+
+        if !success {
+            state.errors_total += 1;
+        }
+        if timed_out {
+            state.timeouts_total += 1;
+            state.consecutive_violations += 1;
+        } else {
+            state.consecutive_violations = 0;
+        }
+    }
+
+    /// 记录出站网络流量
+    pub async fn record_network(&self, plugin_id: &str, bytes: u64) {
+        let now = Instant::now();
+        let mut usage = self.usage.write().await;
+        let state = usage.entry(plugin_id.to_string()).or_default();
+        state.network_bytes_total += bytes;
+        state.network_window.push_back((now, bytes));
+    }
+
+    /// 记录沙箱插件上报的内存使用
+    pub async fn record_memory(&self, plugin_id: &str, memory_mb: u64) {
+        let mut usage = self.usage.write().await;
+        usage.entry(plugin_id.to_string()).or_default().memory_mb = Some(memory_mb);
+    }
+
+    /// 解除挂起状态（插件被重新启动时调用）
+    pub async fn clear_suspension(&self, plugin_id: &str) {
+        let mut usage = self.usage.write().await;
+        if let Some(state) = usage.get_mut(plugin_id) {
+            state.suspension_reason = None;
+            state.consecutive_violations = 0;
+        }
+    }
+
+    /// 清除插件的全部统计
+    pub async fn remove(&self, plugin_id: &str) {
+        self.usage.write().await.remove(plugin_id);
+    }
+
+    /// 获取插件资源使用指标
+    pub async fn get_metrics(&self, plugin_id: &str, limits: ResourceLimits) -> PluginResourceMetrics {
+        let mut usage = self.usage.write().await;
+        let state = usage.entry(plugin_id.to_string()).or_default();
+        state.prune(Instant::now());
+
+        PluginResourceMetrics {
+            plugin_id: plugin_id.to_string(),
+            limits,
+            calls_total: state.calls_total,
+            errors_total: state.errors_total,
+            timeouts_total: state.timeouts_total,
+            throttled_total: state.throttled_total,
+            cpu_time_ms_total: state.cpu_time_ms_total,
+            cpu_percent: state.cpu_percent(),
+            memory_mb: state.memory_mb,
+            network_bytes_total: state.network_bytes_total,
+            network_kbps: state.network_kbps(),
+            latency: state.latency(),
+            suspended: state.suspension_reason.is_some(),
+            suspension_reason: state.suspension_reason.clone(),
+            last_call_at: state.last_call_at,
+        }
+    }
+}
+
+/// 按资源限制评估当前使用情况
+fn evaluate_limits(state: &UsageState, limits: &ResourceLimits) -> LimitDecision {
+    if let (Some(max), Some(used)) = (limits.max_memory_mb, state.memory_mb) {
+        if used > max {
+            return LimitDecision::Suspend(format!("内存使用 {}MB 超过限制 {}MB", used, max));
+        }
+    }
+
+    if let Some(max) = limits.max_cpu_percent {
+        let used = state.cpu_percent();
+        if used > max {
+            return LimitDecision::Throttle(format!("CPU 占用 {:.1}% 超过限制 {:.1}%", used, max));
+        }
+    }
+
+    if let Some(max) = limits.max_network_kbps {
+        let used = state.network_kbps();
+        if used > max as f64 {
+            return LimitDecision::Throttle(format!("出站带宽 {:.1}KB/s 超过限制 {}KB/s", used, max));
+        }
+    }
+
+    LimitDecision::Allow
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> ResourceLimits {
+        ResourceLimits {
+            max_memory_mb: Some(128),
+            max_cpu_percent: Some(10.0),
+            max_disk_mb: None,
+            max_network_kbps: Some(1),
+            max_execution_seconds: Some(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cpu_limit_throttles_then_suspends() {
+        let monitor = PluginResourceMonitor::new();
+        assert_eq!(monitor.check_limits("p", &limits()).await, LimitDecision::Allow);
+
+        // 一分钟窗口内 10 秒执行耗时，约 16.7% CPU
+        monitor.record_call("p", Duration::from_secs(10), true, false).await;
+        for _ in 0..SUSPEND_AFTER_VIOLATIONS - 1 {
+            assert!(matches!(monitor.check_limits("p", &limits()).await, LimitDecision::Throttle(_)));
+        }
+        assert!(matches!(monitor.check_limits("p", &limits()).await, LimitDecision::Suspend(_)));
+
+        let metrics = monitor.get_metrics("p", limits()).await;
+        assert!(metrics.suspended);
+        assert_eq!(metrics.throttled_total, SUSPEND_AFTER_VIOLATIONS as u64);
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit_suspends() {
+        let monitor = PluginResourceMonitor::new();
+        monitor.record_memory("p", 256).await;
+        assert!(matches!(monitor.check_limits("p", &limits()).await, LimitDecision::Suspend(_)));
+    }
+
+    #[tokio::test]
+    async fn test_latency_stats() {
+        let monitor = PluginResourceMonitor::new();
+        for ms in [10, 20, 30, 40] {
+            monitor.record_call("p", Duration::from_millis(ms), ms != 40, false).await;
+        }
+
+        let metrics = monitor.get_metrics("p", ResourceLimits::default()).await;
+        assert_eq!(metrics.calls_total, 4);
+        assert_eq!(metrics.errors_total, 1);
+        assert_eq!(metrics.latency.max_ms, 40);
+        assert_eq!(metrics.latency.avg_ms, 25.0);
+    }
+}
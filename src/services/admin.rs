@@ -0,0 +1,394 @@
+// 平台管理概览服务
+// 为运维看板聚合全平台及单租户的运行统计
+
+use std::collections::{BTreeMap, HashMap};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, PaginatorTrait,
+    QueryFilter, QuerySelect, Statement, Value,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::{prelude::*, tenant};
+use crate::errors::AiStudioError;
+use crate::services::quota::{QuotaService, QuotaStatsResponse};
+
+/// 每小时执行统计
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HourlyExecutionStats {
+    /// 小时（整点）
+    pub hour: DateTime<Utc>,
+    /// Agent 执行次数
+    pub agent_executions: u64,
+    /// 工作流执行次数
+    pub workflow_executions: u64,
+    /// 失败次数（含超时）
+    pub failed: u64,
+}
+
+/// 执行队列深度
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct QueueDepth {
+    /// 等待执行数
+    pub pending: u64,
+    /// 正在执行数
+    pub running: u64,
+}
+
+/// 执行概况
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExecutionOverview {
+    /// 统计窗口（小时）
+    pub window_hours: u32,
+    /// 窗口内执行总数
+    pub total: u64,
+    /// 窗口内失败数
+    pub failed: u64,
+    /// 错误率（0.0-1.0）
+    pub error_rate: f64,
+    /// 按小时分布
+    pub per_hour: Vec<HourlyExecutionStats>,
+    /// 当前队列深度
+    pub queue_depth: QueueDepth,
+}
+
+/// 内容规模统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ContentTotals {
+    /// 用户数
+    pub users: u64,
+    /// 知识库数
+    pub knowledge_bases: u64,
+    /// 文档数
+    pub documents: u64,
+    /// 文档块数
+    pub chunks: u64,
+    /// 向量嵌入数
+    pub embeddings: u64,
+    /// Agent 数
+    pub agents: u64,
+    /// 工作流数
+    pub workflows: u64,
+    /// 存储用量（字节），取各知识库最近一次汇总统计
+    pub storage_bytes: u64,
+    /// 处理失败的文档数，取各知识库最近一次汇总统计
+    pub failed_documents: u64,
+}
+
+/// 平台概览
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PlatformOverview {
+    /// 租户总数
+    pub total_tenants: u64,
+    /// 按状态统计的租户数
+    pub tenants_by_status: HashMap<String, u64>,
+    /// 内容规模
+    pub totals: ContentTotals,
+    /// 执行概况
+    pub executions: ExecutionOverview,
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+}
+
+/// 租户摘要
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TenantSummary {
+    /// 租户 ID
+    pub id: Uuid,
+    /// 租户名称
+    pub name: String,
+    /// 显示名称
+    pub display_name: String,
+    /// 租户状态
+    pub status: String,
+    /// 最后活跃时间
+    pub last_active_at: Option<DateTime<Utc>>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+}
+
+/// 租户下钻概览
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TenantOverview {
+    /// 租户摘要
+    pub tenant: TenantSummary,
+    /// 内容规模
+    pub totals: ContentTotals,
+    /// 执行概况
+    pub executions: ExecutionOverview,
+    /// 配额使用情况
+    pub quotas: Option<QuotaStatsResponse>,
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+}
+
+impl From<tenant::Model> for TenantSummary {
+    fn from(model: tenant::Model) -> Self {
+        Self {
+            id: model.id,
+            name: model.name,
+            display_name: model.display_name,
+            status: format!("{:?}", model.status).to_lowercase(),
+            last_active_at: model.last_active_at.map(|dt| dt.with_timezone(&Utc)),
+            created_at: model.created_at.with_timezone(&Utc),
+        }
+    }
+}
+
+/// 平台管理概览服务
+pub struct AdminDashboardService {
+    db: DatabaseConnection,
+}
+
+impl AdminDashboardService {
+    /// 创建新的管理概览服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 获取全平台概览
+    #[instrument(skip(self))]
+    pub async fn get_overview(&self, window_hours: u32) -> Result<PlatformOverview, AiStudioError> {
+        let tenants = Tenant::find()
+            .select_only()
+            .column(tenant::Column::Status)
+            .into_tuple::<tenant::TenantStatus>()
+            .all(&self.db)
+            .await?;
+
+        let mut tenants_by_status = HashMap::new();
+        for status in &tenants {
+            *tenants_by_status.entry(format!("{:?}", status).to_lowercase()).or_insert(0u64) += 1;
+        }
+
+        Ok(PlatformOverview {
+            total_tenants: tenants.len() as u64,
+            tenants_by_status,
+            totals: self.content_totals(None).await?,
+            executions: self.execution_overview(None, window_hours).await?,
+            generated_at: Utc::now(),
+        })
+    }
+
+    /// 获取单个租户的下钻概览
+    #[instrument(skip(self))]
+    pub async fn get_tenant_overview(
+        &self,
+        tenant_id: Uuid,
+        window_hours: u32,
+    ) -> Result<TenantOverview, AiStudioError> {
+        let tenant = Tenant::find_by_id(tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+        // 配额统计依赖租户 JSON 字段，解析失败不影响其余统计
+        let quotas = QuotaService::new(self.db.clone())
+            .get_quota_stats(tenant_id)
+            .await
+            .ok();
+
+        Ok(TenantOverview {
+            tenant: TenantSummary::from(tenant),
+            totals: self.content_totals(Some(tenant_id)).await?,
+            executions: self.execution_overview(Some(tenant_id), window_hours).await?,
+            quotas,
+            generated_at: Utc::now(),
+        })
+    }
+
+    /// 统计内容规模
+    async fn content_totals(&self, tenant_id: Option<Uuid>) -> Result<ContentTotals, AiStudioError> {
+        use crate::db::entities::{agent, knowledge_base, user, workflow};
+
+        let (users, knowledge_bases, agents, workflows) = match tenant_id {
+            Some(tenant_id) => (
+                User::find().filter(user::Column::TenantId.eq(tenant_id)).count(&self.db).await?,
+                KnowledgeBase::find().filter(knowledge_base::Column::TenantId.eq(tenant_id)).count(&self.db).await?,
+                Agent::find().filter(agent::Column::TenantId.eq(tenant_id)).count(&self.db).await?,
+                Workflow::find().filter(workflow::Column::TenantId.eq(tenant_id)).count(&self.db).await?,
+            ),
+            None => (
+                User::find().count(&self.db).await?,
+                KnowledgeBase::find().count(&self.db).await?,
+                Agent::find().count(&self.db).await?,
+                Workflow::find().count(&self.db).await?,
+            ),
+        };
+
+        // 文档、文档块与嵌入通过知识库归属到租户
+        let kb_scoped = |table: &str| match tenant_id {
+            Some(_) => format!(
+                "SELECT COUNT(*) AS count FROM {} t JOIN knowledge_bases kb ON kb.id = t.knowledge_base_id WHERE kb.tenant_id = $1",
+                table
+            ),
+            None => format!("SELECT COUNT(*) AS count FROM {}", table),
+        };
+        let values: Vec<Value> = tenant_id.map(|id| vec![id.into()]).unwrap_or_default();
+
+        let (storage_bytes, failed_documents) = self.latest_storage_totals(tenant_id).await?;
+
+        Ok(ContentTotals {
+            users,
+            knowledge_bases,
+            documents: self.count_sql(&kb_scoped("documents"), values.clone()).await?,
+            chunks: self.count_sql(&kb_scoped("document_chunks"), values.clone()).await?,
+            embeddings: self.count_sql(&kb_scoped("embeddings"), values).await?,
+            agents,
+            workflows,
+            storage_bytes,
+            failed_documents,
+        })
+    }
+
+    /// 汇总各知识库最近一次存储统计中的存储用量与失败文档数
+    async fn latest_storage_totals(&self, tenant_id: Option<Uuid>) -> Result<(u64, u64), AiStudioError> {
+        let filter = if tenant_id.is_some() { "WHERE tenant_id = $1" } else { "" };
+        let sql = format!(
+            r#"
+            SELECT COALESCE(SUM(file_bytes + database_bytes), 0)::BIGINT AS storage_bytes,
+                   COALESCE(SUM(failed_documents), 0)::BIGINT AS failed_documents
+            FROM (
+                SELECT DISTINCT ON (knowledge_base_id) file_bytes, database_bytes, failed_documents
+                FROM kb_storage_stats {}
+                ORDER BY knowledge_base_id, stat_date DESC
+            ) latest
+            "#,
+            filter
+        );
+        let values: Vec<Value> = tenant_id.map(|id| vec![id.into()]).unwrap_or_default();
+
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(DatabaseBackend::Postgres, &sql, values))
+            .await?;
+        Ok(match row {
+            Some(row) => (
+                row.try_get::<i64>("", "storage_bytes")?.max(0) as u64,
+                row.try_get::<i64>("", "failed_documents")?.max(0) as u64,
+            ),
+            None => (0, 0),
+        })
+    }
+
+    /// 统计执行概况
+    async fn execution_overview(
+        &self,
+        tenant_id: Option<Uuid>,
+        window_hours: u32,
+    ) -> Result<ExecutionOverview, AiStudioError> {
+        let since = Utc::now() - Duration::hours(window_hours as i64);
+        let mut per_hour: BTreeMap<DateTime<Utc>, HourlyExecutionStats> = BTreeMap::new();
+        let mut queue_depth = QueueDepth::default();
+
+        for (table, is_agent) in [("agent_executions", true), ("workflow_executions", false)] {
+            let tenant_filter = if tenant_id.is_some() { "AND tenant_id = $2" } else { "" };
+            let mut values: Vec<Value> = vec![since.into()];
+            if let Some(id) = tenant_id {
+                values.push(id.into());
+            }
+
+            let rows = self.db
+                .query_all(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    &format!(
+                        r#"
+                        SELECT date_trunc('hour', created_at) AS hour,
+                               COUNT(*) AS total,
+                               COUNT(*) FILTER (WHERE status::text IN ('failed', 'timeout')) AS failed
+                        FROM {} WHERE created_at >= $1 {}
+                        GROUP BY 1 ORDER BY 1
+                        "#,
+                        table, tenant_filter
+                    ),
+                    values.clone(),
+                ))
+                .await?;
+
+            for row in rows {
+                let hour: DateTime<Utc> = row.try_get("", "hour")?;
+                let total: i64 = row.try_get("", "total")?;
+                let failed: i64 = row.try_get("", "failed")?;
+
+                let entry = per_hour.entry(hour).or_insert_with(|| HourlyExecutionStats {
+                    hour,
+                    agent_executions: 0,
+                    workflow_executions: 0,
+                    failed: 0,
+                });
+                if is_agent {
+                    entry.agent_executions += total as u64;
+                } else {
+                    entry.workflow_executions += total as u64;
+                }
+                entry.failed += failed as u64;
+            }
+
+            let depth_filter = if tenant_id.is_some() { "WHERE tenant_id = $1" } else { "" };
+            let depth_values: Vec<Value> = tenant_id.map(|id| vec![id.into()]).unwrap_or_default();
+            let row = self.db
+                .query_one(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    &format!(
+                        r#"
+                        SELECT COUNT(*) FILTER (WHERE status::text = 'pending') AS pending,
+                               COUNT(*) FILTER (WHERE status::text = 'running') AS running
+                        FROM {} {}
+                        "#,
+                        table, depth_filter
+                    ),
+                    depth_values,
+                ))
+                .await?;
+
+            if let Some(row) = row {
+                queue_depth.pending += row.try_get::<i64>("", "pending")? as u64;
+                queue_depth.running += row.try_get::<i64>("", "running")? as u64;
+            }
+        }
+
+        let per_hour: Vec<HourlyExecutionStats> = per_hour.into_values().collect();
+        let total: u64 = per_hour.iter().map(|h| h.agent_executions + h.workflow_executions).sum();
+        let failed: u64 = per_hour.iter().map(|h| h.failed).sum();
+
+        Ok(ExecutionOverview {
+            window_hours,
+            total,
+            failed,
+            error_rate: error_rate(failed, total),
+            per_hour,
+            queue_depth,
+        })
+    }
+
+    /// 执行 COUNT 查询
+    async fn count_sql(&self, sql: &str, values: Vec<Value>) -> Result<u64, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(DatabaseBackend::Postgres, sql, values))
+            .await?;
+
+        Ok(row.map(|r| r.try_get::<i64>("", "count")).transpose()?.unwrap_or(0) as u64)
+    }
+}
+
+/// 计算错误率
+fn error_rate(failed: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        failed as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_rate() {
+        assert_eq!(error_rate(0, 0), 0.0);
+        assert_eq!(error_rate(1, 4), 0.25);
+    }
+}
@@ -0,0 +1,493 @@
+// 临时管理员提权服务
+// 用户说明理由申请限时管理员权限，由另一名管理员审批；批准后到期前按管理员处理，
+// 到期自动失效，提权期间的请求在审计日志中标记提权 ID
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult, Statement};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::config::ElevationConfig;
+use crate::db::DatabaseManager;
+use crate::errors::AiStudioError;
+use crate::services::audit_log::{AuditLogService, NewAuditLog};
+use crate::services::cache::{self, CacheNamespace};
+
+/// 提权后获得的角色
+pub const ELEVATED_ROLE: &str = "admin";
+
+/// 申请理由最短长度
+const MIN_JUSTIFICATION_CHARS: usize = 10;
+
+/// 申请理由最大长度
+const MAX_JUSTIFICATION_CHARS: usize = 2000;
+
+/// 提权状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ElevationStatus {
+    /// 待审批
+    Pending,
+    /// 已批准，到期前生效
+    Approved,
+    /// 已拒绝
+    Rejected,
+    /// 已撤销（申请人提前结束或管理员收回）
+    Revoked,
+    /// 已批准但已到期
+    Expired,
+}
+
+impl ElevationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+            Self::Revoked => "revoked",
+            Self::Expired => "expired",
+        }
+    }
+
+    /// 由存储状态与到期时间得出当前状态；到期不落库，读取时判定
+    fn resolve(stored: &str, expires_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Self {
+        match stored {
+            "approved" if expires_at.is_some_and(|at| at <= now) => Self::Expired,
+            "approved" => Self::Approved,
+            "rejected" => Self::Rejected,
+            "revoked" => Self::Revoked,
+            _ => Self::Pending,
+        }
+    }
+}
+
+/// 提权申请
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct RequestElevationRequest {
+    /// 申请理由
+    pub justification: String,
+    /// 提权时长（小时），自批准时起计算
+    pub duration_hours: u32,
+}
+
+/// 审批提权申请
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct ElevationDecisionRequest {
+    /// 审批意见
+    pub note: Option<String>,
+}
+
+/// 提权列表查询参数
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+pub struct ListElevationsQuery {
+    /// 按状态过滤
+    pub status: Option<ElevationStatus>,
+    /// 按申请人过滤
+    pub user_id: Option<Uuid>,
+}
+
+/// 提权记录
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AdminElevation {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// 提权后的角色
+    pub role: String,
+    pub justification: String,
+    pub duration_hours: i32,
+    pub status: ElevationStatus,
+    pub requested_at: DateTime<Utc>,
+    /// 审批人
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub decision_note: Option<String>,
+    /// 生效时间
+    pub starts_at: Option<DateTime<Utc>>,
+    /// 到期时间
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_by: Option<Uuid>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// 用户生效中的提权
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveElevation {
+    pub id: Uuid,
+    pub role: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 用户提权状态缓存项；无提权时同样缓存，避免每次认证都查询
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedElevation {
+    active: Option<ActiveElevation>,
+}
+
+/// 临时管理员提权服务
+pub struct AdminElevationService {
+    db: DatabaseConnection,
+    config: ElevationConfig,
+}
+
+impl AdminElevationService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection, config: ElevationConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// 申请提权，同一用户同时只能有一个待审批或生效中的提权
+    #[instrument(skip(self, request))]
+    pub async fn request(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: RequestElevationRequest,
+    ) -> Result<AdminElevation, AiStudioError> {
+        validate_request(&request, &self.config)?;
+
+        let existing = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT id FROM admin_elevations
+                WHERE user_id = $1
+                  AND (status = 'pending' OR (status = 'approved' AND expires_at > $2))
+                LIMIT 1
+                "#,
+                [user_id.into(), Utc::now().into()],
+            ))
+            .await?;
+        if existing.is_some() {
+            return Err(AiStudioError::conflict("已有待审批或生效中的提权"));
+        }
+
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO admin_elevations (id, tenant_id, user_id, role, justification, duration_hours, status, requested_at)
+                VALUES ($1, $2, $3, $4, $5, $6, 'pending', $7)
+                RETURNING *
+                "#,
+                [
+                    Uuid::new_v4().into(),
+                    tenant_id.into(),
+                    user_id.into(),
+                    ELEVATED_ROLE.into(),
+                    request.justification.trim().to_string().into(),
+                    (request.duration_hours as i32).into(),
+                    Utc::now().into(),
+                ],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::internal("创建提权申请失败"))?;
+
+        let elevation = elevation_from_row(&row)?;
+        self.audit(tenant_id, user_id, "elevation.requested", &elevation).await?;
+        info!(tenant_id = %tenant_id, user_id = %user_id, hours = elevation.duration_hours, "已提交提权申请");
+        Ok(elevation)
+    }
+
+    /// 批准提权，审批人不能是申请人；自批准时起计算到期时间
+    #[instrument(skip(self, request))]
+    pub async fn approve(
+        &self,
+        tenant_id: Uuid,
+        elevation_id: Uuid,
+        approver_id: Uuid,
+        request: ElevationDecisionRequest,
+    ) -> Result<AdminElevation, AiStudioError> {
+        let elevation = self.get(tenant_id, elevation_id).await?;
+        self.ensure_decidable(&elevation, approver_id)?;
+
+        let now = Utc::now();
+        let expires_at = now + Duration::hours(elevation.duration_hours as i64);
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                UPDATE admin_elevations
+                SET status = 'approved', decided_by = $3, decided_at = $4, decision_note = $5,
+                    starts_at = $4, expires_at = $6
+                WHERE id = $1 AND tenant_id = $2 AND status = 'pending'
+                RETURNING *
+                "#,
+                [
+                    elevation_id.into(),
+                    tenant_id.into(),
+                    approver_id.into(),
+                    now.into(),
+                    request.note.into(),
+                    expires_at.into(),
+                ],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::conflict("提权申请已被处理"))?;
+
+        let elevation = elevation_from_row(&row)?;
+        cache::invalidate(CacheNamespace::Elevation, elevation.user_id).await;
+        self.audit(tenant_id, approver_id, "elevation.approved", &elevation).await?;
+        info!(
+            tenant_id = %tenant_id,
+            elevation_id = %elevation_id,
+            approver = %approver_id,
+            expires_at = %expires_at,
+            "提权申请已批准"
+        );
+        Ok(elevation)
+    }
+
+    /// 拒绝提权申请
+    #[instrument(skip(self, request))]
+    pub async fn reject(
+        &self,
+        tenant_id: Uuid,
+        elevation_id: Uuid,
+        approver_id: Uuid,
+        request: ElevationDecisionRequest,
+    ) -> Result<AdminElevation, AiStudioError> {
+        let elevation = self.get(tenant_id, elevation_id).await?;
+        self.ensure_decidable(&elevation, approver_id)?;
+
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                UPDATE admin_elevations
+                SET status = 'rejected', decided_by = $3, decided_at = $4, decision_note = $5
+                WHERE id = $1 AND tenant_id = $2 AND status = 'pending'
+                RETURNING *
+                "#,
+                [
+                    elevation_id.into(),
+                    tenant_id.into(),
+                    approver_id.into(),
+                    Utc::now().into(),
+                    request.note.into(),
+                ],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::conflict("提权申请已被处理"))?;
+
+        let elevation = elevation_from_row(&row)?;
+        self.audit(tenant_id, approver_id, "elevation.rejected", &elevation).await?;
+        info!(tenant_id = %tenant_id, elevation_id = %elevation_id, approver = %approver_id, "提权申请已拒绝");
+        Ok(elevation)
+    }
+
+    /// 撤销待审批或生效中的提权；申请人可提前结束自己的提权，管理员可收回任意提权
+    #[instrument(skip(self))]
+    pub async fn revoke(
+        &self,
+        tenant_id: Uuid,
+        elevation_id: Uuid,
+        actor_id: Uuid,
+        actor_is_admin: bool,
+    ) -> Result<AdminElevation, AiStudioError> {
+        let elevation = self.get(tenant_id, elevation_id).await?;
+        if elevation.user_id != actor_id && !actor_is_admin {
+            return Err(AiStudioError::forbidden("只能撤销自己的提权"));
+        }
+        if !matches!(elevation.status, ElevationStatus::Pending | ElevationStatus::Approved) {
+            return Err(AiStudioError::conflict(format!("提权已处于 {} 状态", elevation.status.as_str())));
+        }
+
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                UPDATE admin_elevations
+                SET status = 'revoked', revoked_by = $3, revoked_at = $4
+                WHERE id = $1 AND tenant_id = $2 AND status IN ('pending', 'approved')
+                RETURNING *
+                "#,
+                [elevation_id.into(), tenant_id.into(), actor_id.into(), Utc::now().into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::conflict("提权已被处理"))?;
+
+        let elevation = elevation_from_row(&row)?;
+        cache::invalidate(CacheNamespace::Elevation, elevation.user_id).await;
+        self.audit(tenant_id, actor_id, "elevation.revoked", &elevation).await?;
+        info!(tenant_id = %tenant_id, elevation_id = %elevation_id, actor = %actor_id, "提权已撤销");
+        Ok(elevation)
+    }
+
+    /// 获取提权记录
+    pub async fn get(&self, tenant_id: Uuid, elevation_id: Uuid) -> Result<AdminElevation, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT * FROM admin_elevations WHERE id = $1 AND tenant_id = $2",
+                [elevation_id.into(), tenant_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("提权记录"))?;
+        elevation_from_row(&row)
+    }
+
+    /// 列出租户的提权记录，按申请时间倒序
+    pub async fn list(&self, tenant_id: Uuid, query: &ListElevationsQuery) -> Result<Vec<AdminElevation>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT * FROM admin_elevations
+                WHERE tenant_id = $1 AND ($2::uuid IS NULL OR user_id = $2)
+                ORDER BY requested_at DESC
+                "#,
+                [tenant_id.into(), query.user_id.into()],
+            ))
+            .await?;
+
+        let elevations = rows.iter().map(elevation_from_row).collect::<Result<Vec<_>, _>>()?;
+        Ok(match query.status {
+            Some(status) => elevations.into_iter().filter(|e| e.status == status).collect(),
+            None => elevations,
+        })
+    }
+
+    /// 检查申请是否可由该审批人处理
+    fn ensure_decidable(&self, elevation: &AdminElevation, approver_id: Uuid) -> Result<(), AiStudioError> {
+        if elevation.user_id == approver_id {
+            return Err(AiStudioError::forbidden("不能审批自己的提权申请"));
+        }
+        if elevation.status != ElevationStatus::Pending {
+            return Err(AiStudioError::conflict(format!("提权已处于 {} 状态", elevation.status.as_str())));
+        }
+        let deadline = elevation.requested_at + Duration::hours(self.config.request_ttl_hours as i64);
+        if deadline <= Utc::now() {
+            return Err(AiStudioError::conflict("提权申请已超过审批时限，请重新申请"));
+        }
+        Ok(())
+    }
+
+    /// 记录提权生命周期事件
+    async fn audit(
+        &self,
+        tenant_id: Uuid,
+        actor_id: Uuid,
+        action: &str,
+        elevation: &AdminElevation,
+    ) -> Result<(), AiStudioError> {
+        let entry = NewAuditLog::event(tenant_id, actor_id, action)
+            .with_resource("elevation", Some(elevation.id))
+            .with_elevation(Some(elevation.id))
+            .with_details(serde_json::json!({
+                "user_id": elevation.user_id,
+                "role": elevation.role,
+                "duration_hours": elevation.duration_hours,
+                "justification": elevation.justification,
+                "decision_note": elevation.decision_note,
+                "expires_at": elevation.expires_at,
+            }));
+        AuditLogService::new(self.db.clone()).record(entry).await
+    }
+}
+
+/// 经缓存加载用户生效中的提权；到期时间在读取时判定，缓存未失效也不会延长提权
+pub async fn load_active_elevation(user_id: Uuid) -> Result<Option<ActiveElevation>, AiStudioError> {
+    let cached = cache::cached(CacheNamespace::Elevation, &user_id.to_string(), move || async move {
+        let db_manager = DatabaseManager::get()?;
+        let row = db_manager
+            .get_connection()
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT id, role, expires_at FROM admin_elevations
+                WHERE user_id = $1 AND status = 'approved' AND expires_at > $2
+                ORDER BY expires_at DESC
+                LIMIT 1
+                "#,
+                [user_id.into(), Utc::now().into()],
+            ))
+            .await?;
+        let active = row
+            .map(|row| -> Result<ActiveElevation, AiStudioError> {
+                Ok(ActiveElevation {
+                    id: row.try_get("", "id")?,
+                    role: row.try_get("", "role")?,
+                    expires_at: row.try_get("", "expires_at")?,
+                })
+            })
+            .transpose()?;
+        Ok::<_, AiStudioError>(Some(CachedElevation { active }))
+    })
+    .await?;
+
+    Ok(cached
+        .and_then(|cached| cached.active)
+        .filter(|active| active.expires_at > Utc::now()))
+}
+
+/// 校验提权申请
+fn validate_request(request: &RequestElevationRequest, config: &ElevationConfig) -> Result<(), AiStudioError> {
+    let justification = request.justification.trim().chars().count();
+    if !(MIN_JUSTIFICATION_CHARS..=MAX_JUSTIFICATION_CHARS).contains(&justification) {
+        return Err(AiStudioError::validation(
+            "justification",
+            format!("申请理由需在 {} 到 {} 个字符之间", MIN_JUSTIFICATION_CHARS, MAX_JUSTIFICATION_CHARS),
+        ));
+    }
+    if request.duration_hours == 0 || request.duration_hours > config.max_hours {
+        return Err(AiStudioError::validation(
+            "duration_hours",
+            format!("提权时长需在 1 到 {} 小时之间", config.max_hours),
+        ));
+    }
+    Ok(())
+}
+
+fn elevation_from_row(row: &QueryResult) -> Result<AdminElevation, AiStudioError> {
+    let status: String = row.try_get("", "status")?;
+    let expires_at: Option<DateTime<Utc>> = row.try_get("", "expires_at")?;
+    Ok(AdminElevation {
+        id: row.try_get("", "id")?,
+        user_id: row.try_get("", "user_id")?,
+        role: row.try_get("", "role")?,
+        justification: row.try_get("", "justification")?,
+        duration_hours: row.try_get("", "duration_hours")?,
+        status: ElevationStatus::resolve(&status, expires_at, Utc::now()),
+        requested_at: row.try_get("", "requested_at")?,
+        decided_by: row.try_get("", "decided_by")?,
+        decided_at: row.try_get("", "decided_at")?,
+        decision_note: row.try_get("", "decision_note")?,
+        starts_at: row.try_get("", "starts_at")?,
+        expires_at,
+        revoked_by: row.try_get("", "revoked_by")?,
+        revoked_at: row.try_get("", "revoked_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_status_expires_approved() {
+        let now = Utc::now();
+        let past = Some(now - Duration::minutes(1));
+        let future = Some(now + Duration::hours(1));
+
+        assert_eq!(ElevationStatus::resolve("approved", future, now), ElevationStatus::Approved);
+        assert_eq!(ElevationStatus::resolve("approved", past, now), ElevationStatus::Expired);
+        assert_eq!(ElevationStatus::resolve("revoked", future, now), ElevationStatus::Revoked);
+        assert_eq!(ElevationStatus::resolve("pending", None, now), ElevationStatus::Pending);
+    }
+
+    #[test]
+    fn test_validate_request() {
+        let config = ElevationConfig::default();
+        let request = |justification: &str, duration_hours| RequestElevationRequest {
+            justification: justification.to_string(),
+            duration_hours,
+        };
+
+        assert!(validate_request(&request("排查租户 A 的索引故障", 2), &config).is_ok());
+        assert!(validate_request(&request("紧急", 2), &config).is_err());
+        assert!(validate_request(&request("排查租户 A 的索引故障", 0), &config).is_err());
+        assert!(validate_request(&request("排查租户 A 的索引故障", config.max_hours + 1), &config).is_err());
+    }
+}
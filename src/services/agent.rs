@@ -64,6 +64,7 @@ impl AgentService {
             max_concurrent_agents: 100,
             memory_config: crate::ai::agent_runtime::MemoryConfig::default(),
             tool_call_timeout_seconds: 30,
+            few_shot_examples: 3,
         };
         
         // 创建 Agent 运行时
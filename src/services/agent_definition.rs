@@ -0,0 +1,601 @@
+// Agent 定义文件服务
+// 以 YAML/JSON 声明式描述 Agent（提示词、工具、模型、预算、记忆设置），支持导入导出，便于版本管理和跨环境迁移
+
+use std::collections::HashSet;
+use std::path::Path;
+use sea_orm::{ActiveEnum, DatabaseConnection};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::ai::agent_runtime::MemoryConfig;
+use crate::db::entities::agent::{self, AgentType};
+use crate::db::repositories::agent::AgentRepository;
+use crate::errors::AiStudioError;
+
+/// 定义文件格式版本
+pub const AGENT_DEFINITION_API_VERSION: &str = "aionix/v1";
+
+/// 定义文件类型
+pub const AGENT_DEFINITION_KIND: &str = "Agent";
+
+/// 记忆设置在 Agent 自定义配置中的键名
+const MEMORY_CONFIG_KEY: &str = "memory";
+
+/// 定义文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DefinitionFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+impl DefinitionFormat {
+    /// 根据文件扩展名判断格式，无法识别时为 YAML
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => DefinitionFormat::Json,
+            _ => DefinitionFormat::Yaml,
+        }
+    }
+
+    /// 根据请求的 Content-Type 判断格式，无法识别时为 YAML
+    pub fn from_content_type(content_type: &str) -> Self {
+        if content_type.contains("json") {
+            DefinitionFormat::Json
+        } else {
+            DefinitionFormat::Yaml
+        }
+    }
+
+    /// 对应的 Content-Type
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            DefinitionFormat::Yaml => "application/yaml",
+            DefinitionFormat::Json => "application/json",
+        }
+    }
+
+    /// 文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DefinitionFormat::Yaml => "yaml",
+            DefinitionFormat::Json => "json",
+        }
+    }
+
+    /// 按格式反序列化文件内容
+    pub fn deserialize<T: DeserializeOwned>(&self, content: &str) -> Result<T, AiStudioError> {
+        match self {
+            DefinitionFormat::Yaml => serde_yaml::from_str(content)
+                .map_err(|e| AiStudioError::validation("definition", format!("YAML 解析失败: {}", e))),
+            DefinitionFormat::Json => serde_json::from_str(content)
+                .map_err(|e| AiStudioError::validation("definition", format!("JSON 解析失败: {}", e))),
+        }
+    }
+
+    /// 按格式序列化文件内容
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<String, AiStudioError> {
+        match self {
+            DefinitionFormat::Yaml => serde_yaml::to_string(value)
+                .map_err(|e| AiStudioError::internal(format!("生成 YAML 失败: {}", e))),
+            DefinitionFormat::Json => Ok(serde_json::to_string_pretty(value)?),
+        }
+    }
+
+    /// 解析定义文件并校验
+    pub fn parse(&self, content: &str) -> Result<AgentDefinition, AiStudioError> {
+        let definition: AgentDefinition = self.deserialize(content)?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// 输出定义文件
+    pub fn render(&self, definition: &AgentDefinition) -> Result<String, AiStudioError> {
+        self.serialize(definition)
+    }
+}
+
+/// 定义文件格式查询参数
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct DefinitionFormatQuery {
+    /// 文件格式：yaml 或 json，导出默认 yaml，导入默认按 Content-Type 判断
+    pub format: Option<DefinitionFormat>,
+}
+
+/// Agent 定义
+///
+/// 不包含 ID、租户、统计等环境相关信息，同一份定义可导入不同环境。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AgentDefinition {
+    /// 格式版本，当前为 `aionix/v1`，省略时视为当前版本
+    #[serde(default = "default_api_version")]
+    pub api_version: String,
+    /// 定义类型，固定为 `Agent`
+    #[serde(default = "default_kind")]
+    pub kind: String,
+    /// Agent 名称，导入时按名称匹配租户内已有 Agent
+    pub name: String,
+    /// Agent 描述
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Agent 类型：conversational、task_executor、data_processor、code_generator、analyst、custom
+    #[serde(default = "default_agent_type")]
+    pub agent_type: String,
+    /// Agent 版本
+    #[serde(default = "default_version")]
+    pub version: String,
+    /// 系统提示词
+    pub system_prompt: String,
+    /// 工具，可只写工具名称
+    #[serde(default)]
+    pub tools: Vec<ToolDefinition>,
+    /// 模型设置
+    #[serde(default)]
+    pub model: ModelDefinition,
+    /// 执行预算
+    #[serde(default)]
+    pub budgets: BudgetDefinition,
+    /// 记忆设置
+    #[serde(default)]
+    pub memory: MemoryDefinition,
+    /// 标签
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// 工具定义
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum ToolDefinition {
+    /// 仅工具名称，使用默认配置
+    Name(String),
+    /// 完整工具配置
+    Detailed {
+        /// 工具名称
+        name: String,
+        /// 工具配置
+        #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+        config: serde_json::Value,
+        /// 是否启用
+        #[serde(default = "default_true")]
+        enabled: bool,
+        /// 权限要求
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        permissions: Vec<String>,
+    },
+}
+
+impl ToolDefinition {
+    /// 工具名称
+    pub fn name(&self) -> &str {
+        match self {
+            ToolDefinition::Name(name) => name,
+            ToolDefinition::Detailed { name, .. } => name,
+        }
+    }
+
+    fn from_tool(tool: &agent::AgentTool) -> Self {
+        if tool.enabled && tool.config.is_null() && tool.permissions.is_empty() {
+            ToolDefinition::Name(tool.name.clone())
+        } else {
+            ToolDefinition::Detailed {
+                name: tool.name.clone(),
+                config: tool.config.clone(),
+                enabled: tool.enabled,
+                permissions: tool.permissions.clone(),
+            }
+        }
+    }
+
+    fn into_tool(self) -> agent::AgentTool {
+        let (name, config, enabled, permissions) = match self {
+            ToolDefinition::Name(name) => (name, serde_json::Value::Null, true, Vec::new()),
+            ToolDefinition::Detailed { name, config, enabled, permissions } => (name, config, enabled, permissions),
+        };
+        agent::AgentTool {
+            description: String::new(),
+            tool_type: "builtin".to_string(),
+            name,
+            config,
+            enabled,
+            permissions,
+        }
+    }
+}
+
+/// 模型设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct ModelDefinition {
+    /// 模型名称
+    pub name: String,
+    /// 温度参数
+    pub temperature: f32,
+    /// 最大 token 数
+    pub max_tokens: u32,
+    /// Top-p 参数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// 停止词
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+}
+
+impl Default for ModelDefinition {
+    fn default() -> Self {
+        let llm = agent::LlmConfig::default();
+        Self {
+            name: llm.model_name,
+            temperature: llm.temperature,
+            max_tokens: llm.max_tokens,
+            top_p: llm.top_p,
+            stop_sequences: llm.stop_sequences,
+        }
+    }
+}
+
+/// 执行预算
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct BudgetDefinition {
+    /// 最大执行时间（秒）
+    pub max_execution_seconds: u32,
+    /// 最大迭代次数
+    pub max_iterations: u32,
+    /// 重试次数
+    pub retry_count: u32,
+    /// 最大网络请求数
+    pub max_network_requests: u32,
+}
+
+impl Default for BudgetDefinition {
+    fn default() -> Self {
+        let execution = agent::ExecutionConfig::default();
+        Self {
+            max_execution_seconds: execution.max_execution_time,
+            max_iterations: execution.max_iterations,
+            retry_count: execution.retry_count,
+            max_network_requests: agent::ResourceLimits::default().max_network_requests,
+        }
+    }
+}
+
+/// 记忆设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct MemoryDefinition {
+    /// 短期记忆容量
+    pub short_term_size: usize,
+    /// 长期记忆容量
+    pub long_term_size: usize,
+    /// 工作记忆容量
+    pub working_size: usize,
+    /// 短期记忆超过该数量时压缩
+    pub compression_threshold: usize,
+}
+
+impl Default for MemoryDefinition {
+    fn default() -> Self {
+        MemoryConfig::default().into()
+    }
+}
+
+impl From<MemoryConfig> for MemoryDefinition {
+    fn from(config: MemoryConfig) -> Self {
+        Self {
+            short_term_size: config.short_term_memory_size,
+            long_term_size: config.long_term_memory_size,
+            working_size: config.working_memory_size,
+            compression_threshold: config.memory_compression_threshold,
+        }
+    }
+}
+
+fn default_api_version() -> String {
+    AGENT_DEFINITION_API_VERSION.to_string()
+}
+
+fn default_kind() -> String {
+    AGENT_DEFINITION_KIND.to_string()
+}
+
+fn default_agent_type() -> String {
+    AgentType::Custom.to_value()
+}
+
+fn default_version() -> String {
+    "1.0.0".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl AgentDefinition {
+    /// 校验定义内容
+    pub fn validate(&self) -> Result<(), AiStudioError> {
+        if self.api_version != AGENT_DEFINITION_API_VERSION {
+            return Err(AiStudioError::validation(
+                "api_version",
+                format!("不支持的格式版本 '{}'，应为 '{}'", self.api_version, AGENT_DEFINITION_API_VERSION),
+            ));
+        }
+        if self.kind != AGENT_DEFINITION_KIND {
+            return Err(AiStudioError::validation("kind", format!("定义类型应为 '{}'", AGENT_DEFINITION_KIND)));
+        }
+        if self.name.trim().is_empty() || self.name.len() > 255 {
+            return Err(AiStudioError::validation("name", "名称不能为空且不超过 255 个字符"));
+        }
+        if self.system_prompt.trim().is_empty() {
+            return Err(AiStudioError::validation("system_prompt", "系统提示词不能为空"));
+        }
+        self.agent_type()?;
+        if !(0.0..=2.0).contains(&self.model.temperature) {
+            return Err(AiStudioError::validation("model.temperature", "温度参数应在 0 到 2 之间"));
+        }
+        if self.model.max_tokens == 0 {
+            return Err(AiStudioError::validation("model.max_tokens", "最大 token 数必须大于 0"));
+        }
+        if self.budgets.max_execution_seconds == 0 || self.budgets.max_iterations == 0 {
+            return Err(AiStudioError::validation("budgets", "执行时间与迭代次数上限必须大于 0"));
+        }
+        if self.memory.compression_threshold > self.memory.short_term_size {
+            return Err(AiStudioError::validation("memory.compression_threshold", "压缩阈值不能超过短期记忆容量"));
+        }
+
+        let mut names = HashSet::new();
+        for tool in &self.tools {
+            if !names.insert(tool.name()) {
+                return Err(AiStudioError::validation("tools", format!("工具 '{}' 重复", tool.name())));
+            }
+        }
+        Ok(())
+    }
+
+    fn agent_type(&self) -> Result<AgentType, AiStudioError> {
+        AgentType::try_from_value(&self.agent_type)
+            .map_err(|_| AiStudioError::validation("agent_type", format!("未知的 Agent 类型 '{}'", self.agent_type)))
+    }
+
+    /// 从已保存的 Agent 生成定义
+    pub fn from_model(model: &agent::Model) -> Result<Self, AiStudioError> {
+        let config: agent::AgentConfig = serde_json::from_value(model.config.clone())?;
+        let tools: Vec<agent::AgentTool> = serde_json::from_value(model.tools.clone())?;
+        let metadata: Option<agent::AgentMetadata> = serde_json::from_value(model.metadata.clone()).ok();
+        let memory = config.custom_config
+            .get(MEMORY_CONFIG_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            api_version: AGENT_DEFINITION_API_VERSION.to_string(),
+            kind: AGENT_DEFINITION_KIND.to_string(),
+            name: model.name.clone(),
+            description: model.description.clone(),
+            agent_type: model.agent_type.to_value(),
+            version: model.version.clone(),
+            system_prompt: model.system_prompt.clone(),
+            tools: tools.iter().map(ToolDefinition::from_tool).collect(),
+            model: ModelDefinition {
+                name: config.llm_config.model_name,
+                temperature: config.llm_config.temperature,
+                max_tokens: config.llm_config.max_tokens,
+                top_p: config.llm_config.top_p,
+                stop_sequences: config.llm_config.stop_sequences,
+            },
+            budgets: BudgetDefinition {
+                max_execution_seconds: config.execution_config.max_execution_time,
+                max_iterations: config.execution_config.max_iterations,
+                retry_count: config.execution_config.retry_count,
+                max_network_requests: config.security_config.resource_limits.max_network_requests,
+            },
+            memory,
+            tags: metadata.map(|metadata| metadata.tags).unwrap_or_default(),
+        })
+    }
+
+    /// 将定义写入 Agent，未在定义中描述的配置（安全、性能等）保持不变
+    fn apply_to(self, model: &mut agent::Model) -> Result<(), AiStudioError> {
+        let mut config: agent::AgentConfig = serde_json::from_value(model.config.clone()).unwrap_or_default();
+        config.llm_config.model_name = self.model.name;
+        config.llm_config.temperature = self.model.temperature;
+        config.llm_config.max_tokens = self.model.max_tokens;
+        config.llm_config.top_p = self.model.top_p;
+        config.llm_config.stop_sequences = self.model.stop_sequences;
+        config.execution_config.max_execution_time = self.budgets.max_execution_seconds;
+        config.execution_config.max_iterations = self.budgets.max_iterations;
+        config.execution_config.retry_count = self.budgets.retry_count;
+        config.security_config.resource_limits.max_network_requests = self.budgets.max_network_requests;
+        config.security_config.allowed_tools = self.tools.iter().map(|tool| tool.name().to_string()).collect();
+        if !config.custom_config.is_object() {
+            config.custom_config = serde_json::json!({});
+        }
+        config.custom_config[MEMORY_CONFIG_KEY] = serde_json::to_value(&self.memory)?;
+
+        let mut metadata: agent::AgentMetadata = serde_json::from_value(model.metadata.clone()).unwrap_or_default();
+        metadata.tags = self.tags;
+
+        model.agent_type = AgentType::try_from_value(&self.agent_type)
+            .map_err(|_| AiStudioError::validation("agent_type", "未知的 Agent 类型"))?;
+        model.description = self.description;
+        model.version = self.version;
+        model.system_prompt = self.system_prompt;
+        model.tools = serde_json::to_value(self.tools.into_iter().map(ToolDefinition::into_tool).collect::<Vec<_>>())?;
+        model.config = serde_json::to_value(config)?;
+        model.metadata = serde_json::to_value(metadata)?;
+        Ok(())
+    }
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AgentImportResult {
+    /// Agent ID
+    pub agent_id: Uuid,
+    /// Agent 名称
+    pub name: String,
+    /// 是否新建，否则为更新已有 Agent
+    pub created: bool,
+    /// 导入后的修订号
+    pub revision: i32,
+}
+
+/// Agent 定义文件服务
+pub struct AgentDefinitionService {
+    db: DatabaseConnection,
+}
+
+impl AgentDefinitionService {
+    /// 创建新的定义文件服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 导出 Agent 定义
+    #[instrument(skip(self))]
+    pub async fn export(&self, tenant_id: Uuid, agent_id: Uuid) -> Result<AgentDefinition, AiStudioError> {
+        let model = AgentRepository::find_by_id(&self.db, agent_id)
+            .await?
+            .filter(|model| model.tenant_id == tenant_id)
+            .ok_or_else(|| AiStudioError::not_found("Agent"))?;
+        AgentDefinition::from_model(&model)
+    }
+
+    /// 按名称导出 Agent 定义
+    #[instrument(skip(self))]
+    pub async fn export_by_name(&self, tenant_id: Uuid, name: &str) -> Result<AgentDefinition, AiStudioError> {
+        let model = AgentRepository::find_by_name_in_tenant(&self.db, tenant_id, name)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("Agent"))?;
+        AgentDefinition::from_model(&model)
+    }
+
+    /// 导入 Agent 定义
+    ///
+    /// 租户内已有同名 Agent 时更新其定义（修订号递增），否则新建。
+    #[instrument(skip(self, definition), fields(name = %definition.name))]
+    pub async fn import(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        definition: AgentDefinition,
+    ) -> Result<AgentImportResult, AiStudioError> {
+        definition.validate()?;
+
+        let (mut model, created) = match AgentRepository::find_by_name_in_tenant(&self.db, tenant_id, &definition.name).await? {
+            Some(model) => (model, false),
+            None => {
+                let model = AgentRepository::create(
+                    &self.db,
+                    tenant_id,
+                    definition.name.clone(),
+                    definition.description.clone(),
+                    definition.agent_type()?,
+                    definition.system_prompt.clone(),
+                    user_id,
+                )
+                .await?;
+                (model, true)
+            }
+        };
+
+        definition.apply_to(&mut model)?;
+        let model = AgentRepository::update(&self.db, model).await?;
+        info!(agent_id = %model.id, created, "导入 Agent 定义");
+
+        Ok(AgentImportResult {
+            agent_id: model.id,
+            name: model.name,
+            created,
+            revision: model.revision,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFINITION: &str = r#"
+api_version: aionix/v1
+kind: Agent
+name: 客服助手
+agent_type: conversational
+system_prompt: 你是一个耐心的客服助手
+tools:
+  - search
+  - name: http_request
+    config:
+      allowed_hosts: [api.example.com]
+model:
+  name: gpt-4o
+  temperature: 0.2
+  max_tokens: 1024
+budgets:
+  max_iterations: 5
+memory:
+  short_term_size: 50
+  compression_threshold: 40
+tags: [support]
+"#;
+
+    #[test]
+    fn test_parse_yaml_definition_with_defaults() {
+        let definition = DefinitionFormat::Yaml.parse(DEFINITION).unwrap();
+        assert_eq!(definition.tools[0], ToolDefinition::Name("search".to_string()));
+        assert_eq!(definition.tools[1].name(), "http_request");
+        assert_eq!(definition.budgets.max_iterations, 5);
+        assert_eq!(definition.budgets.max_execution_seconds, BudgetDefinition::default().max_execution_seconds);
+        assert_eq!(definition.memory.working_size, MemoryConfig::default().working_memory_size);
+        assert_eq!(definition.version, "1.0.0");
+    }
+
+    #[test]
+    fn test_definition_round_trips_through_agent_model() {
+        let definition = DefinitionFormat::Yaml.parse(DEFINITION).unwrap();
+        let now = chrono::Utc::now();
+        let mut model = agent::Model {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: definition.name.clone(),
+            description: None,
+            agent_type: AgentType::Custom,
+            status: agent::AgentStatus::Draft,
+            version: "1.0.0".to_string(),
+            config: serde_json::to_value(agent::AgentConfig::default()).unwrap(),
+            system_prompt: String::new(),
+            tools: serde_json::json!([]),
+            capabilities: serde_json::to_value(agent::AgentCapabilities::default()).unwrap(),
+            metadata: serde_json::to_value(agent::AgentMetadata::default()).unwrap(),
+            execution_stats: serde_json::json!({}),
+            last_executed_at: None,
+            created_by: Uuid::new_v4(),
+            created_at: now.into(),
+            updated_at: now.into(),
+            revision: 1,
+        };
+        definition.clone().apply_to(&mut model).unwrap();
+        assert_eq!(model.agent_type, AgentType::Conversational);
+
+        let exported = AgentDefinition::from_model(&model).unwrap();
+        assert_eq!(exported, definition);
+        let json = DefinitionFormat::Json.render(&exported).unwrap();
+        assert_eq!(DefinitionFormat::Json.parse(&json).unwrap(), definition);
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_definitions() {
+        let wrong_version = DEFINITION.replace("aionix/v1", "aionix/v0");
+        assert!(DefinitionFormat::Yaml.parse(&wrong_version).is_err());
+
+        let duplicate_tools = DEFINITION.replace("  - search\n", "  - search\n  - search\n");
+        assert!(DefinitionFormat::Yaml.parse(&duplicate_tools).is_err());
+
+        let unknown_type = DEFINITION.replace("conversational", "robot");
+        assert!(DefinitionFormat::Yaml.parse(&unknown_type).is_err());
+    }
+}
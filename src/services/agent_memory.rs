@@ -0,0 +1,240 @@
+// Agent 记忆快照服务
+// 任务执行结束时保存 Agent 的记忆项与各推理步骤的记忆检索记录，供调优记忆行为时查看
+
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, QueryFilter, Statement,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::agent_runtime::{AgentMemory, MemoryItem};
+use crate::db::entities::agent_memory_snapshot;
+use crate::db::entities::AgentMemorySnapshot;
+use crate::errors::AiStudioError;
+
+/// 记忆层级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryTier {
+    /// 短期记忆
+    ShortTerm,
+    /// 工作记忆
+    Working,
+    /// 长期记忆
+    LongTerm,
+}
+
+/// 快照中的记忆项
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MemorySnapshotItem {
+    /// 所在层级
+    pub tier: MemoryTier,
+    /// 记忆项
+    #[serde(flatten)]
+    pub item: MemoryItem,
+}
+
+/// 一次检索命中的记忆
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RetrievedMemory {
+    /// 记忆 ID
+    pub memory_id: Uuid,
+    /// 检索排序得分
+    pub score: f32,
+    /// 检索时的记忆内容，记忆被压缩淘汰后仍可查看
+    pub content: String,
+}
+
+/// 单个推理步骤的记忆检索记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MemoryRetrieval {
+    /// 推理步骤序号，从 1 开始
+    pub step: u32,
+    /// 检索时间
+    pub retrieved_at: DateTime<Utc>,
+    /// 按得分降序排列的命中记忆
+    pub memories: Vec<RetrievedMemory>,
+}
+
+impl MemoryRetrieval {
+    /// 记录一次检索，`scored` 为已按得分排序的记忆及其得分
+    pub fn new(step: u32, scored: &[(MemoryItem, f32)]) -> Self {
+        Self {
+            step,
+            retrieved_at: Utc::now(),
+            memories: scored
+                .iter()
+                .map(|(item, score)| RetrievedMemory {
+                    memory_id: item.id,
+                    score: *score,
+                    content: item.content.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 执行的记忆快照
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExecutionMemory {
+    /// Agent ID
+    pub agent_id: Uuid,
+    /// 执行 ID
+    pub execution_id: Uuid,
+    /// 快照时间
+    pub captured_at: DateTime<Utc>,
+    /// 执行结束时的记忆项，按层级分组、组内按重要性降序
+    pub memories: Vec<MemorySnapshotItem>,
+    /// 各推理步骤检索到的记忆
+    pub retrievals: Vec<MemoryRetrieval>,
+}
+
+impl ExecutionMemory {
+    /// 根据 Agent 当前记忆生成快照
+    pub fn capture(
+        agent_id: Uuid,
+        execution_id: Uuid,
+        memory: &AgentMemory,
+        retrievals: Vec<MemoryRetrieval>,
+    ) -> Self {
+        let mut memories = Vec::new();
+        for (tier, items) in [
+            (MemoryTier::Working, &memory.working),
+            (MemoryTier::ShortTerm, &memory.short_term),
+            (MemoryTier::LongTerm, &memory.long_term),
+        ] {
+            let mut items: Vec<&MemoryItem> = items.iter().collect();
+            items.sort_by(|a, b| b.importance_score.total_cmp(&a.importance_score));
+            memories.extend(items.into_iter().map(|item| MemorySnapshotItem { tier, item: item.clone() }));
+        }
+
+        Self {
+            agent_id,
+            execution_id,
+            captured_at: Utc::now(),
+            memories,
+            retrievals,
+        }
+    }
+}
+
+/// Agent 记忆快照服务
+#[derive(Debug, Clone)]
+pub struct AgentMemoryService {
+    db: DatabaseConnection,
+}
+
+impl AgentMemoryService {
+    /// 创建新的记忆快照服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 保存执行的记忆快照，同一执行重复保存时覆盖
+    #[instrument(skip(self, snapshot), fields(agent_id = %snapshot.agent_id, execution_id = %snapshot.execution_id))]
+    pub async fn save(&self, tenant_id: Uuid, snapshot: &ExecutionMemory) -> Result<(), AiStudioError> {
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "INSERT INTO agent_memory_snapshots (tenant_id, agent_id, execution_id, memories, retrievals, created_at) \
+                 VALUES ($1, $2, $3, $4, $5, $6) \
+                 ON CONFLICT (agent_id, execution_id) \
+                 DO UPDATE SET memories = EXCLUDED.memories, retrievals = EXCLUDED.retrievals, created_at = EXCLUDED.created_at",
+                [
+                    tenant_id.into(),
+                    snapshot.agent_id.into(),
+                    snapshot.execution_id.into(),
+                    serde_json::to_value(&snapshot.memories)?.into(),
+                    serde_json::to_value(&snapshot.retrievals)?.into(),
+                    snapshot.captured_at.into(),
+                ],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// 保存记忆快照，失败时只记录警告
+    ///
+    /// 快照仅用于排查，不应影响任务执行结果。
+    pub async fn save_or_warn(&self, tenant_id: Uuid, snapshot: &ExecutionMemory) {
+        if let Err(e) = self.save(tenant_id, snapshot).await {
+            warn!(execution_id = %snapshot.execution_id, error = %e, "保存 Agent 记忆快照失败");
+        }
+    }
+
+    /// 获取执行的记忆快照
+    #[instrument(skip(self))]
+    pub async fn get(
+        &self,
+        tenant_id: Uuid,
+        agent_id: Uuid,
+        execution_id: Uuid,
+    ) -> Result<ExecutionMemory, AiStudioError> {
+        let snapshot = AgentMemorySnapshot::find()
+            .filter(agent_memory_snapshot::Column::TenantId.eq(tenant_id))
+            .filter(agent_memory_snapshot::Column::AgentId.eq(agent_id))
+            .filter(agent_memory_snapshot::Column::ExecutionId.eq(execution_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found(format!("执行 {} 的记忆快照", execution_id)))?;
+
+        Ok(ExecutionMemory {
+            agent_id: snapshot.agent_id,
+            execution_id: snapshot.execution_id,
+            captured_at: snapshot.created_at.with_timezone(&Utc),
+            memories: serde_json::from_value(snapshot.memories)?,
+            retrievals: serde_json::from_value(snapshot.retrievals)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::ai::agent_runtime::MemoryType;
+
+    fn memory_item(content: &str, importance_score: f32) -> MemoryItem {
+        MemoryItem {
+            id: Uuid::new_v4(),
+            memory_type: MemoryType::Conversation,
+            content: content.to_string(),
+            importance_score,
+            access_count: 0,
+            created_at: Utc::now(),
+            last_accessed_at: Utc::now(),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_capture_groups_by_tier_and_importance() {
+        let memory = AgentMemory {
+            short_term: vec![memory_item("低", 0.3), memory_item("高", 0.9)],
+            long_term: vec![memory_item("长期", 0.8)],
+            working: Vec::new(),
+            memory_index: HashMap::new(),
+        };
+        let retrieved = memory.short_term[1].clone();
+        let retrievals = vec![MemoryRetrieval::new(1, &[(retrieved.clone(), 0.9)])];
+
+        let snapshot = ExecutionMemory::capture(Uuid::new_v4(), Uuid::new_v4(), &memory, retrievals);
+        let order: Vec<(MemoryTier, &str)> = snapshot.memories
+            .iter()
+            .map(|m| (m.tier, m.item.content.as_str()))
+            .collect();
+        assert_eq!(order, vec![
+            (MemoryTier::ShortTerm, "高"),
+            (MemoryTier::ShortTerm, "低"),
+            (MemoryTier::LongTerm, "长期"),
+        ]);
+        assert_eq!(snapshot.retrievals[0].memories[0].memory_id, retrieved.id);
+
+        let json = serde_json::to_value(&snapshot.memories[0]).unwrap();
+        assert_eq!(json["tier"], "short_term");
+        assert_eq!(json["importance_score"].as_f64().unwrap() as f32, 0.9);
+    }
+}
@@ -4,6 +4,7 @@
 use crate::ai::{RigAiClientManager, ModelManager, AiHealthChecker, HealthLevel};
 use crate::config::AiConfig;
 use crate::errors::AiStudioError;
+use crate::services::prompt_retention::{record_model_call, ModelCall, ModelCallSource};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -114,6 +115,16 @@ impl AiService for AiServiceImpl {
                 client_manager.generate_text(&prompt).await
             })
         }).await?;
+        record_model_call(ModelCall {
+            tenant_id,
+            source: ModelCallSource::AiService,
+            execution_id: None,
+            reference: None,
+            model: response.model.clone(),
+            prompt: prompt.to_string(),
+            completion: response.text.clone(),
+            tokens_used: response.tokens_used,
+        });
         
         Ok(AiResponse {
             text: response.text,
@@ -205,6 +216,7 @@ impl Default for AiServiceConfig {
                 temperature: 0.7,
                 timeout: 30,
                 retry_attempts: 3,
+                local: None,
             },
             health_check_enabled: true,
             health_check_interval_seconds: 30,
@@ -251,6 +263,7 @@ mod tests {
                 temperature: 0.7,
                 timeout: 30,
                 retry_attempts: 3,
+                local: None,
             },
             health_check_enabled: false, // 测试时禁用
             health_check_interval_seconds: 30,
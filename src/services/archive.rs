@@ -0,0 +1,331 @@
+// 知识库与文档归档服务
+// 将知识库或文档移出检索与问答范围但保留内容与向量，可随时恢复
+
+use std::collections::HashSet;
+use chrono::Utc;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::document::DocumentStatus;
+use crate::db::entities::knowledge_base::KnowledgeBaseStatus;
+use crate::db::entities::{document, knowledge_base, Document, KnowledgeBase};
+use crate::db::update_with_revision;
+use crate::errors::AiStudioError;
+use crate::services::legal_hold::LegalHoldService;
+
+/// 单次批量归档或恢复的最大文档数
+pub const MAX_ARCHIVE_BATCH: usize = 1000;
+
+/// 批量归档或恢复文档请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct DocumentArchiveRequest {
+    /// 文档 ID 列表
+    pub document_ids: Vec<Uuid>,
+}
+
+/// 文档未被归档或恢复的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveSkipReason {
+    /// 文档不存在或不属于当前租户
+    NotFound,
+    /// 文档已归档
+    AlreadyArchived,
+    /// 文档未归档，无需恢复
+    NotArchived,
+    /// 文档正在处理中
+    Processing,
+    /// 文档或其知识库处于法律保留状态
+    LegalHold,
+}
+
+/// 未处理的文档
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SkippedDocument {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 原因
+    pub reason: ArchiveSkipReason,
+}
+
+/// 批量归档或恢复文档结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DocumentArchiveResult {
+    /// 已归档或已恢复的文档
+    pub document_ids: Vec<Uuid>,
+    /// 未处理的文档及原因
+    pub skipped: Vec<SkippedDocument>,
+}
+
+/// 知识库归档或恢复结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct KnowledgeBaseArchiveResult {
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 是否处于归档状态
+    pub archived: bool,
+    /// 知识库中的文档数量，归档不删除文档
+    pub document_count: i32,
+    /// 更新时间
+    pub updated_at: chrono::DateTime<Utc>,
+}
+
+/// 归档服务
+pub struct ArchiveService {
+    db: DatabaseConnection,
+}
+
+impl ArchiveService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 归档知识库，知识库及其文档不再参与检索与问答
+    #[instrument(skip(self))]
+    pub async fn archive_knowledge_base(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        actor: Uuid,
+    ) -> Result<KnowledgeBaseArchiveResult, AiStudioError> {
+        let kb = self.find_knowledge_base(tenant_id, knowledge_base_id).await?;
+        match kb.status {
+            KnowledgeBaseStatus::Archived => return Err(AiStudioError::conflict("知识库已归档")),
+            KnowledgeBaseStatus::Processing => return Err(AiStudioError::conflict("知识库正在处理中，请稍后再归档")),
+            _ => {}
+        }
+        LegalHoldService::new(self.db.clone())
+            .ensure_knowledge_base_mutable(knowledge_base_id, Some(actor), "archive")
+            .await?;
+
+        let kb = self.set_knowledge_base_status(kb, KnowledgeBaseStatus::Archived).await?;
+        info!("知识库已归档: id={}, 文档数={}", kb.id, kb.document_count);
+        Ok(knowledge_base_result(&kb))
+    }
+
+    /// 恢复已归档的知识库
+    #[instrument(skip(self))]
+    pub async fn restore_knowledge_base(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        actor: Uuid,
+    ) -> Result<KnowledgeBaseArchiveResult, AiStudioError> {
+        let kb = self.find_knowledge_base(tenant_id, knowledge_base_id).await?;
+        if !kb.is_archived() {
+            return Err(AiStudioError::conflict("知识库未归档"));
+        }
+        LegalHoldService::new(self.db.clone())
+            .ensure_knowledge_base_mutable(knowledge_base_id, Some(actor), "restore")
+            .await?;
+
+        let kb = self.set_knowledge_base_status(kb, KnowledgeBaseStatus::Active).await?;
+        info!("知识库已恢复: id={}", kb.id);
+        Ok(knowledge_base_result(&kb))
+    }
+
+    /// 批量归档文档，跳过不存在、已归档、处理中或处于法律保留状态的文档
+    #[instrument(skip(self, document_ids), fields(count = document_ids.len()))]
+    pub async fn archive_documents(
+        &self,
+        tenant_id: Uuid,
+        document_ids: &[Uuid],
+    ) -> Result<DocumentArchiveResult, AiStudioError> {
+        self.update_documents(tenant_id, document_ids, true).await
+    }
+
+    /// 批量恢复已归档的文档
+    ///
+    /// 已完成向量化的文档恢复为已完成状态，其余文档恢复为待处理状态，需要重新处理后才能被检索。
+    #[instrument(skip(self, document_ids), fields(count = document_ids.len()))]
+    pub async fn restore_documents(
+        &self,
+        tenant_id: Uuid,
+        document_ids: &[Uuid],
+    ) -> Result<DocumentArchiveResult, AiStudioError> {
+        self.update_documents(tenant_id, document_ids, false).await
+    }
+
+    async fn update_documents(
+        &self,
+        tenant_id: Uuid,
+        document_ids: &[Uuid],
+        archive: bool,
+    ) -> Result<DocumentArchiveResult, AiStudioError> {
+        if document_ids.is_empty() {
+            return Err(AiStudioError::validation("document_ids", "文档 ID 列表不能为空"));
+        }
+        if document_ids.len() > MAX_ARCHIVE_BATCH {
+            return Err(AiStudioError::validation(
+                "document_ids",
+                format!("单次最多处理 {} 个文档", MAX_ARCHIVE_BATCH),
+            ));
+        }
+
+        let documents = Document::find()
+            .inner_join(KnowledgeBase)
+            .filter(knowledge_base::Column::TenantId.eq(tenant_id))
+            .filter(document::Column::Id.is_in(document_ids.iter().copied()))
+            .all(&self.db)
+            .await?;
+        let ids: Vec<Uuid> = documents.iter().map(|doc| doc.id).collect();
+        let held = LegalHoldService::new(self.db.clone()).held_documents(&ids).await?;
+
+        let mut result = DocumentArchiveResult { document_ids: Vec::new(), skipped: Vec::new() };
+        let mut seen = HashSet::new();
+        for &document_id in document_ids.iter().filter(|id| seen.insert(**id)) {
+            if !ids.contains(&document_id) {
+                result.skipped.push(SkippedDocument { document_id, reason: ArchiveSkipReason::NotFound });
+            }
+        }
+
+        let now = Utc::now();
+        let txn = self.db.begin().await?;
+        for doc in documents {
+            let reason = if held.contains(&doc.id) {
+                Some(ArchiveSkipReason::LegalHold)
+            } else {
+                skip_reason(&doc.status, archive)
+            };
+            if let Some(reason) = reason {
+                result.skipped.push(SkippedDocument { document_id: doc.id, reason });
+                continue;
+            }
+
+            let document_id = doc.id;
+            let mut metadata = doc.metadata.clone();
+            let status = if archive {
+                set_archived_at(&mut metadata, Some(now));
+                DocumentStatus::Archived
+            } else {
+                set_archived_at(&mut metadata, None);
+                restored_status(doc.chunk_count)
+            };
+            let revision = doc.revision;
+            let mut active: document::ActiveModel = doc.into();
+            active.status = Set(status);
+            active.metadata = Set(metadata);
+            active.updated_at = Set(now.into());
+            update_with_revision(&txn, active, document::Column::Revision, revision, "文档").await?;
+            result.document_ids.push(document_id);
+        }
+        txn.commit().await?;
+
+        info!(
+            "批量{}文档完成: 租户={}, 成功={}, 跳过={}",
+            if archive { "归档" } else { "恢复" },
+            tenant_id,
+            result.document_ids.len(),
+            result.skipped.len()
+        );
+        Ok(result)
+    }
+
+    async fn find_knowledge_base(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+    ) -> Result<knowledge_base::Model, AiStudioError> {
+        KnowledgeBase::find_by_id(knowledge_base_id)
+            .filter(knowledge_base::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("知识库"))
+    }
+
+    async fn set_knowledge_base_status(
+        &self,
+        kb: knowledge_base::Model,
+        status: KnowledgeBaseStatus,
+    ) -> Result<knowledge_base::Model, AiStudioError> {
+        let revision = kb.revision;
+        let mut active: knowledge_base::ActiveModel = kb.into();
+        active.status = Set(status);
+        active.updated_at = Set(Utc::now().into());
+        update_with_revision(&self.db, active, knowledge_base::Column::Revision, revision, "知识库").await
+    }
+}
+
+/// 文档不能归档（`archive` 为 true）或恢复时的原因
+fn skip_reason(status: &DocumentStatus, archive: bool) -> Option<ArchiveSkipReason> {
+    match (status, archive) {
+        (DocumentStatus::Processing, true) => Some(ArchiveSkipReason::Processing),
+        (DocumentStatus::Archived, true) => Some(ArchiveSkipReason::AlreadyArchived),
+        (DocumentStatus::Archived, false) => None,
+        (_, true) => None,
+        (_, false) => Some(ArchiveSkipReason::NotArchived),
+    }
+}
+
+/// 恢复后的文档状态
+fn restored_status(chunk_count: i32) -> DocumentStatus {
+    if chunk_count > 0 {
+        DocumentStatus::Completed
+    } else {
+        DocumentStatus::Pending
+    }
+}
+
+/// 在文档元数据中记录或清除归档时间，恢复时一并清除重复文档检测写入的 `duplicate_of`
+fn set_archived_at(metadata: &mut Value, archived_at: Option<chrono::DateTime<Utc>>) {
+    if !metadata.is_object() {
+        *metadata = json!({});
+    }
+    if let Some(object) = metadata.as_object_mut() {
+        match archived_at {
+            Some(at) => {
+                object.insert("archived_at".to_string(), json!(at));
+            }
+            None => {
+                object.remove("archived_at");
+                object.remove("duplicate_of");
+            }
+        }
+    }
+}
+
+fn knowledge_base_result(kb: &knowledge_base::Model) -> KnowledgeBaseArchiveResult {
+    KnowledgeBaseArchiveResult {
+        knowledge_base_id: kb.id,
+        archived: kb.is_archived(),
+        document_count: kb.document_count,
+        updated_at: kb.updated_at.with_timezone(&Utc),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_transitions() {
+        assert_eq!(skip_reason(&DocumentStatus::Completed, true), None);
+        assert_eq!(skip_reason(&DocumentStatus::Failed, true), None);
+        assert_eq!(skip_reason(&DocumentStatus::Processing, true), Some(ArchiveSkipReason::Processing));
+        assert_eq!(skip_reason(&DocumentStatus::Archived, true), Some(ArchiveSkipReason::AlreadyArchived));
+        assert_eq!(skip_reason(&DocumentStatus::Archived, false), None);
+        assert_eq!(skip_reason(&DocumentStatus::Completed, false), Some(ArchiveSkipReason::NotArchived));
+
+        assert_eq!(restored_status(12), DocumentStatus::Completed);
+        assert_eq!(restored_status(0), DocumentStatus::Pending);
+    }
+
+    #[test]
+    fn test_archived_at_metadata() {
+        let mut metadata = json!({ "tags": ["hr"], "duplicate_of": Uuid::nil() });
+        set_archived_at(&mut metadata, Some(Utc::now()));
+        assert!(metadata.get("archived_at").is_some());
+
+        set_archived_at(&mut metadata, None);
+        assert_eq!(metadata, json!({ "tags": ["hr"] }));
+
+        let mut metadata = Value::Null;
+        set_archived_at(&mut metadata, None);
+        assert_eq!(metadata, json!({}));
+    }
+}
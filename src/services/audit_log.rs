@@ -0,0 +1,290 @@
+// 审计日志服务
+// 记录租户内的敏感操作与业务事件；提权期间的请求带有提权 ID，便于事后按提权复核
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult, Statement};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{instrument, warn};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::errors::AiStudioError;
+use crate::plugins::event_bus::{event_matches, SystemEvent, SystemEventBus};
+
+/// 默认查询条数
+const DEFAULT_LIMIT: u64 = 100;
+
+/// 单次查询最大条数
+const MAX_LIMIT: u64 = 1000;
+
+/// 写入审计日志的系统事件，执行过程类事件（工作流开始、完成等）不记录
+const AUDITED_EVENT_PATTERNS: [&str; 5] = [
+    "document.*",
+    "agent.*",
+    "workflow.published",
+    "member.*",
+    "user.registered",
+];
+
+/// 待写入的审计事件
+#[derive(Debug, Clone)]
+pub struct NewAuditLog {
+    pub tenant_id: Uuid,
+    pub user_id: Option<Uuid>,
+    /// 操作类型，如 `elevation.approved`、`api.request`
+    pub action: String,
+    pub method: Option<String>,
+    pub path: Option<String>,
+    pub status_code: Option<u16>,
+    /// 操作对象类型，如 `document`、`agent`
+    pub resource_type: Option<String>,
+    pub resource_id: Option<Uuid>,
+    /// 操作发生时生效的提权 ID
+    pub elevation_id: Option<Uuid>,
+    pub ip_address: Option<String>,
+    pub details: serde_json::Value,
+}
+
+impl NewAuditLog {
+    /// 创建不关联请求信息的审计事件
+    pub fn event(tenant_id: Uuid, user_id: Uuid, action: impl Into<String>) -> Self {
+        Self {
+            tenant_id,
+            user_id: Some(user_id),
+            action: action.into(),
+            method: None,
+            path: None,
+            status_code: None,
+            resource_type: None,
+            resource_id: None,
+            elevation_id: None,
+            ip_address: None,
+            details: serde_json::json!({}),
+        }
+    }
+
+    /// 关联操作对象
+    pub fn with_resource(mut self, resource_type: impl Into<String>, resource_id: Option<Uuid>) -> Self {
+        self.resource_type = Some(resource_type.into());
+        self.resource_id = resource_id;
+        self
+    }
+
+    /// 关联提权 ID
+    pub fn with_elevation(mut self, elevation_id: Option<Uuid>) -> Self {
+        self.elevation_id = elevation_id;
+        self
+    }
+
+    /// 附加详情
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// 审计日志记录
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AuditLogEntry {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub action: String,
+    pub method: Option<String>,
+    pub path: Option<String>,
+    pub status_code: Option<i32>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<Uuid>,
+    /// 操作发生时生效的提权 ID
+    pub elevation_id: Option<Uuid>,
+    pub ip_address: Option<String>,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 审计日志查询参数
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+pub struct AuditLogQuery {
+    /// 按用户过滤
+    pub user_id: Option<Uuid>,
+    /// 按提权过滤
+    pub elevation_id: Option<Uuid>,
+    /// 返回条数，默认 100，最多 1000
+    pub limit: Option<u64>,
+}
+
+/// 审计日志服务
+pub struct AuditLogService {
+    db: DatabaseConnection,
+}
+
+impl AuditLogService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 写入审计事件
+    #[instrument(skip(self, entry), fields(tenant_id = %entry.tenant_id, action = %entry.action))]
+    pub async fn record(&self, entry: NewAuditLog) -> Result<(), AiStudioError> {
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO audit_logs
+                    (id, tenant_id, user_id, action, method, path, status_code,
+                     resource_type, resource_id, elevation_id, ip_address, details, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                "#,
+                [
+                    Uuid::new_v4().into(),
+                    entry.tenant_id.into(),
+                    entry.user_id.into(),
+                    entry.action.into(),
+                    entry.method.into(),
+                    entry.path.into(),
+                    entry.status_code.map(i32::from).into(),
+                    entry.resource_type.into(),
+                    entry.resource_id.into(),
+                    entry.elevation_id.into(),
+                    entry.ip_address.into(),
+                    entry.details.into(),
+                    Utc::now().into(),
+                ],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// 查询租户审计日志，按时间倒序
+    pub async fn list(&self, tenant_id: Uuid, query: &AuditLogQuery) -> Result<Vec<AuditLogEntry>, AiStudioError> {
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT * FROM audit_logs
+                WHERE tenant_id = $1
+                  AND ($2::uuid IS NULL OR user_id = $2)
+                  AND ($3::uuid IS NULL OR elevation_id = $3)
+                ORDER BY created_at DESC
+                LIMIT $4
+                "#,
+                [tenant_id.into(), query.user_id.into(), query.elevation_id.into(), (limit as i64).into()],
+            ))
+            .await?;
+        rows.iter().map(audit_log_from_row).collect()
+    }
+
+    /// 启动事件记录，将租户内的业务事件写入审计日志
+    pub fn start_event_recorder(db: DatabaseConnection) {
+        let service = Arc::new(Self::new(db));
+        let mut receiver = SystemEventBus::global().subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        let Some(entry) = audit_entry_from_event(&event) else {
+                            continue;
+                        };
+                        if let Err(e) = service.record(entry).await {
+                            warn!("记录审计事件失败: event_type={}, error={}", event.event_type, e);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("审计事件记录滞后，丢弃 {} 个事件", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+/// 将系统事件转换为审计记录；无租户或无需审计的事件返回 `None`
+///
+/// 资源类型取事件类型的前缀，资源 ID 取载荷中的 `<资源类型>_id`，成员事件取 `user_id`。
+fn audit_entry_from_event(event: &SystemEvent) -> Option<NewAuditLog> {
+    let tenant_id = event.tenant_id?;
+    if !AUDITED_EVENT_PATTERNS.iter().any(|pattern| event_matches(pattern, &event.event_type)) {
+        return None;
+    }
+
+    let resource_type = event.event_type.split('.').next().unwrap_or_default();
+    let id_field = match resource_type {
+        "member" => "user_id".to_string(),
+        other => format!("{}_id", other),
+    };
+    let resource_id = event.payload
+        .get(&id_field)
+        .and_then(|v| v.as_str())
+        .and_then(|v| Uuid::parse_str(v).ok());
+
+    Some(NewAuditLog {
+        tenant_id,
+        user_id: event.actor_id,
+        action: event.event_type.clone(),
+        method: None,
+        path: None,
+        status_code: None,
+        resource_type: Some(resource_type.to_string()),
+        resource_id,
+        elevation_id: None,
+        ip_address: None,
+        details: event.payload.clone(),
+    })
+}
+
+fn audit_log_from_row(row: &QueryResult) -> Result<AuditLogEntry, AiStudioError> {
+    Ok(AuditLogEntry {
+        id: row.try_get("", "id")?,
+        user_id: row.try_get("", "user_id")?,
+        action: row.try_get("", "action")?,
+        method: row.try_get("", "method")?,
+        path: row.try_get("", "path")?,
+        status_code: row.try_get("", "status_code")?,
+        resource_type: row.try_get("", "resource_type")?,
+        resource_id: row.try_get("", "resource_id")?,
+        elevation_id: row.try_get("", "elevation_id")?,
+        ip_address: row.try_get("", "ip_address")?,
+        details: row.try_get("", "details")?,
+        created_at: row.try_get("", "created_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::event_bus::event_types;
+
+    #[test]
+    fn test_audit_entry_from_event() {
+        let tenant_id = Uuid::new_v4();
+        let actor_id = Uuid::new_v4();
+        let document_id = Uuid::new_v4();
+
+        let event = SystemEvent::new(
+            event_types::DOCUMENT_CREATED,
+            Some(tenant_id),
+            serde_json::json!({ "document_id": document_id, "title": "季度报告" }),
+        )
+        .with_actor(Some(actor_id));
+        let entry = audit_entry_from_event(&event).unwrap();
+        assert_eq!(entry.action, "document.created");
+        assert_eq!(entry.user_id, Some(actor_id));
+        assert_eq!(entry.resource_type.as_deref(), Some("document"));
+        assert_eq!(entry.resource_id, Some(document_id));
+
+        let member_id = Uuid::new_v4();
+        let event = SystemEvent::new(event_types::MEMBER_INVITED, Some(tenant_id), serde_json::json!({ "user_id": member_id }));
+        assert_eq!(audit_entry_from_event(&event).unwrap().resource_id, Some(member_id));
+
+        // 执行过程事件与无租户事件不记录
+        let event = SystemEvent::new(event_types::WORKFLOW_COMPLETED, Some(tenant_id), serde_json::json!({}));
+        assert!(audit_entry_from_event(&event).is_none());
+        let event = SystemEvent::new(event_types::DOCUMENT_CREATED, None, serde_json::json!({}));
+        assert!(audit_entry_from_event(&event).is_none());
+    }
+}
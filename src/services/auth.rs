@@ -11,7 +11,12 @@ use sea_orm::{DatabaseConnection, EntityTrait, ColumnTrait, Set, ActiveModelTrai
 
 use crate::errors::AiStudioError;
 use crate::db::entities::{user, tenant, session, Tenant, User, Session};
+use crate::db::entities::usage_anomaly::AnomalyMetric;
 use crate::api::middleware::auth::JwtUtils;
+use crate::plugins::event_bus::{event_types, SystemEvent, SystemEventBus};
+use crate::services::cache::{self, CacheNamespace};
+use crate::services::passkey::count_user_passkeys;
+use crate::services::usage_anomaly::record_usage;
 
 /// 登录请求
 #[derive(Debug, Clone, Deserialize, ToSchema)]
@@ -41,6 +46,8 @@ pub struct LoginResponse {
     pub user: UserInfo,
     /// 租户信息
     pub tenant: TenantInfo,
+    /// 租户要求该用户使用通行密钥登录但其尚未注册，需尽快注册
+    pub passkey_enrollment_required: bool,
 }
 
 /// 刷新令牌请求
@@ -113,6 +120,17 @@ pub struct PasswordResetConfirmRequest {
     pub new_password_confirm: String,
 }
 
+/// 接受邀请请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AcceptInvitationRequest {
+    /// 邀请令牌
+    pub token: String,
+    /// 密码
+    pub password: String,
+    /// 确认密码
+    pub password_confirm: String,
+}
+
 /// 邮箱验证查询参数
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct EmailVerificationQuery {
@@ -199,6 +217,7 @@ impl AuthService {
             .map_err(|e| AiStudioError::internal(format!("密码验证失败: {}", e)))?
         {
             warn!(username = %request.username, "密码验证失败");
+            record_usage(user.tenant_id, AnomalyMetric::FailedLogins, None, 1);
             return Err(AiStudioError::unauthorized("用户名或密码错误".to_string()));
         }
 
@@ -219,8 +238,55 @@ impl AuthService {
             return Err(AiStudioError::forbidden("租户已被暂停或停用".to_string()));
         }
 
+        // 租户要求该角色使用通行密钥时，已注册通行密钥的用户不能再用密码登录
+        let auth_policy = tenant.get_config().unwrap_or_default().auth_policy;
+        let passkey_enrollment_required = if auth_policy.requires_passkey(&user.role.to_string()) {
+            if count_user_passkeys(&self.db, user.id).await? > 0 {
+                warn!(user_id = %user.id, "租户要求使用通行密钥登录，拒绝密码登录");
+                return Err(AiStudioError::forbidden("该账户须使用通行密钥登录".to_string()));
+            }
+            true
+        } else {
+            false
+        };
+
+        self.issue_login(user, tenant, request.remember_me.unwrap_or(false), client_ip, user_agent, passkey_enrollment_required)
+            .await
+    }
+
+    /// 通行密钥校验通过后为用户签发令牌
+    #[instrument(skip(self, user))]
+    pub async fn login_with_passkey(
+        &self,
+        user: user::Model,
+        remember_me: bool,
+        client_ip: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<LoginResponse, AiStudioError> {
+        let tenant = Tenant::find_by_id(user.tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+        if tenant.status != tenant::TenantStatus::Active {
+            return Err(AiStudioError::forbidden("租户已被暂停或停用".to_string()));
+        }
+
+        self.issue_login(user, tenant, remember_me, client_ip, user_agent, false).await
+    }
+
+    /// 生成令牌、创建会话并组装登录响应
+    async fn issue_login(
+        &self,
+        user: user::Model,
+        tenant: tenant::Model,
+        remember_me: bool,
+        client_ip: Option<String>,
+        user_agent: Option<String>,
+        passkey_enrollment_required: bool,
+    ) -> Result<LoginResponse, AiStudioError> {
         // 生成令牌
-        let expires_hours = if request.remember_me.unwrap_or(false) {
+        let expires_hours = if remember_me {
             self.access_token_expires_hours * 7 // 记住我时延长到 7 倍
         } else {
             self.access_token_expires_hours
@@ -278,6 +344,7 @@ impl AuthService {
                 display_name: tenant.display_name,
                 status: format!("{:?}", tenant.status),
             },
+            passkey_enrollment_required,
         })
     }
 
@@ -420,6 +487,16 @@ impl AuthService {
             .await?;
         info!(user_id = %created_user.id, username = %created_user.username, "新用户已创建");
 
+        SystemEventBus::global().publish(SystemEvent::new(
+            event_types::USER_REGISTERED,
+            Some(created_user.tenant_id),
+            serde_json::json!({
+                "user_id": created_user.id,
+                "username": created_user.username,
+                "email": created_user.email,
+            }),
+        ));
+
         // 注册时不需要创建会话，用户需要登录才能获得会话
         // self.create_default_session(&created_user, client_ip, user_agent).await?;
 
@@ -803,4 +880,58 @@ impl AuthService {
         info!("密码重置成功");
         Ok(())
     }
+
+    /// 接受邀请：设置密码并激活受邀用户
+    #[instrument(skip(self, request))]
+    pub async fn accept_invitation(&self, request: AcceptInvitationRequest) -> Result<UserInfo, AiStudioError> {
+        info!("接受用户邀请");
+
+        if request.password != request.password_confirm {
+            return Err(AiStudioError::validation("password", "密码确认不匹配"));
+        }
+
+        // 邀请令牌与密码重置令牌共用字段，只有待激活用户的令牌才是邀请
+        let user = self.find_user_by_reset_token(&request.token).await
+            .map_err(|_| AiStudioError::unauthorized("无效的邀请令牌".to_string()))?;
+        if user.status != user::UserStatus::Pending {
+            return Err(AiStudioError::unauthorized("无效的邀请令牌".to_string()));
+        }
+        match user.password_reset_expires_at {
+            Some(expires_at) if expires_at > Utc::now() => {}
+            _ => return Err(AiStudioError::unauthorized("邀请已过期，请联系管理员重新邀请".to_string())),
+        }
+
+        self.validate_password_strength(&request.password)?;
+
+        let password_hash = hash(&request.password, DEFAULT_COST)
+            .map_err(|e| AiStudioError::internal(format!("密码哈希失败: {}", e)))?;
+
+        let now = Utc::now();
+        let mut user_active: user::ActiveModel = user.into();
+        user_active.password_hash = Set(password_hash);
+        user_active.status = Set(user::UserStatus::Active);
+        // 能收到邀请邮件即说明邮箱有效
+        user_active.email_verified = Set(true);
+        user_active.email_verified_at = Set(Some(now.into()));
+        user_active.password_reset_token = Set(None);
+        user_active.password_reset_expires_at = Set(None);
+        user_active.updated_at = Set(now.into());
+
+        let activated_user = user_active.update(&self.db).await?;
+        cache::invalidate(CacheNamespace::User, activated_user.id).await;
+        info!(user_id = %activated_user.id, "受邀用户已激活");
+
+        SystemEventBus::global().publish(SystemEvent::new(
+            event_types::USER_REGISTERED,
+            Some(activated_user.tenant_id),
+            serde_json::json!({
+                "user_id": activated_user.id,
+                "username": activated_user.username,
+                "email": activated_user.email,
+                "invited": true,
+            }),
+        ));
+
+        self.get_user_info(activated_user.id).await
+    }
 }
\ No newline at end of file
@@ -0,0 +1,327 @@
+// 计费估算服务
+// 按套餐定价估算租户月度费用，并模拟套餐变更或预计用量下的费用与配额超限情况
+
+use chrono::{Datelike, DateTime, TimeZone, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, Statement};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::{BillingConfig, PlanPricing};
+use crate::db::entities::tenant::{self, TenantPlan, TenantUsageStats};
+use crate::db::entities::Tenant;
+use crate::errors::AiStudioError;
+use crate::services::quota::{evaluate_quota, QuotaType, QuotaUsage};
+
+/// 计费用量（当月）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct BillableUsage {
+    /// 文档数
+    pub documents: u64,
+    /// 模型消耗的 token 数
+    pub tokens: u64,
+    /// 工作流与 Agent 执行次数
+    pub executions: u64,
+}
+
+/// 费用明细项
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CostLineItem {
+    /// 计费项：`documents`、`tokens`、`executions`
+    pub item: String,
+    /// 用量
+    pub quantity: u64,
+    /// 套餐包含的额度
+    pub included: u64,
+    /// 超出额度需计费的用量
+    pub billable: u64,
+    /// 金额
+    pub amount: f64,
+}
+
+/// 费用估算
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CostEstimate {
+    /// 套餐
+    pub plan: TenantPlan,
+    /// 币种
+    pub currency: String,
+    /// 月费
+    pub monthly_fee: f64,
+    /// 超额用量明细
+    pub items: Vec<CostLineItem>,
+    /// 合计
+    pub total: f64,
+}
+
+/// 预计用量，未填写的项沿用当前用量
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ProjectedUsage {
+    /// 用户数
+    pub users: Option<u32>,
+    /// 知识库数
+    pub knowledge_bases: Option<u32>,
+    /// 文档数
+    pub documents: Option<u32>,
+    /// 存储使用量（字节）
+    pub storage_bytes: Option<u64>,
+    /// 每月 API 调用数
+    pub monthly_api_calls: Option<u32>,
+    /// 每日 AI 查询数
+    pub daily_ai_queries: Option<u32>,
+    /// 每月 token 数
+    pub monthly_tokens: Option<u64>,
+    /// 每月执行次数
+    pub monthly_executions: Option<u64>,
+}
+
+/// 计费模拟请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct BillingSimulationRequest {
+    /// 模拟切换到的套餐，不填表示保持当前套餐
+    pub plan: Option<TenantPlan>,
+    /// 预计用量
+    #[serde(default)]
+    pub projected: ProjectedUsage,
+}
+
+/// 计费模拟结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BillingSimulationResponse {
+    /// 租户 ID
+    pub tenant_id: Uuid,
+    /// 当前套餐
+    pub current_plan: TenantPlan,
+    /// 模拟套餐
+    pub simulated_plan: TenantPlan,
+    /// 模拟后的配额使用情况
+    pub quotas: Vec<QuotaUsage>,
+    /// 模拟后会超限的配额
+    pub exceeded_quotas: Vec<QuotaType>,
+    /// 按当前套餐与当月用量估算的费用
+    pub current_cost: CostEstimate,
+    /// 按模拟套餐与预计用量估算的费用
+    pub simulated_cost: CostEstimate,
+    /// 模拟费用与当前费用之差
+    pub cost_difference: f64,
+}
+
+/// 套餐对应的定价
+pub fn plan_pricing(config: &BillingConfig, plan: TenantPlan) -> &PlanPricing {
+    match plan {
+        TenantPlan::Free => &config.free,
+        TenantPlan::Standard => &config.standard,
+        TenantPlan::Enterprise => &config.enterprise,
+    }
+}
+
+/// 按套餐定价估算月度费用：月费加上各计费项超出包含额度部分的费用
+pub fn estimate_cost(config: &BillingConfig, plan: TenantPlan, usage: &BillableUsage) -> CostEstimate {
+    let pricing = plan_pricing(config, plan);
+    let items = vec![
+        line_item("documents", usage.documents, pricing.included_documents, pricing.price_per_1k_documents / 1000.0),
+        line_item("tokens", usage.tokens, pricing.included_tokens, pricing.price_per_1k_tokens / 1000.0),
+        line_item("executions", usage.executions, pricing.included_executions, pricing.price_per_execution),
+    ];
+    let total = round_cents(pricing.monthly_fee + items.iter().map(|item| item.amount).sum::<f64>());
+
+    CostEstimate {
+        plan,
+        currency: config.currency.clone(),
+        monthly_fee: pricing.monthly_fee,
+        items,
+        total,
+    }
+}
+
+fn line_item(item: &str, quantity: u64, included: u64, unit_price: f64) -> CostLineItem {
+    let billable = quantity.saturating_sub(included);
+    CostLineItem {
+        item: item.to_string(),
+        quantity,
+        included,
+        billable,
+        amount: round_cents(billable as f64 * unit_price),
+    }
+}
+
+fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// 将预计用量叠加到当前使用统计上
+fn apply_projection(mut stats: TenantUsageStats, projected: &ProjectedUsage) -> TenantUsageStats {
+    if let Some(users) = projected.users {
+        stats.current_users = users;
+    }
+    if let Some(knowledge_bases) = projected.knowledge_bases {
+        stats.current_knowledge_bases = knowledge_bases;
+    }
+    if let Some(documents) = projected.documents {
+        stats.current_documents = documents;
+    }
+    if let Some(storage_bytes) = projected.storage_bytes {
+        stats.current_storage_bytes = storage_bytes;
+    }
+    if let Some(monthly_api_calls) = projected.monthly_api_calls {
+        stats.monthly_api_calls = monthly_api_calls;
+    }
+    if let Some(daily_ai_queries) = projected.daily_ai_queries {
+        stats.daily_ai_queries = daily_ai_queries;
+    }
+    stats
+}
+
+/// 计费估算服务
+pub struct BillingService {
+    db: DatabaseConnection,
+    config: BillingConfig,
+}
+
+impl BillingService {
+    /// 创建新的计费估算服务实例
+    pub fn new(db: DatabaseConnection, config: BillingConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// 模拟套餐变更或预计用量下的费用与配额情况
+    ///
+    /// 保持当前套餐时沿用租户的配额限制，切换套餐时使用目标套餐的标准配额。
+    #[instrument(skip(self))]
+    pub async fn simulate(
+        &self,
+        tenant_id: Uuid,
+        request: &BillingSimulationRequest,
+    ) -> Result<BillingSimulationResponse, AiStudioError> {
+        let tenant = Tenant::find_by_id(tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+        let current_plan = tenant.get_config()
+            .map_err(|e| AiStudioError::internal(format!("解析租户配置失败: {}", e)))?
+            .plan;
+        let simulated_plan = request.plan.unwrap_or(current_plan);
+
+        let limits = if simulated_plan == current_plan {
+            tenant.get_quota_limits()
+                .map_err(|e| AiStudioError::internal(format!("解析配额限制失败: {}", e)))?
+        } else {
+            simulated_plan.default_quota_limits()
+        };
+        let stats = tenant.get_usage_stats()
+            .map_err(|e| AiStudioError::internal(format!("解析使用统计失败: {}", e)))?;
+        let current_usage = self.current_billable_usage(&tenant, &stats).await?;
+
+        let projected_stats = apply_projection(stats, &request.projected);
+        let quotas: Vec<QuotaUsage> = QuotaType::ALL
+            .iter()
+            .map(|quota_type| evaluate_quota(quota_type, &limits, &projected_stats))
+            .collect();
+        let exceeded_quotas = quotas
+            .iter()
+            .filter(|usage| usage.is_exceeded)
+            .map(|usage| usage.quota_type.clone())
+            .collect();
+
+        let projected_usage = BillableUsage {
+            documents: projected_stats.current_documents as u64,
+            tokens: request.projected.monthly_tokens.unwrap_or(current_usage.tokens),
+            executions: request.projected.monthly_executions.unwrap_or(current_usage.executions),
+        };
+        let current_cost = estimate_cost(&self.config, current_plan, &current_usage);
+        let simulated_cost = estimate_cost(&self.config, simulated_plan, &projected_usage);
+        let cost_difference = round_cents(simulated_cost.total - current_cost.total);
+
+        Ok(BillingSimulationResponse {
+            tenant_id,
+            current_plan,
+            simulated_plan,
+            quotas,
+            exceeded_quotas,
+            current_cost,
+            simulated_cost,
+            cost_difference,
+        })
+    }
+
+    /// 当月计费用量
+    ///
+    /// token 数取自用量计数表，仅在启用用量异常检测时有记录。
+    async fn current_billable_usage(
+        &self,
+        tenant: &tenant::Model,
+        stats: &TenantUsageStats,
+    ) -> Result<BillableUsage, AiStudioError> {
+        let month_start = month_start(Utc::now());
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT
+                    (SELECT COALESCE(SUM(count), 0)::BIGINT FROM usage_counters
+                        WHERE tenant_id = $1 AND metric = 'tokens' AND bucket_start >= $2) AS tokens,
+                    (SELECT COUNT(*) FROM workflow_executions WHERE tenant_id = $1 AND created_at >= $2)
+                    + (SELECT COUNT(*) FROM agent_executions WHERE tenant_id = $1 AND created_at >= $2) AS executions
+                "#,
+                vec![tenant.id.into(), month_start.into()],
+            ))
+            .await?;
+
+        let (tokens, executions) = match row {
+            Some(row) => (row.try_get::<i64>("", "tokens")?, row.try_get::<i64>("", "executions")?),
+            None => (0, 0),
+        };
+
+        Ok(BillableUsage {
+            documents: stats.current_documents as u64,
+            tokens: tokens.max(0) as u64,
+            executions: executions.max(0) as u64,
+        })
+    }
+}
+
+/// 所在月份第一天零点（UTC）
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    #[test]
+    fn test_estimate_cost_charges_overage_only() {
+        let config = AppConfig::default().billing;
+        let within = BillableUsage { documents: 500, tokens: 1_000_000, executions: 100 };
+        assert_eq!(estimate_cost(&config, TenantPlan::Standard, &within).total, config.standard.monthly_fee);
+
+        let over = BillableUsage { documents: 3_000, tokens: 3_000_000, executions: 6_000 };
+        let estimate = estimate_cost(&config, TenantPlan::Standard, &over);
+        // 99 + 2000 文档 * 5/千 + 1M token * 0.002/千 + 1000 次 * 0.01
+        assert_eq!(estimate.total, 99.0 + 10.0 + 2.0 + 10.0);
+        assert_eq!(estimate.items[0].billable, 2_000);
+    }
+
+    #[test]
+    fn test_projection_uses_enforcement_calculator() {
+        let stats = TenantUsageStats {
+            current_users: 3,
+            current_knowledge_bases: 1,
+            current_documents: 80,
+            current_storage_bytes: 0,
+            monthly_api_calls: 0,
+            daily_ai_queries: 0,
+            last_updated: Utc::now().into(),
+        };
+        let projected = apply_projection(stats, &ProjectedUsage { documents: Some(150), ..Default::default() });
+        let usage = evaluate_quota(&QuotaType::Documents, &TenantPlan::Free.default_quota_limits(), &projected);
+        assert!(usage.is_exceeded);
+        assert_eq!(usage.current_usage, 150);
+
+        let users = evaluate_quota(&QuotaType::Users, &TenantPlan::Free.default_quota_limits(), &projected);
+        assert!(!users.is_exceeded);
+        assert_eq!(month_start(Utc.with_ymd_and_hms(2026, 3, 17, 8, 0, 0).unwrap()).day(), 1);
+    }
+}
@@ -0,0 +1,563 @@
+// 缓存服务
+// 为租户、用户与工作流定义等热点实体提供 Redis / 内存两种缓存后端，实体更新时主动失效并统计命中率
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use once_cell::sync::OnceCell;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::{CacheBackendKind, CacheConfig};
+use crate::db::entities::user;
+use crate::errors::AiStudioError;
+
+static GLOBAL_CACHE: OnceCell<Arc<CacheService>> = OnceCell::new();
+
+/// 缓存命名空间
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheNamespace {
+    /// 租户（按 ID）
+    Tenant,
+    /// 租户标识符到租户 ID 的映射
+    TenantSlug,
+    /// 用户访问信息
+    User,
+    /// 工作流定义
+    Workflow,
+    /// 路由级响应缓存
+    Route,
+    /// 用户生效中的临时提权
+    Elevation,
+}
+
+impl CacheNamespace {
+    pub const ALL: [CacheNamespace; 6] = [
+        CacheNamespace::Tenant,
+        CacheNamespace::TenantSlug,
+        CacheNamespace::User,
+        CacheNamespace::Workflow,
+        CacheNamespace::Route,
+        CacheNamespace::Elevation,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheNamespace::Tenant => "tenant",
+            CacheNamespace::TenantSlug => "tenant_slug",
+            CacheNamespace::User => "user",
+            CacheNamespace::Workflow => "workflow",
+            CacheNamespace::Route => "route",
+            CacheNamespace::Elevation => "elevation",
+        }
+    }
+}
+
+/// 缓存的用户访问信息
+///
+/// 只保留鉴权所需字段，不缓存密码哈希、二次验证密钥等敏感数据。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedUserAccess {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub role: user::UserRole,
+    pub status: user::UserStatus,
+    pub permissions: Vec<String>,
+}
+
+impl From<user::Model> for CachedUserAccess {
+    fn from(model: user::Model) -> Self {
+        let permissions = serde_json::from_value(model.permissions).unwrap_or_default();
+        Self {
+            id: model.id,
+            tenant_id: model.tenant_id,
+            role: model.role,
+            status: model.status,
+            permissions,
+        }
+    }
+}
+
+/// 缓存后端接口
+///
+/// 值为序列化后的 JSON 字符串，后端只负责存取与过期。
+#[async_trait::async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// 读取缓存项，不存在或已过期时返回 None
+    async fn get(&self, key: &str) -> Result<Option<String>, AiStudioError>;
+
+    /// 写入缓存项
+    async fn set(&self, key: &str, value: String, ttl: Duration) -> Result<(), AiStudioError>;
+
+    /// 删除缓存项
+    async fn delete(&self, key: &str) -> Result<(), AiStudioError>;
+}
+
+/// 基于 Redis 的缓存后端，多实例共享缓存与失效
+#[cfg(feature = "redis")]
+pub struct RedisCacheBackend {
+    client: redis::Client,
+    connection: tokio::sync::OnceCell<redis::aio::ConnectionManager>,
+}
+
+#[cfg(feature = "redis")]
+impl RedisCacheBackend {
+    /// 创建 Redis 缓存后端，连接在首次使用时建立
+    pub fn new(url: &str) -> Result<Self, AiStudioError> {
+        let client = redis::Client::open(url)
+            .map_err(|e| AiStudioError::configuration(format!("缓存 Redis 地址无效: {}", e)))?;
+        Ok(Self { client, connection: tokio::sync::OnceCell::new() })
+    }
+
+    async fn connection(&self) -> Result<redis::aio::ConnectionManager, AiStudioError> {
+        self.connection
+            .get_or_try_init(|| redis::aio::ConnectionManager::new(self.client.clone()))
+            .await
+            .cloned()
+            .map_err(|e| AiStudioError::external_service("cache", e.to_string()))
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait::async_trait]
+impl CacheBackend for RedisCacheBackend {
+    async fn get(&self, key: &str) -> Result<Option<String>, AiStudioError> {
+        let mut conn = self.connection().await?;
+        redis::cmd("GET")
+            .arg(key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| AiStudioError::external_service("cache", e.to_string()))
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) -> Result<(), AiStudioError> {
+        let mut conn = self.connection().await?;
+        redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("EX")
+            .arg(ttl.as_secs().max(1))
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .map_err(|e| AiStudioError::external_service("cache", e.to_string()))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AiStudioError> {
+        let mut conn = self.connection().await?;
+        redis::cmd("DEL")
+            .arg(key)
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .map_err(|e| AiStudioError::external_service("cache", e.to_string()))
+    }
+}
+
+/// 进程内缓存后端
+///
+/// 达到最大条目数时先清理过期项，仍然已满则淘汰最早过期的条目。
+pub struct InMemoryCacheBackend {
+    entries: RwLock<HashMap<String, (String, Instant)>>,
+    max_entries: usize,
+}
+
+impl InMemoryCacheBackend {
+    /// 创建内存缓存后端
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            max_entries: max_entries.max(1),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheBackend for InMemoryCacheBackend {
+    async fn get(&self, key: &str) -> Result<Option<String>, AiStudioError> {
+        let entries = self.entries.read().await;
+        Ok(entries
+            .get(key)
+            .filter(|(_, expires_at)| *expires_at > Instant::now())
+            .map(|(value, _)| value.clone()))
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) -> Result<(), AiStudioError> {
+        let now = Instant::now();
+        let mut entries = self.entries.write().await;
+
+        if entries.len() >= self.max_entries && !entries.contains_key(key) {
+            entries.retain(|_, (_, expires_at)| *expires_at > now);
+            if entries.len() >= self.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (_, expires_at))| *expires_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+
+        entries.insert(key.to_string(), (value, now + ttl));
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AiStudioError> {
+        self.entries.write().await.remove(key);
+        Ok(())
+    }
+}
+
+/// 命名空间命中统计
+#[derive(Default)]
+struct NamespaceCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// 命名空间缓存指标
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CacheNamespaceMetrics {
+    /// 命名空间
+    pub namespace: CacheNamespace,
+    /// 命中次数
+    pub hits: u64,
+    /// 未命中次数
+    pub misses: u64,
+    /// 命中率（0-1），无请求时为 0
+    pub hit_rate: f64,
+    /// 主动失效次数
+    pub invalidations: u64,
+    /// 后端错误次数（出错时直接回源）
+    pub errors: u64,
+}
+
+/// 缓存指标
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CacheMetrics {
+    /// 缓存后端
+    pub backend: String,
+    /// 缓存项存活时间（秒）
+    pub ttl_secs: u64,
+    /// 总命中次数
+    pub hits: u64,
+    /// 总未命中次数
+    pub misses: u64,
+    /// 总命中率（0-1）
+    pub hit_rate: f64,
+    /// 各命名空间指标
+    pub namespaces: Vec<CacheNamespaceMetrics>,
+}
+
+fn hit_rate(hits: u64, misses: u64) -> f64 {
+    let total = hits + misses;
+    if total == 0 { 0.0 } else { hits as f64 / total as f64 }
+}
+
+/// 缓存服务
+///
+/// 缓存不可用时直接回源，缓存故障不影响请求处理。
+pub struct CacheService {
+    backend: Arc<dyn CacheBackend>,
+    backend_name: &'static str,
+    config: CacheConfig,
+    counters: HashMap<CacheNamespace, NamespaceCounters>,
+}
+
+impl CacheService {
+    /// 创建缓存服务实例
+    pub fn new(config: CacheConfig, backend: Arc<dyn CacheBackend>, backend_name: &'static str) -> Self {
+        let counters = CacheNamespace::ALL
+            .into_iter()
+            .map(|namespace| (namespace, NamespaceCounters::default()))
+            .collect();
+        Self { backend, backend_name, config, counters }
+    }
+
+    /// 按配置创建缓存服务
+    ///
+    /// 未启用 redis 特性时 Redis 后端退化为内存后端。
+    pub fn from_config(config: &crate::config::AppConfig) -> Result<Self, AiStudioError> {
+        let cache_config = config.cache.clone();
+        match cache_config.backend {
+            #[cfg(feature = "redis")]
+            CacheBackendKind::Redis => {
+                let backend = Arc::new(RedisCacheBackend::new(&config.redis.url)?);
+                Ok(Self::new(cache_config, backend, "redis"))
+            }
+            #[cfg(not(feature = "redis"))]
+            CacheBackendKind::Redis => {
+                warn!("未启用 redis 特性，缓存使用进程内后端");
+                let backend = Arc::new(InMemoryCacheBackend::new(cache_config.max_entries));
+                Ok(Self::new(cache_config, backend, "memory"))
+            }
+            CacheBackendKind::Memory => {
+                let backend = Arc::new(InMemoryCacheBackend::new(cache_config.max_entries));
+                Ok(Self::new(cache_config, backend, "memory"))
+            }
+        }
+    }
+
+    /// 安装全局缓存服务
+    pub fn install_global(service: Arc<CacheService>) -> Result<(), AiStudioError> {
+        GLOBAL_CACHE.set(service)
+            .map_err(|_| AiStudioError::internal("缓存服务已经初始化"))
+    }
+
+    /// 全局缓存服务，未启用缓存时为空
+    pub fn global() -> Option<Arc<CacheService>> {
+        GLOBAL_CACHE.get().cloned()
+    }
+
+    fn key(&self, namespace: CacheNamespace, key: &str) -> String {
+        format!("{}:{}:{}", self.config.key_prefix, namespace.as_str(), key)
+    }
+
+    fn counters(&self, namespace: CacheNamespace) -> &NamespaceCounters {
+        &self.counters[&namespace]
+    }
+
+    /// 读取缓存，未命中时调用 `loader` 回源并写入缓存
+    ///
+    /// 回源结果为 None 时不缓存，避免新建实体在存活时间内不可见。
+    pub async fn get_or_load<T, F, Fut>(
+        &self,
+        namespace: CacheNamespace,
+        key: &str,
+        loader: F,
+    ) -> Result<Option<T>, AiStudioError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Option<T>, AiStudioError>>,
+    {
+        let cache_key = self.key(namespace, key);
+        let counters = self.counters(namespace);
+
+        match self.backend.get(&cache_key).await {
+            Ok(Some(cached)) => match serde_json::from_str(&cached) {
+                Ok(value) => {
+                    counters.hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(Some(value));
+                }
+                Err(e) => {
+                    // 实体结构变更后旧缓存无法解析，按未命中处理并覆盖
+                    debug!(key = %cache_key, error = %e, "缓存项解析失败");
+                }
+            },
+            Ok(None) => {}
+            Err(e) => {
+                counters.errors.fetch_add(1, Ordering::Relaxed);
+                warn!(key = %cache_key, error = %e, "读取缓存失败，直接回源");
+            }
+        }
+
+        counters.misses.fetch_add(1, Ordering::Relaxed);
+        let value = loader().await?;
+
+        if let Some(value) = &value {
+            match serde_json::to_string(value) {
+                Ok(serialized) => {
+                    let ttl = Duration::from_secs(self.config.ttl_secs);
+                    if let Err(e) = self.backend.set(&cache_key, serialized, ttl).await {
+                        counters.errors.fetch_add(1, Ordering::Relaxed);
+                        warn!(key = %cache_key, error = %e, "写入缓存失败");
+                    }
+                }
+                Err(e) => warn!(key = %cache_key, error = %e, "序列化缓存项失败"),
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// 只读取缓存，不回源；未命中、解析失败或后端出错时返回 None
+    pub async fn get<T: DeserializeOwned>(&self, namespace: CacheNamespace, key: &str) -> Option<T> {
+        let cache_key = self.key(namespace, key);
+        let counters = self.counters(namespace);
+
+        let cached = match self.backend.get(&cache_key).await {
+            Ok(cached) => cached,
+            Err(e) => {
+                counters.errors.fetch_add(1, Ordering::Relaxed);
+                warn!(key = %cache_key, error = %e, "读取缓存失败");
+                None
+            }
+        };
+        match cached.and_then(|cached| serde_json::from_str(&cached).ok()) {
+            Some(value) => {
+                counters.hits.fetch_add(1, Ordering::Relaxed);
+                Some(value)
+            }
+            None => {
+                counters.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// 写入最新值，用于高频更新的字段（如配额用量），避免每次更新都使缓存失效
+    pub async fn put<T: Serialize>(&self, namespace: CacheNamespace, key: &str, value: &T) {
+        self.put_with_ttl(namespace, key, value, Duration::from_secs(self.config.ttl_secs)).await;
+    }
+
+    /// 按指定存活时间写入缓存项
+    pub async fn put_with_ttl<T: Serialize>(&self, namespace: CacheNamespace, key: &str, value: &T, ttl: Duration) {
+        let cache_key = self.key(namespace, key);
+        let result = match serde_json::to_string(value) {
+            Ok(serialized) => self.backend.set(&cache_key, serialized, ttl).await,
+            Err(e) => Err(AiStudioError::internal(format!("序列化缓存项失败: {}", e))),
+        };
+
+        if let Err(e) = result {
+            self.counters(namespace).errors.fetch_add(1, Ordering::Relaxed);
+            warn!(key = %cache_key, error = %e, "写入缓存失败");
+            // 写入失败时删除旧值，避免读到过期数据
+            self.invalidate(namespace, key).await;
+        }
+    }
+
+    /// 使缓存项失效
+    pub async fn invalidate(&self, namespace: CacheNamespace, key: &str) {
+        let cache_key = self.key(namespace, key);
+        let counters = self.counters(namespace);
+        counters.invalidations.fetch_add(1, Ordering::Relaxed);
+
+        if let Err(e) = self.backend.delete(&cache_key).await {
+            counters.errors.fetch_add(1, Ordering::Relaxed);
+            warn!(key = %cache_key, error = %e, "缓存失效失败");
+        }
+    }
+
+    /// 获取缓存指标
+    pub fn metrics(&self) -> CacheMetrics {
+        let namespaces: Vec<CacheNamespaceMetrics> = CacheNamespace::ALL
+            .into_iter()
+            .map(|namespace| {
+                let counters = self.counters(namespace);
+                let hits = counters.hits.load(Ordering::Relaxed);
+                let misses = counters.misses.load(Ordering::Relaxed);
+                CacheNamespaceMetrics {
+                    namespace,
+                    hits,
+                    misses,
+                    hit_rate: hit_rate(hits, misses),
+                    invalidations: counters.invalidations.load(Ordering::Relaxed),
+                    errors: counters.errors.load(Ordering::Relaxed),
+                }
+            })
+            .collect();
+
+        let hits = namespaces.iter().map(|m| m.hits).sum();
+        let misses = namespaces.iter().map(|m| m.misses).sum();
+        CacheMetrics {
+            backend: self.backend_name.to_string(),
+            ttl_secs: self.config.ttl_secs,
+            hits,
+            misses,
+            hit_rate: hit_rate(hits, misses),
+            namespaces,
+        }
+    }
+}
+
+/// 经全局缓存读取，未启用缓存时直接回源
+pub async fn cached<T, F, Fut>(namespace: CacheNamespace, key: &str, loader: F) -> Result<Option<T>, AiStudioError>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<Option<T>, AiStudioError>>,
+{
+    match CacheService::global() {
+        Some(cache) => cache.get_or_load(namespace, key, loader).await,
+        None => loader().await,
+    }
+}
+
+/// 使全局缓存中的实体失效，在实体更新或删除后调用
+pub async fn invalidate(namespace: CacheNamespace, id: Uuid) {
+    if let Some(cache) = CacheService::global() {
+        cache.invalidate(namespace, &id.to_string()).await;
+    }
+}
+
+/// 将实体最新值写入全局缓存
+pub async fn refresh<T: Serialize>(namespace: CacheNamespace, id: Uuid, value: &T) {
+    if let Some(cache) = CacheService::global() {
+        cache.put(namespace, &id.to_string(), value).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(max_entries: usize) -> CacheService {
+        let mut config = crate::config::AppConfig::default().cache;
+        config.max_entries = max_entries;
+        CacheService::new(config, Arc::new(InMemoryCacheBackend::new(max_entries)), "memory")
+    }
+
+    #[tokio::test]
+    async fn test_get_or_load_hits_after_first_load() {
+        let cache = service(10);
+        let loads = &AtomicU64::new(0);
+        let load = move || async move {
+            loads.fetch_add(1, Ordering::Relaxed);
+            Ok(Some("acme".to_string()))
+        };
+
+        let first: Option<String> = cache.get_or_load(CacheNamespace::Tenant, "t1", load).await.unwrap();
+        let second: Option<String> = cache.get_or_load(CacheNamespace::Tenant, "t1", load).await.unwrap();
+        assert_eq!(first.as_deref(), Some("acme"));
+        assert_eq!(second.as_deref(), Some("acme"));
+        assert_eq!(loads.load(Ordering::Relaxed), 1);
+
+        cache.invalidate(CacheNamespace::Tenant, "t1").await;
+        let _: Option<String> = cache.get_or_load(CacheNamespace::Tenant, "t1", load).await.unwrap();
+        assert_eq!(loads.load(Ordering::Relaxed), 2);
+
+        let metrics = cache.metrics();
+        let tenant = metrics.namespaces.iter().find(|m| m.namespace == CacheNamespace::Tenant).unwrap();
+        assert_eq!((tenant.hits, tenant.misses, tenant.invalidations), (1, 2, 1));
+        assert!((metrics.hit_rate - 1.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_missing_entity_is_not_cached() {
+        let cache = service(10);
+        let missing: Option<String> = cache
+            .get_or_load(CacheNamespace::User, "u1", || async { Ok(None) })
+            .await
+            .unwrap();
+        assert!(missing.is_none());
+
+        let created: Option<String> = cache
+            .get_or_load(CacheNamespace::User, "u1", || async { Ok(Some("created".to_string())) })
+            .await
+            .unwrap();
+        assert_eq!(created.as_deref(), Some("created"));
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend_evicts_when_full() {
+        let backend = InMemoryCacheBackend::new(2);
+        let ttl = Duration::from_secs(60);
+        backend.set("a", "1".to_string(), Duration::from_secs(30)).await.unwrap();
+        backend.set("b", "2".to_string(), ttl).await.unwrap();
+        backend.set("c", "3".to_string(), ttl).await.unwrap();
+
+        assert_eq!(backend.get("a").await.unwrap(), None);
+        assert_eq!(backend.get("c").await.unwrap().as_deref(), Some("3"));
+
+        backend.set("d", "4".to_string(), Duration::ZERO).await.unwrap();
+        assert_eq!(backend.get("d").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,614 @@
+// 灰度发布服务
+// 将 Agent 或工作流的新版本只开放给一部分流量或指定用户，其余流量仍由稳定版本处理；
+// 灰度版本的错误率或负面反馈率比稳定版本高出配置的阈值时自动回滚
+
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set, Statement,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::canary_release::{self, CanaryStatus};
+use crate::db::entities::execution_event::ExecutionType;
+use crate::db::entities::CanaryRelease;
+use crate::errors::AiStudioError;
+use crate::services::scheduler::PeriodicJob;
+
+/// 自动回滚检查间隔
+const EVALUATION_INTERVAL: Duration = Duration::from_secs(300);
+
+/// 灰度分流到的版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryArm {
+    /// 稳定版本
+    Stable,
+    /// 灰度版本
+    Canary,
+}
+
+impl CanaryArm {
+    /// 保存在执行记录中的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CanaryArm::Stable => "stable",
+            CanaryArm::Canary => "canary",
+        }
+    }
+}
+
+/// 自动回滚策略
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct CanaryRollbackPolicy {
+    /// 灰度版本至少有这么多已结束的执行后才判断错误率
+    pub min_samples: u32,
+    /// 灰度版本至少有这么多反馈后才判断负面反馈率
+    pub min_feedback: u32,
+    /// 灰度版本错误率比稳定版本高出该值（0-1）时回滚
+    pub max_error_rate_increase: f64,
+    /// 灰度版本负面反馈率比稳定版本高出该值（0-1）时回滚
+    pub max_negative_feedback_increase: f64,
+}
+
+impl Default for CanaryRollbackPolicy {
+    fn default() -> Self {
+        Self {
+            min_samples: 20,
+            min_feedback: 5,
+            max_error_rate_increase: 0.1,
+            max_negative_feedback_increase: 0.2,
+        }
+    }
+}
+
+/// 创建灰度发布请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateCanaryRequest {
+    /// 灰度版本 ID（同租户下另一个 Agent 或已发布的工作流）
+    pub canary_id: Uuid,
+    /// 分流到灰度版本的流量百分比（0-100），同一用户始终分到同一版本
+    #[serde(default)]
+    pub traffic_percent: u8,
+    /// 固定分流到灰度版本的用户
+    #[serde(default)]
+    pub user_ids: Vec<Uuid>,
+    /// 自动回滚策略
+    #[serde(default)]
+    pub rollback: CanaryRollbackPolicy,
+}
+
+/// 结束灰度发布请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct EndCanaryRequest {
+    /// 全量或回滚的原因
+    pub reason: Option<String>,
+}
+
+/// 单个版本的执行统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CanaryArmStats {
+    /// 已结束的执行数
+    pub executions: u64,
+    /// 失败或超时的执行数
+    pub failures: u64,
+    /// 错误率
+    pub error_rate: f64,
+    /// 收到的反馈数
+    pub feedback: u64,
+    /// 负面反馈数
+    pub negative_feedback: u64,
+    /// 负面反馈率
+    pub negative_feedback_rate: f64,
+}
+
+impl CanaryArmStats {
+    fn new(executions: u64, failures: u64, feedback: u64, negative_feedback: u64) -> Self {
+        let rate = |part: u64, total: u64| if total == 0 { 0.0 } else { part as f64 / total as f64 };
+        Self {
+            executions,
+            failures,
+            error_rate: rate(failures, executions),
+            feedback,
+            negative_feedback,
+            negative_feedback_rate: rate(negative_feedback, feedback),
+        }
+    }
+}
+
+/// 灰度发布详情
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CanaryReleaseInfo {
+    /// 灰度发布 ID
+    pub release_id: Uuid,
+    /// 发布对象类型
+    pub target_type: ExecutionType,
+    /// 入口 ID
+    pub target_id: Uuid,
+    /// 稳定版本 ID
+    pub stable_id: Uuid,
+    /// 灰度版本 ID
+    pub canary_id: Uuid,
+    /// 分流到灰度版本的流量百分比
+    pub traffic_percent: u8,
+    /// 固定分流到灰度版本的用户
+    pub user_ids: Vec<Uuid>,
+    /// 状态
+    pub status: CanaryStatus,
+    /// 自动回滚策略
+    pub rollback: CanaryRollbackPolicy,
+    /// 全量或回滚的原因
+    pub end_reason: Option<String>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 全量或回滚时间
+    pub ended_at: Option<DateTime<Utc>>,
+    /// 稳定版本统计
+    pub stable: CanaryArmStats,
+    /// 灰度版本统计
+    pub canary: CanaryArmStats,
+}
+
+impl CanaryReleaseInfo {
+    fn new(model: canary_release::Model, stable: CanaryArmStats, canary: CanaryArmStats) -> Self {
+        Self {
+            release_id: model.id,
+            target_type: model.target_type,
+            target_id: model.target_id,
+            stable_id: model.stable_id,
+            canary_id: model.canary_id,
+            traffic_percent: model.traffic_percent.clamp(0, 100) as u8,
+            user_ids: user_ids_of(&model),
+            status: model.status,
+            rollback: policy_of(&model),
+            end_reason: model.end_reason,
+            created_at: model.created_at.with_timezone(&Utc),
+            ended_at: model.ended_at.map(|at| at.with_timezone(&Utc)),
+            stable,
+            canary,
+        }
+    }
+}
+
+/// 一次执行的分流结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanaryRoute {
+    /// 实际执行的 Agent 或工作流 ID
+    pub serve_id: Uuid,
+    /// 进行中的灰度发布 ID，灰度已结束时为空
+    pub release_id: Option<Uuid>,
+    /// 分流到的版本
+    pub arm: CanaryArm,
+}
+
+/// 灰度发布服务
+#[derive(Debug, Clone)]
+pub struct CanaryService {
+    db: DatabaseConnection,
+}
+
+impl CanaryService {
+    /// 创建灰度发布服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 创建灰度发布
+    ///
+    /// 稳定版本为入口当前实际处理流量的版本：此前灰度已全量时为上次的灰度版本，否则为入口本身。
+    /// 同一入口同时只能有一个进行中的灰度发布。
+    #[instrument(skip(self, request))]
+    pub async fn create(
+        &self,
+        tenant_id: Uuid,
+        target_type: ExecutionType,
+        target_id: Uuid,
+        request: CreateCanaryRequest,
+        created_by: Uuid,
+    ) -> Result<CanaryReleaseInfo, AiStudioError> {
+        validate_request(&request)?;
+
+        let latest = self.latest(tenant_id, target_type, target_id).await?;
+        if latest.as_ref().is_some_and(|release| release.status == CanaryStatus::Active) {
+            return Err(AiStudioError::conflict("该入口已有进行中的灰度发布"));
+        }
+        let stable_id = latest.as_ref().map(serving_id).unwrap_or(target_id);
+        if request.canary_id == stable_id {
+            return Err(AiStudioError::validation("canary_id", "灰度版本不能与稳定版本相同"));
+        }
+
+        let policy = request.rollback;
+        let model = canary_release::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            target_type: Set(target_type),
+            target_id: Set(target_id),
+            stable_id: Set(stable_id),
+            canary_id: Set(request.canary_id),
+            traffic_percent: Set(request.traffic_percent as i16),
+            user_ids: Set(serde_json::to_value(&request.user_ids)?),
+            status: Set(CanaryStatus::Active),
+            min_samples: Set(policy.min_samples as i32),
+            min_feedback: Set(policy.min_feedback as i32),
+            max_error_rate_increase: Set(policy.max_error_rate_increase),
+            max_negative_feedback_increase: Set(policy.max_negative_feedback_increase),
+            end_reason: Set(None),
+            created_by: Set(created_by),
+            created_at: Set(Utc::now().fixed_offset()),
+            ended_at: Set(None),
+        }
+        .insert(&self.db)
+        .await?;
+
+        info!(
+            "灰度发布已创建: release_id={}, target_id={}, stable_id={}, canary_id={}, traffic_percent={}",
+            model.id, target_id, stable_id, model.canary_id, model.traffic_percent
+        );
+        Ok(CanaryReleaseInfo::new(model, CanaryArmStats::default(), CanaryArmStats::default()))
+    }
+
+    /// 入口最近一次灰度发布的详情
+    pub async fn info(
+        &self,
+        tenant_id: Uuid,
+        target_type: ExecutionType,
+        target_id: Uuid,
+    ) -> Result<CanaryReleaseInfo, AiStudioError> {
+        let release = self
+            .latest(tenant_id, target_type, target_id)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("灰度发布"))?;
+        let (stable, canary) = self.arm_stats(release.id).await?;
+        Ok(CanaryReleaseInfo::new(release, stable, canary))
+    }
+
+    /// 为一次执行选择版本，入口没有灰度发布时返回空
+    ///
+    /// 按 `routing_key`（通常为用户 ID）分桶，同一用户始终分到同一版本；未提供时随机分流。
+    pub async fn route(
+        &self,
+        tenant_id: Uuid,
+        target_type: ExecutionType,
+        target_id: Uuid,
+        routing_key: Option<Uuid>,
+    ) -> Result<Option<CanaryRoute>, AiStudioError> {
+        let Some(release) = self.latest(tenant_id, target_type, target_id).await? else {
+            return Ok(None);
+        };
+
+        let route = match release.status {
+            CanaryStatus::Active => {
+                let arm = choose_arm(&release, routing_key.unwrap_or_else(Uuid::new_v4));
+                let serve_id = match arm {
+                    CanaryArm::Stable => release.stable_id,
+                    CanaryArm::Canary => release.canary_id,
+                };
+                CanaryRoute { serve_id, release_id: Some(release.id), arm }
+            }
+            CanaryStatus::Promoted => CanaryRoute { serve_id: release.canary_id, release_id: None, arm: CanaryArm::Canary },
+            CanaryStatus::RolledBack => CanaryRoute { serve_id: release.stable_id, release_id: None, arm: CanaryArm::Stable },
+        };
+        Ok(Some(route))
+    }
+
+    /// 全量灰度版本
+    pub async fn promote(
+        &self,
+        tenant_id: Uuid,
+        target_type: ExecutionType,
+        target_id: Uuid,
+        reason: Option<String>,
+    ) -> Result<CanaryReleaseInfo, AiStudioError> {
+        let release = self.active(tenant_id, target_type, target_id).await?;
+        let release = self.end(release, CanaryStatus::Promoted, reason.unwrap_or_else(|| "手动全量".to_string())).await?;
+        let (stable, canary) = self.arm_stats(release.id).await?;
+        Ok(CanaryReleaseInfo::new(release, stable, canary))
+    }
+
+    /// 手动回滚到稳定版本
+    pub async fn rollback(
+        &self,
+        tenant_id: Uuid,
+        target_type: ExecutionType,
+        target_id: Uuid,
+        reason: Option<String>,
+    ) -> Result<CanaryReleaseInfo, AiStudioError> {
+        let release = self.active(tenant_id, target_type, target_id).await?;
+        let release = self.end(release, CanaryStatus::RolledBack, reason.unwrap_or_else(|| "手动回滚".to_string())).await?;
+        let (stable, canary) = self.arm_stats(release.id).await?;
+        Ok(CanaryReleaseInfo::new(release, stable, canary))
+    }
+
+    /// 检查灰度发布的执行统计，灰度版本明显劣于稳定版本时自动回滚，返回是否已回滚
+    #[instrument(skip(self))]
+    pub async fn evaluate(&self, release_id: Uuid) -> Result<bool, AiStudioError> {
+        let Some(release) = CanaryRelease::find_by_id(release_id).one(&self.db).await? else {
+            return Ok(false);
+        };
+        if release.status != CanaryStatus::Active {
+            return Ok(false);
+        }
+
+        let (stable, canary) = self.arm_stats(release.id).await?;
+        let Some(reason) = degradation(&policy_of(&release), &stable, &canary) else {
+            return Ok(false);
+        };
+        warn!("灰度版本表现劣于稳定版本，自动回滚: release_id={}, reason={}", release.id, reason);
+        self.end(release, CanaryStatus::RolledBack, reason).await?;
+        Ok(true)
+    }
+
+    /// 检查所有进行中的灰度发布，返回自动回滚的数量
+    pub async fn evaluate_active(&self) -> Result<usize, AiStudioError> {
+        let releases = CanaryRelease::find()
+            .filter(canary_release::Column::Status.eq(CanaryStatus::Active))
+            .all(&self.db)
+            .await?;
+
+        let mut rolled_back = 0;
+        for release in releases {
+            match self.evaluate(release.id).await {
+                Ok(true) => rolled_back += 1,
+                Ok(false) => {}
+                Err(e) => warn!("检查灰度发布失败: release_id={}, error={}", release.id, e),
+            }
+        }
+        Ok(rolled_back)
+    }
+
+    /// 灰度发布中尚未结束的执行 ID，用于同步异步执行的状态
+    pub async fn unfinished_executions(&self, release_id: Uuid) -> Result<Vec<Uuid>, AiStudioError> {
+        let rows = self
+            .db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT id FROM execution_records WHERE canary_release_id = $1 AND completed_at IS NULL",
+                [release_id.into()],
+            ))
+            .await?;
+        rows.iter().map(|row| Ok(row.try_get("", "id")?)).collect()
+    }
+
+    async fn latest(
+        &self,
+        tenant_id: Uuid,
+        target_type: ExecutionType,
+        target_id: Uuid,
+    ) -> Result<Option<canary_release::Model>, AiStudioError> {
+        Ok(CanaryRelease::find()
+            .filter(canary_release::Column::TenantId.eq(tenant_id))
+            .filter(canary_release::Column::TargetType.eq(target_type))
+            .filter(canary_release::Column::TargetId.eq(target_id))
+            .order_by_desc(canary_release::Column::CreatedAt)
+            .one(&self.db)
+            .await?)
+    }
+
+    async fn active(
+        &self,
+        tenant_id: Uuid,
+        target_type: ExecutionType,
+        target_id: Uuid,
+    ) -> Result<canary_release::Model, AiStudioError> {
+        self.latest(tenant_id, target_type, target_id)
+            .await?
+            .filter(|release| release.status == CanaryStatus::Active)
+            .ok_or_else(|| AiStudioError::not_found("进行中的灰度发布"))
+    }
+
+    async fn end(
+        &self,
+        release: canary_release::Model,
+        status: CanaryStatus,
+        reason: String,
+    ) -> Result<canary_release::Model, AiStudioError> {
+        let release_id = release.id;
+        let mut active: canary_release::ActiveModel = release.into();
+        active.status = Set(status);
+        active.end_reason = Set(Some(reason));
+        active.ended_at = Set(Some(Utc::now().fixed_offset()));
+        let release = active.update(&self.db).await?;
+
+        info!("灰度发布已结束: release_id={}, status={:?}", release_id, status);
+        Ok(release)
+    }
+
+    async fn arm_stats(&self, release_id: Uuid) -> Result<(CanaryArmStats, CanaryArmStats), AiStudioError> {
+        let rows = self
+            .db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT canary_arm, \
+                 COUNT(*) FILTER (WHERE status IN ('completed', 'failed', 'timeout')) AS executions, \
+                 COUNT(*) FILTER (WHERE status IN ('failed', 'timeout')) AS failures, \
+                 COUNT(feedback) AS feedback, \
+                 COUNT(*) FILTER (WHERE feedback < 0) AS negative_feedback \
+                 FROM execution_records WHERE canary_release_id = $1 GROUP BY canary_arm",
+                [release_id.into()],
+            ))
+            .await?;
+
+        let mut stable = CanaryArmStats::default();
+        let mut canary = CanaryArmStats::default();
+        for row in rows {
+            let count = |column: &str| -> Result<u64, AiStudioError> { Ok(row.try_get::<i64>("", column)?.max(0) as u64) };
+            let stats = CanaryArmStats::new(
+                count("executions")?,
+                count("failures")?,
+                count("feedback")?,
+                count("negative_feedback")?,
+            );
+            match row.try_get::<Option<String>>("", "canary_arm")?.as_deref() {
+                Some("canary") => canary = stats,
+                Some("stable") => stable = stats,
+                _ => {}
+            }
+        }
+        Ok((stable, canary))
+    }
+}
+
+/// 校验创建请求
+fn validate_request(request: &CreateCanaryRequest) -> Result<(), AiStudioError> {
+    if request.traffic_percent > 100 {
+        return Err(AiStudioError::validation("traffic_percent", "流量百分比须在 0 到 100 之间"));
+    }
+    if request.traffic_percent == 0 && request.user_ids.is_empty() {
+        return Err(AiStudioError::validation("traffic_percent", "需要指定流量百分比或灰度用户"));
+    }
+    let policy = &request.rollback;
+    if policy.min_samples == 0 || policy.min_feedback == 0 {
+        return Err(AiStudioError::validation("rollback", "最少样本数和最少反馈数须大于 0"));
+    }
+    for (field, value) in [
+        ("rollback.max_error_rate_increase", policy.max_error_rate_increase),
+        ("rollback.max_negative_feedback_increase", policy.max_negative_feedback_increase),
+    ] {
+        if !(0.0..=1.0).contains(&value) {
+            return Err(AiStudioError::validation(field, "阈值须在 0 到 1 之间"));
+        }
+    }
+    Ok(())
+}
+
+/// 入口当前实际处理流量的版本
+fn serving_id(release: &canary_release::Model) -> Uuid {
+    match release.status {
+        CanaryStatus::Promoted => release.canary_id,
+        CanaryStatus::Active | CanaryStatus::RolledBack => release.stable_id,
+    }
+}
+
+fn user_ids_of(release: &canary_release::Model) -> Vec<Uuid> {
+    serde_json::from_value(release.user_ids.clone()).unwrap_or_default()
+}
+
+fn policy_of(release: &canary_release::Model) -> CanaryRollbackPolicy {
+    CanaryRollbackPolicy {
+        min_samples: release.min_samples.max(0) as u32,
+        min_feedback: release.min_feedback.max(0) as u32,
+        max_error_rate_increase: release.max_error_rate_increase,
+        max_negative_feedback_increase: release.max_negative_feedback_increase,
+    }
+}
+
+/// 选择版本：指定用户固定分到灰度版本，其余按分桶与流量百分比决定
+fn choose_arm(release: &canary_release::Model, routing_key: Uuid) -> CanaryArm {
+    if user_ids_of(release).contains(&routing_key) {
+        return CanaryArm::Canary;
+    }
+    if (bucket(release.id, routing_key) as i16) < release.traffic_percent {
+        CanaryArm::Canary
+    } else {
+        CanaryArm::Stable
+    }
+}
+
+/// 将分流键映射到 0-99 的桶，同一灰度发布内结果固定
+pub fn bucket(release_id: Uuid, routing_key: Uuid) -> u8 {
+    let digest = md5::compute([release_id.as_bytes().as_slice(), routing_key.as_bytes().as_slice()].concat());
+    (u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) % 100) as u8
+}
+
+/// 判断灰度版本是否劣于稳定版本，返回回滚原因
+///
+/// 稳定版本样本不足时以 0 作为基线，避免因稳定版本流量少而放过明显的问题。
+pub fn degradation(
+    policy: &CanaryRollbackPolicy,
+    stable: &CanaryArmStats,
+    canary: &CanaryArmStats,
+) -> Option<String> {
+    if canary.executions >= policy.min_samples as u64 {
+        let baseline = if stable.executions >= policy.min_samples as u64 { stable.error_rate } else { 0.0 };
+        if canary.error_rate - baseline > policy.max_error_rate_increase {
+            return Some(format!(
+                "灰度版本错误率 {:.1}% 超过稳定版本 {:.1}% 的允许范围（+{:.1}%）",
+                canary.error_rate * 100.0,
+                baseline * 100.0,
+                policy.max_error_rate_increase * 100.0
+            ));
+        }
+    }
+    if canary.feedback >= policy.min_feedback as u64 {
+        let baseline = if stable.feedback >= policy.min_feedback as u64 { stable.negative_feedback_rate } else { 0.0 };
+        if canary.negative_feedback_rate - baseline > policy.max_negative_feedback_increase {
+            return Some(format!(
+                "灰度版本负面反馈率 {:.1}% 超过稳定版本 {:.1}% 的允许范围（+{:.1}%）",
+                canary.negative_feedback_rate * 100.0,
+                baseline * 100.0,
+                policy.max_negative_feedback_increase * 100.0
+            ));
+        }
+    }
+    None
+}
+
+/// 灰度发布自动回滚检查任务，覆盖异步执行结束后才更新状态的工作流
+pub struct CanaryEvaluationJob {
+    service: Arc<CanaryService>,
+}
+
+impl CanaryEvaluationJob {
+    /// 创建检查任务
+    pub fn new(service: Arc<CanaryService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for CanaryEvaluationJob {
+    fn name(&self) -> &str {
+        "canary_evaluation"
+    }
+
+    fn interval(&self) -> Duration {
+        EVALUATION_INTERVAL
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        let rolled_back = self.service.evaluate_active().await?;
+        if rolled_back > 0 {
+            info!("灰度发布检查完成，自动回滚 {} 个", rolled_back);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_is_stable_and_spread() {
+        let release_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        assert_eq!(bucket(release_id, user_id), bucket(release_id, user_id));
+
+        let keys: Vec<Uuid> = (0..2000).map(|_| Uuid::new_v4()).collect();
+        let below = keys.iter().filter(|key| bucket(release_id, **key) < 10).count();
+        assert!((100..=300).contains(&below), "10% 流量落入 {} 个桶", below);
+    }
+
+    #[test]
+    fn test_degradation() {
+        let policy = CanaryRollbackPolicy::default();
+        let stable = CanaryArmStats::new(100, 5, 20, 2);
+
+        // 样本不足时不回滚
+        assert_eq!(degradation(&policy, &stable, &CanaryArmStats::new(10, 10, 0, 0)), None);
+        // 错误率高出阈值
+        assert!(degradation(&policy, &stable, &CanaryArmStats::new(40, 8, 0, 0)).is_some());
+        assert_eq!(degradation(&policy, &stable, &CanaryArmStats::new(40, 4, 0, 0)), None);
+        // 负面反馈率高出阈值
+        assert!(degradation(&policy, &stable, &CanaryArmStats::new(40, 2, 6, 3)).is_some());
+        // 稳定版本样本不足时以 0 为基线
+        let quiet = CanaryArmStats::new(3, 0, 0, 0);
+        assert!(degradation(&policy, &quiet, &CanaryArmStats::new(20, 3, 0, 0)).is_some());
+    }
+}
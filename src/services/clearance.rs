@@ -0,0 +1,264 @@
+// 访问密级服务
+// 根据用户角色与权限声明推算访问密级，管理文档及段落密级，并在检索时过滤超出用户密级的文档块
+
+use std::collections::HashSet;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+    sea_query::Expr,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::document::{self, ClearanceLevel};
+use crate::db::entities::{document_chunk, knowledge_base, Document, DocumentChunk, KnowledgeBase};
+use crate::errors::AiStudioError;
+use crate::services::legal_hold::LegalHoldService;
+
+/// 权限声明前缀，如 `clearance:confidential`
+pub const CLEARANCE_PERMISSION_PREFIX: &str = "clearance:";
+
+/// 段落密级标记，按文档块序号区间（含两端）标记
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SectionClearance {
+    /// 起始块序号
+    pub start_chunk: i32,
+    /// 结束块序号
+    pub end_chunk: i32,
+    /// 段落密级
+    pub clearance: ClearanceLevel,
+}
+
+/// 设置文档密级请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateDocumentClearanceRequest {
+    /// 文档整体密级
+    pub clearance: ClearanceLevel,
+    /// 段落密级标记，替换文档现有的全部段落标记
+    #[serde(default)]
+    pub sections: Vec<SectionClearance>,
+}
+
+/// 文档密级信息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DocumentClearanceResponse {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档整体密级
+    pub clearance: ClearanceLevel,
+    /// 高于公开级别的段落标记
+    pub sections: Vec<SectionClearance>,
+}
+
+/// 根据角色与权限声明推算用户的访问密级
+///
+/// 角色决定基础密级，`clearance:<级别>` 权限声明可在此基础上提升。
+pub fn clearance_for(role: &str, permissions: &[String]) -> ClearanceLevel {
+    let base = match role {
+        "super_admin" | "admin" => ClearanceLevel::Restricted,
+        "manager" => ClearanceLevel::Confidential,
+        "user" => ClearanceLevel::Internal,
+        _ => ClearanceLevel::Public,
+    };
+
+    permissions.iter()
+        .filter_map(|permission| permission.strip_prefix(CLEARANCE_PERMISSION_PREFIX))
+        .filter_map(parse_clearance)
+        .fold(base, Ord::max)
+}
+
+/// 解析密级名称
+pub fn parse_clearance(name: &str) -> Option<ClearanceLevel> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "public" => Some(ClearanceLevel::Public),
+        "internal" => Some(ClearanceLevel::Internal),
+        "confidential" => Some(ClearanceLevel::Confidential),
+        "restricted" => Some(ClearanceLevel::Restricted),
+        _ => None,
+    }
+}
+
+/// 将逐块密级合并为连续的段落标记，公开级别的块不产生标记
+pub fn merge_sections(chunks: &[(i32, ClearanceLevel)]) -> Vec<SectionClearance> {
+    let mut sections: Vec<SectionClearance> = Vec::new();
+    for &(index, clearance) in chunks {
+        if clearance == ClearanceLevel::Public {
+            continue;
+        }
+        match sections.last_mut() {
+            Some(last) if last.clearance == clearance && last.end_chunk + 1 == index => last.end_chunk = index,
+            _ => sections.push(SectionClearance { start_chunk: index, end_chunk: index, clearance }),
+        }
+    }
+    sections
+}
+
+/// 访问密级服务
+pub struct ClearanceService {
+    db: DatabaseConnection,
+}
+
+impl ClearanceService {
+    /// 创建新的访问密级服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 返回用户可见的文档块 ID
+    ///
+    /// 块的生效密级取块与所属文档中的较高者；已不存在的块无法确认密级，按最高密级处理。
+    pub async fn visible_chunks(
+        &self,
+        user_clearance: ClearanceLevel,
+        chunk_ids: &[Uuid],
+    ) -> Result<HashSet<Uuid>, AiStudioError> {
+        if chunk_ids.is_empty() || user_clearance == ClearanceLevel::Restricted {
+            return Ok(chunk_ids.iter().copied().collect());
+        }
+
+        let chunks = DocumentChunk::find()
+            .filter(document_chunk::Column::Id.is_in(chunk_ids.iter().copied()))
+            .find_also_related(Document)
+            .all(&self.db)
+            .await?;
+
+        Ok(chunks.into_iter()
+            .filter(|(chunk, doc)| {
+                let document_clearance = doc.as_ref().map_or(ClearanceLevel::Restricted, |doc| doc.clearance);
+                chunk.clearance.max(document_clearance) <= user_clearance
+            })
+            .map(|(chunk, _)| chunk.id)
+            .collect())
+    }
+
+    /// 获取文档密级与段落标记
+    pub async fn get_document_clearance(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+    ) -> Result<DocumentClearanceResponse, AiStudioError> {
+        let doc = self.find_document(tenant_id, document_id).await?;
+        let chunks: Vec<(i32, ClearanceLevel)> = DocumentChunk::find()
+            .filter(document_chunk::Column::DocumentId.eq(document_id))
+            .order_by_asc(document_chunk::Column::ChunkIndex)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|chunk| (chunk.chunk_index, chunk.clearance))
+            .collect();
+
+        Ok(DocumentClearanceResponse {
+            document_id,
+            clearance: doc.clearance,
+            sections: merge_sections(&chunks),
+        })
+    }
+
+    /// 设置文档密级与段落标记
+    ///
+    /// 操作者只能管理不高于自身密级的内容：文档现有密级与新设置的密级都不能超过操作者密级。
+    #[instrument(skip(self, req))]
+    pub async fn update_document_clearance(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+        operator_clearance: ClearanceLevel,
+        req: UpdateDocumentClearanceRequest,
+    ) -> Result<DocumentClearanceResponse, AiStudioError> {
+        let doc = self.find_document(tenant_id, document_id).await?;
+        LegalHoldService::new(self.db.clone())
+            .ensure_document_mutable(&doc, None, "update_clearance")
+            .await?;
+
+        for section in &req.sections {
+            if section.start_chunk < 0 || section.end_chunk < section.start_chunk {
+                return Err(AiStudioError::validation("sections", "段落区间无效"));
+            }
+        }
+
+        let current_max = DocumentChunk::find()
+            .filter(document_chunk::Column::DocumentId.eq(document_id))
+            .all(&self.db)
+            .await?
+            .iter()
+            .map(|chunk| chunk.clearance)
+            .fold(doc.clearance, Ord::max);
+        let requested_max = req.sections.iter()
+            .map(|section| section.clearance)
+            .fold(req.clearance, Ord::max);
+        if current_max.max(requested_max) > operator_clearance {
+            return Err(AiStudioError::forbidden("无权设置高于自身密级的内容"));
+        }
+
+        let txn = self.db.begin().await?;
+
+        let mut active: document::ActiveModel = doc.into();
+        active.clearance = Set(req.clearance);
+        active.update(&txn).await?;
+
+        DocumentChunk::update_many()
+            .col_expr(document_chunk::Column::Clearance, Expr::value(ClearanceLevel::Public))
+            .filter(document_chunk::Column::DocumentId.eq(document_id))
+            .exec(&txn)
+            .await?;
+        for section in &req.sections {
+            DocumentChunk::update_many()
+                .col_expr(document_chunk::Column::Clearance, Expr::value(section.clearance))
+                .filter(document_chunk::Column::DocumentId.eq(document_id))
+                .filter(document_chunk::Column::ChunkIndex.between(section.start_chunk, section.end_chunk))
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        info!("文档密级已更新: document_id={}, clearance={:?}, 段落标记 {} 个", document_id, req.clearance, req.sections.len());
+        self.get_document_clearance(tenant_id, document_id).await
+    }
+
+    async fn find_document(&self, tenant_id: Uuid, document_id: Uuid) -> Result<document::Model, AiStudioError> {
+        Document::find_by_id(document_id)
+            .inner_join(KnowledgeBase)
+            .filter(knowledge_base::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("文档"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clearance_for_role_and_claims() {
+        assert_eq!(clearance_for("admin", &[]), ClearanceLevel::Restricted);
+        assert_eq!(clearance_for("user", &[]), ClearanceLevel::Internal);
+        assert_eq!(clearance_for("guest", &[]), ClearanceLevel::Public);
+
+        let claims = vec!["read".to_string(), "clearance:confidential".to_string()];
+        assert_eq!(clearance_for("user", &claims), ClearanceLevel::Confidential);
+
+        // 权限声明只能提升密级
+        let claims = vec!["clearance:public".to_string()];
+        assert_eq!(clearance_for("manager", &claims), ClearanceLevel::Confidential);
+    }
+
+    #[test]
+    fn test_merge_sections() {
+        let chunks = vec![
+            (0, ClearanceLevel::Public),
+            (1, ClearanceLevel::Confidential),
+            (2, ClearanceLevel::Confidential),
+            (3, ClearanceLevel::Restricted),
+            (5, ClearanceLevel::Restricted),
+        ];
+
+        let sections = merge_sections(&chunks);
+        assert_eq!(sections.len(), 3);
+        assert_eq!((sections[0].start_chunk, sections[0].end_chunk), (1, 2));
+        assert_eq!((sections[1].start_chunk, sections[1].end_chunk), (3, 3));
+        assert_eq!((sections[2].start_chunk, sections[2].end_chunk), (5, 5));
+    }
+}
@@ -0,0 +1,410 @@
+// 同意与条款接受服务
+// 租户发布条款/政策版本，记录每个用户接受的版本与时间；
+// 最新版本要求接受时，未接受的用户在接受前无法访问其他 API
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult, Statement};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::errors::AiStudioError;
+use crate::services::transcript_export::escape_csv;
+
+/// 版本号最大长度
+const MAX_VERSION_CHARS: usize = 50;
+
+/// 标题最大长度
+const MAX_TITLE_CHARS: usize = 200;
+
+/// 接受记录导出的 CSV 表头
+const CSV_HEADER: [&str; 7] = ["accepted_at", "user_id", "username", "email", "version", "ip_address", "user_agent"];
+
+fn default_require_acceptance() -> bool {
+    true
+}
+
+/// 发布条款版本请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PublishTermsRequest {
+    /// 版本号，租户内唯一
+    pub version: String,
+    /// 标题
+    pub title: String,
+    /// 条款正文（Markdown）
+    pub content: String,
+    /// 是否要求用户接受后才能继续使用 API，默认要求
+    #[serde(default = "default_require_acceptance")]
+    pub require_acceptance: bool,
+}
+
+/// 条款版本
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TermsVersionResponse {
+    /// 版本 ID
+    pub id: Uuid,
+    /// 版本号
+    pub version: String,
+    /// 标题
+    pub title: String,
+    /// 条款正文
+    pub content: String,
+    /// 是否要求接受
+    pub require_acceptance: bool,
+    /// 发布人
+    pub published_by: Uuid,
+    /// 发布时间
+    pub published_at: DateTime<Utc>,
+}
+
+/// 当前用户的条款接受状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConsentStatus {
+    /// 租户当前生效的条款版本，未发布时为空
+    pub current: Option<TermsVersionResponse>,
+    /// 是否已接受当前版本
+    pub accepted: bool,
+    /// 接受时间
+    pub accepted_at: Option<DateTime<Utc>>,
+    /// 是否因未接受当前版本而被限制访问
+    pub blocked: bool,
+}
+
+/// 条款接受记录
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TermsAcceptanceRecord {
+    /// 用户 ID
+    pub user_id: Uuid,
+    /// 用户名
+    pub username: Option<String>,
+    /// 邮箱
+    pub email: Option<String>,
+    /// 条款版本 ID
+    pub terms_version_id: Uuid,
+    /// 条款版本号
+    pub version: String,
+    /// 接受时间
+    pub accepted_at: DateTime<Utc>,
+    /// 接受时的客户端 IP
+    pub ip_address: Option<String>,
+    /// 接受时的客户端标识
+    pub user_agent: Option<String>,
+}
+
+/// 接受记录导出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AcceptanceExportFormat {
+    /// JSON 数组
+    Json,
+    /// CSV 表格
+    #[default]
+    Csv,
+}
+
+impl AcceptanceExportFormat {
+    /// 文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+
+    /// 下载时的内容类型
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json; charset=utf-8",
+            Self::Csv => "text/csv; charset=utf-8",
+        }
+    }
+}
+
+/// 接受记录导出参数
+#[derive(Debug, Clone, Deserialize, ToSchema, IntoParams)]
+pub struct ExportAcceptancesQuery {
+    /// 按条款版本过滤，默认导出所有版本
+    pub terms_version_id: Option<Uuid>,
+    /// 导出格式，默认 CSV
+    #[serde(default)]
+    pub format: AcceptanceExportFormat,
+}
+
+/// 同意与条款接受服务
+pub struct ConsentService {
+    db: DatabaseConnection,
+}
+
+impl ConsentService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 发布新的条款版本，发布后即成为租户当前版本
+    #[instrument(skip(self, request))]
+    pub async fn publish_version(
+        &self,
+        tenant_id: Uuid,
+        request: PublishTermsRequest,
+        published_by: Uuid,
+    ) -> Result<TermsVersionResponse, AiStudioError> {
+        let version = request.version.trim().to_string();
+        if version.is_empty() || version.chars().count() > MAX_VERSION_CHARS {
+            return Err(AiStudioError::validation("version", format!("版本号不能为空且不超过 {} 个字符", MAX_VERSION_CHARS)));
+        }
+        let title = request.title.trim().to_string();
+        if title.is_empty() || title.chars().count() > MAX_TITLE_CHARS {
+            return Err(AiStudioError::validation("title", format!("标题不能为空且不超过 {} 个字符", MAX_TITLE_CHARS)));
+        }
+        if request.content.trim().is_empty() {
+            return Err(AiStudioError::validation("content", "条款正文不能为空"));
+        }
+
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO terms_versions (id, tenant_id, version, title, content, require_acceptance, published_by, published_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (tenant_id, version) DO NOTHING
+                RETURNING *
+                "#,
+                [
+                    Uuid::new_v4().into(),
+                    tenant_id.into(),
+                    version.clone().into(),
+                    title.into(),
+                    request.content.into(),
+                    request.require_acceptance.into(),
+                    published_by.into(),
+                    Utc::now().into(),
+                ],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::conflict(format!("条款版本 {} 已存在", version)))?;
+
+        let terms = terms_from_row(&row)?;
+        info!(
+            tenant_id = %tenant_id,
+            version = %terms.version,
+            require_acceptance = terms.require_acceptance,
+            "条款版本已发布"
+        );
+        Ok(terms)
+    }
+
+    /// 列出租户的条款版本，按发布时间倒序
+    pub async fn list_versions(&self, tenant_id: Uuid) -> Result<Vec<TermsVersionResponse>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT * FROM terms_versions WHERE tenant_id = $1 ORDER BY published_at DESC",
+                [tenant_id.into()],
+            ))
+            .await?;
+        rows.iter().map(terms_from_row).collect()
+    }
+
+    /// 租户当前生效的条款版本（最近发布的版本）
+    pub async fn current_version(&self, tenant_id: Uuid) -> Result<Option<TermsVersionResponse>, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT * FROM terms_versions WHERE tenant_id = $1 ORDER BY published_at DESC LIMIT 1",
+                [tenant_id.into()],
+            ))
+            .await?;
+        row.as_ref().map(terms_from_row).transpose()
+    }
+
+    /// 查询用户对当前版本的接受状态
+    pub async fn status(&self, tenant_id: Uuid, user_id: Uuid) -> Result<ConsentStatus, AiStudioError> {
+        let Some(current) = self.current_version(tenant_id).await? else {
+            return Ok(ConsentStatus { current: None, accepted: false, accepted_at: None, blocked: false });
+        };
+
+        let accepted_at: Option<DateTime<Utc>> = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT accepted_at FROM terms_acceptances WHERE terms_version_id = $1 AND user_id = $2",
+                [current.id.into(), user_id.into()],
+            ))
+            .await?
+            .map(|row| row.try_get("", "accepted_at"))
+            .transpose()?;
+
+        let accepted = accepted_at.is_some();
+        Ok(ConsentStatus {
+            blocked: current.require_acceptance && !accepted,
+            current: Some(current),
+            accepted,
+            accepted_at,
+        })
+    }
+
+    /// 用户是否需要先接受当前条款版本才能访问 API
+    pub async fn requires_acceptance(&self, tenant_id: Uuid, user_id: Uuid) -> Result<bool, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT v.require_acceptance AND NOT EXISTS (
+                    SELECT 1 FROM terms_acceptances a WHERE a.terms_version_id = v.id AND a.user_id = $2
+                ) AS blocked
+                FROM terms_versions v
+                WHERE v.tenant_id = $1
+                ORDER BY v.published_at DESC
+                LIMIT 1
+                "#,
+                [tenant_id.into(), user_id.into()],
+            ))
+            .await?;
+        Ok(match row {
+            Some(row) => row.try_get("", "blocked")?,
+            None => false,
+        })
+    }
+
+    /// 接受条款版本，只能接受当前版本；重复接受保留首次接受记录
+    #[instrument(skip(self, user_agent))]
+    pub async fn accept(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        terms_version_id: Uuid,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<ConsentStatus, AiStudioError> {
+        let current = self.current_version(tenant_id).await?
+            .ok_or_else(|| AiStudioError::not_found("条款版本"))?;
+        if current.id != terms_version_id {
+            let exists = self.list_versions(tenant_id).await?.iter().any(|v| v.id == terms_version_id);
+            return Err(if exists {
+                AiStudioError::conflict(format!("该条款版本已被 {} 取代，请接受当前版本", current.version))
+            } else {
+                AiStudioError::not_found("条款版本")
+            });
+        }
+
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO terms_acceptances (id, tenant_id, terms_version_id, user_id, accepted_at, ip_address, user_agent)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (terms_version_id, user_id) DO NOTHING
+                "#,
+                [
+                    Uuid::new_v4().into(),
+                    tenant_id.into(),
+                    terms_version_id.into(),
+                    user_id.into(),
+                    Utc::now().into(),
+                    ip_address.into(),
+                    user_agent.into(),
+                ],
+            ))
+            .await?;
+
+        info!(tenant_id = %tenant_id, user_id = %user_id, version = %current.version, "用户已接受条款");
+        self.status(tenant_id, user_id).await
+    }
+
+    /// 查询接受记录，按接受时间排序
+    pub async fn acceptances(
+        &self,
+        tenant_id: Uuid,
+        terms_version_id: Option<Uuid>,
+    ) -> Result<Vec<TermsAcceptanceRecord>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT a.user_id, u.username, u.email, a.terms_version_id, v.version,
+                       a.accepted_at, a.ip_address, a.user_agent
+                FROM terms_acceptances a
+                JOIN terms_versions v ON v.id = a.terms_version_id
+                LEFT JOIN users u ON u.id = a.user_id
+                WHERE a.tenant_id = $1 AND ($2::uuid IS NULL OR a.terms_version_id = $2)
+                ORDER BY a.accepted_at
+                "#,
+                [tenant_id.into(), terms_version_id.into()],
+            ))
+            .await?;
+
+        let mut records = Vec::with_capacity(rows.len());
+        for row in rows {
+            records.push(TermsAcceptanceRecord {
+                user_id: row.try_get("", "user_id")?,
+                username: row.try_get("", "username")?,
+                email: row.try_get("", "email")?,
+                terms_version_id: row.try_get("", "terms_version_id")?,
+                version: row.try_get("", "version")?,
+                accepted_at: row.try_get("", "accepted_at")?,
+                ip_address: row.try_get("", "ip_address")?,
+                user_agent: row.try_get("", "user_agent")?,
+            });
+        }
+        Ok(records)
+    }
+}
+
+/// 将接受记录渲染为 CSV
+pub fn render_acceptances_csv(records: &[TermsAcceptanceRecord]) -> String {
+    let mut csv = CSV_HEADER.join(",");
+    csv.push_str("\r\n");
+
+    for record in records {
+        let fields = [
+            record.accepted_at.to_rfc3339(),
+            record.user_id.to_string(),
+            record.username.clone().unwrap_or_default(),
+            record.email.clone().unwrap_or_default(),
+            record.version.clone(),
+            record.ip_address.clone().unwrap_or_default(),
+            record.user_agent.clone().unwrap_or_default(),
+        ];
+        csv.push_str(&fields.iter().map(|f| escape_csv(f)).collect::<Vec<_>>().join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+fn terms_from_row(row: &QueryResult) -> Result<TermsVersionResponse, AiStudioError> {
+    Ok(TermsVersionResponse {
+        id: row.try_get("", "id")?,
+        version: row.try_get("", "version")?,
+        title: row.try_get("", "title")?,
+        content: row.try_get("", "content")?,
+        require_acceptance: row.try_get("", "require_acceptance")?,
+        published_by: row.try_get("", "published_by")?,
+        published_at: row.try_get("", "published_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_acceptances_csv() {
+        let record = TermsAcceptanceRecord {
+            user_id: Uuid::nil(),
+            username: Some("alice".to_string()),
+            email: None,
+            terms_version_id: Uuid::nil(),
+            version: "2024.1".to_string(),
+            accepted_at: DateTime::parse_from_rfc3339("2024-05-01T08:00:00Z").unwrap().with_timezone(&Utc),
+            ip_address: Some("10.0.0.1".to_string()),
+            user_agent: Some("Mozilla/5.0 (X11, Linux)".to_string()),
+        };
+
+        let csv = render_acceptances_csv(&[record]);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert!(lines[1].starts_with("2024-05-01T08:00:00+00:00,"));
+        assert!(lines[1].ends_with(",2024.1,10.0.0.1,\"Mozilla/5.0 (X11, Linux)\""));
+    }
+}
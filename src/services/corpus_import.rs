@@ -0,0 +1,686 @@
+// 外部语料导入服务
+// 解析 LangChain Document JSONL 与 LlamaIndex 节点导出，按来源归并为文档，保留原有分块、元数据与可复用的向量
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::chunker::{ChunkMetadata, ChunkPosition, ChunkType, DocumentChunk, DocumentVectorizer};
+use crate::ai::vector_store::{VectorPoint, VectorStoreRegistry};
+use crate::db::entities::{document, document_chunk, embedding, knowledge_base, KnowledgeBase};
+use crate::errors::AiStudioError;
+use crate::services::pii_policy::{apply_pii_policy, PiiPolicyService, PiiTreatment};
+use crate::services::tenant_encryption::TenantEncryptionService;
+
+/// 单次导入的最大记录数
+pub const MAX_IMPORT_RECORDS: usize = 5000;
+
+/// LlamaIndex 节点关系中表示来源文档的键（`NodeRelationship.SOURCE`）
+const LLAMA_SOURCE_RELATIONSHIP: &str = "1";
+
+/// 导入格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CorpusImportFormat {
+    /// LangChain Document JSONL，每行一个 `Document`（`page_content` + `metadata`），也接受 `dumpd` 序列化格式
+    LangchainJsonl,
+    /// LlamaIndex 节点，支持节点数组、每行一个节点的 JSONL 以及 docstore 导出
+    LlamaindexNodes,
+}
+
+/// 导入选项
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct CorpusImportOptions {
+    /// 导入格式，为空时根据文件内容识别
+    pub format: Option<CorpusImportFormat>,
+    /// 按该元数据字段归并文档，为空时 LangChain 使用 `source`，LlamaIndex 使用来源文档关系
+    pub group_by: Option<String>,
+    /// 预计算向量使用的嵌入模型，与知识库嵌入模型一致时直接写入向量，否则重新生成
+    pub embedding_model: Option<String>,
+}
+
+/// 从导出中解析出的文档块
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedChunk {
+    /// 原系统中的 ID
+    pub source_id: Option<String>,
+    /// 正文
+    pub text: String,
+    /// 原始元数据
+    pub metadata: Map<String, Value>,
+    /// 预计算向量
+    pub embedding: Option<Vec<f32>>,
+    /// 在来源文档中的起止字符位置
+    pub span: Option<(usize, usize)>,
+    /// 归并文档的键
+    group_key: Option<String>,
+}
+
+/// 按来源归并后的文档
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedDocument {
+    /// 归并键
+    pub source: String,
+    /// 标题
+    pub title: String,
+    /// 所有文档块共有的元数据
+    pub metadata: Map<String, Value>,
+    /// 文档块，按在来源文档中的位置排列
+    pub chunks: Vec<ImportedChunk>,
+}
+
+/// 被跳过的记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SkippedImportRecord {
+    /// 记录序号（从 1 开始，JSONL 为行号）
+    pub record: usize,
+    /// 原因
+    pub reason: String,
+}
+
+/// 解析结果
+#[derive(Debug, Clone, Default)]
+pub struct ParsedCorpus {
+    pub documents: Vec<ImportedDocument>,
+    pub skipped: Vec<SkippedImportRecord>,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CorpusImportReport {
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 导入格式
+    pub format: CorpusImportFormat,
+    /// 创建的文档
+    pub document_ids: Vec<Uuid>,
+    /// 创建的文档块数
+    pub chunks_created: usize,
+    /// 直接写入的预计算向量数
+    pub embeddings_imported: usize,
+    /// 重新生成的向量数
+    pub embeddings_generated: usize,
+    /// 因个人信息策略排除在向量索引之外的文档块数
+    pub excluded_chunks: usize,
+    /// 被跳过的记录
+    pub skipped: Vec<SkippedImportRecord>,
+}
+
+/// 根据文件内容识别导入格式
+pub fn detect_format(data: &str) -> Option<CorpusImportFormat> {
+    let head: String = data.chars().take(4096).collect();
+    if head.contains("\"page_content\"") {
+        Some(CorpusImportFormat::LangchainJsonl)
+    } else if ["\"id_\"", "\"docstore/data\"", "\"node_id\"", "\"text\""].iter().any(|key| head.contains(key)) {
+        Some(CorpusImportFormat::LlamaindexNodes)
+    } else {
+        None
+    }
+}
+
+/// 解析导出内容并按来源归并为文档
+pub fn parse_corpus(
+    data: &str,
+    format: CorpusImportFormat,
+    group_by: Option<&str>,
+) -> Result<ParsedCorpus, AiStudioError> {
+    let records = match format {
+        CorpusImportFormat::LangchainJsonl => jsonl_records(data)?,
+        CorpusImportFormat::LlamaindexNodes => llama_records(data)?,
+    };
+    if records.len() > MAX_IMPORT_RECORDS {
+        return Err(AiStudioError::validation(
+            "file",
+            format!("单次最多导入 {} 条记录，当前 {} 条", MAX_IMPORT_RECORDS, records.len()),
+        ));
+    }
+
+    let mut parsed = ParsedCorpus::default();
+    let mut chunks = Vec::with_capacity(records.len());
+    for (record, value) in records {
+        let chunk = match format {
+            CorpusImportFormat::LangchainJsonl => langchain_chunk(&value),
+            CorpusImportFormat::LlamaindexNodes => llama_chunk(&value),
+        };
+        match chunk {
+            Ok(chunk) if chunk.text.trim().is_empty() => parsed.skipped.push(SkippedImportRecord {
+                record,
+                reason: "正文为空".to_string(),
+            }),
+            Ok(chunk) => chunks.push((record, chunk)),
+            Err(reason) => parsed.skipped.push(SkippedImportRecord { record, reason }),
+        }
+    }
+
+    parsed.documents = group_chunks(chunks, group_by);
+    Ok(parsed)
+}
+
+/// 按行解析 JSONL，忽略空行；无法解析的行直接报错，避免静默丢失内容
+fn jsonl_records(data: &str) -> Result<Vec<(usize, Value)>, AiStudioError> {
+    data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map(|value| (index + 1, value))
+                .map_err(|e| AiStudioError::validation("file", format!("第 {} 行不是有效的 JSON: {}", index + 1, e)))
+        })
+        .collect()
+}
+
+/// 解析 LlamaIndex 导出：节点数组、docstore 导出或每行一个节点
+fn llama_records(data: &str) -> Result<Vec<(usize, Value)>, AiStudioError> {
+    let Ok(value) = serde_json::from_str::<Value>(data) else {
+        return jsonl_records(data);
+    };
+    let nodes: Vec<Value> = match value {
+        Value::Array(nodes) => nodes,
+        Value::Object(mut object) => match object.remove("docstore/data") {
+            Some(Value::Object(entries)) => entries.into_iter().map(|(_, node)| node).collect(),
+            Some(_) => return Err(AiStudioError::validation("file", "docstore/data 格式错误")),
+            None => vec![Value::Object(object)],
+        },
+        _ => return Err(AiStudioError::validation("file", "LlamaIndex 导出应为节点数组或 docstore 对象")),
+    };
+    Ok(nodes.into_iter().enumerate().map(|(index, node)| (index + 1, node)).collect())
+}
+
+/// 解析一条 LangChain Document
+fn langchain_chunk(value: &Value) -> Result<ImportedChunk, String> {
+    // `langchain_core.load.dumpd` 的序列化格式将字段放在 kwargs 中
+    let fields = value.get("kwargs").filter(|_| value.get("lc").is_some()).unwrap_or(value);
+    let text = fields.get("page_content")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "缺少 page_content 字段".to_string())?;
+    let metadata = object_field(fields, "metadata");
+    let source_id = fields.get("id").and_then(Value::as_str).map(str::to_string);
+    Ok(ImportedChunk {
+        group_key: metadata.get("source").map(value_key),
+        source_id,
+        text: text.to_string(),
+        embedding: embedding_field(value).or_else(|| embedding_field(fields)),
+        span: None,
+        metadata,
+    })
+}
+
+/// 解析一个 LlamaIndex 节点
+fn llama_chunk(value: &Value) -> Result<ImportedChunk, String> {
+    // docstore 中的节点包装为 {"__data__": ..., "__type__": ...}，旧版本的 __data__ 为 JSON 字符串
+    let node = match value.get("__data__") {
+        Some(Value::String(data)) => serde_json::from_str(data).map_err(|e| format!("__data__ 解析失败: {}", e))?,
+        Some(data) => data.clone(),
+        None => value.clone(),
+    };
+    let text = node.get("text")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "缺少 text 字段，非文本节点不导入".to_string())?;
+    let metadata = match object_field(&node, "metadata") {
+        metadata if metadata.is_empty() => object_field(&node, "extra_info"),
+        metadata => metadata,
+    };
+    let source_id = ["id_", "node_id", "doc_id"].iter()
+        .find_map(|key| node.get(*key).and_then(Value::as_str))
+        .map(str::to_string);
+    let group_key = node.pointer(&format!("/relationships/{}/node_id", LLAMA_SOURCE_RELATIONSHIP))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| ["file_path", "file_name"].iter().find_map(|key| metadata.get(*key).map(value_key)));
+    let span = match (node.get("start_char_idx").and_then(Value::as_u64), node.get("end_char_idx").and_then(Value::as_u64)) {
+        (Some(start), Some(end)) => Some((start as usize, end as usize)),
+        _ => None,
+    };
+    Ok(ImportedChunk {
+        source_id,
+        text: text.to_string(),
+        embedding: embedding_field(&node),
+        span,
+        group_key,
+        metadata,
+    })
+}
+
+/// 按归并键组合文档块，保持首次出现的顺序；无法归并的记录各自成为一个文档
+fn group_chunks(chunks: Vec<(usize, ImportedChunk)>, group_by: Option<&str>) -> Vec<ImportedDocument> {
+    let mut documents: Vec<ImportedDocument> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (record, chunk) in chunks {
+        let key = match group_by {
+            Some(field) => chunk.metadata.get(field).map(value_key),
+            None => chunk.group_key.clone(),
+        }
+        .or_else(|| chunk.source_id.clone())
+        .unwrap_or_else(|| format!("record-{}", record));
+
+        match index.get(&key) {
+            Some(&position) => documents[position].chunks.push(chunk),
+            None => {
+                index.insert(key.clone(), documents.len());
+                documents.push(ImportedDocument {
+                    source: key,
+                    title: String::new(),
+                    metadata: Map::new(),
+                    chunks: vec![chunk],
+                });
+            }
+        }
+    }
+
+    for document in &mut documents {
+        if document.chunks.iter().all(|chunk| chunk.span.is_some()) {
+            document.chunks.sort_by_key(|chunk| chunk.span.map(|(start, _)| start));
+        }
+        document.metadata = common_metadata(&document.chunks);
+        document.title = document_title(&document.metadata, &document.source);
+    }
+    documents
+}
+
+/// 所有文档块取值相同的元数据字段
+fn common_metadata(chunks: &[ImportedChunk]) -> Map<String, Value> {
+    let Some((first, rest)) = chunks.split_first() else {
+        return Map::new();
+    };
+    first.metadata.iter()
+        .filter(|(key, value)| rest.iter().all(|chunk| chunk.metadata.get(*key) == Some(*value)))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// 依次取 title、file_name 与来源路径的文件名作为标题
+fn document_title(metadata: &Map<String, Value>, source: &str) -> String {
+    let title = ["title", "file_name"].iter()
+        .find_map(|key| metadata.get(*key).and_then(Value::as_str))
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            let path = source.trim_end_matches('/');
+            path.rsplit(['/', '\\']).next().unwrap_or(path).to_string()
+        });
+    let title = title.trim();
+    if title.is_empty() { source.to_string() } else { title.chars().take(255).collect() }
+}
+
+fn object_field(value: &Value, key: &str) -> Map<String, Value> {
+    value.get(key).and_then(Value::as_object).cloned().unwrap_or_default()
+}
+
+fn embedding_field(value: &Value) -> Option<Vec<f32>> {
+    value.get("embedding")?
+        .as_array()?
+        .iter()
+        .map(|v| v.as_f64().map(|v| v as f32))
+        .collect::<Option<Vec<f32>>>()
+        .filter(|vector| !vector.is_empty())
+}
+
+fn value_key(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// 文档块存储用元数据，原始元数据保存在 custom_fields 中
+fn chunk_storage_metadata(chunk: &ImportedChunk, treatment: &PiiTreatment) -> document_chunk::ChunkMetadata {
+    let mut metadata = document_chunk::ChunkMetadata {
+        page_number: ["page", "page_label", "page_number"].iter()
+            .find_map(|key| chunk.metadata.get(*key))
+            .and_then(|page| page.as_i64().or_else(|| page.as_str().and_then(|s| s.parse().ok())))
+            .map(|page| page as i32),
+        tags: chunk.metadata.get("tags")
+            .and_then(Value::as_array)
+            .map(|tags| tags.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default(),
+        custom_fields: chunk.metadata.clone().into_iter().collect(),
+        ..Default::default()
+    };
+    if let Some(source_id) = &chunk.source_id {
+        metadata.custom_fields.insert("source_id".to_string(), json!(source_id));
+    }
+    if let Some(pii) = &treatment.pii {
+        metadata.tags.extend(pii.kinds.iter().map(|kind| format!("pii:{}", kind.as_str())));
+    }
+    metadata.pii = treatment.pii.clone();
+    metadata
+}
+
+/// 外部语料导入服务
+pub struct CorpusImportService {
+    db: DatabaseConnection,
+    vector_stores: VectorStoreRegistry,
+}
+
+impl CorpusImportService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection, vector_stores: VectorStoreRegistry) -> Self {
+        Self { db, vector_stores }
+    }
+
+    /// 将解析后的语料写入知识库
+    ///
+    /// 保留原有分块；预计算向量的模型与维度和知识库一致、且内容未被个人信息策略改写时直接写入，
+    /// 其余文档块通过 `vectorizer` 重新生成向量，未提供时报错。
+    #[instrument(skip(self, corpus, vectorizer), fields(documents = corpus.documents.len()))]
+    pub async fn import(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        format: CorpusImportFormat,
+        corpus: ParsedCorpus,
+        embedding_model: Option<&str>,
+        vectorizer: Option<&dyn DocumentVectorizer>,
+    ) -> Result<CorpusImportReport, AiStudioError> {
+        let kb = KnowledgeBase::find_by_id(knowledge_base_id)
+            .filter(knowledge_base::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("知识库"))?;
+        if kb.is_archived() {
+            return Err(AiStudioError::conflict("知识库已归档，请先恢复后再导入"));
+        }
+        if corpus.documents.is_empty() {
+            return Err(AiStudioError::validation("file", "导出中没有可导入的记录"));
+        }
+
+        let reuse_embeddings = embedding_model.is_some_and(|model| model == kb.embedding_model);
+        if embedding_model.is_some() && !reuse_embeddings {
+            warn!("预计算向量的模型与知识库不一致，将重新生成: kb={}, 知识库模型={}", kb.id, kb.embedding_model);
+        }
+        let pii_policy = kb.get_config().map(|config| config.pii_policy).unwrap_or_default();
+        let overrides = if pii_policy.enabled {
+            PiiPolicyService::new(self.db.clone()).overrides(kb.id).await?
+        } else {
+            HashMap::new()
+        };
+        let encryption = TenantEncryptionService::from_config(self.db.clone())?;
+        let store = self.vector_stores.for_knowledge_base(kb.id).await?;
+
+        let mut report = CorpusImportReport {
+            knowledge_base_id,
+            format,
+            document_ids: Vec::new(),
+            chunks_created: 0,
+            embeddings_imported: 0,
+            embeddings_generated: 0,
+            excluded_chunks: 0,
+            skipped: corpus.skipped,
+        };
+
+        for imported in corpus.documents {
+            let treatments: Vec<PiiTreatment> = imported.chunks.iter()
+                .map(|chunk| apply_pii_policy(&pii_policy, &overrides, &chunk.text))
+                .collect();
+
+            // 确定每个文档块的向量来源，缺少可用向量的块统一生成
+            let mut vectors: Vec<Option<Vec<f32>>> = imported.chunks.iter()
+                .zip(&treatments)
+                .map(|(chunk, treatment)| {
+                    chunk.embedding.clone().filter(|vector| {
+                        reuse_embeddings
+                            && vector.len() == kb.vector_dimension as usize
+                            && treatment.content == chunk.text
+                    })
+                })
+                .collect();
+            let imported_count = vectors.iter().filter(|vector| vector.is_some()).count();
+            let missing: Vec<usize> = (0..vectors.len())
+                .filter(|&index| vectors[index].is_none() && !treatments[index].excluded())
+                .collect();
+            if !missing.is_empty() {
+                let vectorizer = vectorizer
+                    .ok_or_else(|| AiStudioError::validation("embedding_model", "AI 服务不可用，无法为缺少向量的文档块生成向量"))?;
+                let mut to_embed: Vec<DocumentChunk> = missing.iter()
+                    .map(|&index| vectorizer_input(&treatments[index].content, index, imported.chunks.len()))
+                    .collect();
+                vectorizer.vectorize_chunks(&mut to_embed).await?;
+                for (&index, chunk) in missing.iter().zip(to_embed) {
+                    vectors[index] = chunk.embedding;
+                }
+            }
+
+            let (document_id, points) = self
+                .insert_document(tenant_id, &kb, &encryption, format, &imported, &treatments, vectors)
+                .await?;
+            if !points.is_empty() {
+                store.upsert(kb.id, &points).await?;
+            }
+
+            report.document_ids.push(document_id);
+            report.chunks_created += imported.chunks.len();
+            report.embeddings_imported += imported_count;
+            report.embeddings_generated += missing.len();
+            report.excluded_chunks += treatments.iter().filter(|treatment| treatment.excluded()).count();
+        }
+
+        info!(
+            "外部语料导入完成: kb={}, 格式={:?}, 文档={}, 块={}, 导入向量={}, 生成向量={}, 跳过记录={}",
+            kb.id, format, report.document_ids.len(), report.chunks_created, report.embeddings_imported,
+            report.embeddings_generated, report.skipped.len()
+        );
+        Ok(report)
+    }
+
+    /// 在一个事务中写入文档、文档块与嵌入记录，返回待写入向量存储的向量
+    #[allow(clippy::too_many_arguments)]
+    async fn insert_document(
+        &self,
+        tenant_id: Uuid,
+        kb: &knowledge_base::Model,
+        encryption: &TenantEncryptionService,
+        format: CorpusImportFormat,
+        imported: &ImportedDocument,
+        treatments: &[PiiTreatment],
+        vectors: Vec<Option<Vec<f32>>>,
+    ) -> Result<(Uuid, Vec<VectorPoint>), AiStudioError> {
+        let now = Utc::now();
+        let document_id = Uuid::new_v4();
+        let content = imported.chunks.iter().map(|chunk| chunk.text.as_str()).collect::<Vec<_>>().join("\n\n");
+        let mut metadata = imported.metadata.clone();
+        metadata.insert("import".to_string(), json!({
+            "format": format,
+            "source": imported.source,
+            "imported_at": now,
+        }));
+
+        let mut new_doc = document::ActiveModel {
+            id: Set(document_id),
+            knowledge_base_id: Set(kb.id),
+            title: Set(imported.title.clone()),
+            content: Set(content.clone()),
+            raw_content: Set(Some(content.clone())),
+            summary: Set(None),
+            doc_type: Set(document::DocumentType::Text),
+            status: Set(document::DocumentStatus::Completed),
+            file_path: Set(None),
+            file_name: Set(None),
+            file_size: Set(content.len() as i64),
+            mime_type: Set(Some("text/plain".to_string())),
+            content_hash: Set(Some(format!("{:x}", md5::compute(&content)))),
+            metadata: Set(Value::Object(metadata)),
+            processing_config: Set(json!({})),
+            chunk_count: Set(imported.chunks.len() as i32),
+            processing_started_at: Set(None),
+            processing_completed_at: Set(Some(now.into())),
+            error_message: Set(None),
+            version: Set(1),
+            expires_at: Set(None),
+            review_at: Set(None),
+            owner_id: Set(None),
+            review_notified_at: Set(None),
+            clearance: Set(document::ClearanceLevel::Public),
+            evergreen: Set(false),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+            revision: Set(1),
+        };
+        encryption.encrypt_document(tenant_id, &mut new_doc).await?;
+
+        let txn = self.db.begin().await?;
+        new_doc.insert(&txn).await?;
+
+        let mut points = Vec::new();
+        for (index, ((chunk, treatment), vector)) in imported.chunks.iter().zip(treatments).zip(vectors).enumerate() {
+            let chunk_id = Uuid::new_v4();
+            let text_hash = format!("{:x}", md5::compute(&treatment.content));
+            let position = chunk.span.map(|(start, end)| document_chunk::PositionInfo {
+                start_offset: start as u32,
+                end_offset: end as u32,
+                ..Default::default()
+            }).unwrap_or_default();
+            document_chunk::ActiveModel {
+                id: Set(chunk_id),
+                document_id: Set(document_id),
+                knowledge_base_id: Set(kb.id),
+                chunk_index: Set(index as i32),
+                content: Set(treatment.content.clone()),
+                title: Set(None),
+                summary: Set(None),
+                status: Set(document_chunk::ChunkStatus::Completed),
+                content_length: Set(treatment.content.len() as i32),
+                word_count: Set(treatment.content.split_whitespace().count() as i32),
+                content_hash: Set(text_hash.clone()),
+                metadata: Set(serde_json::to_value(chunk_storage_metadata(chunk, treatment))?),
+                position_info: Set(serde_json::to_value(position)?),
+                processing_started_at: Set(None),
+                processing_completed_at: Set(Some(now.into())),
+                error_message: Set(None),
+                clearance: Set(document::ClearanceLevel::Public),
+                created_at: Set(now.into()),
+                updated_at: Set(now.into()),
+            }
+            .insert(&txn)
+            .await?;
+
+            let Some(vector) = vector.filter(|_| !treatment.excluded()) else {
+                continue;
+            };
+            let embedding_id = Uuid::new_v4();
+            embedding::ActiveModel {
+                id: Set(embedding_id),
+                chunk_id: Set(chunk_id),
+                document_id: Set(document_id),
+                knowledge_base_id: Set(kb.id),
+                embedding_type: Set(embedding::EmbeddingType::Text),
+                status: Set(embedding::EmbeddingStatus::Completed),
+                vector: Set(None),
+                dimension: Set(vector.len() as i32),
+                model_name: Set(kb.embedding_model.clone()),
+                model_version: Set("1".to_string()),
+                source_text: Set(treatment.content.clone()),
+                text_hash: Set(text_hash),
+                metadata: Set(serde_json::to_value(embedding::EmbeddingMetadata::default())?),
+                processing_started_at: Set(None),
+                processing_completed_at: Set(Some(now.into())),
+                error_message: Set(None),
+                created_at: Set(now.into()),
+                updated_at: Set(now.into()),
+            }
+            .insert(&txn)
+            .await?;
+            points.push(VectorPoint { id: embedding_id, chunk_id, document_id, vector });
+        }
+        txn.commit().await?;
+
+        Ok((document_id, points))
+    }
+}
+
+/// 构造向量化输入
+fn vectorizer_input(content: &str, index: usize, total: usize) -> DocumentChunk {
+    DocumentChunk {
+        id: Uuid::new_v4(),
+        content: content.to_string(),
+        metadata: ChunkMetadata {
+            chunk_index: index,
+            total_chunks: total,
+            word_count: content.split_whitespace().count() as u32,
+            character_count: content.chars().count() as u32,
+            language: None,
+            chunk_type: ChunkType::Text,
+            source_page: None,
+            overlap_with_previous: false,
+            overlap_with_next: false,
+            custom_properties: HashMap::new(),
+            table: None,
+        },
+        embedding: None,
+        position: ChunkPosition { start_char: 0, end_char: content.len(), start_line: None, end_line: None },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_langchain_jsonl() {
+        let data = r#"
+{"page_content": "第一段", "metadata": {"source": "docs/handbook.md", "page": 1, "lang": "zh"}}
+{"lc": 1, "type": "constructor", "id": ["langchain", "schema", "document", "Document"], "kwargs": {"page_content": "第二段", "metadata": {"source": "docs/handbook.md", "page": 2, "lang": "zh"}}}
+{"page_content": "", "metadata": {"source": "docs/empty.md"}}
+{"page_content": "独立记录", "metadata": {}, "embedding": [0.1, 0.2]}
+"#;
+        assert_eq!(detect_format(data), Some(CorpusImportFormat::LangchainJsonl));
+
+        let parsed = parse_corpus(data, CorpusImportFormat::LangchainJsonl, None).unwrap();
+        assert_eq!(parsed.skipped, vec![SkippedImportRecord { record: 4, reason: "正文为空".to_string() }]);
+        assert_eq!(parsed.documents.len(), 2);
+
+        let handbook = &parsed.documents[0];
+        assert_eq!(handbook.title, "handbook.md");
+        assert_eq!(handbook.chunks.len(), 2);
+        assert_eq!(handbook.chunks[1].text, "第二段");
+        // 各块页码不同，只保留共有的元数据
+        assert_eq!(handbook.metadata.get("lang"), Some(&json!("zh")));
+        assert!(handbook.metadata.get("page").is_none());
+
+        assert_eq!(parsed.documents[1].source, "record-5");
+        assert_eq!(parsed.documents[1].chunks[0].embedding, Some(vec![0.1, 0.2]));
+    }
+
+    #[test]
+    fn test_parse_llamaindex_nodes() {
+        let docstore = json!({
+            "docstore/data": {
+                "n2": {"__type__": "1", "__data__": {
+                    "id_": "n2", "text": "后半部分", "metadata": {"file_name": "guide.pdf"},
+                    "relationships": {"1": {"node_id": "doc-1"}}, "start_char_idx": 20, "end_char_idx": 40,
+                    "embedding": [0.5, 0.5]
+                }},
+                "n1": {"__type__": "1", "__data__": serde_json::to_string(&json!({
+                    "id_": "n1", "text": "前半部分", "metadata": {"file_name": "guide.pdf"},
+                    "relationships": {"1": {"node_id": "doc-1"}}, "start_char_idx": 0, "end_char_idx": 20
+                })).unwrap()},
+                "img": {"__type__": "2", "__data__": {"id_": "img", "image": "..."}}
+            }
+        })
+        .to_string();
+        assert_eq!(detect_format(&docstore), Some(CorpusImportFormat::LlamaindexNodes));
+
+        let parsed = parse_corpus(&docstore, CorpusImportFormat::LlamaindexNodes, None).unwrap();
+        assert_eq!(parsed.skipped.len(), 1);
+        assert_eq!(parsed.documents.len(), 1);
+        let guide = &parsed.documents[0];
+        assert_eq!(guide.source, "doc-1");
+        assert_eq!(guide.title, "guide.pdf");
+        assert_eq!(guide.chunks.iter().map(|c| c.text.as_str()).collect::<Vec<_>>(), vec!["前半部分", "后半部分"]);
+        assert_eq!(guide.chunks[1].span, Some((20, 40)));
+
+        // 指定归并字段时按元数据归并
+        let nodes = json!([
+            {"id_": "a", "text": "甲", "metadata": {"team": "hr"}},
+            {"id_": "b", "text": "乙", "metadata": {"team": "it"}},
+            {"id_": "c", "text": "丙", "metadata": {"team": "hr"}}
+        ])
+        .to_string();
+        let parsed = parse_corpus(&nodes, CorpusImportFormat::LlamaindexNodes, Some("team")).unwrap();
+        assert_eq!(parsed.documents.iter().map(|d| d.chunks.len()).collect::<Vec<_>>(), vec![2, 1]);
+    }
+}
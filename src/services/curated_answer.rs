@@ -0,0 +1,874 @@
+// 精选答案服务
+// 知识管理员按问题簇复核近期问答，为同类问题审定精选答案；相似问题优先返回精选答案，
+// 精选答案按复核周期到期，到期后不再应答，复核后顺延
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{ActiveEnum, ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult, Statement};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, warn};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::ai::embedding_pool::{EmbeddingPool, EmbeddingPriority};
+use crate::ai::rig_client::RigAiClient;
+use crate::db::entities::document::ClearanceLevel;
+use crate::errors::AiStudioError;
+use crate::services::faq::{bigram_similarity, decide_match, FaqMatchMethod};
+use crate::services::question_suggestion::{
+    cluster_questions, cosine_similarity, normalize_question, parse_vector, LoggedQuestion,
+};
+
+/// 问题的最大长度（字符）
+const MAX_QUESTION_LENGTH: usize = 1000;
+
+/// 答案的最大长度（字符）
+const MAX_ANSWER_LENGTH: usize = 8000;
+
+/// 单个精选答案最多的问法数
+const MAX_VARIANTS: usize = 20;
+
+/// 默认复核周期（天）
+const DEFAULT_REVIEW_INTERVAL_DAYS: u32 = 90;
+
+/// 最长复核周期（天）
+const MAX_REVIEW_INTERVAL_DAYS: u32 = 365;
+
+/// 默认复核近期问答的时间窗口（天）
+const DEFAULT_REVIEW_WINDOW_DAYS: u32 = 7;
+
+/// 最长复核时间窗口（天）
+const MAX_REVIEW_WINDOW_DAYS: u32 = 90;
+
+/// 复核队列默认返回的问题簇数
+const DEFAULT_REVIEW_LIMIT: usize = 20;
+
+/// 复核队列最多返回的问题簇数
+const MAX_REVIEW_LIMIT: usize = 100;
+
+/// 参与聚类的最大问答记录数
+const MAX_REVIEW_QUERIES: u64 = 1000;
+
+/// 两个问题归为同一簇的最低相似度
+const CLUSTER_SIMILARITY: f32 = 0.85;
+
+/// 参与匹配的最大精选答案数
+const MAX_MATCH_CANDIDATES: u64 = 2000;
+
+/// 视为负面的反馈类型
+const NEGATIVE_FEEDBACK: [&str; 4] = ["not_helpful", "incorrect", "incomplete", "irrelevant"];
+
+/// 精选答案状态，到期状态在读取时按到期时间判定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CuratedAnswerStatus {
+    /// 生效中，相似问题优先返回
+    Active,
+    /// 已到复核期，复核前不再应答
+    ReviewDue,
+    /// 已停用
+    Retired,
+}
+
+impl CuratedAnswerStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::ReviewDue => "review_due",
+            Self::Retired => "retired",
+        }
+    }
+
+    fn resolve(is_active: bool, expires_at: DateTime<Utc>, now: DateTime<Utc>) -> Self {
+        if !is_active {
+            Self::Retired
+        } else if expires_at <= now {
+            Self::ReviewDue
+        } else {
+            Self::Active
+        }
+    }
+}
+
+/// 创建精选答案请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateCuratedAnswerRequest {
+    /// 适用的知识库，为空时适用于租户下所有知识库
+    pub knowledge_base_id: Option<Uuid>,
+    /// 代表问题
+    pub question: String,
+    /// 精选答案
+    pub answer: String,
+    /// 同一问题簇中的其他问法
+    #[serde(default)]
+    pub variants: Vec<String>,
+    /// 据以整理答案的问答记录查询 ID
+    pub source_query_id: Option<String>,
+    /// 复核周期（天），默认 90，最长 365
+    pub review_interval_days: Option<u32>,
+    /// 访问密级
+    #[serde(default)]
+    pub clearance: ClearanceLevel,
+}
+
+/// 更新精选答案请求，修改答案视为完成一次复核
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateCuratedAnswerRequest {
+    /// 代表问题
+    pub question: Option<String>,
+    /// 精选答案
+    pub answer: Option<String>,
+    /// 同一问题簇中的其他问法
+    pub variants: Option<Vec<String>>,
+    /// 复核周期（天）
+    pub review_interval_days: Option<u32>,
+    /// 访问密级
+    pub clearance: Option<ClearanceLevel>,
+    /// 是否启用
+    pub is_active: Option<bool>,
+}
+
+/// 精选答案列表查询参数
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+pub struct CuratedAnswerQuery {
+    /// 按知识库过滤
+    pub knowledge_base_id: Option<Uuid>,
+    /// 按状态过滤
+    pub status: Option<CuratedAnswerStatus>,
+}
+
+/// 复核队列查询参数
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+pub struct AnswerReviewQuery {
+    /// 按知识库过滤
+    pub knowledge_base_id: Option<Uuid>,
+    /// 统计最近多少天的问答，默认 7，最长 90
+    pub days: Option<u32>,
+    /// 返回的问题簇数，默认 20，最多 100
+    pub limit: Option<usize>,
+    /// 只返回尚无精选答案的问题簇
+    #[serde(default)]
+    pub uncurated_only: bool,
+}
+
+/// 精选答案
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CuratedAnswer {
+    pub id: Uuid,
+    pub knowledge_base_id: Option<Uuid>,
+    pub question: String,
+    pub answer: String,
+    pub variants: Vec<String>,
+    pub clearance: ClearanceLevel,
+    pub source_query_id: Option<String>,
+    pub status: CuratedAnswerStatus,
+    pub review_interval_days: i32,
+    /// 最近复核人
+    pub reviewed_by: Option<Uuid>,
+    /// 最近复核时间
+    pub reviewed_at: DateTime<Utc>,
+    /// 到期时间，到期后需复核才能继续应答
+    pub expires_at: DateTime<Utc>,
+    pub hit_count: i32,
+    pub last_hit_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 精选答案命中信息，应答时标注答案来自人工审定
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CuratedAnswerMatch {
+    /// 精选答案 ID
+    pub curated_answer_id: Uuid,
+    /// 精选答案的代表问题
+    pub question: String,
+    /// 匹配得分（0-1）
+    pub score: f32,
+    /// 命中方式
+    pub method: FaqMatchMethod,
+    /// 最近复核时间
+    pub reviewed_at: DateTime<Utc>,
+    /// 到期时间
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 待复核的问题簇
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AnswerCluster {
+    /// 代表问题
+    pub representative: String,
+    /// 时间窗口内被问到的次数
+    pub question_count: u32,
+    /// 簇内的不同问法
+    pub variants: Vec<String>,
+    /// 最近一次问答的查询 ID
+    pub latest_query_id: Option<String>,
+    /// 最近一次生成的答案
+    pub latest_answer: Option<String>,
+    /// 平均置信度
+    pub average_confidence: f32,
+    /// 负面反馈次数
+    pub negative_feedback_count: u32,
+    /// 最近提问时间
+    pub last_asked_at: DateTime<Utc>,
+    /// 已覆盖该问题簇的精选答案
+    pub curated_answer_id: Option<Uuid>,
+}
+
+/// 复核队列使用的问答记录
+struct ReviewedQuery {
+    query_id: Option<String>,
+    answer: Option<String>,
+    confidence_score: f32,
+    negative_feedback: bool,
+    created_at: DateTime<Utc>,
+}
+
+/// 参与匹配的精选答案
+struct MatchCandidate {
+    answer: CuratedAnswer,
+    embedding: Option<Vec<f32>>,
+}
+
+/// 精选答案服务
+pub struct CuratedAnswerService {
+    db: DatabaseConnection,
+    embedder: Option<Arc<RigAiClient>>,
+}
+
+impl CuratedAnswerService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db, embedder: None }
+    }
+
+    /// 设置未启用本地嵌入工作池时使用的嵌入客户端
+    pub fn with_embedder(mut self, embedder: Arc<RigAiClient>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// 按问题簇汇总近期问答，负面反馈多、提问多的问题簇在前
+    #[instrument(skip(self, query))]
+    pub async fn review_queue(
+        &self,
+        tenant_id: Uuid,
+        clearance: ClearanceLevel,
+        query: &AnswerReviewQuery,
+    ) -> Result<Vec<AnswerCluster>, AiStudioError> {
+        let days = query.days.unwrap_or(DEFAULT_REVIEW_WINDOW_DAYS).clamp(1, MAX_REVIEW_WINDOW_DAYS);
+        let limit = query.limit.unwrap_or(DEFAULT_REVIEW_LIMIT).clamp(1, MAX_REVIEW_LIMIT);
+        let since = Utc::now() - Duration::days(days as i64);
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT query_id, question, question_embedding::text AS embedding, answer,
+                       confidence_score, feedback_type, created_at
+                FROM qa_query_logs
+                WHERE tenant_id = $1
+                    AND ($2::uuid IS NULL OR knowledge_base_id = $2)
+                    AND clearance <= $3
+                    AND created_at >= $4
+                ORDER BY created_at DESC
+                LIMIT $5
+                "#,
+                vec![
+                    tenant_id.into(),
+                    query.knowledge_base_id.into(),
+                    clearance.into(),
+                    since.into(),
+                    (MAX_REVIEW_QUERIES as i64).into(),
+                ],
+            ))
+            .await?;
+
+        let mut questions = Vec::with_capacity(rows.len());
+        let mut queries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let embedding: Option<String> = row.try_get("", "embedding")?;
+            let feedback_type: Option<String> = row.try_get("", "feedback_type")?;
+            questions.push(LoggedQuestion {
+                question: row.try_get("", "question")?,
+                embedding: embedding.as_deref().and_then(parse_vector),
+            });
+            queries.push(ReviewedQuery {
+                query_id: row.try_get("", "query_id")?,
+                answer: row.try_get("", "answer")?,
+                confidence_score: row.try_get("", "confidence_score")?,
+                negative_feedback: feedback_type.is_some_and(|t| NEGATIVE_FEEDBACK.contains(&t.as_str())),
+                created_at: row.try_get("", "created_at")?,
+            });
+        }
+
+        let candidates = self.candidates(tenant_id, query.knowledge_base_id, clearance, None, false).await?;
+        let mut clusters: Vec<AnswerCluster> = cluster_questions(&questions, CLUSTER_SIMILARITY)
+            .into_iter()
+            .filter_map(|cluster| {
+                // 记录按时间倒序，簇内第一条即最近一次问答
+                let latest = &queries[*cluster.members.first()?];
+                let normalized = normalize_question(&cluster.representative);
+                let curated_answer_id = best_match(&candidates, &normalized, cluster.centroid.as_deref())
+                    .map(|(candidate, _, _)| candidate.answer.id);
+
+                let mut seen = HashSet::new();
+                let variants = cluster.members.iter()
+                    .map(|&i| questions[i].question.trim().to_string())
+                    .filter(|q| seen.insert(normalize_question(q)))
+                    .take(MAX_VARIANTS)
+                    .collect();
+                let members = cluster.members.iter().map(|&i| &queries[i]);
+                Some(AnswerCluster {
+                    question_count: cluster.count,
+                    variants,
+                    latest_query_id: latest.query_id.clone(),
+                    latest_answer: latest.answer.clone(),
+                    average_confidence: members.clone().map(|q| q.confidence_score).sum::<f32>() / cluster.count as f32,
+                    negative_feedback_count: members.filter(|q| q.negative_feedback).count() as u32,
+                    last_asked_at: latest.created_at,
+                    curated_answer_id,
+                    representative: cluster.representative,
+                })
+            })
+            .filter(|cluster| !query.uncurated_only || cluster.curated_answer_id.is_none())
+            .collect();
+
+        clusters.sort_by(|a, b| {
+            b.negative_feedback_count.cmp(&a.negative_feedback_count).then(b.question_count.cmp(&a.question_count))
+        });
+        clusters.truncate(limit);
+        Ok(clusters)
+    }
+
+    /// 创建精选答案，密级不能高于操作者密级
+    #[instrument(skip(self, request))]
+    pub async fn create(
+        &self,
+        tenant_id: Uuid,
+        created_by: Uuid,
+        operator_clearance: ClearanceLevel,
+        request: CreateCuratedAnswerRequest,
+    ) -> Result<CuratedAnswer, AiStudioError> {
+        if request.clearance > operator_clearance {
+            return Err(AiStudioError::forbidden("无权设置高于自身密级的内容"));
+        }
+        let question = validate_text("question", &request.question, MAX_QUESTION_LENGTH)?;
+        let answer = validate_text("answer", &request.answer, MAX_ANSWER_LENGTH)?;
+        let variants = normalize_variants(&question, request.variants)?;
+        let interval = validate_interval(request.review_interval_days.unwrap_or(DEFAULT_REVIEW_INTERVAL_DAYS))?;
+        if let Some(kb_id) = request.knowledge_base_id {
+            self.ensure_knowledge_base(tenant_id, kb_id).await?;
+        }
+        if let Some(query_id) = &request.source_query_id {
+            self.ensure_source_query(tenant_id, query_id).await?;
+        }
+
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO curated_answers
+                    (id, tenant_id, knowledge_base_id, question, answer, variants, clearance, source_query_id,
+                     review_interval_days, reviewed_by, reviewed_at, expires_at, created_by, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $10, $11, $11)
+                "#,
+                vec![
+                    id.into(),
+                    tenant_id.into(),
+                    request.knowledge_base_id.into(),
+                    question.clone().into(),
+                    answer.into(),
+                    serde_json::to_value(&variants)?.into(),
+                    request.clearance.into(),
+                    request.source_query_id.into(),
+                    (interval as i32).into(),
+                    created_by.into(),
+                    now.into(),
+                    (now + Duration::days(interval as i64)).into(),
+                ],
+            ))
+            .await?;
+
+        self.refresh_embedding(id, &question).await;
+        info!("精选答案创建成功: id={}, tenant={}", id, tenant_id);
+        self.get(tenant_id, id, operator_clearance).await
+    }
+
+    /// 列出操作者可见的精选答案，即将到期的在前
+    pub async fn list(
+        &self,
+        tenant_id: Uuid,
+        clearance: ClearanceLevel,
+        query: &CuratedAnswerQuery,
+    ) -> Result<Vec<CuratedAnswer>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT * FROM curated_answers
+                WHERE tenant_id = $1
+                    AND clearance <= $2
+                    AND ($3::uuid IS NULL OR knowledge_base_id = $3)
+                    AND ($4::text IS NULL OR $4 = CASE
+                        WHEN NOT is_active THEN 'retired'
+                        WHEN expires_at <= $5 THEN 'review_due'
+                        ELSE 'active'
+                    END)
+                ORDER BY expires_at
+                "#,
+                vec![
+                    tenant_id.into(),
+                    clearance.into(),
+                    query.knowledge_base_id.into(),
+                    query.status.map(|s| s.as_str().to_string()).into(),
+                    Utc::now().into(),
+                ],
+            ))
+            .await?;
+
+        let now = Utc::now();
+        rows.iter().map(|row| curated_answer_from_row(row, now)).collect()
+    }
+
+    /// 获取精选答案
+    pub async fn get(&self, tenant_id: Uuid, id: Uuid, clearance: ClearanceLevel) -> Result<CuratedAnswer, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT * FROM curated_answers WHERE id = $1 AND tenant_id = $2 AND clearance <= $3",
+                vec![id.into(), tenant_id.into(), clearance.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("精选答案"))?;
+        curated_answer_from_row(&row, Utc::now())
+    }
+
+    /// 更新精选答案；修改答案视为完成一次复核，修改复核周期时按最近复核时间重新计算到期时间
+    #[instrument(skip(self, request))]
+    pub async fn update(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+        operator_id: Uuid,
+        operator_clearance: ClearanceLevel,
+        request: UpdateCuratedAnswerRequest,
+    ) -> Result<CuratedAnswer, AiStudioError> {
+        let existing = self.get(tenant_id, id, operator_clearance).await?;
+        if request.clearance.is_some_and(|clearance| clearance > operator_clearance) {
+            return Err(AiStudioError::forbidden("无权设置高于自身密级的内容"));
+        }
+
+        let question = match &request.question {
+            Some(question) => validate_text("question", question, MAX_QUESTION_LENGTH)?,
+            None => existing.question.clone(),
+        };
+        let answer = match &request.answer {
+            Some(answer) => validate_text("answer", answer, MAX_ANSWER_LENGTH)?,
+            None => existing.answer.clone(),
+        };
+        let variants = match request.variants {
+            Some(variants) => normalize_variants(&question, variants)?,
+            None => existing.variants.clone(),
+        };
+        let interval = match request.review_interval_days {
+            Some(days) => validate_interval(days)?,
+            None => existing.review_interval_days as u32,
+        };
+        let (reviewed_by, reviewed_at) = if request.answer.is_some() {
+            (Some(operator_id), Utc::now())
+        } else {
+            (existing.reviewed_by, existing.reviewed_at)
+        };
+
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                UPDATE curated_answers
+                SET question = $2, answer = $3, variants = $4, clearance = $5, review_interval_days = $6,
+                    reviewed_by = $7, reviewed_at = $8, expires_at = $9, is_active = $10, updated_at = $11
+                WHERE id = $1
+                "#,
+                vec![
+                    id.into(),
+                    question.clone().into(),
+                    answer.into(),
+                    serde_json::to_value(&variants)?.into(),
+                    request.clearance.unwrap_or(existing.clearance).into(),
+                    (interval as i32).into(),
+                    reviewed_by.into(),
+                    reviewed_at.into(),
+                    (reviewed_at + Duration::days(interval as i64)).into(),
+                    request.is_active.unwrap_or(existing.status != CuratedAnswerStatus::Retired).into(),
+                    Utc::now().into(),
+                ],
+            ))
+            .await?;
+
+        if question != existing.question {
+            self.refresh_embedding(id, &question).await;
+        }
+        self.get(tenant_id, id, operator_clearance).await
+    }
+
+    /// 确认答案仍然有效，按复核周期顺延到期时间
+    #[instrument(skip(self))]
+    pub async fn confirm_review(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+        reviewer_id: Uuid,
+        clearance: ClearanceLevel,
+    ) -> Result<CuratedAnswer, AiStudioError> {
+        let existing = self.get(tenant_id, id, clearance).await?;
+        let now = Utc::now();
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                UPDATE curated_answers
+                SET reviewed_by = $2, reviewed_at = $3, expires_at = $4, updated_at = $3
+                WHERE id = $1
+                "#,
+                vec![
+                    id.into(),
+                    reviewer_id.into(),
+                    now.into(),
+                    (now + Duration::days(existing.review_interval_days as i64)).into(),
+                ],
+            ))
+            .await?;
+
+        info!("精选答案已复核: id={}, reviewer={}", id, reviewer_id);
+        self.get(tenant_id, id, clearance).await
+    }
+
+    /// 删除精选答案
+    pub async fn delete(&self, tenant_id: Uuid, id: Uuid, clearance: ClearanceLevel) -> Result<(), AiStudioError> {
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "DELETE FROM curated_answers WHERE id = $1 AND tenant_id = $2 AND clearance <= $3",
+                vec![id.into(), tenant_id.into(), clearance.into()],
+            ))
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AiStudioError::not_found("精选答案"));
+        }
+        Ok(())
+    }
+
+    /// 为问题查找生效中的精选答案
+    ///
+    /// 与 FAQ 相同，结合文本相似度与问题向量相似度判断是否命中；只匹配未到期且提问者可见的答案。
+    /// 命中时返回答案正文并累加命中次数。
+    #[instrument(skip(self, question, question_embedding))]
+    pub async fn match_question(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Option<Uuid>,
+        clearance: ClearanceLevel,
+        question: &str,
+        question_embedding: Option<&[f32]>,
+    ) -> Result<Option<(String, CuratedAnswerMatch)>, AiStudioError> {
+        let normalized = normalize_question(question);
+        if normalized.is_empty() {
+            return Ok(None);
+        }
+
+        let candidates = self.candidates(tenant_id, knowledge_base_id, clearance, question_embedding, true).await?;
+        let Some((candidate, method, score)) = best_match(&candidates, &normalized, question_embedding) else {
+            debug!("未命中精选答案: tenant={}, kb={:?}", tenant_id, knowledge_base_id);
+            return Ok(None);
+        };
+
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE curated_answers SET hit_count = hit_count + 1, last_hit_at = $2 WHERE id = $1",
+                vec![candidate.answer.id.into(), Utc::now().into()],
+            ))
+            .await;
+        if let Err(e) = result {
+            warn!("更新精选答案命中次数失败: id={}, error={}", candidate.answer.id, e);
+        }
+
+        info!("命中精选答案: id={}, method={:?}, score={:.3}", candidate.answer.id, method, score);
+        let curated = &candidate.answer;
+        Ok(Some((
+            curated.answer.clone(),
+            CuratedAnswerMatch {
+                curated_answer_id: curated.id,
+                question: curated.question.clone(),
+                score,
+                method,
+                reviewed_at: curated.reviewed_at,
+                expires_at: curated.expires_at,
+            },
+        )))
+    }
+
+    /// 加载参与匹配的精选答案
+    ///
+    /// 未指定知识库时包含所有知识库的答案；`active_only` 为假时包含已到期的答案，用于判断问题簇是否已有答案。
+    async fn candidates(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Option<Uuid>,
+        clearance: ClearanceLevel,
+        question_embedding: Option<&[f32]>,
+        active_only: bool,
+    ) -> Result<Vec<MatchCandidate>, AiStudioError> {
+        let now = Utc::now();
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT *, embedding::text AS embedding_text
+                FROM curated_answers
+                WHERE tenant_id = $1
+                    AND is_active
+                    AND clearance <= $2
+                    AND ($3::uuid IS NULL OR knowledge_base_id IS NULL OR knowledge_base_id = $3)
+                    AND (NOT $4 OR expires_at > $5)
+                ORDER BY CASE WHEN $6::vector IS NULL OR embedding IS NULL THEN 1 ELSE 0 END, embedding <=> $6::vector
+                LIMIT $7
+                "#,
+                vec![
+                    tenant_id.into(),
+                    clearance.into(),
+                    knowledge_base_id.into(),
+                    active_only.into(),
+                    now.into(),
+                    question_embedding.map(format_vector).into(),
+                    (MAX_MATCH_CANDIDATES as i64).into(),
+                ],
+            ))
+            .await?;
+
+        rows.iter()
+            .map(|row| -> Result<MatchCandidate, AiStudioError> {
+                let embedding: Option<String> = row.try_get("", "embedding_text")?;
+                Ok(MatchCandidate {
+                    answer: curated_answer_from_row(row, now)?,
+                    embedding: embedding.as_deref().and_then(parse_vector),
+                })
+            })
+            .collect()
+    }
+
+    async fn ensure_knowledge_base(&self, tenant_id: Uuid, knowledge_base_id: Uuid) -> Result<(), AiStudioError> {
+        self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT 1 FROM knowledge_bases WHERE id = $1 AND tenant_id = $2",
+                vec![knowledge_base_id.into(), tenant_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("知识库"))?;
+        Ok(())
+    }
+
+    async fn ensure_source_query(&self, tenant_id: Uuid, query_id: &str) -> Result<(), AiStudioError> {
+        self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT 1 FROM qa_query_logs WHERE tenant_id = $1 AND query_id = $2",
+                vec![tenant_id.into(), query_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("问答记录"))?;
+        Ok(())
+    }
+
+    /// 重新生成代表问题的向量，失败时清空向量，答案仍可通过文本匹配命中
+    async fn refresh_embedding(&self, id: Uuid, question: &str) {
+        let vector = self.embed(question).await.map(|v| format_vector(&v));
+
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE curated_answers SET embedding = $1::vector WHERE id = $2",
+                vec![vector.into(), id.into()],
+            ))
+            .await;
+        if let Err(e) = result {
+            warn!("保存精选答案向量失败: id={}, error={}", id, e);
+        }
+    }
+
+    async fn embed(&self, text: &str) -> Option<Vec<f32>> {
+        let result = match (EmbeddingPool::global(), &self.embedder) {
+            (Some(pool), _) => pool.embed(text, EmbeddingPriority::Bulk).await,
+            (None, Some(embedder)) => embedder.generate_embedding(text).await.map(|r| r.embedding),
+            (None, None) => return None,
+        };
+
+        result.map_err(|e| warn!("生成精选答案向量失败: {}", e)).ok()
+    }
+}
+
+/// 挑选与问题最匹配的精选答案，返回命中方式与得分
+fn best_match<'a>(
+    candidates: &'a [MatchCandidate],
+    normalized_question: &str,
+    question_embedding: Option<&[f32]>,
+) -> Option<(&'a MatchCandidate, FaqMatchMethod, f32)> {
+    candidates.iter()
+        .filter_map(|candidate| {
+            let fuzzy = std::iter::once(&candidate.answer.question)
+                .chain(&candidate.answer.variants)
+                .map(|text| bigram_similarity(normalized_question, &normalize_question(text)))
+                .fold(0.0, f32::max);
+            let semantic = match (question_embedding, candidate.embedding.as_deref()) {
+                (Some(question), Some(answer)) => Some(cosine_similarity(question, answer)),
+                _ => None,
+            };
+            let (method, score) = decide_match(fuzzy, semantic)?;
+            Some((candidate, method, score))
+        })
+        .max_by(|a, b| a.2.total_cmp(&b.2))
+}
+
+fn validate_text(field: &str, text: &str, max_length: usize) -> Result<String, AiStudioError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(AiStudioError::validation(field, "不能为空"));
+    }
+    if text.chars().count() > max_length {
+        return Err(AiStudioError::validation(field, format!("不能超过 {} 个字符", max_length)));
+    }
+    Ok(text.to_string())
+}
+
+fn validate_interval(days: u32) -> Result<u32, AiStudioError> {
+    if !(1..=MAX_REVIEW_INTERVAL_DAYS).contains(&days) {
+        return Err(AiStudioError::validation(
+            "review_interval_days",
+            format!("复核周期必须在 1 到 {} 天之间", MAX_REVIEW_INTERVAL_DAYS),
+        ));
+    }
+    Ok(days)
+}
+
+/// 去除空白、去重（忽略与代表问题相同的问法）并限制问法数量
+fn normalize_variants(question: &str, variants: Vec<String>) -> Result<Vec<String>, AiStudioError> {
+    let mut seen = HashSet::from([normalize_question(question)]);
+    let mut normalized = Vec::new();
+    for variant in variants {
+        let variant = validate_text("variants", &variant, MAX_QUESTION_LENGTH)?;
+        if seen.insert(normalize_question(&variant)) {
+            normalized.push(variant);
+        }
+    }
+    if normalized.len() > MAX_VARIANTS {
+        return Err(AiStudioError::validation("variants", format!("问法不能超过 {} 个", MAX_VARIANTS)));
+    }
+    Ok(normalized)
+}
+
+fn format_vector(vector: &[f32]) -> String {
+    format!("[{}]", vector.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))
+}
+
+fn curated_answer_from_row(row: &QueryResult, now: DateTime<Utc>) -> Result<CuratedAnswer, AiStudioError> {
+    let variants: serde_json::Value = row.try_get("", "variants")?;
+    let clearance: i16 = row.try_get("", "clearance")?;
+    let is_active: bool = row.try_get("", "is_active")?;
+    let expires_at: DateTime<Utc> = row.try_get("", "expires_at")?;
+    Ok(CuratedAnswer {
+        id: row.try_get("", "id")?,
+        knowledge_base_id: row.try_get("", "knowledge_base_id")?,
+        question: row.try_get("", "question")?,
+        answer: row.try_get("", "answer")?,
+        variants: serde_json::from_value(variants).unwrap_or_default(),
+        clearance: ClearanceLevel::try_from_value(&clearance)?,
+        source_query_id: row.try_get("", "source_query_id")?,
+        status: CuratedAnswerStatus::resolve(is_active, expires_at, now),
+        review_interval_days: row.try_get("", "review_interval_days")?,
+        reviewed_by: row.try_get("", "reviewed_by")?,
+        reviewed_at: row.try_get("", "reviewed_at")?,
+        expires_at,
+        hit_count: row.try_get("", "hit_count")?,
+        last_hit_at: row.try_get("", "last_hit_at")?,
+        created_by: row.try_get("", "created_by")?,
+        created_at: row.try_get("", "created_at")?,
+        updated_at: row.try_get("", "updated_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(question: &str, variants: &[&str], embedding: Option<Vec<f32>>) -> MatchCandidate {
+        let now = Utc::now();
+        MatchCandidate {
+            answer: CuratedAnswer {
+                id: Uuid::new_v4(),
+                knowledge_base_id: None,
+                question: question.to_string(),
+                answer: "精选答案".to_string(),
+                variants: variants.iter().map(|v| v.to_string()).collect(),
+                clearance: ClearanceLevel::Public,
+                source_query_id: None,
+                status: CuratedAnswerStatus::Active,
+                review_interval_days: 90,
+                reviewed_by: None,
+                reviewed_at: now,
+                expires_at: now + Duration::days(90),
+                hit_count: 0,
+                last_hit_at: None,
+                created_by: None,
+                created_at: now,
+                updated_at: now,
+            },
+            embedding,
+        }
+    }
+
+    #[test]
+    fn test_status_resolve() {
+        let now = Utc::now();
+        assert_eq!(CuratedAnswerStatus::resolve(true, now + Duration::days(1), now), CuratedAnswerStatus::Active);
+        assert_eq!(CuratedAnswerStatus::resolve(true, now, now), CuratedAnswerStatus::ReviewDue);
+        assert_eq!(CuratedAnswerStatus::resolve(false, now + Duration::days(1), now), CuratedAnswerStatus::Retired);
+    }
+
+    #[test]
+    fn test_best_match() {
+        let candidates = vec![
+            candidate("如何申请报销？", &["报销流程是怎样的"], Some(vec![1.0, 0.0])),
+            candidate("年假有几天？", &[], Some(vec![0.0, 1.0])),
+        ];
+
+        let (matched, method, _) = best_match(&candidates, &normalize_question("报销流程是怎样的？"), None).unwrap();
+        assert_eq!(matched.answer.question, "如何申请报销？");
+        assert_eq!(method, FaqMatchMethod::Exact);
+
+        let (matched, method, _) = best_match(&candidates, "休假规定", Some(&[0.05, 0.99])).unwrap();
+        assert_eq!(matched.answer.question, "年假有几天？");
+        assert_eq!(method, FaqMatchMethod::Semantic);
+
+        assert!(best_match(&candidates, "会议室怎么预订", Some(&[0.7, 0.7])).is_none());
+    }
+
+    #[test]
+    fn test_normalize_variants() {
+        let variants = normalize_variants(
+            "如何申请报销？",
+            vec!["如何申请报销".to_string(), " 报销怎么申请 ".to_string(), "报销怎么申请？".to_string()],
+        )
+        .unwrap();
+        assert_eq!(variants, vec!["报销怎么申请".to_string()]);
+        assert!(normalize_variants("问题", vec!["  ".to_string()]).is_err());
+        assert!(validate_interval(0).is_err());
+        assert!(validate_interval(MAX_REVIEW_INTERVAL_DAYS).is_ok());
+    }
+}
@@ -0,0 +1,692 @@
+// 数据集服务
+// 将 CSV/Excel 文件导入为带类型列的数据集，行数据存入 dataset_rows，并提供过滤、分组与聚合查询
+
+use chrono::{DateTime, NaiveDate, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, Set, Statement, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::document_processor::TableInfo;
+use crate::db::entities::dataset::{self, DatasetColumn, DatasetColumnType};
+use crate::db::entities::{document, Dataset};
+use crate::errors::AiStudioError;
+
+/// 每批写入的行数
+const INSERT_BATCH_SIZE: usize = 1000;
+
+/// 查询未指定数量时返回的行数
+const DEFAULT_QUERY_LIMIT: u32 = 100;
+
+/// 单次查询最多返回的行数
+const MAX_QUERY_LIMIT: u32 = 1000;
+
+/// 数据集概要
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DatasetSummary {
+    /// 数据集 ID
+    pub id: Uuid,
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 来源文档 ID
+    pub document_id: Uuid,
+    /// 数据集名称
+    pub name: String,
+    /// 工作表名称
+    pub sheet_name: Option<String>,
+    /// 列定义
+    pub columns: Vec<DatasetColumn>,
+    /// 行数
+    pub row_count: i32,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<dataset::Model> for DatasetSummary {
+    type Error = AiStudioError;
+
+    fn try_from(model: dataset::Model) -> Result<Self, Self::Error> {
+        Ok(Self {
+            columns: model.get_columns()?,
+            id: model.id,
+            knowledge_base_id: model.knowledge_base_id,
+            document_id: model.document_id,
+            name: model.name,
+            sheet_name: model.sheet_name,
+            row_count: model.row_count,
+            created_at: model.created_at.with_timezone(&Utc),
+        })
+    }
+}
+
+/// 聚合函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateFunction {
+    /// 计数，未指定列时统计行数
+    Count,
+    /// 去重计数
+    CountDistinct,
+    /// 求和，仅数值列
+    Sum,
+    /// 平均值，仅数值列
+    Avg,
+    /// 最小值
+    Min,
+    /// 最大值
+    Max,
+}
+
+impl AggregateFunction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::CountDistinct => "count_distinct",
+            Self::Sum => "sum",
+            Self::Avg => "avg",
+            Self::Min => "min",
+            Self::Max => "max",
+        }
+    }
+}
+
+/// 聚合项
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DatasetAggregate {
+    /// 聚合函数
+    pub function: AggregateFunction,
+    /// 聚合列，仅 `count` 可省略
+    pub column: Option<String>,
+}
+
+impl DatasetAggregate {
+    /// 结果列名，如 `sum(营收)`、`count(*)`
+    pub fn label(&self) -> String {
+        format!("{}({})", self.function.as_str(), self.column.as_deref().unwrap_or("*"))
+    }
+}
+
+/// 过滤运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOperator {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    /// 文本包含，不区分大小写
+    Contains,
+    IsNull,
+    NotNull,
+}
+
+/// 过滤条件
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DatasetFilter {
+    /// 列名
+    pub column: String,
+    /// 运算符
+    pub op: FilterOperator,
+    /// 比较值，`is_null`、`not_null` 时忽略
+    #[serde(default)]
+    pub value: Value,
+}
+
+/// 排序
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DatasetOrder {
+    /// 分组列名或聚合结果列名（如 `sum(营收)`）；明细查询时为列名
+    pub field: String,
+    /// 是否降序
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// 数据集查询
+///
+/// 未指定分组与聚合时返回明细行；指定后按分组列汇总。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct DatasetQuery {
+    /// 分组列
+    #[serde(default)]
+    pub group_by: Vec<String>,
+    /// 聚合项
+    #[serde(default)]
+    pub aggregates: Vec<DatasetAggregate>,
+    /// 过滤条件，多个条件同时满足
+    #[serde(default)]
+    pub filters: Vec<DatasetFilter>,
+    /// 排序
+    pub order_by: Option<DatasetOrder>,
+    /// 返回行数，默认 100，最多 1000
+    pub limit: Option<u32>,
+}
+
+/// 数据集查询结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DatasetQueryResult {
+    /// 结果列名
+    pub columns: Vec<String>,
+    /// 结果行，按 `columns` 顺序排列
+    pub rows: Vec<Vec<Value>>,
+    /// 结果是否因数量限制被截断
+    pub truncated: bool,
+}
+
+/// 构建好的查询语句
+struct BuiltQuery {
+    sql: String,
+    values: Vec<sea_orm::Value>,
+    columns: Vec<String>,
+    detail: bool,
+    limit: usize,
+}
+
+/// 数据集服务
+pub struct DatasetService {
+    db: DatabaseConnection,
+}
+
+impl DatasetService {
+    /// 创建数据集服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 将文档中的表格导入为数据集，替换该文档已有的数据集
+    #[instrument(skip(self, document, tables), fields(document_id = %document.id))]
+    pub async fn import_document(
+        &self,
+        tenant_id: Uuid,
+        document: &document::Model,
+        tables: &[TableInfo],
+    ) -> Result<Vec<DatasetSummary>, AiStudioError> {
+        let txn = self.db.begin().await?;
+        Dataset::delete_many()
+            .filter(dataset::Column::DocumentId.eq(document.id))
+            .exec(&txn)
+            .await?;
+
+        let mut summaries = Vec::with_capacity(tables.len());
+        for table in tables {
+            let headers = unique_column_names(&table.headers);
+            let columns = infer_columns(&headers, &table.rows);
+            let name = match &table.caption {
+                Some(sheet) if tables.len() > 1 => format!("{} - {}", document.title, sheet),
+                _ => document.title.clone(),
+            };
+            let model = dataset::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                tenant_id: Set(tenant_id),
+                knowledge_base_id: Set(document.knowledge_base_id),
+                document_id: Set(document.id),
+                name: Set(name.chars().take(255).collect()),
+                sheet_name: Set(table.caption.clone().filter(|_| document.doc_type == document::DocumentType::Spreadsheet)),
+                columns: Set(serde_json::to_value(&columns)?),
+                row_count: Set(table.rows.len() as i32),
+                created_at: Set(Utc::now().into()),
+            }
+            .insert(&txn)
+            .await?;
+
+            for (batch_index, batch) in table.rows.chunks(INSERT_BATCH_SIZE).enumerate() {
+                let rows: Vec<Value> = batch.iter().map(|row| typed_row(&columns, row)).collect();
+                txn.execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    r#"
+                    INSERT INTO dataset_rows (dataset_id, row_index, data)
+                    SELECT $1, $2 + (ordinality - 1)::INTEGER, value
+                    FROM jsonb_array_elements($3::jsonb) WITH ORDINALITY
+                    "#,
+                    [model.id.into(), ((batch_index * INSERT_BATCH_SIZE) as i32).into(), Value::Array(rows).into()],
+                ))
+                .await?;
+            }
+            summaries.push(DatasetSummary::try_from(model)?);
+        }
+        txn.commit().await?;
+
+        info!("文档已导入为数据集: document_id={}, 数据集数={}", document.id, summaries.len());
+        Ok(summaries)
+    }
+
+    /// 列出租户的数据集，可按知识库过滤
+    pub async fn list(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Option<Uuid>,
+    ) -> Result<Vec<DatasetSummary>, AiStudioError> {
+        let mut select = Dataset::find().filter(dataset::Column::TenantId.eq(tenant_id));
+        if let Some(knowledge_base_id) = knowledge_base_id {
+            select = select.filter(dataset::Column::KnowledgeBaseId.eq(knowledge_base_id));
+        }
+        select
+            .order_by_asc(dataset::Column::CreatedAt)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(DatasetSummary::try_from)
+            .collect()
+    }
+
+    /// 获取数据集
+    pub async fn get(&self, tenant_id: Uuid, dataset_id: Uuid) -> Result<DatasetSummary, AiStudioError> {
+        Dataset::find_by_id(dataset_id)
+            .filter(dataset::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("数据集"))?
+            .try_into()
+    }
+
+    /// 查询数据集
+    #[instrument(skip(self, query))]
+    pub async fn query(
+        &self,
+        tenant_id: Uuid,
+        dataset_id: Uuid,
+        query: &DatasetQuery,
+    ) -> Result<DatasetQueryResult, AiStudioError> {
+        let dataset = self.get(tenant_id, dataset_id).await?;
+        let built = build_query(dataset.id, &dataset.columns, query)?;
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(DatabaseBackend::Postgres, &built.sql, built.values))
+            .await?;
+        let truncated = rows.len() > built.limit;
+
+        let mut result_rows = Vec::with_capacity(rows.len().min(built.limit));
+        for row in rows.iter().take(built.limit) {
+            if built.detail {
+                let data: Value = row.try_get("", "data")?;
+                result_rows.push(built.columns.iter().map(|name| data.get(name).cloned().unwrap_or(Value::Null)).collect());
+            } else {
+                let mut values = Vec::with_capacity(built.columns.len());
+                for i in 0..built.columns.len() {
+                    let value: Option<Value> = row.try_get("", &format!("c{}", i))?;
+                    values.push(value.unwrap_or(Value::Null));
+                }
+                result_rows.push(values);
+            }
+        }
+
+        Ok(DatasetQueryResult { columns: built.columns, rows: result_rows, truncated })
+    }
+}
+
+/// 根据列定义构建参数化 SQL，列名与比较值都以参数绑定
+fn build_query(dataset_id: Uuid, columns: &[DatasetColumn], query: &DatasetQuery) -> Result<BuiltQuery, AiStudioError> {
+    let mut values: Vec<sea_orm::Value> = vec![dataset_id.into()];
+    let find_column = |name: &str| {
+        columns.iter().find(|column| column.name == name).ok_or_else(|| {
+            AiStudioError::validation("column", format!("数据集中没有列「{}」", name))
+        })
+    };
+
+    let mut conditions = vec!["dataset_id = $1".to_string()];
+    for filter in &query.filters {
+        let column = find_column(&filter.column)?;
+        let raw = format!("(data->>{})", bind(&mut values, column.name.clone()));
+        let condition = match filter.op {
+            FilterOperator::IsNull => format!("{} IS NULL", raw),
+            FilterOperator::NotNull => format!("{} IS NOT NULL", raw),
+            FilterOperator::Contains => {
+                if column.column_type != DatasetColumnType::Text {
+                    return Err(AiStudioError::validation("op", "contains 仅适用于文本列"));
+                }
+                let text = filter_text(filter)?.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+                format!("{} ILIKE {}", raw, bind(&mut values, format!("%{}%", text)))
+            }
+            op => {
+                if column.column_type == DatasetColumnType::Boolean && !matches!(op, FilterOperator::Eq | FilterOperator::Ne) {
+                    return Err(AiStudioError::validation("op", "布尔列仅支持 eq、ne"));
+                }
+                let placeholder = bind_typed(&mut values, column, filter)?;
+                format!("{} {} {}", typed_expression(&raw, column.column_type), comparison(op), placeholder)
+            }
+        };
+        conditions.push(condition);
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_QUERY_LIMIT).clamp(1, MAX_QUERY_LIMIT) as usize;
+
+    // 明细查询
+    if query.group_by.is_empty() && query.aggregates.is_empty() {
+        let order = match &query.order_by {
+            Some(order) => {
+                let column = find_column(&order.field)?;
+                let raw = format!("(data->>{})", bind(&mut values, column.name.clone()));
+                format!("{} {} NULLS LAST, row_index", typed_expression(&raw, column.column_type), direction(order))
+            }
+            None => "row_index".to_string(),
+        };
+        let sql = format!(
+            "SELECT data FROM dataset_rows WHERE {} ORDER BY {} LIMIT {}",
+            conditions.join(" AND "),
+            order,
+            limit + 1
+        );
+        return Ok(BuiltQuery {
+            sql,
+            values,
+            columns: columns.iter().map(|column| column.name.clone()).collect(),
+            detail: true,
+            limit,
+        });
+    }
+
+    let mut select = Vec::new();
+    let mut labels = Vec::new();
+    for name in &query.group_by {
+        let column = find_column(name)?;
+        let raw = format!("(data->>{})", bind(&mut values, column.name.clone()));
+        select.push(format!("to_jsonb({}) AS c{}", typed_expression(&raw, column.column_type), select.len()));
+        labels.push(column.name.clone());
+    }
+    for aggregate in &query.aggregates {
+        let expression = match (&aggregate.column, aggregate.function) {
+            (None, AggregateFunction::Count) => "count(*)".to_string(),
+            (None, _) => {
+                return Err(AiStudioError::validation("column", format!("{} 需要指定列", aggregate.function.as_str())));
+            }
+            (Some(name), function) => {
+                let column = find_column(name)?;
+                if matches!(function, AggregateFunction::Sum | AggregateFunction::Avg) && !column.column_type.is_numeric() {
+                    return Err(AiStudioError::validation(
+                        "column",
+                        format!("{} 仅适用于数值列，「{}」不是数值列", function.as_str(), column.name),
+                    ));
+                }
+                let raw = format!("(data->>{})", bind(&mut values, column.name.clone()));
+                let typed = typed_expression(&raw, column.column_type);
+                match function {
+                    AggregateFunction::Count => format!("count({})", typed),
+                    AggregateFunction::CountDistinct => format!("count(DISTINCT {})", typed),
+                    AggregateFunction::Sum => format!("sum({})", typed),
+                    AggregateFunction::Avg => format!("round(avg({}), 6)", typed),
+                    AggregateFunction::Min => format!("min({})", typed),
+                    AggregateFunction::Max => format!("max({})", typed),
+                }
+            }
+        };
+        select.push(format!("to_jsonb({}) AS c{}", expression, select.len()));
+        labels.push(aggregate.label());
+    }
+
+    let group_by = if query.group_by.is_empty() {
+        String::new()
+    } else {
+        let positions: Vec<String> = (1..=query.group_by.len()).map(|i| i.to_string()).collect();
+        format!(" GROUP BY {}", positions.join(", "))
+    };
+    let order = match &query.order_by {
+        Some(order) => {
+            let position = labels.iter().position(|label| *label == order.field).ok_or_else(|| {
+                AiStudioError::validation("order_by", format!("排序字段「{}」不在结果列中", order.field))
+            })?;
+            format!(" ORDER BY {} {} NULLS LAST", position + 1, direction(order))
+        }
+        None if !query.group_by.is_empty() => " ORDER BY 1".to_string(),
+        None => String::new(),
+    };
+    let sql = format!(
+        "SELECT {} FROM dataset_rows WHERE {}{}{} LIMIT {}",
+        select.join(", "),
+        conditions.join(" AND "),
+        group_by,
+        order,
+        limit + 1
+    );
+    Ok(BuiltQuery { sql, values, columns: labels, detail: false, limit })
+}
+
+fn bind(values: &mut Vec<sea_orm::Value>, value: impl Into<sea_orm::Value>) -> String {
+    values.push(value.into());
+    format!("${}", values.len())
+}
+
+/// 按列类型绑定比较值
+fn bind_typed(values: &mut Vec<sea_orm::Value>, column: &DatasetColumn, filter: &DatasetFilter) -> Result<String, AiStudioError> {
+    let invalid = || AiStudioError::validation("value", format!("列「{}」的比较值类型不匹配", column.name));
+    match column.column_type {
+        DatasetColumnType::Integer | DatasetColumnType::Float => {
+            let number = match &filter.value {
+                Value::Number(number) => number.as_f64(),
+                Value::String(text) => parse_number(text),
+                _ => None,
+            }
+            .ok_or_else(invalid)?;
+            Ok(format!("{}::numeric", bind(values, number)))
+        }
+        DatasetColumnType::Boolean => {
+            let flag = match &filter.value {
+                Value::Bool(flag) => Some(*flag),
+                Value::String(text) => parse_bool(text),
+                _ => None,
+            }
+            .ok_or_else(invalid)?;
+            Ok(bind(values, flag))
+        }
+        DatasetColumnType::Date => {
+            let date = filter.value.as_str().and_then(parse_date).ok_or_else(invalid)?;
+            Ok(format!("{}::date", bind(values, date)))
+        }
+        DatasetColumnType::Text => Ok(bind(values, filter_text(filter)?)),
+    }
+}
+
+fn filter_text(filter: &DatasetFilter) -> Result<String, AiStudioError> {
+    match &filter.value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Number(number) => Ok(number.to_string()),
+        Value::Bool(flag) => Ok(flag.to_string()),
+        _ => Err(AiStudioError::validation("value", "缺少比较值")),
+    }
+}
+
+fn typed_expression(raw: &str, column_type: DatasetColumnType) -> String {
+    match column_type {
+        DatasetColumnType::Integer | DatasetColumnType::Float => format!("{}::numeric", raw),
+        DatasetColumnType::Boolean => format!("{}::boolean", raw),
+        DatasetColumnType::Date => format!("{}::date", raw),
+        DatasetColumnType::Text => raw.to_string(),
+    }
+}
+
+fn comparison(op: FilterOperator) -> &'static str {
+    match op {
+        FilterOperator::Eq => "=",
+        FilterOperator::Ne => "<>",
+        FilterOperator::Gt => ">",
+        FilterOperator::Gte => ">=",
+        FilterOperator::Lt => "<",
+        FilterOperator::Lte => "<=",
+        FilterOperator::Contains | FilterOperator::IsNull | FilterOperator::NotNull => unreachable!(),
+    }
+}
+
+fn direction(order: &DatasetOrder) -> &'static str {
+    if order.descending { "DESC" } else { "ASC" }
+}
+
+/// 表头去重，空表头命名为「列N」
+fn unique_column_names(headers: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(headers.len());
+    for (i, header) in headers.iter().enumerate() {
+        let base = match header.trim() {
+            "" => format!("列{}", i + 1),
+            name => name.to_string(),
+        };
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while names.contains(&name) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+/// 按列中的非空值推断列类型：整数、小数、布尔、日期依次尝试，都不满足时为文本
+pub fn infer_columns(headers: &[String], rows: &[Vec<String>]) -> Vec<DatasetColumn> {
+    headers
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let values: Vec<&str> = rows
+                .iter()
+                .map(|row| row.get(i).map(|value| value.trim()).unwrap_or_default())
+                .collect();
+            let present: Vec<&str> = values.iter().copied().filter(|value| !value.is_empty()).collect();
+            let column_type = if present.is_empty() {
+                DatasetColumnType::Text
+            } else if present.iter().all(|value| parse_integer(value).is_some()) {
+                DatasetColumnType::Integer
+            } else if present.iter().all(|value| parse_number(value).is_some()) {
+                DatasetColumnType::Float
+            } else if present.iter().all(|value| parse_bool(value).is_some()) {
+                DatasetColumnType::Boolean
+            } else if present.iter().all(|value| parse_date(value).is_some()) {
+                DatasetColumnType::Date
+            } else {
+                DatasetColumnType::Text
+            };
+            DatasetColumn {
+                name: name.clone(),
+                column_type,
+                null_count: (values.len() - present.len()) as u32,
+            }
+        })
+        .collect()
+}
+
+/// 按列类型转换一行数据，无法转换的值存为空
+fn typed_row(columns: &[DatasetColumn], row: &[String]) -> Value {
+    let mut data = serde_json::Map::with_capacity(columns.len());
+    for (i, column) in columns.iter().enumerate() {
+        let raw = row.get(i).map(|value| value.trim()).unwrap_or_default();
+        data.insert(column.name.clone(), typed_value(raw, column.column_type));
+    }
+    Value::Object(data)
+}
+
+fn typed_value(raw: &str, column_type: DatasetColumnType) -> Value {
+    if raw.is_empty() {
+        return Value::Null;
+    }
+    let value = match column_type {
+        DatasetColumnType::Integer => parse_integer(raw).map(Value::from),
+        DatasetColumnType::Float => parse_number(raw)
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        DatasetColumnType::Boolean => parse_bool(raw).map(Value::Bool),
+        DatasetColumnType::Date => parse_date(raw).map(Value::String),
+        DatasetColumnType::Text => Some(Value::String(raw.to_string())),
+    };
+    value.unwrap_or(Value::Null)
+}
+
+/// 去除千分位与货币符号
+fn numeric_text(raw: &str) -> String {
+    raw.trim()
+        .trim_start_matches(['¥', '￥', '$', '€', '£'])
+        .replace(',', "")
+}
+
+fn parse_integer(raw: &str) -> Option<i64> {
+    numeric_text(raw).parse().ok()
+}
+
+fn parse_number(raw: &str) -> Option<f64> {
+    numeric_text(raw).parse::<f64>().ok().filter(|number| number.is_finite())
+}
+
+fn parse_bool(raw: &str) -> Option<bool> {
+    match raw.trim().to_lowercase().as_str() {
+        "true" | "yes" | "是" => Some(true),
+        "false" | "no" | "否" => Some(false),
+        _ => None,
+    }
+}
+
+/// 解析日期，统一为 `YYYY-MM-DD`
+fn parse_date(raw: &str) -> Option<String> {
+    ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(raw.trim(), format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_infer_column_types() {
+        let headers = strings(&["季度", "地区", "营收", "数量", "日期", "", "达标"]);
+        let rows = vec![
+            strings(&["Q3", "华东", "1,200.5", "10", "2024/07/01", "x", "是"]),
+            strings(&["Q3", "华南", "¥800", "", "2024-08-15", "y", "否"]),
+        ];
+        let headers = unique_column_names(&headers);
+        let columns = infer_columns(&headers, &rows);
+        let types: Vec<DatasetColumnType> = columns.iter().map(|column| column.column_type).collect();
+        assert_eq!(types, vec![
+            DatasetColumnType::Text,
+            DatasetColumnType::Text,
+            DatasetColumnType::Float,
+            DatasetColumnType::Integer,
+            DatasetColumnType::Date,
+            DatasetColumnType::Text,
+            DatasetColumnType::Boolean,
+        ]);
+        assert_eq!(columns[5].name, "列6");
+        assert_eq!(columns[3].null_count, 1);
+
+        let row = typed_row(&columns, &rows[0]);
+        assert_eq!(row["营收"], serde_json::json!(1200.5));
+        assert_eq!(row["日期"], serde_json::json!("2024-07-01"));
+    }
+
+    #[test]
+    fn test_build_aggregate_query() {
+        let columns = infer_columns(
+            &strings(&["季度", "地区", "营收"]),
+            &[strings(&["Q3", "华东", "100"])],
+        );
+        let query: DatasetQuery = serde_json::from_value(serde_json::json!({
+            "group_by": ["地区"],
+            "aggregates": [{"function": "sum", "column": "营收"}],
+            "filters": [{"column": "季度", "op": "eq", "value": "Q3"}],
+            "order_by": {"field": "sum(营收)", "descending": true},
+            "limit": 5
+        }))
+        .unwrap();
+
+        let built = build_query(Uuid::nil(), &columns, &query).unwrap();
+        assert_eq!(built.columns, vec!["地区", "sum(营收)"]);
+        assert_eq!(
+            built.sql,
+            "SELECT to_jsonb((data->>$4)) AS c0, to_jsonb(sum((data->>$5)::numeric)) AS c1 FROM dataset_rows \
+             WHERE dataset_id = $1 AND (data->>$2) = $3 GROUP BY 1 ORDER BY 2 DESC NULLS LAST LIMIT 6"
+        );
+        assert_eq!(built.values.len(), 5);
+
+        let mut invalid = query.clone();
+        invalid.aggregates = vec![DatasetAggregate { function: AggregateFunction::Avg, column: Some("地区".to_string()) }];
+        invalid.order_by = None;
+        assert!(build_query(Uuid::nil(), &columns, &invalid).is_err());
+    }
+}
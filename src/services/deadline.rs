@@ -0,0 +1,198 @@
+// 请求截止时间传播
+// HTTP 层为每个请求设置截止时间，RAG 引擎、工具调用、数据库查询与模型调用读取剩余预算，
+// 预算耗尽时返回部分结果或超时错误，避免客户端放弃后仍继续执行
+
+use std::future::Future;
+use std::time::Duration;
+
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult, Statement, TransactionTrait};
+use tokio::time::Instant;
+
+use crate::errors::AiStudioError;
+
+tokio::task_local! {
+    /// 当前任务所属请求的截止时间
+    static CURRENT: Deadline;
+}
+
+/// PostgreSQL 语句超时被取消时的错误码
+const QUERY_CANCELED_SQLSTATE: &str = "57014";
+
+/// 数据库语句超时的最小值，避免剩余预算过小时设置为 0（即不限时）
+const MIN_STATEMENT_TIMEOUT_MS: u128 = 1;
+
+/// 请求截止时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline {
+    at: Instant,
+}
+
+impl Deadline {
+    /// 从现在起经过指定时长后到期
+    pub fn after(duration: Duration) -> Self {
+        Self { at: Instant::now() + duration }
+    }
+
+    /// 到期时刻
+    pub fn at(&self) -> Instant {
+        self.at
+    }
+
+    /// 剩余预算，已到期时为零
+    pub fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    /// 是否已到期
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.at
+    }
+}
+
+/// 当前任务的截止时间，不在请求作用域内时为 None
+///
+/// 截止时间通过任务本地变量传递，`tokio::spawn` 的新任务不会继承，
+/// 需要时先取出再在新任务中调用 [`scope`]。
+pub fn current() -> Option<Deadline> {
+    CURRENT.try_with(|deadline| *deadline).ok()
+}
+
+/// 在截止时间作用域内执行 future，嵌套作用域取更早的截止时间
+pub async fn scope<F: Future>(deadline: Deadline, fut: F) -> F::Output {
+    let effective = match current() {
+        Some(outer) if outer.at < deadline.at => outer,
+        _ => deadline,
+    };
+    CURRENT.scope(effective, fut).await
+}
+
+/// 剩余预算，不在请求作用域内时为 None
+pub fn remaining() -> Option<Duration> {
+    current().map(|deadline| deadline.remaining())
+}
+
+/// 截止时间已到时返回超时错误
+pub fn check(operation: &str) -> Result<(), AiStudioError> {
+    match current() {
+        Some(deadline) if deadline.is_expired() => Err(AiStudioError::timeout(operation)),
+        _ => Ok(()),
+    }
+}
+
+/// 在剩余预算内执行 future，超出时返回超时错误
+pub async fn run<T, F>(operation: &str, fut: F) -> Result<T, AiStudioError>
+where
+    F: Future<Output = Result<T, AiStudioError>>,
+{
+    match current() {
+        Some(deadline) => {
+            if deadline.is_expired() {
+                return Err(AiStudioError::timeout(operation));
+            }
+            tokio::time::timeout_at(deadline.at, fut)
+                .await
+                .unwrap_or_else(|_| Err(AiStudioError::timeout(operation)))
+        }
+        None => fut.await,
+    }
+}
+
+/// 调用方自身超时与剩余预算中较小者
+pub fn budget(default: Duration) -> Duration {
+    match remaining() {
+        Some(remaining) => remaining.min(default),
+        None => default,
+    }
+}
+
+/// 在剩余预算内执行查询
+///
+/// 存在截止时间时在事务中设置 `statement_timeout`，由数据库主动取消超时语句，
+/// 而不仅是丢弃等待中的 future。
+pub async fn query_all(
+    db: &DatabaseConnection,
+    operation: &str,
+    stmt: Statement,
+) -> Result<Vec<QueryResult>, AiStudioError> {
+    let Some(deadline) = current() else {
+        return Ok(db.query_all(stmt).await?);
+    };
+    if deadline.is_expired() {
+        return Err(AiStudioError::timeout(operation));
+    }
+
+    let query = async {
+        let txn = db.begin().await?;
+        let timeout_ms = deadline.remaining().as_millis().max(MIN_STATEMENT_TIMEOUT_MS);
+        txn.execute(Statement::from_string(
+            DatabaseBackend::Postgres,
+            format!("SET LOCAL statement_timeout = {}", timeout_ms),
+        ))
+        .await?;
+        let rows = match txn.query_all(stmt).await {
+            Ok(rows) => rows,
+            Err(e) if is_statement_timeout(&e) => return Err(AiStudioError::timeout(operation)),
+            Err(e) => return Err(e.into()),
+        };
+        txn.commit().await?;
+        Ok(rows)
+    };
+    run(operation, query).await
+}
+
+/// 是否为语句超时导致的取消
+fn is_statement_timeout(err: &sea_orm::DbErr) -> bool {
+    let message = err.to_string();
+    message.contains(QUERY_CANCELED_SQLSTATE) || message.contains("statement timeout")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_nested_scope_keeps_earlier_deadline() {
+        assert!(current().is_none());
+        assert_eq!(budget(Duration::from_secs(5)), Duration::from_secs(5));
+
+        let outer = Deadline::after(Duration::from_millis(200));
+        scope(outer, async move {
+            assert_eq!(current(), Some(outer));
+            scope(Deadline::after(Duration::from_secs(60)), async move {
+                assert_eq!(current(), Some(outer));
+                assert!(budget(Duration::from_secs(5)) <= Duration::from_millis(200));
+            })
+            .await;
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_run_times_out_when_budget_exhausted() {
+        let result = scope(Deadline::after(Duration::from_millis(20)), async {
+            run("slow_operation", async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok::<_, AiStudioError>(())
+            })
+            .await
+        })
+        .await;
+        assert!(matches!(result, Err(AiStudioError::Timeout { ref operation }) if operation == "slow_operation"));
+
+        let result = scope(Deadline::after(Duration::from_secs(5)), async {
+            run("fast_operation", async { Ok::<_, AiStudioError>(42) }).await
+        })
+        .await;
+        assert_eq!(result.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_check_after_expiry() {
+        assert!(check("outside_scope").is_ok());
+        scope(Deadline::after(Duration::ZERO), async {
+            assert!(check("expired").is_err());
+            assert_eq!(remaining(), Some(Duration::ZERO));
+        })
+        .await;
+    }
+}
@@ -0,0 +1,520 @@
+// 文档评论服务
+// 知识管理员可对文档或具体文档块发表批注并回复讨论；评论中的 `@用户名` 会通知被提及的租户成员
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult, Statement};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{info, instrument, warn};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::errors::AiStudioError;
+use crate::services::notification::NotificationService;
+
+/// 评论正文最大长度
+const MAX_BODY_CHARS: usize = 10_000;
+
+/// 批注引用原文最大长度
+const MAX_QUOTE_CHARS: usize = 1_000;
+
+/// 单条评论最多提及的用户数
+const MAX_MENTIONS: usize = 20;
+
+/// 提及通知中的评论摘录长度
+const EXCERPT_CHARS: usize = 200;
+
+/// 发表评论请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateDocumentCommentRequest {
+    /// 评论正文，`@用户名` 会通知对应成员
+    pub body: String,
+    /// 回复的评论 ID，回复统一归入所属主题
+    pub parent_id: Option<Uuid>,
+    /// 批注的文档块，仅主题评论可指定
+    pub chunk_id: Option<Uuid>,
+    /// 批注引用的原文片段
+    pub quote: Option<String>,
+}
+
+/// 编辑评论请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateDocumentCommentRequest {
+    /// 评论正文
+    pub body: String,
+}
+
+/// 评论列表查询参数
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+pub struct DocumentCommentQuery {
+    /// 只返回批注该文档块的主题
+    pub chunk_id: Option<Uuid>,
+    /// 是否包含已解决的主题，默认包含
+    pub include_resolved: Option<bool>,
+}
+
+/// 文档评论
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DocumentComment {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    /// 所属主题评论，主题评论为空
+    pub parent_id: Option<Uuid>,
+    /// 批注的文档块，文档重新处理后可能为空
+    pub chunk_id: Option<Uuid>,
+    /// 批注时文档块的序号
+    pub chunk_index: Option<i32>,
+    /// 批注引用的原文片段
+    pub quote: Option<String>,
+    pub author_id: Option<Uuid>,
+    pub author_name: Option<String>,
+    pub body: String,
+    /// 被提及的用户
+    pub mentions: Vec<Uuid>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 评论主题及其回复
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CommentThread {
+    #[serde(flatten)]
+    pub comment: DocumentComment,
+    /// 按时间排序的回复
+    pub replies: Vec<DocumentComment>,
+}
+
+/// 被提及的租户成员
+struct MentionedUser {
+    id: Uuid,
+    email: String,
+}
+
+/// 文档评论服务
+pub struct DocumentCommentService {
+    db: DatabaseConnection,
+    notifications: NotificationService,
+}
+
+impl DocumentCommentService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            notifications: NotificationService::new(),
+        }
+    }
+
+    /// 发表评论或回复，并通知被提及的成员
+    #[instrument(skip(self, request))]
+    pub async fn create(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+        author_id: Uuid,
+        request: CreateDocumentCommentRequest,
+    ) -> Result<DocumentComment, AiStudioError> {
+        let body = validate_body(&request.body)?;
+        let quote = request.quote.map(|q| q.trim().to_string()).filter(|q| !q.is_empty());
+        if quote.as_ref().is_some_and(|q| q.chars().count() > MAX_QUOTE_CHARS) {
+            return Err(AiStudioError::validation("quote", format!("引用原文不能超过 {} 个字符", MAX_QUOTE_CHARS)));
+        }
+        let document_title = self.document_title(tenant_id, document_id).await?;
+
+        let (parent_id, chunk_id, chunk_index) = match request.parent_id {
+            Some(parent_id) => {
+                if request.chunk_id.is_some() {
+                    return Err(AiStudioError::validation("chunk_id", "回复不能单独指定文档块"));
+                }
+                let parent = self.get(tenant_id, document_id, parent_id).await?;
+                (Some(parent.parent_id.unwrap_or(parent.id)), None, None)
+            }
+            None => match request.chunk_id {
+                Some(chunk_id) => (None, Some(chunk_id), Some(self.chunk_index(document_id, chunk_id).await?)),
+                None => (None, None, None),
+            },
+        };
+
+        let mentioned = self.resolve_mentions(tenant_id, &body, author_id).await?;
+        let mention_ids: Vec<Uuid> = mentioned.iter().map(|user| user.id).collect();
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO document_comments
+                    (id, tenant_id, document_id, chunk_id, chunk_index, quote, parent_id, author_id, body, mentions, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $11)
+                "#,
+                [
+                    id.into(),
+                    tenant_id.into(),
+                    document_id.into(),
+                    chunk_id.into(),
+                    chunk_index.into(),
+                    quote.into(),
+                    parent_id.into(),
+                    author_id.into(),
+                    body.clone().into(),
+                    serde_json::json!(mention_ids).into(),
+                    now.into(),
+                ],
+            ))
+            .await?;
+
+        let comment = self.get(tenant_id, document_id, id).await?;
+        info!(
+            tenant_id = %tenant_id,
+            document_id = %document_id,
+            comment_id = %id,
+            mentions = mention_ids.len(),
+            "文档评论已发表"
+        );
+        self.notify_mentions(tenant_id, &comment, &document_title, &mentioned).await;
+        Ok(comment)
+    }
+
+    /// 编辑评论，仅作者可编辑；只通知新增的提及
+    #[instrument(skip(self, request))]
+    pub async fn update(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+        comment_id: Uuid,
+        author_id: Uuid,
+        request: UpdateDocumentCommentRequest,
+    ) -> Result<DocumentComment, AiStudioError> {
+        let body = validate_body(&request.body)?;
+        let existing = self.get(tenant_id, document_id, comment_id).await?;
+        if existing.author_id != Some(author_id) {
+            return Err(AiStudioError::forbidden("只能编辑自己的评论"));
+        }
+
+        let mentioned = self.resolve_mentions(tenant_id, &body, author_id).await?;
+        let mention_ids: Vec<Uuid> = mentioned.iter().map(|user| user.id).collect();
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE document_comments SET body = $2, mentions = $3, updated_at = $4 WHERE id = $1",
+                [comment_id.into(), body.into(), serde_json::json!(mention_ids).into(), Utc::now().into()],
+            ))
+            .await?;
+
+        let comment = self.get(tenant_id, document_id, comment_id).await?;
+        let added: Vec<MentionedUser> = mentioned
+            .into_iter()
+            .filter(|user| !existing.mentions.contains(&user.id))
+            .collect();
+        if !added.is_empty() {
+            let document_title = self.document_title(tenant_id, document_id).await?;
+            self.notify_mentions(tenant_id, &comment, &document_title, &added).await;
+        }
+        Ok(comment)
+    }
+
+    /// 删除评论，作者或管理员可删除；删除主题同时删除其回复
+    #[instrument(skip(self))]
+    pub async fn delete(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+        comment_id: Uuid,
+        actor_id: Uuid,
+        actor_is_admin: bool,
+    ) -> Result<(), AiStudioError> {
+        let comment = self.get(tenant_id, document_id, comment_id).await?;
+        if comment.author_id != Some(actor_id) && !actor_is_admin {
+            return Err(AiStudioError::forbidden("只能删除自己的评论"));
+        }
+
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "DELETE FROM document_comments WHERE id = $1",
+                [comment_id.into()],
+            ))
+            .await?;
+        info!(tenant_id = %tenant_id, document_id = %document_id, comment_id = %comment_id, "文档评论已删除");
+        Ok(())
+    }
+
+    /// 标记主题已解决或重新打开
+    #[instrument(skip(self))]
+    pub async fn set_resolved(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+        comment_id: Uuid,
+        actor_id: Uuid,
+        resolved: bool,
+    ) -> Result<DocumentComment, AiStudioError> {
+        let comment = self.get(tenant_id, document_id, comment_id).await?;
+        if comment.parent_id.is_some() {
+            return Err(AiStudioError::validation("comment_id", "只能解决主题评论"));
+        }
+
+        let (resolved_by, resolved_at) = if resolved { (Some(actor_id), Some(Utc::now())) } else { (None, None) };
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE document_comments SET resolved_by = $2, resolved_at = $3 WHERE id = $1",
+                [comment_id.into(), resolved_by.into(), resolved_at.into()],
+            ))
+            .await?;
+        self.get(tenant_id, document_id, comment_id).await
+    }
+
+    /// 获取单条评论
+    pub async fn get(&self, tenant_id: Uuid, document_id: Uuid, comment_id: Uuid) -> Result<DocumentComment, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT c.*, u.username AS author_name
+                FROM document_comments c
+                LEFT JOIN users u ON u.id = c.author_id
+                WHERE c.id = $1 AND c.tenant_id = $2 AND c.document_id = $3
+                "#,
+                [comment_id.into(), tenant_id.into(), document_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("评论"))?;
+        comment_from_row(&row)
+    }
+
+    /// 按主题列出文档评论
+    pub async fn list_threads(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+        query: &DocumentCommentQuery,
+    ) -> Result<Vec<CommentThread>, AiStudioError> {
+        self.document_title(tenant_id, document_id).await?;
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT c.*, u.username AS author_name
+                FROM document_comments c
+                LEFT JOIN users u ON u.id = c.author_id
+                WHERE c.tenant_id = $1 AND c.document_id = $2
+                ORDER BY c.created_at, c.id
+                "#,
+                [tenant_id.into(), document_id.into()],
+            ))
+            .await?;
+        let comments = rows.iter().map(comment_from_row).collect::<Result<Vec<_>, _>>()?;
+
+        let include_resolved = query.include_resolved.unwrap_or(true);
+        Ok(build_threads(comments)
+            .into_iter()
+            .filter(|thread| include_resolved || thread.comment.resolved_at.is_none())
+            .filter(|thread| query.chunk_id.is_none() || thread.comment.chunk_id == query.chunk_id)
+            .collect())
+    }
+
+    /// 校验文档属于租户并返回标题
+    async fn document_title(&self, tenant_id: Uuid, document_id: Uuid) -> Result<String, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT d.title FROM documents d
+                JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id
+                WHERE d.id = $1 AND kb.tenant_id = $2
+                "#,
+                [document_id.into(), tenant_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("文档"))?;
+        Ok(row.try_get("", "title")?)
+    }
+
+    /// 校验文档块属于文档并返回块序号
+    async fn chunk_index(&self, document_id: Uuid, chunk_id: Uuid) -> Result<i32, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT chunk_index FROM document_chunks WHERE id = $1 AND document_id = $2",
+                [chunk_id.into(), document_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("文档块"))?;
+        Ok(row.try_get("", "chunk_index")?)
+    }
+
+    /// 将正文中的 `@用户名` 解析为租户成员，忽略不存在的用户名与作者本人
+    async fn resolve_mentions(&self, tenant_id: Uuid, body: &str, author_id: Uuid) -> Result<Vec<MentionedUser>, AiStudioError> {
+        let usernames = parse_mentions(body);
+        if usernames.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT id, email FROM users WHERE tenant_id = $1 AND username = ANY($2) AND id <> $3",
+                [tenant_id.into(), usernames.into(), author_id.into()],
+            ))
+            .await?;
+
+        let mut users = Vec::with_capacity(rows.len());
+        for row in rows {
+            users.push(MentionedUser {
+                id: row.try_get("", "id")?,
+                email: row.try_get("", "email")?,
+            });
+        }
+        Ok(users)
+    }
+
+    /// 通知被提及的成员，发送失败不影响评论
+    async fn notify_mentions(&self, tenant_id: Uuid, comment: &DocumentComment, document_title: &str, users: &[MentionedUser]) {
+        let author = comment.author_name.as_deref().unwrap_or("成员");
+        let excerpt: String = comment.body.chars().take(EXCERPT_CHARS).collect();
+        for user in users {
+            if let Err(e) = self.notifications
+                .send_document_mention(tenant_id, &user.email, author, document_title, &excerpt)
+                .await
+            {
+                warn!("发送文档提及通知失败: comment_id={}, user_id={}, error={}", comment.id, user.id, e);
+            }
+        }
+    }
+}
+
+/// 校验评论正文
+fn validate_body(body: &str) -> Result<String, AiStudioError> {
+    let body = body.trim();
+    if body.is_empty() || body.chars().count() > MAX_BODY_CHARS {
+        return Err(AiStudioError::validation("body", format!("评论不能为空且不超过 {} 个字符", MAX_BODY_CHARS)));
+    }
+    Ok(body.to_string())
+}
+
+/// 提取正文中的 `@用户名`，按出现顺序去重
+///
+/// 用户名由字母、数字、`_`、`.`、`-` 组成，末尾的 `.` 视为标点；邮箱地址中的 `@` 不算提及。
+pub fn parse_mentions(body: &str) -> Vec<String> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-');
+    let mut mentions: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut chars = body.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let preceded_by_name = previous.is_some_and(is_name_char);
+        previous = Some(c);
+        if c != '@' || preceded_by_name {
+            continue;
+        }
+
+        let mut end = start + 1;
+        while let Some(&(index, next)) = chars.peek() {
+            if !is_name_char(next) {
+                break;
+            }
+            end = index + next.len_utf8();
+            previous = Some(next);
+            chars.next();
+        }
+
+        let name = body[start + 1..end].trim_end_matches('.');
+        if !name.is_empty() && !mentions.iter().any(|m| m == name) {
+            mentions.push(name.to_string());
+        }
+        if mentions.len() >= MAX_MENTIONS {
+            break;
+        }
+    }
+    mentions
+}
+
+/// 将评论按主题分组，回复挂在所属主题下
+fn build_threads(comments: Vec<DocumentComment>) -> Vec<CommentThread> {
+    let (roots, replies): (Vec<_>, Vec<_>) = comments.into_iter().partition(|c| c.parent_id.is_none());
+    let mut replies_by_parent: HashMap<Uuid, Vec<DocumentComment>> = HashMap::new();
+    for reply in replies {
+        if let Some(parent_id) = reply.parent_id {
+            replies_by_parent.entry(parent_id).or_default().push(reply);
+        }
+    }
+
+    roots
+        .into_iter()
+        .map(|comment| CommentThread {
+            replies: replies_by_parent.remove(&comment.id).unwrap_or_default(),
+            comment,
+        })
+        .collect()
+}
+
+fn comment_from_row(row: &QueryResult) -> Result<DocumentComment, AiStudioError> {
+    let mentions: serde_json::Value = row.try_get("", "mentions")?;
+    Ok(DocumentComment {
+        id: row.try_get("", "id")?,
+        document_id: row.try_get("", "document_id")?,
+        parent_id: row.try_get("", "parent_id")?,
+        chunk_id: row.try_get("", "chunk_id")?,
+        chunk_index: row.try_get("", "chunk_index")?,
+        quote: row.try_get("", "quote")?,
+        author_id: row.try_get("", "author_id")?,
+        author_name: row.try_get("", "author_name")?,
+        body: row.try_get("", "body")?,
+        mentions: serde_json::from_value(mentions).unwrap_or_default(),
+        resolved_by: row.try_get("", "resolved_by")?,
+        resolved_at: row.try_get("", "resolved_at")?,
+        created_at: row.try_get("", "created_at")?,
+        updated_at: row.try_get("", "updated_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(parent_id: Option<Uuid>) -> DocumentComment {
+        DocumentComment {
+            id: Uuid::new_v4(),
+            document_id: Uuid::nil(),
+            parent_id,
+            chunk_id: None,
+            chunk_index: None,
+            quote: None,
+            author_id: None,
+            author_name: None,
+            body: "ok".to_string(),
+            mentions: Vec::new(),
+            resolved_by: None,
+            resolved_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_parse_mentions() {
+        assert_eq!(
+            parse_mentions("@alice 请确认第三段，抄送 @bob.li. 和 @alice"),
+            vec!["alice".to_string(), "bob.li".to_string()]
+        );
+        assert!(parse_mentions("联系 ops@example.com").is_empty());
+        assert!(parse_mentions("单独的 @ 符号").is_empty());
+    }
+
+    #[test]
+    fn test_build_threads() {
+        let first = comment(None);
+        let second = comment(None);
+        let reply = comment(Some(first.id));
+        let threads = build_threads(vec![first.clone(), second.clone(), reply.clone()]);
+
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].comment.id, first.id);
+        assert_eq!(threads[0].replies.len(), 1);
+        assert_eq!(threads[0].replies[0].id, reply.id);
+        assert!(threads[1].replies.is_empty());
+    }
+}
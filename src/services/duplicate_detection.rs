@@ -0,0 +1,621 @@
+// 重复文档检测
+// 用 MinHash 签名和 LSH 分桶找出知识库内内容相近的文档，按相似度聚类生成报告，并支持按簇合并或归档重复文档
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::{document, Document};
+use crate::db::update_with_revision;
+use crate::errors::AiStudioError;
+use crate::services::legal_hold::LegalHoldService;
+use crate::services::task_queue::{TaskExecutor, TaskInfo, TaskQueueService, TaskStatus, TaskType};
+use crate::services::tenant_encryption::TenantEncryptionService;
+
+/// 默认相似度阈值（估计的 Jaccard 相似度）
+const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.8;
+
+/// 允许的最低相似度阈值，过低时聚类失去意义
+const MIN_SIMILARITY_THRESHOLD: f32 = 0.5;
+
+/// 单次检测最多扫描的文档数
+const MAX_SCAN_DOCUMENTS: usize = 20_000;
+
+/// 分页加载文档的页大小
+const SCAN_PAGE_SIZE: u64 = 200;
+
+/// 字符 shingle 长度
+const SHINGLE_SIZE: usize = 5;
+
+/// MinHash 签名长度
+const NUM_HASHES: usize = 128;
+
+/// LSH 分桶数，每个桶包含 `NUM_HASHES / LSH_BANDS` 个签名值
+const LSH_BANDS: usize = 32;
+
+/// 发起重复文档检测请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct DetectDuplicatesRequest {
+    /// 相似度阈值（0.5-1.0），默认 0.8
+    pub threshold: Option<f32>,
+}
+
+impl DetectDuplicatesRequest {
+    /// 校验并返回生效的阈值
+    pub fn threshold(&self) -> Result<f32, AiStudioError> {
+        let threshold = self.threshold.unwrap_or(DEFAULT_SIMILARITY_THRESHOLD);
+        if !(MIN_SIMILARITY_THRESHOLD..=1.0).contains(&threshold) {
+            return Err(AiStudioError::validation(
+                "threshold",
+                format!("相似度阈值必须在 {} 到 1.0 之间", MIN_SIMILARITY_THRESHOLD),
+            ));
+        }
+        Ok(threshold)
+    }
+}
+
+/// 簇内的文档
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DuplicateDocument {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 文件大小（字节）
+    pub file_size: i64,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+    /// 与建议保留文档的估计相似度
+    pub similarity: f32,
+}
+
+/// 重复文档簇
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DuplicateCluster {
+    /// 簇编号，在报告内唯一
+    pub cluster_id: u32,
+    /// 建议保留的文档：最近更新的文档，更新时间相同时取内容较长者
+    pub suggested_canonical_id: Uuid,
+    /// 簇内文档与建议保留文档的最低相似度
+    pub min_similarity: f32,
+    /// 簇内文档，建议保留的文档排在首位
+    pub documents: Vec<DuplicateDocument>,
+}
+
+/// 重复文档检测报告
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DuplicateReportResponse {
+    /// 报告 ID
+    pub report_id: Uuid,
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 状态：pending、running、completed、failed、cancelled
+    pub status: String,
+    /// 相似度阈值
+    pub threshold: f32,
+    /// 扫描的文档数，完成后返回
+    pub scanned_documents: Option<u32>,
+    /// 重复文档簇，按文档数从多到少排列
+    pub clusters: Vec<DuplicateCluster>,
+    /// 失败原因
+    pub error_message: Option<String>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 完成时间
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// 重复文档簇处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateClusterAction {
+    /// 将其他文档的标签并入保留文档，并归档其他文档
+    Merge,
+    /// 仅归档其他文档
+    Archive,
+}
+
+/// 处理重复文档簇请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ResolveDuplicateClusterRequest {
+    /// 处理方式
+    pub action: DuplicateClusterAction,
+    /// 保留的文档 ID，必须属于该簇，默认使用报告建议的文档
+    pub canonical_id: Option<Uuid>,
+}
+
+/// 重复文档簇处理结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DuplicateClusterResolution {
+    /// 保留的文档 ID
+    pub canonical_id: Uuid,
+    /// 处理方式
+    pub action: DuplicateClusterAction,
+    /// 已归档的文档 ID
+    pub archived: Vec<Uuid>,
+    /// 已删除或已归档而跳过的文档 ID
+    pub skipped: Vec<Uuid>,
+}
+
+/// 检测任务参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DetectionParameters {
+    knowledge_base_id: Uuid,
+    requested_by: Uuid,
+    threshold: f32,
+}
+
+/// 参与聚类的文档签名
+#[derive(Debug, Clone)]
+pub struct DocumentSignature {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 文件大小（字节）
+    pub file_size: i64,
+    /// 内容长度（字符数）
+    pub content_length: usize,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+    /// MinHash 签名
+    pub signature: Vec<u64>,
+}
+
+/// 重复文档检测服务
+pub struct DuplicateDetectionService {
+    db: DatabaseConnection,
+    queue: Arc<TaskQueueService>,
+}
+
+impl DuplicateDetectionService {
+    /// 创建检测服务
+    pub fn new(db: DatabaseConnection, queue: Arc<TaskQueueService>) -> Self {
+        Self { db, queue }
+    }
+
+    /// 提交检测任务
+    #[instrument(skip(self, request))]
+    pub async fn submit(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        requested_by: Uuid,
+        request: DetectDuplicatesRequest,
+    ) -> Result<DuplicateReportResponse, AiStudioError> {
+        let threshold = request.threshold()?;
+        let parameters = serde_json::to_value(DetectionParameters { knowledge_base_id, requested_by, threshold })?;
+        let report_id = self.queue
+            .submit_task(TaskType::DuplicateDetection, tenant_id, parameters, None)
+            .await?;
+
+        info!("重复文档检测任务已提交: report_id={}, kb={}", report_id, knowledge_base_id);
+        self.report(tenant_id, knowledge_base_id, report_id).await
+    }
+
+    /// 查询检测报告
+    pub async fn report(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        report_id: Uuid,
+    ) -> Result<DuplicateReportResponse, AiStudioError> {
+        let task = self.queue
+            .get_task_status(report_id)
+            .await
+            .filter(|task| task.tenant_id == tenant_id && task.task_type == TaskType::DuplicateDetection)
+            .ok_or_else(|| AiStudioError::not_found("重复文档检测报告"))?;
+        let parameters: DetectionParameters = serde_json::from_value(task.parameters.clone())?;
+        if parameters.knowledge_base_id != knowledge_base_id {
+            return Err(AiStudioError::not_found("重复文档检测报告"));
+        }
+
+        let result = task.result.clone().unwrap_or_default();
+        Ok(DuplicateReportResponse {
+            report_id,
+            knowledge_base_id,
+            status: serde_json::to_value(&task.status)?.as_str().unwrap_or_default().to_string(),
+            threshold: parameters.threshold,
+            scanned_documents: result.get("scanned_documents").and_then(|v| v.as_u64()).map(|v| v as u32),
+            clusters: result.get("clusters")
+                .cloned()
+                .and_then(|clusters| serde_json::from_value(clusters).ok())
+                .unwrap_or_default(),
+            error_message: task.error_message,
+            created_at: task.created_at,
+            completed_at: task.completed_at,
+        })
+    }
+
+    /// 处理报告中的一个重复文档簇
+    ///
+    /// 归档簇内除保留文档外的其他文档，并在元数据中记录 `duplicate_of`；合并时还会把其他文档的
+    /// 标签并入保留文档，并在保留文档元数据的 `merged_from` 中记录来源。所有修改在同一事务中完成。
+    #[instrument(skip(self, request))]
+    pub async fn resolve_cluster(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        report_id: Uuid,
+        cluster_id: u32,
+        request: ResolveDuplicateClusterRequest,
+    ) -> Result<DuplicateClusterResolution, AiStudioError> {
+        let report = self.report(tenant_id, knowledge_base_id, report_id).await?;
+        if report.status != serde_json::to_value(TaskStatus::Completed)? {
+            return Err(AiStudioError::conflict("重复文档检测尚未完成"));
+        }
+        let cluster = report.clusters
+            .into_iter()
+            .find(|cluster| cluster.cluster_id == cluster_id)
+            .ok_or_else(|| AiStudioError::not_found("重复文档簇"))?;
+        let canonical_id = request.canonical_id.unwrap_or(cluster.suggested_canonical_id);
+        if !cluster.documents.iter().any(|doc| doc.document_id == canonical_id) {
+            return Err(AiStudioError::validation("canonical_id", "保留的文档必须属于该簇"));
+        }
+
+        // 处于法律保留状态的文档不归档；合并需要修改保留文档，因此其被保留时拒绝合并
+        let member_ids: Vec<Uuid> = cluster.documents.iter().map(|doc| doc.document_id).collect();
+        let held = LegalHoldService::new(self.db.clone()).held_documents(&member_ids).await?;
+        if request.action == DuplicateClusterAction::Merge && held.contains(&canonical_id) {
+            return Err(AiStudioError::conflict("保留的文档处于法律保留状态，无法合并"));
+        }
+
+        let txn = self.db.begin().await?;
+        let canonical = Document::find_by_id(canonical_id)
+            .filter(document::Column::KnowledgeBaseId.eq(knowledge_base_id))
+            .one(&txn)
+            .await?
+            .filter(|doc| doc.status != document::DocumentStatus::Archived)
+            .ok_or_else(|| AiStudioError::conflict("保留的文档已删除或已归档，请重新检测"))?;
+
+        let now = Utc::now();
+        let mut archived = Vec::new();
+        let mut skipped = Vec::new();
+        let mut merged_tags = Vec::new();
+        for member in cluster.documents.iter().filter(|doc| doc.document_id != canonical_id) {
+            if held.contains(&member.document_id) {
+                skipped.push(member.document_id);
+                continue;
+            }
+            let duplicate = Document::find_by_id(member.document_id)
+                .filter(document::Column::KnowledgeBaseId.eq(knowledge_base_id))
+                .one(&txn)
+                .await?;
+            let Some(duplicate) = duplicate.filter(|doc| doc.status != document::DocumentStatus::Archived) else {
+                skipped.push(member.document_id);
+                continue;
+            };
+
+            merged_tags.extend(metadata_tags(&duplicate.metadata));
+            let mut metadata = duplicate.metadata.clone();
+            set_metadata_field(&mut metadata, "duplicate_of", json!(canonical_id));
+            let revision = duplicate.revision;
+            let mut active: document::ActiveModel = duplicate.into();
+            active.status = Set(document::DocumentStatus::Archived);
+            active.metadata = Set(metadata);
+            active.updated_at = Set(now.into());
+            update_with_revision(&txn, active, document::Column::Revision, revision, "文档").await?;
+            archived.push(member.document_id);
+        }
+
+        if request.action == DuplicateClusterAction::Merge && !archived.is_empty() {
+            let mut metadata = canonical.metadata.clone();
+            let mut tags = metadata_tags(&metadata);
+            for tag in merged_tags {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            let mut merged_from: Vec<Value> = metadata.get("merged_from")
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default();
+            merged_from.extend(archived.iter().map(|id| json!(id)));
+            set_metadata_field(&mut metadata, "tags", json!(tags));
+            set_metadata_field(&mut metadata, "merged_from", Value::Array(merged_from));
+
+            let revision = canonical.revision;
+            let mut active: document::ActiveModel = canonical.into();
+            active.metadata = Set(metadata);
+            active.updated_at = Set(now.into());
+            update_with_revision(&txn, active, document::Column::Revision, revision, "文档").await?;
+        }
+        txn.commit().await?;
+
+        info!(
+            "重复文档簇已处理: report_id={}, cluster={}, 保留={}, 归档数={}",
+            report_id, cluster_id, canonical_id, archived.len()
+        );
+        Ok(DuplicateClusterResolution { canonical_id, action: request.action, archived, skipped })
+    }
+}
+
+/// 重复文档检测任务执行器
+pub struct DuplicateDetectionExecutor {
+    db: DatabaseConnection,
+}
+
+impl DuplicateDetectionExecutor {
+    /// 创建执行器
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 分页加载知识库内未归档的文档并计算签名，不保留文档内容
+    async fn load_signatures(&self, knowledge_base_id: Uuid) -> Result<Vec<DocumentSignature>, AiStudioError> {
+        let mut pages = Document::find()
+            .filter(document::Column::KnowledgeBaseId.eq(knowledge_base_id))
+            .filter(document::Column::Status.ne(document::DocumentStatus::Archived))
+            .order_by_asc(document::Column::Id)
+            .paginate(&self.db, SCAN_PAGE_SIZE);
+
+        let encryption = TenantEncryptionService::from_config(self.db.clone())?;
+        let mut signatures = Vec::new();
+        while let Some(page) = pages.fetch_and_next().await? {
+            for doc in page {
+                let content = encryption.decrypt(&doc.content).await?;
+                let Some(signature) = minhash_signature(&content) else {
+                    continue;
+                };
+                signatures.push(DocumentSignature {
+                    document_id: doc.id,
+                    content_length: content.chars().count(),
+                    title: doc.title,
+                    file_size: doc.file_size,
+                    updated_at: doc.updated_at.with_timezone(&Utc),
+                    signature,
+                });
+            }
+            if signatures.len() >= MAX_SCAN_DOCUMENTS {
+                signatures.truncate(MAX_SCAN_DOCUMENTS);
+                break;
+            }
+        }
+        Ok(signatures)
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskExecutor for DuplicateDetectionExecutor {
+    async fn execute(&self, task: &mut TaskInfo) -> Result<(), AiStudioError> {
+        let parameters: DetectionParameters = serde_json::from_value(task.parameters.clone())?;
+
+        let signatures = self.load_signatures(parameters.knowledge_base_id).await?;
+        task.total_count = Some(signatures.len() as u32);
+        task.progress = 60;
+
+        let clusters = build_clusters(&signatures, parameters.threshold);
+        task.success_count = signatures.len() as u32;
+        task.result = Some(json!({
+            "scanned_documents": signatures.len(),
+            "clusters": clusters,
+        }));
+        info!(
+            "重复文档检测完成: report_id={}, kb={}, 文档数={}, 重复簇数={}, 请求人={}",
+            task.id, parameters.knowledge_base_id, signatures.len(), clusters.len(), parameters.requested_by
+        );
+        Ok(())
+    }
+
+    fn supported_task_types(&self) -> Vec<TaskType> {
+        vec![TaskType::DuplicateDetection]
+    }
+}
+
+/// 计算文本的 MinHash 签名，文本规范化后为空时返回 `None`
+///
+/// 规范化时转为小写，并把空白和标点折叠为单个空格，使格式差异不影响相似度。
+pub fn minhash_signature(text: &str) -> Option<Vec<u64>> {
+    let mut normalized = Vec::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            normalized.extend(c.to_lowercase());
+        } else if normalized.last().is_some_and(|last| *last != ' ') {
+            normalized.push(' ');
+        }
+    }
+    if normalized.last() == Some(&' ') {
+        normalized.pop();
+    }
+    if normalized.is_empty() {
+        return None;
+    }
+
+    let shingles: HashSet<u64> = if normalized.len() < SHINGLE_SIZE {
+        HashSet::from([hash_chars(&normalized)])
+    } else {
+        normalized.windows(SHINGLE_SIZE).map(hash_chars).collect()
+    };
+
+    Some(
+        (0..NUM_HASHES as u64)
+            .map(|i| {
+                let seed = splitmix64(i + 1);
+                shingles.iter().map(|shingle| splitmix64(shingle ^ seed)).min().unwrap_or(u64::MAX)
+            })
+            .collect(),
+    )
+}
+
+/// 由两个签名估计 Jaccard 相似度
+pub fn estimate_similarity(a: &[u64], b: &[u64]) -> f32 {
+    if a.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+    let equal = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    equal as f32 / a.len() as f32
+}
+
+/// 按相似度阈值将文档聚类为重复文档簇
+///
+/// 先用 LSH 分桶找出候选文档对，再用签名估计的相似度确认，确认后的文档对按连通关系合并为簇。
+pub fn build_clusters(documents: &[DocumentSignature], threshold: f32) -> Vec<DuplicateCluster> {
+    let rows = NUM_HASHES / LSH_BANDS;
+    let mut parents: Vec<usize> = (0..documents.len()).collect();
+    let mut compared = HashSet::new();
+
+    for band in 0..LSH_BANDS {
+        let mut buckets: HashMap<&[u64], Vec<usize>> = HashMap::new();
+        for (index, doc) in documents.iter().enumerate() {
+            buckets.entry(&doc.signature[band * rows..(band + 1) * rows]).or_default().push(index);
+        }
+        for bucket in buckets.values().filter(|bucket| bucket.len() > 1) {
+            for (i, &a) in bucket.iter().enumerate() {
+                for &b in &bucket[i + 1..] {
+                    if !compared.insert((a, b)) {
+                        continue;
+                    }
+                    if estimate_similarity(&documents[a].signature, &documents[b].signature) >= threshold {
+                        let (root_a, root_b) = (find_root(&mut parents, a), find_root(&mut parents, b));
+                        if root_a != root_b {
+                            parents[root_b] = root_a;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for index in 0..documents.len() {
+        let root = find_root(&mut parents, index);
+        groups.entry(root).or_default().push(index);
+    }
+
+    let mut groups: Vec<Vec<usize>> = groups.into_values().filter(|members| members.len() > 1).collect();
+    groups.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+
+    groups
+        .into_iter()
+        .enumerate()
+        .map(|(cluster_index, mut members)| {
+            members.sort_by(|&a, &b| {
+                let (a, b) = (&documents[a], &documents[b]);
+                b.updated_at.cmp(&a.updated_at).then_with(|| b.content_length.cmp(&a.content_length))
+            });
+            let canonical = &documents[members[0]];
+            let documents: Vec<DuplicateDocument> = members
+                .iter()
+                .map(|&index| {
+                    let doc = &documents[index];
+                    DuplicateDocument {
+                        document_id: doc.document_id,
+                        title: doc.title.clone(),
+                        file_size: doc.file_size,
+                        updated_at: doc.updated_at,
+                        similarity: estimate_similarity(&canonical.signature, &doc.signature),
+                    }
+                })
+                .collect();
+            DuplicateCluster {
+                cluster_id: cluster_index as u32 + 1,
+                suggested_canonical_id: canonical.document_id,
+                min_similarity: documents.iter().map(|doc| doc.similarity).fold(1.0, f32::min),
+                documents,
+            }
+        })
+        .collect()
+}
+
+/// 并查集查找根节点，同时压缩路径
+fn find_root(parents: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    let mut current = index;
+    while parents[current] != root {
+        let next = parents[current];
+        parents[current] = root;
+        current = next;
+    }
+    root
+}
+
+/// 计算 shingle 的哈希值
+fn hash_chars(chars: &[char]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    chars.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// SplitMix64 混合函数，用于从同一个 shingle 哈希派生多个独立的哈希函数
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// 读取元数据中的标签
+fn metadata_tags(metadata: &Value) -> Vec<String> {
+    metadata.get("tags")
+        .and_then(|tags| tags.as_array())
+        .map(|tags| tags.iter().filter_map(|tag| tag.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+/// 写入元数据字段，元数据不是对象时替换为对象
+fn set_metadata_field(metadata: &mut Value, key: &str, value: Value) {
+    if !metadata.is_object() {
+        *metadata = json!({});
+    }
+    if let Some(object) = metadata.as_object_mut() {
+        object.insert(key.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn signature(title: &str, content: &str, age_days: i64) -> DocumentSignature {
+        DocumentSignature {
+            document_id: Uuid::new_v4(),
+            title: title.to_string(),
+            file_size: content.len() as i64,
+            content_length: content.chars().count(),
+            updated_at: Utc::now() - Duration::days(age_days),
+            signature: minhash_signature(content).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_minhash_ignores_formatting() {
+        let a = minhash_signature("退款政策：收到商品后 7 天内可申请无理由退款。").unwrap();
+        let b = minhash_signature("退款政策  收到商品后7天内可申请无理由退款").unwrap();
+        assert_eq!(estimate_similarity(&a, &b), 1.0);
+        assert!(minhash_signature(" ，。 ").is_none());
+    }
+
+    #[test]
+    fn test_build_clusters_groups_near_duplicates() {
+        let base = "Our refund policy allows customers to return any product within thirty days of delivery \
+                    for a full refund, provided the item is unused and in its original packaging.";
+        let docs = vec![
+            signature("退款政策", base, 3),
+            signature("退款政策（新）", &format!("{} Contact support to start a return.", base), 1),
+            signature("配送说明", "Orders ship within two business days and arrive in five to seven days.", 2),
+        ];
+
+        let clusters = build_clusters(&docs, 0.7);
+        assert_eq!(clusters.len(), 1);
+        let cluster = &clusters[0];
+        assert_eq!(cluster.documents.len(), 2);
+        assert_eq!(cluster.suggested_canonical_id, docs[1].document_id);
+        assert!(cluster.min_similarity >= 0.7);
+        assert!(build_clusters(&docs, 1.0).is_empty());
+    }
+}
@@ -0,0 +1,570 @@
+// 知识库向量导出
+// 通过后台任务队列将知识库的文档块正文、元数据与向量导出为 JSONL/Parquet 文件，并签发限时下载链接
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use arrow_array::builder::{Float32Builder, ListBuilder};
+use arrow_array::{ArrayRef, Int32Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, QueryFilter, Statement};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::vector_store::VectorStoreRegistry;
+use crate::db::entities::{knowledge_base, KnowledgeBase};
+use crate::errors::AiStudioError;
+use crate::services::task_queue::{TaskExecutor, TaskInfo, TaskQueueService, TaskStatus, TaskType};
+use crate::services::transcript_export::{remove_expired_exports, DOWNLOAD_URL_TTL_HOURS};
+
+/// 每页读取的文档块数
+const EXPORT_PAGE_SIZE: u64 = 500;
+
+/// 向量导出文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingExportFormat {
+    /// 每行一个文档块的 JSON 对象
+    Jsonl,
+    /// Parquet 列式文件，向量为 `list<float>` 列，元数据为 JSON 字符串列
+    Parquet,
+}
+
+impl EmbeddingExportFormat {
+    /// 文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Jsonl => "jsonl",
+            Self::Parquet => "parquet",
+        }
+    }
+
+    /// 下载时的内容类型
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Jsonl => "application/x-ndjson; charset=utf-8",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+/// 创建向量导出请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateEmbeddingExportRequest {
+    /// 导出格式
+    pub format: EmbeddingExportFormat,
+    /// 是否导出向量，关闭时只导出正文与元数据，默认开启
+    #[serde(default = "default_include_embeddings")]
+    pub include_embeddings: bool,
+    /// 是否包含已归档的文档
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+fn default_include_embeddings() -> bool {
+    true
+}
+
+/// 向量导出状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct EmbeddingExportResponse {
+    /// 导出 ID
+    pub export_id: Uuid,
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 状态：pending、running、completed、failed、cancelled
+    pub status: String,
+    /// 导出格式
+    pub format: EmbeddingExportFormat,
+    /// 导出的文档块数
+    pub chunk_count: Option<u32>,
+    /// 附带向量的文档块数
+    pub embedding_count: Option<u32>,
+    /// 文件大小（字节）
+    pub file_size: Option<u64>,
+    /// 签名下载链接，导出完成后返回
+    pub download_url: Option<String>,
+    /// 下载链接过期时间
+    pub download_expires_at: Option<DateTime<Utc>>,
+    /// 失败原因
+    pub error_message: Option<String>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 完成时间
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// 导出任务参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExportParameters {
+    knowledge_base_id: Uuid,
+    format: EmbeddingExportFormat,
+    include_embeddings: bool,
+    include_archived: bool,
+    requested_by: Uuid,
+}
+
+/// 下载链接签名声明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingDownloadClaims {
+    /// 导出 ID
+    pub sub: Uuid,
+    /// 租户 ID
+    pub tid: Uuid,
+    /// 导出格式
+    pub fmt: EmbeddingExportFormat,
+    /// 过期时间（Unix 时间戳）
+    pub exp: i64,
+}
+
+/// 导出的一个文档块，对应 JSONL 文件中的一行或 Parquet 文件中的一行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedChunk {
+    /// 文档块 ID
+    pub id: Uuid,
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 文档标题
+    pub document_title: String,
+    /// 文档块序号
+    pub chunk_index: i32,
+    /// 正文
+    pub text: String,
+    /// 文档块元数据
+    pub metadata: Value,
+    /// 生成向量的嵌入模型
+    pub embedding_model: Option<String>,
+    /// 向量
+    pub embedding: Option<Vec<f32>>,
+}
+
+/// 向量导出服务
+pub struct EmbeddingExportService {
+    queue: Arc<TaskQueueService>,
+    signing_secret: String,
+}
+
+impl EmbeddingExportService {
+    /// 创建导出服务，`signing_secret` 用于签发下载链接
+    pub fn new(queue: Arc<TaskQueueService>, signing_secret: String) -> Self {
+        Self { queue, signing_secret }
+    }
+
+    /// 提交导出任务
+    #[instrument(skip(self, request))]
+    pub async fn submit(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        requested_by: Uuid,
+        request: CreateEmbeddingExportRequest,
+    ) -> Result<EmbeddingExportResponse, AiStudioError> {
+        let parameters = serde_json::to_value(ExportParameters {
+            knowledge_base_id,
+            format: request.format,
+            include_embeddings: request.include_embeddings,
+            include_archived: request.include_archived,
+            requested_by,
+        })?;
+        let export_id = self.queue
+            .submit_task(TaskType::EmbeddingExport, tenant_id, parameters, None)
+            .await?;
+
+        info!("向量导出任务已提交: export_id={}, kb={}, 请求人={}", export_id, knowledge_base_id, requested_by);
+        self.status(tenant_id, knowledge_base_id, export_id).await
+    }
+
+    /// 查询导出状态，完成时签发下载链接
+    pub async fn status(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        export_id: Uuid,
+    ) -> Result<EmbeddingExportResponse, AiStudioError> {
+        let task = self.queue
+            .get_task_status(export_id)
+            .await
+            .filter(|task| task.tenant_id == tenant_id && task.task_type == TaskType::EmbeddingExport)
+            .ok_or_else(|| AiStudioError::not_found("导出任务"))?;
+        let parameters: ExportParameters = serde_json::from_value(task.parameters.clone())?;
+        if parameters.knowledge_base_id != knowledge_base_id {
+            return Err(AiStudioError::not_found("导出任务"));
+        }
+
+        let (download_url, download_expires_at) = if task.status == TaskStatus::Completed {
+            let expires_at = Utc::now() + Duration::hours(DOWNLOAD_URL_TTL_HOURS);
+            let claims = EmbeddingDownloadClaims { sub: export_id, tid: tenant_id, fmt: parameters.format, exp: expires_at.timestamp() };
+            let token = sign_download_token(&self.signing_secret, &claims)?;
+            (Some(download_url(export_id, &token)), Some(expires_at))
+        } else {
+            (None, None)
+        };
+
+        let result = task.result.clone().unwrap_or_default();
+        let count = |key: &str| result.get(key).and_then(|v| v.as_u64()).map(|v| v as u32);
+        Ok(EmbeddingExportResponse {
+            export_id,
+            knowledge_base_id,
+            status: serde_json::to_value(&task.status)?.as_str().unwrap_or_default().to_string(),
+            format: parameters.format,
+            chunk_count: count("chunk_count"),
+            embedding_count: count("embedding_count"),
+            file_size: result.get("file_size").and_then(|v| v.as_u64()),
+            download_url,
+            download_expires_at,
+            error_message: task.error_message,
+            created_at: task.created_at,
+            completed_at: task.completed_at,
+        })
+    }
+}
+
+/// 向量导出任务执行器
+pub struct EmbeddingExportExecutor {
+    db: DatabaseConnection,
+    vector_stores: Arc<VectorStoreRegistry>,
+    storage_root: PathBuf,
+}
+
+impl EmbeddingExportExecutor {
+    /// 创建执行器，导出文件写入 `storage_root/exports/embeddings/<租户 ID>/`
+    pub fn new(db: DatabaseConnection, vector_stores: Arc<VectorStoreRegistry>, storage_root: impl Into<PathBuf>) -> Self {
+        Self { db, vector_stores, storage_root: storage_root.into() }
+    }
+
+    /// 按文档块 ID 顺序分页读取文档块及其在知识库当前嵌入模型下的嵌入记录
+    async fn load_page(
+        &self,
+        kb: &knowledge_base::Model,
+        after: Uuid,
+        include_archived: bool,
+    ) -> Result<Vec<(ExportedChunk, Option<Uuid>)>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT c.id, c.document_id, c.chunk_index, c.content, c.metadata, d.title,
+                    (SELECT e.id FROM embeddings e
+                     WHERE e.chunk_id = c.id AND e.model_name = $2 AND e.status = 'completed'
+                     ORDER BY e.created_at DESC LIMIT 1) AS embedding_id
+                FROM document_chunks c
+                JOIN documents d ON d.id = c.document_id
+                WHERE c.knowledge_base_id = $1
+                    AND c.id > $3
+                    AND ($4 OR d.status <> 'archived')
+                ORDER BY c.id
+                LIMIT $5
+                "#,
+                vec![
+                    kb.id.into(),
+                    kb.embedding_model.clone().into(),
+                    after.into(),
+                    include_archived.into(),
+                    (EXPORT_PAGE_SIZE as i64).into(),
+                ],
+            ))
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let chunk = ExportedChunk {
+                    id: row.try_get("", "id")?,
+                    document_id: row.try_get("", "document_id")?,
+                    knowledge_base_id: kb.id,
+                    document_title: row.try_get("", "title")?,
+                    chunk_index: row.try_get("", "chunk_index")?,
+                    text: row.try_get("", "content")?,
+                    metadata: row.try_get("", "metadata")?,
+                    embedding_model: None,
+                    embedding: None,
+                };
+                Ok((chunk, row.try_get("", "embedding_id")?))
+            })
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskExecutor for EmbeddingExportExecutor {
+    async fn execute(&self, task: &mut TaskInfo) -> Result<(), AiStudioError> {
+        let parameters: ExportParameters = serde_json::from_value(task.parameters.clone())?;
+        let kb = KnowledgeBase::find_by_id(parameters.knowledge_base_id)
+            .filter(knowledge_base::Column::TenantId.eq(task.tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("知识库"))?;
+        let store = self.vector_stores.for_knowledge_base(kb.id).await?;
+        task.total_count = Some(kb.chunk_count.max(0) as u32);
+
+        let mut writer = ExportWriter::new(parameters.format)?;
+        let mut after = Uuid::nil();
+        let (mut chunk_count, mut embedding_count) = (0u32, 0u32);
+        loop {
+            let page = self.load_page(&kb, after, parameters.include_archived).await?;
+            let Some((last, _)) = page.last() else {
+                break;
+            };
+            after = last.id;
+
+            let vectors = if parameters.include_embeddings {
+                let ids: Vec<Uuid> = page.iter().filter_map(|(_, embedding_id)| *embedding_id).collect();
+                store.fetch(kb.id, &ids).await?
+            } else {
+                Vec::new()
+            };
+            let chunks: Vec<ExportedChunk> = page.into_iter()
+                .map(|(mut chunk, embedding_id)| {
+                    chunk.embedding = embedding_id
+                        .and_then(|id| vectors.iter().find(|point| point.id == id))
+                        .map(|point| point.vector.clone());
+                    chunk.embedding_model = chunk.embedding.as_ref().map(|_| kb.embedding_model.clone());
+                    chunk
+                })
+                .collect();
+
+            chunk_count += chunks.len() as u32;
+            embedding_count += chunks.iter().filter(|chunk| chunk.embedding.is_some()).count() as u32;
+            writer.write(&chunks)?;
+            task.success_count = chunk_count;
+            if let Some(total) = task.total_count.filter(|total| *total > 0) {
+                task.progress = ((chunk_count as u64 * 90 / total as u64).min(90)) as u8;
+            }
+        }
+        let content = writer.finish()?;
+
+        let path = export_file_path(&self.storage_root, task.tenant_id, task.id, parameters.format);
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await
+                .map_err(|e| AiStudioError::internal(format!("创建导出目录失败: {}", e)))?;
+            remove_expired_exports(dir).await;
+        }
+        tokio::fs::write(&path, &content).await
+            .map_err(|e| AiStudioError::internal(format!("写入导出文件失败: {}", e)))?;
+
+        task.result = Some(serde_json::json!({
+            "chunk_count": chunk_count,
+            "embedding_count": embedding_count,
+            "file_size": content.len(),
+        }));
+        info!(
+            "向量导出完成: export_id={}, kb={}, 文档块={}, 向量={}, 请求人={}",
+            task.id, kb.id, chunk_count, embedding_count, parameters.requested_by
+        );
+        Ok(())
+    }
+
+    fn supported_task_types(&self) -> Vec<TaskType> {
+        vec![TaskType::EmbeddingExport]
+    }
+}
+
+/// 按格式逐页写入导出内容
+enum ExportWriter {
+    Jsonl(Vec<u8>),
+    Parquet(Box<ArrowWriter<Vec<u8>>>),
+}
+
+impl ExportWriter {
+    fn new(format: EmbeddingExportFormat) -> Result<Self, AiStudioError> {
+        Ok(match format {
+            EmbeddingExportFormat::Jsonl => Self::Jsonl(Vec::new()),
+            EmbeddingExportFormat::Parquet => {
+                let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+                let writer = ArrowWriter::try_new(Vec::new(), parquet_schema(), Some(properties))
+                    .map_err(parquet_error)?;
+                Self::Parquet(Box::new(writer))
+            }
+        })
+    }
+
+    fn write(&mut self, chunks: &[ExportedChunk]) -> Result<(), AiStudioError> {
+        match self {
+            Self::Jsonl(buffer) => {
+                for chunk in chunks {
+                    serde_json::to_writer(&mut *buffer, chunk)?;
+                    buffer.push(b'\n');
+                }
+                Ok(())
+            }
+            Self::Parquet(writer) => writer.write(&record_batch(chunks)?).map_err(parquet_error),
+        }
+    }
+
+    fn finish(self) -> Result<Vec<u8>, AiStudioError> {
+        match self {
+            Self::Jsonl(buffer) => Ok(buffer),
+            Self::Parquet(mut writer) => {
+                writer.finish().map_err(parquet_error)?;
+                writer.into_inner().map_err(parquet_error)
+            }
+        }
+    }
+}
+
+/// Parquet 文件的列定义
+fn parquet_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("document_id", DataType::Utf8, false),
+        Field::new("knowledge_base_id", DataType::Utf8, false),
+        Field::new("document_title", DataType::Utf8, false),
+        Field::new("chunk_index", DataType::Int32, false),
+        Field::new("text", DataType::Utf8, false),
+        Field::new("metadata", DataType::Utf8, false),
+        Field::new("embedding_model", DataType::Utf8, true),
+        Field::new("embedding", DataType::List(Arc::new(Field::new("item", DataType::Float32, true))), true),
+    ]))
+}
+
+/// 将一页文档块转换为 Arrow 记录批次
+fn record_batch(chunks: &[ExportedChunk]) -> Result<RecordBatch, AiStudioError> {
+    let strings = |f: &dyn Fn(&ExportedChunk) -> String| -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(chunks.iter().map(f)))
+    };
+    let mut embeddings = ListBuilder::new(Float32Builder::new());
+    for chunk in chunks {
+        match &chunk.embedding {
+            Some(vector) => {
+                embeddings.values().append_slice(vector);
+                embeddings.append(true);
+            }
+            None => embeddings.append(false),
+        }
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        strings(&|chunk| chunk.id.to_string()),
+        strings(&|chunk| chunk.document_id.to_string()),
+        strings(&|chunk| chunk.knowledge_base_id.to_string()),
+        strings(&|chunk| chunk.document_title.clone()),
+        Arc::new(Int32Array::from_iter_values(chunks.iter().map(|chunk| chunk.chunk_index))),
+        strings(&|chunk| chunk.text.clone()),
+        strings(&|chunk| chunk.metadata.to_string()),
+        Arc::new(StringArray::from_iter(chunks.iter().map(|chunk| chunk.embedding_model.clone()))),
+        Arc::new(embeddings.finish()),
+    ];
+    RecordBatch::try_new(parquet_schema(), columns)
+        .map_err(|e| AiStudioError::internal(format!("构建 Parquet 记录批次失败: {}", e)))
+}
+
+fn parquet_error(e: parquet::errors::ParquetError) -> AiStudioError {
+    AiStudioError::internal(format!("写入 Parquet 文件失败: {}", e))
+}
+
+/// 导出文件路径
+pub fn export_file_path(storage_root: &Path, tenant_id: Uuid, export_id: Uuid, format: EmbeddingExportFormat) -> PathBuf {
+    storage_root
+        .join("exports")
+        .join("embeddings")
+        .join(tenant_id.to_string())
+        .join(format!("{}.{}", export_id, format.extension()))
+}
+
+/// 下载链接
+pub fn download_url(export_id: Uuid, token: &str) -> String {
+    format!("/api/v1/downloads/embeddings/{}?token={}", export_id, token)
+}
+
+/// 签发下载令牌
+pub fn sign_download_token(secret: &str, claims: &EmbeddingDownloadClaims) -> Result<String, AiStudioError> {
+    encode(&Header::new(Algorithm::HS256), claims, &EncodingKey::from_secret(secret.as_bytes()))
+        .map_err(|e| AiStudioError::internal(format!("签发下载令牌失败: {}", e)))
+}
+
+/// 校验下载令牌，令牌必须与导出 ID 对应且未过期
+pub fn verify_download_token(secret: &str, export_id: Uuid, token: &str) -> Result<EmbeddingDownloadClaims, AiStudioError> {
+    let claims = decode::<EmbeddingDownloadClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::new(Algorithm::HS256),
+    )
+    .map_err(|_| AiStudioError::forbidden("下载链接无效或已过期"))?
+    .claims;
+
+    if claims.sub != export_id {
+        return Err(AiStudioError::forbidden("下载链接无效或已过期"));
+    }
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Array, ListArray};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn chunk(index: i32, embedding: Option<Vec<f32>>) -> ExportedChunk {
+        ExportedChunk {
+            id: Uuid::new_v4(),
+            document_id: Uuid::nil(),
+            knowledge_base_id: Uuid::nil(),
+            document_title: "差旅制度".to_string(),
+            chunk_index: index,
+            text: format!("第 {} 段", index),
+            metadata: serde_json::json!({ "tags": ["hr"] }),
+            embedding_model: embedding.as_ref().map(|_| "text-embedding-3-small".to_string()),
+            embedding,
+        }
+    }
+
+    #[test]
+    fn test_jsonl_export() {
+        let chunks = vec![chunk(0, Some(vec![0.5, -0.25])), chunk(1, None)];
+        let mut writer = ExportWriter::new(EmbeddingExportFormat::Jsonl).unwrap();
+        writer.write(&chunks).unwrap();
+        let content = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        let lines: Vec<ExportedChunk> = content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines, chunks);
+    }
+
+    #[test]
+    fn test_parquet_export_roundtrip() {
+        let mut writer = ExportWriter::new(EmbeddingExportFormat::Parquet).unwrap();
+        writer.write(&[chunk(0, Some(vec![0.5, -0.25, 1.0]))]).unwrap();
+        writer.write(&[chunk(1, None)]).unwrap();
+        let content = writer.finish().unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(content))
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<RecordBatch> = reader.map(|batch| batch.unwrap()).collect();
+        assert_eq!(batches.iter().map(|batch| batch.num_rows()).sum::<usize>(), 2);
+
+        let batch = &batches[0];
+        let text = batch.column_by_name("text").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(text.value(0), "第 0 段");
+        let embeddings = batch.column_by_name("embedding").unwrap().as_any().downcast_ref::<ListArray>().unwrap();
+        let first = embeddings.value(0);
+        let first = first.as_any().downcast_ref::<arrow_array::Float32Array>().unwrap();
+        assert_eq!(first.values().to_vec(), vec![0.5, -0.25, 1.0]);
+        if batch.num_rows() > 1 {
+            assert!(embeddings.is_null(1));
+        }
+    }
+
+    #[test]
+    fn test_download_token_roundtrip() {
+        let export_id = Uuid::new_v4();
+        let claims = EmbeddingDownloadClaims {
+            sub: export_id,
+            tid: Uuid::new_v4(),
+            fmt: EmbeddingExportFormat::Parquet,
+            exp: (Utc::now() + Duration::hours(1)).timestamp(),
+        };
+        let token = sign_download_token("secret", &claims).unwrap();
+
+        assert_eq!(verify_download_token("secret", export_id, &token).unwrap().fmt, EmbeddingExportFormat::Parquet);
+        assert!(verify_download_token("secret", Uuid::new_v4(), &token).is_err());
+    }
+}
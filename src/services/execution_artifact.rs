@@ -0,0 +1,347 @@
+// 执行产物服务
+// 超过阈值的步骤输出转存到存储目录，数据库中仅保留指针，读取时间线时透明取回，到期后连同文件一并清理
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    QueryFilter, Set, Statement,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, instrument, warn};
+use uuid::Uuid;
+
+use crate::config::{AppConfig, ExecutionArtifactConfig};
+use crate::db::entities::execution_artifact;
+use crate::db::entities::execution_event::ExecutionType;
+use crate::db::entities::ExecutionArtifact;
+use crate::errors::AiStudioError;
+use crate::services::scheduler::PeriodicJob;
+
+/// 产物指针在 JSON 中的键名
+pub const ARTIFACT_POINTER_KEY: &str = "$artifact";
+
+/// 产物内容类型
+const ARTIFACT_CONTENT_TYPE: &str = "application/json";
+
+/// 产物指针，替代原输出写入数据库
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactPointer {
+    /// 产物 ID
+    pub id: Uuid,
+    /// 内容大小（字节）
+    pub size_bytes: i64,
+    /// 内容 SHA-256 摘要
+    pub sha256: String,
+    /// 内容类型
+    pub content_type: String,
+}
+
+impl ArtifactPointer {
+    /// 编码为 `{"$artifact": {...}}`
+    pub fn to_value(&self) -> Value {
+        serde_json::json!({ ARTIFACT_POINTER_KEY: self })
+    }
+
+    /// 从 JSON 中识别产物指针，仅当对象只包含 `$artifact` 一个键时成立
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let map = value.as_object().filter(|map| map.len() == 1)?;
+        serde_json::from_value(map.get(ARTIFACT_POINTER_KEY)?.clone()).ok()
+    }
+}
+
+/// 执行产物服务
+#[derive(Debug, Clone)]
+pub struct ExecutionArtifactService {
+    db: DatabaseConnection,
+    storage_root: PathBuf,
+    config: ExecutionArtifactConfig,
+}
+
+impl ExecutionArtifactService {
+    /// 创建新的执行产物服务实例，产物写入 `storage_root/artifacts/executions/<租户 ID>/`
+    pub fn new(db: DatabaseConnection, storage_root: impl Into<PathBuf>, config: ExecutionArtifactConfig) -> Self {
+        Self { db, storage_root: storage_root.into(), config }
+    }
+
+    /// 按应用配置创建
+    pub fn from_app_config(db: DatabaseConnection, config: &AppConfig) -> Self {
+        Self::new(db, &config.storage.path, config.execution_artifacts.clone())
+    }
+
+    /// 输出超过阈值时转存为产物并返回指针，否则原样返回
+    #[instrument(skip(self, value))]
+    pub async fn offload(
+        &self,
+        tenant_id: Uuid,
+        execution_type: ExecutionType,
+        execution_id: Uuid,
+        step_id: Option<&str>,
+        value: Value,
+    ) -> Result<Value, AiStudioError> {
+        if !self.config.enabled || ArtifactPointer::from_value(&value).is_some() {
+            return Ok(value);
+        }
+
+        let content = serde_json::to_vec(&value)?;
+        if content.len() as u64 <= self.config.inline_threshold_bytes {
+            return Ok(value);
+        }
+
+        let id = Uuid::new_v4();
+        let storage_key = artifact_storage_key(tenant_id, id);
+        let path = self.storage_root.join(&storage_key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await
+                .map_err(|e| AiStudioError::internal(format!("创建产物目录失败: {}", e)))?;
+        }
+        tokio::fs::write(&path, &content).await
+            .map_err(|e| AiStudioError::internal(format!("写入执行产物失败: {}", e)))?;
+
+        let pointer = ArtifactPointer {
+            id,
+            size_bytes: content.len() as i64,
+            sha256: sha256_hex(&content),
+            content_type: ARTIFACT_CONTENT_TYPE.to_string(),
+        };
+        let now = Utc::now();
+        let expires_at = (self.config.retention_days > 0)
+            .then(|| (now + chrono::Duration::days(self.config.retention_days as i64)).fixed_offset());
+
+        let inserted = execution_artifact::ActiveModel {
+            id: Set(id),
+            tenant_id: Set(tenant_id),
+            execution_type: Set(execution_type),
+            execution_id: Set(execution_id),
+            step_id: Set(step_id.map(str::to_string)),
+            content_type: Set(pointer.content_type.clone()),
+            size_bytes: Set(pointer.size_bytes),
+            sha256: Set(pointer.sha256.clone()),
+            storage_key: Set(storage_key),
+            expires_at: Set(expires_at),
+            created_at: Set(now.fixed_offset()),
+        }
+        .insert(&self.db)
+        .await;
+        if let Err(e) = inserted {
+            remove_file_or_warn(&path).await;
+            return Err(e.into());
+        }
+
+        debug!(artifact_id = %id, size_bytes = pointer.size_bytes, "输出已转存为执行产物");
+        Ok(pointer.to_value())
+    }
+
+    /// 读取产物内容，产物不存在或已过期时返回 NotFound
+    #[instrument(skip(self))]
+    pub async fn load(&self, tenant_id: Uuid, artifact_id: Uuid) -> Result<Value, AiStudioError> {
+        let model = ExecutionArtifact::find_by_id(artifact_id)
+            .filter(execution_artifact::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .filter(|model| model.expires_at.map_or(true, |expires_at| expires_at > Utc::now()))
+            .ok_or_else(|| AiStudioError::not_found(format!("执行产物 {}", artifact_id)))?;
+
+        let content = tokio::fs::read(self.storage_root.join(&model.storage_key)).await
+            .map_err(|_| AiStudioError::not_found(format!("执行产物 {}", artifact_id)))?;
+        if sha256_hex(&content) != model.sha256 {
+            return Err(AiStudioError::internal(format!("执行产物 {} 内容校验失败", artifact_id)));
+        }
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    /// 将 JSON 中的产物指针替换为产物内容
+    ///
+    /// 已过期或已清理的产物保留指针，并标记 `"available": false`。
+    pub async fn dereference(&self, tenant_id: Uuid, value: &mut Value) -> Result<(), AiStudioError> {
+        let mut pointers = Vec::new();
+        collect_pointers(value, &mut pointers);
+        if pointers.is_empty() {
+            return Ok(());
+        }
+
+        let mut contents = HashMap::with_capacity(pointers.len());
+        for pointer in pointers {
+            if contents.contains_key(&pointer.id) {
+                continue;
+            }
+            let content = match self.load(tenant_id, pointer.id).await {
+                Ok(content) => Some(content),
+                Err(AiStudioError::NotFound { .. }) => None,
+                Err(e) => return Err(e),
+            };
+            contents.insert(pointer.id, content);
+        }
+
+        replace_pointers(value, &contents);
+        Ok(())
+    }
+
+    /// 分批删除过期产物及其文件，返回删除数量
+    #[instrument(skip(self))]
+    pub async fn purge_expired(&self) -> Result<u64, AiStudioError> {
+        let mut purged = 0u64;
+        loop {
+            let rows = self.db
+                .query_all(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    "DELETE FROM execution_artifacts WHERE id IN ( \
+                         SELECT id FROM execution_artifacts WHERE expires_at < CURRENT_TIMESTAMP LIMIT $1 \
+                     ) RETURNING storage_key",
+                    [(self.config.cleanup_batch_size as i64).into()],
+                ))
+                .await?;
+
+            for row in &rows {
+                let storage_key: String = row.try_get("", "storage_key")?;
+                remove_file_or_warn(&self.storage_root.join(storage_key)).await;
+            }
+
+            purged += rows.len() as u64;
+            if rows.len() < self.config.cleanup_batch_size as usize {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+
+        if purged > 0 {
+            info!(purged, "过期执行产物已清理");
+        }
+        Ok(purged)
+    }
+}
+
+/// 产物在存储目录下的相对路径
+pub fn artifact_storage_key(tenant_id: Uuid, artifact_id: Uuid) -> String {
+    format!("artifacts/executions/{}/{}.json", tenant_id, artifact_id)
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+async fn remove_file_or_warn(path: &Path) {
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => warn!(path = %path.display(), error = %e, "删除执行产物文件失败"),
+    }
+}
+
+/// JSON 中是否包含产物指针
+pub fn has_artifact_pointers(value: &Value) -> bool {
+    let mut pointers = Vec::new();
+    collect_pointers(value, &mut pointers);
+    !pointers.is_empty()
+}
+
+fn collect_pointers(value: &Value, pointers: &mut Vec<ArtifactPointer>) {
+    if let Some(pointer) = ArtifactPointer::from_value(value) {
+        pointers.push(pointer);
+        return;
+    }
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_pointers(item, pointers)),
+        Value::Object(map) => map.values().for_each(|item| collect_pointers(item, pointers)),
+        _ => {}
+    }
+}
+
+fn replace_pointers(value: &mut Value, contents: &HashMap<Uuid, Option<Value>>) {
+    if let Some(pointer) = ArtifactPointer::from_value(value) {
+        match contents.get(&pointer.id) {
+            Some(Some(content)) => *value = content.clone(),
+            _ => {
+                if let Some(fields) = value.get_mut(ARTIFACT_POINTER_KEY).and_then(Value::as_object_mut) {
+                    fields.insert("available".to_string(), Value::Bool(false));
+                }
+            }
+        }
+        return;
+    }
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(|item| replace_pointers(item, contents)),
+        Value::Object(map) => map.values_mut().for_each(|item| replace_pointers(item, contents)),
+        _ => {}
+    }
+}
+
+/// 过期执行产物清理周期任务
+pub struct ExecutionArtifactCleanupJob {
+    service: Arc<ExecutionArtifactService>,
+}
+
+impl ExecutionArtifactCleanupJob {
+    /// 创建新的过期执行产物清理任务
+    pub fn new(service: Arc<ExecutionArtifactService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for ExecutionArtifactCleanupJob {
+    fn name(&self) -> &str {
+        "execution_artifact_cleanup"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.service.config.cleanup_interval_secs)
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        self.service.purge_expired().await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pointer() -> ArtifactPointer {
+        ArtifactPointer {
+            id: Uuid::new_v4(),
+            size_bytes: 1 << 20,
+            sha256: "0".repeat(64),
+            content_type: ARTIFACT_CONTENT_TYPE.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_artifact_pointer_roundtrip() {
+        let pointer = pointer();
+        assert_eq!(ArtifactPointer::from_value(&pointer.to_value()), Some(pointer.clone()));
+
+        // 带有其他键的对象不是指针
+        let mut value = pointer.to_value();
+        value["extra"] = Value::Bool(true);
+        assert_eq!(ArtifactPointer::from_value(&value), None);
+    }
+
+    #[test]
+    fn test_replace_nested_pointers() {
+        let (kept, expired) = (pointer(), pointer());
+        let mut payload = serde_json::json!({
+            "step_id": "fetch",
+            "output": kept.to_value(),
+            "history": [expired.to_value()]
+        });
+
+        let mut pointers = Vec::new();
+        collect_pointers(&payload, &mut pointers);
+        assert_eq!(pointers.len(), 2);
+        assert!(pointers.contains(&kept) && pointers.contains(&expired));
+
+        let contents = HashMap::from([
+            (kept.id, Some(serde_json::json!({ "rows": [1, 2, 3] }))),
+            (expired.id, None),
+        ]);
+        replace_pointers(&mut payload, &contents);
+        assert_eq!(payload["output"]["rows"][2], 3);
+        assert_eq!(payload["history"][0][ARTIFACT_POINTER_KEY]["available"], false);
+    }
+}
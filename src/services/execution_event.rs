@@ -0,0 +1,591 @@
+// 执行事件服务
+// 将 Agent 与工作流执行的每次状态转换追加为不可变事件，并据此重建执行时间线与计费时长；
+// 长时间执行的轨迹按步骤分页读取，大体积步骤数据只返回预览，按需展开
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, DatabaseBackend, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Statement,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::ai::agent_stream::truncate_json;
+use crate::config::ExecutionArtifactConfig;
+use crate::db::entities::execution_event::{self, ExecutionType};
+use crate::db::entities::ExecutionEvent;
+use crate::errors::AiStudioError;
+use crate::services::execution_artifact::{has_artifact_pointers, ExecutionArtifactService};
+use crate::services::prompt_retention::{contains_model_io, load_prompt_retention, redact_trace_payload};
+
+/// 状态转换
+#[derive(Debug, Clone)]
+pub struct ExecutionTransition {
+    /// 租户 ID
+    pub tenant_id: Uuid,
+    /// 执行类型
+    pub execution_type: ExecutionType,
+    /// 执行 ID
+    pub execution_id: Uuid,
+    /// 转换前状态
+    pub from_state: Option<String>,
+    /// 转换后状态
+    pub to_state: String,
+    /// 附带数据
+    pub payload: serde_json::Value,
+}
+
+/// 时间线查询参数
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ExecutionTimelineQuery {
+    /// 回放到该时间点（RFC 3339），返回当时的状态
+    pub at: Option<DateTime<Utc>>,
+}
+
+/// 每页默认步骤数
+const DEFAULT_STEP_LIMIT: u64 = 50;
+
+/// 每页最多步骤数
+const MAX_STEP_LIMIT: u64 = 200;
+
+/// 执行步骤分页查询参数
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct ExecutionStepsQuery {
+    /// 上一页返回的 `next_cursor`
+    pub cursor: Option<String>,
+    /// 每页步骤数，默认 50，最多 200；单页数据总量超过上限时提前结束
+    pub limit: Option<u64>,
+}
+
+/// 单个执行步骤查询参数
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct ExecutionStepQuery {
+    /// 只展开步骤数据的一部分（JSON Pointer，如 `/output/rows`），默认展开全部
+    pub pointer: Option<String>,
+}
+
+/// 执行步骤的截断策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TracePagePolicy {
+    /// 单个步骤数据超过该字节数时只返回预览
+    pub preview_bytes: usize,
+    /// 单页步骤数据的总字节上限
+    pub page_bytes: usize,
+}
+
+impl TracePagePolicy {
+    /// 按执行产物配置创建
+    pub fn from_config(config: &ExecutionArtifactConfig) -> Self {
+        Self {
+            preview_bytes: config.trace_preview_bytes as usize,
+            page_bytes: config.trace_page_bytes as usize,
+        }
+    }
+}
+
+/// 执行轨迹中的步骤
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExecutionTraceStep {
+    /// 事件序号
+    pub sequence: i32,
+    /// 转换前状态
+    pub from_state: Option<String>,
+    /// 转换后状态
+    pub to_state: String,
+    /// 步骤数据；截断时为 `{"truncated": true, "original_bytes", "preview"}`，转存的产物保留 `$artifact` 指针
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+    /// 步骤数据序列化后的字节数，不含转存产物的内容
+    pub payload_bytes: u64,
+    /// 步骤数据是否被截断为预览
+    pub truncated: bool,
+    /// 是否可按序号展开完整内容（已截断或包含转存产物）
+    pub expandable: bool,
+    /// 发生时间
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl ExecutionTraceStep {
+    /// 步骤数据超过预览大小时截断
+    fn preview(event: TimelineEvent, preview_bytes: usize) -> Self {
+        let has_artifacts = has_artifact_pointers(&event.payload);
+        let (payload, payload_bytes, truncated) = truncate_json(&event.payload, preview_bytes);
+        Self {
+            sequence: event.sequence,
+            from_state: event.from_state,
+            to_state: event.to_state,
+            payload,
+            payload_bytes: payload_bytes as u64,
+            truncated,
+            expandable: truncated || has_artifacts,
+            occurred_at: event.occurred_at,
+        }
+    }
+
+    /// 完整的步骤数据
+    fn expanded(event: TimelineEvent) -> Self {
+        let payload_bytes = event.payload.to_string().len() as u64;
+        Self {
+            sequence: event.sequence,
+            from_state: event.from_state,
+            to_state: event.to_state,
+            payload: event.payload,
+            payload_bytes,
+            truncated: false,
+            expandable: false,
+            occurred_at: event.occurred_at,
+        }
+    }
+}
+
+/// 执行步骤分页结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExecutionTracePage {
+    /// 执行类型
+    pub execution_type: ExecutionType,
+    /// 执行 ID
+    pub execution_id: Uuid,
+    /// 按序号排列的步骤
+    pub steps: Vec<ExecutionTraceStep>,
+    /// 下一页游标，没有更多步骤时为空
+    pub next_cursor: Option<String>,
+}
+
+/// 编码分页游标，指向上一页最后一个步骤的序号
+fn encode_step_cursor(sequence: i32) -> String {
+    URL_SAFE_NO_PAD.encode(format!("step:{}", sequence))
+}
+
+fn decode_step_cursor(value: &str) -> Result<i32, AiStudioError> {
+    let invalid = || AiStudioError::validation("cursor", "无效的分页游标");
+    let raw = URL_SAFE_NO_PAD.decode(value).map_err(|_| invalid())?;
+    let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+    raw.strip_prefix("step:")
+        .and_then(|sequence| sequence.parse().ok())
+        .ok_or_else(invalid)
+}
+
+/// 按截断策略组装一页步骤
+///
+/// `events` 最多比 `limit` 多一条，用于判断是否还有下一页；单页数据超过上限时提前结束，
+/// 但至少返回一个步骤。
+fn paginate_steps(
+    events: Vec<TimelineEvent>,
+    limit: usize,
+    policy: TracePagePolicy,
+) -> (Vec<ExecutionTraceStep>, Option<String>) {
+    let total = events.len();
+    let mut steps = Vec::with_capacity(total.min(limit));
+    let mut page_bytes = 0usize;
+    for event in events.into_iter().take(limit) {
+        let step = ExecutionTraceStep::preview(event, policy.preview_bytes);
+        let step_bytes = step.payload.to_string().len();
+        if !steps.is_empty() && page_bytes + step_bytes > policy.page_bytes {
+            break;
+        }
+        page_bytes += step_bytes;
+        steps.push(step);
+    }
+
+    let next_cursor = (steps.len() < total)
+        .then(|| steps.last().map(|step| encode_step_cursor(step.sequence)))
+        .flatten();
+    (steps, next_cursor)
+}
+
+/// 时间线中的事件
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TimelineEvent {
+    /// 事件序号
+    pub sequence: i32,
+    /// 转换前状态
+    pub from_state: Option<String>,
+    /// 转换后状态
+    pub to_state: String,
+    /// 附带数据
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+    /// 发生时间
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl From<execution_event::Model> for TimelineEvent {
+    fn from(model: execution_event::Model) -> Self {
+        Self {
+            sequence: model.sequence,
+            from_state: model.from_state,
+            to_state: model.to_state,
+            payload: model.payload,
+            occurred_at: model.occurred_at.with_timezone(&Utc),
+        }
+    }
+}
+
+/// 执行时间线
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExecutionTimeline {
+    /// 执行类型
+    pub execution_type: ExecutionType,
+    /// 执行 ID
+    pub execution_id: Uuid,
+    /// 最新状态
+    pub current_state: Option<String>,
+    /// 指定时间点的状态（查询时携带 at 参数时返回）
+    pub state_at: Option<String>,
+    /// 处于活跃状态的累计时长（毫秒），用于计费
+    pub active_duration_ms: i64,
+    /// 按序号排列的全部事件
+    pub events: Vec<TimelineEvent>,
+}
+
+impl ExecutionTimeline {
+    /// 由按序号排列的事件重建时间线
+    ///
+    /// 未结束的执行，最后一个活跃状态计至 `now`。
+    pub fn rebuild(
+        execution_type: ExecutionType,
+        execution_id: Uuid,
+        events: Vec<TimelineEvent>,
+        at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let state_at = at.and_then(|at| {
+            events
+                .iter()
+                .take_while(|event| event.occurred_at <= at)
+                .last()
+                .map(|event| event.to_state.clone())
+        });
+
+        let active_duration_ms = events
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| execution_type.is_active_state(&event.to_state))
+            .map(|(index, event)| {
+                let until = events.get(index + 1).map(|next| next.occurred_at).unwrap_or(now);
+                (until - event.occurred_at).num_milliseconds().max(0)
+            })
+            .sum();
+
+        Self {
+            execution_type,
+            execution_id,
+            current_state: events.last().map(|event| event.to_state.clone()),
+            state_at,
+            active_duration_ms,
+            events,
+        }
+    }
+}
+
+/// 执行事件服务
+#[derive(Debug, Clone)]
+pub struct ExecutionEventService {
+    db: DatabaseConnection,
+    /// 执行产物存储，配置后超过阈值的事件数据转存为产物，读取时间线时自动取回
+    artifacts: Option<ExecutionArtifactService>,
+}
+
+impl ExecutionEventService {
+    /// 创建新的执行事件服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db, artifacts: None }
+    }
+
+    /// 将大体积事件数据转存为执行产物
+    pub fn with_artifacts(mut self, artifacts: ExecutionArtifactService) -> Self {
+        self.artifacts = Some(artifacts);
+        self
+    }
+
+    /// 追加一次状态转换
+    ///
+    /// 序号在插入时按同一执行已有的最大序号递增，唯一约束保证序号不重复。
+    #[instrument(skip(self, transition), fields(execution_id = %transition.execution_id, to_state = %transition.to_state))]
+    pub async fn record(&self, transition: ExecutionTransition) -> Result<execution_event::Model, AiStudioError> {
+        let execution_type = match transition.execution_type {
+            ExecutionType::Agent => "agent",
+            ExecutionType::Workflow => "workflow",
+        };
+
+        // 模型原始输入与输出按租户留存策略脱敏或省略后再写入轨迹
+        let payload = if contains_model_io(&transition.payload) {
+            let policy = load_prompt_retention(&self.db, transition.tenant_id).await;
+            redact_trace_payload(&policy, transition.payload)
+        } else {
+            transition.payload
+        };
+
+        let payload = match &self.artifacts {
+            Some(artifacts) => {
+                let step_id = payload.get("step_id").and_then(|v| v.as_str()).map(str::to_string);
+                artifacts.offload(
+                    transition.tenant_id,
+                    transition.execution_type,
+                    transition.execution_id,
+                    step_id.as_deref(),
+                    payload,
+                ).await?
+            }
+            None => payload,
+        };
+
+        let event = ExecutionEvent::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "INSERT INTO execution_events \
+                     (tenant_id, execution_type, execution_id, sequence, from_state, to_state, payload, occurred_at) \
+                 SELECT $1, $2, $3, COALESCE(MAX(sequence), 0) + 1, $4, $5, $6, $7 \
+                 FROM execution_events WHERE execution_type = $2 AND execution_id = $3 \
+                 RETURNING *",
+                [
+                    transition.tenant_id.into(),
+                    execution_type.into(),
+                    transition.execution_id.into(),
+                    transition.from_state.into(),
+                    transition.to_state.into(),
+                    payload.into(),
+                    Utc::now().into(),
+                ],
+            ))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::database("写入执行事件失败"))?;
+
+        debug!(sequence = event.sequence, "已记录执行状态转换");
+        Ok(event)
+    }
+
+    /// 追加状态转换，失败时只记录警告
+    ///
+    /// 事件记录不应中断执行本身，供运行时在状态转换处调用。
+    pub async fn record_or_warn(&self, transition: ExecutionTransition) {
+        let execution_id = transition.execution_id;
+        if let Err(e) = self.record(transition).await {
+            warn!(execution_id = %execution_id, error = %e, "记录执行事件失败");
+        }
+    }
+
+    /// 获取执行时间线，事件数据中的产物指针替换为产物内容
+    #[instrument(skip(self))]
+    pub async fn timeline(
+        &self,
+        tenant_id: Uuid,
+        execution_type: ExecutionType,
+        execution_id: Uuid,
+        at: Option<DateTime<Utc>>,
+    ) -> Result<ExecutionTimeline, AiStudioError> {
+        let events = ExecutionEvent::find()
+            .filter(execution_event::Column::TenantId.eq(tenant_id))
+            .filter(execution_event::Column::ExecutionType.eq(execution_type))
+            .filter(execution_event::Column::ExecutionId.eq(execution_id))
+            .order_by_asc(execution_event::Column::Sequence)
+            .all(&self.db)
+            .await?;
+
+        if events.is_empty() {
+            return Err(AiStudioError::not_found(format!("执行 {} 的事件", execution_id)));
+        }
+
+        let mut events: Vec<TimelineEvent> = events.into_iter().map(TimelineEvent::from).collect();
+        if let Some(artifacts) = &self.artifacts {
+            for event in &mut events {
+                artifacts.dereference(tenant_id, &mut event.payload).await?;
+            }
+        }
+
+        Ok(ExecutionTimeline::rebuild(
+            execution_type,
+            execution_id,
+            events,
+            at,
+            Utc::now(),
+        ))
+    }
+
+    /// 分页读取执行步骤
+    ///
+    /// 转存的产物不取回，超过预览大小的步骤数据只返回预览，完整内容通过 [`Self::step`] 按需展开。
+    #[instrument(skip(self, query))]
+    pub async fn steps(
+        &self,
+        tenant_id: Uuid,
+        execution_type: ExecutionType,
+        execution_id: Uuid,
+        query: &ExecutionStepsQuery,
+        policy: TracePagePolicy,
+    ) -> Result<ExecutionTracePage, AiStudioError> {
+        let after = query.cursor.as_deref().map(decode_step_cursor).transpose()?;
+        let limit = query.limit.unwrap_or(DEFAULT_STEP_LIMIT).clamp(1, MAX_STEP_LIMIT);
+
+        // 多取一条判断是否还有下一页
+        let mut select = ExecutionEvent::find()
+            .filter(execution_event::Column::TenantId.eq(tenant_id))
+            .filter(execution_event::Column::ExecutionType.eq(execution_type))
+            .filter(execution_event::Column::ExecutionId.eq(execution_id));
+        if let Some(after) = after {
+            select = select.filter(execution_event::Column::Sequence.gt(after));
+        }
+        let events = select
+            .order_by_asc(execution_event::Column::Sequence)
+            .limit(limit + 1)
+            .all(&self.db)
+            .await?;
+
+        if events.is_empty() && after.is_none() {
+            return Err(AiStudioError::not_found(format!("执行 {} 的事件", execution_id)));
+        }
+
+        let events = events.into_iter().map(TimelineEvent::from).collect();
+        let (steps, next_cursor) = paginate_steps(events, limit as usize, policy);
+        Ok(ExecutionTracePage {
+            execution_type,
+            execution_id,
+            steps,
+            next_cursor,
+        })
+    }
+
+    /// 展开单个执行步骤，取回转存的产物
+    ///
+    /// 指定 `pointer` 时只返回步骤数据中对应的部分，避免为查看局部内容取回整个步骤。
+    #[instrument(skip(self))]
+    pub async fn step(
+        &self,
+        tenant_id: Uuid,
+        execution_type: ExecutionType,
+        execution_id: Uuid,
+        sequence: i32,
+        pointer: Option<&str>,
+    ) -> Result<ExecutionTraceStep, AiStudioError> {
+        let event = ExecutionEvent::find()
+            .filter(execution_event::Column::TenantId.eq(tenant_id))
+            .filter(execution_event::Column::ExecutionType.eq(execution_type))
+            .filter(execution_event::Column::ExecutionId.eq(execution_id))
+            .filter(execution_event::Column::Sequence.eq(sequence))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found(format!("执行 {} 的步骤 {}", execution_id, sequence)))?;
+        let mut event = TimelineEvent::from(event);
+
+        let pointer = pointer.filter(|pointer| !pointer.is_empty());
+        if let Some(pointer) = pointer {
+            if !pointer.starts_with('/') {
+                return Err(AiStudioError::validation("pointer", "JSON Pointer 必须以 / 开头"));
+            }
+            // 指针未进入转存产物时只取回局部内容，否则取回整个步骤后再定位
+            match event.payload.pointer(pointer).cloned() {
+                Some(part) => event.payload = part,
+                None => {
+                    self.dereference(tenant_id, &mut event.payload).await?;
+                    event.payload = event.payload.pointer(pointer).cloned().ok_or_else(|| {
+                        AiStudioError::not_found(format!("步骤 {} 中的 {}", sequence, pointer))
+                    })?;
+                }
+            }
+        }
+        self.dereference(tenant_id, &mut event.payload).await?;
+
+        Ok(ExecutionTraceStep::expanded(event))
+    }
+
+    /// 配置了产物存储时将产物指针替换为产物内容
+    async fn dereference(&self, tenant_id: Uuid, payload: &mut serde_json::Value) -> Result<(), AiStudioError> {
+        match &self.artifacts {
+            Some(artifacts) => artifacts.dereference(tenant_id, payload).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(sequence: i32, to_state: &str, offset_secs: i64, start: DateTime<Utc>) -> TimelineEvent {
+        TimelineEvent {
+            sequence,
+            from_state: None,
+            to_state: to_state.to_string(),
+            payload: serde_json::json!({}),
+            occurred_at: start + chrono::Duration::seconds(offset_secs),
+        }
+    }
+
+    #[test]
+    fn test_rebuild_agent_timeline() {
+        let start = Utc::now();
+        let events = vec![
+            event(1, "idle", 0, start),
+            event(2, "thinking", 10, start),
+            event(3, "executing_tool", 15, start),
+            event(4, "waiting_for_input", 20, start),
+            event(5, "thinking", 60, start),
+            event(6, "completed", 65, start),
+        ];
+
+        let timeline = ExecutionTimeline::rebuild(
+            ExecutionType::Agent,
+            Uuid::new_v4(),
+            events,
+            Some(start + chrono::Duration::seconds(30)),
+            start + chrono::Duration::seconds(100),
+        );
+
+        assert_eq!(timeline.current_state.as_deref(), Some("completed"));
+        assert_eq!(timeline.state_at.as_deref(), Some("waiting_for_input"));
+        // 思考 5 秒 + 工具 5 秒 + 思考 5 秒，等待输入不计费
+        assert_eq!(timeline.active_duration_ms, 15_000);
+    }
+
+    #[test]
+    fn test_paginate_steps_truncates_and_limits_page() {
+        let start = Utc::now();
+        let mut large = event(2, "executing_tool", 1, start);
+        large.payload = serde_json::json!({ "output": "x".repeat(4096) });
+        let events = vec![
+            event(1, "thinking", 0, start),
+            large,
+            event(3, "thinking", 2, start),
+            event(4, "completed", 3, start),
+        ];
+        let policy = TracePagePolicy { preview_bytes: 512, page_bytes: 1024 };
+
+        let (steps, next_cursor) = paginate_steps(events.clone(), 3, policy);
+        assert_eq!(steps.len(), 3);
+        assert!(!steps[0].expandable);
+        assert!(steps[1].truncated && steps[1].expandable);
+        assert!(steps[1].payload_bytes > 4096);
+        assert_eq!(decode_step_cursor(&next_cursor.unwrap()).unwrap(), 3);
+
+        // 单页数据总量超过上限时提前结束，但至少返回一个步骤
+        let tight = TracePagePolicy { preview_bytes: 512, page_bytes: 100 };
+        let (steps, next_cursor) = paginate_steps(events[1..].to_vec(), 3, tight);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(decode_step_cursor(&next_cursor.unwrap()).unwrap(), 2);
+
+        // 最后一页没有游标
+        let (steps, next_cursor) = paginate_steps(events[2..].to_vec(), 3, policy);
+        assert_eq!(steps.len(), 2);
+        assert!(next_cursor.is_none());
+        assert!(decode_step_cursor("not-a-cursor").is_err());
+    }
+
+    #[test]
+    fn test_running_workflow_billed_until_now() {
+        let start = Utc::now();
+        let timeline = ExecutionTimeline::rebuild(
+            ExecutionType::Workflow,
+            Uuid::new_v4(),
+            vec![event(1, "running", 0, start)],
+            Some(start - chrono::Duration::seconds(1)),
+            start + chrono::Duration::seconds(42),
+        );
+
+        assert_eq!(timeline.state_at, None);
+        assert_eq!(timeline.active_duration_ms, 42_000);
+    }
+}
@@ -0,0 +1,396 @@
+// 执行记录与重放服务
+// 保存 Agent 与工作流每次执行的输入、输出、耗时和令牌用量；重放时按原始输入（可局部修改）在当前定义上重新执行，
+// 并将重放关联到原始执行以便对比结果
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::execution_event::ExecutionType;
+use crate::db::entities::{execution_record, ExecutionRecord};
+use crate::errors::AiStudioError;
+
+/// 执行结束的状态，处于这些状态的记录不再更新
+const TERMINAL_STATUSES: [&str; 4] = ["completed", "failed", "cancelled", "timeout"];
+
+/// 重放执行请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ReplayExecutionRequest {
+    /// 对原始输入的修改，按 JSON Merge Patch（RFC 7386）合并，值为 null 的字段会被删除；为空时使用原始输入
+    #[schema(value_type = Option<Object>)]
+    pub input_patch: Option<Value>,
+}
+
+/// 执行反馈请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExecutionFeedbackRequest {
+    /// 是否为正面反馈
+    pub positive: bool,
+}
+
+/// 执行记录
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ExecutionRecordSummary {
+    /// 执行 ID
+    pub execution_id: Uuid,
+    /// 执行类型
+    pub execution_type: ExecutionType,
+    /// 执行对象 ID（Agent ID 或工作流 ID）
+    pub target_id: Uuid,
+    /// 执行状态
+    pub status: String,
+    /// 执行输入
+    #[schema(value_type = Object)]
+    pub input: Value,
+    /// 执行输出
+    #[schema(value_type = Option<Object>)]
+    pub output: Option<Value>,
+    /// 错误信息
+    pub error_message: Option<String>,
+    /// 执行耗时（毫秒）
+    pub duration_ms: Option<i64>,
+    /// 令牌总用量
+    pub total_tokens: Option<i64>,
+    /// 重放的原始执行 ID
+    pub replay_of: Option<Uuid>,
+    /// 开始时间
+    pub started_at: DateTime<Utc>,
+    /// 完成时间
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<execution_record::Model> for ExecutionRecordSummary {
+    fn from(model: execution_record::Model) -> Self {
+        Self {
+            execution_id: model.id,
+            execution_type: model.execution_type,
+            target_id: model.target_id,
+            status: model.status,
+            input: model.input,
+            output: model.output,
+            error_message: model.error_message,
+            duration_ms: model.duration_ms,
+            total_tokens: model.total_tokens,
+            replay_of: model.replay_of,
+            started_at: model.started_at.with_timezone(&Utc),
+            completed_at: model.completed_at.map(|at| at.with_timezone(&Utc)),
+        }
+    }
+}
+
+impl ExecutionRecordSummary {
+    /// 执行是否已结束
+    pub fn is_finished(&self) -> bool {
+        TERMINAL_STATUSES.contains(&self.status.as_str())
+    }
+}
+
+/// 重放与原始执行的对比
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReplayComparison {
+    /// 重放执行
+    pub replay: ExecutionRecordSummary,
+    /// 输入是否经过修改
+    pub input_changed: bool,
+    /// 输出是否不同，任一执行未结束时为空
+    pub output_changed: Option<bool>,
+    /// 耗时差（毫秒，重放减原始）
+    pub duration_delta_ms: Option<i64>,
+    /// 令牌用量差（重放减原始）
+    pub token_delta: Option<i64>,
+}
+
+impl ReplayComparison {
+    /// 对比重放与原始执行
+    pub fn between(original: &ExecutionRecordSummary, replay: ExecutionRecordSummary) -> Self {
+        let both_finished = original.is_finished() && replay.is_finished();
+        Self {
+            input_changed: original.input != replay.input,
+            output_changed: both_finished.then(|| original.output != replay.output),
+            duration_delta_ms: original.duration_ms.zip(replay.duration_ms).map(|(a, b)| b - a),
+            token_delta: original.total_tokens.zip(replay.total_tokens).map(|(a, b)| b - a),
+            replay,
+        }
+    }
+}
+
+/// 执行的重放历史
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReplayHistory {
+    /// 原始执行
+    pub original: ExecutionRecordSummary,
+    /// 各次重放与原始执行的对比，按开始时间排列
+    pub replays: Vec<ReplayComparison>,
+}
+
+/// 执行记录服务
+#[derive(Debug, Clone)]
+pub struct ExecutionRecordService {
+    db: DatabaseConnection,
+}
+
+impl ExecutionRecordService {
+    /// 创建执行记录服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 记录执行开始
+    #[allow(clippy::too_many_arguments)]
+    pub async fn start(
+        &self,
+        tenant_id: Uuid,
+        execution_type: ExecutionType,
+        execution_id: Uuid,
+        target_id: Uuid,
+        input: Value,
+        triggered_by: Option<Uuid>,
+        replay_of: Option<Uuid>,
+    ) -> Result<(), AiStudioError> {
+        execution_record::ActiveModel {
+            id: Set(execution_id),
+            tenant_id: Set(tenant_id),
+            execution_type: Set(execution_type),
+            target_id: Set(target_id),
+            input: Set(input),
+            output: Set(None),
+            status: Set("running".to_string()),
+            error_message: Set(None),
+            duration_ms: Set(None),
+            total_tokens: Set(None),
+            replay_of: Set(replay_of),
+            triggered_by: Set(triggered_by),
+            started_at: Set(Utc::now().fixed_offset()),
+            completed_at: Set(None),
+            canary_release_id: Set(None),
+            canary_arm: Set(None),
+            feedback: Set(None),
+        }
+        .insert(&self.db)
+        .await?;
+
+        debug!("已记录执行开始: execution_id={}, replay_of={:?}", execution_id, replay_of);
+        Ok(())
+    }
+
+    /// 记录执行结束，令牌用量从输出中报告的用量读取
+    pub async fn finish(
+        &self,
+        execution_id: Uuid,
+        status: &str,
+        output: Option<Value>,
+        error_message: Option<String>,
+        duration_ms: i64,
+    ) -> Result<(), AiStudioError> {
+        let Some(record) = ExecutionRecord::find_by_id(execution_id).one(&self.db).await? else {
+            return Ok(());
+        };
+
+        let mut active: execution_record::ActiveModel = record.into();
+        active.total_tokens = Set(output.as_ref().and_then(total_tokens_of));
+        active.status = Set(status.to_string());
+        active.output = Set(output);
+        active.error_message = Set(error_message);
+        active.duration_ms = Set(Some(duration_ms));
+        active.completed_at = Set(Some(Utc::now().fixed_offset()));
+        active.update(&self.db).await?;
+        Ok(())
+    }
+
+    /// 同步异步执行的最新状态，执行已结束时按开始与完成时间计算耗时
+    pub async fn sync_status(
+        &self,
+        execution_id: Uuid,
+        status: &str,
+        completed_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<ExecutionRecordSummary>, AiStudioError> {
+        let Some(record) = ExecutionRecord::find_by_id(execution_id).one(&self.db).await? else {
+            return Ok(None);
+        };
+        if record.status == status {
+            return Ok(Some(record.into()));
+        }
+
+        let started_at = record.started_at.with_timezone(&Utc);
+        let mut active: execution_record::ActiveModel = record.into();
+        active.status = Set(status.to_string());
+        if let Some(completed_at) = completed_at {
+            active.completed_at = Set(Some(completed_at.fixed_offset()));
+            active.duration_ms = Set(Some((completed_at - started_at).num_milliseconds().max(0)));
+        }
+        Ok(Some(active.update(&self.db).await?.into()))
+    }
+
+    /// 标记执行所属的灰度发布与分流到的版本
+    pub async fn tag_canary(&self, execution_id: Uuid, release_id: Uuid, arm: &str) -> Result<(), AiStudioError> {
+        execution_record::ActiveModel {
+            id: Set(execution_id),
+            canary_release_id: Set(Some(release_id)),
+            canary_arm: Set(Some(arm.to_string())),
+            ..Default::default()
+        }
+        .update(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// 记录用户对执行结果的反馈，重复提交时覆盖；指定 `target_id` 时执行须属于该对象
+    #[instrument(skip(self))]
+    pub async fn record_feedback(
+        &self,
+        tenant_id: Uuid,
+        execution_type: ExecutionType,
+        execution_id: Uuid,
+        target_id: Option<Uuid>,
+        positive: bool,
+    ) -> Result<execution_record::Model, AiStudioError> {
+        let record = self.find(tenant_id, execution_type, execution_id).await?;
+        if target_id.is_some_and(|target_id| target_id != record.target_id) {
+            return Err(AiStudioError::not_found("执行记录"));
+        }
+        let mut active: execution_record::ActiveModel = record.into();
+        active.feedback = Set(Some(if positive { 1 } else { -1 }));
+        Ok(active.update(&self.db).await?)
+    }
+
+    /// 读取原始执行并合并输入修改，返回原始记录与重放使用的输入
+    #[instrument(skip(self, input_patch))]
+    pub async fn replay_input(
+        &self,
+        tenant_id: Uuid,
+        execution_type: ExecutionType,
+        execution_id: Uuid,
+        input_patch: Option<&Value>,
+    ) -> Result<(execution_record::Model, Value), AiStudioError> {
+        let original = self.find(tenant_id, execution_type, execution_id).await?;
+        let mut input = original.input.clone();
+        if let Some(patch) = input_patch {
+            merge_patch(&mut input, patch);
+        }
+        Ok((original, input))
+    }
+
+    /// 执行的重放历史
+    pub async fn history(
+        &self,
+        tenant_id: Uuid,
+        execution_type: ExecutionType,
+        execution_id: Uuid,
+    ) -> Result<ReplayHistory, AiStudioError> {
+        let original = ExecutionRecordSummary::from(self.find(tenant_id, execution_type, execution_id).await?);
+        let replays = ExecutionRecord::find()
+            .filter(execution_record::Column::TenantId.eq(tenant_id))
+            .filter(execution_record::Column::ReplayOf.eq(execution_id))
+            .order_by_asc(execution_record::Column::StartedAt)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|replay| ReplayComparison::between(&original, replay.into()))
+            .collect();
+
+        Ok(ReplayHistory { original, replays })
+    }
+
+    async fn find(
+        &self,
+        tenant_id: Uuid,
+        execution_type: ExecutionType,
+        execution_id: Uuid,
+    ) -> Result<execution_record::Model, AiStudioError> {
+        ExecutionRecord::find_by_id(execution_id)
+            .filter(execution_record::Column::TenantId.eq(tenant_id))
+            .filter(execution_record::Column::ExecutionType.eq(execution_type))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("执行记录"))
+    }
+}
+
+/// 按 JSON Merge Patch（RFC 7386）将修改合并到目标
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// 从执行输出报告的用量中读取令牌总数
+///
+/// 支持 `usage` 或 `token_usage` 字段，未提供 `total_tokens` 时取输入与输出令牌之和。
+fn total_tokens_of(output: &Value) -> Option<i64> {
+    let usage = output.get("token_usage").or_else(|| output.get("usage"))?;
+    usage.get("total_tokens").and_then(|v| v.as_i64()).or_else(|| {
+        let prompt = usage.get("prompt_tokens").or_else(|| usage.get("input_tokens"))?.as_i64()?;
+        let completion = usage.get("completion_tokens").or_else(|| usage.get("output_tokens"))?.as_i64()?;
+        Some(prompt + completion)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_patch() {
+        let mut input = json!({
+            "objective": "总结工单",
+            "parameters": { "ticket_id": 42, "language": "zh", "verbose": true }
+        });
+        merge_patch(&mut input, &json!({ "parameters": { "ticket_id": 43, "verbose": null } }));
+        assert_eq!(input, json!({
+            "objective": "总结工单",
+            "parameters": { "ticket_id": 43, "language": "zh" }
+        }));
+    }
+
+    #[test]
+    fn test_replay_comparison() {
+        let summary = |input: Value, output: Value, status: &str, duration_ms: i64| ExecutionRecordSummary {
+            execution_id: Uuid::new_v4(),
+            execution_type: ExecutionType::Agent,
+            target_id: Uuid::nil(),
+            status: status.to_string(),
+            total_tokens: total_tokens_of(&output),
+            input,
+            output: Some(output),
+            error_message: None,
+            duration_ms: Some(duration_ms),
+            replay_of: None,
+            started_at: Utc::now(),
+            completed_at: None,
+        };
+
+        let original = summary(json!({ "q": 1 }), json!({ "answer": "a", "usage": { "total_tokens": 120 } }), "completed", 900);
+        let replay = summary(
+            json!({ "q": 2 }),
+            json!({ "answer": "b", "usage": { "prompt_tokens": 60, "completion_tokens": 40 } }),
+            "completed",
+            700,
+        );
+        let comparison = ReplayComparison::between(&original, replay);
+        assert!(comparison.input_changed);
+        assert_eq!(comparison.output_changed, Some(true));
+        assert_eq!(comparison.duration_delta_ms, Some(-200));
+        assert_eq!(comparison.token_delta, Some(-20));
+
+        let running = summary(json!({ "q": 1 }), json!({}), "running", 0);
+        assert_eq!(ReplayComparison::between(&original, running).output_changed, None);
+    }
+}
@@ -0,0 +1,683 @@
+// 知识库 FAQ/术语表服务
+// 管理人工维护的常见问题与术语释义，问答时先按向量与模糊匹配查找标准答案，命中时无需调用大模型
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, Statement, sea_query::{Expr, Query},
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::embedding_pool::{EmbeddingPool, EmbeddingPriority};
+use crate::ai::rig_client::RigAiClient;
+use crate::db::entities::document::ClearanceLevel;
+use crate::db::entities::kb_faq_entry::{self, FaqEntryType};
+use crate::db::entities::knowledge_base::{self, KnowledgeBaseStatus};
+use crate::db::entities::KbFaqEntry;
+use crate::errors::AiStudioError;
+use crate::services::question_suggestion::normalize_question;
+
+/// 问题或术语的最大长度（字符）
+const MAX_QUESTION_LENGTH: usize = 1000;
+
+/// 答案的最大长度（字符）
+const MAX_ANSWER_LENGTH: usize = 8000;
+
+/// 单个条目最多的别名数
+const MAX_ALIASES: usize = 20;
+
+/// 参与匹配的最大条目数
+const MAX_MATCH_CANDIDATES: u64 = 2000;
+
+/// 向量相似度候选数
+const SEMANTIC_CANDIDATES: u64 = 5;
+
+/// 仅凭文本相似度命中的阈值
+pub const FUZZY_MATCH_THRESHOLD: f32 = 0.9;
+
+/// 仅凭向量相似度命中的阈值
+pub const SEMANTIC_MATCH_THRESHOLD: f32 = 0.92;
+
+/// 文本与向量同时较高时的命中阈值（向量相似度, 文本相似度）
+const COMBINED_MATCH_THRESHOLD: (f32, f32) = (0.85, 0.6);
+
+/// 术语提问的前缀
+const GLOSSARY_PREFIXES: [&str; 6] = ["什么是", "何为", "请解释", "what is ", "what are ", "define "];
+
+/// 术语提问的后缀，较长的在前
+const GLOSSARY_SUFFIXES: [&str; 8] = ["是什么意思", "什么意思", "是什么", "是指什么", "指什么", "的含义", "的定义", "的意思"];
+
+/// FAQ 命中方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FaqMatchMethod {
+    /// 与问题、别名或术语完全一致
+    Exact,
+    /// 文本相似
+    Fuzzy,
+    /// 语义相似
+    Semantic,
+}
+
+/// FAQ 命中信息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FaqMatch {
+    /// 条目 ID
+    pub entry_id: Uuid,
+    /// 条目类型
+    pub entry_type: FaqEntryType,
+    /// 条目的标准问题或术语
+    pub question: String,
+    /// 匹配得分（0-1）
+    pub score: f32,
+    /// 命中方式
+    pub method: FaqMatchMethod,
+}
+
+/// 创建 FAQ 条目请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateFaqEntryRequest {
+    /// 条目类型，默认 FAQ
+    #[serde(default = "default_entry_type")]
+    pub entry_type: FaqEntryType,
+    /// 问题（术语表条目为术语）
+    pub question: String,
+    /// 标准答案（术语表条目为释义）
+    pub answer: String,
+    /// 同义问法或术语别名
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// 访问密级
+    #[serde(default)]
+    pub clearance: ClearanceLevel,
+}
+
+/// 更新 FAQ 条目请求
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateFaqEntryRequest {
+    /// 问题（术语表条目为术语）
+    pub question: Option<String>,
+    /// 标准答案
+    pub answer: Option<String>,
+    /// 同义问法或术语别名
+    pub aliases: Option<Vec<String>>,
+    /// 访问密级
+    pub clearance: Option<ClearanceLevel>,
+    /// 是否启用
+    pub is_active: Option<bool>,
+}
+
+/// FAQ 条目响应
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FaqEntryResponse {
+    /// 条目 ID
+    pub id: Uuid,
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 条目类型
+    pub entry_type: FaqEntryType,
+    /// 问题或术语
+    pub question: String,
+    /// 标准答案或释义
+    pub answer: String,
+    /// 同义问法或术语别名
+    pub aliases: Vec<String>,
+    /// 访问密级
+    pub clearance: ClearanceLevel,
+    /// 是否启用
+    pub is_active: bool,
+    /// 命中次数
+    pub hit_count: i32,
+    /// 最近命中时间
+    pub last_hit_at: Option<DateTime<Utc>>,
+    /// 创建人
+    pub created_by: Option<Uuid>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<kb_faq_entry::Model> for FaqEntryResponse {
+    fn from(model: kb_faq_entry::Model) -> Self {
+        Self {
+            aliases: model.alias_list(),
+            id: model.id,
+            knowledge_base_id: model.knowledge_base_id,
+            entry_type: model.entry_type,
+            question: model.question,
+            answer: model.answer,
+            clearance: model.clearance,
+            is_active: model.is_active,
+            hit_count: model.hit_count,
+            last_hit_at: model.last_hit_at.map(|t| t.with_timezone(&Utc)),
+            created_by: model.created_by,
+            created_at: model.created_at.with_timezone(&Utc),
+            updated_at: model.updated_at.with_timezone(&Utc),
+        }
+    }
+}
+
+fn default_entry_type() -> FaqEntryType {
+    FaqEntryType::Faq
+}
+
+/// FAQ/术语表服务
+pub struct FaqService {
+    db: DatabaseConnection,
+    embedder: Option<Arc<RigAiClient>>,
+}
+
+impl FaqService {
+    /// 创建新的 FAQ 服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db, embedder: None }
+    }
+
+    /// 设置未启用本地嵌入工作池时使用的嵌入客户端
+    pub fn with_embedder(mut self, embedder: Arc<RigAiClient>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// 创建条目，条目密级不能高于操作者密级
+    #[instrument(skip(self, request))]
+    pub async fn create_entry(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        operator_clearance: ClearanceLevel,
+        request: CreateFaqEntryRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<FaqEntryResponse, AiStudioError> {
+        if request.clearance > operator_clearance {
+            return Err(AiStudioError::forbidden("无权设置高于自身密级的内容"));
+        }
+        let question = validate_text("question", &request.question, MAX_QUESTION_LENGTH)?;
+        let answer = validate_text("answer", &request.answer, MAX_ANSWER_LENGTH)?;
+        let aliases = normalize_aliases(&question, request.aliases)?;
+        let now = Utc::now().fixed_offset();
+
+        let entry = kb_faq_entry::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            knowledge_base_id: Set(knowledge_base_id),
+            entry_type: Set(request.entry_type),
+            question: Set(question),
+            answer: Set(answer),
+            aliases: Set(serde_json::to_value(&aliases)?),
+            clearance: Set(request.clearance),
+            is_active: Set(true),
+            hit_count: Set(0),
+            last_hit_at: Set(None),
+            created_by: Set(created_by),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(&self.db)
+        .await?;
+
+        self.refresh_embedding(&entry).await;
+        info!("FAQ 条目创建成功: id={}, kb={}", entry.id, knowledge_base_id);
+        Ok(entry.into())
+    }
+
+    /// 列出知识库中操作者可见的条目
+    pub async fn list_entries(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        clearance: ClearanceLevel,
+    ) -> Result<Vec<FaqEntryResponse>, AiStudioError> {
+        let entries = KbFaqEntry::find()
+            .filter(kb_faq_entry::Column::TenantId.eq(tenant_id))
+            .filter(kb_faq_entry::Column::KnowledgeBaseId.eq(knowledge_base_id))
+            .filter(kb_faq_entry::Column::Clearance.lte(clearance))
+            .order_by_desc(kb_faq_entry::Column::HitCount)
+            .order_by_desc(kb_faq_entry::Column::UpdatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(entries.into_iter().map(FaqEntryResponse::from).collect())
+    }
+
+    /// 获取条目
+    pub async fn get_entry(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        id: Uuid,
+        clearance: ClearanceLevel,
+    ) -> Result<FaqEntryResponse, AiStudioError> {
+        Ok(self.find_entry(tenant_id, knowledge_base_id, id, clearance).await?.into())
+    }
+
+    /// 更新条目，问题变化时重新生成向量
+    #[instrument(skip(self, request))]
+    pub async fn update_entry(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        id: Uuid,
+        operator_clearance: ClearanceLevel,
+        request: UpdateFaqEntryRequest,
+    ) -> Result<FaqEntryResponse, AiStudioError> {
+        let existing = self.find_entry(tenant_id, knowledge_base_id, id, operator_clearance).await?;
+        if request.clearance.is_some_and(|clearance| clearance > operator_clearance) {
+            return Err(AiStudioError::forbidden("无权设置高于自身密级的内容"));
+        }
+
+        let question_changed = request.question.as_ref().is_some_and(|q| q.trim() != existing.question);
+        let question = match &request.question {
+            Some(question) => validate_text("question", question, MAX_QUESTION_LENGTH)?,
+            None => existing.question.clone(),
+        };
+        let aliases = match request.aliases {
+            Some(aliases) => Some(normalize_aliases(&question, aliases)?),
+            None => None,
+        };
+        let mut active: kb_faq_entry::ActiveModel = existing.into();
+
+        if question_changed {
+            active.question = Set(question);
+        }
+        if let Some(answer) = request.answer {
+            active.answer = Set(validate_text("answer", &answer, MAX_ANSWER_LENGTH)?);
+        }
+        if let Some(aliases) = aliases {
+            active.aliases = Set(serde_json::to_value(aliases)?);
+        }
+        if let Some(clearance) = request.clearance {
+            active.clearance = Set(clearance);
+        }
+        if let Some(is_active) = request.is_active {
+            active.is_active = Set(is_active);
+        }
+        active.updated_at = Set(Utc::now().fixed_offset());
+
+        let entry = active.update(&self.db).await?;
+        if question_changed {
+            self.refresh_embedding(&entry).await;
+        }
+        Ok(entry.into())
+    }
+
+    /// 删除条目
+    pub async fn delete_entry(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        id: Uuid,
+        operator_clearance: ClearanceLevel,
+    ) -> Result<(), AiStudioError> {
+        let result = KbFaqEntry::delete_many()
+            .filter(kb_faq_entry::Column::TenantId.eq(tenant_id))
+            .filter(kb_faq_entry::Column::KnowledgeBaseId.eq(knowledge_base_id))
+            .filter(kb_faq_entry::Column::Clearance.lte(operator_clearance))
+            .filter(kb_faq_entry::Column::Id.eq(id))
+            .exec(&self.db)
+            .await?;
+
+        if result.rows_affected == 0 {
+            return Err(AiStudioError::not_found("FAQ 条目"));
+        }
+        Ok(())
+    }
+
+    /// 为问题查找标准答案
+    ///
+    /// 结合文本相似度与问题向量相似度挑选得分最高的启用条目，未达到命中阈值时返回 `None`，
+    /// 由调用方回退到完整的检索增强生成流程。已归档知识库中的条目不参与匹配。命中时累加条目的命中次数。
+    #[instrument(skip(self, question, question_embedding))]
+    pub async fn match_question(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Option<Uuid>,
+        clearance: ClearanceLevel,
+        question: &str,
+        question_embedding: Option<&[f32]>,
+    ) -> Result<Option<(kb_faq_entry::Model, FaqMatch)>, AiStudioError> {
+        let normalized = normalize_question(question);
+        if normalized.is_empty() {
+            return Ok(None);
+        }
+
+        let mut select = KbFaqEntry::find()
+            .filter(kb_faq_entry::Column::TenantId.eq(tenant_id))
+            .filter(kb_faq_entry::Column::IsActive.eq(true))
+            .filter(kb_faq_entry::Column::Clearance.lte(clearance))
+            .filter(kb_faq_entry::Column::KnowledgeBaseId.not_in_subquery(
+                Query::select()
+                    .column(knowledge_base::Column::Id)
+                    .from(knowledge_base::Entity)
+                    .and_where(knowledge_base::Column::Status.eq(KnowledgeBaseStatus::Archived))
+                    .to_owned(),
+            ));
+        if let Some(kb_id) = knowledge_base_id {
+            select = select.filter(kb_faq_entry::Column::KnowledgeBaseId.eq(kb_id));
+        }
+        let entries = select.limit(MAX_MATCH_CANDIDATES).all(&self.db).await?;
+        if entries.is_empty() {
+            return Ok(None);
+        }
+
+        let semantic = match question_embedding {
+            Some(embedding) => self.semantic_scores(tenant_id, knowledge_base_id, clearance, embedding).await?,
+            None => HashMap::new(),
+        };
+
+        let best = entries.into_iter()
+            .filter_map(|entry| {
+                let (method, score) = decide_match(fuzzy_score(&normalized, &entry), semantic.get(&entry.id).copied())?;
+                Some((entry, method, score))
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2));
+
+        let Some((entry, method, score)) = best else {
+            debug!("未命中 FAQ 条目: tenant={}, kb={:?}", tenant_id, knowledge_base_id);
+            return Ok(None);
+        };
+
+        let result = KbFaqEntry::update_many()
+            .col_expr(kb_faq_entry::Column::HitCount, Expr::col(kb_faq_entry::Column::HitCount).add(1))
+            .col_expr(kb_faq_entry::Column::LastHitAt, Expr::value(Utc::now().fixed_offset()))
+            .filter(kb_faq_entry::Column::Id.eq(entry.id))
+            .exec(&self.db)
+            .await;
+        if let Err(e) = result {
+            warn!("更新 FAQ 命中次数失败: id={}, error={}", entry.id, e);
+        }
+
+        info!("命中 FAQ 条目: id={}, method={:?}, score={:.3}", entry.id, method, score);
+        let faq_match = FaqMatch {
+            entry_id: entry.id,
+            entry_type: entry.entry_type,
+            question: entry.question.clone(),
+            score,
+            method,
+        };
+        Ok(Some((entry, faq_match)))
+    }
+
+    /// 与问题向量最相近的若干条目的相似度
+    async fn semantic_scores(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Option<Uuid>,
+        clearance: ClearanceLevel,
+        embedding: &[f32],
+    ) -> Result<HashMap<Uuid, f32>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT id, (1 - (embedding <=> $1::vector))::REAL AS similarity
+                FROM kb_faq_entries
+                WHERE tenant_id = $2
+                    AND ($3::uuid IS NULL OR knowledge_base_id = $3)
+                    AND is_active
+                    AND clearance <= $4
+                    AND embedding IS NOT NULL
+                ORDER BY embedding <=> $1::vector
+                LIMIT $5
+                "#,
+                vec![
+                    format_vector(embedding).into(),
+                    tenant_id.into(),
+                    knowledge_base_id.into(),
+                    clearance.into(),
+                    (SEMANTIC_CANDIDATES as i64).into(),
+                ],
+            ))
+            .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<(Uuid, f32), AiStudioError> {
+                Ok((row.try_get("", "id")?, row.try_get("", "similarity")?))
+            })
+            .collect()
+    }
+
+    async fn find_entry(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        id: Uuid,
+        clearance: ClearanceLevel,
+    ) -> Result<kb_faq_entry::Model, AiStudioError> {
+        KbFaqEntry::find_by_id(id)
+            .filter(kb_faq_entry::Column::TenantId.eq(tenant_id))
+            .filter(kb_faq_entry::Column::KnowledgeBaseId.eq(knowledge_base_id))
+            .filter(kb_faq_entry::Column::Clearance.lte(clearance))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("FAQ 条目"))
+    }
+
+    /// 重新生成条目问题的向量，失败时清空向量，条目仍可通过文本匹配命中
+    async fn refresh_embedding(&self, entry: &kb_faq_entry::Model) {
+        let vector = self.embed(&entry.question).await.map(|v| format_vector(&v));
+
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE kb_faq_entries SET embedding = $1::vector WHERE id = $2",
+                vec![vector.into(), entry.id.into()],
+            ))
+            .await;
+        if let Err(e) = result {
+            warn!("保存 FAQ 条目向量失败: id={}, error={}", entry.id, e);
+        }
+    }
+
+    /// 列出知识库中启用且操作者可见的术语表条目，供检索前展开缩写
+    pub async fn glossary_entries(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        clearance: ClearanceLevel,
+    ) -> Result<Vec<kb_faq_entry::Model>, AiStudioError> {
+        Ok(KbFaqEntry::find()
+            .filter(kb_faq_entry::Column::TenantId.eq(tenant_id))
+            .filter(kb_faq_entry::Column::KnowledgeBaseId.eq(knowledge_base_id))
+            .filter(kb_faq_entry::Column::EntryType.eq(FaqEntryType::Glossary))
+            .filter(kb_faq_entry::Column::IsActive.eq(true))
+            .filter(kb_faq_entry::Column::Clearance.lte(clearance))
+            .limit(MAX_MATCH_CANDIDATES)
+            .all(&self.db)
+            .await?)
+    }
+
+    async fn embed(&self, text: &str) -> Option<Vec<f32>> {
+        let result = match (EmbeddingPool::global(), &self.embedder) {
+            (Some(pool), _) => pool.embed(text, EmbeddingPriority::Bulk).await,
+            (None, Some(embedder)) => embedder.generate_embedding(text).await.map(|r| r.embedding),
+            (None, None) => return None,
+        };
+
+        result.map_err(|e| warn!("生成 FAQ 条目向量失败: {}", e)).ok()
+    }
+}
+
+/// 从「什么是 X」「X 是什么意思」等术语提问中提取术语
+pub fn extract_glossary_term(normalized_question: &str) -> Option<String> {
+    let mut term = normalized_question.trim();
+    if let Some(rest) = GLOSSARY_PREFIXES.iter().find_map(|prefix| term.strip_prefix(prefix)) {
+        term = rest;
+    } else if let Some(rest) = GLOSSARY_SUFFIXES.iter().find_map(|suffix| term.strip_suffix(suffix)) {
+        term = rest;
+    } else {
+        return None;
+    }
+
+    let term = term.trim_matches(|c: char| c.is_whitespace() || "\"'“”「」《》".contains(c));
+    (!term.is_empty()).then(|| term.to_string())
+}
+
+/// 问题与条目的文本相似度
+///
+/// FAQ 条目取与问题及各别名的最高字符二元组相似度；术语表条目要求提问中的术语与术语或别名一致。
+pub fn fuzzy_score(normalized_question: &str, entry: &kb_faq_entry::Model) -> f32 {
+    let candidates = std::iter::once(entry.question.clone())
+        .chain(entry.alias_list())
+        .map(|text| normalize_question(&text));
+
+    match entry.entry_type {
+        FaqEntryType::Faq => candidates
+            .map(|candidate| bigram_similarity(normalized_question, &candidate))
+            .fold(0.0, f32::max),
+        FaqEntryType::Glossary => {
+            let term = extract_glossary_term(normalized_question).unwrap_or_else(|| normalized_question.to_string());
+            candidates
+                .map(|candidate| if candidate == term { 1.0 } else { 0.0 })
+                .fold(0.0, f32::max)
+        }
+    }
+}
+
+/// 根据文本相似度与向量相似度判断是否命中，返回命中方式与得分
+pub fn decide_match(fuzzy: f32, semantic: Option<f32>) -> Option<(FaqMatchMethod, f32)> {
+    let semantic = semantic.unwrap_or(0.0);
+    if fuzzy >= 1.0 {
+        Some((FaqMatchMethod::Exact, 1.0))
+    } else if fuzzy >= FUZZY_MATCH_THRESHOLD && fuzzy >= semantic {
+        Some((FaqMatchMethod::Fuzzy, fuzzy))
+    } else if semantic >= SEMANTIC_MATCH_THRESHOLD
+        || (semantic >= COMBINED_MATCH_THRESHOLD.0 && fuzzy >= COMBINED_MATCH_THRESHOLD.1)
+    {
+        Some((FaqMatchMethod::Semantic, semantic.max(fuzzy)))
+    } else if fuzzy >= FUZZY_MATCH_THRESHOLD {
+        Some((FaqMatchMethod::Fuzzy, fuzzy))
+    } else {
+        None
+    }
+}
+
+/// 字符二元组 Dice 相似度，忽略空白，适用于中英文混合文本
+pub fn bigram_similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().filter(|c| !c.is_whitespace()).collect();
+    let b: Vec<char> = b.chars().filter(|c| !c.is_whitespace()).collect();
+    if a == b {
+        return if a.is_empty() { 0.0 } else { 1.0 };
+    }
+    if a.len() < 2 || b.len() < 2 {
+        return 0.0;
+    }
+
+    let mut counts: HashMap<(char, char), i32> = HashMap::new();
+    for pair in a.windows(2) {
+        *counts.entry((pair[0], pair[1])).or_default() += 1;
+    }
+    let mut shared = 0;
+    for pair in b.windows(2) {
+        if let Some(count) = counts.get_mut(&(pair[0], pair[1])) {
+            if *count > 0 {
+                *count -= 1;
+                shared += 1;
+            }
+        }
+    }
+
+    2.0 * shared as f32 / (a.len() + b.len() - 2) as f32
+}
+
+fn validate_text(field: &str, text: &str, max_length: usize) -> Result<String, AiStudioError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(AiStudioError::validation(field, "不能为空"));
+    }
+    if text.chars().count() > max_length {
+        return Err(AiStudioError::validation(field, format!("不能超过 {} 个字符", max_length)));
+    }
+    Ok(text.to_string())
+}
+
+/// 去除空白、去重（忽略与问题相同的别名）并限制别名数量
+fn normalize_aliases(question: &str, aliases: Vec<String>) -> Result<Vec<String>, AiStudioError> {
+    let mut seen = vec![normalize_question(question)];
+    let mut normalized = Vec::new();
+    for alias in aliases {
+        let alias = validate_text("aliases", &alias, MAX_QUESTION_LENGTH)?;
+        let key = normalize_question(&alias);
+        if !seen.contains(&key) {
+            seen.push(key);
+            normalized.push(alias);
+        }
+    }
+    if normalized.len() > MAX_ALIASES {
+        return Err(AiStudioError::validation("aliases", format!("别名不能超过 {} 个", MAX_ALIASES)));
+    }
+    Ok(normalized)
+}
+
+fn format_vector(vector: &[f32]) -> String {
+    format!("[{}]", vector.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(entry_type: FaqEntryType, question: &str, aliases: &[&str]) -> kb_faq_entry::Model {
+        let now = Utc::now().fixed_offset();
+        kb_faq_entry::Model {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            knowledge_base_id: Uuid::new_v4(),
+            entry_type,
+            question: question.to_string(),
+            answer: "答案".to_string(),
+            aliases: serde_json::json!(aliases),
+            clearance: ClearanceLevel::Public,
+            is_active: true,
+            hit_count: 0,
+            last_hit_at: None,
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_extract_glossary_term() {
+        assert_eq!(extract_glossary_term("什么是 rag").as_deref(), Some("rag"));
+        assert_eq!(extract_glossary_term("向量数据库是什么意思").as_deref(), Some("向量数据库"));
+        assert_eq!(extract_glossary_term("what is \"embedding\"").as_deref(), Some("embedding"));
+        assert_eq!(extract_glossary_term("如何申请报销"), None);
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        let faq = entry(FaqEntryType::Faq, "如何申请报销？", &["报销流程是怎样的"]);
+        assert_eq!(fuzzy_score(&normalize_question("如何申请报销"), &faq), 1.0);
+        assert_eq!(fuzzy_score(&normalize_question("报销流程是怎样的？"), &faq), 1.0);
+        assert!(fuzzy_score("年假有几天", &faq) < 0.2);
+
+        let glossary = entry(FaqEntryType::Glossary, "RAG", &["检索增强生成"]);
+        assert_eq!(fuzzy_score(&normalize_question("什么是RAG？"), &glossary), 1.0);
+        assert_eq!(fuzzy_score(&normalize_question("检索增强生成是什么"), &glossary), 1.0);
+        assert_eq!(fuzzy_score(&normalize_question("RAG 的部署步骤"), &glossary), 0.0);
+    }
+
+    #[test]
+    fn test_decide_match() {
+        assert_eq!(decide_match(1.0, None), Some((FaqMatchMethod::Exact, 1.0)));
+        assert_eq!(decide_match(0.95, Some(0.5)), Some((FaqMatchMethod::Fuzzy, 0.95)));
+        assert_eq!(decide_match(0.3, Some(0.96)), Some((FaqMatchMethod::Semantic, 0.96)));
+        assert_eq!(decide_match(0.7, Some(0.88)), Some((FaqMatchMethod::Semantic, 0.88)));
+        assert_eq!(decide_match(0.3, Some(0.88)), None);
+        assert_eq!(decide_match(0.5, None), None);
+    }
+
+    #[test]
+    fn test_bigram_similarity() {
+        assert_eq!(bigram_similarity("如何申请报销", "如何申请报销"), 1.0);
+        assert!(bigram_similarity("如何申请报销", "怎样申请报销") > 0.5);
+        assert_eq!(bigram_similarity("", ""), 0.0);
+    }
+}
@@ -0,0 +1,433 @@
+// 少样本示例服务
+// 管理挂载到 Agent 或提示词模板上的输入/输出示例，并按与当前任务的向量相似度挑选示例写入提示词
+
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseBackend, DatabaseConnection,
+    EntityTrait, QueryFilter, QueryOrder, Set, Statement, Value,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::embedding_pool::{EmbeddingPool, EmbeddingPriority};
+use crate::ai::rig_client::RigAiClient;
+use crate::db::entities::{few_shot_example, FewShotExample};
+use crate::errors::AiStudioError;
+
+/// 单个示例输入/输出的最大长度（字符）
+const MAX_EXAMPLE_LENGTH: usize = 8000;
+
+/// 单个示例最多的标签数
+const MAX_TAGS: usize = 20;
+
+/// 单次最多挑选的示例数
+pub const MAX_SELECTED_EXAMPLES: usize = 10;
+
+/// 示例挂载目标，Agent 与提示词模板至少指定一个
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct FewShotTarget {
+    /// Agent ID
+    pub agent_id: Option<Uuid>,
+    /// 提示词模板名称
+    pub prompt_template: Option<String>,
+}
+
+impl FewShotTarget {
+    fn validate(&self) -> Result<(), AiStudioError> {
+        if self.agent_id.is_none() && self.prompt_template.as_deref().is_none_or(|t| t.trim().is_empty()) {
+            return Err(AiStudioError::validation("agent_id", "必须指定 agent_id 或 prompt_template"));
+        }
+        if self.prompt_template.as_deref().is_some_and(|t| t.chars().count() > 100) {
+            return Err(AiStudioError::validation("prompt_template", "提示词模板名称不能超过 100 个字符"));
+        }
+        Ok(())
+    }
+
+    /// 按目标过滤：匹配 Agent 或提示词模板任意一个
+    fn condition(&self) -> Condition {
+        let mut condition = Condition::any();
+        if let Some(agent_id) = self.agent_id {
+            condition = condition.add(few_shot_example::Column::AgentId.eq(agent_id));
+        }
+        if let Some(template) = &self.prompt_template {
+            condition = condition.add(few_shot_example::Column::PromptTemplate.eq(template.clone()));
+        }
+        condition
+    }
+}
+
+/// 创建少样本示例请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateFewShotExampleRequest {
+    /// 挂载目标
+    #[serde(flatten)]
+    pub target: FewShotTarget,
+    /// 示例输入
+    pub input: String,
+    /// 示例输出
+    pub output: String,
+    /// 标签
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// 更新少样本示例请求
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateFewShotExampleRequest {
+    /// 示例输入
+    pub input: Option<String>,
+    /// 示例输出
+    pub output: Option<String>,
+    /// 标签
+    pub tags: Option<Vec<String>>,
+}
+
+/// 少样本示例响应
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FewShotExampleResponse {
+    /// 示例 ID
+    pub id: Uuid,
+    /// Agent ID
+    pub agent_id: Option<Uuid>,
+    /// 提示词模板名称
+    pub prompt_template: Option<String>,
+    /// 示例输入
+    pub input: String,
+    /// 示例输出
+    pub output: String,
+    /// 标签
+    pub tags: Vec<String>,
+    /// 与当前任务的相似度（仅挑选结果中返回）
+    pub similarity: Option<f32>,
+    /// 创建人
+    pub created_by: Option<Uuid>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<few_shot_example::Model> for FewShotExampleResponse {
+    fn from(model: few_shot_example::Model) -> Self {
+        Self {
+            tags: model.tag_list(),
+            id: model.id,
+            agent_id: model.agent_id,
+            prompt_template: model.prompt_template,
+            input: model.input,
+            output: model.output,
+            similarity: None,
+            created_by: model.created_by,
+            created_at: model.created_at.with_timezone(&Utc),
+            updated_at: model.updated_at.with_timezone(&Utc),
+        }
+    }
+}
+
+/// 少样本示例服务
+pub struct FewShotExampleService {
+    db: DatabaseConnection,
+    embedder: Option<Arc<RigAiClient>>,
+}
+
+impl FewShotExampleService {
+    /// 创建新的少样本示例服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db, embedder: None }
+    }
+
+    /// 设置未启用本地嵌入工作池时使用的嵌入客户端
+    pub fn with_embedder(mut self, embedder: Arc<RigAiClient>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// 创建示例
+    #[instrument(skip(self, request))]
+    pub async fn create_example(
+        &self,
+        tenant_id: Uuid,
+        request: CreateFewShotExampleRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<FewShotExampleResponse, AiStudioError> {
+        request.target.validate()?;
+        let input = validate_text("input", &request.input)?;
+        let output = validate_text("output", &request.output)?;
+        let tags = normalize_tags(request.tags)?;
+        let now = Utc::now().fixed_offset();
+
+        let example = few_shot_example::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            agent_id: Set(request.target.agent_id),
+            prompt_template: Set(request.target.prompt_template.map(|t| t.trim().to_string())),
+            input: Set(input),
+            output: Set(output),
+            tags: Set(serde_json::to_value(&tags)?),
+            created_by: Set(created_by),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(&self.db)
+        .await?;
+
+        self.refresh_embedding(&example).await;
+        info!("少样本示例创建成功: id={}", example.id);
+        Ok(example.into())
+    }
+
+    /// 列出目标下的示例，可按标签过滤（需包含全部标签）
+    pub async fn list_examples(
+        &self,
+        tenant_id: Uuid,
+        target: &FewShotTarget,
+        tags: &[String],
+    ) -> Result<Vec<FewShotExampleResponse>, AiStudioError> {
+        let mut select = FewShotExample::find()
+            .filter(few_shot_example::Column::TenantId.eq(tenant_id));
+        if target.agent_id.is_some() || target.prompt_template.is_some() {
+            select = select.filter(target.condition());
+        }
+
+        let examples = select
+            .order_by_desc(few_shot_example::Column::UpdatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(examples.into_iter()
+            .filter(|example| {
+                let example_tags = example.tag_list();
+                tags.iter().all(|tag| example_tags.contains(tag))
+            })
+            .map(FewShotExampleResponse::from)
+            .collect())
+    }
+
+    /// 获取示例
+    pub async fn get_example(&self, tenant_id: Uuid, id: Uuid) -> Result<FewShotExampleResponse, AiStudioError> {
+        Ok(self.find_example(tenant_id, id).await?.into())
+    }
+
+    /// 更新示例，输入变化时重新生成向量
+    #[instrument(skip(self, request))]
+    pub async fn update_example(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+        request: UpdateFewShotExampleRequest,
+    ) -> Result<FewShotExampleResponse, AiStudioError> {
+        let existing = self.find_example(tenant_id, id).await?;
+        let input_changed = request.input.as_ref().is_some_and(|input| input.trim() != existing.input);
+        let mut active: few_shot_example::ActiveModel = existing.into();
+
+        if let Some(input) = request.input {
+            active.input = Set(validate_text("input", &input)?);
+        }
+        if let Some(output) = request.output {
+            active.output = Set(validate_text("output", &output)?);
+        }
+        if let Some(tags) = request.tags {
+            active.tags = Set(serde_json::to_value(normalize_tags(tags)?)?);
+        }
+        active.updated_at = Set(Utc::now().fixed_offset());
+
+        let example = active.update(&self.db).await?;
+        if input_changed {
+            self.refresh_embedding(&example).await;
+        }
+        Ok(example.into())
+    }
+
+    /// 删除示例
+    pub async fn delete_example(&self, tenant_id: Uuid, id: Uuid) -> Result<(), AiStudioError> {
+        let result = FewShotExample::delete_many()
+            .filter(few_shot_example::Column::TenantId.eq(tenant_id))
+            .filter(few_shot_example::Column::Id.eq(id))
+            .exec(&self.db)
+            .await?;
+
+        if result.rows_affected == 0 {
+            return Err(AiStudioError::not_found("少样本示例"));
+        }
+        Ok(())
+    }
+
+    /// 挑选与任务最相关的示例
+    ///
+    /// 能生成任务向量时按余弦相似度排序，未生成向量的示例排在最后；否则按最近更新时间挑选。
+    #[instrument(skip(self, task))]
+    pub async fn select_examples(
+        &self,
+        tenant_id: Uuid,
+        target: &FewShotTarget,
+        task: &str,
+        limit: usize,
+    ) -> Result<Vec<FewShotExampleResponse>, AiStudioError> {
+        target.validate()?;
+        let limit = limit.clamp(1, MAX_SELECTED_EXAMPLES);
+
+        let Some(task_vector) = self.embed(task, EmbeddingPriority::Interactive).await else {
+            debug!("任务向量不可用，按更新时间挑选少样本示例");
+            let mut examples = self.list_examples(tenant_id, target, &[]).await?;
+            examples.truncate(limit);
+            return Ok(examples);
+        };
+
+        let values: Vec<Value> = vec![
+            tenant_id.into(),
+            target.agent_id.into(),
+            target.prompt_template.clone().into(),
+            format_vector(&task_vector).into(),
+            (limit as i64).into(),
+        ];
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT id, agent_id, prompt_template, input, output, tags, created_by, created_at, updated_at,
+                       (1 - (embedding <=> $4::vector))::REAL AS similarity
+                FROM few_shot_examples
+                WHERE tenant_id = $1
+                    AND (agent_id = $2 OR prompt_template = $3)
+                ORDER BY embedding <=> $4::vector NULLS LAST, updated_at DESC
+                LIMIT $5
+                "#,
+                values,
+            ))
+            .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<FewShotExampleResponse, AiStudioError> {
+                let tags: serde_json::Value = row.try_get("", "tags")?;
+                let created_at: DateTime<chrono::FixedOffset> = row.try_get("", "created_at")?;
+                let updated_at: DateTime<chrono::FixedOffset> = row.try_get("", "updated_at")?;
+                Ok(FewShotExampleResponse {
+                    id: row.try_get("", "id")?,
+                    agent_id: row.try_get("", "agent_id")?,
+                    prompt_template: row.try_get("", "prompt_template")?,
+                    input: row.try_get("", "input")?,
+                    output: row.try_get("", "output")?,
+                    tags: serde_json::from_value(tags).unwrap_or_default(),
+                    similarity: row.try_get("", "similarity")?,
+                    created_by: row.try_get("", "created_by")?,
+                    created_at: created_at.with_timezone(&Utc),
+                    updated_at: updated_at.with_timezone(&Utc),
+                })
+            })
+            .collect()
+    }
+
+    async fn find_example(&self, tenant_id: Uuid, id: Uuid) -> Result<few_shot_example::Model, AiStudioError> {
+        FewShotExample::find_by_id(id)
+            .filter(few_shot_example::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("少样本示例"))
+    }
+
+    /// 重新生成示例输入的向量，失败时清空向量，示例仍可按时间被挑选
+    async fn refresh_embedding(&self, example: &few_shot_example::Model) {
+        let vector = self.embed(&example.input, EmbeddingPriority::Bulk).await.map(|v| format_vector(&v));
+
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE few_shot_examples SET embedding = $1::vector WHERE id = $2",
+                vec![vector.into(), example.id.into()],
+            ))
+            .await;
+        if let Err(e) = result {
+            warn!("保存少样本示例向量失败: id={}, error={}", example.id, e);
+        }
+    }
+
+    async fn embed(&self, text: &str, priority: EmbeddingPriority) -> Option<Vec<f32>> {
+        let result = match (EmbeddingPool::global(), &self.embedder) {
+            (Some(pool), _) => pool.embed(text, priority).await,
+            (None, Some(embedder)) => embedder.generate_embedding(text).await.map(|r| r.embedding),
+            (None, None) => return None,
+        };
+
+        result.map_err(|e| warn!("生成少样本示例向量失败: {}", e)).ok()
+    }
+}
+
+/// 将示例格式化为提示词片段
+pub fn format_examples_for_prompt(examples: &[FewShotExampleResponse]) -> String {
+    let mut section = String::new();
+    for (index, example) in examples.iter().enumerate() {
+        section.push_str(&format!("示例 {}:\n输入: {}\n输出: {}\n\n", index + 1, example.input, example.output));
+    }
+    section
+}
+
+fn validate_text(field: &str, text: &str) -> Result<String, AiStudioError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(AiStudioError::validation(field, "不能为空"));
+    }
+    if text.chars().count() > MAX_EXAMPLE_LENGTH {
+        return Err(AiStudioError::validation(field, format!("不能超过 {} 个字符", MAX_EXAMPLE_LENGTH)));
+    }
+    Ok(text.to_string())
+}
+
+/// 去除空白、去重并限制标签数量
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, AiStudioError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_TAGS {
+        return Err(AiStudioError::validation("tags", format!("标签不能超过 {} 个", MAX_TAGS)));
+    }
+    Ok(normalized)
+}
+
+fn format_vector(vector: &[f32]) -> String {
+    format!("[{}]", vector.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = normalize_tags(vec![" SQL ".to_string(), "sql".to_string(), "".to_string(), "报表".to_string()]).unwrap();
+        assert_eq!(tags, vec!["sql".to_string(), "报表".to_string()]);
+        assert!(normalize_tags((0..=MAX_TAGS).map(|i| i.to_string()).collect()).is_err());
+    }
+
+    #[test]
+    fn test_target_validation() {
+        assert!(FewShotTarget::default().validate().is_err());
+        assert!(FewShotTarget { agent_id: None, prompt_template: Some("  ".to_string()) }.validate().is_err());
+        assert!(FewShotTarget { agent_id: Some(Uuid::new_v4()), prompt_template: None }.validate().is_ok());
+    }
+
+    #[test]
+    fn test_format_examples_for_prompt() {
+        let now = Utc::now();
+        let example = FewShotExampleResponse {
+            id: Uuid::new_v4(),
+            agent_id: None,
+            prompt_template: Some("sql".to_string()),
+            input: "统计本月订单数".to_string(),
+            output: "SELECT count(*) FROM orders".to_string(),
+            tags: Vec::new(),
+            similarity: Some(0.9),
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let section = format_examples_for_prompt(&[example]);
+        assert!(section.starts_with("示例 1:\n输入: 统计本月订单数\n输出: SELECT"));
+    }
+}
@@ -0,0 +1,519 @@
+// 微调数据集构建
+// 从获得正向反馈或经人工审校的问答记录中筛选问答对，去重并脱敏后导出为 JSONL 微调数据集
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::pii::scan_pii;
+use crate::db::entities::knowledge_base::PiiKind;
+use crate::db::entities::{qa_query_log, QaQueryLog};
+use crate::errors::AiStudioError;
+use crate::services::qa_transcript::MAX_TRANSCRIPT_RECORDS;
+use crate::services::question_suggestion::normalize_question;
+use crate::services::task_queue::{TaskExecutor, TaskInfo, TaskQueueService, TaskStatus, TaskType};
+
+/// 默认的最低评分，评分不低于该值的答案视为正向反馈
+const DEFAULT_MIN_RATING: u8 = 4;
+
+/// 正向反馈类型
+const POSITIVE_FEEDBACK_TYPES: [&str; 1] = ["helpful"];
+
+/// 负向反馈类型，即使评分较高也不纳入数据集
+const NEGATIVE_FEEDBACK_TYPES: [&str; 4] = ["not_helpful", "incorrect", "incomplete", "irrelevant"];
+
+/// 构建微调数据集请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BuildFinetuneDatasetRequest {
+    /// 按知识库过滤
+    pub knowledge_base_id: Option<Uuid>,
+    /// 起始时间（含）
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间（不含）
+    pub end_time: Option<DateTime<Utc>>,
+    /// 最低评分（1-5），默认 4
+    pub min_rating: Option<u8>,
+    /// 最低答案置信度
+    pub min_confidence: Option<f32>,
+    /// 只使用经人工审校的答案
+    #[serde(default)]
+    pub reviewed_only: bool,
+    /// 写入每条样本的系统提示词
+    pub system_prompt: Option<String>,
+    /// 是否脱敏个人信息，默认开启
+    #[serde(default = "default_scrub_pii")]
+    pub scrub_pii: bool,
+}
+
+fn default_scrub_pii() -> bool {
+    true
+}
+
+impl BuildFinetuneDatasetRequest {
+    /// 校验请求参数
+    pub fn validate(&self) -> Result<(), AiStudioError> {
+        if self.min_rating.is_some_and(|rating| !(1..=5).contains(&rating)) {
+            return Err(AiStudioError::validation("min_rating", "最低评分必须在 1-5 之间"));
+        }
+        if self.min_confidence.is_some_and(|confidence| !(0.0..=1.0).contains(&confidence)) {
+            return Err(AiStudioError::validation("min_confidence", "最低置信度必须在 0-1 之间"));
+        }
+        if let (Some(start), Some(end)) = (self.start_time, self.end_time) {
+            if start >= end {
+                return Err(AiStudioError::validation("end_time", "结束时间必须晚于起始时间"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 数据集构建统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FinetuneDatasetStats {
+    /// 满足时间与知识库条件的候选记录数
+    pub candidates: u32,
+    /// 通过审核条件的问答对数
+    pub approved: u32,
+    /// 去重移除的问答对数
+    pub duplicates_removed: u32,
+    /// 脱敏替换的个人信息数
+    pub pii_redactions: u32,
+    /// 最终写入数据集的样本数
+    pub examples: u32,
+    /// 使用人工审校答案的样本数
+    pub reviewed_examples: u32,
+}
+
+/// 微调数据集构建状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FinetuneDatasetResponse {
+    /// 数据集 ID
+    pub dataset_id: Uuid,
+    /// 状态：pending、running、completed、failed、cancelled
+    pub status: String,
+    /// 构建统计，完成后返回
+    pub stats: Option<FinetuneDatasetStats>,
+    /// 数据集文件在存储中的相对路径，完成后返回
+    pub storage_path: Option<String>,
+    /// 文件大小（字节）
+    pub file_size: Option<u64>,
+    /// 失败原因
+    pub error_message: Option<String>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 完成时间
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// 对话消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FinetuneMessage {
+    /// 角色：system、user、assistant
+    pub role: String,
+    /// 消息内容
+    pub content: String,
+}
+
+/// 一条微调样本，对应 JSONL 文件中的一行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FinetuneExample {
+    /// 对话消息
+    pub messages: Vec<FinetuneMessage>,
+}
+
+/// 通过审核的问答对
+#[derive(Debug, Clone)]
+struct ApprovedPair {
+    question: String,
+    answer: String,
+    reviewed: bool,
+    rating: i16,
+    created_at: DateTime<Utc>,
+}
+
+/// 数据集构建任务参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DatasetParameters {
+    requested_by: Uuid,
+    request: BuildFinetuneDatasetRequest,
+}
+
+/// 微调数据集服务
+pub struct FinetuneDatasetService {
+    queue: Arc<TaskQueueService>,
+}
+
+impl FinetuneDatasetService {
+    /// 创建数据集服务
+    pub fn new(queue: Arc<TaskQueueService>) -> Self {
+        Self { queue }
+    }
+
+    /// 提交数据集构建任务
+    #[instrument(skip(self, request))]
+    pub async fn submit(
+        &self,
+        tenant_id: Uuid,
+        requested_by: Uuid,
+        request: BuildFinetuneDatasetRequest,
+    ) -> Result<FinetuneDatasetResponse, AiStudioError> {
+        request.validate()?;
+        let parameters = serde_json::to_value(DatasetParameters { requested_by, request })?;
+        let dataset_id = self.queue
+            .submit_task(TaskType::FinetuneDatasetBuild, tenant_id, parameters, None)
+            .await?;
+
+        info!("微调数据集构建任务已提交: dataset_id={}, tenant={}", dataset_id, tenant_id);
+        self.status(tenant_id, dataset_id).await
+    }
+
+    /// 查询数据集构建状态
+    pub async fn status(&self, tenant_id: Uuid, dataset_id: Uuid) -> Result<FinetuneDatasetResponse, AiStudioError> {
+        let task = self.queue
+            .get_task_status(dataset_id)
+            .await
+            .filter(|task| task.tenant_id == tenant_id && task.task_type == TaskType::FinetuneDatasetBuild)
+            .ok_or_else(|| AiStudioError::not_found("数据集构建任务"))?;
+
+        let completed = task.status == TaskStatus::Completed;
+        let result = task.result.clone().unwrap_or_default();
+        Ok(FinetuneDatasetResponse {
+            dataset_id,
+            status: serde_json::to_value(&task.status)?.as_str().unwrap_or_default().to_string(),
+            stats: result.get("stats").cloned().and_then(|stats| serde_json::from_value(stats).ok()),
+            storage_path: completed.then(|| dataset_relative_path(tenant_id, dataset_id)),
+            file_size: result.get("file_size").and_then(|v| v.as_u64()),
+            error_message: task.error_message,
+            created_at: task.created_at,
+            completed_at: task.completed_at,
+        })
+    }
+}
+
+/// 微调数据集构建任务执行器
+pub struct FinetuneDatasetExecutor {
+    db: DatabaseConnection,
+    storage_root: PathBuf,
+}
+
+impl FinetuneDatasetExecutor {
+    /// 创建执行器，数据集写入 `storage_root/datasets/finetune/<租户 ID>/`
+    pub fn new(db: DatabaseConnection, storage_root: impl Into<PathBuf>) -> Self {
+        Self { db, storage_root: storage_root.into() }
+    }
+
+    /// 查询候选问答记录：有答案，且有正向反馈、评分或人工审校
+    async fn load_candidates(
+        &self,
+        tenant_id: Uuid,
+        request: &BuildFinetuneDatasetRequest,
+    ) -> Result<Vec<qa_query_log::Model>, AiStudioError> {
+        let mut select = QaQueryLog::find()
+            .filter(qa_query_log::Column::TenantId.eq(tenant_id))
+            .filter(qa_query_log::Column::Answer.is_not_null())
+            .filter(
+                Condition::any()
+                    .add(qa_query_log::Column::ReviewedAnswer.is_not_null())
+                    .add(qa_query_log::Column::FeedbackRating.is_not_null())
+                    .add(qa_query_log::Column::FeedbackType.is_not_null()),
+            );
+        if let Some(kb_id) = request.knowledge_base_id {
+            select = select.filter(qa_query_log::Column::KnowledgeBaseId.eq(kb_id));
+        }
+        if let Some(start_time) = request.start_time {
+            select = select.filter(qa_query_log::Column::CreatedAt.gte(start_time));
+        }
+        if let Some(end_time) = request.end_time {
+            select = select.filter(qa_query_log::Column::CreatedAt.lt(end_time));
+        }
+
+        Ok(select
+            .order_by_asc(qa_query_log::Column::CreatedAt)
+            .limit(MAX_TRANSCRIPT_RECORDS)
+            .all(&self.db)
+            .await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskExecutor for FinetuneDatasetExecutor {
+    async fn execute(&self, task: &mut TaskInfo) -> Result<(), AiStudioError> {
+        let parameters: DatasetParameters = serde_json::from_value(task.parameters.clone())?;
+        let request = &parameters.request;
+
+        let candidates = self.load_candidates(task.tenant_id, request).await?;
+        task.total_count = Some(candidates.len() as u32);
+        task.progress = 40;
+
+        let (examples, stats) = build_examples(&candidates, request);
+        let content = render_jsonl(&examples)?;
+
+        let path = self.storage_root.join(dataset_relative_path(task.tenant_id, task.id));
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await
+                .map_err(|e| AiStudioError::internal(format!("创建数据集目录失败: {}", e)))?;
+        }
+        tokio::fs::write(&path, content.as_bytes()).await
+            .map_err(|e| AiStudioError::internal(format!("写入数据集文件失败: {}", e)))?;
+
+        task.success_count = stats.examples;
+        task.result = Some(serde_json::json!({
+            "stats": stats,
+            "file_size": content.len(),
+        }));
+        info!(
+            "微调数据集构建完成: dataset_id={}, 样本数={}, 请求人={}",
+            task.id, stats.examples, parameters.requested_by
+        );
+        Ok(())
+    }
+
+    fn supported_task_types(&self) -> Vec<TaskType> {
+        vec![TaskType::FinetuneDatasetBuild]
+    }
+}
+
+/// 数据集文件相对于存储根目录的路径
+pub fn dataset_relative_path(tenant_id: Uuid, dataset_id: Uuid) -> String {
+    Path::new("datasets")
+        .join("finetune")
+        .join(tenant_id.to_string())
+        .join(format!("{}.jsonl", dataset_id))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// 由候选问答记录构建微调样本
+pub fn build_examples(
+    candidates: &[qa_query_log::Model],
+    request: &BuildFinetuneDatasetRequest,
+) -> (Vec<FinetuneExample>, FinetuneDatasetStats) {
+    let mut stats = FinetuneDatasetStats { candidates: candidates.len() as u32, ..Default::default() };
+    let min_rating = request.min_rating.unwrap_or(DEFAULT_MIN_RATING);
+
+    let approved: Vec<ApprovedPair> = candidates.iter()
+        .filter(|log| request.min_confidence.is_none_or(|min| log.confidence_score >= min))
+        .filter_map(|log| approve(log, min_rating, request.reviewed_only))
+        .collect();
+    stats.approved = approved.len() as u32;
+
+    let deduped = dedupe_pairs(approved);
+    stats.duplicates_removed = stats.approved - deduped.len() as u32;
+
+    let system_prompt = request.system_prompt.as_deref().map(str::trim).filter(|p| !p.is_empty());
+    let examples = deduped.into_iter()
+        .map(|pair| {
+            if pair.reviewed {
+                stats.reviewed_examples += 1;
+            }
+            let (question, answer) = if request.scrub_pii {
+                let (question, question_redactions) = scrub_pii(&pair.question);
+                let (answer, answer_redactions) = scrub_pii(&pair.answer);
+                stats.pii_redactions += question_redactions + answer_redactions;
+                (question, answer)
+            } else {
+                (pair.question, pair.answer)
+            };
+
+            let mut messages = Vec::with_capacity(3);
+            if let Some(prompt) = system_prompt {
+                messages.push(FinetuneMessage { role: "system".to_string(), content: prompt.to_string() });
+            }
+            messages.push(FinetuneMessage { role: "user".to_string(), content: question });
+            messages.push(FinetuneMessage { role: "assistant".to_string(), content: answer });
+            FinetuneExample { messages }
+        })
+        .collect::<Vec<_>>();
+    stats.examples = examples.len() as u32;
+
+    (examples, stats)
+}
+
+/// 判断问答记录能否作为训练样本
+///
+/// 人工审校的答案直接通过；否则要求正向反馈类型或评分达到下限，且没有负向反馈。
+fn approve(log: &qa_query_log::Model, min_rating: u8, reviewed_only: bool) -> Option<ApprovedPair> {
+    let question = log.question.trim();
+    if question.is_empty() {
+        return None;
+    }
+
+    let reviewed_answer = log.reviewed_answer.as_deref().map(str::trim).filter(|a| !a.is_empty());
+    let answer = match reviewed_answer {
+        Some(answer) => answer,
+        None if reviewed_only => return None,
+        None => {
+            let feedback_type = log.feedback_type.as_deref();
+            if feedback_type.is_some_and(|t| NEGATIVE_FEEDBACK_TYPES.contains(&t)) {
+                return None;
+            }
+            let positive_type = feedback_type.is_some_and(|t| POSITIVE_FEEDBACK_TYPES.contains(&t));
+            let rating_ok = log.feedback_rating.is_some_and(|r| r >= i16::from(min_rating));
+            let rating_low = log.feedback_rating.is_some_and(|r| r < i16::from(min_rating));
+            if !(rating_ok || (positive_type && !rating_low)) {
+                return None;
+            }
+            log.answer.as_deref().map(str::trim).filter(|a| !a.is_empty())?
+        }
+    };
+
+    Some(ApprovedPair {
+        question: question.to_string(),
+        answer: answer.to_string(),
+        reviewed: reviewed_answer.is_some(),
+        rating: log.feedback_rating.unwrap_or(0),
+        created_at: log.created_at.with_timezone(&Utc),
+    })
+}
+
+/// 按归一化问题去重，优先保留人工审校、评分更高、时间更新的问答对
+fn dedupe_pairs(pairs: Vec<ApprovedPair>) -> Vec<ApprovedPair> {
+    let mut order = Vec::new();
+    let mut best: HashMap<String, ApprovedPair> = HashMap::new();
+
+    for pair in pairs {
+        let key = normalize_question(&pair.question);
+        match best.get(&key) {
+            Some(existing) if (existing.reviewed, existing.rating, existing.created_at)
+                >= (pair.reviewed, pair.rating, pair.created_at) => {}
+            Some(_) => {
+                best.insert(key, pair);
+            }
+            None => {
+                order.push(key.clone());
+                best.insert(key, pair);
+            }
+        }
+    }
+
+    order.into_iter().filter_map(|key| best.remove(&key)).collect()
+}
+
+/// 脱敏文本中的个人信息，返回脱敏后的文本与替换次数
+///
+/// 识别邮箱、IPv4 地址、手机号、身份证号与银行卡号，分别替换为占位符。
+pub fn scrub_pii(text: &str) -> (String, u32) {
+    let scan = scan_pii(text, &PiiKind::ALL);
+    let count = scan.total();
+    (scan.masked, count)
+}
+
+/// 将样本渲染为 JSONL
+pub fn render_jsonl(examples: &[FinetuneExample]) -> Result<String, AiStudioError> {
+    let mut content = String::new();
+    for example in examples {
+        content.push_str(&serde_json::to_string(example)?);
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::entities::document::ClearanceLevel;
+
+    fn log(question: &str, answer: &str, feedback_type: Option<&str>, rating: Option<i16>) -> qa_query_log::Model {
+        qa_query_log::Model {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            knowledge_base_id: None,
+            user_id: None,
+            question: question.to_string(),
+            normalized_question: normalize_question(question),
+            confidence_score: 0.8,
+            source_count: 1,
+            clearance: ClearanceLevel::Public,
+            query_id: None,
+            session_id: None,
+            answer: Some(answer.to_string()),
+            citations: serde_json::json!([]),
+            feedback_type: feedback_type.map(str::to_string),
+            feedback_rating: rating,
+            feedback_comment: None,
+            feedback_at: None,
+            reviewed_answer: None,
+            reviewed_by: None,
+            reviewed_at: None,
+            created_at: Utc::now().into(),
+        }
+    }
+
+    fn request() -> BuildFinetuneDatasetRequest {
+        BuildFinetuneDatasetRequest {
+            knowledge_base_id: None,
+            start_time: None,
+            end_time: None,
+            min_rating: None,
+            min_confidence: None,
+            reviewed_only: false,
+            system_prompt: None,
+            scrub_pii: true,
+        }
+    }
+
+    #[test]
+    fn test_approval_rules() {
+        assert!(approve(&log("q", "a", Some("helpful"), None), 4, false).is_some());
+        assert!(approve(&log("q", "a", None, Some(5)), 4, false).is_some());
+        assert!(approve(&log("q", "a", Some("helpful"), Some(2)), 4, false).is_none());
+        assert!(approve(&log("q", "a", Some("incorrect"), Some(5)), 4, false).is_none());
+
+        // 人工审校的答案替代原答案，且不受反馈限制
+        let mut reviewed = log("q", "a", Some("incorrect"), None);
+        reviewed.reviewed_answer = Some("修正后的答案".to_string());
+        let pair = approve(&reviewed, 4, true).unwrap();
+        assert_eq!(pair.answer, "修正后的答案");
+        assert!(approve(&log("q", "a", Some("helpful"), None), 4, true).is_none());
+    }
+
+    #[test]
+    fn test_build_examples_dedupes_and_prefers_reviewed() {
+        let mut reviewed = log("报销流程是什么？", "原答案", None, None);
+        reviewed.reviewed_answer = Some("审校答案".to_string());
+        let candidates = vec![
+            log("报销流程是什么", "原答案", Some("helpful"), Some(5)),
+            reviewed,
+            log("请假流程？", "请假答案", None, Some(4)),
+            log("加班规定？", "加班答案", Some("not_helpful"), None),
+        ];
+        let request = BuildFinetuneDatasetRequest { system_prompt: Some("你是企业助手".to_string()), ..request() };
+
+        let (examples, stats) = build_examples(&candidates, &request);
+        assert_eq!(stats.candidates, 4);
+        assert_eq!(stats.approved, 3);
+        assert_eq!(stats.duplicates_removed, 1);
+        assert_eq!(stats.examples, 2);
+        assert_eq!(stats.reviewed_examples, 1);
+        assert_eq!(examples[0].messages[0].role, "system");
+        assert_eq!(examples[0].messages[2].content, "审校答案");
+        assert_eq!(examples[1].messages[2].content, "请假答案");
+    }
+
+    #[test]
+    fn test_scrub_pii() {
+        let (text, count) = scrub_pii("联系张三13800138000或zhangsan@example.com，身份证110101199003071234");
+        assert_eq!(text, "联系张三[PHONE]或[EMAIL]，身份证[ID_NUMBER]");
+        assert_eq!(count, 3);
+
+        let (text, count) = scrub_pii("卡号6222021234567890123，服务器192.168.1.10，共30天");
+        assert_eq!(text, "卡号[CARD_NUMBER]，服务器[IP]，共30天");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_render_jsonl() {
+        let example = FinetuneExample {
+            messages: vec![FinetuneMessage { role: "user".to_string(), content: "问题".to_string() }],
+        };
+        let content = render_jsonl(&[example.clone(), example]).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.starts_with(r#"{"messages":[{"role":"user","content":"问题"}]}"#));
+    }
+}
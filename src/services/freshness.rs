@@ -0,0 +1,539 @@
+// 文档时效服务
+// 按知识库时效策略判定文档是否过期或待复核，用于检索降权、过期文档报表和负责人复核提醒
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::knowledge_base::{
+    FreshnessPolicy, RecencyBoostPolicy, RecencyDecayCurve, StaleDocumentHandling,
+};
+use crate::db::entities::{document, knowledge_base, user, Document, KnowledgeBase, User};
+use crate::errors::AiStudioError;
+use crate::services::notification::{MatchedDocument, NotificationService};
+use crate::services::scheduler::PeriodicJob;
+
+/// 复核提醒扫描间隔
+const REVIEW_SCAN_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// 文档时效状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FreshnessStatus {
+    /// 有效
+    Fresh,
+    /// 临近过期或已到复核时间
+    ReviewDue,
+    /// 已过期
+    Expired,
+}
+
+/// 按策略推算出的文档时效
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentFreshness {
+    /// 时效状态
+    pub status: FreshnessStatus,
+    /// 生效的过期时间
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 生效的复核时间
+    pub review_at: Option<DateTime<Utc>>,
+}
+
+/// 文档时效条目
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DocumentFreshnessEntry {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 负责人 ID
+    pub owner_id: Option<Uuid>,
+    /// 时效状态
+    pub status: FreshnessStatus,
+    /// 生效的过期时间（文档未设置时按策略推算）
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 生效的复核时间（文档未设置时按策略推算）
+    pub review_at: Option<DateTime<Utc>>,
+    /// 最近更新时间
+    pub updated_at: DateTime<Utc>,
+    /// 最近一次发送复核提醒的时间
+    pub review_notified_at: Option<DateTime<Utc>>,
+}
+
+/// 过期文档报表
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StaleDocumentReport {
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 已过期文档数
+    pub expired_count: usize,
+    /// 待复核文档数
+    pub review_due_count: usize,
+    /// 过期与待复核文档，按到期时间升序
+    pub documents: Vec<DocumentFreshnessEntry>,
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+}
+
+/// 文档续期请求
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct RenewDocumentRequest {
+    /// 新的过期时间，为空时按知识库默认有效天数从当前时间推算
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 新的复核时间，为空时按知识库默认复核周期从当前时间推算
+    pub review_at: Option<DateTime<Utc>>,
+}
+
+/// 推算文档时效
+///
+/// 文档自身的过期/复核时间优先，未设置时以最近更新时间为基准按策略推算；
+/// 距过期或复核时间不足提前提醒天数时视为待复核。
+pub fn evaluate_freshness(
+    expires_at: Option<DateTime<Utc>>,
+    review_at: Option<DateTime<Utc>>,
+    updated_at: DateTime<Utc>,
+    policy: &FreshnessPolicy,
+    now: DateTime<Utc>,
+) -> DocumentFreshness {
+    let expires_at = expires_at
+        .or_else(|| policy.default_ttl_days.map(|days| updated_at + chrono::Duration::days(days as i64)));
+    let review_at = review_at
+        .or_else(|| policy.review_interval_days.map(|days| updated_at + chrono::Duration::days(days as i64)));
+    let lead = chrono::Duration::days(policy.reminder_lead_days as i64);
+
+    let status = if expires_at.is_some_and(|at| at <= now) {
+        FreshnessStatus::Expired
+    } else if [expires_at, review_at].into_iter().flatten().any(|at| at - lead <= now) {
+        FreshnessStatus::ReviewDue
+    } else {
+        FreshnessStatus::Fresh
+    };
+
+    DocumentFreshness { status, expires_at, review_at }
+}
+
+/// 文档在检索中的得分权重，`None` 表示应从结果中排除
+pub fn retrieval_weight(status: FreshnessStatus, policy: &FreshnessPolicy) -> Option<f32> {
+    if status != FreshnessStatus::Expired {
+        return Some(1.0);
+    }
+    match policy.stale_handling {
+        StaleDocumentHandling::Ignore => Some(1.0),
+        StaleDocumentHandling::DownWeight => Some(policy.stale_weight.clamp(0.0, 1.0)),
+        StaleDocumentHandling::Exclude => None,
+    }
+}
+
+/// 按文档年龄计算新近度权重
+///
+/// 权重介于策略下限与 1 之间，文档越新越接近 1；未启用或年龄非正时返回 1。
+pub fn recency_weight(age_days: f64, policy: &RecencyBoostPolicy) -> f32 {
+    if !policy.enabled || age_days <= 0.0 {
+        return 1.0;
+    }
+    let floor = policy.min_weight.clamp(0.0, 1.0) as f64;
+    let decay = match policy.curve {
+        RecencyDecayCurve::Exponential => {
+            if policy.half_life_days <= 0.0 {
+                return 1.0;
+            }
+            0.5f64.powf(age_days / policy.half_life_days as f64)
+        }
+        RecencyDecayCurve::Linear => {
+            if policy.max_age_days <= 0.0 {
+                return 1.0;
+            }
+            (1.0 - age_days / policy.max_age_days as f64).max(0.0)
+        }
+        RecencyDecayCurve::Step => {
+            if age_days > policy.max_age_days as f64 { 0.0 } else { 1.0 }
+        }
+    };
+    (floor + (1.0 - floor) * decay) as f32
+}
+
+/// 文档时效服务
+pub struct DocumentFreshnessService {
+    db: DatabaseConnection,
+    notifications: Arc<NotificationService>,
+}
+
+impl DocumentFreshnessService {
+    /// 创建新的文档时效服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            notifications: Arc::new(NotificationService::new()),
+        }
+    }
+
+    /// 计算一组文档的检索权重，`None` 表示文档需从检索结果中排除
+    ///
+    /// 已归档的文档、已归档知识库中的文档，以及已过期且所在知识库要求排除的文档均被排除；
+    /// 其余文档的权重为时效权重与新近度权重之积，常青文档不参与新近度衰减。
+    pub async fn retrieval_weights(&self, document_ids: &[Uuid]) -> Result<HashMap<Uuid, Option<f32>>, AiStudioError> {
+        if document_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let documents = Document::find()
+            .filter(document::Column::Id.is_in(document_ids.iter().copied()))
+            .all(&self.db)
+            .await?;
+        let kb_ids: HashSet<Uuid> = documents.iter().map(|doc| doc.knowledge_base_id).collect();
+        let knowledge_bases = KnowledgeBase::find()
+            .filter(knowledge_base::Column::Id.is_in(kb_ids))
+            .all(&self.db)
+            .await?;
+        let archived_kbs: HashSet<Uuid> = knowledge_bases.iter()
+            .filter(|kb| kb.is_archived())
+            .map(|kb| kb.id)
+            .collect();
+        let policies: HashMap<Uuid, (FreshnessPolicy, RecencyBoostPolicy)> = knowledge_bases
+            .into_iter()
+            .map(|kb| {
+                let recency = kb.get_config().map(|config| config.recency_boost).unwrap_or_default();
+                (kb.id, (policy_of(&kb), recency))
+            })
+            .collect();
+
+        let now = Utc::now();
+        Ok(documents.iter()
+            .map(|doc| {
+                if doc.status == document::DocumentStatus::Archived || archived_kbs.contains(&doc.knowledge_base_id) {
+                    return (doc.id, None);
+                }
+                let (policy, recency) = policies.get(&doc.knowledge_base_id).cloned().unwrap_or_default();
+                let freshness = evaluate_document(doc, &policy, now);
+                let weight = retrieval_weight(freshness.status, &policy).map(|weight| {
+                    if doc.evergreen {
+                        return weight;
+                    }
+                    let age_days = (now - doc.updated_at.with_timezone(&Utc)).num_seconds() as f64 / 86_400.0;
+                    weight * recency_weight(age_days, &recency)
+                });
+                (doc.id, weight)
+            })
+            .collect())
+    }
+
+    /// 生成知识库的过期文档报表
+    #[instrument(skip(self))]
+    pub async fn stale_report(&self, tenant_id: Uuid, knowledge_base_id: Uuid) -> Result<StaleDocumentReport, AiStudioError> {
+        let kb = KnowledgeBase::find_by_id(knowledge_base_id)
+            .filter(knowledge_base::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("知识库"))?;
+
+        let now = Utc::now();
+        let policy = policy_of(&kb);
+        let mut documents: Vec<DocumentFreshnessEntry> = self.candidate_documents(&kb, &policy)
+            .await?
+            .iter()
+            .map(|doc| freshness_entry(doc, evaluate_document(doc, &policy, now)))
+            .filter(|entry| entry.status != FreshnessStatus::Fresh)
+            .collect();
+        documents.sort_by_key(|entry| {
+            [entry.expires_at, entry.review_at].into_iter().flatten().min()
+        });
+
+        let expired_count = documents.iter().filter(|e| e.status == FreshnessStatus::Expired).count();
+        Ok(StaleDocumentReport {
+            knowledge_base_id,
+            expired_count,
+            review_due_count: documents.len() - expired_count,
+            documents,
+            generated_at: now,
+        })
+    }
+
+    /// 复核并续期文档
+    ///
+    /// 未指定的时间按知识库默认天数从当前时间推算，策略也未配置时保留原值；续期后重新开始复核提醒。
+    #[instrument(skip(self, req))]
+    pub async fn renew_document(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+        req: RenewDocumentRequest,
+    ) -> Result<DocumentFreshnessEntry, AiStudioError> {
+        let (doc, kb) = Document::find_by_id(document_id)
+            .find_also_related(KnowledgeBase)
+            .filter(knowledge_base::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .and_then(|(doc, kb)| kb.map(|kb| (doc, kb)))
+            .ok_or_else(|| AiStudioError::not_found("文档"))?;
+
+        let now = Utc::now();
+        let policy = policy_of(&kb);
+        let expires_at = req.expires_at
+            .or_else(|| policy.default_ttl_days.map(|days| now + chrono::Duration::days(days as i64)))
+            .or_else(|| doc.expires_at.map(|at| at.with_timezone(&Utc)));
+        let review_at = req.review_at
+            .or_else(|| policy.review_interval_days.map(|days| now + chrono::Duration::days(days as i64)))
+            .or_else(|| doc.review_at.map(|at| at.with_timezone(&Utc)));
+
+        if expires_at.is_some_and(|at| at <= now) {
+            return Err(AiStudioError::validation("expires_at", "过期时间必须晚于当前时间"));
+        }
+        if review_at.is_some_and(|at| at <= now) {
+            return Err(AiStudioError::validation("review_at", "复核时间必须晚于当前时间"));
+        }
+
+        let mut active: document::ActiveModel = doc.into();
+        active.expires_at = Set(expires_at.map(|at| at.fixed_offset()));
+        active.review_at = Set(review_at.map(|at| at.fixed_offset()));
+        active.review_notified_at = Set(None);
+        let doc = active.update(&self.db).await?;
+
+        info!("文档已续期: document_id={}, expires_at={:?}, review_at={:?}", doc.id, expires_at, review_at);
+        Ok(freshness_entry(&doc, evaluate_document(&doc, &policy, now)))
+    }
+
+    /// 向过期或待复核文档的负责人发送复核提醒
+    ///
+    /// 文档未指定负责人时提醒知识库访问控制中的用户；同一文档在提前提醒天数内只提醒一次。
+    #[instrument(skip(self))]
+    pub async fn send_review_reminders(&self) -> Result<usize, AiStudioError> {
+        let now = Utc::now();
+        let mut sent = 0;
+
+        for kb in KnowledgeBase::find().all(&self.db).await? {
+            let policy = policy_of(&kb);
+            let renotify_after = chrono::Duration::days(policy.reminder_lead_days.max(1) as i64);
+
+            let mut by_recipient: HashMap<Uuid, Vec<document::Model>> = HashMap::new();
+            for doc in self.candidate_documents(&kb, &policy).await? {
+                if evaluate_document(&doc, &policy, now).status == FreshnessStatus::Fresh {
+                    continue;
+                }
+                if doc.review_notified_at.is_some_and(|at| now - at.with_timezone(&Utc) < renotify_after) {
+                    continue;
+                }
+                for recipient in review_recipients(&doc, &kb) {
+                    by_recipient.entry(recipient).or_default().push(doc.clone());
+                }
+            }
+
+            let mut notified: HashMap<Uuid, document::Model> = HashMap::new();
+            for (recipient_id, documents) in by_recipient {
+                let Some(recipient) = User::find_by_id(recipient_id)
+                    .filter(user::Column::TenantId.eq(kb.tenant_id))
+                    .one(&self.db)
+                    .await?
+                else {
+                    continue;
+                };
+
+                let matched: Vec<MatchedDocument> = documents.iter()
+                    .map(|doc| MatchedDocument {
+                        document_id: doc.id,
+                        title: doc.title.clone(),
+                        link: format!("/api/v1/documents/{}", doc.id),
+                    })
+                    .collect();
+                match self.notifications
+                    .send_document_review_reminder(kb.tenant_id, &recipient.email, &kb.name, &matched)
+                    .await
+                {
+                    Ok(_) => {
+                        sent += 1;
+                        notified.extend(documents.into_iter().map(|doc| (doc.id, doc)));
+                    }
+                    Err(e) => warn!("文档复核提醒发送失败: kb_id={}, user_id={}, error={}", kb.id, recipient_id, e),
+                }
+            }
+
+            for doc in notified.into_values() {
+                let mut active: document::ActiveModel = doc.into();
+                active.review_notified_at = Set(Some(now.fixed_offset()));
+                active.update(&self.db).await?;
+            }
+        }
+
+        if sent > 0 {
+            info!("已发送 {} 条文档复核提醒", sent);
+        }
+        Ok(sent)
+    }
+
+    /// 可能过期的文档：策略未配置默认天数时只需检查显式设置了时间的文档
+    async fn candidate_documents(
+        &self,
+        kb: &knowledge_base::Model,
+        policy: &FreshnessPolicy,
+    ) -> Result<Vec<document::Model>, AiStudioError> {
+        let mut query = Document::find()
+            .filter(document::Column::KnowledgeBaseId.eq(kb.id))
+            .filter(document::Column::Status.ne(document::DocumentStatus::Archived));
+        if policy.default_ttl_days.is_none() && policy.review_interval_days.is_none() {
+            query = query.filter(
+                Condition::any()
+                    .add(document::Column::ExpiresAt.is_not_null())
+                    .add(document::Column::ReviewAt.is_not_null()),
+            );
+        }
+
+        let documents = query.order_by_asc(document::Column::UpdatedAt).all(&self.db).await?;
+        debug!("知识库 {} 时效检查候选文档 {} 篇", kb.id, documents.len());
+        Ok(documents)
+    }
+}
+
+/// 读取知识库的时效策略，配置无法解析时使用默认策略
+fn policy_of(kb: &knowledge_base::Model) -> FreshnessPolicy {
+    kb.get_config().map(|config| config.freshness).unwrap_or_default()
+}
+
+fn evaluate_document(doc: &document::Model, policy: &FreshnessPolicy, now: DateTime<Utc>) -> DocumentFreshness {
+    evaluate_freshness(
+        doc.expires_at.map(|at| at.with_timezone(&Utc)),
+        doc.review_at.map(|at| at.with_timezone(&Utc)),
+        doc.updated_at.with_timezone(&Utc),
+        policy,
+        now,
+    )
+}
+
+fn freshness_entry(doc: &document::Model, freshness: DocumentFreshness) -> DocumentFreshnessEntry {
+    DocumentFreshnessEntry {
+        document_id: doc.id,
+        title: doc.title.clone(),
+        owner_id: doc.owner_id,
+        status: freshness.status,
+        expires_at: freshness.expires_at,
+        review_at: freshness.review_at,
+        updated_at: doc.updated_at.with_timezone(&Utc),
+        review_notified_at: doc.review_notified_at.map(|at| at.with_timezone(&Utc)),
+    }
+}
+
+/// 复核提醒接收人
+fn review_recipients(doc: &document::Model, kb: &knowledge_base::Model) -> Vec<Uuid> {
+    if let Some(owner_id) = doc.owner_id {
+        return vec![owner_id];
+    }
+    kb.get_config()
+        .map(|config| {
+            config.access_control.allowed_users.iter()
+                .filter_map(|id| Uuid::parse_str(id).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 文档复核提醒周期任务
+pub struct DocumentReviewJob {
+    service: Arc<DocumentFreshnessService>,
+}
+
+impl DocumentReviewJob {
+    /// 创建新的文档复核提醒周期任务
+    pub fn new(service: Arc<DocumentFreshnessService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for DocumentReviewJob {
+    fn name(&self) -> &str {
+        "document_review_reminders"
+    }
+
+    fn interval(&self) -> Duration {
+        REVIEW_SCAN_INTERVAL
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        self.service.send_review_reminders().await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days(n: i64) -> chrono::Duration {
+        chrono::Duration::days(n)
+    }
+
+    #[test]
+    fn test_explicit_dates_take_precedence() {
+        let now = Utc::now();
+        let policy = FreshnessPolicy {
+            default_ttl_days: Some(30),
+            ..FreshnessPolicy::default()
+        };
+
+        let freshness = evaluate_freshness(Some(now + days(100)), None, now - days(60), &policy, now);
+        assert_eq!(freshness.status, FreshnessStatus::Fresh);
+
+        let freshness = evaluate_freshness(None, None, now - days(60), &policy, now);
+        assert_eq!(freshness.status, FreshnessStatus::Expired);
+        assert_eq!(freshness.expires_at, Some(now - days(30)));
+    }
+
+    #[test]
+    fn test_review_due_within_lead_time() {
+        let now = Utc::now();
+        let policy = FreshnessPolicy::default();
+
+        let freshness = evaluate_freshness(None, Some(now + days(3)), now, &policy, now);
+        assert_eq!(freshness.status, FreshnessStatus::ReviewDue);
+
+        let freshness = evaluate_freshness(Some(now + days(5)), None, now, &policy, now);
+        assert_eq!(freshness.status, FreshnessStatus::ReviewDue);
+
+        let freshness = evaluate_freshness(None, Some(now + days(30)), now, &policy, now);
+        assert_eq!(freshness.status, FreshnessStatus::Fresh);
+
+        let freshness = evaluate_freshness(None, None, now - days(3650), &policy, now);
+        assert_eq!(freshness.status, FreshnessStatus::Fresh);
+    }
+
+    #[test]
+    fn test_retrieval_weight() {
+        let mut policy = FreshnessPolicy::default();
+        assert_eq!(retrieval_weight(FreshnessStatus::ReviewDue, &policy), Some(1.0));
+        assert_eq!(retrieval_weight(FreshnessStatus::Expired, &policy), Some(0.5));
+
+        policy.stale_handling = StaleDocumentHandling::Exclude;
+        assert_eq!(retrieval_weight(FreshnessStatus::Expired, &policy), None);
+
+        policy.stale_handling = StaleDocumentHandling::Ignore;
+        assert_eq!(retrieval_weight(FreshnessStatus::Expired, &policy), Some(1.0));
+    }
+
+    #[test]
+    fn test_recency_weight_curves() {
+        let mut policy = RecencyBoostPolicy::default();
+        assert_eq!(recency_weight(365.0, &policy), 1.0);
+
+        policy.enabled = true;
+        policy.min_weight = 0.0;
+        assert_eq!(recency_weight(0.0, &policy), 1.0);
+        assert!((recency_weight(90.0, &policy) - 0.5).abs() < 1e-6);
+        assert!(recency_weight(30.0, &policy) > recency_weight(60.0, &policy));
+
+        policy.min_weight = 0.3;
+        assert!(recency_weight(10_000.0, &policy) >= 0.3);
+
+        policy.curve = RecencyDecayCurve::Linear;
+        assert!((recency_weight(182.5, &policy) - 0.65).abs() < 1e-6);
+        assert!((recency_weight(1_000.0, &policy) - 0.3).abs() < 1e-6);
+
+        policy.curve = RecencyDecayCurve::Step;
+        assert_eq!(recency_weight(300.0, &policy), 1.0);
+        assert!((recency_weight(400.0, &policy) - 0.3).abs() < 1e-6);
+    }
+}
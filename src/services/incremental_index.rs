@@ -0,0 +1,507 @@
+// 增量索引服务
+// 文档更新后按块与上一版本比对内容哈希，只为新增或变更的文档块生成嵌入，未变更的块沿用原有向量
+// 分块后按知识库的个人信息策略脱敏、标记或排除文档块，被排除的块不写入向量索引
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    QueryFilter, Set, Statement, TransactionTrait,
+};
+use serde::Serialize;
+use tracing::{error, info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::chunker::{AiVectorizer, ChunkerConfig, DocumentChunk, DocumentChunker, DocumentVectorizer, HybridChunker};
+use crate::ai::rig_client::RigAiClientManager;
+use crate::ai::document_processor::{DocumentMetadata, ExtractedText, ProcessingInfo};
+use crate::ai::vector_store::{VectorPoint, VectorStoreRegistry};
+use crate::db::entities::{document, document_chunk, embedding, DocumentChunk as DocumentChunkEntity, KnowledgeBase};
+use crate::config::ConfigLoader;
+use crate::errors::AiStudioError;
+use crate::services::pii_policy::{apply_pii_policy, PiiPolicyService, PiiTreatment};
+use crate::services::tenant_encryption::TenantEncryptionService;
+
+/// 进程内累计的增量重嵌入统计
+static DELTA_COUNTERS: DeltaCounters = DeltaCounters {
+    documents: AtomicU64::new(0),
+    chunks: AtomicU64::new(0),
+    reused: AtomicU64::new(0),
+    embedded: AtomicU64::new(0),
+    removed: AtomicU64::new(0),
+};
+
+struct DeltaCounters {
+    documents: AtomicU64,
+    chunks: AtomicU64,
+    reused: AtomicU64,
+    embedded: AtomicU64,
+    removed: AtomicU64,
+}
+
+/// 增量重嵌入累计统计
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DeltaEmbeddingStats {
+    /// 增量重建索引的文档次数
+    pub documents_reindexed: u64,
+    /// 更新后的文档块总数
+    pub chunks_total: u64,
+    /// 沿用原有向量的文档块数
+    pub chunks_reused: u64,
+    /// 重新生成嵌入的文档块数
+    pub chunks_embedded: u64,
+    /// 删除的旧文档块数
+    pub chunks_removed: u64,
+    /// 节省的嵌入调用次数（每个文档块计一次）
+    pub embedding_calls_saved: u64,
+    /// 节省比例，0-1
+    pub savings_ratio: f64,
+}
+
+/// 获取进程内累计的增量重嵌入统计
+pub fn delta_embedding_stats() -> DeltaEmbeddingStats {
+    let chunks = DELTA_COUNTERS.chunks.load(Ordering::Relaxed);
+    let reused = DELTA_COUNTERS.reused.load(Ordering::Relaxed);
+    DeltaEmbeddingStats {
+        documents_reindexed: DELTA_COUNTERS.documents.load(Ordering::Relaxed),
+        chunks_total: chunks,
+        chunks_reused: reused,
+        chunks_embedded: DELTA_COUNTERS.embedded.load(Ordering::Relaxed),
+        chunks_removed: DELTA_COUNTERS.removed.load(Ordering::Relaxed),
+        embedding_calls_saved: reused,
+        savings_ratio: if chunks == 0 { 0.0 } else { reused as f64 / chunks as f64 },
+    }
+}
+
+/// 单次增量重建索引的结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DeltaReindexReport {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 更新后的文档块数
+    pub total_chunks: usize,
+    /// 沿用原有向量的文档块数
+    pub reused_chunks: usize,
+    /// 重新生成嵌入的文档块数
+    pub embedded_chunks: usize,
+    /// 删除的旧文档块数
+    pub removed_chunks: usize,
+    /// 检测到个人信息的文档块数
+    pub pii_chunks: usize,
+    /// 因个人信息排除在向量索引之外的文档块数
+    pub excluded_chunks: usize,
+}
+
+/// 上一版本的文档块
+#[derive(Debug, Clone)]
+struct ExistingChunk {
+    id: Uuid,
+    content_hash: String,
+    /// 当前嵌入模型下已完成的嵌入，没有时该块不可复用
+    embedding_id: Option<Uuid>,
+}
+
+/// 块级差异
+#[derive(Debug, Default, PartialEq)]
+struct ChunkDeltaPlan {
+    /// 新块序号 -> 沿用的旧块 ID
+    reused: Vec<(usize, Uuid)>,
+    /// 需要生成嵌入的新块序号
+    embed: Vec<usize>,
+    /// 需要删除的旧块 ID
+    removed: Vec<Uuid>,
+}
+
+/// 按内容哈希匹配新旧文档块，相同内容出现多次时按顺序一一对应
+fn plan_chunk_delta(existing: &[ExistingChunk], new_hashes: &[String]) -> ChunkDeltaPlan {
+    let mut available: HashMap<&str, VecDeque<Uuid>> = HashMap::new();
+    for chunk in existing.iter().filter(|chunk| chunk.embedding_id.is_some()) {
+        available.entry(chunk.content_hash.as_str()).or_default().push_back(chunk.id);
+    }
+
+    let mut plan = ChunkDeltaPlan::default();
+    for (index, hash) in new_hashes.iter().enumerate() {
+        match available.get_mut(hash.as_str()).and_then(|ids| ids.pop_front()) {
+            Some(id) => plan.reused.push((index, id)),
+            None => plan.embed.push(index),
+        }
+    }
+
+    let reused: Vec<Uuid> = plan.reused.iter().map(|(_, id)| *id).collect();
+    plan.removed = existing
+        .iter()
+        .map(|chunk| chunk.id)
+        .filter(|id| !reused.contains(id))
+        .collect();
+    plan
+}
+
+/// 文档块内容哈希
+fn chunk_hash(content: &str) -> String {
+    format!("{:x}", md5::compute(content))
+}
+
+/// 增量索引服务
+pub struct IncrementalIndexService {
+    db: DatabaseConnection,
+    vector_stores: VectorStoreRegistry,
+}
+
+impl IncrementalIndexService {
+    /// 创建增量索引服务
+    pub fn new(db: DatabaseConnection, vector_stores: VectorStoreRegistry) -> Self {
+        Self { db, vector_stores }
+    }
+
+    /// 按文档当前内容重新分块，只为内容变更的块生成嵌入
+    ///
+    /// 未变更的块保留原记录与向量，仅更新序号与位置；嵌入模型变更后原有向量不再复用。
+    /// 启用个人信息策略时，按处理后的内容比对，被排除的块只保留记录、不生成嵌入。
+    #[instrument(skip(self, document, chunker, vectorizer), fields(document_id = %document.id))]
+    pub async fn reindex_document(
+        &self,
+        document: &document::Model,
+        chunker: &dyn DocumentChunker,
+        vectorizer: &dyn DocumentVectorizer,
+    ) -> Result<DeltaReindexReport, AiStudioError> {
+        let knowledge_base = KnowledgeBase::find_by_id(document.knowledge_base_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("知识库"))?;
+        let pii_policy = knowledge_base.get_config().map(|config| config.pii_policy).unwrap_or_default();
+        let model_name = knowledge_base.embedding_model;
+
+        // 正文可能按租户密钥加密，分块前透明解密
+        let content = TenantEncryptionService::from_config(self.db.clone())?
+            .decrypt(&document.content)
+            .await?;
+        let mut chunks = chunker.chunk_document(&extracted_text(document, content)).await?;
+        let overrides = if pii_policy.enabled {
+            PiiPolicyService::new(self.db.clone()).overrides(document.knowledge_base_id).await?
+        } else {
+            HashMap::new()
+        };
+        let treatments: Vec<PiiTreatment> = chunks
+            .iter_mut()
+            .map(|chunk| {
+                let treatment = apply_pii_policy(&pii_policy, &overrides, &chunk.content);
+                chunk.content = treatment.content.clone();
+                treatment
+            })
+            .collect();
+        let hashes: Vec<String> = chunks.iter().map(|chunk| chunk_hash(&chunk.content)).collect();
+        // 被排除的块不参与复用，避免沿用此前写入索引的向量
+        let plan_keys: Vec<String> = hashes
+            .iter()
+            .zip(&treatments)
+            .map(|(hash, treatment)| if treatment.excluded() { format!("excluded:{}", hash) } else { hash.clone() })
+            .collect();
+        let existing = self.existing_chunks(document.id, &model_name).await?;
+        let plan = plan_chunk_delta(&existing, &plan_keys);
+        let (excluded, embed): (Vec<usize>, Vec<usize>) =
+            plan.embed.iter().partition(|&&index| treatments[index].excluded());
+
+        // 只为新增或变更的块生成嵌入
+        let mut to_embed: Vec<DocumentChunk> = embed.iter().map(|&index| chunks[index].clone()).collect();
+        if !to_embed.is_empty() {
+            vectorizer.vectorize_chunks(&mut to_embed).await?;
+        }
+        for (&index, chunk) in embed.iter().zip(to_embed) {
+            chunks[index] = chunk;
+        }
+
+        // 外部向量存储中的旧向量需显式删除，embeddings 记录随文档块级联删除
+        let store = self.vector_stores.for_knowledge_base(document.knowledge_base_id).await?;
+        let removed_embeddings: Vec<Uuid> = existing
+            .iter()
+            .filter(|chunk| plan.removed.contains(&chunk.id))
+            .filter_map(|chunk| chunk.embedding_id)
+            .collect();
+        if !removed_embeddings.is_empty() {
+            store.delete(document.knowledge_base_id, &removed_embeddings).await?;
+        }
+
+        let txn = self.db.begin().await?;
+        if !plan.removed.is_empty() {
+            DocumentChunkEntity::delete_many()
+                .filter(document_chunk::Column::Id.is_in(plan.removed.clone()))
+                .exec(&txn)
+                .await?;
+        }
+        // 先将沿用块的序号移到负数区间，避免与 (document_id, chunk_index) 唯一索引冲突
+        txn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE document_chunks SET chunk_index = -chunk_index - 1 WHERE document_id = $1",
+            [document.id.into()],
+        ))
+        .await?;
+
+        let now = Utc::now();
+        for &(index, chunk_id) in &plan.reused {
+            let chunk = &chunks[index];
+            document_chunk::ActiveModel {
+                id: Set(chunk_id),
+                chunk_index: Set(index as i32),
+                metadata: Set(serde_json::to_value(chunk_metadata(chunk, &treatments[index]))?),
+                position_info: Set(serde_json::to_value(position_info(chunk))?),
+                updated_at: Set(now.into()),
+                ..Default::default()
+            }
+            .update(&txn)
+            .await?;
+        }
+
+        let mut points = Vec::with_capacity(embed.len());
+        for &index in &plan.embed {
+            let chunk = &chunks[index];
+            let chunk_id = Uuid::new_v4();
+            document_chunk::ActiveModel {
+                id: Set(chunk_id),
+                document_id: Set(document.id),
+                knowledge_base_id: Set(document.knowledge_base_id),
+                chunk_index: Set(index as i32),
+                content: Set(chunk.content.clone()),
+                title: Set(None),
+                summary: Set(None),
+                status: Set(document_chunk::ChunkStatus::Completed),
+                content_length: Set(chunk.content.len() as i32),
+                word_count: Set(chunk.content.split_whitespace().count() as i32),
+                content_hash: Set(hashes[index].clone()),
+                metadata: Set(serde_json::to_value(chunk_metadata(chunk, &treatments[index]))?),
+                position_info: Set(serde_json::to_value(position_info(chunk))?),
+                processing_started_at: Set(None),
+                processing_completed_at: Set(Some(now.into())),
+                error_message: Set(None),
+                clearance: Set(document.clearance.clone()),
+                created_at: Set(now.into()),
+                updated_at: Set(now.into()),
+            }
+            .insert(&txn)
+            .await?;
+
+            if treatments[index].excluded() {
+                continue;
+            }
+            let vector = chunk.embedding.clone()
+                .ok_or_else(|| AiStudioError::ai("文档块缺少嵌入向量"))?;
+            let embedding_id = Uuid::new_v4();
+            embedding::ActiveModel {
+                id: Set(embedding_id),
+                chunk_id: Set(chunk_id),
+                document_id: Set(document.id),
+                knowledge_base_id: Set(document.knowledge_base_id),
+                embedding_type: Set(embedding::EmbeddingType::Text),
+                status: Set(embedding::EmbeddingStatus::Completed),
+                vector: Set(None),
+                dimension: Set(vector.len() as i32),
+                model_name: Set(model_name.clone()),
+                model_version: Set("1".to_string()),
+                source_text: Set(chunk.content.clone()),
+                text_hash: Set(hashes[index].clone()),
+                metadata: Set(serde_json::to_value(embedding::EmbeddingMetadata::default())?),
+                processing_started_at: Set(None),
+                processing_completed_at: Set(Some(now.into())),
+                error_message: Set(None),
+                created_at: Set(now.into()),
+                updated_at: Set(now.into()),
+            }
+            .insert(&txn)
+            .await?;
+
+            points.push(VectorPoint { id: embedding_id, chunk_id, document_id: document.id, vector });
+        }
+
+        document::ActiveModel {
+            id: Set(document.id),
+            chunk_count: Set(chunks.len() as i32),
+            status: Set(document::DocumentStatus::Completed),
+            processing_completed_at: Set(Some(now.into())),
+            error_message: Set(None),
+            ..Default::default()
+        }
+        .update(&txn)
+        .await?;
+        txn.commit().await?;
+
+        if !points.is_empty() {
+            store.upsert(document.knowledge_base_id, &points).await?;
+        }
+
+        let report = DeltaReindexReport {
+            document_id: document.id,
+            total_chunks: chunks.len(),
+            reused_chunks: plan.reused.len(),
+            embedded_chunks: embed.len(),
+            removed_chunks: plan.removed.len(),
+            pii_chunks: treatments.iter().filter(|treatment| treatment.pii.is_some()).count(),
+            excluded_chunks: excluded.len(),
+        };
+        record_report(&report);
+        info!(
+            "文档增量重建索引完成: document_id={}, 块数={}, 沿用={}, 重新嵌入={}, 删除={}, 含个人信息={}, 排除={}",
+            document.id, report.total_chunks, report.reused_chunks, report.embedded_chunks, report.removed_chunks,
+            report.pii_chunks, report.excluded_chunks
+        );
+        Ok(report)
+    }
+
+    /// 读取文档现有的块及其在指定模型下已完成的嵌入
+    async fn existing_chunks(&self, document_id: Uuid, model_name: &str) -> Result<Vec<ExistingChunk>, AiStudioError> {
+        let rows = self
+            .db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT c.id, c.content_hash, e.id AS embedding_id
+                FROM document_chunks c
+                LEFT JOIN embeddings e
+                    ON e.chunk_id = c.id AND e.model_name = $2 AND e.status = 'completed'
+                WHERE c.document_id = $1
+                ORDER BY c.chunk_index
+                "#,
+                [document_id.into(), model_name.into()],
+            ))
+            .await?;
+
+        let mut chunks: Vec<ExistingChunk> = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id: Uuid = row.try_get("", "id")?;
+            // 同一块存在多条嵌入时只取一条
+            if chunks.last().is_some_and(|chunk| chunk.id == id) {
+                continue;
+            }
+            chunks.push(ExistingChunk {
+                id,
+                content_hash: row.try_get("", "content_hash")?,
+                embedding_id: row.try_get("", "embedding_id")?,
+            });
+        }
+        Ok(chunks)
+    }
+}
+
+/// 在后台按文档的分块配置增量重建索引，失败时将文档标记为处理失败
+pub fn spawn_reindex(db: DatabaseConnection, client_manager: RigAiClientManager, doc: document::Model) {
+    tokio::spawn(async move {
+        let mut chunker_config = ChunkerConfig::default();
+        if let Ok(processing_config) = doc.get_processing_config() {
+            chunker_config.max_chunk_size = processing_config.chunking_config.chunk_size as usize;
+            chunker_config.overlap_size = processing_config.chunking_config.overlap_size as usize;
+        }
+        let chunker = HybridChunker::new(chunker_config);
+        let vectorizer = AiVectorizer::new(client_manager);
+        let service = IncrementalIndexService::new(
+            db.clone(),
+            VectorStoreRegistry::new(db.clone(), ConfigLoader::get().vector.clone()),
+        );
+
+        if let Err(e) = service.reindex_document(&doc, &chunker, &vectorizer).await {
+            error!("文档增量重建索引失败: id={}, error={}", doc.id, e);
+            let failed = document::ActiveModel {
+                id: Set(doc.id),
+                status: Set(document::DocumentStatus::Failed),
+                error_message: Set(Some(e.to_string())),
+                ..Default::default()
+            };
+            if let Err(e) = failed.update(&db).await {
+                error!("更新文档处理状态失败: id={}, error={}", doc.id, e);
+            }
+        }
+    });
+}
+
+fn record_report(report: &DeltaReindexReport) {
+    DELTA_COUNTERS.documents.fetch_add(1, Ordering::Relaxed);
+    DELTA_COUNTERS.chunks.fetch_add(report.total_chunks as u64, Ordering::Relaxed);
+    DELTA_COUNTERS.reused.fetch_add(report.reused_chunks as u64, Ordering::Relaxed);
+    DELTA_COUNTERS.embedded.fetch_add(report.embedded_chunks as u64, Ordering::Relaxed);
+    DELTA_COUNTERS.removed.fetch_add(report.removed_chunks as u64, Ordering::Relaxed);
+}
+
+/// 存储用元数据，附带个人信息检测结果，标记的类型同时写入标签
+fn chunk_metadata(chunk: &DocumentChunk, treatment: &PiiTreatment) -> document_chunk::ChunkMetadata {
+    let mut metadata = chunk.storage_metadata();
+    if let Some(pii) = &treatment.pii {
+        metadata.tags.extend(pii.kinds.iter().map(|kind| format!("pii:{}", kind.as_str())));
+    }
+    metadata.pii = treatment.pii.clone();
+    metadata
+}
+
+fn position_info(chunk: &DocumentChunk) -> document_chunk::PositionInfo {
+    document_chunk::PositionInfo {
+        start_offset: chunk.position.start_char as u32,
+        end_offset: chunk.position.end_char as u32,
+        start_line: chunk.position.start_line.map(|line| line as u32),
+        end_line: chunk.position.end_line.map(|line| line as u32),
+        ..Default::default()
+    }
+}
+
+/// 以文档当前正文（已解密）构造分块输入
+fn extracted_text(document: &document::Model, content: String) -> ExtractedText {
+    let word_count = content.split_whitespace().count() as u32;
+    ExtractedText {
+        content,
+        metadata: DocumentMetadata {
+            title: Some(document.title.clone()),
+            author: None,
+            subject: None,
+            keywords: None,
+            created_date: Some(document.created_at.with_timezone(&Utc)),
+            modified_date: Some(document.updated_at.with_timezone(&Utc)),
+            page_count: None,
+            word_count: Some(word_count),
+            language: None,
+            format: document.mime_type.clone().unwrap_or_else(|| "text/plain".to_string()),
+            file_size: document.file_size.max(0) as u64,
+            custom_properties: HashMap::new(),
+        },
+        pages: None,
+        processing_info: ProcessingInfo {
+            processor_type: "document".to_string(),
+            processing_time_ms: 0,
+            success: true,
+            warnings: Vec::new(),
+            errors: Vec::new(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn existing(id: u128, hash: &str, embedded: bool) -> ExistingChunk {
+        ExistingChunk {
+            id: Uuid::from_u128(id),
+            content_hash: hash.to_string(),
+            embedding_id: embedded.then(|| Uuid::from_u128(id + 100)),
+        }
+    }
+
+    #[test]
+    fn test_plan_chunk_delta() {
+        let old = vec![
+            existing(1, "a", true),
+            existing(2, "b", true),
+            existing(3, "c", true),
+            existing(4, "a", true),
+            existing(5, "d", false),
+        ];
+        let new: Vec<String> = ["a", "x", "c", "a", "a", "d"].iter().map(|s| s.to_string()).collect();
+
+        let plan = plan_chunk_delta(&old, &new);
+        assert_eq!(plan.reused, vec![
+            (0, Uuid::from_u128(1)),
+            (2, Uuid::from_u128(3)),
+            (3, Uuid::from_u128(4)),
+        ]);
+        // 第三个 "a" 没有可复用的旧块，"d" 没有已完成的嵌入
+        assert_eq!(plan.embed, vec![1, 4, 5]);
+        assert_eq!(plan.removed, vec![Uuid::from_u128(2), Uuid::from_u128(5)]);
+    }
+}
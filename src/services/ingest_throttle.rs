@@ -0,0 +1,205 @@
+// 导入类接口限流服务
+// 按 API 密钥维护令牌桶，限制文档导入、重新处理与重建索引等会触发向量化的请求
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::IngestThrottleConfig;
+
+/// 长时间未使用的令牌桶在清理时移除的阈值（秒）
+const IDLE_BUCKET_SECS: f64 = 3600.0;
+
+/// 全局令牌桶表，同一进程内所有导入类接口共享
+static INGEST_THROTTLE: Lazy<IngestThrottle> = Lazy::new(IngestThrottle::default);
+
+/// 令牌桶
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    /// 当前令牌数
+    tokens: f64,
+    /// 上次补充令牌的时间
+    last_refill: Instant,
+}
+
+/// 令牌桶判定结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenBucketDecision {
+    /// 是否放行
+    pub allowed: bool,
+    /// 桶容量
+    pub capacity: u32,
+    /// 判定后剩余的令牌数
+    pub remaining: f64,
+    /// 本次请求消耗的令牌数
+    pub cost: u32,
+    /// 剩余令牌足够本次请求还需等待的秒数，放行时为 0
+    pub retry_after_seconds: u64,
+    /// 令牌补满还需的秒数
+    pub reset_after_seconds: u64,
+}
+
+impl TokenBucket {
+    /// 创建装满令牌的令牌桶
+    pub fn full(capacity: u32, now: Instant) -> Self {
+        Self { tokens: capacity as f64, last_refill: now }
+    }
+
+    /// 补充令牌后尝试取出 `cost` 个令牌，令牌不足时不扣减
+    pub fn try_acquire(&mut self, cost: u32, config: &IngestThrottleConfig, now: Instant) -> TokenBucketDecision {
+        let capacity = config.burst as f64;
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * config.refill_per_second).min(capacity);
+        self.last_refill = now;
+
+        let needed = cost as f64;
+        let allowed = self.tokens >= needed;
+        if allowed {
+            self.tokens -= needed;
+        }
+        let seconds_until = |target: f64| -> u64 {
+            if self.tokens >= target || config.refill_per_second <= 0.0 {
+                0
+            } else {
+                ((target - self.tokens) / config.refill_per_second).ceil() as u64
+            }
+        };
+
+        TokenBucketDecision {
+            allowed,
+            capacity: config.burst,
+            remaining: self.tokens,
+            cost,
+            retry_after_seconds: if allowed { 0 } else { seconds_until(needed) },
+            reset_after_seconds: seconds_until(capacity),
+        }
+    }
+
+    /// 距上次补充令牌的秒数
+    fn idle_secs(&self, now: Instant) -> f64 {
+        now.saturating_duration_since(self.last_refill).as_secs_f64()
+    }
+}
+
+/// 按 API 密钥维护的令牌桶表
+///
+/// 令牌桶保存在进程内存中，多实例部署时每个实例分别计数。
+#[derive(Default)]
+pub struct IngestThrottle {
+    buckets: Mutex<HashMap<Uuid, TokenBucket>>,
+}
+
+impl IngestThrottle {
+    /// 全局令牌桶表
+    pub fn global() -> &'static IngestThrottle {
+        &INGEST_THROTTLE
+    }
+
+    /// 为 API 密钥取出 `cost` 个令牌
+    ///
+    /// 请求消耗超过桶容量时按桶容量计算，避免大批量请求永远无法通过。
+    pub fn acquire(&self, api_key_id: Uuid, cost: u32, config: &IngestThrottleConfig) -> TokenBucketDecision {
+        let now = Instant::now();
+        let cost = cost.clamp(1, config.burst.max(1));
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() > 10_000 {
+            buckets.retain(|_, bucket| bucket.idle_secs(now) < IDLE_BUCKET_SECS);
+        }
+        buckets
+            .entry(api_key_id)
+            .or_insert_with(|| TokenBucket::full(config.burst, now))
+            .try_acquire(cost, config, now)
+    }
+}
+
+/// 导入类接口限流超限详情，作为 429 响应中错误的 details 返回
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct IngestThrottleExceeded {
+    /// 触发限流的 API 密钥
+    pub api_key_id: Uuid,
+    /// 本次请求需要的令牌数
+    pub cost: u32,
+    /// 桶容量（允许的突发请求数）
+    pub burst: u32,
+    /// 当前剩余的令牌数
+    pub available: u32,
+    /// 每秒补充的令牌数
+    pub refill_per_second: f64,
+    /// 建议的重试等待时间（秒）
+    pub retry_after_seconds: u64,
+}
+
+impl IngestThrottleExceeded {
+    /// 根据未通过的判定结果构建超限详情
+    pub fn new(api_key_id: Uuid, decision: &TokenBucketDecision, config: &IngestThrottleConfig) -> Self {
+        Self {
+            api_key_id,
+            cost: decision.cost,
+            burst: decision.capacity,
+            available: decision.remaining.floor() as u32,
+            refill_per_second: config.refill_per_second,
+            retry_after_seconds: decision.retry_after_seconds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn config(burst: u32, refill_per_second: f64) -> IngestThrottleConfig {
+        IngestThrottleConfig {
+            enabled: true,
+            burst,
+            refill_per_second,
+            batch_cost: 5,
+        }
+    }
+
+    #[test]
+    fn test_token_bucket_burst_and_refill() {
+        let config = config(3, 0.5);
+        let start = Instant::now();
+        let mut bucket = TokenBucket::full(config.burst, start);
+
+        for _ in 0..3 {
+            assert!(bucket.try_acquire(1, &config, start).allowed);
+        }
+        let denied = bucket.try_acquire(1, &config, start);
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after_seconds, 2);
+        assert_eq!(denied.reset_after_seconds, 6);
+
+        let later = start + Duration::from_secs(2);
+        let decision = bucket.try_acquire(1, &config, later);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0.0);
+
+        // 长时间空闲后令牌不超过桶容量
+        let decision = bucket.try_acquire(2, &config, later + Duration::from_secs(3600));
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 1.0);
+    }
+
+    #[test]
+    fn test_throttle_is_keyed_by_api_key() {
+        let config = config(2, 0.0);
+        let throttle = IngestThrottle::default();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(throttle.acquire(a, 1, &config).allowed);
+        assert!(throttle.acquire(a, 1, &config).allowed);
+        assert!(!throttle.acquire(a, 1, &config).allowed);
+        assert!(throttle.acquire(b, 1, &config).allowed);
+
+        // 超过桶容量的消耗按桶容量计算
+        let decision = throttle.acquire(b, 100, &config);
+        assert_eq!(decision.cost, 2);
+        assert!(!decision.allowed);
+    }
+}
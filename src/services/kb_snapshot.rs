@@ -0,0 +1,484 @@
+// 知识库快照服务
+// 将知识库当前的文档、文档块与向量冻结为命名快照，支持按快照检索与快照间差异比较
+
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection,
+    EntityTrait, QueryFilter, QueryOrder, Set, Statement, TransactionTrait, Value,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::{kb_snapshot, kb_snapshot_document, knowledge_base, KbSnapshot, KbSnapshotDocument, KnowledgeBase};
+use crate::errors::AiStudioError;
+use crate::services::deadline;
+
+/// 快照名称最大长度
+const MAX_SNAPSHOT_NAME_LENGTH: usize = 100;
+
+/// 知识库快照响应
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct KbSnapshotResponse {
+    /// 快照 ID
+    pub id: Uuid,
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 快照名称
+    pub name: String,
+    /// 快照描述
+    pub description: Option<String>,
+    /// 文档数
+    pub document_count: i32,
+    /// 文档块数
+    pub chunk_count: i32,
+    /// 创建人
+    pub created_by: Option<Uuid>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<kb_snapshot::Model> for KbSnapshotResponse {
+    fn from(model: kb_snapshot::Model) -> Self {
+        Self {
+            id: model.id,
+            knowledge_base_id: model.knowledge_base_id,
+            name: model.name,
+            description: model.description,
+            document_count: model.document_count,
+            chunk_count: model.chunk_count,
+            created_by: model.created_by,
+            created_at: model.created_at.with_timezone(&Utc),
+        }
+    }
+}
+
+/// 文档指纹，用于比较快照间的文档成员
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DocumentFingerprint {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 文档内容哈希
+    pub content_hash: String,
+}
+
+impl From<kb_snapshot_document::Model> for DocumentFingerprint {
+    fn from(model: kb_snapshot_document::Model) -> Self {
+        Self {
+            document_id: model.document_id,
+            title: model.title,
+            content_hash: model.content_hash,
+        }
+    }
+}
+
+/// 快照差异
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct KbSnapshotDiff {
+    /// 起始快照名称
+    pub from: String,
+    /// 目标快照名称，为空表示知识库当前状态
+    pub to: Option<String>,
+    /// 新增的文档
+    pub added: Vec<DocumentFingerprint>,
+    /// 移除的文档
+    pub removed: Vec<DocumentFingerprint>,
+    /// 内容发生变化的文档（目标侧指纹）
+    pub modified: Vec<DocumentFingerprint>,
+    /// 未变化的文档数
+    pub unchanged: usize,
+}
+
+/// 快照检索命中的文档块
+#[derive(Debug, Clone)]
+pub struct SnapshotChunkHit {
+    /// 文档块 ID
+    pub chunk_id: Uuid,
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 块序号
+    pub chunk_index: i32,
+    /// 块内容
+    pub content: String,
+    /// 块元数据
+    pub metadata: serde_json::Value,
+    /// 余弦相似度
+    pub similarity: f32,
+}
+
+/// 知识库快照服务
+pub struct KbSnapshotService {
+    db: DatabaseConnection,
+}
+
+impl KbSnapshotService {
+    /// 创建新的知识库快照服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 创建快照
+    ///
+    /// 在单个事务内复制文档指纹、文档块及其最新向量，之后对知识库的修改不影响快照内容。
+    #[instrument(skip(self, description))]
+    pub async fn create_snapshot(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        name: &str,
+        description: Option<String>,
+        created_by: Uuid,
+    ) -> Result<KbSnapshotResponse, AiStudioError> {
+        let name = validate_snapshot_name(name)?;
+        self.ensure_knowledge_base(tenant_id, knowledge_base_id).await?;
+
+        if self.find_snapshot(knowledge_base_id, &name).await?.is_some() {
+            return Err(AiStudioError::conflict(format!("快照已存在: {}", name)));
+        }
+
+        let txn = self.db.begin().await?;
+        let snapshot_id = Uuid::new_v4();
+
+        kb_snapshot::ActiveModel {
+            id: Set(snapshot_id),
+            tenant_id: Set(tenant_id),
+            knowledge_base_id: Set(knowledge_base_id),
+            name: Set(name.clone()),
+            description: Set(description),
+            document_count: Set(0),
+            chunk_count: Set(0),
+            created_by: Set(Some(created_by)),
+            created_at: Set(Utc::now().fixed_offset()),
+        }
+        .insert(&txn)
+        .await?;
+
+        let documents = txn
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO kb_snapshot_documents (snapshot_id, document_id, title, file_type, content_hash, source_updated_at)
+                SELECT $1, id, title, file_type, md5(COALESCE(content, '')), updated_at
+                FROM documents WHERE knowledge_base_id = $2
+                "#,
+                vec![snapshot_id.into(), knowledge_base_id.into()],
+            ))
+            .await?
+            .rows_affected();
+
+        let chunks = txn
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO kb_snapshot_chunks
+                    (snapshot_id, chunk_id, document_id, chunk_index, content, content_hash, metadata, model_name, vector)
+                SELECT $1, c.id, c.document_id, c.chunk_index, c.content, c.content_hash, c.metadata, e.model_name, e.vector
+                FROM document_chunks c
+                JOIN documents d ON d.id = c.document_id
+                LEFT JOIN LATERAL (
+                    SELECT model_name, vector FROM embeddings
+                    WHERE chunk_id = c.id ORDER BY created_at DESC LIMIT 1
+                ) e ON TRUE
+                WHERE d.knowledge_base_id = $2
+                "#,
+                vec![snapshot_id.into(), knowledge_base_id.into()],
+            ))
+            .await?
+            .rows_affected();
+
+        let mut active: kb_snapshot::ActiveModel = KbSnapshot::find_by_id(snapshot_id)
+            .one(&txn)
+            .await?
+            .ok_or_else(|| AiStudioError::internal("快照写入后未找到"))?
+            .into();
+        active.document_count = Set(documents as i32);
+        active.chunk_count = Set(chunks as i32);
+        let snapshot = active.update(&txn).await?;
+
+        txn.commit().await?;
+
+        info!(
+            knowledge_base_id = %knowledge_base_id,
+            snapshot = %snapshot.name,
+            documents,
+            chunks,
+            "知识库快照已创建"
+        );
+        Ok(snapshot.into())
+    }
+
+    /// 列出知识库的快照
+    #[instrument(skip(self))]
+    pub async fn list_snapshots(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+    ) -> Result<Vec<KbSnapshotResponse>, AiStudioError> {
+        let snapshots = KbSnapshot::find()
+            .filter(kb_snapshot::Column::TenantId.eq(tenant_id))
+            .filter(kb_snapshot::Column::KnowledgeBaseId.eq(knowledge_base_id))
+            .order_by_desc(kb_snapshot::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(snapshots.into_iter().map(Into::into).collect())
+    }
+
+    /// 按名称获取快照
+    #[instrument(skip(self))]
+    pub async fn get_snapshot(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        name: &str,
+    ) -> Result<kb_snapshot::Model, AiStudioError> {
+        self.find_snapshot(knowledge_base_id, name)
+            .await?
+            .filter(|snapshot| snapshot.tenant_id == tenant_id)
+            .ok_or_else(|| AiStudioError::not_found(format!("知识库快照 {}", name)))
+    }
+
+    /// 删除快照
+    #[instrument(skip(self))]
+    pub async fn delete_snapshot(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        name: &str,
+    ) -> Result<(), AiStudioError> {
+        let snapshot = self.get_snapshot(tenant_id, knowledge_base_id, name).await?;
+        KbSnapshot::delete_by_id(snapshot.id).exec(&self.db).await?;
+
+        info!(knowledge_base_id = %knowledge_base_id, snapshot = %name, "知识库快照已删除");
+        Ok(())
+    }
+
+    /// 比较两个快照的文档成员，`to` 为空时与知识库当前状态比较
+    #[instrument(skip(self))]
+    pub async fn diff_snapshots(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        from: &str,
+        to: Option<&str>,
+    ) -> Result<KbSnapshotDiff, AiStudioError> {
+        let from_snapshot = self.get_snapshot(tenant_id, knowledge_base_id, from).await?;
+        let from_documents = self.snapshot_documents(from_snapshot.id).await?;
+
+        let to_documents = match to {
+            Some(to) => {
+                let to_snapshot = self.get_snapshot(tenant_id, knowledge_base_id, to).await?;
+                self.snapshot_documents(to_snapshot.id).await?
+            }
+            None => self.live_documents(knowledge_base_id).await?,
+        };
+
+        let mut diff = diff_documents(&from_documents, &to_documents);
+        diff.from = from_snapshot.name;
+        diff.to = to.map(str::to_string);
+        Ok(diff)
+    }
+
+    /// 在快照中按向量检索文档块
+    #[instrument(skip(self, query_vector))]
+    pub async fn search_snapshot(
+        &self,
+        snapshot_id: Uuid,
+        query_vector: &[f32],
+        top_k: usize,
+        similarity_threshold: f32,
+    ) -> Result<Vec<SnapshotChunkHit>, AiStudioError> {
+        let vector = format!(
+            "[{}]",
+            query_vector.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(",")
+        );
+        let values: Vec<Value> = vec![
+            snapshot_id.into(),
+            vector.into(),
+            similarity_threshold.into(),
+            (top_k as i64).into(),
+        ];
+
+        let rows = deadline::query_all(
+            &self.db,
+            "kb_snapshot.search",
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT chunk_id, document_id, chunk_index, content, metadata,
+                       (1 - (vector <=> $2::vector))::REAL AS similarity
+                FROM kb_snapshot_chunks
+                WHERE snapshot_id = $1
+                    AND vector IS NOT NULL
+                    AND 1 - (vector <=> $2::vector) >= $3
+                ORDER BY vector <=> $2::vector
+                LIMIT $4
+                "#,
+                values,
+            ),
+        )
+        .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<SnapshotChunkHit, AiStudioError> {
+                Ok(SnapshotChunkHit {
+                    chunk_id: row.try_get("", "chunk_id")?,
+                    document_id: row.try_get("", "document_id")?,
+                    chunk_index: row.try_get("", "chunk_index")?,
+                    content: row.try_get("", "content")?,
+                    metadata: row.try_get("", "metadata")?,
+                    similarity: row.try_get("", "similarity")?,
+                })
+            })
+            .collect()
+    }
+
+    /// 获取快照中的文档记录
+    pub async fn snapshot_document_models(
+        &self,
+        snapshot_id: Uuid,
+    ) -> Result<Vec<kb_snapshot_document::Model>, AiStudioError> {
+        Ok(KbSnapshotDocument::find()
+            .filter(kb_snapshot_document::Column::SnapshotId.eq(snapshot_id))
+            .all(&self.db)
+            .await?)
+    }
+
+    async fn snapshot_documents(&self, snapshot_id: Uuid) -> Result<Vec<DocumentFingerprint>, AiStudioError> {
+        Ok(self.snapshot_document_models(snapshot_id)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn live_documents(&self, knowledge_base_id: Uuid) -> Result<Vec<DocumentFingerprint>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT id, title, md5(COALESCE(content, '')) AS content_hash
+                FROM documents WHERE knowledge_base_id = $1
+                "#,
+                vec![knowledge_base_id.into()],
+            ))
+            .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<DocumentFingerprint, AiStudioError> {
+                Ok(DocumentFingerprint {
+                    document_id: row.try_get("", "id")?,
+                    title: row.try_get("", "title")?,
+                    content_hash: row.try_get("", "content_hash")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn find_snapshot(
+        &self,
+        knowledge_base_id: Uuid,
+        name: &str,
+    ) -> Result<Option<kb_snapshot::Model>, AiStudioError> {
+        Ok(KbSnapshot::find()
+            .filter(kb_snapshot::Column::KnowledgeBaseId.eq(knowledge_base_id))
+            .filter(kb_snapshot::Column::Name.eq(name))
+            .one(&self.db)
+            .await?)
+    }
+
+    async fn ensure_knowledge_base(&self, tenant_id: Uuid, knowledge_base_id: Uuid) -> Result<(), AiStudioError> {
+        KnowledgeBase::find_by_id(knowledge_base_id)
+            .filter(knowledge_base::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .map(|_| ())
+            .ok_or_else(|| AiStudioError::not_found(format!("知识库 {}", knowledge_base_id)))
+    }
+}
+
+/// 校验快照名称，仅允许字母、数字及 `.`、`-`、`_`
+fn validate_snapshot_name(name: &str) -> Result<String, AiStudioError> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_SNAPSHOT_NAME_LENGTH {
+        return Err(AiStudioError::validation(
+            "name",
+            format!("快照名称长度必须在 1 到 {} 之间", MAX_SNAPSHOT_NAME_LENGTH),
+        ));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')) {
+        return Err(AiStudioError::validation("name", "快照名称只能包含字母、数字及 . - _"));
+    }
+    Ok(name.to_string())
+}
+
+/// 比较两组文档指纹
+fn diff_documents(from: &[DocumentFingerprint], to: &[DocumentFingerprint]) -> KbSnapshotDiff {
+    let from_map: HashMap<Uuid, &DocumentFingerprint> = from.iter().map(|d| (d.document_id, d)).collect();
+    let to_ids: std::collections::HashSet<Uuid> = to.iter().map(|d| d.document_id).collect();
+
+    let mut diff = KbSnapshotDiff::default();
+    for document in to {
+        match from_map.get(&document.document_id) {
+            None => diff.added.push(document.clone()),
+            Some(previous) if previous.content_hash != document.content_hash || previous.title != document.title => {
+                diff.modified.push(document.clone())
+            }
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+    diff.removed = from.iter()
+        .filter(|d| !to_ids.contains(&d.document_id))
+        .cloned()
+        .collect();
+
+    for list in [&mut diff.added, &mut diff.removed, &mut diff.modified] {
+        list.sort_by(|a, b| a.title.cmp(&b.title));
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(id: u128, title: &str, hash: &str) -> DocumentFingerprint {
+        DocumentFingerprint {
+            document_id: Uuid::from_u128(id),
+            title: title.to_string(),
+            content_hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_documents() {
+        let from = vec![doc(1, "a", "h1"), doc(2, "b", "h2"), doc(3, "c", "h3")];
+        let to = vec![doc(1, "a", "h1"), doc(2, "b", "h2-new"), doc(4, "d", "h4")];
+
+        let diff = diff_documents(&from, &to);
+        assert_eq!(diff.added, vec![doc(4, "d", "h4")]);
+        assert_eq!(diff.removed, vec![doc(3, "c", "h3")]);
+        assert_eq!(diff.modified, vec![doc(2, "b", "h2-new")]);
+        assert_eq!(diff.unchanged, 1);
+    }
+
+    #[test]
+    fn test_diff_identical_snapshots() {
+        let documents = vec![doc(1, "a", "h1"), doc(2, "b", "h2")];
+        let diff = diff_documents(&documents, &documents);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.modified.is_empty());
+        assert_eq!(diff.unchanged, 2);
+    }
+
+    #[test]
+    fn test_validate_snapshot_name() {
+        assert_eq!(validate_snapshot_name(" v2024.01-rc_1 ").unwrap(), "v2024.01-rc_1");
+        assert!(validate_snapshot_name("").is_err());
+        assert!(validate_snapshot_name("bad name").is_err());
+        assert!(validate_snapshot_name(&"x".repeat(101)).is_err());
+    }
+}
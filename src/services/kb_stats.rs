@@ -0,0 +1,346 @@
+// 知识库存储统计服务
+// 统计每个知识库的文档、文档块、嵌入数量与存储用量，夜间汇总后校正知识库计数与租户配额用量
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{NaiveDate, Utc};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QueryResult, Statement,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::{kb_storage_stat, KbStorageStat};
+use crate::errors::AiStudioError;
+use crate::services::quota::QuotaService;
+use crate::services::scheduler::PeriodicJob;
+
+/// 汇总任务执行间隔
+const ROLLUP_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+/// 知识库存储用量
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct KbStorageUsage {
+    /// 文档数
+    pub document_count: u64,
+    /// 文档块数
+    pub chunk_count: u64,
+    /// 向量嵌入数
+    pub embedding_count: u64,
+    /// 原始文件大小（字节）
+    pub file_bytes: u64,
+    /// 数据库占用（字节），含文档、文档块与嵌入行
+    pub database_bytes: u64,
+    /// 处理失败的文档数
+    pub failed_documents: u64,
+    /// 等待或正在处理的文档数
+    pub pending_documents: u64,
+}
+
+impl KbStorageUsage {
+    /// 计入存储配额的总字节数
+    pub fn total_bytes(&self) -> u64 {
+        self.file_bytes + self.database_bytes
+    }
+}
+
+/// 某一天的存储统计
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct KbStorageHistoryPoint {
+    /// 统计日期
+    pub date: NaiveDate,
+    /// 当天的存储用量
+    #[serde(flatten)]
+    pub usage: KbStorageUsage,
+}
+
+impl From<kb_storage_stat::Model> for KbStorageHistoryPoint {
+    fn from(model: kb_storage_stat::Model) -> Self {
+        Self {
+            date: model.stat_date,
+            usage: KbStorageUsage {
+                document_count: model.document_count.max(0) as u64,
+                chunk_count: model.chunk_count.max(0) as u64,
+                embedding_count: model.embedding_count.max(0) as u64,
+                file_bytes: model.file_bytes.max(0) as u64,
+                database_bytes: model.database_bytes.max(0) as u64,
+                failed_documents: model.failed_documents.max(0) as u64,
+                pending_documents: model.pending_documents.max(0) as u64,
+            },
+        }
+    }
+}
+
+/// 汇总结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KbStatsRollupReport {
+    /// 统计的知识库数
+    pub knowledge_bases: u64,
+    /// 校正配额用量的租户数
+    pub tenants: u64,
+}
+
+/// 单个知识库的统计行
+#[derive(Debug, Clone)]
+struct KbStatsRow {
+    knowledge_base_id: Uuid,
+    tenant_id: Uuid,
+    usage: KbStorageUsage,
+}
+
+/// 知识库存储统计服务
+pub struct KbStatsService {
+    db: DatabaseConnection,
+}
+
+impl KbStatsService {
+    /// 创建新的知识库统计服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 实时统计单个知识库的存储用量
+    #[instrument(skip(self))]
+    pub async fn current(&self, knowledge_base_id: Uuid) -> Result<KbStorageUsage, AiStudioError> {
+        Ok(self
+            .compute(Some(knowledge_base_id))
+            .await?
+            .into_iter()
+            .next()
+            .map(|row| row.usage)
+            .unwrap_or_default())
+    }
+
+    /// 最近若干天的汇总记录，按日期升序
+    #[instrument(skip(self))]
+    pub async fn history(&self, knowledge_base_id: Uuid, days: u32) -> Result<Vec<KbStorageHistoryPoint>, AiStudioError> {
+        let since = Utc::now().date_naive() - chrono::Duration::days(days as i64);
+        let rows = KbStorageStat::find()
+            .filter(kb_storage_stat::Column::KnowledgeBaseId.eq(knowledge_base_id))
+            .filter(kb_storage_stat::Column::StatDate.gt(since))
+            .order_by_asc(kb_storage_stat::Column::StatDate)
+            .all(&self.db)
+            .await?;
+        Ok(rows.into_iter().map(KbStorageHistoryPoint::from).collect())
+    }
+
+    /// 汇总全部知识库的当日统计
+    ///
+    /// 写入当日统计记录（同一天重复执行时覆盖），校正知识库上的文档与文档块计数，
+    /// 并用实际值覆盖各租户的知识库、文档与存储配额用量。
+    #[instrument(skip(self))]
+    pub async fn rollup(&self) -> Result<KbStatsRollupReport, AiStudioError> {
+        let rows = self.compute(None).await?;
+        let today = Utc::now().date_naive();
+
+        for row in &rows {
+            self.db
+                .execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    "INSERT INTO kb_storage_stats (tenant_id, knowledge_base_id, stat_date, document_count, chunk_count, \
+                     embedding_count, file_bytes, database_bytes, failed_documents, pending_documents, created_at) \
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW()) \
+                     ON CONFLICT (knowledge_base_id, stat_date) DO UPDATE SET \
+                     document_count = EXCLUDED.document_count, chunk_count = EXCLUDED.chunk_count, \
+                     embedding_count = EXCLUDED.embedding_count, file_bytes = EXCLUDED.file_bytes, \
+                     database_bytes = EXCLUDED.database_bytes, failed_documents = EXCLUDED.failed_documents, \
+                     pending_documents = EXCLUDED.pending_documents, created_at = EXCLUDED.created_at",
+                    [
+                        row.tenant_id.into(),
+                        row.knowledge_base_id.into(),
+                        today.into(),
+                        (row.usage.document_count as i64).into(),
+                        (row.usage.chunk_count as i64).into(),
+                        (row.usage.embedding_count as i64).into(),
+                        (row.usage.file_bytes as i64).into(),
+                        (row.usage.database_bytes as i64).into(),
+                        (row.usage.failed_documents as i64).into(),
+                        (row.usage.pending_documents as i64).into(),
+                    ],
+                ))
+                .await?;
+
+            self.db
+                .execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    "UPDATE knowledge_bases SET document_count = $2, chunk_count = $3, total_size_bytes = $4 WHERE id = $1",
+                    [
+                        row.knowledge_base_id.into(),
+                        (row.usage.document_count.min(i32::MAX as u64) as i32).into(),
+                        (row.usage.chunk_count.min(i32::MAX as u64) as i32).into(),
+                        (row.usage.file_bytes as i64).into(),
+                    ],
+                ))
+                .await?;
+        }
+
+        let quota = QuotaService::new(self.db.clone());
+        let tenants = tenant_totals(&rows);
+        for (tenant_id, (knowledge_bases, usage)) in &tenants {
+            // 单个租户的配额字段损坏不影响其他租户
+            if let Err(e) = quota
+                .sync_content_usage(
+                    *tenant_id,
+                    *knowledge_bases,
+                    usage.document_count.min(u32::MAX as u64) as u32,
+                    usage.total_bytes(),
+                )
+                .await
+            {
+                warn!(tenant_id = %tenant_id, error = %e, "校正租户配额用量失败");
+            }
+        }
+
+        let report = KbStatsRollupReport {
+            knowledge_bases: rows.len() as u64,
+            tenants: tenants.len() as u64,
+        };
+        info!(knowledge_bases = report.knowledge_bases, tenants = report.tenants, "知识库存储统计汇总完成");
+        Ok(report)
+    }
+
+    /// 统计知识库的存储用量，未指定知识库时统计全部
+    async fn compute(&self, knowledge_base_id: Option<Uuid>) -> Result<Vec<KbStatsRow>, AiStudioError> {
+        let (kb_filter, row_filter) = match knowledge_base_id {
+            Some(_) => ("WHERE kb.id = $1", "WHERE knowledge_base_id = $1"),
+            None => ("", ""),
+        };
+        let sql = format!(
+            r#"
+            SELECT kb.id AS knowledge_base_id, kb.tenant_id,
+                   COALESCE(d.document_count, 0) AS document_count,
+                   COALESCE(c.chunk_count, 0) AS chunk_count,
+                   COALESCE(e.embedding_count, 0) AS embedding_count,
+                   COALESCE(d.file_bytes, 0) AS file_bytes,
+                   COALESCE(d.row_bytes, 0) + COALESCE(c.row_bytes, 0) + COALESCE(e.row_bytes, 0) AS database_bytes,
+                   COALESCE(d.failed_documents, 0) AS failed_documents,
+                   COALESCE(d.pending_documents, 0) AS pending_documents
+            FROM knowledge_bases kb
+            LEFT JOIN (
+                SELECT knowledge_base_id, COUNT(*) AS document_count,
+                       SUM(file_size)::BIGINT AS file_bytes,
+                       SUM(pg_column_size(documents.*))::BIGINT AS row_bytes,
+                       COUNT(*) FILTER (WHERE status::text = 'failed') AS failed_documents,
+                       COUNT(*) FILTER (WHERE status::text IN ('pending', 'processing')) AS pending_documents
+                FROM documents {row_filter} GROUP BY knowledge_base_id
+            ) d ON d.knowledge_base_id = kb.id
+            LEFT JOIN (
+                SELECT knowledge_base_id, COUNT(*) AS chunk_count,
+                       SUM(pg_column_size(document_chunks.*))::BIGINT AS row_bytes
+                FROM document_chunks {row_filter} GROUP BY knowledge_base_id
+            ) c ON c.knowledge_base_id = kb.id
+            LEFT JOIN (
+                SELECT knowledge_base_id, COUNT(*) AS embedding_count,
+                       SUM(pg_column_size(embeddings.*))::BIGINT AS row_bytes
+                FROM embeddings {row_filter} GROUP BY knowledge_base_id
+            ) e ON e.knowledge_base_id = kb.id
+            {kb_filter}
+            "#,
+        );
+        let values: Vec<sea_orm::Value> = knowledge_base_id.map(|id| vec![id.into()]).unwrap_or_default();
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(DatabaseBackend::Postgres, &sql, values))
+            .await?;
+        rows.iter().map(stats_row).collect()
+    }
+}
+
+fn stats_row(row: &QueryResult) -> Result<KbStatsRow, AiStudioError> {
+    let count = |column: &str| -> Result<u64, AiStudioError> {
+        Ok(row.try_get::<i64>("", column)?.max(0) as u64)
+    };
+    Ok(KbStatsRow {
+        knowledge_base_id: row.try_get("", "knowledge_base_id")?,
+        tenant_id: row.try_get("", "tenant_id")?,
+        usage: KbStorageUsage {
+            document_count: count("document_count")?,
+            chunk_count: count("chunk_count")?,
+            embedding_count: count("embedding_count")?,
+            file_bytes: count("file_bytes")?,
+            database_bytes: count("database_bytes")?,
+            failed_documents: count("failed_documents")?,
+            pending_documents: count("pending_documents")?,
+        },
+    })
+}
+
+/// 按租户汇总知识库数与存储用量
+fn tenant_totals(rows: &[KbStatsRow]) -> BTreeMap<Uuid, (u32, KbStorageUsage)> {
+    let mut totals: BTreeMap<Uuid, (u32, KbStorageUsage)> = BTreeMap::new();
+    for row in rows {
+        let (knowledge_bases, usage) = totals.entry(row.tenant_id).or_default();
+        *knowledge_bases += 1;
+        usage.document_count += row.usage.document_count;
+        usage.chunk_count += row.usage.chunk_count;
+        usage.embedding_count += row.usage.embedding_count;
+        usage.file_bytes += row.usage.file_bytes;
+        usage.database_bytes += row.usage.database_bytes;
+        usage.failed_documents += row.usage.failed_documents;
+        usage.pending_documents += row.usage.pending_documents;
+    }
+    totals
+}
+
+/// 知识库存储统计夜间汇总任务
+pub struct KbStatsRollupJob {
+    service: Arc<KbStatsService>,
+}
+
+impl KbStatsRollupJob {
+    /// 创建新的知识库统计汇总任务
+    pub fn new(service: Arc<KbStatsService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for KbStatsRollupJob {
+    fn name(&self) -> &str {
+        "kb_storage_stats_rollup"
+    }
+
+    fn interval(&self) -> Duration {
+        ROLLUP_INTERVAL
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        self.service.rollup().await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(tenant_id: Uuid, documents: u64, file_bytes: u64, database_bytes: u64) -> KbStatsRow {
+        KbStatsRow {
+            knowledge_base_id: Uuid::new_v4(),
+            tenant_id,
+            usage: KbStorageUsage {
+                document_count: documents,
+                file_bytes,
+                database_bytes,
+                failed_documents: 1,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_tenant_totals() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let totals = tenant_totals(&[row(a, 3, 100, 20), row(a, 2, 50, 10), row(b, 1, 0, 5)]);
+
+        let (knowledge_bases, usage) = &totals[&a];
+        assert_eq!(*knowledge_bases, 2);
+        assert_eq!(usage.document_count, 5);
+        assert_eq!(usage.failed_documents, 2);
+        assert_eq!(usage.total_bytes(), 180);
+        assert_eq!(totals[&b].1.total_bytes(), 5);
+    }
+}
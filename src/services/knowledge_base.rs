@@ -195,6 +195,7 @@ impl KnowledgeBaseService for KnowledgeBaseServiceImpl {
             last_indexed_at: sea_orm::Set(None),
             created_at: sea_orm::Set(now),
             updated_at: sea_orm::Set(now),
+            revision: sea_orm::Set(1),
         };
         
         let kb = KnowledgeBase::insert(new_kb)
@@ -0,0 +1,499 @@
+// 法律保留服务
+// 对文档、知识库或会话设置法律保留：保留期间记录不可删除或修改（包括批量操作与数据保留任务），
+// 直至授权角色解除；设置、解除与被拦截的修改均记入审计事件
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult, Statement, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{info, instrument, warn};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::db::entities::document;
+use crate::errors::AiStudioError;
+use crate::services::pii_policy::COMPLIANCE_OFFICER_ROLE;
+
+/// 可设置与解除法律保留的角色
+pub const LEGAL_HOLD_ROLE: &str = COMPLIANCE_OFFICER_ROLE;
+
+/// 保留理由最大长度
+const MAX_REASON_CHARS: usize = 2000;
+
+/// 法律保留对象类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LegalHoldTarget {
+    /// 单个文档
+    Document,
+    /// 知识库，保留期间其下所有文档同样不可修改
+    KnowledgeBase,
+    /// 问答或 Agent 会话，按会话 ID 保留问答记录与执行记录
+    Conversation,
+}
+
+impl LegalHoldTarget {
+    /// 类型名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LegalHoldTarget::Document => "document",
+            LegalHoldTarget::KnowledgeBase => "knowledge_base",
+            LegalHoldTarget::Conversation => "conversation",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self, AiStudioError> {
+        match value {
+            "document" => Ok(LegalHoldTarget::Document),
+            "knowledge_base" => Ok(LegalHoldTarget::KnowledgeBase),
+            "conversation" => Ok(LegalHoldTarget::Conversation),
+            other => Err(AiStudioError::internal(format!("未知的法律保留对象类型: {}", other))),
+        }
+    }
+}
+
+/// 设置法律保留请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PlaceLegalHoldRequest {
+    /// 对象类型
+    pub target_type: LegalHoldTarget,
+    /// 对象 ID（文档或知识库 ID，或会话 ID）
+    pub target_id: String,
+    /// 保留理由
+    pub reason: String,
+    /// 案件编号
+    pub case_reference: Option<String>,
+}
+
+/// 解除法律保留请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ReleaseLegalHoldRequest {
+    /// 解除理由
+    pub reason: String,
+}
+
+/// 法律保留列表查询参数
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+pub struct LegalHoldQuery {
+    /// 按对象类型过滤
+    pub target_type: Option<LegalHoldTarget>,
+    /// 按对象 ID 过滤
+    pub target_id: Option<String>,
+    /// 是否包含已解除的保留
+    #[serde(default)]
+    pub include_released: bool,
+}
+
+/// 法律保留
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LegalHoldResponse {
+    /// 保留 ID
+    pub id: Uuid,
+    /// 对象类型
+    pub target_type: LegalHoldTarget,
+    /// 对象 ID
+    pub target_id: String,
+    /// 保留理由
+    pub reason: String,
+    /// 案件编号
+    pub case_reference: Option<String>,
+    /// 设置人
+    pub placed_by: Uuid,
+    /// 设置时间
+    pub placed_at: DateTime<Utc>,
+    /// 解除人
+    pub released_by: Option<Uuid>,
+    /// 解除时间，未解除时为空
+    pub released_at: Option<DateTime<Utc>>,
+    /// 解除理由
+    pub release_reason: Option<String>,
+}
+
+/// 法律保留审计事件
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LegalHoldEvent {
+    /// 事件 ID
+    pub id: Uuid,
+    /// 保留 ID
+    pub hold_id: Uuid,
+    /// 动作：placed、released 或 blocked
+    pub action: String,
+    /// 操作人，数据保留等后台任务为空
+    pub actor: Option<Uuid>,
+    /// 事件详情
+    pub details: Value,
+    /// 发生时间
+    pub created_at: DateTime<Utc>,
+}
+
+/// 法律保留服务
+pub struct LegalHoldService {
+    db: DatabaseConnection,
+}
+
+impl LegalHoldService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 设置法律保留
+    #[instrument(skip(self, request))]
+    pub async fn place_hold(
+        &self,
+        tenant_id: Uuid,
+        request: PlaceLegalHoldRequest,
+        placed_by: Uuid,
+    ) -> Result<LegalHoldResponse, AiStudioError> {
+        let reason = validate_reason("reason", &request.reason)?;
+        if !self.target_exists(tenant_id, request.target_type, &request.target_id).await? {
+            return Err(AiStudioError::not_found(format!("保留对象 {}", request.target_id)));
+        }
+
+        let hold = LegalHoldResponse {
+            id: Uuid::new_v4(),
+            target_type: request.target_type,
+            target_id: request.target_id,
+            reason,
+            case_reference: request.case_reference,
+            placed_by,
+            placed_at: Utc::now(),
+            released_by: None,
+            released_at: None,
+            release_reason: None,
+        };
+
+        let txn = self.db.begin().await?;
+        txn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"
+            INSERT INTO legal_holds (id, tenant_id, target_type, target_id, reason, case_reference, placed_by, placed_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            [
+                hold.id.into(),
+                tenant_id.into(),
+                hold.target_type.as_str().into(),
+                hold.target_id.clone().into(),
+                hold.reason.clone().into(),
+                hold.case_reference.clone().into(),
+                placed_by.into(),
+                hold.placed_at.into(),
+            ],
+        ))
+        .await?;
+        record_event(&txn, tenant_id, hold.id, "placed", Some(placed_by), json!({
+            "reason": hold.reason,
+            "case_reference": hold.case_reference,
+        }))
+        .await?;
+        txn.commit().await?;
+
+        info!(
+            tenant_id = %tenant_id,
+            hold_id = %hold.id,
+            target_type = hold.target_type.as_str(),
+            target_id = %hold.target_id,
+            "法律保留已设置"
+        );
+        Ok(hold)
+    }
+
+    /// 解除法律保留
+    #[instrument(skip(self, request))]
+    pub async fn release_hold(
+        &self,
+        tenant_id: Uuid,
+        hold_id: Uuid,
+        request: ReleaseLegalHoldRequest,
+        released_by: Uuid,
+    ) -> Result<LegalHoldResponse, AiStudioError> {
+        let reason = validate_reason("reason", &request.reason)?;
+
+        let txn = self.db.begin().await?;
+        let row = txn
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                UPDATE legal_holds
+                SET released_by = $3, released_at = $4, release_reason = $5
+                WHERE id = $1 AND tenant_id = $2 AND released_at IS NULL
+                RETURNING *
+                "#,
+                [hold_id.into(), tenant_id.into(), released_by.into(), Utc::now().into(), reason.clone().into()],
+            ))
+            .await?;
+        let Some(row) = row else {
+            return Err(match self.find_hold(tenant_id, hold_id).await? {
+                Some(_) => AiStudioError::conflict("法律保留已解除"),
+                None => AiStudioError::not_found("法律保留"),
+            });
+        };
+        record_event(&txn, tenant_id, hold_id, "released", Some(released_by), json!({ "reason": reason })).await?;
+        txn.commit().await?;
+
+        let hold = hold_from_row(&row)?;
+        info!(
+            tenant_id = %tenant_id,
+            hold_id = %hold_id,
+            target_type = hold.target_type.as_str(),
+            target_id = %hold.target_id,
+            "法律保留已解除"
+        );
+        Ok(hold)
+    }
+
+    /// 列出租户的法律保留，按设置时间倒序
+    pub async fn list_holds(&self, tenant_id: Uuid, query: LegalHoldQuery) -> Result<Vec<LegalHoldResponse>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT * FROM legal_holds
+                WHERE tenant_id = $1
+                    AND ($2::text IS NULL OR target_type = $2)
+                    AND ($3::text IS NULL OR target_id = $3)
+                    AND ($4 OR released_at IS NULL)
+                ORDER BY placed_at DESC
+                "#,
+                [
+                    tenant_id.into(),
+                    query.target_type.map(|target| target.as_str().to_string()).into(),
+                    query.target_id.into(),
+                    query.include_released.into(),
+                ],
+            ))
+            .await?;
+        rows.iter().map(hold_from_row).collect()
+    }
+
+    /// 列出法律保留的审计事件，按时间顺序
+    pub async fn list_events(&self, tenant_id: Uuid, hold_id: Uuid) -> Result<Vec<LegalHoldEvent>, AiStudioError> {
+        if self.find_hold(tenant_id, hold_id).await?.is_none() {
+            return Err(AiStudioError::not_found("法律保留"));
+        }
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT id, hold_id, action, actor, details, created_at FROM legal_hold_events \
+                 WHERE hold_id = $1 ORDER BY created_at",
+                [hold_id.into()],
+            ))
+            .await?;
+
+        let mut events = Vec::with_capacity(rows.len());
+        for row in rows {
+            events.push(LegalHoldEvent {
+                id: row.try_get("", "id")?,
+                hold_id: row.try_get("", "hold_id")?,
+                action: row.try_get("", "action")?,
+                actor: row.try_get("", "actor")?,
+                details: row.try_get("", "details")?,
+                created_at: row.try_get("", "created_at")?,
+            });
+        }
+        Ok(events)
+    }
+
+    /// 确认文档可以修改或删除
+    ///
+    /// 文档本身或其所属知识库处于法律保留状态时拒绝，并记录被拦截的操作。
+    pub async fn ensure_document_mutable(
+        &self,
+        doc: &document::Model,
+        actor: Option<Uuid>,
+        operation: &str,
+    ) -> Result<(), AiStudioError> {
+        let holds = self
+            .active_holds(&[
+                (LegalHoldTarget::Document, doc.id.to_string()),
+                (LegalHoldTarget::KnowledgeBase, doc.knowledge_base_id.to_string()),
+            ])
+            .await?;
+        self.reject_if_held(holds, actor, operation, &doc.id.to_string()).await
+    }
+
+    /// 确认知识库可以修改或删除
+    pub async fn ensure_knowledge_base_mutable(
+        &self,
+        knowledge_base_id: Uuid,
+        actor: Option<Uuid>,
+        operation: &str,
+    ) -> Result<(), AiStudioError> {
+        let holds = self
+            .active_holds(&[(LegalHoldTarget::KnowledgeBase, knowledge_base_id.to_string())])
+            .await?;
+        self.reject_if_held(holds, actor, operation, &knowledge_base_id.to_string()).await
+    }
+
+    /// 返回处于法律保留状态（自身或所属知识库被保留）的文档 ID
+    pub async fn held_documents(&self, document_ids: &[Uuid]) -> Result<HashSet<Uuid>, AiStudioError> {
+        if document_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT d.id FROM documents d
+                WHERE d.id = ANY($1) AND EXISTS (
+                    SELECT 1 FROM legal_holds h
+                    WHERE h.released_at IS NULL AND (
+                        (h.target_type = 'document' AND h.target_id = d.id::text)
+                        OR (h.target_type = 'knowledge_base' AND h.target_id = d.knowledge_base_id::text)
+                    )
+                )
+                "#,
+                [document_ids.to_vec().into()],
+            ))
+            .await?;
+        rows.iter().map(|row| row.try_get("", "id").map_err(Into::into)).collect()
+    }
+
+    async fn active_holds(&self, targets: &[(LegalHoldTarget, String)]) -> Result<Vec<(Uuid, Uuid)>, AiStudioError> {
+        let mut holds = Vec::new();
+        for (target_type, target_id) in targets {
+            let rows = self.db
+                .query_all(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    "SELECT id, tenant_id FROM legal_holds \
+                     WHERE target_type = $1 AND target_id = $2 AND released_at IS NULL",
+                    [target_type.as_str().into(), target_id.clone().into()],
+                ))
+                .await?;
+            for row in rows {
+                holds.push((row.try_get("", "id")?, row.try_get("", "tenant_id")?));
+            }
+        }
+        Ok(holds)
+    }
+
+    async fn reject_if_held(
+        &self,
+        holds: Vec<(Uuid, Uuid)>,
+        actor: Option<Uuid>,
+        operation: &str,
+        record_id: &str,
+    ) -> Result<(), AiStudioError> {
+        if holds.is_empty() {
+            return Ok(());
+        }
+
+        for (hold_id, tenant_id) in &holds {
+            let details = json!({ "operation": operation, "record_id": record_id });
+            if let Err(e) = record_event(&self.db, *tenant_id, *hold_id, "blocked", actor, details).await {
+                warn!("记录法律保留拦截事件失败: hold_id={}, error={}", hold_id, e);
+            }
+        }
+        warn!(record_id = %record_id, operation = %operation, "记录处于法律保留状态，操作已拒绝");
+        Err(AiStudioError::conflict("记录处于法律保留状态，解除保留前不可修改或删除"))
+    }
+
+    async fn find_hold(&self, tenant_id: Uuid, hold_id: Uuid) -> Result<Option<LegalHoldResponse>, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT * FROM legal_holds WHERE id = $1 AND tenant_id = $2",
+                [hold_id.into(), tenant_id.into()],
+            ))
+            .await?;
+        row.as_ref().map(hold_from_row).transpose()
+    }
+
+    async fn target_exists(&self, tenant_id: Uuid, target_type: LegalHoldTarget, target_id: &str) -> Result<bool, AiStudioError> {
+        let sql = match target_type {
+            LegalHoldTarget::Document => {
+                "SELECT 1 FROM documents d JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id \
+                 WHERE d.id::text = $1 AND kb.tenant_id = $2"
+            }
+            LegalHoldTarget::KnowledgeBase => "SELECT 1 FROM knowledge_bases WHERE id::text = $1 AND tenant_id = $2",
+            LegalHoldTarget::Conversation => {
+                "SELECT 1 FROM qa_query_logs WHERE session_id = $1 AND tenant_id = $2 \
+                 UNION ALL SELECT 1 FROM sessions WHERE id::text = $1 AND tenant_id = $2 \
+                 LIMIT 1"
+            }
+        };
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                sql,
+                [target_id.into(), tenant_id.into()],
+            ))
+            .await?;
+        Ok(row.is_some())
+    }
+}
+
+/// 写入审计事件
+async fn record_event<C: ConnectionTrait>(
+    db: &C,
+    tenant_id: Uuid,
+    hold_id: Uuid,
+    action: &str,
+    actor: Option<Uuid>,
+    details: Value,
+) -> Result<(), AiStudioError> {
+    db.execute(Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "INSERT INTO legal_hold_events (id, tenant_id, hold_id, action, actor, details, created_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        [
+            Uuid::new_v4().into(),
+            tenant_id.into(),
+            hold_id.into(),
+            action.into(),
+            actor.into(),
+            details.into(),
+            Utc::now().into(),
+        ],
+    ))
+    .await?;
+    Ok(())
+}
+
+fn hold_from_row(row: &QueryResult) -> Result<LegalHoldResponse, AiStudioError> {
+    let target_type: String = row.try_get("", "target_type")?;
+    Ok(LegalHoldResponse {
+        id: row.try_get("", "id")?,
+        target_type: LegalHoldTarget::parse(&target_type)?,
+        target_id: row.try_get("", "target_id")?,
+        reason: row.try_get("", "reason")?,
+        case_reference: row.try_get("", "case_reference")?,
+        placed_by: row.try_get("", "placed_by")?,
+        placed_at: row.try_get("", "placed_at")?,
+        released_by: row.try_get("", "released_by")?,
+        released_at: row.try_get("", "released_at")?,
+        release_reason: row.try_get("", "release_reason")?,
+    })
+}
+
+fn validate_reason(field: &str, reason: &str) -> Result<String, AiStudioError> {
+    let reason = reason.trim();
+    if reason.is_empty() || reason.chars().count() > MAX_REASON_CHARS {
+        return Err(AiStudioError::validation(field, format!("理由不能为空且不超过 {} 个字符", MAX_REASON_CHARS)));
+    }
+    Ok(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_reason() {
+        assert_eq!(validate_reason("reason", "  诉讼保全 2024-001 ").unwrap(), "诉讼保全 2024-001");
+        assert!(validate_reason("reason", "   ").is_err());
+        assert!(validate_reason("reason", &"长".repeat(MAX_REASON_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_target_round_trip() {
+        for target in [LegalHoldTarget::Document, LegalHoldTarget::KnowledgeBase, LegalHoldTarget::Conversation] {
+            assert_eq!(LegalHoldTarget::parse(target.as_str()).unwrap(), target);
+        }
+    }
+}
@@ -0,0 +1,267 @@
+// 稀疏词法索引
+// 为文档块维护 BM25 倒排表，与向量索引并行，补足嵌入难以命中的标识符、错误码、零件号等精确词项
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement, TransactionTrait};
+use serde_json::json;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::errors::AiStudioError;
+use crate::services::scheduler::PeriodicJob;
+
+/// 索引同步间隔
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 每批同步的文档块数
+const SYNC_BATCH_SIZE: i64 = 500;
+
+/// 每次同步最多处理的批数，剩余文档块留到下次同步
+const MAX_SYNC_BATCHES: usize = 20;
+
+/// 词项最大长度（字符），与 lexical_postings.term 列宽一致
+const MAX_TERM_CHARS: usize = 128;
+
+/// 标识符内部允许的连接符，如 `ERR-4012`、`part_no.7`、`v2/api`
+const TERM_CONNECTORS: &[char] = &['-', '_', '.', '/', ':', '#'];
+
+/// BM25 词频饱和参数
+pub const BM25_K1: f64 = 1.2;
+
+/// BM25 文档长度归一化参数
+pub const BM25_B: f64 = 0.75;
+
+/// 一次同步的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LexicalSyncResult {
+    /// 新建或重建索引的文档块数
+    pub indexed_chunks: u64,
+    /// 写入的词项数
+    pub postings: u64,
+}
+
+/// 稀疏词法索引服务
+///
+/// 索引按文档块的 content_hash 增量同步：未建索引或内容已变更的文档块会被重新分词写入，
+/// 文档块删除时索引随外键级联删除；按个人信息策略排除的文档块不建索引。
+pub struct LexicalIndexService {
+    db: DatabaseConnection,
+}
+
+impl LexicalIndexService {
+    /// 创建词法索引服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 同步未建索引或内容已变更的文档块
+    pub async fn sync(&self) -> Result<LexicalSyncResult, AiStudioError> {
+        let mut result = LexicalSyncResult::default();
+        for _ in 0..MAX_SYNC_BATCHES {
+            let rows = self.db
+                .query_all(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    r#"
+                    SELECT c.id, c.knowledge_base_id, c.content, c.content_hash
+                    FROM document_chunks c
+                    LEFT JOIN lexical_chunks lc ON lc.chunk_id = c.id
+                    WHERE (lc.chunk_id IS NULL OR lc.content_hash <> c.content_hash)
+                        AND COALESCE(c.metadata->'pii'->>'action', '') <> 'exclude'
+                    ORDER BY c.id
+                    LIMIT $1
+                    "#,
+                    [SYNC_BATCH_SIZE.into()],
+                ))
+                .await?;
+            if rows.is_empty() {
+                break;
+            }
+
+            let batch_len = rows.len();
+            for row in rows {
+                let chunk_id: Uuid = row.try_get("", "id")?;
+                let knowledge_base_id: Uuid = row.try_get("", "knowledge_base_id")?;
+                let content: String = row.try_get("", "content")?;
+                let content_hash: String = row.try_get("", "content_hash")?;
+                result.postings += self.index_chunk(chunk_id, knowledge_base_id, &content, &content_hash).await?;
+                result.indexed_chunks += 1;
+            }
+            if (batch_len as i64) < SYNC_BATCH_SIZE {
+                break;
+            }
+        }
+
+        if result.indexed_chunks > 0 {
+            info!("词法索引同步完成: 文档块数={}, 词项数={}", result.indexed_chunks, result.postings);
+        }
+        Ok(result)
+    }
+
+    /// 重建单个文档块的索引，返回写入的词项数
+    async fn index_chunk(
+        &self,
+        chunk_id: Uuid,
+        knowledge_base_id: Uuid,
+        content: &str,
+        content_hash: &str,
+    ) -> Result<u64, AiStudioError> {
+        let tokens = tokenize(content);
+        let frequencies = term_frequencies(&tokens);
+
+        let txn = self.db.begin().await?;
+        txn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "DELETE FROM lexical_chunks WHERE chunk_id = $1",
+            [chunk_id.into()],
+        ))
+        .await?;
+        txn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "INSERT INTO lexical_chunks (chunk_id, knowledge_base_id, content_hash, length) VALUES ($1, $2, $3, $4)",
+            [chunk_id.into(), knowledge_base_id.into(), content_hash.into(), (tokens.len() as i32).into()],
+        ))
+        .await?;
+        if !frequencies.is_empty() {
+            txn.execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO lexical_postings (chunk_id, knowledge_base_id, term, tf)
+                SELECT $1, $2, key, value::INTEGER FROM jsonb_each_text($3::jsonb)
+                "#,
+                [chunk_id.into(), knowledge_base_id.into(), json!(frequencies).into()],
+            ))
+            .await?;
+        }
+        txn.commit().await?;
+
+        debug!("文档块词法索引已更新: chunk_id={}, 词项数={}", chunk_id, frequencies.len());
+        Ok(frequencies.len() as u64)
+    }
+}
+
+/// 词法索引定时同步任务
+pub struct LexicalIndexSyncJob {
+    service: Arc<LexicalIndexService>,
+}
+
+impl LexicalIndexSyncJob {
+    /// 创建同步任务
+    pub fn new(service: Arc<LexicalIndexService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for LexicalIndexSyncJob {
+    fn name(&self) -> &str {
+        "lexical_index_sync"
+    }
+
+    fn interval(&self) -> Duration {
+        SYNC_INTERVAL
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        self.service.sync().await.map(|_| ())
+    }
+}
+
+/// 将文本切分为词项
+///
+/// 字母数字与内部连接符组成的片段整体作为一个词项（如 `err-4012`），含连接符时再拆出各部分；
+/// 中日韩文字按相邻两字切分，单字片段保留单字。所有词项转为小写。
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut cjk = Vec::new();
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut tokens);
+            cjk.push(c);
+        } else if c.is_alphanumeric() || (TERM_CONNECTORS.contains(&c) && !word.is_empty()) {
+            flush_cjk(&mut cjk, &mut tokens);
+            word.extend(c.to_lowercase());
+        } else {
+            flush_word(&mut word, &mut tokens);
+            flush_cjk(&mut cjk, &mut tokens);
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_cjk(&mut cjk, &mut tokens);
+    tokens
+}
+
+fn flush_word(word: &mut String, tokens: &mut Vec<String>) {
+    let term = word.trim_end_matches(TERM_CONNECTORS);
+    if !term.is_empty() {
+        if term.contains(TERM_CONNECTORS) {
+            tokens.extend(term.split(TERM_CONNECTORS).filter(|part| !part.is_empty()).map(truncate_term));
+        }
+        tokens.push(truncate_term(term));
+    }
+    word.clear();
+}
+
+fn flush_cjk(cjk: &mut Vec<char>, tokens: &mut Vec<String>) {
+    match cjk.len() {
+        0 => {}
+        1 => tokens.push(cjk[0].to_string()),
+        _ => tokens.extend(cjk.windows(2).map(|pair| pair.iter().collect::<String>())),
+    }
+    cjk.clear();
+}
+
+fn truncate_term(term: &str) -> String {
+    term.chars().take(MAX_TERM_CHARS).collect()
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}')
+}
+
+/// 统计词频
+pub fn term_frequencies(tokens: &[String]) -> HashMap<&str, u32> {
+    let mut frequencies = HashMap::new();
+    for token in tokens {
+        *frequencies.entry(token.as_str()).or_insert(0) += 1;
+    }
+    frequencies
+}
+
+/// 查询的去重词项
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms = tokenize(query);
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_keeps_identifiers_whole() {
+        let tokens = tokenize("Error ERR-4012 on part PN_77.3b, see docs.");
+        assert!(tokens.contains(&"err-4012".to_string()));
+        assert!(tokens.contains(&"4012".to_string()));
+        assert!(tokens.contains(&"pn_77.3b".to_string()));
+        assert!(tokens.contains(&"docs".to_string()));
+        assert!(!tokens.iter().any(|t| t.ends_with('.')));
+    }
+
+    #[test]
+    fn test_tokenize_cjk_bigrams() {
+        assert_eq!(tokenize("电源故障 E01"), vec!["电源", "源故", "故障", "e01"]);
+        assert_eq!(query_terms("故障 故障"), vec!["故障"]);
+    }
+}
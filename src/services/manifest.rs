@@ -0,0 +1,664 @@
+// 期望状态清单服务
+// 以声明式清单描述租户及其知识库、Agent、工作流、插件配置，对比数据库现状后幂等地创建或更新资源
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use sea_orm::{ActiveEnum, DatabaseConnection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::{info, instrument};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::db::entities::knowledge_base::KnowledgeBaseType;
+use crate::db::entities::tenant::{self, TenantPlan};
+use crate::db::entities::workflow::{WorkflowDefinition, WorkflowType};
+use crate::db::repositories::knowledge_base::KnowledgeBaseRepository;
+use crate::db::repositories::tenant::TenantRepository;
+use crate::db::repositories::workflow::WorkflowRepository;
+use crate::errors::AiStudioError;
+use crate::plugins::plugin_manager::PluginManager;
+use crate::services::agent_definition::{AgentDefinition, AgentDefinitionService, DefinitionFormat};
+use crate::services::plugin_config::PluginConfigService;
+
+/// 清单类型
+pub const MANIFEST_KIND: &str = "Manifest";
+
+/// 期望状态清单
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Manifest {
+    /// 格式版本，当前为 `aionix/v1`
+    pub api_version: String,
+    /// 清单类型，固定为 `Manifest`
+    pub kind: String,
+    /// 租户
+    #[serde(default)]
+    pub tenants: Vec<TenantManifest>,
+}
+
+/// 租户期望状态
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TenantManifest {
+    /// 租户标识符，用于匹配已有租户
+    pub slug: String,
+    /// 租户名称
+    pub name: String,
+    /// 显示名称，省略时与名称相同
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// 订阅套餐，省略时不修改
+    #[serde(default)]
+    pub plan: Option<TenantPlan>,
+    /// 知识库
+    #[serde(default)]
+    pub knowledge_bases: Vec<KnowledgeBaseManifest>,
+    /// Agent 定义，格式与 Agent 定义文件相同
+    #[serde(default)]
+    pub agents: Vec<AgentDefinition>,
+    /// 工作流
+    #[serde(default)]
+    pub workflows: Vec<WorkflowManifest>,
+    /// 插件配置
+    #[serde(default)]
+    pub plugins: Vec<PluginManifest>,
+}
+
+/// 知识库期望状态
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct KnowledgeBaseManifest {
+    /// 知识库名称
+    pub name: String,
+    /// 描述
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 知识库类型：general、faq、documentation、policy、product
+    #[serde(default = "default_kb_type")]
+    pub kb_type: String,
+    /// 嵌入模型，仅创建时使用
+    #[serde(default = "default_embedding_model")]
+    pub embedding_model: String,
+    /// 向量维度，仅创建时使用
+    #[serde(default = "default_vector_dimension")]
+    pub vector_dimension: i32,
+}
+
+/// 工作流期望状态
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WorkflowManifest {
+    /// 工作流名称
+    pub name: String,
+    /// 描述
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 工作流类型：sequential、parallel、conditional、loop、dag
+    #[serde(default = "default_workflow_type")]
+    pub workflow_type: String,
+    /// 版本
+    #[serde(default = "default_version")]
+    pub version: String,
+    /// 工作流定义（节点与边）
+    pub definition: Value,
+}
+
+/// 插件配置期望状态
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PluginManifest {
+    /// 插件 ID
+    pub plugin_id: String,
+    /// 配置项
+    #[serde(default)]
+    pub config: Map<String, Value>,
+}
+
+fn default_kb_type() -> String {
+    KnowledgeBaseType::General.to_value()
+}
+
+fn default_embedding_model() -> String {
+    "text-embedding-3-small".to_string()
+}
+
+fn default_vector_dimension() -> i32 {
+    1536
+}
+
+fn default_workflow_type() -> String {
+    WorkflowType::Sequential.to_value()
+}
+
+fn default_version() -> String {
+    "1.0.0".to_string()
+}
+
+/// 资源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    Tenant,
+    KnowledgeBase,
+    Agent,
+    Workflow,
+    Plugin,
+}
+
+/// 变更动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    /// 新建
+    Create,
+    /// 更新
+    Update,
+    /// 与期望状态一致
+    Unchanged,
+    /// 无法在当前环境应用
+    Skipped,
+}
+
+/// 单个资源的变更
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ResourceChange {
+    /// 所属租户标识符
+    pub tenant: String,
+    /// 资源类型
+    pub kind: ResourceKind,
+    /// 资源名称
+    pub name: String,
+    /// 变更动作
+    pub action: ChangeAction,
+    /// 发生变化的字段
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    /// 说明，跳过时给出原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// 清单对比或应用结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ManifestPlan {
+    /// 是否已应用，仅对比时为 false
+    pub applied: bool,
+    /// 各资源变更
+    pub changes: Vec<ResourceChange>,
+}
+
+impl ManifestPlan {
+    /// 指定动作的资源数
+    pub fn count(&self, action: ChangeAction) -> usize {
+        self.changes.iter().filter(|change| change.action == action).count()
+    }
+
+    /// 是否存在待应用的变更
+    pub fn has_changes(&self) -> bool {
+        self.changes.iter().any(|change| matches!(change.action, ChangeAction::Create | ChangeAction::Update))
+    }
+}
+
+/// 清单查询参数
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct ManifestApplyQuery {
+    /// 只对比不应用，默认 false
+    #[serde(default)]
+    pub dry_run: bool,
+    /// 清单格式，未指定时按 Content-Type 判断
+    pub format: Option<DefinitionFormat>,
+}
+
+impl Manifest {
+    /// 校验清单内容
+    pub fn validate(&self) -> Result<(), AiStudioError> {
+        if self.api_version != crate::services::agent_definition::AGENT_DEFINITION_API_VERSION {
+            return Err(AiStudioError::validation("api_version", format!("不支持的格式版本 '{}'", self.api_version)));
+        }
+        if self.kind != MANIFEST_KIND {
+            return Err(AiStudioError::validation("kind", format!("清单类型应为 '{}'", MANIFEST_KIND)));
+        }
+
+        let mut slugs = HashSet::new();
+        for tenant in &self.tenants {
+            if tenant.slug.trim().is_empty() || tenant.name.trim().is_empty() {
+                return Err(AiStudioError::validation("tenants", "租户标识符和名称不能为空"));
+            }
+            if !slugs.insert(tenant.slug.as_str()) {
+                return Err(AiStudioError::validation("tenants", format!("租户 '{}' 重复", tenant.slug)));
+            }
+            check_unique(&tenant.slug, "knowledge_bases", tenant.knowledge_bases.iter().map(|kb| kb.name.as_str()))?;
+            check_unique(&tenant.slug, "agents", tenant.agents.iter().map(|agent| agent.name.as_str()))?;
+            check_unique(&tenant.slug, "workflows", tenant.workflows.iter().map(|wf| wf.name.as_str()))?;
+            check_unique(&tenant.slug, "plugins", tenant.plugins.iter().map(|plugin| plugin.plugin_id.as_str()))?;
+
+            for kb in &tenant.knowledge_bases {
+                parse_enum::<KnowledgeBaseType>("kb_type", &kb.kb_type)?;
+            }
+            for agent in &tenant.agents {
+                agent.validate()?;
+            }
+            for wf in &tenant.workflows {
+                parse_enum::<WorkflowType>("workflow_type", &wf.workflow_type)?;
+                serde_json::from_value::<WorkflowDefinition>(wf.definition.clone()).map_err(|e| {
+                    AiStudioError::validation("workflows", format!("工作流 '{}' 定义无效: {}", wf.name, e))
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_unique<'a>(tenant: &str, field: &str, names: impl Iterator<Item = &'a str>) -> Result<(), AiStudioError> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name) {
+            return Err(AiStudioError::validation(field, format!("租户 '{}' 中 '{}' 重复", tenant, name)));
+        }
+    }
+    Ok(())
+}
+
+fn parse_enum<T: ActiveEnum<Value = String>>(field: &str, value: &str) -> Result<T, AiStudioError> {
+    T::try_from_value(&value.to_string())
+        .map_err(|_| AiStudioError::validation(field, format!("未知的取值 '{}'", value)))
+}
+
+/// 清单服务
+pub struct ManifestService {
+    db: DatabaseConnection,
+    encryption_key: String,
+    plugin_manager: Option<Arc<PluginManager>>,
+}
+
+impl ManifestService {
+    /// 创建新的清单服务实例
+    pub fn new(db: DatabaseConnection, encryption_key: &str) -> Self {
+        Self {
+            db,
+            encryption_key: encryption_key.to_string(),
+            plugin_manager: None,
+        }
+    }
+
+    /// 使用插件管理器获取插件配置模式
+    ///
+    /// 未设置时（如命令行）插件配置无法按模式校验与加密敏感字段，对应资源会被跳过。
+    pub fn with_plugin_manager(mut self, plugin_manager: Arc<PluginManager>) -> Self {
+        self.plugin_manager = Some(plugin_manager);
+        self
+    }
+
+    /// 对比清单与数据库现状
+    #[instrument(skip(self, manifest))]
+    pub async fn plan(&self, manifest: &Manifest) -> Result<ManifestPlan, AiStudioError> {
+        self.reconcile(manifest, None).await
+    }
+
+    /// 应用清单，`user_id` 记录为新建 Agent、工作流与插件配置的创建人
+    #[instrument(skip(self, manifest))]
+    pub async fn apply(&self, manifest: &Manifest, user_id: Uuid) -> Result<ManifestPlan, AiStudioError> {
+        let plan = self.reconcile(manifest, Some(user_id)).await?;
+        info!(
+            created = plan.count(ChangeAction::Create),
+            updated = plan.count(ChangeAction::Update),
+            "清单已应用"
+        );
+        Ok(plan)
+    }
+
+    /// 逐个资源对比，提供 `apply_as` 时同时应用变更
+    async fn reconcile(&self, manifest: &Manifest, apply_as: Option<Uuid>) -> Result<ManifestPlan, AiStudioError> {
+        manifest.validate()?;
+
+        let mut changes = Vec::new();
+        for desired in &manifest.tenants {
+            let (tenant_id, change) = self.reconcile_tenant(desired, apply_as.is_some()).await?;
+            changes.push(change);
+
+            // 租户尚未创建时其下资源全部为新建
+            let Some(tenant_id) = tenant_id else {
+                changes.extend(tenant_children(desired).map(|(kind, name)| ResourceChange {
+                    tenant: desired.slug.clone(),
+                    kind,
+                    name,
+                    action: ChangeAction::Create,
+                    fields: Vec::new(),
+                    note: None,
+                }));
+                continue;
+            };
+
+            for kb in &desired.knowledge_bases {
+                changes.push(self.reconcile_knowledge_base(&desired.slug, tenant_id, kb, apply_as.is_some()).await?);
+            }
+            for agent in &desired.agents {
+                changes.push(self.reconcile_agent(&desired.slug, tenant_id, agent, apply_as).await?);
+            }
+            for wf in &desired.workflows {
+                changes.push(self.reconcile_workflow(&desired.slug, tenant_id, wf, apply_as).await?);
+            }
+            for plugin in &desired.plugins {
+                changes.push(self.reconcile_plugin(&desired.slug, tenant_id, plugin, apply_as).await?);
+            }
+        }
+
+        Ok(ManifestPlan {
+            applied: apply_as.is_some(),
+            changes,
+        })
+    }
+
+    async fn reconcile_tenant(
+        &self,
+        desired: &TenantManifest,
+        apply: bool,
+    ) -> Result<(Option<Uuid>, ResourceChange), AiStudioError> {
+        let display_name = desired.display_name.clone().unwrap_or_else(|| desired.name.clone());
+        let existing = TenantRepository::find_by_slug(&self.db, &desired.slug).await?;
+
+        let (tenant, action, fields) = match existing {
+            None => {
+                if !apply {
+                    return Ok((None, resource_change(&desired.slug, ResourceKind::Tenant, &desired.slug, ChangeAction::Create, Vec::new())));
+                }
+                let tenant = TenantRepository::create(&self.db, desired.name.clone(), desired.slug.clone(), display_name.clone()).await?;
+                (tenant, ChangeAction::Create, Vec::new())
+            }
+            Some(tenant) => {
+                let mut fields = Vec::new();
+                if tenant.name != desired.name {
+                    fields.push("name".to_string());
+                }
+                if tenant.display_name != display_name {
+                    fields.push("display_name".to_string());
+                }
+                let current_plan = tenant.get_config().map(|config| config.plan).ok();
+                if desired.plan.is_some() && desired.plan != current_plan {
+                    fields.push("plan".to_string());
+                }
+                let action = if fields.is_empty() { ChangeAction::Unchanged } else { ChangeAction::Update };
+                (tenant, action, fields)
+            }
+        };
+
+        // 新建租户时套餐也需要写入
+        let plan_changed = desired.plan.is_some() && (action == ChangeAction::Create || fields.iter().any(|f| f == "plan"));
+        if apply && (action == ChangeAction::Update || plan_changed) {
+            self.update_tenant(tenant.clone(), desired, display_name, plan_changed).await?;
+        }
+
+        Ok((Some(tenant.id), resource_change(&desired.slug, ResourceKind::Tenant, &desired.slug, action, fields)))
+    }
+
+    async fn update_tenant(
+        &self,
+        mut tenant: tenant::Model,
+        desired: &TenantManifest,
+        display_name: String,
+        plan_changed: bool,
+    ) -> Result<(), AiStudioError> {
+        tenant.name = desired.name.clone();
+        tenant.display_name = display_name;
+        if let (true, Some(plan)) = (plan_changed, desired.plan) {
+            let mut config = tenant.get_config()
+                .map_err(|e| AiStudioError::internal(format!("解析租户配置失败: {}", e)))?;
+            config.plan = plan;
+            tenant.config = serde_json::to_value(config)?;
+            tenant.quota_limits = serde_json::to_value(plan.default_quota_limits())?;
+        }
+        TenantRepository::update(&self.db, tenant).await?;
+        Ok(())
+    }
+
+    async fn reconcile_knowledge_base(
+        &self,
+        slug: &str,
+        tenant_id: Uuid,
+        desired: &KnowledgeBaseManifest,
+        apply: bool,
+    ) -> Result<ResourceChange, AiStudioError> {
+        let kb_type = parse_enum::<KnowledgeBaseType>("kb_type", &desired.kb_type)?;
+
+        match KnowledgeBaseRepository::find_by_name_in_tenant(&self.db, tenant_id, &desired.name).await? {
+            None => {
+                if apply {
+                    KnowledgeBaseRepository::create(
+                        &self.db,
+                        tenant_id,
+                        desired.name.clone(),
+                        desired.description.clone(),
+                        kb_type,
+                        desired.embedding_model.clone(),
+                        desired.vector_dimension,
+                    )
+                    .await?;
+                }
+                Ok(resource_change(slug, ResourceKind::KnowledgeBase, &desired.name, ChangeAction::Create, Vec::new()))
+            }
+            Some(mut kb) => {
+                let mut fields = Vec::new();
+                if kb.description != desired.description {
+                    fields.push("description".to_string());
+                }
+                if kb.kb_type != kb_type {
+                    fields.push("kb_type".to_string());
+                }
+                let mut change = resource_change(
+                    slug,
+                    ResourceKind::KnowledgeBase,
+                    &desired.name,
+                    if fields.is_empty() { ChangeAction::Unchanged } else { ChangeAction::Update },
+                    fields,
+                );
+                // 已有向量不随清单重建，嵌入设置只在创建时生效
+                if kb.embedding_model != desired.embedding_model || kb.vector_dimension != desired.vector_dimension {
+                    change.note = Some("嵌入模型与向量维度仅在创建时设置，已忽略".to_string());
+                }
+                if apply && change.action == ChangeAction::Update {
+                    kb.description = desired.description.clone();
+                    kb.kb_type = kb_type;
+                    KnowledgeBaseRepository::update(&self.db, kb).await?;
+                }
+                Ok(change)
+            }
+        }
+    }
+
+    async fn reconcile_agent(
+        &self,
+        slug: &str,
+        tenant_id: Uuid,
+        desired: &AgentDefinition,
+        apply_as: Option<Uuid>,
+    ) -> Result<ResourceChange, AiStudioError> {
+        let service = AgentDefinitionService::new(self.db.clone());
+        let action = match service.export_by_name(tenant_id, &desired.name).await {
+            Ok(current) if &current == desired => ChangeAction::Unchanged,
+            Ok(_) => ChangeAction::Update,
+            Err(AiStudioError::NotFound { .. }) => ChangeAction::Create,
+            Err(e) => return Err(e),
+        };
+
+        if let (Some(user_id), ChangeAction::Create | ChangeAction::Update) = (apply_as, action) {
+            service.import(tenant_id, user_id, desired.clone()).await?;
+        }
+        Ok(resource_change(slug, ResourceKind::Agent, &desired.name, action, Vec::new()))
+    }
+
+    async fn reconcile_workflow(
+        &self,
+        slug: &str,
+        tenant_id: Uuid,
+        desired: &WorkflowManifest,
+        apply_as: Option<Uuid>,
+    ) -> Result<ResourceChange, AiStudioError> {
+        let workflow_type = parse_enum::<WorkflowType>("workflow_type", &desired.workflow_type)?;
+        let definition: WorkflowDefinition = serde_json::from_value(desired.definition.clone())?;
+
+        match WorkflowRepository::find_by_name_in_tenant(&self.db, tenant_id, &desired.name).await? {
+            None => {
+                if let Some(user_id) = apply_as {
+                    let mut created = WorkflowRepository::create(
+                        &self.db,
+                        tenant_id,
+                        desired.name.clone(),
+                        desired.description.clone(),
+                        workflow_type,
+                        definition,
+                        user_id,
+                    )
+                    .await?;
+                    if created.version != desired.version {
+                        created.version = desired.version.clone();
+                        WorkflowRepository::update(&self.db, created).await?;
+                    }
+                }
+                Ok(resource_change(slug, ResourceKind::Workflow, &desired.name, ChangeAction::Create, Vec::new()))
+            }
+            Some(mut current) => {
+                let desired_definition = serde_json::to_value(&definition)?;
+                let mut fields = Vec::new();
+                if current.description != desired.description {
+                    fields.push("description".to_string());
+                }
+                if current.workflow_type != workflow_type {
+                    fields.push("workflow_type".to_string());
+                }
+                if current.version != desired.version {
+                    fields.push("version".to_string());
+                }
+                if current.definition != desired_definition {
+                    fields.push("definition".to_string());
+                }
+                let action = if fields.is_empty() { ChangeAction::Unchanged } else { ChangeAction::Update };
+                if apply_as.is_some() && action == ChangeAction::Update {
+                    current.description = desired.description.clone();
+                    current.workflow_type = workflow_type;
+                    current.version = desired.version.clone();
+                    current.definition = desired_definition;
+                    WorkflowRepository::update(&self.db, current).await?;
+                }
+                Ok(resource_change(slug, ResourceKind::Workflow, &desired.name, action, fields))
+            }
+        }
+    }
+
+    async fn reconcile_plugin(
+        &self,
+        slug: &str,
+        tenant_id: Uuid,
+        desired: &PluginManifest,
+        apply_as: Option<Uuid>,
+    ) -> Result<ResourceChange, AiStudioError> {
+        let Some(plugin_manager) = &self.plugin_manager else {
+            let mut change = resource_change(slug, ResourceKind::Plugin, &desired.plugin_id, ChangeAction::Skipped, Vec::new());
+            change.note = Some("插件配置需通过 API 应用，以便按插件配置模式校验并加密敏感字段".to_string());
+            return Ok(change);
+        };
+
+        let service = PluginConfigService::new(self.db.clone(), &self.encryption_key);
+        let current: Map<String, Value> = service.get_config(tenant_id, &desired.plugin_id).await?.into_iter().collect();
+        let action = if current.is_empty() {
+            ChangeAction::Create
+        } else if current == desired.config {
+            ChangeAction::Unchanged
+        } else {
+            ChangeAction::Update
+        };
+        let fields = changed_keys(&current, &desired.config);
+
+        if let (Some(user_id), ChangeAction::Create | ChangeAction::Update) = (apply_as, action) {
+            let schema = plugin_manager.get_config_schema(&desired.plugin_id).await?;
+            service.save_config(tenant_id, &desired.plugin_id, &schema, desired.config.clone(), user_id).await?;
+        }
+        Ok(resource_change(slug, ResourceKind::Plugin, &desired.plugin_id, action, fields))
+    }
+}
+
+fn resource_change(tenant: &str, kind: ResourceKind, name: &str, action: ChangeAction, fields: Vec<String>) -> ResourceChange {
+    ResourceChange {
+        tenant: tenant.to_string(),
+        kind,
+        name: name.to_string(),
+        action,
+        fields,
+        note: None,
+    }
+}
+
+/// 租户下声明的全部资源
+fn tenant_children(tenant: &TenantManifest) -> impl Iterator<Item = (ResourceKind, String)> + '_ {
+    tenant.knowledge_bases.iter().map(|kb| (ResourceKind::KnowledgeBase, kb.name.clone()))
+        .chain(tenant.agents.iter().map(|agent| (ResourceKind::Agent, agent.name.clone())))
+        .chain(tenant.workflows.iter().map(|wf| (ResourceKind::Workflow, wf.name.clone())))
+        .chain(tenant.plugins.iter().map(|plugin| (ResourceKind::Plugin, plugin.plugin_id.clone())))
+}
+
+/// 两份配置中取值不同的键，敏感字段只报告键名
+fn changed_keys(current: &Map<String, Value>, desired: &Map<String, Value>) -> Vec<String> {
+    let mut keys: Vec<String> = current.keys()
+        .chain(desired.keys())
+        .filter(|key| current.get(*key) != desired.get(*key))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+api_version: aionix/v1
+kind: Manifest
+tenants:
+  - slug: acme
+    name: acme
+    plan: standard
+    knowledge_bases:
+      - name: 产品文档
+        kb_type: documentation
+    agents:
+      - name: 客服助手
+        system_prompt: 你是一个耐心的客服助手
+        tools: [search]
+    workflows:
+      - name: 工单分流
+        definition:
+          nodes: []
+          edges: []
+          entry_node: start
+          exit_nodes: [end]
+          global_variables: {}
+    plugins:
+      - plugin_id: weather
+        config:
+          units: metric
+"#;
+
+    #[test]
+    fn test_parse_and_validate_manifest() {
+        let manifest: Manifest = DefinitionFormat::Yaml.deserialize(MANIFEST).unwrap();
+        manifest.validate().unwrap();
+        let tenant = &manifest.tenants[0];
+        assert_eq!(tenant.plan, Some(TenantPlan::Standard));
+        assert_eq!(tenant.knowledge_bases[0].vector_dimension, 1536);
+        assert_eq!(tenant.workflows[0].workflow_type, "sequential");
+        assert_eq!(tenant_children(tenant).count(), 4);
+
+        let mut duplicate = manifest.clone();
+        duplicate.tenants[0].agents.push(tenant.agents[0].clone());
+        assert!(duplicate.validate().is_err());
+
+        let bad_workflow = MANIFEST.replace("entry_node: start", "entry: start");
+        let manifest: Manifest = DefinitionFormat::Yaml.deserialize(&bad_workflow).unwrap();
+        assert!(manifest.validate().is_err());
+    }
+
+    #[test]
+    fn test_changed_keys() {
+        let current = serde_json::json!({ "units": "imperial", "api_key": "x" });
+        let desired = serde_json::json!({ "units": "metric", "api_key": "x", "lang": "zh" });
+        let keys = changed_keys(current.as_object().unwrap(), desired.as_object().unwrap());
+        assert_eq!(keys, vec!["lang".to_string(), "units".to_string()]);
+    }
+}
@@ -1,26 +1,109 @@
 // 服务层模块
 // 包含所有业务逻辑服务
 
+pub mod admin;
+pub mod admin_elevation;
 pub mod agent;
+pub mod agent_definition;
+pub mod agent_memory;
 pub mod ai;
+pub mod archive;
+pub mod audit_log;
 pub mod auth;
+pub mod billing;
+pub mod cache;
+pub mod canary;
+pub mod clearance;
+pub mod consent;
+pub mod corpus_import;
+pub mod curated_answer;
+pub mod dataset;
+pub mod deadline;
+pub mod document_comment;
+pub mod duplicate_detection;
+pub mod embedding_export;
+pub mod egress;
+pub mod faq;
+pub mod execution_event;
+pub mod execution_artifact;
+pub mod execution_replay;
+pub mod few_shot;
+pub mod finetune_dataset;
+pub mod freshness;
+pub mod incremental_index;
+pub mod ingest_throttle;
+pub mod kb_snapshot;
+pub mod kb_stats;
 pub mod knowledge_base;
+pub mod legal_hold;
+pub mod lexical_index;
+pub mod manifest;
 pub mod monitoring;
 pub mod notification;
+pub mod passkey;
+pub mod pii_policy;
 pub mod plugin;
+pub mod plugin_config;
+pub mod plugin_trust;
+pub mod process_role;
+pub mod prompt_retention;
+pub mod qa_transcript;
+pub mod question_suggestion;
 pub mod quota;
 pub mod rate_limit;
+pub mod replication;
+pub mod retention;
+pub mod route_cache;
+pub mod sandbox;
+pub mod saved_search;
+pub mod scheduled_report;
+pub mod scheduler;
+pub mod self_test;
+pub mod similar_documents;
+pub mod slo;
+pub mod source_health;
 pub mod task_queue;
 pub mod tenant;
+pub mod tenant_activity;
+pub mod tenant_encryption;
+pub mod tenant_persona;
+pub mod tenant_secret;
+pub mod topic_map;
+pub mod transcript_export;
+pub mod usage_anomaly;
+pub mod user_import;
+pub mod user_preferences;
+pub mod vector_migration;
+pub mod web_search;
+pub mod workflow_callback;
 
+pub use admin::*;
 pub use agent::*;
 pub use ai::*;
 pub use auth::*;
+pub use cache::*;
+pub use clearance::*;
+pub use faq::*;
+pub use execution_event::*;
+pub use few_shot::*;
+pub use finetune_dataset::*;
+pub use freshness::*;
+pub use kb_snapshot::*;
 pub use knowledge_base::*;
 pub use monitoring::*;
 pub use notification::*;
 pub use plugin::*;
+pub use plugin_config::*;
+pub use plugin_trust::*;
+pub use qa_transcript::*;
+pub use question_suggestion::*;
 pub use quota::*;
 pub use rate_limit::*;
+pub use replication::*;
+pub use retention::*;
+pub use saved_search::*;
+pub use scheduler::*;
 pub use task_queue::*;
-pub use tenant::*;
\ No newline at end of file
+pub use tenant::*;
+pub use tenant_persona::*;
+pub use transcript_export::*;
\ No newline at end of file
@@ -12,6 +12,7 @@ use std::collections::HashMap;
 use crate::db::entities::prelude::*;
 use crate::errors::AiStudioError;
 use crate::services::quota::QuotaService;
+use crate::services::slo::{SloStatus, SloTracker};
 
 /// 监控指标类型
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
@@ -337,6 +338,16 @@ impl MonitoringService {
         }
         components.insert("ai_service".to_string(), ai_health);
 
+        // 服务等级目标：错误预算快速耗尽时视为不健康
+        if let Some(slo_health) = self.check_slo_health() {
+            match slo_health.status {
+                HealthStatus::Unhealthy => overall_status = HealthStatus::Unhealthy,
+                HealthStatus::Warning if overall_status == HealthStatus::Healthy => overall_status = HealthStatus::Warning,
+                _ => {}
+            }
+            components.insert("slo".to_string(), slo_health);
+        }
+
         // 获取活跃告警数量
         active_alerts = self.get_active_alerts_count().await?;
         if active_alerts > 0 && overall_status == HealthStatus::Healthy {
@@ -491,6 +502,26 @@ impl MonitoringService {
         }
     }
 
+    /// 检查服务等级目标，未启用 SLO 跟踪时为空
+    fn check_slo_health(&self) -> Option<ComponentHealth> {
+        let report = SloTracker::global()?.report();
+        let status = match report.status {
+            SloStatus::Breaching => HealthStatus::Unhealthy,
+            SloStatus::Warning => HealthStatus::Warning,
+            SloStatus::Ok => HealthStatus::Healthy,
+            SloStatus::NoData => HealthStatus::Unknown,
+        };
+        let degraded = report.degraded_classes();
+
+        Some(ComponentHealth {
+            status,
+            response_time_ms: None,
+            error_message: (!degraded.is_empty())
+                .then(|| format!("错误预算消耗过快的接口类别: {}", degraded.join(", "))),
+            last_check: report.generated_at,
+        })
+    }
+
     /// 获取活跃告警数量
     async fn get_active_alerts_count(&self) -> Result<u32, AiStudioError> {
         // 这里应该从数据库查询活跃告警数量
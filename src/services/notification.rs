@@ -8,6 +8,7 @@ use tracing::{info, error, instrument};
 use utoipa::ToSchema;
 use std::collections::HashMap;
 
+use crate::db::entities::usage_anomaly;
 use crate::errors::AiStudioError;
 use crate::services::quota::QuotaUsage;
 use crate::services::monitoring::{AlertEvent, AlertSeverity};
@@ -27,6 +28,18 @@ pub enum NotificationType {
     SystemMaintenance,
     /// 账单提醒
     BillingReminder,
+    /// 保存的搜索匹配到新文档
+    SavedSearchMatch,
+    /// 文档过期或到期待复核
+    DocumentReview,
+    /// 用户邀请
+    UserInvitation,
+    /// 租户用量异常
+    UsageAnomaly,
+    /// 定时报告
+    ScheduledReport,
+    /// 文档评论中被提及
+    DocumentMention,
 }
 
 /// 通知渠道
@@ -107,6 +120,17 @@ pub enum NotificationStatus {
     Cancelled,
 }
 
+/// 通知中引用的文档
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MatchedDocument {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 文档链接
+    pub link: String,
+}
+
 /// 通知模板
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct NotificationTemplate {
@@ -260,6 +284,84 @@ impl NotificationService {
         self.send_notification(message).await
     }
 
+    /// 发送保存的搜索匹配通知
+    #[instrument(skip(self, documents))]
+    pub async fn send_saved_search_alert(
+        &self,
+        tenant_id: Uuid,
+        recipient: &str,
+        search_name: &str,
+        documents: &[MatchedDocument],
+    ) -> Result<Uuid, AiStudioError> {
+        let message = self.create_saved_search_message(tenant_id, recipient, search_name, documents)?;
+        self.send_notification(message).await
+    }
+
+    /// 发送文档复核提醒
+    #[instrument(skip(self, documents))]
+    pub async fn send_document_review_reminder(
+        &self,
+        tenant_id: Uuid,
+        recipient: &str,
+        knowledge_base_name: &str,
+        documents: &[MatchedDocument],
+    ) -> Result<Uuid, AiStudioError> {
+        let message = self.create_document_review_message(tenant_id, recipient, knowledge_base_name, documents)?;
+        self.send_notification(message).await
+    }
+
+    /// 发送用户邀请
+    #[instrument(skip(self, invitation_token))]
+    pub async fn send_user_invitation(
+        &self,
+        tenant_id: Uuid,
+        recipient: &str,
+        tenant_name: &str,
+        invitation_token: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Uuid, AiStudioError> {
+        let message = self.create_user_invitation_message(tenant_id, recipient, tenant_name, invitation_token, expires_at)?;
+        self.send_notification(message).await
+    }
+
+    /// 发送文档评论提及通知
+    #[instrument(skip(self, excerpt))]
+    pub async fn send_document_mention(
+        &self,
+        tenant_id: Uuid,
+        recipient: &str,
+        author: &str,
+        document_title: &str,
+        excerpt: &str,
+    ) -> Result<Uuid, AiStudioError> {
+        let message = self.create_document_mention_message(tenant_id, recipient, author, document_title, excerpt)?;
+        self.send_notification(message).await
+    }
+
+    /// 发送用量异常告警
+    #[instrument(skip(self, anomaly))]
+    pub async fn send_usage_anomaly(
+        &self,
+        anomaly: &usage_anomaly::Model,
+    ) -> Result<Uuid, AiStudioError> {
+        let message = self.create_usage_anomaly_message(anomaly)?;
+        self.send_notification(message).await
+    }
+
+    /// 发送定时报告
+    #[instrument(skip(self, recipients, download_url))]
+    pub async fn send_scheduled_report(
+        &self,
+        tenant_id: Uuid,
+        recipients: &[String],
+        report_name: &str,
+        period: &str,
+        download_url: &str,
+    ) -> Result<Uuid, AiStudioError> {
+        let message = self.create_scheduled_report_message(tenant_id, recipients, report_name, period, download_url)?;
+        self.send_notification(message).await
+    }
+
     /// 发送通知
     #[instrument(skip(self))]
     pub async fn send_notification(
@@ -541,6 +643,283 @@ impl NotificationService {
         })
     }
 
+    /// 创建保存的搜索匹配消息
+    fn create_saved_search_message(
+        &self,
+        tenant_id: Uuid,
+        recipient: &str,
+        search_name: &str,
+        documents: &[MatchedDocument],
+    ) -> Result<NotificationMessage, AiStudioError> {
+        let template = self.templates.get(&NotificationType::SavedSearchMatch)
+            .ok_or_else(|| AiStudioError::internal("保存的搜索通知模板不存在".to_string()))?;
+
+        let document_list = documents.iter()
+            .map(|doc| format!("- {}：{}", doc.title, doc.link))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let title = template.title_template
+            .replace("{search_name}", search_name)
+            .replace("{count}", &documents.len().to_string());
+
+        let content = template.content_template
+            .replace("{search_name}", search_name)
+            .replace("{count}", &documents.len().to_string())
+            .replace("{documents}", &document_list);
+
+        let mut metadata = HashMap::new();
+        metadata.insert("search_name".to_string(), serde_json::json!(search_name));
+        metadata.insert("documents".to_string(), serde_json::json!(documents));
+
+        Ok(NotificationMessage {
+            id: Uuid::new_v4(),
+            tenant_id,
+            notification_type: NotificationType::SavedSearchMatch,
+            title,
+            content,
+            priority: template.default_priority.clone(),
+            channels: template.supported_channels.clone(),
+            recipients: vec![recipient.to_string()],
+            metadata,
+            created_at: Utc::now(),
+            sent_at: None,
+            status: NotificationStatus::Pending,
+            retry_count: 0,
+            max_retries: 3,
+        })
+    }
+
+    /// 创建文档复核提醒消息
+    fn create_document_review_message(
+        &self,
+        tenant_id: Uuid,
+        recipient: &str,
+        knowledge_base_name: &str,
+        documents: &[MatchedDocument],
+    ) -> Result<NotificationMessage, AiStudioError> {
+        let template = self.templates.get(&NotificationType::DocumentReview)
+            .ok_or_else(|| AiStudioError::internal("文档复核通知模板不存在".to_string()))?;
+
+        let document_list = documents.iter()
+            .map(|doc| format!("- {}：{}", doc.title, doc.link))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let title = template.title_template
+            .replace("{knowledge_base}", knowledge_base_name)
+            .replace("{count}", &documents.len().to_string());
+
+        let content = template.content_template
+            .replace("{knowledge_base}", knowledge_base_name)
+            .replace("{count}", &documents.len().to_string())
+            .replace("{documents}", &document_list);
+
+        let mut metadata = HashMap::new();
+        metadata.insert("knowledge_base".to_string(), serde_json::json!(knowledge_base_name));
+        metadata.insert("documents".to_string(), serde_json::json!(documents));
+
+        Ok(NotificationMessage {
+            id: Uuid::new_v4(),
+            tenant_id,
+            notification_type: NotificationType::DocumentReview,
+            title,
+            content,
+            priority: template.default_priority.clone(),
+            channels: template.supported_channels.clone(),
+            recipients: vec![recipient.to_string()],
+            metadata,
+            created_at: Utc::now(),
+            sent_at: None,
+            status: NotificationStatus::Pending,
+            retry_count: 0,
+            max_retries: 3,
+        })
+    }
+
+    /// 创建用户邀请消息
+    fn create_user_invitation_message(
+        &self,
+        tenant_id: Uuid,
+        recipient: &str,
+        tenant_name: &str,
+        invitation_token: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<NotificationMessage, AiStudioError> {
+        let template = self.templates.get(&NotificationType::UserInvitation)
+            .ok_or_else(|| AiStudioError::internal("用户邀请通知模板不存在".to_string()))?;
+
+        let expires_at_text = expires_at.format("%Y-%m-%d %H:%M UTC").to_string();
+
+        let title = template.title_template
+            .replace("{tenant}", tenant_name);
+
+        let content = template.content_template
+            .replace("{tenant}", tenant_name)
+            .replace("{token}", invitation_token)
+            .replace("{expires_at}", &expires_at_text);
+
+        // 邀请令牌只出现在邮件正文中，不写入元数据
+        let mut metadata = HashMap::new();
+        metadata.insert("tenant".to_string(), serde_json::json!(tenant_name));
+        metadata.insert("expires_at".to_string(), serde_json::json!(expires_at));
+
+        Ok(NotificationMessage {
+            id: Uuid::new_v4(),
+            tenant_id,
+            notification_type: NotificationType::UserInvitation,
+            title,
+            content,
+            priority: template.default_priority.clone(),
+            channels: template.supported_channels.clone(),
+            recipients: vec![recipient.to_string()],
+            metadata,
+            created_at: Utc::now(),
+            sent_at: None,
+            status: NotificationStatus::Pending,
+            retry_count: 0,
+            max_retries: 3,
+        })
+    }
+
+    /// 创建定时报告消息
+    fn create_scheduled_report_message(
+        &self,
+        tenant_id: Uuid,
+        recipients: &[String],
+        report_name: &str,
+        period: &str,
+        download_url: &str,
+    ) -> Result<NotificationMessage, AiStudioError> {
+        let template = self.templates.get(&NotificationType::ScheduledReport)
+            .ok_or_else(|| AiStudioError::internal("定时报告模板不存在".to_string()))?;
+
+        let title = template.title_template
+            .replace("{report}", report_name)
+            .replace("{period}", period);
+
+        let content = template.content_template
+            .replace("{report}", report_name)
+            .replace("{period}", period)
+            .replace("{link}", download_url);
+
+        let mut metadata = HashMap::new();
+        metadata.insert("report".to_string(), serde_json::json!(report_name));
+        metadata.insert("period".to_string(), serde_json::json!(period));
+
+        Ok(NotificationMessage {
+            id: Uuid::new_v4(),
+            tenant_id,
+            notification_type: NotificationType::ScheduledReport,
+            title,
+            content,
+            priority: template.default_priority.clone(),
+            channels: template.supported_channels.clone(),
+            recipients: recipients.to_vec(),
+            metadata,
+            created_at: Utc::now(),
+            sent_at: None,
+            status: NotificationStatus::Pending,
+            retry_count: 0,
+            max_retries: 3,
+        })
+    }
+
+    /// 创建文档评论提及消息
+    fn create_document_mention_message(
+        &self,
+        tenant_id: Uuid,
+        recipient: &str,
+        author: &str,
+        document_title: &str,
+        excerpt: &str,
+    ) -> Result<NotificationMessage, AiStudioError> {
+        let template = self.templates.get(&NotificationType::DocumentMention)
+            .ok_or_else(|| AiStudioError::internal("文档提及通知模板不存在".to_string()))?;
+
+        let title = template.title_template
+            .replace("{author}", author)
+            .replace("{document}", document_title);
+
+        let content = template.content_template
+            .replace("{author}", author)
+            .replace("{document}", document_title)
+            .replace("{excerpt}", excerpt);
+
+        let mut metadata = HashMap::new();
+        metadata.insert("author".to_string(), serde_json::json!(author));
+        metadata.insert("document".to_string(), serde_json::json!(document_title));
+
+        Ok(NotificationMessage {
+            id: Uuid::new_v4(),
+            tenant_id,
+            notification_type: NotificationType::DocumentMention,
+            title,
+            content,
+            priority: template.default_priority.clone(),
+            channels: template.supported_channels.clone(),
+            recipients: vec![recipient.to_string()],
+            metadata,
+            created_at: Utc::now(),
+            sent_at: None,
+            status: NotificationStatus::Pending,
+            retry_count: 0,
+            max_retries: 3,
+        })
+    }
+
+    /// 创建用量异常消息
+    fn create_usage_anomaly_message(
+        &self,
+        anomaly: &usage_anomaly::Model,
+    ) -> Result<NotificationMessage, AiStudioError> {
+        let template = self.templates.get(&NotificationType::UsageAnomaly)
+            .ok_or_else(|| AiStudioError::internal("用量异常模板不存在".to_string()))?;
+
+        let metric = anomaly.metric.display_name();
+        let action = match (anomaly.api_key_id, anomaly.throttled) {
+            (Some(key_id), true) => format!("主要来源 API 密钥 {} 已被临时限流", key_id),
+            (Some(key_id), false) => format!("主要来源为 API 密钥 {}", key_id),
+            (None, _) => "未定位到单一来源的 API 密钥".to_string(),
+        };
+
+        let title = template.title_template
+            .replace("{metric}", metric);
+
+        let content = template.content_template
+            .replace("{metric}", metric)
+            .replace("{bucket}", &anomaly.bucket_start.format("%Y-%m-%d %H:00 UTC").to_string())
+            .replace("{observed}", &anomaly.observed_value.to_string())
+            .replace("{baseline}", &format!("{:.1}", anomaly.baseline_mean))
+            .replace("{action}", &action);
+
+        let mut metadata = HashMap::new();
+        metadata.insert("anomaly_id".to_string(), serde_json::json!(anomaly.id));
+        metadata.insert("metric".to_string(), serde_json::json!(anomaly.metric));
+        metadata.insert("observed_value".to_string(), serde_json::json!(anomaly.observed_value));
+        metadata.insert("baseline_mean".to_string(), serde_json::json!(anomaly.baseline_mean));
+        metadata.insert("baseline_stddev".to_string(), serde_json::json!(anomaly.baseline_stddev));
+        metadata.insert("api_key_id".to_string(), serde_json::json!(anomaly.api_key_id));
+        metadata.insert("throttled".to_string(), serde_json::json!(anomaly.throttled));
+
+        Ok(NotificationMessage {
+            id: Uuid::new_v4(),
+            tenant_id: anomaly.tenant_id,
+            notification_type: NotificationType::UsageAnomaly,
+            title,
+            content,
+            priority: template.default_priority.clone(),
+            channels: template.supported_channels.clone(),
+            recipients: self.get_default_recipients(anomaly.tenant_id, &NotificationType::UsageAnomaly),
+            metadata,
+            created_at: Utc::now(),
+            sent_at: None,
+            status: NotificationStatus::Pending,
+            retry_count: 0,
+            max_retries: 5,
+        })
+    }
+
     /// 发送到指定渠道
     async fn send_to_channel(
         &self,
@@ -715,6 +1094,114 @@ impl NotificationService {
             },
         );
 
+        // 保存的搜索匹配模板
+        templates.insert(
+            NotificationType::SavedSearchMatch,
+            NotificationTemplate {
+                id: Uuid::new_v4(),
+                name: "保存的搜索匹配".to_string(),
+                notification_type: NotificationType::SavedSearchMatch,
+                title_template: "保存的搜索「{search_name}」有 {count} 篇新文档".to_string(),
+                content_template: "您订阅的搜索「{search_name}」匹配到 {count} 篇新文档：\n{documents}".to_string(),
+                supported_channels: vec![
+                    NotificationChannel::Email,
+                    NotificationChannel::InApp,
+                ],
+                default_priority: NotificationPriority::Normal,
+                enabled: true,
+            },
+        );
+
+        // 文档复核提醒模板
+        templates.insert(
+            NotificationType::DocumentReview,
+            NotificationTemplate {
+                id: Uuid::new_v4(),
+                name: "文档复核提醒".to_string(),
+                notification_type: NotificationType::DocumentReview,
+                title_template: "知识库「{knowledge_base}」有 {count} 篇文档需要复核".to_string(),
+                content_template: "您负责的以下文档已过期或到期待复核，请确认内容是否仍然有效并续期：\n{documents}".to_string(),
+                supported_channels: vec![
+                    NotificationChannel::Email,
+                    NotificationChannel::InApp,
+                ],
+                default_priority: NotificationPriority::Normal,
+                enabled: true,
+            },
+        );
+
+        // 用户邀请模板
+        templates.insert(
+            NotificationType::UserInvitation,
+            NotificationTemplate {
+                id: Uuid::new_v4(),
+                name: "用户邀请".to_string(),
+                notification_type: NotificationType::UserInvitation,
+                title_template: "您被邀请加入「{tenant}」".to_string(),
+                content_template: "管理员邀请您加入「{tenant}」。请在 {expires_at} 前使用以下邀请码设置密码并激活账户：\n{token}".to_string(),
+                supported_channels: vec![
+                    NotificationChannel::Email,
+                ],
+                default_priority: NotificationPriority::Normal,
+                enabled: true,
+            },
+        );
+
+        // 用量异常模板
+        templates.insert(
+            NotificationType::UsageAnomaly,
+            NotificationTemplate {
+                id: Uuid::new_v4(),
+                name: "用量异常".to_string(),
+                notification_type: NotificationType::UsageAnomaly,
+                title_template: "用量异常：{metric}突增".to_string(),
+                content_template: "{bucket} 起的一小时内{metric}达到 {observed}，显著高于近期每小时平均 {baseline}。{action}。请确认是否为正常业务行为。".to_string(),
+                supported_channels: vec![
+                    NotificationChannel::Email,
+                    NotificationChannel::InApp,
+                    NotificationChannel::Webhook,
+                    NotificationChannel::Slack,
+                ],
+                default_priority: NotificationPriority::High,
+                enabled: true,
+            },
+        );
+
+        // 定时报告模板
+        templates.insert(
+            NotificationType::ScheduledReport,
+            NotificationTemplate {
+                id: Uuid::new_v4(),
+                name: "定时报告".to_string(),
+                notification_type: NotificationType::ScheduledReport,
+                title_template: "定时报告「{report}」（{period}）".to_string(),
+                content_template: "定时报告「{report}」已生成，统计区间 {period}。下载链接：\n{link}".to_string(),
+                supported_channels: vec![
+                    NotificationChannel::Email,
+                ],
+                default_priority: NotificationPriority::Normal,
+                enabled: true,
+            },
+        );
+
+        // 文档提及模板
+        templates.insert(
+            NotificationType::DocumentMention,
+            NotificationTemplate {
+                id: Uuid::new_v4(),
+                name: "文档提及".to_string(),
+                notification_type: NotificationType::DocumentMention,
+                title_template: "{author} 在文档「{document}」的评论中提到了您".to_string(),
+                content_template: "{author} 在文档「{document}」的评论中提到了您：\n{excerpt}".to_string(),
+                supported_channels: vec![
+                    NotificationChannel::Email,
+                    NotificationChannel::InApp,
+                ],
+                default_priority: NotificationPriority::Normal,
+                enabled: true,
+            },
+        );
+
         templates
     }
 }
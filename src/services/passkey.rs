@@ -0,0 +1,1027 @@
+// 通行密钥（WebAuthn）服务
+// 用户在密码之外注册通行密钥并用其登录：生成注册与登录挑战、校验浏览器返回的凭据、管理用户的设备列表与租户的强制策略
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use ciborium::value::Value as CborValue;
+use rand::RngCore;
+use ring::signature;
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryResult, Set, Statement,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::WebAuthnConfig;
+use crate::db::entities::tenant::{self, TenantAuthPolicy};
+use crate::db::entities::usage_anomaly::AnomalyMetric;
+use crate::db::entities::{user, Tenant, User};
+use crate::db::update_with_revision;
+use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace};
+use crate::services::usage_anomaly::record_usage;
+
+/// 挑战用途：注册
+const CEREMONY_REGISTRATION: &str = "registration";
+
+/// 挑战用途：登录
+const CEREMONY_AUTHENTICATION: &str = "authentication";
+
+/// 通行密钥名称的最大长度（字符）
+const MAX_NAME_CHARS: usize = 100;
+
+/// 每个用户最多注册的通行密钥数
+const MAX_CREDENTIALS_PER_USER: u64 = 20;
+
+/// 可被策略强制的角色
+const KNOWN_ROLES: [&str; 4] = ["admin", "manager", "user", "viewer"];
+
+/// COSE 算法：ECDSA P-256 + SHA-256
+const COSE_ALG_ES256: i64 = -7;
+
+/// COSE 算法：Ed25519
+const COSE_ALG_EDDSA: i64 = -8;
+
+/// COSE 算法：RSASSA-PKCS1-v1_5 + SHA-256
+const COSE_ALG_RS256: i64 = -257;
+
+/// 认证器数据标志位
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_BACKUP_ELIGIBLE: u8 = 0x08;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+/// 注册或登录选项，`public_key` 原样传给 `navigator.credentials.create()`/`get()`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PasskeyCeremonyOptions {
+    /// 挑战 ID，完成注册或登录时回传
+    pub challenge_id: Uuid,
+    /// WebAuthn 选项（二进制字段为 base64url 编码）
+    #[schema(value_type = Object)]
+    pub public_key: serde_json::Value,
+}
+
+/// 注册通行密钥请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct RegisterPasskeyRequest {
+    /// 注册选项中的挑战 ID
+    pub challenge_id: Uuid,
+    /// 设备名称，默认按注册日期命名
+    pub name: Option<String>,
+    /// `PublicKeyCredential.toJSON()` 的结果
+    pub credential: RegistrationCredential,
+}
+
+/// 浏览器返回的注册凭据
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct RegistrationCredential {
+    /// 凭据 ID（base64url）
+    pub id: String,
+    pub response: AttestationResponse,
+}
+
+/// 注册凭据的认证器响应
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationResponse {
+    /// base64url 编码的 clientDataJSON
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    /// base64url 编码的 attestationObject
+    pub attestation_object: String,
+    /// 认证器支持的传输方式
+    #[serde(default)]
+    pub transports: Vec<String>,
+}
+
+/// 获取登录选项请求
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct PasskeyLoginOptionsRequest {
+    /// 用户名或邮箱，为空时使用可发现凭据由认证器选择账户
+    pub username: Option<String>,
+    /// 租户标识
+    pub tenant_slug: Option<String>,
+}
+
+/// 通行密钥登录请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PasskeyLoginRequest {
+    /// 登录选项中的挑战 ID
+    pub challenge_id: Uuid,
+    /// `PublicKeyCredential.toJSON()` 的结果
+    pub credential: AssertionCredential,
+    /// 是否记住登录状态
+    pub remember_me: Option<bool>,
+}
+
+/// 浏览器返回的登录凭据
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AssertionCredential {
+    /// 凭据 ID（base64url）
+    pub id: String,
+    pub response: AssertionResponse,
+}
+
+/// 登录凭据的认证器响应
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResponse {
+    /// base64url 编码的 clientDataJSON
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    /// base64url 编码的 authenticatorData
+    pub authenticator_data: String,
+    /// base64url 编码的签名
+    pub signature: String,
+    /// base64url 编码的用户句柄（注册时的用户 ID）
+    pub user_handle: Option<String>,
+}
+
+/// 重命名通行密钥请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct RenamePasskeyRequest {
+    pub name: String,
+}
+
+/// 已注册的通行密钥
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PasskeyCredentialInfo {
+    pub id: Uuid,
+    /// 设备名称
+    pub name: String,
+    /// COSE 签名算法
+    pub algorithm: i32,
+    /// 认证器型号标识
+    pub aaguid: Option<Uuid>,
+    /// 认证器支持的传输方式
+    pub transports: Vec<String>,
+    /// 是否可在设备间同步（如云端钥匙串）
+    pub backup_eligible: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// 浏览器收集的客户端数据
+#[derive(Debug, Deserialize)]
+struct CollectedClientData {
+    #[serde(rename = "type")]
+    ceremony_type: String,
+    challenge: String,
+    origin: String,
+}
+
+/// 解析后的认证器数据
+#[derive(Debug)]
+struct AuthenticatorData {
+    rp_id_hash: [u8; 32],
+    flags: u8,
+    sign_count: u32,
+    attested: Option<AttestedCredential>,
+}
+
+/// 注册时认证器返回的凭据
+#[derive(Debug)]
+struct AttestedCredential {
+    aaguid: [u8; 16],
+    credential_id: Vec<u8>,
+    /// COSE 格式公钥
+    public_key: Vec<u8>,
+}
+
+/// 通过校验的新凭据
+#[derive(Debug)]
+struct VerifiedCredential {
+    credential_id: Vec<u8>,
+    public_key: Vec<u8>,
+    algorithm: i64,
+    sign_count: u32,
+    aaguid: Option<Uuid>,
+    backup_eligible: bool,
+}
+
+/// COSE 公钥
+#[derive(Debug)]
+enum CosePublicKey {
+    Es256 { x: Vec<u8>, y: Vec<u8> },
+    EdDsa { x: Vec<u8> },
+    Rs256 { n: Vec<u8>, e: Vec<u8> },
+}
+
+impl CosePublicKey {
+    fn verify(&self, message: &[u8], sig: &[u8]) -> bool {
+        match self {
+            CosePublicKey::Es256 { x, y } => {
+                let mut point = Vec::with_capacity(1 + x.len() + y.len());
+                point.push(0x04);
+                point.extend_from_slice(x);
+                point.extend_from_slice(y);
+                signature::UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_ASN1, point)
+                    .verify(message, sig)
+                    .is_ok()
+            }
+            CosePublicKey::EdDsa { x } => signature::UnparsedPublicKey::new(&signature::ED25519, x)
+                .verify(message, sig)
+                .is_ok(),
+            CosePublicKey::Rs256 { n, e } => signature::RsaPublicKeyComponents { n, e }
+                .verify(&signature::RSA_PKCS1_2048_8192_SHA256, message, sig)
+                .is_ok(),
+        }
+    }
+}
+
+/// 从数据库取出的挑战
+struct StoredChallenge {
+    tenant_id: Option<Uuid>,
+    user_id: Option<Uuid>,
+    challenge: String,
+}
+
+/// 通行密钥服务
+pub struct PasskeyService {
+    db: DatabaseConnection,
+    config: WebAuthnConfig,
+}
+
+impl PasskeyService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection, config: WebAuthnConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// 生成注册选项，已注册的凭据列入排除列表，避免同一认证器重复注册
+    #[instrument(skip(self))]
+    pub async fn start_registration(&self, tenant_id: Uuid, user_id: Uuid) -> Result<PasskeyCeremonyOptions, AiStudioError> {
+        let user = User::find_by_id(user_id)
+            .filter(user::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("用户"))?;
+
+        let existing = self.credential_descriptors(user_id).await?;
+        if existing.len() as u64 >= MAX_CREDENTIALS_PER_USER {
+            return Err(AiStudioError::validation(
+                "credential",
+                format!("每个用户最多注册 {} 个通行密钥", MAX_CREDENTIALS_PER_USER),
+            ));
+        }
+
+        let (challenge_id, challenge) = self
+            .create_challenge(CEREMONY_REGISTRATION, Some(tenant_id), Some(user_id))
+            .await?;
+        let pub_key_cred_params: Vec<serde_json::Value> = [COSE_ALG_ES256, COSE_ALG_EDDSA, COSE_ALG_RS256]
+            .iter()
+            .map(|alg| serde_json::json!({ "type": "public-key", "alg": alg }))
+            .collect();
+        let public_key = serde_json::json!({
+            "rp": { "id": self.config.rp_id, "name": self.config.rp_name },
+            "user": {
+                "id": URL_SAFE_NO_PAD.encode(user.id.as_bytes()),
+                "name": user.username,
+                "displayName": user.display_name,
+            },
+            "challenge": challenge,
+            "pubKeyCredParams": pub_key_cred_params,
+            "timeout": self.config.challenge_ttl_secs * 1000,
+            "excludeCredentials": existing,
+            "authenticatorSelection": {
+                "residentKey": "preferred",
+                "requireResidentKey": false,
+                "userVerification": self.user_verification(),
+            },
+            "attestation": "none",
+        });
+
+        Ok(PasskeyCeremonyOptions { challenge_id, public_key })
+    }
+
+    /// 校验注册响应并保存凭据
+    ///
+    /// 只请求 `none` 证明，不校验认证器证明声明；凭据的可信度来自已登录用户本人发起的注册。
+    #[instrument(skip(self, request))]
+    pub async fn finish_registration(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: RegisterPasskeyRequest,
+    ) -> Result<PasskeyCredentialInfo, AiStudioError> {
+        let stored = self.take_challenge(request.challenge_id, CEREMONY_REGISTRATION).await?;
+        if stored.user_id != Some(user_id) || stored.tenant_id != Some(tenant_id) {
+            return Err(AiStudioError::unauthorized("通行密钥挑战无效或已过期"));
+        }
+
+        let name = match request.name {
+            Some(name) => normalize_name(&name)?,
+            None => format!("通行密钥 {}", Utc::now().format("%Y-%m-%d")),
+        };
+        let verified = verify_registration(&self.config, &stored.challenge, &request.credential.response)?;
+        if decode_base64url("credential.id", &request.credential.id)? != verified.credential_id {
+            return Err(AiStudioError::validation("credential.id", "凭据 ID 与认证器数据不一致"));
+        }
+
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO passkey_credentials
+                    (id, tenant_id, user_id, credential_id, public_key, algorithm, sign_count, name, aaguid, transports, backup_eligible, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                ON CONFLICT (credential_id) DO NOTHING
+                RETURNING *
+                "#,
+                [
+                    Uuid::new_v4().into(),
+                    tenant_id.into(),
+                    user_id.into(),
+                    verified.credential_id.into(),
+                    verified.public_key.into(),
+                    (verified.algorithm as i32).into(),
+                    (verified.sign_count as i64).into(),
+                    name.into(),
+                    verified.aaguid.into(),
+                    serde_json::json!(request.credential.response.transports).into(),
+                    verified.backup_eligible.into(),
+                    Utc::now().into(),
+                ],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::conflict("该通行密钥已注册"))?;
+
+        let credential = credential_from_row(&row)?;
+        info!(user_id = %user_id, credential = %credential.id, algorithm = credential.algorithm, "通行密钥已注册");
+        Ok(credential)
+    }
+
+    /// 生成登录选项
+    ///
+    /// 提供用户名时只允许该用户的凭据；用户不存在时返回与可发现凭据相同的选项，不暴露账户是否存在。
+    #[instrument(skip(self, request))]
+    pub async fn start_authentication(&self, request: PasskeyLoginOptionsRequest) -> Result<PasskeyCeremonyOptions, AiStudioError> {
+        let tenant_id = match request.tenant_slug.as_deref() {
+            Some(slug) => Tenant::find()
+                .filter(tenant::Column::Slug.eq(slug))
+                .one(&self.db)
+                .await?
+                .map(|tenant| tenant.id),
+            None => None,
+        };
+
+        let user = match request.username.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+            Some(username) => {
+                let mut query = User::find().filter(
+                    Condition::any()
+                        .add(user::Column::Username.eq(username))
+                        .add(user::Column::Email.eq(username)),
+                );
+                if let Some(tenant_id) = tenant_id {
+                    query = query.filter(user::Column::TenantId.eq(tenant_id));
+                }
+                query.one(&self.db).await?
+            }
+            None => None,
+        };
+        let allow_credentials = match &user {
+            Some(user) => self.credential_descriptors(user.id).await?,
+            None => Vec::new(),
+        };
+
+        let (challenge_id, challenge) = self
+            .create_challenge(CEREMONY_AUTHENTICATION, tenant_id, user.as_ref().map(|u| u.id))
+            .await?;
+        let public_key = serde_json::json!({
+            "challenge": challenge,
+            "timeout": self.config.challenge_ttl_secs * 1000,
+            "rpId": self.config.rp_id,
+            "allowCredentials": allow_credentials,
+            "userVerification": self.user_verification(),
+        });
+
+        Ok(PasskeyCeremonyOptions { challenge_id, public_key })
+    }
+
+    /// 校验登录响应，成功时返回对应用户并更新签名计数
+    #[instrument(skip(self, request))]
+    pub async fn finish_authentication(&self, request: &PasskeyLoginRequest) -> Result<user::Model, AiStudioError> {
+        let stored = self.take_challenge(request.challenge_id, CEREMONY_AUTHENTICATION).await?;
+        let credential_id = decode_base64url("credential.id", &request.credential.id)?;
+
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT * FROM passkey_credentials WHERE credential_id = $1",
+                [credential_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::unauthorized("通行密钥无效"))?;
+        let id: Uuid = row.try_get("", "id")?;
+        let tenant_id: Uuid = row.try_get("", "tenant_id")?;
+        let user_id: Uuid = row.try_get("", "user_id")?;
+        let public_key: Vec<u8> = row.try_get("", "public_key")?;
+        let sign_count: i64 = row.try_get("", "sign_count")?;
+
+        if stored.user_id.is_some_and(|expected| expected != user_id)
+            || stored.tenant_id.is_some_and(|expected| expected != tenant_id)
+        {
+            return Err(AiStudioError::unauthorized("通行密钥无效"));
+        }
+        if let Some(user_handle) = &request.credential.response.user_handle {
+            if decode_base64url("credential.response.userHandle", user_handle)? != user_id.as_bytes() {
+                return Err(AiStudioError::unauthorized("通行密钥无效"));
+            }
+        }
+
+        let new_sign_count = match verify_assertion(
+            &self.config,
+            &stored.challenge,
+            &public_key,
+            sign_count as u32,
+            &request.credential.response,
+        ) {
+            Ok(count) => count,
+            Err(e) => {
+                warn!(user_id = %user_id, credential = %id, error = %e, "通行密钥校验失败");
+                record_usage(tenant_id, AnomalyMetric::FailedLogins, None, 1);
+                return Err(e);
+            }
+        };
+
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE passkey_credentials SET sign_count = $2, last_used_at = $3 WHERE id = $1",
+                [id.into(), (new_sign_count as i64).into(), Utc::now().into()],
+            ))
+            .await?;
+
+        User::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::unauthorized("通行密钥无效"))
+    }
+
+    /// 列出用户的通行密钥
+    pub async fn list_credentials(&self, user_id: Uuid) -> Result<Vec<PasskeyCredentialInfo>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT * FROM passkey_credentials WHERE user_id = $1 ORDER BY created_at",
+                [user_id.into()],
+            ))
+            .await?;
+        rows.iter().map(credential_from_row).collect()
+    }
+
+    /// 重命名用户的通行密钥
+    pub async fn rename_credential(
+        &self,
+        user_id: Uuid,
+        credential_id: Uuid,
+        name: &str,
+    ) -> Result<PasskeyCredentialInfo, AiStudioError> {
+        let name = normalize_name(name)?;
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE passkey_credentials SET name = $3 WHERE id = $1 AND user_id = $2 RETURNING *",
+                [credential_id.into(), user_id.into(), name.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("通行密钥"))?;
+        credential_from_row(&row)
+    }
+
+    /// 删除用户的通行密钥，租户强制该用户角色使用通行密钥时不能删除最后一个
+    #[instrument(skip(self))]
+    pub async fn delete_credential(&self, tenant_id: Uuid, user_id: Uuid, credential_id: Uuid) -> Result<(), AiStudioError> {
+        let user = User::find_by_id(user_id)
+            .filter(user::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("用户"))?;
+        let policy = self.get_auth_policy(tenant_id).await?;
+        if policy.requires_passkey(&user.role.to_string()) && count_user_passkeys(&self.db, user_id).await? <= 1 {
+            return Err(AiStudioError::conflict("租户要求该角色使用通行密钥登录，不能删除最后一个通行密钥"));
+        }
+
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "DELETE FROM passkey_credentials WHERE id = $1 AND user_id = $2",
+                [credential_id.into(), user_id.into()],
+            ))
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(AiStudioError::not_found("通行密钥"));
+        }
+
+        info!(user_id = %user_id, credential = %credential_id, "通行密钥已删除");
+        Ok(())
+    }
+
+    /// 获取租户登录认证策略
+    pub async fn get_auth_policy(&self, tenant_id: Uuid) -> Result<TenantAuthPolicy, AiStudioError> {
+        let tenant = Tenant::find_by_id(tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+        Ok(tenant.get_config().unwrap_or_default().auth_policy)
+    }
+
+    /// 替换租户登录认证策略
+    #[instrument(skip(self, policy))]
+    pub async fn update_auth_policy(
+        &self,
+        tenant_id: Uuid,
+        policy: TenantAuthPolicy,
+    ) -> Result<TenantAuthPolicy, AiStudioError> {
+        let policy = normalize_auth_policy(policy)?;
+
+        let tenant = Tenant::find_by_id(tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+        let mut config = tenant.get_config()
+            .map_err(|e| AiStudioError::internal(format!("解析租户配置失败: {}", e)))?;
+        config.auth_policy = policy.clone();
+
+        let revision = tenant.revision;
+        let mut active_tenant: tenant::ActiveModel = tenant.into();
+        active_tenant.config = Set(serde_json::to_value(&config)?);
+        active_tenant.updated_at = Set(Utc::now().into());
+        update_with_revision(&self.db, active_tenant, tenant::Column::Revision, revision, "租户").await?;
+        cache::invalidate(CacheNamespace::Tenant, tenant_id).await;
+
+        info!(tenant_id = %tenant_id, roles = ?policy.passkey_required_roles, "租户登录认证策略已更新");
+        Ok(policy)
+    }
+
+    fn user_verification(&self) -> &'static str {
+        if self.config.require_user_verification { "required" } else { "preferred" }
+    }
+
+    /// 用户已注册凭据的描述符，用于排除列表与允许列表
+    async fn credential_descriptors(&self, user_id: Uuid) -> Result<Vec<serde_json::Value>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT credential_id, transports FROM passkey_credentials WHERE user_id = $1",
+                [user_id.into()],
+            ))
+            .await?;
+
+        let mut descriptors = Vec::with_capacity(rows.len());
+        for row in rows {
+            let credential_id: Vec<u8> = row.try_get("", "credential_id")?;
+            let transports: serde_json::Value = row.try_get("", "transports")?;
+            descriptors.push(serde_json::json!({
+                "type": "public-key",
+                "id": URL_SAFE_NO_PAD.encode(credential_id),
+                "transports": transports,
+            }));
+        }
+        Ok(descriptors)
+    }
+
+    /// 生成并保存一次性挑战，顺带清理过期挑战
+    async fn create_challenge(
+        &self,
+        ceremony: &str,
+        tenant_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+    ) -> Result<(Uuid, String), AiStudioError> {
+        let now = Utc::now();
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "DELETE FROM passkey_challenges WHERE expires_at < $1",
+                [now.into()],
+            ))
+            .await?;
+
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let challenge = URL_SAFE_NO_PAD.encode(bytes);
+        let id = Uuid::new_v4();
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO passkey_challenges (id, tenant_id, user_id, ceremony, challenge, expires_at, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+                [
+                    id.into(),
+                    tenant_id.into(),
+                    user_id.into(),
+                    ceremony.into(),
+                    challenge.clone().into(),
+                    (now + Duration::seconds(self.config.challenge_ttl_secs as i64)).into(),
+                    now.into(),
+                ],
+            ))
+            .await?;
+        Ok((id, challenge))
+    }
+
+    /// 取出并作废挑战，每个挑战只能使用一次
+    async fn take_challenge(&self, challenge_id: Uuid, ceremony: &str) -> Result<StoredChallenge, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                DELETE FROM passkey_challenges
+                WHERE id = $1 AND ceremony = $2 AND expires_at >= $3
+                RETURNING tenant_id, user_id, challenge
+                "#,
+                [challenge_id.into(), ceremony.into(), Utc::now().into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::unauthorized("通行密钥挑战无效或已过期"))?;
+
+        Ok(StoredChallenge {
+            tenant_id: row.try_get("", "tenant_id")?,
+            user_id: row.try_get("", "user_id")?,
+            challenge: row.try_get("", "challenge")?,
+        })
+    }
+}
+
+/// 用户已注册的通行密钥数量
+pub async fn count_user_passkeys(db: &DatabaseConnection, user_id: Uuid) -> Result<u64, AiStudioError> {
+    let row = db
+        .query_one(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT COUNT(*) AS count FROM passkey_credentials WHERE user_id = $1",
+            [user_id.into()],
+        ))
+        .await?;
+    let count: i64 = match row {
+        Some(row) => row.try_get("", "count")?,
+        None => 0,
+    };
+    Ok(count as u64)
+}
+
+/// 校验注册响应：客户端数据、依赖方、用户在场标志与凭据公钥
+fn verify_registration(
+    config: &WebAuthnConfig,
+    expected_challenge: &str,
+    response: &AttestationResponse,
+) -> Result<VerifiedCredential, AiStudioError> {
+    let client_data_json = decode_base64url("clientDataJSON", &response.client_data_json)?;
+    verify_client_data(config, &client_data_json, "webauthn.create", expected_challenge)?;
+
+    let attestation_object = decode_base64url("attestationObject", &response.attestation_object)?;
+    let attestation: CborValue = ciborium::de::from_reader(attestation_object.as_slice())
+        .map_err(|_| AiStudioError::validation("attestationObject", "无法解析证明对象"))?;
+    let auth_data = cbor_map_get(&attestation, &CborValue::Text("authData".to_string()))
+        .and_then(|value| match value {
+            CborValue::Bytes(bytes) => Some(bytes),
+            _ => None,
+        })
+        .ok_or_else(|| AiStudioError::validation("attestationObject", "证明对象缺少认证器数据"))?;
+
+    let auth_data = parse_authenticator_data(auth_data)?;
+    verify_authenticator_flags(config, &auth_data)?;
+    let attested = auth_data
+        .attested
+        .ok_or_else(|| AiStudioError::validation("attestationObject", "认证器数据缺少凭据"))?;
+    let (algorithm, _) = parse_cose_key(&attested.public_key)?;
+
+    Ok(VerifiedCredential {
+        credential_id: attested.credential_id,
+        public_key: attested.public_key,
+        algorithm,
+        sign_count: auth_data.sign_count,
+        aaguid: Some(Uuid::from_bytes(attested.aaguid)).filter(|aaguid| !aaguid.is_nil()),
+        backup_eligible: auth_data.flags & FLAG_BACKUP_ELIGIBLE != 0,
+    })
+}
+
+/// 校验登录响应，返回新的签名计数
+///
+/// 认证器上报的计数不大于已保存的计数时视为凭据被复制；两者均为 0 表示认证器不支持计数。
+fn verify_assertion(
+    config: &WebAuthnConfig,
+    expected_challenge: &str,
+    public_key: &[u8],
+    stored_sign_count: u32,
+    response: &AssertionResponse,
+) -> Result<u32, AiStudioError> {
+    let client_data_json = decode_base64url("clientDataJSON", &response.client_data_json)?;
+    verify_client_data(config, &client_data_json, "webauthn.get", expected_challenge)?;
+
+    let authenticator_data = decode_base64url("authenticatorData", &response.authenticator_data)?;
+    let auth_data = parse_authenticator_data(&authenticator_data)?;
+    verify_authenticator_flags(config, &auth_data)?;
+
+    let sig = decode_base64url("signature", &response.signature)?;
+    let (_, key) = parse_cose_key(public_key)?;
+    let mut message = authenticator_data;
+    message.extend_from_slice(&Sha256::digest(&client_data_json));
+    if !key.verify(&message, &sig) {
+        return Err(AiStudioError::unauthorized("通行密钥签名无效"));
+    }
+
+    if (auth_data.sign_count != 0 || stored_sign_count != 0) && auth_data.sign_count <= stored_sign_count {
+        return Err(AiStudioError::unauthorized("通行密钥签名计数异常，凭据可能已被复制"));
+    }
+    Ok(auth_data.sign_count)
+}
+
+fn verify_client_data(
+    config: &WebAuthnConfig,
+    client_data_json: &[u8],
+    expected_type: &str,
+    expected_challenge: &str,
+) -> Result<(), AiStudioError> {
+    let client_data: CollectedClientData = serde_json::from_slice(client_data_json)
+        .map_err(|_| AiStudioError::validation("clientDataJSON", "无法解析客户端数据"))?;
+
+    if client_data.ceremony_type != expected_type {
+        return Err(AiStudioError::validation("clientDataJSON", format!("客户端数据类型应为 {}", expected_type)));
+    }
+    if decode_base64url("clientDataJSON.challenge", &client_data.challenge)?
+        != decode_base64url("challenge", expected_challenge)?
+    {
+        return Err(AiStudioError::unauthorized("通行密钥挑战不匹配"));
+    }
+    let origin = client_data.origin.trim_end_matches('/');
+    if !config.origins.iter().any(|allowed| allowed.trim_end_matches('/') == origin) {
+        return Err(AiStudioError::unauthorized(format!("不允许的来源: {}", client_data.origin)));
+    }
+    Ok(())
+}
+
+fn verify_authenticator_flags(config: &WebAuthnConfig, auth_data: &AuthenticatorData) -> Result<(), AiStudioError> {
+    if auth_data.rp_id_hash[..] != Sha256::digest(config.rp_id.as_bytes())[..] {
+        return Err(AiStudioError::unauthorized("通行密钥不属于当前依赖方"));
+    }
+    if auth_data.flags & FLAG_USER_PRESENT == 0 {
+        return Err(AiStudioError::unauthorized("认证器未确认用户在场"));
+    }
+    if config.require_user_verification && auth_data.flags & FLAG_USER_VERIFIED == 0 {
+        return Err(AiStudioError::unauthorized("认证器未完成用户验证"));
+    }
+    Ok(())
+}
+
+/// 解析认证器数据：rpIdHash(32) | flags(1) | signCount(4) | [attestedCredentialData] | [extensions]
+fn parse_authenticator_data(data: &[u8]) -> Result<AuthenticatorData, AiStudioError> {
+    let invalid = || AiStudioError::validation("authenticatorData", "认证器数据格式无效");
+    if data.len() < 37 {
+        return Err(invalid());
+    }
+    let rp_id_hash: [u8; 32] = data[..32].try_into().map_err(|_| invalid())?;
+    let flags = data[32];
+    let sign_count = u32::from_be_bytes(data[33..37].try_into().map_err(|_| invalid())?);
+
+    let attested = if flags & FLAG_ATTESTED_CREDENTIAL != 0 {
+        let rest = &data[37..];
+        if rest.len() < 18 {
+            return Err(invalid());
+        }
+        let aaguid: [u8; 16] = rest[..16].try_into().map_err(|_| invalid())?;
+        let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+        let rest = &rest[18..];
+        if rest.len() < id_len {
+            return Err(invalid());
+        }
+        let credential_id = rest[..id_len].to_vec();
+
+        // 公钥之后可能跟随扩展数据，按 CBOR 解析出公钥实际占用的字节
+        let key_bytes = &rest[id_len..];
+        let mut reader = key_bytes;
+        let _: CborValue = ciborium::de::from_reader(&mut reader).map_err(|_| invalid())?;
+        let public_key = key_bytes[..key_bytes.len() - reader.len()].to_vec();
+
+        Some(AttestedCredential { aaguid, credential_id, public_key })
+    } else {
+        None
+    };
+
+    Ok(AuthenticatorData { rp_id_hash, flags, sign_count, attested })
+}
+
+/// 解析 COSE 公钥，返回算法与公钥
+fn parse_cose_key(bytes: &[u8]) -> Result<(i64, CosePublicKey), AiStudioError> {
+    let invalid = |message: &str| AiStudioError::validation("publicKey", message.to_string());
+    let key: CborValue = ciborium::de::from_reader(bytes).map_err(|_| invalid("无法解析凭据公钥"))?;
+    let int_label = |label: i64| cbor_map_get(&key, &CborValue::Integer(label.into()));
+    let int_value = |label: i64| match int_label(label) {
+        Some(CborValue::Integer(value)) => i64::try_from(i128::from(*value)).ok(),
+        _ => None,
+    };
+    let bytes_value = |label: i64| match int_label(label) {
+        Some(CborValue::Bytes(value)) => Some(value.clone()),
+        _ => None,
+    };
+
+    let kty = int_value(1).ok_or_else(|| invalid("凭据公钥缺少密钥类型"))?;
+    let alg = int_value(3).ok_or_else(|| invalid("凭据公钥缺少算法"))?;
+    let key = match (kty, alg) {
+        // EC2 + P-256
+        (2, COSE_ALG_ES256) if int_value(-1) == Some(1) => CosePublicKey::Es256 {
+            x: bytes_value(-2).filter(|x| x.len() == 32).ok_or_else(|| invalid("凭据公钥坐标无效"))?,
+            y: bytes_value(-3).filter(|y| y.len() == 32).ok_or_else(|| invalid("凭据公钥坐标无效"))?,
+        },
+        // OKP + Ed25519
+        (1, COSE_ALG_EDDSA) if int_value(-1) == Some(6) => CosePublicKey::EdDsa {
+            x: bytes_value(-2).filter(|x| x.len() == 32).ok_or_else(|| invalid("凭据公钥无效"))?,
+        },
+        (3, COSE_ALG_RS256) => CosePublicKey::Rs256 {
+            n: bytes_value(-1).ok_or_else(|| invalid("凭据公钥模数缺失"))?,
+            e: bytes_value(-2).ok_or_else(|| invalid("凭据公钥指数缺失"))?,
+        },
+        _ => return Err(invalid("不支持的凭据公钥算法")),
+    };
+    Ok((alg, key))
+}
+
+fn cbor_map_get<'a>(value: &'a CborValue, key: &CborValue) -> Option<&'a CborValue> {
+    match value {
+        CborValue::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+        _ => None,
+    }
+}
+
+fn decode_base64url(field: &str, value: &str) -> Result<Vec<u8>, AiStudioError> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| AiStudioError::validation(field, "不是有效的 base64url 编码"))
+}
+
+fn normalize_name(name: &str) -> Result<String, AiStudioError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(AiStudioError::validation("name", format!("名称不能为空且不超过 {} 个字符", MAX_NAME_CHARS)));
+    }
+    Ok(name.to_string())
+}
+
+/// 校验并规范化策略：角色转为小写并去重
+fn normalize_auth_policy(policy: TenantAuthPolicy) -> Result<TenantAuthPolicy, AiStudioError> {
+    let mut roles: Vec<String> = Vec::new();
+    for role in policy.passkey_required_roles {
+        let role = role.trim().to_lowercase();
+        if !KNOWN_ROLES.contains(&role.as_str()) {
+            return Err(AiStudioError::validation(
+                "passkey_required_roles",
+                format!("未知角色 {}，可选值: {}", role, KNOWN_ROLES.join(", ")),
+            ));
+        }
+        if !roles.contains(&role) {
+            roles.push(role);
+        }
+    }
+    Ok(TenantAuthPolicy { passkey_required_roles: roles })
+}
+
+fn credential_from_row(row: &QueryResult) -> Result<PasskeyCredentialInfo, AiStudioError> {
+    let transports: serde_json::Value = row.try_get("", "transports")?;
+    Ok(PasskeyCredentialInfo {
+        id: row.try_get("", "id")?,
+        name: row.try_get("", "name")?,
+        algorithm: row.try_get("", "algorithm")?,
+        aaguid: row.try_get("", "aaguid")?,
+        transports: serde_json::from_value(transports).unwrap_or_default(),
+        backup_eligible: row.try_get("", "backup_eligible")?,
+        created_at: row.try_get("", "created_at")?,
+        last_used_at: row.try_get("", "last_used_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+
+    fn challenge() -> String {
+        URL_SAFE_NO_PAD.encode([7u8; 32])
+    }
+
+    fn config() -> WebAuthnConfig {
+        WebAuthnConfig::default()
+    }
+
+    fn client_data(ceremony_type: &str, challenge: &str, origin: &str) -> String {
+        URL_SAFE_NO_PAD.encode(
+            serde_json::json!({ "type": ceremony_type, "challenge": challenge, "origin": origin }).to_string(),
+        )
+    }
+
+    fn auth_data(flags: u8, sign_count: u32, attested: Option<(&[u8], &[u8])>) -> Vec<u8> {
+        let mut data = Sha256::digest(config().rp_id.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        if let Some((credential_id, public_key)) = attested {
+            data.extend_from_slice(&[0u8; 16]);
+            data.extend_from_slice(&(credential_id.len() as u16).to_be_bytes());
+            data.extend_from_slice(credential_id);
+            data.extend_from_slice(public_key);
+        }
+        data
+    }
+
+    /// 生成 P-256 密钥对与对应的 COSE 公钥
+    fn es256_key() -> (EcdsaKeyPair, Vec<u8>) {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+        let point = key_pair.public_key().as_ref().to_vec();
+        let cose = CborValue::Map(vec![
+            (CborValue::Integer(1.into()), CborValue::Integer(2.into())),
+            (CborValue::Integer(3.into()), CborValue::Integer(COSE_ALG_ES256.into())),
+            (CborValue::Integer((-1).into()), CborValue::Integer(1.into())),
+            (CborValue::Integer((-2).into()), CborValue::Bytes(point[1..33].to_vec())),
+            (CborValue::Integer((-3).into()), CborValue::Bytes(point[33..65].to_vec())),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&cose, &mut bytes).unwrap();
+        (key_pair, bytes)
+    }
+
+    fn assertion(key_pair: &EcdsaKeyPair, sign_count: u32, challenge: &str) -> AssertionResponse {
+        let client_data_json = client_data("webauthn.get", challenge, "http://localhost:3000");
+        let authenticator_data = auth_data(FLAG_USER_PRESENT, sign_count, None);
+        let mut message = authenticator_data.clone();
+        message.extend_from_slice(&Sha256::digest(URL_SAFE_NO_PAD.decode(&client_data_json).unwrap()));
+        let sig = key_pair.sign(&SystemRandom::new(), &message).unwrap();
+        AssertionResponse {
+            client_data_json,
+            authenticator_data: URL_SAFE_NO_PAD.encode(authenticator_data),
+            signature: URL_SAFE_NO_PAD.encode(sig.as_ref()),
+            user_handle: None,
+        }
+    }
+
+    #[test]
+    fn test_register_then_authenticate_es256() {
+        let (key_pair, cose_key) = es256_key();
+        let credential_id = b"credential-1".to_vec();
+        let attestation = CborValue::Map(vec![
+            (CborValue::Text("fmt".to_string()), CborValue::Text("none".to_string())),
+            (CborValue::Text("attStmt".to_string()), CborValue::Map(Vec::new())),
+            (
+                CborValue::Text("authData".to_string()),
+                CborValue::Bytes(auth_data(FLAG_USER_PRESENT | FLAG_ATTESTED_CREDENTIAL, 0, Some((&credential_id, &cose_key)))),
+            ),
+        ]);
+        let mut attestation_object = Vec::new();
+        ciborium::ser::into_writer(&attestation, &mut attestation_object).unwrap();
+        let response = AttestationResponse {
+            client_data_json: client_data("webauthn.create", &challenge(), "http://localhost:3000"),
+            attestation_object: URL_SAFE_NO_PAD.encode(attestation_object),
+            transports: vec!["internal".to_string()],
+        };
+
+        let verified = verify_registration(&config(), &challenge(), &response).unwrap();
+        assert_eq!(verified.credential_id, credential_id);
+        assert_eq!(verified.algorithm, COSE_ALG_ES256);
+        assert_eq!(verified.public_key, cose_key);
+        assert!(verified.aaguid.is_none());
+
+        let count = verify_assertion(&config(), &challenge(), &verified.public_key, 0, &assertion(&key_pair, 1, &challenge())).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_assertion_rejections() {
+        let (key_pair, cose_key) = es256_key();
+
+        // 挑战不匹配
+        let other = URL_SAFE_NO_PAD.encode(b"another challenge");
+        assert!(verify_assertion(&config(), &challenge(), &cose_key, 0, &assertion(&key_pair, 1, &other)).is_err());
+
+        // 签名计数回退
+        assert!(verify_assertion(&config(), &challenge(), &cose_key, 5, &assertion(&key_pair, 5, &challenge())).is_err());
+
+        // 其他密钥签名
+        let (other_key, _) = es256_key();
+        assert!(verify_assertion(&config(), &challenge(), &cose_key, 0, &assertion(&other_key, 1, &challenge())).is_err());
+
+        // 不允许的来源
+        let mut config = config();
+        config.origins = vec!["https://studio.example.com".to_string()];
+        assert!(verify_assertion(&config, &challenge(), &cose_key, 0, &assertion(&key_pair, 1, &challenge())).is_err());
+    }
+
+    #[test]
+    fn test_normalize_auth_policy() {
+        let policy = normalize_auth_policy(TenantAuthPolicy {
+            passkey_required_roles: vec!["Admin".to_string(), "admin".to_string(), "manager".to_string()],
+        })
+        .unwrap();
+        assert_eq!(policy.passkey_required_roles, vec!["admin", "manager"]);
+        assert!(policy.requires_passkey("admin"));
+        assert!(!policy.requires_passkey("user"));
+
+        assert!(normalize_auth_policy(TenantAuthPolicy { passkey_required_roles: vec!["root".to_string()] }).is_err());
+    }
+}
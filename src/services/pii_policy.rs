@@ -0,0 +1,368 @@
+// 个人信息索引策略服务
+// 索引时按知识库策略对含个人信息的文档块脱敏、标记或排除，汇总受影响的文档块，并记录合规人员的放行/排除裁定
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::pii::scan_pii;
+use crate::db::entities::document_chunk::ChunkPii;
+use crate::db::entities::knowledge_base::{PiiIndexAction, PiiIndexPolicy, PiiKind, PiiOverrideDecision};
+use crate::errors::AiStudioError;
+
+/// 可查看报告并作出裁定的角色
+pub const COMPLIANCE_OFFICER_ROLE: &str = "compliance_officer";
+
+/// 报告中最多列出的文档块数
+const MAX_REPORT_ENTRIES: i64 = 1000;
+
+/// 报告中内容预览的最大字符数
+const PREVIEW_CHARS: usize = 200;
+
+/// 单个文档块按策略处理后的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiiTreatment {
+    /// 写入索引的内容
+    pub content: String,
+    /// 检测结果，未检测到或策略未启用时为空
+    pub pii: Option<ChunkPii>,
+}
+
+impl PiiTreatment {
+    /// 是否排除在向量索引之外
+    pub fn excluded(&self) -> bool {
+        self.pii.as_ref().is_some_and(|pii| pii.action == PiiIndexAction::Exclude)
+    }
+}
+
+/// 按策略与裁定处理文档块内容
+///
+/// 裁定优先于策略：放行的内容按原文索引并保留标记，排除的内容不写入向量索引。
+pub fn apply_pii_policy(
+    policy: &PiiIndexPolicy,
+    overrides: &HashMap<String, PiiOverrideDecision>,
+    content: &str,
+) -> PiiTreatment {
+    let unchanged = || PiiTreatment { content: content.to_string(), pii: None };
+    if !policy.enabled {
+        return unchanged();
+    }
+    let scan = scan_pii(content, &policy.detectors);
+    if !scan.found() {
+        return unchanged();
+    }
+
+    let original_hash = format!("{:x}", md5::compute(content));
+    let decision = overrides.get(&original_hash).copied();
+    let action = match decision {
+        Some(PiiOverrideDecision::Allow) => PiiIndexAction::Tag,
+        Some(PiiOverrideDecision::Exclude) => PiiIndexAction::Exclude,
+        None => policy.action,
+    };
+    PiiTreatment {
+        content: if action == PiiIndexAction::Mask { scan.masked.clone() } else { content.to_string() },
+        pii: Some(ChunkPii { kinds: scan.kinds(), action, decision, original_hash }),
+    }
+}
+
+/// 受影响的文档块
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PiiReportEntry {
+    /// 文档块 ID
+    pub chunk_id: Uuid,
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub document_title: String,
+    /// 块序号
+    pub chunk_index: i32,
+    /// 检测到的类型
+    pub kinds: Vec<PiiKind>,
+    /// 实际采用的处理方式
+    pub action: PiiIndexAction,
+    /// 合规人员的裁定
+    pub decision: Option<PiiOverrideDecision>,
+    /// 脱敏后的内容预览
+    pub preview: String,
+}
+
+/// 个人信息索引报告
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PiiReport {
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 当前策略
+    pub policy: PiiIndexPolicy,
+    /// 受影响的文档块总数
+    pub affected_chunks: u64,
+    /// 按处理方式统计的块数
+    pub by_action: BTreeMap<String, u64>,
+    /// 按个人信息类型统计的块数
+    pub by_kind: BTreeMap<String, u64>,
+    /// 受影响的文档块，最多 1000 条
+    pub entries: Vec<PiiReportEntry>,
+}
+
+/// 裁定请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PiiOverrideRequest {
+    /// 裁定
+    pub decision: PiiOverrideDecision,
+    /// 理由
+    pub reason: Option<String>,
+}
+
+/// 裁定记录
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PiiOverride {
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 原文哈希
+    pub content_hash: String,
+    /// 裁定
+    pub decision: PiiOverrideDecision,
+    /// 理由
+    pub reason: Option<String>,
+    /// 裁定人
+    pub decided_by: Uuid,
+    /// 裁定时间
+    pub decided_at: DateTime<Utc>,
+    /// 包含该内容、需要重建索引的文档
+    pub affected_documents: Vec<Uuid>,
+}
+
+/// 个人信息索引策略服务
+pub struct PiiPolicyService {
+    db: DatabaseConnection,
+}
+
+impl PiiPolicyService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 知识库的全部裁定，按原文哈希索引
+    pub async fn overrides(&self, knowledge_base_id: Uuid) -> Result<HashMap<String, PiiOverrideDecision>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT content_hash, decision FROM kb_pii_overrides WHERE knowledge_base_id = $1",
+                [knowledge_base_id.into()],
+            ))
+            .await?;
+
+        let mut overrides = HashMap::with_capacity(rows.len());
+        for row in rows {
+            let decision: String = row.try_get("", "decision")?;
+            overrides.insert(row.try_get("", "content_hash")?, parse_decision(&decision)?);
+        }
+        Ok(overrides)
+    }
+
+    /// 生成知识库的个人信息索引报告
+    #[instrument(skip(self, policy))]
+    pub async fn report(&self, knowledge_base_id: Uuid, policy: PiiIndexPolicy) -> Result<PiiReport, AiStudioError> {
+        let by_action_rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT metadata->'pii'->>'action' AS action, COUNT(*) AS chunks
+                FROM document_chunks
+                WHERE knowledge_base_id = $1 AND metadata ? 'pii'
+                GROUP BY 1
+                "#,
+                [knowledge_base_id.into()],
+            ))
+            .await?;
+        let mut by_action = BTreeMap::new();
+        for row in by_action_rows {
+            let action: String = row.try_get("", "action")?;
+            let chunks: i64 = row.try_get("", "chunks")?;
+            by_action.insert(action, chunks as u64);
+        }
+
+        let by_kind_rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT kind, COUNT(*) AS chunks
+                FROM document_chunks, jsonb_array_elements_text(metadata->'pii'->'kinds') AS kind
+                WHERE knowledge_base_id = $1 AND metadata ? 'pii'
+                GROUP BY kind
+                "#,
+                [knowledge_base_id.into()],
+            ))
+            .await?;
+        let mut by_kind = BTreeMap::new();
+        for row in by_kind_rows {
+            let kind: String = row.try_get("", "kind")?;
+            let chunks: i64 = row.try_get("", "chunks")?;
+            by_kind.insert(kind, chunks as u64);
+        }
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT c.id, c.document_id, d.title, c.chunk_index, c.content, c.metadata->'pii' AS pii
+                FROM document_chunks c
+                JOIN documents d ON d.id = c.document_id
+                WHERE c.knowledge_base_id = $1 AND c.metadata ? 'pii'
+                ORDER BY d.title, c.chunk_index
+                LIMIT $2
+                "#,
+                [knowledge_base_id.into(), MAX_REPORT_ENTRIES.into()],
+            ))
+            .await?;
+        let mut entries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let pii: ChunkPii = serde_json::from_value(row.try_get("", "pii")?)?;
+            let content: String = row.try_get("", "content")?;
+            let preview: String = scan_pii(&content, &PiiKind::ALL).masked.chars().take(PREVIEW_CHARS).collect();
+            entries.push(PiiReportEntry {
+                chunk_id: row.try_get("", "id")?,
+                document_id: row.try_get("", "document_id")?,
+                document_title: row.try_get("", "title")?,
+                chunk_index: row.try_get("", "chunk_index")?,
+                kinds: pii.kinds,
+                action: pii.action,
+                decision: pii.decision,
+                preview,
+            });
+        }
+
+        Ok(PiiReport {
+            knowledge_base_id,
+            policy,
+            affected_chunks: by_action.values().sum(),
+            by_action,
+            by_kind,
+            entries,
+        })
+    }
+
+    /// 对文档块的内容作出裁定
+    ///
+    /// 裁定按原文哈希记录，对知识库中所有相同内容的块生效，相关文档重建索引后按裁定处理。
+    #[instrument(skip(self, request))]
+    pub async fn set_override(
+        &self,
+        knowledge_base_id: Uuid,
+        chunk_id: Uuid,
+        request: PiiOverrideRequest,
+        decided_by: Uuid,
+    ) -> Result<PiiOverride, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT metadata->'pii'->>'original_hash' AS content_hash FROM document_chunks \
+                 WHERE id = $1 AND knowledge_base_id = $2",
+                [chunk_id.into(), knowledge_base_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("文档块"))?;
+        let content_hash: String = row
+            .try_get::<Option<String>>("", "content_hash")?
+            .ok_or_else(|| AiStudioError::validation("chunk_id", "该文档块未检测到个人信息"))?;
+
+        let decided_at = Utc::now();
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO kb_pii_overrides (knowledge_base_id, content_hash, decision, reason, decided_by, decided_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (knowledge_base_id, content_hash) DO UPDATE
+                SET decision = EXCLUDED.decision, reason = EXCLUDED.reason,
+                    decided_by = EXCLUDED.decided_by, decided_at = EXCLUDED.decided_at
+                "#,
+                [
+                    knowledge_base_id.into(),
+                    content_hash.clone().into(),
+                    decision_str(request.decision).into(),
+                    request.reason.clone().into(),
+                    decided_by.into(),
+                    decided_at.into(),
+                ],
+            ))
+            .await?;
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT DISTINCT document_id FROM document_chunks \
+                 WHERE knowledge_base_id = $1 AND metadata->'pii'->>'original_hash' = $2",
+                [knowledge_base_id.into(), content_hash.clone().into()],
+            ))
+            .await?;
+        let affected_documents = rows
+            .iter()
+            .map(|row| row.try_get("", "document_id"))
+            .collect::<Result<Vec<Uuid>, _>>()?;
+
+        info!(
+            "个人信息裁定已记录: knowledge_base_id={}, decision={}, 涉及文档数={}",
+            knowledge_base_id,
+            decision_str(request.decision),
+            affected_documents.len()
+        );
+        Ok(PiiOverride {
+            knowledge_base_id,
+            content_hash,
+            decision: request.decision,
+            reason: request.reason,
+            decided_by,
+            decided_at,
+            affected_documents,
+        })
+    }
+}
+
+fn decision_str(decision: PiiOverrideDecision) -> &'static str {
+    match decision {
+        PiiOverrideDecision::Allow => "allow",
+        PiiOverrideDecision::Exclude => "exclude",
+    }
+}
+
+fn parse_decision(value: &str) -> Result<PiiOverrideDecision, AiStudioError> {
+    match value {
+        "allow" => Ok(PiiOverrideDecision::Allow),
+        "exclude" => Ok(PiiOverrideDecision::Exclude),
+        other => Err(AiStudioError::internal(format!("未知的个人信息裁定: {}", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_pii_policy() {
+        let content = "客户邮箱 zhangsan@example.com";
+        let mut policy = PiiIndexPolicy::default();
+        assert_eq!(apply_pii_policy(&policy, &HashMap::new(), content).pii, None);
+
+        policy.enabled = true;
+        let masked = apply_pii_policy(&policy, &HashMap::new(), content);
+        assert_eq!(masked.content, "客户邮箱 [EMAIL]");
+        let pii = masked.pii.unwrap();
+        assert_eq!((pii.kinds, pii.action), (vec![PiiKind::Email], PiiIndexAction::Mask));
+
+        // 放行裁定优先于脱敏策略
+        let overrides = HashMap::from([(pii.original_hash, PiiOverrideDecision::Allow)]);
+        let allowed = apply_pii_policy(&policy, &overrides, content);
+        assert_eq!(allowed.content, content);
+        assert_eq!(allowed.pii.as_ref().unwrap().action, PiiIndexAction::Tag);
+        assert!(!allowed.excluded());
+
+        policy.action = PiiIndexAction::Exclude;
+        assert!(apply_pii_policy(&policy, &HashMap::new(), content).excluded());
+    }
+}
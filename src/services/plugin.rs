@@ -8,10 +8,12 @@ use sea_orm::DatabaseConnection;
 
 use crate::plugins::{
     plugin_manager::{PluginManager, PluginManagerFactory, PluginManagerConfig},
+    plugin_signing::{TrustPolicy, TrustedKey},
     plugin_interface::{PluginApi, PluginContext, PluginEvent, LogLevel, SystemInfo, HttpResponse, MemoryUsage, CpuUsage, DiskUsage},
 };
 use crate::errors::AiStudioError;
 use crate::config::AppConfig;
+use crate::services::plugin_config::PluginConfigService;
 
 /// 插件服务管理器
 pub struct PluginService {
@@ -28,7 +30,7 @@ impl PluginService {
         info!("初始化插件服务");
         
         // 创建插件 API 实现
-        let plugin_api = Arc::new(PluginApiImpl::new(db.clone()));
+        let plugin_api = Arc::new(PluginApiImpl::new(db.clone(), &config.security.encryption_key));
         
         // 创建插件管理器配置
         let manager_config = PluginManagerConfig {
@@ -42,7 +44,16 @@ impl PluginService {
                 crate::plugins::plugin_interface::PluginPermission::FileSystem,
                 crate::plugins::plugin_interface::PluginPermission::Network,
                 crate::plugins::plugin_interface::PluginPermission::UserData,
+                crate::plugins::plugin_interface::PluginPermission::HttpRoutes,
             ],
+            event_max_retries: 3,
+            event_retry_base_delay_ms: 500,
+            trust_policy: TrustPolicy {
+                trusted_keys: config.security.plugin_trusted_keys.iter()
+                    .map(|public_key| TrustedKey::new("deployment", public_key, None))
+                    .collect::<Result<Vec<_>, _>>()?,
+                require_signature: config.is_production(),
+            },
         };
         
         // 创建插件管理器
@@ -88,12 +99,15 @@ pub struct PluginServiceStats {
 pub struct PluginApiImpl {
     /// 数据库连接
     db: Arc<DatabaseConnection>,
+    /// 租户插件配置服务
+    config_service: PluginConfigService,
 }
 
 impl PluginApiImpl {
     /// 创建新的插件 API 实现
-    pub fn new(db: Arc<DatabaseConnection>) -> Self {
-        Self { db }
+    pub fn new(db: Arc<DatabaseConnection>, encryption_key: &str) -> Self {
+        let config_service = PluginConfigService::new(db.as_ref().clone(), encryption_key);
+        Self { db, config_service }
     }
 }
 
@@ -224,6 +238,15 @@ impl PluginApi for PluginApiImpl {
         })
     }
     
+    /// 获取租户级插件配置
+    async fn get_tenant_plugin_config(
+        &self,
+        tenant_id: uuid::Uuid,
+        plugin_id: &str,
+    ) -> Result<std::collections::HashMap<String, serde_json::Value>, AiStudioError> {
+        self.config_service.get_config(tenant_id, plugin_id).await
+    }
+    
     /// 访问数据库
     async fn database_query(
         &self,
@@ -0,0 +1,295 @@
+// 租户插件配置服务
+// 存储租户级插件配置，敏感字段使用 AES-256-GCM 加密后落库
+
+use std::collections::HashMap;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::{tenant_plugin_config, TenantPluginConfig};
+use crate::errors::AiStudioError;
+use crate::plugins::config_schema;
+
+/// 返回给客户端的敏感字段占位符
+///
+/// 保存配置时若敏感字段取值为该占位符，则保留原有密文。
+pub const SECRET_MASK: &str = "******";
+
+/// AES-GCM 随机数长度（字节）
+const NONCE_LEN: usize = 12;
+
+/// 敏感配置加解密器
+#[derive(Clone)]
+pub struct SecretCipher {
+    cipher: Aes256Gcm,
+}
+
+impl SecretCipher {
+    /// 由配置中的加密密钥派生 256 位密钥
+    pub fn new(encryption_key: &str) -> Self {
+        let digest = Sha256::digest(encryption_key.as_bytes());
+        Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&digest)),
+        }
+    }
+
+    /// 加密并编码为 base64（随机数 + 密文）
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, AiStudioError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self.cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| AiStudioError::internal("加密插件配置失败"))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(BASE64.encode(payload))
+    }
+
+    /// 解码并解密
+    pub fn decrypt(&self, encoded: &str) -> Result<String, AiStudioError> {
+        let payload = BASE64.decode(encoded)
+            .map_err(|_| AiStudioError::internal("插件配置密文格式无效"))?;
+        if payload.len() <= NONCE_LEN {
+            return Err(AiStudioError::internal("插件配置密文格式无效"));
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| AiStudioError::internal("解密插件配置失败，请检查加密密钥"))?;
+
+        String::from_utf8(plaintext).map_err(|_| AiStudioError::internal("插件配置明文编码无效"))
+    }
+}
+
+/// 租户插件配置响应（敏感字段已脱敏）
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TenantPluginConfigResponse {
+    /// 插件 ID
+    pub plugin_id: String,
+    /// 配置项
+    pub config: HashMap<String, Value>,
+    /// 敏感字段
+    pub secret_fields: Vec<String>,
+    /// 最后更新时间
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// 租户插件配置服务
+pub struct PluginConfigService {
+    db: DatabaseConnection,
+    cipher: SecretCipher,
+}
+
+impl PluginConfigService {
+    /// 创建新的租户插件配置服务实例
+    pub fn new(db: DatabaseConnection, encryption_key: &str) -> Self {
+        Self {
+            db,
+            cipher: SecretCipher::new(encryption_key),
+        }
+    }
+
+    /// 获取租户插件配置（敏感字段已解密）
+    #[instrument(skip(self))]
+    pub async fn get_config(
+        &self,
+        tenant_id: Uuid,
+        plugin_id: &str,
+    ) -> Result<HashMap<String, Value>, AiStudioError> {
+        match self.find(tenant_id, plugin_id).await? {
+            Some(model) => Ok(self.decrypt_model(&model)?.into_iter().collect()),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    /// 获取脱敏后的租户插件配置
+    #[instrument(skip(self, schema))]
+    pub async fn get_masked_config(
+        &self,
+        tenant_id: Uuid,
+        plugin_id: &str,
+        schema: &Value,
+    ) -> Result<TenantPluginConfigResponse, AiStudioError> {
+        let model = self.find(tenant_id, plugin_id).await?;
+        let mut config = match &model {
+            Some(model) => self.decrypt_model(model)?,
+            None => Map::new(),
+        };
+        config_schema::apply_defaults(schema, &mut config);
+
+        Ok(masked_response(plugin_id, config, schema, model.map(|m| m.updated_at.with_timezone(&Utc))))
+    }
+
+    /// 校验并保存租户插件配置
+    #[instrument(skip(self, schema, config))]
+    pub async fn save_config(
+        &self,
+        tenant_id: Uuid,
+        plugin_id: &str,
+        schema: &Value,
+        mut config: Map<String, Value>,
+        updated_by: Uuid,
+    ) -> Result<TenantPluginConfigResponse, AiStudioError> {
+        let existing = self.find(tenant_id, plugin_id).await?;
+        let secret_fields = config_schema::secret_fields(schema);
+
+        // 占位符表示保留原有密钥
+        let previous = match &existing {
+            Some(model) => self.decrypt_model(model)?,
+            None => Map::new(),
+        };
+        for field in &secret_fields {
+            if config.get(field).and_then(Value::as_str) == Some(SECRET_MASK) {
+                match previous.get(field) {
+                    Some(value) => config.insert(field.clone(), value.clone()),
+                    None => config.remove(field),
+                };
+            }
+        }
+
+        let mut effective = config.clone();
+        config_schema::apply_defaults(schema, &mut effective);
+        config_schema::validate_against_schema(schema, &Value::Object(effective))?;
+
+        let mut plain = Map::new();
+        let mut secrets = Map::new();
+        for (name, value) in &config {
+            if secret_fields.contains(name) {
+                secrets.insert(name.clone(), Value::String(self.cipher.encrypt(&value.to_string())?));
+            } else {
+                plain.insert(name.clone(), value.clone());
+            }
+        }
+
+        let now = Utc::now().fixed_offset();
+        let model = match existing {
+            Some(model) => {
+                let mut active: tenant_plugin_config::ActiveModel = model.into();
+                active.config = Set(Value::Object(plain));
+                active.encrypted_secrets = Set(Value::Object(secrets));
+                active.updated_by = Set(Some(updated_by));
+                active.updated_at = Set(now);
+                active.update(&self.db).await?
+            }
+            None => {
+                tenant_plugin_config::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    tenant_id: Set(tenant_id),
+                    plugin_id: Set(plugin_id.to_string()),
+                    config: Set(Value::Object(plain)),
+                    encrypted_secrets: Set(Value::Object(secrets)),
+                    updated_by: Set(Some(updated_by)),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                }
+                .insert(&self.db)
+                .await?
+            }
+        };
+
+        info!(tenant_id = %tenant_id, plugin_id = %plugin_id, "租户插件配置已保存");
+
+        let mut config = self.decrypt_model(&model)?;
+        config_schema::apply_defaults(schema, &mut config);
+        Ok(masked_response(plugin_id, config, schema, Some(model.updated_at.with_timezone(&Utc))))
+    }
+
+    /// 删除租户插件配置
+    #[instrument(skip(self))]
+    pub async fn delete_config(&self, tenant_id: Uuid, plugin_id: &str) -> Result<(), AiStudioError> {
+        TenantPluginConfig::delete_many()
+            .filter(tenant_plugin_config::Column::TenantId.eq(tenant_id))
+            .filter(tenant_plugin_config::Column::PluginId.eq(plugin_id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn find(
+        &self,
+        tenant_id: Uuid,
+        plugin_id: &str,
+    ) -> Result<Option<tenant_plugin_config::Model>, AiStudioError> {
+        Ok(TenantPluginConfig::find()
+            .filter(tenant_plugin_config::Column::TenantId.eq(tenant_id))
+            .filter(tenant_plugin_config::Column::PluginId.eq(plugin_id))
+            .one(&self.db)
+            .await?)
+    }
+
+    /// 合并明文配置与解密后的敏感配置
+    fn decrypt_model(&self, model: &tenant_plugin_config::Model) -> Result<Map<String, Value>, AiStudioError> {
+        let mut config = model.config.as_object().cloned().unwrap_or_default();
+
+        if let Some(secrets) = model.encrypted_secrets.as_object() {
+            for (name, encrypted) in secrets {
+                let Some(encrypted) = encrypted.as_str() else { continue };
+                let plaintext = self.cipher.decrypt(encrypted)?;
+                let value = serde_json::from_str(&plaintext).unwrap_or(Value::String(plaintext));
+                config.insert(name.clone(), value);
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// 构建脱敏响应
+fn masked_response(
+    plugin_id: &str,
+    mut config: Map<String, Value>,
+    schema: &Value,
+    updated_at: Option<DateTime<Utc>>,
+) -> TenantPluginConfigResponse {
+    let secret_fields = config_schema::secret_fields(schema);
+    for field in &secret_fields {
+        if let Some(value) = config.get_mut(field) {
+            *value = Value::String(SECRET_MASK.to_string());
+        }
+    }
+
+    TenantPluginConfigResponse {
+        plugin_id: plugin_id.to_string(),
+        config: config.into_iter().collect(),
+        secret_fields,
+        updated_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_cipher_roundtrip() {
+        let cipher = SecretCipher::new(&"k".repeat(32));
+        let encrypted = cipher.encrypt("\"token-123\"").unwrap();
+
+        assert_ne!(encrypted, "\"token-123\"");
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), "\"token-123\"");
+        assert!(SecretCipher::new(&"x".repeat(32)).decrypt(&encrypted).is_err());
+    }
+
+    #[test]
+    fn test_masked_response_hides_secrets() {
+        let schema = serde_json::json!({
+            "properties": { "api_token": { "type": "string", "writeOnly": true } }
+        });
+        let mut config = Map::new();
+        config.insert("api_token".to_string(), Value::String("secret".to_string()));
+        config.insert("endpoint".to_string(), Value::String("https://example.com".to_string()));
+
+        let response = masked_response("demo", config, &schema, None);
+        assert_eq!(response.config["api_token"], Value::String(SECRET_MASK.to_string()));
+        assert_eq!(response.config["endpoint"], Value::String("https://example.com".to_string()));
+    }
+}
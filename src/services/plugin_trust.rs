@@ -0,0 +1,155 @@
+// 插件签名信任服务
+// 管理租户登记的插件签名公钥
+
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::{plugin_trusted_key, PluginTrustedKey};
+use crate::errors::AiStudioError;
+use crate::plugins::plugin_signing::TrustedKey;
+
+/// 受信任密钥响应
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrustedKeyResponse {
+    /// 密钥 ID
+    pub key_id: String,
+    /// 密钥名称
+    pub name: String,
+    /// base64 编码的 ed25519 公钥
+    pub public_key: String,
+    /// 登记人
+    pub created_by: Option<Uuid>,
+    /// 登记时间
+    pub created_at: DateTime<Utc>,
+    /// 吊销时间
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<plugin_trusted_key::Model> for TrustedKeyResponse {
+    fn from(model: plugin_trusted_key::Model) -> Self {
+        Self {
+            key_id: model.key_id,
+            name: model.name,
+            public_key: model.public_key,
+            created_by: model.created_by,
+            created_at: model.created_at.with_timezone(&Utc),
+            revoked_at: model.revoked_at.map(|t| t.with_timezone(&Utc)),
+        }
+    }
+}
+
+/// 插件签名信任服务
+pub struct PluginTrustService {
+    db: DatabaseConnection,
+}
+
+impl PluginTrustService {
+    /// 创建新的插件签名信任服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 列出租户登记的全部密钥（含已吊销）
+    #[instrument(skip(self))]
+    pub async fn list_keys(&self, tenant_id: Uuid) -> Result<Vec<TrustedKeyResponse>, AiStudioError> {
+        let keys = PluginTrustedKey::find()
+            .filter(plugin_trusted_key::Column::TenantId.eq(tenant_id))
+            .order_by_desc(plugin_trusted_key::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(keys.into_iter().map(Into::into).collect())
+    }
+
+    /// 获取租户当前有效的受信任密钥
+    #[instrument(skip(self))]
+    pub async fn active_keys(&self, tenant_id: Uuid) -> Result<Vec<TrustedKey>, AiStudioError> {
+        let keys = PluginTrustedKey::find()
+            .filter(plugin_trusted_key::Column::TenantId.eq(tenant_id))
+            .filter(plugin_trusted_key::Column::RevokedAt.is_null())
+            .all(&self.db)
+            .await?;
+
+        Ok(keys.into_iter()
+            .map(|key| TrustedKey {
+                key_id: key.key_id,
+                name: key.name,
+                public_key: key.public_key,
+                tenant_id: Some(key.tenant_id),
+            })
+            .collect())
+    }
+
+    /// 登记受信任密钥
+    #[instrument(skip(self, public_key))]
+    pub async fn add_key(
+        &self,
+        tenant_id: Uuid,
+        name: &str,
+        public_key: &str,
+        created_by: Uuid,
+    ) -> Result<TrustedKeyResponse, AiStudioError> {
+        if name.trim().is_empty() {
+            return Err(AiStudioError::validation("name", "密钥名称不能为空"));
+        }
+
+        let key = TrustedKey::new(name.trim(), public_key, Some(tenant_id))?;
+
+        let existing = PluginTrustedKey::find()
+            .filter(plugin_trusted_key::Column::TenantId.eq(tenant_id))
+            .filter(plugin_trusted_key::Column::KeyId.eq(&key.key_id))
+            .one(&self.db)
+            .await?;
+        if existing.is_some() {
+            return Err(AiStudioError::conflict(format!("密钥已登记: {}", key.key_id)));
+        }
+
+        let now = Utc::now().fixed_offset();
+        let model = plugin_trusted_key::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            key_id: Set(key.key_id.clone()),
+            name: Set(key.name),
+            public_key: Set(key.public_key),
+            created_by: Set(Some(created_by)),
+            revoked_at: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(&self.db)
+        .await?;
+
+        info!(tenant_id = %tenant_id, key_id = %model.key_id, "插件签名密钥已登记");
+        Ok(model.into())
+    }
+
+    /// 吊销受信任密钥
+    ///
+    /// 吊销只影响之后的安装，已安装插件保留其签名者记录。
+    #[instrument(skip(self))]
+    pub async fn revoke_key(&self, tenant_id: Uuid, key_id: &str) -> Result<TrustedKeyResponse, AiStudioError> {
+        let model = PluginTrustedKey::find()
+            .filter(plugin_trusted_key::Column::TenantId.eq(tenant_id))
+            .filter(plugin_trusted_key::Column::KeyId.eq(key_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found(format!("签名密钥 {}", key_id)))?;
+
+        if !model.is_active() {
+            return Ok(model.into());
+        }
+
+        let mut active: plugin_trusted_key::ActiveModel = model.into();
+        active.revoked_at = Set(Some(Utc::now().fixed_offset()));
+        let model = active.update(&self.db).await?;
+
+        info!(tenant_id = %tenant_id, key_id = %key_id, "插件签名密钥已吊销");
+        Ok(model.into())
+    }
+}
@@ -0,0 +1,61 @@
+// 进程角色
+// 记录本进程以 api、worker 还是 all 角色运行，以及后台处理是否已启动，供健康与就绪检查使用
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use once_cell::sync::OnceCell;
+
+use crate::config::ProcessRole;
+use crate::errors::AiStudioError;
+
+static CURRENT_ROLE: OnceCell<ProcessRole> = OnceCell::new();
+
+static WORKERS_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// 设置本进程的角色，启动时调用一次
+pub fn install(role: ProcessRole) -> Result<(), AiStudioError> {
+    CURRENT_ROLE.set(role)
+        .map_err(|_| AiStudioError::internal("进程角色已经设置"))
+}
+
+/// 本进程的角色，未设置时视为 all
+pub fn current() -> ProcessRole {
+    CURRENT_ROLE.get().copied().unwrap_or_default()
+}
+
+/// 标记周期任务已启动
+pub fn mark_workers_started() {
+    WORKERS_STARTED.store(true, Ordering::Release);
+}
+
+/// 周期任务是否已启动
+pub fn workers_started() -> bool {
+    WORKERS_STARTED.load(Ordering::Acquire)
+}
+
+/// 按角色判断是否就绪，未就绪时返回原因
+///
+/// 所有角色都依赖数据库；执行周期任务的角色还需调度器已经启动。
+pub fn readiness_blocker(role: ProcessRole, database_ready: bool, workers_started: bool) -> Option<&'static str> {
+    if !database_ready {
+        return Some("数据库连接不可用");
+    }
+    if role.runs_workers() && !workers_started {
+        return Some("周期任务尚未启动");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness_depends_on_role() {
+        assert_eq!(readiness_blocker(ProcessRole::Api, true, false), None);
+        assert!(readiness_blocker(ProcessRole::Worker, true, false).is_some());
+        assert!(readiness_blocker(ProcessRole::All, true, false).is_some());
+        assert_eq!(readiness_blocker(ProcessRole::Worker, true, true), None);
+        assert_eq!(readiness_blocker(ProcessRole::Api, false, true), Some("数据库连接不可用"));
+    }
+}
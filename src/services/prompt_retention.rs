@@ -0,0 +1,513 @@
+// 提示词留存服务
+// 按租户策略决定是否保存发送给模型的原始提示词与模型输出、保存多久以及如何脱敏，
+// 在模型调用日志与执行轨迹写入时强制执行，并定期删除到期的调用日志
+
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, Set, Statement};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{debug, info, instrument, warn};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::ai::pii::scan_pii;
+use crate::db::entities::knowledge_base::PiiKind;
+use crate::db::entities::tenant::{self, PromptRedaction, TenantPromptRetention};
+use crate::db::entities::Tenant;
+use crate::db::update_with_revision;
+use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace};
+use crate::services::scheduler::PeriodicJob;
+
+/// 调用日志的写入连接，启动时设置
+static PROMPT_RECORDER: OnceCell<DatabaseConnection> = OnceCell::new();
+
+/// 保存天数上限
+const MAX_RETENTION_DAYS: u32 = 365;
+
+/// 单条内容最多保存的字符数上限
+const MAX_RETAINED_CHARS: u32 = 200_000;
+
+/// 调用日志列表的默认条数
+const DEFAULT_LOG_LIMIT: u64 = 50;
+
+/// 调用日志列表的最大条数
+const MAX_LOG_LIMIT: u64 = 200;
+
+/// 到期调用日志的清理间隔
+const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// 执行轨迹中视为模型原始输入的字段
+const TRACE_PROMPT_KEYS: [&str; 2] = ["prompt", "raw_prompt"];
+
+/// 执行轨迹中视为模型原始输出的字段
+const TRACE_COMPLETION_KEYS: [&str; 2] = ["completion", "raw_completion"];
+
+/// 模型调用来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelCallSource {
+    /// AI 服务的通用文本生成
+    AiService,
+    /// 知识库问答的答案生成
+    Rag,
+    /// Agent 推理步骤
+    Agent,
+}
+
+impl ModelCallSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelCallSource::AiService => "ai_service",
+            ModelCallSource::Rag => "rag",
+            ModelCallSource::Agent => "agent",
+        }
+    }
+}
+
+/// 一次模型调用
+#[derive(Debug, Clone)]
+pub struct ModelCall {
+    /// 租户 ID
+    pub tenant_id: Uuid,
+    /// 调用来源
+    pub source: ModelCallSource,
+    /// 所属执行 ID
+    pub execution_id: Option<Uuid>,
+    /// 来源内的关联标识，如问答查询 ID
+    pub reference: Option<String>,
+    /// 模型 ID
+    pub model: String,
+    /// 发送给模型的提示词
+    pub prompt: String,
+    /// 模型返回的输出
+    pub completion: String,
+    /// 消耗的 token 数量
+    pub tokens_used: Option<u32>,
+}
+
+/// 调用日志查询参数
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct ModelCallLogQuery {
+    /// 按执行 ID 过滤
+    pub execution_id: Option<Uuid>,
+    /// 按来源过滤：ai_service、rag、agent
+    pub source: Option<String>,
+    /// 返回条数，默认 50，最多 200
+    pub limit: Option<u64>,
+}
+
+/// 模型调用日志
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ModelCallLog {
+    /// 日志 ID
+    pub id: Uuid,
+    /// 调用来源
+    pub source: String,
+    /// 所属执行 ID
+    pub execution_id: Option<Uuid>,
+    /// 来源内的关联标识
+    pub reference: Option<String>,
+    /// 模型 ID
+    pub model: String,
+    /// 脱敏后的提示词，未开启保存时为空
+    pub prompt: Option<String>,
+    /// 脱敏后的模型输出，未开启保存时为空
+    pub completion: Option<String>,
+    /// 提示词原文字符数
+    pub prompt_chars: i32,
+    /// 模型输出原文字符数
+    pub completion_chars: i32,
+    /// 保存时采用的脱敏方式
+    pub redaction: String,
+    /// 消耗的 token 数量
+    pub tokens_used: Option<i32>,
+    /// 调用时间
+    pub created_at: DateTime<Utc>,
+    /// 到期时间
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 提示词留存服务
+pub struct PromptRetentionService {
+    db: DatabaseConnection,
+}
+
+impl PromptRetentionService {
+    /// 创建新的提示词留存服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 获取租户留存策略
+    pub async fn get_policy(&self, tenant_id: Uuid) -> Result<TenantPromptRetention, AiStudioError> {
+        let tenant = Tenant::find_by_id(tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+        Ok(tenant.get_config().unwrap_or_default().prompt_retention)
+    }
+
+    /// 替换租户留存策略
+    ///
+    /// 关闭保存后立即删除已保存的调用日志；缩短保存天数时，已保存日志的到期时间随之提前。
+    #[instrument(skip(self, policy))]
+    pub async fn update_policy(
+        &self,
+        tenant_id: Uuid,
+        policy: TenantPromptRetention,
+    ) -> Result<TenantPromptRetention, AiStudioError> {
+        let policy = normalize_policy(policy)?;
+
+        let tenant = Tenant::find_by_id(tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+        let mut config = tenant.get_config()
+            .map_err(|e| AiStudioError::internal(format!("解析租户配置失败: {}", e)))?;
+        config.prompt_retention = policy.clone();
+
+        let revision = tenant.revision;
+        let mut active_tenant: tenant::ActiveModel = tenant.into();
+        active_tenant.config = Set(serde_json::to_value(&config)?);
+        active_tenant.updated_at = Set(Utc::now().into());
+        update_with_revision(&self.db, active_tenant, tenant::Column::Revision, revision, "租户").await?;
+        cache::invalidate(CacheNamespace::Tenant, tenant_id).await;
+
+        let affected = if policy.persist_prompts || policy.persist_completions {
+            self.db
+                .execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    r#"
+                    UPDATE model_call_logs
+                    SET expires_at = LEAST(expires_at, created_at + make_interval(days => $2))
+                    WHERE tenant_id = $1
+                    "#,
+                    [tenant_id.into(), (policy.retention_days as i32).into()],
+                ))
+                .await?
+                .rows_affected()
+        } else {
+            self.db
+                .execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    "DELETE FROM model_call_logs WHERE tenant_id = $1",
+                    [tenant_id.into()],
+                ))
+                .await?
+                .rows_affected()
+        };
+
+        info!(
+            tenant_id = %tenant_id,
+            persist_prompts = policy.persist_prompts,
+            persist_completions = policy.persist_completions,
+            retention_days = policy.retention_days,
+            affected_logs = affected,
+            "租户提示词留存策略已更新"
+        );
+        Ok(policy)
+    }
+
+    /// 列出租户的模型调用日志，按调用时间倒序
+    pub async fn list_logs(
+        &self,
+        tenant_id: Uuid,
+        query: &ModelCallLogQuery,
+    ) -> Result<Vec<ModelCallLog>, AiStudioError> {
+        let limit = query.limit.unwrap_or(DEFAULT_LOG_LIMIT).clamp(1, MAX_LOG_LIMIT);
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT id, source, execution_id, reference, model, prompt, completion,
+                       prompt_chars, completion_chars, redaction, tokens_used, created_at, expires_at
+                FROM model_call_logs
+                WHERE tenant_id = $1
+                    AND expires_at > CURRENT_TIMESTAMP
+                    AND ($2::uuid IS NULL OR execution_id = $2)
+                    AND ($3::varchar IS NULL OR source = $3)
+                ORDER BY created_at DESC
+                LIMIT $4
+                "#,
+                [
+                    tenant_id.into(),
+                    query.execution_id.into(),
+                    query.source.clone().into(),
+                    (limit as i64).into(),
+                ],
+            ))
+            .await?;
+
+        let mut logs = Vec::with_capacity(rows.len());
+        for row in rows {
+            logs.push(ModelCallLog {
+                id: row.try_get("", "id")?,
+                source: row.try_get("", "source")?,
+                execution_id: row.try_get("", "execution_id")?,
+                reference: row.try_get("", "reference")?,
+                model: row.try_get("", "model")?,
+                prompt: row.try_get("", "prompt")?,
+                completion: row.try_get("", "completion")?,
+                prompt_chars: row.try_get("", "prompt_chars")?,
+                completion_chars: row.try_get("", "completion_chars")?,
+                redaction: row.try_get("", "redaction")?,
+                tokens_used: row.try_get("", "tokens_used")?,
+                created_at: row.try_get("", "created_at")?,
+                expires_at: row.try_get("", "expires_at")?,
+            });
+        }
+        Ok(logs)
+    }
+
+    /// 删除到期的调用日志，返回删除的行数
+    pub async fn purge_expired(&self) -> Result<u64, AiStudioError> {
+        let result = self.db
+            .execute(Statement::from_string(
+                DatabaseBackend::Postgres,
+                "DELETE FROM model_call_logs WHERE expires_at <= CURRENT_TIMESTAMP".to_string(),
+            ))
+            .await?;
+        if result.rows_affected() > 0 {
+            info!("已删除 {} 条到期的模型调用日志", result.rows_affected());
+        }
+        Ok(result.rows_affected())
+    }
+}
+
+/// 读取租户留存策略，租户不存在或配置无效时使用默认策略（不保存）
+///
+/// 经租户缓存读取，用于模型调用与执行事件写入路径，读取失败不应影响调用本身。
+pub async fn load_prompt_retention(db: &DatabaseConnection, tenant_id: Uuid) -> TenantPromptRetention {
+    let loaded = cache::cached(CacheNamespace::Tenant, &tenant_id.to_string(), || async {
+        Ok::<_, AiStudioError>(Tenant::find_by_id(tenant_id).one(db).await?)
+    })
+    .await;
+
+    match loaded {
+        Ok(Some(tenant)) => tenant.get_config().unwrap_or_default().prompt_retention,
+        Ok(None) => TenantPromptRetention::default(),
+        Err(e) => {
+            warn!(tenant_id = %tenant_id, error = %e, "读取提示词留存策略失败，按不保存处理");
+            TenantPromptRetention::default()
+        }
+    }
+}
+
+/// 启用模型调用日志
+pub fn install_prompt_recorder(db: DatabaseConnection) -> Result<(), AiStudioError> {
+    PROMPT_RECORDER.set(db)
+        .map_err(|_| AiStudioError::internal("模型调用日志已经初始化"))
+}
+
+/// 记录一次模型调用
+///
+/// 在后台按租户策略脱敏后写入，不阻塞调用方；租户未开启保存或未启用调用日志时直接忽略。
+pub fn record_model_call(call: ModelCall) {
+    let Some(db) = PROMPT_RECORDER.get() else {
+        return;
+    };
+
+    let db = db.clone();
+    tokio::spawn(async move {
+        let policy = load_prompt_retention(&db, call.tenant_id).await;
+        if !policy.persist_prompts && !policy.persist_completions {
+            return;
+        }
+
+        let prompt = policy.persist_prompts.then(|| retain_text(&policy, &call.prompt));
+        let completion = policy.persist_completions.then(|| retain_text(&policy, &call.completion));
+        let expires_at = Utc::now() + chrono::Duration::days(policy.retention_days as i64);
+        let result = db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO model_call_logs
+                    (tenant_id, source, execution_id, reference, model, prompt, completion,
+                     prompt_chars, completion_chars, redaction, tokens_used, expires_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                "#,
+                [
+                    call.tenant_id.into(),
+                    call.source.as_str().into(),
+                    call.execution_id.into(),
+                    call.reference.into(),
+                    call.model.into(),
+                    prompt.into(),
+                    completion.into(),
+                    (call.prompt.chars().count() as i32).into(),
+                    (call.completion.chars().count() as i32).into(),
+                    redaction_name(policy.redaction).into(),
+                    call.tokens_used.map(|tokens| tokens as i32).into(),
+                    expires_at.into(),
+                ],
+            ))
+            .await;
+        match result {
+            Ok(_) => debug!(tenant_id = %call.tenant_id, source = call.source.as_str(), "已记录模型调用"),
+            Err(e) => warn!(tenant_id = %call.tenant_id, error = %e, "记录模型调用失败"),
+        }
+    });
+}
+
+/// 执行轨迹数据是否包含模型原始输入或输出字段
+pub fn contains_model_io(payload: &Value) -> bool {
+    match payload {
+        Value::Object(map) => map.iter().any(|(key, value)| is_model_io_key(key) || contains_model_io(value)),
+        Value::Array(items) => items.iter().any(contains_model_io),
+        _ => false,
+    }
+}
+
+/// 按留存策略处理执行轨迹数据中的模型原始输入与输出
+///
+/// 未开启保存的字段替换为只含字符数的占位对象，开启保存的字段按脱敏方式与长度上限处理；其他字段保持不变。
+pub fn redact_trace_payload(policy: &TenantPromptRetention, payload: Value) -> Value {
+    match payload {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let persist = if TRACE_PROMPT_KEYS.contains(&key.as_str()) {
+                        Some(policy.persist_prompts)
+                    } else if TRACE_COMPLETION_KEYS.contains(&key.as_str()) {
+                        Some(policy.persist_completions)
+                    } else {
+                        None
+                    };
+                    let value = match (persist, value) {
+                        (Some(true), Value::String(text)) => Value::String(retain_text(policy, &text)),
+                        (Some(false), Value::String(text)) => json!({ "omitted": true, "chars": text.chars().count() }),
+                        (_, value) => redact_trace_payload(policy, value),
+                    };
+                    (key, value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(|item| redact_trace_payload(policy, item)).collect()),
+        value => value,
+    }
+}
+
+/// 按脱敏方式与长度上限处理待保存的文本
+pub fn retain_text(policy: &TenantPromptRetention, text: &str) -> String {
+    let retained = match policy.redaction {
+        PromptRedaction::None => text.to_string(),
+        PromptRedaction::MaskPii => scan_pii(text, &PiiKind::ALL).masked,
+        PromptRedaction::Digest => return format!("md5:{:x}", md5::compute(text)),
+    };
+    match policy.max_chars {
+        Some(max_chars) if retained.chars().count() > max_chars as usize => {
+            let mut truncated: String = retained.chars().take(max_chars as usize).collect();
+            truncated.push('…');
+            truncated
+        }
+        _ => retained,
+    }
+}
+
+fn is_model_io_key(key: &str) -> bool {
+    TRACE_PROMPT_KEYS.contains(&key) || TRACE_COMPLETION_KEYS.contains(&key)
+}
+
+fn redaction_name(redaction: PromptRedaction) -> &'static str {
+    match redaction {
+        PromptRedaction::None => "none",
+        PromptRedaction::MaskPii => "mask_pii",
+        PromptRedaction::Digest => "digest",
+    }
+}
+
+/// 校验留存策略
+fn normalize_policy(policy: TenantPromptRetention) -> Result<TenantPromptRetention, AiStudioError> {
+    if !(1..=MAX_RETENTION_DAYS).contains(&policy.retention_days) {
+        return Err(AiStudioError::validation(
+            "retention_days",
+            format!("保存天数必须在 1 到 {} 之间", MAX_RETENTION_DAYS),
+        ));
+    }
+    if policy.max_chars.is_some_and(|max_chars| !(1..=MAX_RETAINED_CHARS).contains(&max_chars)) {
+        return Err(AiStudioError::validation(
+            "max_chars",
+            format!("单条内容最多保存的字符数必须在 1 到 {} 之间", MAX_RETAINED_CHARS),
+        ));
+    }
+    Ok(policy)
+}
+
+/// 到期模型调用日志清理任务
+pub struct PromptLogPurgeJob {
+    service: PromptRetentionService,
+}
+
+impl PromptLogPurgeJob {
+    /// 创建新的到期调用日志清理任务
+    pub fn new(service: PromptRetentionService) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for PromptLogPurgeJob {
+    fn name(&self) -> &str {
+        "prompt_log_purge"
+    }
+
+    fn interval(&self) -> Duration {
+        PURGE_INTERVAL
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        self.service.purge_expired().await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(redaction: PromptRedaction) -> TenantPromptRetention {
+        TenantPromptRetention {
+            persist_prompts: true,
+            persist_completions: false,
+            redaction,
+            ..TenantPromptRetention::default()
+        }
+    }
+
+    #[test]
+    fn test_retain_text() {
+        let text = "请联系 alice@example.com 处理退款";
+        assert_eq!(retain_text(&policy(PromptRedaction::None), text), text);
+        assert!(!retain_text(&policy(PromptRedaction::MaskPii), text).contains("alice@example.com"));
+        assert!(retain_text(&policy(PromptRedaction::Digest), text).starts_with("md5:"));
+
+        let truncated = TenantPromptRetention { max_chars: Some(3), ..policy(PromptRedaction::None) };
+        assert_eq!(retain_text(&truncated, text), "请联系…");
+    }
+
+    #[test]
+    fn test_redact_trace_payload() {
+        let payload = json!({
+            "task_id": "t1",
+            "steps": [{ "prompt": "alice@example.com", "completion": "好的" }],
+        });
+        assert!(contains_model_io(&payload));
+        assert!(!contains_model_io(&json!({ "task_id": "t1" })));
+
+        let redacted = redact_trace_payload(&policy(PromptRedaction::MaskPii), payload);
+        assert_eq!(redacted["task_id"], "t1");
+        let step = &redacted["steps"][0];
+        assert!(!step["prompt"].as_str().unwrap().contains("alice@example.com"));
+        assert_eq!(step["completion"], json!({ "omitted": true, "chars": 2 }));
+    }
+
+    #[test]
+    fn test_normalize_policy() {
+        assert!(normalize_policy(TenantPromptRetention::default()).is_ok());
+        assert!(normalize_policy(TenantPromptRetention { retention_days: 0, ..Default::default() }).is_err());
+        assert!(normalize_policy(TenantPromptRetention { max_chars: Some(0), ..Default::default() }).is_err());
+    }
+}
@@ -715,9 +715,20 @@ mod tests {
                     doc_type: "text".to_string(),
                     relevance_score: 0.8,
                     chunk_count: 1,
+                    knowledge_base_id: None,
+                    knowledge_base_name: None,
                 }
             ],
             query_stats: todo!(),
+            kb_version: None,
+            structured_output: None,
+            faq_match: None,
+            curated_answer: None,
+            confidence: None,
+            insufficient_information: false,
+            query_rewrite: None,
+            retrieval_explanation: None,
+            deadline_exceeded: false,
             generated_at: Utc::now(),
         };
         
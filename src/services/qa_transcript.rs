@@ -0,0 +1,288 @@
+// 问答会话记录服务
+// 记录每次问答的问题、答案、引用与用户反馈，供会话导出、合规审查与热门问题统计使用
+
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Statement,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::document::ClearanceLevel;
+use crate::db::entities::{qa_query_log, QaQueryLog};
+use crate::errors::AiStudioError;
+use crate::services::question_suggestion::normalize_question;
+
+/// 单次导出的最大记录数
+pub const MAX_TRANSCRIPT_RECORDS: u64 = 100_000;
+
+/// 答案引用的来源文档
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TranscriptCitation {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 相关性分数
+    pub relevance_score: f32,
+    /// 引用的文档块 ID
+    pub chunk_ids: Vec<Uuid>,
+}
+
+/// 待记录的问答
+#[derive(Debug, Clone)]
+pub struct QaTranscriptEntry<'a> {
+    /// 租户 ID
+    pub tenant_id: Uuid,
+    /// 知识库 ID
+    pub knowledge_base_id: Option<Uuid>,
+    /// 用户 ID
+    pub user_id: Option<Uuid>,
+    /// 查询 ID
+    pub query_id: &'a str,
+    /// 会话 ID
+    pub session_id: Option<&'a str>,
+    /// 问题
+    pub question: &'a str,
+    /// 问题向量
+    pub embedding: Option<&'a [f32]>,
+    /// 答案
+    pub answer: &'a str,
+    /// 答案引用
+    pub citations: &'a [TranscriptCitation],
+    /// 答案置信度
+    pub confidence_score: f32,
+    /// 提问者访问密级
+    pub clearance: ClearanceLevel,
+}
+
+/// 用户对答案的反馈
+#[derive(Debug, Clone)]
+pub struct TranscriptFeedback {
+    /// 反馈类型
+    pub feedback_type: String,
+    /// 评分（1-5）
+    pub rating: Option<u8>,
+    /// 反馈内容
+    pub comment: Option<String>,
+}
+
+/// 会话记录查询条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranscriptFilter {
+    /// 会话 ID
+    pub session_id: Option<String>,
+    /// 起始时间（含）
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间（不含）
+    pub end_time: Option<DateTime<Utc>>,
+    /// 知识库 ID
+    pub knowledge_base_id: Option<Uuid>,
+    /// 提问用户 ID
+    pub user_id: Option<Uuid>,
+}
+
+/// 一条问答会话记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptRecord {
+    /// 查询 ID
+    pub query_id: Option<String>,
+    /// 会话 ID
+    pub session_id: Option<String>,
+    /// 知识库 ID
+    pub knowledge_base_id: Option<Uuid>,
+    /// 提问用户 ID
+    pub user_id: Option<Uuid>,
+    /// 问题
+    pub question: String,
+    /// 答案
+    pub answer: Option<String>,
+    /// 答案置信度
+    pub confidence_score: f32,
+    /// 答案引用
+    pub citations: Vec<TranscriptCitation>,
+    /// 反馈类型
+    pub feedback_type: Option<String>,
+    /// 评分
+    pub feedback_rating: Option<i16>,
+    /// 反馈内容
+    pub feedback_comment: Option<String>,
+    /// 提问时间
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<qa_query_log::Model> for TranscriptRecord {
+    fn from(model: qa_query_log::Model) -> Self {
+        Self {
+            citations: serde_json::from_value(model.citations).unwrap_or_default(),
+            query_id: model.query_id,
+            session_id: model.session_id,
+            knowledge_base_id: model.knowledge_base_id,
+            user_id: model.user_id,
+            question: model.question,
+            answer: model.answer,
+            confidence_score: model.confidence_score,
+            feedback_type: model.feedback_type,
+            feedback_rating: model.feedback_rating,
+            feedback_comment: model.feedback_comment,
+            created_at: model.created_at.with_timezone(&Utc),
+        }
+    }
+}
+
+/// 问答会话记录服务
+pub struct QaTranscriptService {
+    db: DatabaseConnection,
+}
+
+impl QaTranscriptService {
+    /// 创建新的会话记录服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 记录一次问答
+    pub async fn record(&self, entry: QaTranscriptEntry<'_>) -> Result<(), AiStudioError> {
+        let normalized = normalize_question(entry.question);
+        if normalized.is_empty() {
+            return Ok(());
+        }
+
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO qa_query_logs
+                    (tenant_id, knowledge_base_id, user_id, query_id, session_id, question, normalized_question,
+                     question_embedding, answer, citations, confidence_score, source_count, clearance)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8::vector, $9, $10, $11, $12, $13)
+                "#,
+                vec![
+                    entry.tenant_id.into(),
+                    entry.knowledge_base_id.into(),
+                    entry.user_id.into(),
+                    entry.query_id.into(),
+                    entry.session_id.map(str::to_string).into(),
+                    entry.question.trim().into(),
+                    normalized.into(),
+                    entry.embedding.map(format_vector).into(),
+                    entry.answer.into(),
+                    serde_json::to_value(entry.citations)?.into(),
+                    entry.confidence_score.into(),
+                    (entry.citations.len() as i32).into(),
+                    entry.clearance.into(),
+                ],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// 记录用户对答案的反馈，重复提交时覆盖之前的反馈
+    #[instrument(skip(self, feedback))]
+    pub async fn record_feedback(
+        &self,
+        tenant_id: Uuid,
+        query_id: &str,
+        feedback: TranscriptFeedback,
+    ) -> Result<(), AiStudioError> {
+        if feedback.rating.is_some_and(|rating| !(1..=5).contains(&rating)) {
+            return Err(AiStudioError::validation("rating", "评分必须在 1-5 之间"));
+        }
+
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                UPDATE qa_query_logs
+                SET feedback_type = $3, feedback_rating = $4, feedback_comment = $5, feedback_at = CURRENT_TIMESTAMP
+                WHERE tenant_id = $1 AND query_id = $2
+                "#,
+                vec![
+                    tenant_id.into(),
+                    query_id.into(),
+                    feedback.feedback_type.into(),
+                    feedback.rating.map(i16::from).into(),
+                    feedback.comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).into(),
+                ],
+            ))
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AiStudioError::not_found("问答记录"));
+        }
+        info!("问答反馈已记录: query_id={}", query_id);
+        Ok(())
+    }
+
+    /// 保存人工审校后的答案，构建微调数据集时替代原答案
+    #[instrument(skip(self, answer))]
+    pub async fn review_answer(
+        &self,
+        tenant_id: Uuid,
+        query_id: &str,
+        answer: &str,
+        reviewer_id: Uuid,
+    ) -> Result<(), AiStudioError> {
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Err(AiStudioError::validation("answer", "审校答案不能为空"));
+        }
+
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                UPDATE qa_query_logs
+                SET reviewed_answer = $3, reviewed_by = $4, reviewed_at = CURRENT_TIMESTAMP
+                WHERE tenant_id = $1 AND query_id = $2
+                "#,
+                vec![tenant_id.into(), query_id.into(), answer.into(), reviewer_id.into()],
+            ))
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AiStudioError::not_found("问答记录"));
+        }
+        info!("问答答案已审校: query_id={}, reviewer={}", query_id, reviewer_id);
+        Ok(())
+    }
+
+    /// 按条件查询会话记录，按提问时间升序
+    pub async fn load_transcripts(
+        &self,
+        tenant_id: Uuid,
+        filter: &TranscriptFilter,
+    ) -> Result<Vec<TranscriptRecord>, AiStudioError> {
+        let mut select = QaQueryLog::find().filter(qa_query_log::Column::TenantId.eq(tenant_id));
+        if let Some(session_id) = &filter.session_id {
+            select = select.filter(qa_query_log::Column::SessionId.eq(session_id.as_str()));
+        }
+        if let Some(start_time) = filter.start_time {
+            select = select.filter(qa_query_log::Column::CreatedAt.gte(start_time));
+        }
+        if let Some(end_time) = filter.end_time {
+            select = select.filter(qa_query_log::Column::CreatedAt.lt(end_time));
+        }
+        if let Some(kb_id) = filter.knowledge_base_id {
+            select = select.filter(qa_query_log::Column::KnowledgeBaseId.eq(kb_id));
+        }
+        if let Some(user_id) = filter.user_id {
+            select = select.filter(qa_query_log::Column::UserId.eq(user_id));
+        }
+
+        let logs = select
+            .order_by_asc(qa_query_log::Column::CreatedAt)
+            .limit(MAX_TRANSCRIPT_RECORDS)
+            .all(&self.db)
+            .await?;
+
+        Ok(logs.into_iter().map(TranscriptRecord::from).collect())
+    }
+}
+
+fn format_vector(vector: &[f32]) -> String {
+    format!("[{}]", vector.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))
+}
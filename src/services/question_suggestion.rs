@@ -0,0 +1,582 @@
+// 问题推荐服务
+// 按向量聚类问答查询日志中的热门问题，并结合文档摘要生成推荐问题与相关问题
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::embedding_pool::{EmbeddingPool, EmbeddingPriority};
+use crate::ai::rig_client::RigAiClient;
+use crate::db::entities::document::ClearanceLevel;
+use crate::errors::AiStudioError;
+
+/// 热门问题统计窗口（天）
+const POPULAR_WINDOW_DAYS: i64 = 30;
+
+/// 参与聚类的最大查询日志数
+const MAX_CLUSTER_QUERIES: u64 = 1000;
+
+/// 两个问题归为同一簇的最低相似度
+const CLUSTER_SIMILARITY: f32 = 0.85;
+
+/// 相关问题的最低相似度
+const RELATED_MIN_SIMILARITY: f32 = 0.5;
+
+/// 与当前问题相似度达到该值时视为同一问题，不再推荐
+const SAME_QUESTION_SIMILARITY: f32 = 0.95;
+
+/// 文档推荐问题的得分权重，相同相关度下优先推荐真实用户问过的问题
+const DOCUMENT_SCORE_WEIGHT: f32 = 0.8;
+
+/// 默认推荐数量
+pub const DEFAULT_SUGGESTION_LIMIT: usize = 5;
+
+/// 最大推荐数量
+pub const MAX_SUGGESTION_LIMIT: usize = 20;
+
+/// 摘要开头的指代词
+const SUMMARY_SUBJECTS: [&str; 5] = ["本文档", "该文档", "这篇文档", "本文", "本篇"];
+
+/// 摘要开头的概述动词
+const SUMMARY_VERBS: [&str; 8] = ["主要介绍了", "介绍了", "主要描述了", "描述了", "说明了", "讲述了", "概述了", "总结了"];
+
+/// 由摘要生成问题时主题的最大长度（字符）
+const MAX_TOPIC_CHARS: usize = 30;
+
+/// 推荐问题来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionSource {
+    /// 其他用户问过的热门问题
+    PopularQuery,
+    /// 由文档摘要生成
+    DocumentSummary,
+}
+
+/// 推荐问题
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SuggestedQuestion {
+    /// 问题
+    pub question: String,
+    /// 来源
+    pub source: SuggestionSource,
+    /// 推荐得分（0-1）
+    pub score: f32,
+    /// 统计窗口内被问到的次数（热门问题）
+    pub popularity: Option<u32>,
+    /// 来源文档 ID（文档摘要问题）
+    pub document_id: Option<Uuid>,
+}
+
+/// 相关问题请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct RelatedQuestionsRequest {
+    /// 知识库 ID，为空时在租户的全部知识库中推荐
+    pub knowledge_base_id: Option<Uuid>,
+    /// 刚回答过的问题，指定时推荐与之相关的问题
+    pub question: Option<String>,
+    /// 推荐数量，默认 5，最大 20
+    pub limit: Option<usize>,
+}
+
+/// 相关问题响应
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RelatedQuestionsResponse {
+    /// 推荐问题，按得分降序
+    pub suggestions: Vec<SuggestedQuestion>,
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+}
+
+/// 聚类输入的历史问题
+#[derive(Debug, Clone)]
+pub struct LoggedQuestion {
+    /// 问题原文
+    pub question: String,
+    /// 问题向量
+    pub embedding: Option<Vec<f32>>,
+}
+
+/// 热门问题簇
+#[derive(Debug, Clone)]
+pub struct QuestionCluster {
+    /// 代表问题，取簇内出现次数最多的问法
+    pub representative: String,
+    /// 簇内问题数
+    pub count: u32,
+    /// 簇中心向量
+    pub centroid: Option<Vec<f32>>,
+    /// 簇内问题在输入中的下标，按输入顺序
+    pub members: Vec<usize>,
+}
+
+/// 问题推荐服务
+pub struct QuestionSuggestionService {
+    db: DatabaseConnection,
+    embedder: Option<Arc<RigAiClient>>,
+}
+
+impl QuestionSuggestionService {
+    /// 创建新的问题推荐服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db, embedder: None }
+    }
+
+    /// 设置未配置嵌入工作池时使用的嵌入客户端
+    pub fn with_embedder(mut self, embedder: Arc<RigAiClient>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// 推荐相关问题
+    ///
+    /// 指定刚回答过的问题时按语义相关度推荐，否则推荐热门问题与知识库文档生成的问题。
+    #[instrument(skip(self, req))]
+    pub async fn related_questions(
+        &self,
+        tenant_id: Uuid,
+        clearance: ClearanceLevel,
+        req: &RelatedQuestionsRequest,
+    ) -> Result<RelatedQuestionsResponse, AiStudioError> {
+        let limit = req.limit.unwrap_or(DEFAULT_SUGGESTION_LIMIT).clamp(1, MAX_SUGGESTION_LIMIT);
+        let question = req.question.as_deref().map(str::trim).filter(|q| !q.is_empty());
+        let question_embedding = match question {
+            Some(question) => self.embed(question).await,
+            None => None,
+        };
+
+        let clusters = cluster_questions(
+            &self.recent_questions(tenant_id, req.knowledge_base_id, clearance).await?,
+            CLUSTER_SIMILARITY,
+        );
+        let mut suggestions = rank_clusters(&clusters, question_embedding.as_deref());
+        suggestions.extend(
+            self.document_suggestions(tenant_id, req.knowledge_base_id, clearance, question_embedding.as_deref(), limit)
+                .await?,
+        );
+
+        let exclude: HashSet<String> = question.map(normalize_question).into_iter().collect();
+        Ok(RelatedQuestionsResponse {
+            suggestions: dedupe_suggestions(suggestions, &exclude, limit),
+            generated_at: Utc::now(),
+        })
+    }
+
+    /// 输入补全：返回包含已输入文本的热门问题，不足时以文档问题补齐
+    #[instrument(skip(self))]
+    pub async fn autocomplete(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Option<Uuid>,
+        clearance: ClearanceLevel,
+        partial_question: &str,
+        limit: usize,
+    ) -> Result<Vec<String>, AiStudioError> {
+        let needle = normalize_question(partial_question);
+        let clusters = cluster_questions(
+            &self.recent_questions(tenant_id, knowledge_base_id, clearance).await?,
+            CLUSTER_SIMILARITY,
+        );
+
+        let mut suggestions: Vec<SuggestedQuestion> = rank_clusters(&clusters, None)
+            .into_iter()
+            .filter(|s| normalize_question(&s.question).contains(&needle))
+            .collect();
+        suggestions.extend(
+            self.document_suggestions(tenant_id, knowledge_base_id, clearance, None, MAX_SUGGESTION_LIMIT)
+                .await?
+                .into_iter()
+                .filter(|s| normalize_question(&s.question).contains(&needle)),
+        );
+
+        let exclude = HashSet::from([needle]);
+        Ok(dedupe_suggestions(suggestions, &exclude, limit)
+            .into_iter()
+            .map(|s| s.question)
+            .collect())
+    }
+
+    /// 统计窗口内有来源文档支撑的历史问题，只包含提问者密级不高于当前用户的记录
+    async fn recent_questions(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Option<Uuid>,
+        clearance: ClearanceLevel,
+    ) -> Result<Vec<LoggedQuestion>, AiStudioError> {
+        let since = Utc::now() - chrono::Duration::days(POPULAR_WINDOW_DAYS);
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT question, question_embedding::text AS embedding
+                FROM qa_query_logs
+                WHERE tenant_id = $1
+                    AND ($2::uuid IS NULL OR knowledge_base_id = $2)
+                    AND source_count > 0
+                    AND clearance <= $3
+                    AND created_at >= $4
+                ORDER BY created_at DESC
+                LIMIT $5
+                "#,
+                vec![
+                    tenant_id.into(),
+                    knowledge_base_id.into(),
+                    clearance.into(),
+                    since.into(),
+                    (MAX_CLUSTER_QUERIES as i64).into(),
+                ],
+            ))
+            .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<LoggedQuestion, AiStudioError> {
+                let embedding: Option<String> = row.try_get("", "embedding")?;
+                Ok(LoggedQuestion {
+                    question: row.try_get("", "question")?,
+                    embedding: embedding.as_deref().and_then(parse_vector),
+                })
+            })
+            .collect()
+    }
+
+    /// 由文档摘要生成推荐问题
+    ///
+    /// 指定问题向量时选取与之最相关的文档，否则选取最近更新的文档；包含超出用户密级段落的文档不参与推荐。
+    async fn document_suggestions(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Option<Uuid>,
+        clearance: ClearanceLevel,
+        question_embedding: Option<&[f32]>,
+        limit: usize,
+    ) -> Result<Vec<SuggestedQuestion>, AiStudioError> {
+        let statement = match question_embedding {
+            Some(embedding) => Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT d.id, d.title, d.summary, MAX(1 - (e.vector <=> $1::vector))::real AS similarity
+                FROM embeddings e
+                JOIN document_chunks c ON c.id = e.chunk_id
+                JOIN documents d ON d.id = c.document_id
+                JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id
+                WHERE kb.tenant_id = $2
+                    AND ($3::uuid IS NULL OR d.knowledge_base_id = $3)
+                    AND d.status = 'completed'
+                    AND d.clearance <= $4
+                    AND NOT EXISTS (
+                        SELECT 1 FROM document_chunks rc WHERE rc.document_id = d.id AND rc.clearance > $4
+                    )
+                GROUP BY d.id, d.title, d.summary
+                ORDER BY similarity DESC
+                LIMIT $5
+                "#,
+                vec![
+                    format_vector(embedding).into(),
+                    tenant_id.into(),
+                    knowledge_base_id.into(),
+                    clearance.into(),
+                    (limit as i64).into(),
+                ],
+            ),
+            None => Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT d.id, d.title, d.summary, NULL::real AS similarity
+                FROM documents d
+                JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id
+                WHERE kb.tenant_id = $1
+                    AND ($2::uuid IS NULL OR d.knowledge_base_id = $2)
+                    AND d.status = 'completed'
+                    AND d.clearance <= $3
+                    AND NOT EXISTS (
+                        SELECT 1 FROM document_chunks rc WHERE rc.document_id = d.id AND rc.clearance > $3
+                    )
+                ORDER BY d.updated_at DESC
+                LIMIT $4
+                "#,
+                vec![
+                    tenant_id.into(),
+                    knowledge_base_id.into(),
+                    clearance.into(),
+                    (limit as i64).into(),
+                ],
+            ),
+        };
+
+        let rows = self.db.query_all(statement).await?;
+        let mut suggestions = Vec::new();
+        for row in rows {
+            let similarity: Option<f32> = row.try_get("", "similarity")?;
+            if similarity.is_some_and(|s| s < RELATED_MIN_SIMILARITY) {
+                continue;
+            }
+            let title: String = row.try_get("", "title")?;
+            let summary: Option<String> = row.try_get("", "summary")?;
+            suggestions.push(SuggestedQuestion {
+                question: summary_question(&title, summary.as_deref()),
+                source: SuggestionSource::DocumentSummary,
+                score: similarity.unwrap_or(0.5) * DOCUMENT_SCORE_WEIGHT,
+                popularity: None,
+                document_id: Some(row.try_get("", "id")?),
+            });
+        }
+
+        debug!("由文档生成 {} 个推荐问题", suggestions.len());
+        Ok(suggestions)
+    }
+
+    async fn embed(&self, text: &str) -> Option<Vec<f32>> {
+        let result = match (EmbeddingPool::global(), &self.embedder) {
+            (Some(pool), _) => pool.embed(text, EmbeddingPriority::Interactive).await,
+            (None, Some(embedder)) => embedder.generate_embedding(text).await.map(|r| r.embedding),
+            (None, None) => return None,
+        };
+
+        result.map_err(|e| warn!("生成问题向量失败: {}", e)).ok()
+    }
+}
+
+/// 归一化问题：去除首尾空白与句末标点、合并空白并转为小写
+pub fn normalize_question(question: &str) -> String {
+    question
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(['?', '？', '。', '.', '!', '！'])
+        .trim()
+        .to_lowercase()
+}
+
+/// 将历史问题按向量相似度贪心聚类，按簇大小降序返回
+///
+/// 归一化后相同的问题直接归为一簇；缺少向量的问题只按文本归簇。
+pub fn cluster_questions(questions: &[LoggedQuestion], threshold: f32) -> Vec<QuestionCluster> {
+    struct Builder {
+        centroid: Option<Vec<f32>>,
+        embedded: u32,
+        count: u32,
+        variants: HashMap<String, (String, u32)>,
+        members: Vec<usize>,
+    }
+
+    let mut builders: Vec<Builder> = Vec::new();
+    for (index, logged) in questions.iter().enumerate() {
+        let normalized = normalize_question(&logged.question);
+        if normalized.is_empty() {
+            continue;
+        }
+
+        let position = builders.iter().position(|b| b.variants.contains_key(&normalized)).or_else(|| {
+            let embedding = logged.embedding.as_deref()?;
+            builders.iter()
+                .enumerate()
+                .filter_map(|(i, b)| b.centroid.as_deref().map(|c| (i, cosine_similarity(c, embedding))))
+                .filter(|(_, similarity)| *similarity >= threshold)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i)
+        });
+
+        let builder = match position {
+            Some(i) => &mut builders[i],
+            None => {
+                builders.push(Builder { centroid: None, embedded: 0, count: 0, variants: HashMap::new(), members: Vec::new() });
+                builders.last_mut().unwrap()
+            }
+        };
+
+        builder.count += 1;
+        builder.members.push(index);
+        builder.variants.entry(normalized).or_insert_with(|| (logged.question.trim().to_string(), 0)).1 += 1;
+        if let Some(embedding) = &logged.embedding {
+            builder.embedded += 1;
+            match &mut builder.centroid {
+                Some(centroid) if centroid.len() == embedding.len() => {
+                    let n = builder.embedded as f32;
+                    for (c, v) in centroid.iter_mut().zip(embedding) {
+                        *c += (v - *c) / n;
+                    }
+                }
+                Some(_) => {}
+                None => builder.centroid = Some(embedding.clone()),
+            }
+        }
+    }
+
+    let mut clusters: Vec<QuestionCluster> = builders.into_iter()
+        .map(|b| {
+            let representative = b.variants.into_values()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+                .map(|(question, _)| question)
+                .unwrap_or_default();
+            QuestionCluster { representative, count: b.count, centroid: b.centroid, members: b.members }
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.count.cmp(&a.count));
+    clusters
+}
+
+/// 热门问题簇转换为推荐问题
+///
+/// 指定问题向量时按相似度打分并排除同一问题，否则按热度打分。
+fn rank_clusters(clusters: &[QuestionCluster], question_embedding: Option<&[f32]>) -> Vec<SuggestedQuestion> {
+    let max_count = clusters.iter().map(|c| c.count).max().unwrap_or(1) as f32;
+    clusters.iter()
+        .filter_map(|cluster| {
+            let score = match question_embedding {
+                Some(embedding) => {
+                    let similarity = cosine_similarity(cluster.centroid.as_deref()?, embedding);
+                    if !(RELATED_MIN_SIMILARITY..SAME_QUESTION_SIMILARITY).contains(&similarity) {
+                        return None;
+                    }
+                    similarity
+                }
+                None => cluster.count as f32 / max_count,
+            };
+            Some(SuggestedQuestion {
+                question: cluster.representative.clone(),
+                source: SuggestionSource::PopularQuery,
+                score,
+                popularity: Some(cluster.count),
+                document_id: None,
+            })
+        })
+        .collect()
+}
+
+/// 按得分排序并去除重复问题
+fn dedupe_suggestions(
+    mut suggestions: Vec<SuggestedQuestion>,
+    exclude: &HashSet<String>,
+    limit: usize,
+) -> Vec<SuggestedQuestion> {
+    suggestions.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut seen = exclude.clone();
+    suggestions.into_iter()
+        .filter(|s| seen.insert(normalize_question(&s.question)))
+        .take(limit)
+        .collect()
+}
+
+/// 由文档标题与摘要生成问题
+///
+/// 摘要首句形如「本文介绍了……」时以其主题提问，否则以标题提问。
+pub fn summary_question(title: &str, summary: Option<&str>) -> String {
+    let topic = summary
+        .and_then(|summary| {
+            summary.split(['。', '！', '？', '.', '!', '?', '\n'])
+                .map(str::trim)
+                .find(|sentence| !sentence.is_empty())
+        })
+        .and_then(|sentence| {
+            let sentence = SUMMARY_SUBJECTS.iter()
+                .find_map(|subject| sentence.strip_prefix(subject))
+                .unwrap_or(sentence);
+            SUMMARY_VERBS.iter()
+                .find_map(|verb| sentence.strip_prefix(verb))
+                .map(|topic| topic.trim_start_matches(['，', ',', ' ']).trim())
+        })
+        .filter(|topic| !topic.is_empty() && topic.chars().count() <= MAX_TOPIC_CHARS);
+
+    match topic {
+        Some(topic) => format!("{}是什么？", topic),
+        None => format!("{}主要讲了什么？", title.trim()),
+    }
+}
+
+/// 向量余弦相似度，维度不一致或为零向量时返回 0
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+fn format_vector(vector: &[f32]) -> String {
+    format!("[{}]", vector.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))
+}
+
+/// 解析 pgvector 文本格式的向量
+pub fn parse_vector(text: &str) -> Option<Vec<f32>> {
+    text.trim()
+        .strip_prefix('[')?
+        .strip_suffix(']')?
+        .split(',')
+        .map(|v| v.trim().parse::<f32>().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logged(question: &str, embedding: Option<Vec<f32>>) -> LoggedQuestion {
+        LoggedQuestion { question: question.to_string(), embedding }
+    }
+
+    #[test]
+    fn test_normalize_question() {
+        assert_eq!(normalize_question("  如何  申请 报销？ "), "如何 申请 报销");
+        assert_eq!(normalize_question("What is RAG?"), "what is rag");
+    }
+
+    #[test]
+    fn test_cluster_questions() {
+        let questions = vec![
+            logged("如何申请报销？", Some(vec![1.0, 0.0])),
+            logged("报销怎么申请", Some(vec![0.95, 0.05])),
+            logged("如何申请报销", None),
+            logged("年假有几天？", Some(vec![0.0, 1.0])),
+        ];
+
+        let clusters = cluster_questions(&questions, 0.9);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].count, 3);
+        assert_eq!(clusters[0].members, vec![0, 1, 2]);
+        assert_eq!(clusters[0].representative, "如何申请报销？");
+        assert_eq!(clusters[1].representative, "年假有几天？");
+    }
+
+    #[test]
+    fn test_rank_clusters_excludes_same_question() {
+        let clusters = cluster_questions(
+            &[
+                logged("如何申请报销？", Some(vec![1.0, 0.0])),
+                logged("报销需要哪些材料？", Some(vec![0.8, 0.6])),
+                logged("年假有几天？", Some(vec![0.0, 1.0])),
+            ],
+            0.99,
+        );
+
+        let related = rank_clusters(&clusters, Some(&[1.0, 0.0]));
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].question, "报销需要哪些材料？");
+    }
+
+    #[test]
+    fn test_summary_question() {
+        assert_eq!(
+            summary_question("差旅制度", Some("本文介绍了公司差旅报销流程和标准。适用于全体员工。")),
+            "公司差旅报销流程和标准是什么？"
+        );
+        assert_eq!(summary_question("差旅制度", Some("适用于全体员工")), "差旅制度主要讲了什么？");
+        assert_eq!(summary_question("差旅制度", None), "差旅制度主要讲了什么？");
+    }
+
+    #[test]
+    fn test_parse_vector() {
+        assert_eq!(parse_vector("[0.5,-1,2]"), Some(vec![0.5, -1.0, 2.0]));
+        assert_eq!(parse_vector("0.5,1"), None);
+    }
+}
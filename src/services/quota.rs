@@ -10,7 +10,9 @@ use tracing::{info, instrument, debug};
 use utoipa::ToSchema;
 
 use crate::db::entities::{tenant, prelude::*};
+use crate::db::entities::tenant::{TenantQuotaLimits, TenantUsageStats};
 use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace};
 
 /// 配额类型枚举
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
@@ -29,6 +31,63 @@ pub enum QuotaType {
     DailyAiQueries,
 }
 
+impl QuotaType {
+    /// 所有配额类型
+    pub const ALL: [QuotaType; 6] = [
+        QuotaType::Users,
+        QuotaType::KnowledgeBases,
+        QuotaType::Documents,
+        QuotaType::Storage,
+        QuotaType::MonthlyApiCalls,
+        QuotaType::DailyAiQueries,
+    ];
+}
+
+/// 按配额限制与使用统计计算配额使用情况
+///
+/// 配额检查与套餐模拟共用此计算，保证模拟结果与实际限制一致。
+pub fn evaluate_quota(quota_type: &QuotaType, limits: &TenantQuotaLimits, stats: &TenantUsageStats) -> QuotaUsage {
+    let (current_usage, limit, reset_time) = match quota_type {
+        QuotaType::Users => (stats.current_users as u64, limits.max_users as u64, None),
+        QuotaType::KnowledgeBases => (stats.current_knowledge_bases as u64, limits.max_knowledge_bases as u64, None),
+        QuotaType::Documents => (stats.current_documents as u64, limits.max_documents as u64, None),
+        QuotaType::Storage => (stats.current_storage_bytes, limits.max_storage_bytes, None),
+        QuotaType::MonthlyApiCalls => {
+            let next_month = Utc::now().with_day(1).unwrap() + Duration::days(32);
+            let reset_time = next_month.with_day(1).unwrap();
+            (stats.monthly_api_calls as u64, limits.monthly_api_calls as u64, Some(reset_time))
+        },
+        QuotaType::DailyAiQueries => {
+            let tomorrow = Utc::now().date_naive() + chrono::Duration::days(1);
+            let reset_time = tomorrow.and_hms_opt(0, 0, 0).unwrap().and_utc();
+            (stats.daily_ai_queries as u64, limits.daily_ai_queries as u64, Some(reset_time))
+        },
+    };
+
+    let usage_percentage = if limit > 0 {
+        (current_usage as f64 / limit as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    let is_exceeded = current_usage > limit;
+    let remaining = if current_usage < limit {
+        limit - current_usage
+    } else {
+        0
+    };
+
+    QuotaUsage {
+        quota_type: quota_type.clone(),
+        current_usage,
+        limit,
+        usage_percentage,
+        is_exceeded,
+        remaining,
+        reset_time,
+    }
+}
+
 /// 配额使用情况
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct QuotaUsage {
@@ -202,6 +261,7 @@ impl QuotaService {
         active_tenant.updated_at = Set(Utc::now().into());
 
         let updated_tenant = active_tenant.update(&self.db).await?;
+        cache::refresh(CacheNamespace::Tenant, updated_tenant.id, &updated_tenant).await;
 
         // 返回更新后的配额使用情况
         self.get_quota_usage(&updated_tenant, &request.quota_type).await
@@ -293,7 +353,37 @@ impl QuotaService {
             .map_err(|e| AiStudioError::internal(format!("序列化使用统计失败: {}", e)))?);
         active_tenant.updated_at = Set(now.into());
 
-        active_tenant.update(&self.db).await?;
+        let updated_tenant = active_tenant.update(&self.db).await?;
+        cache::refresh(CacheNamespace::Tenant, updated_tenant.id, &updated_tenant).await;
+        Ok(())
+    }
+
+    /// 用实际统计覆盖知识库、文档与存储用量
+    ///
+    /// 增量更新可能因中途失败的操作产生偏差，由知识库统计汇总任务定期校正。
+    #[instrument(skip(self))]
+    pub async fn sync_content_usage(
+        &self,
+        tenant_id: Uuid,
+        knowledge_bases: u32,
+        documents: u32,
+        storage_bytes: u64,
+    ) -> Result<(), AiStudioError> {
+        let tenant = self.get_tenant(tenant_id).await?;
+        let mut usage_stats = tenant.get_usage_stats()
+            .map_err(|e| AiStudioError::internal(format!("解析使用统计失败: {}", e)))?;
+
+        usage_stats.current_knowledge_bases = knowledge_bases;
+        usage_stats.current_documents = documents;
+        usage_stats.current_storage_bytes = storage_bytes;
+
+        let mut active_tenant: tenant::ActiveModel = tenant.into();
+        active_tenant.usage_stats = Set(serde_json::to_value(&usage_stats)
+            .map_err(|e| AiStudioError::internal(format!("序列化使用统计失败: {}", e)))?);
+        active_tenant.updated_at = Set(Utc::now().into());
+
+        let updated_tenant = active_tenant.update(&self.db).await?;
+        cache::refresh(CacheNamespace::Tenant, updated_tenant.id, &updated_tenant).await;
         Ok(())
     }
 
@@ -351,45 +441,7 @@ impl QuotaService {
         let stats = tenant.get_usage_stats()
             .map_err(|e| AiStudioError::internal(format!("解析使用统计失败: {}", e)))?;
 
-        let (current_usage, limit, reset_time) = match quota_type {
-            QuotaType::Users => (stats.current_users as u64, limits.max_users as u64, None),
-            QuotaType::KnowledgeBases => (stats.current_knowledge_bases as u64, limits.max_knowledge_bases as u64, None),
-            QuotaType::Documents => (stats.current_documents as u64, limits.max_documents as u64, None),
-            QuotaType::Storage => (stats.current_storage_bytes, limits.max_storage_bytes, None),
-            QuotaType::MonthlyApiCalls => {
-                let next_month = Utc::now().with_day(1).unwrap() + Duration::days(32);
-                let reset_time = next_month.with_day(1).unwrap();
-                (stats.monthly_api_calls as u64, limits.monthly_api_calls as u64, Some(reset_time))
-            },
-            QuotaType::DailyAiQueries => {
-                let tomorrow = Utc::now().date_naive() + chrono::Duration::days(1);
-                let reset_time = tomorrow.and_hms_opt(0, 0, 0).unwrap().and_utc();
-                (stats.daily_ai_queries as u64, limits.daily_ai_queries as u64, Some(reset_time))
-            },
-        };
-
-        let usage_percentage = if limit > 0 {
-            (current_usage as f64 / limit as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        let is_exceeded = current_usage > limit;
-        let remaining = if current_usage < limit {
-            limit - current_usage
-        } else {
-            0
-        };
-
-        Ok(QuotaUsage {
-            quota_type: quota_type.clone(),
-            current_usage,
-            limit,
-            usage_percentage,
-            is_exceeded,
-            remaining,
-            reset_time,
-        })
+        Ok(evaluate_quota(quota_type, &limits, &stats))
     }
 }
 
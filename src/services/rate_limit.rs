@@ -37,6 +37,80 @@ pub struct RateLimitResult {
     pub reset_time: DateTime<Utc>,
     /// 重试建议时间（秒）
     pub retry_after: Option<u64>,
+    /// 策略名称
+    #[serde(default)]
+    pub policy: String,
+    /// 时间窗口（秒）
+    #[serde(default)]
+    pub window_seconds: u64,
+}
+
+impl RateLimitResult {
+    /// 距窗口重置的秒数
+    pub fn reset_after_seconds(&self) -> u64 {
+        (self.reset_time - Utc::now()).num_seconds().max(0) as u64
+    }
+}
+
+/// 限流维度
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitScope {
+    /// 全局
+    Global,
+    /// 客户端 IP
+    Ip,
+    /// 租户
+    Tenant,
+    /// 用户
+    User,
+    /// API 密钥
+    ApiKey,
+    /// 接口
+    Endpoint,
+    /// 自定义键
+    Custom,
+}
+
+/// 限流超限详情，作为 429 响应中错误的 details 返回，供客户端实现退避
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RateLimitExceeded {
+    /// 触发限流的维度
+    pub scope: RateLimitScope,
+    /// 触发限流的策略名称
+    pub policy: String,
+    /// 请求所属租户
+    pub tenant_id: Option<Uuid>,
+    /// 窗口内允许的最大请求数
+    pub limit: u64,
+    /// 窗口内剩余请求数
+    pub remaining: u64,
+    /// 时间窗口（秒）
+    pub window_seconds: u64,
+    /// 距窗口重置的秒数
+    pub reset_after_seconds: u64,
+    /// 窗口重置时间
+    pub reset_at: DateTime<Utc>,
+    /// 建议的重试等待时间（秒）
+    pub retry_after_seconds: u64,
+}
+
+impl RateLimitExceeded {
+    /// 根据未通过的限流结果构建超限详情
+    pub fn new(scope: RateLimitScope, tenant_id: Option<Uuid>, result: &RateLimitResult) -> Self {
+        let reset_after_seconds = result.reset_after_seconds();
+        Self {
+            scope,
+            policy: result.policy.clone(),
+            tenant_id,
+            limit: result.max_requests,
+            remaining: result.remaining_requests,
+            window_seconds: result.window_seconds,
+            reset_after_seconds,
+            reset_at: result.reset_time,
+            retry_after_seconds: result.retry_after.unwrap_or(reset_after_seconds),
+        }
+    }
 }
 
 /// 限流键类型
@@ -50,12 +124,29 @@ pub enum RateLimitKeyType {
     ApiKey(Uuid),
     /// 基于 IP 的限流
     Ip(String),
+    /// 基于接口的限流，键为 `METHOD 路由模式`，已识别租户时按租户隔离
+    Endpoint(String),
     /// 全局限流
     Global,
     /// 自定义键
     Custom(String),
 }
 
+impl RateLimitKeyType {
+    /// 限流维度
+    pub fn scope(&self) -> RateLimitScope {
+        match self {
+            RateLimitKeyType::Tenant(_) => RateLimitScope::Tenant,
+            RateLimitKeyType::User(_) => RateLimitScope::User,
+            RateLimitKeyType::ApiKey(_) => RateLimitScope::ApiKey,
+            RateLimitKeyType::Ip(_) => RateLimitScope::Ip,
+            RateLimitKeyType::Endpoint(_) => RateLimitScope::Endpoint,
+            RateLimitKeyType::Global => RateLimitScope::Global,
+            RateLimitKeyType::Custom(_) => RateLimitScope::Custom,
+        }
+    }
+}
+
 /// 限流配置
 #[derive(Debug, Clone)]
 pub struct RateLimitConfig {
@@ -110,6 +201,8 @@ impl RateLimitService {
                 remaining_requests: policy.max_requests,
                 reset_time: Utc::now() + chrono::Duration::seconds(policy.window_seconds as i64),
                 retry_after: None,
+                policy: policy.name.clone(),
+                window_seconds: policy.window_seconds,
             });
         }
 
@@ -264,6 +357,8 @@ impl RateLimitService {
             remaining_requests: if allowed { remaining_requests - 1 } else { remaining_requests },
             reset_time,
             retry_after,
+            policy: policy.name.clone(),
+            window_seconds: policy.window_seconds,
         })
     }
 
@@ -333,6 +428,8 @@ impl RateLimitService {
             remaining_requests,
             reset_time: Utc::now() + chrono::Duration::seconds(policy.window_seconds as i64),
             retry_after: if !allowed { Some(policy.window_seconds) } else { None },
+            policy: policy.name.clone(),
+            window_seconds: policy.window_seconds,
         })
     }
 
@@ -371,6 +468,8 @@ impl RateLimitService {
             remaining_requests: policy.max_requests,
             reset_time: Utc::now() + chrono::Duration::seconds(policy.window_seconds as i64),
             retry_after: None,
+            policy: policy.name.clone(),
+            window_seconds: policy.window_seconds,
         })
     }
 
@@ -396,6 +495,8 @@ impl RateLimitService {
             remaining_requests: policy.max_requests,
             reset_time: Utc::now() + chrono::Duration::seconds(policy.window_seconds as i64),
             retry_after: None,
+            policy: policy.name.clone(),
+            window_seconds: policy.window_seconds,
         })
     }
 
@@ -417,6 +518,7 @@ impl RateLimitService {
             RateLimitKeyType::User(id) => format!("user:{}", id),
             RateLimitKeyType::ApiKey(id) => format!("apikey:{}", id),
             RateLimitKeyType::Ip(ip) => format!("ip:{}", ip),
+            RateLimitKeyType::Endpoint(endpoint) => format!("endpoint:{}", endpoint),
             RateLimitKeyType::Global => "global".to_string(),
             RateLimitKeyType::Custom(key) => format!("custom:{}", key),
         };
@@ -506,6 +608,18 @@ impl RateLimitPolicies {
             },
         ]
     }
+
+    /// 接口限流策略
+    pub fn endpoint_policies() -> Vec<RateLimitPolicy> {
+        vec![
+            RateLimitPolicy {
+                window_seconds: 60,
+                max_requests: 300,
+                name: "endpoint_per_minute".to_string(),
+                enabled: true,
+            },
+        ]
+    }
 }
 
 /// 限流服务工厂
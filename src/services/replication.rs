@@ -0,0 +1,743 @@
+// 跨区域复制服务
+// 主区域将复制事件表中的租户数据变更发布到队列，备用区域按顺序消费并应用，支持提升为主区域
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use sea_orm::{
+    ConnectionTrait, DatabaseBackend, DatabaseConnection, DatabaseTransaction, QueryResult, Statement,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::{ReplicationConfig, ReplicationRole};
+use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace};
+use crate::services::scheduler::PeriodicJob;
+
+static GLOBAL_REPLICATION: OnceCell<Arc<ReplicationService>> = OnceCell::new();
+
+/// 参与复制的数据表，按外键依赖顺序排列
+pub const REPLICATED_TABLES: [&str; 5] = [
+    "tenants",
+    "knowledge_bases",
+    "documents",
+    "document_chunks",
+    "embeddings",
+];
+
+/// 仅复制元数据的表
+///
+/// 复制事件不携带向量数据，备用区域不应用这些事件，提升后重新生成向量。
+const METADATA_ONLY_TABLES: [&str; 1] = ["embeddings"];
+
+/// 提升时排空队列的最大批次数，避免主区域仍在写入时无限等待
+const MAX_DRAIN_BATCHES: usize = 1000;
+
+/// 复制事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplicationEvent {
+    /// 事件 ID（主区域复制事件表自增 ID）
+    pub id: i64,
+    /// 所属租户
+    pub tenant_id: Option<Uuid>,
+    /// 数据表
+    pub table_name: String,
+    /// 记录 ID
+    pub record_id: Uuid,
+    /// 操作类型：insert、update、delete
+    pub operation: String,
+    /// 变更后的行数据（删除时为删除前的行数据）
+    pub payload: serde_json::Value,
+    /// 变更时间
+    pub created_at: DateTime<Utc>,
+}
+
+impl ReplicationEvent {
+    fn from_row(row: &QueryResult) -> Result<Self, AiStudioError> {
+        Ok(Self {
+            id: row.try_get("", "id")?,
+            tenant_id: row.try_get("", "tenant_id")?,
+            table_name: row.try_get("", "table_name")?,
+            record_id: row.try_get("", "record_id")?,
+            operation: row.try_get("", "operation")?,
+            payload: row.try_get("", "payload")?,
+            created_at: row.try_get("", "created_at")?,
+        })
+    }
+}
+
+/// 复制队列接口
+///
+/// 队列按发布顺序投递事件，位置标识由队列实现定义，备用区域以最后应用的位置作为游标。
+#[async_trait::async_trait]
+pub trait ReplicationQueue: Send + Sync {
+    /// 按顺序发布事件
+    async fn publish(&self, events: &[ReplicationEvent]) -> Result<(), AiStudioError>;
+
+    /// 读取位置之后的事件，返回（位置，事件）列表
+    async fn read(
+        &self,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<(String, ReplicationEvent)>, AiStudioError>;
+}
+
+/// 基于 Redis Stream 的复制队列
+#[cfg(feature = "redis")]
+pub struct RedisReplicationQueue {
+    client: redis::Client,
+    stream: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisReplicationQueue {
+    /// 创建 Redis Stream 复制队列
+    pub fn new(url: &str, stream: impl Into<String>) -> Result<Self, AiStudioError> {
+        let client = redis::Client::open(url)
+            .map_err(|e| AiStudioError::configuration(format!("复制队列地址无效: {}", e)))?;
+        Ok(Self { client, stream: stream.into() })
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait::async_trait]
+impl ReplicationQueue for RedisReplicationQueue {
+    async fn publish(&self, events: &[ReplicationEvent]) -> Result<(), AiStudioError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.client.get_async_connection().await
+            .map_err(|e| AiStudioError::external_service("replication_queue", e.to_string()))?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for event in events {
+            let body = serde_json::to_string(event)
+                .map_err(|e| AiStudioError::internal(format!("序列化复制事件失败: {}", e)))?;
+            pipe.cmd("XADD").arg(&self.stream).arg("*").arg("event").arg(body).ignore();
+        }
+
+        pipe.query_async::<_, ()>(&mut conn).await
+            .map_err(|e| AiStudioError::external_service("replication_queue", e.to_string()))
+    }
+
+    async fn read(
+        &self,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<(String, ReplicationEvent)>, AiStudioError> {
+        let mut conn = self.client.get_async_connection().await
+            .map_err(|e| AiStudioError::external_service("replication_queue", e.to_string()))?;
+
+        // "(" 前缀表示不包含起始位置
+        let start = after.map(|position| format!("({}", position)).unwrap_or_else(|| "-".to_string());
+        let entries: Vec<(String, Vec<String>)> = redis::cmd("XRANGE")
+            .arg(&self.stream)
+            .arg(start)
+            .arg("+")
+            .arg("COUNT")
+            .arg(limit)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| AiStudioError::external_service("replication_queue", e.to_string()))?;
+
+        entries
+            .into_iter()
+            .map(|(position, fields)| {
+                let body = fields
+                    .chunks(2)
+                    .find(|pair| pair[0] == "event")
+                    .and_then(|pair| pair.get(1))
+                    .ok_or_else(|| AiStudioError::internal(format!("复制事件 {} 缺少 event 字段", position)))?;
+                let event = serde_json::from_str(body)
+                    .map_err(|e| AiStudioError::internal(format!("解析复制事件 {} 失败: {}", position, e)))?;
+                Ok((position, event))
+            })
+            .collect()
+    }
+}
+
+/// 内存复制队列，用于单进程部署与测试
+#[derive(Default)]
+pub struct InMemoryReplicationQueue {
+    events: RwLock<Vec<ReplicationEvent>>,
+}
+
+impl InMemoryReplicationQueue {
+    /// 创建内存复制队列
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl ReplicationQueue for InMemoryReplicationQueue {
+    async fn publish(&self, events: &[ReplicationEvent]) -> Result<(), AiStudioError> {
+        self.events.write().await.extend_from_slice(events);
+        Ok(())
+    }
+
+    async fn read(
+        &self,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<(String, ReplicationEvent)>, AiStudioError> {
+        let start = match after {
+            Some(position) => position.parse::<usize>()
+                .map_err(|_| AiStudioError::validation("position", format!("无效的队列位置: {}", position)))?
+                + 1,
+            None => 0,
+        };
+
+        let events = self.events.read().await;
+        Ok(events
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(limit)
+            .map(|(index, event)| (index.to_string(), event.clone()))
+            .collect())
+    }
+}
+
+/// 备用区域的消费进度（本实例内存状态）
+#[derive(Debug, Clone, Default)]
+struct ApplierProgress {
+    /// 最后一次成功读取队列的时间
+    last_poll_at: Option<DateTime<Utc>>,
+    /// 最后一次读取时队列是否已无积压
+    caught_up: bool,
+}
+
+/// 复制延迟状态
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReplicationLagStatus {
+    /// 区域标识
+    pub region: String,
+    /// 区域角色
+    pub role: String,
+    /// 复制延迟（秒），无法确定时为空
+    pub lag_secs: Option<u64>,
+    /// 允许的最大延迟（秒）
+    pub max_lag_secs: u64,
+    /// 延迟是否在允许范围内
+    pub healthy: bool,
+    /// 主区域尚未发布的事件数
+    pub unpublished_events: Option<u64>,
+    /// 备用区域最后应用的事件 ID
+    pub last_applied_event_id: Option<i64>,
+    /// 备用区域最后应用时间
+    pub last_applied_at: Option<DateTime<Utc>>,
+    /// 最近一次提升时间
+    pub promoted_at: Option<DateTime<Utc>>,
+}
+
+/// 提升结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PromotionReport {
+    /// 区域标识
+    pub region: String,
+    /// 提升前排空队列时应用的事件数
+    pub drained_events: u64,
+    /// 缺少向量、需要重新生成的分块数
+    pub chunks_missing_embeddings: u64,
+    /// 提升时间
+    pub promoted_at: DateTime<Utc>,
+}
+
+/// 区域复制状态（replication_state 表）
+#[derive(Debug, Clone)]
+struct RegionState {
+    role: ReplicationRole,
+    stream_position: Option<String>,
+    last_applied_event_id: Option<i64>,
+    last_applied_source_at: Option<DateTime<Utc>>,
+    last_applied_at: Option<DateTime<Utc>>,
+    promoted_at: Option<DateTime<Utc>>,
+}
+
+/// 跨区域复制服务
+pub struct ReplicationService {
+    db: DatabaseConnection,
+    config: ReplicationConfig,
+    queue: Arc<dyn ReplicationQueue>,
+    progress: RwLock<ApplierProgress>,
+    columns: RwLock<HashMap<String, Vec<String>>>,
+}
+
+impl ReplicationService {
+    /// 创建新的复制服务实例
+    pub fn new(db: DatabaseConnection, config: ReplicationConfig, queue: Arc<dyn ReplicationQueue>) -> Self {
+        Self {
+            db,
+            config,
+            queue,
+            progress: RwLock::new(ApplierProgress::default()),
+            columns: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 安装全局复制服务
+    pub fn install_global(service: Arc<ReplicationService>) -> Result<(), AiStudioError> {
+        GLOBAL_REPLICATION.set(service)
+            .map_err(|_| AiStudioError::internal("复制服务已经初始化"))
+    }
+
+    /// 全局复制服务，未启用复制时为空
+    pub fn global() -> Option<Arc<ReplicationService>> {
+        GLOBAL_REPLICATION.get().cloned()
+    }
+
+    /// 初始化区域复制状态
+    ///
+    /// 已持久化的角色优先于配置，区域提升后无需修改配置即可保持主区域角色。
+    pub async fn init(&self) -> Result<ReplicationRole, AiStudioError> {
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "INSERT INTO replication_state (region, role) VALUES ($1, $2) ON CONFLICT (region) DO NOTHING",
+                [self.config.region.clone().into(), self.config.role.as_str().into()],
+            ))
+            .await?;
+
+        let role = self.load_state(&self.db, false).await?.role;
+        if role != self.config.role {
+            warn!(region = %self.config.region, role = role.as_str(), "区域角色与配置不一致，以已持久化的角色为准");
+        }
+        Ok(role)
+    }
+
+    /// 当前区域角色
+    pub async fn current_role(&self) -> Result<ReplicationRole, AiStudioError> {
+        Ok(self.load_state(&self.db, false).await?.role)
+    }
+
+    /// 发布尚未发布的复制事件（主区域）
+    ///
+    /// 按事件 ID 顺序发布，发布成功后标记；标记失败时下一轮按相同顺序重新发布，应用端按记录 ID 幂等。
+    #[instrument(skip(self))]
+    pub async fn publish_pending(&self) -> Result<usize, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT id, tenant_id, table_name, record_id, operation, payload, created_at \
+                 FROM replication_events WHERE published_at IS NULL ORDER BY id LIMIT $1",
+                [(self.config.batch_size as i64).into()],
+            ))
+            .await?;
+
+        let events = rows.iter().map(ReplicationEvent::from_row).collect::<Result<Vec<_>, _>>()?;
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        self.queue.publish(&events).await?;
+
+        // 事件 ID 均为整数，直接拼接不存在注入风险
+        let ids = events.iter().map(|event| event.id.to_string()).collect::<Vec<_>>().join(",");
+        self.db
+            .execute(Statement::from_string(
+                DatabaseBackend::Postgres,
+                format!("UPDATE replication_events SET published_at = NOW() WHERE id IN ({})", ids),
+            ))
+            .await?;
+
+        debug!(count = events.len(), "已发布复制事件");
+        Ok(events.len())
+    }
+
+    /// 清理已发布且超过保留时间的复制事件
+    pub async fn prune_published(&self, retention: Duration) -> Result<u64, AiStudioError> {
+        let cutoff = Utc::now() - chrono::Duration::from_std(retention).unwrap_or_else(|_| chrono::Duration::days(1));
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "DELETE FROM replication_events WHERE published_at IS NOT NULL AND published_at < $1",
+                [cutoff.into()],
+            ))
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 应用队列中的下一批复制事件（备用区域）
+    ///
+    /// 整批事件与消费位置在同一事务中提交；锁定区域状态行，多实例时同一时刻只有一个实例在应用。
+    #[instrument(skip(self))]
+    pub async fn apply_pending(&self) -> Result<usize, AiStudioError> {
+        let txn = self.db.begin().await?;
+        let state = self.load_state(&txn, true).await?;
+
+        let batch = self.queue
+            .read(state.stream_position.as_deref(), self.config.batch_size as usize)
+            .await?;
+        {
+            let mut progress = self.progress.write().await;
+            progress.last_poll_at = Some(Utc::now());
+            progress.caught_up = batch.len() < self.config.batch_size as usize;
+        }
+
+        let Some((last_position, last_event)) = batch.last().cloned() else {
+            txn.commit().await?;
+            return Ok(0);
+        };
+
+        // 应用期间不再捕获复制事件，避免备用区域产生回环事件
+        txn.execute(Statement::from_string(
+            DatabaseBackend::Postgres,
+            "SET LOCAL aionix.replication_apply = 'on'".to_string(),
+        ))
+        .await?;
+
+        for (_, event) in &batch {
+            self.apply_event(&txn, event).await?;
+        }
+
+        txn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE replication_state SET stream_position = $2, last_applied_event_id = $3, \
+             last_applied_source_at = $4, last_applied_at = NOW() WHERE region = $1",
+            [
+                self.config.region.clone().into(),
+                last_position.into(),
+                last_event.id.into(),
+                last_event.created_at.into(),
+            ],
+        ))
+        .await?;
+        txn.commit().await?;
+
+        for (_, event) in batch.iter().filter(|(_, event)| event.table_name == "tenants") {
+            cache::invalidate(CacheNamespace::Tenant, event.record_id).await;
+        }
+
+        debug!(count = batch.len(), last_event_id = last_event.id, "已应用复制事件");
+        Ok(batch.len())
+    }
+
+    /// 应用单个复制事件
+    async fn apply_event(&self, txn: &DatabaseTransaction, event: &ReplicationEvent) -> Result<(), AiStudioError> {
+        let table = event.table_name.as_str();
+        if !REPLICATED_TABLES.contains(&table) {
+            return Err(AiStudioError::validation("table_name", format!("不支持复制的数据表: {}", table)));
+        }
+        if METADATA_ONLY_TABLES.contains(&table) {
+            return Ok(());
+        }
+
+        match event.operation.as_str() {
+            "delete" => {
+                txn.execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    &format!("DELETE FROM {} WHERE id = $1", table),
+                    [event.record_id.into()],
+                ))
+                .await?;
+            }
+            "insert" | "update" => {
+                let columns = self.table_columns(txn, table).await?;
+                txn.execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    &upsert_sql(table, &columns),
+                    [event.payload.clone().into()],
+                ))
+                .await?;
+            }
+            other => {
+                return Err(AiStudioError::validation("operation", format!("未知的复制操作: {}", other)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 将备用区域提升为主区域
+    ///
+    /// 先排空队列中已发布的事件，再持久化主区域角色；提升后本区域开始发布自己的复制事件。
+    #[instrument(skip(self))]
+    pub async fn promote(&self) -> Result<PromotionReport, AiStudioError> {
+        if self.current_role().await? == ReplicationRole::Primary {
+            return Err(AiStudioError::conflict(format!("区域 {} 已经是主区域", self.config.region)));
+        }
+
+        let mut drained_events = 0u64;
+        for _ in 0..MAX_DRAIN_BATCHES {
+            let applied = self.apply_pending().await?;
+            drained_events += applied as u64;
+            if applied < self.config.batch_size as usize {
+                break;
+            }
+        }
+
+        let promoted_at = Utc::now();
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE replication_state SET role = 'primary', promoted_at = $2 WHERE region = $1",
+                [self.config.region.clone().into(), promoted_at.into()],
+            ))
+            .await?;
+
+        let row = self.db
+            .query_one(Statement::from_string(
+                DatabaseBackend::Postgres,
+                "SELECT COUNT(*) AS count FROM document_chunks c \
+                 WHERE NOT EXISTS (SELECT 1 FROM embeddings e WHERE e.chunk_id = c.id)"
+                    .to_string(),
+            ))
+            .await?;
+        let chunks_missing_embeddings = row
+            .map(|r| r.try_get::<i64>("", "count").unwrap_or(0))
+            .unwrap_or(0) as u64;
+
+        info!(
+            region = %self.config.region,
+            drained_events = drained_events,
+            chunks_missing_embeddings = chunks_missing_embeddings,
+            "区域已提升为主区域"
+        );
+
+        Ok(PromotionReport {
+            region: self.config.region.clone(),
+            drained_events,
+            chunks_missing_embeddings,
+            promoted_at,
+        })
+    }
+
+    /// 获取复制延迟状态
+    ///
+    /// 主区域的延迟为最早未发布事件的等待时间；备用区域已追平时为距最后一次读取队列的时间，
+    /// 否则为最后应用事件的变更时间至今。
+    pub async fn lag_status(&self) -> Result<ReplicationLagStatus, AiStudioError> {
+        let state = self.load_state(&self.db, false).await?;
+        let now = Utc::now();
+
+        let (lag_secs, unpublished_events) = match state.role {
+            ReplicationRole::Primary => {
+                let row = self.db
+                    .query_one(Statement::from_string(
+                        DatabaseBackend::Postgres,
+                        "SELECT COUNT(*) AS count, MIN(created_at) AS oldest \
+                         FROM replication_events WHERE published_at IS NULL"
+                            .to_string(),
+                    ))
+                    .await?;
+                let (count, oldest) = match row {
+                    Some(row) => (
+                        row.try_get::<i64>("", "count")?,
+                        row.try_get::<Option<DateTime<Utc>>>("", "oldest")?,
+                    ),
+                    None => (0, None),
+                };
+                (Some(oldest.map(|oldest| seconds_between(oldest, now)).unwrap_or(0)), Some(count as u64))
+            }
+            ReplicationRole::Passive => {
+                let progress = self.progress.read().await.clone();
+                (passive_lag_secs(&progress, state.last_applied_source_at, now), None)
+            }
+        };
+
+        Ok(ReplicationLagStatus {
+            region: self.config.region.clone(),
+            role: state.role.as_str().to_string(),
+            lag_secs,
+            max_lag_secs: self.config.max_lag_secs,
+            healthy: lag_secs.is_some_and(|lag| lag <= self.config.max_lag_secs),
+            unpublished_events,
+            last_applied_event_id: state.last_applied_event_id,
+            last_applied_at: state.last_applied_at,
+            promoted_at: state.promoted_at,
+        })
+    }
+
+    /// 读取区域复制状态
+    async fn load_state<C: ConnectionTrait>(&self, conn: &C, for_update: bool) -> Result<RegionState, AiStudioError> {
+        let sql = format!(
+            "SELECT role, stream_position, last_applied_event_id, last_applied_source_at, last_applied_at, promoted_at \
+             FROM replication_state WHERE region = $1{}",
+            if for_update { " FOR UPDATE" } else { "" }
+        );
+        let row = conn
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                &sql,
+                [self.config.region.clone().into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found(format!("区域复制状态 {}", self.config.region)))?;
+
+        let role: String = row.try_get("", "role")?;
+        Ok(RegionState {
+            role: if role == "primary" { ReplicationRole::Primary } else { ReplicationRole::Passive },
+            stream_position: row.try_get("", "stream_position")?,
+            last_applied_event_id: row.try_get("", "last_applied_event_id")?,
+            last_applied_source_at: row.try_get("", "last_applied_source_at")?,
+            last_applied_at: row.try_get("", "last_applied_at")?,
+            promoted_at: row.try_get("", "promoted_at")?,
+        })
+    }
+
+    /// 获取数据表的列名（按定义顺序，进程内缓存）
+    async fn table_columns(&self, txn: &DatabaseTransaction, table: &str) -> Result<Vec<String>, AiStudioError> {
+        if let Some(columns) = self.columns.read().await.get(table) {
+            return Ok(columns.clone());
+        }
+
+        let rows = txn
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT column_name FROM information_schema.columns \
+                 WHERE table_schema = current_schema() AND table_name = $1 ORDER BY ordinal_position",
+                [table.into()],
+            ))
+            .await?;
+        let columns = rows
+            .iter()
+            .map(|row| row.try_get::<String>("", "column_name"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.columns.write().await.insert(table.to_string(), columns.clone());
+        Ok(columns)
+    }
+}
+
+/// 构造按 ID 幂等写入整行的语句（`$1` 为行数据 JSON）
+fn upsert_sql(table: &str, columns: &[String]) -> String {
+    let column_list = columns.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(", ");
+    let updates = columns
+        .iter()
+        .filter(|c| c.as_str() != "id")
+        .map(|c| format!("\"{c}\" = EXCLUDED.\"{c}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "INSERT INTO {table} ({columns}) SELECT {columns} FROM jsonb_populate_record(NULL::{table}, $1) \
+         ON CONFLICT (id) DO UPDATE SET {updates}",
+        table = table,
+        columns = column_list,
+        updates = updates,
+    )
+}
+
+/// 计算备用区域的复制延迟（秒）
+fn passive_lag_secs(
+    progress: &ApplierProgress,
+    last_applied_source_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<u64> {
+    let last_poll_at = progress.last_poll_at?;
+    if progress.caught_up {
+        Some(seconds_between(last_poll_at, now))
+    } else {
+        last_applied_source_at.map(|source_at| seconds_between(source_at, now))
+    }
+}
+
+fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> u64 {
+    (to - from).num_seconds().max(0) as u64
+}
+
+/// 跨区域复制周期任务
+///
+/// 主区域发布复制事件并清理已发布的旧事件，备用区域消费并应用复制事件。
+pub struct ReplicationJob {
+    service: Arc<ReplicationService>,
+    name: String,
+}
+
+impl ReplicationJob {
+    /// 已发布事件的保留时间
+    const PUBLISHED_RETENTION: Duration = Duration::from_secs(24 * 3600);
+
+    /// 创建新的复制周期任务
+    pub fn new(service: Arc<ReplicationService>) -> Self {
+        let name = format!("replication_{}", service.config.region);
+        Self { service, name }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for ReplicationJob {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.service.config.interval_secs)
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        match self.service.current_role().await? {
+            ReplicationRole::Primary => {
+                while self.service.publish_pending().await? >= self.service.config.batch_size as usize {}
+                self.service.prune_published(Self::PUBLISHED_RETENTION).await?;
+            }
+            ReplicationRole::Passive => {
+                while self.service.apply_pending().await? >= self.service.config.batch_size as usize {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: i64) -> ReplicationEvent {
+        ReplicationEvent {
+            id,
+            tenant_id: Some(Uuid::new_v4()),
+            table_name: "documents".to_string(),
+            record_id: Uuid::new_v4(),
+            operation: "insert".to_string(),
+            payload: serde_json::json!({ "id": Uuid::new_v4() }),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_queue_reads_after_position() {
+        let queue = InMemoryReplicationQueue::new();
+        queue.publish(&[event(1), event(2), event(3)]).await.unwrap();
+
+        let first = queue.read(None, 2).await.unwrap();
+        assert_eq!(first.iter().map(|(_, e)| e.id).collect::<Vec<_>>(), vec![1, 2]);
+
+        let rest = queue.read(Some(&first[1].0), 10).await.unwrap();
+        assert_eq!(rest.iter().map(|(_, e)| e.id).collect::<Vec<_>>(), vec![3]);
+        assert!(queue.read(Some(&rest[0].0), 10).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_upsert_sql_updates_all_columns_but_id() {
+        let columns = vec!["id".to_string(), "name".to_string(), "updated_at".to_string()];
+        let sql = upsert_sql("knowledge_bases", &columns);
+
+        assert!(sql.starts_with("INSERT INTO knowledge_bases (\"id\", \"name\", \"updated_at\")"));
+        assert!(sql.contains("jsonb_populate_record(NULL::knowledge_bases, $1)"));
+        assert!(sql.ends_with("DO UPDATE SET \"name\" = EXCLUDED.\"name\", \"updated_at\" = EXCLUDED.\"updated_at\""));
+    }
+
+    #[test]
+    fn test_passive_lag() {
+        let now = Utc::now();
+        let never_polled = ApplierProgress::default();
+        assert_eq!(passive_lag_secs(&never_polled, None, now), None);
+
+        let caught_up = ApplierProgress { last_poll_at: Some(now - chrono::Duration::seconds(3)), caught_up: true };
+        assert_eq!(passive_lag_secs(&caught_up, Some(now - chrono::Duration::hours(1)), now), Some(3));
+
+        let behind = ApplierProgress { last_poll_at: Some(now), caught_up: false };
+        assert_eq!(passive_lag_secs(&behind, Some(now - chrono::Duration::seconds(90)), now), Some(90));
+    }
+}
@@ -0,0 +1,301 @@
+// 数据保留服务
+// 按数据类别的保留策略分批清理过期会话、执行记录和审计日志
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info, instrument};
+
+use crate::config::RetentionConfig;
+use crate::errors::AiStudioError;
+use crate::services::scheduler::PeriodicJob;
+
+/// 数据类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataClass {
+    /// 用户会话
+    Sessions,
+    /// Agent 执行记录
+    AgentExecutions,
+    /// 工作流执行记录（步骤执行记录级联删除）
+    WorkflowExecutions,
+    /// 审计日志
+    AuditLogs,
+}
+
+impl DataClass {
+    /// 按清理顺序排列的所有类别
+    ///
+    /// 执行记录引用会话，因此先清理执行记录再清理会话。
+    pub fn all() -> [DataClass; 4] {
+        [
+            DataClass::AgentExecutions,
+            DataClass::WorkflowExecutions,
+            DataClass::AuditLogs,
+            DataClass::Sessions,
+        ]
+    }
+
+    /// 对应的数据表
+    pub fn table(&self) -> &'static str {
+        match self {
+            DataClass::Sessions => "sessions",
+            DataClass::AgentExecutions => "agent_executions",
+            DataClass::WorkflowExecutions => "workflow_executions",
+            DataClass::AuditLogs => "audit_logs",
+        }
+    }
+
+    /// 过期判定条件（`$1` 为截止时间）
+    ///
+    /// 处于法律保留状态的会话及其执行记录不参与清理。
+    fn expiry_predicate(&self) -> &'static str {
+        match self {
+            DataClass::Sessions => {
+                "expires_at < $1 \
+                 AND NOT EXISTS (SELECT 1 FROM agent_executions ae WHERE ae.session_id = sessions.id) \
+                 AND NOT EXISTS (SELECT 1 FROM workflow_executions we WHERE we.session_id = sessions.id) \
+                 AND NOT EXISTS (SELECT 1 FROM legal_holds h WHERE h.released_at IS NULL \
+                     AND h.target_type = 'conversation' AND h.target_id = sessions.id::text)"
+            }
+            DataClass::AgentExecutions => {
+                "completed_at IS NOT NULL AND completed_at < $1 \
+                 AND NOT EXISTS (SELECT 1 FROM legal_holds h WHERE h.released_at IS NULL \
+                     AND h.target_type = 'conversation' AND h.target_id = agent_executions.session_id::text)"
+            }
+            DataClass::WorkflowExecutions => {
+                "completed_at IS NOT NULL AND completed_at < $1 \
+                 AND NOT EXISTS (SELECT 1 FROM legal_holds h WHERE h.released_at IS NULL \
+                     AND h.target_type = 'conversation' AND h.target_id = workflow_executions.session_id::text)"
+            }
+            DataClass::AuditLogs => "created_at < $1",
+        }
+    }
+
+    /// 从配置中读取保留天数
+    pub fn retention_days(&self, config: &RetentionConfig) -> u32 {
+        match self {
+            DataClass::Sessions => config.sessions_days,
+            DataClass::AgentExecutions => config.agent_executions_days,
+            DataClass::WorkflowExecutions => config.workflow_executions_days,
+            DataClass::AuditLogs => config.audit_logs_days,
+        }
+    }
+}
+
+/// 单个类别的清理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    /// 数据类别
+    pub data_class: DataClass,
+    /// 截止时间（早于该时间的数据被清理）
+    pub cutoff: DateTime<Utc>,
+    /// 删除行数
+    pub deleted_rows: u64,
+    /// 执行批次数
+    pub batches: u32,
+    /// 耗时（毫秒）
+    pub duration_ms: u64,
+}
+
+/// 累计清理指标
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionMetrics {
+    /// 各类别累计回收行数
+    pub reclaimed_rows: HashMap<DataClass, u64>,
+    /// 累计执行次数
+    pub runs: u64,
+    /// 最后一次执行时间
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// 最后一次执行结果
+    pub last_reports: Vec<RetentionReport>,
+}
+
+/// 数据保留服务
+pub struct RetentionService {
+    db: DatabaseConnection,
+    config: RetentionConfig,
+    metrics: Arc<RwLock<RetentionMetrics>>,
+}
+
+impl RetentionService {
+    /// 创建新的数据保留服务实例
+    pub fn new(db: DatabaseConnection, config: RetentionConfig) -> Self {
+        Self {
+            db,
+            config,
+            metrics: Arc::new(RwLock::new(RetentionMetrics::default())),
+        }
+    }
+
+    /// 按保留策略清理所有类别
+    #[instrument(skip(self))]
+    pub async fn run_cleanup(&self) -> Result<Vec<RetentionReport>, AiStudioError> {
+        let mut reports = Vec::new();
+
+        for data_class in DataClass::all() {
+            let days = data_class.retention_days(&self.config);
+            if days == 0 {
+                debug!(data_class = ?data_class, "保留天数为 0，跳过清理");
+                continue;
+            }
+
+            if !self.table_exists(data_class.table()).await? {
+                debug!(table = data_class.table(), "数据表不存在，跳过清理");
+                continue;
+            }
+
+            let cutoff = Utc::now() - chrono::Duration::days(days as i64);
+            reports.push(self.purge(data_class, cutoff).await?);
+        }
+
+        let mut metrics = self.metrics.write().await;
+        for report in &reports {
+            *metrics.reclaimed_rows.entry(report.data_class).or_insert(0) += report.deleted_rows;
+        }
+        metrics.runs += 1;
+        metrics.last_run_at = Some(Utc::now());
+        metrics.last_reports = reports.clone();
+
+        Ok(reports)
+    }
+
+    /// 分批删除某一类别中早于截止时间的数据
+    #[instrument(skip(self))]
+    pub async fn purge(
+        &self,
+        data_class: DataClass,
+        cutoff: DateTime<Utc>,
+    ) -> Result<RetentionReport, AiStudioError> {
+        let started = Instant::now();
+        let table = data_class.table();
+        let sql = format!(
+            "DELETE FROM {table} WHERE ctid IN (SELECT ctid FROM {table} WHERE {predicate} LIMIT {limit})",
+            table = table,
+            predicate = data_class.expiry_predicate(),
+            limit = self.config.batch_size,
+        );
+
+        let mut deleted_rows = 0u64;
+        let mut batches = 0u32;
+
+        loop {
+            let result = self.db
+                .execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    &sql,
+                    [cutoff.into()],
+                ))
+                .await?;
+
+            batches += 1;
+            deleted_rows += result.rows_affected();
+
+            if result.rows_affected() < self.config.batch_size as u64 {
+                break;
+            }
+
+            // 批次之间让出执行权，避免长时间占用连接和锁
+            tokio::task::yield_now().await;
+        }
+
+        let report = RetentionReport {
+            data_class,
+            cutoff,
+            deleted_rows,
+            batches,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+
+        if deleted_rows > 0 {
+            info!(
+                table = table,
+                deleted_rows = deleted_rows,
+                batches = batches,
+                duration_ms = report.duration_ms,
+                "数据保留清理完成"
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// 获取累计清理指标
+    pub async fn get_metrics(&self) -> RetentionMetrics {
+        self.metrics.read().await.clone()
+    }
+
+    /// 检查表是否存在
+    async fn table_exists(&self, table_name: &str) -> Result<bool, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT EXISTS (SELECT FROM information_schema.tables WHERE table_name = $1) AS exists",
+                [table_name.into()],
+            ))
+            .await?;
+
+        Ok(row.map(|r| r.try_get("", "exists").unwrap_or(false)).unwrap_or(false))
+    }
+}
+
+/// 数据保留周期任务
+pub struct RetentionJob {
+    service: Arc<RetentionService>,
+}
+
+impl RetentionJob {
+    /// 创建新的数据保留周期任务
+    pub fn new(service: Arc<RetentionService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for RetentionJob {
+    fn name(&self) -> &str {
+        "data_retention"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.service.config.interval_secs)
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        self.service.run_cleanup().await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions_cleaned_after_executions() {
+        let order = DataClass::all();
+        let sessions = order.iter().position(|c| *c == DataClass::Sessions).unwrap();
+        let agent = order.iter().position(|c| *c == DataClass::AgentExecutions).unwrap();
+        let workflow = order.iter().position(|c| *c == DataClass::WorkflowExecutions).unwrap();
+        assert!(sessions > agent);
+        assert!(sessions > workflow);
+    }
+
+    #[test]
+    fn test_held_conversations_excluded() {
+        for data_class in [DataClass::Sessions, DataClass::AgentExecutions, DataClass::WorkflowExecutions] {
+            assert!(data_class.expiry_predicate().contains("legal_holds"));
+        }
+    }
+
+    #[test]
+    fn test_retention_days_from_config() {
+        let config = crate::config::AppConfig::default().retention;
+        assert_eq!(DataClass::Sessions.retention_days(&config), config.sessions_days);
+        assert_eq!(DataClass::AuditLogs.retention_days(&config), config.audit_logs_days);
+    }
+}
@@ -0,0 +1,156 @@
+// 路由级响应缓存
+// 为读多写少的 GET 接口缓存完整响应，按租户/用户/查询参数区分缓存项，写接口通过轮换缓存代次使其失效
+
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::services::cache::{CacheNamespace, CacheService};
+
+/// 缓存代次的存活时间，远长于任何路由的缓存时间
+const GENERATION_TTL: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// 路由缓存作用域，同一作用域内的缓存项由对应的写接口统一失效
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteCacheScope {
+    /// 知识库列表与详情
+    KnowledgeBases,
+    /// 工作流列表与详情
+    Workflows,
+    /// 版本信息
+    Version,
+}
+
+impl RouteCacheScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteCacheScope::KnowledgeBases => "knowledge_bases",
+            RouteCacheScope::Workflows => "workflows",
+            RouteCacheScope::Version => "version",
+        }
+    }
+}
+
+/// 缓存项的区分维度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RouteCacheVary {
+    /// 按租户区分，同时使缓存项只随该租户的写操作失效
+    pub tenant: bool,
+    /// 按用户区分，用于结果依赖用户权限的接口
+    pub user: bool,
+    /// 按查询参数区分
+    pub query: bool,
+}
+
+/// 计算缓存键所需的请求信息
+#[derive(Debug, Clone, Default)]
+pub struct RouteCacheRequest<'a> {
+    pub path: &'a str,
+    pub query: &'a str,
+    pub tenant_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+}
+
+/// 缓存的响应
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedRouteResponse {
+    pub content_type: Option<String>,
+    /// 原响应的 ETag，命中缓存时原样返回以便客户端继续做条件更新
+    pub etag: Option<String>,
+    pub body: String,
+}
+
+/// 缓存代次的键，作用域按租户区分时每个租户单独维护代次
+pub fn generation_key(scope: RouteCacheScope, tenant_id: Option<Uuid>) -> String {
+    match tenant_id {
+        Some(tenant_id) => format!("gen:{}:{}", scope.as_str(), tenant_id),
+        None => format!("gen:{}", scope.as_str()),
+    }
+}
+
+/// 响应缓存键
+///
+/// 要求按租户或用户区分但请求中缺少对应信息时返回 None，此时不使用缓存，避免跨租户共享响应。
+pub fn response_key(
+    scope: RouteCacheScope,
+    vary: RouteCacheVary,
+    generation: &str,
+    request: &RouteCacheRequest<'_>,
+) -> Option<String> {
+    let mut key = format!("resp:{}:{}:{}", scope.as_str(), generation, request.path);
+    if vary.tenant {
+        key.push_str(&format!("|t={}", request.tenant_id?));
+    }
+    if vary.user {
+        key.push_str(&format!("|u={}", request.user_id?));
+    }
+    if vary.query {
+        let mut pairs: Vec<&str> = request.query.split('&').filter(|pair| !pair.is_empty()).collect();
+        pairs.sort_unstable();
+        key.push_str(&format!("|q={}", pairs.join("&")));
+    }
+    Some(key)
+}
+
+/// 读取作用域当前的缓存代次，不存在时生成新代次
+pub async fn current_generation(cache: &CacheService, scope: RouteCacheScope, tenant_id: Option<Uuid>) -> String {
+    let key = generation_key(scope, tenant_id);
+    if let Some(generation) = cache.get::<String>(CacheNamespace::Route, &key).await {
+        return generation;
+    }
+    let generation = Uuid::new_v4().simple().to_string();
+    cache.put_with_ttl(CacheNamespace::Route, &key, &generation, GENERATION_TTL).await;
+    generation
+}
+
+/// 使作用域内的路由缓存失效，在对应的写接口成功后调用
+///
+/// 传入租户时只影响该租户按租户区分的缓存项，同时使不区分租户的缓存项失效。
+pub async fn invalidate(scope: RouteCacheScope, tenant_id: Option<Uuid>) {
+    let Some(cache) = CacheService::global() else {
+        return;
+    };
+    if let Some(tenant_id) = tenant_id {
+        cache.invalidate(CacheNamespace::Route, &generation_key(scope, Some(tenant_id))).await;
+    }
+    cache.invalidate(CacheNamespace::Route, &generation_key(scope, None)).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::services::cache::InMemoryCacheBackend;
+
+    #[test]
+    fn test_response_key_vary() {
+        let tenant = Uuid::new_v4();
+        let request = RouteCacheRequest { path: "/api/v1/knowledge-bases", query: "page=2&q=hr", tenant_id: Some(tenant), user_id: None };
+        let reordered = RouteCacheRequest { query: "q=hr&page=2", ..request.clone() };
+        let vary = RouteCacheVary { tenant: true, user: false, query: true };
+
+        let key = response_key(RouteCacheScope::KnowledgeBases, vary, "g1", &request).unwrap();
+        assert_eq!(key, response_key(RouteCacheScope::KnowledgeBases, vary, "g1", &reordered).unwrap());
+        assert!(key.contains(&tenant.to_string()));
+
+        // 缺少用户信息时不缓存
+        let by_user = RouteCacheVary { user: true, ..vary };
+        assert!(response_key(RouteCacheScope::KnowledgeBases, by_user, "g1", &request).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_generation_rotates_after_invalidation() {
+        let cache = CacheService::new(
+            crate::config::AppConfig::default().cache,
+            Arc::new(InMemoryCacheBackend::new(100)),
+            "memory",
+        );
+        let tenant = Some(Uuid::new_v4());
+
+        let first = current_generation(&cache, RouteCacheScope::Workflows, tenant).await;
+        assert_eq!(first, current_generation(&cache, RouteCacheScope::Workflows, tenant).await);
+
+        cache.invalidate(CacheNamespace::Route, &generation_key(RouteCacheScope::Workflows, tenant)).await;
+        assert_ne!(first, current_generation(&cache, RouteCacheScope::Workflows, tenant).await);
+    }
+}
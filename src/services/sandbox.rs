@@ -0,0 +1,599 @@
+// 租户沙箱服务
+// 为租户维护一份独立的沙箱租户，复制选定的 Agent、工作流与知识库定义用于测试，
+// 验证后按名称对应关系整体提升回生产环境，复制时重写资源之间的 ID 引用
+
+use std::collections::{BTreeSet, HashMap};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::{agent, knowledge_base, tenant_sandbox, workflow, Agent, KnowledgeBase, TenantSandbox, Workflow};
+use crate::db::repositories::tenant::TenantRepository;
+use crate::db::update_with_revision;
+use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace};
+use crate::services::manifest::{ChangeAction, ResourceChange, ResourceKind};
+
+/// 沙箱租户标识符后缀
+const SANDBOX_SLUG_SUFFIX: &str = "-sandbox";
+
+/// 参与比较与复制的 Agent 字段
+const AGENT_FIELDS: &[&str] = &["description", "agent_type", "version", "config", "system_prompt", "tools", "capabilities"];
+
+/// 参与比较与复制的工作流字段
+const WORKFLOW_FIELDS: &[&str] = &[
+    "description", "workflow_type", "version", "definition", "config", "input_schema", "output_schema",
+];
+
+/// 参与比较与复制的知识库字段，嵌入模型与向量维度只在创建时复制
+const KNOWLEDGE_BASE_FIELDS: &[&str] = &["description", "kb_type", "config"];
+
+/// 按名称选择的资源
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct SandboxSelection {
+    /// Agent 名称
+    #[serde(default)]
+    pub agents: Vec<String>,
+    /// 工作流名称
+    #[serde(default)]
+    pub workflows: Vec<String>,
+    /// 知识库名称
+    #[serde(default)]
+    pub knowledge_bases: Vec<String>,
+}
+
+impl SandboxSelection {
+    /// 是否未选择任何资源
+    pub fn is_empty(&self) -> bool {
+        self.agents.is_empty() && self.workflows.is_empty() && self.knowledge_bases.is_empty()
+    }
+}
+
+/// 沙箱信息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SandboxInfo {
+    /// 生产租户 ID
+    pub tenant_id: Uuid,
+    /// 沙箱租户 ID
+    pub sandbox_tenant_id: Uuid,
+    /// 沙箱租户标识符，通过 `X-Tenant-Slug` 请求头访问沙箱
+    pub sandbox_slug: String,
+    /// 创建人
+    pub created_by: Uuid,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 最近一次提升时间
+    pub last_promoted_at: Option<DateTime<Utc>>,
+    /// 最近一次提升的操作人
+    pub last_promoted_by: Option<Uuid>,
+}
+
+/// 沙箱与生产环境之间的复制结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SandboxSync {
+    /// 沙箱信息
+    pub sandbox: SandboxInfo,
+    /// 是否已写入，仅预览时为 false
+    pub applied: bool,
+    /// 目标环境中各资源的变更，`tenant` 为目标租户标识符
+    pub changes: Vec<ResourceChange>,
+}
+
+/// 租户内的一个可复制资源
+#[derive(Debug, Clone)]
+struct Resource {
+    kind: ResourceKind,
+    id: Uuid,
+    name: String,
+    /// 完整的实体 JSON
+    model: Value,
+}
+
+impl Resource {
+    fn new<M: Serialize>(kind: ResourceKind, id: Uuid, name: &str, model: &M) -> Result<Self, AiStudioError> {
+        Ok(Self {
+            kind,
+            id,
+            name: name.to_string(),
+            model: serde_json::to_value(model)?,
+        })
+    }
+
+    /// 参与比较与复制的字段
+    fn fields(&self) -> &'static [&'static str] {
+        match self.kind {
+            ResourceKind::Agent => AGENT_FIELDS,
+            ResourceKind::Workflow => WORKFLOW_FIELDS,
+            _ => KNOWLEDGE_BASE_FIELDS,
+        }
+    }
+
+    fn field(&self, name: &str) -> &Value {
+        self.model.get(name).unwrap_or(&Value::Null)
+    }
+}
+
+/// 一个待写入目标环境的资源
+#[derive(Debug, Clone)]
+struct PlannedCopy {
+    change: ResourceChange,
+    /// 写入目标环境的完整实体 JSON
+    model: Value,
+    /// 更新时目标资源的当前修订号
+    revision: Option<i32>,
+}
+
+/// 租户沙箱服务
+pub struct SandboxService {
+    db: DatabaseConnection,
+}
+
+impl SandboxService {
+    /// 创建新的沙箱服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 获取租户的沙箱
+    #[instrument(skip(self))]
+    pub async fn get(&self, tenant_id: Uuid) -> Result<SandboxInfo, AiStudioError> {
+        let record = self.find(tenant_id).await?
+            .ok_or_else(|| AiStudioError::not_found("租户沙箱"))?;
+        self.info(record).await
+    }
+
+    /// 将选定资源复制到沙箱，沙箱不存在时先创建
+    ///
+    /// 沙箱中已有的同名资源会被生产环境的定义覆盖，沙箱中的文档等数据保持不变。
+    #[instrument(skip(self, selection))]
+    pub async fn sync_to_sandbox(
+        &self,
+        tenant_id: Uuid,
+        selection: &SandboxSelection,
+        user_id: Uuid,
+    ) -> Result<SandboxSync, AiStudioError> {
+        if selection.is_empty() {
+            return Err(AiStudioError::validation("selection", "请至少选择一个 Agent、工作流或知识库"));
+        }
+
+        let record = match self.find(tenant_id).await? {
+            Some(record) => record,
+            None => self.create_sandbox(tenant_id, user_id).await?,
+        };
+        let sandbox = self.info(record).await?;
+
+        let changes = self
+            .copy(tenant_id, sandbox.sandbox_tenant_id, &sandbox.sandbox_slug, selection, user_id, None)
+            .await?;
+        info!(tenant_id = %tenant_id, sandbox_tenant_id = %sandbox.sandbox_tenant_id, "已同步资源到沙箱");
+
+        Ok(SandboxSync { sandbox, applied: true, changes })
+    }
+
+    /// 预览或执行从沙箱到生产环境的提升
+    ///
+    /// 未选择资源时提升沙箱中的全部资源；选中资源引用的其他资源会一并提升。
+    /// 所有写入在同一事务中完成，任一资源失败时生产环境保持不变。
+    #[instrument(skip(self, selection))]
+    pub async fn promote(
+        &self,
+        tenant_id: Uuid,
+        selection: &SandboxSelection,
+        user_id: Uuid,
+        dry_run: bool,
+    ) -> Result<SandboxSync, AiStudioError> {
+        let record = self.find(tenant_id).await?
+            .ok_or_else(|| AiStudioError::not_found("租户沙箱"))?;
+        let production = TenantRepository::find_by_id(&self.db, tenant_id).await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+        let changes = if dry_run {
+            let source = load_resources(&self.db, record.sandbox_tenant_id).await?;
+            let target = load_resources(&self.db, tenant_id).await?;
+            plan_copy(&source, &target, tenant_id, &production.slug, selection, user_id)?
+                .into_iter()
+                .map(|planned| planned.change)
+                .collect()
+        } else {
+            let changes = self
+                .copy(record.sandbox_tenant_id, tenant_id, &production.slug, selection, user_id, Some(record.id))
+                .await?;
+            info!(tenant_id = %tenant_id, user_id = %user_id, "沙箱已提升到生产环境");
+            changes
+        };
+
+        let record = self.find(tenant_id).await?
+            .ok_or_else(|| AiStudioError::not_found("租户沙箱"))?;
+        Ok(SandboxSync {
+            sandbox: self.info(record).await?,
+            applied: !dry_run,
+            changes,
+        })
+    }
+
+    /// 在一个事务中把源租户的选定资源写入目标租户，提升时同时记录到 `promoted_sandbox` 对应的沙箱
+    async fn copy(
+        &self,
+        source_tenant_id: Uuid,
+        target_tenant_id: Uuid,
+        target_slug: &str,
+        selection: &SandboxSelection,
+        user_id: Uuid,
+        promoted_sandbox: Option<Uuid>,
+    ) -> Result<Vec<ResourceChange>, AiStudioError> {
+        let txn = self.db.begin().await?;
+        let source = load_resources(&txn, source_tenant_id).await?;
+        let target = load_resources(&txn, target_tenant_id).await?;
+        let planned = plan_copy(&source, &target, target_tenant_id, target_slug, selection, user_id)?;
+
+        let mut updated_workflows = Vec::new();
+        for copy in &planned {
+            let create = match copy.change.action {
+                ChangeAction::Create => true,
+                ChangeAction::Update => false,
+                _ => continue,
+            };
+            match copy.change.kind {
+                ResourceKind::Agent => {
+                    let active = agent::ActiveModel::from_json(copy.model.clone())?;
+                    if create {
+                        active.insert(&txn).await?;
+                    } else {
+                        update_with_revision(&txn, active, agent::Column::Revision, copy.revision.unwrap_or_default(), "Agent").await?;
+                    }
+                }
+                ResourceKind::KnowledgeBase => {
+                    let active = knowledge_base::ActiveModel::from_json(copy.model.clone())?;
+                    if create {
+                        active.insert(&txn).await?;
+                    } else {
+                        update_with_revision(&txn, active, knowledge_base::Column::Revision, copy.revision.unwrap_or_default(), "知识库").await?;
+                    }
+                }
+                ResourceKind::Workflow => {
+                    let active = workflow::ActiveModel::from_json(copy.model.clone())?;
+                    let model = if create { active.insert(&txn).await? } else { active.update(&txn).await? };
+                    updated_workflows.push(model.id);
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(record_id) = promoted_sandbox {
+            let mut active: tenant_sandbox::ActiveModel = TenantSandbox::find_by_id(record_id)
+                .one(&txn)
+                .await?
+                .ok_or_else(|| AiStudioError::not_found("租户沙箱"))?
+                .into();
+            active.last_promoted_at = Set(Some(Utc::now().into()));
+            active.last_promoted_by = Set(Some(user_id));
+            active.update(&txn).await?;
+        }
+        txn.commit().await?;
+
+        for id in updated_workflows {
+            cache::invalidate(CacheNamespace::Workflow, id).await;
+        }
+        Ok(planned.into_iter().map(|copy| copy.change).collect())
+    }
+
+    async fn find(&self, tenant_id: Uuid) -> Result<Option<tenant_sandbox::Model>, AiStudioError> {
+        Ok(TenantSandbox::find()
+            .filter(tenant_sandbox::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?)
+    }
+
+    async fn info(&self, record: tenant_sandbox::Model) -> Result<SandboxInfo, AiStudioError> {
+        let sandbox_tenant = TenantRepository::find_by_id(&self.db, record.sandbox_tenant_id).await?
+            .ok_or_else(|| AiStudioError::not_found("沙箱租户"))?;
+        Ok(SandboxInfo {
+            tenant_id: record.tenant_id,
+            sandbox_tenant_id: record.sandbox_tenant_id,
+            sandbox_slug: sandbox_tenant.slug,
+            created_by: record.created_by,
+            created_at: record.created_at.with_timezone(&Utc),
+            last_promoted_at: record.last_promoted_at.map(|at| at.with_timezone(&Utc)),
+            last_promoted_by: record.last_promoted_by,
+        })
+    }
+
+    /// 创建沙箱租户，沿用生产租户的配置与配额
+    async fn create_sandbox(&self, tenant_id: Uuid, user_id: Uuid) -> Result<tenant_sandbox::Model, AiStudioError> {
+        let production = TenantRepository::find_by_id(&self.db, tenant_id).await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+        if let Some(parent) = TenantSandbox::find()
+            .filter(tenant_sandbox::Column::SandboxTenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+        {
+            return Err(AiStudioError::conflict(format!("该租户已是租户 {} 的沙箱", parent.tenant_id)));
+        }
+
+        let mut sandbox = TenantRepository::create(
+            &self.db,
+            format!("{}{}", production.name, SANDBOX_SLUG_SUFFIX),
+            format!("{}{}", production.slug, SANDBOX_SLUG_SUFFIX),
+            format!("{}（沙箱）", production.display_name),
+        )
+        .await?;
+        sandbox.config = production.config.clone();
+        sandbox.quota_limits = production.quota_limits.clone();
+        let sandbox = TenantRepository::update(&self.db, sandbox).await?;
+
+        let record = tenant_sandbox::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            sandbox_tenant_id: Set(sandbox.id),
+            created_by: Set(user_id),
+            created_at: Set(Utc::now().into()),
+            last_promoted_at: Set(None),
+            last_promoted_by: Set(None),
+        }
+        .insert(&self.db)
+        .await?;
+        info!(tenant_id = %tenant_id, sandbox_tenant_id = %sandbox.id, "租户沙箱创建成功");
+        Ok(record)
+    }
+}
+
+/// 加载租户内全部可复制资源
+async fn load_resources<C: ConnectionTrait>(db: &C, tenant_id: Uuid) -> Result<Vec<Resource>, AiStudioError> {
+    let mut resources = Vec::new();
+    for kb in KnowledgeBase::find().filter(knowledge_base::Column::TenantId.eq(tenant_id)).all(db).await? {
+        resources.push(Resource::new(ResourceKind::KnowledgeBase, kb.id, &kb.name, &kb)?);
+    }
+    for agent in Agent::find().filter(agent::Column::TenantId.eq(tenant_id)).all(db).await? {
+        resources.push(Resource::new(ResourceKind::Agent, agent.id, &agent.name, &agent)?);
+    }
+    for wf in Workflow::find().filter(workflow::Column::TenantId.eq(tenant_id)).all(db).await? {
+        resources.push(Resource::new(ResourceKind::Workflow, wf.id, &wf.name, &wf)?);
+    }
+    Ok(resources)
+}
+
+/// 计算把源资源复制到目标租户所需的变更
+///
+/// 源资源按名称与目标资源对应，不存在时预先分配新 ID；选中资源引用的其他源资源会一并复制，
+/// 所有引用按对应关系改写为目标环境中的 ID。
+fn plan_copy(
+    source: &[Resource],
+    target: &[Resource],
+    target_tenant_id: Uuid,
+    target_slug: &str,
+    selection: &SandboxSelection,
+    user_id: Uuid,
+) -> Result<Vec<PlannedCopy>, AiStudioError> {
+    let selected = select_with_references(source, selection)?;
+
+    let by_name: HashMap<(ResourceKind, &str), &Resource> = target
+        .iter()
+        .map(|resource| ((resource.kind, resource.name.as_str()), resource))
+        .collect();
+    let id_map: HashMap<Uuid, Uuid> = selected
+        .iter()
+        .map(|resource| {
+            let target_id = by_name
+                .get(&(resource.kind, resource.name.as_str()))
+                .map(|existing| existing.id)
+                .unwrap_or_else(Uuid::new_v4);
+            (resource.id, target_id)
+        })
+        .collect();
+
+    let now = Value::String(Utc::now().to_rfc3339());
+    let mut planned = Vec::new();
+    for resource in selected {
+        let mut change = ResourceChange {
+            tenant: target_slug.to_string(),
+            kind: resource.kind,
+            name: resource.name.clone(),
+            action: ChangeAction::Create,
+            fields: Vec::new(),
+            note: None,
+        };
+
+        let mut model = match by_name.get(&(resource.kind, resource.name.as_str())) {
+            Some(existing) => {
+                let mut model = existing.model.clone();
+                for field in resource.fields() {
+                    let mut value = resource.field(field).clone();
+                    remap_references(&mut value, &id_map);
+                    if &value != existing.field(field) {
+                        change.fields.push(field.to_string());
+                    }
+                    model[*field] = value;
+                }
+                change.action = if change.fields.is_empty() { ChangeAction::Unchanged } else { ChangeAction::Update };
+                if resource.kind == ResourceKind::KnowledgeBase
+                    && (resource.field("embedding_model") != existing.field("embedding_model")
+                        || resource.field("vector_dimension") != existing.field("vector_dimension"))
+                {
+                    change.note = Some("嵌入模型与向量维度不同，目标知识库保留原设置".to_string());
+                }
+                model
+            }
+            None => new_model(resource, id_map[&resource.id], target_tenant_id, user_id, &now, &id_map)?,
+        };
+        model["updated_at"] = now.clone();
+
+        planned.push(PlannedCopy {
+            revision: by_name
+                .get(&(resource.kind, resource.name.as_str()))
+                .and_then(|existing| existing.field("revision").as_i64())
+                .map(|revision| revision as i32),
+            change,
+            model,
+        });
+    }
+    Ok(planned)
+}
+
+/// 按名称选出源资源，并补全它们直接或间接引用的源资源
+fn select_with_references<'a>(
+    source: &'a [Resource],
+    selection: &SandboxSelection,
+) -> Result<Vec<&'a Resource>, AiStudioError> {
+    let mut selected: BTreeSet<usize> = if selection.is_empty() {
+        (0..source.len()).collect()
+    } else {
+        let mut indexes = BTreeSet::new();
+        for (kind, names) in [
+            (ResourceKind::KnowledgeBase, &selection.knowledge_bases),
+            (ResourceKind::Agent, &selection.agents),
+            (ResourceKind::Workflow, &selection.workflows),
+        ] {
+            for name in names {
+                let index = source
+                    .iter()
+                    .position(|resource| resource.kind == kind && &resource.name == name)
+                    .ok_or_else(|| AiStudioError::not_found(format!("{:?} '{}'", kind, name)))?;
+                indexes.insert(index);
+            }
+        }
+        indexes
+    };
+
+    let index_by_id: HashMap<Uuid, usize> = source.iter().enumerate().map(|(i, resource)| (resource.id, i)).collect();
+    let mut pending: Vec<usize> = selected.iter().copied().collect();
+    while let Some(index) = pending.pop() {
+        let resource = &source[index];
+        let mut referenced = Vec::new();
+        for field in resource.fields() {
+            collect_references(resource.field(field), &mut referenced);
+        }
+        for id in referenced {
+            if let Some(&dependency) = index_by_id.get(&id) {
+                if selected.insert(dependency) {
+                    pending.push(dependency);
+                }
+            }
+        }
+    }
+
+    // 按知识库、Agent、工作流的顺序写入，与加载顺序一致
+    Ok(selected.into_iter().map(|index| &source[index]).collect())
+}
+
+/// 在目标租户中新建资源时的完整实体 JSON，统计与运行状态不随定义复制
+fn new_model(
+    resource: &Resource,
+    id: Uuid,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    now: &Value,
+    id_map: &HashMap<Uuid, Uuid>,
+) -> Result<Value, AiStudioError> {
+    let mut model = resource.model.clone();
+    for field in resource.fields() {
+        remap_references(&mut model[*field], id_map);
+    }
+    model["id"] = Value::String(id.to_string());
+    model["tenant_id"] = Value::String(tenant_id.to_string());
+    model["created_at"] = now.clone();
+
+    match resource.kind {
+        ResourceKind::Agent => {
+            model["execution_stats"] = serde_json::to_value(agent::AgentExecutionStats::default())?;
+            model["last_executed_at"] = Value::Null;
+            model["created_by"] = Value::String(user_id.to_string());
+            model["revision"] = Value::from(1);
+        }
+        ResourceKind::Workflow => {
+            model["execution_stats"] = serde_json::to_value(workflow::WorkflowExecutionStats::default())?;
+            model["last_executed_at"] = Value::Null;
+            model["created_by"] = Value::String(user_id.to_string());
+        }
+        _ => {
+            model["document_count"] = Value::from(0);
+            model["chunk_count"] = Value::from(0);
+            model["total_size_bytes"] = Value::from(0);
+            model["last_indexed_at"] = Value::Null;
+            model["revision"] = Value::from(1);
+        }
+    }
+    Ok(model)
+}
+
+/// 收集 JSON 中所有形如 UUID 的字符串
+fn collect_references(value: &Value, out: &mut Vec<Uuid>) {
+    match value {
+        Value::String(s) => {
+            if let Ok(id) = Uuid::parse_str(s) {
+                out.push(id);
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_references(item, out)),
+        Value::Object(map) => map.values().for_each(|item| collect_references(item, out)),
+        _ => {}
+    }
+}
+
+/// 将 JSON 中引用源资源的 ID 改写为目标环境中的 ID
+fn remap_references(value: &mut Value, id_map: &HashMap<Uuid, Uuid>) {
+    match value {
+        Value::String(s) => {
+            if let Some(mapped) = Uuid::parse_str(s).ok().and_then(|id| id_map.get(&id)) {
+                *s = mapped.to_string();
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| remap_references(item, id_map)),
+        Value::Object(map) => map.values_mut().for_each(|item| remap_references(item, id_map)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resource(kind: ResourceKind, name: &str, model: Value) -> Resource {
+        let id = Uuid::parse_str(model["id"].as_str().unwrap()).unwrap();
+        Resource { kind, id, name: name.to_string(), model }
+    }
+
+    fn agent_model(id: Uuid, system_prompt: &str) -> Value {
+        json!({ "id": id.to_string(), "description": null, "agent_type": "Conversational", "version": "1.0.0",
+                "config": {}, "system_prompt": system_prompt, "tools": [], "capabilities": {}, "revision": 3 })
+    }
+
+    fn workflow_model(id: Uuid, agent_id: Uuid) -> Value {
+        json!({ "id": id.to_string(), "description": null, "workflow_type": "Sequential", "version": "1.0.0",
+                "definition": { "nodes": [{ "id": "n1", "node_type": { "type": "Agent", "agent_id": agent_id.to_string() } }] },
+                "config": {}, "input_schema": {}, "output_schema": {} })
+    }
+
+    #[test]
+    fn test_promotion_remaps_references_and_pulls_dependencies() {
+        let (sandbox_agent, sandbox_workflow, production_agent) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let source = vec![
+            resource(ResourceKind::Agent, "客服助手", agent_model(sandbox_agent, "新提示词")),
+            resource(ResourceKind::Workflow, "工单分流", workflow_model(sandbox_workflow, sandbox_agent)),
+        ];
+        let target = vec![resource(ResourceKind::Agent, "客服助手", agent_model(production_agent, "旧提示词"))];
+        let selection = SandboxSelection { workflows: vec!["工单分流".to_string()], ..Default::default() };
+
+        let planned = plan_copy(&source, &target, Uuid::new_v4(), "acme", &selection, Uuid::new_v4()).unwrap();
+        let agent = &planned[0];
+        assert_eq!(agent.change.action, ChangeAction::Update);
+        assert_eq!(agent.change.fields, vec!["system_prompt".to_string()]);
+        assert_eq!(agent.model["id"], production_agent.to_string());
+        assert_eq!(agent.revision, Some(3));
+
+        let workflow = &planned[1];
+        assert_eq!(workflow.change.action, ChangeAction::Create);
+        assert_ne!(workflow.model["id"], sandbox_workflow.to_string());
+        assert_eq!(workflow.model["definition"]["nodes"][0]["node_type"]["agent_id"], production_agent.to_string());
+
+        let unknown = SandboxSelection { agents: vec!["不存在".to_string()], ..Default::default() };
+        assert!(plan_copy(&source, &target, Uuid::new_v4(), "acme", &unknown, Uuid::new_v4()).is_err());
+    }
+}
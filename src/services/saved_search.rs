@@ -0,0 +1,594 @@
+// 保存的搜索服务
+// 管理用户对知识库保存的搜索条件，并在新文档匹配订阅时通过通知服务提醒用户
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, Set, Statement,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::{debug, info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::embedding_pool::{EmbeddingPool, EmbeddingPriority};
+use crate::ai::rig_client::RigAiClient;
+use crate::db::entities::document::{self, DocumentMetadata};
+use crate::db::entities::saved_search::{self, SavedSearchFilters, SearchMatchMode};
+use crate::db::entities::{knowledge_base, user, Document, KnowledgeBase, SavedSearch, User};
+use crate::errors::AiStudioError;
+use crate::plugins::event_bus::{event_types, SystemEvent, SystemEventBus};
+use crate::services::notification::{MatchedDocument, NotificationService};
+use crate::services::tenant_encryption::TenantEncryptionService;
+
+/// 默认语义相似度阈值
+pub const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.8;
+
+/// 参与语义匹配的文档内容最大长度（字符）
+const MAX_EMBED_CHARS: usize = 4000;
+
+/// 每个用户最多保存的搜索数
+const MAX_SEARCHES_PER_USER: usize = 100;
+
+/// 创建保存的搜索请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateSavedSearchRequest {
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 搜索名称
+    pub name: String,
+    /// 查询内容
+    pub query: String,
+    /// 匹配方式，默认关键词匹配
+    #[serde(default = "default_match_mode")]
+    pub match_mode: SearchMatchMode,
+    /// 语义匹配的相似度阈值（0-1），默认 0.8
+    pub similarity_threshold: Option<f32>,
+    /// 过滤条件
+    #[serde(default)]
+    pub filters: SavedSearchFilters,
+    /// 是否订阅新文档告警，默认开启
+    #[serde(default = "default_alerts_enabled")]
+    pub alerts_enabled: bool,
+}
+
+fn default_match_mode() -> SearchMatchMode { SearchMatchMode::Keyword }
+fn default_alerts_enabled() -> bool { true }
+
+/// 更新保存的搜索请求
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateSavedSearchRequest {
+    /// 搜索名称
+    pub name: Option<String>,
+    /// 查询内容
+    pub query: Option<String>,
+    /// 语义匹配的相似度阈值
+    pub similarity_threshold: Option<f32>,
+    /// 过滤条件
+    pub filters: Option<SavedSearchFilters>,
+    /// 是否订阅新文档告警
+    pub alerts_enabled: Option<bool>,
+}
+
+/// 保存的搜索响应
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SavedSearchResponse {
+    /// 搜索 ID
+    pub id: Uuid,
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 搜索名称
+    pub name: String,
+    /// 查询内容
+    pub query: String,
+    /// 匹配方式
+    pub match_mode: SearchMatchMode,
+    /// 语义匹配的相似度阈值
+    pub similarity_threshold: Option<f32>,
+    /// 过滤条件
+    pub filters: SavedSearchFilters,
+    /// 是否订阅新文档告警
+    pub alerts_enabled: bool,
+    /// 最近一次告警时间
+    pub last_alerted_at: Option<DateTime<Utc>>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<saved_search::Model> for SavedSearchResponse {
+    fn from(model: saved_search::Model) -> Self {
+        Self {
+            filters: model.get_filters(),
+            id: model.id,
+            knowledge_base_id: model.knowledge_base_id,
+            name: model.name,
+            query: model.query,
+            match_mode: model.match_mode,
+            similarity_threshold: model.similarity_threshold,
+            alerts_enabled: model.alerts_enabled,
+            last_alerted_at: model.last_alerted_at.map(|t| t.with_timezone(&Utc)),
+            created_at: model.created_at.with_timezone(&Utc),
+            updated_at: model.updated_at.with_timezone(&Utc),
+        }
+    }
+}
+
+/// 保存的搜索服务
+pub struct SavedSearchService {
+    db: DatabaseConnection,
+    embedder: Option<Arc<RigAiClient>>,
+    notifications: Arc<NotificationService>,
+}
+
+impl SavedSearchService {
+    /// 创建新的保存的搜索服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            embedder: None,
+            notifications: Arc::new(NotificationService::new()),
+        }
+    }
+
+    /// 设置未启用本地嵌入工作池时使用的嵌入客户端
+    pub fn with_embedder(mut self, embedder: Arc<RigAiClient>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// 创建保存的搜索
+    #[instrument(skip(self, request))]
+    pub async fn create_search(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: CreateSavedSearchRequest,
+    ) -> Result<SavedSearchResponse, AiStudioError> {
+        let name = validate_name(&request.name)?;
+        let query = validate_query(&request.query)?;
+        let threshold = resolve_threshold(request.match_mode, request.similarity_threshold)?;
+
+        KnowledgeBase::find_by_id(request.knowledge_base_id)
+            .filter(knowledge_base::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("知识库"))?;
+
+        let existing = SavedSearch::find()
+            .filter(saved_search::Column::TenantId.eq(tenant_id))
+            .filter(saved_search::Column::UserId.eq(user_id))
+            .all(&self.db)
+            .await?;
+        if existing.len() >= MAX_SEARCHES_PER_USER {
+            return Err(AiStudioError::validation(
+                "saved_searches",
+                format!("每个用户最多保存 {} 个搜索", MAX_SEARCHES_PER_USER),
+            ));
+        }
+
+        let now = Utc::now().fixed_offset();
+        let search = saved_search::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            user_id: Set(user_id),
+            knowledge_base_id: Set(request.knowledge_base_id),
+            name: Set(name),
+            query: Set(query),
+            match_mode: Set(request.match_mode),
+            similarity_threshold: Set(threshold),
+            filters: Set(serde_json::to_value(&request.filters)?),
+            alerts_enabled: Set(request.alerts_enabled),
+            last_alerted_at: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(&self.db)
+        .await?;
+
+        if search.match_mode == SearchMatchMode::Semantic {
+            self.refresh_query_embedding(&search).await;
+        }
+        info!("保存的搜索创建成功: id={}, user_id={}", search.id, user_id);
+        Ok(search.into())
+    }
+
+    /// 列出用户保存的搜索
+    pub async fn list_searches(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        knowledge_base_id: Option<Uuid>,
+    ) -> Result<Vec<SavedSearchResponse>, AiStudioError> {
+        let mut select = SavedSearch::find()
+            .filter(saved_search::Column::TenantId.eq(tenant_id))
+            .filter(saved_search::Column::UserId.eq(user_id));
+        if let Some(kb_id) = knowledge_base_id {
+            select = select.filter(saved_search::Column::KnowledgeBaseId.eq(kb_id));
+        }
+
+        let searches = select
+            .order_by_desc(saved_search::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+        Ok(searches.into_iter().map(SavedSearchResponse::from).collect())
+    }
+
+    /// 获取保存的搜索
+    pub async fn get_search(&self, tenant_id: Uuid, user_id: Uuid, id: Uuid) -> Result<SavedSearchResponse, AiStudioError> {
+        Ok(self.find_search(tenant_id, user_id, id).await?.into())
+    }
+
+    /// 更新保存的搜索，查询变化时重新生成查询向量
+    #[instrument(skip(self, request))]
+    pub async fn update_search(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        id: Uuid,
+        request: UpdateSavedSearchRequest,
+    ) -> Result<SavedSearchResponse, AiStudioError> {
+        let existing = self.find_search(tenant_id, user_id, id).await?;
+        let query_changed = request.query.as_ref().is_some_and(|q| q.trim() != existing.query);
+        let match_mode = existing.match_mode;
+        let mut active: saved_search::ActiveModel = existing.into();
+
+        if let Some(name) = request.name {
+            active.name = Set(validate_name(&name)?);
+        }
+        if let Some(query) = request.query {
+            active.query = Set(validate_query(&query)?);
+        }
+        if request.similarity_threshold.is_some() {
+            active.similarity_threshold = Set(resolve_threshold(match_mode, request.similarity_threshold)?);
+        }
+        if let Some(filters) = request.filters {
+            active.filters = Set(serde_json::to_value(&filters)?);
+        }
+        if let Some(alerts_enabled) = request.alerts_enabled {
+            active.alerts_enabled = Set(alerts_enabled);
+        }
+        active.updated_at = Set(Utc::now().fixed_offset());
+
+        let search = active.update(&self.db).await?;
+        if query_changed && search.match_mode == SearchMatchMode::Semantic {
+            self.refresh_query_embedding(&search).await;
+        }
+        Ok(search.into())
+    }
+
+    /// 删除保存的搜索
+    pub async fn delete_search(&self, tenant_id: Uuid, user_id: Uuid, id: Uuid) -> Result<(), AiStudioError> {
+        let result = SavedSearch::delete_many()
+            .filter(saved_search::Column::TenantId.eq(tenant_id))
+            .filter(saved_search::Column::UserId.eq(user_id))
+            .filter(saved_search::Column::Id.eq(id))
+            .exec(&self.db)
+            .await?;
+
+        if result.rows_affected == 0 {
+            return Err(AiStudioError::not_found("保存的搜索"));
+        }
+        Ok(())
+    }
+
+    /// 找出订阅了告警且与文档匹配的搜索
+    pub async fn find_matching_searches(
+        &self,
+        tenant_id: Uuid,
+        doc: &document::Model,
+    ) -> Result<Vec<saved_search::Model>, AiStudioError> {
+        let searches = SavedSearch::find()
+            .filter(saved_search::Column::TenantId.eq(tenant_id))
+            .filter(saved_search::Column::KnowledgeBaseId.eq(doc.knowledge_base_id))
+            .filter(saved_search::Column::AlertsEnabled.eq(true))
+            .all(&self.db)
+            .await?;
+        if searches.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let metadata = doc.get_metadata().unwrap_or_default();
+        let candidates: Vec<saved_search::Model> = searches.into_iter()
+            .filter(|search| filters_match(&search.get_filters(), &doc.doc_type, &metadata))
+            .collect();
+
+        let mut matched = Vec::new();
+        let mut semantic_ids = HashSet::new();
+        for search in candidates {
+            match search.match_mode {
+                SearchMatchMode::Keyword if keyword_matches(&search.query, &doc.title, &doc.content) => {
+                    matched.push(search);
+                }
+                SearchMatchMode::Keyword => {}
+                SearchMatchMode::Semantic => {
+                    semantic_ids.insert(search.id);
+                    matched.push(search);
+                }
+            }
+        }
+
+        if !semantic_ids.is_empty() {
+            let hits = self.semantic_hits(doc).await?;
+            matched.retain(|search| !semantic_ids.contains(&search.id) || hits.contains(&search.id));
+        }
+        Ok(matched)
+    }
+
+    /// 处理新文档：匹配订阅并通知搜索所属用户
+    #[instrument(skip(self, doc), fields(document_id = %doc.id))]
+    pub async fn alert_new_document(&self, tenant_id: Uuid, doc: &document::Model) -> Result<usize, AiStudioError> {
+        let matches = self.find_matching_searches(tenant_id, doc).await?;
+        if matches.is_empty() {
+            return Ok(0);
+        }
+
+        let documents = vec![MatchedDocument {
+            document_id: doc.id,
+            title: doc.title.clone(),
+            link: document_link(doc.id),
+        }];
+
+        let mut sent = 0;
+        for search in &matches {
+            let Some(owner) = User::find_by_id(search.user_id)
+                .filter(user::Column::TenantId.eq(tenant_id))
+                .one(&self.db)
+                .await?
+            else {
+                continue;
+            };
+
+            match self.notifications
+                .send_saved_search_alert(tenant_id, &owner.email, &search.name, &documents)
+                .await
+            {
+                Ok(_) => {
+                    sent += 1;
+                    let mut active: saved_search::ActiveModel = search.clone().into();
+                    active.last_alerted_at = Set(Some(Utc::now().fixed_offset()));
+                    active.update(&self.db).await?;
+                }
+                Err(e) => warn!("保存的搜索告警发送失败: search_id={}, error={}", search.id, e),
+            }
+        }
+
+        info!("新文档匹配保存的搜索: document_id={}, 通知 {} 个订阅", doc.id, sent);
+        Ok(sent)
+    }
+
+    /// 启动告警监听，订阅文档创建事件并发送匹配通知
+    pub fn start_alert_listener(db: DatabaseConnection) {
+        let service = Arc::new(Self::new(db));
+        let mut receiver = SystemEventBus::global().subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if event.event_type == event_types::DOCUMENT_CREATED => {
+                        let service = service.clone();
+                        tokio::spawn(async move { service.handle_document_event(event).await });
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("保存的搜索告警监听滞后，丢弃 {} 个事件", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    async fn handle_document_event(&self, event: SystemEvent) {
+        let Some(tenant_id) = event.tenant_id else {
+            return;
+        };
+        let Some(document_id) = event.payload.get("document_id")
+            .and_then(|v| v.as_str())
+            .and_then(|v| Uuid::parse_str(v).ok())
+        else {
+            return;
+        };
+
+        let mut doc = match Document::find_by_id(document_id).one(&self.db).await {
+            Ok(Some(doc)) => doc,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("加载新文档失败: document_id={}, error={}", document_id, e);
+                return;
+            }
+        };
+        let decrypted = match TenantEncryptionService::from_config(self.db.clone()) {
+            Ok(encryption) => encryption.decrypt_document(&mut doc).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = decrypted {
+            warn!("解密新文档失败: document_id={}, error={}", document_id, e);
+            return;
+        }
+        if let Err(e) = self.alert_new_document(tenant_id, &doc).await {
+            warn!("处理保存的搜索告警失败: document_id={}, error={}", document_id, e);
+        }
+    }
+
+    async fn find_search(&self, tenant_id: Uuid, user_id: Uuid, id: Uuid) -> Result<saved_search::Model, AiStudioError> {
+        SavedSearch::find_by_id(id)
+            .filter(saved_search::Column::TenantId.eq(tenant_id))
+            .filter(saved_search::Column::UserId.eq(user_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("保存的搜索"))
+    }
+
+    /// 返回与文档语义相似度达到阈值的搜索 ID，文档无法生成向量时返回空集
+    async fn semantic_hits(&self, doc: &document::Model) -> Result<HashSet<Uuid>, AiStudioError> {
+        let text: String = format!("{}\n{}", doc.title, doc.content).chars().take(MAX_EMBED_CHARS).collect();
+        let Some(vector) = self.embed(&text, EmbeddingPriority::Bulk).await else {
+            debug!("文档向量不可用，跳过语义匹配: document_id={}", doc.id);
+            return Ok(HashSet::new());
+        };
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT id
+                FROM saved_searches
+                WHERE knowledge_base_id = $1
+                    AND alerts_enabled
+                    AND match_mode = 'semantic'
+                    AND query_embedding IS NOT NULL
+                    AND 1 - (query_embedding <=> $2::vector) >= COALESCE(similarity_threshold, $3)
+                "#,
+                vec![doc.knowledge_base_id.into(), format_vector(&vector).into(), DEFAULT_SIMILARITY_THRESHOLD.into()],
+            ))
+            .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<Uuid, AiStudioError> { Ok(row.try_get("", "id")?) })
+            .collect()
+    }
+
+    /// 重新生成查询向量，失败时清空向量，该搜索暂不参与语义匹配
+    async fn refresh_query_embedding(&self, search: &saved_search::Model) {
+        let vector = self.embed(&search.query, EmbeddingPriority::Bulk).await.map(|v| format_vector(&v));
+        if vector.is_none() {
+            warn!("保存的搜索查询向量不可用，语义告警暂不生效: id={}", search.id);
+        }
+
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "UPDATE saved_searches SET query_embedding = $1::vector WHERE id = $2",
+                vec![vector.into(), search.id.into()],
+            ))
+            .await;
+        if let Err(e) = result {
+            warn!("保存查询向量失败: id={}, error={}", search.id, e);
+        }
+    }
+
+    async fn embed(&self, text: &str, priority: EmbeddingPriority) -> Option<Vec<f32>> {
+        let result = match (EmbeddingPool::global(), &self.embedder) {
+            (Some(pool), _) => pool.embed(text, priority).await,
+            (None, Some(embedder)) => embedder.generate_embedding(text).await.map(|r| r.embedding),
+            (None, None) => return None,
+        };
+
+        result.map_err(|e| warn!("生成向量失败: {}", e)).ok()
+    }
+}
+
+/// 关键词匹配：查询中的每个词都出现在标题或内容中（不区分大小写）
+pub fn keyword_matches(query: &str, title: &str, content: &str) -> bool {
+    let title = title.to_lowercase();
+    let content = content.to_lowercase();
+    let mut terms = query.split_whitespace().peekable();
+    if terms.peek().is_none() {
+        return false;
+    }
+
+    terms.all(|term| {
+        let term = term.to_lowercase();
+        title.contains(&term) || content.contains(&term)
+    })
+}
+
+/// 判断文档是否满足过滤条件
+pub fn filters_match(filters: &SavedSearchFilters, doc_type: &document::DocumentType, metadata: &DocumentMetadata) -> bool {
+    if !filters.doc_types.is_empty() && !filters.doc_types.contains(doc_type) {
+        return false;
+    }
+    if !filters.tags.iter().all(|tag| metadata.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))) {
+        return false;
+    }
+    match &filters.author {
+        Some(author) => metadata.author.as_deref().is_some_and(|a| a.eq_ignore_ascii_case(author)),
+        None => true,
+    }
+}
+
+/// 文档详情链接
+fn document_link(document_id: Uuid) -> String {
+    format!("/api/v1/documents/{}", document_id)
+}
+
+fn validate_name(name: &str) -> Result<String, AiStudioError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > 255 {
+        return Err(AiStudioError::validation("name", "搜索名称不能为空且不能超过 255 个字符"));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_query(query: &str) -> Result<String, AiStudioError> {
+    let query = query.trim();
+    if query.is_empty() || query.chars().count() > 2000 {
+        return Err(AiStudioError::validation("query", "查询内容不能为空且不能超过 2000 个字符"));
+    }
+    Ok(query.to_string())
+}
+
+/// 校验相似度阈值；关键词匹配不使用阈值
+fn resolve_threshold(mode: SearchMatchMode, threshold: Option<f32>) -> Result<Option<f32>, AiStudioError> {
+    match (mode, threshold) {
+        (SearchMatchMode::Keyword, _) => Ok(None),
+        (SearchMatchMode::Semantic, Some(t)) if !(t > 0.0 && t <= 1.0) => {
+            Err(AiStudioError::validation("similarity_threshold", "相似度阈值必须在 (0, 1] 之间"))
+        }
+        (SearchMatchMode::Semantic, t) => Ok(Some(t.unwrap_or(DEFAULT_SIMILARITY_THRESHOLD))),
+    }
+}
+
+fn format_vector(vector: &[f32]) -> String {
+    format!("[{}]", vector.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::entities::document::DocumentType;
+
+    #[test]
+    fn test_keyword_matches() {
+        assert!(keyword_matches("季度 报表", "2024 第三季度报表", ""));
+        assert!(keyword_matches("Rust async", "笔记", "Notes on RUST and Async IO"));
+        assert!(!keyword_matches("rust python", "Rust 入门", "只讲 Rust"));
+        assert!(!keyword_matches("   ", "任意标题", "任意内容"));
+    }
+
+    #[test]
+    fn test_filters_match() {
+        let metadata = DocumentMetadata {
+            author: Some("Alice".to_string()),
+            tags: vec!["Finance".to_string(), "2024".to_string()],
+            ..Default::default()
+        };
+
+        assert!(filters_match(&SavedSearchFilters::default(), &DocumentType::Text, &metadata));
+
+        let filters = SavedSearchFilters {
+            doc_types: vec![DocumentType::Text],
+            tags: vec!["finance".to_string()],
+            author: Some("alice".to_string()),
+        };
+        assert!(filters_match(&filters, &DocumentType::Text, &metadata));
+
+        let wrong_type = SavedSearchFilters { doc_types: vec![DocumentType::Pdf], ..filters.clone() };
+        assert!(!filters_match(&wrong_type, &DocumentType::Text, &metadata));
+
+        let missing_tag = SavedSearchFilters { tags: vec!["legal".to_string()], ..filters };
+        assert!(!filters_match(&missing_tag, &DocumentType::Text, &metadata));
+    }
+
+    #[test]
+    fn test_resolve_threshold() {
+        assert_eq!(resolve_threshold(SearchMatchMode::Keyword, Some(0.5)).unwrap(), None);
+        assert_eq!(resolve_threshold(SearchMatchMode::Semantic, None).unwrap(), Some(DEFAULT_SIMILARITY_THRESHOLD));
+        assert!(resolve_threshold(SearchMatchMode::Semantic, Some(1.5)).is_err());
+    }
+}
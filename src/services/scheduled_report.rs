@@ -0,0 +1,1162 @@
+// 定时报告服务
+// 按计划汇总用量、问答分析或工作流输出，渲染为 HTML/PDF 报告，通过邮件或 Slack 投递下载链接
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult, Statement};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{error, info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::workflow_document::{
+    document_download_url, generated_document_path, parse_markdown, render_html, render_pdf,
+    sanitize_file_name, sign_document_token, DocumentDownloadClaims, DOCUMENT_DOWNLOAD_TTL_DAYS,
+};
+use crate::ai::workflow_engine::GeneratedDocumentFormat;
+use crate::config::AppConfig;
+use crate::db::entities::usage_anomaly::AnomalyMetric;
+use crate::errors::AiStudioError;
+use crate::services::egress::TenantClients;
+use crate::services::notification::NotificationService;
+use crate::services::plugin_config::SecretCipher;
+use crate::services::scheduler::PeriodicJob;
+
+/// 到期报告检查间隔（秒）
+const DUE_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// 每次检查最多执行的报告数
+const MAX_DUE_REPORTS: u64 = 50;
+
+/// 每个租户最多创建的定时报告数
+const MAX_REPORTS_PER_TENANT: i64 = 100;
+
+/// 单个报告最多的投递目标数
+const MAX_DELIVERIES: usize = 20;
+
+/// Slack 投递超时
+const SLACK_TIMEOUT_SECS: u64 = 10;
+
+/// 问答分析中置信度低于该值的回答计为低置信度
+const LOW_CONFIDENCE_THRESHOLD: f64 = 0.5;
+
+/// 问答分析中列出的高频问题数
+const TOP_QUESTIONS: i64 = 10;
+
+/// 报告数据来源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReportSource {
+    /// 租户用量：token 用量、登录失败、文档删除与问答次数，含按天明细
+    Usage,
+    /// 问答分析：提问量、平均置信度、低置信度与无来源回答、高频问题
+    QaAnalytics {
+        /// 限定知识库，默认统计全部知识库
+        #[serde(default)]
+        knowledge_base_id: Option<Uuid>,
+    },
+    /// 工作流输出：统计区间内该工作流最近一次成功执行的输出
+    WorkflowOutput {
+        /// 工作流 ID
+        workflow_id: Uuid,
+    },
+}
+
+/// 报告频率
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFrequency {
+    /// 每天
+    Daily,
+    /// 每周
+    Weekly,
+    /// 每月
+    Monthly,
+}
+
+/// 报告计划，时间均为 UTC
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ReportSchedule {
+    /// 频率
+    pub frequency: ReportFrequency,
+    /// 执行的整点（0-23）
+    #[serde(default)]
+    pub hour: u32,
+    /// 每周执行的星期（1 为周一，7 为周日），默认周一
+    #[serde(default)]
+    pub weekday: Option<u32>,
+    /// 每月执行的日期（1-28），默认 1 日
+    #[serde(default)]
+    pub day_of_month: Option<u32>,
+}
+
+impl ReportSchedule {
+    fn validate(&self) -> Result<(), AiStudioError> {
+        if self.hour > 23 {
+            return Err(AiStudioError::validation("schedule.hour", "执行时间必须在 0-23 之间"));
+        }
+        if self.weekday.is_some_and(|day| !(1..=7).contains(&day)) {
+            return Err(AiStudioError::validation("schedule.weekday", "星期必须在 1-7 之间"));
+        }
+        if self.day_of_month.is_some_and(|day| !(1..=28).contains(&day)) {
+            return Err(AiStudioError::validation("schedule.day_of_month", "日期必须在 1-28 之间"));
+        }
+        Ok(())
+    }
+
+    fn matches(&self, date: NaiveDate) -> bool {
+        match self.frequency {
+            ReportFrequency::Daily => true,
+            ReportFrequency::Weekly => date.weekday().number_from_monday() == self.weekday.unwrap_or(1),
+            ReportFrequency::Monthly => date.day() == self.day_of_month.unwrap_or(1),
+        }
+    }
+
+    /// 严格晚于 `after` 的下一次执行时间
+    pub fn next_run_after(&self, after: DateTime<Utc>) -> DateTime<Utc> {
+        let mut date = after.date_naive();
+        loop {
+            if let Some(candidate) = date.and_hms_opt(self.hour.min(23), 0, 0).map(|t| t.and_utc()) {
+                if candidate > after && self.matches(date) {
+                    return candidate;
+                }
+            }
+            date = date.succ_opt().unwrap_or(date);
+        }
+    }
+
+    /// 以 `end` 结束的统计区间起点
+    pub fn period_start(&self, end: DateTime<Utc>) -> DateTime<Utc> {
+        match self.frequency {
+            ReportFrequency::Daily => end - chrono::Duration::days(1),
+            ReportFrequency::Weekly => end - chrono::Duration::days(7),
+            ReportFrequency::Monthly => end.checked_sub_months(Months::new(1)).unwrap_or(end - chrono::Duration::days(30)),
+        }
+    }
+}
+
+/// 报告格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    /// HTML 页面
+    #[default]
+    Html,
+    /// PDF 文档
+    Pdf,
+}
+
+impl ReportFormat {
+    fn document_format(&self) -> GeneratedDocumentFormat {
+        match self {
+            ReportFormat::Html => GeneratedDocumentFormat::Html,
+            ReportFormat::Pdf => GeneratedDocumentFormat::Pdf,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReportFormat::Html => "html",
+            ReportFormat::Pdf => "pdf",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "pdf" => ReportFormat::Pdf,
+            _ => ReportFormat::Html,
+        }
+    }
+}
+
+/// 投递渠道
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "channel", rename_all = "snake_case")]
+pub enum ReportDelivery {
+    /// 邮件，发送下载链接
+    Email {
+        /// 收件人
+        recipients: Vec<String>,
+    },
+    /// Slack Incoming Webhook，发送报告摘要与下载链接；地址加密存储，查询时脱敏
+    Slack {
+        /// Webhook 地址
+        webhook_url: String,
+    },
+}
+
+/// 创建定时报告请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateScheduledReportRequest {
+    /// 报告名称
+    pub name: String,
+    /// 数据来源
+    pub source: ReportSource,
+    /// 计划
+    pub schedule: ReportSchedule,
+    /// 格式，默认 HTML
+    #[serde(default)]
+    pub format: ReportFormat,
+    /// 投递渠道
+    pub deliveries: Vec<ReportDelivery>,
+    /// 是否启用，默认启用
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 更新定时报告请求，未提供的字段保持不变
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateScheduledReportRequest {
+    /// 报告名称
+    pub name: Option<String>,
+    /// 数据来源
+    pub source: Option<ReportSource>,
+    /// 计划
+    pub schedule: Option<ReportSchedule>,
+    /// 格式
+    pub format: Option<ReportFormat>,
+    /// 投递渠道，整体替换
+    pub deliveries: Option<Vec<ReportDelivery>>,
+    /// 是否启用
+    pub enabled: Option<bool>,
+}
+
+/// 定时报告
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ScheduledReportResponse {
+    /// 报告 ID
+    pub id: Uuid,
+    /// 报告名称
+    pub name: String,
+    /// 数据来源
+    pub source: ReportSource,
+    /// 计划
+    pub schedule: ReportSchedule,
+    /// 格式
+    pub format: ReportFormat,
+    /// 投递渠道（Slack 地址已脱敏）
+    pub deliveries: Vec<ReportDelivery>,
+    /// 是否启用
+    pub enabled: bool,
+    /// 下次执行时间
+    pub next_run_at: DateTime<Utc>,
+    /// 最近一次执行时间
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// 创建人
+    pub created_by: Uuid,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 单个投递目标的结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeliveryResult {
+    /// 渠道
+    pub channel: String,
+    /// 投递目标（Slack 地址已脱敏）
+    pub target: String,
+    /// 是否成功
+    pub success: bool,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+/// 报告执行记录
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReportRunResponse {
+    /// 执行 ID
+    pub id: Uuid,
+    /// 报告 ID
+    pub report_id: Uuid,
+    /// 触发方式：scheduled 或 manual
+    pub trigger: String,
+    /// 统计区间起点
+    pub period_start: DateTime<Utc>,
+    /// 统计区间终点
+    pub period_end: DateTime<Utc>,
+    /// 状态：completed 或 failed
+    pub status: String,
+    /// 报告下载链接
+    pub download_url: Option<String>,
+    /// 各投递目标的结果
+    pub deliveries: Vec<DeliveryResult>,
+    /// 失败原因
+    pub error: Option<String>,
+    /// 开始时间
+    pub started_at: DateTime<Utc>,
+    /// 完成时间
+    pub completed_at: DateTime<Utc>,
+}
+
+/// 生成的报告
+struct RenderedReport {
+    summary: String,
+    download_url: String,
+}
+
+/// 定时报告服务
+pub struct ScheduledReportService {
+    db: DatabaseConnection,
+    storage_root: PathBuf,
+    signing_secret: String,
+    cipher: SecretCipher,
+    http: TenantClients,
+    notifications: NotificationService,
+}
+
+impl ScheduledReportService {
+    /// 创建服务，报告文件写入 `storage_root/exports/workflows/<租户 ID>/`，与工作流生成文档共用下载链接
+    pub fn new(db: DatabaseConnection, storage_root: impl Into<PathBuf>, signing_secret: String, encryption_key: &str) -> Self {
+        Self {
+            db,
+            storage_root: storage_root.into(),
+            signing_secret,
+            cipher: SecretCipher::new(encryption_key),
+            http: TenantClients::new(|builder| builder.timeout(Duration::from_secs(SLACK_TIMEOUT_SECS))),
+            notifications: NotificationService::new(),
+        }
+    }
+
+    /// 按应用配置创建
+    pub fn from_app_config(db: DatabaseConnection, config: &AppConfig) -> Self {
+        Self::new(db, &config.storage.path, config.security.jwt_secret.clone(), &config.security.encryption_key)
+    }
+
+    /// 创建定时报告
+    #[instrument(skip(self, request))]
+    pub async fn create_report(
+        &self,
+        tenant_id: Uuid,
+        request: CreateScheduledReportRequest,
+        created_by: Uuid,
+    ) -> Result<ScheduledReportResponse, AiStudioError> {
+        let name = validate_name(&request.name)?;
+        request.schedule.validate()?;
+        validate_deliveries(&request.deliveries)?;
+        self.validate_source(tenant_id, &request.source).await?;
+
+        let count: i64 = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT COUNT(*) AS count FROM scheduled_reports WHERE tenant_id = $1",
+                [tenant_id.into()],
+            ))
+            .await?
+            .map(|row| row.try_get("", "count"))
+            .transpose()?
+            .unwrap_or(0);
+        if count >= MAX_REPORTS_PER_TENANT {
+            return Err(AiStudioError::conflict(format!("每个租户最多创建 {} 个定时报告", MAX_REPORTS_PER_TENANT)));
+        }
+
+        let now = Utc::now();
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO scheduled_reports
+                    (id, tenant_id, name, source, schedule, format, deliveries, enabled, next_run_at, created_by, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $11)
+                RETURNING *
+                "#,
+                [
+                    Uuid::new_v4().into(),
+                    tenant_id.into(),
+                    name.into(),
+                    serde_json::to_value(&request.source)?.into(),
+                    serde_json::to_value(&request.schedule)?.into(),
+                    request.format.as_str().into(),
+                    self.seal_deliveries(&request.deliveries)?.into(),
+                    request.enabled.into(),
+                    request.schedule.next_run_after(now).into(),
+                    created_by.into(),
+                    now.into(),
+                ],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::internal("创建定时报告失败"))?;
+
+        let report = report_from_row(&row)?;
+        info!(tenant_id = %tenant_id, report_id = %report.id, next_run_at = %report.next_run_at, "定时报告已创建");
+        Ok(report)
+    }
+
+    /// 列出租户的定时报告
+    pub async fn list_reports(&self, tenant_id: Uuid) -> Result<Vec<ScheduledReportResponse>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT * FROM scheduled_reports WHERE tenant_id = $1 ORDER BY created_at DESC",
+                [tenant_id.into()],
+            ))
+            .await?;
+        rows.iter().map(report_from_row).collect()
+    }
+
+    /// 获取定时报告
+    pub async fn get_report(&self, tenant_id: Uuid, report_id: Uuid) -> Result<ScheduledReportResponse, AiStudioError> {
+        report_from_row(&self.find_row(tenant_id, report_id).await?)
+    }
+
+    /// 更新定时报告，修改计划后重新计算下次执行时间
+    #[instrument(skip(self, request))]
+    pub async fn update_report(
+        &self,
+        tenant_id: Uuid,
+        report_id: Uuid,
+        request: UpdateScheduledReportRequest,
+    ) -> Result<ScheduledReportResponse, AiStudioError> {
+        let row = self.find_row(tenant_id, report_id).await?;
+        let current = report_from_row(&row)?;
+
+        let name = match &request.name {
+            Some(name) => validate_name(name)?,
+            None => current.name,
+        };
+        if let Some(source) = &request.source {
+            self.validate_source(tenant_id, source).await?;
+        }
+        let schedule_changed = request.schedule.as_ref().is_some_and(|schedule| *schedule != current.schedule);
+        let schedule = request.schedule.unwrap_or(current.schedule);
+        schedule.validate()?;
+        let deliveries: Value = match &request.deliveries {
+            Some(deliveries) => {
+                validate_deliveries(deliveries)?;
+                self.seal_deliveries(deliveries)?
+            }
+            None => row.try_get("", "deliveries")?,
+        };
+        let next_run_at = if schedule_changed {
+            schedule.next_run_after(Utc::now())
+        } else {
+            current.next_run_at
+        };
+
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                UPDATE scheduled_reports
+                SET name = $3, source = $4, schedule = $5, format = $6, deliveries = $7,
+                    enabled = $8, next_run_at = $9, updated_at = $10
+                WHERE id = $1 AND tenant_id = $2
+                RETURNING *
+                "#,
+                [
+                    report_id.into(),
+                    tenant_id.into(),
+                    name.into(),
+                    serde_json::to_value(request.source.unwrap_or(current.source))?.into(),
+                    serde_json::to_value(&schedule)?.into(),
+                    request.format.unwrap_or(current.format).as_str().into(),
+                    deliveries.into(),
+                    request.enabled.unwrap_or(current.enabled).into(),
+                    next_run_at.into(),
+                    Utc::now().into(),
+                ],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("定时报告"))?;
+        report_from_row(&row)
+    }
+
+    /// 删除定时报告及其执行记录
+    pub async fn delete_report(&self, tenant_id: Uuid, report_id: Uuid) -> Result<(), AiStudioError> {
+        let result = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "DELETE FROM scheduled_reports WHERE id = $1 AND tenant_id = $2",
+                [report_id.into(), tenant_id.into()],
+            ))
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(AiStudioError::not_found("定时报告"));
+        }
+        info!(tenant_id = %tenant_id, report_id = %report_id, "定时报告已删除");
+        Ok(())
+    }
+
+    /// 立即生成并投递报告，统计区间以当前时间结束，不影响计划执行时间
+    pub async fn run_now(&self, tenant_id: Uuid, report_id: Uuid) -> Result<ReportRunResponse, AiStudioError> {
+        let row = self.find_row(tenant_id, report_id).await?;
+        self.execute(&row, Utc::now(), "manual").await
+    }
+
+    /// 列出报告的执行记录，按开始时间倒序
+    pub async fn list_runs(&self, tenant_id: Uuid, report_id: Uuid, limit: u64) -> Result<Vec<ReportRunResponse>, AiStudioError> {
+        self.find_row(tenant_id, report_id).await?;
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT * FROM scheduled_report_runs WHERE report_id = $1 ORDER BY started_at DESC LIMIT $2",
+                [report_id.into(), (limit.clamp(1, 200) as i64).into()],
+            ))
+            .await?;
+        rows.iter().map(run_from_row).collect()
+    }
+
+    /// 执行所有到期的报告
+    ///
+    /// 先推进下次执行时间再生成报告；服务停机期间错过的周期不补发。
+    pub async fn run_due_reports(&self) -> Result<usize, AiStudioError> {
+        let now = Utc::now();
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT * FROM scheduled_reports WHERE enabled AND next_run_at <= $1 ORDER BY next_run_at LIMIT $2",
+                [now.into(), (MAX_DUE_REPORTS as i64).into()],
+            ))
+            .await?;
+
+        let mut executed = 0;
+        for row in rows {
+            let report = report_from_row(&row)?;
+            self.db
+                .execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    "UPDATE scheduled_reports SET next_run_at = $2, last_run_at = $3 WHERE id = $1",
+                    [report.id.into(), report.schedule.next_run_after(now).into(), now.into()],
+                ))
+                .await?;
+
+            match self.execute(&row, report.next_run_at, "scheduled").await {
+                Ok(run) if run.status == "completed" => executed += 1,
+                Ok(run) => warn!(report_id = %report.id, error = ?run.error, "定时报告执行失败"),
+                Err(e) => error!(report_id = %report.id, error = %e, "定时报告执行失败"),
+            }
+        }
+        Ok(executed)
+    }
+
+    /// 生成报告、投递并记录执行结果
+    async fn execute(&self, row: &QueryResult, period_end: DateTime<Utc>, trigger: &str) -> Result<ReportRunResponse, AiStudioError> {
+        let report = report_from_row(row)?;
+        let tenant_id: Uuid = row.try_get("", "tenant_id")?;
+        let deliveries = self.open_deliveries(&row.try_get("", "deliveries")?)?;
+        let period_start = report.schedule.period_start(period_end);
+        let started_at = Utc::now();
+
+        let (download_url, results, error) = match self.render(tenant_id, &report, period_start, period_end).await {
+            Ok(rendered) => {
+                let period = format_period(period_start, period_end);
+                let results = self.deliver(tenant_id, &report.name, &period, &rendered, &deliveries).await;
+                let error = (!results.is_empty() && results.iter().all(|r| !r.success))
+                    .then(|| "所有投递目标均失败".to_string());
+                (Some(rendered.download_url), results, error)
+            }
+            Err(e) => (None, Vec::new(), Some(e.to_string())),
+        };
+
+        let run = ReportRunResponse {
+            id: Uuid::new_v4(),
+            report_id: report.id,
+            trigger: trigger.to_string(),
+            period_start,
+            period_end,
+            status: if error.is_none() { "completed" } else { "failed" }.to_string(),
+            download_url,
+            deliveries: results,
+            error,
+            started_at,
+            completed_at: Utc::now(),
+        };
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO scheduled_report_runs
+                    (id, report_id, tenant_id, trigger, period_start, period_end, status, download_url, deliveries, error, started_at, completed_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                "#,
+                [
+                    run.id.into(),
+                    run.report_id.into(),
+                    tenant_id.into(),
+                    run.trigger.clone().into(),
+                    run.period_start.into(),
+                    run.period_end.into(),
+                    run.status.clone().into(),
+                    run.download_url.clone().into(),
+                    serde_json::to_value(&run.deliveries)?.into(),
+                    run.error.clone().into(),
+                    run.started_at.into(),
+                    run.completed_at.into(),
+                ],
+            ))
+            .await?;
+
+        info!(
+            report_id = %run.report_id,
+            trigger = %run.trigger,
+            status = %run.status,
+            "定时报告已执行"
+        );
+        Ok(run)
+    }
+
+    /// 查询数据并渲染报告文件，返回摘要与下载链接
+    async fn render(
+        &self,
+        tenant_id: Uuid,
+        report: &ScheduledReportResponse,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<RenderedReport, AiStudioError> {
+        let body = match &report.source {
+            ReportSource::Usage => self.usage_markdown(tenant_id, period_start, period_end).await?,
+            ReportSource::QaAnalytics { knowledge_base_id } => {
+                self.qa_markdown(tenant_id, *knowledge_base_id, period_start, period_end).await?
+            }
+            ReportSource::WorkflowOutput { workflow_id } => {
+                self.workflow_markdown(tenant_id, *workflow_id, period_start, period_end).await?
+            }
+        };
+        let markdown = format!("统计区间：{}\n\n{}", format_period(period_start, period_end), body);
+
+        let format = report.format.document_format();
+        let blocks = parse_markdown(&markdown);
+        let content = match format {
+            GeneratedDocumentFormat::Pdf => render_pdf(Some(&report.name), &blocks),
+            _ => render_html(Some(&report.name), &blocks).into_bytes(),
+        };
+
+        let document_id = Uuid::new_v4();
+        let path = generated_document_path(&self.storage_root, tenant_id, document_id, format);
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await
+                .map_err(|e| AiStudioError::internal(format!("创建报告目录失败: {}", e)))?;
+        }
+        tokio::fs::write(&path, &content).await
+            .map_err(|e| AiStudioError::internal(format!("写入报告文件失败: {}", e)))?;
+
+        let file_name = format!(
+            "{}-{}.{}",
+            sanitize_file_name(&report.name),
+            period_end.format("%Y%m%d"),
+            format.extension()
+        );
+        let token = sign_document_token(&self.signing_secret, &DocumentDownloadClaims {
+            sub: document_id,
+            tid: tenant_id,
+            fmt: format,
+            name: file_name,
+            exp: (Utc::now() + chrono::Duration::days(DOCUMENT_DOWNLOAD_TTL_DAYS)).timestamp(),
+        })?;
+
+        Ok(RenderedReport {
+            summary: summarize(&markdown),
+            download_url: document_download_url(document_id, &token),
+        })
+    }
+
+    /// 向各投递目标发送报告，单个目标失败不影响其他目标
+    async fn deliver(
+        &self,
+        tenant_id: Uuid,
+        report_name: &str,
+        period: &str,
+        rendered: &RenderedReport,
+        deliveries: &[ReportDelivery],
+    ) -> Vec<DeliveryResult> {
+        let mut results = Vec::with_capacity(deliveries.len());
+        for delivery in deliveries {
+            let (channel, target, outcome) = match delivery {
+                ReportDelivery::Email { recipients } => (
+                    "email",
+                    recipients.join(", "),
+                    self.notifications
+                        .send_scheduled_report(tenant_id, recipients, report_name, period, &rendered.download_url)
+                        .await
+                        .map(|_| ()),
+                ),
+                ReportDelivery::Slack { webhook_url } => (
+                    "slack",
+                    mask_webhook(webhook_url),
+                    self.post_slack(tenant_id, webhook_url, report_name, period, rendered).await,
+                ),
+            };
+            if let Err(e) = &outcome {
+                warn!(channel = channel, error = %e, "报告投递失败");
+            }
+            results.push(DeliveryResult {
+                channel: channel.to_string(),
+                target,
+                success: outcome.is_ok(),
+                error: outcome.err().map(|e| e.to_string()),
+            });
+        }
+        results
+    }
+
+    async fn post_slack(
+        &self,
+        tenant_id: Uuid,
+        webhook_url: &str,
+        report_name: &str,
+        period: &str,
+        rendered: &RenderedReport,
+    ) -> Result<(), AiStudioError> {
+        let text = format!(
+            "*{}*（{}）\n{}\n<{}|下载报告>",
+            report_name, period, rendered.summary, rendered.download_url
+        );
+        let response = self.http
+            .client(tenant_id)
+            .await?
+            .post(webhook_url)
+            .json(&json!({ "text": text }))
+            .send()
+            .await
+            .map_err(|e| AiStudioError::external_service("slack", e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(AiStudioError::external_service("slack", format!("HTTP {}", response.status())));
+        }
+        Ok(())
+    }
+
+    async fn usage_markdown(&self, tenant_id: Uuid, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<String, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT date_trunc('day', bucket_start) AS day, metric, SUM(count)::BIGINT AS total
+                FROM usage_counters
+                WHERE tenant_id = $1 AND bucket_start >= $2 AND bucket_start < $3
+                GROUP BY day, metric
+                ORDER BY day
+                "#,
+                [tenant_id.into(), start.into(), end.into()],
+            ))
+            .await?;
+        let mut daily: Vec<(DateTime<Utc>, String, i64)> = Vec::with_capacity(rows.len());
+        for row in rows {
+            daily.push((row.try_get("", "day")?, row.try_get("", "metric")?, row.try_get("", "total")?));
+        }
+
+        let questions: i64 = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT COUNT(*) AS count FROM qa_query_logs WHERE tenant_id = $1 AND created_at >= $2 AND created_at < $3",
+                [tenant_id.into(), start.into(), end.into()],
+            ))
+            .await?
+            .map(|row| row.try_get("", "count"))
+            .transpose()?
+            .unwrap_or(0);
+
+        Ok(usage_report(&daily, questions))
+    }
+
+    async fn qa_markdown(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Option<Uuid>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<String, AiStudioError> {
+        let summary = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT COUNT(*) AS total,
+                       COALESCE(AVG(confidence_score), 0)::DOUBLE PRECISION AS avg_confidence,
+                       COUNT(*) FILTER (WHERE confidence_score < $5) AS low_confidence,
+                       COUNT(*) FILTER (WHERE source_count = 0) AS no_sources,
+                       COUNT(DISTINCT user_id) AS users
+                FROM qa_query_logs
+                WHERE tenant_id = $1 AND created_at >= $2 AND created_at < $3
+                    AND ($4::uuid IS NULL OR knowledge_base_id = $4)
+                "#,
+                [tenant_id.into(), start.into(), end.into(), knowledge_base_id.into(), LOW_CONFIDENCE_THRESHOLD.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::internal("查询问答统计失败"))?;
+
+        let top = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT MIN(question) AS question, COUNT(*) AS count
+                FROM qa_query_logs
+                WHERE tenant_id = $1 AND created_at >= $2 AND created_at < $3
+                    AND ($4::uuid IS NULL OR knowledge_base_id = $4)
+                GROUP BY normalized_question
+                ORDER BY count DESC
+                LIMIT $5
+                "#,
+                [tenant_id.into(), start.into(), end.into(), knowledge_base_id.into(), TOP_QUESTIONS.into()],
+            ))
+            .await?;
+
+        let total: i64 = summary.try_get("", "total")?;
+        let avg_confidence: f64 = summary.try_get("", "avg_confidence")?;
+        let low_confidence: i64 = summary.try_get("", "low_confidence")?;
+        let no_sources: i64 = summary.try_get("", "no_sources")?;
+        let users: i64 = summary.try_get("", "users")?;
+
+        let mut markdown = format!(
+            "## 概览\n\n- 提问数：{}\n- 提问用户数：{}\n- 平均置信度：{:.2}\n- 低置信度回答（低于 {:.1}）：{}\n- 无引用来源的回答：{}\n",
+            total, users, avg_confidence, LOW_CONFIDENCE_THRESHOLD, low_confidence, no_sources
+        );
+        if !top.is_empty() {
+            markdown.push_str("\n## 高频问题\n\n");
+            for (i, row) in top.iter().enumerate() {
+                let question: String = row.try_get("", "question")?;
+                let count: i64 = row.try_get("", "count")?;
+                markdown.push_str(&format!("{}. {}（{} 次）\n", i + 1, question, count));
+            }
+        }
+        Ok(markdown)
+    }
+
+    async fn workflow_markdown(
+        &self,
+        tenant_id: Uuid,
+        workflow_id: Uuid,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<String, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT e.output, e.completed_at
+                FROM workflow_executions e
+                JOIN workflows w ON w.id = e.workflow_id
+                WHERE w.id = $1 AND w.tenant_id = $2 AND e.status = 'completed'
+                    AND e.completed_at >= $3 AND e.completed_at < $4
+                ORDER BY e.completed_at DESC
+                LIMIT 1
+                "#,
+                [workflow_id.into(), tenant_id.into(), start.into(), end.into()],
+            ))
+            .await?;
+
+        let Some(row) = row else {
+            return Ok("统计区间内该工作流没有成功的执行。".to_string());
+        };
+        let output: Option<Value> = row.try_get("", "output")?;
+        let completed_at: DateTime<Utc> = row.try_get("", "completed_at")?;
+        Ok(format!(
+            "执行完成时间：{}\n\n{}",
+            completed_at.format("%Y-%m-%d %H:%M UTC"),
+            value_markdown(&output.unwrap_or(Value::Null))
+        ))
+    }
+
+    async fn validate_source(&self, tenant_id: Uuid, source: &ReportSource) -> Result<(), AiStudioError> {
+        let (sql, id) = match source {
+            ReportSource::Usage | ReportSource::QaAnalytics { knowledge_base_id: None } => return Ok(()),
+            ReportSource::QaAnalytics { knowledge_base_id: Some(id) } => {
+                ("SELECT 1 FROM knowledge_bases WHERE id = $1 AND tenant_id = $2", *id)
+            }
+            ReportSource::WorkflowOutput { workflow_id } => {
+                ("SELECT 1 FROM workflows WHERE id = $1 AND tenant_id = $2", *workflow_id)
+            }
+        };
+        let exists = self.db
+            .query_one(Statement::from_sql_and_values(DatabaseBackend::Postgres, sql, [id.into(), tenant_id.into()]))
+            .await?
+            .is_some();
+        if !exists {
+            return Err(AiStudioError::not_found(format!("报告数据来源 {}", id)));
+        }
+        Ok(())
+    }
+
+    async fn find_row(&self, tenant_id: Uuid, report_id: Uuid) -> Result<QueryResult, AiStudioError> {
+        self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT * FROM scheduled_reports WHERE id = $1 AND tenant_id = $2",
+                [report_id.into(), tenant_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("定时报告"))
+    }
+
+    /// 加密 Slack 地址后序列化投递渠道
+    fn seal_deliveries(&self, deliveries: &[ReportDelivery]) -> Result<Value, AiStudioError> {
+        let sealed = deliveries
+            .iter()
+            .map(|delivery| match delivery {
+                ReportDelivery::Slack { webhook_url } => Ok(ReportDelivery::Slack {
+                    webhook_url: self.cipher.encrypt(webhook_url)?,
+                }),
+                other => Ok(other.clone()),
+            })
+            .collect::<Result<Vec<_>, AiStudioError>>()?;
+        Ok(serde_json::to_value(sealed)?)
+    }
+
+    /// 解密投递渠道中的 Slack 地址
+    fn open_deliveries(&self, value: &Value) -> Result<Vec<ReportDelivery>, AiStudioError> {
+        serde_json::from_value::<Vec<ReportDelivery>>(value.clone())?
+            .into_iter()
+            .map(|delivery| match delivery {
+                ReportDelivery::Slack { webhook_url } => Ok(ReportDelivery::Slack {
+                    webhook_url: self.cipher.decrypt(&webhook_url)?,
+                }),
+                other => Ok(other),
+            })
+            .collect()
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, AiStudioError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > 200 {
+        return Err(AiStudioError::validation("name", "报告名称不能为空且不超过 200 个字符"));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_deliveries(deliveries: &[ReportDelivery]) -> Result<(), AiStudioError> {
+    if deliveries.is_empty() || deliveries.len() > MAX_DELIVERIES {
+        return Err(AiStudioError::validation("deliveries", format!("投递渠道数量必须在 1-{} 之间", MAX_DELIVERIES)));
+    }
+    for delivery in deliveries {
+        match delivery {
+            ReportDelivery::Email { recipients } => {
+                if recipients.is_empty() || recipients.iter().any(|r| !r.contains('@')) {
+                    return Err(AiStudioError::validation("deliveries", "邮件收件人不能为空且必须为有效邮箱"));
+                }
+            }
+            ReportDelivery::Slack { webhook_url } => {
+                if !webhook_url.starts_with("https://") {
+                    return Err(AiStudioError::validation("deliveries", "Slack Webhook 地址必须使用 HTTPS"));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 脱敏 Webhook 地址，仅保留主机名
+fn mask_webhook(url: &str) -> String {
+    let host = url
+        .strip_prefix("https://")
+        .and_then(|rest| rest.split('/').next())
+        .unwrap_or("");
+    format!("https://{}/***", host)
+}
+
+fn format_period(start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    format!("{} 至 {}", start.format("%Y-%m-%d %H:%M"), end.format("%Y-%m-%d %H:%M UTC"))
+}
+
+/// 渲染用量报告正文
+fn usage_report(daily: &[(DateTime<Utc>, String, i64)], questions: i64) -> String {
+    let total = |metric: AnomalyMetric| -> i64 {
+        daily.iter().filter(|(_, m, _)| m == metric.as_str()).map(|(_, _, count)| count).sum()
+    };
+
+    let mut markdown = String::from("## 概览\n\n");
+    for metric in AnomalyMetric::ALL {
+        markdown.push_str(&format!("- {}：{}\n", metric.display_name(), total(metric)));
+    }
+    markdown.push_str(&format!("- 问答次数：{}\n", questions));
+
+    let mut days: Vec<DateTime<Utc>> = daily.iter().map(|(day, _, _)| *day).collect();
+    days.dedup();
+    if !days.is_empty() {
+        markdown.push_str("\n## 按天明细\n\n");
+        for day in days {
+            let values = AnomalyMetric::ALL
+                .iter()
+                .map(|metric| {
+                    let count: i64 = daily.iter()
+                        .filter(|(d, m, _)| *d == day && m == metric.as_str())
+                        .map(|(_, _, count)| count)
+                        .sum();
+                    format!("{} {}", metric.display_name(), count)
+                })
+                .collect::<Vec<_>>()
+                .join("，");
+            markdown.push_str(&format!("- {}：{}\n", day.format("%Y-%m-%d"), values));
+        }
+    }
+    markdown
+}
+
+/// 将工作流输出渲染为 Markdown，顶层字段作为小节
+fn value_markdown(value: &Value) -> String {
+    match value {
+        Value::Null => "工作流没有输出。".to_string(),
+        Value::String(text) => text.clone(),
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| {
+                let body = match value {
+                    Value::String(text) => text.clone(),
+                    Value::Array(items) => items
+                        .iter()
+                        .map(|item| format!("- {}", item.as_str().map(str::to_string).unwrap_or_else(|| item.to_string())))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    other => other.to_string(),
+                };
+                format!("## {}\n\n{}\n", key, body)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    }
+}
+
+/// 报告摘要：取正文中的列表项，用于 Slack 消息
+fn summarize(markdown: &str) -> String {
+    markdown
+        .lines()
+        .filter(|line| line.starts_with("- "))
+        .take(8)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn report_from_row(row: &QueryResult) -> Result<ScheduledReportResponse, AiStudioError> {
+    let deliveries: Vec<ReportDelivery> = serde_json::from_value(row.try_get("", "deliveries")?)?;
+    let format: String = row.try_get("", "format")?;
+    Ok(ScheduledReportResponse {
+        id: row.try_get("", "id")?,
+        name: row.try_get("", "name")?,
+        source: serde_json::from_value(row.try_get("", "source")?)?,
+        schedule: serde_json::from_value(row.try_get("", "schedule")?)?,
+        format: ReportFormat::parse(&format),
+        deliveries: deliveries
+            .into_iter()
+            .map(|delivery| match delivery {
+                ReportDelivery::Slack { .. } => ReportDelivery::Slack { webhook_url: "***".to_string() },
+                other => other,
+            })
+            .collect(),
+        enabled: row.try_get("", "enabled")?,
+        next_run_at: row.try_get("", "next_run_at")?,
+        last_run_at: row.try_get("", "last_run_at")?,
+        created_by: row.try_get("", "created_by")?,
+        created_at: row.try_get("", "created_at")?,
+        updated_at: row.try_get("", "updated_at")?,
+    })
+}
+
+fn run_from_row(row: &QueryResult) -> Result<ReportRunResponse, AiStudioError> {
+    Ok(ReportRunResponse {
+        id: row.try_get("", "id")?,
+        report_id: row.try_get("", "report_id")?,
+        trigger: row.try_get("", "trigger")?,
+        period_start: row.try_get("", "period_start")?,
+        period_end: row.try_get("", "period_end")?,
+        status: row.try_get("", "status")?,
+        download_url: row.try_get("", "download_url")?,
+        deliveries: serde_json::from_value(row.try_get("", "deliveries")?)?,
+        error: row.try_get("", "error")?,
+        started_at: row.try_get("", "started_at")?,
+        completed_at: row.try_get("", "completed_at")?,
+    })
+}
+
+/// 定时报告周期任务
+pub struct ScheduledReportJob {
+    service: Arc<ScheduledReportService>,
+}
+
+impl ScheduledReportJob {
+    /// 创建新的定时报告周期任务
+    pub fn new(service: Arc<ScheduledReportService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for ScheduledReportJob {
+    fn name(&self) -> &str {
+        "scheduled_reports"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(DUE_CHECK_INTERVAL_SECS)
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        self.service.run_due_reports().await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_next_run_after() {
+        // 2024-05-01 为周三
+        let now = at("2024-05-01T10:30:00Z");
+        let daily = ReportSchedule { frequency: ReportFrequency::Daily, hour: 8, weekday: None, day_of_month: None };
+        assert_eq!(daily.next_run_after(now), at("2024-05-02T08:00:00Z"));
+        assert_eq!(daily.next_run_after(at("2024-05-01T07:59:00Z")), at("2024-05-01T08:00:00Z"));
+
+        let weekly = ReportSchedule { frequency: ReportFrequency::Weekly, hour: 9, weekday: Some(1), day_of_month: None };
+        assert_eq!(weekly.next_run_after(now), at("2024-05-06T09:00:00Z"));
+
+        let monthly = ReportSchedule { frequency: ReportFrequency::Monthly, hour: 0, weekday: None, day_of_month: Some(1) };
+        assert_eq!(monthly.next_run_after(now), at("2024-06-01T00:00:00Z"));
+        assert_eq!(monthly.period_start(at("2024-03-01T00:00:00Z")), at("2024-02-01T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_schedule_validation() {
+        let mut schedule = ReportSchedule { frequency: ReportFrequency::Monthly, hour: 24, weekday: None, day_of_month: None };
+        assert!(schedule.validate().is_err());
+        schedule.hour = 6;
+        schedule.day_of_month = Some(31);
+        assert!(schedule.validate().is_err());
+        schedule.day_of_month = Some(28);
+        assert!(schedule.validate().is_ok());
+    }
+
+    #[test]
+    fn test_usage_report_totals_and_daily_rows() {
+        let day1 = at("2024-05-01T00:00:00Z");
+        let day2 = at("2024-05-02T00:00:00Z");
+        let daily = vec![
+            (day1, "tokens".to_string(), 1000),
+            (day1, "document_deletions".to_string(), 2),
+            (day2, "tokens".to_string(), 500),
+        ];
+        let markdown = usage_report(&daily, 42);
+
+        assert!(markdown.contains("- Token 用量：1500\n"));
+        assert!(markdown.contains("- 问答次数：42\n"));
+        assert!(markdown.contains("- 2024-05-01：Token 用量 1000，登录失败次数 0，文档删除数 2\n"));
+        assert!(markdown.contains("- 2024-05-02：Token 用量 500"));
+    }
+
+    #[test]
+    fn test_delivery_validation_and_masking() {
+        assert!(validate_deliveries(&[]).is_err());
+        assert!(validate_deliveries(&[ReportDelivery::Email { recipients: vec!["ops".to_string()] }]).is_err());
+        assert!(validate_deliveries(&[ReportDelivery::Slack { webhook_url: "http://hooks.slack.com/x".to_string() }]).is_err());
+        assert!(validate_deliveries(&[ReportDelivery::Email { recipients: vec!["ops@example.com".to_string()] }]).is_ok());
+        assert_eq!(mask_webhook("https://hooks.slack.com/services/T000/B000/XXXX"), "https://hooks.slack.com/***");
+    }
+
+    #[test]
+    fn test_value_markdown_sections() {
+        let markdown = value_markdown(&json!({ "summary": "本周稳定", "risks": ["延迟升高", 3] }));
+        assert!(markdown.contains("## risks\n\n- 延迟升高\n- 3\n"));
+        assert!(markdown.contains("## summary\n\n本周稳定\n"));
+    }
+}
@@ -0,0 +1,187 @@
+// 周期任务调度服务
+// 借助分布式租约保证多实例部署时每个周期任务只由一个实例执行
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, error, info, warn};
+
+use crate::errors::AiStudioError;
+use crate::db::DistributedLockService;
+
+/// 周期任务接口
+#[async_trait::async_trait]
+pub trait PeriodicJob: Send + Sync {
+    /// 任务名称（全局唯一，同时作为分布式租约名称）
+    fn name(&self) -> &str;
+
+    /// 执行间隔
+    fn interval(&self) -> Duration;
+
+    /// 执行任务
+    async fn run(&self) -> Result<(), AiStudioError>;
+}
+
+/// 周期任务运行状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobRunState {
+    /// 任务名称
+    pub name: String,
+    /// 最后一次执行时间
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// 最后一次执行是否成功
+    pub last_success: Option<bool>,
+    /// 最后一次错误信息
+    pub last_error: Option<String>,
+    /// 本实例累计执行次数
+    pub run_count: u64,
+    /// 因其他实例持有租约而跳过的次数
+    pub skipped_count: u64,
+}
+
+/// 周期任务调度服务
+pub struct SchedulerService {
+    lock_service: DistributedLockService,
+    jobs: Vec<Arc<dyn PeriodicJob>>,
+    states: Arc<RwLock<HashMap<String, JobRunState>>>,
+}
+
+impl SchedulerService {
+    /// 创建新的调度服务实例
+    pub fn new(lock_service: DistributedLockService) -> Self {
+        Self {
+            lock_service,
+            jobs: Vec::new(),
+            states: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 注册周期任务
+    pub fn register(&mut self, job: Arc<dyn PeriodicJob>) {
+        info!(job = %job.name(), interval_secs = job.interval().as_secs(), "注册周期任务");
+        self.jobs.push(job);
+    }
+
+    /// 启动所有已注册的周期任务
+    pub fn start(&self) {
+        for job in &self.jobs {
+            let job = job.clone();
+            let lock_service = self.lock_service.clone();
+            let states = self.states.clone();
+
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(job.interval());
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+                loop {
+                    interval.tick().await;
+                    Self::tick(job.as_ref(), &lock_service, &states).await;
+                }
+            });
+        }
+
+        info!(count = self.jobs.len(), "周期任务调度器已启动");
+    }
+
+    /// 立即执行指定任务一次（仍受租约保护）
+    pub async fn trigger(&self, name: &str) -> Result<(), AiStudioError> {
+        let job = self.jobs.iter()
+            .find(|job| job.name() == name)
+            .ok_or_else(|| AiStudioError::not_found(format!("周期任务 {}", name)))?;
+
+        Self::tick(job.as_ref(), &self.lock_service, &self.states).await;
+        Ok(())
+    }
+
+    /// 获取所有任务的运行状态
+    pub async fn get_states(&self) -> Vec<JobRunState> {
+        let states = self.states.read().await;
+        let mut result: Vec<JobRunState> = self.jobs.iter()
+            .map(|job| {
+                states.get(job.name()).cloned().unwrap_or_else(|| JobRunState {
+                    name: job.name().to_string(),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        result.sort_by(|a, b| a.name.cmp(&b.name));
+        result
+    }
+
+    /// 单次调度：获取租约后执行任务
+    ///
+    /// 租约时长略短于执行间隔，持有者在下一个周期自动续约；
+    /// 持有者宕机后租约过期，其他实例在下一个周期接管。
+    async fn tick(
+        job: &dyn PeriodicJob,
+        lock_service: &DistributedLockService,
+        states: &RwLock<HashMap<String, JobRunState>>,
+    ) {
+        let lease_name = format!("scheduler:{}", job.name());
+        let ttl = lease_ttl(job.interval());
+
+        let acquired = match lock_service.try_acquire_lease(&lease_name, ttl).await {
+            Ok(acquired) => acquired,
+            Err(e) => {
+                warn!(job = %job.name(), error = %e, "获取调度租约失败，跳过本次执行");
+                false
+            }
+        };
+
+        if !acquired {
+            debug!(job = %job.name(), "租约由其他实例持有，跳过本次执行");
+            let mut states = states.write().await;
+            let state = states.entry(job.name().to_string()).or_insert_with(|| JobRunState {
+                name: job.name().to_string(),
+                ..Default::default()
+            });
+            state.skipped_count += 1;
+            return;
+        }
+
+        let started_at = Utc::now();
+        let result = job.run().await;
+
+        let mut states = states.write().await;
+        let state = states.entry(job.name().to_string()).or_insert_with(|| JobRunState {
+            name: job.name().to_string(),
+            ..Default::default()
+        });
+        state.last_run_at = Some(started_at);
+        state.run_count += 1;
+
+        match result {
+            Ok(()) => {
+                debug!(job = %job.name(), "周期任务执行成功");
+                state.last_success = Some(true);
+                state.last_error = None;
+            }
+            Err(e) => {
+                error!(job = %job.name(), error = %e, "周期任务执行失败");
+                state.last_success = Some(false);
+                state.last_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// 根据执行间隔计算租约时长
+fn lease_ttl(interval: Duration) -> Duration {
+    let margin = (interval / 10).min(Duration::from_secs(30));
+    interval.saturating_sub(margin).max(Duration::from_secs(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lease_ttl_shorter_than_interval() {
+        assert_eq!(lease_ttl(Duration::from_secs(60)), Duration::from_secs(54));
+        assert_eq!(lease_ttl(Duration::from_secs(3600)), Duration::from_secs(3570));
+        assert_eq!(lease_ttl(Duration::from_millis(500)), Duration::from_secs(1));
+    }
+}
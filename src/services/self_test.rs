@@ -0,0 +1,227 @@
+// 启动自检
+// `--self-test` 启动模式下依次检查迁移状态、数据库读写、嵌入生成与 Agent 工具调用，供部署流水线与容器健康门禁使用
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement, TransactionTrait};
+use serde::Serialize;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::ai::agent_runtime::{ExecutionContext, ToolEnum};
+use crate::ai::client::{AiClient, AiClientManager, MockAiClient};
+use crate::ai::tools::calculator_tool::CalculatorTool;
+use crate::config::AiConfig;
+use crate::db::migrations::{MigrationManager, MigrationState};
+use crate::errors::AiStudioError;
+
+/// 单项检查的超时时间
+const CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 嵌入检查使用的样例文本
+const SAMPLE_TEXT: &str = "Aionix 启动自检样例文本";
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestCheck {
+    /// 检查名称
+    pub name: &'static str,
+    /// 是否通过
+    pub passed: bool,
+    /// 耗时（毫秒）
+    pub duration_ms: u64,
+    /// 结果说明或失败原因
+    pub detail: String,
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    /// 所有检查是否通过
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    /// 逐行输出的文本报告
+    pub fn render(&self) -> String {
+        let mut lines: Vec<String> = self.checks
+            .iter()
+            .map(|check| format!(
+                "[{}] {} ({}ms): {}",
+                if check.passed { "通过" } else { "失败" },
+                check.name,
+                check.duration_ms,
+                check.detail
+            ))
+            .collect();
+        let failed = self.checks.iter().filter(|check| !check.passed).count();
+        lines.push(if failed == 0 {
+            format!("自检通过，共 {} 项", self.checks.len())
+        } else {
+            format!("自检失败，{}/{} 项未通过", failed, self.checks.len())
+        });
+        lines.join("\n")
+    }
+}
+
+/// 启动自检
+pub struct SelfTest {
+    db: DatabaseConnection,
+    ai_config: AiConfig,
+}
+
+impl SelfTest {
+    pub fn new(db: DatabaseConnection, ai_config: AiConfig) -> Self {
+        Self { db, ai_config }
+    }
+
+    /// 依次执行所有检查，单项失败不影响后续检查
+    pub async fn run(&self) -> SelfTestReport {
+        let checks = vec![
+            run_check("migrations", self.check_migrations()).await,
+            run_check("database_read_write", self.check_database_read_write()).await,
+            run_check("embedding", self.check_embedding()).await,
+            run_check("agent_step", check_agent_step(Arc::new(MockAiClient::new(Arc::new(self.ai_config.clone()))))).await,
+        ];
+        SelfTestReport { checks }
+    }
+
+    /// 所有迁移均已应用且校验和一致
+    async fn check_migrations(&self) -> Result<String, AiStudioError> {
+        let report = MigrationManager::new(self.db.clone()).status_report().await?;
+        let problems: Vec<String> = report
+            .iter()
+            .filter(|migration| matches!(migration.state, MigrationState::Pending | MigrationState::ChecksumMismatch))
+            .map(|migration| format!("{}({:?})", migration.version, migration.state))
+            .collect();
+        if !problems.is_empty() {
+            return Err(AiStudioError::internal(format!("迁移未就绪: {}", problems.join(", "))));
+        }
+        Ok(format!("{} 个迁移均已应用", report.len()))
+    }
+
+    /// 在事务内的临时表中写入并读回一行，结束后回滚，不留下任何数据
+    async fn check_database_read_write(&self) -> Result<String, AiStudioError> {
+        let txn = self.db.begin().await?;
+        txn.execute_unprepared(
+            "CREATE TEMPORARY TABLE aionix_self_test (id UUID PRIMARY KEY, value TEXT NOT NULL) ON COMMIT DROP",
+        )
+        .await?;
+
+        let id = Uuid::new_v4();
+        txn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "INSERT INTO aionix_self_test (id, value) VALUES ($1, $2)",
+            [id.into(), SAMPLE_TEXT.into()],
+        ))
+        .await?;
+        let row = txn
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT value FROM aionix_self_test WHERE id = $1",
+                [id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::internal("写入的临时行未能读回"))?;
+        let value: String = row.try_get("", "value")?;
+        txn.rollback().await?;
+
+        if value != SAMPLE_TEXT {
+            return Err(AiStudioError::internal("读回的临时行内容不一致"));
+        }
+        Ok("临时行写入并读回成功".to_string())
+    }
+
+    /// 使用配置的 AI 服务为样例文本生成嵌入向量
+    async fn check_embedding(&self) -> Result<String, AiStudioError> {
+        let client = AiClientManager::new(self.ai_config.clone())?.client();
+        let response = client.generate_embedding(SAMPLE_TEXT).await?;
+        if response.embedding.is_empty() || response.embedding.iter().any(|value| !value.is_finite()) {
+            return Err(AiStudioError::ai("嵌入向量为空或包含非法数值".to_string()));
+        }
+        Ok(format!("模型 {} 返回 {} 维向量", response.model, response.embedding.len()))
+    }
+}
+
+/// 使用模拟模型执行一步 Agent 推理并调用计算器工具，验证推理与工具执行链路
+async fn check_agent_step(provider: Arc<dyn AiClient>) -> Result<String, AiStudioError> {
+    let reply = provider.generate_text("自检：计算 2 + 3").await?;
+    if reply.text.trim().is_empty() {
+        return Err(AiStudioError::ai("模拟模型返回空回复".to_string()));
+    }
+
+    let tool = ToolEnum::CalculatorTool(CalculatorTool::new());
+    let parameters: HashMap<String, serde_json::Value> = [
+        ("operation".to_string(), serde_json::json!("add")),
+        ("a".to_string(), serde_json::json!(2)),
+        ("b".to_string(), serde_json::json!(3)),
+    ]
+    .into_iter()
+    .collect();
+    tool.validate_parameters(&parameters)?;
+    let context = ExecutionContext {
+        current_task: None,
+        execution_history: Vec::new(),
+        context_variables: HashMap::new(),
+        session_id: None,
+        user_id: None,
+        tool_progress: None,
+    };
+    let result = tool.execute(parameters, &context).await?;
+    if !result.success || result.data.get("result").and_then(|v| v.as_f64()) != Some(5.0) {
+        return Err(AiStudioError::internal(format!("工具调用结果异常: {}", result.data)));
+    }
+    Ok(format!("模型 {} 回复，工具 {} 执行成功", reply.model, tool.metadata().name))
+}
+
+/// 执行单项检查并记录耗时，超时视为失败
+async fn run_check<F>(name: &'static str, check: F) -> SelfTestCheck
+where
+    F: Future<Output = Result<String, AiStudioError>>,
+{
+    let started = Instant::now();
+    let outcome = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(AiStudioError::timeout(name)),
+    };
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    match outcome {
+        Ok(detail) => {
+            info!("自检通过: {} ({}ms)", name, duration_ms);
+            SelfTestCheck { name, passed: true, duration_ms, detail }
+        }
+        Err(e) => {
+            warn!("自检失败: {} ({}ms): {}", name, duration_ms, e);
+            SelfTestCheck { name, passed: false, duration_ms, detail: e.to_string() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_agent_step_with_mock_provider() {
+        let provider = Arc::new(MockAiClient::new(Arc::new(crate::config::AppConfig::default().ai)));
+        let check = run_check("agent_step", check_agent_step(provider)).await;
+        assert!(check.passed, "{}", check.detail);
+    }
+
+    #[tokio::test]
+    async fn test_failed_check_fails_report() {
+        let failed = run_check("embedding", async { Err(AiStudioError::ai("不可用".to_string())) }).await;
+        let passed = run_check("migrations", async { Ok("ok".to_string()) }).await;
+        let report = SelfTestReport { checks: vec![passed, failed] };
+
+        assert!(!report.passed());
+        assert!(report.render().ends_with("自检失败，1/2 项未通过"));
+    }
+}
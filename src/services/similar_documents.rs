@@ -0,0 +1,206 @@
+// 相似文档推荐服务
+// 以文档块向量的平均值作为文档向量，在同一知识库内查找最相近的文档，用于重复内容复核与门户的「相关文章」
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::api::models::{PaginatedResponse, PaginationInfo};
+use crate::db::entities::document::ClearanceLevel;
+use crate::errors::AiStudioError;
+
+/// 默认最低相似度
+const DEFAULT_MIN_SCORE: f32 = 0.75;
+
+/// 默认每页条数
+const DEFAULT_PAGE_SIZE: u32 = 10;
+
+/// 每页最大条数
+const MAX_PAGE_SIZE: u32 = 50;
+
+/// 参与分页的最多相似文档数
+const MAX_SIMILAR_DOCUMENTS: i64 = 200;
+
+/// 相似文档查询参数
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+pub struct SimilarDocumentsQuery {
+    /// 最低相似度（0-1），默认 0.75
+    pub min_score: Option<f32>,
+    /// 页码，从 1 开始
+    pub page: Option<u32>,
+    /// 每页条数，默认 10，最多 50
+    pub page_size: Option<u32>,
+}
+
+impl SimilarDocumentsQuery {
+    /// 校验并返回生效的最低相似度与分页参数
+    fn resolve(&self) -> Result<(f32, u32, u32), AiStudioError> {
+        let min_score = self.min_score.unwrap_or(DEFAULT_MIN_SCORE);
+        if !(0.0..=1.0).contains(&min_score) {
+            return Err(AiStudioError::validation("min_score", "最低相似度必须在 0 到 1 之间"));
+        }
+        let page = self.page.unwrap_or(1).max(1);
+        let page_size = self.page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        Ok((min_score, page, page_size))
+    }
+}
+
+/// 相似文档
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SimilarDocument {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 文档摘要
+    pub summary: Option<String>,
+    /// 与源文档的余弦相似度
+    pub score: f32,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 相似文档推荐服务
+pub struct SimilarDocumentService {
+    db: DatabaseConnection,
+}
+
+impl SimilarDocumentService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 查找同一知识库内与文档最相似的文档，按相似度降序分页
+    ///
+    /// 只比较与源文档使用同一嵌入模型的向量；源文档尚未生成向量时返回空列表。
+    /// 处理未完成、密级高于请求者或包含高密级段落的文档不参与推荐。
+    #[instrument(skip(self, query))]
+    pub async fn find_similar(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+        clearance: ClearanceLevel,
+        query: &SimilarDocumentsQuery,
+    ) -> Result<PaginatedResponse<SimilarDocument>, AiStudioError> {
+        let (min_score, page, page_size) = query.resolve()?;
+        let knowledge_base_id = self.source_knowledge_base(tenant_id, document_id, clearance).await?;
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                WITH target AS (
+                    SELECT e.model_name, AVG(e.vector) AS centroid
+                    FROM embeddings e
+                    JOIN document_chunks c ON c.id = e.chunk_id
+                    WHERE c.document_id = $1
+                    GROUP BY e.model_name
+                    ORDER BY COUNT(*) DESC
+                    LIMIT 1
+                ),
+                scored AS (
+                    SELECT c.document_id,
+                           (1 - (AVG(e.vector) <=> (SELECT centroid FROM target)))::REAL AS similarity
+                    FROM embeddings e
+                    JOIN document_chunks c ON c.id = e.chunk_id
+                    JOIN documents d ON d.id = c.document_id
+                    WHERE e.model_name = (SELECT model_name FROM target)
+                        AND d.knowledge_base_id = $2
+                        AND d.id <> $1
+                        AND d.status = 'completed'
+                        AND d.clearance <= $3
+                        AND NOT EXISTS (
+                            SELECT 1 FROM document_chunks rc WHERE rc.document_id = d.id AND rc.clearance > $3
+                        )
+                    GROUP BY c.document_id
+                )
+                SELECT d.id, d.title, d.summary, d.updated_at, s.similarity
+                FROM scored s
+                JOIN documents d ON d.id = s.document_id
+                WHERE s.similarity >= $4
+                ORDER BY s.similarity DESC, d.id
+                LIMIT $5
+                "#,
+                vec![
+                    document_id.into(),
+                    knowledge_base_id.into(),
+                    clearance.into(),
+                    min_score.into(),
+                    MAX_SIMILAR_DOCUMENTS.into(),
+                ],
+            ))
+            .await?;
+
+        let mut documents = Vec::with_capacity(rows.len());
+        for row in rows {
+            documents.push(SimilarDocument {
+                document_id: row.try_get("", "id")?,
+                title: row.try_get("", "title")?,
+                summary: row.try_get("", "summary")?,
+                score: row.try_get("", "similarity")?,
+                updated_at: row.try_get("", "updated_at")?,
+            });
+        }
+
+        debug!("找到 {} 个相似文档: document_id={}", documents.len(), document_id);
+        let total = documents.len() as u64;
+        let data = page_of(documents, page, page_size);
+        Ok(PaginatedResponse::new(data, PaginationInfo::new(page, page_size, total)))
+    }
+
+    /// 校验源文档属于租户且请求者可见，返回其所在知识库
+    async fn source_knowledge_base(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+        clearance: ClearanceLevel,
+    ) -> Result<Uuid, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT d.knowledge_base_id FROM documents d
+                JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id
+                WHERE d.id = $1 AND kb.tenant_id = $2 AND d.clearance <= $3
+                "#,
+                vec![document_id.into(), tenant_id.into(), clearance.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("文档"))?;
+        Ok(row.try_get("", "knowledge_base_id")?)
+    }
+}
+
+/// 取出指定页的记录，页码从 1 开始
+fn page_of<T>(items: Vec<T>, page: u32, page_size: u32) -> Vec<T> {
+    let offset = (page as usize - 1).saturating_mul(page_size as usize);
+    items.into_iter().skip(offset).take(page_size as usize).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_resolve() {
+        assert_eq!(SimilarDocumentsQuery::default().resolve().unwrap(), (DEFAULT_MIN_SCORE, 1, DEFAULT_PAGE_SIZE));
+
+        let query = SimilarDocumentsQuery { min_score: Some(0.9), page: Some(0), page_size: Some(500) };
+        assert_eq!(query.resolve().unwrap(), (0.9, 1, MAX_PAGE_SIZE));
+
+        let query = SimilarDocumentsQuery { min_score: Some(1.5), ..Default::default() };
+        assert!(query.resolve().is_err());
+    }
+
+    #[test]
+    fn test_page_of() {
+        let items: Vec<u32> = (1..=25).collect();
+        assert_eq!(page_of(items.clone(), 1, 10), (1..=10).collect::<Vec<_>>());
+        assert_eq!(page_of(items.clone(), 3, 10), (21..=25).collect::<Vec<_>>());
+        assert!(page_of(items, 4, 10).is_empty());
+    }
+}
@@ -0,0 +1,354 @@
+// 服务等级目标跟踪
+// 按接口类别统计请求的延迟与服务端错误，计算多个时间窗口内错误预算的消耗速度（燃烧率）
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::config::{SloClassConfig, SloConfig};
+use crate::errors::AiStudioError;
+
+static GLOBAL_SLO_TRACKER: OnceCell<Arc<SloTracker>> = OnceCell::new();
+
+/// 计数分桶长度（秒）
+const BUCKET_SECS: i64 = 60;
+
+/// 保留的分桶数，覆盖最长的报告窗口
+const RETAINED_BUCKETS: usize = 360;
+
+/// 报告的时间窗口（分钟）
+const REPORT_WINDOWS: [i64; 3] = [5, 60, 360];
+
+/// 快速消耗阈值：5 分钟与 1 小时窗口同时超过时判定为违约，按此速度 30 天的错误预算约 2 天耗尽
+const FAST_BURN_RATE: f64 = 14.4;
+
+/// 慢速消耗阈值：1 小时与 6 小时窗口同时超过时发出预警
+const SLOW_BURN_RATE: f64 = 6.0;
+
+/// SLO 状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SloStatus {
+    /// 最近一小时没有请求
+    NoData,
+    /// 错误预算消耗正常
+    Ok,
+    /// 错误预算消耗偏快
+    Warning,
+    /// 错误预算快速耗尽
+    Breaching,
+}
+
+/// 单个时间窗口的统计
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SloWindowReport {
+    /// 窗口长度（分钟）
+    pub window_minutes: i64,
+    /// 请求总数
+    pub total_requests: u64,
+    /// 服务端错误（5xx）请求数
+    pub error_requests: u64,
+    /// 超过延迟阈值的请求数
+    pub slow_requests: u64,
+    /// 可用性，没有请求时为空
+    pub availability: Option<f64>,
+    /// 延迟达标率，没有请求时为空
+    pub latency_compliance: Option<f64>,
+    /// 可用性错误预算的消耗速度，1 表示恰好在目标周期内耗尽
+    pub availability_burn_rate: f64,
+    /// 延迟错误预算的消耗速度
+    pub latency_burn_rate: f64,
+}
+
+impl SloWindowReport {
+    fn max_burn_rate(&self) -> f64 {
+        self.availability_burn_rate.max(self.latency_burn_rate)
+    }
+}
+
+/// 接口类别的 SLO 报告
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SloClassReport {
+    /// 类别名称
+    pub name: String,
+    /// 延迟阈值（毫秒）
+    pub latency_threshold_ms: u64,
+    /// 延迟目标
+    pub latency_target: f64,
+    /// 可用性目标
+    pub availability_target: f64,
+    /// 当前状态
+    pub status: SloStatus,
+    /// 各时间窗口的统计，按窗口由短到长排列
+    pub windows: Vec<SloWindowReport>,
+}
+
+/// SLO 报告
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SloReport {
+    /// 所有类别中最差的状态
+    pub status: SloStatus,
+    /// 各类别的报告
+    pub classes: Vec<SloClassReport>,
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+}
+
+impl SloReport {
+    /// 状态不为正常的类别名称
+    pub fn degraded_classes(&self) -> Vec<&str> {
+        self.classes
+            .iter()
+            .filter(|class| class.status >= SloStatus::Warning)
+            .map(|class| class.name.as_str())
+            .collect()
+    }
+}
+
+/// 一分钟内的请求计数
+#[derive(Debug, Clone, Copy, Default)]
+struct MinuteBucket {
+    minute: i64,
+    total: u64,
+    errors: u64,
+    slow: u64,
+}
+
+/// 单个接口类别的计数
+struct ClassTracker {
+    config: SloClassConfig,
+    buckets: Mutex<Vec<MinuteBucket>>,
+}
+
+impl ClassTracker {
+    fn new(config: SloClassConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(vec![MinuteBucket::default(); RETAINED_BUCKETS]),
+        }
+    }
+
+    fn record(&self, minute: i64, is_error: bool, is_slow: bool) {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = &mut buckets[minute.rem_euclid(RETAINED_BUCKETS as i64) as usize];
+        if bucket.minute != minute {
+            *bucket = MinuteBucket { minute, ..MinuteBucket::default() };
+        }
+        bucket.total += 1;
+        bucket.errors += is_error as u64;
+        bucket.slow += is_slow as u64;
+    }
+
+    fn report(&self, now_minute: i64) -> SloClassReport {
+        let buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let windows: Vec<SloWindowReport> = REPORT_WINDOWS
+            .iter()
+            .map(|&window| {
+                let (total, errors, slow) = buckets
+                    .iter()
+                    .filter(|b| b.total > 0 && b.minute > now_minute - window && b.minute <= now_minute)
+                    .fold((0, 0, 0), |(t, e, s), b| (t + b.total, e + b.errors, s + b.slow));
+                SloWindowReport {
+                    window_minutes: window,
+                    total_requests: total,
+                    error_requests: errors,
+                    slow_requests: slow,
+                    availability: (total > 0).then(|| 1.0 - errors as f64 / total as f64),
+                    latency_compliance: (total > 0).then(|| 1.0 - slow as f64 / total as f64),
+                    availability_burn_rate: burn_rate(errors, total, self.config.availability_target),
+                    latency_burn_rate: burn_rate(slow, total, self.config.latency_target),
+                }
+            })
+            .collect();
+
+        SloClassReport {
+            name: self.config.name.clone(),
+            latency_threshold_ms: self.config.latency_threshold_ms,
+            latency_target: self.config.latency_target,
+            availability_target: self.config.availability_target,
+            status: classify_status(&windows),
+            windows,
+        }
+    }
+}
+
+/// SLO 跟踪器
+///
+/// 计数保存在进程内，多实例部署时每个实例只反映自身处理的请求。
+pub struct SloTracker {
+    classes: Vec<ClassTracker>,
+}
+
+impl SloTracker {
+    /// 按配置创建跟踪器
+    pub fn new(config: &SloConfig) -> Self {
+        Self {
+            classes: config.classes.iter().cloned().map(ClassTracker::new).collect(),
+        }
+    }
+
+    /// 注册为全局跟踪器
+    pub fn install_global(tracker: Arc<SloTracker>) -> Result<(), AiStudioError> {
+        GLOBAL_SLO_TRACKER.set(tracker)
+            .map_err(|_| AiStudioError::internal("SLO 跟踪器已经初始化"))
+    }
+
+    /// 全局跟踪器，未启用 SLO 跟踪时为空
+    pub fn global() -> Option<Arc<SloTracker>> {
+        GLOBAL_SLO_TRACKER.get().cloned()
+    }
+
+    /// 记录一次请求，未归入任何类别的请求被忽略
+    pub fn record(&self, path: &str, status_code: u16, latency: Duration) {
+        self.record_at(path, status_code, latency, Utc::now());
+    }
+
+    /// 生成当前的 SLO 报告
+    pub fn report(&self) -> SloReport {
+        self.report_at(Utc::now())
+    }
+
+    fn record_at(&self, path: &str, status_code: u16, latency: Duration, now: DateTime<Utc>) {
+        let Some(class) = self.classify(path) else {
+            return;
+        };
+        let is_slow = latency.as_millis() > class.config.latency_threshold_ms as u128;
+        class.record(minute_of(now), status_code >= 500, is_slow);
+    }
+
+    fn report_at(&self, now: DateTime<Utc>) -> SloReport {
+        let now_minute = minute_of(now);
+        let classes: Vec<SloClassReport> = self.classes.iter().map(|class| class.report(now_minute)).collect();
+        SloReport {
+            status: classes.iter().map(|class| class.status).max().unwrap_or(SloStatus::NoData),
+            classes,
+            generated_at: now,
+        }
+    }
+
+    /// 按最长路径前缀确定请求所属的类别
+    fn classify(&self, path: &str) -> Option<&ClassTracker> {
+        self.classes
+            .iter()
+            .filter_map(|class| {
+                class.config.path_prefixes
+                    .iter()
+                    .filter(|prefix| matches_prefix(path, prefix))
+                    .map(|prefix| prefix.len())
+                    .max()
+                    .map(|len| (len, class))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, class)| class)
+    }
+}
+
+/// 记录一次请求到全局跟踪器
+pub fn record_request(path: &str, status_code: u16, latency: Duration) {
+    if let Some(tracker) = SloTracker::global() {
+        tracker.record(path, status_code, latency);
+    }
+}
+
+/// 路径是否位于前缀之下，按路径段边界匹配
+fn matches_prefix(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// 错误预算的消耗速度：不达标请求占比与允许占比之比
+fn burn_rate(bad: u64, total: u64, target: f64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (bad as f64 / total as f64) / (1.0 - target)
+}
+
+/// 按多窗口燃烧率判定状态
+fn classify_status(windows: &[SloWindowReport]) -> SloStatus {
+    let [short, hour, long] = [&windows[0], &windows[1], &windows[2]];
+    if hour.total_requests == 0 {
+        SloStatus::NoData
+    } else if short.max_burn_rate() >= FAST_BURN_RATE && hour.max_burn_rate() >= FAST_BURN_RATE {
+        SloStatus::Breaching
+    } else if hour.max_burn_rate() >= SLOW_BURN_RATE && long.max_burn_rate() >= SLOW_BURN_RATE {
+        SloStatus::Warning
+    } else {
+        SloStatus::Ok
+    }
+}
+
+fn minute_of(time: DateTime<Utc>) -> i64 {
+    time.timestamp().div_euclid(BUCKET_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    fn tracker() -> SloTracker {
+        SloTracker::new(&AppConfig::default().slo)
+    }
+
+    #[test]
+    fn test_classify_by_longest_prefix() {
+        let tracker = tracker();
+        assert_eq!(tracker.classify("/api/v1/qa/ask").map(|c| c.config.name.as_str()), Some("qa"));
+        assert_eq!(tracker.classify("/api/v1/workflows/1/execute").map(|c| c.config.name.as_str()), Some("agents"));
+        assert_eq!(tracker.classify("/api/v1/qanda").map(|c| c.config.name.as_str()), Some("api"));
+        assert!(tracker.classify("/health").is_none());
+    }
+
+    #[test]
+    fn test_burn_rate_and_status() {
+        let tracker = tracker();
+        let now = Utc::now();
+
+        let report = tracker.report_at(now);
+        assert_eq!(report.status, SloStatus::NoData);
+
+        // 可用性目标 99.9%：1% 的错误率对应 10 倍燃烧率
+        for i in 0..1000 {
+            let status = if i < 10 { 500 } else { 200 };
+            tracker.record_at("/api/v1/documents", status, Duration::from_millis(20), now);
+        }
+        let report = tracker.report_at(now);
+        let api = report.classes.iter().find(|c| c.name == "api").unwrap();
+        assert_eq!(api.windows[0].total_requests, 1000);
+        assert!((api.windows[0].availability_burn_rate - 10.0).abs() < 1e-6);
+        assert_eq!(api.windows[0].latency_burn_rate, 0.0);
+        assert_eq!(api.status, SloStatus::Warning);
+
+        // 慢请求持续增加后进入违约
+        for _ in 0..200 {
+            tracker.record_at("/api/v1/documents", 200, Duration::from_secs(2), now);
+        }
+        let report = tracker.report_at(now);
+        assert_eq!(report.status, SloStatus::Breaching);
+        assert_eq!(report.degraded_classes(), vec!["api"]);
+    }
+
+    #[test]
+    fn test_old_buckets_leave_short_windows() {
+        let tracker = tracker();
+        let now = Utc::now();
+        tracker.record_at("/api/v1/qa/ask", 503, Duration::from_millis(100), now - chrono::Duration::minutes(30));
+
+        let report = tracker.report_at(now);
+        let qa = report.classes.iter().find(|c| c.name == "qa").unwrap();
+        assert_eq!(qa.windows[0].total_requests, 0);
+        assert_eq!(qa.windows[1].error_requests, 1);
+
+        // 超出保留范围后分桶被复用
+        tracker.record_at("/api/v1/qa/ask", 200, Duration::from_millis(100), now + chrono::Duration::minutes(330));
+        let report = tracker.report_at(now + chrono::Duration::minutes(330));
+        let qa = report.classes.iter().find(|c| c.name == "qa").unwrap();
+        assert_eq!(qa.windows[2].total_requests, 1);
+        assert_eq!(qa.windows[2].error_requests, 0);
+    }
+}
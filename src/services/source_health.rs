@@ -0,0 +1,473 @@
+// 来源链接健康检查
+// 定期重新请求网页导入文档的来源地址，标记链接失效、重定向和内容漂移，结果写入文档元数据的 `source_health`，
+// 按知识库策略在来源内容明显变化时自动重新导入
+
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, QueryFilter, Set, Statement,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::knowledge_base::{KnowledgeBaseStatus, SourceCheckPolicy};
+use crate::db::entities::{document, knowledge_base, Document, KnowledgeBase};
+use crate::db::update_with_revision;
+use crate::errors::AiStudioError;
+use crate::services::duplicate_detection::{estimate_similarity, minhash_signature};
+use crate::services::egress::TenantClients;
+use crate::services::scheduler::PeriodicJob;
+use crate::services::tenant_encryption::TenantEncryptionService;
+
+/// 检查任务运行间隔，每次只检查已到检查时间的文档
+const SOURCE_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// 单次运行最多检查的文档数，避免集中请求外部站点
+const MAX_CHECKS_PER_RUN: u64 = 100;
+
+/// 请求来源地址的超时时间
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 最多跟随的重定向次数
+const MAX_REDIRECTS: usize = 5;
+
+/// 来源内容的最大字节数，超出部分不参与比较
+const MAX_SOURCE_BYTES: usize = 5 * 1024 * 1024;
+
+static SCRIPT_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<(script|style|noscript)[^>]*>.*?</(script|style|noscript)>").unwrap());
+static TAG_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+static WHITESPACE_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
+
+/// 来源健康状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceHealthStatus {
+    /// 来源可访问且内容与文档一致
+    Healthy,
+    /// 来源已重定向到其他地址
+    Redirected,
+    /// 来源内容与文档内容差异超过阈值
+    Drifted,
+    /// 来源已不存在（404/410）
+    Broken,
+    /// 请求失败或返回其他错误状态
+    Unreachable,
+}
+
+/// 文档来源健康信息，保存在文档元数据的 `source_health` 字段
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SourceHealth {
+    /// 健康状态
+    pub status: SourceHealthStatus,
+    /// HTTP 状态码，请求失败时为空
+    pub http_status: Option<u16>,
+    /// 重定向后的最终地址
+    pub final_url: Option<String>,
+    /// 来源内容与文档内容的差异（0-1）
+    pub drift: Option<f32>,
+    /// 请求失败原因
+    pub error: Option<String>,
+    /// 检查时间
+    pub checked_at: DateTime<Utc>,
+    /// 最近一次按来源内容重新导入的时间
+    pub reingested_at: Option<DateTime<Utc>>,
+}
+
+/// 来源健康报表条目
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SourceHealthEntry {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 来源地址
+    pub source_url: String,
+    /// 最近一次检查结果，尚未检查时为空
+    pub health: Option<SourceHealth>,
+}
+
+/// 知识库来源健康报表
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SourceHealthReport {
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 带有来源地址的文档数
+    pub total: u32,
+    /// 尚未检查的文档数
+    pub unchecked: u32,
+    /// 健康的文档数
+    pub healthy: u32,
+    /// 需要关注的文档，即最近一次检查结果不健康的文档
+    pub issues: Vec<SourceHealthEntry>,
+}
+
+/// 一次来源请求的结果
+#[derive(Debug, Clone)]
+struct FetchOutcome {
+    http_status: Option<u16>,
+    final_url: Option<String>,
+    text: Option<String>,
+    error: Option<String>,
+}
+
+/// 来源链接健康检查服务
+pub struct SourceHealthService {
+    db: DatabaseConnection,
+    clients: TenantClients,
+}
+
+impl SourceHealthService {
+    /// 创建检查服务
+    pub fn new(db: DatabaseConnection) -> Result<Self, AiStudioError> {
+        let clients = TenantClients::new(|builder| {
+            builder
+                .timeout(FETCH_TIMEOUT)
+                .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
+                .user_agent(concat!("aionix-source-checker/", env!("CARGO_PKG_VERSION")))
+        });
+        // 提前创建默认客户端，出站配置无效时在启动阶段报告
+        clients.default_client()?;
+        Ok(Self { db, clients })
+    }
+
+    /// 检查各知识库中已到检查时间的文档来源，返回检查的文档数
+    #[instrument(skip(self))]
+    pub async fn check_due_sources(&self) -> Result<usize, AiStudioError> {
+        let now = Utc::now();
+        let mut checked = 0;
+
+        let knowledge_bases = KnowledgeBase::find()
+            .filter(knowledge_base::Column::Status.eq(KnowledgeBaseStatus::Active))
+            .all(&self.db)
+            .await?;
+        for kb in knowledge_bases {
+            let policy = kb.get_config().map(|config| config.source_check).unwrap_or_default();
+            if !policy.enabled {
+                continue;
+            }
+            let remaining = MAX_CHECKS_PER_RUN.saturating_sub(checked as u64);
+            if remaining == 0 {
+                break;
+            }
+
+            let due_before = now - chrono::Duration::hours(policy.interval_hours.max(1) as i64);
+            for doc_id in self.due_documents(kb.id, due_before, remaining).await? {
+                if let Err(e) = self.check_document(kb.tenant_id, doc_id, &policy).await {
+                    warn!("来源检查失败: document_id={}, error={}", doc_id, e);
+                }
+                checked += 1;
+            }
+        }
+
+        if checked > 0 {
+            info!("已检查 {} 篇文档的来源链接", checked);
+        }
+        Ok(checked)
+    }
+
+    /// 立即检查单篇文档的来源并更新元数据，按文档所属租户的出站策略请求来源
+    pub async fn check_document(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+        policy: &SourceCheckPolicy,
+    ) -> Result<Option<SourceHealth>, AiStudioError> {
+        let Some(doc) = Document::find_by_id(document_id).one(&self.db).await? else {
+            return Ok(None);
+        };
+        let Some(source_url) = doc.metadata.get("source_url").and_then(|v| v.as_str()).map(str::to_string) else {
+            return Ok(None);
+        };
+
+        let outcome = self.fetch(tenant_id, &source_url).await;
+        let previous = source_health_of(&doc.metadata);
+        let now = Utc::now();
+        let content = TenantEncryptionService::from_config(self.db.clone())?
+            .decrypt(&doc.content)
+            .await?;
+        let mut health = evaluate_source(&source_url, &outcome, &content, policy.drift_threshold, now);
+        health.reingested_at = previous.and_then(|previous| previous.reingested_at);
+
+        let revision = doc.revision;
+        let mut metadata = doc.metadata.clone();
+        let mut active: document::ActiveModel = doc.into();
+        let reingest = policy.auto_reingest && health.status == SourceHealthStatus::Drifted;
+        if let (true, Some(text)) = (reingest, outcome.text) {
+            health.reingested_at = Some(now);
+            active.file_size = Set(text.len() as i64);
+            active.content_hash = Set(Some(format!("{:x}", md5::compute(&text))));
+            active.content = Set(text);
+            active.status = Set(document::DocumentStatus::Pending);
+            active.chunk_count = Set(0);
+            active.processing_started_at = Set(None);
+            active.processing_completed_at = Set(None);
+            active.error_message = Set(None);
+            if let sea_orm::ActiveValue::Unchanged(version) = &active.version {
+                active.version = Set(version + 1);
+            }
+            active.updated_at = Set(now.fixed_offset());
+            info!("来源内容已变化，重新导入文档: document_id={}, url={}", document_id, source_url);
+        }
+
+        if let Some(object) = metadata.as_object_mut() {
+            object.insert("source_health".to_string(), serde_json::to_value(&health)?);
+        }
+        active.metadata = Set(metadata);
+        update_with_revision(&self.db, active, document::Column::Revision, revision, "文档").await?;
+
+        debug!("来源检查完成: document_id={}, status={:?}", document_id, health.status);
+        Ok(Some(health))
+    }
+
+    /// 知识库来源健康报表
+    pub async fn report(&self, tenant_id: Uuid, knowledge_base_id: Uuid) -> Result<SourceHealthReport, AiStudioError> {
+        KnowledgeBase::find_by_id(knowledge_base_id)
+            .filter(knowledge_base::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("知识库"))?;
+
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT id, title, metadata->>'source_url' AS source_url, metadata->'source_health' AS source_health
+                FROM documents
+                WHERE knowledge_base_id = $1
+                  AND status <> 'archived'
+                  AND metadata->>'source_url' IS NOT NULL
+                ORDER BY title
+                "#,
+                [knowledge_base_id.into()],
+            ))
+            .await?;
+
+        let mut report = SourceHealthReport {
+            knowledge_base_id,
+            total: rows.len() as u32,
+            unchecked: 0,
+            healthy: 0,
+            issues: Vec::new(),
+        };
+        for row in rows {
+            let health = row.try_get::<Option<Value>>("", "source_health")?
+                .and_then(|value| serde_json::from_value::<SourceHealth>(value).ok());
+            match health.as_ref().map(|health| health.status) {
+                None => report.unchecked += 1,
+                Some(SourceHealthStatus::Healthy) => report.healthy += 1,
+                Some(_) => report.issues.push(SourceHealthEntry {
+                    document_id: row.try_get("", "id")?,
+                    title: row.try_get("", "title")?,
+                    source_url: row.try_get("", "source_url")?,
+                    health,
+                }),
+            }
+        }
+        Ok(report)
+    }
+
+    /// 已到检查时间的文档，从未检查过的优先
+    async fn due_documents(
+        &self,
+        knowledge_base_id: Uuid,
+        due_before: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<Vec<Uuid>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT id FROM documents
+                WHERE knowledge_base_id = $1
+                  AND status <> 'archived'
+                  AND metadata->>'source_url' IS NOT NULL
+                  AND COALESCE((metadata->'source_health'->>'checked_at')::timestamptz, '-infinity') < $2
+                ORDER BY (metadata->'source_health'->>'checked_at')::timestamptz NULLS FIRST
+                LIMIT $3
+                "#,
+                [knowledge_base_id.into(), due_before.into(), (limit as i64).into()],
+            ))
+            .await?;
+
+        rows.iter()
+            .map(|row| row.try_get::<Uuid>("", "id").map_err(AiStudioError::from))
+            .collect()
+    }
+
+    /// 按租户出站策略请求来源地址，网页内容转换为纯文本
+    async fn fetch(&self, tenant_id: Uuid, source_url: &str) -> FetchOutcome {
+        let mut outcome = FetchOutcome { http_status: None, final_url: None, text: None, error: None };
+        if !(source_url.starts_with("http://") || source_url.starts_with("https://")) {
+            outcome.error = Some("仅支持 http/https 来源地址".to_string());
+            return outcome;
+        }
+
+        let client = match self.clients.client(tenant_id).await {
+            Ok(client) => client,
+            Err(e) => {
+                outcome.error = Some(e.to_string());
+                return outcome;
+            }
+        };
+        let response = match client.get(source_url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                outcome.error = Some(e.to_string());
+                return outcome;
+            }
+        };
+        outcome.http_status = Some(response.status().as_u16());
+        outcome.final_url = Some(response.url().to_string());
+        if !response.status().is_success() {
+            return outcome;
+        }
+
+        let is_html = response.headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|content_type| content_type.contains("html"));
+        match response.bytes().await {
+            Ok(bytes) => {
+                let body = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_SOURCE_BYTES)]);
+                outcome.text = Some(if is_html { html_to_text(&body) } else { body.trim().to_string() });
+            }
+            Err(e) => outcome.error = Some(e.to_string()),
+        }
+        outcome
+    }
+}
+
+/// 读取文档元数据中的来源健康信息
+pub fn source_health_of(metadata: &Value) -> Option<SourceHealth> {
+    metadata.get("source_health").cloned().and_then(|value| serde_json::from_value(value).ok())
+}
+
+/// 根据请求结果判定来源健康状态
+///
+/// 状态按严重程度取最高者：失效 > 不可达 > 漂移 > 重定向 > 健康。
+fn evaluate_source(
+    source_url: &str,
+    outcome: &FetchOutcome,
+    content: &str,
+    drift_threshold: f32,
+    now: DateTime<Utc>,
+) -> SourceHealth {
+    let redirected = outcome.final_url.as_deref().is_some_and(|final_url| final_url != source_url);
+    let drift = outcome.text.as_deref().map(|text| content_drift(content, text));
+
+    let status = match outcome.http_status {
+        Some(404) | Some(410) => SourceHealthStatus::Broken,
+        Some(code) if !(200..300).contains(&code) => SourceHealthStatus::Unreachable,
+        None => SourceHealthStatus::Unreachable,
+        Some(_) if outcome.text.is_none() => SourceHealthStatus::Unreachable,
+        Some(_) if drift.is_some_and(|drift| drift >= drift_threshold) => SourceHealthStatus::Drifted,
+        Some(_) if redirected => SourceHealthStatus::Redirected,
+        Some(_) => SourceHealthStatus::Healthy,
+    };
+
+    SourceHealth {
+        status,
+        http_status: outcome.http_status,
+        final_url: outcome.final_url.clone().filter(|_| redirected),
+        drift,
+        error: outcome.error.clone(),
+        checked_at: now,
+        reingested_at: None,
+    }
+}
+
+/// 来源内容与文档内容的差异，取 1 减去两者的估计 Jaccard 相似度
+fn content_drift(content: &str, source_text: &str) -> f32 {
+    match (minhash_signature(content), minhash_signature(source_text)) {
+        (Some(a), Some(b)) => 1.0 - estimate_similarity(&a, &b),
+        (None, None) => 0.0,
+        _ => 1.0,
+    }
+}
+
+/// 去除脚本、样式和标签，将网页转换为纯文本
+fn html_to_text(html: &str) -> String {
+    let text = SCRIPT_PATTERN.replace_all(html, " ");
+    let text = TAG_PATTERN.replace_all(&text, " ");
+    WHITESPACE_PATTERN.replace_all(&text, " ").trim().to_string()
+}
+
+/// 来源链接检查周期任务
+pub struct SourceHealthCheckJob {
+    service: Arc<SourceHealthService>,
+}
+
+impl SourceHealthCheckJob {
+    /// 创建新的来源链接检查周期任务
+    pub fn new(service: Arc<SourceHealthService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for SourceHealthCheckJob {
+    fn name(&self) -> &str {
+        "source_health_check"
+    }
+
+    fn interval(&self) -> Duration {
+        SOURCE_CHECK_INTERVAL
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        self.service.check_due_sources().await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://docs.example.com/refund";
+    const CONTENT: &str = "Our refund policy allows customers to return any product within thirty days of delivery.";
+
+    fn outcome(http_status: Option<u16>, final_url: &str, text: Option<&str>) -> FetchOutcome {
+        FetchOutcome {
+            http_status,
+            final_url: Some(final_url.to_string()),
+            text: text.map(str::to_string),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_source_status() {
+        let now = Utc::now();
+        let check = |outcome: FetchOutcome| evaluate_source(URL, &outcome, CONTENT, 0.3, now).status;
+
+        assert_eq!(check(outcome(Some(200), URL, Some(CONTENT))), SourceHealthStatus::Healthy);
+        assert_eq!(check(outcome(Some(404), URL, None)), SourceHealthStatus::Broken);
+        assert_eq!(check(outcome(Some(503), URL, None)), SourceHealthStatus::Unreachable);
+        assert_eq!(
+            check(outcome(Some(200), "https://docs.example.com/policies/refund", Some(CONTENT))),
+            SourceHealthStatus::Redirected
+        );
+        assert_eq!(
+            check(outcome(Some(200), URL, Some("Shipping is free for orders over fifty dollars."))),
+            SourceHealthStatus::Drifted
+        );
+
+        let healthy = evaluate_source(URL, &outcome(Some(200), URL, Some(CONTENT)), CONTENT, 0.3, now);
+        assert_eq!(healthy.final_url, None);
+        assert_eq!(healthy.drift, Some(0.0));
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        let html = "<html><head><style>p { color: red; }</style><script>track()</script></head>\
+                    <body><h1>退款政策</h1>\n<p>30 天内可退款</p></body></html>";
+        assert_eq!(html_to_text(html), "退款政策 30 天内可退款");
+    }
+}
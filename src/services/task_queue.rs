@@ -1,16 +1,26 @@
 // 任务队列服务
 // 用于处理异步批量操作和长时间运行的任务
 
-use std::sync::Arc;
-use std::collections::HashMap;
-use tokio::sync::{RwLock, mpsc};
+use std::sync::{Arc, Mutex};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::{Notify, RwLock};
+use once_cell::sync::OnceCell;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn, error, debug};
+use utoipa::ToSchema;
 
 use crate::errors::AiStudioError;
 
+static GLOBAL_TASK_QUEUE: OnceCell<Arc<TaskQueueService>> = OnceCell::new();
+
+/// 默认工作线程数量
+pub const DEFAULT_WORKER_COUNT: usize = 4;
+
+/// 只处理交互任务的预留工作线程数量，保证批量任务占满其他线程时交互任务仍能立即执行
+const RESERVED_INTERACTIVE_WORKERS: usize = 1;
+
 /// 任务状态
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -33,6 +43,144 @@ pub enum TaskType {
     BatchDocumentExport,
     DocumentProcessing,
     KnowledgeBaseReindex,
+    QaTranscriptExport,
+    FinetuneDatasetBuild,
+    DuplicateDetection,
+    TopicClustering,
+    VectorMigration,
+    EmbeddingExport,
+}
+
+impl TaskType {
+    /// 任务类型的默认优先级
+    pub fn default_priority(&self) -> QueuePriority {
+        match self {
+            TaskType::DocumentProcessing | TaskType::QaTranscriptExport => QueuePriority::Interactive,
+            TaskType::KnowledgeBaseReindex => QueuePriority::Scheduled,
+            TaskType::BatchDocumentDelete
+            | TaskType::BatchDocumentUpdate
+            | TaskType::BatchDocumentReprocess
+            | TaskType::BatchDocumentImport
+            | TaskType::BatchDocumentExport
+            | TaskType::FinetuneDatasetBuild
+            | TaskType::DuplicateDetection
+            | TaskType::TopicClustering
+            | TaskType::VectorMigration
+            | TaskType::EmbeddingExport => QueuePriority::Bulk,
+        }
+    }
+}
+
+/// 任务优先级
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueuePriority {
+    /// 用户等待结果的交互任务，如单个文档重新处理
+    Interactive,
+    /// 定时同步等周期任务
+    Scheduled,
+    /// 批量导入等大批量任务
+    Bulk,
+}
+
+impl QueuePriority {
+    /// 全部优先级，按从高到低排列
+    pub const ALL: [QueuePriority; 3] = [QueuePriority::Interactive, QueuePriority::Scheduled, QueuePriority::Bulk];
+
+    /// 加权轮询中的权重
+    pub fn weight(self) -> u32 {
+        match self {
+            QueuePriority::Interactive => 6,
+            QueuePriority::Scheduled => 3,
+            QueuePriority::Bulk => 1,
+        }
+    }
+
+    fn lane(self) -> usize {
+        self as usize
+    }
+}
+
+/// 按优先级分道的待处理队列
+///
+/// 出队使用平滑加权轮询：各非空通道按权重累积额度，额度最高者出队并扣除本轮总权重，
+/// 低优先级通道不会被饿死，高优先级通道也不会被大批量任务堵住。
+#[derive(Debug, Default)]
+struct PriorityLanes {
+    lanes: [VecDeque<Uuid>; 3],
+    credits: [i64; 3],
+}
+
+impl PriorityLanes {
+    fn push(&mut self, priority: QueuePriority, task_id: Uuid) {
+        self.lanes[priority.lane()].push_back(task_id);
+    }
+
+    fn pop_weighted(&mut self) -> Option<Uuid> {
+        let mut total = 0;
+        let mut best: Option<usize> = None;
+        for priority in QueuePriority::ALL {
+            let lane = priority.lane();
+            if self.lanes[lane].is_empty() {
+                continue;
+            }
+            self.credits[lane] += priority.weight() as i64;
+            total += priority.weight() as i64;
+            if best.map_or(true, |best| self.credits[lane] > self.credits[best]) {
+                best = Some(lane);
+            }
+        }
+
+        let lane = best?;
+        self.credits[lane] -= total;
+        self.pop_lane(lane)
+    }
+
+    fn pop_priority(&mut self, priority: QueuePriority) -> Option<Uuid> {
+        self.pop_lane(priority.lane())
+    }
+
+    fn pop_lane(&mut self, lane: usize) -> Option<Uuid> {
+        let task_id = self.lanes[lane].pop_front();
+        if self.lanes[lane].is_empty() {
+            self.credits[lane] = 0;
+        }
+        task_id
+    }
+
+    fn remove(&mut self, task_id: Uuid) {
+        for lane in &mut self.lanes {
+            lane.retain(|id| *id != task_id);
+        }
+    }
+
+    fn depth(&self, priority: QueuePriority) -> usize {
+        self.lanes[priority.lane()].len()
+    }
+}
+
+/// 单个优先级通道的队列指标
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TaskQueueLaneMetrics {
+    /// 优先级
+    pub priority: QueuePriority,
+    /// 调度权重
+    pub weight: u32,
+    /// 排队中的任务数
+    pub queued: usize,
+    /// 执行中的任务数
+    pub running: usize,
+}
+
+/// 任务队列指标
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TaskQueueMetrics {
+    /// 工作线程总数
+    pub workers: usize,
+    /// 预留给交互任务的工作线程数
+    pub reserved_interactive_workers: usize,
+    /// 各优先级通道指标
+    pub lanes: Vec<TaskQueueLaneMetrics>,
 }
 
 /// 任务信息
@@ -42,6 +190,8 @@ pub struct TaskInfo {
     pub id: Uuid,
     /// 任务类型
     pub task_type: TaskType,
+    /// 优先级
+    pub priority: QueuePriority,
     /// 租户 ID
     pub tenant_id: Uuid,
     /// 任务状态
@@ -84,31 +234,59 @@ pub trait TaskExecutor: Send + Sync {
 pub struct TaskQueueService {
     /// 任务存储
     tasks: Arc<RwLock<HashMap<Uuid, TaskInfo>>>,
-    /// 任务发送器
-    task_sender: mpsc::UnboundedSender<Uuid>,
+    /// 按优先级分道的待处理任务
+    lanes: Arc<Mutex<PriorityLanes>>,
+    /// 有新任务入队时唤醒工作线程
+    notify: Arc<Notify>,
     /// 任务执行器
     executors: Arc<RwLock<HashMap<TaskType, Arc<dyn TaskExecutor>>>>,
+    /// 工作线程总数
+    workers: usize,
 }
 
 impl TaskQueueService {
     /// 创建新的任务队列服务
     pub fn new() -> Self {
-        let tasks = Arc::new(RwLock::new(HashMap::new()));
-        let (task_sender, task_receiver) = mpsc::unbounded_channel();
-        let executors = Arc::new(RwLock::new(HashMap::new()));
-        
+        Self::with_workers(DEFAULT_WORKER_COUNT)
+    }
+
+    /// 创建指定工作线程数量的任务队列服务，其中一个线程预留给交互任务
+    pub fn with_workers(workers: usize) -> Self {
+        let workers = workers.max(RESERVED_INTERACTIVE_WORKERS + 1);
         let service = Self {
-            tasks: tasks.clone(),
-            task_sender,
-            executors: executors.clone(),
+            tasks: Arc::new(RwLock::new(HashMap::new())),
+            lanes: Arc::new(Mutex::new(PriorityLanes::default())),
+            notify: Arc::new(Notify::new()),
+            executors: Arc::new(RwLock::new(HashMap::new())),
+            workers,
         };
-        
+
         // 启动任务处理器
-        tokio::spawn(Self::task_processor(tasks, task_receiver, executors));
-        
+        for index in 0..workers {
+            let reserved = (index < RESERVED_INTERACTIVE_WORKERS).then_some(QueuePriority::Interactive);
+            tokio::spawn(Self::task_processor(
+                reserved,
+                service.tasks.clone(),
+                service.lanes.clone(),
+                service.notify.clone(),
+                service.executors.clone(),
+            ));
+        }
+
         service
     }
     
+    /// 安装全局任务队列，应用启动时调用一次
+    pub fn install_global(queue: Arc<TaskQueueService>) -> Result<(), AiStudioError> {
+        GLOBAL_TASK_QUEUE.set(queue)
+            .map_err(|_| AiStudioError::internal("任务队列已经初始化"))
+    }
+
+    /// 全局任务队列
+    pub fn global() -> Option<Arc<TaskQueueService>> {
+        GLOBAL_TASK_QUEUE.get().cloned()
+    }
+    
     /// 注册任务执行器
     pub async fn register_executor(&self, executor: Arc<dyn TaskExecutor>) {
         let mut executors = self.executors.write().await;
@@ -117,13 +295,26 @@ impl TaskQueueService {
         }
     }
     
-    /// 提交任务
+    /// 提交任务，使用任务类型的默认优先级
     pub async fn submit_task(
         &self,
         task_type: TaskType,
         tenant_id: Uuid,
         parameters: serde_json::Value,
         total_count: Option<u32>,
+    ) -> Result<Uuid, AiStudioError> {
+        let priority = task_type.default_priority();
+        self.submit_task_with_priority(task_type, priority, tenant_id, parameters, total_count).await
+    }
+
+    /// 以指定优先级提交任务
+    pub async fn submit_task_with_priority(
+        &self,
+        task_type: TaskType,
+        priority: QueuePriority,
+        tenant_id: Uuid,
+        parameters: serde_json::Value,
+        total_count: Option<u32>,
     ) -> Result<Uuid, AiStudioError> {
         let task_id = Uuid::new_v4();
         let now = Utc::now();
@@ -131,6 +322,7 @@ impl TaskQueueService {
         let task = TaskInfo {
             id: task_id,
             task_type: task_type.clone(),
+            priority,
             tenant_id,
             status: TaskStatus::Pending,
             parameters,
@@ -152,15 +344,42 @@ impl TaskQueueService {
             tasks.insert(task_id, task);
         }
         
-        // 发送任务到处理队列
-        self.task_sender.send(task_id).map_err(|e| {
-            error!("发送任务到队列失败: {}", e);
-            AiStudioError::internal("任务队列错误")
-        })?;
+        // 放入对应优先级通道
+        self.lanes.lock().unwrap().push(priority, task_id);
+        self.notify.notify_waiters();
         
-        info!("任务已提交: id={}, type={:?}", task_id, task_type);
+        info!("任务已提交: id={}, type={:?}, priority={:?}", task_id, task_type, priority);
         Ok(task_id)
     }
+
+    /// 各优先级通道的排队与执行情况
+    pub async fn metrics(&self) -> TaskQueueMetrics {
+        let queued: Vec<usize> = {
+            let lanes = self.lanes.lock().unwrap();
+            QueuePriority::ALL.iter().map(|priority| lanes.depth(*priority)).collect()
+        };
+        let tasks = self.tasks.read().await;
+
+        let lanes = QueuePriority::ALL
+            .into_iter()
+            .zip(queued)
+            .map(|(priority, queued)| TaskQueueLaneMetrics {
+                priority,
+                weight: priority.weight(),
+                queued,
+                running: tasks
+                    .values()
+                    .filter(|task| task.priority == priority && task.status == TaskStatus::Running)
+                    .count(),
+            })
+            .collect();
+
+        TaskQueueMetrics {
+            workers: self.workers,
+            reserved_interactive_workers: RESERVED_INTERACTIVE_WORKERS,
+            lanes,
+        }
+    }
     
     /// 获取任务状态
     pub async fn get_task_status(&self, task_id: Uuid) -> Option<TaskInfo> {
@@ -182,6 +401,9 @@ impl TaskQueueService {
         let mut tasks = self.tasks.write().await;
         if let Some(task) = tasks.get_mut(&task_id) {
             if task.status == TaskStatus::Pending || task.status == TaskStatus::Running {
+                if task.status == TaskStatus::Pending {
+                    self.lanes.lock().unwrap().remove(task_id);
+                }
                 task.status = TaskStatus::Cancelled;
                 task.completed_at = Some(Utc::now());
                 info!("任务已取消: id={}", task_id);
@@ -211,14 +433,35 @@ impl TaskQueueService {
     }
     
     /// 任务处理器
+    ///
+    /// `reserved` 为 Some 时只处理该优先级的任务，否则按权重从各通道取任务。
     async fn task_processor(
+        reserved: Option<QueuePriority>,
         tasks: Arc<RwLock<HashMap<Uuid, TaskInfo>>>,
-        mut task_receiver: mpsc::UnboundedReceiver<Uuid>,
+        lanes: Arc<Mutex<PriorityLanes>>,
+        notify: Arc<Notify>,
         executors: Arc<RwLock<HashMap<TaskType, Arc<dyn TaskExecutor>>>>,
     ) {
-        info!("任务处理器已启动");
+        info!("任务处理器已启动: reserved={:?}", reserved);
         
-        while let Some(task_id) = task_receiver.recv().await {
+        loop {
+            // 先登记唤醒再检查队列，避免检查与等待之间入队的任务被错过
+            let notified = notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let next = {
+                let mut lanes = lanes.lock().unwrap();
+                match reserved {
+                    Some(priority) => lanes.pop_priority(priority),
+                    None => lanes.pop_weighted(),
+                }
+            };
+            let Some(task_id) = next else {
+                notified.await;
+                continue;
+            };
+
             // 获取任务
             let mut task = {
                 let mut tasks_guard = tasks.write().await;
@@ -280,8 +523,6 @@ impl TaskQueueService {
                 }
             }
         }
-        
-        info!("任务处理器已停止");
     }
     
     /// 启动定期清理任务
@@ -407,4 +648,37 @@ mod tests {
         let task = service.get_task_status(task_id).await.unwrap();
         assert_eq!(task.status, TaskStatus::Cancelled);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_priority_lanes_weighted_dispatch() {
+        let mut lanes = PriorityLanes::default();
+        let bulk: Vec<Uuid> = (0..20).map(|_| Uuid::new_v4()).collect();
+        for id in &bulk {
+            lanes.push(QueuePriority::Bulk, *id);
+        }
+        let interactive = Uuid::new_v4();
+        lanes.push(QueuePriority::Interactive, interactive);
+
+        // 大量批量任务排在前面时，交互任务仍然最先出队
+        assert_eq!(lanes.pop_weighted(), Some(interactive));
+        assert_eq!(lanes.depth(QueuePriority::Bulk), 20);
+
+        // 交互与定时任务持续涌入时，批量任务也能按权重获得调度
+        for _ in 0..10 {
+            lanes.push(QueuePriority::Interactive, Uuid::new_v4());
+            lanes.push(QueuePriority::Scheduled, Uuid::new_v4());
+            lanes.push(QueuePriority::Bulk, Uuid::new_v4());
+        }
+        for _ in 0..10 {
+            assert!(lanes.pop_weighted().is_some());
+        }
+        // 10 次出队按 6:3:1 分配
+        assert_eq!(lanes.depth(QueuePriority::Interactive), 4);
+        assert_eq!(lanes.depth(QueuePriority::Scheduled), 7);
+        assert_eq!(lanes.depth(QueuePriority::Bulk), 29);
+
+        assert!(lanes.pop_priority(QueuePriority::Interactive).is_some());
+        lanes.remove(bulk[1]);
+        assert_eq!(lanes.depth(QueuePriority::Bulk), 28);
+    }
+}
@@ -9,8 +9,10 @@ use utoipa::ToSchema;
 use sea_orm::{EntityTrait, ColumnTrait, QueryFilter, ActiveModelTrait, QuerySelect, Set, PaginatorTrait, QueryOrder};
 
 use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace};
+use crate::services::tenant_encryption::shred_tenant_keys;
 use crate::db::entities::{Tenant, tenant, user};
-use crate::db::DatabaseManager;
+use crate::db::{DatabaseManager, update_with_revision};
 use crate::api::{PaginationQuery, PaginatedResponse};
 use crate::api::models::PaginationInfo;
 use sea_orm::DatabaseConnection;
@@ -113,6 +115,8 @@ pub struct TenantResponse {
     pub created_at: chrono::DateTime<Utc>,
     pub updated_at: chrono::DateTime<Utc>,
     pub last_active_at: Option<chrono::DateTime<Utc>>,
+    /// 修订号，更新时通过 If-Match 携带
+    pub revision: i32,
 }
 
 /// 租户统计响应
@@ -186,6 +190,7 @@ impl TenantService {
             created_at: Set(now.into()),
             updated_at: Set(now.into()),
             last_active_at: Set(Some(now.into())),
+            revision: Set(1),
         };
 
         let created_tenant = tenant.insert(&self.db).await?;
@@ -207,6 +212,7 @@ impl TenantService {
             created_at: created_tenant.created_at.into(),
             updated_at: created_tenant.updated_at.into(),
             last_active_at: created_tenant.last_active_at.map(|dt| dt.into()),
+            revision: created_tenant.revision,
         })
     }
 
@@ -234,8 +240,15 @@ impl TenantService {
     }
 
     /// 更新租户
+    ///
+    /// `expected_revision` 为客户端持有的修订号，缺省时以本次读取到的修订号为准
     #[instrument(skip(self, request))]
-    pub async fn update_tenant(&self, tenant_id: Uuid, request: UpdateTenantRequest) -> Result<TenantResponse, AiStudioError> {
+    pub async fn update_tenant(
+        &self,
+        tenant_id: Uuid,
+        request: UpdateTenantRequest,
+        expected_revision: Option<i32>,
+    ) -> Result<TenantResponse, AiStudioError> {
         info!(tenant_id = %tenant_id, "更新租户");
 
         let tenant = Tenant::find_by_id(tenant_id)
@@ -243,6 +256,7 @@ impl TenantService {
             .await?
             .ok_or_else(|| AiStudioError::not_found("租户"))?;
 
+        let expected_revision = expected_revision.unwrap_or(tenant.revision);
         let mut active_tenant: tenant::ActiveModel = tenant.into();
 
         // 更新字段
@@ -270,7 +284,15 @@ impl TenantService {
 
         active_tenant.updated_at = Set(Utc::now().into());
 
-        let updated_tenant = active_tenant.update(&self.db).await?;
+        let updated_tenant = update_with_revision(
+            &self.db,
+            active_tenant,
+            tenant::Column::Revision,
+            expected_revision,
+            "租户",
+        )
+        .await?;
+        cache::invalidate(CacheNamespace::Tenant, tenant_id).await;
 
         info!(tenant_id = %tenant_id, "租户更新成功");
 
@@ -288,6 +310,12 @@ impl TenantService {
             .await?
             .ok_or_else(|| AiStudioError::not_found("租户"))?;
 
+        // 先销毁数据密钥，使备份或副本中残留的加密内容同样无法恢复
+        let shredded = shred_tenant_keys(&self.db, tenant_id).await?;
+        if shredded > 0 {
+            info!(tenant_id = %tenant_id, shredded, "租户数据密钥已销毁");
+        }
+
         // 删除租户下的所有用户
         user::Entity::delete_many()
             .filter(user::Column::TenantId.eq(tenant_id))
@@ -299,6 +327,9 @@ impl TenantService {
             .exec(&self.db)
             .await?;
 
+        // 租户缓存失效后，其下用户的鉴权检查随之失败，无需逐个清理用户缓存
+        cache::invalidate(CacheNamespace::Tenant, tenant_id).await;
+
         info!(tenant_id = %tenant_id, "租户删除成功");
 
         Ok(())
@@ -448,7 +479,7 @@ impl TenantService {
             ..Default::default()
         };
 
-        self.update_tenant(tenant_id, request).await
+        self.update_tenant(tenant_id, request, None).await
     }
 
     /// 激活租户
@@ -461,7 +492,7 @@ impl TenantService {
             ..Default::default()
         };
 
-        self.update_tenant(tenant_id, request).await
+        self.update_tenant(tenant_id, request, None).await
     }
 
     // 私有辅助方法
@@ -536,6 +567,7 @@ impl TenantService {
             created_at: tenant.created_at.into(),
             updated_at: tenant.updated_at.into(),
             last_active_at: tenant.last_active_at.map(|dt| dt.into()),
+            revision: tenant.revision,
         })
     }
 }
\ No newline at end of file
@@ -0,0 +1,281 @@
+// 租户活动时间线服务
+// 从审计日志汇总租户内的业务变更（添加文档、修改智能体、发布工作流、邀请成员），
+// 按时间倒序以游标分页返回，租户管理员无需阅读原始审计日志即可了解近期变更
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, TimeZone, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult, Statement};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::errors::AiStudioError;
+use crate::plugins::event_bus::event_types;
+
+/// 默认每页条数
+const DEFAULT_LIMIT: u64 = 50;
+
+/// 每页最大条数
+const MAX_LIMIT: u64 = 200;
+
+/// 活动类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityType {
+    /// 添加了文档
+    DocumentAdded,
+    /// 创建或修改了智能体
+    AgentModified,
+    /// 发布了工作流
+    WorkflowPublished,
+    /// 邀请了成员
+    MemberInvited,
+}
+
+impl ActivityType {
+    pub const ALL: [ActivityType; 4] = [
+        ActivityType::DocumentAdded,
+        ActivityType::AgentModified,
+        ActivityType::WorkflowPublished,
+        ActivityType::MemberInvited,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DocumentAdded => "document_added",
+            Self::AgentModified => "agent_modified",
+            Self::WorkflowPublished => "workflow_published",
+            Self::MemberInvited => "member_invited",
+        }
+    }
+
+    /// 对应的审计事件类型
+    pub fn actions(&self) -> &'static [&'static str] {
+        match self {
+            Self::DocumentAdded => &[event_types::DOCUMENT_CREATED],
+            Self::AgentModified => &[event_types::AGENT_CREATED, event_types::AGENT_UPDATED],
+            Self::WorkflowPublished => &[event_types::WORKFLOW_PUBLISHED],
+            Self::MemberInvited => &[event_types::MEMBER_INVITED],
+        }
+    }
+
+    /// 由审计事件类型得出活动类型
+    pub fn from_action(action: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.actions().contains(&action))
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == value)
+    }
+}
+
+/// 活动时间线查询参数
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+pub struct ActivityQuery {
+    /// 活动类型，逗号分隔，如 `document_added,workflow_published`；默认全部类型
+    pub types: Option<String>,
+    /// 上一页返回的 `next_cursor`
+    pub cursor: Option<String>,
+    /// 每页条数，默认 50，最多 200
+    pub limit: Option<u64>,
+}
+
+/// 活动记录
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ActivityItem {
+    /// 审计记录 ID
+    pub id: Uuid,
+    pub activity_type: ActivityType,
+    /// 原始审计事件类型
+    pub action: String,
+    /// 操作人
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<Uuid>,
+    /// 可直接展示的活动描述
+    pub summary: String,
+    pub details: serde_json::Value,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// 活动时间线分页结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ActivityPage {
+    pub items: Vec<ActivityItem>,
+    /// 下一页游标，没有更多记录时为空
+    pub next_cursor: Option<String>,
+}
+
+/// 分页游标，指向上一页最后一条记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ActivityCursor {
+    created_at: DateTime<Utc>,
+    id: Uuid,
+}
+
+impl ActivityCursor {
+    fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}:{}", self.created_at.timestamp_micros(), self.id))
+    }
+
+    fn decode(value: &str) -> Result<Self, AiStudioError> {
+        let invalid = || AiStudioError::validation("cursor", "无效的分页游标");
+        let raw = URL_SAFE_NO_PAD.decode(value).map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let (micros, id) = raw.split_once(':').ok_or_else(invalid)?;
+        let micros: i64 = micros.parse().map_err(|_| invalid())?;
+        Ok(Self {
+            created_at: Utc.timestamp_micros(micros).single().ok_or_else(invalid)?,
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// 租户活动时间线服务
+pub struct TenantActivityService {
+    db: DatabaseConnection,
+}
+
+impl TenantActivityService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 按时间倒序分页查询租户活动
+    pub async fn list(&self, tenant_id: Uuid, query: &ActivityQuery) -> Result<ActivityPage, AiStudioError> {
+        let types = parse_types(query.types.as_deref())?;
+        let actions: Vec<String> = types
+            .iter()
+            .flat_map(|t| t.actions().iter().map(|a| a.to_string()))
+            .collect();
+        let cursor = query.cursor.as_deref().map(ActivityCursor::decode).transpose()?;
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        // 多取一条判断是否还有下一页
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT a.id, a.user_id, a.action, a.resource_type, a.resource_id, a.details, a.created_at,
+                       u.username AS actor_name
+                FROM audit_logs a
+                LEFT JOIN users u ON u.id = a.user_id
+                WHERE a.tenant_id = $1
+                  AND a.action = ANY($2)
+                  AND ($3::timestamptz IS NULL OR (a.created_at, a.id) < ($3, $4))
+                ORDER BY a.created_at DESC, a.id DESC
+                LIMIT $5
+                "#,
+                [
+                    tenant_id.into(),
+                    actions.into(),
+                    cursor.map(|c| c.created_at).into(),
+                    cursor.map(|c| c.id).into(),
+                    (limit as i64 + 1).into(),
+                ],
+            ))
+            .await?;
+
+        let mut items = rows.iter().map(activity_from_row).collect::<Result<Vec<_>, _>>()?;
+        let next_cursor = if items.len() as u64 > limit {
+            items.truncate(limit as usize);
+            items.last().map(|item| ActivityCursor { created_at: item.occurred_at, id: item.id }.encode())
+        } else {
+            None
+        };
+
+        Ok(ActivityPage { items, next_cursor })
+    }
+}
+
+/// 解析活动类型过滤，未指定时返回全部类型
+fn parse_types(value: Option<&str>) -> Result<Vec<ActivityType>, AiStudioError> {
+    let Some(value) = value.filter(|v| !v.trim().is_empty()) else {
+        return Ok(ActivityType::ALL.to_vec());
+    };
+
+    let mut types = Vec::new();
+    for name in value.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let activity_type = ActivityType::parse(name)
+            .ok_or_else(|| AiStudioError::validation("types", format!("未知的活动类型: {}", name)))?;
+        if !types.contains(&activity_type) {
+            types.push(activity_type);
+        }
+    }
+    Ok(types)
+}
+
+/// 生成活动描述
+fn render_summary(activity_type: ActivityType, action: &str, actor: Option<&str>, details: &serde_json::Value) -> String {
+    let actor = actor.unwrap_or("系统");
+    let field = |key: &str| details.get(key).and_then(|v| v.as_str()).unwrap_or("未命名");
+    match activity_type {
+        ActivityType::DocumentAdded => format!("{} 添加了文档「{}」", actor, field("title")),
+        ActivityType::AgentModified if action == event_types::AGENT_CREATED => {
+            format!("{} 创建了智能体「{}」", actor, field("name"))
+        }
+        ActivityType::AgentModified => format!("{} 修改了智能体「{}」", actor, field("name")),
+        ActivityType::WorkflowPublished => format!("{} 发布了工作流「{}」", actor, field("name")),
+        ActivityType::MemberInvited => format!("{} 邀请了成员 {}", actor, field("email")),
+    }
+}
+
+fn activity_from_row(row: &QueryResult) -> Result<ActivityItem, AiStudioError> {
+    let action: String = row.try_get("", "action")?;
+    let activity_type = ActivityType::from_action(&action)
+        .ok_or_else(|| AiStudioError::internal(format!("未知的活动事件类型: {}", action)))?;
+    let actor_name: Option<String> = row.try_get("", "actor_name")?;
+    let details: serde_json::Value = row.try_get("", "details")?;
+
+    Ok(ActivityItem {
+        id: row.try_get("", "id")?,
+        activity_type,
+        summary: render_summary(activity_type, &action, actor_name.as_deref(), &details),
+        action,
+        actor_id: row.try_get("", "user_id")?,
+        actor_name,
+        resource_type: row.try_get("", "resource_type")?,
+        resource_id: row.try_get("", "resource_id")?,
+        details,
+        occurred_at: row.try_get("", "created_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = ActivityCursor {
+            created_at: Utc.timestamp_micros(1_714_550_400_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+        assert_eq!(ActivityCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(ActivityCursor::decode("not-a-cursor").is_err());
+    }
+
+    #[test]
+    fn test_parse_types() {
+        assert_eq!(parse_types(None).unwrap().len(), ActivityType::ALL.len());
+        assert_eq!(
+            parse_types(Some("workflow_published, document_added,workflow_published")).unwrap(),
+            vec![ActivityType::WorkflowPublished, ActivityType::DocumentAdded]
+        );
+        assert!(parse_types(Some("document_deleted")).is_err());
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let details = serde_json::json!({ "name": "客服助手" });
+        assert_eq!(
+            render_summary(ActivityType::AgentModified, event_types::AGENT_UPDATED, Some("alice"), &details),
+            "alice 修改了智能体「客服助手」"
+        );
+        assert_eq!(ActivityType::from_action(event_types::AGENT_CREATED), Some(ActivityType::AgentModified));
+        assert_eq!(ActivityType::from_action(event_types::DOCUMENT_DELETED), None);
+    }
+}
@@ -0,0 +1,566 @@
+// 租户内容加密服务
+// 文档正文与原始文件按租户的数据密钥加密落库（信封加密）：数据密钥由平台主密钥、租户提供的密钥或外部 KMS 包装，
+// 密文携带密钥 ID，读取时透明解密；支持密钥轮换、吊销，租户删除时销毁全部密钥使密文不可恢复
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use rand::{rngs::OsRng, RngCore};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::{ConfigLoader, KmsConfig, SecurityConfig};
+use crate::db::entities::document;
+use crate::errors::AiStudioError;
+use crate::services::egress;
+use crate::services::plugin_config::SecretCipher;
+
+/// 密文前缀，后接密钥 ID 与 base64 密文
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// 数据密钥长度（字节）
+const DATA_KEY_LEN: usize = 32;
+
+/// 重新加密时每批处理的文档数
+const REENCRYPT_BATCH_SIZE: i64 = 100;
+
+/// 数据密钥来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    /// 平台生成，由主密钥包装
+    Managed,
+    /// 租户提供，由主密钥包装
+    Provided,
+    /// 平台生成，由外部 KMS 中租户的主密钥包装
+    Kms,
+}
+
+/// 数据密钥状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStatus {
+    /// 用于加密新内容
+    Active,
+    /// 已轮换，仅用于解密
+    Retired,
+    /// 已吊销，密钥材料已销毁
+    Revoked,
+}
+
+impl KeySource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            KeySource::Managed => "managed",
+            KeySource::Provided => "provided",
+            KeySource::Kms => "kms",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self, AiStudioError> {
+        match value {
+            "managed" => Ok(KeySource::Managed),
+            "provided" => Ok(KeySource::Provided),
+            "kms" => Ok(KeySource::Kms),
+            other => Err(AiStudioError::internal(format!("未知的密钥来源: {}", other))),
+        }
+    }
+}
+
+impl KeyStatus {
+    fn parse(value: &str) -> Result<Self, AiStudioError> {
+        match value {
+            "active" => Ok(KeyStatus::Active),
+            "retired" => Ok(KeyStatus::Retired),
+            "revoked" => Ok(KeyStatus::Revoked),
+            other => Err(AiStudioError::internal(format!("未知的密钥状态: {}", other))),
+        }
+    }
+}
+
+/// 轮换（或首次启用）数据密钥请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct RotateTenantKeyRequest {
+    /// 密钥来源
+    pub source: KeySource,
+    /// 租户提供的密钥材料（base64，32 字节），来源为 provided 时必填
+    pub key_material: Option<String>,
+    /// KMS 中的主密钥 ID，来源为 kms 时必填
+    pub kms_key_id: Option<String>,
+}
+
+/// 数据密钥信息（不含密钥材料）
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TenantKeyInfo {
+    /// 密钥 ID
+    pub id: Uuid,
+    /// 版本号，每次轮换递增
+    pub version: i32,
+    /// 来源
+    pub source: KeySource,
+    /// KMS 主密钥 ID
+    pub kms_key_id: Option<String>,
+    /// 状态
+    pub status: KeyStatus,
+    /// 创建人
+    pub created_by: Option<Uuid>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 轮换时间
+    pub retired_at: Option<DateTime<Utc>>,
+    /// 吊销时间
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// 吊销结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RevokeTenantKeysResponse {
+    /// 租户 ID
+    pub tenant_id: Uuid,
+    /// 吊销的密钥数
+    pub revoked_keys: u64,
+    /// 吊销时间
+    pub revoked_at: DateTime<Utc>,
+}
+
+/// 外部 KMS 客户端
+///
+/// 通过 `POST {url}/keys/{key_id}/wrap` 与 `/unwrap` 包装和解包数据密钥，请求与响应均为 base64 编码。
+struct KmsClient {
+    http: reqwest::Client,
+    url: String,
+    token: Option<String>,
+}
+
+impl KmsClient {
+    fn new(config: &KmsConfig) -> Result<Self, AiStudioError> {
+        let http = egress::client_builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| AiStudioError::internal(format!("创建 KMS 客户端失败: {}", e)))?;
+        Ok(Self {
+            http,
+            url: config.url.trim_end_matches('/').to_string(),
+            token: config.token.clone().filter(|token| !token.is_empty()),
+        })
+    }
+
+    async fn call(&self, key_id: &str, operation: &str, field: &str, value: &str) -> Result<String, AiStudioError> {
+        let mut request = self.http
+            .post(format!("{}/keys/{}/{}", self.url, key_id, operation))
+            .json(&json!({ field: value }));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await
+            .map_err(|e| AiStudioError::external_service("KMS", e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(AiStudioError::external_service("KMS", format!("{} 返回状态 {}", operation, status)));
+        }
+        let body: serde_json::Value = response.json().await
+            .map_err(|e| AiStudioError::external_service("KMS", e.to_string()))?;
+        let output = if operation == "wrap" { "ciphertext" } else { "plaintext" };
+        body.get(output)
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| AiStudioError::external_service("KMS", format!("{} 响应缺少 {} 字段", operation, output)))
+    }
+
+    async fn wrap(&self, key_id: &str, data_key: &str) -> Result<String, AiStudioError> {
+        self.call(key_id, "wrap", "plaintext", data_key).await
+    }
+
+    async fn unwrap(&self, key_id: &str, wrapped: &str) -> Result<String, AiStudioError> {
+        self.call(key_id, "unwrap", "ciphertext", wrapped).await
+    }
+}
+
+/// 租户内容加密服务
+///
+/// 租户没有有效密钥时内容按明文存储；解密时未加密的内容原样返回，因此启用加密前写入的数据无需迁移即可读取。
+pub struct TenantEncryptionService {
+    db: DatabaseConnection,
+    master: SecretCipher,
+    kms: Option<KmsClient>,
+    /// 已解包的数据密钥，按密钥 ID 缓存，仅在服务实例内有效
+    data_keys: Mutex<HashMap<Uuid, SecretCipher>>,
+}
+
+impl TenantEncryptionService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection, security: &SecurityConfig) -> Result<Self, AiStudioError> {
+        Ok(Self {
+            db,
+            master: SecretCipher::new(&security.encryption_key),
+            kms: security.kms.as_ref().map(KmsClient::new).transpose()?,
+            data_keys: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// 按应用配置创建服务
+    pub fn from_config(db: DatabaseConnection) -> Result<Self, AiStudioError> {
+        Self::new(db, &ConfigLoader::get().security)
+    }
+
+    /// 列出租户的数据密钥
+    pub async fn list_keys(&self, tenant_id: Uuid) -> Result<Vec<TenantKeyInfo>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT id, version, source, kms_key_id, status, created_by, created_at, retired_at, revoked_at
+                FROM tenant_encryption_keys
+                WHERE tenant_id = $1
+                ORDER BY version DESC
+                "#,
+                [tenant_id.into()],
+            ))
+            .await?;
+
+        let mut keys = Vec::with_capacity(rows.len());
+        for row in rows {
+            let source: String = row.try_get("", "source")?;
+            let status: String = row.try_get("", "status")?;
+            let created_at: DateTime<Utc> = row.try_get("", "created_at")?;
+            keys.push(TenantKeyInfo {
+                id: row.try_get("", "id")?,
+                version: row.try_get("", "version")?,
+                source: KeySource::parse(&source)?,
+                kms_key_id: row.try_get("", "kms_key_id")?,
+                status: KeyStatus::parse(&status)?,
+                created_by: row.try_get("", "created_by")?,
+                created_at,
+                retired_at: row.try_get("", "retired_at")?,
+                revoked_at: row.try_get("", "revoked_at")?,
+            });
+        }
+        Ok(keys)
+    }
+
+    /// 生成新的数据密钥并设为有效，原有效密钥转为仅解密
+    ///
+    /// 首次调用即为租户启用内容加密。已有内容需调用 [`Self::reencrypt_documents`] 改用新密钥。
+    #[instrument(skip(self, request))]
+    pub async fn rotate_key(
+        &self,
+        tenant_id: Uuid,
+        request: RotateTenantKeyRequest,
+        created_by: Uuid,
+    ) -> Result<TenantKeyInfo, AiStudioError> {
+        let data_key = match request.source {
+            KeySource::Provided => {
+                let material = request.key_material.as_deref()
+                    .ok_or_else(|| AiStudioError::validation("key_material", "来源为 provided 时必须提供密钥材料"))?;
+                let decoded = BASE64.decode(material)
+                    .map_err(|_| AiStudioError::validation("key_material", "密钥材料必须为 base64 编码"))?;
+                if decoded.len() != DATA_KEY_LEN {
+                    return Err(AiStudioError::validation("key_material", format!("密钥材料必须为 {} 字节", DATA_KEY_LEN)));
+                }
+                material.to_string()
+            }
+            KeySource::Managed | KeySource::Kms => generate_data_key(),
+        };
+        let (wrapped_key, kms_key_id) = match request.source {
+            KeySource::Kms => {
+                let kms = self.kms.as_ref()
+                    .ok_or_else(|| AiStudioError::validation("source", "未配置 KMS，无法使用 kms 来源"))?;
+                let key_id = request.kms_key_id
+                    .filter(|key_id| !key_id.is_empty())
+                    .ok_or_else(|| AiStudioError::validation("kms_key_id", "来源为 kms 时必须提供 KMS 主密钥 ID"))?;
+                (kms.wrap(&key_id, &data_key).await?, Some(key_id))
+            }
+            KeySource::Managed | KeySource::Provided => (self.master.encrypt(&data_key)?, None),
+        };
+
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let txn = self.db.begin().await?;
+        txn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE tenant_encryption_keys SET status = 'retired', retired_at = $2 WHERE tenant_id = $1 AND status = 'active'",
+            [tenant_id.into(), now.into()],
+        ))
+        .await?;
+        let row = txn
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO tenant_encryption_keys (id, tenant_id, version, source, kms_key_id, wrapped_key, status, created_by, created_at)
+                SELECT $1, $2, COALESCE(MAX(version), 0) + 1, $3, $4, $5, 'active', $6, $7
+                FROM tenant_encryption_keys WHERE tenant_id = $2
+                RETURNING version
+                "#,
+                [
+                    id.into(),
+                    tenant_id.into(),
+                    request.source.as_str().into(),
+                    kms_key_id.clone().into(),
+                    wrapped_key.into(),
+                    created_by.into(),
+                    now.into(),
+                ],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::internal("写入数据密钥失败"))?;
+        txn.commit().await?;
+
+        let version: i32 = row.try_get("", "version")?;
+        info!(tenant_id = %tenant_id, version, source = request.source.as_str(), "租户数据密钥已轮换");
+        Ok(TenantKeyInfo {
+            id,
+            version,
+            source: request.source,
+            kms_key_id,
+            status: KeyStatus::Active,
+            created_by: Some(created_by),
+            created_at: now,
+            retired_at: None,
+            revoked_at: None,
+        })
+    }
+
+    /// 吊销租户的全部数据密钥
+    ///
+    /// 包装后的密钥材料被清除，此后该租户已加密的内容无法再解密；新写入的内容按明文存储，直至再次轮换密钥。
+    #[instrument(skip(self))]
+    pub async fn revoke_keys(&self, tenant_id: Uuid) -> Result<RevokeTenantKeysResponse, AiStudioError> {
+        let revoked_at = Utc::now();
+        let revoked_keys = shred_tenant_keys(&self.db, tenant_id).await?;
+        self.data_keys.lock().unwrap().clear();
+
+        warn!(tenant_id = %tenant_id, revoked_keys, "租户数据密钥已吊销，已加密内容不可恢复");
+        Ok(RevokeTenantKeysResponse { tenant_id, revoked_keys, revoked_at })
+    }
+
+    /// 以租户的有效密钥加密内容，租户未启用加密时原样返回
+    pub async fn encrypt(&self, tenant_id: Uuid, plaintext: &str) -> Result<String, AiStudioError> {
+        let Some((key_id, cipher)) = self.active_key(tenant_id).await? else {
+            return Ok(plaintext.to_string());
+        };
+        Ok(format!("{}{}:{}", ENCRYPTED_PREFIX, key_id, cipher.encrypt(plaintext)?))
+    }
+
+    /// 解密内容，未加密的内容原样返回
+    pub async fn decrypt(&self, stored: &str) -> Result<String, AiStudioError> {
+        let Some((key_id, payload)) = parse_ciphertext(stored) else {
+            return Ok(stored.to_string());
+        };
+        self.data_key(key_id)
+            .await?
+            .decrypt(payload)
+            .map_err(|_| AiStudioError::internal("解密文档内容失败，数据密钥可能已被替换"))
+    }
+
+    /// 加密待写入的文档正文与原始文件内容
+    pub async fn encrypt_document(&self, tenant_id: Uuid, model: &mut document::ActiveModel) -> Result<(), AiStudioError> {
+        if let sea_orm::ActiveValue::Set(content) = &model.content {
+            model.content = sea_orm::Set(self.encrypt(tenant_id, content).await?);
+        }
+        if let sea_orm::ActiveValue::Set(Some(raw_content)) = &model.raw_content {
+            model.raw_content = sea_orm::Set(Some(self.encrypt(tenant_id, raw_content).await?));
+        }
+        Ok(())
+    }
+
+    /// 就地解密文档正文与原始文件内容
+    pub async fn decrypt_document(&self, doc: &mut document::Model) -> Result<(), AiStudioError> {
+        doc.content = self.decrypt(&doc.content).await?;
+        if let Some(raw_content) = &doc.raw_content {
+            doc.raw_content = Some(self.decrypt(raw_content).await?);
+        }
+        Ok(())
+    }
+
+    /// 将租户的文档改用当前有效密钥加密
+    ///
+    /// 处理明文与由旧密钥加密的文档，按批提交；已吊销密钥加密的文档无法解密，会被跳过。返回重新加密的文档数。
+    #[instrument(skip(self))]
+    pub async fn reencrypt_documents(&self, tenant_id: Uuid) -> Result<u64, AiStudioError> {
+        let Some((active_id, _)) = self.active_key(tenant_id).await? else {
+            return Ok(0);
+        };
+        let active_prefix = format!("{}{}:", ENCRYPTED_PREFIX, active_id);
+
+        let mut reencrypted = 0;
+        let mut after = Uuid::nil();
+        loop {
+            let rows = self.db
+                .query_all(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    r#"
+                    SELECT d.id, d.content, d.raw_content
+                    FROM documents d
+                    JOIN knowledge_bases kb ON kb.id = d.knowledge_base_id
+                    WHERE kb.tenant_id = $1 AND d.id > $2
+                        AND (NOT starts_with(d.content, $3)
+                            OR (d.raw_content IS NOT NULL AND NOT starts_with(d.raw_content, $3)))
+                    ORDER BY d.id
+                    LIMIT $4
+                    "#,
+                    [tenant_id.into(), after.into(), active_prefix.clone().into(), REENCRYPT_BATCH_SIZE.into()],
+                ))
+                .await?;
+            let Some(last) = rows.last() else { break };
+            after = last.try_get("", "id")?;
+
+            for row in &rows {
+                let id: Uuid = row.try_get("", "id")?;
+                let content: String = row.try_get("", "content")?;
+                let raw_content: Option<String> = row.try_get("", "raw_content")?;
+                let (content, raw_content) = match self.reencrypt_pair(tenant_id, &content, raw_content.as_deref()).await {
+                    Ok(pair) => pair,
+                    Err(e) => {
+                        warn!(tenant_id = %tenant_id, document_id = %id, "文档重新加密失败，已跳过: {}", e);
+                        continue;
+                    }
+                };
+                self.db
+                    .execute(Statement::from_sql_and_values(
+                        DatabaseBackend::Postgres,
+                        "UPDATE documents SET content = $2, raw_content = $3 WHERE id = $1",
+                        [id.into(), content.into(), raw_content.into()],
+                    ))
+                    .await?;
+                reencrypted += 1;
+            }
+        }
+
+        info!(tenant_id = %tenant_id, reencrypted, "租户文档已改用当前数据密钥加密");
+        Ok(reencrypted)
+    }
+
+    async fn reencrypt_pair(
+        &self,
+        tenant_id: Uuid,
+        content: &str,
+        raw_content: Option<&str>,
+    ) -> Result<(String, Option<String>), AiStudioError> {
+        let content = self.encrypt(tenant_id, &self.decrypt(content).await?).await?;
+        let raw_content = match raw_content {
+            Some(raw_content) => Some(self.encrypt(tenant_id, &self.decrypt(raw_content).await?).await?),
+            None => None,
+        };
+        Ok((content, raw_content))
+    }
+
+    async fn active_key(&self, tenant_id: Uuid) -> Result<Option<(Uuid, SecretCipher)>, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT id FROM tenant_encryption_keys WHERE tenant_id = $1 AND status = 'active'",
+                [tenant_id.into()],
+            ))
+            .await?;
+        match row {
+            Some(row) => {
+                let key_id: Uuid = row.try_get("", "id")?;
+                Ok(Some((key_id, self.data_key(key_id).await?)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 解包数据密钥
+    async fn data_key(&self, key_id: Uuid) -> Result<SecretCipher, AiStudioError> {
+        if let Some(cipher) = self.data_keys.lock().unwrap().get(&key_id) {
+            return Ok(cipher.clone());
+        }
+
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT source, kms_key_id, wrapped_key FROM tenant_encryption_keys WHERE id = $1",
+                [key_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::internal("文档内容的数据密钥不存在"))?;
+        let wrapped_key: String = row
+            .try_get::<Option<String>>("", "wrapped_key")?
+            .ok_or_else(|| AiStudioError::forbidden("文档内容的数据密钥已吊销"))?;
+        let source: String = row.try_get("", "source")?;
+        let data_key = match KeySource::parse(&source)? {
+            KeySource::Kms => {
+                let kms = self.kms.as_ref().ok_or_else(|| AiStudioError::internal("未配置 KMS，无法解包数据密钥"))?;
+                let kms_key_id: String = row.try_get("", "kms_key_id")?;
+                kms.unwrap(&kms_key_id, &wrapped_key).await?
+            }
+            KeySource::Managed | KeySource::Provided => self.master.decrypt(&wrapped_key)?,
+        };
+
+        let cipher = SecretCipher::new(&data_key);
+        self.data_keys.lock().unwrap().insert(key_id, cipher.clone());
+        Ok(cipher)
+    }
+}
+
+/// 吊销租户的全部数据密钥并清除密钥材料，返回吊销的密钥数
+///
+/// 删除租户前调用，使备份或副本中残留的密文同样无法解密。
+pub async fn shred_tenant_keys<C: ConnectionTrait>(db: &C, tenant_id: Uuid) -> Result<u64, AiStudioError> {
+    let result = db
+        .execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"
+            UPDATE tenant_encryption_keys
+            SET status = 'revoked', wrapped_key = NULL, revoked_at = $2
+            WHERE tenant_id = $1 AND status <> 'revoked'
+            "#,
+            [tenant_id.into(), Utc::now().into()],
+        ))
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// 内容是否已加密
+pub fn is_encrypted(stored: &str) -> bool {
+    parse_ciphertext(stored).is_some()
+}
+
+/// 解析密文中的密钥 ID 与 base64 密文
+fn parse_ciphertext(stored: &str) -> Option<(Uuid, &str)> {
+    let rest = stored.strip_prefix(ENCRYPTED_PREFIX)?;
+    let (key_id, payload) = rest.split_once(':')?;
+    Some((Uuid::parse_str(key_id).ok()?, payload))
+}
+
+/// 生成随机数据密钥（base64）
+fn generate_data_key() -> String {
+    let mut key = [0u8; DATA_KEY_LEN];
+    OsRng.fill_bytes(&mut key);
+    BASE64.encode(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ciphertext() {
+        let key_id = Uuid::new_v4();
+        let stored = format!("{}{}:{}", ENCRYPTED_PREFIX, key_id, "cGF5bG9hZA==");
+        assert_eq!(parse_ciphertext(&stored), Some((key_id, "cGF5bG9hZA==")));
+        assert!(is_encrypted(&stored));
+
+        assert!(!is_encrypted("普通文档内容"));
+        assert!(!is_encrypted("enc:v1:not-a-uuid:payload"));
+    }
+
+    #[test]
+    fn test_data_key_round_trip() {
+        let data_key = generate_data_key();
+        assert_eq!(BASE64.decode(&data_key).unwrap().len(), DATA_KEY_LEN);
+
+        let cipher = SecretCipher::new(&data_key);
+        let encrypted = cipher.encrypt("合同正文").unwrap();
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), "合同正文");
+        assert!(SecretCipher::new(&generate_data_key()).decrypt(&encrypted).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+// 租户人设服务
+// 管理租户助手的名称、语气要求、禁用措辞与欢迎语，问答与 Agent 组装提示词时注入，使各租户的助手以自己的口吻回答
+
+use chrono::Utc;
+use sea_orm::{DatabaseConnection, EntityTrait, Set};
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+use crate::db::entities::tenant::{self, TenantPersona};
+use crate::db::entities::Tenant;
+use crate::db::update_with_revision;
+use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace};
+
+/// 助手名称的最大长度（字符）
+const MAX_ASSISTANT_NAME_LEN: usize = 50;
+
+/// 语气要求的最大长度（字符）
+const MAX_TONE_GUIDELINES_LEN: usize = 2000;
+
+/// 欢迎语的最大长度（字符）
+const MAX_GREETING_LEN: usize = 500;
+
+/// 禁用措辞的最大条数
+const MAX_BANNED_PHRASES: usize = 50;
+
+/// 单条禁用措辞的最大长度（字符）
+const MAX_BANNED_PHRASE_LEN: usize = 100;
+
+/// 租户人设服务
+pub struct TenantPersonaService {
+    db: DatabaseConnection,
+}
+
+impl TenantPersonaService {
+    /// 创建新的租户人设服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 获取租户人设
+    #[instrument(skip(self))]
+    pub async fn get_persona(&self, tenant_id: Uuid) -> Result<TenantPersona, AiStudioError> {
+        let tenant = Tenant::find_by_id(tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+        Ok(tenant.get_config().unwrap_or_default().persona)
+    }
+
+    /// 替换租户人设
+    #[instrument(skip(self, persona))]
+    pub async fn update_persona(
+        &self,
+        tenant_id: Uuid,
+        persona: TenantPersona,
+    ) -> Result<TenantPersona, AiStudioError> {
+        let persona = normalize_persona(persona)?;
+
+        let tenant = Tenant::find_by_id(tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+        let mut config = tenant.get_config()
+            .map_err(|e| AiStudioError::internal(format!("解析租户配置失败: {}", e)))?;
+        config.persona = persona.clone();
+
+        let revision = tenant.revision;
+        let mut active_tenant: tenant::ActiveModel = tenant.into();
+        active_tenant.config = Set(serde_json::to_value(&config)?);
+        active_tenant.updated_at = Set(Utc::now().into());
+        update_with_revision(&self.db, active_tenant, tenant::Column::Revision, revision, "租户").await?;
+        cache::invalidate(CacheNamespace::Tenant, tenant_id).await;
+
+        info!(tenant_id = %tenant_id, "租户人设已更新");
+        Ok(persona)
+    }
+}
+
+/// 读取租户人设，租户不存在或配置无效时使用默认人设
+///
+/// 用于请求路径上的提示词组装，人设缺失不应使问答失败。
+pub async fn load_tenant_persona(db: &DatabaseConnection, tenant_id: Uuid) -> TenantPersona {
+    match TenantPersonaService::new(db.clone()).get_persona(tenant_id).await {
+        Ok(persona) => persona,
+        Err(e) => {
+            warn!(tenant_id = %tenant_id, error = %e, "读取租户人设失败，使用默认人设");
+            TenantPersona::default()
+        }
+    }
+}
+
+/// 将租户人设格式化为提示词片段，未配置人设时为空
+pub fn format_persona_for_prompt(persona: &TenantPersona) -> String {
+    let mut section = String::new();
+    if let Some(name) = &persona.assistant_name {
+        section.push_str(&format!("你的名字是「{}」，请以该身份与用户交流。\n", name));
+    }
+    if let Some(tone) = &persona.tone_guidelines {
+        section.push_str(&format!("语气要求：{}\n", tone));
+    }
+    if !persona.banned_phrases.is_empty() {
+        section.push_str(&format!("回答中禁止使用以下措辞：{}\n", persona.banned_phrases.join("、")));
+    }
+    if !section.is_empty() {
+        section.push('\n');
+    }
+    section
+}
+
+/// 校验并规范化人设：去除首尾空白，空字段视为未设置，禁用措辞去重
+fn normalize_persona(persona: TenantPersona) -> Result<TenantPersona, AiStudioError> {
+    let assistant_name = normalize_text("assistant_name", persona.assistant_name, MAX_ASSISTANT_NAME_LEN)?;
+    let tone_guidelines = normalize_text("tone_guidelines", persona.tone_guidelines, MAX_TONE_GUIDELINES_LEN)?;
+    let greeting = normalize_text("greeting", persona.greeting, MAX_GREETING_LEN)?;
+
+    let mut banned_phrases: Vec<String> = Vec::new();
+    for phrase in persona.banned_phrases {
+        let phrase = phrase.trim();
+        if phrase.is_empty() || banned_phrases.iter().any(|p| p == phrase) {
+            continue;
+        }
+        if phrase.chars().count() > MAX_BANNED_PHRASE_LEN {
+            return Err(AiStudioError::validation(
+                "banned_phrases",
+                format!("单条禁用措辞不能超过 {} 个字符", MAX_BANNED_PHRASE_LEN),
+            ));
+        }
+        banned_phrases.push(phrase.to_string());
+    }
+    if banned_phrases.len() > MAX_BANNED_PHRASES {
+        return Err(AiStudioError::validation(
+            "banned_phrases",
+            format!("禁用措辞不能超过 {} 条", MAX_BANNED_PHRASES),
+        ));
+    }
+
+    Ok(TenantPersona {
+        assistant_name,
+        tone_guidelines,
+        banned_phrases,
+        greeting,
+    })
+}
+
+fn normalize_text(field: &str, value: Option<String>, max_len: usize) -> Result<Option<String>, AiStudioError> {
+    let Some(value) = value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) else {
+        return Ok(None);
+    };
+    if value.chars().count() > max_len {
+        return Err(AiStudioError::validation(field, format!("不能超过 {} 个字符", max_len)));
+    }
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_persona() {
+        let persona = normalize_persona(TenantPersona {
+            assistant_name: Some("  小安 ".to_string()),
+            tone_guidelines: Some("   ".to_string()),
+            banned_phrases: vec!["亲".to_string(), " ".to_string(), "亲 ".to_string(), "绝对保证".to_string()],
+            greeting: None,
+        })
+        .unwrap();
+
+        assert_eq!(persona.assistant_name.as_deref(), Some("小安"));
+        assert_eq!(persona.tone_guidelines, None);
+        assert_eq!(persona.banned_phrases, vec!["亲", "绝对保证"]);
+
+        let too_long = TenantPersona {
+            assistant_name: Some("名".repeat(MAX_ASSISTANT_NAME_LEN + 1)),
+            ..TenantPersona::default()
+        };
+        assert!(normalize_persona(too_long).is_err());
+    }
+
+    #[test]
+    fn test_format_persona_for_prompt() {
+        assert_eq!(format_persona_for_prompt(&TenantPersona::default()), "");
+
+        let section = format_persona_for_prompt(&TenantPersona {
+            assistant_name: Some("小安".to_string()),
+            tone_guidelines: Some("亲切、简短".to_string()),
+            banned_phrases: vec!["亲".to_string(), "绝对保证".to_string()],
+            greeting: Some("您好，我是小安".to_string()),
+        });
+        assert!(section.contains("你的名字是「小安」"));
+        assert!(section.contains("语气要求：亲切、简短"));
+        assert!(section.contains("亲、绝对保证"));
+        assert!(!section.contains("您好，我是小安"));
+    }
+}
@@ -0,0 +1,206 @@
+// 租户密钥服务
+// 存储工作流步骤通过 {{secrets.NAME}} 引用的凭据，密钥值加密落库且从不通过 API 返回
+
+use std::collections::{BTreeSet, HashMap};
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::{tenant_secret, TenantSecret};
+use crate::errors::AiStudioError;
+use crate::services::plugin_config::SecretCipher;
+
+/// 密钥名称最大长度
+pub const MAX_SECRET_NAME_LEN: usize = 128;
+
+/// 密钥值最大长度（字节）
+pub const MAX_SECRET_VALUE_BYTES: usize = 16 * 1024;
+
+/// 租户密钥元数据（不含密钥值）
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TenantSecretResponse {
+    /// 密钥名称
+    pub name: String,
+    /// 密钥说明
+    pub description: Option<String>,
+    /// 最后修改人
+    pub updated_by: Option<Uuid>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<tenant_secret::Model> for TenantSecretResponse {
+    fn from(model: tenant_secret::Model) -> Self {
+        Self {
+            name: model.name,
+            description: model.description,
+            updated_by: model.updated_by,
+            created_at: model.created_at.with_timezone(&Utc),
+            updated_at: model.updated_at.with_timezone(&Utc),
+        }
+    }
+}
+
+/// 密钥名称是否合法：字母或下划线开头，仅包含字母、数字和下划线
+pub fn is_valid_secret_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else { return false };
+    name.len() <= MAX_SECRET_NAME_LEN
+        && (first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 租户密钥服务
+#[derive(Clone)]
+pub struct TenantSecretService {
+    db: DatabaseConnection,
+    cipher: SecretCipher,
+}
+
+impl TenantSecretService {
+    /// 创建新的租户密钥服务实例
+    pub fn new(db: DatabaseConnection, encryption_key: &str) -> Self {
+        Self {
+            db,
+            cipher: SecretCipher::new(encryption_key),
+        }
+    }
+
+    /// 列出租户的密钥（仅元数据）
+    #[instrument(skip(self))]
+    pub async fn list_secrets(&self, tenant_id: Uuid) -> Result<Vec<TenantSecretResponse>, AiStudioError> {
+        let secrets = TenantSecret::find()
+            .filter(tenant_secret::Column::TenantId.eq(tenant_id))
+            .order_by_asc(tenant_secret::Column::Name)
+            .all(&self.db)
+            .await?;
+        Ok(secrets.into_iter().map(Into::into).collect())
+    }
+
+    /// 创建或覆盖密钥
+    #[instrument(skip(self, value, description))]
+    pub async fn put_secret(
+        &self,
+        tenant_id: Uuid,
+        name: &str,
+        value: &str,
+        description: Option<String>,
+        updated_by: Uuid,
+    ) -> Result<TenantSecretResponse, AiStudioError> {
+        if !is_valid_secret_name(name) {
+            return Err(AiStudioError::validation(
+                "name",
+                format!("密钥名称只能包含字母、数字和下划线，不能以数字开头，且不超过 {} 个字符", MAX_SECRET_NAME_LEN),
+            ));
+        }
+        if value.is_empty() || value.len() > MAX_SECRET_VALUE_BYTES {
+            return Err(AiStudioError::validation(
+                "value",
+                format!("密钥值不能为空且不超过 {} 字节", MAX_SECRET_VALUE_BYTES),
+            ));
+        }
+
+        let encrypted_value = self.cipher.encrypt(value)?;
+        let now = Utc::now().fixed_offset();
+        let model = match self.find(tenant_id, name).await? {
+            Some(model) => {
+                let mut active: tenant_secret::ActiveModel = model.into();
+                active.encrypted_value = Set(encrypted_value);
+                active.description = Set(description);
+                active.updated_by = Set(Some(updated_by));
+                active.updated_at = Set(now);
+                active.update(&self.db).await?
+            }
+            None => {
+                tenant_secret::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    tenant_id: Set(tenant_id),
+                    name: Set(name.to_string()),
+                    description: Set(description),
+                    encrypted_value: Set(encrypted_value),
+                    updated_by: Set(Some(updated_by)),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                }
+                .insert(&self.db)
+                .await?
+            }
+        };
+
+        info!(tenant_id = %tenant_id, name = %name, "租户密钥已保存");
+        Ok(model.into())
+    }
+
+    /// 删除密钥
+    #[instrument(skip(self))]
+    pub async fn delete_secret(&self, tenant_id: Uuid, name: &str) -> Result<(), AiStudioError> {
+        let result = TenantSecret::delete_many()
+            .filter(tenant_secret::Column::TenantId.eq(tenant_id))
+            .filter(tenant_secret::Column::Name.eq(name))
+            .exec(&self.db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(AiStudioError::not_found(format!("密钥 {}", name)));
+        }
+
+        info!(tenant_id = %tenant_id, name = %name, "租户密钥已删除");
+        Ok(())
+    }
+
+    /// 解密指定名称的密钥，任一密钥不存在时返回错误
+    #[instrument(skip(self))]
+    pub async fn resolve_secrets(
+        &self,
+        tenant_id: Uuid,
+        names: &BTreeSet<String>,
+    ) -> Result<HashMap<String, String>, AiStudioError> {
+        if names.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let models = TenantSecret::find()
+            .filter(tenant_secret::Column::TenantId.eq(tenant_id))
+            .filter(tenant_secret::Column::Name.is_in(names.iter().cloned()))
+            .all(&self.db)
+            .await?;
+
+        let mut secrets = HashMap::with_capacity(models.len());
+        for model in models {
+            secrets.insert(model.name.clone(), self.cipher.decrypt(&model.encrypted_value)?);
+        }
+
+        let missing: Vec<&str> = names.iter().filter(|name| !secrets.contains_key(*name)).map(String::as_str).collect();
+        if !missing.is_empty() {
+            return Err(AiStudioError::validation("secrets", format!("引用的密钥不存在: {}", missing.join(", "))));
+        }
+        Ok(secrets)
+    }
+
+    async fn find(&self, tenant_id: Uuid, name: &str) -> Result<Option<tenant_secret::Model>, AiStudioError> {
+        Ok(TenantSecret::find()
+            .filter(tenant_secret::Column::TenantId.eq(tenant_id))
+            .filter(tenant_secret::Column::Name.eq(name))
+            .one(&self.db)
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_secret_name() {
+        assert!(is_valid_secret_name("CRM_TOKEN"));
+        assert!(is_valid_secret_name("_internal2"));
+        assert!(!is_valid_secret_name(""));
+        assert!(!is_valid_secret_name("2FA_KEY"));
+        assert!(!is_valid_secret_name("crm-token"));
+        assert!(!is_valid_secret_name(&"A".repeat(MAX_SECRET_NAME_LEN + 1)));
+    }
+}
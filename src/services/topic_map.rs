@@ -0,0 +1,714 @@
+// 知识库主题地图
+// 后台任务把知识库文档的平均向量聚类为主题，由模型或标题关键词生成主题名称，帮助管理员发现覆盖缺口与内容重叠
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::client::AiClient;
+use crate::db::entities::document::ClearanceLevel;
+use crate::errors::AiStudioError;
+use crate::services::question_suggestion::{cosine_similarity, parse_vector};
+use crate::services::task_queue::{TaskExecutor, TaskInfo, TaskQueueService, TaskType};
+
+/// 单次聚类最多加载的文档数
+const MAX_CLUSTER_DOCUMENTS: i64 = 5_000;
+
+/// 主题数下限
+const MIN_TOPICS: usize = 2;
+
+/// 主题数上限
+const MAX_TOPICS: usize = 20;
+
+/// k-means 最大迭代次数
+const MAX_ITERATIONS: usize = 25;
+
+/// 每个主题返回的代表文档数
+const REPRESENTATIVE_DOCUMENTS: usize = 3;
+
+/// 每个主题提取的关键词数
+const TOPIC_KEYWORDS: usize = 5;
+
+/// 生成主题名称时提供给模型的标题数
+const LABEL_PROMPT_TITLES: usize = 8;
+
+/// 主题名称的最大字符数
+const MAX_LABEL_CHARS: usize = 40;
+
+/// 主题聚类任务参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ClusteringParameters {
+    knowledge_base_id: Uuid,
+    requested_by: Uuid,
+}
+
+/// 主题聚类任务状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TopicClusteringJob {
+    /// 任务 ID
+    pub task_id: Uuid,
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 状态：pending、running、completed、failed、cancelled
+    pub status: String,
+    /// 进度（0-100）
+    pub progress: u8,
+    /// 失败原因
+    pub error_message: Option<String>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 完成时间
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// 主题名称来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TopicLabelSource {
+    /// 由模型根据代表文档标题生成
+    Llm,
+    /// 由文档标题中的高频关键词拼接
+    Keywords,
+}
+
+/// 主题内的代表文档
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TopicDocument {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 与主题中心的余弦相似度
+    pub similarity: f32,
+}
+
+/// 主题
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Topic {
+    /// 主题编号，按文档数从多到少从 1 开始
+    pub topic_id: u32,
+    /// 主题名称
+    pub label: String,
+    /// 主题名称来源
+    pub label_source: TopicLabelSource,
+    /// 标题中的高频关键词
+    pub keywords: Vec<String>,
+    /// 文档数
+    pub document_count: u32,
+    /// 文档块数
+    pub chunk_count: u32,
+    /// 文档数占知识库的比例，比例很小的主题通常意味着覆盖不足
+    pub share: f32,
+    /// 文档与主题中心的平均相似度，越高说明内容越集中
+    pub cohesion: f32,
+    /// 最相近的其他主题
+    pub nearest_topic_id: Option<u32>,
+    /// 与最相近主题的中心相似度，过高时两个主题可能重复
+    pub nearest_topic_similarity: Option<f32>,
+    /// 最接近主题中心的代表文档
+    pub representative_documents: Vec<TopicDocument>,
+}
+
+/// 知识库主题地图
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct KbTopicMap {
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 生成该地图的任务 ID
+    pub task_id: Uuid,
+    /// 参与聚类的文档数
+    pub document_count: u32,
+    /// 参与聚类的文档块数
+    pub chunk_count: u32,
+    /// 使用的嵌入模型
+    pub model_name: Option<String>,
+    /// 主题列表，按文档数从多到少排列
+    pub topics: Vec<Topic>,
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+}
+
+/// 参与聚类的文档
+#[derive(Debug, Clone)]
+pub struct TopicCandidate {
+    /// 文档 ID
+    pub document_id: Uuid,
+    /// 文档标题
+    pub title: String,
+    /// 文档块数
+    pub chunk_count: u32,
+    /// 文档块向量的平均值
+    pub vector: Vec<f32>,
+}
+
+/// 聚类得到的文档分组
+#[derive(Debug, Clone)]
+pub struct TopicGroup {
+    /// 组内文档在输入中的下标，按与中心的相似度降序排列
+    pub members: Vec<usize>,
+    /// 各成员与中心的相似度，与 `members` 一一对应
+    pub similarities: Vec<f32>,
+    /// 归一化后的中心向量
+    pub centroid: Vec<f32>,
+}
+
+/// 主题地图服务
+pub struct TopicMapService {
+    db: DatabaseConnection,
+    queue: Arc<TaskQueueService>,
+}
+
+impl TopicMapService {
+    /// 创建服务
+    pub fn new(db: DatabaseConnection, queue: Arc<TaskQueueService>) -> Self {
+        Self { db, queue }
+    }
+
+    /// 提交主题聚类任务
+    #[instrument(skip(self))]
+    pub async fn submit(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        requested_by: Uuid,
+    ) -> Result<TopicClusteringJob, AiStudioError> {
+        let parameters = serde_json::to_value(ClusteringParameters { knowledge_base_id, requested_by })?;
+        let task_id = self.queue
+            .submit_task(TaskType::TopicClustering, tenant_id, parameters, None)
+            .await?;
+
+        info!("主题聚类任务已提交: task_id={}, kb={}", task_id, knowledge_base_id);
+        self.job(tenant_id, knowledge_base_id, task_id).await
+    }
+
+    /// 查询主题聚类任务状态
+    pub async fn job(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        task_id: Uuid,
+    ) -> Result<TopicClusteringJob, AiStudioError> {
+        let task = self.queue
+            .get_task_status(task_id)
+            .await
+            .filter(|task| task.tenant_id == tenant_id && task.task_type == TaskType::TopicClustering)
+            .ok_or_else(|| AiStudioError::not_found("主题聚类任务"))?;
+        let parameters: ClusteringParameters = serde_json::from_value(task.parameters.clone())?;
+        if parameters.knowledge_base_id != knowledge_base_id {
+            return Err(AiStudioError::not_found("主题聚类任务"));
+        }
+
+        Ok(TopicClusteringJob {
+            task_id,
+            knowledge_base_id,
+            status: serde_json::to_value(&task.status)?.as_str().unwrap_or_default().to_string(),
+            progress: task.progress,
+            error_message: task.error_message,
+            created_at: task.created_at,
+            completed_at: task.completed_at,
+        })
+    }
+
+    /// 获取知识库最近一次生成的主题地图
+    ///
+    /// 代表文档中超出请求者密级的文档不返回，主题的规模统计不受影响。
+    pub async fn topic_map(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        clearance: ClearanceLevel,
+    ) -> Result<KbTopicMap, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT task_id, topics, document_count, chunk_count, model_name, generated_at
+                FROM kb_topic_maps
+                WHERE knowledge_base_id = $1 AND tenant_id = $2
+                "#,
+                [knowledge_base_id.into(), tenant_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("主题地图"))?;
+
+        let mut topics: Vec<Topic> = serde_json::from_value(row.try_get("", "topics")?)?;
+        let document_ids: Vec<Uuid> = topics
+            .iter()
+            .flat_map(|topic| topic.representative_documents.iter().map(|doc| doc.document_id))
+            .collect();
+        let visible = self.visible_documents(&document_ids, clearance).await?;
+        for topic in &mut topics {
+            topic.representative_documents.retain(|doc| visible.contains(&doc.document_id));
+        }
+
+        let document_count: i32 = row.try_get("", "document_count")?;
+        let chunk_count: i32 = row.try_get("", "chunk_count")?;
+        Ok(KbTopicMap {
+            knowledge_base_id,
+            task_id: row.try_get("", "task_id")?,
+            document_count: document_count as u32,
+            chunk_count: chunk_count as u32,
+            model_name: row.try_get("", "model_name")?,
+            topics,
+            generated_at: row.try_get("", "generated_at")?,
+        })
+    }
+
+    /// 筛选请求者可见的文档
+    async fn visible_documents(
+        &self,
+        document_ids: &[Uuid],
+        clearance: ClearanceLevel,
+    ) -> Result<HashSet<Uuid>, AiStudioError> {
+        if document_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT d.id FROM documents d
+                WHERE d.id = ANY($1) AND d.clearance <= $2
+                    AND NOT EXISTS (
+                        SELECT 1 FROM document_chunks c WHERE c.document_id = d.id AND c.clearance > $2
+                    )
+                "#,
+                [document_ids.to_vec().into(), clearance.into()],
+            ))
+            .await?;
+        rows.iter().map(|row| row.try_get("", "id").map_err(Into::into)).collect()
+    }
+}
+
+/// 主题聚类任务执行器
+pub struct TopicClusteringExecutor {
+    db: DatabaseConnection,
+    labeler: Option<Arc<dyn AiClient>>,
+}
+
+impl TopicClusteringExecutor {
+    /// 创建执行器，未提供模型客户端时使用标题关键词作为主题名称
+    pub fn new(db: DatabaseConnection, labeler: Option<Arc<dyn AiClient>>) -> Self {
+        Self { db, labeler }
+    }
+
+    /// 加载知识库内已处理完成文档的平均向量，只使用知识库中最常用的嵌入模型
+    async fn load_candidates(
+        &self,
+        knowledge_base_id: Uuid,
+    ) -> Result<(Option<String>, Vec<TopicCandidate>), AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                WITH model AS (
+                    SELECT e.model_name
+                    FROM embeddings e
+                    JOIN document_chunks c ON c.id = e.chunk_id
+                    JOIN documents d ON d.id = c.document_id
+                    WHERE d.knowledge_base_id = $1
+                    GROUP BY e.model_name
+                    ORDER BY COUNT(*) DESC
+                    LIMIT 1
+                )
+                SELECT d.id, d.title, e.model_name, COUNT(*) AS chunk_count, AVG(e.vector)::text AS centroid
+                FROM embeddings e
+                JOIN document_chunks c ON c.id = e.chunk_id
+                JOIN documents d ON d.id = c.document_id
+                WHERE d.knowledge_base_id = $1
+                    AND d.status = 'completed'
+                    AND e.model_name = (SELECT model_name FROM model)
+                GROUP BY d.id, d.title, e.model_name
+                ORDER BY d.id
+                LIMIT $2
+                "#,
+                [knowledge_base_id.into(), MAX_CLUSTER_DOCUMENTS.into()],
+            ))
+            .await?;
+
+        let mut model_name = None;
+        let mut candidates = Vec::with_capacity(rows.len());
+        for row in rows {
+            let centroid: String = row.try_get("", "centroid")?;
+            let Some(vector) = parse_vector(&centroid) else {
+                continue;
+            };
+            let chunk_count: i64 = row.try_get("", "chunk_count")?;
+            model_name = Some(row.try_get::<String>("", "model_name")?);
+            candidates.push(TopicCandidate {
+                document_id: row.try_get("", "id")?,
+                title: row.try_get("", "title")?,
+                chunk_count: chunk_count as u32,
+                vector,
+            });
+        }
+        Ok((model_name, candidates))
+    }
+
+    /// 由模型生成主题名称，失败或返回空内容时使用关键词名称
+    async fn label_topic(&self, titles: &[&str], keywords: &[String]) -> (String, TopicLabelSource) {
+        if let Some(labeler) = &self.labeler {
+            let prompt = label_prompt(titles, keywords);
+            match labeler.generate_text(&prompt).await {
+                Ok(response) => {
+                    if let Some(label) = clean_label(&response.text) {
+                        return (label, TopicLabelSource::Llm);
+                    }
+                }
+                Err(e) => warn!("主题名称生成失败，使用关键词名称: {}", e),
+            }
+        }
+        (keyword_label(titles, keywords), TopicLabelSource::Keywords)
+    }
+
+    /// 保存主题地图，覆盖该知识库之前的结果
+    async fn save_map(
+        &self,
+        task: &TaskInfo,
+        knowledge_base_id: Uuid,
+        model_name: Option<String>,
+        topics: &[Topic],
+        document_count: usize,
+        chunk_count: u32,
+    ) -> Result<(), AiStudioError> {
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO kb_topic_maps
+                    (knowledge_base_id, tenant_id, task_id, topics, document_count, chunk_count, model_name, generated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, CURRENT_TIMESTAMP)
+                ON CONFLICT (knowledge_base_id) DO UPDATE SET
+                    task_id = EXCLUDED.task_id,
+                    topics = EXCLUDED.topics,
+                    document_count = EXCLUDED.document_count,
+                    chunk_count = EXCLUDED.chunk_count,
+                    model_name = EXCLUDED.model_name,
+                    generated_at = EXCLUDED.generated_at
+                "#,
+                [
+                    knowledge_base_id.into(),
+                    task.tenant_id.into(),
+                    task.id.into(),
+                    serde_json::to_value(topics)?.into(),
+                    (document_count as i32).into(),
+                    (chunk_count as i32).into(),
+                    model_name.into(),
+                ],
+            ))
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskExecutor for TopicClusteringExecutor {
+    async fn execute(&self, task: &mut TaskInfo) -> Result<(), AiStudioError> {
+        let parameters: ClusteringParameters = serde_json::from_value(task.parameters.clone())?;
+
+        let (model_name, candidates) = self.load_candidates(parameters.knowledge_base_id).await?;
+        task.total_count = Some(candidates.len() as u32);
+        task.progress = 30;
+
+        let vectors: Vec<Vec<f32>> = candidates.iter().map(|candidate| candidate.vector.clone()).collect();
+        let groups = cluster_topics(&vectors, topic_count(candidates.len()));
+        task.progress = 60;
+
+        let total_chunks: u32 = candidates.iter().map(|candidate| candidate.chunk_count).sum();
+        let mut topics = Vec::with_capacity(groups.len());
+        for (index, group) in groups.iter().enumerate() {
+            let titles: Vec<&str> = group.members.iter().map(|&member| candidates[member].title.as_str()).collect();
+            let keywords = title_keywords(&titles, TOPIC_KEYWORDS);
+            let (label, label_source) = self.label_topic(&titles[..titles.len().min(LABEL_PROMPT_TITLES)], &keywords).await;
+            let (nearest_topic_id, nearest_topic_similarity) = nearest_group(&groups, index)
+                .map(|(nearest, similarity)| (Some(nearest as u32 + 1), Some(similarity)))
+                .unwrap_or((None, None));
+
+            topics.push(Topic {
+                topic_id: index as u32 + 1,
+                label,
+                label_source,
+                keywords,
+                document_count: group.members.len() as u32,
+                chunk_count: group.members.iter().map(|&member| candidates[member].chunk_count).sum(),
+                share: group.members.len() as f32 / candidates.len() as f32,
+                cohesion: group.similarities.iter().sum::<f32>() / group.similarities.len() as f32,
+                nearest_topic_id,
+                nearest_topic_similarity,
+                representative_documents: group.members
+                    .iter()
+                    .zip(&group.similarities)
+                    .take(REPRESENTATIVE_DOCUMENTS)
+                    .map(|(&member, &similarity)| TopicDocument {
+                        document_id: candidates[member].document_id,
+                        title: candidates[member].title.clone(),
+                        similarity,
+                    })
+                    .collect(),
+            });
+        }
+        task.progress = 90;
+
+        self.save_map(
+            task,
+            parameters.knowledge_base_id,
+            model_name,
+            &topics,
+            candidates.len(),
+            total_chunks,
+        )
+        .await?;
+
+        task.success_count = candidates.len() as u32;
+        task.result = Some(json!({
+            "document_count": candidates.len(),
+            "topic_count": topics.len(),
+        }));
+        info!(
+            "主题聚类完成: task_id={}, kb={}, 文档数={}, 主题数={}, 请求人={}",
+            task.id, parameters.knowledge_base_id, candidates.len(), topics.len(), parameters.requested_by
+        );
+        Ok(())
+    }
+
+    fn supported_task_types(&self) -> Vec<TaskType> {
+        vec![TaskType::TopicClustering]
+    }
+}
+
+/// 按文档数确定主题数：约为 √(n/2)，限制在 2 到 20 之间且不超过文档数
+pub fn topic_count(documents: usize) -> usize {
+    if documents == 0 {
+        return 0;
+    }
+    let k = ((documents as f32 / 2.0).sqrt().round() as usize).clamp(MIN_TOPICS, MAX_TOPICS);
+    k.min(documents)
+}
+
+/// 用余弦距离的 k-means 将向量聚为 `k` 组，结果按组大小降序排列，空组被丢弃
+///
+/// 初始中心用最远点法选取：第一个中心为输入中的第一个向量，之后每次选取与已有中心最不相似的向量，
+/// 因此相同输入总是得到相同结果。
+pub fn cluster_topics(vectors: &[Vec<f32>], k: usize) -> Vec<TopicGroup> {
+    let k = k.min(vectors.len());
+    if k == 0 {
+        return Vec::new();
+    }
+    let normalized: Vec<Vec<f32>> = vectors.iter().map(|vector| normalize(vector)).collect();
+
+    let mut centroids = vec![normalized[0].clone()];
+    let mut best_similarity: Vec<f32> = normalized.iter().map(|vector| dot(vector, &centroids[0])).collect();
+    while centroids.len() < k {
+        let (farthest, _) = best_similarity
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(Ordering::Equal))
+            .unwrap_or((0, &0.0));
+        centroids.push(normalized[farthest].clone());
+        for (index, vector) in normalized.iter().enumerate() {
+            best_similarity[index] = best_similarity[index].max(dot(vector, &normalized[farthest]));
+        }
+    }
+
+    let mut assignments = vec![usize::MAX; normalized.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+        for (index, vector) in normalized.iter().enumerate() {
+            let nearest = nearest_centroid(vector, &centroids);
+            if assignments[index] != nearest {
+                assignments[index] = nearest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        for (cluster, centroid) in centroids.iter_mut().enumerate() {
+            let mut sum = vec![0.0; centroid.len()];
+            let mut count = 0;
+            for (vector, _) in normalized.iter().zip(&assignments).filter(|(_, assigned)| **assigned == cluster) {
+                for (total, value) in sum.iter_mut().zip(vector) {
+                    *total += value;
+                }
+                count += 1;
+            }
+            if count > 0 {
+                *centroid = normalize(&sum);
+            }
+        }
+    }
+
+    let mut groups: Vec<TopicGroup> = centroids
+        .into_iter()
+        .enumerate()
+        .filter_map(|(cluster, centroid)| {
+            let mut members: Vec<(usize, f32)> = assignments
+                .iter()
+                .enumerate()
+                .filter(|(_, assigned)| **assigned == cluster)
+                .map(|(index, _)| (index, dot(&normalized[index], &centroid)))
+                .collect();
+            if members.is_empty() {
+                return None;
+            }
+            members.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal).then_with(|| a.0.cmp(&b.0)));
+            Some(TopicGroup {
+                members: members.iter().map(|(index, _)| *index).collect(),
+                similarities: members.iter().map(|(_, similarity)| *similarity).collect(),
+                centroid,
+            })
+        })
+        .collect();
+    groups.sort_by(|a, b| b.members.len().cmp(&a.members.len()).then_with(|| a.members[0].cmp(&b.members[0])));
+    groups
+}
+
+/// 与指定分组中心最相似的其他分组及其相似度
+fn nearest_group(groups: &[TopicGroup], index: usize) -> Option<(usize, f32)> {
+    groups
+        .iter()
+        .enumerate()
+        .filter(|(other, _)| *other != index)
+        .map(|(other, group)| (other, cosine_similarity(&groups[index].centroid, &group.centroid)))
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+}
+
+/// 提取标题中出现在最多文档里的关键词
+///
+/// 标题按非字母数字字符切分为词，忽略单字符词与纯数字；出现文档数相同的关键词按字典序排列。
+pub fn title_keywords(titles: &[&str], limit: usize) -> Vec<String> {
+    let mut frequency: HashMap<String, usize> = HashMap::new();
+    for title in titles {
+        let words: HashSet<String> = title
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+            .filter(|word| word.chars().count() > 1 && !word.chars().all(|c| c.is_ascii_digit()))
+            .collect();
+        for word in words {
+            *frequency.entry(word).or_default() += 1;
+        }
+    }
+
+    let mut keywords: Vec<(String, usize)> = frequency.into_iter().collect();
+    keywords.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    keywords.into_iter().take(limit).map(|(word, _)| word).collect()
+}
+
+/// 由关键词拼接主题名称，没有关键词时使用最具代表性的标题
+fn keyword_label(titles: &[&str], keywords: &[String]) -> String {
+    if keywords.is_empty() {
+        return titles.first().map(|title| truncate_chars(title, MAX_LABEL_CHARS)).unwrap_or_default();
+    }
+    keywords.iter().take(3).cloned().collect::<Vec<_>>().join(" / ")
+}
+
+/// 构造生成主题名称的提示词
+fn label_prompt(titles: &[&str], keywords: &[String]) -> String {
+    let mut prompt = String::from(
+        "以下是知识库中属于同一主题的文档标题，请用一个不超过 12 个字的短语概括该主题。只输出主题名称，不要解释。\n\n",
+    );
+    for title in titles {
+        prompt.push_str("- ");
+        prompt.push_str(title);
+        prompt.push('\n');
+    }
+    if !keywords.is_empty() {
+        prompt.push_str(&format!("\n高频关键词：{}\n", keywords.join("、")));
+    }
+    prompt
+}
+
+/// 清理模型返回的主题名称：取第一行，去掉引号与前缀，过长时截断
+fn clean_label(text: &str) -> Option<String> {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let line = line
+        .strip_prefix("主题名称：")
+        .or_else(|| line.strip_prefix("主题："))
+        .unwrap_or(line);
+    let label = line.trim_matches(|c: char| c.is_whitespace() || "\"'“”‘’「」《》*#".contains(c));
+    if label.is_empty() {
+        return None;
+    }
+    Some(truncate_chars(label, MAX_LABEL_CHARS))
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+fn normalize(vector: &[f32]) -> Vec<f32> {
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return vector.to_vec();
+    }
+    vector.iter().map(|value| value / norm).collect()
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn nearest_centroid(vector: &[f32], centroids: &[Vec<f32>]) -> usize {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(index, centroid)| (index, dot(vector, centroid)))
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal).then_with(|| b.0.cmp(&a.0)))
+        .map(|(index, _)| index)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_count() {
+        assert_eq!(topic_count(0), 0);
+        assert_eq!(topic_count(1), 1);
+        assert_eq!(topic_count(8), 2);
+        assert_eq!(topic_count(200), 10);
+        assert_eq!(topic_count(5_000), MAX_TOPICS);
+    }
+
+    #[test]
+    fn test_cluster_topics_separates_directions() {
+        let vectors = vec![
+            vec![1.0, 0.1, 0.0],
+            vec![0.0, 1.0, 0.1],
+            vec![0.9, 0.0, 0.1],
+            vec![0.1, 0.9, 0.0],
+            vec![1.0, 0.0, 0.0],
+        ];
+
+        let groups = cluster_topics(&vectors, 2);
+        assert_eq!(groups.len(), 2);
+        let mut first = groups[0].members.clone();
+        first.sort();
+        assert_eq!(first, vec![0, 2, 4]);
+        let mut second = groups[1].members.clone();
+        second.sort();
+        assert_eq!(second, vec![1, 3]);
+        assert!(groups[0].similarities.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(nearest_group(&groups, 0).is_some_and(|(nearest, similarity)| nearest == 1 && similarity < 0.5));
+    }
+
+    #[test]
+    fn test_labels_from_titles() {
+        let titles = ["Refund policy", "Refund request form", "退款 流程", "Shipping and refund FAQ"];
+        let keywords = title_keywords(&titles, 3);
+        assert_eq!(keywords[0], "refund");
+        assert_eq!(keyword_label(&titles, &keywords), keywords.join(" / "));
+        assert_eq!(keyword_label(&["1"], &[]), "1");
+
+        assert_eq!(clean_label("\n主题名称：“退款与售后”\n说明……").as_deref(), Some("退款与售后"));
+        assert!(clean_label("  \n ").is_none());
+    }
+}
@@ -0,0 +1,438 @@
+// 问答会话记录导出
+// 通过后台任务队列将会话或时间段内的问答记录导出为 JSON/CSV 文件，并签发限时下载链接
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::errors::AiStudioError;
+use crate::services::qa_transcript::{QaTranscriptService, TranscriptFilter, TranscriptRecord};
+use crate::services::task_queue::{TaskExecutor, TaskInfo, TaskQueueService, TaskStatus, TaskType};
+
+/// 按时间段导出时允许的最大跨度（天）
+const MAX_EXPORT_RANGE_DAYS: i64 = 366;
+
+/// 下载链接有效期（小时），导出文件在此之后会被清理
+pub const DOWNLOAD_URL_TTL_HOURS: i64 = 24;
+
+/// CSV 表头
+const CSV_HEADER: [&str; 12] = [
+    "created_at", "session_id", "query_id", "user_id", "knowledge_base_id", "question", "answer",
+    "confidence_score", "citations", "feedback_type", "feedback_rating", "feedback_comment",
+];
+
+/// 导出文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptExportFormat {
+    /// JSON 数组
+    Json,
+    /// CSV 表格，引用以「标题 (文档 ID)」形式合并为一列
+    Csv,
+}
+
+impl TranscriptExportFormat {
+    /// 文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+
+    /// 下载时的内容类型
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json; charset=utf-8",
+            Self::Csv => "text/csv; charset=utf-8",
+        }
+    }
+}
+
+/// 创建会话记录导出请求，会话 ID 与起始时间至少指定一个
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateTranscriptExportRequest {
+    /// 导出格式
+    pub format: TranscriptExportFormat,
+    /// 导出单个会话
+    pub session_id: Option<String>,
+    /// 起始时间（含）
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间（不含），默认当前时间
+    pub end_time: Option<DateTime<Utc>>,
+    /// 按知识库过滤
+    pub knowledge_base_id: Option<Uuid>,
+    /// 按提问用户过滤，仅管理员可导出其他用户的记录
+    pub user_id: Option<Uuid>,
+}
+
+impl CreateTranscriptExportRequest {
+    /// 校验请求并转换为查询条件
+    pub fn into_filter(self) -> Result<TranscriptFilter, AiStudioError> {
+        let session_id = self.session_id.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        if session_id.is_none() && self.start_time.is_none() {
+            return Err(AiStudioError::validation("session_id", "必须指定会话 ID 或起始时间"));
+        }
+
+        let end_time = match (self.start_time, self.end_time) {
+            (Some(_), None) => Some(Utc::now()),
+            (_, end_time) => end_time,
+        };
+        if let (Some(start), Some(end)) = (self.start_time, end_time) {
+            if start >= end {
+                return Err(AiStudioError::validation("end_time", "结束时间必须晚于起始时间"));
+            }
+            if end - start > Duration::days(MAX_EXPORT_RANGE_DAYS) {
+                return Err(AiStudioError::validation(
+                    "start_time",
+                    format!("导出时间跨度不能超过 {} 天", MAX_EXPORT_RANGE_DAYS),
+                ));
+            }
+        }
+
+        Ok(TranscriptFilter {
+            session_id,
+            start_time: self.start_time,
+            end_time,
+            knowledge_base_id: self.knowledge_base_id,
+            user_id: self.user_id,
+        })
+    }
+}
+
+/// 会话记录导出状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TranscriptExportResponse {
+    /// 导出 ID
+    pub export_id: Uuid,
+    /// 状态：pending、running、completed、failed、cancelled
+    pub status: String,
+    /// 导出格式
+    pub format: TranscriptExportFormat,
+    /// 导出的记录数
+    pub record_count: Option<u32>,
+    /// 文件大小（字节）
+    pub file_size: Option<u64>,
+    /// 签名下载链接，导出完成后返回
+    pub download_url: Option<String>,
+    /// 下载链接过期时间
+    pub download_expires_at: Option<DateTime<Utc>>,
+    /// 失败原因
+    pub error_message: Option<String>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 完成时间
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// 导出任务参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExportParameters {
+    format: TranscriptExportFormat,
+    requested_by: Uuid,
+    filter: TranscriptFilter,
+}
+
+/// 下载链接签名声明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadClaims {
+    /// 导出 ID
+    pub sub: Uuid,
+    /// 租户 ID
+    pub tid: Uuid,
+    /// 导出格式
+    pub fmt: TranscriptExportFormat,
+    /// 过期时间（Unix 时间戳）
+    pub exp: i64,
+}
+
+/// 会话记录导出服务
+pub struct TranscriptExportService {
+    queue: Arc<TaskQueueService>,
+    signing_secret: String,
+}
+
+impl TranscriptExportService {
+    /// 创建导出服务，`signing_secret` 用于签发下载链接
+    pub fn new(queue: Arc<TaskQueueService>, signing_secret: String) -> Self {
+        Self { queue, signing_secret }
+    }
+
+    /// 提交导出任务
+    #[instrument(skip(self, filter))]
+    pub async fn submit(
+        &self,
+        tenant_id: Uuid,
+        requested_by: Uuid,
+        format: TranscriptExportFormat,
+        filter: TranscriptFilter,
+    ) -> Result<TranscriptExportResponse, AiStudioError> {
+        let parameters = serde_json::to_value(ExportParameters { format, requested_by, filter })?;
+        let export_id = self.queue
+            .submit_task(TaskType::QaTranscriptExport, tenant_id, parameters, None)
+            .await?;
+
+        info!("会话记录导出任务已提交: export_id={}, tenant={}", export_id, tenant_id);
+        self.status(tenant_id, export_id).await
+    }
+
+    /// 查询导出状态，完成时签发下载链接
+    pub async fn status(&self, tenant_id: Uuid, export_id: Uuid) -> Result<TranscriptExportResponse, AiStudioError> {
+        let task = self.queue
+            .get_task_status(export_id)
+            .await
+            .filter(|task| task.tenant_id == tenant_id && task.task_type == TaskType::QaTranscriptExport)
+            .ok_or_else(|| AiStudioError::not_found("导出任务"))?;
+        let parameters: ExportParameters = serde_json::from_value(task.parameters.clone())?;
+
+        let (download_url, download_expires_at) = if task.status == TaskStatus::Completed {
+            let expires_at = Utc::now() + Duration::hours(DOWNLOAD_URL_TTL_HOURS);
+            let claims = DownloadClaims { sub: export_id, tid: tenant_id, fmt: parameters.format, exp: expires_at.timestamp() };
+            let token = sign_download_token(&self.signing_secret, &claims)?;
+            (Some(download_url(export_id, &token)), Some(expires_at))
+        } else {
+            (None, None)
+        };
+
+        let result = task.result.clone().unwrap_or_default();
+        Ok(TranscriptExportResponse {
+            export_id,
+            status: serde_json::to_value(&task.status)?.as_str().unwrap_or_default().to_string(),
+            format: parameters.format,
+            record_count: result.get("record_count").and_then(|v| v.as_u64()).map(|v| v as u32),
+            file_size: result.get("file_size").and_then(|v| v.as_u64()),
+            download_url,
+            download_expires_at,
+            error_message: task.error_message,
+            created_at: task.created_at,
+            completed_at: task.completed_at,
+        })
+    }
+}
+
+/// 会话记录导出任务执行器
+pub struct TranscriptExportExecutor {
+    db: DatabaseConnection,
+    storage_root: PathBuf,
+}
+
+impl TranscriptExportExecutor {
+    /// 创建执行器，导出文件写入 `storage_root/exports/transcripts/<租户 ID>/`
+    pub fn new(db: DatabaseConnection, storage_root: impl Into<PathBuf>) -> Self {
+        Self { db, storage_root: storage_root.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskExecutor for TranscriptExportExecutor {
+    async fn execute(&self, task: &mut TaskInfo) -> Result<(), AiStudioError> {
+        let parameters: ExportParameters = serde_json::from_value(task.parameters.clone())?;
+
+        let records = QaTranscriptService::new(self.db.clone())
+            .load_transcripts(task.tenant_id, &parameters.filter)
+            .await?;
+        task.total_count = Some(records.len() as u32);
+        task.progress = 50;
+
+        let content = match parameters.format {
+            TranscriptExportFormat::Json => serde_json::to_string_pretty(&records)?,
+            TranscriptExportFormat::Csv => render_csv(&records),
+        };
+
+        let path = export_file_path(&self.storage_root, task.tenant_id, task.id, parameters.format);
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await
+                .map_err(|e| AiStudioError::internal(format!("创建导出目录失败: {}", e)))?;
+            remove_expired_exports(dir).await;
+        }
+        tokio::fs::write(&path, content.as_bytes()).await
+            .map_err(|e| AiStudioError::internal(format!("写入导出文件失败: {}", e)))?;
+
+        task.success_count = records.len() as u32;
+        task.result = Some(serde_json::json!({
+            "record_count": records.len(),
+            "file_size": content.len(),
+        }));
+        info!("会话记录导出完成: export_id={}, 记录数={}, 请求人={}", task.id, records.len(), parameters.requested_by);
+        Ok(())
+    }
+
+    fn supported_task_types(&self) -> Vec<TaskType> {
+        vec![TaskType::QaTranscriptExport]
+    }
+}
+
+/// 导出文件路径
+pub fn export_file_path(storage_root: &Path, tenant_id: Uuid, export_id: Uuid, format: TranscriptExportFormat) -> PathBuf {
+    storage_root
+        .join("exports")
+        .join("transcripts")
+        .join(tenant_id.to_string())
+        .join(format!("{}.{}", export_id, format.extension()))
+}
+
+/// 下载链接
+pub fn download_url(export_id: Uuid, token: &str) -> String {
+    format!("/api/v1/downloads/transcripts/{}?token={}", export_id, token)
+}
+
+/// 签发下载令牌
+pub fn sign_download_token(secret: &str, claims: &DownloadClaims) -> Result<String, AiStudioError> {
+    encode(&Header::new(Algorithm::HS256), claims, &EncodingKey::from_secret(secret.as_bytes()))
+        .map_err(|e| AiStudioError::internal(format!("签发下载令牌失败: {}", e)))
+}
+
+/// 校验下载令牌，令牌必须与导出 ID 对应且未过期
+pub fn verify_download_token(secret: &str, export_id: Uuid, token: &str) -> Result<DownloadClaims, AiStudioError> {
+    let claims = decode::<DownloadClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::new(Algorithm::HS256),
+    )
+    .map_err(|_| AiStudioError::forbidden("下载链接无效或已过期"))?
+    .claims;
+
+    if claims.sub != export_id {
+        return Err(AiStudioError::forbidden("下载链接无效或已过期"));
+    }
+    Ok(claims)
+}
+
+/// 将会话记录渲染为 CSV
+pub fn render_csv(records: &[TranscriptRecord]) -> String {
+    let mut csv = CSV_HEADER.join(",");
+    csv.push_str("\r\n");
+
+    for record in records {
+        let citations = record.citations.iter()
+            .map(|c| format!("{} ({})", c.title, c.document_id))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let fields = [
+            record.created_at.to_rfc3339(),
+            record.session_id.clone().unwrap_or_default(),
+            record.query_id.clone().unwrap_or_default(),
+            record.user_id.map(|id| id.to_string()).unwrap_or_default(),
+            record.knowledge_base_id.map(|id| id.to_string()).unwrap_or_default(),
+            record.question.clone(),
+            record.answer.clone().unwrap_or_default(),
+            format!("{:.3}", record.confidence_score),
+            citations,
+            record.feedback_type.clone().unwrap_or_default(),
+            record.feedback_rating.map(|r| r.to_string()).unwrap_or_default(),
+            record.feedback_comment.clone().unwrap_or_default(),
+        ];
+        csv.push_str(&fields.iter().map(|f| escape_csv(f)).collect::<Vec<_>>().join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// 按 RFC 4180 转义 CSV 字段
+pub(crate) fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// 删除目录中超过下载有效期的导出文件
+pub(crate) async fn remove_expired_exports(dir: &Path) {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return;
+    };
+    let ttl = std::time::Duration::from_secs(DOWNLOAD_URL_TTL_HOURS as u64 * 3600);
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let expired = entry.metadata().await
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > ttl);
+        if expired {
+            if let Err(e) = tokio::fs::remove_file(entry.path()).await {
+                warn!("清理过期导出文件失败: path={:?}, error={}", entry.path(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::qa_transcript::TranscriptCitation;
+
+    fn record() -> TranscriptRecord {
+        TranscriptRecord {
+            query_id: Some("rag_1".to_string()),
+            session_id: Some("session_1".to_string()),
+            knowledge_base_id: None,
+            user_id: None,
+            question: "报销需要哪些材料？".to_string(),
+            answer: Some("需要发票, 以及\"审批单\"".to_string()),
+            confidence_score: 0.8,
+            citations: vec![TranscriptCitation {
+                document_id: Uuid::nil(),
+                title: "差旅制度".to_string(),
+                relevance_score: 0.9,
+                chunk_ids: Vec::new(),
+            }],
+            feedback_type: Some("helpful".to_string()),
+            feedback_rating: Some(5),
+            feedback_comment: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_render_csv_escapes_fields() {
+        let csv = render_csv(&[record()]);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert!(lines[1].contains("\"需要发票, 以及\"\"审批单\"\"\""));
+        assert!(lines[1].contains(&format!("差旅制度 ({})", Uuid::nil())));
+    }
+
+    #[test]
+    fn test_download_token_roundtrip() {
+        let export_id = Uuid::new_v4();
+        let claims = DownloadClaims {
+            sub: export_id,
+            tid: Uuid::new_v4(),
+            fmt: TranscriptExportFormat::Csv,
+            exp: (Utc::now() + Duration::hours(1)).timestamp(),
+        };
+        let token = sign_download_token("secret", &claims).unwrap();
+
+        assert_eq!(verify_download_token("secret", export_id, &token).unwrap().tid, claims.tid);
+        assert!(verify_download_token("other", export_id, &token).is_err());
+        assert!(verify_download_token("secret", Uuid::new_v4(), &token).is_err());
+    }
+
+    #[test]
+    fn test_export_request_validation() {
+        let request = CreateTranscriptExportRequest {
+            format: TranscriptExportFormat::Json,
+            session_id: None,
+            start_time: None,
+            end_time: None,
+            knowledge_base_id: None,
+            user_id: None,
+        };
+        assert!(request.clone().into_filter().is_err());
+
+        let filter = CreateTranscriptExportRequest { start_time: Some(Utc::now() - Duration::days(7)), ..request.clone() }
+            .into_filter()
+            .unwrap();
+        assert!(filter.end_time.is_some());
+
+        let too_long = CreateTranscriptExportRequest { start_time: Some(Utc::now() - Duration::days(400)), ..request };
+        assert!(too_long.into_filter().is_err());
+    }
+}
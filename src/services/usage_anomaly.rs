@@ -0,0 +1,490 @@
+// 用量异常检测服务
+// 按小时统计各租户的 token 用量、登录失败与文档删除，与滚动基线比较发现突增，发送告警并可自动限流来源 API 密钥
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, Statement,
+};
+use serde::Serialize;
+use tracing::{debug, info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::UsageAnomalyConfig;
+use crate::db::entities::usage_anomaly::{self, AnomalyMetric};
+use crate::db::entities::{api_key, ApiKey, UsageAnomaly};
+use crate::errors::AiStudioError;
+use crate::services::notification::NotificationService;
+use crate::services::scheduler::PeriodicJob;
+
+/// 用量计数的连接，启用异常检测后设置
+static USAGE_RECORDER: OnceCell<DatabaseConnection> = OnceCell::new();
+
+/// 计数分桶长度（秒）
+const BUCKET_SECS: i64 = 3600;
+
+/// 列表查询的最大条数
+const MAX_LIST_LIMIT: u64 = 200;
+
+/// 启用用量计数
+pub fn install_usage_recorder(db: DatabaseConnection) -> Result<(), AiStudioError> {
+    USAGE_RECORDER.set(db)
+        .map_err(|_| AiStudioError::internal("用量计数已经初始化"))
+}
+
+/// 记录租户用量，计入当前小时
+///
+/// 计数在后台写入，不阻塞调用方；未启用异常检测时直接忽略。
+pub fn record_usage(tenant_id: Uuid, metric: AnomalyMetric, api_key_id: Option<Uuid>, amount: u64) {
+    let Some(db) = USAGE_RECORDER.get() else {
+        return;
+    };
+    if amount == 0 {
+        return;
+    }
+
+    let db = db.clone();
+    let bucket_start = hour_bucket(Utc::now());
+    tokio::spawn(async move {
+        let result = db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO usage_counters (tenant_id, metric, api_key_id, bucket_start, count)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (tenant_id, metric, bucket_start, api_key_id)
+                DO UPDATE SET count = usage_counters.count + EXCLUDED.count
+                "#,
+                vec![
+                    tenant_id.into(),
+                    metric.as_str().into(),
+                    api_key_id.unwrap_or_else(Uuid::nil).into(),
+                    bucket_start.into(),
+                    (amount.min(i64::MAX as u64) as i64).into(),
+                ],
+            ))
+            .await;
+        if let Err(e) = result {
+            warn!(tenant_id = %tenant_id, metric = metric.as_str(), error = %e, "记录用量失败");
+        }
+    });
+}
+
+/// 用量异常响应
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UsageAnomalyResponse {
+    /// 异常 ID
+    pub id: Uuid,
+    /// 异常指标
+    pub metric: AnomalyMetric,
+    /// 异常所在小时的起始时间
+    pub bucket_start: DateTime<Utc>,
+    /// 检测时该小时的观测值
+    pub observed_value: i64,
+    /// 滚动基线的均值
+    pub baseline_mean: f64,
+    /// 滚动基线的标准差
+    pub baseline_stddev: f64,
+    /// 贡献用量最多的 API 密钥
+    pub api_key_id: Option<Uuid>,
+    /// 是否已对该 API 密钥自动限流
+    pub throttled: bool,
+    /// 检测时间
+    pub detected_at: DateTime<Utc>,
+}
+
+impl From<usage_anomaly::Model> for UsageAnomalyResponse {
+    fn from(model: usage_anomaly::Model) -> Self {
+        Self {
+            id: model.id,
+            metric: model.metric,
+            bucket_start: model.bucket_start.with_timezone(&Utc),
+            observed_value: model.observed_value,
+            baseline_mean: model.baseline_mean,
+            baseline_stddev: model.baseline_stddev,
+            api_key_id: model.api_key_id,
+            throttled: model.throttled,
+            detected_at: model.detected_at.with_timezone(&Utc),
+        }
+    }
+}
+
+/// 滚动基线
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Baseline {
+    mean: f64,
+    stddev: f64,
+}
+
+impl Baseline {
+    /// 由历史各小时的计数计算均值与总体标准差
+    fn from_history(history: &[f64]) -> Self {
+        if history.is_empty() {
+            return Self { mean: 0.0, stddev: 0.0 };
+        }
+        let n = history.len() as f64;
+        let mean = history.iter().sum::<f64>() / n;
+        let variance = history.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        Self { mean, stddev: variance.sqrt() }
+    }
+
+    /// 当前值是否显著高于基线
+    ///
+    /// 需同时超过最小计数、均值的倍数与均值加若干倍标准差；标准差按至少 1 计算，避免平稳序列的微小波动触发告警。
+    fn is_exceeded_by(&self, current: f64, min_count: u64, config: &UsageAnomalyConfig) -> bool {
+        current >= min_count as f64
+            && current >= self.mean * config.min_ratio
+            && current > self.mean + config.sensitivity * self.stddev.max(1.0)
+    }
+}
+
+/// 一个租户一个指标的小时序列
+#[derive(Debug, Default)]
+struct MetricSeries {
+    buckets: BTreeMap<DateTime<Utc>, i64>,
+}
+
+impl MetricSeries {
+    /// 拆分为当前小时的计数与此前的历史
+    ///
+    /// 历史从序列中最早的小时开始，没有计数的小时按 0 补齐；历史不足 `min_history_hours` 时返回空。
+    fn split(&self, current_bucket: DateTime<Utc>, min_history_hours: u32) -> Option<(i64, Vec<f64>)> {
+        let current = self.buckets.get(&current_bucket).copied().unwrap_or(0);
+        let first = *self.buckets.keys().next()?;
+        let history_hours = (current_bucket - first).num_hours();
+        if history_hours < min_history_hours.max(1) as i64 {
+            return None;
+        }
+
+        let history = (1..=history_hours)
+            .map(|h| {
+                let bucket = current_bucket - chrono::Duration::hours(h);
+                self.buckets.get(&bucket).copied().unwrap_or(0) as f64
+            })
+            .collect();
+        Some((current, history))
+    }
+}
+
+/// 用量异常检测服务
+pub struct UsageAnomalyService {
+    db: DatabaseConnection,
+    config: UsageAnomalyConfig,
+    notifications: NotificationService,
+}
+
+impl UsageAnomalyService {
+    /// 创建新的用量异常检测服务
+    pub fn new(db: DatabaseConnection, config: UsageAnomalyConfig) -> Self {
+        Self {
+            db,
+            config,
+            notifications: NotificationService::new(),
+        }
+    }
+
+    /// 检测各租户当前小时的用量异常，发送告警并按配置限流来源 API 密钥
+    ///
+    /// 每个租户的每个指标在同一小时内只告警一次；返回本次新发现的异常。
+    #[instrument(skip(self))]
+    pub async fn detect_anomalies(&self) -> Result<Vec<usage_anomaly::Model>, AiStudioError> {
+        let current_bucket = hour_bucket(Utc::now());
+        let window_start = current_bucket - chrono::Duration::hours(self.config.baseline_hours as i64);
+
+        let series = self.load_series(window_start).await?;
+        let mut detected = Vec::new();
+
+        for ((tenant_id, metric), series) in series {
+            let Some((current, history)) = series.split(current_bucket, self.config.min_history_hours) else {
+                continue;
+            };
+            let baseline = Baseline::from_history(&history);
+            if !baseline.is_exceeded_by(current as f64, self.min_count(metric), &self.config) {
+                continue;
+            }
+
+            match self.raise_anomaly(tenant_id, metric, current_bucket, current, baseline).await {
+                Ok(Some(anomaly)) => detected.push(anomaly),
+                Ok(None) => debug!(tenant_id = %tenant_id, metric = metric.as_str(), "本小时已告警，跳过"),
+                Err(e) => warn!(tenant_id = %tenant_id, metric = metric.as_str(), error = %e, "处理用量异常失败"),
+            }
+        }
+
+        // 早于基线窗口的计数不再参与检测
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "DELETE FROM usage_counters WHERE bucket_start < $1",
+                vec![window_start.into()],
+            ))
+            .await?;
+
+        if !detected.is_empty() {
+            info!("发现 {} 个用量异常", detected.len());
+        }
+        Ok(detected)
+    }
+
+    /// 列出租户最近的用量异常
+    #[instrument(skip(self))]
+    pub async fn list_anomalies(&self, tenant_id: Uuid, limit: Option<u64>) -> Result<Vec<UsageAnomalyResponse>, AiStudioError> {
+        let anomalies = UsageAnomaly::find()
+            .filter(usage_anomaly::Column::TenantId.eq(tenant_id))
+            .order_by_desc(usage_anomaly::Column::DetectedAt)
+            .limit(limit.unwrap_or(50).clamp(1, MAX_LIST_LIMIT))
+            .all(&self.db)
+            .await?;
+
+        Ok(anomalies.into_iter().map(UsageAnomalyResponse::from).collect())
+    }
+
+    /// 读取窗口内各租户各指标的小时计数
+    async fn load_series(&self, window_start: DateTime<Utc>) -> Result<HashMap<(Uuid, AnomalyMetric), MetricSeries>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT tenant_id, metric, bucket_start, SUM(count)::BIGINT AS total
+                FROM usage_counters
+                WHERE bucket_start >= $1
+                GROUP BY tenant_id, metric, bucket_start
+                "#,
+                vec![window_start.into()],
+            ))
+            .await?;
+
+        let mut series: HashMap<(Uuid, AnomalyMetric), MetricSeries> = HashMap::new();
+        for row in rows {
+            let metric: String = row.try_get("", "metric")?;
+            let Some(metric) = AnomalyMetric::parse(&metric) else {
+                continue;
+            };
+            let tenant_id: Uuid = row.try_get("", "tenant_id")?;
+            let bucket_start: DateTime<Utc> = row.try_get("", "bucket_start")?;
+            let total: i64 = row.try_get("", "total")?;
+            series.entry((tenant_id, metric)).or_default().buckets.insert(bucket_start, total);
+        }
+        Ok(series)
+    }
+
+    /// 记录异常并告警，本小时已记录过时返回空
+    async fn raise_anomaly(
+        &self,
+        tenant_id: Uuid,
+        metric: AnomalyMetric,
+        bucket_start: DateTime<Utc>,
+        observed: i64,
+        baseline: Baseline,
+    ) -> Result<Option<usage_anomaly::Model>, AiStudioError> {
+        let dominant_key = self.dominant_api_key(tenant_id, metric, bucket_start, observed).await?;
+
+        let inserted = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                INSERT INTO usage_anomalies (tenant_id, metric, bucket_start, observed_value, baseline_mean, baseline_stddev, api_key_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (tenant_id, metric, bucket_start) DO NOTHING
+                RETURNING id
+                "#,
+                vec![
+                    tenant_id.into(),
+                    metric.as_str().into(),
+                    bucket_start.into(),
+                    observed.into(),
+                    baseline.mean.into(),
+                    baseline.stddev.into(),
+                    dominant_key.into(),
+                ],
+            ))
+            .await?;
+        let Some(row) = inserted else {
+            return Ok(None);
+        };
+        let anomaly_id: Uuid = row.try_get("", "id")?;
+
+        let mut anomaly = UsageAnomaly::find_by_id(anomaly_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("用量异常"))?;
+
+        // 登录失败不经过 API 密钥，只对 token 用量与文档删除限流
+        if let Some(key_id) = dominant_key.filter(|_| self.config.auto_throttle && metric != AnomalyMetric::FailedLogins) {
+            match self.throttle_api_key(tenant_id, key_id).await {
+                Ok(true) => {
+                    let mut active: usage_anomaly::ActiveModel = anomaly.into();
+                    active.throttled = Set(true);
+                    anomaly = active.update(&self.db).await?;
+                }
+                Ok(false) => {}
+                Err(e) => warn!(api_key_id = %key_id, error = %e, "自动限流 API 密钥失败"),
+            }
+        }
+
+        warn!(
+            tenant_id = %tenant_id,
+            metric = metric.as_str(),
+            observed,
+            baseline_mean = baseline.mean,
+            throttled = anomaly.throttled,
+            "检测到用量异常"
+        );
+
+        if let Err(e) = self.notifications.send_usage_anomaly(&anomaly).await {
+            warn!(anomaly_id = %anomaly.id, error = %e, "用量异常告警发送失败");
+        }
+        Ok(Some(anomaly))
+    }
+
+    /// 贡献当前小时用量过半的 API 密钥
+    async fn dominant_api_key(
+        &self,
+        tenant_id: Uuid,
+        metric: AnomalyMetric,
+        bucket_start: DateTime<Utc>,
+        observed: i64,
+    ) -> Result<Option<Uuid>, AiStudioError> {
+        let row = self.db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                SELECT api_key_id, count
+                FROM usage_counters
+                WHERE tenant_id = $1 AND metric = $2 AND bucket_start = $3 AND api_key_id <> $4
+                ORDER BY count DESC
+                LIMIT 1
+                "#,
+                vec![tenant_id.into(), metric.as_str().into(), bucket_start.into(), Uuid::nil().into()],
+            ))
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let key_id: Uuid = row.try_get("", "api_key_id")?;
+        let count: i64 = row.try_get("", "count")?;
+        Ok((count * 2 > observed).then_some(key_id))
+    }
+
+    /// 临时限流 API 密钥，密钥不存在或不属于该租户时返回 false
+    async fn throttle_api_key(&self, tenant_id: Uuid, key_id: Uuid) -> Result<bool, AiStudioError> {
+        let Some(key) = ApiKey::find_by_id(key_id)
+            .filter(api_key::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        let mut permissions = key.get_permissions()
+            .map_err(|e| AiStudioError::internal(format!("解析 API 密钥权限失败: {}", e)))?;
+        let until = Utc::now() + chrono::Duration::minutes(self.config.throttle_minutes as i64);
+        permissions.throttled_until = Some(until);
+
+        let mut active: api_key::ActiveModel = key.into();
+        active.permissions = Set(serde_json::to_value(&permissions)?);
+        active.updated_at = Set(Utc::now().into());
+        active.update(&self.db).await?;
+
+        info!(tenant_id = %tenant_id, api_key_id = %key_id, until = %until, "API 密钥因用量异常被临时限流");
+        Ok(true)
+    }
+
+    fn min_count(&self, metric: AnomalyMetric) -> u64 {
+        match metric {
+            AnomalyMetric::Tokens => self.config.min_tokens,
+            AnomalyMetric::FailedLogins => self.config.min_failed_logins,
+            AnomalyMetric::DocumentDeletions => self.config.min_document_deletions,
+        }
+    }
+}
+
+/// 时间所在小时的起始时间
+fn hour_bucket(time: DateTime<Utc>) -> DateTime<Utc> {
+    let ts = time.timestamp();
+    DateTime::from_timestamp(ts - ts.rem_euclid(BUCKET_SECS), 0).unwrap_or(time)
+}
+
+/// 用量异常检测周期任务
+pub struct UsageAnomalyJob {
+    service: Arc<UsageAnomalyService>,
+}
+
+impl UsageAnomalyJob {
+    /// 创建新的用量异常检测周期任务
+    pub fn new(service: Arc<UsageAnomalyService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for UsageAnomalyJob {
+    fn name(&self) -> &str {
+        "usage_anomaly_detection"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.service.config.interval_secs)
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        self.service.detect_anomalies().await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    fn at(hour: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 - 1_700_000_000 % BUCKET_SECS + hour * BUCKET_SECS, 0).unwrap()
+    }
+
+    #[test]
+    fn test_hour_bucket() {
+        let bucket = at(0);
+        assert_eq!(hour_bucket(bucket), bucket);
+        assert_eq!(hour_bucket(bucket + chrono::Duration::minutes(59)), bucket);
+        assert_eq!(hour_bucket(bucket + chrono::Duration::minutes(60)), at(1));
+    }
+
+    #[test]
+    fn test_series_split_fills_gaps_and_requires_history() {
+        let mut series = MetricSeries::default();
+        series.buckets.insert(at(0), 10);
+        series.buckets.insert(at(2), 20);
+        series.buckets.insert(at(3), 90);
+
+        let (current, history) = series.split(at(3), 3).unwrap();
+        assert_eq!(current, 90);
+        assert_eq!(history, vec![20.0, 0.0, 10.0]);
+
+        // 历史不足时不检测
+        assert!(series.split(at(3), 4).is_none());
+    }
+
+    #[test]
+    fn test_baseline_detects_spike() {
+        let config = AppConfig::default().usage_anomaly;
+        let steady: Vec<f64> = (0..48).map(|h| if h % 2 == 0 { 900.0 } else { 1100.0 }).collect();
+        let baseline = Baseline::from_history(&steady);
+        assert!((baseline.mean - 1000.0).abs() < f64::EPSILON);
+        assert!((baseline.stddev - 100.0).abs() < 1e-9);
+
+        // 平常波动与低于最小计数的突增都不告警
+        assert!(!baseline.is_exceeded_by(1200.0, 100, &config));
+        assert!(!baseline.is_exceeded_by(5000.0, 10000, &config));
+        assert!(baseline.is_exceeded_by(5000.0, 100, &config));
+
+        // 基线为零时按最小计数判断
+        let idle = Baseline::from_history(&[0.0; 24]);
+        assert!(!idle.is_exceeded_by(10.0, config.min_failed_logins, &config));
+        assert!(idle.is_exceeded_by(config.min_failed_logins as f64, config.min_failed_logins, &config));
+    }
+}
@@ -0,0 +1,456 @@
+// 用户批量导入服务
+// 从 CSV 批量邀请租户用户：为每行创建待激活账户、生成带过期时间的邀请令牌并发送邀请邮件
+//
+// 邀请令牌复用用户表的密码重置令牌字段，处于 Pending 状态的用户通过
+// `AuthService::accept_invitation` 设置密码并激活账户
+
+use std::collections::HashSet;
+use bcrypt::{hash, DEFAULT_COST};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use serde::Serialize;
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::{tenant, user, Tenant};
+use crate::db::repositories::UserRepository;
+use crate::errors::AiStudioError;
+use crate::plugins::event_bus::{event_types, SystemEvent, SystemEventBus};
+use crate::services::notification::NotificationService;
+
+/// 邀请默认有效期（小时）
+pub const DEFAULT_INVITATION_EXPIRY_HOURS: i64 = 72;
+
+/// 邀请最长有效期（小时）
+const MAX_INVITATION_EXPIRY_HOURS: i64 = 24 * 30;
+
+/// 单次导入的最大行数
+const MAX_IMPORT_ROWS: usize = 1000;
+
+/// 用户导入结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UserImportReport {
+    /// 数据行总数（不含表头）
+    pub total_rows: usize,
+    /// 成功邀请数
+    pub invited_count: usize,
+    /// 失败行数
+    pub failed_count: usize,
+    /// 已发出的邀请
+    pub invitations: Vec<InvitationSummary>,
+    /// 失败行及原因
+    pub errors: Vec<ImportRowError>,
+}
+
+/// 已发出的邀请
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct InvitationSummary {
+    /// CSV 行号（表头为第 1 行）
+    pub row: usize,
+    /// 用户 ID
+    pub user_id: Uuid,
+    /// 邮箱
+    pub email: String,
+    /// 用户名
+    pub username: String,
+    /// 邀请过期时间
+    pub expires_at: DateTime<Utc>,
+    /// 邀请邮件是否发送成功
+    pub email_sent: bool,
+}
+
+/// 导入失败的行
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportRowError {
+    /// CSV 行号（表头为第 1 行）
+    pub row: usize,
+    /// 邮箱（能解析出时）
+    pub email: Option<String>,
+    /// 失败原因
+    pub message: String,
+}
+
+/// 解析后的 CSV 行
+#[derive(Debug, Clone, PartialEq)]
+struct ImportRow {
+    row: usize,
+    email: String,
+    username: Option<String>,
+    display_name: Option<String>,
+    role: Option<String>,
+}
+
+/// 用户批量导入服务
+pub struct UserImportService {
+    db: DatabaseConnection,
+    notifications: NotificationService,
+}
+
+impl UserImportService {
+    /// 创建新的用户导入服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            notifications: NotificationService::new(),
+        }
+    }
+
+    /// 从 CSV 导入并邀请用户
+    ///
+    /// CSV 首行为表头，必须包含 `email` 列，可选 `username`、`display_name`、`role` 列。
+    /// 单行失败不影响其他行，失败原因在结果的 `errors` 中按行返回。
+    #[instrument(skip(self, csv))]
+    pub async fn import_users(
+        &self,
+        tenant_id: Uuid,
+        invited_by: Uuid,
+        csv: &str,
+        expires_in_hours: Option<i64>,
+    ) -> Result<UserImportReport, AiStudioError> {
+        let expires_in_hours = expires_in_hours.unwrap_or(DEFAULT_INVITATION_EXPIRY_HOURS);
+        if !(1..=MAX_INVITATION_EXPIRY_HOURS).contains(&expires_in_hours) {
+            return Err(AiStudioError::validation(
+                "expires_in_hours",
+                format!("邀请有效期必须在 1 到 {} 小时之间", MAX_INVITATION_EXPIRY_HOURS),
+            ));
+        }
+
+        let tenant = Tenant::find_by_id(tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+        if tenant.status != tenant::TenantStatus::Active {
+            return Err(AiStudioError::forbidden("租户已被暂停或停用".to_string()));
+        }
+
+        let (rows, mut errors) = parse_import_csv(csv)?;
+        let total_rows = rows.len() + errors.len();
+        info!(tenant_id = %tenant_id, total_rows, "批量导入用户");
+
+        let max_users = tenant.get_quota_limits().unwrap_or_default().max_users as u64;
+        let mut remaining = max_users.saturating_sub(UserRepository::count_by_tenant(&self.db, tenant_id).await?);
+
+        let expires_at = Utc::now() + Duration::hours(expires_in_hours);
+        let mut invitations = Vec::new();
+        let mut seen_emails = HashSet::new();
+        let mut seen_usernames = HashSet::new();
+
+        for row in rows {
+            let email = row.email.to_lowercase();
+            let username = row.username.clone().unwrap_or_else(|| username_from_email(&email));
+            let row_error = |message: String| ImportRowError {
+                row: row.row,
+                email: Some(email.clone()),
+                message,
+            };
+
+            if !seen_emails.insert(email.clone()) {
+                errors.push(row_error("邮箱在文件中重复".to_string()));
+                continue;
+            }
+            if !seen_usernames.insert(username.clone()) {
+                errors.push(row_error(format!("用户名 '{}' 在文件中重复", username)));
+                continue;
+            }
+            let role = match parse_role(row.role.as_deref()) {
+                Ok(role) => role,
+                Err(message) => {
+                    errors.push(row_error(message));
+                    continue;
+                }
+            };
+            if remaining == 0 {
+                errors.push(row_error(format!("租户用户数已达上限 {}", max_users)));
+                continue;
+            }
+
+            let display_name = row.display_name.clone().unwrap_or_else(|| username.clone());
+            match self
+                .create_invited_user(&tenant, invited_by, email.clone(), username, display_name, role, expires_at)
+                .await
+            {
+                Ok((user, token)) => {
+                    remaining -= 1;
+                    let email_sent = match self
+                        .notifications
+                        .send_user_invitation(tenant.id, &user.email, &tenant.display_name, &token, expires_at)
+                        .await
+                    {
+                        Ok(_) => true,
+                        Err(e) => {
+                            warn!(user_id = %user.id, error = %e, "发送邀请邮件失败");
+                            false
+                        }
+                    };
+                    SystemEventBus::global().publish(
+                        SystemEvent::new(
+                            event_types::MEMBER_INVITED,
+                            Some(tenant.id),
+                            serde_json::json!({
+                                "user_id": user.id,
+                                "email": user.email,
+                                "username": user.username,
+                                "role": user.role,
+                            }),
+                        )
+                        .with_actor(Some(invited_by)),
+                    );
+                    invitations.push(InvitationSummary {
+                        row: row.row,
+                        user_id: user.id,
+                        email: user.email,
+                        username: user.username,
+                        expires_at,
+                        email_sent,
+                    });
+                }
+                Err(e) => errors.push(row_error(e.to_string())),
+            }
+        }
+
+        errors.sort_by_key(|e| e.row);
+        info!(
+            tenant_id = %tenant_id,
+            invited = invitations.len(),
+            failed = errors.len(),
+            "用户导入完成"
+        );
+
+        Ok(UserImportReport {
+            total_rows,
+            invited_count: invitations.len(),
+            failed_count: errors.len(),
+            invitations,
+            errors,
+        })
+    }
+
+    /// 创建待激活用户并生成邀请令牌
+    #[allow(clippy::too_many_arguments)]
+    async fn create_invited_user(
+        &self,
+        tenant: &tenant::Model,
+        invited_by: Uuid,
+        email: String,
+        username: String,
+        display_name: String,
+        role: user::UserRole,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(user::Model, String), AiStudioError> {
+        if UserRepository::exists_by_email(&self.db, &email).await? {
+            return Err(AiStudioError::conflict(format!("邮箱 '{}' 已存在", email)));
+        }
+        if UserRepository::exists_by_username_in_tenant(&self.db, tenant.id, &username).await? {
+            return Err(AiStudioError::conflict(format!("用户名 '{}' 在该租户内已存在", username)));
+        }
+
+        // 激活前的密码为随机值，用户无法以此登录
+        let password_hash = hash(Uuid::new_v4().to_string(), DEFAULT_COST)
+            .map_err(|e| AiStudioError::internal(format!("密码哈希失败: {}", e)))?;
+        let token = Uuid::new_v4().simple().to_string();
+        let now = Utc::now();
+
+        let mut metadata = user::UserMetadata::default();
+        metadata.custom_fields.insert("invited_by".to_string(), serde_json::json!(invited_by));
+        metadata.custom_fields.insert("invited_at".to_string(), serde_json::json!(now));
+
+        let user = user::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant.id),
+            username: Set(username),
+            email: Set(email),
+            password_hash: Set(password_hash),
+            display_name: Set(display_name),
+            avatar_url: Set(None),
+            role: Set(role),
+            status: Set(user::UserStatus::Pending),
+            preferences: Set(serde_json::to_value(user::UserPreferences::default())?),
+            permissions: Set(serde_json::to_value(user::UserPermissions::default())?),
+            metadata: Set(serde_json::to_value(metadata)?),
+            phone: Set(None),
+            email_verified: Set(false),
+            email_verified_at: Set(None),
+            phone_verified: Set(false),
+            phone_verified_at: Set(None),
+            two_factor_enabled: Set(false),
+            two_factor_secret: Set(None),
+            last_login_at: Set(None),
+            last_login_ip: Set(None),
+            failed_login_attempts: Set(0),
+            locked_until: Set(None),
+            password_reset_token: Set(Some(token.clone())),
+            password_reset_expires_at: Set(Some(expires_at.into())),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+        };
+
+        let user = user.insert(&self.db).await?;
+        info!(user_id = %user.id, tenant_id = %tenant.id, "已创建待激活的受邀用户");
+        Ok((user, token))
+    }
+}
+
+/// 解析导入 CSV，返回有效行与格式错误的行
+fn parse_import_csv(csv: &str) -> Result<(Vec<ImportRow>, Vec<ImportRowError>), AiStudioError> {
+    let mut lines = csv
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| AiStudioError::validation("csv", "CSV 内容为空"))?;
+    let header: Vec<String> = split_csv_line(header)
+        .into_iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let email_col = column("email")
+        .ok_or_else(|| AiStudioError::validation("csv", "CSV 表头缺少 email 列"))?;
+    let username_col = column("username");
+    let display_name_col = column("display_name");
+    let role_col = column("role");
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (row, line) in lines {
+        if rows.len() + errors.len() >= MAX_IMPORT_ROWS {
+            return Err(AiStudioError::validation(
+                "csv",
+                format!("单次最多导入 {} 个用户", MAX_IMPORT_ROWS),
+            ));
+        }
+
+        let fields = split_csv_line(line);
+        let field = |col: Option<usize>| {
+            col.and_then(|c| fields.get(c))
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let email = field(Some(email_col));
+        match email {
+            Some(email) if is_valid_email(&email) => rows.push(ImportRow {
+                row,
+                email,
+                username: field(username_col),
+                display_name: field(display_name_col),
+                role: field(role_col),
+            }),
+            Some(email) => errors.push(ImportRowError {
+                row,
+                message: format!("无效的邮箱地址: {}", email),
+                email: Some(email),
+            }),
+            None => errors.push(ImportRowError {
+                row,
+                email: None,
+                message: "缺少邮箱".to_string(),
+            }),
+        }
+    }
+
+    Ok((rows, errors))
+}
+
+/// 按 RFC 4180 拆分单行 CSV，支持双引号包裹与 `""` 转义
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+/// 简单的邮箱格式校验
+fn is_valid_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !email.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// 以邮箱本地部分作为默认用户名
+fn username_from_email(email: &str) -> String {
+    email.split('@').next().unwrap_or(email).to_string()
+}
+
+/// 解析角色，缺省为普通用户；批量导入不允许直接授予管理员角色
+fn parse_role(role: Option<&str>) -> Result<user::UserRole, String> {
+    match role.map(|r| r.to_lowercase()).as_deref() {
+        None | Some("user") => Ok(user::UserRole::User),
+        Some("viewer") => Ok(user::UserRole::Viewer),
+        Some("manager") => Ok(user::UserRole::Manager),
+        Some("admin") => Err("批量导入不能授予管理员角色".to_string()),
+        Some(other) => Err(format!("未知角色: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_csv_line() {
+        assert_eq!(split_csv_line("a,b,c"), vec!["a", "b", "c"]);
+        assert_eq!(
+            split_csv_line("a@x.com,\"Li, Lei\",\"say \"\"hi\"\"\""),
+            vec!["a@x.com", "Li, Lei", "say \"hi\""]
+        );
+        assert_eq!(split_csv_line("a,,"), vec!["a", "", ""]);
+    }
+
+    #[test]
+    fn test_parse_import_csv_reports_row_errors() {
+        let csv = "\u{feff}Email,username,role\nalice@example.com,alice,manager\n\nnot-an-email,bob\n,carol\n";
+        let (rows, errors) = parse_import_csv(csv).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].row, 2);
+        assert_eq!(rows[0].username.as_deref(), Some("alice"));
+        assert_eq!(rows[0].role.as_deref(), Some("manager"));
+        assert_eq!(rows[0].display_name, None);
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].row, 4);
+        assert_eq!(errors[0].email.as_deref(), Some("not-an-email"));
+        assert_eq!(errors[1].row, 5);
+        assert_eq!(errors[1].email, None);
+    }
+
+    #[test]
+    fn test_parse_import_csv_requires_email_column() {
+        assert!(parse_import_csv("username\nalice\n").is_err());
+        assert!(parse_import_csv("").is_err());
+    }
+
+    #[test]
+    fn test_parse_role() {
+        assert_eq!(parse_role(None).unwrap(), user::UserRole::User);
+        assert_eq!(parse_role(Some("Viewer")).unwrap(), user::UserRole::Viewer);
+        assert!(parse_role(Some("admin")).is_err());
+        assert!(parse_role(Some("owner")).is_err());
+    }
+}
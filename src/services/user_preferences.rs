@@ -0,0 +1,254 @@
+// 用户偏好服务
+// 读写用户的偏好设置，问答与 Agent 在组装提示词时按偏好调整回答语言、详略和引用方式
+
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use serde::Deserialize;
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::entities::user::{self, AnswerVerbosity, CitationStyle, UserPreferences};
+use crate::db::entities::User;
+use crate::errors::AiStudioError;
+
+/// 语言标识的最大长度
+const MAX_LANGUAGE_LEN: usize = 32;
+
+/// 时区名称的最大长度
+const MAX_TIMEZONE_LEN: usize = 64;
+
+/// 更新用户偏好请求，未提供的字段保持不变
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateUserPreferencesRequest {
+    /// 语言设置
+    pub language: Option<String>,
+    /// 时区设置（IANA 名称，如 Asia/Shanghai）
+    pub timezone: Option<String>,
+    /// 主题设置
+    pub theme: Option<String>,
+    /// 回答语言，空字符串表示跟随语言设置
+    pub answer_language: Option<String>,
+    /// 回答详略程度
+    pub verbosity: Option<AnswerVerbosity>,
+    /// 引用来源的方式
+    pub citation_style: Option<CitationStyle>,
+}
+
+impl UpdateUserPreferencesRequest {
+    /// 将更新合并到现有偏好
+    fn apply(self, mut preferences: UserPreferences) -> Result<UserPreferences, AiStudioError> {
+        if let Some(language) = self.language {
+            preferences.language = validate_language("language", &language)?;
+        }
+        if let Some(timezone) = self.timezone {
+            preferences.timezone = validate_timezone(&timezone)?;
+        }
+        if let Some(theme) = self.theme {
+            preferences.theme = theme.trim().to_string();
+        }
+        if let Some(answer_language) = self.answer_language {
+            preferences.answer_language = if answer_language.trim().is_empty() {
+                None
+            } else {
+                Some(validate_language("answer_language", &answer_language)?)
+            };
+        }
+        if let Some(verbosity) = self.verbosity {
+            preferences.verbosity = verbosity;
+        }
+        if let Some(citation_style) = self.citation_style {
+            preferences.citation_style = citation_style;
+        }
+        Ok(preferences)
+    }
+}
+
+/// 用户偏好服务
+pub struct UserPreferenceService {
+    db: DatabaseConnection,
+}
+
+impl UserPreferenceService {
+    /// 创建新的用户偏好服务实例
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 获取用户偏好
+    #[instrument(skip(self))]
+    pub async fn get_preferences(&self, user_id: Uuid) -> Result<UserPreferences, AiStudioError> {
+        let user = User::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("用户"))?;
+
+        Ok(parse_preferences(&user))
+    }
+
+    /// 获取用户偏好，用户不存在或查询失败时使用默认偏好
+    ///
+    /// 用于请求路径上的提示词组装，偏好缺失不应使问答失败。
+    pub async fn preferences_or_default(&self, user_id: Uuid) -> UserPreferences {
+        match self.get_preferences(user_id).await {
+            Ok(preferences) => preferences,
+            Err(e) => {
+                warn!(user_id = %user_id, error = %e, "读取用户偏好失败，使用默认偏好");
+                UserPreferences::default()
+            }
+        }
+    }
+
+    /// 更新用户偏好
+    #[instrument(skip(self, request))]
+    pub async fn update_preferences(
+        &self,
+        user_id: Uuid,
+        request: UpdateUserPreferencesRequest,
+    ) -> Result<UserPreferences, AiStudioError> {
+        let user = User::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("用户"))?;
+
+        let preferences = request.apply(parse_preferences(&user))?;
+
+        let mut active_user: user::ActiveModel = user.into();
+        active_user.preferences = Set(serde_json::to_value(&preferences)?);
+        active_user.updated_at = Set(Utc::now().into());
+        active_user.update(&self.db).await?;
+
+        info!(user_id = %user_id, "用户偏好已更新");
+        Ok(preferences)
+    }
+}
+
+/// 将用户偏好格式化为提示词片段
+pub fn format_preferences_for_prompt(preferences: &UserPreferences) -> String {
+    let mut section = format!(
+        "回答要求:\n- 使用{}回答\n- 回答风格：{}\n",
+        preferences.effective_answer_language(),
+        preferences.verbosity.style()
+    );
+    let citation = preferences.citation_style.instruction();
+    if !citation.is_empty() {
+        section.push_str(&format!("- {}\n", citation));
+    }
+    section.push_str(&format!("- 涉及日期和时间时按用户所在时区 {} 表述\n\n", preferences.timezone));
+    section
+}
+
+/// 解析用户偏好，早期写入的不完整数据按默认值补齐
+fn parse_preferences(user: &user::Model) -> UserPreferences {
+    user.get_preferences().unwrap_or_else(|e| {
+        warn!(user_id = %user.id, error = %e, "用户偏好格式无效，使用默认偏好");
+        UserPreferences::default()
+    })
+}
+
+/// 校验语言标识，如 zh-CN、en、中文
+fn validate_language(field: &str, language: &str) -> Result<String, AiStudioError> {
+    let language = language.trim();
+    if language.is_empty() || language.chars().count() > MAX_LANGUAGE_LEN {
+        return Err(AiStudioError::validation(
+            field,
+            format!("语言标识不能为空且不能超过 {} 个字符", MAX_LANGUAGE_LEN),
+        ));
+    }
+    Ok(language.to_string())
+}
+
+/// 校验时区名称，接受 UTC、IANA 名称（Area/Location）或 ±HH:MM 偏移
+fn validate_timezone(timezone: &str) -> Result<String, AiStudioError> {
+    let timezone = timezone.trim();
+    let is_offset = |tz: &str| {
+        let bytes = tz.as_bytes();
+        bytes.len() == 6
+            && (bytes[0] == b'+' || bytes[0] == b'-')
+            && bytes[3] == b':'
+            && [1, 2, 4, 5].iter().all(|&i| bytes[i].is_ascii_digit())
+    };
+    let is_iana = |tz: &str| {
+        tz.contains('/')
+            && tz.split('/').all(|part| {
+                !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+'))
+            })
+    };
+
+    if timezone.len() <= MAX_TIMEZONE_LEN && (timezone == "UTC" || is_offset(timezone) || is_iana(timezone)) {
+        Ok(timezone.to_string())
+    } else {
+        Err(AiStudioError::validation("timezone", format!("无效的时区: {}", timezone)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_update_keeps_unset_fields() {
+        let preferences = UpdateUserPreferencesRequest {
+            verbosity: Some(AnswerVerbosity::Concise),
+            answer_language: Some("English".to_string()),
+            ..Default::default()
+        }
+        .apply(UserPreferences::default())
+        .unwrap();
+
+        assert_eq!(preferences.verbosity, AnswerVerbosity::Concise);
+        assert_eq!(preferences.effective_answer_language(), "English");
+        assert_eq!(preferences.citation_style, CitationStyle::Inline);
+        assert_eq!(preferences.timezone, "Asia/Shanghai");
+
+        let cleared = UpdateUserPreferencesRequest {
+            answer_language: Some(" ".to_string()),
+            ..Default::default()
+        }
+        .apply(preferences)
+        .unwrap();
+        assert_eq!(cleared.answer_language, None);
+        assert_eq!(cleared.effective_answer_language(), "zh-CN");
+    }
+
+    #[test]
+    fn test_format_preferences_for_prompt() {
+        let preferences = UserPreferences {
+            answer_language: Some("English".to_string()),
+            verbosity: AnswerVerbosity::Concise,
+            citation_style: CitationStyle::None,
+            timezone: "UTC".to_string(),
+            ..UserPreferences::default()
+        };
+        let section = format_preferences_for_prompt(&preferences);
+
+        assert!(section.contains("使用English回答"));
+        assert!(section.contains(AnswerVerbosity::Concise.style()));
+        assert!(section.contains("时区 UTC"));
+        assert!(!section.contains("标注信息来源"));
+    }
+
+    #[test]
+    fn test_validate_timezone() {
+        assert!(validate_timezone("UTC").is_ok());
+        assert!(validate_timezone("America/Argentina/Buenos_Aires").is_ok());
+        assert!(validate_timezone("+08:00").is_ok());
+        assert!(validate_timezone("Beijing").is_err());
+        assert!(validate_timezone("Asia//Shanghai").is_err());
+        assert!(validate_timezone("+8:00").is_err());
+    }
+
+    #[test]
+    fn test_legacy_preferences_fill_defaults() {
+        let preferences: UserPreferences = serde_json::from_value(serde_json::json!({
+            "language": "en",
+            "timezone": "UTC"
+        }))
+        .unwrap();
+
+        assert_eq!(preferences.language, "en");
+        assert_eq!(preferences.verbosity, AnswerVerbosity::Balanced);
+        assert_eq!(preferences.citation_style, CitationStyle::Inline);
+        assert_eq!(preferences.theme, "default");
+    }
+}
@@ -0,0 +1,327 @@
+// 向量存储迁移
+// 将知识库的向量从当前后端分批复制到目标后端，校验数量后切换知识库配置，并可清理原后端的数据
+
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, Set, Statement};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::vector_store::VectorStoreRegistry;
+use crate::db::entities::knowledge_base::{self, VectorBackend};
+use crate::db::entities::KnowledgeBase;
+use crate::db::update_with_revision;
+use crate::errors::AiStudioError;
+use crate::services::task_queue::{TaskExecutor, TaskInfo, TaskQueueService, TaskStatus, TaskType};
+
+/// 每批迁移的向量数
+const MIGRATION_BATCH_SIZE: i64 = 500;
+
+/// 发起向量存储迁移请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VectorMigrationRequest {
+    /// 目标后端
+    pub target: VectorBackend,
+    /// 迁移完成后保留原后端的数据，默认删除
+    #[serde(default)]
+    pub keep_source: bool,
+}
+
+/// 向量存储迁移结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VectorMigrationSummary {
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 原后端
+    pub source: VectorBackend,
+    /// 目标后端
+    pub target: VectorBackend,
+    /// 已迁移的向量数
+    pub migrated: u64,
+    /// 嵌入记录已完成但原后端缺少向量的数量，这些嵌入需要重新生成
+    pub missing: u64,
+    /// 是否已删除原后端的数据
+    pub source_dropped: bool,
+}
+
+/// 向量存储迁移任务状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VectorMigrationStatus {
+    /// 任务 ID
+    pub task_id: Uuid,
+    /// 知识库 ID
+    pub knowledge_base_id: Uuid,
+    /// 状态：pending、running、completed、failed、cancelled
+    pub status: String,
+    /// 目标后端
+    pub target: VectorBackend,
+    /// 迁移结果，完成后返回
+    pub summary: Option<VectorMigrationSummary>,
+    /// 失败原因
+    pub error_message: Option<String>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 完成时间
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// 迁移任务参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MigrationParameters {
+    knowledge_base_id: Uuid,
+    requested_by: Uuid,
+    target: VectorBackend,
+    keep_source: bool,
+}
+
+/// 向量存储迁移器，任务执行器与命令行工具共用
+pub struct VectorMigrator {
+    db: DatabaseConnection,
+    stores: Arc<VectorStoreRegistry>,
+}
+
+impl VectorMigrator {
+    /// 创建迁移器
+    pub fn new(db: DatabaseConnection, stores: Arc<VectorStoreRegistry>) -> Self {
+        Self { db, stores }
+    }
+
+    /// 迁移知识库的向量
+    ///
+    /// 按嵌入 ID 分批从原后端读取并写入目标后端，目标后端数量不少于已迁移数量时才切换知识库配置；
+    /// 切换前检索仍走原后端，迁移失败不影响现有检索。
+    #[instrument(skip(self))]
+    pub async fn migrate(
+        &self,
+        knowledge_base_id: Uuid,
+        target: VectorBackend,
+        keep_source: bool,
+    ) -> Result<VectorMigrationSummary, AiStudioError> {
+        let source = self.stores.backend_of(knowledge_base_id).await?;
+        if source == target {
+            return Err(AiStudioError::validation("target", format!("知识库已使用 {} 后端", target.as_str())));
+        }
+        let source_store = self.stores.store(source).await?;
+        let target_store = self.stores.store(target).await?;
+        info!("开始迁移向量: kb={}, {} -> {}", knowledge_base_id, source.as_str(), target.as_str());
+
+        let mut migrated = 0u64;
+        let mut missing = 0u64;
+        let mut after = Uuid::nil();
+        loop {
+            let ids = self.completed_embedding_ids(knowledge_base_id, after).await?;
+            let Some(last) = ids.last() else {
+                break;
+            };
+            after = *last;
+
+            let points = source_store.fetch(knowledge_base_id, &ids).await?;
+            target_store.upsert(knowledge_base_id, &points).await?;
+            migrated += points.len() as u64;
+            missing += ids.len().saturating_sub(points.len()) as u64;
+        }
+
+        let target_count = target_store.count(knowledge_base_id).await?;
+        if target_count < migrated {
+            return Err(AiStudioError::internal(format!(
+                "迁移校验失败：已写入 {} 条向量，目标后端仅有 {} 条",
+                migrated, target_count
+            )));
+        }
+        if missing > 0 {
+            warn!("原后端缺少部分向量: kb={}, missing={}", knowledge_base_id, missing);
+        }
+
+        self.switch_backend(knowledge_base_id, source, target).await?;
+
+        let source_dropped = !keep_source;
+        if source_dropped {
+            source_store.drop_knowledge_base(knowledge_base_id).await?;
+        }
+
+        info!(
+            "向量迁移完成: kb={}, {} -> {}, 迁移数={}, 缺失数={}",
+            knowledge_base_id, source.as_str(), target.as_str(), migrated, missing
+        );
+        Ok(VectorMigrationSummary { knowledge_base_id, source, target, migrated, missing, source_dropped })
+    }
+
+    /// 按 ID 顺序读取一批已完成的嵌入 ID
+    async fn completed_embedding_ids(&self, knowledge_base_id: Uuid, after: Uuid) -> Result<Vec<Uuid>, AiStudioError> {
+        let rows = self.db
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT id FROM embeddings WHERE knowledge_base_id = $1 AND status = 'completed' AND id > $2 \
+                 ORDER BY id LIMIT $3",
+                [knowledge_base_id.into(), after.into(), MIGRATION_BATCH_SIZE.into()],
+            ))
+            .await?;
+        rows.iter()
+            .map(|row| row.try_get("", "id").map_err(AiStudioError::from))
+            .collect()
+    }
+
+    /// 切换知识库配置的后端，迁移期间后端被其他操作改变时放弃切换
+    async fn switch_backend(
+        &self,
+        knowledge_base_id: Uuid,
+        source: VectorBackend,
+        target: VectorBackend,
+    ) -> Result<(), AiStudioError> {
+        let kb = KnowledgeBase::find_by_id(knowledge_base_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("知识库"))?;
+        let mut config = kb.get_config()?;
+        if config.vector_backend != source {
+            return Err(AiStudioError::conflict("迁移期间知识库的向量后端已被修改"));
+        }
+        config.vector_backend = target;
+
+        let revision = kb.revision;
+        let mut active: knowledge_base::ActiveModel = kb.into();
+        active.config = Set(serde_json::to_value(&config)?);
+        active.updated_at = Set(Utc::now().into());
+        update_with_revision(&self.db, active, knowledge_base::Column::Revision, revision, "知识库").await?;
+        Ok(())
+    }
+}
+
+/// 向量存储迁移服务
+pub struct VectorMigrationService {
+    stores: Arc<VectorStoreRegistry>,
+    queue: Arc<TaskQueueService>,
+}
+
+impl VectorMigrationService {
+    /// 创建迁移服务
+    pub fn new(stores: Arc<VectorStoreRegistry>, queue: Arc<TaskQueueService>) -> Self {
+        Self { stores, queue }
+    }
+
+    /// 提交迁移任务，目标后端未配置或与当前后端相同时直接拒绝
+    #[instrument(skip(self, request))]
+    pub async fn submit(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        requested_by: Uuid,
+        request: VectorMigrationRequest,
+    ) -> Result<VectorMigrationStatus, AiStudioError> {
+        if !self.stores.is_configured(request.target) {
+            return Err(AiStudioError::validation(
+                "target",
+                format!("未配置 {} 向量存储", request.target.as_str()),
+            ));
+        }
+        if self.stores.backend_of(knowledge_base_id).await? == request.target {
+            return Err(AiStudioError::validation(
+                "target",
+                format!("知识库已使用 {} 后端", request.target.as_str()),
+            ));
+        }
+        if self.has_pending_migration(tenant_id, knowledge_base_id).await {
+            return Err(AiStudioError::conflict("该知识库已有进行中的向量迁移"));
+        }
+
+        let parameters = serde_json::to_value(MigrationParameters {
+            knowledge_base_id,
+            requested_by,
+            target: request.target,
+            keep_source: request.keep_source,
+        })?;
+        let task_id = self.queue
+            .submit_task(TaskType::VectorMigration, tenant_id, parameters, None)
+            .await?;
+
+        info!("向量迁移任务已提交: task_id={}, kb={}, target={}", task_id, knowledge_base_id, request.target.as_str());
+        self.status(tenant_id, knowledge_base_id, task_id).await
+    }
+
+    /// 查询迁移任务状态
+    pub async fn status(
+        &self,
+        tenant_id: Uuid,
+        knowledge_base_id: Uuid,
+        task_id: Uuid,
+    ) -> Result<VectorMigrationStatus, AiStudioError> {
+        let task = self.queue
+            .get_task_status(task_id)
+            .await
+            .filter(|task| task.tenant_id == tenant_id && task.task_type == TaskType::VectorMigration)
+            .ok_or_else(|| AiStudioError::not_found("向量迁移任务"))?;
+        let parameters: MigrationParameters = serde_json::from_value(task.parameters.clone())?;
+        if parameters.knowledge_base_id != knowledge_base_id {
+            return Err(AiStudioError::not_found("向量迁移任务"));
+        }
+
+        Ok(VectorMigrationStatus {
+            task_id,
+            knowledge_base_id,
+            status: serde_json::to_value(&task.status)?.as_str().unwrap_or_default().to_string(),
+            target: parameters.target,
+            summary: task.result.clone().and_then(|result| serde_json::from_value(result).ok()),
+            error_message: task.error_message,
+            created_at: task.created_at,
+            completed_at: task.completed_at,
+        })
+    }
+
+    /// 知识库是否有排队或执行中的迁移任务
+    async fn has_pending_migration(&self, tenant_id: Uuid, knowledge_base_id: Uuid) -> bool {
+        let kb = knowledge_base_id.to_string();
+        self.queue
+            .get_tenant_tasks(tenant_id)
+            .await
+            .iter()
+            .filter(|task| task.task_type == TaskType::VectorMigration && matches!(task.status, TaskStatus::Pending | TaskStatus::Running))
+            .any(|task| task.parameters.get("knowledge_base_id").and_then(|v| v.as_str()) == Some(kb.as_str()))
+    }
+}
+
+/// 向量存储迁移任务执行器
+pub struct VectorMigrationExecutor {
+    migrator: VectorMigrator,
+}
+
+impl VectorMigrationExecutor {
+    /// 创建执行器
+    pub fn new(db: DatabaseConnection, stores: Arc<VectorStoreRegistry>) -> Self {
+        Self { migrator: VectorMigrator::new(db, stores) }
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskExecutor for VectorMigrationExecutor {
+    async fn execute(&self, task: &mut TaskInfo) -> Result<(), AiStudioError> {
+        let parameters: MigrationParameters = serde_json::from_value(task.parameters.clone())?;
+
+        let summary = self.migrator
+            .migrate(parameters.knowledge_base_id, parameters.target, parameters.keep_source)
+            .await?;
+        task.total_count = Some((summary.migrated + summary.missing) as u32);
+        task.success_count = summary.migrated as u32;
+        task.error_count = summary.missing as u32;
+        task.result = Some(serde_json::to_value(&summary)?);
+        info!("向量迁移任务完成: task_id={}, 请求人={}", task.id, parameters.requested_by);
+        Ok(())
+    }
+
+    fn supported_task_types(&self) -> Vec<TaskType> {
+        vec![TaskType::VectorMigration]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration_request_defaults() {
+        let request: VectorMigrationRequest = serde_json::from_str(r#"{"target":"qdrant"}"#).unwrap();
+        assert_eq!(request.target, VectorBackend::Qdrant);
+        assert!(!request.keep_source);
+    }
+}
@@ -0,0 +1,512 @@
+// 联网搜索服务
+// 按租户设置调用 SerpAPI、Bing 或自建 SearxNG，提取结果摘要、去重并按 Agent 的域名策略过滤
+
+use std::collections::{BTreeSet, HashSet};
+use std::time::Duration;
+
+use chrono::Utc;
+use sea_orm::{DatabaseConnection, EntityTrait, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info, instrument, warn};
+use url::Url;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::db::entities::tenant::{self, TenantWebSearch, WebSearchProvider};
+use crate::db::entities::Tenant;
+use crate::db::update_with_revision;
+use crate::errors::AiStudioError;
+use crate::services::cache::{self, CacheNamespace};
+use crate::services::egress::TenantClients;
+use crate::services::tenant_secret::{is_valid_secret_name, TenantSecretService};
+
+/// SerpAPI 默认接口地址
+const SERPAPI_ENDPOINT: &str = "https://serpapi.com/search.json";
+
+/// Bing Web Search 默认接口地址
+const BING_ENDPOINT: &str = "https://api.bing.microsoft.com/v7.0/search";
+
+/// 搜索请求超时时间
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 单次向搜索后端请求的最大结果数
+const MAX_PROVIDER_RESULTS: usize = 50;
+
+/// 结果摘要的最大字符数
+const MAX_SNIPPET_CHARS: usize = 300;
+
+/// Agent 域名规则的最大条数
+const MAX_DOMAIN_RULES: usize = 100;
+
+/// 去重时忽略的跟踪参数
+const TRACKING_PARAMS: [&str; 4] = ["gclid", "fbclid", "msclkid", "spm"];
+
+/// Agent 联网搜索的域名策略
+///
+/// 域名规则同时匹配其子域名；设置允许列表后只保留列表内的结果，禁止列表优先于允许列表。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct WebSearchDomainPolicy {
+    /// 允许的域名，为空时不限制
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// 禁止的域名
+    #[serde(default)]
+    pub blocked_domains: Vec<String>,
+}
+
+impl WebSearchDomainPolicy {
+    /// 校验并规范化域名规则：去除协议、前导点与空白，统一小写
+    pub fn normalized(self) -> Result<Self, AiStudioError> {
+        let normalize = |field: &str, domains: Vec<String>| -> Result<Vec<String>, AiStudioError> {
+            let domains: Vec<String> = domains
+                .iter()
+                .map(|domain| {
+                    let domain = domain.trim().to_ascii_lowercase();
+                    let domain = domain.split_once("://").map(|(_, rest)| rest.to_string()).unwrap_or(domain);
+                    domain.trim_start_matches('.').trim_end_matches('/').to_string()
+                })
+                .filter(|domain| !domain.is_empty())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            if domains.len() > MAX_DOMAIN_RULES {
+                return Err(AiStudioError::validation(field, format!("域名规则最多 {} 条", MAX_DOMAIN_RULES)));
+            }
+            if let Some(invalid) = domains.iter().find(|d| d.contains('/') || d.contains(char::is_whitespace)) {
+                return Err(AiStudioError::validation(field, format!("无效的域名: {}", invalid)));
+            }
+            Ok(domains)
+        };
+        Ok(Self {
+            allowed_domains: normalize("allowed_domains", self.allowed_domains)?,
+            blocked_domains: normalize("blocked_domains", self.blocked_domains)?,
+        })
+    }
+
+    /// 结果地址是否符合策略
+    pub fn allows(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        let matches = |domain: &String| host == *domain || host.ends_with(&format!(".{}", domain));
+        if self.blocked_domains.iter().any(matches) {
+            return false;
+        }
+        self.allowed_domains.is_empty() || self.allowed_domains.iter().any(matches)
+    }
+}
+
+/// 联网搜索结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebSearchHit {
+    /// 标题
+    pub title: String,
+    /// 结果地址，用作引用链接
+    pub url: String,
+    /// 从结果中提取的纯文本摘要
+    pub snippet: String,
+    /// 结果所在域名
+    pub domain: String,
+    /// 去重过滤后的排名，从 1 开始
+    pub rank: usize,
+}
+
+/// 读取租户的联网搜索设置，读取失败时视为未开启
+pub async fn load_tenant_web_search(db: &DatabaseConnection, tenant_id: Uuid) -> TenantWebSearch {
+    let loaded = cache::cached(CacheNamespace::Tenant, &tenant_id.to_string(), || async {
+        Ok::<_, AiStudioError>(Tenant::find_by_id(tenant_id).one(db).await?)
+    })
+    .await;
+
+    match loaded {
+        Ok(Some(tenant)) => tenant.get_config().unwrap_or_default().web_search,
+        Ok(None) => TenantWebSearch::default(),
+        Err(e) => {
+            warn!(tenant_id = %tenant_id, error = %e, "读取租户联网搜索设置失败，按未开启处理");
+            TenantWebSearch::default()
+        }
+    }
+}
+
+/// 联网搜索服务
+#[derive(Clone)]
+pub struct WebSearchService {
+    db: DatabaseConnection,
+    secrets: TenantSecretService,
+    clients: TenantClients,
+}
+
+impl std::fmt::Debug for WebSearchService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSearchService").finish_non_exhaustive()
+    }
+}
+
+impl WebSearchService {
+    /// 创建新的联网搜索服务实例
+    pub fn new(db: DatabaseConnection, encryption_key: &str) -> Self {
+        Self {
+            secrets: TenantSecretService::new(db.clone(), encryption_key),
+            db,
+            clients: TenantClients::new(|builder| {
+                builder
+                    .timeout(SEARCH_TIMEOUT)
+                    .user_agent(concat!("aionix-web-search/", env!("CARGO_PKG_VERSION")))
+            }),
+        }
+    }
+
+    /// 按应用配置创建
+    pub fn from_app_config(db: DatabaseConnection, config: &AppConfig) -> Self {
+        Self::new(db, &config.security.encryption_key)
+    }
+
+    /// 获取租户联网搜索设置
+    pub async fn get_settings(&self, tenant_id: Uuid) -> Result<TenantWebSearch, AiStudioError> {
+        let tenant = Tenant::find_by_id(tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+        Ok(tenant.get_config().unwrap_or_default().web_search)
+    }
+
+    /// 替换租户联网搜索设置
+    #[instrument(skip(self, settings))]
+    pub async fn update_settings(
+        &self,
+        tenant_id: Uuid,
+        settings: TenantWebSearch,
+    ) -> Result<TenantWebSearch, AiStudioError> {
+        let settings = normalize_settings(settings)?;
+
+        let tenant = Tenant::find_by_id(tenant_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("租户"))?;
+
+        let mut config = tenant.get_config()
+            .map_err(|e| AiStudioError::internal(format!("解析租户配置失败: {}", e)))?;
+        config.web_search = settings.clone();
+
+        let revision = tenant.revision;
+        let mut active_tenant: tenant::ActiveModel = tenant.into();
+        active_tenant.config = Set(serde_json::to_value(&config)?);
+        active_tenant.updated_at = Set(Utc::now().into());
+        update_with_revision(&self.db, active_tenant, tenant::Column::Revision, revision, "租户").await?;
+        cache::invalidate(CacheNamespace::Tenant, tenant_id).await;
+
+        info!(tenant_id = %tenant_id, provider = ?settings.provider, "租户联网搜索设置已更新");
+        Ok(settings)
+    }
+
+    /// 按租户设置联网搜索，结果经摘要提取、去重与域名策略过滤后最多返回 `limit` 条
+    #[instrument(skip(self, policy))]
+    pub async fn search(
+        &self,
+        tenant_id: Uuid,
+        query: &str,
+        limit: usize,
+        policy: &WebSearchDomainPolicy,
+    ) -> Result<Vec<WebSearchHit>, AiStudioError> {
+        let settings = load_tenant_web_search(&self.db, tenant_id).await;
+        let provider = settings.provider
+            .ok_or_else(|| AiStudioError::forbidden("租户未开启联网搜索"))?;
+
+        // 过滤与去重会丢弃部分结果，多请求一些
+        let count = (limit * 2).clamp(limit, MAX_PROVIDER_RESULTS);
+        let body = self.fetch(tenant_id, provider, &settings, query, count).await?;
+        let hits = finalize_hits(parse_results(provider, &body), policy, limit);
+        debug!("联网搜索完成: provider={:?}, 查询='{}', 结果数={}", provider, query, hits.len());
+        Ok(hits)
+    }
+
+    /// 调用搜索后端，返回原始 JSON 响应
+    async fn fetch(
+        &self,
+        tenant_id: Uuid,
+        provider: WebSearchProvider,
+        settings: &TenantWebSearch,
+        query: &str,
+        count: usize,
+    ) -> Result<Value, AiStudioError> {
+        let api_key = match &settings.api_key_secret {
+            Some(name) => {
+                let names = BTreeSet::from([name.clone()]);
+                self.secrets.resolve_secrets(tenant_id, &names).await?.remove(name)
+            }
+            None => None,
+        };
+        let require_key = || api_key.clone()
+            .ok_or_else(|| AiStudioError::configuration(format!("{:?} 搜索后端未配置 API 密钥", provider)));
+
+        let client = self.clients.client(tenant_id).await?;
+        let count = count.to_string();
+        let request = match provider {
+            WebSearchProvider::SerpApi => {
+                let mut params = vec![
+                    ("engine", "google".to_string()),
+                    ("q", query.to_string()),
+                    ("num", count),
+                    ("api_key", require_key()?),
+                ];
+                if let Some(market) = &settings.market {
+                    params.push(("hl", market.clone()));
+                }
+                if settings.safe_search {
+                    params.push(("safe", "active".to_string()));
+                }
+                client.get(settings.endpoint.as_deref().unwrap_or(SERPAPI_ENDPOINT)).query(&params)
+            }
+            WebSearchProvider::Bing => {
+                let mut params = vec![
+                    ("q", query.to_string()),
+                    ("count", count),
+                    ("textDecorations", "false".to_string()),
+                    ("safeSearch", if settings.safe_search { "Strict" } else { "Off" }.to_string()),
+                ];
+                if let Some(market) = &settings.market {
+                    params.push(("mkt", market.clone()));
+                }
+                client.get(settings.endpoint.as_deref().unwrap_or(BING_ENDPOINT))
+                    .header("Ocp-Apim-Subscription-Key", require_key()?)
+                    .query(&params)
+            }
+            WebSearchProvider::Searxng => {
+                let endpoint = settings.endpoint.as_deref()
+                    .ok_or_else(|| AiStudioError::configuration("SearxNG 搜索后端未配置接口地址"))?;
+                let mut params = vec![
+                    ("q", query.to_string()),
+                    ("format", "json".to_string()),
+                    ("safesearch", if settings.safe_search { "1" } else { "0" }.to_string()),
+                ];
+                if let Some(market) = &settings.market {
+                    params.push(("language", market.clone()));
+                }
+                let request = client.get(format!("{}/search", endpoint.trim_end_matches('/'))).query(&params);
+                // 自建实例可能位于鉴权网关之后
+                match &api_key {
+                    Some(key) => request.bearer_auth(key),
+                    None => request,
+                }
+            }
+        };
+
+        let response = request.send().await.map_err(|e| {
+            AiStudioError::external_service("web_search".to_string(), format!("联网搜索请求失败: {}", e.without_url()))
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(AiStudioError::external_service(
+                "web_search".to_string(),
+                format!("联网搜索后端返回错误状态: {}", status),
+            ));
+        }
+        response.json::<Value>().await.map_err(|e| {
+            AiStudioError::external_service("web_search".to_string(), format!("解析联网搜索响应失败: {}", e))
+        })
+    }
+}
+
+/// 校验并规范化租户联网搜索设置：去除空白，空字符串视为未设置
+fn normalize_settings(mut settings: TenantWebSearch) -> Result<TenantWebSearch, AiStudioError> {
+    let trim = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    settings.api_key_secret = trim(settings.api_key_secret);
+    settings.endpoint = trim(settings.endpoint).map(|endpoint| endpoint.trim_end_matches('/').to_string());
+    settings.market = trim(settings.market);
+
+    if let Some(name) = &settings.api_key_secret {
+        if !is_valid_secret_name(name) {
+            return Err(AiStudioError::validation("api_key_secret", "无效的租户密钥名称"));
+        }
+    }
+    if let Some(endpoint) = &settings.endpoint {
+        let url = Url::parse(endpoint)
+            .map_err(|_| AiStudioError::validation("endpoint", "无效的接口地址"))?;
+        if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+            return Err(AiStudioError::validation("endpoint", "接口地址必须是 http 或 https 地址"));
+        }
+    }
+    if settings.market.as_ref().is_some_and(|market| market.len() > 16) {
+        return Err(AiStudioError::validation("market", "搜索市场不能超过 16 个字符"));
+    }
+
+    match settings.provider {
+        Some(WebSearchProvider::SerpApi | WebSearchProvider::Bing) if settings.api_key_secret.is_none() => {
+            Err(AiStudioError::validation("api_key_secret", "SerpAPI 与 Bing 需要指定保存 API 密钥的租户密钥"))
+        }
+        Some(WebSearchProvider::Searxng) if settings.endpoint.is_none() => {
+            Err(AiStudioError::validation("endpoint", "SearxNG 需要指定实例地址"))
+        }
+        _ => Ok(settings),
+    }
+}
+
+/// 从搜索后端的响应中取出 (标题, 地址, 原始摘要)
+fn parse_results(provider: WebSearchProvider, body: &Value) -> Vec<(String, String, String)> {
+    let (items, title_key, url_key, snippet_key) = match provider {
+        WebSearchProvider::SerpApi => (body.get("organic_results"), "title", "link", "snippet"),
+        WebSearchProvider::Bing => (body.pointer("/webPages/value"), "name", "url", "snippet"),
+        WebSearchProvider::Searxng => (body.get("results"), "title", "url", "content"),
+    };
+    let field = |item: &Value, key: &str| item.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+    items
+        .and_then(Value::as_array)
+        .map(|items| {
+            items.iter()
+                .map(|item| (field(item, title_key), field(item, url_key), field(item, snippet_key)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 过滤非 HTTP 地址与策略外的域名，按规范化地址去重并提取摘要
+fn finalize_hits(
+    raw: Vec<(String, String, String)>,
+    policy: &WebSearchDomainPolicy,
+    limit: usize,
+) -> Vec<WebSearchHit> {
+    let mut seen = HashSet::new();
+    let mut hits = Vec::new();
+    for (title, url, snippet) in raw {
+        let Ok(parsed) = Url::parse(&url) else { continue };
+        if !matches!(parsed.scheme(), "http" | "https") {
+            continue;
+        }
+        let Some(host) = parsed.host_str().map(str::to_ascii_lowercase) else { continue };
+        if !policy.allows(&host) || !seen.insert(dedup_key(&parsed)) {
+            continue;
+        }
+        hits.push(WebSearchHit {
+            title: extract_snippet(&title),
+            url,
+            snippet: extract_snippet(&snippet),
+            domain: host,
+            rank: hits.len() + 1,
+        });
+        if hits.len() >= limit {
+            break;
+        }
+    }
+    hits
+}
+
+/// 去重键：忽略协议、www 前缀、片段、末尾斜杠与跟踪参数，查询参数按名称排序
+fn dedup_key(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let mut query: Vec<(String, String)> = url.query_pairs()
+        .filter(|(key, _)| !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_ref()))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    query.sort();
+    let query = query.iter().map(|(key, value)| format!("{}={}", key, value)).collect::<Vec<_>>().join("&");
+    format!("{}{}?{}", host, url.path().trim_end_matches('/'), query)
+}
+
+/// 提取纯文本摘要：去除 HTML 标签、解码常见实体、合并空白并截断
+fn extract_snippet(raw: &str) -> String {
+    let mut text = String::with_capacity(raw.len());
+    let mut in_tag = false;
+    for c in raw.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= MAX_SNIPPET_CHARS {
+        return text;
+    }
+    let truncated: String = text.chars().take(MAX_SNIPPET_CHARS).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_finalize_hits() {
+        let body = serde_json::json!({
+            "webPages": {
+                "value": [
+                    {"name": "Rust <b>官网</b>", "url": "https://www.rust-lang.org/", "snippet": "A language &amp; ecosystem"},
+                    {"name": "Rust 官网", "url": "https://rust-lang.org?utm_source=bing", "snippet": "重复结果"},
+                    {"name": "Blocked", "url": "https://spam.example.com/rust", "snippet": "x"},
+                    {"name": "FTP", "url": "ftp://files.rust-lang.org/", "snippet": "x"},
+                    {"name": "Docs", "url": "https://doc.rust-lang.org/book/#intro", "snippet": "The book"}
+                ]
+            }
+        });
+        let policy = WebSearchDomainPolicy {
+            allowed_domains: Vec::new(),
+            blocked_domains: vec!["example.com".to_string()],
+        };
+
+        let hits = finalize_hits(parse_results(WebSearchProvider::Bing, &body), &policy, 10);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].title, "Rust 官网");
+        assert_eq!(hits[0].snippet, "A language & ecosystem");
+        assert_eq!(hits[0].domain, "www.rust-lang.org");
+        assert_eq!(hits[1].url, "https://doc.rust-lang.org/book/#intro");
+        assert_eq!(hits[1].rank, 2);
+
+        assert_eq!(finalize_hits(parse_results(WebSearchProvider::Bing, &body), &policy, 1).len(), 1);
+        assert!(parse_results(WebSearchProvider::SerpApi, &body).is_empty());
+    }
+
+    #[test]
+    fn test_domain_policy() {
+        let policy = WebSearchDomainPolicy {
+            allowed_domains: vec![" https://Docs.RS/ ".to_string(), ".rust-lang.org".to_string()],
+            blocked_domains: vec!["blog.rust-lang.org".to_string()],
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(policy.allowed_domains, vec!["docs.rs", "rust-lang.org"]);
+
+        assert!(policy.allows("docs.rs"));
+        assert!(policy.allows("doc.rust-lang.org"));
+        assert!(!policy.allows("blog.rust-lang.org"));
+        assert!(!policy.allows("notrust-lang.org"));
+        assert!(!policy.allows("example.com"));
+        assert!(WebSearchDomainPolicy::default().allows("example.com"));
+    }
+
+    #[test]
+    fn test_normalize_settings() {
+        let settings = TenantWebSearch {
+            provider: Some(WebSearchProvider::Bing),
+            api_key_secret: Some(" BING_KEY ".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(normalize_settings(settings).unwrap().api_key_secret.as_deref(), Some("BING_KEY"));
+
+        let missing_key = TenantWebSearch { provider: Some(WebSearchProvider::SerpApi), ..Default::default() };
+        assert!(normalize_settings(missing_key).is_err());
+
+        let missing_endpoint = TenantWebSearch { provider: Some(WebSearchProvider::Searxng), ..Default::default() };
+        assert!(normalize_settings(missing_endpoint).is_err());
+
+        let searxng = TenantWebSearch {
+            provider: Some(WebSearchProvider::Searxng),
+            endpoint: Some("https://search.internal/".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(normalize_settings(searxng).unwrap().endpoint.as_deref(), Some("https://search.internal"));
+    }
+}
@@ -0,0 +1,384 @@
+// 工作流外部回调服务
+// 为等待回调步骤生成带签名校验的回调地址，接收外部系统回调并按映射写入工作流上下文，超时后标记步骤超时
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    FromQueryResult, QueryFilter, Set, Statement,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sha2::Sha256;
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::ai::workflow_engine::{CallbackTimeoutAction, StepConfig};
+use crate::db::entities::workflow_callback::{self, CallbackStatus};
+use crate::db::entities::WorkflowCallback;
+use crate::errors::AiStudioError;
+use crate::plugins::event_bus::{event_types, SystemEvent, SystemEventBus};
+use crate::services::scheduler::PeriodicJob;
+
+/// 回调签名请求头，值为 `sha256=<十六进制 HMAC-SHA256>`
+pub const CALLBACK_SIGNATURE_HEADER: &str = "X-Aionix-Signature";
+
+/// 超时检查间隔（秒）
+const TIMEOUT_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// 回调载荷最大字节数
+pub const MAX_CALLBACK_PAYLOAD_BYTES: usize = 1024 * 1024;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 等待回调步骤的回调注册信息，作为步骤输出交给外部系统
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CallbackRegistration {
+    /// 回调 ID
+    pub callback_id: Uuid,
+    /// 回调地址
+    pub callback_url: String,
+    /// 回调签名密钥，外部系统用其对请求体计算 HMAC-SHA256
+    pub signing_secret: String,
+    /// 超时时间
+    pub expires_at: chrono::DateTime<Utc>,
+}
+
+/// 回调接收结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CallbackReceipt {
+    /// 回调 ID
+    pub callback_id: Uuid,
+    /// 工作流执行 ID
+    pub execution_id: Uuid,
+    /// 步骤 ID
+    pub step_id: String,
+    /// 接收时间
+    pub received_at: chrono::DateTime<Utc>,
+}
+
+/// 等待回调步骤的当前结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallbackOutcome {
+    /// 仍在等待
+    Pending,
+    /// 已收到回调，附带写入上下文的值
+    Received(Value),
+    /// 已超时，附带步骤配置的处理方式
+    TimedOut(CallbackTimeoutAction),
+}
+
+/// 工作流外部回调服务
+pub struct WorkflowCallbackService {
+    db: DatabaseConnection,
+    signing_secret: String,
+}
+
+impl WorkflowCallbackService {
+    /// 创建新的外部回调服务，`signing_secret` 用于派生每个回调的签名密钥
+    pub fn new(db: DatabaseConnection, signing_secret: String) -> Self {
+        Self { db, signing_secret }
+    }
+
+    /// 为等待回调步骤注册回调
+    #[instrument(skip(self, config))]
+    pub async fn register(
+        &self,
+        tenant_id: Uuid,
+        execution_id: Uuid,
+        step_id: &str,
+        config: &StepConfig,
+    ) -> Result<CallbackRegistration, AiStudioError> {
+        let StepConfig::WaitForCallback { timeout_seconds, .. } = config else {
+            return Err(AiStudioError::validation("config", "步骤配置不是等待回调"));
+        };
+
+        let now = Utc::now();
+        let expires_at = now + chrono::Duration::seconds(*timeout_seconds as i64);
+        let callback = workflow_callback::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            execution_id: Set(execution_id),
+            step_id: Set(step_id.to_string()),
+            status: Set(CallbackStatus::Pending),
+            step_config: Set(serde_json::to_value(config)?),
+            payload: Set(None),
+            context: Set(None),
+            expires_at: Set(expires_at.into()),
+            received_at: Set(None),
+            created_at: Set(now.into()),
+        }
+        .insert(&self.db)
+        .await?;
+
+        info!("等待外部回调: callback_id={}, execution_id={}, step_id={}", callback.id, execution_id, step_id);
+        Ok(CallbackRegistration {
+            callback_id: callback.id,
+            callback_url: callback_url(callback.id),
+            signing_secret: callback_secret(&self.signing_secret, callback.id),
+            expires_at,
+        })
+    }
+
+    /// 接收外部系统回调
+    ///
+    /// 签名按原始请求体校验；每个回调只接受一次，超时后的回调被拒绝。
+    #[instrument(skip(self, body, signature))]
+    pub async fn receive(
+        &self,
+        callback_id: Uuid,
+        body: &[u8],
+        signature: Option<&str>,
+    ) -> Result<CallbackReceipt, AiStudioError> {
+        let callback = WorkflowCallback::find_by_id(callback_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("回调"))?;
+
+        let secret = callback_secret(&self.signing_secret, callback_id);
+        if !signature.is_some_and(|signature| verify_signature(&secret, body, signature)) {
+            warn!("外部回调签名校验失败: callback_id={}", callback_id);
+            return Err(AiStudioError::forbidden("回调签名无效"));
+        }
+
+        let payload: Value = serde_json::from_slice(body)
+            .map_err(|e| AiStudioError::validation("body", format!("回调载荷不是有效的 JSON: {}", e)))?;
+        let context = match serde_json::from_value(callback.step_config.clone()) {
+            Ok(StepConfig::WaitForCallback { payload_mapping, .. }) => map_payload(&payload, &payload_mapping),
+            _ => payload.clone(),
+        };
+
+        // 仅在仍处于等待状态且未超时时接受，避免重复回调或与超时检查竞争
+        let now = Utc::now();
+        let updated = self.db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"
+                UPDATE workflow_callbacks
+                SET status = 'received', payload = $2, context = $3, received_at = $4
+                WHERE id = $1 AND status = 'pending' AND expires_at > $4
+                "#,
+                vec![callback_id.into(), payload.into(), context.clone().into(), now.into()],
+            ))
+            .await?;
+        if updated.rows_affected() == 0 {
+            return Err(match callback.status {
+                CallbackStatus::Received => AiStudioError::conflict("回调已处理"),
+                _ => AiStudioError::conflict("回调已超时"),
+            });
+        }
+
+        info!("收到外部回调: callback_id={}, execution_id={}, step_id={}", callback_id, callback.execution_id, callback.step_id);
+        SystemEventBus::global().publish(SystemEvent::new(
+            event_types::WORKFLOW_CALLBACK_RECEIVED,
+            Some(callback.tenant_id),
+            serde_json::json!({
+                "callback_id": callback_id,
+                "execution_id": callback.execution_id,
+                "step_id": callback.step_id,
+                "context": context,
+            }),
+        ));
+
+        Ok(CallbackReceipt {
+            callback_id,
+            execution_id: callback.execution_id,
+            step_id: callback.step_id,
+            received_at: now,
+        })
+    }
+
+    /// 查询等待回调步骤的当前结果
+    pub async fn outcome(&self, tenant_id: Uuid, callback_id: Uuid) -> Result<CallbackOutcome, AiStudioError> {
+        let callback = WorkflowCallback::find_by_id(callback_id)
+            .filter(workflow_callback::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| AiStudioError::not_found("回调"))?;
+
+        Ok(match callback.status {
+            CallbackStatus::Pending => CallbackOutcome::Pending,
+            CallbackStatus::Received => CallbackOutcome::Received(callback.context.unwrap_or(Value::Null)),
+            CallbackStatus::TimedOut => CallbackOutcome::TimedOut(timeout_action(&callback.step_config)),
+        })
+    }
+
+    /// 将超时未回调的记录标记为超时并发布事件，返回处理的数量
+    pub async fn expire_overdue(&self) -> Result<usize, AiStudioError> {
+        let expired = workflow_callback::Model::find_by_statement(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"
+            UPDATE workflow_callbacks
+            SET status = 'timed_out'
+            WHERE status = 'pending' AND expires_at <= $1
+            RETURNING *
+            "#,
+            vec![Utc::now().into()],
+        ))
+        .all(&self.db)
+        .await?;
+
+        for callback in &expired {
+            warn!("等待外部回调超时: callback_id={}, execution_id={}, step_id={}", callback.id, callback.execution_id, callback.step_id);
+            SystemEventBus::global().publish(SystemEvent::new(
+                event_types::WORKFLOW_CALLBACK_TIMED_OUT,
+                Some(callback.tenant_id),
+                serde_json::json!({
+                    "callback_id": callback.id,
+                    "execution_id": callback.execution_id,
+                    "step_id": callback.step_id,
+                    "on_timeout": timeout_action(&callback.step_config),
+                }),
+            ));
+        }
+        Ok(expired.len())
+    }
+}
+
+/// 回调地址
+pub fn callback_url(callback_id: Uuid) -> String {
+    format!("/api/v1/workflow-callbacks/{}", callback_id)
+}
+
+/// 由服务端密钥派生单个回调的签名密钥，无需单独存储
+pub fn callback_secret(signing_secret: &str, callback_id: Uuid) -> String {
+    let mut mac = HmacSha256::new_from_slice(signing_secret.as_bytes())
+        .expect("HMAC 可接受任意长度的密钥");
+    mac.update(b"workflow-callback:");
+    mac.update(callback_id.as_bytes());
+    to_hex(&mac.finalize().into_bytes())
+}
+
+/// 计算请求体签名，格式与回调签名请求头一致
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .expect("HMAC 可接受任意长度的密钥");
+    mac.update(body);
+    format!("sha256={}", to_hex(&mac.finalize().into_bytes()))
+}
+
+/// 以常量时间校验请求体签名
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(expected) = signature.trim().strip_prefix("sha256=").and_then(from_hex) else {
+        return false;
+    };
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .expect("HMAC 可接受任意长度的密钥");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// 按 JSON Pointer 映射提取回调载荷，映射为空时返回整个载荷，载荷中不存在的字段取 null
+pub fn map_payload(payload: &Value, fields: &HashMap<String, String>) -> Value {
+    if fields.is_empty() {
+        return payload.clone();
+    }
+    let context: Map<String, Value> = fields.iter()
+        .map(|(name, pointer)| (name.clone(), payload.pointer(pointer).cloned().unwrap_or(Value::Null)))
+        .collect();
+    Value::Object(context)
+}
+
+/// 从步骤配置快照中读取超时处理方式
+fn timeout_action(step_config: &Value) -> CallbackTimeoutAction {
+    match serde_json::from_value(step_config.clone()) {
+        Ok(StepConfig::WaitForCallback { on_timeout, .. }) => on_timeout,
+        _ => CallbackTimeoutAction::default(),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect()
+}
+
+/// 外部回调超时检查周期任务
+pub struct WorkflowCallbackTimeoutJob {
+    service: Arc<WorkflowCallbackService>,
+}
+
+impl WorkflowCallbackTimeoutJob {
+    /// 创建新的外部回调超时检查周期任务
+    pub fn new(service: Arc<WorkflowCallbackService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait::async_trait]
+impl PeriodicJob for WorkflowCallbackTimeoutJob {
+    fn name(&self) -> &str {
+        "workflow_callback_timeout"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(TIMEOUT_CHECK_INTERVAL_SECS)
+    }
+
+    async fn run(&self) -> Result<(), AiStudioError> {
+        self.service.expire_overdue().await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_signature_roundtrip() {
+        let callback_id = Uuid::new_v4();
+        let secret = callback_secret("server-secret", callback_id);
+        assert_eq!(secret, callback_secret("server-secret", callback_id));
+        assert_ne!(secret, callback_secret("server-secret", Uuid::new_v4()));
+
+        let body = br#"{"status":"done"}"#;
+        let signature = sign_payload(&secret, body);
+        assert!(verify_signature(&secret, body, &signature));
+        assert!(!verify_signature(&secret, br#"{"status":"failed"}"#, &signature));
+        assert!(!verify_signature("other", body, &signature));
+        assert!(!verify_signature(&secret, body, "sha256=zz"));
+        assert!(!verify_signature(&secret, body, signature.trim_start_matches("sha256=")));
+    }
+
+    #[test]
+    fn test_map_payload() {
+        let payload = json!({ "data": { "status": "approved", "items": [{ "id": 7 }] } });
+        let fields = HashMap::from([
+            ("status".to_string(), "/data/status".to_string()),
+            ("first_item".to_string(), "/data/items/0/id".to_string()),
+            ("missing".to_string(), "/data/reason".to_string()),
+            ("raw".to_string(), String::new()),
+        ]);
+
+        let context = map_payload(&payload, &fields);
+        assert_eq!(context["status"], "approved");
+        assert_eq!(context["first_item"], 7);
+        assert_eq!(context["missing"], Value::Null);
+        assert_eq!(context["raw"], payload);
+        assert_eq!(map_payload(&payload, &HashMap::new()), payload);
+    }
+
+    #[test]
+    fn test_timeout_action_from_snapshot() {
+        let config = StepConfig::WaitForCallback {
+            timeout_seconds: 60,
+            payload_mapping: HashMap::new(),
+            on_timeout: CallbackTimeoutAction::Continue,
+        };
+        assert_eq!(timeout_action(&serde_json::to_value(&config).unwrap()), CallbackTimeoutAction::Continue);
+        assert_eq!(timeout_action(&json!({})), CallbackTimeoutAction::Fail);
+    }
+}
@@ -0,0 +1,10 @@
+// 端到端集成测试
+// 启动临时 PostgreSQL，按服务端的方式组装完整应用，验证认证 → 上传 → 处理 → 问答链路
+//
+// 运行方式（需要本地 Docker）：
+//   cargo test --features e2e --test e2e
+// 设置 AIONIX_E2E_DATABASE_URL 可改用已有数据库（需已安装 pgvector 扩展）；
+// 设置 AIONIX_E2E_AI_ENDPOINT 后会启用问答步骤，否则跳过
+
+mod pipeline;
+mod support;
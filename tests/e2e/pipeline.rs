@@ -0,0 +1,170 @@
+// 核心业务链路端到端测试
+// 认证 → 上传 → 处理 → 问答，每一步都经过完整的中间件与路由
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use actix_http::Request;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use aionix::db::entities::document::DocumentStatus;
+use aionix::db::entities::knowledge_base;
+
+use crate::support::{
+    multipart_body, send, Session, TestContext, ADMIN_PASSWORD, ADMIN_USERNAME, DEFAULT_TENANT_SLUG,
+};
+
+/// 上传的测试文档内容
+const DOCUMENT_CONTENT: &str = "Aionix 是一个多租户 AI 问答平台。\n平台的默认问答模型通过 AI 配置中的 model_endpoint 指定。";
+
+#[actix_web::test]
+async fn test_auth_upload_process_qa_pipeline() {
+    let context = TestContext::setup().await;
+    let app = context.init_app().await;
+
+    let session = login(&app).await;
+    let knowledge_base_id = seeded_knowledge_base(&context.db, session.tenant_id).await;
+    let document_id = upload_document(&app, &session, knowledge_base_id).await;
+    process_document(&app, &session, document_id).await;
+
+    if context.rag_engine.is_some() {
+        ask_question(&app, &session, knowledge_base_id).await;
+    } else {
+        eprintln!("未设置 AIONIX_E2E_AI_ENDPOINT，跳过问答步骤");
+    }
+}
+
+/// 使用种子数据中的管理员登录，并确认错误密码被拒绝
+async fn login<S, B>(app: &S) -> Session
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let credentials = |password: &str| {
+        json!({
+            "username": ADMIN_USERNAME,
+            "password": password,
+            "tenant_slug": DEFAULT_TENANT_SLUG,
+        })
+    };
+
+    let request = TestRequest::post().uri("/api/v1/auth/login").set_json(credentials("wrong-password"));
+    let (status, _) = send(app, request.to_request()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "错误密码应登录失败");
+
+    let request = TestRequest::post().uri("/api/v1/auth/login").set_json(credentials(ADMIN_PASSWORD));
+    let (status, body) = send(app, request.to_request()).await;
+    assert_eq!(status, StatusCode::OK, "管理员登录失败: {}", body);
+
+    let data = &body["data"];
+    assert_eq!(data["token_type"], "Bearer");
+    assert_eq!(data["user"]["username"], ADMIN_USERNAME);
+
+    Session {
+        access_token: data["access_token"].as_str().expect("登录响应缺少访问令牌").to_string(),
+        tenant_id: parse_uuid(&data["user"]["tenant_id"]),
+    }
+}
+
+/// 查找种子数据为默认租户创建的知识库
+async fn seeded_knowledge_base(db: &DatabaseConnection, tenant_id: Uuid) -> Uuid {
+    knowledge_base::Entity::find()
+        .filter(knowledge_base::Column::TenantId.eq(tenant_id))
+        .one(db)
+        .await
+        .expect("查询知识库失败")
+        .expect("种子数据中缺少默认知识库")
+        .id
+}
+
+/// 上传文本文档并确认可以读取
+async fn upload_document<S, B>(app: &S, session: &Session, knowledge_base_id: Uuid) -> Uuid
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let (content_type, payload) = multipart_body(
+        &[("knowledge_base_id", &knowledge_base_id.to_string()), ("title", "平台简介")],
+        "aionix-intro.txt",
+        DOCUMENT_CONTENT.as_bytes(),
+    );
+    let request = session
+        .authorize(TestRequest::post().uri("/api/v1/documents/upload"))
+        .insert_header(("Content-Type", content_type))
+        .set_payload(payload);
+    let (status, body) = send(app, request.to_request()).await;
+    assert_eq!(status, StatusCode::CREATED, "文档上传失败: {}", body);
+    assert_eq!(body["data"]["file_name"], "aionix-intro.txt");
+    let document_id = parse_uuid(&body["data"]["id"]);
+
+    let request = session.authorize(TestRequest::get().uri(&format!("/api/v1/documents/{}", document_id)));
+    let (status, body) = send(app, request.to_request()).await;
+    assert_eq!(status, StatusCode::OK, "读取文档失败: {}", body);
+    assert_eq!(body["data"]["title"], "平台简介");
+    assert_eq!(body["data"]["knowledge_base_id"], knowledge_base_id.to_string());
+    assert_eq!(document_status(&body), DocumentStatus::Pending);
+
+    document_id
+}
+
+/// 触发文档处理，确认状态流转且处理中的文档不能重复提交
+async fn process_document<S, B>(app: &S, session: &Session, document_id: Uuid)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let reprocess_uri = format!("/api/v1/documents/{}/reprocess", document_id);
+
+    let request = session.authorize(TestRequest::post().uri(&reprocess_uri));
+    let (status, body) = send(app, request.to_request()).await;
+    assert_eq!(status, StatusCode::OK, "触发文档处理失败: {}", body);
+    assert_eq!(body["data"]["status"], "processing");
+
+    let request = session.authorize(TestRequest::get().uri(&format!("/api/v1/documents/{}", document_id)));
+    let (status, body) = send(app, request.to_request()).await;
+    assert_eq!(status, StatusCode::OK, "读取文档失败: {}", body);
+    assert_eq!(document_status(&body), DocumentStatus::Processing);
+    assert!(!body["data"]["processing_started_at"].is_null(), "处理开始时间未记录");
+
+    let request = session.authorize(TestRequest::post().uri(&reprocess_uri));
+    let (status, _) = send(app, request.to_request()).await;
+    assert_eq!(status, StatusCode::CONFLICT, "处理中的文档不应重复提交");
+}
+
+/// 针对知识库提问，确认返回答案
+async fn ask_question<S, B>(app: &S, session: &Session, knowledge_base_id: Uuid)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let request = session
+        .authorize(TestRequest::post().uri("/api/v1/qa/ask"))
+        .set_json(json!({ "question": "   ", "knowledge_base_id": knowledge_base_id }));
+    let (status, _) = send(app, request.to_request()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "空问题应被拒绝");
+
+    let request = session
+        .authorize(TestRequest::post().uri("/api/v1/qa/ask"))
+        .set_json(json!({ "question": "Aionix 是什么平台？", "knowledge_base_id": knowledge_base_id }));
+    let (status, body) = send(app, request.to_request()).await;
+    assert_eq!(status, StatusCode::OK, "问答请求失败: {}", body);
+
+    let data = &body["data"];
+    assert!(!data["session_id"].as_str().unwrap_or_default().is_empty(), "问答响应缺少会话 ID");
+    assert!(
+        !data["answer"].as_str().unwrap_or_default().is_empty() || data["insufficient_information"] == true,
+        "问答响应既无答案也未标记资料不足: {}",
+        body
+    );
+}
+
+fn parse_uuid(value: &Value) -> Uuid {
+    value.as_str().and_then(|s| Uuid::parse_str(s).ok()).unwrap_or_else(|| panic!("无效的 UUID: {}", value))
+}
+
+fn document_status(body: &Value) -> DocumentStatus {
+    serde_json::from_value(body["data"]["status"].clone()).expect("无法解析文档状态")
+}
@@ -0,0 +1,189 @@
+// 端到端测试支撑
+// 负责准备临时数据库、全局配置与应用实例，以及构造请求的辅助函数
+
+use std::sync::Arc;
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::StatusCode;
+use actix_web::test::{self, TestRequest};
+use actix_web::{web, App};
+use actix_http::Request;
+use sea_orm::DatabaseConnection;
+use serde_json::Value;
+use tempfile::TempDir;
+use testcontainers_modules::postgres::Postgres;
+use testcontainers_modules::testcontainers::runners::AsyncRunner;
+use testcontainers_modules::testcontainers::{ContainerAsync, ImageExt};
+use uuid::Uuid;
+
+use aionix::ai::{InMemoryVectorSearch, RagEngine, RigAiClientManager};
+use aionix::api::middleware::tenant::{TenantIdentificationMiddleware, TenantIdentificationStrategy};
+use aionix::api::routes::ApiRouteConfig;
+use aionix::config::{AppConfig, ConfigLoader};
+use aionix::db::{DatabaseManager, MigrationManager, SeedDataManager};
+use aionix::errors::ErrorHandlerMiddleware;
+use aionix::services::KnowledgeBaseServiceImpl;
+
+/// 带 pgvector 扩展的 PostgreSQL 镜像
+const POSTGRES_IMAGE: &str = "pgvector/pgvector";
+const POSTGRES_TAG: &str = "pg16";
+
+/// 测试用密钥，满足配置校验的最小长度
+const TEST_SECRET: &str = "aionix-e2e-test-secret-0123456789abcdef";
+
+/// multipart 请求的分隔符
+const MULTIPART_BOUNDARY: &str = "aionix-e2e-boundary";
+
+/// 种子数据中的默认租户与管理员
+pub const DEFAULT_TENANT_SLUG: &str = "default";
+pub const ADMIN_USERNAME: &str = "admin";
+pub const ADMIN_PASSWORD: &str = "Admin123!";
+
+/// 端到端测试上下文
+///
+/// 持有临时容器与存储目录，离开作用域时一并清理。
+pub struct TestContext {
+    /// 数据库连接
+    pub db: DatabaseConnection,
+    /// RAG 引擎，未配置 AI 端点时为空
+    pub rag_engine: Option<web::Data<RagEngine>>,
+    _container: Option<ContainerAsync<Postgres>>,
+    _storage: TempDir,
+}
+
+impl TestContext {
+    /// 启动数据库并完成配置、迁移与种子数据初始化
+    ///
+    /// 数据库连接与配置都是进程级全局状态，每个测试进程只能调用一次。
+    pub async fn setup() -> Self {
+        let (database_url, container) = match std::env::var("AIONIX_E2E_DATABASE_URL") {
+            Ok(url) => (url, None),
+            Err(_) => {
+                let container = Postgres::default()
+                    .with_name(POSTGRES_IMAGE)
+                    .with_tag(POSTGRES_TAG)
+                    .start()
+                    .await
+                    .expect("启动 PostgreSQL 容器失败，请确认 Docker 可用");
+                let host = container.get_host().await.expect("获取容器地址失败");
+                let port = container.get_host_port_ipv4(5432).await.expect("获取容器端口失败");
+                (format!("postgres://postgres:postgres@{}:{}/postgres", host, port), Some(container))
+            }
+        };
+        let storage = tempfile::tempdir().expect("创建临时存储目录失败");
+        let ai_endpoint = std::env::var("AIONIX_E2E_AI_ENDPOINT").ok();
+
+        // SAFETY: 在初始化全局配置和数据库连接之前设置，测试进程内只有此处修改环境变量
+        unsafe {
+            std::env::set_var("AIONIX_ENVIRONMENT__NAME", "test");
+            std::env::set_var("AIONIX_DATABASE__URL", &database_url);
+            std::env::set_var("AIONIX_SECURITY__JWT_SECRET", TEST_SECRET);
+            std::env::set_var("AIONIX_SECURITY__ENCRYPTION_KEY", TEST_SECRET);
+            std::env::set_var("AIONIX_STORAGE__PATH", storage.path());
+            if let Some(endpoint) = &ai_endpoint {
+                std::env::set_var("AIONIX_AI__MODEL_ENDPOINT", endpoint);
+            }
+        }
+
+        let config = ConfigLoader::init().expect("加载测试配置失败");
+        DatabaseManager::init(config.database.clone()).await.expect("连接测试数据库失败");
+        let db = DatabaseManager::get().expect("数据库连接未初始化").get_connection().clone();
+
+        let migration_manager = MigrationManager::new(db.clone());
+        migration_manager.init().await.expect("初始化迁移表失败");
+        migration_manager.migrate().await.expect("执行数据库迁移失败");
+        SeedDataManager::new(db.clone()).seed_all().await.expect("初始化种子数据失败");
+
+        let rag_engine = match ai_endpoint {
+            Some(_) => Some(build_rag_engine(config, &db).await),
+            None => None,
+        };
+
+        Self { db, rag_engine, _container: container, _storage: storage }
+    }
+
+    /// 按服务端的中间件与路由配置初始化应用
+    pub async fn init_app(
+        &self,
+    ) -> impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error> {
+        let mut app = App::new()
+            .wrap(TenantIdentificationMiddleware::optional(TenantIdentificationStrategy::Header))
+            .wrap(ErrorHandlerMiddleware)
+            .app_data(web::Data::new(self.db.clone()));
+        if let Some(rag_engine) = &self.rag_engine {
+            app = app.app_data(rag_engine.clone());
+        }
+        test::init_service(app.configure(ApiRouteConfig::configure_dev)).await
+    }
+}
+
+/// 构建问答使用的 RAG 引擎
+async fn build_rag_engine(config: &AppConfig, db: &DatabaseConnection) -> web::Data<RagEngine> {
+    let ai_client = RigAiClientManager::new(config.ai.clone())
+        .await
+        .expect("初始化 AI 客户端失败，请确认已启用 ai 特性且端点可用");
+    let db = Arc::new(db.clone());
+
+    web::Data::new(RagEngine::new(
+        Arc::new(ai_client.clone()),
+        db.clone(),
+        Arc::new(InMemoryVectorSearch::new(ai_client)),
+        Arc::new(KnowledgeBaseServiceImpl::new(db.clone())),
+        None,
+    ))
+}
+
+/// 登录后的会话
+pub struct Session {
+    /// 访问令牌
+    pub access_token: String,
+    /// 所属租户 ID
+    pub tenant_id: Uuid,
+}
+
+impl Session {
+    /// 为请求附加认证与租户头
+    pub fn authorize(&self, request: TestRequest) -> TestRequest {
+        request
+            .insert_header(("Authorization", format!("Bearer {}", self.access_token)))
+            .insert_header(("X-Tenant-ID", self.tenant_id.to_string()))
+    }
+}
+
+/// 发送请求并返回状态码与 JSON 响应体（非 JSON 响应体返回 Null）
+pub async fn send<S, B>(app: &S, request: Request) -> (StatusCode, Value)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let response = test::call_service(app, request).await;
+    let status = response.status();
+    let body = test::read_body(response).await;
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+/// 构造 multipart/form-data 请求体，返回 Content-Type 与请求体
+pub fn multipart_body(fields: &[(&str, &str)], file_name: &str, file_content: &[u8]) -> (String, Vec<u8>) {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                MULTIPART_BOUNDARY, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: text/plain\r\n\r\n",
+            MULTIPART_BOUNDARY, file_name
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(file_content);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", MULTIPART_BOUNDARY).as_bytes());
+
+    (format!("multipart/form-data; boundary={}", MULTIPART_BOUNDARY), body)
+}